@@ -0,0 +1,113 @@
+//! Feature transform read path: the time-major scan served by the `*_by_time`
+//! covering indexes versus the same store without them, where SQLite has to sort the
+//! `(symbol_id, open_time_ms)` primary key into `(open_time_ms, symbol_id)` order.
+//!
+//! Run with `cargo bench --bench feature_transform`. The store covers one synthetic
+//! day of 1s klines for all four symbols by default; set `PMM_BENCH_TRANSFORM_DAYS=30`
+//! for a month (building it takes a few minutes).
+
+use std::hint::black_box;
+use std::path::Path;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use pmm::{
+    transform_store_range, BinanceSymbol, FeatureTransformConfig, FeatureTransformRequest,
+    GapPolicy, Kline1s, KlineStore, KlineValidationConfig, ALL_BINANCE_SYMBOLS,
+    FEATURE_SCHEMA_VERSION,
+};
+use rusqlite::Connection;
+
+const START_MS: i64 = 1_704_067_200_000; // 2024-01-01T00:00:00Z
+const SECONDS_PER_DAY: i64 = 86_400;
+
+fn days() -> i64 {
+    std::env::var("PMM_BENCH_TRANSFORM_DAYS")
+        .ok()
+        .and_then(|raw| raw.parse::<i64>().ok())
+        .filter(|days| *days > 0)
+        .unwrap_or(1)
+}
+
+fn kline(symbol: BinanceSymbol, i: i64) -> Kline1s {
+    let base = match symbol {
+        BinanceSymbol::BtcUsdt => 42_000.0,
+        BinanceSymbol::EthUsdt => 2_300.0,
+        BinanceSymbol::SolUsdt => 100.0,
+        BinanceSymbol::XrpUsdt => 0.6,
+    };
+    let close = base * (1.0 + ((i % 600) as f64 - 300.0) * 1e-5);
+    let open_time_ms = START_MS + i * 1_000;
+    Kline1s {
+        open_time_ms,
+        open: close,
+        high: close * 1.0001,
+        low: close * 0.9999,
+        close,
+        volume: 1.0 + (i % 17) as f64,
+        close_time_ms: open_time_ms + 999,
+        quote_asset_volume: close * (1.0 + (i % 17) as f64),
+        trade_count: 1 + (i % 40) as u64,
+        taker_buy_base_volume: 0.5,
+        taker_buy_quote_volume: close * 0.5,
+    }
+}
+
+fn build_store(path: &Path, seconds: i64) {
+    let mut store = KlineStore::open(path, KlineValidationConfig::default()).expect("open store");
+    for symbol in ALL_BINANCE_SYMBOLS {
+        for day_start in (0..seconds).step_by(SECONDS_PER_DAY as usize) {
+            let rows = (day_start..(day_start + SECONDS_PER_DAY).min(seconds))
+                .map(|i| kline(symbol, i))
+                .collect();
+            store.upsert_rows(symbol, rows).expect("upsert day");
+        }
+    }
+}
+
+fn bench_transform(c: &mut Criterion) {
+    let seconds = days() * SECONDS_PER_DAY;
+    let dir = tempfile::tempdir().expect("tempdir");
+    let indexed = dir.path().join("indexed.sqlite");
+    build_store(&indexed, seconds);
+    let unindexed = dir.path().join("unindexed.sqlite");
+    Connection::open(&indexed)
+        .and_then(|conn| conn.execute_batch(&format!("VACUUM INTO '{}'", unindexed.display())))
+        .expect("copy store");
+    Connection::open(&unindexed)
+        .and_then(|conn| {
+            conn.execute_batch(
+                "DROP INDEX klines_1s_by_time; DROP INDEX klines_1s_slim_by_time; VACUUM;",
+            )
+        })
+        .expect("drop time indexes");
+
+    let req = FeatureTransformRequest {
+        start_ts_ms_utc: START_MS,
+        end_ts_ms_utc_exclusive: START_MS + seconds * 1_000,
+    };
+    let cfg = FeatureTransformConfig {
+        windows_seconds: vec![5, 15, 60],
+        max_duration_seconds: 86_400,
+        gap_policy: GapPolicy::Strict,
+        schema_version: FEATURE_SCHEMA_VERSION,
+        preset: None,
+        auto_backfill: false,
+    };
+
+    let mut group = c.benchmark_group("feature_transform");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(seconds as u64 * 4));
+    for (name, path) in [("pk_sort", &unindexed), ("time_index", &indexed)] {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let (_, rows, _) =
+                    transform_store_range(black_box(path), &req, &cfg).expect("transform");
+                black_box(rows.len())
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_transform);
+criterion_main!(benches);
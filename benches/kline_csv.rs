@@ -0,0 +1,144 @@
+//! Archive CSV ingestion: the previous `StringRecord` + `str::parse` path (whole entry
+//! buffered first) versus `parse_kline_csv` (fixed-column line splitter, byte-level
+//! number parsing, streamed entry), over one synthetic day of 1s klines.
+//!
+//! Run with `cargo bench --bench kline_csv`.
+
+use std::fs;
+use std::hint::black_box;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use csv::StringRecord;
+use pmm::{parse_kline_csv, BinanceSymbol, Kline1s, KlineLoadRequest};
+use zip::write::SimpleFileOptions;
+use zip::ZipArchive;
+
+const DAY_START_MS: i64 = 1_704_067_200_000; // 2024-01-01T00:00:00Z
+const ROWS: i64 = 86_400;
+
+fn day_request() -> KlineLoadRequest {
+    KlineLoadRequest {
+        symbol: BinanceSymbol::BtcUsdt,
+        start_ts_ms_utc: DAY_START_MS,
+        end_ts_ms_utc_exclusive: DAY_START_MS + ROWS * 1_000,
+    }
+}
+
+fn synthetic_day_csv() -> Vec<u8> {
+    let mut out = Vec::with_capacity(ROWS as usize * 128);
+    for i in 0..ROWS {
+        let open_time = DAY_START_MS + i * 1_000;
+        let price = 42_000.0 + (i % 600) as f64 * 0.37;
+        writeln!(
+            out,
+            "{open_time},{price:.8},{:.8},{:.8},{:.8},{:.8},{},{:.8},{},{:.8},{:.8},0",
+            price + 1.25,
+            price - 1.25,
+            price + 0.5,
+            0.01 + (i % 17) as f64 * 0.003,
+            open_time + 999,
+            price * 0.05,
+            1 + i % 40,
+            0.004 + (i % 11) as f64 * 0.001,
+            price * 0.02,
+        )
+        .expect("write csv row");
+    }
+    out
+}
+
+fn field(record: &StringRecord, idx: usize) -> &str {
+    record.get(idx).unwrap_or_default()
+}
+
+fn string_record_parse(csv_buf: &[u8], req: &KlineLoadRequest) -> Vec<Kline1s> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .from_reader(csv_buf);
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record.expect("csv record");
+        let row = Kline1s {
+            open_time_ms: field(&record, 0).parse().expect("open_time_ms"),
+            open: field(&record, 1).parse().expect("open"),
+            high: field(&record, 2).parse().expect("high"),
+            low: field(&record, 3).parse().expect("low"),
+            close: field(&record, 4).parse().expect("close"),
+            volume: field(&record, 5).parse().expect("volume"),
+            close_time_ms: field(&record, 6).parse().expect("close_time_ms"),
+            quote_asset_volume: field(&record, 7).parse().expect("quote_asset_volume"),
+            trade_count: field(&record, 8).parse().expect("trade_count"),
+            taker_buy_base_volume: field(&record, 9).parse().expect("taker_buy_base_volume"),
+            taker_buy_quote_volume: field(&record, 10).parse().expect("taker_buy_quote_volume"),
+        };
+        if row.open_time_ms >= req.start_ts_ms_utc && row.open_time_ms < req.end_ts_ms_utc_exclusive
+        {
+            rows.push(row);
+        }
+    }
+    rows
+}
+
+fn write_zip(path: &Path, csv_body: &[u8]) {
+    let mut zip = zip::ZipWriter::new(fs::File::create(path).expect("create zip"));
+    zip.start_file("BTCUSDT-1s-2024-01-01.csv", SimpleFileOptions::default())
+        .expect("start zip entry");
+    zip.write_all(csv_body).expect("write zip entry");
+    zip.finish().expect("finish zip");
+}
+
+fn kline_csv(c: &mut Criterion) {
+    let req = day_request();
+    let csv_buf = synthetic_day_csv();
+    assert_eq!(
+        string_record_parse(&csv_buf, &req),
+        parse_kline_csv(csv_buf.as_slice(), &req).expect("parse")
+    );
+
+    let mut group = c.benchmark_group("kline_csv_parse_day");
+    group.throughput(Throughput::Elements(ROWS as u64));
+    group.sample_size(20);
+    group.bench_function("string_record", |b| {
+        b.iter(|| black_box(string_record_parse(&csv_buf, &req).len()))
+    });
+    group.bench_function("line_splitter", |b| {
+        b.iter(|| {
+            black_box(
+                parse_kline_csv(csv_buf.as_slice(), &req)
+                    .expect("parse")
+                    .len(),
+            )
+        })
+    });
+    group.finish();
+
+    let dir = tempfile::tempdir().expect("tempdir");
+    let archive = dir.path().join("BTCUSDT-1s-2024-01-01.zip");
+    write_zip(&archive, &csv_buf);
+
+    let mut group = c.benchmark_group("kline_archive_ingest_day");
+    group.throughput(Throughput::Elements(ROWS as u64));
+    group.sample_size(20);
+    group.bench_function("string_record_buffered", |b| {
+        b.iter(|| {
+            let mut zip = ZipArchive::new(fs::File::open(&archive).expect("open")).expect("zip");
+            let mut entry = zip.by_index(0).expect("entry");
+            let mut buf = Vec::new();
+            entry.read_to_end(&mut buf).expect("inflate");
+            black_box(string_record_parse(&buf, &req).len())
+        })
+    });
+    group.bench_function("line_splitter_streamed", |b| {
+        b.iter(|| {
+            let mut zip = ZipArchive::new(fs::File::open(&archive).expect("open")).expect("zip");
+            let entry = zip.by_index(0).expect("entry");
+            black_box(parse_kline_csv(entry, &req).expect("parse").len())
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, kline_csv);
+criterion_main!(benches);
@@ -0,0 +1,93 @@
+//! Snapshot read latency: the previous `RwLock<DashboardSnapshot>` clone-per-request
+//! pattern versus the `ArcSwap`-backed sources, with and without a concurrent publisher.
+//!
+//! Run with `cargo bench --bench snapshot_source`.
+
+use std::hint::black_box;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::Duration;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use pmm::{demo_snapshot, DashboardSnapshot, DashboardSnapshotSource, InMemoryMockSnapshotSource};
+
+struct RwLockCloneSource {
+    inner: RwLock<DashboardSnapshot>,
+}
+
+impl RwLockCloneSource {
+    fn snapshot(&self) -> DashboardSnapshot {
+        self.inner.read().expect("bench lock").clone()
+    }
+
+    fn replace_snapshot(&self, snapshot: DashboardSnapshot) {
+        *self.inner.write().expect("bench lock") = snapshot;
+    }
+}
+
+fn with_publisher<F>(publish: F, run: impl FnOnce())
+where
+    F: Fn() + Send + 'static,
+{
+    let stop = Arc::new(AtomicBool::new(false));
+    let stop_bg = Arc::clone(&stop);
+    let handle = thread::spawn(move || {
+        while !stop_bg.load(Ordering::Relaxed) {
+            publish();
+            thread::sleep(Duration::from_micros(100));
+        }
+    });
+    run();
+    stop.store(true, Ordering::Relaxed);
+    handle.join().expect("publisher thread");
+}
+
+fn snapshot_read(c: &mut Criterion) {
+    let snapshot = demo_snapshot();
+    let rwlock = Arc::new(RwLockCloneSource {
+        inner: RwLock::new(snapshot.clone()),
+    });
+    let arc_swap = Arc::new(InMemoryMockSnapshotSource::new(snapshot.clone()));
+
+    let mut group = c.benchmark_group("snapshot_read");
+    group.bench_function("rwlock_clone", |b| {
+        b.iter(|| black_box(rwlock.snapshot().rows.len()))
+    });
+    group.bench_function("arc_swap", |b| {
+        b.iter(|| black_box(arc_swap.snapshot().rows.len()))
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("snapshot_read_with_publisher");
+    {
+        let writer = Arc::clone(&rwlock);
+        let next = snapshot.clone();
+        with_publisher(
+            move || writer.replace_snapshot(next.clone()),
+            || {
+                group.bench_function("rwlock_clone", |b| {
+                    b.iter(|| black_box(rwlock.snapshot().rows.len()))
+                });
+            },
+        );
+    }
+    {
+        let writer = Arc::clone(&arc_swap);
+        let next = snapshot.clone();
+        with_publisher(
+            move || {
+                writer.replace_snapshot(next.clone());
+            },
+            || {
+                group.bench_function("arc_swap", |b| {
+                    b.iter(|| black_box(arc_swap.snapshot().rows.len()))
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, snapshot_read);
+criterion_main!(benches);
@@ -0,0 +1,80 @@
+//! Fixture-based kline load: plan archives, stage them locally, load 1s klines.
+//!
+//! Uses the sample CSVs under `tests/fixtures/binance`, zips each one into the
+//! archive path `plan_required_archives` expects under a temporary data root,
+//! and loads them with checksum verification off so no network is touched.
+//!
+//! Run with `cargo run --example kline_fixture_load`.
+
+use pmm::{
+    load_1s_klines, plan_required_archives, write_kline_archive, BinanceSymbol,
+    HistoricalKlinesConfig, KlineLoadRequest,
+};
+
+const START_TS_MS: i64 = 1_704_067_200_000; // 2024-01-01T00:00:00Z
+const POINTS: i64 = 3;
+const FIXTURES: [(BinanceSymbol, &str); 4] = [
+    (
+        BinanceSymbol::BtcUsdt,
+        include_str!("../tests/fixtures/binance/BTCUSDT_1s_sample.csv"),
+    ),
+    (
+        BinanceSymbol::EthUsdt,
+        include_str!("../tests/fixtures/binance/ETHUSDT_1s_sample.csv"),
+    ),
+    (
+        BinanceSymbol::SolUsdt,
+        include_str!("../tests/fixtures/binance/SOLUSDT_1s_sample.csv"),
+    ),
+    (
+        BinanceSymbol::XrpUsdt,
+        include_str!("../tests/fixtures/binance/XRPUSDT_1s_sample.csv"),
+    ),
+];
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let data_root = tempfile::tempdir()?;
+    let cfg = HistoricalKlinesConfig {
+        data_root: data_root.path().to_path_buf(),
+        verify_checksum: false,
+        ..HistoricalKlinesConfig::default()
+    };
+
+    for (symbol, csv) in FIXTURES {
+        let req = KlineLoadRequest {
+            symbol,
+            start_ts_ms_utc: START_TS_MS,
+            end_ts_ms_utc_exclusive: START_TS_MS + POINTS * 1_000,
+        };
+
+        let archives = plan_required_archives(&req);
+        for archive in &archives {
+            write_kline_archive(&cfg.data_root.join(&archive.relative_path), csv)?;
+        }
+
+        let loaded = load_1s_klines(&req, &cfg)?;
+        assert_eq!(loaded.rows.len() as i64, POINTS);
+        assert_eq!(loaded.coverage.missing_points, 0);
+
+        let first = &loaded.rows[0];
+        let last = &loaded.rows[loaded.rows.len() - 1];
+        println!(
+            "{:<8} {} archive(s), {} rows, close {} -> {}",
+            symbol.as_str(),
+            archives.len(),
+            loaded.rows.len(),
+            first.close,
+            last.close
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn example_runs() {
+        super::main().unwrap();
+    }
+}
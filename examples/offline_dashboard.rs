@@ -0,0 +1,75 @@
+//! Offline dashboard: serve the demo snapshot from an in-memory source.
+//!
+//! Builds the axum router over `InMemoryMockSnapshotSource`, issues one request
+//! against `/dashboard` and `/dashboard/snapshot` without binding a socket,
+//! then publishes a replacement snapshot and shows the next poll picking it up
+//! with the new publish `seq`.
+//!
+//! Run with `cargo run --example offline_dashboard`.
+
+use std::sync::Arc;
+
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+    Router,
+};
+use pmm::{
+    dashboard_router, demo_snapshot, DashboardDisplaySnapshot, DashboardSnapshot,
+    DashboardSnapshotSource, InMemoryMockSnapshotSource,
+};
+use tower::util::ServiceExt;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let snapshot = demo_snapshot();
+    let row_count = snapshot.rows.len();
+    let source = Arc::new(InMemoryMockSnapshotSource::new(snapshot));
+    let app = dashboard_router(source.clone());
+
+    let (status, html) = get(&app, "/dashboard").await?;
+    assert_eq!(status, StatusCode::OK);
+    assert!(html.contains("<table"));
+    println!("/dashboard -> {status} ({} bytes)", html.len());
+
+    let (status, body) = get(&app, "/dashboard/snapshot?coin=BTC").await?;
+    assert_eq!(status, StatusCode::OK);
+    let display: DashboardDisplaySnapshot = serde_json::from_str(&body)?;
+    assert!(display.rows.iter().all(|row| row.coin == "BTC"));
+    println!(
+        "/dashboard/snapshot?coin=BTC -> {} of {row_count} rows",
+        display.rows.len()
+    );
+
+    let first_row = source.snapshot().rows[..1].to_vec();
+    let seq = source.replace_snapshot(DashboardSnapshot {
+        rows: first_row,
+        seq: 0,
+    });
+
+    let (_, body) = get(&app, "/dashboard/snapshot").await?;
+    let display: DashboardDisplaySnapshot = serde_json::from_str(&body)?;
+    assert_eq!(display.rows.len(), 1);
+    assert_eq!(display.seq, seq);
+    println!("after publish -> {} row at seq {seq}", display.rows.len());
+
+    Ok(())
+}
+
+async fn get(app: &Router, uri: &str) -> Result<(StatusCode, String), Box<dyn std::error::Error>> {
+    let response = app
+        .clone()
+        .oneshot(Request::builder().uri(uri).body(Body::empty())?)
+        .await?;
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, String::from_utf8(bytes.to_vec())?))
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn example_runs() {
+        super::main().unwrap();
+    }
+}
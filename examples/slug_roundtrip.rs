@@ -0,0 +1,66 @@
+//! Slug roundtrip: dashboard codes -> typed coin/duration -> Polymarket slug.
+//!
+//! Parses the string codes the dashboard uses, builds the slug for a fixed
+//! interval, and checks that the discovery schedule produces the same slug for
+//! the same key and that each scheduled slug parses back to its key.
+//!
+//! Run with `cargo run --example slug_roundtrip`.
+
+use pmm::build_active_discovery_keys;
+use pmm::prelude::*;
+
+const NOW_TS_UTC: i64 = 1_735_689_900; // 2025-01-01T00:05:00Z
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cfg = SlugConfig::default();
+
+    for (coin_code, duration_code, ts, expected) in [
+        ("BTC", "5m", 1_771_449_000, "btc-updown-5m-1771449000"),
+        (
+            "ETH",
+            "1h",
+            1_736_971_200,
+            "ethereum-up-or-down-january-15-3pm-et",
+        ),
+        (
+            "SOL",
+            "1d",
+            1_772_040_400,
+            "solana-up-or-down-on-february-26",
+        ),
+    ] {
+        let coin = parse_coin(coin_code)?;
+        let duration = parse_duration(duration_code)?;
+        let slug = build_slug(coin, duration, ts, cfg)?;
+        assert_eq!(slug, expected);
+
+        let key = DiscoveryKey::new(coin, duration, ts, cfg)?;
+        assert_eq!(key.slug, slug);
+        println!("{coin_code:<4} {duration_code:<4} {ts} -> {slug}");
+    }
+
+    let keys = build_active_discovery_keys(NOW_TS_UTC, &Coin::all(), &ALL_DURATIONS, cfg)?;
+    for key in &keys {
+        let rebuilt = build_slug(key.coin, key.duration, key.start_ts_utc, cfg)?;
+        assert_eq!(rebuilt, key.slug);
+        let parsed = parse_slug_at(&key.slug, NOW_TS_UTC)?;
+        assert_eq!(parsed, (key.coin, key.duration, key.start_ts_utc));
+    }
+    println!(
+        "{} active keys rebuild to identical slugs and parse back",
+        keys.len()
+    );
+
+    assert!(parse_coin("DOGE").is_err());
+    assert!(parse_duration("2h").is_err());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn example_runs() {
+        super::main().unwrap();
+    }
+}
@@ -0,0 +1,608 @@
+//! Persistent alert state and `/alerts`.
+//!
+//! Each alert key (`clock.drift`, `discovery.live_unavailable`, ...) keeps one row in
+//! SQLite with its last fire/notify times and acknowledgement. Firing an alert only
+//! notifies (logs `alert.fired`) when it is new, when it was cleared since, when its
+//! severity rose, or when the re-notify interval has passed; acknowledged alerts stay
+//! quiet until they clear. Because the state outlives the process, a restart does not
+//! re-notify everything that is still firing. State lives behind [`AlertBackend`]:
+//! [`AlertStore`] (SQLite) by default, or the shared Postgres database.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use axum::extract::{Path as UrlPath, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{debug, error, info, warn};
+
+use crate::env_vars::Settings;
+use crate::persistence::{open_alert_backend, AlertBackend, PersistenceBackend};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertSeverity {
+    Warn,
+    Critical,
+}
+
+impl AlertSeverity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Warn => "warn",
+            Self::Critical => "critical",
+        }
+    }
+
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            "warn" => Some(Self::Warn),
+            "critical" => Some(Self::Critical),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertState {
+    Active,
+    Acknowledged,
+    Cleared,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AlertRecord {
+    pub key: String,
+    pub severity: AlertSeverity,
+    pub message: String,
+    pub state: AlertState,
+    /// Start of the current firing episode (reset when a cleared alert fires again).
+    pub first_fired_ts_utc: i64,
+    pub last_fired_ts_utc: i64,
+    pub last_notified_ts_utc: i64,
+    /// Fires in the current episode, notified or not.
+    pub fire_count: u64,
+    pub acked_ts_utc: Option<i64>,
+    pub cleared_ts_utc: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertDecision {
+    Notify,
+    Suppressed(SuppressReason),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuppressReason {
+    Acknowledged,
+    RecentlyNotified,
+}
+
+impl SuppressReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Acknowledged => "acknowledged",
+            Self::RecentlyNotified => "recently_notified",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlertConfig {
+    /// SQLite file holding alert state; `None` disables alerting (with any backend).
+    pub store_path: Option<PathBuf>,
+    /// Minimum seconds between notifications for an alert that keeps firing.
+    pub renotify_after_secs: i64,
+    pub backend: PersistenceBackend,
+}
+
+impl Default for AlertConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl AlertConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_ALERT_STORE_PATH` (`off` disables), `PMM_ALERT_RENOTIFY_SECS`, and
+    /// `PMM_DATABASE_URL`, falling back to defaults for missing/invalid values.
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            store_path: settings.get("PMM_ALERT_STORE_PATH"),
+            renotify_after_secs: settings.get_where("PMM_ALERT_RENOTIFY_SECS", |v| *v >= 0),
+            backend: PersistenceBackend::from_settings(settings),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum AlertStoreError {
+    #[error("sqlite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[cfg(feature = "postgres")]
+    #[error("postgres error: {0}")]
+    Postgres(#[from] postgres::Error),
+    #[error("invalid stored alert row: {0}")]
+    InvalidRow(String),
+    #[error("storage backend unavailable: {0}")]
+    BackendUnavailable(String),
+}
+
+/// SQLite-backed alert state (`alerts`, one row per key). The state column is not
+/// stored; it follows from `cleared_ts_utc` and `acked_ts_utc`.
+pub struct AlertStore {
+    conn: Connection,
+    renotify_after_secs: i64,
+}
+
+const ALERT_COLUMNS: &str = "key, severity, message, first_fired_ts_utc, last_fired_ts_utc, \
+     last_notified_ts_utc, fire_count, acked_ts_utc, cleared_ts_utc";
+
+impl AlertStore {
+    pub fn open(path: &Path, renotify_after_secs: i64) -> Result<Self, AlertStoreError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "
+            PRAGMA journal_mode=WAL;
+            CREATE TABLE IF NOT EXISTS alerts (
+                key TEXT PRIMARY KEY,
+                severity TEXT NOT NULL,
+                message TEXT NOT NULL,
+                first_fired_ts_utc INTEGER NOT NULL,
+                last_fired_ts_utc INTEGER NOT NULL,
+                last_notified_ts_utc INTEGER NOT NULL,
+                fire_count INTEGER NOT NULL,
+                acked_ts_utc INTEGER,
+                cleared_ts_utc INTEGER
+            );
+            ",
+        )?;
+        Ok(Self {
+            conn,
+            renotify_after_secs,
+        })
+    }
+}
+
+impl AlertBackend for AlertStore {
+    fn renotify_after_secs(&self) -> i64 {
+        self.renotify_after_secs
+    }
+
+    fn get(&mut self, key: &str) -> Result<Option<AlertRecord>, AlertStoreError> {
+        self.conn
+            .query_row(
+                &format!("SELECT {ALERT_COLUMNS} FROM alerts WHERE key = ?1"),
+                params![key],
+                read_alert_row,
+            )
+            .optional()?
+            .transpose()
+    }
+
+    fn list(&mut self) -> Result<Vec<AlertRecord>, AlertStoreError> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {ALERT_COLUMNS} FROM alerts ORDER BY last_fired_ts_utc DESC, key"
+        ))?;
+        let rows = stmt.query_map([], read_alert_row)?;
+        let mut out = Vec::new();
+        for row in rows {
+            out.push(row??);
+        }
+        Ok(out)
+    }
+
+    fn write(&mut self, record: &AlertRecord) -> Result<(), AlertStoreError> {
+        self.conn.execute(
+            &format!(
+                "INSERT OR REPLACE INTO alerts ({ALERT_COLUMNS})
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)"
+            ),
+            params![
+                record.key,
+                record.severity.as_str(),
+                record.message,
+                record.first_fired_ts_utc,
+                record.last_fired_ts_utc,
+                record.last_notified_ts_utc,
+                record.fire_count as i64,
+                record.acked_ts_utc,
+                record.cleared_ts_utc,
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn clear(&mut self, key: &str, now_ts_utc: i64) -> Result<bool, AlertStoreError> {
+        let changed = self.conn.execute(
+            "UPDATE alerts SET cleared_ts_utc = ?2 WHERE key = ?1 AND cleared_ts_utc IS NULL",
+            params![key, now_ts_utc],
+        )?;
+        Ok(changed > 0)
+    }
+}
+
+/// The decision and updated record for `key` firing at `now_ts_utc`, given its stored
+/// record.
+pub(crate) fn next_fire(
+    existing: Option<AlertRecord>,
+    key: &str,
+    severity: AlertSeverity,
+    message: &str,
+    now_ts_utc: i64,
+    renotify_after_secs: i64,
+) -> (AlertDecision, AlertRecord) {
+    match existing {
+        Some(prev) if prev.state != AlertState::Cleared => {
+            let escalated = severity > prev.severity;
+            let decision = if escalated {
+                AlertDecision::Notify
+            } else if prev.state == AlertState::Acknowledged {
+                AlertDecision::Suppressed(SuppressReason::Acknowledged)
+            } else if now_ts_utc - prev.last_notified_ts_utc < renotify_after_secs {
+                AlertDecision::Suppressed(SuppressReason::RecentlyNotified)
+            } else {
+                AlertDecision::Notify
+            };
+            let notified = decision == AlertDecision::Notify;
+            let record = AlertRecord {
+                severity,
+                message: message.to_string(),
+                // A severity increase re-opens an acknowledged alert.
+                state: if escalated {
+                    AlertState::Active
+                } else {
+                    prev.state
+                },
+                last_fired_ts_utc: now_ts_utc,
+                last_notified_ts_utc: if notified {
+                    now_ts_utc
+                } else {
+                    prev.last_notified_ts_utc
+                },
+                fire_count: prev.fire_count + 1,
+                acked_ts_utc: if escalated { None } else { prev.acked_ts_utc },
+                ..prev
+            };
+            (decision, record)
+        }
+        _ => (
+            AlertDecision::Notify,
+            AlertRecord {
+                key: key.to_string(),
+                severity,
+                message: message.to_string(),
+                state: AlertState::Active,
+                first_fired_ts_utc: now_ts_utc,
+                last_fired_ts_utc: now_ts_utc,
+                last_notified_ts_utc: now_ts_utc,
+                fire_count: 1,
+                acked_ts_utc: None,
+                cleared_ts_utc: None,
+            },
+        ),
+    }
+}
+
+/// `existing` marked acknowledged; `None` when it is unknown or cleared.
+pub(crate) fn acknowledged(existing: Option<AlertRecord>, now_ts_utc: i64) -> Option<AlertRecord> {
+    let prev = existing?;
+    if prev.state == AlertState::Cleared {
+        return None;
+    }
+    Some(AlertRecord {
+        state: AlertState::Acknowledged,
+        acked_ts_utc: prev.acked_ts_utc.or(Some(now_ts_utc)),
+        ..prev
+    })
+}
+
+/// Derives the state from the stored acknowledgement/clear timestamps.
+pub(crate) fn alert_state(acked_ts_utc: Option<i64>, cleared_ts_utc: Option<i64>) -> AlertState {
+    match (cleared_ts_utc, acked_ts_utc) {
+        (Some(_), _) => AlertState::Cleared,
+        (None, Some(_)) => AlertState::Acknowledged,
+        (None, None) => AlertState::Active,
+    }
+}
+
+fn read_alert_row(row: &Row<'_>) -> rusqlite::Result<Result<AlertRecord, AlertStoreError>> {
+    let severity: String = row.get(1)?;
+    let acked_ts_utc: Option<i64> = row.get(7)?;
+    let cleared_ts_utc: Option<i64> = row.get(8)?;
+    let Some(severity) = AlertSeverity::parse(&severity) else {
+        return Ok(Err(AlertStoreError::InvalidRow(format!(
+            "unknown severity {severity:?}"
+        ))));
+    };
+    let state = alert_state(acked_ts_utc, cleared_ts_utc);
+    Ok(Ok(AlertRecord {
+        key: row.get(0)?,
+        severity,
+        message: row.get(2)?,
+        state,
+        first_fired_ts_utc: row.get(3)?,
+        last_fired_ts_utc: row.get(4)?,
+        last_notified_ts_utc: row.get(5)?,
+        fire_count: row.get::<_, i64>(6)?.max(0) as u64,
+        acked_ts_utc,
+        cleared_ts_utc,
+    }))
+}
+
+/// Shared handle to an [`AlertBackend`] that logs notifications; cheap to clone into
+/// background tasks and the router.
+#[derive(Clone)]
+pub struct AlertManager {
+    store: Arc<Mutex<Box<dyn AlertBackend>>>,
+}
+
+impl AlertManager {
+    pub fn new(store: impl AlertBackend + 'static) -> Self {
+        Self::from_backend(Box::new(store))
+    }
+
+    pub fn from_backend(store: Box<dyn AlertBackend>) -> Self {
+        Self {
+            store: Arc::new(Mutex::new(store)),
+        }
+    }
+
+    /// Opens the configured store; `None` when alerting is disabled or the store cannot
+    /// be opened (logged as `alert.disabled`).
+    pub fn from_config(cfg: &AlertConfig) -> Option<Self> {
+        let path = cfg.store_path.as_ref()?;
+        match open_alert_backend(&cfg.backend, path, cfg.renotify_after_secs) {
+            Ok(store) => Some(Self::from_backend(store)),
+            Err(err) => {
+                warn!(
+                    component = "alerts",
+                    event = "alert.disabled",
+                    backend = cfg.backend.as_str(),
+                    path = %path.display(),
+                    error = %err
+                );
+                None
+            }
+        }
+    }
+
+    /// Records a firing alert and logs `alert.fired` when it should notify.
+    pub fn fire(&self, key: &str, severity: AlertSeverity, message: &str) -> AlertDecision {
+        let result = self
+            .lock()
+            .fire(key, severity, message, Utc::now().timestamp());
+        match result {
+            Ok(AlertDecision::Notify) => {
+                match severity {
+                    AlertSeverity::Critical => error!(
+                        component = "alerts",
+                        event = "alert.fired",
+                        key,
+                        severity = severity.as_str(),
+                        message
+                    ),
+                    AlertSeverity::Warn => warn!(
+                        component = "alerts",
+                        event = "alert.fired",
+                        key,
+                        severity = severity.as_str(),
+                        message
+                    ),
+                }
+                AlertDecision::Notify
+            }
+            Ok(AlertDecision::Suppressed(reason)) => {
+                debug!(
+                    component = "alerts",
+                    event = "alert.suppressed",
+                    key,
+                    reason = reason.as_str()
+                );
+                AlertDecision::Suppressed(reason)
+            }
+            Err(err) => {
+                // Losing the dedupe state must not silence the alert.
+                error!(
+                    component = "alerts",
+                    event = "alert.store_error",
+                    key,
+                    severity = severity.as_str(),
+                    message,
+                    error = %err
+                );
+                AlertDecision::Notify
+            }
+        }
+    }
+
+    /// Clears `key`, logging `alert.cleared` when it was firing.
+    pub fn clear(&self, key: &str) {
+        match self.lock().clear(key, Utc::now().timestamp()) {
+            Ok(true) => info!(component = "alerts", event = "alert.cleared", key),
+            Ok(false) => {}
+            Err(err) => error!(
+                component = "alerts",
+                event = "alert.store_error",
+                key,
+                error = %err
+            ),
+        }
+    }
+
+    pub fn acknowledge(&self, key: &str) -> Result<Option<AlertRecord>, AlertStoreError> {
+        let record = self.lock().acknowledge(key, Utc::now().timestamp())?;
+        if record.is_some() {
+            info!(component = "alerts", event = "alert.acknowledged", key);
+        }
+        Ok(record)
+    }
+
+    pub fn list(&self) -> Result<Vec<AlertRecord>, AlertStoreError> {
+        self.lock().list()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Box<dyn AlertBackend>> {
+        // A panic mid-update leaves the store consistent, so the poisoned guard is usable.
+        self.store
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AlertsQuery {
+    /// `active`, `acknowledged`, `cleared`, or `all`; defaults to active + acknowledged.
+    pub state: Option<String>,
+}
+
+/// Router serving `GET /alerts` and `POST /alerts/{key}/ack`.
+pub fn alerts_router(alerts: AlertManager) -> Router {
+    Router::new()
+        .route("/alerts", get(get_alerts))
+        .route("/alerts/{key}/ack", post(post_alert_ack))
+        .with_state(alerts)
+}
+
+async fn get_alerts(
+    State(alerts): State<AlertManager>,
+    Query(query): Query<AlertsQuery>,
+) -> Response {
+    let keep: fn(AlertState) -> bool = match query.state.as_deref() {
+        None | Some("") => |state| state != AlertState::Cleared,
+        Some("active") => |state| state == AlertState::Active,
+        Some("acknowledged") => |state| state == AlertState::Acknowledged,
+        Some("cleared") => |state| state == AlertState::Cleared,
+        Some("all") => |_| true,
+        Some(other) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                format!("unknown alert state: {other}"),
+            )
+                .into_response()
+        }
+    };
+    match alerts.list() {
+        Ok(records) => Json(
+            records
+                .into_iter()
+                .filter(|record| keep(record.state))
+                .collect::<Vec<_>>(),
+        )
+        .into_response(),
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+    }
+}
+
+async fn post_alert_ack(
+    State(alerts): State<AlertManager>,
+    UrlPath(key): UrlPath<String>,
+) -> Response {
+    match alerts.acknowledge(&key) {
+        Ok(Some(record)) => Json(record).into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, format!("no firing alert: {key}")).into_response(),
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_survives_reopen_so_restarts_do_not_renotify() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("alerts.sqlite");
+
+        let mut store = AlertStore::open(&path, 600).unwrap();
+        let fire = |store: &mut AlertStore, ts| {
+            store
+                .fire("clock.drift", AlertSeverity::Warn, "offset 400ms", ts)
+                .unwrap()
+        };
+        assert_eq!(fire(&mut store, 1_000), AlertDecision::Notify);
+        assert_eq!(
+            fire(&mut store, 1_100),
+            AlertDecision::Suppressed(SuppressReason::RecentlyNotified)
+        );
+        drop(store);
+
+        let mut store = AlertStore::open(&path, 600).unwrap();
+        assert_eq!(
+            fire(&mut store, 1_200),
+            AlertDecision::Suppressed(SuppressReason::RecentlyNotified)
+        );
+        assert_eq!(fire(&mut store, 1_600), AlertDecision::Notify);
+        let record = store.get("clock.drift").unwrap().unwrap();
+        assert_eq!(record.fire_count, 4);
+        assert_eq!(record.first_fired_ts_utc, 1_000);
+        assert_eq!(record.last_notified_ts_utc, 1_600);
+
+        assert!(store.clear("clock.drift", 1_700).unwrap());
+        assert!(!store.clear("clock.drift", 1_701).unwrap());
+        assert_eq!(fire(&mut store, 1_800), AlertDecision::Notify);
+        let record = store.get("clock.drift").unwrap().unwrap();
+        assert_eq!(record.state, AlertState::Active);
+        assert_eq!(record.fire_count, 1);
+        assert_eq!(record.first_fired_ts_utc, 1_800);
+    }
+
+    #[test]
+    fn acknowledged_alerts_stay_quiet_until_they_escalate_or_clear() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = AlertStore::open(&dir.path().join("alerts.sqlite"), 0).unwrap();
+
+        store
+            .fire("discovery.live_unavailable", AlertSeverity::Warn, "x", 10)
+            .unwrap();
+        let acked = store
+            .acknowledge("discovery.live_unavailable", 20)
+            .unwrap()
+            .unwrap();
+        assert_eq!(acked.state, AlertState::Acknowledged);
+        assert_eq!(acked.acked_ts_utc, Some(20));
+        assert_eq!(
+            store
+                .fire("discovery.live_unavailable", AlertSeverity::Warn, "x", 30)
+                .unwrap(),
+            AlertDecision::Suppressed(SuppressReason::Acknowledged)
+        );
+        assert_eq!(
+            store
+                .fire(
+                    "discovery.live_unavailable",
+                    AlertSeverity::Critical,
+                    "y",
+                    40
+                )
+                .unwrap(),
+            AlertDecision::Notify
+        );
+        let record = store.get("discovery.live_unavailable").unwrap().unwrap();
+        assert_eq!(record.state, AlertState::Active);
+        assert_eq!(record.acked_ts_utc, None);
+
+        assert!(store.acknowledge("unknown", 50).unwrap().is_none());
+        store.clear("discovery.live_unavailable", 60).unwrap();
+        assert!(store
+            .acknowledge("discovery.live_unavailable", 70)
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,813 @@
+//! Historical analysis: feature drift between two time ranges, and time-of-day
+//! performance attribution.
+//!
+//! Drift compares per-column feature distributions (mean/std/quantiles) of a baseline
+//! range (e.g. the training window) against a current range (e.g. last week) and
+//! scores each column with the population stability index (PSI) over
+//! baseline-quantile bins.
+//!
+//! Attribution buckets settled trades or paper fills by ET day-of-week and hour-of-day
+//! per duration ([`time_of_day_attribution`]): liquidity in these markets follows the
+//! US session, so PnL and fill quality are read as a 7 x 24 heatmap rather than a
+//! single total.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use chrono::{Datelike, TimeZone, Timelike, Utc};
+use chrono_tz::America::New_York;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::backtest::BacktestReport;
+use crate::binance_klines::BinanceSymbol;
+use crate::features::{
+    transform_store_range, FeatureError, FeatureRow, FeatureSchema, FeatureTransformConfig,
+    FeatureTransformRequest,
+};
+use crate::fill_model::OrderSide;
+use crate::kline_store::{KlineStore, KlineStoreError};
+use crate::paper_trading::SimulatedFill;
+use crate::sizing::SizingSide;
+use crate::slug::{parse_slug_at, Duration};
+
+const STEP_MS: i64 = 1_000;
+/// Floor applied to bin proportions so empty bins keep PSI finite.
+const PSI_EPSILON: f64 = 1e-4;
+/// Row labels of every [`TimeOfDayMatrix`].
+pub const WEEKDAY_LABELS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DriftConfig {
+    /// Number of baseline-quantile bins used for PSI.
+    pub psi_bins: usize,
+    /// PSI at or above which a column is reported as `moderate` drift.
+    pub moderate_psi: f64,
+    /// PSI at or above which a column is reported as `significant` drift.
+    pub significant_psi: f64,
+    /// Upper bound on sampled rows per range; longer ranges are strided evenly.
+    pub max_samples: usize,
+    /// Ranges are transformed in chunks of this many seconds to bound memory.
+    /// Rolling windows restart at each chunk boundary.
+    pub chunk_seconds: u32,
+}
+
+impl Default for DriftConfig {
+    fn default() -> Self {
+        Self {
+            psi_bins: 10,
+            moderate_psi: 0.1,
+            significant_psi: 0.25,
+            max_samples: 200_000,
+            chunk_seconds: 86_400,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DriftLevel {
+    Stable,
+    Moderate,
+    Significant,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeatureDistribution {
+    pub count: u64,
+    pub mean: f64,
+    pub std: f64,
+    pub min: f64,
+    pub p05: f64,
+    pub p25: f64,
+    pub p50: f64,
+    pub p75: f64,
+    pub p95: f64,
+    pub max: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeatureDrift {
+    pub column: String,
+    pub baseline: FeatureDistribution,
+    pub current: FeatureDistribution,
+    /// `(current.mean - baseline.mean) / baseline.std`; `0` when the baseline is constant.
+    pub mean_shift_std: f64,
+    pub psi: f64,
+    pub level: DriftLevel,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeatureDriftReport {
+    pub schema_version: u32,
+    pub schema_fingerprint: String,
+    pub baseline_range: FeatureTransformRequest,
+    pub current_range: FeatureTransformRequest,
+    pub baseline_rows: u64,
+    pub current_rows: u64,
+    pub features: Vec<FeatureDrift>,
+    /// Columns at `moderate` or `significant` drift, highest PSI first.
+    pub drifted_columns: Vec<String>,
+}
+
+#[derive(Debug, Error)]
+pub enum DriftError {
+    #[error("invalid drift config: {0}")]
+    InvalidConfig(String),
+    #[error("{range} range produced no finite values for column {column}")]
+    EmptyColumn { range: &'static str, column: String },
+    #[error("feature row at {ts_ms_utc} has {found} values, schema has {expected} columns")]
+    RowWidthMismatch {
+        ts_ms_utc: i64,
+        found: usize,
+        expected: usize,
+    },
+    #[error("feature transform failed: {0}")]
+    Feature(#[from] FeatureError),
+}
+
+/// Drift of every schema column between two in-memory feature row sets.
+pub fn feature_drift(
+    schema: &FeatureSchema,
+    baseline_rows: &[FeatureRow],
+    current_rows: &[FeatureRow],
+    cfg: &DriftConfig,
+) -> Result<Vec<FeatureDrift>, DriftError> {
+    validate_config(cfg)?;
+    let baseline = column_values(schema, baseline_rows)?;
+    let current = column_values(schema, current_rows)?;
+    compare_columns(schema, baseline, current, cfg)
+}
+
+/// Transforms both ranges of the `klines_1s` store at `store_path` and reports drift
+/// for every feature column.
+pub fn feature_drift_report(
+    store_path: &Path,
+    baseline_range: &FeatureTransformRequest,
+    current_range: &FeatureTransformRequest,
+    transform_cfg: &FeatureTransformConfig,
+    drift_cfg: &DriftConfig,
+) -> Result<FeatureDriftReport, DriftError> {
+    validate_config(drift_cfg)?;
+    info!(
+        component = "analysis",
+        event = "analysis.drift.start",
+        store_path = %store_path.display(),
+        baseline_start_ts_ms_utc = baseline_range.start_ts_ms_utc,
+        baseline_end_ts_ms_utc_exclusive = baseline_range.end_ts_ms_utc_exclusive,
+        current_start_ts_ms_utc = current_range.start_ts_ms_utc,
+        current_end_ts_ms_utc_exclusive = current_range.end_ts_ms_utc_exclusive
+    );
+
+    let (schema, baseline, baseline_rows) =
+        sample_range(store_path, baseline_range, transform_cfg, drift_cfg)?;
+    let (_, current, current_rows) =
+        sample_range(store_path, current_range, transform_cfg, drift_cfg)?;
+    let features = compare_columns(&schema, baseline, current, drift_cfg)?;
+
+    let mut drifted: Vec<&FeatureDrift> = features
+        .iter()
+        .filter(|drift| drift.level != DriftLevel::Stable)
+        .collect();
+    drifted.sort_by(|a, b| b.psi.total_cmp(&a.psi));
+    let drifted_columns: Vec<String> = drifted.iter().map(|d| d.column.clone()).collect();
+
+    if drifted_columns.is_empty() {
+        info!(
+            component = "analysis",
+            event = "analysis.drift.finish",
+            columns = features.len(),
+            drifted_columns = 0
+        );
+    } else {
+        warn!(
+            component = "analysis",
+            event = "analysis.drift.finish",
+            columns = features.len(),
+            drifted_columns = drifted_columns.len(),
+            top_column = %drifted_columns[0],
+            top_psi = drifted[0].psi
+        );
+    }
+
+    Ok(FeatureDriftReport {
+        schema_version: schema.version,
+        schema_fingerprint: schema.fingerprint,
+        baseline_range: baseline_range.clone(),
+        current_range: current_range.clone(),
+        baseline_rows,
+        current_rows,
+        features,
+        drifted_columns,
+    })
+}
+
+/// Summary statistics over finite `values`; quantiles use linear interpolation.
+pub fn feature_distribution(values: &[f64]) -> Option<FeatureDistribution> {
+    let mut sorted: Vec<f64> = values.iter().copied().filter(|v| v.is_finite()).collect();
+    if sorted.is_empty() {
+        return None;
+    }
+    sorted.sort_by(f64::total_cmp);
+
+    let n = sorted.len() as f64;
+    let mean = sorted.iter().sum::<f64>() / n;
+    let variance = sorted.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / n;
+
+    Some(FeatureDistribution {
+        count: sorted.len() as u64,
+        mean,
+        std: variance.sqrt(),
+        min: sorted[0],
+        p05: quantile(&sorted, 0.05),
+        p25: quantile(&sorted, 0.25),
+        p50: quantile(&sorted, 0.50),
+        p75: quantile(&sorted, 0.75),
+        p95: quantile(&sorted, 0.95),
+        max: sorted[sorted.len() - 1],
+    })
+}
+
+/// Population stability index of `current` against `baseline`, binned at baseline
+/// quantiles. Duplicate bin edges (low-cardinality features) are collapsed.
+pub fn population_stability_index(baseline: &[f64], current: &[f64], bins: usize) -> f64 {
+    let mut sorted: Vec<f64> = baseline.iter().copied().filter(|v| v.is_finite()).collect();
+    let current: Vec<f64> = current.iter().copied().filter(|v| v.is_finite()).collect();
+    if sorted.is_empty() || current.is_empty() || bins == 0 {
+        return 0.0;
+    }
+    sorted.sort_by(f64::total_cmp);
+
+    let mut edges: Vec<f64> = (1..bins)
+        .map(|i| quantile(&sorted, i as f64 / bins as f64))
+        .collect();
+    edges.dedup();
+
+    let baseline_share = bin_shares(&sorted, &edges);
+    let current_share = bin_shares(&current, &edges);
+    baseline_share
+        .iter()
+        .zip(&current_share)
+        .map(|(b, c)| (c - b) * (c / b).ln())
+        .sum()
+}
+
+pub fn drift_level(psi: f64, cfg: &DriftConfig) -> DriftLevel {
+    if psi >= cfg.significant_psi {
+        DriftLevel::Significant
+    } else if psi >= cfg.moderate_psi {
+        DriftLevel::Moderate
+    } else {
+        DriftLevel::Stable
+    }
+}
+
+fn validate_config(cfg: &DriftConfig) -> Result<(), DriftError> {
+    if cfg.psi_bins < 2 {
+        return Err(DriftError::InvalidConfig(
+            "psi_bins must be at least 2".to_string(),
+        ));
+    }
+    if !(cfg.moderate_psi >= 0.0 && cfg.moderate_psi <= cfg.significant_psi) {
+        return Err(DriftError::InvalidConfig(format!(
+            "thresholds must satisfy 0 <= moderate_psi ({}) <= significant_psi ({})",
+            cfg.moderate_psi, cfg.significant_psi
+        )));
+    }
+    if cfg.max_samples == 0 || cfg.chunk_seconds == 0 {
+        return Err(DriftError::InvalidConfig(
+            "max_samples and chunk_seconds must be positive".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn column_values(schema: &FeatureSchema, rows: &[FeatureRow]) -> Result<Vec<Vec<f64>>, DriftError> {
+    let mut columns = vec![Vec::with_capacity(rows.len()); schema.columns.len()];
+    for row in rows {
+        push_row(&mut columns, row)?;
+    }
+    Ok(columns)
+}
+
+fn push_row(columns: &mut [Vec<f64>], row: &FeatureRow) -> Result<(), DriftError> {
+    if row.values.len() != columns.len() {
+        return Err(DriftError::RowWidthMismatch {
+            ts_ms_utc: row.ts_ms_utc,
+            found: row.values.len(),
+            expected: columns.len(),
+        });
+    }
+    for (column, value) in columns.iter_mut().zip(&row.values) {
+        column.push(*value);
+    }
+    Ok(())
+}
+
+/// Transforms `range` chunk by chunk, keeping every `stride`-th second so at most
+/// `max_samples` rows are held. Returns the schema, sampled columns, and row count.
+fn sample_range(
+    store_path: &Path,
+    range: &FeatureTransformRequest,
+    transform_cfg: &FeatureTransformConfig,
+    drift_cfg: &DriftConfig,
+) -> Result<(FeatureSchema, Vec<Vec<f64>>, u64), DriftError> {
+    let total_points =
+        ((range.end_ts_ms_utc_exclusive - range.start_ts_ms_utc).max(0) / STEP_MS) as usize;
+    let stride = total_points.div_ceil(drift_cfg.max_samples).max(1) as i64;
+    let chunk_ms = i64::from(drift_cfg.chunk_seconds) * STEP_MS;
+
+    let mut schema = None;
+    let mut columns: Vec<Vec<f64>> = Vec::new();
+    let mut sampled_rows = 0u64;
+    let mut chunk_start = range.start_ts_ms_utc;
+    while chunk_start < range.end_ts_ms_utc_exclusive {
+        let chunk = FeatureTransformRequest {
+            start_ts_ms_utc: chunk_start,
+            end_ts_ms_utc_exclusive: (chunk_start + chunk_ms).min(range.end_ts_ms_utc_exclusive),
+        };
+        let (chunk_schema, rows, _) = transform_store_range(store_path, &chunk, transform_cfg)?;
+        if columns.is_empty() {
+            columns = vec![Vec::new(); chunk_schema.columns.len()];
+        }
+        for row in rows
+            .iter()
+            .filter(|row| (row.ts_ms_utc / STEP_MS) % stride == 0)
+        {
+            push_row(&mut columns, row)?;
+            sampled_rows += 1;
+        }
+        schema.get_or_insert(chunk_schema);
+        chunk_start = chunk.end_ts_ms_utc_exclusive;
+    }
+
+    let schema = match schema {
+        Some(schema) => schema,
+        None => {
+            return Err(
+                FeatureError::InvalidRequest("drift range must be non-empty".to_string()).into(),
+            )
+        }
+    };
+    Ok((schema, columns, sampled_rows))
+}
+
+fn compare_columns(
+    schema: &FeatureSchema,
+    baseline: Vec<Vec<f64>>,
+    current: Vec<Vec<f64>>,
+    cfg: &DriftConfig,
+) -> Result<Vec<FeatureDrift>, DriftError> {
+    schema
+        .columns
+        .iter()
+        .zip(baseline.iter().zip(&current))
+        .map(|(column, (baseline_values, current_values))| {
+            let baseline_dist =
+                feature_distribution(baseline_values).ok_or_else(|| DriftError::EmptyColumn {
+                    range: "baseline",
+                    column: column.name.clone(),
+                })?;
+            let current_dist =
+                feature_distribution(current_values).ok_or_else(|| DriftError::EmptyColumn {
+                    range: "current",
+                    column: column.name.clone(),
+                })?;
+            let psi = population_stability_index(baseline_values, current_values, cfg.psi_bins);
+            let mean_shift_std = if baseline_dist.std > 0.0 {
+                (current_dist.mean - baseline_dist.mean) / baseline_dist.std
+            } else {
+                0.0
+            };
+            Ok(FeatureDrift {
+                column: column.name.clone(),
+                baseline: baseline_dist,
+                current: current_dist,
+                mean_shift_std,
+                psi,
+                level: drift_level(psi, cfg),
+            })
+        })
+        .collect()
+}
+
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let pos = q * (sorted.len() - 1) as f64;
+    let lo = pos.floor() as usize;
+    let hi = pos.ceil() as usize;
+    sorted[lo] + (sorted[hi] - sorted[lo]) * (pos - lo as f64)
+}
+
+/// Share of `values` per bin, where bin `i` is `(edges[i-1], edges[i]]` and the outer
+/// bins are open-ended.
+fn bin_shares(values: &[f64], edges: &[f64]) -> Vec<f64> {
+    let mut counts = vec![0usize; edges.len() + 1];
+    for value in values {
+        counts[edges.partition_point(|edge| edge < value)] += 1;
+    }
+    counts
+        .into_iter()
+        .map(|count| (count as f64 / values.len() as f64).max(PSI_EPSILON))
+        .collect()
+}
+
+/// One settled trade or fill to attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AttributionSample {
+    /// When the position was taken, UTC seconds.
+    pub ts_utc: i64,
+    pub duration: Duration,
+    pub pnl_usdc: f64,
+    /// USDC paid, or received for a sale.
+    pub notional_usdc: f64,
+    pub shares: f64,
+    /// Whether the order rested on the book; `None` when unknown (backtest entries).
+    pub maker: Option<bool>,
+}
+
+impl AttributionSample {
+    /// One sample per settled entry, taken at the interval start.
+    pub fn from_backtest(report: &BacktestReport) -> Vec<Self> {
+        report
+            .trades
+            .iter()
+            .map(|trade| Self {
+                ts_utc: trade.start_ts_utc,
+                duration: report.duration,
+                pnl_usdc: trade.pnl_usdc,
+                notional_usdc: trade.stake_usdc,
+                shares: trade.stake_usdc / trade.price,
+                maker: None,
+            })
+            .collect()
+    }
+}
+
+/// Settles paper `fills` against the `klines_1s` store like a backtest: UP wins when
+/// the close of the interval's last 1s kline is at or above the open of its first.
+/// Fills whose slug does not name a coin interval, or whose interval lacks either
+/// kline, are counted in the second value instead.
+pub fn settle_paper_fills(
+    klines: &KlineStore,
+    fills: &[SimulatedFill],
+) -> Result<(Vec<AttributionSample>, u64), KlineStoreError> {
+    let mut winners: HashMap<&str, Option<(Duration, SizingSide)>> = HashMap::new();
+    let mut samples = Vec::new();
+    let mut unsettled = 0;
+    for fill in fills {
+        let winner = match winners.get(fill.slug.as_str()) {
+            Some(winner) => *winner,
+            None => {
+                let winner = interval_winner(klines, &fill.slug, fill.ts_ms.div_euclid(1_000))?;
+                winners.insert(&fill.slug, winner);
+                winner
+            }
+        };
+        let Some((duration, winner)) = winner else {
+            unsettled += 1;
+            continue;
+        };
+        let payout = if fill.outcome == winner { 1.0 } else { 0.0 };
+        let pnl_per_share = match fill.side {
+            OrderSide::Buy => payout - fill.price,
+            OrderSide::Sell => fill.price - payout,
+        };
+        samples.push(AttributionSample {
+            ts_utc: fill.ts_ms.div_euclid(1_000),
+            duration,
+            pnl_usdc: pnl_per_share * fill.size,
+            notional_usdc: fill.price * fill.size,
+            shares: fill.size,
+            maker: Some(!fill.taker),
+        });
+    }
+    Ok((samples, unsettled))
+}
+
+fn interval_winner(
+    klines: &KlineStore,
+    slug: &str,
+    ts_utc: i64,
+) -> Result<Option<(Duration, SizingSide)>, KlineStoreError> {
+    let Ok((coin, duration, start_ts_utc)) = parse_slug_at(slug, ts_utc) else {
+        return Ok(None);
+    };
+    let Some(symbol) = BinanceSymbol::for_coin(coin) else {
+        return Ok(None);
+    };
+    let start_ms = start_ts_utc * 1_000;
+    let end_ms = duration.interval_end(start_ts_utc) * 1_000;
+    let open = klines.first_open_in_range(symbol, start_ms, start_ms + STEP_MS)?;
+    let close = klines.closes_range(symbol, end_ms - STEP_MS, end_ms)?;
+    Ok(match (open, close.first()) {
+        (Some((_, open)), Some(&(_, close))) => Some((
+            duration,
+            if close >= open {
+                SizingSide::Yes
+            } else {
+                SizingSide::No
+            },
+        )),
+        _ => None,
+    })
+}
+
+/// Totals of the samples in one bucket.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TimeOfDayCell {
+    pub samples: u64,
+    pub pnl_usdc: f64,
+    pub notional_usdc: f64,
+    pub shares: f64,
+    /// `pnl_usdc / notional_usdc`; `None` without notional.
+    pub return_on_notional: Option<f64>,
+    /// `pnl_usdc / shares`: what a share was worth against its settlement.
+    pub edge_per_share: Option<f64>,
+    /// Share of samples with positive PnL; `None` when empty.
+    pub hit_rate: Option<f64>,
+    /// Share of samples that rested on the book, among those where it is known.
+    pub maker_share: Option<f64>,
+    #[serde(skip)]
+    wins: u64,
+    #[serde(skip)]
+    maker_known: u64,
+    #[serde(skip)]
+    makers: u64,
+}
+
+impl TimeOfDayCell {
+    fn add(&mut self, sample: &AttributionSample) {
+        self.samples += 1;
+        self.pnl_usdc += sample.pnl_usdc;
+        self.notional_usdc += sample.notional_usdc;
+        self.shares += sample.shares;
+        self.wins += u64::from(sample.pnl_usdc > 0.0);
+        if let Some(maker) = sample.maker {
+            self.maker_known += 1;
+            self.makers += u64::from(maker);
+        }
+    }
+
+    fn finish(&mut self) {
+        let ratio = |num: f64, den: f64| (den > 0.0).then(|| num / den);
+        self.return_on_notional = ratio(self.pnl_usdc, self.notional_usdc);
+        self.edge_per_share = ratio(self.pnl_usdc, self.shares);
+        self.hit_rate = ratio(self.wins as f64, self.samples as f64);
+        self.maker_share = ratio(self.makers as f64, self.maker_known as f64);
+    }
+}
+
+/// One duration's heatmap.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimeOfDayMatrix {
+    pub duration: Duration,
+    pub total: TimeOfDayCell,
+    /// `cells[weekday][hour_et]`, Monday first (see [`WEEKDAY_LABELS`]).
+    pub cells: Vec<Vec<TimeOfDayCell>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimeOfDayReport {
+    /// Zone of the weekday and hour buckets, `America/New_York`.
+    pub timezone: String,
+    pub weekdays: Vec<String>,
+    /// Durations with samples, shortest first.
+    pub matrices: Vec<TimeOfDayMatrix>,
+    /// Inputs that could not be settled and are not in any matrix.
+    pub unsettled: u64,
+}
+
+impl TimeOfDayReport {
+    /// One line per duration, weekday, and hour, every bucket included so the matrix
+    /// is complete; empty ratios are blank.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from(
+            "duration,weekday,hour_et,samples,pnl_usdc,notional_usdc,return_on_notional,\
+             edge_per_share,hit_rate,maker_share\n",
+        );
+        let opt = |value: Option<f64>| value.map(|v| v.to_string()).unwrap_or_default();
+        for matrix in &self.matrices {
+            for (weekday, hours) in WEEKDAY_LABELS.iter().zip(&matrix.cells) {
+                for (hour, cell) in hours.iter().enumerate() {
+                    let _ = writeln!(
+                        out,
+                        "{},{weekday},{hour},{},{},{},{},{},{},{}",
+                        matrix.duration.as_label(),
+                        cell.samples,
+                        cell.pnl_usdc,
+                        cell.notional_usdc,
+                        opt(cell.return_on_notional),
+                        opt(cell.edge_per_share),
+                        opt(cell.hit_rate),
+                        opt(cell.maker_share),
+                    );
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Buckets `samples` by ET weekday and hour of `ts_utc`, per duration. `unsettled` is
+/// passed through to the report.
+pub fn time_of_day_attribution(samples: &[AttributionSample], unsettled: u64) -> TimeOfDayReport {
+    let mut matrices: Vec<TimeOfDayMatrix> = Duration::ALL
+        .into_iter()
+        .map(|duration| TimeOfDayMatrix {
+            duration,
+            total: TimeOfDayCell::default(),
+            cells: vec![vec![TimeOfDayCell::default(); 24]; 7],
+        })
+        .collect();
+    let mut unsettled = unsettled;
+    for sample in samples {
+        let Some(local) = Utc
+            .timestamp_opt(sample.ts_utc, 0)
+            .single()
+            .map(|ts| ts.with_timezone(&New_York))
+        else {
+            unsettled += 1;
+            continue;
+        };
+        let Some(matrix) = matrices.iter_mut().find(|m| m.duration == sample.duration) else {
+            unsettled += 1;
+            continue;
+        };
+        let weekday = local.weekday().num_days_from_monday() as usize;
+        matrix.cells[weekday][local.hour() as usize].add(sample);
+        matrix.total.add(sample);
+    }
+    matrices.retain(|matrix| matrix.total.samples > 0);
+    for matrix in &mut matrices {
+        matrix.total.finish();
+        matrix
+            .cells
+            .iter_mut()
+            .flatten()
+            .for_each(TimeOfDayCell::finish);
+    }
+    TimeOfDayReport {
+        timezone: "America/New_York".to_string(),
+        weekdays: WEEKDAY_LABELS.iter().map(|day| day.to_string()).collect(),
+        matrices,
+        unsettled,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::{FeatureColumn, FeatureDType};
+
+    fn schema(columns: &[&str]) -> FeatureSchema {
+        FeatureSchema {
+            version: 1,
+            fingerprint: "test".to_string(),
+            columns: columns
+                .iter()
+                .map(|name| FeatureColumn {
+                    name: name.to_string(),
+                    dtype: FeatureDType::F64,
+                })
+                .collect(),
+        }
+    }
+
+    fn rows(values: impl Iterator<Item = (f64, f64)>) -> Vec<FeatureRow> {
+        values
+            .enumerate()
+            .map(|(i, (a, b))| FeatureRow {
+                ts_ms_utc: i as i64 * 1_000,
+                values: vec![a, b],
+            })
+            .collect()
+    }
+
+    #[test]
+    fn distribution_quantiles_interpolate_and_skip_non_finite() {
+        let values: Vec<f64> = (0..=100).map(f64::from).chain([f64::NAN]).collect();
+        let dist = feature_distribution(&values).unwrap();
+        assert_eq!(dist.count, 101);
+        assert_eq!(dist.mean, 50.0);
+        assert_eq!((dist.min, dist.max), (0.0, 100.0));
+        assert_eq!((dist.p05, dist.p50, dist.p95), (5.0, 50.0, 95.0));
+        assert!(feature_distribution(&[f64::NAN]).is_none());
+    }
+
+    #[test]
+    fn shifted_column_is_flagged_and_stable_column_is_not() {
+        let schema = schema(&["stable", "shifted"]);
+        let baseline = rows((0..1_000).map(|i| ((i % 100) as f64, (i % 100) as f64)));
+        let current = rows((0..1_000).map(|i| ((i % 100) as f64, (i % 100) as f64 + 40.0)));
+
+        let drift = feature_drift(&schema, &baseline, &current, &DriftConfig::default()).unwrap();
+        assert_eq!(drift[0].level, DriftLevel::Stable);
+        assert!(drift[0].psi.abs() < 1e-9);
+        assert_eq!(drift[1].level, DriftLevel::Significant);
+        assert!(drift[1].psi > 1.0);
+        assert!(drift[1].mean_shift_std > 1.0);
+    }
+
+    #[test]
+    fn settled_paper_fills_are_bucketed_by_et_weekday_and_hour() {
+        use crate::binance_klines::Kline1s;
+        use crate::kline_validation::KlineValidationConfig;
+
+        // Monday 2025-01-06 09:30 ET (EST).
+        let start = 1_736_173_800;
+        let kline = |open_time_ms: i64, price: f64| Kline1s {
+            open_time_ms,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume: 1.0,
+            close_time_ms: open_time_ms + 999,
+            quote_asset_volume: price,
+            trade_count: 1,
+            taker_buy_base_volume: 0.5,
+            taker_buy_quote_volume: price / 2.0,
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("klines_1s.sqlite");
+        let mut store = KlineStore::open(&path, KlineValidationConfig::default()).unwrap();
+        store
+            .upsert_rows(
+                BinanceSymbol::BtcUsdt,
+                vec![
+                    kline(start * 1_000, 100.0),
+                    kline((start + 299) * 1_000, 101.0),
+                ],
+            )
+            .unwrap();
+        let fill = |slug: String, outcome, price, size, taker| SimulatedFill {
+            fill_id: format!("{slug}-{price}"),
+            order_id: "o".to_string(),
+            slug,
+            outcome,
+            token_id: "t".to_string(),
+            side: OrderSide::Buy,
+            price,
+            size,
+            taker,
+            ts_ms: (start + 60) * 1_000,
+        };
+        let slug = |start: i64| format!("btc-updown-5m-{start}");
+        let fills = [
+            fill(slug(start), SizingSide::Yes, 0.6, 10.0, false),
+            fill(slug(start), SizingSide::No, 0.3, 5.0, true),
+            // No klines for the next interval, and no coin behind the last slug.
+            fill(slug(start + 300), SizingSide::Yes, 0.5, 1.0, false),
+            fill("not-a-market".to_string(), SizingSide::Yes, 0.5, 1.0, false),
+        ];
+        let (mut samples, unsettled) = settle_paper_fills(&store, &fills).unwrap();
+        assert_eq!(unsettled, 2);
+        // Monday 09:30 ET in summer is 13:30 UTC.
+        samples.push(AttributionSample {
+            ts_utc: 1_751_895_000,
+            duration: Duration::M5,
+            pnl_usdc: 0.5,
+            notional_usdc: 1.0,
+            shares: 2.0,
+            maker: None,
+        });
+
+        let report = time_of_day_attribution(&samples, unsettled);
+        assert_eq!(report.unsettled, 2);
+        assert_eq!(report.matrices.len(), 1);
+        let matrix = &report.matrices[0];
+        assert_eq!(matrix.duration, Duration::M5);
+        let cell = &matrix.cells[0][9];
+        assert_eq!(cell.samples, 3);
+        assert!((cell.pnl_usdc - 3.0).abs() < 1e-9, "{}", cell.pnl_usdc);
+        assert!((cell.notional_usdc - 8.5).abs() < 1e-9);
+        assert_eq!(cell.maker_share, Some(0.5));
+        assert_eq!(cell.hit_rate, Some(2.0 / 3.0));
+        assert_eq!(matrix.total, *cell);
+
+        let csv = report.to_csv();
+        assert_eq!(csv.lines().count(), 1 + 7 * 24);
+        assert!(csv.contains("\n5m,Mon,9,3,"));
+        assert!(csv.contains("\n5m,Sun,23,0,0,0,,,,\n"));
+    }
+
+    #[test]
+    fn row_width_mismatch_is_explicit() {
+        let schema = schema(&["a", "b"]);
+        let bad = vec![FeatureRow {
+            ts_ms_utc: 7_000,
+            values: vec![1.0],
+        }];
+        let err = feature_drift(&schema, &bad, &bad, &DriftConfig::default()).unwrap_err();
+        assert!(matches!(
+            err,
+            DriftError::RowWidthMismatch {
+                ts_ms_utc: 7_000,
+                found: 1,
+                expected: 2
+            }
+        ));
+    }
+}
@@ -0,0 +1,337 @@
+//! Replays the `klines_1s` store through the feature transform and a [`Strategy`].
+//!
+//! For every interval of one duration in the replay range, each coin's strategy call
+//! sees the feature row of the last second before the interval start (its kline closes
+//! at the start, so nothing after the start leaks in) and may enter one side at a
+//! price of its choosing. The interval settles like an up/down market: UP wins when
+//! the close of the interval's last 1s kline is at or above the open of its first. A
+//! winning entry pays `1` per share; fees are not charged.
+//!
+//! Intervals whose first or last kline is missing, or whose decision row was not
+//! produced (warm-up, or a gap under [`GapPolicy::ReportAndSkip`]), are counted as
+//! skipped instead of guessed.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::info;
+
+use crate::binance_klines::BinanceSymbol;
+use crate::features::{
+    transform_store_range, FeatureError, FeatureRow, FeatureSchema, FeatureTransformConfig,
+    FeatureTransformReport, FeatureTransformRequest, GapPolicy,
+};
+use crate::kline_store::{KlineStore, KlineStoreError};
+use crate::sizing::SizingSide;
+use crate::slug::{Coin, Duration};
+
+const STEP_MS: i64 = 1_000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BacktestConfig {
+    pub store_path: PathBuf,
+    pub duration: Duration,
+    pub coins: Vec<Coin>,
+    /// Intervals starting at or after this and ending by `end_ts_utc` are replayed.
+    pub start_ts_utc: i64,
+    pub end_ts_utc: i64,
+    /// Defaults to the duration's window preset, skipping gaps instead of failing.
+    pub transform: FeatureTransformConfig,
+}
+
+impl BacktestConfig {
+    pub fn new(
+        store_path: impl Into<PathBuf>,
+        duration: Duration,
+        start_ts_utc: i64,
+        end_ts_utc: i64,
+    ) -> Self {
+        Self {
+            store_path: store_path.into(),
+            duration,
+            coins: vec![Coin::BTC, Coin::ETH, Coin::SOL, Coin::XRP],
+            start_ts_utc,
+            end_ts_utc,
+            transform: FeatureTransformConfig {
+                gap_policy: GapPolicy::ReportAndSkip,
+                ..FeatureTransformConfig::for_duration(duration)
+            },
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum BacktestError {
+    #[error("invalid backtest range: {0}")]
+    InvalidRange(String),
+    #[error("coin {0:?} has no klines in the store")]
+    UnsupportedCoin(Coin),
+    #[error("feature transform failed: {0}")]
+    Features(#[from] FeatureError),
+    #[error("kline store error: {0}")]
+    Klines(#[from] KlineStoreError),
+}
+
+/// One coin's interval as the strategy sees it at the start.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BacktestInterval {
+    pub coin: Coin,
+    pub duration: Duration,
+    pub start_ts_utc: i64,
+    pub end_ts_utc: i64,
+    /// Open of the first 1s kline: the price the interval resolves against.
+    pub open_price: f64,
+}
+
+/// A position taken at the interval start and held to settlement.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BacktestEntry {
+    pub side: SizingSide,
+    /// Price paid per share, in `(0, 1)`.
+    pub price: f64,
+    pub stake_usdc: f64,
+}
+
+/// Decision logic under test.
+pub trait Strategy {
+    fn name(&self) -> &str;
+
+    /// Called once before the replay with the schema of the rows `decide` receives.
+    fn prepare(&mut self, _schema: &FeatureSchema) {}
+
+    /// Entry for `interval`, if any, from the features of the second before it starts.
+    fn decide(
+        &mut self,
+        interval: &BacktestInterval,
+        features: &FeatureRow,
+    ) -> Option<BacktestEntry>;
+}
+
+/// One settled entry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BacktestTrade {
+    pub coin: Coin,
+    pub start_ts_utc: i64,
+    pub end_ts_utc: i64,
+    pub side: SizingSide,
+    pub price: f64,
+    pub stake_usdc: f64,
+    pub open_price: f64,
+    pub close_price: f64,
+    pub won: bool,
+    pub pnl_usdc: f64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BacktestSummary {
+    pub trades: u64,
+    pub wins: u64,
+    /// `wins / trades`; `None` without trades.
+    pub hit_rate: Option<f64>,
+    pub staked_usdc: f64,
+    pub pnl_usdc: f64,
+    /// Largest drop of cumulative PnL from its running peak (starting at `0`), in
+    /// settlement order.
+    pub max_drawdown_usdc: f64,
+}
+
+impl BacktestSummary {
+    fn from_trades<'a>(trades: impl IntoIterator<Item = &'a BacktestTrade>) -> Self {
+        let mut summary = Self::default();
+        let mut peak = 0.0_f64;
+        for trade in trades {
+            summary.trades += 1;
+            summary.wins += u64::from(trade.won);
+            summary.staked_usdc += trade.stake_usdc;
+            summary.pnl_usdc += trade.pnl_usdc;
+            peak = peak.max(summary.pnl_usdc);
+            summary.max_drawdown_usdc = summary.max_drawdown_usdc.max(peak - summary.pnl_usdc);
+        }
+        summary.hit_rate =
+            (summary.trades > 0).then(|| summary.wins as f64 / summary.trades as f64);
+        summary
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BacktestReport {
+    pub strategy: String,
+    pub duration: Duration,
+    pub start_ts_utc: i64,
+    pub end_ts_utc: i64,
+    pub schema_fingerprint: String,
+    /// Coin intervals replayed, entered or not.
+    pub intervals: u64,
+    /// Coin intervals without settlement klines or a decision row.
+    pub skipped_intervals: u64,
+    /// Per-interval results, in settlement order.
+    pub trades: Vec<BacktestTrade>,
+    pub summary: BacktestSummary,
+    pub by_coin: BTreeMap<Coin, BacktestSummary>,
+    pub transform: FeatureTransformReport,
+}
+
+/// Replays `cfg` through `strategy`.
+pub fn run_backtest(
+    cfg: &BacktestConfig,
+    strategy: &mut dyn Strategy,
+) -> Result<BacktestReport, BacktestError> {
+    if cfg.end_ts_utc <= cfg.start_ts_utc {
+        return Err(BacktestError::InvalidRange(format!(
+            "end {} is not after start {}",
+            cfg.end_ts_utc, cfg.start_ts_utc
+        )));
+    }
+    let symbols = cfg
+        .coins
+        .iter()
+        .map(|&coin| BinanceSymbol::for_coin(coin).ok_or(BacktestError::UnsupportedCoin(coin)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let starts = interval_starts(cfg.duration, cfg.start_ts_utc, cfg.end_ts_utc);
+
+    // Warm the rolling windows up before the first decision row.
+    let warmup_ms = i64::from(
+        cfg.transform
+            .windows_seconds
+            .iter()
+            .copied()
+            .max()
+            .unwrap_or(1),
+    ) * STEP_MS;
+    let req = FeatureTransformRequest {
+        start_ts_ms_utc: cfg.start_ts_utc * 1_000 - warmup_ms - STEP_MS,
+        end_ts_ms_utc_exclusive: cfg.end_ts_utc * 1_000,
+    };
+    let (schema, rows, transform) = transform_store_range(&cfg.store_path, &req, &cfg.transform)?;
+    strategy.prepare(&schema);
+    let klines = KlineStore::open_read_only(&cfg.store_path)?;
+
+    let mut intervals = 0;
+    let mut skipped_intervals = 0;
+    let mut trades = Vec::new();
+    for (start_ts_utc, end_ts_utc) in starts {
+        let start_ms = start_ts_utc * 1_000;
+        let end_ms = end_ts_utc * 1_000;
+        let decision_row = decision_row(&rows, start_ms);
+        for (&coin, &symbol) in cfg.coins.iter().zip(&symbols) {
+            intervals += 1;
+            let open = klines
+                .first_open_in_range(symbol, start_ms, start_ms + STEP_MS)?
+                .map(|(_, open)| open);
+            let close = klines
+                .closes_range(symbol, end_ms - STEP_MS, end_ms)?
+                .first()
+                .map(|&(_, close)| close);
+            let (Some(row), Some(open_price), Some(close_price)) = (decision_row, open, close)
+            else {
+                skipped_intervals += 1;
+                continue;
+            };
+            let interval = BacktestInterval {
+                coin,
+                duration: cfg.duration,
+                start_ts_utc,
+                end_ts_utc,
+                open_price,
+            };
+            let Some(entry) = strategy
+                .decide(&interval, row)
+                .filter(|entry| entry.price > 0.0 && entry.price < 1.0 && entry.stake_usdc > 0.0)
+            else {
+                continue;
+            };
+            trades.push(settle(&interval, entry, close_price));
+        }
+    }
+
+    let mut by_coin = BTreeMap::new();
+    for &coin in &cfg.coins {
+        by_coin.insert(
+            coin,
+            BacktestSummary::from_trades(trades.iter().filter(|trade| trade.coin == coin)),
+        );
+    }
+    let report = BacktestReport {
+        strategy: strategy.name().to_string(),
+        duration: cfg.duration,
+        start_ts_utc: cfg.start_ts_utc,
+        end_ts_utc: cfg.end_ts_utc,
+        schema_fingerprint: schema.fingerprint,
+        intervals,
+        skipped_intervals,
+        summary: BacktestSummary::from_trades(&trades),
+        trades,
+        by_coin,
+        transform,
+    };
+    info!(
+        component = "backtest",
+        event = "backtest.finish",
+        strategy = %report.strategy,
+        duration = report.duration.as_label(),
+        intervals = report.intervals,
+        skipped_intervals = report.skipped_intervals,
+        trades = report.summary.trades,
+        pnl_usdc = report.summary.pnl_usdc,
+        max_drawdown_usdc = report.summary.max_drawdown_usdc
+    );
+    Ok(report)
+}
+
+/// `(start, end)` of every interval of `duration` inside `[start_ts_utc, end_ts_utc]`.
+pub(crate) fn interval_starts(
+    duration: Duration,
+    start_ts_utc: i64,
+    end_ts_utc: i64,
+) -> Vec<(i64, i64)> {
+    let first = crate::discovery::interval_starts_for_now(
+        duration,
+        start_ts_utc,
+        crate::slug::SlugConfig::default(),
+    );
+    let mut start = if first.active_start_ts_utc >= start_ts_utc {
+        first.active_start_ts_utc
+    } else {
+        first.next_start_ts_utc
+    };
+    let mut intervals = Vec::new();
+    loop {
+        let end = duration.interval_end(start);
+        if end > end_ts_utc || end <= start {
+            return intervals;
+        }
+        intervals.push((start, end));
+        start = end;
+    }
+}
+
+/// Row of the second before `start_ms`, if the transform produced it.
+fn decision_row(rows: &[FeatureRow], start_ms: i64) -> Option<&FeatureRow> {
+    let idx = rows.partition_point(|row| row.ts_ms_utc < start_ms);
+    idx.checked_sub(1)
+        .map(|idx| &rows[idx])
+        .filter(|row| row.ts_ms_utc == start_ms - STEP_MS)
+}
+
+fn settle(interval: &BacktestInterval, entry: BacktestEntry, close_price: f64) -> BacktestTrade {
+    let up = close_price >= interval.open_price;
+    let won = match entry.side {
+        SizingSide::Yes => up,
+        SizingSide::No => !up,
+    };
+    let shares = entry.stake_usdc / entry.price;
+    BacktestTrade {
+        coin: interval.coin,
+        start_ts_utc: interval.start_ts_utc,
+        end_ts_utc: interval.end_ts_utc,
+        side: entry.side,
+        price: entry.price,
+        stake_usdc: entry.stake_usdc,
+        open_price: interval.open_price,
+        close_price,
+        won,
+        pnl_usdc: if won { shares } else { 0.0 } - entry.stake_usdc,
+    }
+}
@@ -1,7 +1,10 @@
 use std::collections::HashMap;
 
 use chrono::{Datelike, NaiveDate, TimeZone, Utc};
-use pmm::{load_1s_klines, BinanceSymbol, HistoricalKlinesConfig, KlineLoadRequest};
+use pmm::{
+    load_1s_klines, load_settings, BinanceSymbol, HistoricalKlinesConfig, KlineLoadRequest,
+    KlineScope,
+};
 
 #[derive(Default, Debug, Clone, Copy)]
 struct Totals {
@@ -12,12 +15,18 @@ struct Totals {
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let settings = load_settings()?;
     let start_date = NaiveDate::from_ymd_opt(2025, 1, 1).expect("valid start date");
     let today_utc = Utc::now().date_naive();
     let end_date_exclusive = today_utc; // latest completed UTC day
 
-    let start_ts = day_start_ts_ms(start_date);
+    let scope = KlineScope::from_settings(&settings);
     let end_ts = day_start_ts_ms(end_date_exclusive);
+    let start_ts = if scope.restrict_range {
+        scope.repair_start_ms(end_ts)
+    } else {
+        day_start_ts_ms(start_date)
+    };
 
     if end_ts <= start_ts {
         return Err(format!(
@@ -33,19 +42,18 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         ..HistoricalKlinesConfig::default()
     };
 
+    let symbols = scope.symbols.as_slice();
+    let symbol_names: Vec<&str> = symbols.iter().map(|symbol| symbol.as_str()).collect();
     println!(
-        "Running Binance 1s combined audit for BTCUSDT/ETHUSDT/SOLUSDT/XRPUSDT from {} 00:00:00 UTC to {} 00:00:00 UTC (exclusive)",
-        start_date,
+        "Running Binance 1s combined audit for {} from {} UTC to {} 00:00:00 UTC (exclusive)",
+        symbol_names.join("/"),
+        Utc.timestamp_millis_opt(start_ts)
+            .single()
+            .expect("valid start timestamp")
+            .format("%Y-%m-%d %H:%M:%S"),
         end_date_exclusive
     );
 
-    let symbols = [
-        BinanceSymbol::BtcUsdt,
-        BinanceSymbol::EthUsdt,
-        BinanceSymbol::SolUsdt,
-        BinanceSymbol::XrpUsdt,
-    ];
-
     let mut totals: HashMap<BinanceSymbol, Totals> = symbols
         .iter()
         .copied()
@@ -81,7 +89,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
         println!("\nWindow {} -> {}", window_start_date, window_end_date);
 
-        for symbol in symbols {
+        for &symbol in symbols {
             let req = KlineLoadRequest {
                 symbol,
                 start_ts_ms_utc: cursor,
@@ -120,7 +128,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     let mut initial_missing_total = 0u64;
-    for symbol in symbols {
+    for &symbol in symbols {
         let t = totals
             .get(&symbol)
             .expect("totals map should contain symbol");
@@ -139,7 +147,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut remaining_missing = 0u64;
     if initial_missing_total > 0 {
         println!("\nRefill pass: downloading only missing ranges per symbol...");
-        for symbol in symbols {
+        for &symbol in symbols {
             let ranges = gap_ranges_by_symbol
                 .get(&symbol)
                 .cloned()
@@ -1,51 +1,203 @@
 use std::{net::SocketAddr, sync::Arc};
 
 use pmm::{
-    dashboard_router, init_logging, log_app_bind, log_app_start, log_source_selected,
-    logging_config_from_env, DashboardSnapshotSource, InMemoryMockSnapshotSource,
+    alerts_router, curve_router, dashboard_router_with_config, discovery_schedule_router,
+    grafana_router, health_router_with_warmup, init_logging, journal_router, load_settings,
+    log_app_bind, log_app_start, log_source_selected, logging_config, metrics_router,
+    model_input_router, quality_router, run_preflight, run_warmup, shutdown_signal,
+    snapshot_history_router, strategy_toggle_router, with_dashboard_token, with_request_ids,
+    AlertConfig, AlertManager, BinanceRestTail, ClockDriftConfig, ClockDriftMonitor,
+    DashboardConfig, DashboardSnapshotSource, DashboardViewConfig, DashboardViews,
+    DiscoveryUniverse, InMemoryMockSnapshotSource, JournalConfig, MarketCurveConfig,
+    MarketToggleConfig, MarketToggles, ModelInputConfig, ModelInputStore, PersistenceBackend,
+    PreflightConfig, QualityConfig, QualityScorecards, Settings, SimulatedDemoSnapshotSource,
+    SlugConfig, SnapshotRecorder, SnapshotRecorderConfig, SnapshotStore, SnapshotStoreConfig,
+    TradeJournal, WarmupConfig, WarmupTracker,
 };
+#[cfg(feature = "trading")]
+use pmm::{cancel_all_on_shutdown, OrderClient, RunMode, ShutdownConfig, TradingConfig};
 #[cfg(feature = "discovery-sdk")]
 use pmm::{LiveDiscoveryConfig, LiveDiscoverySnapshotSource};
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let logging_cfg = logging_config_from_env();
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let settings = load_settings()?;
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?
+        .block_on(serve(settings))
+}
+
+async fn serve(settings: Settings) -> Result<(), Box<dyn std::error::Error>> {
+    let logging_cfg = logging_config(&settings);
     init_logging(&logging_cfg)?;
     log_app_start(&logging_cfg);
+    settings.validate().log();
+    PersistenceBackend::from_settings(&settings).log_sqlite_only_stores();
 
-    let addr: SocketAddr = std::env::var("PMM_DASHBOARD_ADDR")
-        .unwrap_or_else(|_| "127.0.0.1:8080".to_string())
-        .parse()?;
+    if !settings
+        .get::<String>("PMM_PREFLIGHT")
+        .eq_ignore_ascii_case("off")
+    {
+        let report = run_preflight(&PreflightConfig::from_settings(&settings), &settings).await;
+        print!("{}", report.render_table());
+        if report.blocks_start() {
+            return Err("preflight found critical failures in live mode".into());
+        }
+    }
 
-    let source: Arc<dyn DashboardSnapshotSource> = source_from_env();
-    let app = dashboard_router(source);
+    let addr: SocketAddr = settings.get("PMM_DASHBOARD_ADDR");
+
+    let alerts = AlertManager::from_config(&AlertConfig::from_settings(&settings));
+    let toggles = MarketToggles::from_config(&MarketToggleConfig::from_settings(&settings));
+    let model_inputs = ModelInputStore::from_config(&ModelInputConfig::from_settings(&settings));
+    let source: Arc<dyn DashboardSnapshotSource> = source_from_settings(
+        &settings,
+        alerts.clone(),
+        toggles.clone(),
+        model_inputs.clone(),
+    );
+    let clock = ClockDriftMonitor::spawn_with_alerts(
+        ClockDriftConfig::from_settings(&settings),
+        alerts.clone(),
+    );
+    let recorder = SnapshotRecorder::spawn(
+        source.clone(),
+        &SnapshotRecorderConfig::from_settings(&settings),
+    );
+    let history = SnapshotStore::spawn(
+        source.clone(),
+        &SnapshotStoreConfig::from_settings(&settings),
+    );
+    let warmup = spawn_warmup(WarmupConfig::from_settings(&settings));
+    let quality_cfg = QualityConfig::from_settings(&settings);
+    let quality = QualityScorecards::from_config(&quality_cfg);
+    quality.spawn_job(source.clone(), &quality_cfg);
+    let views = DashboardViews::from_config(&DashboardViewConfig::from_settings(&settings));
+    let dashboard = DashboardConfig::from_settings(&settings);
+    let redaction = dashboard.redaction.clone();
+    let mut app = dashboard_router_with_config(source.clone(), dashboard, views)
+        .merge(health_router_with_warmup(Some(clock), warmup))
+        .merge(metrics_router())
+        .merge(strategy_toggle_router(toggles))
+        // No fill feed runs in this process, so notes are served without a ledger.
+        .merge(journal_router(
+            TradeJournal::from_config(&JournalConfig::from_settings(&settings)),
+            None,
+        ))
+        .merge(quality_router(quality))
+        .merge(discovery_schedule_router(
+            DiscoveryUniverse::from_settings(&settings),
+            SlugConfig::default(),
+        ));
+    if let Some(alerts) = alerts {
+        app = app.merge(alerts_router(alerts));
+    }
+    if let Some(history) = history {
+        app = app.merge(snapshot_history_router(history));
+    }
+    if let Some(model_inputs) = model_inputs {
+        app = app.merge(model_input_router(model_inputs));
+    }
+    if let Some(recorder) = recorder {
+        app = app
+            .merge(curve_router(
+                source,
+                recorder.clone(),
+                MarketCurveConfig::from_settings(&settings),
+            ))
+            .merge(grafana_router(recorder));
+    }
+    // The token covers every merged router, not only the dashboard's own routes.
+    let app = with_request_ids(with_dashboard_token(app, redaction));
     let listener = tokio::net::TcpListener::bind(addr).await?;
     let bound_addr = listener.local_addr()?;
 
     log_app_bind(bound_addr);
-    axum::serve(listener, app).await?;
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown_signal())
+        .await?;
 
+    #[cfg(feature = "trading")]
+    cancel_wallet_orders(&settings).await?;
     Ok(())
 }
 
+/// In live mode with a wallet key, cancels the wallet's resting orders before the
+/// process exits, and fails when some are still open after the timeout.
+#[cfg(feature = "trading")]
+async fn cancel_wallet_orders(settings: &Settings) -> Result<(), Box<dyn std::error::Error>> {
+    let live = settings.get::<RunMode>("PMM_MODE") == RunMode::Live;
+    let trading = TradingConfig::from_settings(settings);
+    if !live || trading.private_key.is_none() {
+        return Ok(());
+    }
+    let client = OrderClient::connect(&trading).await?;
+    let report = cancel_all_on_shutdown(&client, &ShutdownConfig::from_settings(settings)).await?;
+    if !report.is_clean() {
+        return Err(format!("{} orders still open at shutdown", report.still_open.len()).into());
+    }
+    Ok(())
+}
+
+/// Starts the cold-start warm-up in the background; `/healthz` reports its progress.
+fn spawn_warmup(cfg: WarmupConfig) -> Option<WarmupTracker> {
+    if !cfg.enabled {
+        return None;
+    }
+    let tracker = WarmupTracker::new();
+    let progress = tracker.clone();
+    tokio::task::spawn_blocking(move || {
+        let mut tail = match BinanceRestTail::new(&cfg) {
+            Ok(tail) => tail,
+            Err(err) => return progress.fail(err),
+        };
+        let now_ms = || chrono::Utc::now().timestamp_millis();
+        // Outcome is logged and published on the tracker.
+        let _ = run_warmup(&cfg, &mut tail, &progress, &now_ms);
+    });
+    Some(tracker)
+}
+
 #[cfg(feature = "discovery-sdk")]
-fn source_from_env() -> Arc<dyn DashboardSnapshotSource> {
-    let force_demo = std::env::var("PMM_DASHBOARD_USE_DEMO")
-        .map(|raw| raw == "1" || raw.eq_ignore_ascii_case("true"))
-        .unwrap_or(false);
-
-    if force_demo {
-        log_source_selected("demo", Some("PMM_DASHBOARD_USE_DEMO"), None);
-        Arc::new(InMemoryMockSnapshotSource::demo())
+fn source_from_settings(
+    settings: &Settings,
+    alerts: Option<AlertManager>,
+    toggles: MarketToggles,
+    model_inputs: Option<ModelInputStore>,
+) -> Arc<dyn DashboardSnapshotSource> {
+    if settings.get("PMM_DASHBOARD_USE_DEMO") {
+        demo_source(settings, "PMM_DASHBOARD_USE_DEMO")
     } else {
-        let cfg = LiveDiscoveryConfig::default();
+        let cfg = LiveDiscoveryConfig {
+            toggles,
+            model_inputs,
+            ..LiveDiscoveryConfig::from_settings(settings)
+        };
         log_source_selected("live_discovery", None, Some(cfg.refresh_interval_ms));
-        Arc::new(LiveDiscoverySnapshotSource::spawn(cfg))
+        Arc::new(LiveDiscoverySnapshotSource::spawn_with_alerts(cfg, alerts))
     }
 }
 
 #[cfg(not(feature = "discovery-sdk"))]
-fn source_from_env() -> Arc<dyn DashboardSnapshotSource> {
-    log_source_selected("demo", Some("discovery_sdk_disabled"), None);
-    Arc::new(InMemoryMockSnapshotSource::demo())
+fn source_from_settings(
+    settings: &Settings,
+    _alerts: Option<AlertManager>,
+    _toggles: MarketToggles,
+    _model_inputs: Option<ModelInputStore>,
+) -> Arc<dyn DashboardSnapshotSource> {
+    demo_source(settings, "discovery_sdk_disabled")
+}
+
+/// Static demo rows, or rows on a compressed clock when `PMM_DASHBOARD_DEMO_SPEED` is set.
+fn demo_source(settings: &Settings, reason: &str) -> Arc<dyn DashboardSnapshotSource> {
+    match SimulatedDemoSnapshotSource::speed(settings) {
+        Some(speed) => {
+            log_source_selected("demo_simulated_clock", Some(reason), None);
+            Arc::new(SimulatedDemoSnapshotSource::starting_now(speed))
+        }
+        None => {
+            log_source_selected("demo", Some(reason), None);
+            Arc::new(InMemoryMockSnapshotSource::demo())
+        }
+    }
 }
@@ -0,0 +1,48 @@
+use pmm::{load_settings, schema_drift_report, GammaPayloadConfig, GammaPayloadStore};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cfg = GammaPayloadConfig::from_settings(&load_settings()?);
+    if !cfg.store_path.exists() {
+        return Err(format!(
+            "no payload store at {} (enable sampling with PMM_GAMMA_PAYLOAD_SAMPLE_EVERY)",
+            cfg.store_path.display()
+        )
+        .into());
+    }
+    let store = GammaPayloadStore::open(&cfg.store_path, cfg.max_samples)?;
+    let samples = store.samples(cfg.max_samples)?;
+    let report = schema_drift_report(&samples);
+
+    println!(
+        "Compared {} sampled Gamma payloads from {}",
+        report.samples,
+        cfg.store_path.display()
+    );
+    if report.unknown_fields.is_empty() {
+        println!("\nRESULT: no fields dropped by the SDK parser.");
+        return Ok(());
+    }
+    for field in &report.unknown_fields {
+        println!(
+            "  {}{} | samples={} example_slug={}",
+            if field.fee_or_reward {
+                "[fee/reward] "
+            } else {
+                ""
+            },
+            field.path,
+            field.samples,
+            field.example_slug
+        );
+    }
+
+    if report.has_fee_or_reward_drift() {
+        Err("fee/reward-relevant Gamma fields are dropped by the SDK parser".into())
+    } else {
+        println!(
+            "\nRESULT: {} unknown fields, none fee/reward-relevant.",
+            report.unknown_fields.len()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+use std::path::PathBuf;
+
+use chrono::Utc;
+use pmm::{load_settings, KlineStore, KlineValidationConfig};
+
+const DAY_MS: i64 = 86_400_000;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let settings = load_settings()?;
+    let data_root: PathBuf = settings.get("PMM_BINANCE_DATA_ROOT");
+    let store_path = settings
+        .explicit("PMM_BINANCE_STORE_PATH")
+        .unwrap_or_else(|| data_root.join("klines_1s.sqlite"));
+    let keep_days: i64 = settings.get_where("PMM_KLINE_COMPACT_AFTER_DAYS", |days| *days > 0);
+
+    if !store_path.exists() {
+        return Err(format!("no kline store at {}", store_path.display()).into());
+    }
+    let size_before = std::fs::metadata(&store_path)?.len();
+    let cutoff_ms = (Utc::now().timestamp_millis() / DAY_MS - keep_days) * DAY_MS;
+
+    println!(
+        "Compacting klines older than {} days (open_time_ms < {}) in {}",
+        keep_days,
+        cutoff_ms,
+        store_path.display()
+    );
+    let mut store = KlineStore::open(&store_path, KlineValidationConfig::default())?;
+    let moved = store.compact_older_than(cutoff_ms)?;
+    drop(store);
+
+    let size_after = std::fs::metadata(&store_path)?.len();
+    println!(
+        "RESULT: moved {} rows to klines_1s_slim; store size {} -> {} bytes",
+        moved, size_before, size_after
+    );
+    Ok(())
+}
@@ -0,0 +1,1024 @@
+use std::collections::HashMap;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Arc;
+
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use pmm::{
+    audit_slug_uniqueness, build_active_discovery_keys, build_display_snapshot,
+    build_feature_schema, build_slug, dashboard_router_with_config, demo_snapshot,
+    feature_drift_report, import_klines, init_logging, load_1s_klines, load_replay_timeline,
+    load_settings, log_app_bind, logging_config, parse_replay_speed, plan_required_archives,
+    play_replay, read_paper_fills, read_report_json, render_dashboard_html, render_env_table,
+    resolve_discovery_batch_with_fetcher, run_preflight, settle_paper_fills, shutdown_signal,
+    time_of_day_attribution, transform_store_range, write_kline_archive, AttributionSample,
+    BacktestReport, BinanceSymbol, Coin, DashboardConfig, DashboardFilters, DashboardViews,
+    DiscoveryConfig, DiscoveryStatus, DiscoveryUniverse, DriftConfig, Duration,
+    FeatureTransformConfig, FeatureTransformRequest, FeatureWindowPreset, GapPolicy,
+    HistoricalKlinesConfig, KlineConflictPolicy, KlineImportError, KlineImportFormat,
+    KlineImportRequest, KlineLoadRequest, KlineStore, KlineStoreError, KlineValidationConfig,
+    PaperTradingConfig, PersistenceBackend, PreflightConfig, ReplayConfig, ReplaySnapshotSource,
+    ReplaySummary, Settings, SlugAuditReport, SlugAuditRequest, SlugConfig, SlugFetchOutcome,
+    ALL_DURATIONS, FEATURE_SCHEMA_VERSION,
+};
+#[cfg(feature = "discovery-sdk")]
+use pmm::{count_gamma_markets_by_slug, cross_check_gamma_sample};
+#[cfg(feature = "demo-data")]
+use pmm::{demo_replay_config, demo_replay_timeline};
+
+const USAGE: &str = "usage: pmm <command>
+
+commands:
+  selftest   run the offline pipeline health check
+  preflight  check config, stores, Gamma/Binance reachability, clock drift, and (with
+             PMM_MODE=live) wallet credentials; exits 1 on any critical failure
+  env        print every PMM_* variable with its effective value and source (env,
+             default, unset, or INVALID when a set value falls back to the default);
+             secrets are redacted; exits 1 on invalid values
+  drift      compare feature distributions between two date ranges
+             pmm drift --baseline <from>..<to> --current <from>..<to> [--store <path>]
+             [--windows 5,15,60 | --preset short|medium|long]
+             (UTC dates YYYY-MM-DD, end exclusive; prints a JSON drift report)
+  slug-audit check that every slug over a date range names exactly one interval
+             pmm slug-audit --range <from>..<to> [--sample 50] [--gamma]
+             (uses PMM_UNIVERSE_*; --gamma looks the sample up on Gamma;
+             exits 1 on unhandled collisions or slugs matching several markets)
+  replay     play recorded dashboard rows, decisions, and paper fills in accelerated time
+             pmm replay --from <ts> --to <ts> [--speed 10x] [--addr 127.0.0.1:8080]
+             [--snapshots <path>] [--decisions <path>] [--fills <path>]
+             (unix seconds or RFC3339; stores default to PMM_SNAPSHOT_STORE_PATH,
+             PMM_DECISION_STORE_PATH, PMM_PAPER_STORE_PATH; with --addr the dashboard
+             stays up after the replay until Ctrl-C)
+  serve      serve the dashboard from the bundled demo dataset (demo-data feature)
+             pmm serve --demo [--addr 127.0.0.1:8080] [--speed 1x]
+             (--addr defaults to PMM_DASHBOARD_ADDR; the ten-minute recording loops
+             until Ctrl-C; the live dashboard runs through dashboard_server)
+  sync       import 1s klines from a CSV or Parquet archive into the kline store
+             pmm sync --import <path> --symbol BTCUSDT [--store <path>]
+             [--format csv|parquet] [--on-conflict keep|overwrite]
+             [--require-continuous] [--dry-run]
+             (store defaults to PMM_BINANCE_STORE_PATH; prints a JSON report with
+             gaps and conflicts; Binance downloads run through binance_store_sync)
+  attribution PnL and fill quality by ET weekday and hour, per duration
+             pmm attribution --from <ts> --to <ts> [--fills <path>] [--store <path>]
+             [--csv]
+             pmm attribution --backtest <report.json> [--csv]
+             (paper fills default to PMM_PAPER_STORE_PATH and settle against the
+             kline store; prints a 7 x 24 JSON matrix per duration, or CSV)";
+
+const FIXTURE_START_TS_MS: i64 = 1_704_067_200_000; // 2024-01-01T00:00:00Z
+const FIXTURE_POINTS: i64 = 3;
+const FIXTURES: [(BinanceSymbol, &str); 4] = [
+    (
+        BinanceSymbol::BtcUsdt,
+        include_str!("../../tests/fixtures/binance/BTCUSDT_1s_sample.csv"),
+    ),
+    (
+        BinanceSymbol::EthUsdt,
+        include_str!("../../tests/fixtures/binance/ETHUSDT_1s_sample.csv"),
+    ),
+    (
+        BinanceSymbol::SolUsdt,
+        include_str!("../../tests/fixtures/binance/SOLUSDT_1s_sample.csv"),
+    ),
+    (
+        BinanceSymbol::XrpUsdt,
+        include_str!("../../tests/fixtures/binance/XRPUSDT_1s_sample.csv"),
+    ),
+];
+
+type CheckResult = Result<String, String>;
+
+fn main() -> ExitCode {
+    let settings = match load_settings() {
+        Ok(settings) => settings,
+        Err(err) => {
+            eprintln!("pmm: {err}");
+            return ExitCode::from(2);
+        }
+    };
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("selftest") => run_selftest(),
+        Some("preflight") => run_preflight_command(&settings),
+        Some("env") => run_env(&settings),
+        Some("drift") => run_drift(&settings, &args[1..]),
+        Some("slug-audit") => run_slug_audit(&settings, &args[1..]),
+        Some("replay") => run_replay(&settings, &args[1..]),
+        Some("serve") => run_serve(&settings, &args[1..]),
+        Some("sync") => run_sync(&settings, &args[1..]),
+        Some("attribution") => run_attribution(&settings, &args[1..]),
+        Some("-h") | Some("--help") | Some("help") => {
+            println!("{USAGE}");
+            ExitCode::SUCCESS
+        }
+        _ => {
+            eprintln!("{USAGE}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn run_env(settings: &Settings) -> ExitCode {
+    let validation = settings.validate();
+    print!("{}", render_env_table(&settings.effective(), &validation));
+    if validation.invalid.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn run_selftest() -> ExitCode {
+    let work_dir = std::env::temp_dir().join(format!(
+        "pmm-selftest-{}-{}",
+        std::process::id(),
+        Utc::now().timestamp_millis()
+    ));
+
+    let checks: [(&str, CheckResult); 5] = [
+        ("slug", check_slugs()),
+        ("discovery", check_discovery()),
+        ("klines", check_kline_load(&work_dir.join("binance"))),
+        ("features", check_feature_transform(&work_dir)),
+        ("dashboard", check_dashboard()),
+    ];
+    let _ = fs::remove_dir_all(&work_dir);
+
+    println!("pmm selftest");
+    let mut failed = 0usize;
+    for (name, result) in &checks {
+        match result {
+            Ok(detail) => println!("  PASS  {name:<10} {detail}"),
+            Err(reason) => {
+                failed += 1;
+                println!("  FAIL  {name:<10} {reason}");
+            }
+        }
+    }
+
+    if failed == 0 {
+        println!("RESULT: {} checks passed", checks.len());
+        ExitCode::SUCCESS
+    } else {
+        println!("RESULT: {failed} of {} checks failed", checks.len());
+        ExitCode::FAILURE
+    }
+}
+
+fn run_preflight_command(settings: &Settings) -> ExitCode {
+    let runtime = match tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(err) => {
+            eprintln!("pmm preflight: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let report = runtime.block_on(run_preflight(
+        &PreflightConfig::from_settings(settings),
+        settings,
+    ));
+    print!("{}", report.render_table());
+    if report.failures() == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn run_drift(settings: &Settings, args: &[String]) -> ExitCode {
+    match drift_report(settings, args) {
+        Ok(json) => {
+            println!("{json}");
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("pmm drift: {err}\n\n{USAGE}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn drift_report(
+    settings: &Settings,
+    args: &[String],
+) -> Result<String, Box<dyn std::error::Error>> {
+    let mut baseline = None;
+    let mut current = None;
+    let mut store_path = None;
+    let mut transform_cfg = FeatureTransformConfig {
+        gap_policy: GapPolicy::ReportAndSkip,
+        ..FeatureTransformConfig::default()
+    };
+
+    let mut iter = args.iter();
+    while let Some(flag) = iter.next() {
+        let value = iter
+            .next()
+            .ok_or_else(|| format!("missing value for {flag}"))?;
+        match flag.as_str() {
+            "--baseline" => baseline = Some(parse_date_range(value)?),
+            "--current" => current = Some(parse_date_range(value)?),
+            "--store" => store_path = Some(PathBuf::from(value)),
+            "--preset" => {
+                let preset = FeatureWindowPreset::parse(value)
+                    .ok_or_else(|| format!("invalid --preset value '{value}'"))?;
+                transform_cfg.windows_seconds = preset.windows_seconds();
+                transform_cfg.preset = Some(preset);
+            }
+            "--windows" => {
+                transform_cfg.preset = None;
+                transform_cfg.windows_seconds = value
+                    .split(',')
+                    .map(|w| w.trim().parse::<u32>())
+                    .collect::<Result<_, _>>()
+                    .map_err(|_| format!("invalid --windows value '{value}'"))?
+            }
+            other => return Err(format!("unknown flag {other}").into()),
+        }
+    }
+    let baseline = baseline.ok_or("--baseline is required")?;
+    let current = current.ok_or("--current is required")?;
+    let store_path = match store_path {
+        Some(path) => path,
+        None => default_kline_store_path(settings, "pmm drift")?,
+    };
+
+    let report = feature_drift_report(
+        &store_path,
+        &baseline,
+        &current,
+        &transform_cfg,
+        &DriftConfig::default(),
+    )?;
+    Ok(serde_json::to_string_pretty(&report)?)
+}
+
+/// `PMM_BINANCE_STORE_PATH`, else `klines_1s.sqlite` under `PMM_BINANCE_DATA_ROOT`.
+/// Fails while `PMM_DATABASE_URL` selects Postgres, where that file is not the store.
+fn default_kline_store_path(
+    settings: &Settings,
+    command: &str,
+) -> Result<PathBuf, KlineStoreError> {
+    PersistenceBackend::from_settings(settings).require_sqlite_klines(command)?;
+    Ok(settings
+        .explicit("PMM_BINANCE_STORE_PATH")
+        .unwrap_or_else(|| {
+            settings
+                .get::<PathBuf>("PMM_BINANCE_DATA_ROOT")
+                .join("klines_1s.sqlite")
+        }))
+}
+
+fn run_attribution(settings: &Settings, args: &[String]) -> ExitCode {
+    match attribution_report(settings, args) {
+        Ok(out) => {
+            println!("{out}");
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("pmm attribution: {err}\n\n{USAGE}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn attribution_report(
+    settings: &Settings,
+    args: &[String],
+) -> Result<String, Box<dyn std::error::Error>> {
+    let mut from = None;
+    let mut to = None;
+    let mut fills_path = PaperTradingConfig::from_settings(settings).store_path;
+    let mut store_path = None;
+    let mut backtest = None;
+    let mut csv = false;
+
+    let mut iter = args.iter();
+    while let Some(flag) = iter.next() {
+        if flag == "--csv" {
+            csv = true;
+            continue;
+        }
+        let value = iter
+            .next()
+            .ok_or_else(|| format!("missing value for {flag}"))?;
+        match flag.as_str() {
+            "--from" => from = Some(parse_replay_ts(value)?),
+            "--to" => to = Some(parse_replay_ts(value)?),
+            "--fills" => fills_path = Some(PathBuf::from(value)),
+            "--store" => store_path = Some(PathBuf::from(value)),
+            "--backtest" => backtest = Some(PathBuf::from(value)),
+            other => return Err(format!("unknown flag {other}").into()),
+        }
+    }
+
+    let (samples, unsettled) = match backtest {
+        Some(path) => {
+            let envelope = read_report_json::<BacktestReport>(&path)?;
+            (AttributionSample::from_backtest(&envelope.report), 0)
+        }
+        None => {
+            let from = from.ok_or("--from is required")?;
+            let to = to.ok_or("--to is required")?;
+            let fills_path = fills_path.ok_or("PMM_PAPER_STORE_PATH is off; pass --fills")?;
+            let fills = read_paper_fills(&fills_path, from * 1_000, to * 1_000 + 999)?;
+            let store_path = match store_path {
+                Some(path) => path,
+                None => default_kline_store_path(settings, "pmm attribution")?,
+            };
+            settle_paper_fills(&KlineStore::open_read_only(&store_path)?, &fills)?
+        }
+    };
+    let report = time_of_day_attribution(&samples, unsettled);
+    if csv {
+        return Ok(report.to_csv().trim_end().to_string());
+    }
+    Ok(serde_json::to_string_pretty(&report)?)
+}
+
+fn run_sync(settings: &Settings, args: &[String]) -> ExitCode {
+    let (store_path, req) = match sync_args(settings, args) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            eprintln!("pmm sync: {err}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    let report = KlineStore::open(&store_path, KlineValidationConfig::from_settings(settings))
+        .map_err(KlineImportError::from)
+        .and_then(|mut store| import_klines(&mut store, &req));
+    match report.map(|report| serde_json::to_string_pretty(&report)) {
+        Ok(Ok(json)) => {
+            println!("{json}");
+            ExitCode::SUCCESS
+        }
+        Ok(Err(err)) => {
+            eprintln!("pmm sync: {err}");
+            ExitCode::FAILURE
+        }
+        Err(err) => {
+            eprintln!("pmm sync: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn sync_args(
+    settings: &Settings,
+    args: &[String],
+) -> Result<(PathBuf, KlineImportRequest), String> {
+    let mut path = None;
+    let mut symbol = None;
+    let mut store_path = None;
+    let mut format = None;
+    let mut on_conflict = KlineConflictPolicy::default();
+    let mut require_continuous = false;
+    let mut dry_run = false;
+
+    let mut iter = args.iter();
+    while let Some(flag) = iter.next() {
+        match flag.as_str() {
+            "--require-continuous" => {
+                require_continuous = true;
+                continue;
+            }
+            "--dry-run" => {
+                dry_run = true;
+                continue;
+            }
+            _ => {}
+        }
+        let value = iter
+            .next()
+            .ok_or_else(|| format!("missing value for {flag}"))?;
+        match flag.as_str() {
+            "--import" => path = Some(PathBuf::from(value)),
+            "--symbol" => {
+                symbol = Some(
+                    BinanceSymbol::parse(value)
+                        .ok_or_else(|| format!("unknown --symbol '{value}'"))?,
+                )
+            }
+            "--store" => store_path = Some(PathBuf::from(value)),
+            "--format" => {
+                format = Some(match value.to_ascii_lowercase().as_str() {
+                    "csv" => KlineImportFormat::Csv,
+                    "parquet" => KlineImportFormat::Parquet,
+                    _ => return Err(format!("invalid --format '{value}'")),
+                })
+            }
+            "--on-conflict" => {
+                on_conflict = KlineConflictPolicy::parse(value)
+                    .ok_or_else(|| format!("invalid --on-conflict '{value}'"))?
+            }
+            other => return Err(format!("unknown flag {other}")),
+        }
+    }
+    let path = path.ok_or("--import is required")?;
+    let symbol = symbol.ok_or("--symbol is required")?;
+    let store_path = match store_path {
+        Some(path) => path,
+        None => default_kline_store_path(settings, "pmm sync").map_err(|err| err.to_string())?,
+    };
+    Ok((
+        store_path,
+        KlineImportRequest {
+            format,
+            on_conflict,
+            require_continuous,
+            dry_run,
+            ..KlineImportRequest::new(path, symbol)
+        },
+    ))
+}
+
+fn run_slug_audit(settings: &Settings, args: &[String]) -> ExitCode {
+    match slug_audit_report(settings, args) {
+        Ok(report) => {
+            match serde_json::to_string_pretty(&report) {
+                Ok(json) => println!("{json}"),
+                Err(err) => {
+                    eprintln!("pmm slug-audit: {err}");
+                    return ExitCode::FAILURE;
+                }
+            }
+            if report.passed() {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            }
+        }
+        Err(err) => {
+            eprintln!("pmm slug-audit: {err}\n\n{USAGE}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn slug_audit_report(
+    settings: &Settings,
+    args: &[String],
+) -> Result<SlugAuditReport, Box<dyn std::error::Error>> {
+    let mut range = None;
+    let mut sample_size = 50usize;
+    let mut gamma = false;
+
+    let mut iter = args.iter();
+    while let Some(flag) = iter.next() {
+        if flag == "--gamma" {
+            gamma = true;
+            continue;
+        }
+        let value = iter
+            .next()
+            .ok_or_else(|| format!("missing value for {flag}"))?;
+        match flag.as_str() {
+            "--range" => range = Some(parse_date_range(value)?),
+            "--sample" => {
+                sample_size = value
+                    .parse()
+                    .map_err(|_| format!("invalid --sample '{value}'"))?
+            }
+            other => return Err(format!("unknown flag {other}").into()),
+        }
+    }
+    let range = range.ok_or("--range is required")?;
+
+    let mut report = audit_slug_uniqueness(&SlugAuditRequest {
+        start_ts_utc: range.start_ts_ms_utc / 1_000,
+        end_ts_utc_exclusive: range.end_ts_ms_utc_exclusive / 1_000,
+        universe: DiscoveryUniverse::from_settings(settings),
+        slug_cfg: SlugConfig::default(),
+        sample_size,
+    })?;
+    if gamma {
+        cross_check_gamma(&mut report)?;
+    }
+    Ok(report)
+}
+
+fn run_replay(settings: &Settings, args: &[String]) -> ExitCode {
+    let (cfg, addr) = match replay_args(settings, args) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            eprintln!("pmm replay: {err}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    if let Err(err) = init_logging(&logging_config(settings)) {
+        eprintln!("pmm replay: {err}");
+        return ExitCode::FAILURE;
+    }
+    let runtime = match tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(err) => {
+            eprintln!("pmm replay: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+    match runtime.block_on(replay(settings, cfg, addr)) {
+        Ok(summary) => match serde_json::to_string_pretty(&summary) {
+            Ok(json) => {
+                println!("{json}");
+                ExitCode::SUCCESS
+            }
+            Err(err) => {
+                eprintln!("pmm replay: {err}");
+                ExitCode::FAILURE
+            }
+        },
+        Err(err) => {
+            eprintln!("pmm replay: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn replay(
+    settings: &Settings,
+    cfg: ReplayConfig,
+    addr: Option<SocketAddr>,
+) -> Result<ReplaySummary, Box<dyn std::error::Error>> {
+    let timeline = load_replay_timeline(&cfg)?;
+    let source = ReplaySnapshotSource::new(cfg.from_ts_utc);
+    let Some(addr) = addr else {
+        return Ok(play_replay(timeline, &source, &cfg).await);
+    };
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    log_app_bind(listener.local_addr()?);
+    let app = dashboard_router_with_config(
+        Arc::new(source.clone()),
+        DashboardConfig::from_settings(settings),
+        DashboardViews::in_memory(),
+    );
+    let server = tokio::spawn(async move {
+        axum::serve(listener, app)
+            .with_graceful_shutdown(shutdown_signal())
+            .await
+    });
+    let summary = play_replay(timeline, &source, &cfg).await;
+    server.await??;
+    Ok(summary)
+}
+
+fn replay_args(
+    settings: &Settings,
+    args: &[String],
+) -> Result<(ReplayConfig, Option<SocketAddr>), Box<dyn std::error::Error>> {
+    let mut from = None;
+    let mut to = None;
+    let mut speed = 10u32;
+    let mut addr = None;
+    let mut stores: [Option<PathBuf>; 3] = [None, None, None];
+
+    let mut iter = args.iter();
+    while let Some(flag) = iter.next() {
+        let value = iter
+            .next()
+            .ok_or_else(|| format!("missing value for {flag}"))?;
+        match flag.as_str() {
+            "--from" => from = Some(parse_replay_ts(value)?),
+            "--to" => to = Some(parse_replay_ts(value)?),
+            "--speed" => {
+                speed = parse_replay_speed(value)
+                    .ok_or_else(|| format!("invalid --speed '{value}', expected e.g. 10x"))?
+            }
+            "--addr" => {
+                addr = Some(
+                    value
+                        .parse::<SocketAddr>()
+                        .map_err(|_| format!("invalid --addr '{value}'"))?,
+                )
+            }
+            "--snapshots" => stores[0] = Some(PathBuf::from(value)),
+            "--decisions" => stores[1] = Some(PathBuf::from(value)),
+            "--fills" => stores[2] = Some(PathBuf::from(value)),
+            other => return Err(format!("unknown flag {other}").into()),
+        }
+    }
+    let from = from.ok_or("--from is required")?;
+    let to = to.ok_or("--to is required")?;
+    let mut cfg = ReplayConfig::from_settings(settings, from, to, speed);
+    let [snapshots, decisions, fills] = stores;
+    cfg.snapshot_store = snapshots.or(cfg.snapshot_store);
+    cfg.decision_store = decisions.or(cfg.decision_store);
+    cfg.paper_store = fills.or(cfg.paper_store);
+    Ok((cfg, addr))
+}
+
+fn run_serve(settings: &Settings, args: &[String]) -> ExitCode {
+    let (addr, speed) = match serve_args(settings, args) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            eprintln!("pmm serve: {err}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    if let Err(err) = init_logging(&logging_config(settings)) {
+        eprintln!("pmm serve: {err}");
+        return ExitCode::FAILURE;
+    }
+    let runtime = match tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(err) => {
+            eprintln!("pmm serve: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+    match runtime.block_on(serve_demo(settings, addr, speed)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("pmm serve: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn serve_args(
+    settings: &Settings,
+    args: &[String],
+) -> Result<(SocketAddr, u32), Box<dyn std::error::Error>> {
+    let mut demo = false;
+    let mut addr = settings.get::<SocketAddr>("PMM_DASHBOARD_ADDR").to_string();
+    let mut speed = 1u32;
+
+    let mut iter = args.iter();
+    while let Some(flag) = iter.next() {
+        if flag == "--demo" {
+            demo = true;
+            continue;
+        }
+        let value = iter
+            .next()
+            .ok_or_else(|| format!("missing value for {flag}"))?;
+        match flag.as_str() {
+            "--addr" => addr = value.clone(),
+            "--speed" => {
+                speed = parse_replay_speed(value)
+                    .ok_or_else(|| format!("invalid --speed '{value}', expected e.g. 10x"))?
+            }
+            other => return Err(format!("unknown flag {other}").into()),
+        }
+    }
+    if !demo {
+        return Err(
+            "only --demo is supported; the live dashboard runs through dashboard_server".into(),
+        );
+    }
+    let addr = addr
+        .parse::<SocketAddr>()
+        .map_err(|_| format!("invalid --addr '{addr}'"))?;
+    Ok((addr, speed))
+}
+
+/// Serves the bundled recording through a replay source, starting it over each time it
+/// ends.
+#[cfg(feature = "demo-data")]
+async fn serve_demo(
+    settings: &Settings,
+    addr: SocketAddr,
+    speed: u32,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let timeline = demo_replay_timeline()?;
+    let cfg = demo_replay_config(speed);
+    let source = ReplaySnapshotSource::new(cfg.from_ts_utc);
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    log_app_bind(listener.local_addr()?);
+    let app = dashboard_router_with_config(
+        Arc::new(source.clone()),
+        DashboardConfig::from_settings(settings),
+        DashboardViews::in_memory(),
+    );
+    let replay = async {
+        loop {
+            play_replay(timeline.clone(), &source, &cfg).await;
+        }
+    };
+    tokio::select! {
+        served = axum::serve(listener, app).with_graceful_shutdown(shutdown_signal()) => served?,
+        _ = replay => {}
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "demo-data"))]
+async fn serve_demo(
+    _settings: &Settings,
+    _addr: SocketAddr,
+    _speed: u32,
+) -> Result<(), Box<dyn std::error::Error>> {
+    Err(
+        "--demo requires the demo-data feature (cargo run --features demo-data -- serve --demo)"
+            .into(),
+    )
+}
+
+/// Unix seconds or an RFC3339 timestamp.
+fn parse_replay_ts(raw: &str) -> Result<i64, String> {
+    let raw = raw.trim();
+    raw.parse::<i64>()
+        .ok()
+        .or_else(|| {
+            DateTime::parse_from_rfc3339(raw)
+                .ok()
+                .map(|ts| ts.timestamp())
+        })
+        .ok_or_else(|| format!("invalid timestamp '{raw}', expected unix seconds or RFC3339"))
+}
+
+#[cfg(feature = "discovery-sdk")]
+fn cross_check_gamma(report: &mut SlugAuditReport) -> Result<(), Box<dyn std::error::Error>> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    let timeout_ms = DiscoveryConfig::default().timeout_ms;
+    let mut counts = runtime.block_on(count_gamma_markets_by_slug(
+        &report.gamma_sample,
+        timeout_ms,
+    ));
+    cross_check_gamma_sample(report, |slug| {
+        counts
+            .remove(slug)
+            .unwrap_or_else(|| Err("not looked up".to_string()))
+    });
+    Ok(())
+}
+
+#[cfg(not(feature = "discovery-sdk"))]
+fn cross_check_gamma(_report: &mut SlugAuditReport) -> Result<(), Box<dyn std::error::Error>> {
+    Err("--gamma requires the discovery-sdk feature".into())
+}
+
+/// `YYYY-MM-DD..YYYY-MM-DD` (UTC, end exclusive) as a feature transform request.
+fn parse_date_range(raw: &str) -> Result<FeatureTransformRequest, String> {
+    let (from, to) = raw
+        .split_once("..")
+        .ok_or_else(|| format!("invalid range '{raw}', expected <from>..<to>"))?;
+    let day_start_ms = |date: &str| {
+        NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+            .map(|d| d.and_time(NaiveTime::MIN).and_utc().timestamp_millis())
+            .map_err(|_| format!("invalid date '{date}' in range '{raw}'"))
+    };
+    let req = FeatureTransformRequest {
+        start_ts_ms_utc: day_start_ms(from)?,
+        end_ts_ms_utc_exclusive: day_start_ms(to)?,
+    };
+    if req.end_ts_ms_utc_exclusive <= req.start_ts_ms_utc {
+        return Err(format!("empty range '{raw}'"));
+    }
+    Ok(req)
+}
+
+fn check_slugs() -> CheckResult {
+    let known = [
+        (
+            Coin::BTC,
+            Duration::M5,
+            1_771_449_000,
+            "btc-updown-5m-1771449000",
+        ),
+        (
+            Coin::ETH,
+            Duration::H1,
+            1_736_971_200,
+            "ethereum-up-or-down-january-15-3pm-et",
+        ),
+        (
+            Coin::SOL,
+            Duration::D1,
+            1_772_040_400,
+            "solana-up-or-down-on-february-26",
+        ),
+    ];
+    for (coin, duration, ts, expected) in known {
+        let slug =
+            build_slug(coin, duration, ts, SlugConfig::default()).map_err(|e| e.to_string())?;
+        if slug != expected {
+            return Err(format!("expected {expected}, got {slug}"));
+        }
+    }
+
+    let keys = build_active_discovery_keys(
+        1_735_689_900,
+        &Coin::all(),
+        &ALL_DURATIONS,
+        SlugConfig::default(),
+    )
+    .map_err(|e| e.to_string())?;
+    let again = build_active_discovery_keys(
+        1_735_689_900,
+        &Coin::all(),
+        &ALL_DURATIONS,
+        SlugConfig::default(),
+    )
+    .map_err(|e| e.to_string())?;
+    if keys != again {
+        return Err("active key schedule is not deterministic".to_string());
+    }
+
+    Ok(format!(
+        "{} known vectors, {} active keys",
+        known.len(),
+        keys.len()
+    ))
+}
+
+fn check_discovery() -> CheckResult {
+    let mut keys = build_active_discovery_keys(
+        1_735_689_900,
+        &Coin::all(),
+        &ALL_DURATIONS,
+        SlugConfig::default(),
+    )
+    .map_err(|e| e.to_string())?;
+    keys.push(keys[0].clone());
+
+    let rows = resolve_discovery_batch_with_fetcher(
+        &keys,
+        &DiscoveryConfig {
+            batch_size: 7,
+            ..DiscoveryConfig::default()
+        },
+        |slugs| {
+            let mut out = HashMap::new();
+            for (idx, slug) in slugs.iter().enumerate() {
+                if idx % 2 == 0 {
+                    out.insert(slug.clone(), SlugFetchOutcome::Found(slug.clone()));
+                }
+            }
+            Ok(out)
+        },
+    )
+    .map_err(|e| e.to_string())?;
+
+    if rows.len() != keys.len() {
+        return Err(format!("expected {} rows, got {}", keys.len(), rows.len()));
+    }
+    if rows.iter().zip(&keys).any(|(row, key)| row.key != *key) {
+        return Err("row order does not match key order".to_string());
+    }
+    let resolved = rows
+        .iter()
+        .filter(|row| matches!(row.status, DiscoveryStatus::Resolved { .. }))
+        .count();
+    if resolved == 0 || resolved == rows.len() {
+        return Err(format!(
+            "expected mixed resolution, got {resolved} resolved"
+        ));
+    }
+
+    Ok(format!(
+        "{} rows ({} resolved, {} unresolved)",
+        rows.len(),
+        resolved,
+        rows.len() - resolved
+    ))
+}
+
+fn check_kline_load(data_root: &Path) -> CheckResult {
+    let cfg = HistoricalKlinesConfig {
+        data_root: data_root.to_path_buf(),
+        verify_checksum: false,
+        ..HistoricalKlinesConfig::default()
+    };
+
+    let mut total = 0usize;
+    for (symbol, csv) in FIXTURES {
+        let req = fixture_request(symbol);
+        for archive in plan_required_archives(&req) {
+            write_kline_archive(&cfg.data_root.join(&archive.relative_path), csv)
+                .map_err(|e| e.to_string())?;
+        }
+
+        let loaded = load_1s_klines(&req, &cfg).map_err(|e| e.to_string())?;
+        if loaded.coverage.missing_points != 0 || loaded.rows.len() as i64 != FIXTURE_POINTS {
+            return Err(format!(
+                "{} coverage mismatch: rows={} missing={}",
+                symbol.as_str(),
+                loaded.rows.len(),
+                loaded.coverage.missing_points
+            ));
+        }
+        total += loaded.rows.len();
+    }
+
+    Ok(format!("{total} rows across {} symbols", FIXTURES.len()))
+}
+
+fn check_feature_transform(work_dir: &Path) -> CheckResult {
+    let data_root = work_dir.join("binance");
+    let store_path = work_dir.join("klines_1s.sqlite");
+    let kline_cfg = HistoricalKlinesConfig {
+        data_root,
+        verify_checksum: false,
+        ..HistoricalKlinesConfig::default()
+    };
+
+    let mut store = KlineStore::open(&store_path, KlineValidationConfig::default())
+        .map_err(|e| e.to_string())?;
+    for (symbol, _) in FIXTURES {
+        let loaded =
+            load_1s_klines(&fixture_request(symbol), &kline_cfg).map_err(|e| e.to_string())?;
+        store
+            .upsert_rows(symbol, loaded.rows)
+            .map_err(|e| e.to_string())?;
+    }
+    drop(store);
+
+    let cfg = FeatureTransformConfig {
+        windows_seconds: vec![1],
+        max_duration_seconds: 86_400,
+        gap_policy: GapPolicy::Strict,
+        schema_version: FEATURE_SCHEMA_VERSION,
+        preset: None,
+        auto_backfill: false,
+    };
+    let req = FeatureTransformRequest {
+        start_ts_ms_utc: FIXTURE_START_TS_MS,
+        end_ts_ms_utc_exclusive: FIXTURE_START_TS_MS + FIXTURE_POINTS * 1_000,
+    };
+    let (schema, rows, report) =
+        transform_store_range(&store_path, &req, &cfg).map_err(|e| e.to_string())?;
+
+    if schema != build_feature_schema(&cfg) {
+        return Err("schema differs from build_feature_schema".to_string());
+    }
+    if rows.is_empty() || report.skipped_points != 0 {
+        return Err(format!(
+            "unexpected transform output: rows={} skipped={}",
+            rows.len(),
+            report.skipped_points
+        ));
+    }
+    if let Some(row) = rows.iter().find(|row| {
+        row.values.len() != schema.columns.len() || row.values.iter().any(|v| !v.is_finite())
+    }) {
+        return Err(format!("malformed feature row at {}", row.ts_ms_utc));
+    }
+
+    Ok(format!(
+        "{} rows x {} columns, fingerprint {}",
+        rows.len(),
+        schema.columns.len(),
+        &schema.fingerprint[..12]
+    ))
+}
+
+fn check_dashboard() -> CheckResult {
+    let snapshot = demo_snapshot();
+    let expected = Coin::all().len() * ALL_DURATIONS.len() * 3;
+    if snapshot.rows.len() != expected {
+        return Err(format!(
+            "demo snapshot has {} rows, expected {expected}",
+            snapshot.rows.len()
+        ));
+    }
+
+    let display = build_display_snapshot(
+        &snapshot,
+        &DashboardFilters::all_selected(),
+        Utc::now().timestamp(),
+    );
+    if display.rows.len() != expected {
+        return Err(format!(
+            "display snapshot has {} rows, expected {expected}",
+            display.rows.len()
+        ));
+    }
+
+    let html = render_dashboard_html(&snapshot);
+    let rendered_rows = html
+        .match_indices("<tr data-row=\"")
+        .filter(|(idx, pat)| html[idx + pat.len()..].starts_with(|c: char| c.is_ascii_digit()))
+        .count();
+    if !html.contains("<table") || rendered_rows != expected {
+        return Err(format!(
+            "rendered {rendered_rows} table rows, expected {expected}"
+        ));
+    }
+
+    Ok(format!(
+        "{rendered_rows} rows rendered ({} bytes)",
+        html.len()
+    ))
+}
+
+fn fixture_request(symbol: BinanceSymbol) -> KlineLoadRequest {
+    KlineLoadRequest {
+        symbol,
+        start_ts_ms_utc: FIXTURE_START_TS_MS,
+        end_ts_ms_utc_exclusive: FIXTURE_START_TS_MS + FIXTURE_POINTS * 1_000,
+    }
+}
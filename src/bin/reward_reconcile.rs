@@ -0,0 +1,90 @@
+use chrono::{Days, Utc};
+use pmm::{
+    fetch_reward_payouts, load_settings, reconcile_rewards, RewardAccrualStore,
+    RewardReconcileConfig,
+};
+
+const DAY_SECS: i64 = 86_400;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cfg = RewardReconcileConfig::from_settings(&load_settings()?);
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?
+        .block_on(reconcile(cfg))
+}
+
+async fn reconcile(cfg: RewardReconcileConfig) -> Result<(), Box<dyn std::error::Error>> {
+    if !cfg.accrual_store_path.exists() {
+        return Err(format!(
+            "no reward accrual store at {}",
+            cfg.accrual_store_path.display()
+        )
+        .into());
+    }
+
+    // Accrual days [today - lookback, today); their payouts land `payout_lag_days` later.
+    let today = Utc::now().date_naive();
+    let first_day = today
+        .checked_sub_days(Days::new(u64::from(cfg.lookback_days)))
+        .ok_or("lookback out of range")?;
+    let accrual_start = first_day
+        .and_hms_opt(0, 0, 0)
+        .expect("midnight")
+        .and_utc()
+        .timestamp();
+    let accrual_end = today
+        .and_hms_opt(0, 0, 0)
+        .expect("midnight")
+        .and_utc()
+        .timestamp();
+    let lag = i64::from(cfg.payout_lag_days) * DAY_SECS;
+
+    let store = RewardAccrualStore::open(&cfg.accrual_store_path)?;
+    let accruals = store.accruals_ending_between(accrual_start, accrual_end)?;
+    let payouts = fetch_reward_payouts(&cfg, accrual_start + lag, accrual_end + lag).await?;
+    let report = reconcile_rewards(&accruals, &payouts, &cfg);
+
+    println!(
+        "Reconciling {} accruals against {} reward payouts for {} to {} (accrual days, UTC)",
+        accruals.len(),
+        payouts.len(),
+        first_day,
+        today.pred_opt().unwrap_or(today)
+    );
+    for day in &report.days {
+        println!(
+            "\n{}{} | estimated={:.4} actual={:.4} diff={:+.4}",
+            if day.flagged { "[!] " } else { "" },
+            day.accrual_day,
+            day.estimated_usdc,
+            day.actual_usdc,
+            day.diff_usdc
+        );
+        for market in report
+            .markets
+            .iter()
+            .filter(|market| market.accrual_day == day.accrual_day)
+            .filter(|market| market.flagged || market.slug.is_none())
+        {
+            println!(
+                "  {}{} | estimated={:.4} actual={:.4} diff={:+.4}",
+                if market.flagged { "[!] " } else { "" },
+                market.slug.as_deref().unwrap_or("(unattributed)"),
+                market.estimated_usdc,
+                market.actual_usdc,
+                market.diff_usdc
+            );
+        }
+    }
+
+    match report.flagged_count() {
+        0 => {
+            println!("\nRESULT: estimates within tolerance of actual payouts.");
+            Ok(())
+        }
+        flagged => {
+            Err(format!("{flagged} day/market reward discrepancies beyond tolerance").into())
+        }
+    }
+}
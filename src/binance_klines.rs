@@ -1,20 +1,35 @@
 //! Step 8 historical Binance 1s kline loading.
 
 use std::fs;
-use std::io::{Cursor, Read, Write};
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 
 use chrono::{Datelike, Duration as ChronoDuration, NaiveDate, TimeZone, Utc};
-use csv::StringRecord;
+use csv::ByteRecord;
+use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use thiserror::Error;
 use tracing::{debug, info, warn};
 use zip::ZipArchive;
 
+use crate::discovery::DiscoveryUniverse;
+use crate::env_vars::Settings;
+use crate::features::FeatureTransformConfig;
+use crate::slug::Coin;
+use crate::warmup::KlineTailSource;
+
 const BINANCE_DATA_BASE_URL: &str = "https://data.binance.vision/data/spot";
+/// Binance spot REST klines endpoint, used to fill the tail archives do not cover yet.
+pub const BINANCE_REST_KLINES_URL: &str = "https://api.binance.com/api/v3/klines";
+/// Rows per REST klines request (the endpoint's maximum).
+const REST_KLINES_LIMIT: usize = 1_000;
 const STEP_MS: i64 = 1_000;
 const MAX_REPORTED_GAP_RANGES: usize = 256;
+/// One 31-day month of 1s rows; larger requests grow the buffer as rows arrive.
+const MAX_PREALLOCATED_ROWS: i64 = 31 * 86_400;
+/// SQLite file under the data root holding [`ChecksumFailureLog`].
+pub const CHECKSUM_FAILURE_LOG_FILE: &str = "checksum_failures.sqlite";
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum BinanceSymbol {
@@ -33,6 +48,100 @@ impl BinanceSymbol {
             Self::XrpUsdt => "XRPUSDT",
         }
     }
+
+    /// Parses a symbol such as `BTCUSDT` (case-insensitive).
+    pub fn parse(raw: &str) -> Option<Self> {
+        let raw = raw.trim();
+        ALL_BINANCE_SYMBOLS
+            .into_iter()
+            .find(|symbol| symbol.as_str().eq_ignore_ascii_case(raw))
+    }
+
+    /// The stored symbol behind `coin`'s [`CoinSpec::binance_symbol`]; `None` for coins
+    /// the kline store does not carry.
+    pub fn for_coin(coin: Coin) -> Option<Self> {
+        let wanted = coin.spec().binance_symbol.as_str();
+        ALL_BINANCE_SYMBOLS
+            .into_iter()
+            .find(|symbol| symbol.as_str() == wanted)
+    }
+}
+
+pub const ALL_BINANCE_SYMBOLS: [BinanceSymbol; 4] = [
+    BinanceSymbol::BtcUsdt,
+    BinanceSymbol::EthUsdt,
+    BinanceSymbol::SolUsdt,
+    BinanceSymbol::XrpUsdt,
+];
+
+/// Kline symbols and history a strategy instance actually needs, derived from its
+/// discovery universe so ingestion and repair scale with the configured scope.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KlineScope {
+    pub symbols: Vec<BinanceSymbol>,
+    /// History needed behind `now`: the longest configured interval plus feature warm-up.
+    pub lookback_ms: i64,
+    /// When set, repair and audit cover only `lookback_ms` behind now instead of the
+    /// full history from the configured start date.
+    pub restrict_range: bool,
+}
+
+impl KlineScope {
+    /// `feature_windows` is `Some` when the cross-asset feature transform runs; it
+    /// reads a full four-symbol frame every second, so all symbols stay in scope.
+    pub fn for_universe(universe: &DiscoveryUniverse, feature_windows: Option<&[u32]>) -> Self {
+        let symbols = match feature_windows {
+            Some(_) => ALL_BINANCE_SYMBOLS.to_vec(),
+            None => universe
+                .coins
+                .iter()
+                .copied()
+                .filter_map(BinanceSymbol::for_coin)
+                .collect(),
+        };
+        let warmup_seconds = feature_windows
+            .and_then(|windows| windows.iter().copied().max())
+            .map(i64::from)
+            .unwrap_or(0);
+
+        Self {
+            symbols,
+            lookback_ms: (universe.max_duration_seconds() + warmup_seconds) * STEP_MS,
+            restrict_range: false,
+        }
+    }
+
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Scope for the universe in `PMM_UNIVERSE_COINS`/`PMM_UNIVERSE_DURATIONS`.
+    /// `PMM_KLINE_SCOPE_FEATURES=false` drops the cross-asset feature inputs and
+    /// `PMM_KLINE_REPAIR_RANGE=scope` restricts repair/audit to the lookback.
+    pub fn from_settings(settings: &Settings) -> Self {
+        let with_features: bool = settings.get("PMM_KLINE_SCOPE_FEATURES");
+        let feature_windows = FeatureTransformConfig::default().windows_seconds;
+        let mut scope = Self::for_universe(
+            &DiscoveryUniverse::from_settings(settings),
+            with_features.then_some(feature_windows.as_slice()),
+        );
+        scope.restrict_range = settings
+            .parsed("PMM_KLINE_REPAIR_RANGE", |raw| {
+                Some(raw.eq_ignore_ascii_case("scope"))
+            })
+            .unwrap_or_default();
+        scope
+    }
+
+    pub fn includes(&self, symbol: BinanceSymbol) -> bool {
+        self.symbols.contains(&symbol)
+    }
+
+    /// Start of the range repair must cover to serve `now_ms`, floored to the second.
+    pub fn repair_start_ms(&self, now_ms: i64) -> i64 {
+        now_ms.div_euclid(STEP_MS) * STEP_MS - self.lookback_ms
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -159,14 +268,37 @@ pub enum KlineLoadError {
     ParseField { field: &'static str, value: String },
     #[error("invalid checksum payload for {url}: {payload}")]
     InvalidChecksumPayload { url: String, payload: String },
-    #[error("checksum mismatch for {path}: expected {expected}, actual {actual}")]
+    #[error(
+        "checksum mismatch for {path} after a re-download: expected {expected}, \
+         got {first_actual} then {retry_actual}"
+    )]
     ChecksumMismatch {
         path: PathBuf,
         expected: String,
-        actual: String,
+        /// Hash of the file quarantined before the re-download.
+        first_actual: String,
+        /// Hash of the re-downloaded file.
+        retry_actual: String,
     },
+    #[error("checksum failure log error: {0}")]
+    FailureLog(#[from] rusqlite::Error),
 }
 
+/// Plans the Binance Vision archives covering `req`: whole months use the monthly
+/// archive, partial months fall back to one daily archive per touched day.
+///
+/// ```
+/// use pmm::{plan_required_archives, ArchiveKind, BinanceSymbol, KlineLoadRequest};
+///
+/// let req = KlineLoadRequest {
+///     symbol: BinanceSymbol::BtcUsdt,
+///     start_ts_ms_utc: 1_704_067_200_000, // 2024-01-01T00:00:00Z
+///     end_ts_ms_utc_exclusive: 1_704_240_000_000, // 2024-01-03T00:00:00Z
+/// };
+/// let archives = plan_required_archives(&req);
+/// assert_eq!(archives.len(), 2);
+/// assert!(archives.iter().all(|a| a.kind == ArchiveKind::Daily));
+/// ```
 pub fn plan_required_archives(req: &KlineLoadRequest) -> Vec<ArchiveRef> {
     let Some((start_dt, end_dt)) = request_bounds(req) else {
         return Vec::new();
@@ -288,6 +420,37 @@ pub fn load_1s_klines(
     })
 }
 
+/// [`KlineTailSource`] over [`load_1s_klines`]: serves any range the Binance Vision
+/// archives cover, caching them under `data_root`. Used by the feature transform's
+/// `auto_backfill`.
+pub struct ArchiveKlineSource {
+    cfg: HistoricalKlinesConfig,
+}
+
+impl ArchiveKlineSource {
+    pub fn new(cfg: HistoricalKlinesConfig) -> Self {
+        Self { cfg }
+    }
+}
+
+impl KlineTailSource for ArchiveKlineSource {
+    fn fetch(
+        &mut self,
+        symbol: BinanceSymbol,
+        start_ts_ms: i64,
+        end_ts_ms_exclusive: i64,
+    ) -> Result<Vec<Kline1s>, String> {
+        let req = KlineLoadRequest {
+            symbol,
+            start_ts_ms_utc: start_ts_ms,
+            end_ts_ms_utc_exclusive: end_ts_ms_exclusive,
+        };
+        load_1s_klines(&req, &self.cfg)
+            .map(|loaded| loaded.rows)
+            .map_err(|err| err.to_string())
+    }
+}
+
 fn sync_archives_with_fetcher(
     archives: &[ArchiveRef],
     cfg: &HistoricalKlinesConfig,
@@ -332,6 +495,7 @@ fn sync_archives_with_fetcher(
                     expected = %expected,
                     actual = %actual
                 );
+                quarantine_mismatch(cfg, archive, &local_path, &expected, &actual)?;
                 expected_checksum = Some(expected);
             } else {
                 info!(
@@ -350,35 +514,50 @@ fn sync_archives_with_fetcher(
             }
         }
 
-        let bytes = fetch_bytes_with_retry(fetcher, &archive.url, cfg)?;
-        write_atomic(&local_path, &bytes)?;
-
-        if cfg.verify_checksum {
-            let expected = match expected_checksum {
-                Some(value) => value,
+        // A mismatched download is quarantined and fetched once more; only a second
+        // mismatch in a row fails the sync.
+        let mut first_actual = None;
+        let bytes = loop {
+            let bytes = fetch_bytes_with_retry(fetcher, &archive.url, cfg)?;
+            write_atomic(&local_path, &bytes)?;
+            if !cfg.verify_checksum {
+                break bytes;
+            }
+            let expected = match &expected_checksum {
+                Some(value) => value.clone(),
                 None => {
                     let checksum_url = format!("{}.CHECKSUM", archive.url);
-                    fetch_checksum_with_retry(fetcher, &checksum_url, cfg)?
+                    let value = fetch_checksum_with_retry(fetcher, &checksum_url, cfg)?;
+                    expected_checksum = Some(value.clone());
+                    value
                 }
             };
             let actual = file_sha256_hex(&local_path)?;
-            if !actual.eq_ignore_ascii_case(&expected) {
-                warn!(
-                    component = "binance_klines",
-                    event = "binance.sync.file.checksum_failed",
-                    symbol = archive.symbol.as_str(),
-                    kind = archive.kind.as_path_segment(),
-                    path = %local_path.display(),
-                    expected = %expected,
-                    actual = %actual
-                );
-                return Err(KlineLoadError::ChecksumMismatch {
-                    path: local_path,
-                    expected,
-                    actual,
-                });
+            if actual.eq_ignore_ascii_case(&expected) {
+                break bytes;
             }
-        }
+            warn!(
+                component = "binance_klines",
+                event = "binance.sync.file.checksum_failed",
+                symbol = archive.symbol.as_str(),
+                kind = archive.kind.as_path_segment(),
+                path = %local_path.display(),
+                expected = %expected,
+                actual = %actual
+            );
+            quarantine_mismatch(cfg, archive, &local_path, &expected, &actual)?;
+            match first_actual.take() {
+                None => first_actual = Some(actual),
+                Some(first_actual) => {
+                    return Err(KlineLoadError::ChecksumMismatch {
+                        path: local_path,
+                        expected,
+                        first_actual,
+                        retry_actual: actual,
+                    })
+                }
+            }
+        };
 
         info!(
             component = "binance_klines",
@@ -413,9 +592,8 @@ fn parse_zip_archive(path: &Path, req: &KlineLoadRequest) -> Result<Vec<Kline1s>
         });
     }
 
-    let mut csv_buf = None;
     for idx in 0..zip.len() {
-        let mut entry = zip.by_index(idx)?;
+        let entry = zip.by_index(idx)?;
         if entry.is_dir() {
             continue;
         }
@@ -423,59 +601,284 @@ fn parse_zip_archive(path: &Path, req: &KlineLoadRequest) -> Result<Vec<Kline1s>
             continue;
         }
 
-        let mut buf = Vec::new();
-        entry.read_to_end(&mut buf)?;
-        csv_buf = Some(buf);
-        break;
+        return parse_kline_csv(entry, req);
     }
-    let csv_buf = csv_buf.ok_or_else(|| KlineLoadError::MissingCsvEntry {
+
+    Err(KlineLoadError::MissingCsvEntry {
         path: path.to_path_buf(),
-    })?;
+    })
+}
 
-    let mut reader = csv::ReaderBuilder::new()
-        .has_headers(false)
-        .from_reader(Cursor::new(csv_buf));
+/// Writes `csv_body` as the single CSV entry of a Binance-layout zip archive at `path`,
+/// creating parent directories. Used for offline fixtures.
+pub fn write_kline_archive(path: &Path, csv_body: &str) -> Result<(), KlineLoadError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut zip = zip::ZipWriter::new(fs::File::create(path)?);
+    zip.start_file("data.csv", zip::write::SimpleFileOptions::default())?;
+    zip.write_all(csv_body.as_bytes())?;
+    zip.finish()?;
+    Ok(())
+}
 
-    let mut rows = Vec::new();
-    for record in reader.records() {
-        let record = record?;
-        let row = parse_kline_record(&record)?;
-        if row.open_time_ms >= req.start_ts_ms_utc && row.open_time_ms < req.end_ts_ms_utc_exclusive
-        {
-            rows.push(row);
+/// Parses one row of a REST `/api/v3/klines` response (an array of 11+ numbers or
+/// numeric strings).
+pub fn parse_rest_kline_row(value: &serde_json::Value) -> Result<Kline1s, String> {
+    let row = value
+        .as_array()
+        .ok_or("unexpected REST row: expected array")?;
+    if row.len() < 11 {
+        return Err(format!("unexpected REST row length: {}", row.len()));
+    }
+
+    Ok(Kline1s {
+        open_time_ms: json_number(&row[0], serde_json::Value::as_i64)?,
+        open: json_number(&row[1], serde_json::Value::as_f64)?,
+        high: json_number(&row[2], serde_json::Value::as_f64)?,
+        low: json_number(&row[3], serde_json::Value::as_f64)?,
+        close: json_number(&row[4], serde_json::Value::as_f64)?,
+        volume: json_number(&row[5], serde_json::Value::as_f64)?,
+        close_time_ms: json_number(&row[6], serde_json::Value::as_i64)?,
+        quote_asset_volume: json_number(&row[7], serde_json::Value::as_f64)?,
+        trade_count: json_number(&row[8], serde_json::Value::as_u64)?,
+        taker_buy_base_volume: json_number(&row[9], serde_json::Value::as_f64)?,
+        taker_buy_quote_volume: json_number(&row[10], serde_json::Value::as_f64)?,
+    })
+}
+
+fn json_number<T: std::str::FromStr>(
+    value: &serde_json::Value,
+    as_number: fn(&serde_json::Value) -> Option<T>,
+) -> Result<T, String> {
+    if let Some(number) = as_number(value) {
+        return Ok(number);
+    }
+    value
+        .as_str()
+        .and_then(|text| text.parse().ok())
+        .ok_or_else(|| format!("expected numeric value, got {value}"))
+}
+
+/// Fetches 1s klines for `symbol` in `[start_ts_ms, end_ts_ms_exclusive)` from the REST
+/// endpoint at `url`, paging until the range is covered or the endpoint has no more rows.
+pub fn fetch_rest_klines(
+    client: &reqwest::blocking::Client,
+    url: &str,
+    symbol: BinanceSymbol,
+    start_ts_ms: i64,
+    end_ts_ms_exclusive: i64,
+) -> Result<Vec<Kline1s>, String> {
+    let mut out = Vec::new();
+    let mut cursor = start_ts_ms;
+    while cursor < end_ts_ms_exclusive {
+        let response = client
+            .get(url)
+            .query(&[
+                ("symbol", symbol.as_str().to_string()),
+                ("interval", "1s".to_string()),
+                ("startTime", cursor.to_string()),
+                ("endTime", (end_ts_ms_exclusive - 1).to_string()),
+                ("limit", REST_KLINES_LIMIT.to_string()),
+            ])
+            .send()
+            .and_then(|response| response.error_for_status())
+            .map_err(|err| format!("binance REST error for {}: {err}", symbol.as_str()))?;
+        let payload: serde_json::Value = response
+            .json()
+            .map_err(|err| format!("binance REST body for {}: {err}", symbol.as_str()))?;
+        let rows = payload
+            .as_array()
+            .ok_or("unexpected REST payload: expected top-level array")?;
+        let Some(last) = rows.last() else {
+            break;
+        };
+        let last_open = parse_rest_kline_row(last)?.open_time_ms;
+        for row in rows {
+            out.push(parse_rest_kline_row(row)?);
+        }
+        if last_open < cursor {
+            return Err(format!(
+                "rest cursor did not advance for {} at {cursor}",
+                symbol.as_str()
+            ));
+        }
+        cursor = last_open + STEP_MS;
+    }
+    out.retain(|row| row.open_time_ms < end_ts_ms_exclusive);
+    Ok(out)
+}
+
+/// Parses headerless Binance 1s kline CSV, keeping rows whose open time falls in `req`.
+///
+/// Binance archives are unquoted, so instead of a general CSV reader one SWAR pass over
+/// a reused chunk buffer finds the comma and newline offsets, and numbers are parsed
+/// straight from the field bytes: no UTF-8 validation, no per-row allocation. Archive
+/// entries are streamed, not buffered. Blank lines, `\r\n` endings, and a leading
+/// UTF-8 BOM are accepted.
+pub fn parse_kline_csv<R: Read>(
+    mut reader: R,
+    req: &KlineLoadRequest,
+) -> Result<Vec<Kline1s>, KlineLoadError> {
+    let expected_points = (req.end_ts_ms_utc_exclusive - req.start_ts_ms_utc) / STEP_MS;
+    let mut rows = Vec::with_capacity(expected_points.clamp(0, MAX_PREALLOCATED_ROWS) as usize);
+    let mut buf = vec![0u8; CSV_CHUNK_BYTES];
+    let mut filled = 0;
+    let mut at_start = true;
+
+    loop {
+        if filled == buf.len() {
+            // A single line longer than the buffer.
+            buf.resize(buf.len() * 2, 0);
+        }
+        let read = match reader.read(&mut buf[filled..]) {
+            Ok(read) => read,
+            Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err.into()),
+        };
+        filled += read;
+        let eof = read == 0;
+        if at_start && (filled >= UTF8_BOM.len() || eof) {
+            if buf[..filled].starts_with(UTF8_BOM) {
+                buf.copy_within(UTF8_BOM.len()..filled, 0);
+                filled -= UTF8_BOM.len();
+            }
+            at_start = false;
+        }
+        if eof && filled > 0 && buf[filled - 1] != b'\n' {
+            // Terminate a last line that has no newline.
+            buf.truncate(filled);
+            buf.push(b'\n');
+            filled += 1;
+        }
+
+        let consumed = split_kline_lines(&buf[..filled], |found, fields| {
+            if found < KLINE_FIELDS {
+                return Err(KlineLoadError::InvalidRecordColumns {
+                    found,
+                    expected: KLINE_FIELDS,
+                });
+            }
+            let row = parse_kline_fields(fields)?;
+            if row.open_time_ms >= req.start_ts_ms_utc
+                && row.open_time_ms < req.end_ts_ms_utc_exclusive
+            {
+                rows.push(row);
+            }
+            Ok(())
+        })?;
+        buf.copy_within(consumed..filled, 0);
+        filled -= consumed;
+        if eof {
+            return Ok(rows);
         }
     }
+}
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Bytes read from an archive entry per refill of the `parse_kline_csv` buffer.
+const CSV_CHUNK_BYTES: usize = 256 * 1024;
+
+/// Kline columns read from a record; the 12th Binance column (`ignore`) is skipped.
+const KLINE_FIELDS: usize = 11;
+
+/// Calls `on_line` with the field count and the first [`KLINE_FIELDS`] fields of every
+/// non-blank `\n`-terminated line in `bytes`, and returns how many bytes those lines
+/// span; a trailing partial line is left for the next chunk.
+///
+/// Delimiters are found eight bytes at a time and visited in order through the set
+/// bits of a mask, so each byte is examined once.
+fn split_kline_lines(
+    bytes: &[u8],
+    mut on_line: impl FnMut(usize, &[&[u8]; KLINE_FIELDS]) -> Result<(), KlineLoadError>,
+) -> Result<usize, KlineLoadError> {
+    let mut fields = [&bytes[..0]; KLINE_FIELDS];
+    let mut found = 0;
+    let mut field_start = 0;
+    let mut consumed = 0;
+    let mut on_delimiter = |pos: usize| -> Result<(), KlineLoadError> {
+        if found < KLINE_FIELDS {
+            fields[found] = &bytes[field_start..pos];
+        }
+        found += 1;
+        field_start = pos + 1;
+        if bytes[pos] == b'\n' {
+            if found <= KLINE_FIELDS {
+                let last = &mut fields[found - 1];
+                *last = last.strip_suffix(b"\r").unwrap_or(last);
+            }
+            if found > 1 || !fields[0].is_empty() {
+                on_line(found, &fields)?;
+            }
+            found = 0;
+            consumed = pos + 1;
+        }
+        Ok(())
+    };
+
+    let mut words = bytes.chunks_exact(8);
+    let mut offset = 0;
+    for word in words.by_ref() {
+        let word = u64::from_le_bytes(word.try_into().expect("eight-byte chunk"));
+        let mut mask = delimiter_mask(word);
+        while mask != 0 {
+            on_delimiter(offset + (mask.trailing_zeros() / 8) as usize)?;
+            mask &= mask - 1;
+        }
+        offset += 8;
+    }
+    for (idx, &byte) in words.remainder().iter().enumerate() {
+        if byte == b',' || byte == b'\n' {
+            on_delimiter(offset + idx)?;
+        }
+    }
+    Ok(consumed)
+}
 
-    Ok(rows)
+/// High bit of each byte of `word` that is `,` or `\n`. Exact: no carry crosses bytes,
+/// so bytes after a match are not misreported.
+fn delimiter_mask(word: u64) -> u64 {
+    const LOW7: u64 = 0x7F7F_7F7F_7F7F_7F7F;
+    let zero_bytes = |x: u64| !(((x & LOW7) + LOW7) | x | LOW7);
+    zero_bytes(word ^ 0x2C2C_2C2C_2C2C_2C2C) | zero_bytes(word ^ 0x0A0A_0A0A_0A0A_0A0A)
 }
 
-fn parse_kline_record(record: &StringRecord) -> Result<Kline1s, KlineLoadError> {
-    if record.len() < 11 {
+pub(crate) fn parse_kline_record(record: &ByteRecord) -> Result<Kline1s, KlineLoadError> {
+    if record.len() < KLINE_FIELDS {
         return Err(KlineLoadError::InvalidRecordColumns {
             found: record.len(),
-            expected: 11,
+            expected: KLINE_FIELDS,
         });
     }
 
-    let open_time_raw = parse_i64(record, 0, "open_time_ms")?;
-    let close_time_raw = parse_i64(record, 6, "close_time_ms")?;
+    let mut fields = [&b""[..]; KLINE_FIELDS];
+    for (field, raw) in fields.iter_mut().zip(record) {
+        *field = raw;
+    }
+    parse_kline_fields(&fields)
+}
+
+fn parse_kline_fields(fields: &[&[u8]; KLINE_FIELDS]) -> Result<Kline1s, KlineLoadError> {
+    let open_time_raw = parse_i64(fields[0], "open_time_ms")?;
+    let close_time_raw = parse_i64(fields[6], "close_time_ms")?;
 
     Ok(Kline1s {
         open_time_ms: normalize_to_millis(open_time_raw),
-        open: parse_f64(record, 1, "open")?,
-        high: parse_f64(record, 2, "high")?,
-        low: parse_f64(record, 3, "low")?,
-        close: parse_f64(record, 4, "close")?,
-        volume: parse_f64(record, 5, "volume")?,
+        open: parse_f64(fields[1], "open")?,
+        high: parse_f64(fields[2], "high")?,
+        low: parse_f64(fields[3], "low")?,
+        close: parse_f64(fields[4], "close")?,
+        volume: parse_f64(fields[5], "volume")?,
         close_time_ms: normalize_to_millis(close_time_raw),
-        quote_asset_volume: parse_f64(record, 7, "quote_asset_volume")?,
-        trade_count: parse_u64(record, 8, "trade_count")?,
-        taker_buy_base_volume: parse_f64(record, 9, "taker_buy_base_volume")?,
-        taker_buy_quote_volume: parse_f64(record, 10, "taker_buy_quote_volume")?,
+        quote_asset_volume: parse_f64(fields[7], "quote_asset_volume")?,
+        trade_count: parse_u64(fields[8], "trade_count")?,
+        taker_buy_base_volume: parse_f64(fields[9], "taker_buy_base_volume")?,
+        taker_buy_quote_volume: parse_f64(fields[10], "taker_buy_quote_volume")?,
     })
 }
 
-fn normalize_to_millis(ts: i64) -> i64 {
+pub(crate) fn normalize_to_millis(ts: i64) -> i64 {
     // Binance 1s historical archives can be emitted in microseconds.
     if ts.abs() >= 1_000_000_000_000_000_000 {
         ts / 1_000_000
@@ -486,43 +889,120 @@ fn normalize_to_millis(ts: i64) -> i64 {
     }
 }
 
-fn parse_i64(
-    record: &StringRecord,
-    idx: usize,
-    field: &'static str,
-) -> Result<i64, KlineLoadError> {
-    let raw = record.get(idx).unwrap_or_default();
-    raw.parse::<i64>().map_err(|_| KlineLoadError::ParseField {
-        field,
-        value: raw.to_string(),
-    })
+fn parse_i64(raw: &[u8], field: &'static str) -> Result<i64, KlineLoadError> {
+    let parsed = match raw.split_first() {
+        Some((b'-', digits)) => parse_digits(digits).and_then(|v| 0i64.checked_sub_unsigned(v)),
+        Some((b'+', digits)) => parse_digits(digits).and_then(|v| i64::try_from(v).ok()),
+        _ => parse_digits(raw).and_then(|v| i64::try_from(v).ok()),
+    };
+    parsed.ok_or_else(|| parse_field_error(raw, field))
 }
 
-fn parse_u64(
-    record: &StringRecord,
-    idx: usize,
-    field: &'static str,
-) -> Result<u64, KlineLoadError> {
-    let raw = record.get(idx).unwrap_or_default();
-    raw.parse::<u64>().map_err(|_| KlineLoadError::ParseField {
-        field,
-        value: raw.to_string(),
+fn parse_u64(raw: &[u8], field: &'static str) -> Result<u64, KlineLoadError> {
+    parse_digits(raw.strip_prefix(b"+").unwrap_or(raw)).ok_or_else(|| parse_field_error(raw, field))
+}
+
+fn parse_f64(raw: &[u8], field: &'static str) -> Result<f64, KlineLoadError> {
+    parse_plain_decimal(raw)
+        .or_else(|| {
+            std::str::from_utf8(raw)
+                .ok()
+                .and_then(|text| text.parse::<f64>().ok())
+        })
+        .ok_or_else(|| parse_field_error(raw, field))
+}
+
+/// Exact fast path for the `digits[.digits]` numbers Binance archives contain.
+///
+/// When the digits fit in 2^53 and there are at most 19 of them, both the mantissa and
+/// the power of ten are exact `f64`s, so one division yields the correctly rounded value
+/// `str::parse` would return. Anything else (signs, exponents, long mantissas) returns
+/// `None` and goes through `str::parse`.
+fn parse_plain_decimal(raw: &[u8]) -> Option<f64> {
+    const MAX_EXACT_MANTISSA: u64 = 1 << 53;
+    const POW10: [f64; 20] = [
+        1e0, 1e1, 1e2, 1e3, 1e4, 1e5, 1e6, 1e7, 1e8, 1e9, 1e10, 1e11, 1e12, 1e13, 1e14, 1e15, 1e16,
+        1e17, 1e18, 1e19,
+    ];
+
+    let (int_part, frac_part) = match raw.iter().position(|&byte| byte == b'.') {
+        Some(dot) => (&raw[..dot], &raw[dot + 1..]),
+        None => (raw, &raw[..0]),
+    };
+    if int_part.is_empty() || int_part.len() + frac_part.len() > MAX_SAFE_DIGITS {
+        return None;
+    }
+
+    let mantissa = accumulate_digits(accumulate_digits(0, int_part)?, frac_part)?;
+    if mantissa > MAX_EXACT_MANTISSA {
+        return None;
+    }
+    Some(mantissa as f64 / POW10[frac_part.len()])
+}
+
+/// ASCII decimal digits without sign; `None` on empty input, other bytes, or overflow.
+fn parse_digits(digits: &[u8]) -> Option<u64> {
+    if digits.is_empty() {
+        return None;
+    }
+    if digits.len() <= MAX_SAFE_DIGITS {
+        return accumulate_digits(0, digits);
+    }
+    digits.iter().try_fold(0u64, |acc, &byte| {
+        let digit = byte.wrapping_sub(b'0');
+        if digit > 9 {
+            return None;
+        }
+        acc.checked_mul(10)?.checked_add(u64::from(digit))
     })
 }
 
-fn parse_f64(
-    record: &StringRecord,
-    idx: usize,
-    field: &'static str,
-) -> Result<f64, KlineLoadError> {
-    let raw = record.get(idx).unwrap_or_default();
-    raw.parse::<f64>().map_err(|_| KlineLoadError::ParseField {
+/// Any run of this many decimal digits fits in a `u64`.
+const MAX_SAFE_DIGITS: usize = 19;
+
+/// Appends `digits` to `acc` (`acc * 10^len + digits`), eight bytes at a time where
+/// possible. Callers keep the combined digit count within `MAX_SAFE_DIGITS`.
+fn accumulate_digits(mut acc: u64, mut digits: &[u8]) -> Option<u64> {
+    while let Some((chunk, rest)) = digits.split_first_chunk::<8>() {
+        acc = acc * 100_000_000 + parse_eight_digits(u64::from_le_bytes(*chunk))?;
+        digits = rest;
+    }
+    for &byte in digits {
+        let digit = byte.wrapping_sub(b'0');
+        if digit > 9 {
+            return None;
+        }
+        acc = acc * 10 + u64::from(digit);
+    }
+    Some(acc)
+}
+
+/// SWAR conversion of eight ASCII digits (loaded little-endian, so the first byte is the
+/// most significant digit); `None` if any byte is not `0-9`.
+fn parse_eight_digits(chunk: u64) -> Option<u64> {
+    const HIGH_NIBBLES: u64 = 0xF0F0_F0F0_F0F0_F0F0;
+    let all_digits = ((chunk & HIGH_NIBBLES)
+        | ((chunk.wrapping_add(0x0606_0606_0606_0606) & HIGH_NIBBLES) >> 4))
+        == 0x3333_3333_3333_3333;
+    if !all_digits {
+        return None;
+    }
+
+    let mut value = chunk - 0x3030_3030_3030_3030;
+    value = (value.wrapping_mul(10)).wrapping_add(value >> 8) & 0x00FF_00FF_00FF_00FF;
+    value = (value.wrapping_mul(100)).wrapping_add(value >> 16) & 0x0000_FFFF_0000_FFFF;
+    value = (value.wrapping_mul(10_000)).wrapping_add(value >> 32) & 0x0000_0000_FFFF_FFFF;
+    Some(value)
+}
+
+fn parse_field_error(raw: &[u8], field: &'static str) -> KlineLoadError {
+    KlineLoadError::ParseField {
         field,
-        value: raw.to_string(),
-    })
+        value: String::from_utf8_lossy(raw).into_owned(),
+    }
 }
 
-fn compute_coverage(
+pub(crate) fn compute_coverage(
     req: &KlineLoadRequest,
     rows: &[Kline1s],
     duplicate_points_removed: u64,
@@ -675,6 +1155,160 @@ fn write_atomic(path: &Path, bytes: &[u8]) -> Result<(), KlineLoadError> {
     Ok(())
 }
 
+/// Moves a file that failed its checksum aside as `<name>.quarantined-<UTC timestamp>`
+/// and records the failure in the data root's [`ChecksumFailureLog`].
+fn quarantine_mismatch(
+    cfg: &HistoricalKlinesConfig,
+    archive: &ArchiveRef,
+    local_path: &Path,
+    expected: &str,
+    actual: &str,
+) -> Result<(), KlineLoadError> {
+    let now = crate::faults::now_utc();
+    let file_name = local_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let stamp = now.format("%Y%m%dT%H%M%S%3fZ");
+    let mut quarantined_path =
+        local_path.with_file_name(format!("{file_name}.quarantined-{stamp}"));
+    // Back-to-back failures can share a millisecond; never overwrite an earlier one.
+    let mut suffix = 1;
+    while quarantined_path.exists() {
+        quarantined_path =
+            local_path.with_file_name(format!("{file_name}.quarantined-{stamp}-{suffix}"));
+        suffix += 1;
+    }
+    fs::rename(local_path, &quarantined_path)?;
+
+    let mut log = ChecksumFailureLog::open(&cfg.data_root.join(CHECKSUM_FAILURE_LOG_FILE))?;
+    let occurrences = log.record(&ChecksumFailure {
+        archive_path: archive.relative_path.to_string_lossy().to_string(),
+        url: archive.url.clone(),
+        expected: expected.to_string(),
+        actual: actual.to_string(),
+        quarantined_path: quarantined_path.clone(),
+        seen_at_ms: now.timestamp_millis(),
+    })?;
+    warn!(
+        component = "binance_klines",
+        event = "binance.sync.file.quarantined",
+        symbol = archive.symbol.as_str(),
+        kind = archive.kind.as_path_segment(),
+        path = %quarantined_path.display(),
+        occurrences = occurrences
+    );
+    Ok(())
+}
+
+/// One archive file whose SHA-256 did not match its `.CHECKSUM`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChecksumFailure {
+    /// Archive path relative to the data root.
+    pub archive_path: String,
+    pub url: String,
+    pub expected: String,
+    pub actual: String,
+    /// Where the bad file was moved; the latest one for repeated failures.
+    pub quarantined_path: PathBuf,
+    pub seen_at_ms: i64,
+}
+
+/// A logged failure with how often the same `(archive, expected, actual)` was seen.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChecksumFailureRecord {
+    pub failure: ChecksumFailure,
+    pub first_seen_ms: i64,
+    pub occurrences: u64,
+}
+
+/// Persistent log of checksum failures (`checksum_failures`, one row per archive,
+/// expected hash, and actual hash), so a mirror serving the same bad bytes again
+/// bumps a counter instead of adding rows.
+pub struct ChecksumFailureLog {
+    conn: Connection,
+}
+
+impl ChecksumFailureLog {
+    pub fn open(path: &Path) -> Result<Self, KlineLoadError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS checksum_failures (
+                archive_path TEXT NOT NULL,
+                expected TEXT NOT NULL,
+                actual TEXT NOT NULL,
+                url TEXT NOT NULL,
+                quarantined_path TEXT NOT NULL,
+                first_seen_ms INTEGER NOT NULL,
+                last_seen_ms INTEGER NOT NULL,
+                occurrences INTEGER NOT NULL,
+                PRIMARY KEY(archive_path, expected, actual)
+            ) WITHOUT ROWID;
+            ",
+        )?;
+        Ok(Self { conn })
+    }
+
+    /// Records `failure`, returning how often it has been seen including this time.
+    pub fn record(&mut self, failure: &ChecksumFailure) -> Result<u64, KlineLoadError> {
+        let occurrences: i64 = self.conn.query_row(
+            "
+            INSERT INTO checksum_failures (
+                archive_path, expected, actual, url, quarantined_path,
+                first_seen_ms, last_seen_ms, occurrences
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6, 1)
+            ON CONFLICT(archive_path, expected, actual) DO UPDATE SET
+                url = excluded.url,
+                quarantined_path = excluded.quarantined_path,
+                last_seen_ms = excluded.last_seen_ms,
+                occurrences = occurrences + 1
+            RETURNING occurrences
+            ",
+            params![
+                failure.archive_path,
+                failure.expected,
+                failure.actual,
+                failure.url,
+                failure.quarantined_path.to_string_lossy(),
+                failure.seen_at_ms,
+            ],
+            |row| row.get(0),
+        )?;
+        Ok(occurrences as u64)
+    }
+
+    /// Every logged failure, most recently seen first.
+    pub fn failures(&self) -> Result<Vec<ChecksumFailureRecord>, KlineLoadError> {
+        let mut stmt = self.conn.prepare(
+            "
+            SELECT archive_path, url, expected, actual, quarantined_path,
+                   last_seen_ms, first_seen_ms, occurrences
+            FROM checksum_failures
+            ORDER BY last_seen_ms DESC, archive_path ASC
+            ",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(ChecksumFailureRecord {
+                failure: ChecksumFailure {
+                    archive_path: row.get(0)?,
+                    url: row.get(1)?,
+                    expected: row.get(2)?,
+                    actual: row.get(3)?,
+                    quarantined_path: PathBuf::from(row.get::<_, String>(4)?),
+                    seen_at_ms: row.get(5)?,
+                },
+                first_seen_ms: row.get(6)?,
+                occurrences: row.get::<_, i64>(7)? as u64,
+            })
+        })?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+}
+
 fn file_sha256_hex(path: &Path) -> Result<String, KlineLoadError> {
     let bytes = fs::read(path)?;
     let mut hasher = Sha256::new();
@@ -789,6 +1423,7 @@ fn retry<T>(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::slug::Duration;
     use std::collections::HashMap;
     use tempfile::tempdir;
     use zip::write::SimpleFileOptions;
@@ -859,7 +1494,7 @@ mod tests {
 
     #[test]
     fn parse_kline_record_enforces_schema_and_numbers() {
-        let record = StringRecord::from(vec![
+        let record = ByteRecord::from(vec![
             "1704067200000",
             "100",
             "101",
@@ -878,7 +1513,7 @@ mod tests {
         assert_eq!(parsed.open_time_ms, 1_704_067_200_000);
         assert_eq!(parsed.trade_count, 42);
 
-        let bad = StringRecord::from(vec!["oops"]);
+        let bad = ByteRecord::from(vec!["oops"]);
         assert!(matches!(
             parse_kline_record(&bad).unwrap_err(),
             KlineLoadError::InvalidRecordColumns { .. }
@@ -887,7 +1522,7 @@ mod tests {
 
     #[test]
     fn parse_kline_record_normalizes_microsecond_timestamps_to_millis() {
-        let record = StringRecord::from(vec![
+        let record = ByteRecord::from(vec![
             "1735689600000000",
             "100",
             "101",
@@ -907,6 +1542,121 @@ mod tests {
         assert_eq!(parsed.close_time_ms, 1_735_689_600_999);
     }
 
+    #[test]
+    fn byte_field_parsing_matches_str_parse_semantics() {
+        assert_eq!(parse_i64(b"-42", "a").unwrap(), -42);
+        assert_eq!(parse_i64(b"+7", "b").unwrap(), 7);
+        assert_eq!(parse_u64(b"+7", "b").unwrap(), 7);
+        assert!(parse_u64(b"-42", "a").is_err());
+        assert!(parse_u64(b"18446744073709551616", "c").is_err());
+        assert_eq!(parse_f64(b"1e-3", "d").unwrap(), 0.001);
+        assert!(parse_i64(b"", "f").is_err());
+        match parse_f64(b"1.5x", "e").unwrap_err() {
+            KlineLoadError::ParseField { field, value } => {
+                assert_eq!(field, "e");
+                assert_eq!(value, "1.5x");
+            }
+            other => panic!("unexpected error {other:?}"),
+        }
+    }
+
+    #[test]
+    fn fast_decimal_paths_agree_with_std_parse() {
+        let samples = [
+            "0",
+            "7",
+            "12345678",
+            "123456789012345678",
+            "9999999999999999999",
+            "12345678901234567890",
+            "0.00100000",
+            "42000.37000000",
+            "0.12345678901234567",
+            "9007199254740993",
+            "1.0000000000000000000001",
+            "1234a678",
+            "12345678.1234567/",
+        ];
+        for sample in samples {
+            let raw = sample.as_bytes();
+            assert_eq!(
+                parse_f64(raw, "f").ok(),
+                sample.parse::<f64>().ok(),
+                "{sample}"
+            );
+            assert_eq!(
+                parse_u64(raw, "u").ok(),
+                sample.parse::<u64>().ok(),
+                "{sample}"
+            );
+            assert_eq!(
+                parse_i64(raw, "i").ok(),
+                sample.parse::<i64>().ok(),
+                "{sample}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_kline_csv_keeps_only_rows_inside_request() {
+        let req = KlineLoadRequest {
+            symbol: BinanceSymbol::BtcUsdt,
+            start_ts_ms_utc: 1_704_067_201_000,
+            end_ts_ms_utc_exclusive: 1_704_067_202_000,
+        };
+        let csv = "1704067200000,1,1,1,1,1,1704067200999,1,1,1,1,0\n\
+                   1704067201000,2,2,2,2,2,1704067201999,2,3,2,2,0\n\
+                   1704067202000,3,3,3,3,3,1704067202999,3,3,3,3,0\n";
+
+        let rows = parse_kline_csv(csv.as_bytes(), &req).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].open_time_ms, 1_704_067_201_000);
+        assert_eq!(rows[0].trade_count, 3);
+    }
+
+    /// Hands out a few bytes per `read`, so lines straddle buffer refills.
+    struct TrickleReader<'a>(&'a [u8]);
+
+    impl Read for TrickleReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = buf.len().min(self.0.len()).min(7);
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn parse_kline_csv_splits_lines_across_reads_and_line_endings() {
+        let req = sample_req();
+        let csv = "\u{feff}1704067200000,1,1,1,1,1,1704067200999,1,1,1,1,0\r\n\
+                   \n\
+                   1704067201000,2,2,2,2,2,1704067201999,2,3,2,2\n\
+                   1704067202000,3,3,3,3,3,1704067202999,3,4,3,3,0,extra";
+
+        let rows = parse_kline_csv(TrickleReader(csv.as_bytes()), &req).unwrap();
+        assert_eq!(rows, parse_kline_csv(csv.as_bytes(), &req).unwrap());
+        assert_eq!(
+            rows.iter().map(|row| row.trade_count).collect::<Vec<_>>(),
+            vec![1, 3, 4]
+        );
+
+        // No false matches next to a match (`-` and `\x0b` sit one above `,` and `\n`).
+        assert_eq!(
+            delimiter_mask(u64::from_le_bytes(*b",-\n\x0b-a,\r")),
+            0x0080_0000_0080_0080
+        );
+
+        let short = "1704067200000,1,1,1,1,1,1704067200999,1,1,1\n";
+        assert!(matches!(
+            parse_kline_csv(short.as_bytes(), &req).unwrap_err(),
+            KlineLoadError::InvalidRecordColumns {
+                found: 10,
+                expected: 11
+            }
+        ));
+    }
+
     #[test]
     fn gap_detection_and_duplicates_are_reported() {
         let req = sample_req();
@@ -975,7 +1725,88 @@ mod tests {
             .with(&archive.url, &zip_bytes);
 
         let err = sync_archives_with_fetcher(&archives, &cfg, &fetcher).unwrap_err();
-        assert!(matches!(err, KlineLoadError::ChecksumMismatch { .. }));
+        let zip_hash = hex::encode(Sha256::digest(&zip_bytes));
+        match err {
+            KlineLoadError::ChecksumMismatch {
+                first_actual,
+                retry_actual,
+                ..
+            } => assert_eq!((first_actual, retry_actual), (zip_hash.clone(), zip_hash)),
+            other => panic!("unexpected error {other:?}"),
+        }
+
+        // The cached file and both downloads were moved aside and logged as one failure.
+        assert!(!zip_path.exists());
+        let quarantined = fs::read_dir(zip_path.parent().unwrap())
+            .unwrap()
+            .filter(|entry| {
+                entry
+                    .as_ref()
+                    .unwrap()
+                    .file_name()
+                    .to_string_lossy()
+                    .contains(".zip.quarantined-")
+            })
+            .count();
+        assert_eq!(quarantined, 3);
+        let log = ChecksumFailureLog::open(&temp.path().join(CHECKSUM_FAILURE_LOG_FILE)).unwrap();
+        let failures = log.failures().unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].occurrences, 3);
+        assert_eq!(failures[0].failure.url, archive.url);
+        assert!(failures[0].failure.quarantined_path.exists());
+    }
+
+    #[test]
+    fn mismatched_download_is_quarantined_and_retried_once() {
+        struct FlakyFetcher {
+            checksum_url: String,
+            checksum: Vec<u8>,
+            bodies: std::cell::RefCell<Vec<Vec<u8>>>,
+        }
+
+        impl HttpFetcher for FlakyFetcher {
+            fn get_bytes(&self, url: &str) -> Result<Vec<u8>, KlineLoadError> {
+                if url == self.checksum_url {
+                    return Ok(self.checksum.clone());
+                }
+                Ok(self.bodies.borrow_mut().remove(0))
+            }
+        }
+
+        let req = sample_req();
+        let temp = tempdir().unwrap();
+        let cfg = HistoricalKlinesConfig {
+            data_root: temp.path().to_path_buf(),
+            verify_checksum: true,
+            ..HistoricalKlinesConfig::default()
+        };
+        let archives = plan_required_archives(&req);
+        let archive = &archives[0];
+        let good_path = temp.path().join("good.zip");
+        write_zip(&good_path, sample_csv());
+        let good = fs::read(&good_path).unwrap();
+        let fetcher = FlakyFetcher {
+            checksum_url: format!("{}.CHECKSUM", archive.url),
+            checksum: format!("{}  x.zip\n", hex::encode(Sha256::digest(&good))).into_bytes(),
+            bodies: std::cell::RefCell::new(vec![b"truncated".to_vec(), good.clone()]),
+        };
+
+        let local = sync_archives_with_fetcher(&archives, &cfg, &fetcher).unwrap();
+        assert_eq!(local[0].source, LocalArchiveSource::Downloaded);
+        assert_eq!(fs::read(&local[0].local_path).unwrap(), good);
+
+        let log = ChecksumFailureLog::open(&temp.path().join(CHECKSUM_FAILURE_LOG_FILE)).unwrap();
+        let failures = log.failures().unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(
+            failures[0].failure.actual,
+            hex::encode(Sha256::digest(b"truncated"))
+        );
+        assert_eq!(
+            fs::read(&failures[0].failure.quarantined_path).unwrap(),
+            b"truncated"
+        );
     }
 
     #[test]
@@ -998,4 +1829,26 @@ mod tests {
         assert_eq!(local.len(), 1);
         assert_eq!(local[0].source, LocalArchiveSource::Cached);
     }
+
+    #[test]
+    fn kline_scope_follows_universe_and_feature_inputs() {
+        let universe = DiscoveryUniverse {
+            coins: vec![Coin::SOL],
+            durations: vec![Duration::M5, Duration::M15],
+            pairs: Vec::new(),
+        };
+
+        let bare = KlineScope::for_universe(&universe, None);
+        assert_eq!(bare.symbols, vec![BinanceSymbol::SolUsdt]);
+        assert_eq!(bare.lookback_ms, 900_000);
+        assert!(!bare.includes(BinanceSymbol::BtcUsdt));
+
+        let with_features = KlineScope::for_universe(&universe, Some(&[5, 60]));
+        assert_eq!(with_features.symbols, ALL_BINANCE_SYMBOLS.to_vec());
+        assert_eq!(with_features.lookback_ms, 960_000);
+        assert_eq!(
+            with_features.repair_start_ms(1_704_067_200_999),
+            1_704_067_200_000 - 960_000
+        );
+    }
 }
@@ -0,0 +1,487 @@
+//! Live spot prices over the Binance websocket.
+//!
+//! [`BinanceWsStream`] subscribes to one combined stream (`@bookTicker` mids or
+//! `@trade` prices) for the Binance symbol of every tracked coin and keeps
+//! [`LivePrices`]: the latest price per symbol plus the first price seen in each second,
+//! so an interval's reference price can be read back once the interval has started.
+//! Consumers read prices through the [`PriceSource`] trait, so the dashboard's `price`
+//! and `ref_price` columns can come from any source.
+//!
+//! Latest prices are dropped when the connection is lost, so a silent stream never shows
+//! a stale price as a live one; captured references are kept.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, info, warn};
+
+use crate::env_vars::{EnvValue, Settings};
+use crate::slug::Coin;
+
+pub const DEFAULT_BINANCE_WS_URL: &str = "wss://stream.binance.com:9443";
+
+/// Which Binance stream prices come from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BinanceStreamKind {
+    /// Mid of the best bid and ask, stamped with the local receive time.
+    #[default]
+    BookTicker,
+    /// Last trade price, stamped with the trade time.
+    Trade,
+}
+
+impl BinanceStreamKind {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "book_ticker" => Some(Self::BookTicker),
+            "trade" => Some(Self::Trade),
+            _ => None,
+        }
+    }
+
+    fn suffix(self) -> &'static str {
+        match self {
+            Self::BookTicker => "bookTicker",
+            Self::Trade => "trade",
+        }
+    }
+}
+
+impl EnvValue for BinanceStreamKind {
+    fn parse_env(raw: &str) -> Option<Self> {
+        Self::parse(raw)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BinanceWsConfig {
+    /// Websocket base URL; `None` disables streaming.
+    pub ws_url: Option<String>,
+    pub stream: BinanceStreamKind,
+    /// Cap for the exponential reconnect delay.
+    pub max_backoff_ms: u64,
+    /// How long per-second prices are kept for reference lookups; covers the longest
+    /// interval a row can be in.
+    pub history_secs: i64,
+    /// Latest capture of a reference after the interval start that still counts.
+    pub max_ref_delay_ms: i64,
+}
+
+impl Default for BinanceWsConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl BinanceWsConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_BINANCE_WS_URL` (`off` disables), `PMM_BINANCE_WS_STREAM`
+    /// (`book_ticker|trade`), `PMM_BINANCE_WS_MAX_BACKOFF_MS`,
+    /// `PMM_BINANCE_WS_HISTORY_SECS`, and `PMM_BINANCE_WS_MAX_REF_DELAY_MS`.
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            ws_url: settings.get("PMM_BINANCE_WS_URL"),
+            stream: settings.get("PMM_BINANCE_WS_STREAM"),
+            max_backoff_ms: settings.get("PMM_BINANCE_WS_MAX_BACKOFF_MS"),
+            history_secs: settings.get_where("PMM_BINANCE_WS_HISTORY_SECS", |secs| *secs > 0),
+            max_ref_delay_ms: settings.get_where("PMM_BINANCE_WS_MAX_REF_DELAY_MS", |ms| *ms >= 0),
+        }
+    }
+
+    /// Combined stream URL for `coins`, e.g.
+    /// `{base}/stream?streams=btcusdt@bookTicker/ethusdt@bookTicker`.
+    pub fn stream_url(&self, coins: &[Coin]) -> Option<String> {
+        let base = self.ws_url.as_deref()?.trim_end_matches('/');
+        let mut streams: Vec<String> = coins
+            .iter()
+            .map(|coin| {
+                format!(
+                    "{}@{}",
+                    coin.spec().binance_symbol.to_ascii_lowercase(),
+                    self.stream.suffix()
+                )
+            })
+            .collect();
+        streams.sort();
+        streams.dedup();
+        if streams.is_empty() {
+            return None;
+        }
+        Some(format!("{base}/stream?streams={}", streams.join("/")))
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum BinanceWsError {
+    #[error("websocket error: {0}")]
+    WebSocket(#[from] tokio_tungstenite::tungstenite::Error),
+    #[error("invalid Binance stream message: {0}")]
+    InvalidMessage(#[from] serde_json::Error),
+    #[error("invalid {field} {value:?} in Binance stream message")]
+    InvalidNumber { field: &'static str, value: String },
+    #[error("Binance stream closed by the server")]
+    Closed,
+}
+
+/// One observed price of a coin.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LivePrice {
+    pub price: f64,
+    pub ts_ms_utc: i64,
+}
+
+/// Live spot prices by coin.
+pub trait PriceSource: Send + Sync {
+    /// Most recent price; `None` until one has arrived.
+    fn latest(&self, coin: Coin) -> Option<LivePrice>;
+
+    /// Reference price of an interval starting at `start_ts_utc`: the first price seen
+    /// at or after the start, when it was captured soon enough to stand in for the
+    /// opening price.
+    fn reference(&self, coin: Coin, start_ts_utc: i64) -> Option<LivePrice>;
+}
+
+#[derive(Debug, Default)]
+struct SymbolPrices {
+    latest: Option<LivePrice>,
+    /// First price of each second, keyed by the second.
+    first_per_sec: BTreeMap<i64, LivePrice>,
+}
+
+#[derive(Debug, Default)]
+struct PriceState {
+    by_symbol: HashMap<String, SymbolPrices>,
+}
+
+/// Shared price state; cloning shares it.
+#[derive(Debug, Clone)]
+pub struct LivePrices {
+    state: Arc<Mutex<PriceState>>,
+    history_secs: i64,
+    max_ref_delay_ms: i64,
+}
+
+impl Default for LivePrices {
+    fn default() -> Self {
+        Self::new(&BinanceWsConfig::default())
+    }
+}
+
+impl LivePrices {
+    pub fn new(cfg: &BinanceWsConfig) -> Self {
+        Self {
+            state: Arc::default(),
+            history_secs: cfg.history_secs,
+            max_ref_delay_ms: cfg.max_ref_delay_ms,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, PriceState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Records `price` for `symbol` (e.g. `BTCUSDT`), pruning history older than the
+    /// configured window.
+    pub fn record(&self, symbol: &str, price: LivePrice) {
+        let mut state = self.lock();
+        let prices = state
+            .by_symbol
+            .entry(symbol.to_ascii_uppercase())
+            .or_default();
+        if prices
+            .latest
+            .is_none_or(|latest| latest.ts_ms_utc <= price.ts_ms_utc)
+        {
+            prices.latest = Some(price);
+        }
+        let sec = price.ts_ms_utc.div_euclid(1_000);
+        prices.first_per_sec.entry(sec).or_insert(price);
+        let oldest = sec - self.history_secs;
+        prices.first_per_sec = prices.first_per_sec.split_off(&oldest);
+    }
+
+    /// Drops every latest price, e.g. after the stream disconnects. Captured per-second
+    /// history stays available for references.
+    pub fn clear_latest(&self) {
+        for prices in self.lock().by_symbol.values_mut() {
+            prices.latest = None;
+        }
+    }
+
+    /// Applies one combined-stream text frame received at `now_ms_utc`, returning
+    /// whether it carried a price. Frames of other event types are ignored.
+    pub fn apply_message(&self, text: &str, now_ms_utc: i64) -> Result<bool, BinanceWsError> {
+        let frame: WireFrame = serde_json::from_str(text)?;
+        let (symbol, price, ts_ms_utc) = match frame.data {
+            WireData::Trade {
+                symbol,
+                price,
+                trade_ms,
+            } => (symbol, parse_number("price", &price)?, trade_ms),
+            WireData::BookTicker {
+                symbol,
+                best_bid,
+                best_ask,
+            } => {
+                let bid = parse_number("bid", &best_bid)?;
+                let ask = parse_number("ask", &best_ask)?;
+                if bid <= 0.0 || ask <= 0.0 {
+                    return Ok(false);
+                }
+                (symbol, (bid + ask) / 2.0, now_ms_utc)
+            }
+            WireData::Other(_) => return Ok(false),
+        };
+        self.record(&symbol, LivePrice { price, ts_ms_utc });
+        Ok(true)
+    }
+
+    fn symbol_prices<T>(&self, coin: Coin, read: impl FnOnce(&SymbolPrices) -> T) -> Option<T> {
+        let state = self.lock();
+        state
+            .by_symbol
+            .get(&coin.spec().binance_symbol.to_ascii_uppercase())
+            .map(read)
+    }
+}
+
+impl PriceSource for LivePrices {
+    fn latest(&self, coin: Coin) -> Option<LivePrice> {
+        self.symbol_prices(coin, |prices| prices.latest).flatten()
+    }
+
+    fn reference(&self, coin: Coin, start_ts_utc: i64) -> Option<LivePrice> {
+        let max_ref_delay_ms = self.max_ref_delay_ms;
+        self.symbol_prices(coin, |prices| {
+            prices
+                .first_per_sec
+                .range(start_ts_utc..)
+                .next()
+                .filter(|(sec, _)| (**sec - start_ts_utc) * 1_000 <= max_ref_delay_ms)
+                .map(|(_, price)| *price)
+        })
+        .flatten()
+    }
+}
+
+#[derive(Deserialize)]
+struct WireFrame {
+    data: WireData,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum WireData {
+    Trade {
+        #[serde(rename = "s")]
+        symbol: String,
+        #[serde(rename = "p")]
+        price: String,
+        #[serde(rename = "T")]
+        trade_ms: i64,
+    },
+    BookTicker {
+        #[serde(rename = "s")]
+        symbol: String,
+        #[serde(rename = "b")]
+        best_bid: String,
+        #[serde(rename = "a")]
+        best_ask: String,
+    },
+    Other(serde::de::IgnoredAny),
+}
+
+fn parse_number(field: &'static str, raw: &str) -> Result<f64, BinanceWsError> {
+    raw.trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|value| value.is_finite())
+        .ok_or_else(|| BinanceWsError::InvalidNumber {
+            field,
+            value: raw.to_string(),
+        })
+}
+
+/// Handle to the background websocket task. Cloning shares the prices.
+#[derive(Debug, Clone)]
+pub struct BinanceWsStream {
+    prices: LivePrices,
+}
+
+impl BinanceWsStream {
+    /// Starts streaming prices for `coins`, or `None` when `cfg.ws_url` is unset or no
+    /// coin is given.
+    pub fn spawn(cfg: BinanceWsConfig, coins: &[Coin]) -> Option<Self> {
+        let url = cfg.stream_url(coins)?;
+        let stream = Self {
+            prices: LivePrices::new(&cfg),
+        };
+        tokio::spawn(run_stream(url, cfg, stream.prices.clone()));
+        Some(stream)
+    }
+
+    pub fn prices(&self) -> &LivePrices {
+        &self.prices
+    }
+}
+
+impl PriceSource for BinanceWsStream {
+    fn latest(&self, coin: Coin) -> Option<LivePrice> {
+        self.prices.latest(coin)
+    }
+
+    fn reference(&self, coin: Coin, start_ts_utc: i64) -> Option<LivePrice> {
+        self.prices.reference(coin, start_ts_utc)
+    }
+}
+
+async fn run_stream(url: String, cfg: BinanceWsConfig, prices: LivePrices) {
+    let mut failures = 0u32;
+    loop {
+        let err = stream_once(&url, &prices, &mut failures).await;
+        prices.clear_latest();
+        failures = failures.saturating_add(1);
+        let delay_ms = reconnect_delay_ms(failures, cfg.max_backoff_ms);
+        warn!(
+            component = "binance_ws",
+            event = "binance_ws.disconnected",
+            consecutive_failures = failures,
+            retry_in_ms = delay_ms,
+            error = %err
+        );
+        tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
+    }
+}
+
+/// Streams until the connection fails; resets `failures` once a price arrives.
+async fn stream_once(url: &str, prices: &LivePrices, failures: &mut u32) -> BinanceWsError {
+    let mut socket = match tokio_tungstenite::connect_async(url).await {
+        Ok((socket, _)) => socket,
+        Err(err) => return err.into(),
+    };
+    info!(
+        component = "binance_ws",
+        event = "binance_ws.subscribed",
+        url = url
+    );
+    loop {
+        let text = match socket.next().await {
+            None => return BinanceWsError::Closed,
+            Some(Err(err)) => return err.into(),
+            Some(Ok(Message::Text(text))) => text,
+            Some(Ok(Message::Close(_))) => return BinanceWsError::Closed,
+            // Pings are answered by tungstenite on the next read.
+            Some(Ok(_)) => continue,
+        };
+        let now_ms_utc = crate::faults::now_utc().timestamp_millis();
+        match prices.apply_message(&text, now_ms_utc) {
+            Ok(true) => *failures = 0,
+            Ok(false) => {}
+            Err(err) => debug!(
+                component = "binance_ws",
+                event = "binance_ws.message_skipped",
+                error = %err
+            ),
+        }
+    }
+}
+
+fn reconnect_delay_ms(failures: u32, max_backoff_ms: u64) -> u64 {
+    let exponent = failures.saturating_sub(1).min(16);
+    500u64.saturating_mul(1 << exponent).min(max_backoff_ms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: i64 = 1_771_449_000;
+
+    #[test]
+    fn book_tickers_and_trades_update_latest_and_references() {
+        let prices = LivePrices::default();
+        assert_eq!(prices.latest(Coin::BTC), None);
+
+        let ticker = r#"{"stream":"btcusdt@bookTicker","data":{"u":1,"s":"BTCUSDT",
+            "b":"100.0","B":"1","a":"101.0","A":"2"}}"#;
+        assert!(prices.apply_message(ticker, START * 1_000 - 200).unwrap());
+        assert!(prices.apply_message(ticker, START * 1_000 + 2_300).unwrap());
+        let trade = r#"{"stream":"btcusdt@trade","data":{"e":"trade","E":1,"s":"BTCUSDT",
+            "t":7,"p":"102.5","q":"0.1","T":1771449003100,"m":false}}"#;
+        assert!(prices.apply_message(trade, START * 1_000 + 3_200).unwrap());
+        let other = r#"{"stream":"x","data":{"result":null,"id":1}}"#;
+        assert!(!prices.apply_message(other, START * 1_000).unwrap());
+        assert!(prices.apply_message("not json", 0).is_err());
+
+        let latest = prices.latest(Coin::BTC).unwrap();
+        assert_eq!((latest.price, latest.ts_ms_utc), (102.5, 1_771_449_003_100));
+        assert_eq!(prices.latest(Coin::ETH), None);
+
+        // The first price at or after the start, within the delay tolerance.
+        let reference = prices.reference(Coin::BTC, START).unwrap();
+        assert_eq!(reference.price, 100.5);
+        assert_eq!(reference.ts_ms_utc, START * 1_000 + 2_300);
+        assert_eq!(
+            prices.reference(Coin::BTC, START - 3).unwrap().ts_ms_utc,
+            START * 1_000 - 200
+        );
+        assert_eq!(prices.reference(Coin::BTC, START - 10), None);
+        assert_eq!(prices.reference(Coin::BTC, START + 10), None);
+
+        prices.clear_latest();
+        assert_eq!(prices.latest(Coin::BTC), None);
+        assert!(prices.reference(Coin::BTC, START).is_some());
+    }
+
+    #[test]
+    fn history_is_pruned_to_the_window() {
+        let prices = LivePrices::new(&BinanceWsConfig {
+            history_secs: 60,
+            ..BinanceWsConfig::default()
+        });
+        let at = |secs: i64| LivePrice {
+            price: secs as f64,
+            ts_ms_utc: (START + secs) * 1_000,
+        };
+        prices.record("btcusdt", at(0));
+        prices.record("BTCUSDT", at(30));
+        assert_eq!(prices.reference(Coin::BTC, START).unwrap().price, 0.0);
+        prices.record("BTCUSDT", at(90));
+        assert_eq!(prices.reference(Coin::BTC, START), None);
+        assert_eq!(prices.reference(Coin::BTC, START + 30).unwrap().price, 30.0);
+        // An out-of-order tick never replaces a newer latest price.
+        prices.record("BTCUSDT", at(45));
+        assert_eq!(prices.latest(Coin::BTC).unwrap().price, 90.0);
+    }
+
+    #[test]
+    fn stream_url_combines_every_coin() {
+        let cfg = BinanceWsConfig {
+            ws_url: Some("wss://example.com/".to_string()),
+            stream: BinanceStreamKind::Trade,
+            ..BinanceWsConfig::default()
+        };
+        assert_eq!(
+            cfg.stream_url(&[Coin::ETH, Coin::BTC, Coin::ETH])
+                .as_deref(),
+            Some("wss://example.com/stream?streams=btcusdt@trade/ethusdt@trade")
+        );
+        assert_eq!(cfg.stream_url(&[]), None);
+        assert_eq!(
+            BinanceStreamKind::parse("Book_Ticker"),
+            Some(BinanceStreamKind::BookTicker)
+        );
+        assert_eq!(BinanceStreamKind::parse("depth"), None);
+    }
+}
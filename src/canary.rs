@@ -0,0 +1,490 @@
+//! Canary quoting: a second strategy configuration on a subset of markets.
+//!
+//! [`CanaryConfig`] names the (coin, duration) pairs the canary owns and the quote
+//! parameters it runs with. [`CanaryQuoting`] drives two [`QuotingEngine`]s side by
+//! side, each seeing only its own markets: the primary quotes everything else, so a
+//! market is never quoted twice. Canary decisions are recorded under the
+//! `two_sided_quotes_canary` routine through their own [`DecisionLog`], and
+//! [`CanaryConfig::attribute_pnl`] splits positions by arm, so a parameter change can
+//! be judged on low-stakes markets before it is rolled out.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::capital::{CapitalConfig, CapitalUsage};
+use crate::env_vars::{EnvValue, Settings};
+use crate::orders::ThrottledTradingClient;
+use crate::positions::PositionSource;
+use crate::sizing::SizingConfig;
+use crate::slug::{parse_coin, Coin, Duration};
+use crate::strategy::{
+    DecisionLog, FlattenConfig, OrderIntent, PreMarketQuoteConfig, QuoteMarket, QuoteMarketState,
+    QuotingEngine, StrategyConfig,
+};
+use crate::toggles::MarketToggles;
+use crate::trading::TradingClient;
+
+/// Decision `routine` of the canary engine.
+pub const CANARY_ROUTINE: &str = "two_sided_quotes_canary";
+
+/// One market of a quoting cycle with its live inputs.
+type MarketInput = (QuoteMarket, QuoteMarketState);
+
+/// Which engine a market belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StrategyArm {
+    Primary,
+    Canary,
+}
+
+impl StrategyArm {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Primary => "primary",
+            Self::Canary => "canary",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CanaryConfig {
+    /// Markets the canary quotes instead of the primary.
+    pub markets: HashSet<(Coin, Duration)>,
+    /// The canary's quote parameters; dry-run follows the primary.
+    pub strategy: StrategyConfig,
+}
+
+impl CanaryConfig {
+    /// Canary on `markets` (see [`parse_canary_markets`]) running `params`
+    /// (comma-separated `key=value` quote parameters) on top of `primary`'s default and
+    /// per-duration parameters.
+    pub fn parse(markets: &str, params: &str, primary: &StrategyConfig) -> Result<Self, String> {
+        let markets = parse_canary_markets(markets)?;
+        if markets.is_empty() {
+            return Err("no canary markets".to_string());
+        }
+        let mut overrides = primary.overrides.clone();
+        for duration_params in overrides.values_mut() {
+            *duration_params = duration_params.with_overrides(params)?;
+        }
+        Ok(Self {
+            markets,
+            strategy: StrategyConfig {
+                dry_run: primary.dry_run,
+                default: primary.default.with_overrides(params)?,
+                overrides,
+            },
+        })
+    }
+
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env(primary: &StrategyConfig) -> Option<Self> {
+        Self::from_settings(&Settings::from_env(), primary)
+    }
+
+    /// Reads `PMM_CANARY_MARKETS` (`off`, unset, or invalid disables the canary) and
+    /// `PMM_CANARY_PARAMS`.
+    pub fn from_settings(settings: &Settings, primary: &StrategyConfig) -> Option<Self> {
+        let markets: String = settings.get::<Option<String>>("PMM_CANARY_MARKETS")?;
+        let params = settings
+            .parsed("PMM_CANARY_PARAMS", String::parse_env)
+            .unwrap_or_default();
+        Self::parse(&markets, &params, primary).ok()
+    }
+
+    pub fn arm(&self, coin: Coin, duration: Duration) -> StrategyArm {
+        if self.markets.contains(&(coin, duration)) {
+            StrategyArm::Canary
+        } else {
+            StrategyArm::Primary
+        }
+    }
+
+    /// Realized and marked PnL of `markets`' positions, split by arm. `mark_yes` values
+    /// open shares; markets without a mark count realized PnL only.
+    pub fn attribute_pnl(
+        &self,
+        markets: &[QuoteMarket],
+        positions: &dyn PositionSource,
+        mark_yes: impl Fn(&QuoteMarket) -> Option<f64>,
+    ) -> CanaryPnl {
+        let mut pnl = CanaryPnl {
+            primary: ArmPnl::new(StrategyArm::Primary),
+            canary: ArmPnl::new(StrategyArm::Canary),
+        };
+        for market in markets {
+            let Some(position) = positions.position(&market.slug) else {
+                continue;
+            };
+            let arm = match self.arm(market.coin, market.duration) {
+                StrategyArm::Primary => &mut pnl.primary,
+                StrategyArm::Canary => &mut pnl.canary,
+            };
+            arm.markets += 1;
+            arm.realized_pnl_usdc += position.realized_pnl_usdc();
+            arm.net_profit_usdc += position.net_profit_usdc(mark_yes(market));
+        }
+        pnl
+    }
+}
+
+/// Parses `COIN:duration` pairs separated by commas (`BTC:5m,ETH:15m`).
+pub fn parse_canary_markets(raw: &str) -> Result<HashSet<(Coin, Duration)>, String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (coin, duration) = entry
+                .split_once(':')
+                .ok_or_else(|| format!("expected COIN:duration, got {entry:?}"))?;
+            let coin =
+                parse_coin(&coin.trim().to_ascii_uppercase()).map_err(|err| err.to_string())?;
+            let duration = Duration::parse(duration.trim()).map_err(|err| err.to_string())?;
+            Ok((coin, duration))
+        })
+        .collect()
+}
+
+/// Position PnL of the markets one arm quoted.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArmPnl {
+    pub arm: StrategyArm,
+    /// Markets with a position.
+    pub markets: usize,
+    pub realized_pnl_usdc: f64,
+    /// Realized plus marked unrealized PnL.
+    pub net_profit_usdc: f64,
+}
+
+impl ArmPnl {
+    fn new(arm: StrategyArm) -> Self {
+        Self {
+            arm,
+            markets: 0,
+            realized_pnl_usdc: 0.0,
+            net_profit_usdc: 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CanaryPnl {
+    pub primary: ArmPnl,
+    pub canary: ArmPnl,
+}
+
+impl CanaryPnl {
+    /// Logs both arms as `canary.pnl`.
+    pub fn log(&self) {
+        for arm in [&self.primary, &self.canary] {
+            info!(
+                component = "strategy",
+                event = "canary.pnl",
+                arm = arm.arm.as_str(),
+                markets = arm.markets,
+                realized_pnl_usdc = arm.realized_pnl_usdc,
+                net_profit_usdc = arm.net_profit_usdc
+            );
+        }
+    }
+}
+
+/// Primary and canary quoting engines over one market list.
+///
+/// Both engines throttle on the wallet's [`CapitalUsage`]; the canary runs second and
+/// sees the headroom less what the primary's new bids locked in the same cycle.
+#[derive(Debug)]
+pub struct CanaryQuoting {
+    canary: CanaryConfig,
+    capital_usage: Option<CapitalUsage>,
+    primary_engine: QuotingEngine,
+    canary_engine: QuotingEngine,
+}
+
+impl CanaryQuoting {
+    pub fn new(
+        primary: StrategyConfig,
+        canary: CanaryConfig,
+        window: PreMarketQuoteConfig,
+        flatten: FlattenConfig,
+        toggles: MarketToggles,
+    ) -> Self {
+        let canary_engine = QuotingEngine::new(
+            canary.strategy.clone(),
+            window.clone(),
+            flatten,
+            toggles.clone(),
+        )
+        .with_routine(CANARY_ROUTINE);
+        Self {
+            canary,
+            capital_usage: None,
+            primary_engine: QuotingEngine::new(primary, window, flatten, toggles),
+            canary_engine,
+        }
+    }
+
+    /// Throttles both engines by `capital` instead of the defaults.
+    pub fn with_capital(mut self, capital: CapitalConfig) -> Self {
+        self.primary_engine = self.primary_engine.with_capital(capital);
+        self.canary_engine = self.canary_engine.with_capital(capital);
+        self
+    }
+
+    /// Caps both engines' bid sizes by the Kelly stake under `sizing`.
+    pub fn with_sizing(mut self, sizing: SizingConfig) -> Self {
+        self.primary_engine = self.primary_engine.with_sizing(sizing);
+        self.canary_engine = self.canary_engine.with_sizing(sizing);
+        self
+    }
+
+    pub fn set_capital_usage(&mut self, usage: Option<CapitalUsage>) {
+        self.primary_engine.set_capital_usage(usage.clone());
+        self.canary_engine.set_capital_usage(usage.clone());
+        self.capital_usage = usage;
+    }
+
+    pub fn canary_config(&self) -> &CanaryConfig {
+        &self.canary
+    }
+
+    pub fn engine(&self, arm: StrategyArm) -> &QuotingEngine {
+        match arm {
+            StrategyArm::Primary => &self.primary_engine,
+            StrategyArm::Canary => &self.canary_engine,
+        }
+    }
+
+    /// `markets` split into the primary's and the canary's.
+    pub fn split(
+        &self,
+        markets: &[(QuoteMarket, QuoteMarketState)],
+    ) -> (Vec<MarketInput>, Vec<MarketInput>) {
+        markets.iter().cloned().partition(|(market, _)| {
+            self.canary.arm(market.coin, market.duration) == StrategyArm::Primary
+        })
+    }
+
+    /// Runs one cycle of each engine on its markets, recording the primary's decisions
+    /// to `primary_log` and the canary's to `canary_log`. Both arms share `client`'s
+    /// rate limits. Returns the intents that went through, by arm.
+    pub async fn run_cycle(
+        &mut self,
+        markets: &[(QuoteMarket, QuoteMarketState)],
+        now_ts_utc: i64,
+        client: &ThrottledTradingClient<impl TradingClient>,
+        primary_log: &dyn DecisionLog,
+        canary_log: &dyn DecisionLog,
+    ) -> Vec<(StrategyArm, OrderIntent)> {
+        let (primary, canary) = self.split(markets);
+        let mut executed: Vec<_> = self
+            .primary_engine
+            .run_cycle(&primary, now_ts_utc, client, primary_log)
+            .await
+            .into_iter()
+            .map(|intent| (StrategyArm::Primary, intent))
+            .collect();
+        if let Some(mut usage) = self.capital_usage.clone() {
+            let locked_usdc: f64 = executed
+                .iter()
+                .filter_map(|(_, intent)| match intent {
+                    OrderIntent::Place { order, .. } => Some(order.price * order.size),
+                    OrderIntent::Cancel { .. } => None,
+                })
+                .sum();
+            usage.headroom_usdc = usage.headroom_usdc.map(|headroom| headroom - locked_usdc);
+            self.canary_engine.set_capital_usage(Some(usage));
+        }
+        executed.extend(
+            self.canary_engine
+                .run_cycle(&canary, now_ts_utc, client, canary_log)
+                .await
+                .into_iter()
+                .map(|intent| (StrategyArm::Canary, intent)),
+        );
+        executed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::clob_stream::{OrderBook, OrderBookSource};
+    use crate::fill_model::BookTop;
+    use crate::models::ModelPrediction;
+    use crate::orders::OrderRateLimitConfig;
+    use crate::paper_trading::{PaperOrderClient, PaperTradingConfig};
+    use crate::positions::{MarketPosition, OutcomePosition};
+    use crate::strategy::DecisionRecord;
+
+    #[derive(Default)]
+    struct MemoryLog(Mutex<Vec<DecisionRecord>>);
+
+    impl DecisionLog for MemoryLog {
+        fn record(&self, record: &DecisionRecord) {
+            self.0.lock().unwrap().push(record.clone());
+        }
+    }
+
+    struct NoBooks;
+
+    impl OrderBookSource for NoBooks {
+        fn book(&self, _slug: &str) -> Option<OrderBook> {
+            None
+        }
+    }
+
+    struct Positions(HashMap<String, MarketPosition>);
+
+    impl PositionSource for Positions {
+        fn position(&self, slug: &str) -> Option<MarketPosition> {
+            self.0.get(slug).copied()
+        }
+    }
+
+    fn market(coin: Coin, slug: &str) -> QuoteMarket {
+        QuoteMarket {
+            slug: slug.to_string(),
+            coin,
+            duration: Duration::M15,
+            start_ts_utc: 900,
+            end_ts_utc: 1_800,
+            accepting_orders_ts_utc: None,
+            yes_token_id: format!("{slug}-yes"),
+            no_token_id: format!("{slug}-no"),
+            neg_risk: false,
+            neg_risk_group: None,
+        }
+    }
+
+    fn state() -> QuoteMarketState {
+        QuoteMarketState {
+            prediction: Some(ModelPrediction {
+                probability_yes: 0.6,
+                model_id: "m".to_string(),
+            }),
+            book: Some(BookTop {
+                ts_ms: 1_000_000,
+                best_bid: Some(0.4),
+                best_ask: Some(0.8),
+                bid_size: 10.0,
+                ask_size: 10.0,
+            }),
+            position: None,
+        }
+    }
+
+    #[test]
+    fn parses_markets_and_layers_params_on_the_primary() {
+        assert!(parse_canary_markets("btc:5m").is_ok());
+        assert!(parse_canary_markets("BTC").is_err());
+        assert!(parse_canary_markets("BTC:7m").is_err());
+        assert!(CanaryConfig::parse("", "", &StrategyConfig::default()).is_err());
+
+        let mut primary = StrategyConfig::default();
+        primary.overrides.insert(Duration::H1, primary.default);
+        let canary = CanaryConfig::parse("BTC:15m, ETH:5m", "half_spread=0.05", &primary).unwrap();
+        assert_eq!(canary.markets.len(), 2);
+        assert_eq!(canary.arm(Coin::ETH, Duration::M5), StrategyArm::Canary);
+        assert_eq!(canary.arm(Coin::ETH, Duration::M15), StrategyArm::Primary);
+        assert_eq!(canary.strategy.default.half_spread, 0.05);
+        assert_eq!(canary.strategy.params(Duration::H1).half_spread, 0.05);
+        assert_eq!(
+            canary.strategy.default.order_usdc,
+            primary.default.order_usdc
+        );
+    }
+
+    #[tokio::test]
+    async fn each_engine_quotes_and_logs_only_its_own_markets() {
+        let primary = StrategyConfig::default();
+        let canary = CanaryConfig::parse("ETH:15m", "half_spread=0.05", &primary).unwrap();
+        let mut quoting = CanaryQuoting::new(
+            primary,
+            canary,
+            PreMarketQuoteConfig::default(),
+            FlattenConfig::default(),
+            MarketToggles::in_memory(),
+        );
+        let paper = PaperTradingConfig {
+            store_path: None,
+            ..PaperTradingConfig::default()
+        };
+        let client = ThrottledTradingClient::new(
+            PaperOrderClient::open(paper, Arc::new(NoBooks)).unwrap(),
+            OrderRateLimitConfig::default(),
+        );
+        let markets = [
+            (market(Coin::BTC, "btc-updown-15m-900"), state()),
+            (market(Coin::ETH, "eth-updown-15m-900"), state()),
+        ];
+        let (primary_log, canary_log) = (MemoryLog::default(), MemoryLog::default());
+
+        let executed = quoting
+            .run_cycle(&markets, 1_000, &client, &primary_log, &canary_log)
+            .await;
+        assert_eq!(executed.len(), 4);
+        let yes_bid = |arm: StrategyArm, slug: &str| {
+            quoting
+                .engine(arm)
+                .resting(slug, crate::sizing::SizingSide::Yes)
+                .map(|quote| quote.level.price)
+        };
+        assert_eq!(
+            yes_bid(StrategyArm::Primary, "btc-updown-15m-900"),
+            Some(0.58)
+        );
+        assert_eq!(
+            yes_bid(StrategyArm::Canary, "eth-updown-15m-900"),
+            Some(0.55)
+        );
+        assert_eq!(yes_bid(StrategyArm::Primary, "eth-updown-15m-900"), None);
+
+        let canary_records = canary_log.0.lock().unwrap();
+        assert_eq!(canary_records.len(), 2);
+        assert!(canary_records
+            .iter()
+            .all(|record| record.routine == CANARY_ROUTINE && record.slug.starts_with("eth")));
+        assert!(primary_log
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .all(|record| record.routine == "two_sided_quotes" && record.slug.starts_with("btc")));
+    }
+
+    #[test]
+    fn pnl_is_attributed_by_arm() {
+        let canary = CanaryConfig::parse("ETH:15m", "", &StrategyConfig::default()).unwrap();
+        let long_yes = |realized_pnl_usdc: f64| MarketPosition {
+            yes: OutcomePosition {
+                shares: 10.0,
+                avg_price: 0.5,
+                realized_pnl_usdc,
+            },
+            ..MarketPosition::default()
+        };
+        let positions = Positions(HashMap::from([
+            ("btc-updown-15m-900".to_string(), long_yes(1.0)),
+            ("eth-updown-15m-900".to_string(), long_yes(-2.0)),
+        ]));
+        let markets = [
+            market(Coin::BTC, "btc-updown-15m-900"),
+            market(Coin::ETH, "eth-updown-15m-900"),
+            market(Coin::ETH, "eth-updown-15m-1800"),
+        ];
+
+        let pnl = canary.attribute_pnl(&markets, &positions, |_| Some(0.6));
+        assert_eq!(pnl.primary.markets, 1);
+        assert!((pnl.primary.realized_pnl_usdc - 1.0).abs() < 1e-9);
+        assert!((pnl.primary.net_profit_usdc - 2.0).abs() < 1e-9);
+        assert_eq!(pnl.canary.markets, 1);
+        assert!((pnl.canary.net_profit_usdc + 1.0).abs() < 1e-9);
+    }
+}
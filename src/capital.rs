@@ -0,0 +1,228 @@
+//! Capital utilization: how much of the wallet's USDC the open bids and positions tie up.
+//!
+//! The CLOB does not escrow collateral for resting orders, but it rejects a match the
+//! wallet cannot pay for, so every open bid locks `remaining × price` of the balance
+//! (sells lock shares, not USDC). Positions were paid for already and sit outside the
+//! balance at their average cost. Headroom is the balance less the bid collateral;
+//! utilization is the locked share of all capital, bids plus position cost over
+//! balance plus position cost. As utilization passes [`CapitalConfig::throttle_start`]
+//! the quoting engine scales its bids down, and stops placing new ones once headroom
+//! is at [`CapitalConfig::min_headroom_usdc`].
+
+use serde::{Deserialize, Serialize};
+
+use crate::env_vars::Settings;
+use crate::fill_model::OrderSide;
+use crate::positions::MarketPosition;
+use crate::trading::OpenOrder;
+
+/// Quote scales are rounded down to quarters, so a bid is resized a handful of times
+/// as headroom shrinks rather than on every balance poll.
+const QUOTE_SCALE_STEPS: f64 = 4.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CapitalConfig {
+    /// Utilization, in `[0, 1]`, above which bid sizes shrink linearly to zero at full
+    /// utilization.
+    pub throttle_start: f64,
+    /// Free USDC never committed to new bids.
+    pub min_headroom_usdc: f64,
+}
+
+impl Default for CapitalConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl CapitalConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_CAPITAL_THROTTLE_START` and `PMM_CAPITAL_MIN_HEADROOM_USDC`, falling
+    /// back to defaults for missing/invalid values.
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            throttle_start: settings.get_where("PMM_CAPITAL_THROTTLE_START", |start| {
+                (0.0..=1.0).contains(start)
+            }),
+            min_headroom_usdc: settings
+                .get_where("PMM_CAPITAL_MIN_HEADROOM_USDC", |usdc| *usdc >= 0.0),
+        }
+    }
+}
+
+/// Collateral tied up by the wallet's open orders and positions.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CapitalUsage {
+    /// Wallet USDC; `None` until the balance was read.
+    pub balance_usdc: Option<f64>,
+    /// `remaining × price` of every open bid.
+    pub order_collateral_usdc: f64,
+    /// Average cost of the shares held.
+    pub position_cost_usdc: f64,
+    pub open_orders: usize,
+    /// Balance not locked by bids; `None` without a balance.
+    pub headroom_usdc: Option<f64>,
+    /// Bids plus positions over balance plus positions; `None` without a balance.
+    pub utilization: Option<f64>,
+    pub updated_ts_utc: i64,
+}
+
+impl CapitalUsage {
+    pub fn compute<'a>(
+        balance_usdc: Option<f64>,
+        orders: impl IntoIterator<Item = &'a OpenOrder>,
+        positions: impl IntoIterator<Item = MarketPosition>,
+        now_ts_utc: i64,
+    ) -> Self {
+        let mut open_orders = 0;
+        let mut order_collateral_usdc = 0.0;
+        for order in orders {
+            open_orders += 1;
+            if order.side == OrderSide::Buy {
+                order_collateral_usdc += order.remaining() * order.price;
+            }
+        }
+        let position_cost_usdc = positions
+            .into_iter()
+            .map(|position| {
+                position.yes.shares * position.yes.avg_price
+                    + position.no.shares * position.no.avg_price
+            })
+            .sum::<f64>();
+        let capital = balance_usdc.map(|balance| balance.max(0.0) + position_cost_usdc);
+        Self {
+            balance_usdc,
+            order_collateral_usdc,
+            position_cost_usdc,
+            open_orders,
+            headroom_usdc: balance_usdc.map(|balance| balance - order_collateral_usdc),
+            utilization: capital.map(|capital| {
+                if capital > 0.0 {
+                    ((order_collateral_usdc + position_cost_usdc) / capital).min(1.0)
+                } else {
+                    1.0
+                }
+            }),
+            updated_ts_utc: now_ts_utc,
+        }
+    }
+
+    /// Factor, in quarters of `[0, 1]`, new bid sizes are scaled by: `1` up to
+    /// `throttle_start` utilization, falling linearly to `0` at full utilization, and
+    /// `0` once headroom is at the floor. `1` while the balance is unknown.
+    pub fn quote_scale(&self, cfg: &CapitalConfig) -> f64 {
+        let (Some(headroom), Some(utilization)) = (self.headroom_usdc, self.utilization) else {
+            return 1.0;
+        };
+        if headroom <= cfg.min_headroom_usdc {
+            return 0.0;
+        }
+        if utilization <= cfg.throttle_start || cfg.throttle_start >= 1.0 {
+            return 1.0;
+        }
+        let scale = ((1.0 - utilization) / (1.0 - cfg.throttle_start)).clamp(0.0, 1.0);
+        (scale * QUOTE_SCALE_STEPS + 1e-9).floor() / QUOTE_SCALE_STEPS
+    }
+
+    /// USDC new bids may still lock: headroom above the floor. `None` while the balance
+    /// is unknown.
+    pub fn quote_budget_usdc(&self, cfg: &CapitalConfig) -> Option<f64> {
+        self.headroom_usdc
+            .map(|headroom| (headroom - cfg.min_headroom_usdc).max(0.0))
+    }
+}
+
+/// Capital usage of the trading wallet.
+pub trait CapitalSource: Send + Sync {
+    /// Latest usage; `None` until the wallet was polled.
+    fn capital_usage(&self) -> Option<CapitalUsage>;
+}
+
+/// The `/wallet` view: usage plus the throttle it implies.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WalletReport {
+    pub capital: Option<CapitalUsage>,
+    pub config: CapitalConfig,
+    /// [`CapitalUsage::quote_scale`]; `1` without usage.
+    pub quote_scale: f64,
+}
+
+impl WalletReport {
+    pub fn new(capital: Option<CapitalUsage>, config: CapitalConfig) -> Self {
+        Self {
+            quote_scale: capital
+                .as_ref()
+                .map_or(1.0, |usage| usage.quote_scale(&config)),
+            capital,
+            config,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::positions::OutcomePosition;
+
+    fn bid(price: f64, size: f64, matched: f64) -> OpenOrder {
+        OpenOrder {
+            order_id: "o".into(),
+            token_id: "t".into(),
+            market: "m".into(),
+            outcome: "Up".into(),
+            side: OrderSide::Buy,
+            price,
+            original_size: size,
+            size_matched: matched,
+            status: "LIVE".into(),
+            created_ts_utc: 0,
+            expires_ts_utc: None,
+        }
+    }
+
+    #[test]
+    fn bids_and_positions_lock_collateral_and_throttle_quotes() {
+        let ask = OpenOrder {
+            side: OrderSide::Sell,
+            ..bid(0.9, 100.0, 0.0)
+        };
+        let held = MarketPosition {
+            yes: OutcomePosition {
+                shares: 20.0,
+                avg_price: 0.5,
+                realized_pnl_usdc: 0.0,
+            },
+            ..MarketPosition::default()
+        };
+        let orders = [bid(0.5, 100.0, 20.0), bid(0.2, 50.0, 0.0), ask];
+        let usage = CapitalUsage::compute(Some(100.0), &orders, [held], 7);
+        assert_eq!(usage.open_orders, 3);
+        // 80 × 0.5 + 50 × 0.2; the ask locks shares only.
+        assert!((usage.order_collateral_usdc - 50.0).abs() < 1e-9);
+        assert!((usage.position_cost_usdc - 10.0).abs() < 1e-9);
+        assert_eq!(usage.headroom_usdc, Some(50.0));
+        // (50 + 10) / (100 + 10)
+        assert!((usage.utilization.unwrap() - 60.0 / 110.0).abs() < 1e-9);
+
+        let cfg = CapitalConfig::default();
+        assert_eq!(usage.quote_scale(&cfg), 1.0);
+        assert_eq!(usage.quote_budget_usdc(&cfg), Some(40.0));
+
+        // 71% used: (1 - 0.71) / 0.4 = 0.74, rounded down to a half.
+        let tight = CapitalUsage::compute(Some(75.0), &orders, [held], 7);
+        assert!((tight.utilization.unwrap() - 0.705_882).abs() < 1e-6);
+        assert_eq!(tight.quote_scale(&cfg), 0.5);
+        assert_eq!(
+            CapitalUsage::compute(Some(58.0), &orders, [held], 7).quote_scale(&cfg),
+            0.0
+        );
+        assert_eq!(
+            CapitalUsage::compute(None, &orders, [held], 7).quote_scale(&cfg),
+            1.0
+        );
+    }
+}
@@ -0,0 +1,607 @@
+//! Live CLOB order books over the Polymarket market websocket.
+//!
+//! Discovery resolves each [`DiscoveryKey`] to a market with CLOB token ids; a
+//! [`BookSubscription`] ties the market's slugs to its YES token. [`ClobBookStream`]
+//! subscribes to those tokens on `PMM_CLOB_WS_URL`, applies `book` snapshots and
+//! `price_change` deltas to [`ClobBooks`], and reconnects with exponential backoff.
+//! Consumers read books through the [`OrderBookSource`] trait, so the dashboard's
+//! `best_bid_yes`/`best_ask_yes` columns can come from any source.
+//!
+//! Books are dropped when the connection is lost, so a silent stream never shows stale
+//! prices as live ones.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::watch;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, info, warn};
+
+use crate::discovery::{DiscoveryKey, DiscoveryRow, DiscoveryStatus, MarketMeta};
+use crate::env_vars::Settings;
+use crate::fill_model::BookTop;
+
+pub const DEFAULT_CLOB_WS_URL: &str = "wss://ws-subscriptions-clob.polymarket.com/ws/market";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClobStreamConfig {
+    /// Market channel URL; `None` disables streaming.
+    pub ws_url: Option<String>,
+    /// Keep-alive `PING` cadence; the server drops quiet connections.
+    pub ping_interval_ms: u64,
+    /// Cap for the exponential reconnect delay.
+    pub max_backoff_ms: u64,
+}
+
+impl Default for ClobStreamConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl ClobStreamConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_CLOB_WS_URL` (`off` disables), `PMM_CLOB_PING_INTERVAL_MS`, and
+    /// `PMM_CLOB_MAX_BACKOFF_MS`.
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            ws_url: settings.get("PMM_CLOB_WS_URL"),
+            ping_interval_ms: settings.get_where("PMM_CLOB_PING_INTERVAL_MS", |ms| *ms > 0),
+            max_backoff_ms: settings.get("PMM_CLOB_MAX_BACKOFF_MS"),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ClobStreamError {
+    #[error("websocket error: {0}")]
+    WebSocket(#[from] tokio_tungstenite::tungstenite::Error),
+    #[error("invalid market channel message: {0}")]
+    InvalidMessage(#[from] serde_json::Error),
+    #[error("invalid {field} {value:?} in market channel message")]
+    InvalidNumber { field: &'static str, value: String },
+    #[error("market channel closed by the server")]
+    Closed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BookLevel {
+    pub price: f64,
+    pub size: f64,
+}
+
+/// Resting liquidity for one CLOB token; bids best (highest) first, asks best
+/// (lowest) first.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OrderBook {
+    pub bids: Vec<BookLevel>,
+    pub asks: Vec<BookLevel>,
+    /// Local receive time of the last applied message.
+    pub updated_ms_utc: i64,
+}
+
+impl OrderBook {
+    fn set_level(&mut self, side: BookSide, price: f64, size: f64) {
+        let levels = match side {
+            BookSide::Bid => &mut self.bids,
+            BookSide::Ask => &mut self.asks,
+        };
+        levels.retain(|level| level.price != price);
+        if size > 0.0 {
+            levels.push(BookLevel { price, size });
+        }
+        sort_levels(levels, side);
+    }
+
+    /// Best prices with the size resting at each, stamped with the last update.
+    pub fn top(&self) -> BookTop {
+        BookTop {
+            ts_ms: self.updated_ms_utc,
+            best_bid: self.bids.first().map(|level| level.price),
+            best_ask: self.asks.first().map(|level| level.price),
+            bid_size: self.bids.first().map_or(0.0, |level| level.size),
+            ask_size: self.asks.first().map_or(0.0, |level| level.size),
+        }
+    }
+
+    /// Total size resting on each side, as `(bids, asks)`.
+    pub fn depth(&self) -> (f64, f64) {
+        (
+            self.bids.iter().map(|level| level.size).sum(),
+            self.asks.iter().map(|level| level.size).sum(),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BookSide {
+    Bid,
+    Ask,
+}
+
+fn sort_levels(levels: &mut [BookLevel], side: BookSide) {
+    match side {
+        BookSide::Bid => levels.sort_by(|a, b| b.price.total_cmp(&a.price)),
+        BookSide::Ask => levels.sort_by(|a, b| a.price.total_cmp(&b.price)),
+    }
+}
+
+/// Live YES-token books by market slug.
+pub trait OrderBookSource: Send + Sync {
+    /// Full YES book for `slug`; `None` until a book has arrived.
+    fn book(&self, slug: &str) -> Option<OrderBook>;
+
+    fn top_of_book(&self, slug: &str) -> Option<BookTop> {
+        self.book(slug).map(|book| book.top())
+    }
+}
+
+/// The outcome tokens of one discovered market, under every slug the dashboard may
+/// show it by. Books are streamed for the YES token only.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BookSubscription {
+    pub key: DiscoveryKey,
+    /// Gamma's slug when it differs from the scheduled one.
+    pub market_slug: Option<String>,
+    pub yes_token_id: String,
+    pub no_token_id: Option<String>,
+    /// See [`MarketMeta::neg_risk`].
+    pub neg_risk: bool,
+    /// See [`MarketMeta::neg_risk_group`].
+    pub neg_risk_group: Option<String>,
+}
+
+impl BookSubscription {
+    /// Subscription for a resolved row with CLOB token ids.
+    pub fn from_discovery_row(row: &DiscoveryRow<MarketMeta>) -> Option<Self> {
+        let DiscoveryStatus::Resolved { market } = &row.status else {
+            return None;
+        };
+        Some(Self {
+            key: row.key.clone(),
+            market_slug: market.slug.clone().filter(|slug| *slug != row.key.slug),
+            yes_token_id: market.token_ids.first()?.clone(),
+            no_token_id: market.token_ids.get(1).cloned(),
+            neg_risk: market.neg_risk == Some(true),
+            neg_risk_group: market.neg_risk_group().map(str::to_string),
+        })
+    }
+
+    /// The scheduled slug, then Gamma's when it differs.
+    pub fn slugs(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.key.slug.as_str()).chain(self.market_slug.as_deref())
+    }
+}
+
+#[derive(Debug, Default)]
+struct BookState {
+    token_by_slug: HashMap<String, String>,
+    books: HashMap<String, OrderBook>,
+}
+
+/// Shared book state; cloning shares it.
+#[derive(Debug, Clone, Default)]
+pub struct ClobBooks {
+    state: Arc<Mutex<BookState>>,
+}
+
+impl ClobBooks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BookState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Replaces the tracked markets; books of tokens no longer tracked are dropped.
+    /// Returns the subscribed token ids, sorted.
+    pub fn set_subscriptions(&self, subscriptions: &[BookSubscription]) -> Vec<String> {
+        let mut state = self.lock();
+        state.token_by_slug = subscriptions
+            .iter()
+            .flat_map(|sub| {
+                sub.slugs()
+                    .map(|slug| (slug.to_string(), sub.yes_token_id.clone()))
+            })
+            .collect();
+        let mut tokens: Vec<String> = subscriptions
+            .iter()
+            .map(|sub| sub.yes_token_id.clone())
+            .collect();
+        tokens.sort();
+        tokens.dedup();
+        state
+            .books
+            .retain(|token, _| tokens.binary_search(token).is_ok());
+        tokens
+    }
+
+    /// Book for a CLOB token.
+    pub fn token_book(&self, token_id: &str) -> Option<OrderBook> {
+        self.lock().books.get(token_id).cloned()
+    }
+
+    /// Drops every book, e.g. after the stream disconnects.
+    pub fn clear(&self) {
+        self.lock().books.clear();
+    }
+
+    /// Applies one market channel text frame (a single event or an array of them)
+    /// received at `now_ms_utc`, returning how many events changed a book. `PONG`
+    /// replies and unknown event types are ignored.
+    pub fn apply_message(&self, text: &str, now_ms_utc: i64) -> Result<usize, ClobStreamError> {
+        let text = text.trim();
+        if !text.starts_with('{') && !text.starts_with('[') {
+            return Ok(0);
+        }
+        let events = match serde_json::from_str::<WireFrame>(text)? {
+            WireFrame::One(event) => vec![event],
+            WireFrame::Many(events) => events,
+        };
+        let mut state = self.lock();
+        let mut applied = 0;
+        for event in events {
+            match event {
+                WireEvent::Book {
+                    asset_id,
+                    bids,
+                    asks,
+                } => {
+                    let mut book = OrderBook {
+                        bids: parse_levels(&bids)?,
+                        asks: parse_levels(&asks)?,
+                        updated_ms_utc: now_ms_utc,
+                    };
+                    sort_levels(&mut book.bids, BookSide::Bid);
+                    sort_levels(&mut book.asks, BookSide::Ask);
+                    state.books.insert(asset_id, book);
+                    applied += 1;
+                }
+                WireEvent::PriceChange { price_changes } => {
+                    for change in price_changes {
+                        // Deltas only make sense on top of a snapshot.
+                        let Some(book) = state.books.get_mut(&change.asset_id) else {
+                            continue;
+                        };
+                        let side = match change.side.as_str() {
+                            "BUY" => BookSide::Bid,
+                            "SELL" => BookSide::Ask,
+                            _ => continue,
+                        };
+                        book.set_level(
+                            side,
+                            parse_number("price", &change.price)?,
+                            parse_number("size", &change.size)?,
+                        );
+                        book.updated_ms_utc = now_ms_utc;
+                        applied += 1;
+                    }
+                }
+                WireEvent::Other => {}
+            }
+        }
+        Ok(applied)
+    }
+}
+
+impl OrderBookSource for ClobBooks {
+    fn book(&self, slug: &str) -> Option<OrderBook> {
+        let state = self.lock();
+        let token = state.token_by_slug.get(slug)?;
+        state.books.get(token).cloned()
+    }
+
+    fn top_of_book(&self, slug: &str) -> Option<BookTop> {
+        let state = self.lock();
+        let token = state.token_by_slug.get(slug)?;
+        state.books.get(token).map(OrderBook::top)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum WireFrame {
+    One(WireEvent),
+    Many(Vec<WireEvent>),
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "event_type", rename_all = "snake_case")]
+enum WireEvent {
+    Book {
+        asset_id: String,
+        #[serde(alias = "buys", default)]
+        bids: Vec<WireLevel>,
+        #[serde(alias = "sells", default)]
+        asks: Vec<WireLevel>,
+    },
+    PriceChange {
+        #[serde(default)]
+        price_changes: Vec<WirePriceChange>,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+struct WireLevel {
+    price: String,
+    size: String,
+}
+
+#[derive(Deserialize)]
+struct WirePriceChange {
+    asset_id: String,
+    price: String,
+    size: String,
+    side: String,
+}
+
+fn parse_number(field: &'static str, raw: &str) -> Result<f64, ClobStreamError> {
+    raw.trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|value| value.is_finite())
+        .ok_or_else(|| ClobStreamError::InvalidNumber {
+            field,
+            value: raw.to_string(),
+        })
+}
+
+fn parse_levels(levels: &[WireLevel]) -> Result<Vec<BookLevel>, ClobStreamError> {
+    levels
+        .iter()
+        .map(|level| {
+            Ok(BookLevel {
+                price: parse_number("price", &level.price)?,
+                size: parse_number("size", &level.size)?,
+            })
+        })
+        .filter(|level| !matches!(level, Ok(BookLevel { size, .. }) if *size <= 0.0))
+        .collect()
+}
+
+/// Handle to the background websocket task. Cloning shares the books and the
+/// subscription set.
+#[derive(Debug, Clone)]
+pub struct ClobBookStream {
+    books: ClobBooks,
+    tokens: Arc<watch::Sender<Vec<String>>>,
+}
+
+impl ClobBookStream {
+    /// Starts streaming, or `None` when `cfg.ws_url` is unset. Nothing connects until
+    /// the first [`ClobBookStream::subscribe`] with at least one token.
+    pub fn spawn(cfg: ClobStreamConfig) -> Option<Self> {
+        let url = cfg.ws_url.clone()?;
+        let (tokens, rx) = watch::channel(Vec::new());
+        let stream = Self {
+            books: ClobBooks::new(),
+            tokens: Arc::new(tokens),
+        };
+        tokio::spawn(run_stream(url, cfg, stream.books.clone(), rx));
+        Some(stream)
+    }
+
+    /// Tracks `subscriptions`, reconnecting with the new token set when it changed.
+    pub fn subscribe(&self, subscriptions: &[BookSubscription]) {
+        let tokens = self.books.set_subscriptions(subscriptions);
+        self.tokens.send_if_modified(|current| {
+            if *current == tokens {
+                return false;
+            }
+            *current = tokens;
+            true
+        });
+    }
+
+    pub fn books(&self) -> &ClobBooks {
+        &self.books
+    }
+}
+
+impl OrderBookSource for ClobBookStream {
+    fn book(&self, slug: &str) -> Option<OrderBook> {
+        self.books.book(slug)
+    }
+
+    fn top_of_book(&self, slug: &str) -> Option<BookTop> {
+        self.books.top_of_book(slug)
+    }
+}
+
+async fn run_stream(
+    url: String,
+    cfg: ClobStreamConfig,
+    books: ClobBooks,
+    mut tokens: watch::Receiver<Vec<String>>,
+) {
+    let mut failures = 0u32;
+    loop {
+        let current = tokens.borrow_and_update().clone();
+        if current.is_empty() {
+            if tokens.changed().await.is_err() {
+                return;
+            }
+            continue;
+        }
+        let outcome = stream_once(&url, &cfg, &books, &current, &mut tokens).await;
+        books.clear();
+        match outcome {
+            // Token set changed: reconnect right away with the new one.
+            Ok(()) => failures = 0,
+            Err(err) => {
+                failures = failures.saturating_add(1);
+                let delay_ms = reconnect_delay_ms(failures, cfg.max_backoff_ms);
+                warn!(
+                    component = "clob_stream",
+                    event = "clob_stream.disconnected",
+                    consecutive_failures = failures,
+                    retry_in_ms = delay_ms,
+                    error = %err
+                );
+                tokio::select! {
+                    _ = tokio::time::sleep(std::time::Duration::from_millis(delay_ms)) => {}
+                    changed = tokens.changed() => if changed.is_err() { return },
+                }
+            }
+        }
+        if tokens.has_changed().is_err() {
+            return;
+        }
+    }
+}
+
+/// Streams until the token set changes (`Ok`) or the connection fails.
+async fn stream_once(
+    url: &str,
+    cfg: &ClobStreamConfig,
+    books: &ClobBooks,
+    token_ids: &[String],
+    tokens: &mut watch::Receiver<Vec<String>>,
+) -> Result<(), ClobStreamError> {
+    let (mut socket, _) = tokio_tungstenite::connect_async(url).await?;
+    let subscribe = serde_json::json!({ "assets_ids": token_ids, "type": "market" });
+    socket
+        .send(Message::Text(subscribe.to_string().into()))
+        .await?;
+    info!(
+        component = "clob_stream",
+        event = "clob_stream.subscribed",
+        tokens = token_ids.len()
+    );
+
+    let mut ping = tokio::time::interval(std::time::Duration::from_millis(cfg.ping_interval_ms));
+    ping.tick().await;
+    loop {
+        tokio::select! {
+            changed = tokens.changed() => {
+                if changed.is_err() {
+                    return Ok(());
+                }
+                let _ = socket.close(None).await;
+                return Ok(());
+            }
+            _ = ping.tick() => {
+                socket.send(Message::Text("PING".into())).await?;
+            }
+            message = socket.next() => {
+                let text = match message.ok_or(ClobStreamError::Closed)?? {
+                    Message::Text(text) => text,
+                    Message::Close(_) => return Err(ClobStreamError::Closed),
+                    _ => continue,
+                };
+                let now_ms_utc = crate::faults::now_utc().timestamp_millis();
+                if let Err(err) = books.apply_message(&text, now_ms_utc) {
+                    debug!(
+                        component = "clob_stream",
+                        event = "clob_stream.message_skipped",
+                        error = %err
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn reconnect_delay_ms(failures: u32, max_backoff_ms: u64) -> u64 {
+    let exponent = failures.saturating_sub(1).min(16);
+    500u64.saturating_mul(1 << exponent).min(max_backoff_ms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::slug::{Coin, Duration, SlugConfig};
+
+    fn subscription(token: &str) -> BookSubscription {
+        let key = DiscoveryKey::new(
+            Coin::BTC,
+            Duration::M5,
+            1_771_449_000,
+            SlugConfig::default(),
+        )
+        .unwrap();
+        let row = DiscoveryRow {
+            key,
+            status: DiscoveryStatus::Resolved {
+                market: MarketMeta {
+                    token_ids: vec![token.to_string(), "no-token".to_string()],
+                    ..MarketMeta::default()
+                },
+            },
+        };
+        BookSubscription::from_discovery_row(&row).unwrap()
+    }
+
+    #[test]
+    fn snapshots_and_deltas_maintain_the_yes_book() {
+        let books = ClobBooks::new();
+        assert_eq!(books.set_subscriptions(&[subscription("yes")]), ["yes"]);
+        let slug = "btc-updown-5m-1771449000";
+        assert_eq!(books.top_of_book(slug), None);
+
+        let snapshot = r#"[{"event_type":"book","asset_id":"yes","market":"0x1",
+            "bids":[{"price":"0.48","size":"30"},{"price":"0.50","size":"10"}],
+            "asks":[{"price":"0.55","size":"20"}],"timestamp":"1"}]"#;
+        assert_eq!(books.apply_message(snapshot, 1_000).unwrap(), 1);
+        let top = books.top_of_book(slug).unwrap();
+        assert_eq!((top.best_bid, top.best_ask), (Some(0.50), Some(0.55)));
+        assert_eq!((top.bid_size, top.ask_size), (10.0, 20.0));
+        assert_eq!(books.book(slug).unwrap().depth(), (40.0, 20.0));
+
+        let delta = r#"{"event_type":"price_change","market":"0x1","price_changes":[
+            {"asset_id":"yes","price":"0.50","size":"0","side":"BUY"},
+            {"asset_id":"yes","price":"0.53","size":"5","side":"SELL"},
+            {"asset_id":"other","price":"0.1","size":"5","side":"BUY"}]}"#;
+        assert_eq!(books.apply_message(delta, 2_000).unwrap(), 2);
+        let top = books.top_of_book(slug).unwrap();
+        assert_eq!((top.best_bid, top.best_ask), (Some(0.48), Some(0.53)));
+        assert_eq!(top.ts_ms, 2_000);
+
+        assert_eq!(books.apply_message("PONG", 3_000).unwrap(), 0);
+        assert_eq!(
+            books
+                .apply_message(r#"{"event_type":"last_trade_price"}"#, 3_000)
+                .unwrap(),
+            0
+        );
+        assert!(books.apply_message("{not json", 3_000).is_err());
+
+        books.set_subscriptions(&[]);
+        assert_eq!(books.token_book("yes"), None);
+        assert_eq!(books.top_of_book(slug), None);
+    }
+
+    #[test]
+    fn only_resolved_rows_with_tokens_subscribe() {
+        let mut row = DiscoveryRow {
+            key: subscription("yes").key,
+            status: DiscoveryStatus::Resolved {
+                market: MarketMeta::default(),
+            },
+        };
+        assert_eq!(BookSubscription::from_discovery_row(&row), None);
+        row.status = DiscoveryStatus::Unresolved {
+            reason: crate::discovery::UnresolvedReason::NotFound,
+        };
+        assert_eq!(BookSubscription::from_discovery_row(&row), None);
+    }
+
+    #[test]
+    fn reconnect_delay_backs_off_to_the_cap() {
+        assert_eq!(reconnect_delay_ms(1, 30_000), 500);
+        assert_eq!(reconnect_delay_ms(3, 30_000), 2_000);
+        assert_eq!(reconnect_delay_ms(40, 30_000), 30_000);
+    }
+}
@@ -0,0 +1,410 @@
+//! Local clock drift detection.
+//!
+//! Interval boundaries and settlement are computed from the local clock, so a skewed
+//! host silently shifts every `start_ts`/`end_ts`. The monitor periodically compares
+//! local time against an SNTP server and Binance's `/api/v3/time`, keeps the latest
+//! measurement for `/healthz`, and logs `clock.drift.exceeded` past the thresholds.
+//!
+//! Offsets are `reference - local`: positive means the local clock is behind.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use arc_swap::ArcSwapOption;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, error, warn};
+
+use crate::alerts::{AlertManager, AlertSeverity};
+use crate::env_vars::Settings;
+
+/// Seconds between the NTP epoch (1900-01-01) and the Unix epoch.
+const NTP_UNIX_EPOCH_DELTA_SECS: i64 = 2_208_988_800;
+const NTP_PACKET_LEN: usize = 48;
+/// Alert key fired by [`ClockDriftMonitor::spawn_with_alerts`].
+pub const CLOCK_DRIFT_ALERT_KEY: &str = "clock.drift";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClockDriftConfig {
+    /// `host:port` of an SNTP server; `None` skips the NTP source.
+    pub ntp_server: Option<String>,
+    /// Binance server-time endpoint; `None` skips the Binance source.
+    pub binance_time_url: Option<String>,
+    pub check_interval_ms: u64,
+    pub timeout_ms: u64,
+    pub warn_threshold_ms: i64,
+    pub alert_threshold_ms: i64,
+}
+
+impl Default for ClockDriftConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl ClockDriftConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_CLOCK_NTP_SERVER` and `PMM_CLOCK_BINANCE_TIME_URL` (`off` disables a
+    /// source), `PMM_CLOCK_CHECK_INTERVAL_MS`, `PMM_CLOCK_TIMEOUT_MS`,
+    /// `PMM_CLOCK_DRIFT_WARN_MS`, and `PMM_CLOCK_DRIFT_ALERT_MS`.
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            ntp_server: settings.get("PMM_CLOCK_NTP_SERVER"),
+            binance_time_url: settings.get("PMM_CLOCK_BINANCE_TIME_URL"),
+            check_interval_ms: settings.get_where("PMM_CLOCK_CHECK_INTERVAL_MS", |v| *v > 0),
+            timeout_ms: settings.get_where("PMM_CLOCK_TIMEOUT_MS", |v| *v > 0),
+            warn_threshold_ms: settings.get_where("PMM_CLOCK_DRIFT_WARN_MS", |v| *v > 0),
+            alert_threshold_ms: settings.get_where("PMM_CLOCK_DRIFT_ALERT_MS", |v| *v > 0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClockSource {
+    Ntp,
+    Binance,
+}
+
+impl ClockSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Ntp => "ntp",
+            Self::Binance => "binance",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClockDriftLevel {
+    /// No source answered.
+    Unknown,
+    Ok,
+    Warn,
+    Alert,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClockSample {
+    pub source: ClockSource,
+    pub offset_ms: i64,
+    pub rtt_ms: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClockSourceError {
+    pub source: ClockSource,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClockDriftStatus {
+    pub checked_at_ts_utc: i64,
+    /// Largest-magnitude offset across answering sources.
+    pub offset_ms: Option<i64>,
+    pub level: ClockDriftLevel,
+    pub samples: Vec<ClockSample>,
+    pub errors: Vec<ClockSourceError>,
+}
+
+/// Standard NTP offset from client send (`t0`), server receive (`t1`), server send
+/// (`t2`), and client receive (`t3`), all in Unix ms. Returns `(offset_ms, rtt_ms)`.
+fn ntp_offset_ms(t0: i64, t1: i64, t2: i64, t3: i64) -> (i64, i64) {
+    (((t1 - t0) + (t2 - t3)) / 2, (t3 - t0) - (t2 - t1))
+}
+
+/// Offset against a single server timestamp taken somewhere inside `[t0, t3]`,
+/// assuming it was taken at the midpoint.
+fn single_stamp_offset_ms(t0: i64, server_ms: i64, t3: i64) -> (i64, i64) {
+    (server_ms - (t0 + t3) / 2, t3 - t0)
+}
+
+fn clock_drift_level(offset_ms: Option<i64>, cfg: &ClockDriftConfig) -> ClockDriftLevel {
+    match offset_ms.map(i64::abs) {
+        None => ClockDriftLevel::Unknown,
+        Some(abs) if abs >= cfg.alert_threshold_ms => ClockDriftLevel::Alert,
+        Some(abs) if abs >= cfg.warn_threshold_ms => ClockDriftLevel::Warn,
+        Some(_) => ClockDriftLevel::Ok,
+    }
+}
+
+fn unix_ms_to_ntp(ms: i64) -> [u8; 8] {
+    let secs = (ms.div_euclid(1_000) + NTP_UNIX_EPOCH_DELTA_SECS) as u32;
+    let frac = ((ms.rem_euclid(1_000) as u64) << 32) / 1_000;
+    let mut out = [0u8; 8];
+    out[..4].copy_from_slice(&secs.to_be_bytes());
+    out[4..].copy_from_slice(&(frac as u32).to_be_bytes());
+    out
+}
+
+fn ntp_to_unix_ms(raw: &[u8]) -> i64 {
+    let secs = u32::from_be_bytes([raw[0], raw[1], raw[2], raw[3]]) as i64;
+    let frac = u32::from_be_bytes([raw[4], raw[5], raw[6], raw[7]]) as i64;
+    (secs - NTP_UNIX_EPOCH_DELTA_SECS) * 1_000 + ((frac * 1_000 + (1 << 31)) >> 32)
+}
+
+/// SNTP client request (v3, mode 3) carrying `t0` as the transmit timestamp.
+fn build_sntp_request(t0_ms: i64) -> [u8; NTP_PACKET_LEN] {
+    let mut packet = [0u8; NTP_PACKET_LEN];
+    packet[0] = 0x1B;
+    packet[40..48].copy_from_slice(&unix_ms_to_ntp(t0_ms));
+    packet
+}
+
+/// Server receive/transmit timestamps (Unix ms) from an SNTP reply to `request`.
+fn parse_sntp_reply(request: &[u8; NTP_PACKET_LEN], reply: &[u8]) -> Result<(i64, i64), String> {
+    if reply.len() < NTP_PACKET_LEN {
+        return Err(format!("short reply ({} bytes)", reply.len()));
+    }
+    let mode = reply[0] & 0x07;
+    if mode != 4 && mode != 5 {
+        return Err(format!("unexpected mode {mode}"));
+    }
+    if reply[1] == 0 {
+        return Err("kiss-of-death reply (stratum 0)".to_string());
+    }
+    if reply[24..32] != request[40..48] {
+        return Err("originate timestamp does not match request".to_string());
+    }
+    Ok((
+        ntp_to_unix_ms(&reply[32..40]),
+        ntp_to_unix_ms(&reply[40..48]),
+    ))
+}
+
+async fn sample_ntp(server: &str, timeout: Duration) -> Result<ClockSample, String> {
+    let addr: SocketAddr = tokio::net::lookup_host(server)
+        .await
+        .map_err(|err| format!("resolve {server}: {err}"))?
+        .next()
+        .ok_or_else(|| format!("resolve {server}: no addresses"))?;
+    let bind: SocketAddr = if addr.is_ipv4() {
+        "0.0.0.0:0"
+    } else {
+        "[::]:0"
+    }
+    .parse()
+    .expect("valid bind address");
+    let socket = tokio::net::UdpSocket::bind(bind)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    let t0 = Utc::now().timestamp_millis();
+    let request = build_sntp_request(t0);
+    let mut reply = [0u8; 128];
+    let len = tokio::time::timeout(timeout, async {
+        socket.send_to(&request, addr).await?;
+        socket.recv(&mut reply).await
+    })
+    .await
+    .map_err(|_| format!("timed out after {}ms", timeout.as_millis()))?
+    .map_err(|err| err.to_string())?;
+    let t3 = Utc::now().timestamp_millis();
+
+    let (t1, t2) = parse_sntp_reply(&request, &reply[..len])?;
+    let (offset_ms, rtt_ms) = ntp_offset_ms(t0, t1, t2, t3);
+    Ok(ClockSample {
+        source: ClockSource::Ntp,
+        offset_ms,
+        rtt_ms,
+    })
+}
+
+#[derive(Deserialize)]
+struct BinanceServerTime {
+    #[serde(rename = "serverTime")]
+    server_time: i64,
+}
+
+async fn sample_binance(client: &reqwest::Client, url: &str) -> Result<ClockSample, String> {
+    let t0 = Utc::now().timestamp_millis();
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|err| err.to_string())?;
+    let body = response.bytes().await.map_err(|err| err.to_string())?;
+    let t3 = Utc::now().timestamp_millis();
+    let parsed: BinanceServerTime =
+        serde_json::from_slice(&body).map_err(|err| format!("invalid body: {err}"))?;
+
+    let (offset_ms, rtt_ms) = single_stamp_offset_ms(t0, parsed.server_time, t3);
+    Ok(ClockSample {
+        source: ClockSource::Binance,
+        offset_ms,
+        rtt_ms,
+    })
+}
+
+/// Runs one check against every configured source.
+pub async fn measure_clock_drift(cfg: &ClockDriftConfig) -> ClockDriftStatus {
+    let timeout = Duration::from_millis(cfg.timeout_ms.max(1));
+    let mut samples = Vec::new();
+    let mut errors = Vec::new();
+    let mut record = |source: ClockSource, result: Result<ClockSample, String>| match result {
+        Ok(sample) => samples.push(sample),
+        Err(error) => errors.push(ClockSourceError { source, error }),
+    };
+
+    if let Some(server) = &cfg.ntp_server {
+        record(ClockSource::Ntp, sample_ntp(server, timeout).await);
+    }
+    if let Some(url) = &cfg.binance_time_url {
+        let result = match reqwest::Client::builder().timeout(timeout).build() {
+            Ok(client) => sample_binance(&client, url).await,
+            Err(err) => Err(err.to_string()),
+        };
+        record(ClockSource::Binance, result);
+    }
+
+    let offset_ms = samples
+        .iter()
+        .map(|sample| sample.offset_ms)
+        .max_by_key(|offset| offset.abs());
+    ClockDriftStatus {
+        checked_at_ts_utc: Utc::now().timestamp(),
+        offset_ms,
+        level: clock_drift_level(offset_ms, cfg),
+        samples,
+        errors,
+    }
+}
+
+/// Latest clock drift measurement, refreshed by a background task.
+#[derive(Debug, Clone, Default)]
+pub struct ClockDriftMonitor {
+    latest: Arc<ArcSwapOption<ClockDriftStatus>>,
+}
+
+impl ClockDriftMonitor {
+    /// Starts periodic checks on the current Tokio runtime. With no source configured
+    /// the monitor stays empty.
+    pub fn spawn(cfg: ClockDriftConfig) -> Self {
+        Self::spawn_with_alerts(cfg, None)
+    }
+
+    /// Like [`ClockDriftMonitor::spawn`], also firing the `clock.drift` alert while the
+    /// offset is past the warn threshold and clearing it once back within.
+    pub fn spawn_with_alerts(cfg: ClockDriftConfig, alerts: Option<AlertManager>) -> Self {
+        let monitor = Self::default();
+        if cfg.ntp_server.is_none() && cfg.binance_time_url.is_none() {
+            return monitor;
+        }
+        let monitor_bg = monitor.clone();
+        tokio::spawn(async move {
+            loop {
+                let status = measure_clock_drift(&cfg).await;
+                log_clock_drift(&status, &cfg);
+                if let Some(alerts) = &alerts {
+                    update_clock_alert(alerts, &status);
+                }
+                monitor_bg.record(status);
+                tokio::time::sleep(Duration::from_millis(cfg.check_interval_ms.max(1_000))).await;
+            }
+        });
+        monitor
+    }
+
+    pub fn record(&self, status: ClockDriftStatus) {
+        self.latest.store(Some(Arc::new(status)));
+    }
+
+    pub fn status(&self) -> Option<ClockDriftStatus> {
+        self.latest.load_full().map(|status| (*status).clone())
+    }
+}
+
+fn update_clock_alert(alerts: &AlertManager, status: &ClockDriftStatus) {
+    let severity = match status.level {
+        ClockDriftLevel::Alert => AlertSeverity::Critical,
+        ClockDriftLevel::Warn => AlertSeverity::Warn,
+        ClockDriftLevel::Ok => return alerts.clear(CLOCK_DRIFT_ALERT_KEY),
+        // No measurement says nothing about the clock; keep the current state.
+        ClockDriftLevel::Unknown => return,
+    };
+    let message = format!(
+        "local clock offset {}ms",
+        status.offset_ms.unwrap_or_default()
+    );
+    alerts.fire(CLOCK_DRIFT_ALERT_KEY, severity, &message);
+}
+
+fn log_clock_drift(status: &ClockDriftStatus, cfg: &ClockDriftConfig) {
+    for failure in &status.errors {
+        warn!(
+            component = "clock",
+            event = "clock.drift.source_error",
+            source = failure.source.as_str(),
+            error = %failure.error
+        );
+    }
+    let offset_ms = status.offset_ms.unwrap_or_default();
+    match status.level {
+        ClockDriftLevel::Alert => error!(
+            component = "clock",
+            event = "clock.drift.exceeded",
+            offset_ms,
+            threshold_ms = cfg.alert_threshold_ms,
+            level = "alert"
+        ),
+        ClockDriftLevel::Warn => warn!(
+            component = "clock",
+            event = "clock.drift.exceeded",
+            offset_ms,
+            threshold_ms = cfg.warn_threshold_ms,
+            level = "warn"
+        ),
+        ClockDriftLevel::Ok | ClockDriftLevel::Unknown => debug!(
+            component = "clock",
+            event = "clock.drift.checked",
+            offset_ms = ?status.offset_ms,
+            sources = status.samples.len()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ntp_offset_uses_symmetric_delay() {
+        // Local clock 500ms behind, 40ms each way, 10ms server processing.
+        let (offset, rtt) = ntp_offset_ms(1_000, 1_540, 1_550, 1_090);
+        assert_eq!((offset, rtt), (500, 80));
+        assert_eq!(single_stamp_offset_ms(1_000, 1_545, 1_090), (500, 90));
+
+        let cfg = ClockDriftConfig::default();
+        assert_eq!(clock_drift_level(None, &cfg), ClockDriftLevel::Unknown);
+        assert_eq!(clock_drift_level(Some(-249), &cfg), ClockDriftLevel::Ok);
+        assert_eq!(clock_drift_level(Some(-250), &cfg), ClockDriftLevel::Warn);
+        assert_eq!(clock_drift_level(Some(1_000), &cfg), ClockDriftLevel::Alert);
+    }
+
+    #[test]
+    fn sntp_reply_round_trips_timestamps() {
+        let t0 = 1_772_000_000_123;
+        assert_eq!(ntp_to_unix_ms(&unix_ms_to_ntp(t0)), t0);
+
+        let request = build_sntp_request(t0);
+        let mut reply = [0u8; NTP_PACKET_LEN];
+        reply[0] = 0x1C; // v3, server mode
+        reply[1] = 2;
+        reply[24..32].copy_from_slice(&request[40..48]);
+        reply[32..40].copy_from_slice(&unix_ms_to_ntp(t0 + 700));
+        reply[40..48].copy_from_slice(&unix_ms_to_ntp(t0 + 701));
+        assert_eq!(parse_sntp_reply(&request, &reply), Ok((t0 + 700, t0 + 701)));
+
+        let mut spoofed = reply;
+        spoofed[31] ^= 0xFF;
+        assert!(parse_sntp_reply(&request, &spoofed).is_err());
+        assert!(parse_sntp_reply(&request, &reply[..40]).is_err());
+    }
+}
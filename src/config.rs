@@ -0,0 +1,447 @@
+//! Central configuration file (`pmm.toml`).
+//!
+//! One TOML file with `[discovery]`, `[dashboard]`, `[klines]`, `[features]`,
+//! `[logging]`, and `[strategy]` sections (plus a top-level `mode`). Every key stands
+//! for a registered `PMM_*` variable (see [`crate::env_vars`]) and is checked with that
+//! variable's validator when the file loads, so a bad value fails with the key it came
+//! from instead of silently falling back to a default.
+//!
+//! [`load_settings`] layers the file's values under the process environment in one
+//! [`Settings`], so the environment overrides the file; binaries build their configs
+//! from it with each config's `from_settings`. Nothing is written back to the
+//! environment.
+//!
+//! ```toml
+//! mode = "paper"
+//!
+//! [discovery]
+//! coins = ["BTC", "ETH"]
+//! timeout_ms = 3000
+//!
+//! [logging]
+//! format = "json"
+//! dir = "logs"
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::env_vars::{env_var, Settings};
+
+/// File read by [`load_settings`] when `PMM_CONFIG` is unset.
+pub const DEFAULT_CONFIG_PATH: &str = "pmm.toml";
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("cannot read config file {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("invalid config file {path}: {source}")]
+    Parse {
+        path: PathBuf,
+        source: Box<toml::de::Error>,
+    },
+    #[error("invalid config values: {}", describe_invalid(.0))]
+    Invalid(Vec<InvalidConfigValue>),
+}
+
+/// A file value its variable's validator rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidConfigValue {
+    /// Dotted key, e.g. `logging.format`.
+    pub key: String,
+    pub env: &'static str,
+    pub value: String,
+    pub problem: String,
+}
+
+fn describe_invalid(invalid: &[InvalidConfigValue]) -> String {
+    invalid
+        .iter()
+        .map(|entry| {
+            format!(
+                "{}={:?} ({}): {}",
+                entry.key, entry.value, entry.env, entry.problem
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// One file value in environment form.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigValue {
+    /// Dotted key, e.g. `logging.format`.
+    pub key: String,
+    pub env: &'static str,
+    pub value: String,
+}
+
+/// A config value rendered the way its variable is written.
+trait EnvText {
+    fn env_text(&self) -> String;
+}
+
+impl EnvText for u64 {
+    fn env_text(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl EnvText for f64 {
+    fn env_text(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl EnvText for bool {
+    fn env_text(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl EnvText for String {
+    fn env_text(&self) -> String {
+        self.clone()
+    }
+}
+
+impl EnvText for PathBuf {
+    fn env_text(&self) -> String {
+        self.display().to_string()
+    }
+}
+
+/// Lists are comma-joined, as the list variables expect.
+impl EnvText for Vec<String> {
+    fn env_text(&self) -> String {
+        self.join(",")
+    }
+}
+
+/// Declares a config section: each field is optional and names the variable it sets.
+macro_rules! config_section {
+    (
+        $(#[$meta:meta])*
+        $name:ident {
+            $($(#[$field_meta:meta])* $field:ident: $ty:ty => $env:literal,)+
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+        #[serde(default, deny_unknown_fields)]
+        pub struct $name {
+            $($(#[$field_meta])* pub $field: Option<$ty>,)+
+        }
+
+        impl $name {
+            fn push_values(&self, section: &str, out: &mut Vec<ConfigValue>) {
+                $(
+                    if let Some(value) = &self.$field {
+                        out.push(ConfigValue {
+                            key: format!("{section}.{}", stringify!($field)),
+                            env: $env,
+                            value: value.env_text(),
+                        });
+                    }
+                )+
+            }
+        }
+    };
+}
+
+config_section! {
+    /// Market discovery and the Gamma API.
+    DiscoverySection {
+        coins: Vec<String> => "PMM_UNIVERSE_COINS",
+        durations: Vec<String> => "PMM_UNIVERSE_DURATIONS",
+        pairs: Vec<String> => "PMM_UNIVERSE_PAIRS",
+        timeout_ms: u64 => "PMM_DISCOVERY_TIMEOUT_MS",
+        batch_size: u64 => "PMM_DISCOVERY_BATCH_SIZE",
+        max_retries: u64 => "PMM_DISCOVERY_MAX_RETRIES",
+        retry_backoff_ms: u64 => "PMM_DISCOVERY_RETRY_BACKOFF_MS",
+        deadlines: bool => "PMM_DISCOVERY_DEADLINES",
+        min_call_budget_ms: u64 => "PMM_DISCOVERY_MIN_CALL_BUDGET_MS",
+        gamma_base_urls: Vec<String> => "PMM_GAMMA_BASE_URLS",
+    }
+}
+
+config_section! {
+    /// `dashboard_server`.
+    DashboardSection {
+        addr: String => "PMM_DASHBOARD_ADDR",
+        use_demo: bool => "PMM_DASHBOARD_USE_DEMO",
+        demo_speed: u64 => "PMM_DASHBOARD_DEMO_SPEED",
+        refresh_ms: u64 => "PMM_DASHBOARD_DISCOVERY_REFRESH_MS",
+        ready_max_stale_intervals: u64 => "PMM_READY_MAX_STALE_INTERVALS",
+        ready_check_klines: bool => "PMM_READY_CHECK_KLINES",
+        live_deadline_ms: u64 => "PMM_DASHBOARD_LIVE_DEADLINE_MS",
+        live_escalate_after: u64 => "PMM_DASHBOARD_LIVE_ESCALATE_AFTER",
+        live_degrade_after_mins: u64 => "PMM_DASHBOARD_LIVE_DEGRADE_AFTER_MINS",
+        live_max_backoff_ms: u64 => "PMM_DASHBOARD_LIVE_MAX_BACKOFF_MS",
+        stale_max_failures: u64 => "PMM_DASHBOARD_STALE_MAX_FAILURES",
+        dry_run_quotes: bool => "PMM_DASHBOARD_DRY_RUN_QUOTES",
+        number_locale: String => "PMM_DASHBOARD_NUMBER_LOCALE",
+        public_columns: Vec<String> => "PMM_DASHBOARD_PUBLIC_COLUMNS",
+        snapshot_store_path: String => "PMM_SNAPSHOT_STORE_PATH",
+        view_store_path: String => "PMM_DASHBOARD_VIEW_STORE_PATH",
+    }
+}
+
+config_section! {
+    /// The Binance kline store and its sync.
+    KlinesSection {
+        data_root: PathBuf => "PMM_BINANCE_DATA_ROOT",
+        store_path: PathBuf => "PMM_BINANCE_STORE_PATH",
+        start_date: String => "PMM_KLINE_START_DATE",
+        repair_range: String => "PMM_KLINE_REPAIR_RANGE",
+        scope_features: bool => "PMM_KLINE_SCOPE_FEATURES",
+        validation_rules: Vec<String> => "PMM_KLINE_VALIDATION_RULES",
+        validation_policy: String => "PMM_KLINE_VALIDATION_POLICY",
+        halt_min_secs: u64 => "PMM_KLINE_HALT_MIN_SECS",
+        compact_after_days: u64 => "PMM_KLINE_COMPACT_AFTER_DAYS",
+    }
+}
+
+config_section! {
+    /// Online features, warm-up, and models.
+    FeaturesSection {
+        warmup: bool => "PMM_WARMUP_ENABLED",
+        preset: String => "PMM_WARMUP_PRESET",
+        warmup_max_lag_ms: u64 => "PMM_WARMUP_MAX_LAG_MS",
+        model_dir: String => "PMM_MODEL_DIR",
+        model_input_store_path: String => "PMM_MODEL_INPUT_STORE_PATH",
+        model_input_retention_secs: u64 => "PMM_MODEL_INPUT_RETENTION_SECS",
+        probability_alpha: f64 => "PMM_PROB_SMOOTHING_ALPHA",
+        probability_hysteresis: f64 => "PMM_PROB_HYSTERESIS",
+        smoothed_display: bool => "PMM_PROB_SMOOTHING_DISPLAY",
+    }
+}
+
+config_section! {
+    /// Logging to stdout and files.
+    LoggingSection {
+        level: String => "PMM_LOG_LEVEL",
+        format: String => "PMM_LOG_FORMAT",
+        target: bool => "PMM_LOG_TARGET",
+        stdout: bool => "PMM_LOG_STDOUT",
+        dir: PathBuf => "PMM_LOG_DIR",
+        file_prefix: String => "PMM_LOG_FILE_PREFIX",
+        rotation: String => "PMM_LOG_ROTATION",
+        max_bytes: u64 => "PMM_LOG_MAX_BYTES",
+        max_files: u64 => "PMM_LOG_MAX_FILES",
+    }
+}
+
+config_section! {
+    /// The quoting engine.
+    StrategySection {
+        dry_run: bool => "PMM_STRATEGY_DRY_RUN",
+        half_spread: f64 => "PMM_STRATEGY_HALF_SPREAD",
+        order_usdc: f64 => "PMM_STRATEGY_ORDER_USDC",
+        skew_per_share: f64 => "PMM_STRATEGY_SKEW_PER_SHARE",
+        max_exposure_usdc: f64 => "PMM_STRATEGY_MAX_EXPOSURE_USDC",
+        /// Same syntax as the variable, e.g. `5m:half_spread=0.03;1d:order_usdc=5`.
+        overrides: String => "PMM_STRATEGY_OVERRIDES",
+        min_edge: f64 => "PMM_QUOTE_MIN_EDGE",
+        max_order_usdc: f64 => "PMM_QUOTE_MAX_ORDER_USDC",
+        max_position_usdc: f64 => "PMM_QUOTE_MAX_POSITION_USDC",
+    }
+}
+
+/// Typed contents of `pmm.toml`; unknown keys and sections are rejected.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PmmConfig {
+    /// `paper` or `live` (`PMM_MODE`).
+    pub mode: Option<String>,
+    pub discovery: DiscoverySection,
+    pub dashboard: DashboardSection,
+    pub klines: KlinesSection,
+    pub features: FeaturesSection,
+    pub logging: LoggingSection,
+    pub strategy: StrategySection,
+}
+
+impl PmmConfig {
+    /// Parses and validates TOML text; `path` only labels errors.
+    pub fn parse(text: &str, path: &Path) -> Result<Self, ConfigError> {
+        let config: Self = toml::from_str(text).map_err(|err| ConfigError::Parse {
+            path: path.to_path_buf(),
+            source: Box::new(err),
+        })?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Reads, parses, and validates the file at `path`.
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let text = std::fs::read_to_string(path).map_err(|source| ConfigError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::parse(&text, path)
+    }
+
+    /// Every value set in the file, in environment form.
+    pub fn values(&self) -> Vec<ConfigValue> {
+        let mut out = Vec::new();
+        if let Some(mode) = &self.mode {
+            out.push(ConfigValue {
+                key: "mode".to_string(),
+                env: "PMM_MODE",
+                value: mode.clone(),
+            });
+        }
+        self.discovery.push_values("discovery", &mut out);
+        self.dashboard.push_values("dashboard", &mut out);
+        self.klines.push_values("klines", &mut out);
+        self.features.push_values("features", &mut out);
+        self.logging.push_values("logging", &mut out);
+        self.strategy.push_values("strategy", &mut out);
+        out
+    }
+
+    /// Checks every value with its variable's validator.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let invalid: Vec<_> = self
+            .values()
+            .into_iter()
+            .filter_map(|entry| {
+                let var = env_var(entry.env).expect("config keys name registered variables");
+                let problem = var.kind.validate(&entry.value).err()?;
+                Some(InvalidConfigValue {
+                    key: entry.key,
+                    env: entry.env,
+                    value: entry.value,
+                    problem,
+                })
+            })
+            .collect();
+        if invalid.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Invalid(invalid))
+        }
+    }
+
+    /// The process environment over this file's values over the registry defaults.
+    pub fn settings(&self) -> Settings {
+        Settings::from_env().with_file_values(
+            self.values()
+                .into_iter()
+                .map(|entry| (entry.env, entry.value)),
+        )
+    }
+}
+
+/// The path named by `PMM_CONFIG` (`off` skips the file), else [`DEFAULT_CONFIG_PATH`]
+/// when it exists; `None` when there is no file to read.
+pub fn config_path() -> Option<PathBuf> {
+    match std::env::var("PMM_CONFIG") {
+        Ok(raw) if raw.trim().eq_ignore_ascii_case("off") => None,
+        Ok(raw) if !raw.trim().is_empty() => Some(PathBuf::from(raw.trim())),
+        _ => {
+            let default = PathBuf::from(DEFAULT_CONFIG_PATH);
+            default.is_file().then_some(default)
+        }
+    }
+}
+
+/// The settings every binary starts from: the file at [`config_path`] under the
+/// process environment, or the environment alone when there is no file.
+pub fn load_settings() -> Result<Settings, ConfigError> {
+    match config_path() {
+        Some(path) => Ok(PmmConfig::load(&path)?.settings()),
+        None => Ok(Settings::from_env()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sections_map_to_their_variables() {
+        let config = PmmConfig::parse(
+            r#"
+            mode = "live"
+
+            [discovery]
+            coins = ["BTC", "ETH"]
+            timeout_ms = 2500
+
+            [klines]
+            store_path = "/data/klines.sqlite"
+
+            [logging]
+            format = "json"
+            stdout = false
+
+            [strategy]
+            half_spread = 0.03
+            "#,
+            Path::new("pmm.toml"),
+        )
+        .unwrap();
+        let values: Vec<_> = config
+            .values()
+            .into_iter()
+            .map(|entry| (entry.env, entry.value))
+            .collect();
+        assert_eq!(
+            values,
+            [
+                ("PMM_MODE", "live"),
+                ("PMM_UNIVERSE_COINS", "BTC,ETH"),
+                ("PMM_DISCOVERY_TIMEOUT_MS", "2500"),
+                ("PMM_BINANCE_STORE_PATH", "/data/klines.sqlite"),
+                ("PMM_LOG_FORMAT", "json"),
+                ("PMM_LOG_STDOUT", "false"),
+                ("PMM_STRATEGY_HALF_SPREAD", "0.03"),
+            ]
+            .map(|(env, value)| (env, value.to_string()))
+        );
+    }
+
+    #[test]
+    fn settings_read_the_file_values() {
+        let config = PmmConfig::parse(
+            "[discovery]\nbatch_size = 7\n[logging]\nstdout = false\n",
+            Path::new("pmm.toml"),
+        )
+        .unwrap();
+        let settings = config.settings();
+        assert_eq!(settings.get::<u64>("PMM_DISCOVERY_BATCH_SIZE"), 7);
+        assert!(!settings.get::<bool>("PMM_LOG_STDOUT"));
+        assert!(settings.is_from_file("PMM_DISCOVERY_BATCH_SIZE"));
+        assert_eq!(std::env::var_os("PMM_DISCOVERY_BATCH_SIZE"), None);
+    }
+
+    #[test]
+    fn bad_values_and_unknown_keys_name_the_key() {
+        let err = PmmConfig::parse(
+            "mode = \"yolo\"\n[discovery]\ncoins = [\"BTC\", \"DOGEE\"]\n",
+            Path::new("pmm.toml"),
+        )
+        .unwrap_err();
+        let ConfigError::Invalid(invalid) = &err else {
+            panic!("{err}");
+        };
+        let keys: Vec<_> = invalid.iter().map(|entry| entry.key.as_str()).collect();
+        assert_eq!(keys, ["mode", "discovery.coins"]);
+        assert!(err.to_string().contains("DOGEE"));
+
+        let err =
+            PmmConfig::parse("[logging]\nformats = \"json\"\n", Path::new("pmm.toml")).unwrap_err();
+        assert!(matches!(err, ConfigError::Parse { .. }));
+        assert!(err.to_string().contains("formats"), "{err}");
+        let err =
+            PmmConfig::parse("[discovery]\ntimeout_ms = -1\n", Path::new("pmm.toml")).unwrap_err();
+        assert!(matches!(err, ConfigError::Parse { .. }));
+    }
+}
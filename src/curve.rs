@@ -0,0 +1,292 @@
+//! `/markets/{slug}/curve`: how one active market's estimate evolved within its
+//! interval.
+//!
+//! The model probability and YES book mid come from the [`SnapshotRecorder`] series;
+//! spot vs the interval reference price is recomputed from the kline store, so it is
+//! available at 1s resolution even for seconds the recorder did not sample. Every
+//! series is `[unix_ms, value]` pairs from the interval start up to now, oldest first.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use axum::extract::{Path as UrlPath, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+
+use crate::binance_klines::BinanceSymbol;
+use crate::dashboard::{DashboardRow, DashboardSnapshotSource};
+use crate::env_vars::Settings;
+use crate::grafana::{thin_points, SnapshotRecorder};
+use crate::kline_store::KlineStoreError;
+use crate::persistence::{open_kline_reader, KlineBackend, PersistenceBackend};
+use crate::slug::parse_coin;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarketCurveConfig {
+    pub store_path: PathBuf,
+    pub backend: PersistenceBackend,
+    /// Per-series point cap when the request does not pass `max_points`.
+    pub max_points: usize,
+}
+
+impl Default for MarketCurveConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl MarketCurveConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_BINANCE_STORE_PATH`, `PMM_DATABASE_URL`, and `PMM_CURVE_MAX_POINTS`,
+    /// falling back to defaults for missing/invalid values.
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            store_path: settings.get("PMM_BINANCE_STORE_PATH"),
+            backend: PersistenceBackend::from_settings(settings),
+            max_points: settings.get_where("PMM_CURVE_MAX_POINTS", |points| *points > 0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarketCurve {
+    pub slug: String,
+    pub coin: String,
+    pub duration: String,
+    pub start_ts_utc: i64,
+    pub end_ts_utc: i64,
+    pub now_ts_utc: i64,
+    /// Open of the first stored kline of the interval.
+    pub ref_price: Option<f64>,
+    pub probability: Vec<(i64, f64)>,
+    pub mid: Vec<(i64, f64)>,
+    /// `(close / ref_price - 1) * 100` per stored 1s kline, stamped at the kline's close.
+    pub spot_vs_ref_pct: Vec<(i64, f64)>,
+    /// Why `spot_vs_ref_pct` is empty when the kline store could not be read.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub klines_error: Option<String>,
+}
+
+/// Curve for `row` from the interval start up to `min(now, end)`; kline series are
+/// left empty when `klines` is `None`.
+pub fn market_curve(
+    row: &DashboardRow,
+    recorder: &SnapshotRecorder,
+    klines: Option<&mut dyn KlineBackend>,
+    now_ts_utc: i64,
+    max_points: usize,
+) -> Result<MarketCurve, KlineStoreError> {
+    let from_ms = row.start_ts_utc * 1_000;
+    let to_ms = now_ts_utc.min(row.end_ts_utc) * 1_000;
+    let series = |metric: &str| {
+        recorder.points(
+            &format!("{metric}:{}", row.slug),
+            from_ms,
+            to_ms,
+            max_points,
+        )
+    };
+    let mut curve = MarketCurve {
+        slug: row.slug.clone(),
+        coin: row.coin.clone(),
+        duration: row.duration.clone(),
+        start_ts_utc: row.start_ts_utc,
+        end_ts_utc: row.end_ts_utc,
+        now_ts_utc,
+        ref_price: None,
+        probability: series("probability"),
+        mid: series("mid"),
+        spot_vs_ref_pct: Vec::new(),
+        klines_error: None,
+    };
+    let (Some(klines), Ok(coin)) = (klines, parse_coin(&row.coin)) else {
+        return Ok(curve);
+    };
+    if from_ms >= to_ms {
+        return Ok(curve);
+    }
+    let Some(symbol) = BinanceSymbol::for_coin(coin) else {
+        return Ok(curve);
+    };
+    let Some(ref_price) = klines
+        .first_open_in_range(symbol, from_ms, to_ms)?
+        .map(|(_, open)| open)
+        .filter(|price| *price > 0.0)
+    else {
+        return Ok(curve);
+    };
+    let points = klines
+        .closes_range(symbol, from_ms, to_ms)?
+        .into_iter()
+        // A 1s kline's close is at the end of its second.
+        .map(|(open_time_ms, close)| (open_time_ms + 1_000, (close / ref_price - 1.0) * 100.0))
+        .collect();
+    curve.ref_price = Some(ref_price);
+    curve.spot_vs_ref_pct = thin_points(points, max_points);
+    Ok(curve)
+}
+
+#[derive(Clone)]
+struct CurveState {
+    source: Arc<dyn DashboardSnapshotSource>,
+    recorder: SnapshotRecorder,
+    cfg: Arc<MarketCurveConfig>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct CurveQuery {
+    max_points: Option<usize>,
+}
+
+/// Router serving `/markets/{slug}/curve` for markets in the current snapshot.
+pub fn curve_router(
+    source: Arc<dyn DashboardSnapshotSource>,
+    recorder: SnapshotRecorder,
+    cfg: MarketCurveConfig,
+) -> Router {
+    Router::new()
+        .route("/markets/{slug}/curve", get(get_market_curve))
+        .with_state(CurveState {
+            source,
+            recorder,
+            cfg: Arc::new(cfg),
+        })
+}
+
+async fn get_market_curve(
+    State(state): State<CurveState>,
+    UrlPath(slug): UrlPath<String>,
+    Query(query): Query<CurveQuery>,
+) -> Response {
+    let snapshot = state.source.snapshot();
+    let Some(row) = snapshot.rows.iter().find(|row| row.slug == slug).cloned() else {
+        return (StatusCode::NOT_FOUND, format!("no active market: {slug}")).into_response();
+    };
+    let now_ts_utc = state.source.now_ts_utc();
+    let max_points = query
+        .max_points
+        .filter(|points| *points > 0)
+        .unwrap_or(state.cfg.max_points);
+    let curve = tokio::task::spawn_blocking(move || {
+        let mut klines = match open_kline_reader(&state.cfg.backend, &state.cfg.store_path) {
+            Ok(klines) => klines,
+            Err(err) => {
+                let mut curve = market_curve(&row, &state.recorder, None, now_ts_utc, max_points)?;
+                curve.klines_error = Some(err.to_string());
+                return Ok(curve);
+            }
+        };
+        market_curve(
+            &row,
+            &state.recorder,
+            Some(klines.as_mut()),
+            now_ts_utc,
+            max_points,
+        )
+    })
+    .await;
+    match curve {
+        Ok(Ok(curve)) => Json(curve).into_response(),
+        Ok(Err(err)) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binance_klines::Kline1s;
+    use crate::dashboard::DashboardSnapshot;
+    use crate::kline_store::KlineStore;
+    use crate::kline_validation::KlineValidationConfig;
+
+    // 2025-01-01T00:00:00Z
+    const START: i64 = 1_735_689_600;
+
+    fn kline(open_time_ms: i64, open: f64, close: f64) -> Kline1s {
+        Kline1s {
+            open_time_ms,
+            open,
+            high: open.max(close),
+            low: open.min(close),
+            close,
+            volume: 1.0,
+            close_time_ms: open_time_ms + 999,
+            quote_asset_volume: close,
+            trade_count: 1,
+            taker_buy_base_volume: 0.5,
+            taker_buy_quote_volume: close / 2.0,
+        }
+    }
+
+    #[test]
+    fn curve_covers_the_interval_up_to_now() {
+        let mut row =
+            DashboardRow::unresolved_with_times("btc-updown-5m-x", "BTC", "5m", START, START + 300);
+        row.mock_columns.clear();
+        let recorder = SnapshotRecorder::new(3_600);
+        for (offset, probability, bid) in [
+            (-5, "0.40", "0.38"),
+            (5, "0.55", "0.52"),
+            (10, "0.60", "0.58"),
+        ] {
+            row.probability = Some(probability.to_string());
+            row.best_bid_yes = Some(bid.to_string());
+            row.best_ask_yes = Some("0.62".to_string());
+            recorder.record(
+                &DashboardSnapshot {
+                    rows: vec![row.clone()],
+                    seq: 1,
+                },
+                (START + offset) * 1_000,
+            );
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = KlineStore::open(
+            &dir.path().join("klines_1s.sqlite"),
+            KlineValidationConfig::default(),
+        )
+        .unwrap();
+        let rows = (0..12)
+            .map(|n| kline((START + n) * 1_000, 100.0 + n as f64, 101.0 + n as f64))
+            .collect();
+        store.upsert_rows(BinanceSymbol::BtcUsdt, rows).unwrap();
+
+        let mut curve_at = |now, max_points| {
+            market_curve(
+                &row,
+                &recorder,
+                Some(&mut store as &mut dyn KlineBackend),
+                now,
+                max_points,
+            )
+            .unwrap()
+        };
+        let curve = curve_at(START + 10, 1_000);
+        assert_eq!(curve.ref_price, Some(100.0));
+        assert_eq!(
+            curve.probability,
+            [((START + 5) * 1_000, 0.55), ((START + 10) * 1_000, 0.60)]
+        );
+        let (mid_ts, mid) = curve.mid[0];
+        assert_eq!(mid_ts, (START + 5) * 1_000);
+        assert!((mid - 0.57).abs() < 1e-9);
+        assert_eq!(curve.spot_vs_ref_pct.len(), 10);
+        let (spot_ts, spot_pct) = curve.spot_vs_ref_pct[0];
+        assert_eq!(spot_ts, (START + 1) * 1_000);
+        assert!((spot_pct - 1.0).abs() < 1e-9);
+
+        assert_eq!(curve_at(START + 10, 5).spot_vs_ref_pct.len(), 5);
+
+        let before_start = curve_at(START, 1_000);
+        assert!(before_start.probability.is_empty() && before_start.spot_vs_ref_pct.is_empty());
+    }
+}
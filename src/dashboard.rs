@@ -1,36 +1,102 @@
 //! Step 4 dashboard logic: filters, in-interval evaluation, formatting, and realtime rendering.
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::convert::Infallible;
 #[cfg(feature = "discovery-sdk")]
-use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 #[cfg(feature = "discovery-sdk")]
 use std::time::Instant;
 
+use arc_swap::{ArcSwap, ArcSwapOption};
 use axum::{
-    extract::{Query, State},
-    response::{Html, IntoResponse},
+    body::Body,
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, Query, State,
+    },
+    http::{
+        header::{CONTENT_DISPOSITION, CONTENT_TYPE},
+        HeaderMap, StatusCode,
+    },
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Html, IntoResponse, Response,
+    },
     routing::get,
     Json, Router,
 };
-use chrono::{Datelike, Days, TimeZone, Timelike, Utc};
-use chrono_tz::America::New_York;
+use chrono::{TimeZone, Utc};
+use futures_util::stream::{self, Stream};
 use serde::{Deserialize, Deserializer, Serialize};
+use thiserror::Error;
+use tokio::sync::watch;
 use tracing::info;
 #[cfg(feature = "discovery-sdk")]
 use tracing::{debug, error, warn};
 
+#[cfg(feature = "discovery-sdk")]
+use crate::alerts::{AlertManager, AlertSeverity};
+use crate::binance_klines::BinanceSymbol;
+use crate::binance_ws::PriceSource;
+#[cfg(feature = "discovery-sdk")]
+use crate::binance_ws::{BinanceWsConfig, BinanceWsStream};
+#[cfg(feature = "trading")]
+use crate::capital::CapitalSource;
+use crate::capital::{CapitalConfig, CapitalUsage, WalletReport};
+use crate::clob_stream::OrderBookSource;
+#[cfg(feature = "discovery-sdk")]
+use crate::clob_stream::{BookSubscription, ClobBookStream, ClobStreamConfig};
 use crate::discovery::{
-    build_previous_active_and_next_discovery_keys, DiscoveryWindow, ALL_COINS, ALL_DURATIONS,
+    build_previous_active_and_next_discovery_keys, DiscoveryWindow, ALL_DURATIONS,
 };
 #[cfg(feature = "discovery-sdk")]
 use crate::discovery::{
-    resolve_discovery_batch, DiscoveryConfig, DiscoveryRow, DiscoveryStatus, ScheduledDiscoveryKey,
-    SdkMarket, UnresolvedReason,
+    discovery_deadline_ts_ms, resolve_discovery_batch_with_sampler, DiscoveryConfig, DiscoveryRow,
+    DiscoveryStatus, DiscoveryUniverse, MarketMeta, ScheduledDiscoveryKey, UnresolvedReason,
+};
+use crate::env_vars::{EnvValue, Settings};
+use crate::fees::{FeeParams, FeeRegistry, CRYPTO_15_MIN_FEE_TYPE};
+use crate::fill_model::OrderSide;
+#[cfg(feature = "discovery-sdk")]
+use crate::gamma_endpoints::{GammaEndpointConfig, GammaEndpointPool, GammaEndpointStatus};
+#[cfg(feature = "discovery-sdk")]
+use crate::gamma_schema::{GammaPayloadConfig, GammaPayloadSampler};
+use crate::health::{readiness_report, ReadinessConfig};
+#[cfg(feature = "discovery-sdk")]
+use crate::model::{load_model_registry, ModelConfig};
+#[cfg(feature = "discovery-sdk")]
+use crate::model_inputs::ModelInputStore;
+use crate::model_inputs::{ModelInputRecord, ModelInputSource};
+use crate::models::ModelRegistry;
+use crate::persistence::open_kline_reader;
+#[cfg(feature = "discovery-sdk")]
+use crate::positions::PositionConfig;
+#[cfg(feature = "trading")]
+use crate::positions::PositionFeed;
+use crate::positions::PositionSource;
+use crate::preflight::RunMode;
+#[cfg(feature = "discovery-sdk")]
+use crate::quality::record_discovery_cycle;
+use crate::queue_position::QueueEstimate;
+#[cfg(feature = "discovery-sdk")]
+use crate::realized_vol::{RealizedVolConfig, RealizedVolTracker};
+use crate::redaction::{is_redacted, RedactionConfig};
+use crate::sizing::{kelly_stake, taker_fee_per_share, SizingConfig, SizingInput, SizingSide};
+use crate::slug::{parse_coin, Coin, Duration, MarketUnderlying, SlugConfig};
+use crate::smoothing::ProbabilitySmoother;
+#[cfg(feature = "discovery-sdk")]
+use crate::smoothing::ProbabilitySmoothingConfig;
+use crate::strategy::quote_feature_schema;
+use crate::strategy::{
+    interval_feature_row, plan_maker_quotes, PreMarketQuoteConfig, QuoteWindowInput,
 };
-use crate::slug::{Coin, Duration, SlugConfig};
+use crate::toggles::MarketToggles;
+use crate::views::{DashboardView, DashboardViewSpec, DashboardViews};
+#[cfg(feature = "discovery-sdk")]
+use crate::webhooks::{market_events_between, WebhookConfig, WebhookEmitter};
 
-pub const DASHBOARD_HEADERS: [&str; 21] = [
+pub const DASHBOARD_HEADERS: [&str; 27] = [
     "Link",
     "Coin",
     "Duration",
@@ -40,6 +106,9 @@ pub const DASHBOARD_HEADERS: [&str; 21] = [
     "Ref Price",
     "Price",
     "Probability",
+    "Realized Vol %",
+    "Price vs Ref %",
+    "Move Z",
     "Best Bid YES",
     "Best Ask YES",
     "Position Net",
@@ -47,14 +116,21 @@ pub const DASHBOARD_HEADERS: [&str; 21] = [
     "Pos NO",
     "Offer YES",
     "Offer NO",
+    "Suggested Size",
     "Net Profit",
     "Taker Fee %",
     "Maker Fee %",
     "Fee Exp",
     "Reward %",
+    "Volume 24h",
+    "Liquidity",
 ];
 
-pub const DASHBOARD_COLUMN_KEYS: [&str; 21] = [
+/// Seconds-to-expiry horizons [`apply_model_probabilities`] scores each row at; shown
+/// as extra probability columns with `?horizons=`.
+pub const PROBABILITY_HORIZONS_SECONDS: [u32; 3] = [60, 300, 900];
+
+pub const DASHBOARD_COLUMN_KEYS: [&str; 27] = [
     "link",
     "coin",
     "duration",
@@ -64,6 +140,9 @@ pub const DASHBOARD_COLUMN_KEYS: [&str; 21] = [
     "ref_price",
     "price",
     "probability",
+    "realized_vol",
+    "price_vs_ref_pct",
+    "move_z",
     "best_bid_yes",
     "best_ask_yes",
     "position_net",
@@ -71,24 +150,106 @@ pub const DASHBOARD_COLUMN_KEYS: [&str; 21] = [
     "pos_no",
     "offer_yes",
     "offer_no",
+    "suggested_size",
     "net_profit",
     "taker_fee_pct",
     "maker_fee_pct",
     "fee_exponent",
     "reward_pct",
+    "volume_24h",
+    "liquidity",
+];
+
+const DURATION_OPTIONS: [&str; Duration::ALL.len()] = {
+    let mut labels = [""; Duration::ALL.len()];
+    let mut i = 0;
+    while i < labels.len() {
+        labels[i] = Duration::ALL[i].as_label();
+        i += 1;
+    }
+    labels
+};
+const STREAM_TICK_MS: u64 = 250;
+/// Cadence the client script asks for (`min_interval`) while its tab is hidden.
+const HIDDEN_REFRESH_MS: u64 = 10_000;
+/// Changed rows the client script patches per animation frame; the rest wait for the
+/// next frame, so a burst over hundreds of rows never blocks the page for long.
+const ROW_PATCH_BUDGET: usize = 120;
+/// Longest `min_interval` a stream honours, so an idle client still hears of changes.
+const MAX_STREAM_INTERVAL_MS: u64 = 60_000;
+/// How long a push client of a publishing source waits for the next publish before
+/// re-checking anyway: time-derived columns and source status move without one.
+const PUBLISH_RECHECK_MS: u64 = 1_000;
+/// Columns that only carry information for durations with a fee schedule.
+const FEE_COLUMN_KEYS: [&str; 4] = [
+    "taker_fee_pct",
+    "maker_fee_pct",
+    "fee_exponent",
+    "reward_pct",
 ];
 
-const COIN_OPTIONS: [&str; 4] = ["BTC", "ETH", "SOL", "XRP"];
-const DURATION_OPTIONS: [&str; 5] = ["5m", "15m", "1h", "4h", "1d"];
+/// Whether `duration` markets charge taker fees (and so pay maker rewards); longer
+/// windows are fee-free.
+fn duration_has_fees(duration: Duration) -> bool {
+    matches!(duration, Duration::M5 | Duration::M15)
+}
+
+/// Poll and stream cadence for `duration` rows: short windows move fast enough to need
+/// every tick, longer ones refresh less often.
+fn duration_refresh_ms(duration: Duration) -> u64 {
+    match duration {
+        Duration::M5 => STREAM_TICK_MS,
+        Duration::M15 => 500,
+        Duration::H1 => 1_000,
+        Duration::H4 => 2_000,
+        Duration::D1 => 5_000,
+    }
+}
+
+/// Tickers of every registered coin, the dashboard's coin options.
+fn coin_options() -> Vec<&'static str> {
+    Coin::all().into_iter().map(Coin::ticker).collect()
+}
+
 #[cfg(feature = "discovery-sdk")]
 static DISCOVERY_CYCLE_SEQ: AtomicU64 = AtomicU64::new(1);
 const DASHBOARD_CLIENT_SCRIPT: &str = r#"<script>
 (function () {
-  const params = window.location.search;
+  // Duration routes (`/dashboard/5m`) pin the duration filter for every feed request.
+  const durationPreset = '__DURATION_PRESET__';
+  const query = new URLSearchParams(window.location.search);
+  if (durationPreset) {
+    query.set('duration', durationPreset);
+  }
+  const params = query.toString() ? '?' + query.toString() : '';
   const tbody = document.getElementById('dashboard-body');
   const rowCount = document.getElementById('row-count');
   const filterForm = document.getElementById('filters-form');
+  const liveBanner = document.getElementById('live-banner');
+  const liveBannerSince = document.getElementById('live-banner-since');
+  const liveBannerDegraded = document.getElementById('live-banner-degraded');
+  const liveBannerHealth = document.getElementById('live-banner-health');
+  const occupancyBody = document.getElementById('occupancy-body');
+  const pausedBadge = document.getElementById('paused-badge');
+  const activityList = document.getElementById('activity-list');
+  const walletHeadroom = document.getElementById('wallet-headroom');
   let inflight = false;
+  let pollTimer = null;
+  let stream = null;
+  let socket = null;
+  let occupancyFetchedAt = 0;
+  let activityFetchedAt = 0;
+  let walletFetchedAt = 0;
+  // Rows and seq of the last applied snapshot; deltas only apply on top of `lastSeq`.
+  let rows = [];
+  let lastSeq = null;
+  // Rendered rows by slug: `{tr, cells, title}`, `cells` holding each `<td>`'s markup.
+  const rowEls = new Map();
+  const cellTemplate = document.createElement('template');
+  const ROW_PATCH_BUDGET = __ROW_PATCH_BUDGET__;
+  // `?horizons=` probability columns, after the fixed ones.
+  const HORIZONS = [__PROBABILITY_HORIZONS__];
+  let renderFrame = null;
 
   function esc(v) {
     return String(v)
@@ -113,6 +274,44 @@ const DASHBOARD_CLIENT_SCRIPT: &str = r#"<script>
     return Array.isArray(row.mock_columns) && row.mock_columns.includes(key);
   }
 
+  function hasSimulated(row, key) {
+    return Array.isArray(row.simulated_columns) && row.simulated_columns.includes(key);
+  }
+
+  function queueTag(ahead) {
+    if (!ahead || ahead === '-') {
+      return '';
+    }
+    return ` <span class="queue-ahead" title="Estimated shares queued ahead">q ${esc(ahead)}</span>`;
+  }
+
+  function refDelayTag(delayMs) {
+    const ms = Number(delayMs);
+    if (!(ms > 0)) {
+      return '';
+    }
+    return ` <span class="ref-late" title="Reference captured ${ms}ms after the interval start">+${ms / 1000}s</span>`;
+  }
+
+  function negRiskTag(group) {
+    if (!group) {
+      return '';
+    }
+    return `<span class="neg-risk" title="Neg-risk event ${esc(group)}: shares collateral with its other markets">NR</span>`;
+  }
+
+  function rawTag(raw) {
+    if (!raw) {
+      return '';
+    }
+    return ` <span class="prob-raw" title="Model probability before smoothing">raw ${esc(raw)}</span>`;
+  }
+
+  function horizonCell(row, horizon) {
+    const byHorizon = row.probability_horizons || {};
+    return String(horizon) in byHorizon ? byHorizon[horizon] : '–';
+  }
+
   function tdClass(row, key, extra) {
     const cls = [];
     if (extra) {
@@ -120,38 +319,211 @@ const DASHBOARD_CLIENT_SCRIPT: &str = r#"<script>
     }
     if (hasMock(row, key)) {
       cls.push('cell-mock');
+    } else if (hasSimulated(row, key)) {
+      cls.push('cell-sim');
     }
     return cls.join(' ');
   }
 
-  function renderRow(row, idx) {
+  // Columns withheld from a public view are missing from the row JSON.
+  function cell(row, key) {
+    return key in row ? row[key] : '–';
+  }
+
+  // One `<td>` string per column; a row is patched cell by cell where these differ.
+  function rowCells(row) {
     const endLocal = localHHMM(Number(row.end_ts_utc));
-    return `<tr data-row="${idx}">
-      <td class="${tdClass(row, 'link', 'market-cell')}">
+    return [
+      `<td class="${tdClass(row, 'link', 'market-cell')}">
         <a class="market-btn" target="_blank" rel="noopener noreferrer" href="${esc(row.link_url)}">Open Market</a>
+        <button type="button" class="note-btn" data-slug="${esc(row.slug)}" title="Add a journal note">Note</button>${negRiskTag(row.neg_risk_group)}
         <span class="slug-id" title="${esc(row.slug)}">${esc(row.slug)}</span>
-      </td>
-      <td class="${tdClass(row, 'coin', '')}">${esc(row.coin)}</td>
-      <td class="${tdClass(row, 'duration', '')}">${esc(row.duration)}</td>
-      <td class="${tdClass(row, 'bets_open', '')}">${esc(row.bets_open)}</td>
-      <td class="${tdClass(row, 'in_interval', '')}">${esc(row.in_interval)}</td>
-      <td data-end-ts="${row.end_ts_utc}" class="${tdClass(row, 'end', '')}">${esc(endLocal)}</td>
-      <td class="${tdClass(row, 'ref_price', '')}">${esc(row.ref_price)}</td>
-      <td class="${tdClass(row, 'price', '')}">${esc(row.price)}</td>
-      <td class="${tdClass(row, 'probability', '')}">${esc(row.probability)}</td>
-      <td class="${tdClass(row, 'best_bid_yes', '')}">${esc(row.best_bid_yes)}</td>
-      <td class="${tdClass(row, 'best_ask_yes', '')}">${esc(row.best_ask_yes)}</td>
-      <td class="${tdClass(row, 'position_net', '')}">${esc(row.position_net)}</td>
-      <td class="${tdClass(row, 'pos_yes', '')}">${esc(row.pos_yes)}</td>
-      <td class="${tdClass(row, 'pos_no', '')}">${esc(row.pos_no)}</td>
-      <td class="${tdClass(row, 'offer_yes', '')}">${esc(row.offer_yes)}</td>
-      <td class="${tdClass(row, 'offer_no', '')}">${esc(row.offer_no)}</td>
-      <td class="${tdClass(row, 'net_profit', '')}">${esc(row.net_profit)}</td>
-      <td class="${tdClass(row, 'taker_fee_pct', '')}">${esc(row.taker_fee_pct)}</td>
-      <td class="${tdClass(row, 'maker_fee_pct', '')}">${esc(row.maker_fee_pct)}</td>
-      <td class="${tdClass(row, 'fee_exponent', '')}">${esc(row.fee_exponent)}</td>
-      <td class="${tdClass(row, 'reward_pct', '')}">${esc(row.reward_pct)}</td>
-    </tr>`;
+      </td>`,
+      `<td class="${tdClass(row, 'coin', '')}">${esc(row.coin)}</td>`,
+      `<td class="${tdClass(row, 'duration', '')}">${esc(row.duration)}</td>`,
+      `<td class="${tdClass(row, 'bets_open', '')}">${esc(row.bets_open)}</td>`,
+      `<td class="${tdClass(row, 'in_interval', '')}">${esc(row.in_interval)}</td>`,
+      `<td data-end-ts="${row.end_ts_utc}" class="${tdClass(row, 'end', '')}">${esc(endLocal)}</td>`,
+      `<td class="${tdClass(row, 'ref_price', '')}">${esc(cell(row, 'ref_price'))}${refDelayTag(row.ref_capture_delay_ms)}</td>`,
+      `<td class="${tdClass(row, 'price', '')}">${esc(cell(row, 'price'))}</td>`,
+      `<td class="${tdClass(row, 'probability', '')}">${esc(cell(row, 'probability'))}${rawTag(row.probability_raw)}</td>`,
+      `<td class="${tdClass(row, 'realized_vol', '')}">${esc(cell(row, 'realized_vol'))}</td>`,
+      `<td class="${tdClass(row, 'price_vs_ref_pct', '')}">${esc(cell(row, 'price_vs_ref_pct'))}</td>`,
+      `<td class="${tdClass(row, 'move_z', '')}">${esc(cell(row, 'move_z'))}</td>`,
+      `<td class="${tdClass(row, 'best_bid_yes', '')}">${esc(cell(row, 'best_bid_yes'))}</td>`,
+      `<td class="${tdClass(row, 'best_ask_yes', '')}">${esc(cell(row, 'best_ask_yes'))}</td>`,
+      `<td class="${tdClass(row, 'position_net', '')}">${esc(cell(row, 'position_net'))}</td>`,
+      `<td class="${tdClass(row, 'pos_yes', '')}">${esc(cell(row, 'pos_yes'))}</td>`,
+      `<td class="${tdClass(row, 'pos_no', '')}">${esc(cell(row, 'pos_no'))}</td>`,
+      `<td class="${tdClass(row, 'offer_yes', '')}">${esc(cell(row, 'offer_yes'))}${queueTag(row.queue_ahead_yes)}</td>`,
+      `<td class="${tdClass(row, 'offer_no', '')}">${esc(cell(row, 'offer_no'))}${queueTag(row.queue_ahead_no)}</td>`,
+      `<td class="${tdClass(row, 'suggested_size', '')}">${esc(cell(row, 'suggested_size'))}</td>`,
+      `<td class="${tdClass(row, 'net_profit', '')}">${esc(cell(row, 'net_profit'))}</td>`,
+      `<td class="${tdClass(row, 'taker_fee_pct', '')}">${esc(cell(row, 'taker_fee_pct'))}</td>`,
+      `<td class="${tdClass(row, 'maker_fee_pct', '')}">${esc(cell(row, 'maker_fee_pct'))}</td>`,
+      `<td class="${tdClass(row, 'fee_exponent', '')}">${esc(cell(row, 'fee_exponent'))}</td>`,
+      `<td class="${tdClass(row, 'reward_pct', '')}">${esc(cell(row, 'reward_pct'))}</td>`,
+      `<td class="${tdClass(row, 'volume_24h', '')}">${esc(cell(row, 'volume_24h'))}</td>`,
+      `<td class="${tdClass(row, 'liquidity', '')}">${esc(cell(row, 'liquidity'))}</td>`,
+      ...HORIZONS.map((h) => `<td class="${tdClass(row, 'probability', '')}">${esc(horizonCell(row, h))}</td>`),
+    ];
+  }
+
+  function rowTitle(row) {
+    return Number(row.stale_as_of_ts_utc) > 0
+      ? `Stale: last resolved ${localHHMM(Number(row.stale_as_of_ts_utc))}`
+      : '';
+  }
+
+  function parseCell(html) {
+    cellTemplate.innerHTML = html;
+    return cellTemplate.content.firstElementChild;
+  }
+
+  // Brings `entry.tr` in line with `row`, replacing only the cells whose markup changed.
+  function patchRow(entry, row, cells) {
+    const title = rowTitle(row);
+    if (title !== entry.title) {
+      entry.tr.className = title ? 'row-stale' : '';
+      if (title) {
+        entry.tr.title = title;
+      } else {
+        entry.tr.removeAttribute('title');
+      }
+      entry.title = title;
+    }
+    cells.forEach((html, i) => {
+      if (html !== entry.cells[i]) {
+        entry.tr.cells[i].replaceWith(parseCell(html));
+      }
+    });
+    entry.cells = cells;
+  }
+
+  function createRow(row, cells) {
+    const tr = document.createElement('tr');
+    tr.dataset.slug = row.slug;
+    tr.innerHTML = cells.join('');
+    const entry = { tr, cells, title: '' };
+    patchRow(entry, row, cells);
+    rowEls.set(row.slug, entry);
+    return entry;
+  }
+
+  function updateLiveBanner(status) {
+    if (!liveBanner || !liveBannerSince) {
+      return;
+    }
+    const since = status ? Number(status.live_unavailable_since_ts_utc) : NaN;
+    const down = !!status && Number(status.consecutive_failures) > 0 && since > 0;
+    liveBanner.hidden = !down;
+    if (down) {
+      liveBanner.setAttribute('data-since-ts', String(since));
+      liveBannerSince.textContent = localHHMM(since);
+      const degraded = Number(status.degraded_since_ts_utc) > 0;
+      liveBanner.classList.toggle('degraded', degraded);
+      if (liveBannerDegraded) {
+        liveBannerDegraded.hidden = !degraded;
+      }
+      if (liveBannerHealth) {
+        const score = status.health_score;
+        liveBannerHealth.textContent = score === null || score === undefined
+          ? ''
+          : ` (health ${Number(score)}/100)`;
+      }
+    }
+  }
+
+  function renderOccupancy(grid) {
+    const cells = Array.isArray(grid.cells) ? grid.cells : [];
+    occupancyBody.innerHTML = (grid.coins || []).map((coin) => {
+      const tds = cells.filter((cell) => cell.coin === coin).map((cell) => {
+        const attention = cell.needs_attention
+          ? ' class="occ-attention" title="Exposure left in an ended window"'
+          : '';
+        const dots = ['previous', 'active', 'next']
+          .map((w) => `<span class="occ-dot occ-${esc(cell[w])}" title="${w}: ${esc(cell[w])}"></span>`)
+          .join('');
+        return `<td${attention}>${dots}</td>`;
+      }).join('');
+      return `<tr><th>${esc(coin)}</th>${tds}</tr>`;
+    }).join('');
+  }
+
+  async function refreshOccupancy() {
+    // Unfiltered and slow-moving: at most one fetch per second.
+    const now = Date.now();
+    if (!occupancyBody || now - occupancyFetchedAt < 1000) {
+      return;
+    }
+    occupancyFetchedAt = now;
+    try {
+      const r = await fetch('/dashboard/occupancy', { cache: 'no-store' });
+      if (r.ok) {
+        renderOccupancy(await r.json());
+      }
+    } catch (_err) {
+      // Keep the last grid on transient failures.
+    }
+  }
+
+  function renderActivity(feed) {
+    const events = Array.isArray(feed.events) ? feed.events : [];
+    if (events.length === 0) {
+      activityList.innerHTML = '<li class="act-empty">No changes yet</li>';
+      return;
+    }
+    activityList.innerHTML = events.map((e) =>
+      `<li class="act act-${esc(e.kind)}"><span class="act-time" data-ts="${esc(e.ts_utc)}">${esc(localHHMM(Number(e.ts_utc)))}</span> <b>${esc(e.coin)} ${esc(e.duration)}</b> ${esc(String(e.kind).replace(/_/g, ' '))} <span class="act-detail">${esc(e.detail)}</span> <span class="slug-id">${esc(e.slug)}</span></li>`
+    ).join('');
+  }
+
+  async function refreshActivity() {
+    // Same cadence as the occupancy grid.
+    const now = Date.now();
+    if (!activityList || now - activityFetchedAt < 1000) {
+      return;
+    }
+    activityFetchedAt = now;
+    try {
+      const r = await fetch('/dashboard/activity', { cache: 'no-store' });
+      if (r.ok) {
+        renderActivity(await r.json());
+      }
+    } catch (_err) {
+      // Keep the last feed on transient failures.
+    }
+  }
+
+  function renderWallet(report) {
+    const usage = report.capital;
+    if (!usage || usage.headroom_usdc === null || usage.headroom_usdc === undefined) {
+      walletHeadroom.innerHTML = 'Headroom: <b>-</b>';
+      return;
+    }
+    const used = Math.round(Number(usage.utilization) * 100);
+    const scale = Number(report.quote_scale);
+    const state = scale <= 0 ? ' headroom-out' : (scale < 1 ? ' headroom-low' : '');
+    walletHeadroom.className = 'headroom' + state;
+    walletHeadroom.title = `Balance ${Number(usage.balance_usdc).toFixed(2)} USDC; bids lock ${Number(usage.order_collateral_usdc).toFixed(2)}, positions cost ${Number(usage.position_cost_usdc).toFixed(2)}; quotes at ${Math.round(scale * 100)}%`;
+    walletHeadroom.innerHTML = `Headroom: <b>$${esc(Number(usage.headroom_usdc).toFixed(2))}</b> (${esc(used)}% used)`;
+  }
+
+  async function refreshWallet() {
+    // Same cadence as the occupancy grid; the wallet itself is polled every few seconds.
+    const now = Date.now();
+    if (!walletHeadroom || now - walletFetchedAt < 1000) {
+      return;
+    }
+    walletFetchedAt = now;
+    try {
+      const r = await fetch('/wallet', { cache: 'no-store' });
+      if (r.ok) {
+        renderWallet(await r.json());
+      }
+    } catch (_err) {
+      // Keep the last figure on transient failures.
+    }
   }
 
   function rewriteExistingEndCells() {
@@ -161,6 +533,98 @@ const DASHBOARD_CLIENT_SCRIPT: &str = r#"<script>
     });
   }
 
+  // Rows are keyed by slug: unchanged rows are left alone, changed ones patched in
+  // place, and the DOM reordered only where it differs from `rows`. Work is coalesced
+  // into one pass per animation frame (none while the tab is hidden) and capped at
+  // `__ROW_PATCH_BUDGET__` row patches; rows over the cap keep their last content until
+  // the next frame.
+  function patchRows() {
+    renderFrame = null;
+    const live = new Set(rows.map((row) => row.slug));
+    for (const [slug, entry] of rowEls) {
+      if (!live.has(slug)) {
+        entry.tr.remove();
+        rowEls.delete(slug);
+      }
+    }
+    Array.from(tbody.children).forEach((tr) => {
+      const entry = rowEls.get(tr.dataset.slug);
+      if (!entry || entry.tr !== tr) {
+        // Server-rendered rows are replaced on the first pass.
+        tr.remove();
+      }
+    });
+    let budget = ROW_PATCH_BUDGET;
+    let deferred = false;
+    let prev = null;
+    rows.forEach((row) => {
+      let entry = rowEls.get(row.slug);
+      if (budget > 0) {
+        const cells = rowCells(row);
+        if (!entry) {
+          entry = createRow(row, cells);
+          budget -= 1;
+        } else if (cells.some((html, i) => html !== entry.cells[i]) || rowTitle(row) !== entry.title) {
+          patchRow(entry, row, cells);
+          budget -= 1;
+        }
+      } else {
+        deferred = true;
+      }
+      if (!entry) {
+        return;
+      }
+      const slot = prev ? prev.nextElementSibling : tbody.firstElementChild;
+      if (slot !== entry.tr) {
+        tbody.insertBefore(entry.tr, slot);
+      }
+      prev = entry.tr;
+    });
+    if (deferred) {
+      renderFrame = requestAnimationFrame(patchRows);
+    }
+  }
+
+  function renderRows() {
+    if (rowCount) {
+      rowCount.textContent = String(rows.length);
+    }
+    if (renderFrame === null) {
+      renderFrame = requestAnimationFrame(patchRows);
+    }
+  }
+
+  function applySnapshot(payload) {
+    rows = Array.isArray(payload.rows) ? payload.rows : [];
+    lastSeq = Number(payload.seq);
+    if (pausedBadge && !document.hidden) {
+      pausedBadge.hidden = true;
+    }
+    renderRows();
+    updateLiveBanner(payload.source_status);
+    refreshOccupancy();
+    refreshActivity();
+    refreshWallet();
+  }
+
+  function applyDelta(delta) {
+    if (lastSeq === null || Number(delta.prev_seq) !== lastSeq) {
+      // Missed an update (or never had a base): full fetch, later deltas re-check.
+      refresh();
+      return;
+    }
+    const bySlug = new Map(rows.map((row) => [row.slug, row]));
+    (delta.removed || []).forEach((slug) => bySlug.delete(slug));
+    (delta.upserts || []).forEach((row) => bySlug.set(row.slug, row));
+    rows = (delta.order || []).map((slug) => bySlug.get(slug)).filter(Boolean);
+    lastSeq = Number(delta.seq);
+    renderRows();
+    updateLiveBanner(delta.source_status);
+    refreshOccupancy();
+    refreshActivity();
+    refreshWallet();
+  }
+
   async function refresh() {
     if (inflight) {
       return;
@@ -171,12 +635,7 @@ const DASHBOARD_CLIENT_SCRIPT: &str = r#"<script>
       if (!r.ok) {
         return;
       }
-      const payload = await r.json();
-      const rows = Array.isArray(payload.rows) ? payload.rows : [];
-      tbody.innerHTML = rows.map((row, idx) => renderRow(row, idx)).join('');
-      if (rowCount) {
-        rowCount.textContent = String(rows.length);
-      }
+      applySnapshot(await r.json());
     } catch (_err) {
       // Keep UI stale on transient polling/network failures.
     } finally {
@@ -184,20 +643,164 @@ const DASHBOARD_CLIENT_SCRIPT: &str = r#"<script>
     }
   }
 
+  // Hidden tabs ask the server for at most one update per `__HIDDEN_REFRESH_MS__`ms.
+  function streamParams() {
+    if (!document.hidden) {
+      return params;
+    }
+    const slowed = new URLSearchParams(query);
+    slowed.set('min_interval', '__HIDDEN_REFRESH_MS__');
+    return '?' + slowed.toString();
+  }
+
+  function startPolling() {
+    clearInterval(pollTimer);
+    pollTimer = document.hidden
+      ? setInterval(refresh, __HIDDEN_REFRESH_MS__)
+      : setInterval(refresh, __REFRESH_MS__);
+  }
+
+  function startStream() {
+    if (stream) {
+      stream.close();
+    }
+    const current = new EventSource('/dashboard/stream' + streamParams());
+    stream = current;
+    current.addEventListener('snapshot', (e) => applySnapshot(JSON.parse(e.data)));
+    current.addEventListener('delta', (e) => applyDelta(JSON.parse(e.data)));
+    current.onerror = () => {
+      // The browser reconnects on its own and the server opens with a full snapshot;
+      // only fall back to polling once the stream is closed for good.
+      if (current === stream && current.readyState === EventSource.CLOSED) {
+        stream = null;
+        startPolling();
+      }
+    };
+  }
+
+  // Preferred transport: the server pushes on every publish over `/dashboard/ws`. A
+  // dropped socket reconnects (and opens with a full snapshot again); one that never
+  // opened, e.g. behind a proxy without WebSocket support, falls back to the event
+  // stream or polling.
+  function startSocket() {
+    if (socket) {
+      socket.onclose = null;
+      socket.close();
+    }
+    const scheme = window.location.protocol === 'https:' ? 'wss:' : 'ws:';
+    const current = new WebSocket(`${scheme}//${window.location.host}/dashboard/ws${streamParams()}`);
+    let opened = false;
+    socket = current;
+    current.onopen = () => {
+      opened = true;
+    };
+    current.onmessage = (e) => {
+      const push = JSON.parse(e.data);
+      if (push.type === 'delta') {
+        applyDelta(push);
+      } else {
+        applySnapshot(push);
+      }
+    };
+    current.onclose = () => {
+      if (current !== socket) {
+        return;
+      }
+      socket = null;
+      if (opened) {
+        setTimeout(startSocket, 2000);
+      } else if (typeof EventSource === 'function') {
+        startStream();
+      } else {
+        startPolling();
+      }
+    };
+  }
+
+  // Slow down while hidden; on return, resync at once. A reopened socket or stream
+  // starts with a full snapshot, so no delta is applied across the gap. The badge stays up until
+  // that fresh snapshot lands.
+  function onVisibilityChange() {
+    if (pausedBadge && document.hidden) {
+      pausedBadge.hidden = false;
+    }
+    if (socket) {
+      startSocket();
+    } else if (stream) {
+      startStream();
+    } else if (pollTimer !== null) {
+      startPolling();
+      if (!document.hidden) {
+        refresh();
+      }
+    }
+  }
+
+  // Journal notes go to `POST /markets/{slug}/journal`; the author is remembered in
+  // this browser.
+  async function addJournalNote(slug) {
+    const text = window.prompt(`Journal note for ${slug}`);
+    if (!text || !text.trim()) {
+      return;
+    }
+    const saved = window.localStorage.getItem('pmm.journal.author') || '';
+    const author = (window.prompt('Author', saved) || '').trim();
+    if (!author) {
+      return;
+    }
+    window.localStorage.setItem('pmm.journal.author', author);
+    try {
+      const r = await fetch(`/markets/${encodeURIComponent(slug)}/journal`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ author, text: text.trim() }),
+      });
+      if (!r.ok) {
+        window.alert(`Note not saved: ${r.status} ${await r.text()}`);
+      }
+    } catch (_) {
+      window.alert('Note not saved: journal unreachable');
+    }
+  }
+
   rewriteExistingEndCells();
+  tbody.addEventListener('click', (e) => {
+    const button = e.target.closest('.note-btn');
+    if (button) {
+      addJournalNote(button.getAttribute('data-slug'));
+    }
+  });
+  if (liveBanner && liveBannerSince && !liveBanner.hidden) {
+    liveBannerSince.textContent = localHHMM(Number(liveBanner.getAttribute('data-since-ts')));
+  }
   if (filterForm) {
     filterForm.addEventListener('change', () => {
       const next = new URLSearchParams(new FormData(filterForm)).toString();
-      window.location.assign(next ? `/dashboard?${next}` : '/dashboard');
+      const base = filterForm.getAttribute('action');
+      window.location.assign(next ? `${base}?${next}` : base);
     });
   }
-  setInterval(refresh, 250);
+  if (pausedBadge) {
+    pausedBadge.hidden = !document.hidden;
+  }
+  document.addEventListener('visibilitychange', onVisibilityChange);
+  if (typeof WebSocket === 'function') {
+    startSocket();
+  } else if (typeof EventSource === 'function') {
+    startStream();
+  } else {
+    startPolling();
+  }
 })();
 </script>"#;
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct DashboardSnapshot {
     pub rows: Vec<DashboardRow>,
+    /// Publish sequence number, stamped by the snapshot source on every publish.
+    /// Strictly increasing for one source; builders leave it at `0`.
+    #[serde(default)]
+    pub seq: u64,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -211,8 +814,34 @@ pub struct DashboardRow {
     pub in_interval: Option<String>,
     pub end_hhmm: Option<String>,
     pub ref_price: Option<String>,
+    /// How long after the interval start the kline behind `ref_price` opened, when the
+    /// reference came from the kline store (see `apply_kline_columns`).
+    #[serde(default)]
+    pub ref_capture_delay_ms: Option<i64>,
     pub price: Option<String>,
     pub probability: Option<String>,
+    /// Model probability before smoothing; set with `probability_smoothed` while a
+    /// [`ProbabilitySmoother`] is enabled (see [`apply_probability_smoothing`]).
+    #[serde(default)]
+    pub probability_raw: Option<String>,
+    /// Smoothed probability that dry-run quotes act on.
+    #[serde(default)]
+    pub probability_smoothed: Option<String>,
+    /// Model probability of the current move with that many seconds left, per horizon
+    /// of [`PROBABILITY_HORIZONS_SECONDS`] that fits in the interval.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub probability_horizons: BTreeMap<u32, String>,
+    /// Realized volatility of the coin since the interval started, in percent
+    /// (annualized or per-interval, see `RealizedVolConfig`).
+    #[serde(default)]
+    pub realized_vol: Option<String>,
+    /// Coin spot vs the interval reference price, in percent.
+    #[serde(default)]
+    pub price_vs_ref_pct: Option<String>,
+    /// The same move in standard deviations of trailing volatility over the elapsed
+    /// part of the interval.
+    #[serde(default)]
+    pub move_z: Option<String>,
     pub best_bid_yes: Option<String>,
     pub best_ask_yes: Option<String>,
     pub position_net: Option<String>,
@@ -220,15 +849,51 @@ pub struct DashboardRow {
     pub pos_no: Option<String>,
     pub offer_yes: Option<String>,
     pub offer_no: Option<String>,
+    /// Estimated shares queued ahead of the resting order in `offer_yes` / `offer_no`
+    /// (see [`apply_queue_estimates`]).
+    #[serde(default)]
+    pub queue_ahead_yes: Option<String>,
+    #[serde(default)]
+    pub queue_ahead_no: Option<String>,
+    #[serde(default)]
+    pub suggested_size: Option<String>,
     pub net_profit: Option<String>,
     pub taker_fee_pct: Option<String>,
     pub maker_fee_pct: Option<String>,
     pub fee_exponent: Option<String>,
     pub reward_pct: Option<String>,
+    /// Gamma's traded volume over the last 24 hours, in USDC.
+    #[serde(default)]
+    pub volume_24h: Option<String>,
+    /// Gamma's order book liquidity, in USDC.
+    #[serde(default)]
+    pub liquidity: Option<String>,
     pub mock_columns: Vec<String>,
+    /// Columns filled from the strategy's would-be output in dry-run mode rather than
+    /// from live state (see [`apply_dry_run_quotes`]).
+    #[serde(default)]
+    pub simulated_columns: Vec<String>,
+    /// Set when the row carries last-known-good metadata after a failed lookup:
+    /// the UTC time the metadata was last resolved.
+    #[serde(default)]
+    pub stale_as_of_ts_utc: Option<i64>,
+    /// UTC time the market started accepting orders, as reported by Gamma. 4h/1d
+    /// markets open well before their interval starts.
+    #[serde(default)]
+    pub accepting_orders_ts_utc: Option<i64>,
+    /// Set for markets of a neg-risk event: the group they share collateral with (see
+    /// [`MarketMeta::neg_risk_group`]).
+    #[serde(default)]
+    pub neg_risk_group: Option<String>,
 }
 
 impl DashboardRow {
+    /// What the row's market tracks. `coin` holds a [`MarketUnderlying::label`], so pair
+    /// rows read `ETH/BTC` and per-coin overlays (klines, Binance prices) skip them.
+    pub fn underlying(&self) -> Option<MarketUnderlying> {
+        MarketUnderlying::parse(&self.coin).ok()
+    }
+
     pub fn unresolved(
         slug: impl Into<String>,
         coin: impl Into<String>,
@@ -254,8 +919,15 @@ impl DashboardRow {
             in_interval: None,
             end_hhmm: None,
             ref_price: None,
+            ref_capture_delay_ms: None,
             price: None,
             probability: None,
+            probability_raw: None,
+            probability_smoothed: None,
+            probability_horizons: BTreeMap::new(),
+            realized_vol: None,
+            price_vs_ref_pct: None,
+            move_z: None,
             best_bid_yes: None,
             best_ask_yes: None,
             position_net: None,
@@ -263,27 +935,48 @@ impl DashboardRow {
             pos_no: None,
             offer_yes: None,
             offer_no: None,
+            queue_ahead_yes: None,
+            queue_ahead_no: None,
+            suggested_size: None,
             net_profit: None,
             taker_fee_pct: None,
             maker_fee_pct: None,
             fee_exponent: None,
             reward_pct: None,
+            volume_24h: None,
+            liquidity: None,
             mock_columns: default_mock_columns(),
+            simulated_columns: Vec::new(),
+            stale_as_of_ts_utc: None,
+            accepting_orders_ts_utc: None,
+            neg_risk_group: None,
         }
     }
 
     pub fn is_mock_column(&self, column_key: &str) -> bool {
         self.mock_columns.iter().any(|entry| entry == column_key)
     }
+
+    pub fn is_simulated_column(&self, column_key: &str) -> bool {
+        self.simulated_columns
+            .iter()
+            .any(|entry| entry == column_key)
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct DashboardDisplaySnapshot {
     pub now_ts_utc: i64,
+    #[serde(default)]
+    pub seq: u64,
     pub rows: Vec<DashboardDisplayRow>,
+    #[serde(default)]
+    pub source_status: SnapshotStatus,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Cells withheld from a public request (see [`RedactionConfig`]) hold
+/// [`crate::REDACTED`] and are left out of the JSON; they deserialize as empty.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct DashboardDisplayRow {
     pub slug: String,
     pub link_url: String,
@@ -294,22 +987,68 @@ pub struct DashboardDisplayRow {
     pub bets_open: String,
     pub in_interval: String,
     pub end_hhmm: String,
+    #[serde(default, skip_serializing_if = "is_redacted")]
     pub ref_price: String,
+    #[serde(default)]
+    pub ref_capture_delay_ms: Option<i64>,
+    #[serde(default, skip_serializing_if = "is_redacted")]
     pub price: String,
+    #[serde(default, skip_serializing_if = "is_redacted")]
     pub probability: String,
+    /// Raw model probability while `probability` shows a smoothed value that differs.
+    #[serde(default, skip_serializing_if = "is_redacted")]
+    pub probability_raw: String,
+    /// Probabilities of the `?horizons=` query by seconds to expiry, `-` where the row
+    /// has none. Empty, and left out of the JSON, without the query or when
+    /// `probability` is withheld.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub probability_horizons: BTreeMap<u32, String>,
+    #[serde(default, skip_serializing_if = "is_redacted")]
+    pub realized_vol: String,
+    #[serde(default, skip_serializing_if = "is_redacted")]
+    pub price_vs_ref_pct: String,
+    #[serde(default, skip_serializing_if = "is_redacted")]
+    pub move_z: String,
+    #[serde(default, skip_serializing_if = "is_redacted")]
     pub best_bid_yes: String,
+    #[serde(default, skip_serializing_if = "is_redacted")]
     pub best_ask_yes: String,
+    #[serde(default, skip_serializing_if = "is_redacted")]
     pub position_net: String,
+    #[serde(default, skip_serializing_if = "is_redacted")]
     pub pos_yes: String,
+    #[serde(default, skip_serializing_if = "is_redacted")]
     pub pos_no: String,
+    #[serde(default, skip_serializing_if = "is_redacted")]
     pub offer_yes: String,
+    #[serde(default, skip_serializing_if = "is_redacted")]
     pub offer_no: String,
+    #[serde(default, skip_serializing_if = "is_redacted")]
+    pub queue_ahead_yes: String,
+    #[serde(default, skip_serializing_if = "is_redacted")]
+    pub queue_ahead_no: String,
+    #[serde(default, skip_serializing_if = "is_redacted")]
+    pub suggested_size: String,
+    #[serde(default, skip_serializing_if = "is_redacted")]
     pub net_profit: String,
+    #[serde(default, skip_serializing_if = "is_redacted")]
     pub taker_fee_pct: String,
+    #[serde(default, skip_serializing_if = "is_redacted")]
     pub maker_fee_pct: String,
+    #[serde(default, skip_serializing_if = "is_redacted")]
     pub fee_exponent: String,
+    #[serde(default, skip_serializing_if = "is_redacted")]
     pub reward_pct: String,
+    #[serde(default, skip_serializing_if = "is_redacted")]
+    pub volume_24h: String,
+    #[serde(default, skip_serializing_if = "is_redacted")]
+    pub liquidity: String,
     pub mock_columns: Vec<String>,
+    #[serde(default)]
+    pub simulated_columns: Vec<String>,
+    pub stale_as_of_ts_utc: Option<i64>,
+    #[serde(default)]
+    pub neg_risk_group: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Default)]
@@ -322,8 +1061,49 @@ pub struct DashboardQuery {
     pub bets_open: Vec<String>,
     #[serde(default, deserialize_with = "deserialize_vec_or_single")]
     pub in_interval: Vec<String>,
+    /// Lower bounds (USDC) on `volume_24h` and `liquidity`; empty or invalid is no
+    /// bound.
+    #[serde(default)]
+    pub min_volume_24h: Option<String>,
+    #[serde(default)]
+    pub min_liquidity: Option<String>,
+    /// Column key (from `DASHBOARD_COLUMN_KEYS`) to order rows by; unknown keys keep
+    /// the snapshot order.
+    #[serde(default)]
+    pub sort_by: Option<String>,
+    /// `asc` (default) or `desc`.
+    #[serde(default)]
+    pub order: Option<String>,
+    /// Page of the sorted rows; invalid values mean no limit / offset `0`.
+    #[serde(default)]
+    pub limit: Option<String>,
+    #[serde(default)]
+    pub offset: Option<String>,
+    /// Column keys (from `DASHBOARD_COLUMN_KEYS`) to show, as repeated values or comma
+    /// lists; empty shows every column.
+    #[serde(default, deserialize_with = "deserialize_vec_or_single")]
+    pub columns: Vec<String>,
+    /// Horizons (seconds to expiry, from [`PROBABILITY_HORIZONS_SECONDS`]) to add as
+    /// probability columns; present but naming none (`?horizons=`) adds them all.
+    #[serde(default, deserialize_with = "deserialize_vec_or_single")]
+    pub horizons: Vec<String>,
 }
 
+/// Query keys [`dashboard_query_from_pairs`] understands besides `columns`; saved views
+/// may preset any of them.
+pub(crate) const DASHBOARD_FILTER_KEYS: [&str; 10] = [
+    "coin",
+    "duration",
+    "bets_open",
+    "in_interval",
+    "min_volume_24h",
+    "min_liquidity",
+    "sort_by",
+    "order",
+    "limit",
+    "offset",
+];
+
 #[derive(Deserialize)]
 #[serde(untagged)]
 enum VecOrSingle {
@@ -360,15 +1140,43 @@ pub struct DashboardFilters {
     pub durations: HashSet<String>,
     pub bets_open: HashSet<BetsOpenFilter>,
     pub in_interval: HashSet<InIntervalFilter>,
+    /// Rows below a bound, or without the figure, are filtered out.
+    pub min_volume_24h: Option<f64>,
+    pub min_liquidity: Option<f64>,
+    /// Column rows are ordered by; `None` keeps the snapshot order.
+    pub sort_by: Option<&'static str>,
+    pub descending: bool,
+    /// Rows skipped, then rows kept (`None`: all), after filtering and sorting.
+    pub offset: usize,
+    pub limit: Option<usize>,
+    /// Columns to show; `None` shows all. The market link is always shown.
+    pub visible_columns: Option<Vec<&'static str>>,
+    /// Horizons shown as extra probability columns, farthest from expiry first.
+    pub probability_horizons: Vec<u32>,
 }
 
 impl DashboardFilters {
     pub fn from_query(query: &DashboardQuery) -> Self {
         Self {
-            coins: parse_set_or_all(&query.coin, &COIN_OPTIONS),
+            coins: parse_set_or_all(&query.coin, &coin_options()),
             durations: parse_set_or_all(&query.duration, &DURATION_OPTIONS),
             bets_open: parse_bets_open(&query.bets_open),
             in_interval: parse_in_interval(&query.in_interval),
+            min_volume_24h: parse_min_bound(query.min_volume_24h.as_deref()),
+            min_liquidity: parse_min_bound(query.min_liquidity.as_deref()),
+            sort_by: query.sort_by.as_deref().and_then(|raw| {
+                DASHBOARD_COLUMN_KEYS
+                    .into_iter()
+                    .find(|key| key.eq_ignore_ascii_case(raw.trim()))
+            }),
+            descending: query
+                .order
+                .as_deref()
+                .is_some_and(|raw| raw.trim().eq_ignore_ascii_case("desc")),
+            offset: parse_count(query.offset.as_deref()).unwrap_or(0),
+            limit: parse_count(query.limit.as_deref()),
+            visible_columns: parse_columns(&query.columns),
+            probability_horizons: parse_horizons(&query.horizons),
         }
     }
 
@@ -392,238 +1200,2369 @@ impl DashboardFilters {
         self.in_interval.contains(&value)
     }
 
+    /// Selected durations, shortest first; unknown labels are skipped.
+    fn selected_durations(&self) -> impl Iterator<Item = Duration> + '_ {
+        Duration::ALL
+            .into_iter()
+            .filter(|duration| self.duration_selected(duration.as_label()))
+    }
+
+    /// Poll and stream cadence: the fastest cadence among the selected durations.
+    pub fn refresh_ms(&self) -> u64 {
+        self.selected_durations()
+            .map(duration_refresh_ms)
+            .min()
+            .unwrap_or(STREAM_TICK_MS)
+    }
+
+    /// Column keys (from `DASHBOARD_COLUMN_KEYS`) not to show: those left out of
+    /// `visible_columns`, and the fee and reward columns when every selected duration
+    /// is fee-free.
+    pub fn hidden_columns(&self) -> Vec<&'static str> {
+        let fee_free = !self.selected_durations().any(duration_has_fees);
+        DASHBOARD_COLUMN_KEYS
+            .into_iter()
+            .filter(|key| {
+                let unselected = self
+                    .visible_columns
+                    .as_ref()
+                    .is_some_and(|visible| *key != "link" && !visible.contains(key));
+                unselected || (fee_free && FEE_COLUMN_KEYS.contains(key))
+            })
+            .collect()
+    }
+
     fn allows_unknown_bets_open(&self) -> bool {
         self.bets_open_selected(BetsOpenFilter::Open)
             && self.bets_open_selected(BetsOpenFilter::Closed)
     }
 }
 
-pub trait DashboardSnapshotSource: Send + Sync + 'static {
-    fn snapshot(&self) -> DashboardSnapshot;
+/// Server-side number formatting for numeric dashboard cells.
+///
+/// The default reproduces the historical output: significant-digit rounding, `.` as the
+/// decimal separator, and no digit grouping. `column_decimals` switches individual columns
+/// (keyed by `DASHBOARD_COLUMN_KEYS`) to a fixed number of decimal places.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumberFormatConfig {
+    pub decimal_separator: char,
+    pub thousands_separator: Option<char>,
+    pub column_decimals: BTreeMap<String, usize>,
 }
 
-#[derive(Clone)]
-pub struct InMemoryMockSnapshotSource {
-    inner: Arc<RwLock<DashboardSnapshot>>,
+impl Default for NumberFormatConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
 }
 
-impl InMemoryMockSnapshotSource {
-    pub fn new(snapshot: DashboardSnapshot) -> Self {
+impl NumberFormatConfig {
+    /// Separator preset for a locale tag such as `en-US`, `de-DE`, or `fr`.
+    /// Unknown tags fall back to the default (`.` decimals, no grouping).
+    pub fn for_locale(tag: &str) -> Self {
+        let language = tag
+            .trim()
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let (decimal_separator, thousands_separator) = match language.as_str() {
+            "en" => ('.', Some(',')),
+            "de" | "es" | "it" | "nl" | "pt" => (',', Some('.')),
+            "fr" => (',', Some('\u{202f}')),
+            _ => ('.', None),
+        };
+
         Self {
-            inner: Arc::new(RwLock::new(snapshot)),
+            decimal_separator,
+            thousands_separator,
+            column_decimals: BTreeMap::new(),
         }
     }
 
-    pub fn demo() -> Self {
-        Self::new(demo_snapshot())
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
     }
 
-    pub fn replace_snapshot(&self, snapshot: DashboardSnapshot) {
-        let mut guard = self
-            .inner
-            .write()
-            .expect("in-memory snapshot lock should not be poisoned");
-        *guard = snapshot;
-    }
-}
+    /// Reads `PMM_DASHBOARD_NUMBER_LOCALE`, `PMM_DASHBOARD_DECIMAL_SEPARATOR`,
+    /// `PMM_DASHBOARD_THOUSANDS_SEPARATOR`, and `PMM_DASHBOARD_COLUMN_DECIMALS`
+    /// (e.g. `price=2,ref_price=2`). Invalid values are ignored.
+    pub fn from_settings(settings: &Settings) -> Self {
+        let locale = settings.parsed("PMM_DASHBOARD_NUMBER_LOCALE", String::parse_env);
+        // A locale replaces the separator defaults; separators set explicitly win.
+        let separator = |name: &str| match locale {
+            Some(_) => settings.value(name),
+            None => settings.parsed(name, String::parse_env),
+        };
+        let mut cfg = Self::for_locale(locale.as_deref().unwrap_or_default());
 
-impl DashboardSnapshotSource for InMemoryMockSnapshotSource {
-    fn snapshot(&self) -> DashboardSnapshot {
-        self.inner
-            .read()
-            .expect("in-memory snapshot lock should not be poisoned")
-            .clone()
+        if let Some(sep) =
+            separator("PMM_DASHBOARD_DECIMAL_SEPARATOR").and_then(|raw| single_char(&raw))
+        {
+            cfg.decimal_separator = sep;
+        }
+        if let Some(raw) = separator("PMM_DASHBOARD_THOUSANDS_SEPARATOR") {
+            cfg.thousands_separator = match raw.to_ascii_lowercase().as_str() {
+                "" | "none" => None,
+                "space" => Some(' '),
+                _ => single_char(&raw).or(cfg.thousands_separator),
+            };
+        }
+        cfg.column_decimals = settings
+            .parsed("PMM_DASHBOARD_COLUMN_DECIMALS", |raw| {
+                Some(parse_column_decimals(raw))
+            })
+            .unwrap_or_default();
+        if cfg.thousands_separator == Some(cfg.decimal_separator) {
+            cfg.thousands_separator = None;
+        }
+
+        cfg
     }
 }
 
-#[cfg(feature = "discovery-sdk")]
-#[derive(Debug, Clone, Copy)]
-pub struct LiveDiscoveryConfig {
-    pub refresh_interval_ms: u64,
-    pub slug_config: SlugConfig,
-    pub discovery_config: DiscoveryConfig,
+/// Where the rows currently served by a snapshot source came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotProvenance {
+    /// Rows supplied directly by the caller (in-memory/mock sources).
+    #[default]
+    Static,
+    /// Demo rows served while the live source has not produced a snapshot yet.
+    Demo,
+    /// Rows from the latest successful live discovery cycle.
+    Live,
+    /// Schedule-only rows served while live data is unavailable.
+    Placeholder,
 }
 
-#[cfg(feature = "discovery-sdk")]
-impl Default for LiveDiscoveryConfig {
-    fn default() -> Self {
-        let offset = std::env::var("PMFLIPS_DISCOVERY_OFFSET_4H_MIN")
-            .ok()
-            .and_then(|raw| raw.parse::<i32>().ok())
-            .unwrap_or(60);
-
-        let timeout_ms = std::env::var("PMM_DISCOVERY_TIMEOUT_MS")
-            .ok()
-            .and_then(|raw| raw.parse::<u64>().ok())
-            .unwrap_or(3_000);
-        let batch_size = std::env::var("PMM_DISCOVERY_BATCH_SIZE")
-            .ok()
-            .and_then(|raw| raw.parse::<usize>().ok())
-            .unwrap_or(64);
-        let max_retries = std::env::var("PMM_DISCOVERY_MAX_RETRIES")
-            .ok()
-            .and_then(|raw| raw.parse::<u32>().ok())
-            .unwrap_or(2);
-        let retry_backoff_ms = std::env::var("PMM_DISCOVERY_RETRY_BACKOFF_MS")
-            .ok()
-            .and_then(|raw| raw.parse::<u64>().ok())
-            .unwrap_or(200);
-        let refresh_interval_ms = std::env::var("PMM_DASHBOARD_DISCOVERY_REFRESH_MS")
-            .ok()
-            .and_then(|raw| raw.parse::<u64>().ok())
-            .unwrap_or(1_000);
+/// Provenance and freshness of the snapshot a source is currently serving.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct SnapshotStatus {
+    pub provenance: SnapshotProvenance,
+    pub updated_at_ts_utc: Option<i64>,
+    pub last_live_ts_utc: Option<i64>,
+    pub live_unavailable_since_ts_utc: Option<i64>,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+    /// 0-100 for sources that score their health (see [`SnapshotStatus::score_health`]);
+    /// `None` otherwise.
+    #[serde(default)]
+    pub health_score: Option<u8>,
+    /// Set once the score reached `0`: the source serves last-known-good (or
+    /// placeholder) rows and keeps retrying in the background.
+    #[serde(default)]
+    pub degraded_since_ts_utc: Option<i64>,
+}
 
+impl SnapshotStatus {
+    /// Initial status of a live source that is still serving its startup rows.
+    pub fn awaiting_live(now_ts_utc: i64) -> Self {
         Self {
-            refresh_interval_ms,
-            slug_config: SlugConfig {
-                discovery_offset_4h_min: offset,
-            },
-            discovery_config: DiscoveryConfig {
-                timeout_ms,
-                batch_size,
-                max_retries,
-                retry_backoff_ms,
-                include_tag: false,
-            },
+            provenance: SnapshotProvenance::Demo,
+            updated_at_ts_utc: Some(now_ts_utc),
+            last_live_ts_utc: None,
+            live_unavailable_since_ts_utc: Some(now_ts_utc),
+            consecutive_failures: 0,
+            last_error: None,
+            health_score: None,
+            degraded_since_ts_utc: None,
         }
     }
-}
 
-#[cfg(feature = "discovery-sdk")]
-#[derive(Clone)]
-pub struct LiveDiscoverySnapshotSource {
-    inner: Arc<RwLock<DashboardSnapshot>>,
-}
+    pub fn age_seconds(&self, now_ts_utc: i64) -> Option<i64> {
+        self.updated_at_ts_utc
+            .map(|updated| now_ts_utc.saturating_sub(updated).max(0))
+    }
 
-#[cfg(feature = "discovery-sdk")]
-impl LiveDiscoverySnapshotSource {
-    pub fn spawn(config: LiveDiscoveryConfig) -> Self {
-        let initial = demo_snapshot();
-        let inner = Arc::new(RwLock::new(initial));
-        let inner_bg = Arc::clone(&inner);
+    /// True once at least one live cycle has failed since live data was last available.
+    pub fn live_unavailable(&self) -> bool {
+        self.consecutive_failures > 0 && self.live_unavailable_since_ts_utc.is_some()
+    }
 
-        tokio::spawn(async move {
-            loop {
-                let refreshed = build_live_discovery_snapshot(config).await;
-                {
-                    let mut guard = inner_bg
-                        .write()
-                        .expect("live discovery snapshot lock should not be poisoned");
-                    *guard = refreshed;
-                }
-                tokio::time::sleep(std::time::Duration::from_millis(config.refresh_interval_ms))
-                    .await;
-            }
-        });
+    pub fn record_live_success(&mut self, now_ts_utc: i64) {
+        self.provenance = SnapshotProvenance::Live;
+        self.updated_at_ts_utc = Some(now_ts_utc);
+        self.last_live_ts_utc = Some(now_ts_utc);
+        self.live_unavailable_since_ts_utc = None;
+        self.consecutive_failures = 0;
+        self.last_error = None;
+    }
 
-        Self { inner }
+    /// Records a failed live cycle and returns the consecutive failure count.
+    pub fn record_live_failure(&mut self, now_ts_utc: i64, reason: impl Into<String>) -> u32 {
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        self.live_unavailable_since_ts_utc.get_or_insert(now_ts_utc);
+        self.last_error = Some(reason.into());
+        self.consecutive_failures
     }
-}
 
-#[cfg(feature = "discovery-sdk")]
-impl DashboardSnapshotSource for LiveDiscoverySnapshotSource {
-    fn snapshot(&self) -> DashboardSnapshot {
-        self.inner
-            .read()
-            .expect("live discovery snapshot lock should not be poisoned")
-            .clone()
+    /// Scores the source: `100` while live, falling linearly to `0` over
+    /// `degrade_after_secs` of continuous failure, when it becomes degraded. Returns
+    /// true when this call entered degraded mode.
+    pub fn score_health(&mut self, now_ts_utc: i64, degrade_after_secs: i64) -> bool {
+        let window = degrade_after_secs.max(1);
+        let failing_secs = match self.live_unavailable_since_ts_utc {
+            Some(since) if self.live_unavailable() => (now_ts_utc - since).clamp(0, window),
+            _ => 0,
+        };
+        let score = 100 - 100 * failing_secs / window;
+        self.health_score = Some(score as u8);
+        if score > 0 {
+            self.degraded_since_ts_utc = None;
+            return false;
+        }
+        let entered = self.degraded_since_ts_utc.is_none();
+        self.degraded_since_ts_utc.get_or_insert(now_ts_utc);
+        entered
     }
 }
 
-pub fn dashboard_router(source: Arc<dyn DashboardSnapshotSource>) -> Router {
-    Router::new()
+/// Read side of the dashboard. `snapshot` is called once per HTTP request, so
+/// implementations should hand out a shared pointer rather than cloning rows.
+pub trait DashboardSnapshotSource: Send + Sync + 'static {
+    fn snapshot(&self) -> Arc<DashboardSnapshot>;
+
+    fn status(&self) -> SnapshotStatus {
+        SnapshotStatus::default()
+    }
+
+    /// Clock rows are evaluated against (`in_interval`, filters). Wall clock unless the
+    /// source simulates time.
+    fn now_ts_utc(&self) -> i64 {
+        crate::faults::now_utc().timestamp()
+    }
+
+    /// Collateral the trading wallet has tied up; `None` without a wallet.
+    fn capital_usage(&self) -> Option<CapitalUsage> {
+        None
+    }
+
+    /// Seq of each published snapshot, so push clients wake on publish instead of
+    /// re-reading the source every tick. `None` for sources that rebuild on read.
+    fn published(&self) -> Option<watch::Receiver<u64>> {
+        None
+    }
+}
+
+#[derive(Clone)]
+pub struct InMemoryMockSnapshotSource {
+    inner: Arc<SnapshotSlot>,
+}
+
+impl InMemoryMockSnapshotSource {
+    pub fn new(snapshot: DashboardSnapshot) -> Self {
+        Self {
+            inner: Arc::new(SnapshotSlot::new(snapshot)),
+        }
+    }
+
+    pub fn demo() -> Self {
+        Self::new(demo_snapshot())
+    }
+
+    /// Publishes `snapshot` with the next sequence number and returns that number.
+    pub fn replace_snapshot(&self, snapshot: DashboardSnapshot) -> u64 {
+        self.inner.publish(snapshot)
+    }
+}
+
+impl DashboardSnapshotSource for InMemoryMockSnapshotSource {
+    fn snapshot(&self) -> Arc<DashboardSnapshot> {
+        self.inner.load_full()
+    }
+
+    fn published(&self) -> Option<watch::Receiver<u64>> {
+        Some(self.inner.subscribe())
+    }
+}
+
+/// Wall clock compressed by a constant factor, starting at a chosen simulated time, or
+/// a [`SimulatedClock::manual`] clock that only moves when advanced. Clones share the
+/// clock.
+#[derive(Debug, Clone)]
+pub struct SimulatedClock {
+    start_ts_utc: i64,
+    speed: u32,
+    drive: ClockDrive,
+}
+
+#[derive(Debug, Clone)]
+enum ClockDrive {
+    Wall(std::time::Instant),
+    /// Simulated seconds advanced so far.
+    Manual(Arc<AtomicI64>),
+}
+
+impl SimulatedClock {
+    /// `speed` simulated seconds pass per real second (`60` = one minute per second).
+    pub fn new(start_ts_utc: i64, speed: u32) -> Self {
+        Self {
+            start_ts_utc,
+            speed: speed.max(1),
+            drive: ClockDrive::Wall(std::time::Instant::now()),
+        }
+    }
+
+    /// A clock standing at `start_ts_utc` until [`Self::advance`] moves it.
+    pub fn manual(start_ts_utc: i64) -> Self {
+        Self {
+            start_ts_utc,
+            speed: 1,
+            drive: ClockDrive::Manual(Arc::new(AtomicI64::new(0))),
+        }
+    }
+
+    /// Moves a manual clock `secs` forward; wall-driven clocks ignore it.
+    pub fn advance(&self, secs: i64) {
+        if let ClockDrive::Manual(offset) = &self.drive {
+            offset.fetch_add(secs, Ordering::Relaxed);
+        }
+    }
+
+    pub fn speed(&self) -> u32 {
+        self.speed
+    }
+
+    pub fn now_ts_utc(&self) -> i64 {
+        match &self.drive {
+            ClockDrive::Wall(started) => {
+                let elapsed_ms = started.elapsed().as_millis() as i64;
+                self.start_ts_utc + elapsed_ms * i64::from(self.speed) / 1_000
+            }
+            ClockDrive::Manual(offset) => self.start_ts_utc + offset.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Demo rows driven by a [`SimulatedClock`], for watching interval rollover, in-interval
+/// flips, and window transitions in the UI within seconds. Rows are rebuilt for the
+/// simulated time on read and republished (new seq) whenever the schedule moved.
+#[derive(Clone)]
+pub struct SimulatedDemoSnapshotSource {
+    inner: Arc<SnapshotSlot>,
+    clock: SimulatedClock,
+}
+
+impl SimulatedDemoSnapshotSource {
+    pub fn new(clock: SimulatedClock) -> Self {
+        Self {
+            inner: Arc::new(SnapshotSlot::new(demo_snapshot_at(clock.now_ts_utc()))),
+            clock,
+        }
+    }
+
+    /// Starts the simulated clock at the current wall time.
+    pub fn starting_now(speed: u32) -> Self {
+        Self::new(SimulatedClock::new(Utc::now().timestamp(), speed))
+    }
+
+    /// [`Self::speed`] on the process environment.
+    pub fn speed_from_env() -> Option<u32> {
+        Self::speed(&Settings::from_env())
+    }
+
+    /// `PMM_DASHBOARD_DEMO_SPEED` (simulated seconds per real second); `None` when
+    /// unset, invalid, or `1`.
+    pub fn speed(settings: &Settings) -> Option<u32> {
+        settings
+            .get::<Option<u32>>("PMM_DASHBOARD_DEMO_SPEED")
+            .filter(|speed| *speed > 1)
+    }
+}
+
+impl DashboardSnapshotSource for SimulatedDemoSnapshotSource {
+    fn snapshot(&self) -> Arc<DashboardSnapshot> {
+        let current = self.inner.load_full();
+        let rebuilt = demo_snapshot_at(self.clock.now_ts_utc());
+        if rebuilt.rows != current.rows {
+            self.inner.publish(rebuilt);
+            return self.inner.load_full();
+        }
+        current
+    }
+
+    fn status(&self) -> SnapshotStatus {
+        SnapshotStatus {
+            provenance: SnapshotProvenance::Demo,
+            updated_at_ts_utc: Some(self.clock.now_ts_utc()),
+            ..SnapshotStatus::default()
+        }
+    }
+
+    fn now_ts_utc(&self) -> i64 {
+        self.clock.now_ts_utc()
+    }
+}
+
+/// The published snapshot of a source, plus a watch of its seq for push clients.
+struct SnapshotSlot {
+    current: ArcSwap<DashboardSnapshot>,
+    published: watch::Sender<u64>,
+}
+
+impl SnapshotSlot {
+    fn new(snapshot: DashboardSnapshot) -> Self {
+        let (published, _) = watch::channel(snapshot.seq);
+        Self {
+            current: ArcSwap::from_pointee(snapshot),
+            published,
+        }
+    }
+
+    fn load_full(&self) -> Arc<DashboardSnapshot> {
+        self.current.load_full()
+    }
+
+    fn subscribe(&self) -> watch::Receiver<u64> {
+        self.published.subscribe()
+    }
+
+    /// Stores `snapshot` with `seq` one past the currently published snapshot. The
+    /// compare-and-swap loop keeps seq strictly increasing even with several publishers.
+    fn publish(&self, snapshot: DashboardSnapshot) -> u64 {
+        let mut next = Arc::new(snapshot);
+        loop {
+            let current = self.current.load_full();
+            let seq = current.seq + 1;
+            Arc::get_mut(&mut next)
+                .expect("unpublished snapshot is uniquely owned")
+                .seq = seq;
+            let previous = self.current.compare_and_swap(&current, Arc::clone(&next));
+            if Arc::ptr_eq(&previous, &current) {
+                self.published
+                    .send_modify(|latest| *latest = (*latest).max(seq));
+                return seq;
+            }
+        }
+    }
+}
+
+/// Alert key fired by [`LiveDiscoverySnapshotSource::spawn_with_alerts`].
+#[cfg(feature = "discovery-sdk")]
+pub const LIVE_UNAVAILABLE_ALERT_KEY: &str = "discovery.live_unavailable";
+
+/// Alert key fired by [`LiveDiscoverySnapshotSource::spawn_with_alerts`] while any row's
+/// reference price was captured more than `RealizedVolConfig::ref_capture_alert_ms`
+/// after its interval started.
+#[cfg(feature = "discovery-sdk")]
+pub const REF_CAPTURE_ALERT_KEY: &str = "ref_price.late_capture";
+
+#[cfg(feature = "discovery-sdk")]
+#[derive(Debug, Clone)]
+pub struct LiveDiscoveryConfig {
+    pub refresh_interval_ms: u64,
+    pub slug_config: SlugConfig,
+    pub discovery_config: DiscoveryConfig,
+    /// Coins and durations to discover; rows outside it are not scheduled.
+    pub universe: DiscoveryUniverse,
+    /// Upper bound on one discovery cycle before it is abandoned and counted as a failure.
+    pub cycle_deadline_ms: u64,
+    /// Consecutive failures after which live-unavailable logs escalate from warn to error.
+    pub failure_escalate_after: u32,
+    /// Minutes of continuous failure after which the source is degraded: it serves
+    /// last-known-good rows marked stale until a cycle succeeds.
+    pub degrade_after_mins: u64,
+    /// Cap for the exponential retry delay applied while live data is unavailable.
+    pub max_retry_backoff_ms: u64,
+    /// Consecutive failed lookups after which a slug's last-known-good row is dropped
+    /// and the row is downgraded to unresolved.
+    pub stale_row_max_failures: u32,
+    pub sizing_config: SizingConfig,
+    /// Lifecycle webhooks, derived from consecutive live snapshots.
+    pub webhooks: WebhookConfig,
+    /// Gamma base URLs to fail over between.
+    pub gamma_endpoints: GammaEndpointConfig,
+    /// Kline store backing `realized_vol`, `price_vs_ref_pct`, and `move_z`.
+    pub realized_vol: RealizedVolConfig,
+    /// Sampled raw Gamma payloads for schema-drift detection.
+    pub gamma_payloads: GammaPayloadConfig,
+    /// Fee schedule the fee columns are looked up in, at the time of the cycle.
+    pub fees: FeeRegistry,
+    /// Would-be quotes shown in the Offer columns while not trading live.
+    pub dry_run_quotes: DryRunQuoteConfig,
+    /// Coin/duration toggles: disabled pairs get no quotes and are resolved last.
+    pub toggles: MarketToggles,
+    /// CLOB websocket feeding `best_bid_yes`/`best_ask_yes`.
+    pub clob_stream: ClobStreamConfig,
+    /// Binance websocket feeding `price` and, while the kline store has none, `ref_price`.
+    pub binance_ws: BinanceWsConfig,
+    /// Wallet trade history feeding `pos_yes`, `pos_no`, `position_net`, and
+    /// `net_profit` (polled with the `trading` feature only).
+    pub positions: PositionConfig,
+    /// Model artifacts scoring `probability` from the Binance prices.
+    pub models: ModelConfig,
+    /// Captures the inputs of every `probability` shown; `None` records nothing.
+    pub model_inputs: Option<ModelInputStore>,
+    /// EWMA and hysteresis applied to the scored `probability`.
+    pub smoothing: ProbabilitySmoothingConfig,
+}
+
+#[cfg(feature = "discovery-sdk")]
+impl Default for LiveDiscoveryConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+#[cfg(feature = "discovery-sdk")]
+impl LiveDiscoveryConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads the discovery and refresh settings and every feed's own configuration.
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            refresh_interval_ms: settings.get("PMM_DASHBOARD_DISCOVERY_REFRESH_MS"),
+            slug_config: SlugConfig::default(),
+            discovery_config: DiscoveryConfig::from_settings(settings),
+            universe: DiscoveryUniverse::from_settings(settings),
+            cycle_deadline_ms: settings.get("PMM_DASHBOARD_LIVE_DEADLINE_MS"),
+            failure_escalate_after: settings.get("PMM_DASHBOARD_LIVE_ESCALATE_AFTER"),
+            degrade_after_mins: settings.get("PMM_DASHBOARD_LIVE_DEGRADE_AFTER_MINS"),
+            max_retry_backoff_ms: settings.get("PMM_DASHBOARD_LIVE_MAX_BACKOFF_MS"),
+            stale_row_max_failures: settings.get("PMM_DASHBOARD_STALE_MAX_FAILURES"),
+            sizing_config: SizingConfig::from_settings(settings),
+            webhooks: WebhookConfig::from_settings(settings),
+            gamma_endpoints: GammaEndpointConfig::from_settings(settings),
+            realized_vol: RealizedVolConfig::from_settings(settings),
+            gamma_payloads: GammaPayloadConfig::from_settings(settings),
+            fees: FeeRegistry::from_settings(settings).unwrap_or_else(|err| {
+                warn!(
+                    component = "dashboard",
+                    event = "fees.schedule_invalid",
+                    error = %err
+                );
+                FeeRegistry::builtin()
+            }),
+            dry_run_quotes: DryRunQuoteConfig::from_settings(settings),
+            toggles: MarketToggles::in_memory(),
+            clob_stream: ClobStreamConfig::from_settings(settings),
+            binance_ws: BinanceWsConfig::from_settings(settings),
+            positions: PositionConfig::from_settings(settings),
+            models: ModelConfig::from_settings(settings),
+            model_inputs: None,
+            smoothing: ProbabilitySmoothingConfig::from_settings(settings),
+        }
+    }
+}
+
+#[cfg(feature = "discovery-sdk")]
+#[derive(Clone)]
+pub struct LiveDiscoverySnapshotSource {
+    inner: Arc<SnapshotSlot>,
+    status: Arc<ArcSwap<SnapshotStatus>>,
+    gamma_endpoints: GammaEndpointPool,
+    #[cfg(feature = "trading")]
+    positions: Option<PositionFeed>,
+}
+
+#[cfg(feature = "discovery-sdk")]
+impl LiveDiscoverySnapshotSource {
+    pub fn spawn(config: LiveDiscoveryConfig) -> Self {
+        Self::spawn_with_alerts(config, None)
+    }
+
+    /// Like [`LiveDiscoverySnapshotSource::spawn`], also firing the
+    /// `discovery.live_unavailable` alert once failures escalate and clearing it on the
+    /// next live success.
+    pub fn spawn_with_alerts(config: LiveDiscoveryConfig, alerts: Option<AlertManager>) -> Self {
+        #[cfg(feature = "trading")]
+        let positions = PositionFeed::spawn(config.positions.clone());
+        let source = Self {
+            inner: Arc::new(SnapshotSlot::new(demo_snapshot())),
+            status: Arc::new(ArcSwap::from_pointee(SnapshotStatus::awaiting_live(
+                crate::faults::now_utc().timestamp(),
+            ))),
+            gamma_endpoints: GammaEndpointPool::new(config.gamma_endpoints.clone()),
+            #[cfg(feature = "trading")]
+            positions: positions.clone(),
+        };
+        let source_bg = source.clone();
+
+        let webhooks = WebhookEmitter::spawn(config.webhooks.clone());
+        let mut kline_stats = open_realized_vol_tracker(&config.realized_vol);
+        let payload_sampler = open_gamma_payload_sampler(&config.gamma_payloads);
+        let books = ClobBookStream::spawn(config.clob_stream.clone());
+        let prices = BinanceWsStream::spawn(config.binance_ws.clone(), &config.universe.coins);
+        let models =
+            load_model_registry(&config.models, &quote_feature_schema()).unwrap_or_else(|err| {
+                warn!(
+                    component = "dashboard",
+                    event = "model.load_failed",
+                    error = %err
+                );
+                ModelRegistry::new()
+            });
+        let smoother = ProbabilitySmoother::new(config.smoothing);
+
+        tokio::spawn(async move {
+            let mut last_good = LastKnownGood::default();
+            // Events are only diffed between live snapshots, so the demo -> live switch
+            // does not fire a burst of discovery events.
+            let mut last_live: Option<Arc<DashboardSnapshot>> = None;
+            loop {
+                let deadline = std::time::Duration::from_millis(config.cycle_deadline_ms);
+                let mut outcome = match tokio::time::timeout(
+                    deadline,
+                    build_live_discovery_snapshot(
+                        &config,
+                        &source_bg.gamma_endpoints,
+                        payload_sampler.as_ref(),
+                    ),
+                )
+                .await
+                {
+                    Ok(outcome) => outcome,
+                    Err(_) => {
+                        record_discovery_cycle(0, 0, 0, true, config.cycle_deadline_ms);
+                        LiveCycleOutcome {
+                            rows: None,
+                            failure: Some(format!(
+                                "discovery cycle exceeded {}ms deadline",
+                                config.cycle_deadline_ms
+                            )),
+                            subscriptions: Vec::new(),
+                        }
+                    }
+                };
+                if let Some(books) = &books {
+                    // Failed cycles keep the current subscriptions rather than dropping them.
+                    if outcome.failure.is_none() {
+                        books.subscribe(&outcome.subscriptions);
+                    }
+                    if let Some(rows) = &mut outcome.rows {
+                        apply_order_books(
+                            books,
+                            rows.iter_mut().map(|cycle_row| &mut cycle_row.row),
+                        );
+                    }
+                }
+                #[cfg(feature = "trading")]
+                if let Some(positions) = &positions {
+                    if outcome.failure.is_none() {
+                        positions.track_markets(&outcome.subscriptions);
+                    }
+                    if let Some(rows) = &mut outcome.rows {
+                        apply_positions(
+                            positions,
+                            rows.iter_mut().map(|cycle_row| &mut cycle_row.row),
+                        );
+                    }
+                }
+                if let (Some(tracker), Some(rows)) = (&mut kline_stats, &mut outcome.rows) {
+                    // Incremental reads: a handful of new klines per row and cycle.
+                    apply_kline_columns(
+                        tracker,
+                        rows.iter_mut().map(|cycle_row| &mut cycle_row.row),
+                        crate::faults::now_utc().timestamp(),
+                    );
+                }
+                if let (Some(prices), Some(rows)) = (&prices, &mut outcome.rows) {
+                    apply_live_prices(prices, rows.iter_mut().map(|cycle_row| &mut cycle_row.row));
+                    let captures = apply_model_probabilities(
+                        &models,
+                        prices,
+                        rows.iter_mut().map(|cycle_row| &mut cycle_row.row),
+                        crate::faults::now_utc().timestamp(),
+                    );
+                    apply_probability_smoothing(
+                        &smoother,
+                        &captures,
+                        rows.iter_mut().map(|cycle_row| &mut cycle_row.row),
+                    );
+                    if let Some(store) = config.model_inputs.clone() {
+                        // Failures are logged by the store.
+                        let _ = tokio::task::spawn_blocking(move || store.record_or_log(&captures))
+                            .await;
+                    }
+                }
+                if let Some(rows) = &mut outcome.rows {
+                    apply_suggested_sizes(
+                        &config.sizing_config,
+                        rows.iter_mut().map(|cycle_row| &mut cycle_row.row),
+                    );
+                    apply_dry_run_quotes(
+                        &config.dry_run_quotes,
+                        &config.toggles,
+                        rows.iter_mut().map(|cycle_row| &mut cycle_row.row),
+                        crate::faults::now_utc().timestamp(),
+                    );
+                }
+                let failures = source_bg.apply_cycle_outcome(
+                    outcome,
+                    &mut last_good,
+                    &config,
+                    crate::faults::now_utc().timestamp(),
+                );
+                if let Some(webhooks) = &webhooks {
+                    source_bg.emit_market_events(webhooks, &mut last_live);
+                }
+                if let Some(alerts) = &alerts {
+                    source_bg.update_live_alert(alerts, &config, failures);
+                    if kline_stats.is_some() && failures == 0 {
+                        update_ref_capture_alert(
+                            alerts,
+                            &source_bg.inner.load_full().rows,
+                            config.realized_vol.ref_capture_alert_ms,
+                        );
+                    }
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(live_retry_delay_ms(
+                    &config, failures,
+                )))
+                .await;
+            }
+        });
+
+        source
+    }
+
+    fn apply_cycle_outcome(
+        &self,
+        outcome: LiveCycleOutcome,
+        last_good: &mut LastKnownGood,
+        config: &LiveDiscoveryConfig,
+        now_ts_utc: i64,
+    ) -> u32 {
+        // Only the refresh task writes, so load-modify-store cannot lose updates.
+        let mut status = SnapshotStatus::clone(&self.status.load());
+
+        let merged = outcome.rows.map(|rows| {
+            let (rows, summary) = merge_with_last_known_good(
+                rows,
+                last_good,
+                now_ts_utc,
+                config.stale_row_max_failures,
+            );
+            if summary.stale_rows > 0 || summary.downgraded_rows > 0 {
+                warn!(
+                    component = "dashboard",
+                    event = "discovery.rows.stale",
+                    stale_rows = summary.stale_rows,
+                    downgraded_rows = summary.downgraded_rows,
+                    max_failures = config.stale_row_max_failures
+                );
+            }
+            (DashboardSnapshot { rows, seq: 0 }, summary.stale_rows)
+        });
+
+        let degrade_after_secs = (config.degrade_after_mins * 60) as i64;
+        let Some(reason) = outcome.failure else {
+            let failed_cycles = status.consecutive_failures;
+            let unavailable_secs = status
+                .live_unavailable_since_ts_utc
+                .map(|since| now_ts_utc - since)
+                .unwrap_or(0);
+            let was_degraded = status.degraded_since_ts_utc.is_some();
+            if let Some((snapshot, _)) = merged {
+                self.replace_snapshot(snapshot);
+            }
+            status.record_live_success(now_ts_utc);
+            status.score_health(now_ts_utc, degrade_after_secs);
+            self.status.store(Arc::new(status));
+            if failed_cycles > 0 {
+                info!(
+                    component = "dashboard",
+                    event = "discovery.live.recovered",
+                    failed_cycles,
+                    unavailable_secs,
+                    was_degraded
+                );
+            }
+            return 0;
+        };
+
+        let failures = status.record_live_failure(now_ts_utc, reason.clone());
+        let entered_degraded = status.score_health(now_ts_utc, degrade_after_secs);
+        let replacement = match merged {
+            Some(merged) => Some(merged),
+            // Never keep serving demo numbers once the first live cycle has failed.
+            None if status.provenance == SnapshotProvenance::Demo => {
+                Some((placeholder_snapshot(now_ts_utc, config, &reason), 0))
+            }
+            None => None,
+        };
+        if let Some((snapshot, stale_rows)) = replacement {
+            self.replace_snapshot(snapshot);
+            status.provenance = if stale_rows > 0 {
+                SnapshotProvenance::Live
+            } else {
+                SnapshotProvenance::Placeholder
+            };
+            status.updated_at_ts_utc = Some(now_ts_utc);
+        } else if entered_degraded && status.provenance == SnapshotProvenance::Live {
+            // The rows of the last live cycle are all we have: say so on every row.
+            if let Some(last_live) = status.last_live_ts_utc {
+                let mut snapshot = DashboardSnapshot::clone(&self.inner.load_full());
+                for row in &mut snapshot.rows {
+                    row.stale_as_of_ts_utc.get_or_insert(last_live);
+                }
+                self.replace_snapshot(snapshot);
+            }
+        }
+        if entered_degraded {
+            error!(
+                component = "dashboard",
+                event = "discovery.live.degraded",
+                consecutive_failures = failures,
+                degrade_after_mins = config.degrade_after_mins,
+                last_live_ts_utc = status.last_live_ts_utc,
+                provenance = ?status.provenance,
+                reason = %reason
+            );
+        }
+
+        let unavailable_secs = status
+            .live_unavailable_since_ts_utc
+            .map(|since| now_ts_utc - since)
+            .unwrap_or(0);
+        let provenance = status.provenance;
+        self.status.store(Arc::new(status));
+        let retry_in_ms = live_retry_delay_ms(config, failures);
+        if live_failure_escalated(failures, config.failure_escalate_after) {
+            error!(
+                component = "dashboard",
+                event = "discovery.live.unavailable",
+                consecutive_failures = failures,
+                unavailable_secs,
+                provenance = ?provenance,
+                retry_in_ms,
+                reason = %reason
+            );
+        } else {
+            warn!(
+                component = "dashboard",
+                event = "discovery.live.unavailable",
+                consecutive_failures = failures,
+                unavailable_secs,
+                provenance = ?provenance,
+                retry_in_ms,
+                reason = %reason
+            );
+        }
+
+        failures
+    }
+
+    /// Health and latency of each configured Gamma endpoint.
+    pub fn gamma_endpoint_statuses(&self) -> Vec<GammaEndpointStatus> {
+        self.gamma_endpoints.statuses()
+    }
+
+    fn update_live_alert(
+        &self,
+        alerts: &AlertManager,
+        config: &LiveDiscoveryConfig,
+        failures: u32,
+    ) {
+        if failures == 0 {
+            alerts.clear(LIVE_UNAVAILABLE_ALERT_KEY);
+        } else if live_failure_escalated(failures, config.failure_escalate_after) {
+            let status = self.status.load();
+            let message = format!(
+                "live discovery failing for {failures} cycles: {}",
+                status.last_error.as_deref().unwrap_or("unknown error")
+            );
+            alerts.fire(
+                LIVE_UNAVAILABLE_ALERT_KEY,
+                AlertSeverity::Critical,
+                &message,
+            );
+        }
+    }
+
+    fn replace_snapshot(&self, snapshot: DashboardSnapshot) {
+        self.inner.publish(snapshot);
+    }
+
+    fn emit_market_events(
+        &self,
+        webhooks: &WebhookEmitter,
+        last_live: &mut Option<Arc<DashboardSnapshot>>,
+    ) {
+        if self.status.load().provenance != SnapshotProvenance::Live {
+            return;
+        }
+        let current = self.inner.load_full();
+        if let Some(prev) = last_live.as_ref() {
+            if prev.seq == current.seq {
+                return;
+            }
+            for event in market_events_between(prev, &current, crate::faults::now_utc().timestamp())
+            {
+                webhooks.emit(event);
+            }
+        }
+        *last_live = Some(current);
+    }
+}
+
+#[cfg(feature = "discovery-sdk")]
+impl DashboardSnapshotSource for LiveDiscoverySnapshotSource {
+    fn snapshot(&self) -> Arc<DashboardSnapshot> {
+        self.inner.load_full()
+    }
+
+    fn published(&self) -> Option<watch::Receiver<u64>> {
+        Some(self.inner.subscribe())
+    }
+
+    fn status(&self) -> SnapshotStatus {
+        SnapshotStatus::clone(&self.status.load())
+    }
+
+    #[cfg(feature = "trading")]
+    fn capital_usage(&self) -> Option<CapitalUsage> {
+        self.positions.as_ref()?.capital_usage()
+    }
+}
+
+#[cfg(feature = "discovery-sdk")]
+fn open_gamma_payload_sampler(cfg: &GammaPayloadConfig) -> Option<Arc<GammaPayloadSampler>> {
+    match GammaPayloadSampler::open(cfg) {
+        Ok(sampler) => sampler.map(Arc::new),
+        Err(err) => {
+            warn!(
+                component = "dashboard",
+                event = "gamma_schema.disabled",
+                path = %cfg.store_path.display(),
+                error = %err
+            );
+            None
+        }
+    }
+}
+
+#[cfg(feature = "discovery-sdk")]
+fn open_realized_vol_tracker(cfg: &RealizedVolConfig) -> Option<RealizedVolTracker> {
+    match RealizedVolTracker::open(cfg.clone()) {
+        Ok(tracker) => tracker,
+        Err(err) => {
+            warn!(
+                component = "dashboard",
+                event = "realized_vol.disabled",
+                path = ?cfg.store_path,
+                error = %err
+            );
+            None
+        }
+    }
+}
+
+/// Fills the kline-derived columns (`realized_vol`, `price_vs_ref_pct`, `move_z`, and
+/// `ref_price` with its `ref_capture_delay_ms` when unset) on rows whose interval has
+/// started. Values that cannot be computed yet stay `None`.
+#[cfg(feature = "discovery-sdk")]
+pub fn apply_kline_columns<'a>(
+    tracker: &mut RealizedVolTracker,
+    rows: impl IntoIterator<Item = &'a mut DashboardRow>,
+    now_ts_utc: i64,
+) {
+    let mut store_error = None;
+    for row in rows {
+        let Ok(coin) = parse_coin(&row.coin) else {
+            continue;
+        };
+        let stats = match tracker.interval_stats(coin, row.start_ts_utc, row.end_ts_utc, now_ts_utc)
+        {
+            Ok(stats) => stats,
+            Err(err) => {
+                store_error = Some(err);
+                continue;
+            }
+        };
+        row.realized_vol = stats.realized_vol.map(|vol| format!("{:.4}", vol * 100.0));
+        row.price_vs_ref_pct = stats.price_vs_ref_pct.map(|pct| format!("{pct:.4}"));
+        row.move_z = stats.move_z.map(|z| format!("{z:.3}"));
+        if row.ref_price.is_none() {
+            row.ref_price = stats.ref_price.map(|price| price.to_string());
+            row.ref_capture_delay_ms = stats.ref_price.and(stats.ref_capture_delay_ms);
+        }
+        for (column, is_live) in [
+            ("realized_vol", row.realized_vol.is_some()),
+            ("price_vs_ref_pct", row.price_vs_ref_pct.is_some()),
+            ("move_z", row.move_z.is_some()),
+            ("ref_price", row.ref_price.is_some()),
+        ] {
+            if is_live {
+                row.mock_columns.retain(|entry| entry != column);
+            }
+        }
+    }
+    tracker.end_pass();
+    if let Some(err) = store_error {
+        warn!(
+            component = "dashboard",
+            event = "realized_vol.store_error",
+            error = %err
+        );
+    }
+}
+
+/// Rows whose reference was captured more than `threshold_ms` late, as `(slug, delay)`.
+pub fn late_ref_captures(rows: &[DashboardRow], threshold_ms: i64) -> Vec<(&str, i64)> {
+    rows.iter()
+        .filter_map(|row| {
+            row.ref_capture_delay_ms
+                .filter(|delay_ms| *delay_ms > threshold_ms)
+                .map(|delay_ms| (row.slug.as_str(), delay_ms))
+        })
+        .collect()
+}
+
+/// Fires [`REF_CAPTURE_ALERT_KEY`] listing the late rows, or clears it when there are
+/// none (including once a late reference was backfilled).
+#[cfg(feature = "discovery-sdk")]
+fn update_ref_capture_alert(alerts: &AlertManager, rows: &[DashboardRow], threshold_ms: i64) {
+    let late = late_ref_captures(rows, threshold_ms);
+    if late.is_empty() {
+        alerts.clear(REF_CAPTURE_ALERT_KEY);
+        return;
+    }
+    let listed = late
+        .iter()
+        .map(|(slug, delay_ms)| format!("{slug} +{delay_ms}ms"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    alerts.fire(
+        REF_CAPTURE_ALERT_KEY,
+        AlertSeverity::Warn,
+        &format!(
+            "reference price captured late for {} markets: {listed}",
+            late.len()
+        ),
+    );
+}
+
+#[cfg(feature = "discovery-sdk")]
+struct LiveCycleOutcome {
+    rows: Option<Vec<LiveCycleRow>>,
+    failure: Option<String>,
+    /// YES tokens of the markets resolved this cycle.
+    subscriptions: Vec<BookSubscription>,
+}
+
+#[cfg(feature = "discovery-sdk")]
+struct LiveCycleRow {
+    /// Scheduled slug; the resolved row may carry the market's own slug instead.
+    key_slug: String,
+    row: DashboardRow,
+    lookup: RowLookup,
+}
+
+#[cfg(feature = "discovery-sdk")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RowLookup {
+    Resolved,
+    NotFound,
+    Failed,
+    /// Not looked up this cycle because its deadline was too close; not a failure.
+    Skipped,
+}
+
+/// Last resolved row per slug, kept by the refresh task so a transient lookup
+/// failure does not erase metadata that was valid one cycle ago.
+#[cfg(feature = "discovery-sdk")]
+#[derive(Debug, Default)]
+struct LastKnownGood {
+    entries: HashMap<String, LastKnownGoodEntry>,
+}
+
+#[cfg(feature = "discovery-sdk")]
+#[derive(Debug)]
+struct LastKnownGoodEntry {
+    row: DashboardRow,
+    resolved_at_ts_utc: i64,
+    consecutive_failures: u32,
+}
+
+#[cfg(feature = "discovery-sdk")]
+#[derive(Debug, Default, PartialEq, Eq)]
+struct MergeSummary {
+    stale_rows: usize,
+    downgraded_rows: usize,
+}
+
+#[cfg(feature = "discovery-sdk")]
+fn merge_with_last_known_good(
+    rows: Vec<LiveCycleRow>,
+    last_good: &mut LastKnownGood,
+    now_ts_utc: i64,
+    max_failures: u32,
+) -> (Vec<DashboardRow>, MergeSummary) {
+    let mut summary = MergeSummary::default();
+    let mut seen = HashSet::with_capacity(rows.len());
+    let mut merged = Vec::with_capacity(rows.len());
+
+    for LiveCycleRow {
+        key_slug,
+        row,
+        lookup,
+    } in rows
+    {
+        match lookup {
+            RowLookup::Resolved => {
+                last_good.entries.insert(
+                    key_slug.clone(),
+                    LastKnownGoodEntry {
+                        row: row.clone(),
+                        resolved_at_ts_utc: now_ts_utc,
+                        consecutive_failures: 0,
+                    },
+                );
+                merged.push(row);
+            }
+            RowLookup::NotFound => {
+                last_good.entries.remove(&key_slug);
+                merged.push(row);
+            }
+            RowLookup::Skipped => match last_good.entries.get(&key_slug) {
+                Some(entry) => {
+                    let mut stale = entry.row.clone();
+                    stale.stale_as_of_ts_utc = Some(entry.resolved_at_ts_utc);
+                    summary.stale_rows += 1;
+                    merged.push(stale);
+                }
+                None => merged.push(row),
+            },
+            RowLookup::Failed => {
+                let Some(entry) = last_good.entries.get_mut(&key_slug) else {
+                    seen.insert(key_slug);
+                    merged.push(row);
+                    continue;
+                };
+                entry.consecutive_failures += 1;
+                if entry.consecutive_failures < max_failures {
+                    let mut stale = entry.row.clone();
+                    stale.stale_as_of_ts_utc = Some(entry.resolved_at_ts_utc);
+                    summary.stale_rows += 1;
+                    merged.push(stale);
+                } else {
+                    last_good.entries.remove(&key_slug);
+                    summary.downgraded_rows += 1;
+                    merged.push(row);
+                }
+            }
+        }
+        seen.insert(key_slug);
+    }
+
+    // Slugs that rolled out of the previous/active/next schedule are no longer needed.
+    last_good.entries.retain(|slug, _| seen.contains(slug));
+    (merged, summary)
+}
+
+#[cfg(feature = "discovery-sdk")]
+fn live_retry_delay_ms(config: &LiveDiscoveryConfig, consecutive_failures: u32) -> u64 {
+    if consecutive_failures == 0 {
+        return config.refresh_interval_ms;
+    }
+
+    let base = config.refresh_interval_ms.max(1);
+    let cap = config.max_retry_backoff_ms.max(base);
+    base.saturating_mul(1u64 << consecutive_failures.min(16))
+        .min(cap)
+}
+
+#[cfg(feature = "discovery-sdk")]
+fn live_failure_escalated(consecutive_failures: u32, escalate_after: u32) -> bool {
+    consecutive_failures >= escalate_after.max(1)
+}
+
+#[cfg(feature = "discovery-sdk")]
+fn placeholder_snapshot(
+    now_ts_utc: i64,
+    config: &LiveDiscoveryConfig,
+    reason: &str,
+) -> DashboardSnapshot {
+    let rows = config
+        .universe
+        .previous_active_and_next_keys(now_ts_utc, config.slug_config)
+        .unwrap_or_default()
+        .iter()
+        .map(|scheduled| unresolved_dashboard_row_with_reason(scheduled, reason))
+        .collect();
+
+    DashboardSnapshot { rows, seq: 0 }
+}
+
+pub fn dashboard_router(source: Arc<dyn DashboardSnapshotSource>) -> Router {
+    dashboard_router_with_format(source, NumberFormatConfig::default())
+}
+
+pub fn dashboard_router_with_format(
+    source: Arc<dyn DashboardSnapshotSource>,
+    number_format: NumberFormatConfig,
+) -> Router {
+    dashboard_router_with_redaction(source, number_format, RedactionConfig::default())
+}
+
+/// The dashboard with a public view; the shorter constructors show everyone everything.
+pub fn dashboard_router_with_redaction(
+    source: Arc<dyn DashboardSnapshotSource>,
+    number_format: NumberFormatConfig,
+    redaction: RedactionConfig,
+) -> Router {
+    dashboard_router_with_views(
+        source,
+        number_format,
+        redaction,
+        DashboardViews::in_memory(),
+    )
+}
+
+/// The dashboard with saved views: `?view=<name>` on any dashboard route applies one,
+/// and `/dashboard/views` lists them and (for private requests) creates, replaces, and
+/// deletes them. The activity feed, capital, and readiness use their defaults.
+///
+/// Starts the activity feed's background task, so it needs a Tokio runtime.
+pub fn dashboard_router_with_views(
+    source: Arc<dyn DashboardSnapshotSource>,
+    number_format: NumberFormatConfig,
+    redaction: RedactionConfig,
+    views: DashboardViews,
+) -> Router {
+    let config = DashboardConfig {
+        number_format,
+        redaction,
+        ..DashboardConfig::default()
+    };
+    dashboard_router_with_config(source, config, views)
+}
+
+/// Everything the dashboard routes are configured with besides their source and views.
+#[derive(Debug, Clone, Default)]
+pub struct DashboardConfig {
+    pub number_format: NumberFormatConfig,
+    pub redaction: RedactionConfig,
+    pub activity: ActivityFeedConfig,
+    pub capital: CapitalConfig,
+    pub readiness: ReadinessConfig,
+}
+
+impl DashboardConfig {
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            number_format: NumberFormatConfig::from_settings(settings),
+            redaction: RedactionConfig::from_settings(settings),
+            activity: ActivityFeedConfig::from_settings(settings),
+            capital: CapitalConfig::from_settings(settings),
+            readiness: ReadinessConfig::from_settings(settings),
+        }
+    }
+}
+
+/// [`dashboard_router_with_views`] with every dashboard config given.
+///
+/// Starts the activity feed's background task, so it needs a Tokio runtime.
+pub fn dashboard_router_with_config(
+    source: Arc<dyn DashboardSnapshotSource>,
+    config: DashboardConfig,
+    views: DashboardViews,
+) -> Router {
+    let state = DashboardAppState::new(source, config, views);
+    state.activity.follow(Arc::clone(&state.source));
+    Router::new()
         .route("/dashboard", get(get_dashboard_html))
         .route("/dashboard/snapshot", get(get_dashboard_snapshot))
-        .with_state(DashboardAppState { source })
+        .route("/dashboard/stream", get(get_dashboard_stream))
+        .route("/dashboard/ws", get(get_dashboard_ws))
+        .route("/dashboard/occupancy", get(get_dashboard_occupancy))
+        .route("/dashboard/activity", get(get_dashboard_activity))
+        .route("/dashboard/export", get(get_dashboard_export))
+        .route("/dashboard/views", get(get_dashboard_views))
+        .route(
+            "/dashboard/views/{name}",
+            get(get_dashboard_view)
+                .put(put_dashboard_view)
+                .delete(delete_dashboard_view),
+        )
+        .route("/wallet", get(get_wallet))
+        .route("/readyz", get(get_readyz))
+        .route("/dashboard/{duration}", get(get_duration_dashboard_html))
+        .with_state(state)
+}
+
+pub fn market_link(slug: &str) -> String {
+    format!("https://polymarket.com/event/{slug}")
+}
+
+pub fn compute_in_interval(now_ts_utc: i64, start_ts_utc: i64, end_ts_utc: i64) -> bool {
+    start_ts_utc <= now_ts_utc && now_ts_utc < end_ts_utc
+}
+
+pub fn apply_filters(
+    rows: &[DashboardRow],
+    filters: &DashboardFilters,
+    now_ts_utc: i64,
+) -> Vec<DashboardRow> {
+    rows.iter()
+        .filter(|row| row_matches_filters(row, filters, now_ts_utc))
+        .cloned()
+        .collect()
+}
+
+fn row_matches_filters(row: &DashboardRow, filters: &DashboardFilters, now_ts_utc: i64) -> bool {
+    row_matches_coins(row, filters)
+        && filters.duration_selected(&row.duration)
+        && row_matches_bets_open(row, filters)
+        && row_matches_in_interval(row, filters, now_ts_utc)
+        && row_meets_min(row.volume_24h.as_deref(), filters.min_volume_24h)
+        && row_meets_min(row.liquidity.as_deref(), filters.min_liquidity)
+}
+
+pub fn format_row_for_display(row: &DashboardRow, now_ts_utc: i64) -> DashboardDisplayRow {
+    format_row_for_display_with_format(row, now_ts_utc, &NumberFormatConfig::default())
+}
+
+pub fn format_row_for_display_with_format(
+    row: &DashboardRow,
+    now_ts_utc: i64,
+    fmt: &NumberFormatConfig,
+) -> DashboardDisplayRow {
+    let in_interval = compute_in_interval(now_ts_utc, row.start_ts_utc, row.end_ts_utc);
+
+    DashboardDisplayRow {
+        slug: row.slug.clone(),
+        link_url: market_link(&row.slug),
+        coin: row.coin.clone(),
+        duration: row.duration.clone(),
+        start_ts_utc: row.start_ts_utc,
+        end_ts_utc: row.end_ts_utc,
+        bets_open: format_column_value("bets_open", row.bets_open.as_deref(), fmt),
+        in_interval: if in_interval { "yes" } else { "no" }.to_string(),
+        end_hhmm: utc_hhmm(row.end_ts_utc),
+        ref_price: format_column_value("ref_price", row.ref_price.as_deref(), fmt),
+        ref_capture_delay_ms: row.ref_capture_delay_ms,
+        price: format_column_value("price", row.price.as_deref(), fmt),
+        probability: format_column_value("probability", row.probability.as_deref(), fmt),
+        probability_raw: match (&row.probability_raw, &row.probability) {
+            (Some(raw), Some(shown)) if raw != shown => {
+                format_column_value("probability", Some(raw), fmt)
+            }
+            _ => String::new(),
+        },
+        probability_horizons: row
+            .probability_horizons
+            .iter()
+            .map(|(horizon, probability)| {
+                let shown = format_column_value("probability", Some(probability), fmt);
+                (*horizon, shown)
+            })
+            .collect(),
+        realized_vol: format_column_value("realized_vol", row.realized_vol.as_deref(), fmt),
+        price_vs_ref_pct: format_column_value(
+            "price_vs_ref_pct",
+            row.price_vs_ref_pct.as_deref(),
+            fmt,
+        ),
+        move_z: format_column_value("move_z", row.move_z.as_deref(), fmt),
+        best_bid_yes: format_column_value("best_bid_yes", row.best_bid_yes.as_deref(), fmt),
+        best_ask_yes: format_column_value("best_ask_yes", row.best_ask_yes.as_deref(), fmt),
+        position_net: format_column_value("position_net", row.position_net.as_deref(), fmt),
+        pos_yes: format_column_value("pos_yes", row.pos_yes.as_deref(), fmt),
+        pos_no: format_column_value("pos_no", row.pos_no.as_deref(), fmt),
+        offer_yes: format_column_value("offer_yes", row.offer_yes.as_deref(), fmt),
+        offer_no: format_column_value("offer_no", row.offer_no.as_deref(), fmt),
+        queue_ahead_yes: format_column_value("queue_ahead", row.queue_ahead_yes.as_deref(), fmt),
+        queue_ahead_no: format_column_value("queue_ahead", row.queue_ahead_no.as_deref(), fmt),
+        suggested_size: format_column_value("suggested_size", row.suggested_size.as_deref(), fmt),
+        net_profit: format_column_value("net_profit", row.net_profit.as_deref(), fmt),
+        taker_fee_pct: format_column_value("taker_fee_pct", row.taker_fee_pct.as_deref(), fmt),
+        maker_fee_pct: format_column_value("maker_fee_pct", row.maker_fee_pct.as_deref(), fmt),
+        fee_exponent: format_column_value("fee_exponent", row.fee_exponent.as_deref(), fmt),
+        reward_pct: format_column_value("reward_pct", row.reward_pct.as_deref(), fmt),
+        volume_24h: format_column_value("volume_24h", row.volume_24h.as_deref(), fmt),
+        liquidity: format_column_value("liquidity", row.liquidity.as_deref(), fmt),
+        mock_columns: row.mock_columns.clone(),
+        simulated_columns: row.simulated_columns.clone(),
+        stale_as_of_ts_utc: row.stale_as_of_ts_utc,
+        neg_risk_group: row.neg_risk_group.clone(),
+    }
+}
+
+/// Suggested stake for a row as `usdc@SIDE` (or `0` when sizing declines), from the
+/// row's model `probability`, the YES book (the mid of `best_bid_yes` and
+/// `best_ask_yes`, or the ask alone), and the taker fee curve. `None` until both the
+/// probability and the YES ask are known.
+pub fn suggested_size_for_row(
+    row: &DashboardRow,
+    cfg: &SizingConfig,
+    bankroll: f64,
+) -> Option<String> {
+    let probability_yes = parse_probability_fraction(row.probability.as_deref()?)?;
+    let book_price = |raw: &Option<String>| raw.as_deref()?.trim().parse::<f64>().ok();
+    let best_ask = book_price(&row.best_ask_yes)?;
+    let price_yes = book_price(&row.best_bid_yes).map_or(best_ask, |bid| (bid + best_ask) / 2.0);
+    let fee_rate = row
+        .taker_fee_pct
+        .as_deref()
+        .and_then(|raw| raw.trim().parse::<f64>().ok())
+        .unwrap_or(0.0);
+    let fee_exponent = row
+        .fee_exponent
+        .as_deref()
+        .and_then(|raw| raw.trim().parse::<f64>().ok());
+
+    let input = SizingInput {
+        probability_yes,
+        price_yes,
+        fee_per_share: taker_fee_per_share(price_yes, fee_rate, fee_exponent),
+        bankroll,
+    };
+    let decision = kelly_stake(&input, cfg).ok()?;
+    Some(match decision.side {
+        Some(side) if decision.is_trade() => format!("{}@{}", decision.stake_usdc, side.as_str()),
+        _ => "0".to_string(),
+    })
+}
+
+/// Fills `suggested_size` from each row's current probability and YES book; run it
+/// after the books and model probabilities are applied. Rows whose probability or
+/// book is still mock keep their values.
+pub fn apply_suggested_sizes<'a>(
+    cfg: &SizingConfig,
+    rows: impl IntoIterator<Item = &'a mut DashboardRow>,
+) {
+    for row in rows {
+        if ["probability", "best_ask_yes"]
+            .iter()
+            .any(|column| row.is_mock_column(column))
+        {
+            continue;
+        }
+        if let Some(size) = suggested_size_for_row(row, cfg, cfg.per_market_bankroll) {
+            row.suggested_size = Some(size);
+            row.mock_columns.retain(|entry| entry != "suggested_size");
+        }
+    }
+}
+
+/// Dry-run overlay: the strategy's would-be quotes shown in the Offer columns.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DryRunQuoteConfig {
+    pub enabled: bool,
+    /// Quoting phases and limits the would-be quotes are planned under.
+    pub quotes: PreMarketQuoteConfig,
+}
+
+impl Default for DryRunQuoteConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl DryRunQuoteConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_DASHBOARD_DRY_RUN_QUOTES` (defaults to on unless `PMM_MODE=live`) and
+    /// the quoting limits of [`PreMarketQuoteConfig::from_settings`].
+    pub fn from_settings(settings: &Settings) -> Self {
+        let live = settings.get::<RunMode>("PMM_MODE") == RunMode::Live;
+        Self {
+            enabled: settings
+                .get::<Option<bool>>("PMM_DASHBOARD_DRY_RUN_QUOTES")
+                .unwrap_or(!live),
+            quotes: PreMarketQuoteConfig::from_settings(settings),
+        }
+    }
+}
+
+/// Fills `best_bid_yes`/`best_ask_yes` from live YES books. Rows without a book keep
+/// their values; a side with no resting orders shows as empty.
+pub fn apply_order_books<'a>(
+    source: &dyn OrderBookSource,
+    rows: impl IntoIterator<Item = &'a mut DashboardRow>,
+) {
+    for row in rows {
+        let Some(top) = source.top_of_book(&row.slug) else {
+            continue;
+        };
+        row.best_bid_yes = top.best_bid.map(|price| price.to_string());
+        row.best_ask_yes = top.best_ask.map(|price| price.to_string());
+        row.mock_columns
+            .retain(|entry| entry != "best_bid_yes" && entry != "best_ask_yes");
+    }
+}
+
+/// Fills `price` with the latest live spot price and, while still unset, `ref_price`
+/// with the live reference of the row's interval. `ref_capture_delay_ms` counts whole
+/// seconds from the interval start to the captured price, like a store-filled reference.
+/// Rows without a live price keep their values.
+pub fn apply_live_prices<'a>(
+    source: &dyn PriceSource,
+    rows: impl IntoIterator<Item = &'a mut DashboardRow>,
+) {
+    for row in rows {
+        let Ok(coin) = parse_coin(&row.coin) else {
+            continue;
+        };
+        if let Some(latest) = source.latest(coin) {
+            row.price = Some(latest.price.to_string());
+            row.mock_columns.retain(|entry| entry != "price");
+        }
+        if row.ref_price.is_some() {
+            continue;
+        }
+        if let Some(reference) = source.reference(coin, row.start_ts_utc) {
+            row.ref_price = Some(reference.price.to_string());
+            row.ref_capture_delay_ms =
+                Some((reference.ts_ms_utc.div_euclid(1_000) - row.start_ts_utc) * 1_000);
+            row.mock_columns.retain(|entry| entry != "ref_price");
+        }
+    }
+}
+
+/// Fills `probability` with the model registered for each row's coin and duration,
+/// scored on the row's [`crate::spot_feature_row`] features. Rows of ended intervals,
+/// and rows without a model or both live prices, keep their value. Returns the inputs of
+/// every probability filled, for the [`crate::ModelInputStore`].
+pub fn apply_model_probabilities<'a>(
+    models: &ModelRegistry,
+    prices: &dyn PriceSource,
+    rows: impl IntoIterator<Item = &'a mut DashboardRow>,
+    now_ts_utc: i64,
+) -> Vec<ModelInputRecord> {
+    let schema = quote_feature_schema();
+    let mut captures = Vec::new();
+    for row in rows {
+        if row.end_ts_utc <= now_ts_utc {
+            continue;
+        }
+        let (Ok(coin), Ok(duration)) = (parse_coin(&row.coin), Duration::parse(&row.duration))
+        else {
+            continue;
+        };
+        let Some(features) =
+            interval_feature_row(prices, coin, row.start_ts_utc, row.end_ts_utc, now_ts_utc)
+        else {
+            continue;
+        };
+        let Some(prediction) = models.predict(coin, duration, &features) else {
+            continue;
+        };
+        row.probability = Some(prediction.probability_yes.to_string());
+        row.probability_horizons = horizon_probabilities(models, prices, coin, duration, row);
+        row.mock_columns.retain(|entry| entry != "probability");
+        captures.push(ModelInputRecord::new(
+            &row.slug,
+            now_ts_utc,
+            ModelInputSource::Dashboard,
+            &schema,
+            &features,
+            &prediction,
+        ));
+    }
+    captures
+}
+
+/// The model's probability of `row`'s current move with each of
+/// [`PROBABILITY_HORIZONS_SECONDS`] left to expiry, for the horizons no longer than the
+/// interval.
+fn horizon_probabilities(
+    models: &ModelRegistry,
+    prices: &dyn PriceSource,
+    coin: Coin,
+    duration: Duration,
+    row: &DashboardRow,
+) -> BTreeMap<u32, String> {
+    PROBABILITY_HORIZONS_SECONDS
+        .into_iter()
+        .filter(|horizon| i64::from(*horizon) <= row.end_ts_utc - row.start_ts_utc)
+        .filter_map(|horizon| {
+            let as_if_ts_utc = row.end_ts_utc - i64::from(horizon);
+            let features =
+                interval_feature_row(prices, coin, row.start_ts_utc, row.end_ts_utc, as_if_ts_utc)?;
+            let prediction = models.predict(coin, duration, &features)?;
+            Some((horizon, prediction.probability_yes.to_string()))
+        })
+        .collect()
+}
+
+/// Runs the predictions in `captures` through `smoother`: their rows keep the model
+/// output in `probability_raw` and the published value in `probability_smoothed`, and
+/// `probability` shows the smoothed one only with `display` set. Disabled smoothing
+/// leaves rows untouched.
+pub fn apply_probability_smoothing<'a>(
+    smoother: &ProbabilitySmoother,
+    captures: &[ModelInputRecord],
+    rows: impl IntoIterator<Item = &'a mut DashboardRow>,
+) {
+    if !smoother.config().enabled() {
+        return;
+    }
+    let scored: HashMap<&str, &ModelInputRecord> = captures
+        .iter()
+        .map(|capture| (capture.slug.as_str(), capture))
+        .collect();
+    for row in rows {
+        let Some(capture) = scored.get(row.slug.as_str()) else {
+            continue;
+        };
+        let smoothed = smoother
+            .update(&row.slug, capture.probability_yes, capture.ts_utc)
+            .to_string();
+        row.probability_raw = Some(capture.probability_yes.to_string());
+        if smoother.config().display {
+            row.probability = Some(smoothed.clone());
+        }
+        row.probability_smoothed = Some(smoothed);
+    }
+}
+
+/// Fills `offer_yes`/`offer_no` (`size@price`) with the bids the strategy would rest,
+/// on rows whose offers are still mocked, and marks them simulated. A row gets quotes
+/// only in a quoting phase of an enabled coin/duration and once its `probability` is
+/// known (its `probability_smoothed` when set); without a YES book the bids are not capped against it. Disabled configs
+/// leave rows untouched.
+pub fn apply_dry_run_quotes<'a>(
+    cfg: &DryRunQuoteConfig,
+    toggles: &MarketToggles,
+    rows: impl IntoIterator<Item = &'a mut DashboardRow>,
+    now_ts_utc: i64,
+) {
+    if !cfg.enabled {
+        return;
+    }
+    for row in rows {
+        let (Ok(coin), Ok(duration)) = (parse_coin(&row.coin), Duration::parse(&row.duration))
+        else {
+            continue;
+        };
+        let plan = toggles.plan_quote_window(
+            coin,
+            &QuoteWindowInput {
+                slug: row.slug.clone(),
+                duration,
+                start_ts_utc: row.start_ts_utc,
+                end_ts_utc: row.end_ts_utc,
+                accepting_orders_ts_utc: row.accepting_orders_ts_utc,
+            },
+            now_ts_utc,
+            &cfg.quotes,
+        );
+        let (Some(limits), Some(probability_yes)) = (
+            plan.limits,
+            row.probability_smoothed
+                .as_deref()
+                .or(row.probability.as_deref())
+                .and_then(parse_probability_fraction),
+        ) else {
+            continue;
+        };
+        let price = |raw: Option<&str>| raw.and_then(|value| value.trim().parse::<f64>().ok());
+        let quotes = plan_maker_quotes(
+            probability_yes,
+            price(row.best_bid_yes.as_deref()),
+            price(row.best_ask_yes.as_deref()),
+            &limits,
+        );
+        for (column, level) in [("offer_yes", quotes.yes), ("offer_no", quotes.no)] {
+            if !row.is_mock_column(column) {
+                continue;
+            }
+            let value = match level {
+                Some(level) => format!("{}@{}", level.size, level.price),
+                None => "0".to_string(),
+            };
+            match column {
+                "offer_yes" => row.offer_yes = Some(value),
+                _ => row.offer_no = Some(value),
+            }
+            row.mock_columns.retain(|entry| entry != column);
+            row.simulated_columns.push(column.to_string());
+        }
+    }
+}
+
+/// Shows our resting buy orders in `offer_yes`/`offer_no` as `remaining@price`, with
+/// the estimated size queued ahead in `queue_ahead_yes`/`queue_ahead_no`. With several
+/// open orders on one outcome the best-priced one is shown. Real orders replace mocked
+/// or simulated offers.
+pub fn apply_queue_estimates<'a>(
+    rows: impl IntoIterator<Item = &'a mut DashboardRow>,
+    estimates: &[QueueEstimate],
+) {
+    let round = |value: f64| (value * 100.0).round() / 100.0;
+    for row in rows {
+        for (outcome, column) in [(SizingSide::Yes, "offer_yes"), (SizingSide::No, "offer_no")] {
+            let Some(best) = estimates
+                .iter()
+                .filter(|estimate| {
+                    estimate.order.slug == row.slug
+                        && estimate.order.outcome == outcome
+                        && estimate.order.side == OrderSide::Buy
+                        && estimate.remaining() > 0.0
+                })
+                .max_by(|a, b| a.order.price.total_cmp(&b.order.price))
+            else {
+                continue;
+            };
+            let offer = Some(format!("{}@{}", round(best.remaining()), best.order.price));
+            let ahead = Some(round(best.ahead).to_string());
+            match outcome {
+                SizingSide::Yes => (row.offer_yes, row.queue_ahead_yes) = (offer, ahead),
+                SizingSide::No => (row.offer_no, row.queue_ahead_no) = (offer, ahead),
+            }
+            row.mock_columns.retain(|entry| entry != column);
+            row.simulated_columns.retain(|entry| entry != column);
+        }
+    }
+}
+
+/// Fills `pos_yes`/`pos_no` (`shares@avg_price`), `position_net` (net shares of the
+/// larger side at its average price, `shares@avg_price@YES|NO`), and `net_profit`
+/// (realized plus unrealized at the YES book mid, fees included; realized only while
+/// the row has no live book) from tracked positions. Rows never filled keep their
+/// values.
+pub fn apply_positions<'a>(
+    source: &dyn PositionSource,
+    rows: impl IntoIterator<Item = &'a mut DashboardRow>,
+) {
+    let round = |value: f64| (value * 1_000_000.0).round() / 1_000_000.0;
+    let held = |shares: f64, avg_price: f64| {
+        if shares == 0.0 {
+            "0".to_string()
+        } else {
+            format!("{}@{}", round(shares), round(avg_price))
+        }
+    };
+    for row in rows {
+        let Some(position) = source.position(&row.slug) else {
+            continue;
+        };
+        let live_price = |column: &str, value: &Option<String>| {
+            (!row.is_mock_column(column))
+                .then(|| value.as_deref()?.trim().parse::<f64>().ok())
+                .flatten()
+        };
+        let mark_yes = match (
+            live_price("best_bid_yes", &row.best_bid_yes),
+            live_price("best_ask_yes", &row.best_ask_yes),
+        ) {
+            (Some(bid), Some(ask)) => Some((bid + ask) / 2.0),
+            _ => None,
+        };
+        let net = position.net_shares();
+        row.position_net = Some(if net > 0.0 {
+            format!("{}@{}@YES", round(net), round(position.yes.avg_price))
+        } else if net < 0.0 {
+            format!("{}@{}@NO", round(-net), round(position.no.avg_price))
+        } else {
+            "0".to_string()
+        });
+        row.pos_yes = Some(held(position.yes.shares, position.yes.avg_price));
+        row.pos_no = Some(held(position.no.shares, position.no.avg_price));
+        row.net_profit = Some(round(position.net_profit_usdc(mark_yes)).to_string());
+        for column in ["pos_yes", "pos_no", "position_net", "net_profit"] {
+            row.mock_columns.retain(|entry| entry != column);
+            row.simulated_columns.retain(|entry| entry != column);
+        }
+    }
+}
+
+/// What one coin/duration window currently holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowOccupancy {
+    #[default]
+    Empty,
+    Position,
+    Orders,
+    PositionAndOrders,
+}
+
+impl WindowOccupancy {
+    fn from_holdings(position: bool, orders: bool) -> Self {
+        match (position, orders) {
+            (false, false) => Self::Empty,
+            (true, false) => Self::Position,
+            (false, true) => Self::Orders,
+            (true, true) => Self::PositionAndOrders,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Empty => "empty",
+            Self::Position => "position",
+            Self::Orders => "orders",
+            Self::PositionAndOrders => "position_and_orders",
+        }
+    }
+}
+
+/// Occupancy of the previous/active/next windows of one coin/duration pair.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OccupancyCell {
+    pub coin: String,
+    pub duration: String,
+    pub previous: WindowOccupancy,
+    pub active: WindowOccupancy,
+    pub next: WindowOccupancy,
+    /// Exposure left in a window that already ended.
+    pub needs_attention: bool,
+}
+
+/// Coin × duration grid of window occupancy, coins major.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OccupancyGrid {
+    pub now_ts_utc: i64,
+    pub coins: Vec<String>,
+    pub durations: Vec<String>,
+    pub cells: Vec<OccupancyCell>,
+    pub attention_count: usize,
+}
+
+/// Summarizes which windows hold positions or open orders. Mock and simulated cells
+/// are not holdings; rows that ended before `now_ts_utc` count as the previous window.
+pub fn occupancy_grid(rows: &[DashboardRow], now_ts_utc: i64) -> OccupancyGrid {
+    let held = |row: &DashboardRow, column: &str, value: &Option<String>| {
+        live_size(row, column, value).is_some_and(|size| size != 0.0)
+    };
+    let coins = coin_options();
+    let mut cells = Vec::with_capacity(coins.len() * DURATION_OPTIONS.len());
+    for &coin in &coins {
+        for duration in DURATION_OPTIONS {
+            // (position, orders) for previous/active/next.
+            let mut windows = [(false, false); 3];
+            for row in rows
+                .iter()
+                .filter(|row| row.coin == coin && row.duration == duration)
+            {
+                let window = if row.end_ts_utc <= now_ts_utc {
+                    0
+                } else if row.start_ts_utc <= now_ts_utc {
+                    1
+                } else {
+                    2
+                };
+                let position = held(row, "pos_yes", &row.pos_yes)
+                    || held(row, "pos_no", &row.pos_no)
+                    || held(row, "position_net", &row.position_net);
+                let orders =
+                    held(row, "offer_yes", &row.offer_yes) || held(row, "offer_no", &row.offer_no);
+                windows[window].0 |= position;
+                windows[window].1 |= orders;
+            }
+            let [previous, active, next] =
+                windows.map(|(position, orders)| WindowOccupancy::from_holdings(position, orders));
+            cells.push(OccupancyCell {
+                coin: coin.to_string(),
+                duration: duration.to_string(),
+                previous,
+                active,
+                next,
+                needs_attention: previous != WindowOccupancy::Empty,
+            });
+        }
+    }
+    OccupancyGrid {
+        now_ts_utc,
+        coins: coins.iter().map(|coin| coin.to_string()).collect(),
+        durations: DURATION_OPTIONS
+            .iter()
+            .map(|duration| duration.to_string())
+            .collect(),
+        attention_count: cells.iter().filter(|cell| cell.needs_attention).count(),
+        cells,
+    }
+}
+
+/// Size in a position/offer cell (`"10"` or `"10@0.52"`), unless the cell is mock or
+/// simulated.
+fn live_size(row: &DashboardRow, column: &str, value: &Option<String>) -> Option<f64> {
+    if row.is_mock_column(column) || row.is_simulated_column(column) {
+        return None;
+    }
+    value
+        .as_deref()
+        .and_then(|value| value.split('@').next())
+        .and_then(|size| size.trim().parse::<f64>().ok())
+}
+
+/// A notable change listed in the dashboard activity feed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityKind {
+    /// The interval ended; the market now awaits resolution.
+    MarketEnded,
+    /// The active interval of a coin/duration moved to a new market.
+    IntervalRolled,
+    /// Discovery found the market's Gamma metadata.
+    RowResolved,
+    /// Discovery lost the market's Gamma metadata.
+    RowUnresolved,
+    /// The model probability moved by at least `ActivityFeedConfig::probability_move`.
+    ProbabilityMove,
+    /// A live YES/NO position changed size.
+    Fill,
 }
 
-pub fn market_link(slug: &str) -> String {
-    format!("https://polymarket.com/event/{slug}")
+impl ActivityKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::MarketEnded => "market_ended",
+            Self::IntervalRolled => "interval_rolled",
+            Self::RowResolved => "row_resolved",
+            Self::RowUnresolved => "row_unresolved",
+            Self::ProbabilityMove => "probability_move",
+            Self::Fill => "fill",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActivityEvent {
+    /// Clock second the change was observed at.
+    pub ts_utc: i64,
+    pub kind: ActivityKind,
+    pub slug: String,
+    pub coin: String,
+    pub duration: String,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ActivityFeedConfig {
+    /// Events kept; older ones drop off.
+    pub capacity: usize,
+    /// Smallest probability change, as a fraction, reported as a move.
+    pub probability_move: f64,
+}
+
+impl Default for ActivityFeedConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl ActivityFeedConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_ACTIVITY_FEED_CAPACITY` and `PMM_ACTIVITY_PROBABILITY_MOVE`, falling
+    /// back to defaults for missing/invalid values.
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            capacity: settings.get_where("PMM_ACTIVITY_FEED_CAPACITY", |value| *value > 0),
+            probability_move: settings
+                .get_where("PMM_ACTIVITY_PROBABILITY_MOVE", |value| *value > 0.0),
+        }
+    }
+}
+
+/// Changes between `prev` seen at `prev_now_ts_utc` and `next` seen at `now_ts_utc`, in
+/// row order: rollovers first, then per-row events.
+pub fn activity_between(
+    prev: &DashboardSnapshot,
+    prev_now_ts_utc: i64,
+    next: &DashboardSnapshot,
+    now_ts_utc: i64,
+    cfg: &ActivityFeedConfig,
+) -> Vec<ActivityEvent> {
+    let event = |row: &DashboardRow, kind: ActivityKind, detail: String| ActivityEvent {
+        ts_utc: now_ts_utc,
+        kind,
+        slug: row.slug.clone(),
+        coin: row.coin.clone(),
+        duration: row.duration.clone(),
+        detail,
+    };
+    let mut events = Vec::new();
+
+    let active = |rows: &[DashboardRow], now: i64| -> HashMap<(String, String), String> {
+        rows.iter()
+            .filter(|row| row.start_ts_utc <= now && now < row.end_ts_utc)
+            .map(|row| ((row.coin.clone(), row.duration.clone()), row.slug.clone()))
+            .collect()
+    };
+    let active_before = active(&prev.rows, prev_now_ts_utc);
+    let active_after = active(&next.rows, now_ts_utc);
+    for row in &next.rows {
+        let key = (row.coin.clone(), row.duration.clone());
+        if active_after.get(&key) != Some(&row.slug) {
+            continue;
+        }
+        if let Some(previous) = active_before.get(&key).filter(|slug| **slug != row.slug) {
+            events.push(event(
+                row,
+                ActivityKind::IntervalRolled,
+                format!("from {previous}"),
+            ));
+        }
+    }
+
+    for row in &prev.rows {
+        if prev_now_ts_utc < row.end_ts_utc && row.end_ts_utc <= now_ts_utc {
+            events.push(event(
+                row,
+                ActivityKind::MarketEnded,
+                format!("ended {} UTC", utc_hhmm(row.end_ts_utc)),
+            ));
+        }
+    }
+
+    let before: HashMap<&str, &DashboardRow> = prev
+        .rows
+        .iter()
+        .map(|row| (row.slug.as_str(), row))
+        .collect();
+    for row in &next.rows {
+        let Some(old) = before.get(row.slug.as_str()) else {
+            continue;
+        };
+        match (&old.bets_open, &row.bets_open) {
+            (None, Some(bets_open)) => events.push(event(
+                row,
+                ActivityKind::RowResolved,
+                format!("bets {bets_open}"),
+            )),
+            (Some(_), None) => events.push(event(
+                row,
+                ActivityKind::RowUnresolved,
+                "metadata lost".to_string(),
+            )),
+            _ => {}
+        }
+
+        let probability = |row: &DashboardRow| {
+            (!row.is_mock_column("probability"))
+                .then_some(row.probability.as_deref())
+                .flatten()
+                .and_then(parse_probability_fraction)
+        };
+        if let (Some(from), Some(to)) = (probability(old), probability(row)) {
+            if (to - from).abs() >= cfg.probability_move - 1e-9 {
+                events.push(event(
+                    row,
+                    ActivityKind::ProbabilityMove,
+                    format!("{:.1}% -> {:.1}%", from * 100.0, to * 100.0),
+                ));
+            }
+        }
+
+        for (column, side, old_value, new_value) in [
+            ("pos_yes", "YES", &old.pos_yes, &row.pos_yes),
+            ("pos_no", "NO", &old.pos_no, &row.pos_no),
+        ] {
+            let from = live_size(old, column, old_value).unwrap_or(0.0);
+            let to = live_size(row, column, new_value).unwrap_or(0.0);
+            if from != to {
+                events.push(event(
+                    row,
+                    ActivityKind::Fill,
+                    format!("{side} {from} -> {to}"),
+                ));
+            }
+        }
+    }
+    events
+}
+
+/// Rolling feed of [`activity_between`] events across the snapshots it observes,
+/// newest first. It only advances when observed, so changes between two observations
+/// collapse into one comparison; [`ActivityFeed::follow`] observes every publication.
+pub struct ActivityFeed {
+    cfg: ActivityFeedConfig,
+    state: Mutex<ActivityFeedState>,
+}
+
+#[derive(Default)]
+struct ActivityFeedState {
+    last: Option<(Arc<DashboardSnapshot>, i64)>,
+    events: VecDeque<ActivityEvent>,
+}
+
+impl ActivityFeed {
+    pub fn new(cfg: ActivityFeedConfig) -> Self {
+        Self {
+            cfg,
+            state: Mutex::new(ActivityFeedState::default()),
+        }
+    }
+
+    /// Records the changes from the last observed snapshot to `snapshot` at
+    /// `now_ts_utc`. The first observation only sets the baseline.
+    pub fn observe(&self, snapshot: Arc<DashboardSnapshot>, now_ts_utc: i64) {
+        let mut state = self
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some((prev, prev_now)) = &state.last {
+            if Arc::ptr_eq(prev, &snapshot) && *prev_now == now_ts_utc {
+                return;
+            }
+            let events = activity_between(prev, *prev_now, &snapshot, now_ts_utc, &self.cfg);
+            // Newest first, keeping one observation's events in row order.
+            for event in events.into_iter().rev() {
+                state.events.push_front(event);
+            }
+            state.events.truncate(self.cfg.capacity);
+        }
+        state.last = Some((snapshot, now_ts_utc));
+    }
+
+    /// Observes `source` now, then on every publication and clock second from a
+    /// background task, so the feed advances while nobody polls the dashboard. The
+    /// task stops once the feed is dropped. Needs a Tokio runtime.
+    pub fn follow(self: &Arc<Self>, source: Arc<dyn DashboardSnapshotSource>) {
+        self.observe(source.snapshot(), source.now_ts_utc());
+        let feed = Arc::downgrade(self);
+        let mut published = source.published();
+        tokio::spawn(async move {
+            let mut tick = tokio::time::interval(std::time::Duration::from_secs(1));
+            tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            loop {
+                let closed = match published.as_mut() {
+                    Some(rx) => tokio::select! {
+                        changed = rx.changed() => changed.is_err(),
+                        _ = tick.tick() => false,
+                    },
+                    None => {
+                        tick.tick().await;
+                        false
+                    }
+                };
+                if closed {
+                    published = None;
+                }
+                let Some(feed) = feed.upgrade() else {
+                    return;
+                };
+                feed.observe(source.snapshot(), source.now_ts_utc());
+            }
+        });
+    }
+
+    /// Kept events, newest first.
+    pub fn events(&self) -> Vec<ActivityEvent> {
+        let state = self
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        state.events.iter().cloned().collect()
+    }
 }
 
-pub fn compute_in_interval(now_ts_utc: i64, start_ts_utc: i64, end_ts_utc: i64) -> bool {
-    start_ts_utc <= now_ts_utc && now_ts_utc < end_ts_utc
+/// Body of `/dashboard/activity`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActivityFeedView {
+    pub now_ts_utc: i64,
+    pub events: Vec<ActivityEvent>,
 }
 
-pub fn apply_filters(
-    rows: &[DashboardRow],
+pub fn build_display_snapshot(
+    snapshot: &DashboardSnapshot,
     filters: &DashboardFilters,
     now_ts_utc: i64,
-) -> Vec<DashboardRow> {
-    rows.iter()
-        .filter(|row| filters.coin_selected(&row.coin))
-        .filter(|row| filters.duration_selected(&row.duration))
-        .filter(|row| row_matches_bets_open(row, filters))
-        .filter(|row| row_matches_in_interval(row, filters, now_ts_utc))
-        .cloned()
-        .collect()
-}
-
-pub fn format_row_for_display(row: &DashboardRow, now_ts_utc: i64) -> DashboardDisplayRow {
-    let in_interval = compute_in_interval(now_ts_utc, row.start_ts_utc, row.end_ts_utc);
-
-    DashboardDisplayRow {
-        slug: row.slug.clone(),
-        link_url: market_link(&row.slug),
-        coin: row.coin.clone(),
-        duration: row.duration.clone(),
-        start_ts_utc: row.start_ts_utc,
-        end_ts_utc: row.end_ts_utc,
-        bets_open: format_column_value("bets_open", row.bets_open.as_deref()),
-        in_interval: if in_interval { "yes" } else { "no" }.to_string(),
-        end_hhmm: utc_hhmm(row.end_ts_utc),
-        ref_price: format_column_value("ref_price", row.ref_price.as_deref()),
-        price: format_column_value("price", row.price.as_deref()),
-        probability: format_column_value("probability", row.probability.as_deref()),
-        best_bid_yes: format_column_value("best_bid_yes", row.best_bid_yes.as_deref()),
-        best_ask_yes: format_column_value("best_ask_yes", row.best_ask_yes.as_deref()),
-        position_net: format_column_value("position_net", row.position_net.as_deref()),
-        pos_yes: format_column_value("pos_yes", row.pos_yes.as_deref()),
-        pos_no: format_column_value("pos_no", row.pos_no.as_deref()),
-        offer_yes: format_column_value("offer_yes", row.offer_yes.as_deref()),
-        offer_no: format_column_value("offer_no", row.offer_no.as_deref()),
-        net_profit: format_column_value("net_profit", row.net_profit.as_deref()),
-        taker_fee_pct: format_column_value("taker_fee_pct", row.taker_fee_pct.as_deref()),
-        maker_fee_pct: format_column_value("maker_fee_pct", row.maker_fee_pct.as_deref()),
-        fee_exponent: format_column_value("fee_exponent", row.fee_exponent.as_deref()),
-        reward_pct: format_column_value("reward_pct", row.reward_pct.as_deref()),
-        mock_columns: row.mock_columns.clone(),
-    }
+) -> DashboardDisplaySnapshot {
+    build_display_snapshot_with_format(
+        snapshot,
+        filters,
+        now_ts_utc,
+        &NumberFormatConfig::default(),
+    )
 }
 
-pub fn build_display_snapshot(
+pub fn build_display_snapshot_with_format(
     snapshot: &DashboardSnapshot,
     filters: &DashboardFilters,
     now_ts_utc: i64,
+    fmt: &NumberFormatConfig,
 ) -> DashboardDisplaySnapshot {
     let filtered = apply_filters(&snapshot.rows, filters, now_ts_utc);
-    let rows = filtered
+    let rows = sort_and_page(filtered.iter().map(|row| (row, row)), filters, now_ts_utc)
+        .into_iter()
+        .map(|row| {
+            let mut display = format_row_for_display_with_format(row, now_ts_utc, fmt);
+            select_probability_horizons(&mut display, &filters.probability_horizons);
+            display
+        })
+        .collect();
+
+    DashboardDisplaySnapshot {
+        now_ts_utc,
+        seq: snapshot.seq,
+        rows,
+        source_status: SnapshotStatus::default(),
+    }
+}
+
+/// Row-level change between two display snapshots, as sent on `/dashboard/stream`.
+///
+/// A delta applies only to a client holding exactly `prev_seq`; anything else is a
+/// gap and the client must resync from `/dashboard/snapshot`. `seq == prev_seq` when
+/// only time-derived columns (bets open, in interval) changed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DashboardSnapshotDelta {
+    pub seq: u64,
+    pub prev_seq: u64,
+    pub now_ts_utc: i64,
+    /// Slugs of every row after the delta, in display order.
+    pub order: Vec<String>,
+    pub upserts: Vec<DashboardDisplayRow>,
+    pub removed: Vec<String>,
+    pub source_status: SnapshotStatus,
+}
+
+/// One `/dashboard/ws` text frame: `{"type": "snapshot", ...}` on connect, then
+/// `{"type": "delta", ...}` whenever the filtered display changes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DashboardPush {
+    Snapshot(DashboardDisplaySnapshot),
+    Delta(DashboardSnapshotDelta),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("snapshot seq gap: holding {held_seq}, delta applies to {prev_seq}")]
+pub struct SnapshotSeqGap {
+    pub held_seq: u64,
+    pub prev_seq: u64,
+}
+
+/// Delta taking `prev` to `next`, or `None` when rows, status, and seq are unchanged.
+pub fn diff_display_snapshots(
+    prev: &DashboardDisplaySnapshot,
+    next: &DashboardDisplaySnapshot,
+) -> Option<DashboardSnapshotDelta> {
+    let before: BTreeMap<&str, &DashboardDisplayRow> = prev
+        .rows
+        .iter()
+        .map(|row| (row.slug.as_str(), row))
+        .collect();
+    let after: HashSet<&str> = next.rows.iter().map(|row| row.slug.as_str()).collect();
+
+    let upserts: Vec<DashboardDisplayRow> = next
+        .rows
         .iter()
-        .map(|row| format_row_for_display(row, now_ts_utc))
+        .filter(|row| before.get(row.slug.as_str()) != Some(row))
+        .cloned()
+        .collect();
+    let removed: Vec<String> = prev
+        .rows
+        .iter()
+        .filter(|row| !after.contains(row.slug.as_str()))
+        .map(|row| row.slug.clone())
         .collect();
+    let order: Vec<String> = next.rows.iter().map(|row| row.slug.clone()).collect();
+    let reordered = prev
+        .rows
+        .iter()
+        .map(|row| row.slug.as_str())
+        .ne(order.iter().map(String::as_str));
+
+    if upserts.is_empty()
+        && removed.is_empty()
+        && !reordered
+        && prev.seq == next.seq
+        && prev.source_status == next.source_status
+    {
+        return None;
+    }
+
+    Some(DashboardSnapshotDelta {
+        seq: next.seq,
+        prev_seq: prev.seq,
+        now_ts_utc: next.now_ts_utc,
+        order,
+        upserts,
+        removed,
+        source_status: next.source_status.clone(),
+    })
+}
 
-    DashboardDisplaySnapshot { now_ts_utc, rows }
+/// Applies `delta` in place. On a seq gap `held` is left untouched and the caller
+/// should replace it with a fresh full snapshot.
+pub fn apply_snapshot_delta(
+    held: &mut DashboardDisplaySnapshot,
+    delta: &DashboardSnapshotDelta,
+) -> Result<(), SnapshotSeqGap> {
+    if held.seq != delta.prev_seq {
+        return Err(SnapshotSeqGap {
+            held_seq: held.seq,
+            prev_seq: delta.prev_seq,
+        });
+    }
+
+    let mut by_slug: BTreeMap<String, DashboardDisplayRow> = std::mem::take(&mut held.rows)
+        .into_iter()
+        .map(|row| (row.slug.clone(), row))
+        .collect();
+    for slug in &delta.removed {
+        by_slug.remove(slug);
+    }
+    for row in &delta.upserts {
+        by_slug.insert(row.slug.clone(), row.clone());
+    }
+    held.rows = delta
+        .order
+        .iter()
+        .filter_map(|slug| by_slug.remove(slug))
+        .collect();
+    held.seq = delta.seq;
+    held.now_ts_utc = delta.now_ts_utc;
+    held.source_status = delta.source_status.clone();
+    Ok(())
 }
 
 pub fn render_dashboard_html(snapshot: &DashboardSnapshot) -> String {
     let filters = DashboardFilters::all_selected();
-    render_dashboard_html_with_filters(snapshot, &filters, Utc::now().timestamp())
+    let now_ts_utc = Utc::now().timestamp();
+    let display = build_display_snapshot(snapshot, &filters, now_ts_utc);
+    render_dashboard_html_with_filters(
+        Some(&occupancy_grid(&snapshot.rows, now_ts_utc)),
+        &display,
+        &filters,
+        now_ts_utc,
+        &SnapshotStatus::default(),
+        &[],
+        None,
+        &[],
+        None,
+    )
 }
 
+/// `display` is the snapshot filtered with `filters` and formatted; `occupancy` is the
+/// grid of the unfiltered snapshot, and `None` leaves out both it and the wallet
+/// headroom. `preset` is the duration pinned by a `/dashboard/{duration}` route, which
+/// replaces the duration checkboxes with links. `views` are the saved view names,
+/// linked from the filters; `view` is the one `filters` were expanded from.
+#[allow(clippy::too_many_arguments)]
 fn render_dashboard_html_with_filters(
-    snapshot: &DashboardSnapshot,
+    occupancy: Option<&OccupancyGrid>,
+    display: &DashboardDisplaySnapshot,
     filters: &DashboardFilters,
     now_ts_utc: i64,
+    status: &SnapshotStatus,
+    activity: &[ActivityEvent],
+    preset: Option<Duration>,
+    views: &[String],
+    view: Option<&str>,
 ) -> String {
-    let display = build_display_snapshot(snapshot, filters, now_ts_utc);
-    let now_utc = Utc::now().format("%Y-%m-%d %H:%M:%S UTC").to_string();
+    let now_utc = Utc
+        .timestamp_opt(now_ts_utc, 0)
+        .single()
+        .map(|now| now.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+        .unwrap_or_else(|| "-".to_string());
 
     let mut out = String::new();
     out.push_str("<!DOCTYPE html><html><head><meta charset=\"utf-8\">\n");
     out.push_str("<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n");
     out.push_str("<title>PMM Dashboard</title>\n");
-    out.push_str("<style>:root{--bg:#f5f1e7;--bg2:#e9f0f2;--card:#ffffff;--ink:#182026;--muted:#5f6a73;--line:#d7dce1;--head:#14343f;--btn:#0c5f78;--btnhover:#094d61;--mockbg:#fff5b8;--mockink:#555c63}*{box-sizing:border-box}body{margin:0;color:var(--ink);font-family:\"Space Grotesk\",\"Avenir Next\",\"Segoe UI\",sans-serif;background:radial-gradient(circle at 10% 5%, #ffe7a3 0%, transparent 30%),radial-gradient(circle at 90% 0%, #b9e5f0 0%, transparent 28%),linear-gradient(160deg,var(--bg),var(--bg2));min-height:100vh}.shell{max-width:none;width:100%;margin:0;padding:20px 16px 26px}.hero{background:linear-gradient(135deg,#102f3a 0%,#24576b 100%);color:#f7fbfc;border-radius:16px;padding:18px 20px;box-shadow:0 10px 30px rgba(16,47,58,.25)}.hero h1{margin:0 0 8px;font-size:1.58rem}.hero-meta{display:flex;gap:14px;flex-wrap:wrap;font-size:.9rem;color:#dcebf0}.filters{margin-top:12px;background:rgba(255,255,255,.1);border:1px solid rgba(255,255,255,.22);border-radius:12px;padding:10px 12px}.filter-grid{display:grid;grid-template-columns:repeat(4,minmax(160px,1fr));gap:10px}.filter-block{background:rgba(0,0,0,.12);border-radius:10px;padding:8px}.filter-title{font-size:.74rem;letter-spacing:.04em;text-transform:uppercase;margin:0 0 6px;color:#dbeaf0}.filter-item{display:flex;align-items:center;gap:6px;font-size:.85rem;margin:3px 0}.filter-actions{margin-top:10px;display:flex;gap:10px;align-items:center}.auto-note{font-size:.76rem;color:#dcebf0;opacity:.9}.btn{padding:7px 10px;border-radius:8px;border:1px solid rgba(0,0,0,.15);font-weight:700;font-size:.78rem;cursor:pointer}.btn-reset{background:#e4eef2;color:#1b3642;text-decoration:none}.card{margin-top:14px;background:var(--card);border:1px solid #cbd4db;border-radius:16px;overflow:hidden;box-shadow:0 12px 28px rgba(26,35,42,.12)}.table-wrap{overflow:auto;max-height:75vh}table{width:100%;border-collapse:collapse;min-width:1300px}thead th{position:sticky;top:0;z-index:2;background:var(--head);color:#f2f7f9;font-size:.79rem;text-transform:uppercase;letter-spacing:.04em;padding:10px;border-bottom:1px solid #0e2730}tbody td{font-size:.84rem;padding:8px 10px;border-bottom:1px solid var(--line);white-space:nowrap}tbody tr:nth-child(even){background:#fafcfd}.market-cell{min-width:220px}.market-btn{display:inline-flex;align-items:center;justify-content:center;background:linear-gradient(135deg,var(--btn),#0f7592);color:#fff;text-decoration:none;padding:7px 10px;border-radius:9px;font-weight:700;font-size:.76rem;border:1px solid rgba(0,0,0,.12);box-shadow:0 2px 8px rgba(12,95,120,.25)}.market-btn:hover{background:linear-gradient(135deg,var(--btnhover),#0d5f78)}.slug-id{display:block;margin-top:6px;font-family:\"IBM Plex Mono\",\"SFMono-Regular\",monospace;font-size:.67rem;color:var(--muted);max-width:260px;overflow:hidden;text-overflow:ellipsis}.cell-mock{background:linear-gradient(135deg,var(--mockbg) 0%,#fff3ca 100%);color:var(--mockink)}.cell-mock::after{content:\" M\";font-size:.62rem;font-weight:700;color:#8c6a00}.legend{padding:10px 14px;border-top:1px solid var(--line);font-size:.8rem;color:var(--muted);background:#f8fbfc;display:flex;justify-content:space-between;gap:12px;flex-wrap:wrap}.legend b{color:#8c6a00}@media (max-width:980px){.filter-grid{grid-template-columns:repeat(2,minmax(150px,1fr))}}@media (max-width:760px){.hero h1{font-size:1.28rem}.shell{padding:12px}.card{margin-top:12px;border-radius:12px}.filter-grid{grid-template-columns:1fr}}</style>\n");
+    out.push_str("<style>:root{--bg:#f5f1e7;--bg2:#e9f0f2;--card:#ffffff;--ink:#182026;--muted:#5f6a73;--line:#d7dce1;--head:#14343f;--btn:#0c5f78;--btnhover:#094d61;--mockbg:#fff5b8;--mockink:#555c63;--simbg:#e6f1fb;--simink:#1b4a72}*{box-sizing:border-box}body{margin:0;color:var(--ink);font-family:\"Space Grotesk\",\"Avenir Next\",\"Segoe UI\",sans-serif;background:radial-gradient(circle at 10% 5%, #ffe7a3 0%, transparent 30%),radial-gradient(circle at 90% 0%, #b9e5f0 0%, transparent 28%),linear-gradient(160deg,var(--bg),var(--bg2));min-height:100vh}.shell{max-width:none;width:100%;margin:0;padding:20px 16px 26px}.hero{background:linear-gradient(135deg,#102f3a 0%,#24576b 100%);color:#f7fbfc;border-radius:16px;padding:18px 20px;box-shadow:0 10px 30px rgba(16,47,58,.25)}.hero h1{margin:0 0 8px;font-size:1.58rem}.hero-meta{display:flex;gap:14px;flex-wrap:wrap;font-size:.9rem;color:#dcebf0}.filters{margin-top:12px;background:rgba(255,255,255,.1);border:1px solid rgba(255,255,255,.22);border-radius:12px;padding:10px 12px}.filter-grid{display:grid;grid-template-columns:repeat(4,minmax(160px,1fr));gap:10px}.filter-block{background:rgba(0,0,0,.12);border-radius:10px;padding:8px}.filter-title{font-size:.74rem;letter-spacing:.04em;text-transform:uppercase;margin:0 0 6px;color:#dbeaf0}.filter-item{display:flex;align-items:center;gap:6px;font-size:.85rem;margin:3px 0}.filter-actions{margin-top:10px;display:flex;gap:10px;align-items:center}.auto-note{font-size:.76rem;color:#dcebf0;opacity:.9}.btn{padding:7px 10px;border-radius:8px;border:1px solid rgba(0,0,0,.15);font-weight:700;font-size:.78rem;cursor:pointer}.btn-reset{background:#e4eef2;color:#1b3642;text-decoration:none}.card{margin-top:14px;background:var(--card);border:1px solid #cbd4db;border-radius:16px;overflow:hidden;box-shadow:0 12px 28px rgba(26,35,42,.12)}.table-wrap{overflow:auto;max-height:75vh}table{width:100%;border-collapse:collapse;min-width:1300px}thead th{position:sticky;top:0;z-index:2;background:var(--head);color:#f2f7f9;font-size:.79rem;text-transform:uppercase;letter-spacing:.04em;padding:10px;border-bottom:1px solid #0e2730}tbody td{font-size:.84rem;padding:8px 10px;border-bottom:1px solid var(--line);white-space:nowrap}tbody tr:nth-child(even){background:#fafcfd}.market-cell{min-width:220px}.market-btn{display:inline-flex;align-items:center;justify-content:center;background:linear-gradient(135deg,var(--btn),#0f7592);color:#fff;text-decoration:none;padding:7px 10px;border-radius:9px;font-weight:700;font-size:.76rem;border:1px solid rgba(0,0,0,.12);box-shadow:0 2px 8px rgba(12,95,120,.25)}.market-btn:hover{background:linear-gradient(135deg,var(--btnhover),#0d5f78)}.note-btn{margin-left:6px;padding:6px 8px;border-radius:9px;border:1px solid #cbd4db;background:#f8fbfc;color:#1b3642;font-weight:700;font-size:.72rem;cursor:pointer}.note-btn:hover{background:#e4eef2}.slug-id{display:block;margin-top:6px;font-family:\"IBM Plex Mono\",\"SFMono-Regular\",monospace;font-size:.67rem;color:var(--muted);max-width:260px;overflow:hidden;text-overflow:ellipsis}.cell-mock{background:linear-gradient(135deg,var(--mockbg) 0%,#fff3ca 100%);color:var(--mockink)}.cell-mock::after{content:\" M\";font-size:.62rem;font-weight:700;color:#8c6a00}.cell-sim{background:repeating-linear-gradient(135deg,var(--simbg) 0 6px,#dcecf9 6px 12px);color:var(--simink);font-style:italic}.queue-ahead{font-size:.7rem;color:var(--muted);font-style:normal}.prob-raw{font-size:.7rem;color:var(--muted)}.neg-risk{margin-left:6px;padding:1px 6px;border-radius:999px;background:#efe3fb;color:#5b2a8c;font-weight:700;font-size:.66rem}.ref-late{font-size:.7rem;font-weight:700;color:#b3261e}.cell-sim::after{content:\" S\";font-size:.62rem;font-weight:700;font-style:normal;color:#1d5f96}.legend{padding:10px 14px;border-top:1px solid var(--line);font-size:.8rem;color:var(--muted);background:#f8fbfc;display:flex;justify-content:space-between;gap:12px;flex-wrap:wrap}.legend b{color:#8c6a00}.live-banner{margin:0 0 12px;padding:12px 16px;border-radius:12px;background:#b3261e;color:#fff;font-weight:700;font-size:.95rem;box-shadow:0 6px 18px rgba(179,38,30,.3)}.live-banner[hidden]{display:none}.live-banner.degraded{background:#5c1a14}.paused-badge{padding:1px 8px;border-radius:999px;background:#e4eef2;color:#1b3642;font-weight:700;font-size:.78rem;text-transform:uppercase;letter-spacing:.04em}.paused-badge[hidden]{display:none}.headroom-low b{color:#ffd37a}.headroom-out b{color:#ff9b8f}tbody tr.row-stale td{color:var(--muted);font-style:italic}.occupancy{padding:10px 14px}.occ-head{display:flex;gap:14px;align-items:center;flex-wrap:wrap;font-size:.84rem;color:var(--muted);margin-bottom:6px}.occ-head b{color:var(--ink)}table.occ-grid{width:auto;min-width:0}.occ-grid th,.occ-grid td{padding:4px 10px;font-size:.78rem;text-align:center;border-bottom:1px solid var(--line)}.occ-grid td{white-space:nowrap}.occ-dot{display:inline-block;width:10px;height:10px;margin:0 2px;border-radius:50%;border:1px solid #9aa5ad;vertical-align:middle}.occ-position{background:#e07b00;border-color:#b86400}.occ-orders{background:#1d6fb8;border-color:#165a96}.occ-position_and_orders{background:#7b3fb8;border-color:#633296}.occ-attention{background:#fde4e1;box-shadow:inset 0 0 0 2px #b3261e}.activity{padding:10px 14px}.act-list{list-style:none;margin:0;padding:0;max-height:180px;overflow:auto;font-size:.8rem}.act-list li{padding:3px 0;border-bottom:1px solid var(--line)}.act-list .slug-id{display:inline;margin:0 0 0 6px}.act-time{font-family:\"IBM Plex Mono\",\"SFMono-Regular\",monospace;color:var(--muted)}.act-empty{color:var(--muted)}.act-fill b,.act-market_ended b{color:#b86400}.act-row_unresolved b{color:#b3261e}.act-probability_move b{color:#1d6fb8}.duration-link,.view-link{color:#f7fbfc}.duration-link.current,.view-link.current{font-weight:700;text-decoration:none}@media (max-width:980px){.filter-grid{grid-template-columns:repeat(2,minmax(150px,1fr))}}@media (max-width:760px){.hero h1{font-size:1.28rem}.shell{padding:12px}.card{margin-top:12px;border-radius:12px}.filter-grid{grid-template-columns:1fr}}</style>\n");
     out.push_str("</head><body><main class=\"shell\">\n");
+    out.push_str(&render_live_banner(status));
     out.push_str("<section class=\"hero\"><h1>PMM Dashboard</h1>");
     out.push_str("<div class=\"hero-meta\">\n");
     out.push_str("<span>Scope: 4 coins × 5 durations × previous/active/next</span>");
@@ -635,22 +3574,53 @@ fn render_dashboard_html_with_filters(
         "<span>Server UTC: {}</span>",
         escape_html(&now_utc)
     ));
-    out.push_str("<span>Refresh: 250ms</span>");
+    let refresh_ms = filters.refresh_ms();
+    out.push_str(&format!("<span>Refresh: {refresh_ms}ms</span>"));
+    if occupancy.is_some() {
+        out.push_str(
+            "<span id=\"wallet-headroom\" title=\"Wallet USDC not locked by open bids\">Headroom: <b>-</b></span>",
+        );
+    }
+    out.push_str(&format!(
+        "<span id=\"paused-badge\" class=\"paused-badge\" title=\"Tab hidden: updates slowed to every {HIDDEN_REFRESH_MS}ms\" hidden>paused</span>"
+    ));
     out.push_str("</div>");
 
-    out.push_str(
-        "<form id=\"filters-form\" class=\"filters\" method=\"get\" action=\"/dashboard\">\n",
-    );
+    let route = dashboard_route(preset);
+    out.push_str(&format!(
+        "<form id=\"filters-form\" class=\"filters\" method=\"get\" action=\"{route}\">\n"
+    ));
+    if let Some(view) = view {
+        out.push_str(&format!(
+            "<input type=\"hidden\" name=\"view\" value=\"{}\">",
+            escape_html(view)
+        ));
+    }
+    if !filters.probability_horizons.is_empty() {
+        out.push_str(&format!(
+            "<input type=\"hidden\" name=\"horizons\" value=\"{}\">",
+            horizons_param(&filters.probability_horizons)
+        ));
+    }
     out.push_str("<div class=\"filter-grid\">\n");
-    out.push_str(&render_checkbox_group("Coin", "coin", &COIN_OPTIONS, |v| {
-        filters.coin_selected(v)
-    }));
+    if !views.is_empty() {
+        out.push_str(&render_view_links(&route, views, view));
+    }
     out.push_str(&render_checkbox_group(
-        "Duration",
-        "duration",
-        &DURATION_OPTIONS,
-        |v| filters.duration_selected(v),
+        "Coin",
+        "coin",
+        &coin_options(),
+        |v| filters.coin_selected(v),
     ));
+    match preset {
+        Some(duration) => out.push_str(&render_duration_links(duration)),
+        None => out.push_str(&render_checkbox_group(
+            "Duration",
+            "duration",
+            &DURATION_OPTIONS,
+            |v| filters.duration_selected(v),
+        )),
+    }
     out.push_str(&render_checkbox_group(
         "Bets Open",
         "bets_open",
@@ -671,10 +3641,23 @@ fn render_dashboard_html_with_filters(
             _ => false,
         },
     ));
+    out.push_str(&render_min_inputs(
+        "Market Size",
+        &[
+            ("min_volume_24h", "Min vol 24h", filters.min_volume_24h),
+            ("min_liquidity", "Min liquidity", filters.min_liquidity),
+        ],
+    ));
     out.push_str("</div>");
-    out.push_str("<div class=\"filter-actions\"><a class=\"btn btn-reset\" href=\"/dashboard\">Reset</a><span class=\"auto-note\">Auto-applies on checkbox change</span></div>");
+    out.push_str(&format!("<div class=\"filter-actions\"><a class=\"btn btn-reset\" href=\"{route}\">Reset</a><span class=\"auto-note\">Auto-applies on checkbox change</span></div>"));
     out.push_str("</form></section>\n");
 
+    if let Some(grid) = occupancy {
+        out.push_str(&render_occupancy_html(grid));
+    }
+    out.push_str(&render_activity_html(activity));
+
+    out.push_str(&render_hidden_columns_style(&filters.hidden_columns()));
     out.push_str(
         "<section class=\"card\"><div class=\"table-wrap\"><table id=\"dashboard-table\">\n",
     );
@@ -684,28 +3667,48 @@ fn render_dashboard_html_with_filters(
         out.push_str(&escape_html(header));
         out.push_str("</th>");
     }
+    for horizon in &filters.probability_horizons {
+        out.push_str("<th>");
+        out.push_str(&escape_html(&horizon_header(*horizon)));
+        out.push_str("</th>");
+    }
     out.push_str("</tr></thead><tbody id=\"dashboard-body\">\n");
-    out.push_str(&render_rows_html(&display.rows));
+    out.push_str(&render_rows_html(
+        &display.rows,
+        &filters.probability_horizons,
+    ));
     out.push_str("</tbody></table></div>");
-    out.push_str("<div class=\"legend\"><span>Mock-backed cells are highlighted <b>yellow/grey</b> and tagged with <b>M</b>.</span><span>End is shown in <b>local browser time</b>.</span></div></section>");
+    out.push_str("<div class=\"legend\"><span>Mock-backed cells are highlighted <b>yellow/grey</b> and tagged with <b>M</b>; dry-run strategy quotes are striped <b>blue</b> and tagged with <b>S</b>.</span><span>End is shown in <b>local browser time</b>.</span></div></section>");
 
-    out.push_str(DASHBOARD_CLIENT_SCRIPT);
+    out.push_str(
+        &DASHBOARD_CLIENT_SCRIPT
+            .replace("__DURATION_PRESET__", preset.map_or("", Duration::as_label))
+            .replace("__REFRESH_MS__", &refresh_ms.to_string())
+            .replace("__HIDDEN_REFRESH_MS__", &HIDDEN_REFRESH_MS.to_string())
+            .replace("__ROW_PATCH_BUDGET__", &ROW_PATCH_BUDGET.to_string())
+            .replace(
+                "__PROBABILITY_HORIZONS__",
+                &horizons_param(&filters.probability_horizons),
+            ),
+    );
 
     out.push_str("</main></body></html>\n");
     out
 }
 
 #[cfg(feature = "discovery-sdk")]
-async fn build_live_discovery_snapshot(config: LiveDiscoveryConfig) -> DashboardSnapshot {
+async fn build_live_discovery_snapshot(
+    config: &LiveDiscoveryConfig,
+    gamma_endpoints: &GammaEndpointPool,
+    payload_sampler: Option<&Arc<GammaPayloadSampler>>,
+) -> LiveCycleOutcome {
     let cycle_id = DISCOVERY_CYCLE_SEQ.fetch_add(1, Ordering::Relaxed);
     let started_at = Instant::now();
-    let now_ts = Utc::now().timestamp();
-    let scheduled = match build_previous_active_and_next_discovery_keys(
-        now_ts,
-        &ALL_COINS,
-        &ALL_DURATIONS,
-        config.slug_config,
-    ) {
+    let now_ts = crate::faults::now_utc().timestamp();
+    let mut scheduled = match config
+        .universe
+        .previous_active_and_next_keys(now_ts, config.slug_config)
+    {
         Ok(keys) => keys,
         Err(err) => {
             error!(
@@ -724,9 +3727,30 @@ async fn build_live_discovery_snapshot(config: LiveDiscoveryConfig) -> Dashboard
                 transport_error_count = 0usize,
                 duration_ms = started_at.elapsed().as_millis() as u64
             );
-            return DashboardSnapshot { rows: Vec::new() };
+            record_discovery_cycle(0, 0, 0, true, started_at.elapsed().as_millis() as u64);
+            return LiveCycleOutcome {
+                rows: None,
+                failure: Some(err.to_string()),
+                subscriptions: Vec::new(),
+            };
         }
     };
+    // Disabled pairs stay on the dashboard but go last, behind the markets we quote.
+    // Within each group the most imminent deadline is looked up first.
+    let now_ts_ms = now_ts.saturating_mul(1_000);
+    scheduled.sort_by_key(|entry| {
+        let deadline = config
+            .discovery_config
+            .deadline_aware
+            .then(|| discovery_deadline_ts_ms(&entry.key, now_ts_ms))
+            .flatten();
+        (
+            !config
+                .toggles
+                .is_enabled(entry.key.coin, entry.key.duration),
+            deadline.unwrap_or(i64::MAX),
+        )
+    });
     info!(
         component = "dashboard",
         event = "discovery.cycle.start",
@@ -735,8 +3759,16 @@ async fn build_live_discovery_snapshot(config: LiveDiscoveryConfig) -> Dashboard
     );
 
     let keys: Vec<_> = scheduled.iter().map(|entry| entry.key.clone()).collect();
-    let (rows, resolved_count, unresolved_count, transport_error_count) =
-        match resolve_discovery_batch(&keys, &config.discovery_config).await {
+    let mut subscriptions = Vec::new();
+    let (rows, resolved_count, unresolved_count, transport_error_count, failure) =
+        match resolve_discovery_batch_with_sampler(
+            &keys,
+            &config.discovery_config,
+            gamma_endpoints,
+            payload_sampler,
+        )
+        .await
+        {
             Ok(resolved) => {
                 let mut rows = Vec::with_capacity(scheduled.len());
                 let mut resolved_count = 0usize;
@@ -744,9 +3776,23 @@ async fn build_live_discovery_snapshot(config: LiveDiscoveryConfig) -> Dashboard
                 let mut transport_error_count = 0usize;
 
                 for (row, scheduled_key) in resolved.iter().zip(scheduled.iter()) {
-                    match &row.status {
+                    subscriptions.extend(BookSubscription::from_discovery_row(row));
+                    let lookup = match &row.status {
                         DiscoveryStatus::Resolved { .. } => {
                             resolved_count += 1;
+                            RowLookup::Resolved
+                        }
+                        DiscoveryStatus::Unresolved {
+                            reason: UnresolvedReason::NotFound,
+                        } => {
+                            unresolved_count += 1;
+                            RowLookup::NotFound
+                        }
+                        DiscoveryStatus::Unresolved {
+                            reason: UnresolvedReason::DeadlineSkipped,
+                        } => {
+                            unresolved_count += 1;
+                            RowLookup::Skipped
                         }
                         DiscoveryStatus::Unresolved { reason } => {
                             unresolved_count += 1;
@@ -757,16 +3803,21 @@ async fn build_live_discovery_snapshot(config: LiveDiscoveryConfig) -> Dashboard
                                     event = "discovery.degraded.row_transport",
                                     cycle_id,
                                     slug = %row.key.slug,
-                                    coin = %coin_label(row.key.coin),
-                                    duration = %duration_label(row.key.duration),
+                                    coin = %row.key.underlying().label(),
+                                    duration = row.key.duration.as_label(),
                                     window = %discovery_window_label(scheduled_key.window),
                                     reason = %message
                                 );
                             }
+                            RowLookup::Failed
                         }
-                    }
+                    };
 
-                    rows.push(discovery_row_to_dashboard_row(row, scheduled_key));
+                    rows.push(LiveCycleRow {
+                        key_slug: row.key.slug.clone(),
+                        row: discovery_row_to_dashboard_row(row, scheduled_key, config, now_ts),
+                        lookup,
+                    });
                 }
 
                 (
@@ -774,6 +3825,7 @@ async fn build_live_discovery_snapshot(config: LiveDiscoveryConfig) -> Dashboard
                     resolved_count,
                     unresolved_count,
                     transport_error_count,
+                    None,
                 )
             }
             Err(err) => {
@@ -793,13 +3845,19 @@ async fn build_live_discovery_snapshot(config: LiveDiscoveryConfig) -> Dashboard
                 (
                     scheduled
                         .iter()
-                        .map(|scheduled_key| {
-                            unresolved_dashboard_row_with_reason(scheduled_key, &err.to_string())
+                        .map(|scheduled_key| LiveCycleRow {
+                            key_slug: scheduled_key.key.slug.clone(),
+                            row: unresolved_dashboard_row_with_reason(
+                                scheduled_key,
+                                &err.to_string(),
+                            ),
+                            lookup: RowLookup::Failed,
                         })
                         .collect(),
                     0usize,
                     scheduled.len(),
                     scheduled.len(),
+                    Some(err.to_string()),
                 )
             }
         };
@@ -814,8 +3872,21 @@ async fn build_live_discovery_snapshot(config: LiveDiscoveryConfig) -> Dashboard
         transport_error_count,
         duration_ms = started_at.elapsed().as_millis() as u64
     );
+    record_discovery_cycle(
+        scheduled.len(),
+        resolved_count,
+        rows.iter()
+            .filter(|row| row.lookup == RowLookup::Failed)
+            .count(),
+        failure.is_some(),
+        started_at.elapsed().as_millis() as u64,
+    );
 
-    DashboardSnapshot { rows }
+    LiveCycleOutcome {
+        rows: Some(rows),
+        failure,
+        subscriptions,
+    }
 }
 
 #[cfg(feature = "discovery-sdk")]
@@ -829,15 +3900,17 @@ fn discovery_window_label(window: DiscoveryWindow) -> &'static str {
 
 #[cfg(feature = "discovery-sdk")]
 fn discovery_row_to_dashboard_row(
-    row: &DiscoveryRow<SdkMarket>,
+    row: &DiscoveryRow<MarketMeta>,
     scheduled: &ScheduledDiscoveryKey,
+    config: &LiveDiscoveryConfig,
+    now_ts_utc: i64,
 ) -> DashboardRow {
     let start_ts_utc = row.key.start_ts_utc;
-    let end_ts_utc = interval_end_ts_utc(start_ts_utc, row.key.duration);
+    let end_ts_utc = row.key.duration.interval_end(start_ts_utc);
     let mut dashboard_row = DashboardRow::unresolved_with_times(
         row.key.slug.clone(),
-        coin_label(row.key.coin),
-        duration_label(row.key.duration),
+        row.key.underlying().label(),
+        row.key.duration.as_label(),
         start_ts_utc,
         end_ts_utc,
     );
@@ -856,13 +3929,28 @@ fn discovery_row_to_dashboard_row(
             if let Some(slug) = market.slug.clone() {
                 dashboard_row.slug = slug;
             }
-            dashboard_row.bets_open = market_bets_open_label(market);
-            let fee_params = market_fee_params(market, row.key.duration);
-            dashboard_row.taker_fee_pct = Some(fee_params.taker_fee_pct);
-            dashboard_row.maker_fee_pct = Some(fee_params.maker_fee_pct);
-            dashboard_row.fee_exponent = Some(fee_params.fee_exponent);
-            dashboard_row.reward_pct =
-                Some(market_reward_pct(market).unwrap_or_else(|| "0".to_string()));
+            dashboard_row.bets_open = market
+                .bets_open()
+                .map(|open| if open { "open" } else { "closed" }.to_string());
+            dashboard_row.accepting_orders_ts_utc = market.accepting_orders_ts_utc;
+            dashboard_row.neg_risk_group = market.neg_risk_group().map(str::to_string);
+            let fee_columns = fee_columns_from_type(
+                market.fee_type.as_deref(),
+                market.fees_enabled,
+                row.key.duration,
+                &config.fees,
+                now_ts_utc,
+            );
+            dashboard_row.taker_fee_pct = Some(fee_columns.taker_fee_pct);
+            dashboard_row.maker_fee_pct = Some(fee_columns.maker_fee_pct);
+            dashboard_row.fee_exponent = Some(fee_columns.fee_exponent);
+            dashboard_row.reward_pct = Some(
+                market
+                    .reward_rate()
+                    .map_or_else(|| "0".to_string(), |rate| rate.to_string()),
+            );
+            dashboard_row.volume_24h = market.volume_24h.map(|volume| volume.to_string());
+            dashboard_row.liquidity = market.liquidity.map(|liquidity| liquidity.to_string());
             dashboard_row.mock_columns = resolved_mock_columns(&dashboard_row);
         }
         DiscoveryStatus::Unresolved { reason } => {
@@ -881,11 +3969,11 @@ fn unresolved_dashboard_row_with_reason(
     reason: &str,
 ) -> DashboardRow {
     let start_ts_utc = scheduled.key.start_ts_utc;
-    let end_ts_utc = interval_end_ts_utc(start_ts_utc, scheduled.key.duration);
+    let end_ts_utc = scheduled.key.duration.interval_end(start_ts_utc);
     let mut row = DashboardRow::unresolved_with_times(
         scheduled.key.slug.clone(),
-        coin_label(scheduled.key.coin),
-        duration_label(scheduled.key.duration),
+        scheduled.key.underlying().label(),
+        scheduled.key.duration.as_label(),
         start_ts_utc,
         end_ts_utc,
     );
@@ -911,6 +3999,8 @@ fn resolved_mock_columns(row: &DashboardRow) -> Vec<String> {
         ("maker_fee_pct", row.maker_fee_pct.is_some()),
         ("fee_exponent", row.fee_exponent.is_some()),
         ("reward_pct", row.reward_pct.is_some()),
+        ("volume_24h", row.volume_24h.is_some()),
+        ("liquidity", row.liquidity.is_some()),
     ];
 
     for (column, is_live) in live_columns {
@@ -923,136 +4013,69 @@ fn resolved_mock_columns(row: &DashboardRow) -> Vec<String> {
 }
 
 #[cfg(feature = "discovery-sdk")]
-fn market_bets_open_label(market: &SdkMarket) -> Option<String> {
-    if let Some(accepting) = market.accepting_orders {
-        return Some(if accepting { "open" } else { "closed" }.to_string());
-    }
-    if let Some(closed) = market.closed {
-        return Some(if closed { "closed" } else { "open" }.to_string());
-    }
-    if let Some(active) = market.active {
-        return Some(if active { "open" } else { "closed" }.to_string());
-    }
-    None
-}
-
-#[cfg(feature = "discovery-sdk")]
-struct FeeParams {
+struct FeeColumns {
     taker_fee_pct: String,
     maker_fee_pct: String,
     fee_exponent: String,
 }
 
+/// Fee columns from the schedule in force at `now_ts_utc`; unknown fee types and
+/// disabled fees show zero.
 #[cfg(feature = "discovery-sdk")]
-fn market_fee_params(market: &SdkMarket, duration: Duration) -> FeeParams {
-    let fee_type = market
-        .format_type
-        .as_deref()
-        .map(str::trim)
-        .filter(|value| !value.is_empty())
-        .or_else(|| {
-            market
-                .market_type
-                .as_deref()
-                .map(str::trim)
-                .filter(|value| !value.is_empty())
-        });
-    fee_params_from_type(fee_type, market.fees_enabled, duration)
-}
-
-#[cfg(feature = "discovery-sdk")]
-fn fee_params_from_type(
+fn fee_columns_from_type(
     fee_type: Option<&str>,
     fees_enabled: Option<bool>,
     duration: Duration,
-) -> FeeParams {
-    let fees_enabled = fees_enabled.unwrap_or(false);
-    if !fees_enabled {
-        return FeeParams {
-            taker_fee_pct: "0".to_string(),
-            maker_fee_pct: "0".to_string(),
-            fee_exponent: "-".to_string(),
-        };
-    }
-
-    // Per dashboard spec: crypto_15_min fees profile when fees are enabled.
-    if fees_enabled && matches!(fee_type, Some("crypto_15_min")) {
-        return FeeParams {
-            taker_fee_pct: "0.25".to_string(),
-            maker_fee_pct: "-0.05".to_string(),
-            fee_exponent: "2".to_string(),
-        };
-    }
-
-    // SDK fallback: Gamma feeType is not exposed in current SDK Market type for these crypto rows.
-    if fee_type.is_none() && matches!(duration, Duration::M5 | Duration::M15) {
-        return FeeParams {
-            taker_fee_pct: "0.25".to_string(),
-            maker_fee_pct: "-0.05".to_string(),
-            fee_exponent: "2".to_string(),
-        };
-    }
-
-    if fee_type.is_none() {
-        return FeeParams {
-            taker_fee_pct: "0".to_string(),
-            maker_fee_pct: "0".to_string(),
-            fee_exponent: "-".to_string(),
-        };
-    }
-
-    FeeParams {
-        taker_fee_pct: "0".to_string(),
-        maker_fee_pct: "0".to_string(),
-        fee_exponent: "-".to_string(),
-    }
-}
-
-#[cfg(feature = "discovery-sdk")]
-fn market_reward_pct(market: &SdkMarket) -> Option<String> {
-    if let Some(rewards) = market
-        .clob_rewards
-        .as_ref()
-        .and_then(|rewards| rewards.first())
-    {
-        if let Some(rate) = &rewards.rewards_daily_rate {
-            return Some(rate.to_string());
-        }
-        if let Some(amount) = &rewards.rewards_amount {
-            return Some(amount.to_string());
-        }
+    fees: &FeeRegistry,
+    now_ts_utc: i64,
+) -> FeeColumns {
+    let params = if fees_enabled.unwrap_or(false) {
+        // SDK fallback: Gamma feeType is not exposed in current SDK Market type for these crypto rows.
+        let fee_type = fee_type.or(duration_has_fees(duration).then_some(CRYPTO_15_MIN_FEE_TYPE));
+        fee_type
+            .and_then(|fee_type| fees.fee_params_at(fee_type, now_ts_utc))
+            .unwrap_or(FeeParams::DISABLED)
+    } else {
+        FeeParams::DISABLED
+    };
+    FeeColumns {
+        taker_fee_pct: params.taker_fee_rate.to_string(),
+        maker_fee_pct: params.maker_fee_rate.to_string(),
+        fee_exponent: params
+            .fee_exponent
+            .map_or_else(|| "-".to_string(), |exponent| exponent.to_string()),
     }
-    market.uma_reward.as_ref().map(|value| value.to_string())
 }
 
 pub fn demo_snapshot() -> DashboardSnapshot {
-    let now_ts = Utc::now().timestamp();
-    let offset = std::env::var("PMFLIPS_DISCOVERY_OFFSET_4H_MIN")
-        .ok()
-        .and_then(|raw| raw.parse::<i32>().ok())
-        .unwrap_or(60);
-
-    let slug_cfg = SlugConfig {
-        discovery_offset_4h_min: offset,
-    };
+    demo_snapshot_at(Utc::now().timestamp())
+}
 
-    let rows =
-        build_previous_active_and_next_discovery_keys(now_ts, &ALL_COINS, &ALL_DURATIONS, slug_cfg)
-            .unwrap_or_default()
-            .into_iter()
-            .map(scheduled_key_to_demo_row)
-            .collect();
+/// Demo rows for the previous/active/next windows around `now_ts`.
+pub fn demo_snapshot_at(now_ts: i64) -> DashboardSnapshot {
+    let slug_cfg = SlugConfig::default();
 
-    DashboardSnapshot { rows }
+    let rows = build_previous_active_and_next_discovery_keys(
+        now_ts,
+        &Coin::all(),
+        &ALL_DURATIONS,
+        slug_cfg,
+    )
+    .unwrap_or_default()
+    .into_iter()
+    .map(scheduled_key_to_demo_row)
+    .collect();
+
+    DashboardSnapshot { rows, seq: 0 }
 }
 
 fn scheduled_key_to_demo_row(scheduled: crate::discovery::ScheduledDiscoveryKey) -> DashboardRow {
     let start_ts_utc = scheduled.key.start_ts_utc;
-    let end_ts_utc = interval_end_ts_utc(start_ts_utc, scheduled.key.duration);
+    let end_ts_utc = scheduled.key.duration.interval_end(start_ts_utc);
     let mut row = DashboardRow::unresolved_with_times(
         scheduled.key.slug,
-        coin_label(scheduled.key.coin),
-        duration_label(scheduled.key.duration),
+        scheduled.key.coin.ticker(),
+        scheduled.key.duration.as_label(),
         start_ts_utc,
         end_ts_utc,
     );
@@ -1069,83 +4092,207 @@ fn scheduled_key_to_demo_row(scheduled: crate::discovery::ScheduledDiscoveryKey)
     row
 }
 
-fn coin_label(coin: Coin) -> &'static str {
-    match coin {
-        Coin::Btc => "BTC",
-        Coin::Eth => "ETH",
-        Coin::Sol => "SOL",
-        Coin::Xrp => "XRP",
-    }
+fn default_mock_columns() -> Vec<String> {
+    DASHBOARD_COLUMN_KEYS
+        .iter()
+        .skip(3)
+        .filter(|entry| **entry != "in_interval" && **entry != "end")
+        .map(|entry| (*entry).to_string())
+        .collect()
 }
 
-fn duration_label(duration: Duration) -> &'static str {
-    match duration {
-        Duration::M5 => "5m",
-        Duration::M15 => "15m",
-        Duration::H1 => "1h",
-        Duration::H4 => "4h",
-        Duration::D1 => "1d",
+/// Pair rows match while both of their coins are selected.
+fn row_matches_coins(row: &DashboardRow, filters: &DashboardFilters) -> bool {
+    match row.underlying() {
+        Some(underlying @ MarketUnderlying::Pair(..)) => underlying
+            .coins()
+            .iter()
+            .all(|coin| filters.coin_selected(coin.ticker())),
+        _ => filters.coin_selected(&row.coin),
     }
 }
 
-fn interval_end_ts_utc(start_ts_utc: i64, duration: Duration) -> i64 {
-    match duration {
-        Duration::M5 => start_ts_utc.saturating_add(5 * 60),
-        Duration::M15 => start_ts_utc.saturating_add(15 * 60),
-        Duration::H1 => start_ts_utc.saturating_add(60 * 60),
-        Duration::H4 => {
-            let start_ny = Utc
-                .timestamp_opt(start_ts_utc, 0)
-                .single()
-                .expect("valid start timestamp expected")
-                .with_timezone(&New_York);
-            let date = start_ny.date_naive();
-            let block_hour = (start_ny.hour() / 4) * 4;
-
-            let next_local = if block_hour == 20 {
-                let next_date = date
-                    .checked_add_days(Days::new(1))
-                    .expect("next date should exist");
-                ny_datetime(next_date, 0).expect("valid next ET 4h boundary")
-            } else {
-                ny_datetime(date, block_hour + 4).expect("valid next ET 4h boundary")
-            };
+/// Whether a numeric cell clears `min`; without a bound every row does.
+fn row_meets_min(raw: Option<&str>, min: Option<f64>) -> bool {
+    let Some(min) = min else {
+        return true;
+    };
+    raw.and_then(|value| value.trim().parse::<f64>().ok())
+        .is_some_and(|value| value >= min)
+}
 
-            next_local.with_timezone(&Utc).timestamp()
-        }
-        Duration::D1 => {
-            let start_ny = Utc
-                .timestamp_opt(start_ts_utc, 0)
-                .single()
-                .expect("valid start timestamp expected")
-                .with_timezone(&New_York);
-            let next_date = start_ny
-                .date_naive()
-                .checked_add_days(Days::new(1))
-                .expect("next date should exist");
-            ny_datetime(next_date, 12)
-                .expect("valid next ET noon boundary")
-                .with_timezone(&Utc)
-                .timestamp()
-        }
+fn parse_min_bound(raw: Option<&str>) -> Option<f64> {
+    raw.and_then(|value| value.trim().parse::<f64>().ok())
+        .filter(|value| value.is_finite())
+}
+
+fn parse_count(raw: Option<&str>) -> Option<usize> {
+    raw.and_then(|value| value.trim().parse::<usize>().ok())
+}
+
+/// Known column keys among comma-separated `values`, matched case-insensitively;
+/// `None` when none is known, which shows every column.
+fn parse_columns(values: &[String]) -> Option<Vec<&'static str>> {
+    let columns: Vec<&'static str> = values
+        .iter()
+        .flat_map(|value| value.split(','))
+        .filter_map(|raw| {
+            DASHBOARD_COLUMN_KEYS
+                .into_iter()
+                .find(|key| key.eq_ignore_ascii_case(raw.trim()))
+        })
+        .collect();
+    (!columns.is_empty()).then_some(columns)
+}
+
+/// Known horizons among comma-separated `values` (seconds, `s` suffix optional),
+/// farthest from expiry first. None without the parameter; all of
+/// [`PROBABILITY_HORIZONS_SECONDS`] when it names no known one.
+fn parse_horizons(values: &[String]) -> Vec<u32> {
+    if values.is_empty() {
+        return Vec::new();
+    }
+    let mut horizons: Vec<u32> = values
+        .iter()
+        .flat_map(|value| value.split(','))
+        .filter_map(|raw| {
+            let raw = raw.trim();
+            raw.strip_suffix(['s', 'S'])
+                .unwrap_or(raw)
+                .parse::<u32>()
+                .ok()
+        })
+        .filter(|horizon| PROBABILITY_HORIZONS_SECONDS.contains(horizon))
+        .collect();
+    if horizons.is_empty() {
+        horizons = PROBABILITY_HORIZONS_SECONDS.to_vec();
     }
+    horizons.sort_unstable_by(|a, b| b.cmp(a));
+    horizons.dedup();
+    horizons
 }
 
-fn ny_datetime(date: chrono::NaiveDate, hour: u32) -> Option<chrono::DateTime<chrono_tz::Tz>> {
-    New_York
-        .with_ymd_and_hms(date.year(), date.month(), date.day(), hour, 0, 0)
-        .single()
+/// Column header of a probability horizon: `Prob 5m Left`, or seconds when not whole
+/// minutes.
+fn horizon_header(horizon: u32) -> String {
+    if horizon.is_multiple_of(60) {
+        format!("Prob {}m Left", horizon / 60)
+    } else {
+        format!("Prob {horizon}s Left")
+    }
 }
 
-fn default_mock_columns() -> Vec<String> {
-    DASHBOARD_COLUMN_KEYS
+/// Keeps the `horizons` of `row`'s horizon probabilities, `-` where it has none.
+fn select_probability_horizons(row: &mut DashboardDisplayRow, horizons: &[u32]) {
+    let mut all = std::mem::take(&mut row.probability_horizons);
+    row.probability_horizons = horizons
         .iter()
-        .skip(3)
-        .filter(|entry| **entry != "in_interval" && **entry != "end")
-        .map(|entry| (*entry).to_string())
+        .map(|horizon| {
+            let value = all.remove(horizon).unwrap_or_else(|| "-".to_string());
+            (*horizon, value)
+        })
+        .collect();
+}
+
+/// The `offset`/`limit` page of `rows` (each paired with what to return for it) in
+/// `sort_by` order. The sort is stable, and rows without a value for the column sort
+/// last in either direction.
+fn sort_and_page<'a, T>(
+    rows: impl Iterator<Item = (&'a DashboardRow, T)>,
+    filters: &DashboardFilters,
+    now_ts_utc: i64,
+) -> Vec<T> {
+    let mut rows: Vec<(&DashboardRow, T)> = rows.collect();
+    if let Some(column) = filters.sort_by {
+        let mut keyed: Vec<(Option<SortValue>, (&DashboardRow, T))> = rows
+            .into_iter()
+            .map(|pair| (sort_value(pair.0, column, now_ts_utc), pair))
+            .collect();
+        keyed.sort_by(|(a, _), (b, _)| match (a, b) {
+            (Some(a), Some(b)) => {
+                let ordering = a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal);
+                if filters.descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            }
+            (a, b) => b.is_some().cmp(&a.is_some()),
+        });
+        rows = keyed.into_iter().map(|(_, pair)| pair).collect();
+    }
+    rows.into_iter()
+        .skip(filters.offset)
+        .take(filters.limit.unwrap_or(usize::MAX))
+        .map(|(_, item)| item)
         .collect()
 }
 
+#[derive(Debug, PartialEq, PartialOrd)]
+enum SortValue {
+    Number(f64),
+    Text(String),
+}
+
+/// What `column` sorts `row` by: numbers for numeric cells (the leading figure of
+/// `shares@price` cells, negative for a net `NO` position), the duration length, the
+/// end time, and text otherwise.
+fn sort_value(row: &DashboardRow, column: &str, now_ts_utc: i64) -> Option<SortValue> {
+    let number = |cell: &Option<String>| {
+        cell.as_deref()
+            .and_then(|raw| raw.split('@').next())
+            .and_then(|lead| lead.trim().parse::<f64>().ok())
+            .filter(|value| value.is_finite())
+    };
+    let value = match column {
+        "link" => return Some(SortValue::Text(row.slug.clone())),
+        "coin" => return Some(SortValue::Text(row.coin.clone())),
+        "bets_open" => return row.bets_open.clone().map(SortValue::Text),
+        "duration" => Duration::parse(&row.duration)
+            .ok()
+            .map(|duration| duration.step_seconds() as f64),
+        "in_interval" => Some(f64::from(u8::from(compute_in_interval(
+            now_ts_utc,
+            row.start_ts_utc,
+            row.end_ts_utc,
+        )))),
+        "end" => Some(row.end_ts_utc as f64),
+        "position_net" => number(&row.position_net).map(|shares| {
+            if row
+                .position_net
+                .as_deref()
+                .is_some_and(|raw| raw.ends_with("@NO"))
+            {
+                -shares
+            } else {
+                shares
+            }
+        }),
+        "ref_price" => number(&row.ref_price),
+        "price" => number(&row.price),
+        "probability" => number(&row.probability),
+        "realized_vol" => number(&row.realized_vol),
+        "price_vs_ref_pct" => number(&row.price_vs_ref_pct),
+        "move_z" => number(&row.move_z),
+        "best_bid_yes" => number(&row.best_bid_yes),
+        "best_ask_yes" => number(&row.best_ask_yes),
+        "pos_yes" => number(&row.pos_yes),
+        "pos_no" => number(&row.pos_no),
+        "offer_yes" => number(&row.offer_yes),
+        "offer_no" => number(&row.offer_no),
+        "suggested_size" => number(&row.suggested_size),
+        "net_profit" => number(&row.net_profit),
+        "taker_fee_pct" => number(&row.taker_fee_pct),
+        "maker_fee_pct" => number(&row.maker_fee_pct),
+        "fee_exponent" => number(&row.fee_exponent),
+        "reward_pct" => number(&row.reward_pct),
+        "volume_24h" => number(&row.volume_24h),
+        "liquidity" => number(&row.liquidity),
+        _ => None,
+    };
+    value.map(SortValue::Number)
+}
+
 fn row_matches_bets_open(row: &DashboardRow, filters: &DashboardFilters) -> bool {
     match parse_bets_open_value(row.bets_open.as_deref()) {
         Some(value) => filters.bets_open_selected(value),
@@ -1228,13 +4375,13 @@ fn utc_hhmm(ts: i64) -> String {
         .unwrap_or_else(|| "-".to_string())
 }
 
-fn format_column_value(column_key: &str, raw: Option<&str>) -> String {
+fn format_column_value(column_key: &str, raw: Option<&str>, fmt: &NumberFormatConfig) -> String {
     match column_key {
         "taker_fee_pct" | "maker_fee_pct" | "reward_pct" => {
             if raw.map(|entry| entry.trim().is_empty()).unwrap_or(true) {
                 "0".to_string()
             } else {
-                format_maybe_composite(raw.unwrap_or_default(), 3)
+                format_maybe_composite(raw.unwrap_or_default(), 3, column_key, fmt)
             }
         }
         "fee_exponent" => raw
@@ -1247,11 +4394,22 @@ fn format_column_value(column_key: &str, raw: Option<&str>) -> String {
             })
             .unwrap_or_else(|| "-".to_string()),
         "probability" => raw
-            .map(format_probability)
+            .map(|value| format_probability(value, fmt))
             .unwrap_or_else(|| "-".to_string()),
-        "ref_price" | "price" | "best_bid_yes" | "best_ask_yes" | "position_net" | "pos_yes"
-        | "pos_no" | "offer_yes" | "offer_no" | "net_profit" => raw
-            .map(|value| format_maybe_composite(value, 4))
+        // Whole USDC unless the column has configured decimals.
+        "volume_24h" | "liquidity" => {
+            match raw.and_then(|value| value.trim().parse::<f64>().ok()) {
+                Some(value) if fmt.column_decimals.contains_key(column_key) => {
+                    format_number(value, 0, column_key, fmt)
+                }
+                Some(value) => localize_number(&format!("{value:.0}"), fmt),
+                None => "-".to_string(),
+            }
+        }
+        "ref_price" | "price" | "realized_vol" | "price_vs_ref_pct" | "move_z" | "best_bid_yes"
+        | "best_ask_yes" | "position_net" | "pos_yes" | "pos_no" | "offer_yes" | "offer_no"
+        | "queue_ahead" | "suggested_size" | "net_profit" => raw
+            .map(|value| format_maybe_composite(value, 4, column_key, fmt))
             .unwrap_or_else(|| "-".to_string()),
         _ => raw
             .map(|value| {
@@ -1265,7 +4423,15 @@ fn format_column_value(column_key: &str, raw: Option<&str>) -> String {
     }
 }
 
-fn format_probability(value: &str) -> String {
+fn parse_probability_fraction(value: &str) -> Option<f64> {
+    let trimmed = value.trim();
+    match trimmed.strip_suffix('%') {
+        Some(percent) => percent.trim().parse::<f64>().ok().map(|p| p / 100.0),
+        None => trimmed.parse::<f64>().ok(),
+    }
+}
+
+fn format_probability(value: &str, fmt: &NumberFormatConfig) -> String {
     let trimmed = value.trim();
     if trimmed.is_empty() || trimmed == "-" {
         return "-".to_string();
@@ -1283,28 +4449,114 @@ fn format_probability(value: &str) -> String {
             if !is_percent_input && numeric <= 1.0 {
                 numeric *= 100.0;
             }
-            format!("{}%", format_significant(numeric, 3))
+            format!("{}%", format_number(numeric, 3, "probability", fmt))
         }
         Err(_) => trimmed.to_string(),
     }
 }
 
-fn format_maybe_composite(value: &str, sig_digits: usize) -> String {
+fn format_maybe_composite(
+    value: &str,
+    sig_digits: usize,
+    column_key: &str,
+    fmt: &NumberFormatConfig,
+) -> String {
     if value.contains('@') {
         return value
             .split('@')
-            .map(|segment| format_numeric_or_keep(segment.trim(), sig_digits))
+            .map(|segment| format_numeric_or_keep(segment.trim(), sig_digits, column_key, fmt))
             .collect::<Vec<_>>()
             .join("@");
     }
 
-    format_numeric_or_keep(value.trim(), sig_digits)
+    format_numeric_or_keep(value.trim(), sig_digits, column_key, fmt)
+}
+
+fn format_numeric_or_keep(
+    value: &str,
+    sig_digits: usize,
+    column_key: &str,
+    fmt: &NumberFormatConfig,
+) -> String {
+    match value.parse::<f64>() {
+        Ok(number) => format_number(number, sig_digits, column_key, fmt),
+        Err(_) => value.to_string(),
+    }
+}
+
+fn format_number(
+    value: f64,
+    sig_digits: usize,
+    column_key: &str,
+    fmt: &NumberFormatConfig,
+) -> String {
+    if !value.is_finite() {
+        return value.to_string();
+    }
+
+    let plain = match fmt.column_decimals.get(column_key) {
+        Some(&decimals) => {
+            let fixed = format!("{value:.decimals$}");
+            if fixed.starts_with('-') && fixed[1..].chars().all(|c| c == '0' || c == '.') {
+                fixed[1..].to_string()
+            } else {
+                fixed
+            }
+        }
+        None => format_significant(value, sig_digits),
+    };
+
+    localize_number(&plain, fmt)
+}
+
+fn localize_number(plain: &str, fmt: &NumberFormatConfig) -> String {
+    let (sign, unsigned) = match plain.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", plain),
+    };
+    let (int_part, frac_part) = match unsigned.split_once('.') {
+        Some((int_part, frac_part)) => (int_part, Some(frac_part)),
+        None => (unsigned, None),
+    };
+
+    let mut out = String::with_capacity(plain.len() + int_part.len() / 3);
+    out.push_str(sign);
+    match fmt.thousands_separator {
+        Some(sep) => {
+            for (idx, ch) in int_part.chars().enumerate() {
+                if idx > 0 && (int_part.len() - idx) % 3 == 0 {
+                    out.push(sep);
+                }
+                out.push(ch);
+            }
+        }
+        None => out.push_str(int_part),
+    }
+    if let Some(frac_part) = frac_part {
+        out.push(fmt.decimal_separator);
+        out.push_str(frac_part);
+    }
+
+    out
+}
+
+fn parse_column_decimals(raw: &str) -> BTreeMap<String, usize> {
+    raw.split(',')
+        .filter_map(|entry| {
+            let (key, decimals) = entry.split_once('=')?;
+            let key = key.trim().to_ascii_lowercase();
+            let decimals = decimals.trim().parse::<usize>().ok()?;
+            (DASHBOARD_COLUMN_KEYS.contains(&key.as_str()) && decimals <= 12)
+                .then_some((key, decimals))
+        })
+        .collect()
 }
 
-fn format_numeric_or_keep(value: &str, sig_digits: usize) -> String {
-    match value.parse::<f64>() {
-        Ok(number) => format_significant(number, sig_digits),
-        Err(_) => value.to_string(),
+fn single_char(raw: &str) -> Option<char> {
+    let mut chars = raw.chars();
+    match (chars.next(), chars.next()) {
+        (Some(ch), None) => Some(ch),
+        _ => None,
     }
 }
 
@@ -1352,6 +4604,72 @@ fn trim_number(raw: String) -> String {
     }
 }
 
+/// Page path for the all-durations dashboard or a `/dashboard/{duration}` route.
+fn dashboard_route(preset: Option<Duration>) -> String {
+    match preset {
+        Some(duration) => format!("/dashboard/{}", duration.as_label()),
+        None => "/dashboard".to_string(),
+    }
+}
+
+/// Duration block for a `/dashboard/{duration}` page: links to the other duration
+/// routes instead of checkboxes, since the route fixes the filter.
+fn render_duration_links(current: Duration) -> String {
+    let mut out = String::new();
+    out.push_str("<section class=\"filter-block\"><p class=\"filter-title\">Duration</p>");
+    out.push_str("<a class=\"filter-item duration-link\" href=\"/dashboard\">all</a>");
+    for duration in Duration::ALL {
+        let label = duration.as_label();
+        let current_class = if duration == current { " current" } else { "" };
+        out.push_str(&format!(
+            "<a class=\"filter-item duration-link{current_class}\" href=\"/dashboard/{label}\">{label}</a>"
+        ));
+    }
+    out.push_str("</section>");
+    out
+}
+
+/// Links to the dashboard at `route` without a view and with each saved view.
+fn render_view_links(route: &str, views: &[String], current: Option<&str>) -> String {
+    let mut out = String::new();
+    out.push_str("<section class=\"filter-block\"><p class=\"filter-title\">View</p>");
+    let none_class = if current.is_none() { " current" } else { "" };
+    out.push_str(&format!(
+        "<a class=\"filter-item view-link{none_class}\" href=\"{route}\">none</a>"
+    ));
+    for name in views {
+        let current_class = if current == Some(name.as_str()) {
+            " current"
+        } else {
+            ""
+        };
+        let name = escape_html(name);
+        out.push_str(&format!(
+            "<a class=\"filter-item view-link{current_class}\" href=\"{route}?view={name}\">{name}</a>"
+        ));
+    }
+    out.push_str("</section>");
+    out
+}
+
+/// Hides whole table columns by position, so rows rendered by the client script are
+/// covered as well as the server-rendered ones.
+fn render_hidden_columns_style(hidden: &[&str]) -> String {
+    let selectors: Vec<String> = DASHBOARD_COLUMN_KEYS
+        .iter()
+        .enumerate()
+        .filter(|(_, key)| hidden.contains(key))
+        .map(|(idx, _)| {
+            let nth = idx + 1;
+            format!("#dashboard-table th:nth-child({nth}),#dashboard-table td:nth-child({nth})")
+        })
+        .collect();
+    if selectors.is_empty() {
+        return String::new();
+    }
+    format!("<style>{}{{display:none}}</style>\n", selectors.join(","))
+}
+
 fn render_checkbox_group<F>(
     title: &str,
     param_name: &str,
@@ -1373,7 +4691,7 @@ where
         out.push_str(&escape_html(param_name));
         out.push_str("\" value=\"");
         out.push_str(&escape_html(option));
-        out.push_str("\"");
+        out.push('"');
         out.push_str(checked);
         out.push_str("> <span>");
         out.push_str(&escape_html(option));
@@ -1384,34 +4702,152 @@ where
     out
 }
 
-fn render_rows_html(rows: &[DashboardDisplayRow]) -> String {
+/// Number inputs for lower bounds; an empty input means no bound.
+fn render_min_inputs(title: &str, inputs: &[(&str, &str, Option<f64>)]) -> String {
     let mut out = String::new();
-    for (idx, row) in rows.iter().enumerate() {
-        out.push_str(&render_row_html(row, idx));
+    out.push_str("<section class=\"filter-block\">");
+    out.push_str("<p class=\"filter-title\">");
+    out.push_str(&escape_html(title));
+    out.push_str("</p>");
+
+    for (param_name, label, value) in inputs {
+        let value = value.map(|v| v.to_string()).unwrap_or_default();
+        out.push_str(&format!(
+            "<label class=\"filter-item\"><span>{}</span><input type=\"number\" min=\"0\" step=\"any\" name=\"{}\" value=\"{}\"></label>",
+            escape_html(label),
+            escape_html(param_name),
+            escape_html(&value)
+        ));
     }
+
+    out.push_str("</section>");
+    out
+}
+
+fn render_occupancy_html(grid: &OccupancyGrid) -> String {
+    let mut out = String::from(
+        "<section class=\"card occupancy\"><div class=\"occ-head\"><b>Window occupancy</b><span>previous · active · next; <span class=\"occ-dot occ-position\"></span> position <span class=\"occ-dot occ-orders\"></span> orders <span class=\"occ-dot occ-position_and_orders\"></span> both</span></div><table class=\"occ-grid\"><thead><tr><th></th>",
+    );
+    for duration in &grid.durations {
+        out.push_str(&format!("<th>{}</th>", escape_html(duration)));
+    }
+    out.push_str("</tr></thead><tbody id=\"occupancy-body\">");
+    out.push_str(&render_occupancy_rows(grid));
+    out.push_str("</tbody></table></section>\n");
     out
 }
 
-fn render_row_html(row: &DashboardDisplayRow, idx: usize) -> String {
+fn render_occupancy_rows(grid: &OccupancyGrid) -> String {
     let mut out = String::new();
-    out.push_str(&format!("<tr data-row=\"{idx}\">"));
+    for coin in &grid.coins {
+        out.push_str(&format!("<tr><th>{}</th>", escape_html(coin)));
+        for cell in grid.cells.iter().filter(|cell| &cell.coin == coin) {
+            out.push_str(if cell.needs_attention {
+                "<td class=\"occ-attention\" title=\"Exposure left in an ended window\">"
+            } else {
+                "<td>"
+            });
+            for (window, occupancy) in [
+                ("previous", cell.previous),
+                ("active", cell.active),
+                ("next", cell.next),
+            ] {
+                out.push_str(&format!(
+                    "<span class=\"occ-dot occ-{0}\" title=\"{1}: {0}\"></span>",
+                    occupancy.as_str(),
+                    window
+                ));
+            }
+            out.push_str("</td>");
+        }
+        out.push_str("</tr>");
+    }
+    out
+}
 
-    let link_class = if row.mock_columns.iter().any(|entry| entry == "link") {
-        "cell-mock"
-    } else {
+fn render_activity_html(events: &[ActivityEvent]) -> String {
+    let mut out = String::from(
+        "<section class=\"card activity\"><div class=\"occ-head\"><b>Activity</b><span>what changed recently, newest first</span></div><ol class=\"act-list\" id=\"activity-list\">",
+    );
+    out.push_str(&render_activity_items(events));
+    out.push_str("</ol></section>\n");
+    out
+}
+
+fn render_activity_items(events: &[ActivityEvent]) -> String {
+    if events.is_empty() {
+        return "<li class=\"act-empty\">No changes yet</li>".to_string();
+    }
+    let mut out = String::new();
+    for event in events {
+        out.push_str(&format!(
+            "<li class=\"act act-{}\"><span class=\"act-time\" data-ts=\"{}\">{}</span> <b>{} {}</b> {} <span class=\"act-detail\">{}</span> <span class=\"slug-id\">{}</span></li>",
+            event.kind.as_str(),
+            event.ts_utc,
+            utc_hhmm(event.ts_utc),
+            escape_html(&event.coin),
+            escape_html(&event.duration),
+            event.kind.as_str().replace('_', " "),
+            escape_html(&event.detail),
+            escape_html(&event.slug),
+        ));
+    }
+    out
+}
+
+fn render_live_banner(status: &SnapshotStatus) -> String {
+    let since_ts = status.live_unavailable_since_ts_utc.unwrap_or(0);
+    let hidden = if status.live_unavailable() {
         ""
+    } else {
+        " hidden"
     };
-    out.push_str(&format!("<td class=\"market-cell {}\">", link_class));
-    out.push_str("<a class=\"market-btn\" target=\"_blank\" rel=\"noopener noreferrer\" href=\"");
-    out.push_str(&escape_html(&row.link_url));
-    out.push_str("\">Open Market</a>");
-    out.push_str("<span class=\"slug-id\" title=\"");
-    out.push_str(&escape_html(&row.slug));
-    out.push_str("\">");
-    out.push_str(&escape_html(&row.slug));
-    out.push_str("</span></td>");
+    let (class, degraded_hidden) = match status.degraded_since_ts_utc {
+        Some(_) => ("live-banner degraded", ""),
+        None => ("live-banner", " hidden"),
+    };
+    let health = status
+        .health_score
+        .map(|score| format!(" (health {score}/100)"))
+        .unwrap_or_default();
+
+    format!(
+        "<div id=\"live-banner\" class=\"{class}\" role=\"alert\" data-since-ts=\"{since_ts}\"{hidden}><span id=\"live-banner-degraded\"{degraded_hidden}>Degraded: serving last-known-good rows. </span>Live data unavailable since <b id=\"live-banner-since\">{}</b><span id=\"live-banner-health\">{health}</span>; retrying</div>\n",
+        utc_hhmm(since_ts)
+    )
+}
+
+/// Marks a reference captured after the interval start; on-time references get nothing.
+fn ref_delay_tag(delay_ms: i64) -> String {
+    if delay_ms <= 0 {
+        return String::new();
+    }
+    format!(
+        " <span class=\"ref-late\" title=\"Reference captured {delay_ms}ms after the interval start\">+{}s</span>",
+        delay_ms as f64 / 1_000.0
+    )
+}
+
+fn render_rows_html(rows: &[DashboardDisplayRow], horizons: &[u32]) -> String {
+    let mut out = String::new();
+    for (idx, row) in rows.iter().enumerate() {
+        out.push_str(&render_row_html(row, idx, horizons));
+    }
+    out
+}
 
-    let columns: [(&str, &str); 20] = [
+/// `horizons` as the comma list `?horizons=` takes.
+fn horizons_param(horizons: &[u32]) -> String {
+    horizons
+        .iter()
+        .map(u32::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Every cell after the market link, keyed like `DASHBOARD_COLUMN_KEYS`.
+fn display_cells(row: &DashboardDisplayRow) -> [(&'static str, &str); 26] {
+    [
         ("coin", &row.coin),
         ("duration", &row.duration),
         ("bets_open", &row.bets_open),
@@ -1420,6 +4856,9 @@ fn render_row_html(row: &DashboardDisplayRow, idx: usize) -> String {
         ("ref_price", &row.ref_price),
         ("price", &row.price),
         ("probability", &row.probability),
+        ("realized_vol", &row.realized_vol),
+        ("price_vs_ref_pct", &row.price_vs_ref_pct),
+        ("move_z", &row.move_z),
         ("best_bid_yes", &row.best_bid_yes),
         ("best_ask_yes", &row.best_ask_yes),
         ("position_net", &row.position_net),
@@ -1427,16 +4866,58 @@ fn render_row_html(row: &DashboardDisplayRow, idx: usize) -> String {
         ("pos_no", &row.pos_no),
         ("offer_yes", &row.offer_yes),
         ("offer_no", &row.offer_no),
+        ("suggested_size", &row.suggested_size),
         ("net_profit", &row.net_profit),
         ("taker_fee_pct", &row.taker_fee_pct),
         ("maker_fee_pct", &row.maker_fee_pct),
         ("fee_exponent", &row.fee_exponent),
         ("reward_pct", &row.reward_pct),
-    ];
+        ("volume_24h", &row.volume_24h),
+        ("liquidity", &row.liquidity),
+    ]
+}
+
+/// One table row; `horizons` adds a probability cell each after the fixed columns,
+/// withheld where the row has no value for it.
+fn render_row_html(row: &DashboardDisplayRow, idx: usize, horizons: &[u32]) -> String {
+    let mut out = String::new();
+    match row.stale_as_of_ts_utc {
+        Some(ts) => out.push_str(&format!(
+            "<tr data-row=\"{idx}\" class=\"row-stale\" title=\"Stale: last resolved {} UTC\">",
+            utc_hhmm(ts)
+        )),
+        None => out.push_str(&format!("<tr data-row=\"{idx}\">")),
+    }
 
-    for (key, value) in columns {
+    let link_class = if row.mock_columns.iter().any(|entry| entry == "link") {
+        "cell-mock"
+    } else {
+        ""
+    };
+    out.push_str(&format!("<td class=\"market-cell {}\">", link_class));
+    out.push_str("<a class=\"market-btn\" target=\"_blank\" rel=\"noopener noreferrer\" href=\"");
+    out.push_str(&escape_html(&row.link_url));
+    out.push_str("\">Open Market</a>");
+    out.push_str("<button type=\"button\" class=\"note-btn\" data-slug=\"");
+    out.push_str(&escape_html(&row.slug));
+    out.push_str("\" title=\"Add a journal note\">Note</button>");
+    if let Some(group) = &row.neg_risk_group {
+        out.push_str(&format!(
+            "<span class=\"neg-risk\" title=\"Neg-risk event {}: shares collateral with its other markets\">NR</span>",
+            escape_html(group)
+        ));
+    }
+    out.push_str("<span class=\"slug-id\" title=\"");
+    out.push_str(&escape_html(&row.slug));
+    out.push_str("\">");
+    out.push_str(&escape_html(&row.slug));
+    out.push_str("</span></td>");
+
+    for (key, value) in display_cells(row) {
         let class = if row.mock_columns.iter().any(|entry| entry == key) {
             "cell-mock"
+        } else if row.simulated_columns.iter().any(|entry| entry == key) {
+            "cell-sim"
         } else {
             ""
         };
@@ -1454,65 +4935,674 @@ fn render_row_html(row: &DashboardDisplayRow, idx: usize) -> String {
             out.push_str(class);
             out.push_str("\">");
             out.push_str(&escape_html(value));
+            if let Some(delay_ms) = row.ref_capture_delay_ms.filter(|_| key == "ref_price") {
+                out.push_str(&ref_delay_tag(delay_ms));
+            }
+            if key == "probability" && !row.probability_raw.is_empty() {
+                out.push_str(
+                    " <span class=\"prob-raw\" title=\"Model probability before smoothing\">raw ",
+                );
+                out.push_str(&escape_html(&row.probability_raw));
+                out.push_str("</span>");
+            }
+            let queue_ahead = match key {
+                "offer_yes" => row.queue_ahead_yes.as_str(),
+                "offer_no" => row.queue_ahead_no.as_str(),
+                _ => "",
+            };
+            if !queue_ahead.is_empty() && queue_ahead != "-" {
+                out.push_str(
+                    " <span class=\"queue-ahead\" title=\"Estimated shares queued ahead\">q ",
+                );
+                out.push_str(&escape_html(queue_ahead));
+                out.push_str("</span>");
+            }
             out.push_str("</td>");
         }
     }
+    let probability_class = if row.mock_columns.iter().any(|entry| entry == "probability") {
+        "cell-mock"
+    } else {
+        ""
+    };
+    for horizon in horizons {
+        let value = row
+            .probability_horizons
+            .get(horizon)
+            .map_or(crate::REDACTED, String::as_str);
+        out.push_str(&format!(
+            "<td class=\"{probability_class}\">{}</td>",
+            escape_html(value)
+        ));
+    }
 
     out.push_str("</tr>\n");
     out
 }
 
-fn escape_html(input: &str) -> String {
-    input
-        .replace('&', "&amp;")
-        .replace('<', "&lt;")
-        .replace('>', "&gt;")
-        .replace('"', "&quot;")
-        .replace('\'', "&#39;")
+fn escape_html(input: &str) -> String {
+    input
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[derive(Clone)]
+struct DashboardAppState {
+    source: Arc<dyn DashboardSnapshotSource>,
+    number_format: Arc<NumberFormatConfig>,
+    display_cache: Arc<ArcSwapOption<PrecomputedDisplay>>,
+    /// Fed by [`ActivityFeed::follow`] on every publication and clock second.
+    activity: Arc<ActivityFeed>,
+    capital: CapitalConfig,
+    readiness: Arc<ReadinessConfig>,
+    redaction: Arc<RedactionConfig>,
+    views: DashboardViews,
+}
+
+/// Every row of one published snapshot, formatted for display at one clock second.
+/// Formatting is the expensive part of a poll, so it happens once per snapshot and
+/// second and every request after that only filters and serializes.
+struct PrecomputedDisplay {
+    snapshot: Arc<DashboardSnapshot>,
+    now_ts_utc: i64,
+    /// One display row per snapshot row, in the same order.
+    display: DashboardDisplaySnapshot,
+}
+
+impl DashboardAppState {
+    fn new(
+        source: Arc<dyn DashboardSnapshotSource>,
+        config: DashboardConfig,
+        views: DashboardViews,
+    ) -> Self {
+        Self {
+            source,
+            number_format: Arc::new(config.number_format),
+            display_cache: Arc::new(ArcSwapOption::empty()),
+            activity: Arc::new(ActivityFeed::new(config.activity)),
+            capital: config.capital,
+            readiness: Arc::new(config.readiness),
+            redaction: Arc::new(config.redaction),
+            views,
+        }
+    }
+
+    /// The unfiltered display of the current snapshot, formatted on the first request
+    /// after a publication or clock tick. Concurrent misses may both format; the
+    /// results are identical.
+    fn precomputed_display(&self) -> Arc<PrecomputedDisplay> {
+        let snapshot = self.source.snapshot();
+        let now_ts_utc = self.source.now_ts_utc();
+        if let Some(cached) = self.display_cache.load_full() {
+            if Arc::ptr_eq(&cached.snapshot, &snapshot) && cached.now_ts_utc == now_ts_utc {
+                return cached;
+            }
+        }
+        let display = DashboardDisplaySnapshot {
+            now_ts_utc,
+            seq: snapshot.seq,
+            rows: snapshot
+                .rows
+                .iter()
+                .map(|row| format_row_for_display_with_format(row, now_ts_utc, &self.number_format))
+                .collect(),
+            source_status: SnapshotStatus::default(),
+        };
+        let precomputed = Arc::new(PrecomputedDisplay {
+            snapshot,
+            now_ts_utc,
+            display,
+        });
+        self.display_cache.store(Some(Arc::clone(&precomputed)));
+        precomputed
+    }
+}
+
+impl PrecomputedDisplay {
+    /// The display rows whose snapshot rows pass `filters`, like
+    /// [`build_display_snapshot_with_format`] without reformatting.
+    fn filtered(&self, filters: &DashboardFilters) -> DashboardDisplaySnapshot {
+        let matching = self
+            .snapshot
+            .rows
+            .iter()
+            .zip(&self.display.rows)
+            .filter(|(row, _)| row_matches_filters(row, filters, self.now_ts_utc));
+        let rows = sort_and_page(matching, filters, self.now_ts_utc)
+            .into_iter()
+            .map(|row| {
+                let mut row = row.clone();
+                select_probability_horizons(&mut row, &filters.probability_horizons);
+                row
+            })
+            .collect();
+        DashboardDisplaySnapshot {
+            now_ts_utc: self.now_ts_utc,
+            seq: self.display.seq,
+            rows,
+            source_status: SnapshotStatus::default(),
+        }
+    }
+}
+
+async fn get_dashboard_html(
+    State(state): State<DashboardAppState>,
+    headers: HeaderMap,
+    Query(query_pairs): Query<Vec<(String, String)>>,
+) -> impl IntoResponse {
+    Html(render_dashboard_page(&state, &headers, &query_pairs, None))
+}
+
+/// `/dashboard/{duration}`: the dashboard with the duration filter preset, at that
+/// duration's refresh cadence and columns. Unknown durations are a 404.
+async fn get_duration_dashboard_html(
+    State(state): State<DashboardAppState>,
+    headers: HeaderMap,
+    Path(duration): Path<String>,
+    Query(query_pairs): Query<Vec<(String, String)>>,
+) -> Response {
+    match Duration::parse(&duration) {
+        Ok(duration) => Html(render_dashboard_page(
+            &state,
+            &headers,
+            &query_pairs,
+            Some(duration),
+        ))
+        .into_response(),
+        Err(_) => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Public requests get redacted rows, no fills in the activity feed, and neither the
+/// occupancy grid nor the wallet headroom.
+fn render_dashboard_page(
+    state: &DashboardAppState,
+    headers: &HeaderMap,
+    query_pairs: &[(String, String)],
+    preset: Option<Duration>,
+) -> String {
+    let public = state.redaction.redacts(headers);
+    let status = state.source.status();
+    let view = state.views.selected(query_pairs);
+    let mut query = dashboard_query_from_pairs(&state.views.expand(query_pairs));
+    if let Some(duration) = preset {
+        query.duration = vec![duration.as_label().to_string()];
+    }
+    let filters = DashboardFilters::from_query(&query);
+    let precomputed = state.precomputed_display();
+    let mut display = precomputed.filtered(&filters);
+    let mut activity = state.activity.events();
+    if public {
+        display
+            .rows
+            .iter_mut()
+            .for_each(|row| state.redaction.redact_row(row));
+        activity.retain(|event| event.kind != ActivityKind::Fill);
+    }
+    let filtered_rows = display.rows.len();
+    info!(
+        component = "dashboard",
+        event = "http.dashboard.request",
+        route = %dashboard_route(preset),
+        query_present = !query_pairs.is_empty(),
+        public,
+        view = view.as_deref(),
+        filtered_rows
+    );
+    let occupancy =
+        (!public).then(|| occupancy_grid(&precomputed.snapshot.rows, precomputed.now_ts_utc));
+    render_dashboard_html_with_filters(
+        occupancy.as_ref(),
+        &display,
+        &filters,
+        precomputed.now_ts_utc,
+        &status,
+        &activity,
+        preset,
+        &state.views.names(),
+        view.as_deref(),
+    )
+}
+
+async fn get_dashboard_snapshot(
+    State(state): State<DashboardAppState>,
+    headers: HeaderMap,
+    Query(query_pairs): Query<Vec<(String, String)>>,
+) -> impl IntoResponse {
+    let query = dashboard_query_from_pairs(&state.views.expand(&query_pairs));
+    let filters = DashboardFilters::from_query(&query);
+    let public = state.redaction.redacts(&headers);
+    let display_snapshot = current_display_snapshot(&state, &filters, public);
+    let filtered_rows = display_snapshot.rows.len();
+    info!(
+        component = "dashboard",
+        event = "http.snapshot.request",
+        route = "/dashboard/snapshot",
+        query_present = !query_pairs.is_empty(),
+        filtered_rows
+    );
+    Json(display_snapshot)
+}
+
+/// Occupancy grid over the unfiltered snapshot, so hidden rows still show exposure.
+/// `401` for public requests.
+async fn get_dashboard_occupancy(
+    State(state): State<DashboardAppState>,
+    headers: HeaderMap,
+) -> Response {
+    if state.redaction.redacts(&headers) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    let snapshot = state.source.snapshot();
+    Json(occupancy_grid(&snapshot.rows, state.source.now_ts_utc())).into_response()
+}
+
+/// Wallet collateral usage and the quote throttle it implies. `401` for public
+/// requests.
+async fn get_wallet(State(state): State<DashboardAppState>, headers: HeaderMap) -> Response {
+    if state.redaction.redacts(&headers) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    Json(WalletReport::new(
+        state.source.capital_usage(),
+        state.capital,
+    ))
+    .into_response()
+}
+
+/// `200` once the source serves fresh live rows (and the kline store, when checked,
+/// answers); `503` with the failing checks otherwise.
+async fn get_readyz(State(state): State<DashboardAppState>) -> impl IntoResponse {
+    let backend = state.readiness.backend.clone();
+    let klines = match state.readiness.kline_store.clone() {
+        Some(path) => Some(
+            tokio::task::spawn_blocking(move || {
+                open_kline_reader(&backend, &path)
+                    .and_then(|mut store| store.count_range(BinanceSymbol::BtcUsdt, 0, 0))
+                    .map(|_| ())
+                    .map_err(|err| format!("kline store {}: {err}", path.display()))
+            })
+            .await
+            .unwrap_or_else(|err| Err(err.to_string())),
+        ),
+        None => None,
+    };
+    let report = readiness_report(
+        &state.source.status(),
+        klines,
+        &state.readiness,
+        crate::faults::now_utc().timestamp(),
+    );
+    let code = if report.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (code, Json(report))
+}
+
+/// Activity feed over the unfiltered snapshot, newest first. Public requests get no
+/// fills.
+async fn get_dashboard_activity(
+    State(state): State<DashboardAppState>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let now_ts_utc = state.precomputed_display().now_ts_utc;
+    let mut events = state.activity.events();
+    if state.redaction.redacts(&headers) {
+        events.retain(|event| event.kind != ActivityKind::Fill);
+    }
+    Json(ActivityFeedView { now_ts_utc, events })
+}
+
+/// `/dashboard/export?format=csv|json`: the rows `/dashboard/snapshot` would return
+/// for the same query, one record per row under the `DASHBOARD_COLUMN_KEYS` header plus
+/// `mock_columns`, streamed as a download. CSV is the default; unknown formats are a
+/// `400`.
+async fn get_dashboard_export(
+    State(state): State<DashboardAppState>,
+    headers: HeaderMap,
+    Query(query_pairs): Query<Vec<(String, String)>>,
+) -> Response {
+    let format = query_pairs
+        .iter()
+        .rev()
+        .find(|(key, _)| key.trim().eq_ignore_ascii_case("format"))
+        .map_or("csv".to_string(), |(_, value)| {
+            value.trim().to_ascii_lowercase()
+        });
+    let (content_type, extension) = match format.as_str() {
+        "csv" => ("text/csv; charset=utf-8", "csv"),
+        "json" => ("application/json", "json"),
+        other => {
+            return (
+                StatusCode::BAD_REQUEST,
+                format!("unknown export format {other:?}; use csv or json"),
+            )
+                .into_response()
+        }
+    };
+    let query = dashboard_query_from_pairs(&state.views.expand(&query_pairs));
+    let filters = DashboardFilters::from_query(&query);
+    let public = state.redaction.redacts(&headers);
+    let display = current_display_snapshot(&state, &filters, public);
+    let filtered_rows = display.rows.len();
+    info!(
+        component = "dashboard",
+        event = "http.export.request",
+        route = "/dashboard/export",
+        format = extension,
+        public,
+        filtered_rows
+    );
+
+    let disposition = format!(
+        "attachment; filename=\"dashboard-{}.{extension}\"",
+        display.now_ts_utc
+    );
+    let rows = display.rows.into_iter();
+    let chunks: Box<dyn Iterator<Item = String> + Send> = if extension == "csv" {
+        let header = EXPORT_COLUMNS.join(",") + "\n";
+        Box::new(std::iter::once(header).chain(rows.map(|row| export_csv_line(&row))))
+    } else {
+        let records = rows.enumerate().map(|(idx, row)| {
+            let separator = if idx == 0 { "" } else { "," };
+            format!("{separator}\n{}", export_json_record(&row))
+        });
+        Box::new(
+            std::iter::once("[".to_string())
+                .chain(records)
+                .chain(std::iter::once("\n]\n".to_string())),
+        )
+    };
+    (
+        [
+            (CONTENT_TYPE, content_type.to_string()),
+            (CONTENT_DISPOSITION, disposition),
+        ],
+        Body::from_stream(stream::iter(chunks.map(Ok::<_, Infallible>))),
+    )
+        .into_response()
+}
+
+/// Header of `/dashboard/export`: `DASHBOARD_COLUMN_KEYS`, then the provenance column.
+const EXPORT_COLUMNS: [&str; DASHBOARD_COLUMN_KEYS.len() + 1] = {
+    let mut columns = [""; DASHBOARD_COLUMN_KEYS.len() + 1];
+    let mut idx = 0;
+    while idx < DASHBOARD_COLUMN_KEYS.len() {
+        columns[idx] = DASHBOARD_COLUMN_KEYS[idx];
+        idx += 1;
+    }
+    columns[idx] = "mock_columns";
+    columns
+};
+
+/// The exported values of `row` in `DASHBOARD_COLUMN_KEYS` order: the market URL for
+/// `link`, the end timestamp (UTC seconds) for `end`, and the displayed text
+/// otherwise. Withheld cells are `None`.
+fn export_values(row: &DashboardDisplayRow) -> Vec<Option<String>> {
+    std::iter::once(Some(row.link_url.clone()))
+        .chain(display_cells(row).into_iter().map(|(key, value)| {
+            if key == "end" {
+                Some(row.end_ts_utc.to_string())
+            } else {
+                (!is_redacted(value)).then(|| value.to_string())
+            }
+        }))
+        .collect()
+}
+
+/// One CSV record: withheld cells are empty and `mock_columns` is `;`-separated.
+fn export_csv_line(row: &DashboardDisplayRow) -> String {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    let record = export_values(row)
+        .into_iter()
+        .map(Option::unwrap_or_default)
+        .chain(std::iter::once(row.mock_columns.join(";")));
+    // Writing to memory only fails on I/O, which a `Vec` cannot.
+    let _ = writer.write_record(record);
+    writer
+        .into_inner()
+        .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+        .unwrap_or_default()
+}
+
+/// One JSON object keyed by the export header: withheld cells are `null` and
+/// `mock_columns` is an array.
+fn export_json_record(row: &DashboardDisplayRow) -> String {
+    let mut record = String::from("{");
+    for (idx, (key, value)) in DASHBOARD_COLUMN_KEYS
+        .into_iter()
+        .zip(export_values(row))
+        .enumerate()
+    {
+        if idx > 0 {
+            record.push(',');
+        }
+        let value = serde_json::to_string(&value).unwrap_or_else(|_| "null".to_string());
+        record.push_str(&format!("\"{key}\":{value}"));
+    }
+    let mock_columns =
+        serde_json::to_string(&row.mock_columns).unwrap_or_else(|_| "[]".to_string());
+    record.push_str(&format!(",\"mock_columns\":{mock_columns}}}"));
+    record
+}
+
+/// Every saved view, by name.
+async fn get_dashboard_views(State(state): State<DashboardAppState>) -> Json<Vec<DashboardView>> {
+    Json(state.views.list())
+}
+
+async fn get_dashboard_view(
+    State(state): State<DashboardAppState>,
+    Path(name): Path<String>,
+) -> Response {
+    match state.views.get(&name) {
+        Some(view) => Json(view).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Creates or replaces a view from a [`DashboardViewSpec`] body. `400` for a bad name or
+/// an unknown column or filter, `401` for public requests.
+async fn put_dashboard_view(
+    State(state): State<DashboardAppState>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+    Json(spec): Json<DashboardViewSpec>,
+) -> Response {
+    if state.redaction.redacts(&headers) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    match state
+        .views
+        .upsert(&name, spec, crate::faults::now_utc().timestamp())
+    {
+        Ok(view) => Json(view).into_response(),
+        Err(err) if err.is_invalid_input() => {
+            (StatusCode::BAD_REQUEST, err.to_string()).into_response()
+        }
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+    }
 }
 
-#[derive(Clone)]
-struct DashboardAppState {
-    source: Arc<dyn DashboardSnapshotSource>,
+/// `204` once removed, `404` when there was no such view, `401` for public requests.
+async fn delete_dashboard_view(
+    State(state): State<DashboardAppState>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+) -> Response {
+    if state.redaction.redacts(&headers) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    match state.views.delete(&name) {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => StatusCode::NOT_FOUND.into_response(),
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+    }
 }
 
-async fn get_dashboard_html(
+/// Server-sent events: one `snapshot` event with the full filtered display snapshot,
+/// then a `delta` event whenever the rows or source status change. Event ids carry
+/// the snapshot seq so clients can detect gaps and resync. Changes are checked every
+/// [`stream_tick_ms`].
+async fn get_dashboard_stream(
     State(state): State<DashboardAppState>,
+    headers: HeaderMap,
     Query(query_pairs): Query<Vec<(String, String)>>,
-) -> impl IntoResponse {
-    let snapshot = state.source.snapshot();
-    let query = dashboard_query_from_pairs(&query_pairs);
-    let filters = DashboardFilters::from_query(&query);
-    let now_ts_utc = Utc::now().timestamp();
-    let filtered_rows = apply_filters(&snapshot.rows, &filters, now_ts_utc).len();
-    info!(
-        component = "dashboard",
-        event = "http.dashboard.request",
-        route = "/dashboard",
-        query_present = !query_pairs.is_empty(),
-        filtered_rows
-    );
-    let html = render_dashboard_html_with_filters(&snapshot, &filters, now_ts_utc);
-    Html(html)
+) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
+    let push = DisplayPush::open(state, &headers, &query_pairs, "/dashboard/stream");
+    let events = stream::unfold(push, |mut push| async move {
+        let event = match push.next().await {
+            DashboardPush::Snapshot(snapshot) => Event::default()
+                .event("snapshot")
+                .id(snapshot.seq.to_string())
+                .json_data(&snapshot),
+            DashboardPush::Delta(delta) => Event::default()
+                .event("delta")
+                .id(delta.seq.to_string())
+                .json_data(&delta),
+        };
+        Some((event, push))
+    });
+
+    Sse::new(events).keep_alive(KeepAlive::default())
 }
 
-async fn get_dashboard_snapshot(
+/// Same updates as `/dashboard/stream`, as [`DashboardPush`] JSON text frames.
+async fn get_dashboard_ws(
     State(state): State<DashboardAppState>,
+    headers: HeaderMap,
     Query(query_pairs): Query<Vec<(String, String)>>,
-) -> impl IntoResponse {
-    let snapshot = state.source.snapshot();
-    let query = dashboard_query_from_pairs(&query_pairs);
-    let filters = DashboardFilters::from_query(&query);
-    let display_snapshot = build_display_snapshot(&snapshot, &filters, Utc::now().timestamp());
-    let filtered_rows = display_snapshot.rows.len();
-    info!(
-        component = "dashboard",
-        event = "http.snapshot.request",
-        route = "/dashboard/snapshot",
-        query_present = !query_pairs.is_empty(),
-        filtered_rows
-    );
-    Json(display_snapshot)
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    let push = DisplayPush::open(state, &headers, &query_pairs, "/dashboard/ws");
+    upgrade.on_upgrade(|socket| push_to_socket(socket, push))
+}
+
+async fn push_to_socket(mut socket: WebSocket, mut push: DisplayPush) {
+    loop {
+        tokio::select! {
+            update = push.next() => {
+                let Ok(text) = serde_json::to_string(&update) else {
+                    return;
+                };
+                if socket.send(Message::Text(text.into())).await.is_err() {
+                    return;
+                }
+            }
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_)) | Err(_)) | None => return,
+                // Pings are answered by the socket; clients have nothing else to say.
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+/// One push client: a full snapshot first, then a delta whenever its filtered display
+/// changes. Updates are at least a tick apart; between them a publishing source wakes
+/// the client on publish, any other source is re-read every tick.
+struct DisplayPush {
+    state: DashboardAppState,
+    filters: DashboardFilters,
+    public: bool,
+    tick_ms: u64,
+    published: Option<watch::Receiver<u64>>,
+    held: Option<DashboardDisplaySnapshot>,
+}
+
+impl DisplayPush {
+    fn open(
+        state: DashboardAppState,
+        headers: &HeaderMap,
+        query_pairs: &[(String, String)],
+        route: &'static str,
+    ) -> Self {
+        let query = dashboard_query_from_pairs(&state.views.expand(query_pairs));
+        let filters = DashboardFilters::from_query(&query);
+        let tick_ms = stream_tick_ms(filters.refresh_ms(), query_pairs);
+        let published = state.source.published();
+        info!(
+            component = "dashboard",
+            event = "http.stream.open",
+            route,
+            query_present = !query_pairs.is_empty(),
+            tick_ms,
+            on_publish = published.is_some()
+        );
+        Self {
+            public: state.redaction.redacts(headers),
+            state,
+            filters,
+            tick_ms,
+            published,
+            held: None,
+        }
+    }
+
+    /// Cancel-safe: an update is only marked as sent once it is returned.
+    async fn next(&mut self) -> DashboardPush {
+        loop {
+            if self.held.is_some() {
+                self.wait().await;
+            }
+            let next = current_display_snapshot(&self.state, &self.filters, self.public);
+            let push = match &self.held {
+                None => DashboardPush::Snapshot(next.clone()),
+                Some(prev) => match diff_display_snapshots(prev, &next) {
+                    Some(delta) => DashboardPush::Delta(delta),
+                    None => continue,
+                },
+            };
+            self.held = Some(next);
+            return push;
+        }
+    }
+
+    async fn wait(&mut self) {
+        tokio::time::sleep(std::time::Duration::from_millis(self.tick_ms)).await;
+        if let Some(published) = &mut self.published {
+            let recheck = std::time::Duration::from_millis(PUBLISH_RECHECK_MS);
+            if let Ok(Err(_)) = tokio::time::timeout(recheck, published.changed()).await {
+                // The source stopped publishing; fall back to re-reading every tick.
+                self.published = None;
+            }
+        }
+    }
+}
+
+/// Tick of a stream: the filters' cadence, slowed to `?min_interval=<ms>` (at most
+/// [`MAX_STREAM_INTERVAL_MS`]) when the client asks, e.g. from a hidden tab. A faster
+/// `min_interval` than the filters' cadence is ignored.
+fn stream_tick_ms(refresh_ms: u64, query_pairs: &[(String, String)]) -> u64 {
+    query_pairs
+        .iter()
+        .rev()
+        .find(|(key, _)| key.trim().eq_ignore_ascii_case("min_interval"))
+        .and_then(|(_, value)| value.trim().parse::<u64>().ok())
+        .map_or(refresh_ms, |min_interval| {
+            refresh_ms.max(min_interval.min(MAX_STREAM_INTERVAL_MS))
+        })
+}
+
+fn current_display_snapshot(
+    state: &DashboardAppState,
+    filters: &DashboardFilters,
+    public: bool,
+) -> DashboardDisplaySnapshot {
+    let mut display_snapshot = state.precomputed_display().filtered(filters);
+    if public {
+        display_snapshot
+            .rows
+            .iter_mut()
+            .for_each(|row| state.redaction.redact_row(row));
+    }
+    display_snapshot.source_status = state.source.status();
+    display_snapshot
 }
 
 fn dashboard_query_from_pairs(query_pairs: &[(String, String)]) -> DashboardQuery {
@@ -1524,6 +5614,14 @@ fn dashboard_query_from_pairs(query_pairs: &[(String, String)]) -> DashboardQuer
             "duration" => query.duration.push(value.clone()),
             "bets_open" => query.bets_open.push(value.clone()),
             "in_interval" => query.in_interval.push(value.clone()),
+            "min_volume_24h" => query.min_volume_24h = Some(value.clone()),
+            "min_liquidity" => query.min_liquidity = Some(value.clone()),
+            "sort_by" => query.sort_by = Some(value.clone()),
+            "order" => query.order = Some(value.clone()),
+            "limit" => query.limit = Some(value.clone()),
+            "offset" => query.offset = Some(value.clone()),
+            "columns" => query.columns.push(value.clone()),
+            "horizons" => query.horizons.push(value.clone()),
             _ => {}
         }
     }
@@ -1552,8 +5650,15 @@ mod tests {
             in_interval: None,
             end_hhmm: None,
             ref_price: Some("0.4987654".to_string()),
+            ref_capture_delay_ms: None,
             price: Some("0.5123456".to_string()),
             probability: Some("0.5123".to_string()),
+            probability_raw: None,
+            probability_smoothed: None,
+            probability_horizons: BTreeMap::new(),
+            realized_vol: Some("48.123456".to_string()),
+            price_vs_ref_pct: Some("0.1234567".to_string()),
+            move_z: Some("-1.23456".to_string()),
             best_bid_yes: Some("0.51".to_string()),
             best_ask_yes: Some("0.5139".to_string()),
             position_net: Some("12.34567@0.498765@YES".to_string()),
@@ -1561,28 +5666,80 @@ mod tests {
             pos_no: None,
             offer_yes: Some("2.34567@0.52".to_string()),
             offer_no: Some("3.33333@0.48".to_string()),
+            queue_ahead_yes: None,
+            queue_ahead_no: None,
+            suggested_size: Some("12.3456@YES".to_string()),
             net_profit: Some("0.123456".to_string()),
             taker_fee_pct: Some("0.25".to_string()),
             maker_fee_pct: Some("-0.05".to_string()),
             fee_exponent: Some("2".to_string()),
             reward_pct: Some("0.004567".to_string()),
+            volume_24h: Some("18234.5678".to_string()),
+            liquidity: None,
             mock_columns: vec!["price".to_string()],
+            simulated_columns: Vec::new(),
+            stale_as_of_ts_utc: None,
+            accepting_orders_ts_utc: None,
+            neg_risk_group: None,
         }
     }
 
     #[test]
     fn header_order_and_column_count_are_exact() {
-        assert_eq!(DASHBOARD_HEADERS.len(), 21);
-        assert_eq!(DASHBOARD_COLUMN_KEYS.len(), 21);
+        assert_eq!(DASHBOARD_HEADERS.len(), 27);
+        assert_eq!(DASHBOARD_COLUMN_KEYS.len(), 27);
         assert_eq!(DASHBOARD_HEADERS[0], "Link");
         assert_eq!(DASHBOARD_HEADERS[8], "Probability");
-        assert_eq!(DASHBOARD_HEADERS[20], "Reward %");
+        assert_eq!(DASHBOARD_HEADERS[9], "Realized Vol %");
+        assert_eq!(DASHBOARD_COLUMN_KEYS[9], "realized_vol");
+        assert_eq!(DASHBOARD_COLUMN_KEYS[11], "move_z");
+        assert_eq!(DASHBOARD_HEADERS[19], "Suggested Size");
+        assert_eq!(DASHBOARD_HEADERS[24], "Reward %");
+    }
+
+    #[cfg(feature = "discovery-sdk")]
+    fn fee_columns(
+        fee_type: Option<&str>,
+        fees_enabled: Option<bool>,
+        duration: Duration,
+    ) -> FeeColumns {
+        fee_columns_from_type(
+            fee_type,
+            fees_enabled,
+            duration,
+            &FeeRegistry::builtin(),
+            1_735_689_600,
+        )
+    }
+
+    #[cfg(feature = "discovery-sdk")]
+    #[test]
+    fn fee_columns_follow_the_schedule_in_force_now() {
+        use crate::fees::FeeScheduleEntry;
+
+        let change_ts = 1_767_225_600;
+        let fees = FeeRegistry::new(vec![FeeScheduleEntry {
+            fee_type: CRYPTO_15_MIN_FEE_TYPE.to_string(),
+            effective_from_ts_utc: Some(change_ts),
+            effective_until_ts_utc: None,
+            params: FeeParams {
+                taker_fee_rate: 0.25,
+                maker_fee_rate: -0.05,
+                fee_exponent: Some(2.0),
+            },
+        }])
+        .unwrap();
+        let at = |now| fee_columns_from_type(None, Some(true), Duration::M5, &fees, now);
+        assert_eq!(at(change_ts - 1).taker_fee_pct, "0");
+        assert_eq!(at(change_ts - 1).fee_exponent, "-");
+        assert_eq!(at(change_ts).taker_fee_pct, "0.25");
+        assert_eq!(at(change_ts).maker_fee_pct, "-0.05");
     }
 
     #[cfg(feature = "discovery-sdk")]
     #[test]
     fn fee_params_match_crypto_15_min_profile() {
-        let params = fee_params_from_type(Some("crypto_15_min"), Some(true), Duration::H1);
+        let params = fee_columns(Some("crypto_15_min"), Some(true), Duration::H1);
         assert_eq!(params.taker_fee_pct, "0.25");
         assert_eq!(params.maker_fee_pct, "-0.05");
         assert_eq!(params.fee_exponent, "2");
@@ -1591,7 +5748,7 @@ mod tests {
     #[cfg(feature = "discovery-sdk")]
     #[test]
     fn fee_params_default_when_fee_type_missing_or_disabled() {
-        let disabled = fee_params_from_type(Some("crypto_15_min"), Some(false), Duration::M15);
+        let disabled = fee_columns(Some("crypto_15_min"), Some(false), Duration::M15);
         assert_eq!(disabled.taker_fee_pct, "0");
         assert_eq!(disabled.maker_fee_pct, "0");
         assert_eq!(disabled.fee_exponent, "-");
@@ -1600,22 +5757,22 @@ mod tests {
     #[cfg(feature = "discovery-sdk")]
     #[test]
     fn fee_params_fallback_for_5m_and_15m_when_type_missing() {
-        let m5 = fee_params_from_type(None, Some(true), Duration::M5);
+        let m5 = fee_columns(None, Some(true), Duration::M5);
         assert_eq!(m5.taker_fee_pct, "0.25");
         assert_eq!(m5.maker_fee_pct, "-0.05");
         assert_eq!(m5.fee_exponent, "2");
 
-        let m15 = fee_params_from_type(None, Some(true), Duration::M15);
+        let m15 = fee_columns(None, Some(true), Duration::M15);
         assert_eq!(m15.taker_fee_pct, "0.25");
         assert_eq!(m15.maker_fee_pct, "-0.05");
         assert_eq!(m15.fee_exponent, "2");
 
-        let h1 = fee_params_from_type(None, Some(true), Duration::H1);
+        let h1 = fee_columns(None, Some(true), Duration::H1);
         assert_eq!(h1.taker_fee_pct, "0");
         assert_eq!(h1.maker_fee_pct, "0");
         assert_eq!(h1.fee_exponent, "-");
 
-        let d1 = fee_params_from_type(None, Some(true), Duration::D1);
+        let d1 = fee_columns(None, Some(true), Duration::D1);
         assert_eq!(d1.taker_fee_pct, "0");
         assert_eq!(d1.maker_fee_pct, "0");
         assert_eq!(d1.fee_exponent, "-");
@@ -1624,7 +5781,7 @@ mod tests {
     #[cfg(feature = "discovery-sdk")]
     #[test]
     fn fee_params_no_type_and_fees_disabled_is_zero() {
-        let params = fee_params_from_type(None, Some(false), Duration::M5);
+        let params = fee_columns(None, Some(false), Duration::M5);
         assert_eq!(params.taker_fee_pct, "0");
         assert_eq!(params.maker_fee_pct, "0");
         assert_eq!(params.fee_exponent, "-");
@@ -1648,6 +5805,7 @@ mod tests {
             duration: vec!["1h".to_string()],
             bets_open: vec!["open".to_string()],
             in_interval: vec!["yes".to_string()],
+            ..DashboardQuery::default()
         };
         let filters = DashboardFilters::from_query(&query);
         let now = 1_000;
@@ -1664,6 +5822,110 @@ mod tests {
         assert_eq!(filtered.len(), 2);
         assert_eq!(filtered[0].coin, "BTC");
         assert_eq!(filtered[1].coin, "ETH");
+
+        // Pair rows need both legs selected.
+        let pairs = vec![
+            sample_row("ETH/BTC", "1h", 900, 1100, Some("open")),
+            sample_row("SOL/BTC", "1h", 900, 1100, Some("open")),
+        ];
+        let filtered = apply_filters(&pairs, &filters, now);
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(
+            filtered[0].underlying(),
+            Some(MarketUnderlying::Pair(Coin::ETH, Coin::BTC))
+        );
+    }
+
+    #[test]
+    fn market_size_minimums_drop_rows_below_or_without_the_figure() {
+        let mut liquid = sample_row("BTC", "1h", 900, 1100, Some("open"));
+        liquid.liquidity = Some("5000".to_string());
+        let thin = sample_row("ETH", "1h", 900, 1100, Some("open"));
+        let rows = vec![liquid, thin];
+
+        let query = dashboard_query_from_pairs(&[
+            ("min_volume_24h".to_string(), "10000".to_string()),
+            ("min_liquidity".to_string(), String::new()),
+        ]);
+        let filters = DashboardFilters::from_query(&query);
+        assert_eq!(filters.min_volume_24h, Some(10_000.0));
+        assert_eq!(filters.min_liquidity, None);
+        assert_eq!(apply_filters(&rows, &filters, 1_000).len(), 2);
+
+        let filters = DashboardFilters::from_query(&DashboardQuery {
+            min_liquidity: Some("1000".to_string()),
+            ..DashboardQuery::default()
+        });
+        let filtered = apply_filters(&rows, &filters, 1_000);
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].coin, "BTC");
+
+        let fmt = NumberFormatConfig {
+            thousands_separator: Some(','),
+            ..NumberFormatConfig::default()
+        };
+        let display = format_row_for_display_with_format(&rows[0], 1_000, &fmt);
+        assert_eq!(display.volume_24h, "18,235");
+        assert_eq!(display.liquidity, "5,000");
+        let display = format_row_for_display_with_format(&rows[1], 1_000, &fmt);
+        assert_eq!(display.liquidity, "-");
+    }
+
+    #[test]
+    fn sort_by_orders_filtered_rows_then_pages_them() {
+        let mut rows = vec![
+            sample_row("BTC", "5m", 100, 400, Some("true")),
+            sample_row("ETH", "15m", 100, 1_000, Some("true")),
+            sample_row("SOL", "1h", 100, 3_700, Some("true")),
+            sample_row("XRP", "5m", 100, 400, Some("true")),
+        ];
+        rows[0].net_profit = Some("-2.5".to_string());
+        rows[1].net_profit = Some("10".to_string());
+        rows[2].net_profit = None;
+        rows[3].net_profit = Some("0.75".to_string());
+        let snapshot = DashboardSnapshot { rows, seq: 0 };
+        let coins = |query: DashboardQuery| -> Vec<String> {
+            build_display_snapshot(&snapshot, &DashboardFilters::from_query(&query), 200)
+                .rows
+                .into_iter()
+                .map(|row| row.coin)
+                .collect()
+        };
+        let by = |column: &str, order: Option<&str>| DashboardQuery {
+            sort_by: Some(column.to_string()),
+            order: order.map(str::to_string),
+            ..DashboardQuery::default()
+        };
+
+        // Rows without a value stay last in both directions.
+        assert_eq!(coins(by("net_profit", None)), ["BTC", "XRP", "ETH", "SOL"]);
+        assert_eq!(
+            coins(by("NET_PROFIT", Some("desc"))),
+            ["ETH", "XRP", "BTC", "SOL"]
+        );
+        // Stable: equal end times keep snapshot order.
+        assert_eq!(coins(by("end", Some("desc"))), ["SOL", "ETH", "BTC", "XRP"]);
+        assert_eq!(
+            coins(by("unknown", Some("desc"))),
+            ["BTC", "ETH", "SOL", "XRP"]
+        );
+        assert_eq!(
+            coins(DashboardQuery {
+                limit: Some("2".to_string()),
+                offset: Some("1".to_string()),
+                ..by("net_profit", Some("desc"))
+            }),
+            ["XRP", "BTC"]
+        );
+        assert_eq!(
+            coins(DashboardQuery {
+                coin: vec!["BTC".to_string(), "ETH".to_string()],
+                limit: Some("x".to_string()),
+                offset: Some("5".to_string()),
+                ..DashboardQuery::default()
+            }),
+            Vec::<String>::new()
+        );
     }
 
     #[test]
@@ -1688,6 +5950,426 @@ mod tests {
         assert_eq!(display.probability, "51.2%");
     }
 
+    #[test]
+    fn suggested_size_uses_probability_yes_book_and_fee_curve() {
+        let mut row = DashboardRow::unresolved_with_times("btc-updown-15m-1", "BTC", "15m", 0, 900);
+        let cfg = SizingConfig::default();
+        assert_eq!(suggested_size_for_row(&row, &cfg, 1_000.0), None);
+
+        // The spot price is not a YES price; without a book there is nothing to size.
+        row.probability = Some("70%".to_string());
+        row.price = Some("97234.56".to_string());
+        row.taker_fee_pct = Some("0.25".to_string());
+        row.fee_exponent = Some("2".to_string());
+        assert_eq!(suggested_size_for_row(&row, &cfg, 1_000.0), None);
+
+        row.best_bid_yes = Some("0.49".to_string());
+        row.best_ask_yes = Some("0.51".to_string());
+        let label = suggested_size_for_row(&row, &cfg, 1_000.0).unwrap();
+        assert_eq!(label, "50@YES");
+
+        // A mock probability is not sized; the model's one is.
+        let mut rows = [row.clone()];
+        rows[0].mock_columns = vec!["probability".to_string(), "suggested_size".to_string()];
+        apply_suggested_sizes(&cfg, rows.iter_mut());
+        assert_eq!(rows[0].suggested_size, None);
+        rows[0].mock_columns.retain(|entry| entry != "probability");
+        apply_suggested_sizes(&cfg, rows.iter_mut());
+        assert_eq!(
+            rows[0].suggested_size,
+            suggested_size_for_row(&row, &cfg, cfg.per_market_bankroll)
+        );
+        assert!(rows[0].suggested_size.is_some());
+        assert!(!rows[0].is_mock_column("suggested_size"));
+
+        row.probability = Some("0.505".to_string());
+        assert_eq!(
+            suggested_size_for_row(&row, &cfg, 1_000.0).as_deref(),
+            Some("0")
+        );
+    }
+
+    #[test]
+    fn number_format_applies_column_decimals_and_locale_separators() {
+        let mut row = sample_row("BTC", "1h", 100, 200, Some("open"));
+        row.ref_price = Some("97234.567".to_string());
+
+        let mut fmt = NumberFormatConfig::for_locale("de-DE");
+        fmt.column_decimals = parse_column_decimals("ref_price=2, price=2, bogus=3, pos_no=x");
+        let display = format_row_for_display_with_format(&row, 150, &fmt);
+
+        assert_eq!(fmt.column_decimals.len(), 2);
+        assert_eq!(display.ref_price, "97.234,57");
+        assert_eq!(display.price, "0,51");
+        assert_eq!(display.position_net, "12,35@0,4988@YES");
+        assert_eq!(display.maker_fee_pct, "-0,05");
+        assert_eq!(display.probability, "51,2%");
+    }
+
+    #[test]
+    fn number_format_default_matches_legacy_output() {
+        let row = sample_row("BTC", "1h", 100, 200, Some("open"));
+        assert_eq!(
+            format_row_for_display_with_format(&row, 150, &NumberFormatConfig::default()),
+            format_row_for_display(&row, 150)
+        );
+        assert_eq!(
+            NumberFormatConfig::for_locale("xx"),
+            NumberFormatConfig::default()
+        );
+        assert_eq!(
+            localize_number("-1234567.5", &NumberFormatConfig::for_locale("en")),
+            "-1,234,567.5"
+        );
+    }
+
+    #[test]
+    fn snapshot_status_tracks_unavailable_since_across_failures() {
+        let mut status = SnapshotStatus::awaiting_live(1_000);
+        assert_eq!(status.provenance, SnapshotProvenance::Demo);
+        assert!(!status.live_unavailable());
+
+        assert_eq!(status.record_live_failure(1_015, "timeout"), 1);
+        assert_eq!(status.record_live_failure(1_030, "timeout"), 2);
+        assert!(status.live_unavailable());
+        assert_eq!(status.live_unavailable_since_ts_utc, Some(1_000));
+        assert_eq!(status.age_seconds(1_030), Some(30));
+
+        status.record_live_success(1_045);
+        assert_eq!(status.provenance, SnapshotProvenance::Live);
+        assert!(!status.live_unavailable());
+        assert_eq!(status.last_live_ts_utc, Some(1_045));
+
+        status.record_live_failure(1_060, "transport");
+        assert_eq!(status.live_unavailable_since_ts_utc, Some(1_060));
+        assert_eq!(status.last_error.as_deref(), Some("transport"));
+    }
+
+    #[test]
+    fn health_score_falls_over_the_degrade_window_then_degrades() {
+        let mut status = SnapshotStatus::awaiting_live(1_000);
+        assert!(!status.score_health(1_000, 300));
+        assert_eq!(status.health_score, Some(100));
+
+        status.record_live_failure(1_060, "timeout");
+        assert!(!status.score_health(1_060, 300));
+        assert_eq!(status.health_score, Some(80));
+        assert!(!render_live_banner(&status).contains("class=\"live-banner degraded\""));
+        assert!(render_live_banner(&status).contains("(health 80/100)"));
+
+        status.record_live_failure(1_300, "timeout");
+        assert!(status.score_health(1_300, 300));
+        assert!(!status.score_health(1_330, 300));
+        assert_eq!(
+            (status.health_score, status.degraded_since_ts_utc),
+            (Some(0), Some(1_300))
+        );
+        let banner = render_live_banner(&status);
+        assert!(banner.contains("class=\"live-banner degraded\""));
+        assert!(banner.contains("<span id=\"live-banner-degraded\">Degraded"));
+
+        status.record_live_success(1_360);
+        assert!(!status.score_health(1_360, 300));
+        assert_eq!(
+            (status.health_score, status.degraded_since_ts_utc),
+            (Some(100), None)
+        );
+    }
+
+    #[test]
+    fn live_banner_is_hidden_until_a_live_cycle_fails() {
+        let banner_tag = |status: &SnapshotStatus| {
+            let banner = render_live_banner(status);
+            banner[..banner.find('>').unwrap() + 1].to_string()
+        };
+        let mut status = SnapshotStatus::awaiting_live(1_700_000_000);
+        assert!(banner_tag(&status).contains(" hidden>"));
+
+        status.record_live_failure(1_700_000_015, "timeout");
+        let banner = render_live_banner(&status);
+        assert!(!banner_tag(&status).contains(" hidden>"));
+        assert!(banner.contains("Live data unavailable since"));
+        assert!(banner.contains("data-since-ts=\"1700000000\""));
+    }
+
+    #[cfg(feature = "discovery-sdk")]
+    fn test_live_config() -> LiveDiscoveryConfig {
+        LiveDiscoveryConfig {
+            refresh_interval_ms: 1_000,
+            slug_config: SlugConfig::default(),
+            discovery_config: DiscoveryConfig::default(),
+            universe: DiscoveryUniverse::default(),
+            cycle_deadline_ms: 5_000,
+            failure_escalate_after: 3,
+            degrade_after_mins: 5,
+            max_retry_backoff_ms: 10_000,
+            stale_row_max_failures: 3,
+            sizing_config: SizingConfig::default(),
+            webhooks: WebhookConfig::default(),
+            gamma_endpoints: GammaEndpointConfig::default(),
+            realized_vol: RealizedVolConfig {
+                store_path: None,
+                ..RealizedVolConfig::default()
+            },
+            gamma_payloads: GammaPayloadConfig::default(),
+            fees: FeeRegistry::builtin(),
+            dry_run_quotes: DryRunQuoteConfig::default(),
+            toggles: MarketToggles::in_memory(),
+            clob_stream: ClobStreamConfig {
+                ws_url: None,
+                ..ClobStreamConfig::default()
+            },
+            binance_ws: BinanceWsConfig {
+                ws_url: None,
+                ..BinanceWsConfig::default()
+            },
+            positions: PositionConfig {
+                poll_ms: None,
+                ..PositionConfig::default()
+            },
+            models: ModelConfig { dir: None },
+            model_inputs: None,
+            smoothing: ProbabilitySmoothingConfig::default(),
+        }
+    }
+
+    #[cfg(feature = "discovery-sdk")]
+    fn cycle_row(slug: &str, lookup: RowLookup, price: Option<&str>) -> LiveCycleRow {
+        let mut row = DashboardRow::unresolved_with_times(slug, "BTC", "5m", 100, 400);
+        row.price = price.map(str::to_string);
+        LiveCycleRow {
+            key_slug: slug.to_string(),
+            row,
+            lookup,
+        }
+    }
+
+    #[cfg(feature = "discovery-sdk")]
+    #[test]
+    fn failed_lookups_keep_last_known_good_until_max_failures() {
+        let mut last_good = LastKnownGood::default();
+        let (rows, summary) = merge_with_last_known_good(
+            vec![
+                cycle_row("a", RowLookup::Resolved, Some("0.5")),
+                cycle_row("b", RowLookup::Resolved, Some("0.6")),
+            ],
+            &mut last_good,
+            1_000,
+            3,
+        );
+        assert_eq!(summary, MergeSummary::default());
+        assert!(rows.iter().all(|row| row.stale_as_of_ts_utc.is_none()));
+
+        for (now, expected_stale) in [(1_005, 1usize), (1_010, 1)] {
+            let (rows, summary) = merge_with_last_known_good(
+                vec![
+                    cycle_row("a", RowLookup::Failed, None),
+                    cycle_row("b", RowLookup::Resolved, Some("0.7")),
+                ],
+                &mut last_good,
+                now,
+                3,
+            );
+            assert_eq!(summary.stale_rows, expected_stale);
+            assert_eq!(rows[0].price.as_deref(), Some("0.5"));
+            assert_eq!(rows[0].stale_as_of_ts_utc, Some(1_000));
+            assert_eq!(rows[1].price.as_deref(), Some("0.7"));
+        }
+
+        let (rows, summary) = merge_with_last_known_good(
+            vec![cycle_row("a", RowLookup::Failed, None)],
+            &mut last_good,
+            1_015,
+            3,
+        );
+        assert_eq!(summary.downgraded_rows, 1);
+        assert!(rows[0].price.is_none());
+        assert!(rows[0].stale_as_of_ts_utc.is_none());
+        assert!(
+            last_good.entries.is_empty(),
+            "rolled-out slug b should be pruned"
+        );
+    }
+
+    #[cfg(feature = "discovery-sdk")]
+    #[test]
+    fn not_found_discards_last_known_good() {
+        let mut last_good = LastKnownGood::default();
+        merge_with_last_known_good(
+            vec![cycle_row("a", RowLookup::Resolved, Some("0.5"))],
+            &mut last_good,
+            1_000,
+            3,
+        );
+        let (rows, _) = merge_with_last_known_good(
+            vec![cycle_row("a", RowLookup::NotFound, None)],
+            &mut last_good,
+            1_005,
+            3,
+        );
+        assert!(rows[0].price.is_none());
+        let (rows, summary) = merge_with_last_known_good(
+            vec![cycle_row("a", RowLookup::Failed, None)],
+            &mut last_good,
+            1_010,
+            3,
+        );
+        assert_eq!(summary, MergeSummary::default());
+        assert!(rows[0].stale_as_of_ts_utc.is_none());
+    }
+
+    #[cfg(feature = "discovery-sdk")]
+    #[test]
+    fn skipped_lookups_show_last_known_good_without_counting_as_failures() {
+        let mut last_good = LastKnownGood::default();
+        merge_with_last_known_good(
+            vec![cycle_row("a", RowLookup::Resolved, Some("0.5"))],
+            &mut last_good,
+            1_000,
+            1,
+        );
+        for now in [1_005, 1_010] {
+            let (rows, summary) = merge_with_last_known_good(
+                vec![
+                    cycle_row("a", RowLookup::Skipped, None),
+                    cycle_row("b", RowLookup::Skipped, None),
+                ],
+                &mut last_good,
+                now,
+                1,
+            );
+            assert_eq!(summary.stale_rows, 1);
+            assert_eq!(rows[0].price.as_deref(), Some("0.5"));
+            assert_eq!(rows[0].stale_as_of_ts_utc, Some(1_000));
+            assert!(rows[1].price.is_none());
+        }
+    }
+
+    #[cfg(feature = "discovery-sdk")]
+    #[test]
+    fn live_retry_delay_backs_off_and_escalates() {
+        let cfg = test_live_config();
+        assert_eq!(live_retry_delay_ms(&cfg, 0), 1_000);
+        assert_eq!(live_retry_delay_ms(&cfg, 1), 2_000);
+        assert_eq!(live_retry_delay_ms(&cfg, 3), 8_000);
+        assert_eq!(live_retry_delay_ms(&cfg, 30), 10_000);
+
+        assert!(!live_failure_escalated(2, cfg.failure_escalate_after));
+        assert!(live_failure_escalated(3, cfg.failure_escalate_after));
+        assert!(live_failure_escalated(1, 0));
+    }
+
+    #[cfg(feature = "discovery-sdk")]
+    #[test]
+    fn failed_first_cycle_replaces_demo_rows_with_placeholders() {
+        let cfg = test_live_config();
+        let source = LiveDiscoverySnapshotSource {
+            inner: Arc::new(SnapshotSlot::new(demo_snapshot())),
+            status: Arc::new(ArcSwap::from_pointee(SnapshotStatus::awaiting_live(
+                1_735_689_600,
+            ))),
+            gamma_endpoints: GammaEndpointPool::new(cfg.gamma_endpoints.clone()),
+            #[cfg(feature = "trading")]
+            positions: None,
+        };
+
+        let mut last_good = LastKnownGood::default();
+
+        let failures = source.apply_cycle_outcome(
+            LiveCycleOutcome {
+                rows: None,
+                failure: Some("deadline".to_string()),
+                subscriptions: Vec::new(),
+            },
+            &mut last_good,
+            &cfg,
+            1_735_689_615,
+        );
+        assert_eq!(failures, 1);
+        let status = source.status();
+        assert_eq!(status.provenance, SnapshotProvenance::Placeholder);
+        assert!(status.live_unavailable());
+        let snapshot = source.snapshot();
+        let rows = &snapshot.rows;
+        assert_eq!(rows.len(), 60);
+        assert!(rows
+            .iter()
+            .all(|row| row.price.is_none()
+                && row.net_profit.as_deref() == Some("transport:deadline")));
+
+        let failures = source.apply_cycle_outcome(
+            LiveCycleOutcome {
+                rows: Some(Vec::new()),
+                failure: None,
+                subscriptions: Vec::new(),
+            },
+            &mut last_good,
+            &cfg,
+            1_735_689_630,
+        );
+        assert_eq!(failures, 0);
+        assert_eq!(source.status().provenance, SnapshotProvenance::Live);
+        assert!(source.snapshot().rows.is_empty());
+    }
+
+    #[cfg(feature = "discovery-sdk")]
+    #[test]
+    fn degraded_source_marks_last_known_good_rows_stale_until_recovery() {
+        let cfg = LiveDiscoveryConfig {
+            degrade_after_mins: 1,
+            ..test_live_config()
+        };
+        let mut live = SnapshotStatus::awaiting_live(1_000);
+        live.record_live_success(1_000);
+        let source = LiveDiscoverySnapshotSource {
+            inner: Arc::new(SnapshotSlot::new(DashboardSnapshot {
+                rows: vec![sample_row("BTC", "5m", 900, 1_200, Some("true"))],
+                seq: 0,
+            })),
+            status: Arc::new(ArcSwap::from_pointee(live)),
+            gamma_endpoints: GammaEndpointPool::new(cfg.gamma_endpoints.clone()),
+            #[cfg(feature = "trading")]
+            positions: None,
+        };
+        let mut last_good = LastKnownGood::default();
+        let failed = || LiveCycleOutcome {
+            rows: None,
+            failure: Some("transport".to_string()),
+            subscriptions: Vec::new(),
+        };
+
+        // Failing time counts from the first failed cycle.
+        source.apply_cycle_outcome(failed(), &mut last_good, &cfg, 1_030);
+        assert_eq!(source.status().health_score, Some(100));
+        source.apply_cycle_outcome(failed(), &mut last_good, &cfg, 1_060);
+        assert_eq!(source.status().health_score, Some(50));
+        assert_eq!(source.snapshot().rows[0].stale_as_of_ts_utc, None);
+
+        source.apply_cycle_outcome(failed(), &mut last_good, &cfg, 1_090);
+        let status = source.status();
+        assert_eq!(status.degraded_since_ts_utc, Some(1_090));
+        assert_eq!(status.provenance, SnapshotProvenance::Live);
+        let snapshot = source.snapshot();
+        assert_eq!(snapshot.seq, 1);
+        assert_eq!(snapshot.rows[0].stale_as_of_ts_utc, Some(1_000));
+
+        source.apply_cycle_outcome(
+            LiveCycleOutcome {
+                rows: Some(Vec::new()),
+                failure: None,
+                subscriptions: Vec::new(),
+            },
+            &mut last_good,
+            &cfg,
+            1_120,
+        );
+        let status = source.status();
+        assert_eq!(
+            (status.health_score, status.degraded_since_ts_utc),
+            (Some(100), None)
+        );
+    }
+
     #[test]
     fn unresolved_row_remains_visible_with_placeholders_and_mock_columns() {
         let row = DashboardRow::unresolved_with_times("xrp-updown-15m-2", "XRP", "15m", 100, 200);
@@ -1718,6 +6400,70 @@ mod tests {
         assert_eq!(snapshot.rows.len(), 60);
     }
 
+    #[test]
+    fn simulated_demo_source_rolls_intervals_over_in_compressed_time() {
+        // One second before a 5m boundary, on a clock only the test moves.
+        let boundary = 1_735_689_900;
+        let clock = SimulatedClock::manual(boundary - 1);
+        let source = SimulatedDemoSnapshotSource::new(clock.clone());
+        let active_5m_start = |snapshot: &DashboardSnapshot, now: i64| {
+            snapshot
+                .rows
+                .iter()
+                .find(|row| {
+                    row.coin == "BTC"
+                        && row.duration == "5m"
+                        && compute_in_interval(now, row.start_ts_utc, row.end_ts_utc)
+                })
+                .map(|row| row.start_ts_utc)
+        };
+        let before = source.snapshot();
+        assert_eq!(active_5m_start(&before, boundary - 1), Some(boundary - 300));
+
+        assert_eq!(source.snapshot().seq, before.seq);
+
+        clock.advance(50);
+        let now = source.now_ts_utc();
+        assert_eq!(now, boundary + 49);
+        let after = source.snapshot();
+        assert!(after.seq > before.seq);
+        assert_eq!(active_5m_start(&after, now), Some(boundary));
+        assert_eq!(source.status().provenance, SnapshotProvenance::Demo);
+    }
+
+    #[test]
+    fn duration_filters_pick_cadence_and_fee_columns() {
+        let filters = |durations: &[&str]| {
+            DashboardFilters::from_query(&DashboardQuery {
+                duration: durations.iter().map(|d| d.to_string()).collect(),
+                ..DashboardQuery::default()
+            })
+        };
+
+        assert_eq!(DashboardFilters::all_selected().refresh_ms(), 250);
+        assert!(DashboardFilters::all_selected().hidden_columns().is_empty());
+        assert_eq!(filters(&["15m"]).refresh_ms(), 500);
+        assert!(filters(&["15m"]).hidden_columns().is_empty());
+        assert_eq!(filters(&["4h", "1d"]).refresh_ms(), 2_000);
+        assert_eq!(filters(&["1h"]).hidden_columns(), FEE_COLUMN_KEYS.to_vec());
+
+        let visible = DashboardFilters::from_query(&DashboardQuery {
+            duration: vec!["1h".to_string()],
+            columns: vec!["Price,bogus".to_string(), "taker_fee_pct".to_string()],
+            ..DashboardQuery::default()
+        });
+        assert_eq!(
+            visible.visible_columns,
+            Some(vec!["price", "taker_fee_pct"])
+        );
+        let hidden = visible.hidden_columns();
+        assert!(!hidden.contains(&"link"));
+        assert!(!hidden.contains(&"price"));
+        assert!(hidden.contains(&"coin"));
+        assert!(hidden.contains(&"taker_fee_pct"));
+        assert_eq!(hidden.len(), DASHBOARD_COLUMN_KEYS.len() - 2);
+    }
+
     #[test]
     fn rendered_html_has_button_mock_and_polling_script() {
         let snapshot = DashboardSnapshot {
@@ -1728,12 +6474,384 @@ mod tests {
                 100,
                 200,
             )],
+            seq: 0,
         };
 
         let html = render_dashboard_html(&snapshot);
         assert!(html.contains("market-btn"));
         assert!(html.contains("Open Market"));
+        assert!(html.contains("class=\"note-btn\" data-slug="));
         assert!(html.contains("cell-mock"));
         assert!(html.contains("setInterval(refresh, 250)"));
+        // Rows are patched by slug within a per-frame budget, never rebuilt wholesale.
+        assert!(html.contains(&format!("const ROW_PATCH_BUDGET = {ROW_PATCH_BUDGET};")));
+        assert!(html.contains("requestAnimationFrame(patchRows)"));
+        assert!(!html.contains("tbody.innerHTML"));
+    }
+
+    #[test]
+    fn tracked_positions_replace_mock_position_columns() {
+        use crate::positions::{MarketPosition, OutcomePosition};
+
+        struct Fixed(MarketPosition);
+        impl PositionSource for Fixed {
+            fn position(&self, slug: &str) -> Option<MarketPosition> {
+                (slug == "btc-updown-5m-1").then_some(self.0)
+            }
+        }
+        let source = Fixed(MarketPosition {
+            yes: OutcomePosition {
+                shares: 10.0,
+                avg_price: 0.4,
+                realized_pnl_usdc: 0.5,
+            },
+            no: OutcomePosition {
+                shares: 4.0,
+                avg_price: 0.5,
+                realized_pnl_usdc: 0.0,
+            },
+            fees_usdc: 0.1,
+        });
+
+        let mut booked =
+            DashboardRow::unresolved_with_times("btc-updown-5m-1", "BTC", "5m", 0, 300);
+        let mut unbooked = booked.clone();
+        unbooked.slug = "btc-updown-5m-2".to_string();
+        apply_positions(&source, [&mut booked, &mut unbooked]);
+        assert_eq!(booked.pos_yes.as_deref(), Some("10@0.4"));
+        assert_eq!(booked.pos_no.as_deref(), Some("4@0.5"));
+        assert_eq!(booked.position_net.as_deref(), Some("6@0.4@YES"));
+        // Mock book prices are no mark: realized only.
+        assert_eq!(booked.net_profit.as_deref(), Some("0.4"));
+        assert!(!booked.is_mock_column("pos_yes") && !booked.is_mock_column("net_profit"));
+        assert!(unbooked.is_mock_column("pos_yes") && unbooked.pos_yes.is_none());
+
+        // With a live book, open shares are marked at the mid: 10 * 0.2 + 4 * (0.4 - 0.5).
+        booked.best_bid_yes = Some("0.58".to_string());
+        booked.best_ask_yes = Some("0.62".to_string());
+        booked
+            .mock_columns
+            .retain(|column| column != "best_bid_yes" && column != "best_ask_yes");
+        apply_positions(&source, [&mut booked]);
+        assert_eq!(booked.net_profit.as_deref(), Some("2"));
+    }
+
+    #[test]
+    fn resting_orders_show_in_offers_with_their_queue_position() {
+        use crate::fill_model::TradePrint;
+        use crate::queue_position::{QueuePositionTracker, RestingOrder};
+
+        let slug = "btc-updown-5m-1";
+        let order = |order_id: &str, outcome, price| RestingOrder {
+            order_id: order_id.to_string(),
+            slug: slug.to_string(),
+            outcome,
+            side: OrderSide::Buy,
+            price,
+            size: 10.0,
+            placed_ts_ms: 0,
+        };
+        let mut tracker = QueuePositionTracker::new();
+        tracker.place(order("a", SizingSide::Yes, 0.5), 40.0);
+        tracker.place(order("b", SizingSide::Yes, 0.52), 25.5);
+        tracker.place(order("c", SizingSide::No, 0.45), 0.0);
+        tracker.on_trade(
+            slug,
+            SizingSide::No,
+            &TradePrint {
+                ts_ms: 1,
+                price: 0.45,
+                size: 4.0,
+                taker_side: OrderSide::Sell,
+            },
+        );
+
+        let mut row = DashboardRow::unresolved_with_times(slug, "BTC", "5m", 100, 400);
+        row.simulated_columns.push("offer_no".to_string());
+        row.mock_columns.retain(|entry| entry != "offer_no");
+        let mut rows = [row];
+        apply_queue_estimates(rows.iter_mut(), &tracker.estimates());
+        let [row] = rows;
+        assert_eq!(row.offer_yes.as_deref(), Some("10@0.52"));
+        assert_eq!(row.queue_ahead_yes.as_deref(), Some("25.5"));
+        assert_eq!(row.offer_no.as_deref(), Some("6@0.45"));
+        assert_eq!(row.queue_ahead_no.as_deref(), Some("0"));
+        assert!(!row.is_mock_column("offer_yes") && !row.is_simulated_column("offer_no"));
+
+        let html = render_dashboard_html(&DashboardSnapshot {
+            rows: vec![row],
+            seq: 0,
+        });
+        assert!(html.contains(
+            "10@0.52 <span class=\"queue-ahead\" title=\"Estimated shares queued ahead\">q 25.5</span>"
+        ));
+    }
+
+    struct FixedClockSource {
+        inner: InMemoryMockSnapshotSource,
+        now_ts_utc: std::sync::atomic::AtomicI64,
+    }
+
+    impl DashboardSnapshotSource for FixedClockSource {
+        fn snapshot(&self) -> Arc<DashboardSnapshot> {
+            self.inner.snapshot()
+        }
+
+        fn now_ts_utc(&self) -> i64 {
+            self.now_ts_utc.load(std::sync::atomic::Ordering::Relaxed)
+        }
+    }
+
+    #[test]
+    fn precomputed_display_is_shared_until_publish_or_tick() {
+        let rows = vec![
+            sample_row("BTC", "5m", 100, 400, Some("true")),
+            sample_row("ETH", "15m", 500, 1_400, Some("false")),
+        ];
+        let source = Arc::new(FixedClockSource {
+            inner: InMemoryMockSnapshotSource::new(DashboardSnapshot { rows, seq: 0 }),
+            now_ts_utc: std::sync::atomic::AtomicI64::new(200),
+        });
+        let state = DashboardAppState::new(
+            source.clone(),
+            DashboardConfig::default(),
+            DashboardViews::in_memory(),
+        );
+
+        let first = state.precomputed_display();
+        assert!(Arc::ptr_eq(&first, &state.precomputed_display()));
+        let query = DashboardQuery {
+            coin: vec!["ETH".to_string()],
+            ..DashboardQuery::default()
+        };
+        let sorted = DashboardQuery {
+            sort_by: Some("end".to_string()),
+            order: Some("desc".to_string()),
+            limit: Some("1".to_string()),
+            ..DashboardQuery::default()
+        };
+        for filters in [
+            DashboardFilters::all_selected(),
+            DashboardFilters::from_query(&query),
+            DashboardFilters::from_query(&sorted),
+        ] {
+            assert_eq!(
+                first.filtered(&filters),
+                build_display_snapshot(&source.snapshot(), &filters, 200)
+            );
+        }
+
+        // A clock tick re-evaluates time-derived columns; a publication the rows.
+        source
+            .now_ts_utc
+            .store(600, std::sync::atomic::Ordering::Relaxed);
+        let ticked = state.precomputed_display();
+        assert!(!Arc::ptr_eq(&first, &ticked));
+        assert_eq!(ticked.display.rows[1].in_interval, "yes");
+        source.inner.replace_snapshot(DashboardSnapshot {
+            rows: Vec::new(),
+            seq: 0,
+        });
+        let published = state.precomputed_display();
+        assert_eq!(published.display.seq, 1);
+        assert!(published.display.rows.is_empty());
+    }
+
+    #[test]
+    fn late_ref_captures_are_listed_and_tagged() {
+        let mut on_time =
+            DashboardRow::unresolved_with_times("btc-updown-5m-1", "BTC", "5m", 0, 300);
+        on_time.ref_price = Some("100".to_string());
+        on_time.ref_capture_delay_ms = Some(0);
+        let mut late = on_time.clone();
+        late.slug = "eth-updown-5m-1".to_string();
+        late.ref_capture_delay_ms = Some(3_000);
+        let rows = vec![on_time, late];
+
+        assert_eq!(
+            late_ref_captures(&rows, 0),
+            vec![("eth-updown-5m-1", 3_000)]
+        );
+        assert!(late_ref_captures(&rows, 3_000).is_empty());
+
+        let html = render_dashboard_html(&DashboardSnapshot { rows, seq: 0 });
+        assert!(!html.contains("captured 0ms"));
+        assert!(html.contains(
+            "100 <span class=\"ref-late\" title=\"Reference captured 3000ms after the interval start\">+3s</span>"
+        ));
+    }
+
+    #[test]
+    fn dry_run_quotes_fill_mocked_offers_as_simulated() {
+        let mut quoting =
+            DashboardRow::unresolved_with_times("btc-updown-5m-1", "BTC", "5m", 100, 400);
+        quoting.probability = Some("57%".to_string());
+        quoting.best_bid_yes = Some("0.50".to_string());
+        quoting.best_ask_yes = Some("0.60".to_string());
+        let mut no_model = quoting.clone();
+        no_model.probability = None;
+        let mut not_started = quoting.clone();
+        not_started.start_ts_utc = 300;
+
+        let cfg = DryRunQuoteConfig::default();
+        let toggles = MarketToggles::in_memory();
+        let mut rows = [quoting, no_model, not_started];
+        apply_dry_run_quotes(&cfg, &toggles, rows.iter_mut(), 200);
+        let [quoting, no_model, not_started] = rows;
+
+        // In-interval limits: $50 orders, one cent of edge.
+        assert_eq!(quoting.offer_yes.as_deref(), Some("89.28@0.56"));
+        assert_eq!(quoting.offer_no.as_deref(), Some("119.04@0.42"));
+        assert!(quoting.is_simulated_column("offer_yes") && !quoting.is_mock_column("offer_yes"));
+        for row in [&no_model, &not_started] {
+            assert_eq!(row.offer_yes, None);
+            assert!(row.simulated_columns.is_empty() && row.is_mock_column("offer_no"));
+        }
+
+        let html = render_dashboard_html(&DashboardSnapshot {
+            rows: vec![quoting.clone()],
+            seq: 0,
+        });
+        assert!(html.contains("<td class=\"cell-sim\">89.28@0.56</td>"));
+
+        let mut disabled = [no_model.clone()];
+        disabled[0].probability = Some("0.57".to_string());
+        let cfg = DryRunQuoteConfig {
+            enabled: false,
+            ..DryRunQuoteConfig::default()
+        };
+        apply_dry_run_quotes(&cfg, &toggles, disabled.iter_mut(), 200);
+        assert_eq!(disabled[0].offer_yes, None);
+
+        // A switched-off pair keeps its offers mocked.
+        toggles.set(Coin::BTC, Duration::M5, false, 150).unwrap();
+        let mut toggled = [no_model.clone()];
+        toggled[0].probability = Some("0.57".to_string());
+        apply_dry_run_quotes(
+            &DryRunQuoteConfig::default(),
+            &toggles,
+            toggled.iter_mut(),
+            200,
+        );
+        assert_eq!(toggled[0].offer_yes, None);
+        assert!(toggled[0].is_mock_column("offer_yes"));
+    }
+
+    #[test]
+    fn replace_snapshot_stamps_strictly_increasing_seq() {
+        let source = InMemoryMockSnapshotSource::new(DashboardSnapshot::default());
+        assert_eq!(source.snapshot().seq, 0);
+
+        assert_eq!(source.replace_snapshot(DashboardSnapshot::default()), 1);
+        // Caller-provided seq is ignored; the source owns the sequence.
+        let seq = source.replace_snapshot(DashboardSnapshot {
+            rows: Vec::new(),
+            seq: 99,
+        });
+        assert_eq!(seq, 2);
+        assert_eq!(source.snapshot().seq, 2);
+    }
+
+    #[test]
+    fn snapshot_delta_applies_on_matching_seq_and_rejects_gaps() {
+        let row =
+            |slug: &str, end: i64| DashboardRow::unresolved_with_times(slug, "BTC", "5m", 100, end);
+        let filters = DashboardFilters::all_selected();
+        let before = build_display_snapshot(
+            &DashboardSnapshot {
+                rows: vec![row("a", 200), row("b", 200), row("c", 200)],
+                seq: 4,
+            },
+            &filters,
+            150,
+        );
+        let after = build_display_snapshot(
+            &DashboardSnapshot {
+                rows: vec![row("d", 200), row("a", 200), row("c", 300)],
+                seq: 7,
+            },
+            &filters,
+            150,
+        );
+
+        let delta = diff_display_snapshots(&before, &after).expect("rows changed");
+        assert_eq!((delta.prev_seq, delta.seq), (4, 7));
+        assert_eq!(delta.removed, vec!["b".to_string()]);
+        let upserted: Vec<&str> = delta.upserts.iter().map(|r| r.slug.as_str()).collect();
+        assert_eq!(upserted, vec!["d", "c"]);
+
+        let mut held = before.clone();
+        apply_snapshot_delta(&mut held, &delta).unwrap();
+        assert_eq!(held, after);
+
+        let gap = apply_snapshot_delta(&mut held, &delta).unwrap_err();
+        assert_eq!(
+            gap,
+            SnapshotSeqGap {
+                held_seq: 7,
+                prev_seq: 4
+            }
+        );
+        assert_eq!(held, after);
+        assert!(diff_display_snapshots(&after, &after).is_none());
+    }
+
+    #[test]
+    fn activity_lists_rollovers_endings_resolution_flips_moves_and_fills() {
+        let cfg = ActivityFeedConfig::default();
+        let ending = sample_row("BTC", "5m", 0, 300, Some("open"));
+        let mut next_window = sample_row("BTC", "5m", 300, 600, None);
+        next_window.pos_yes = None;
+        let mut eth = sample_row("ETH", "15m", 0, 900, Some("open"));
+        let prev = DashboardSnapshot {
+            rows: vec![ending.clone(), next_window.clone(), eth.clone()],
+            seq: 1,
+        };
+
+        next_window.bets_open = Some("open".to_string());
+        eth.probability = Some("58%".to_string());
+        eth.pos_yes = Some("5@0.55".to_string());
+        // Mock probabilities and dry-run positions are not activity.
+        let mut quiet = ending.clone();
+        quiet.mock_columns.push("probability".to_string());
+        quiet.probability = Some("0.9".to_string());
+        quiet.simulated_columns.push("pos_no".to_string());
+        quiet.pos_no = Some("3@0.4".to_string());
+        let next = DashboardSnapshot {
+            rows: vec![quiet, next_window, eth],
+            seq: 2,
+        };
+
+        let events = activity_between(&prev, 299, &next, 300, &cfg);
+        let kinds: Vec<_> = events
+            .iter()
+            .map(|event| (event.kind, event.slug.as_str(), event.detail.as_str()))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                (ActivityKind::IntervalRolled, "btc-5m-300", "from btc-5m-0"),
+                (ActivityKind::MarketEnded, "btc-5m-0", "ended 00:05 UTC"),
+                (ActivityKind::RowResolved, "btc-5m-300", "bets open"),
+                (ActivityKind::ProbabilityMove, "eth-15m-0", "51.2% -> 58.0%"),
+                (ActivityKind::Fill, "eth-15m-0", "YES 1.23456 -> 5"),
+            ]
+        );
+        assert!(events.iter().all(|event| event.ts_utc == 300));
+        assert!(activity_between(&next, 300, &next, 301, &cfg).is_empty());
+
+        let feed = ActivityFeed::new(ActivityFeedConfig { capacity: 3, ..cfg });
+        feed.observe(Arc::new(prev), 299);
+        assert!(feed.events().is_empty());
+        feed.observe(Arc::new(next), 300);
+        let kept: Vec<_> = feed.events().iter().map(|event| event.kind).collect();
+        assert_eq!(
+            kept,
+            [
+                ActivityKind::IntervalRolled,
+                ActivityKind::MarketEnded,
+                ActivityKind::RowResolved
+            ]
+        );
     }
 }
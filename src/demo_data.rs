@@ -0,0 +1,150 @@
+//! Bundled demo dataset (`demo-data` feature).
+//!
+//! Ten minutes of 1s klines for every Binance symbol plus the dashboard rows recorded
+//! over the same span, compiled into the binary from `demo/`. `pmm serve --demo` plays
+//! the rows through [`play_replay`](crate::play_replay), and [`write_demo_kline_store`]
+//! seeds a kline store for the feature transform, so both run on a fresh machine with no
+//! network.
+
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::binance_klines::{
+    parse_kline_csv, BinanceSymbol, Kline1s, KlineLoadError, KlineLoadRequest,
+};
+use crate::kline_store::{KlineStore, KlineStoreError};
+use crate::kline_validation::KlineValidationConfig;
+use crate::replay::{ReplayConfig, ReplayEvent, ReplayEventKind, ReplayTimeline};
+use crate::snapshot_store::SnapshotHistoryPoint;
+
+/// First second of the demo recording (2024-01-01T00:00:00Z).
+pub const DEMO_START_TS_UTC: i64 = 1_704_067_200;
+/// End of the demo recording, exclusive.
+pub const DEMO_END_TS_UTC: i64 = DEMO_START_TS_UTC + 600;
+
+const DEMO_KLINES: [(BinanceSymbol, &str); 4] = [
+    (
+        BinanceSymbol::BtcUsdt,
+        include_str!("../demo/klines/BTCUSDT_1s.csv"),
+    ),
+    (
+        BinanceSymbol::EthUsdt,
+        include_str!("../demo/klines/ETHUSDT_1s.csv"),
+    ),
+    (
+        BinanceSymbol::SolUsdt,
+        include_str!("../demo/klines/SOLUSDT_1s.csv"),
+    ),
+    (
+        BinanceSymbol::XrpUsdt,
+        include_str!("../demo/klines/XRPUSDT_1s.csv"),
+    ),
+];
+
+/// Recorded rows as [`SnapshotHistoryPoint`] JSON lines: every row once at the start,
+/// then only the rows that changed.
+const DEMO_SNAPSHOTS: &str = include_str!("../demo/snapshots.jsonl");
+
+#[derive(Debug, Error)]
+pub enum DemoDataError {
+    #[error("demo snapshot line {line}: {source}")]
+    Snapshot {
+        line: usize,
+        source: serde_json::Error,
+    },
+    #[error("demo klines: {0}")]
+    Klines(#[from] KlineLoadError),
+    #[error("kline store: {0}")]
+    Store(#[from] KlineStoreError),
+}
+
+/// The bundled 1s klines of `symbol`, oldest first.
+pub fn demo_klines(symbol: BinanceSymbol) -> Result<Vec<Kline1s>, DemoDataError> {
+    let csv = DEMO_KLINES
+        .iter()
+        .find(|(candidate, _)| *candidate == symbol)
+        .map_or("", |(_, csv)| *csv);
+    let req = KlineLoadRequest {
+        symbol,
+        start_ts_ms_utc: DEMO_START_TS_UTC * 1_000,
+        end_ts_ms_utc_exclusive: DEMO_END_TS_UTC * 1_000,
+    };
+    Ok(parse_kline_csv(csv.as_bytes(), &req)?)
+}
+
+/// The bundled dashboard rows, oldest first.
+pub fn demo_snapshot_history() -> Result<Vec<SnapshotHistoryPoint>, DemoDataError> {
+    DEMO_SNAPSHOTS
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line).map_err(|source| DemoDataError::Snapshot {
+                line: index + 1,
+                source,
+            })
+        })
+        .collect()
+}
+
+/// The bundled rows as a replay timeline for [`demo_replay_config`].
+pub fn demo_replay_timeline() -> Result<ReplayTimeline, DemoDataError> {
+    let events = demo_snapshot_history()?
+        .into_iter()
+        .map(|point| ReplayEvent {
+            ts_ms: point.ts_ms,
+            kind: ReplayEventKind::Row(Box::new(point.row)),
+        })
+        .collect();
+    Ok(ReplayTimeline {
+        events,
+        rows_truncated: false,
+    })
+}
+
+/// Replays the whole recording at `speed`, reading no stores.
+pub fn demo_replay_config(speed: u32) -> ReplayConfig {
+    ReplayConfig {
+        from_ts_utc: DEMO_START_TS_UTC,
+        to_ts_utc: DEMO_END_TS_UTC,
+        speed: speed.max(1),
+        tick_ms: 250,
+        snapshot_store: None,
+        decision_store: None,
+        paper_store: None,
+    }
+}
+
+/// Writes the bundled klines of every symbol into the kline store at `path` (created
+/// if needed). Returns how many rows were written.
+pub fn write_demo_kline_store(path: &Path) -> Result<usize, DemoDataError> {
+    let mut store = KlineStore::open(path, KlineValidationConfig::default())?;
+    let mut written = 0;
+    for (symbol, _) in DEMO_KLINES {
+        let rows = demo_klines(symbol)?;
+        written += rows.len();
+        store.upsert_rows(symbol, rows)?;
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_data_covers_the_recording() {
+        for (symbol, _) in DEMO_KLINES {
+            let rows = demo_klines(symbol).unwrap();
+            assert_eq!(rows.len() as i64, DEMO_END_TS_UTC - DEMO_START_TS_UTC);
+        }
+        let timeline = demo_replay_timeline().unwrap();
+        assert!(timeline.events.windows(2).all(|w| w[0].ts_ms <= w[1].ts_ms));
+        assert_eq!(timeline.events[0].ts_ms, DEMO_START_TS_UTC * 1_000);
+        assert!(timeline
+            .events
+            .iter()
+            .all(|event| event.ts_ms < DEMO_END_TS_UTC * 1_000));
+    }
+}
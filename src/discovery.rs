@@ -4,17 +4,35 @@ use std::collections::{HashMap, HashSet};
 
 use chrono::{Datelike, Days, TimeZone, Timelike, Utc};
 use chrono_tz::America::New_York;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use tracing::{debug, error, warn};
 
-use crate::{build_slug, Coin, Duration, SlugConfig, SlugError};
+#[cfg(feature = "discovery-sdk")]
+use std::sync::Arc;
+#[cfg(feature = "discovery-sdk")]
+use std::time::Instant;
+
+use crate::env_vars::Settings;
+#[cfg(feature = "discovery-sdk")]
+use crate::gamma_endpoints::{GammaEndpointConfig, GammaEndpointPool};
+#[cfg(feature = "discovery-sdk")]
+use crate::gamma_schema::GammaPayloadSampler;
+use crate::{
+    build_slug, build_underlying_slug, parse_coin, Coin, Duration, MarketUnderlying, SlugConfig,
+    SlugError, PAIR_DURATIONS,
+};
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct DiscoveryKey {
+    /// The market's coin, or the base coin of a pair market.
     pub coin: Coin,
     pub duration: Duration,
     pub start_ts_utc: i64,
     pub slug: String,
+    /// Quote coin of a pair market; `None` for single-coin markets.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quote: Option<Coin>,
 }
 
 impl DiscoveryKey {
@@ -29,9 +47,33 @@ impl DiscoveryKey {
             duration,
             start_ts_utc,
             slug: build_slug(coin, duration, start_ts_utc, slug_cfg)?,
+            quote: None,
+        })
+    }
+
+    /// Key of a single-coin or pair market; see [`build_underlying_slug`].
+    pub fn for_underlying(
+        underlying: MarketUnderlying,
+        duration: Duration,
+        start_ts_utc: i64,
+        slug_cfg: SlugConfig,
+    ) -> Result<Self, SlugError> {
+        Ok(Self {
+            coin: underlying.base(),
+            duration,
+            start_ts_utc,
+            slug: build_underlying_slug(underlying, duration, start_ts_utc, slug_cfg)?,
+            quote: underlying.quote(),
         })
     }
 
+    pub fn underlying(&self) -> MarketUnderlying {
+        match self.quote {
+            Some(quote) => MarketUnderlying::Pair(self.coin, quote),
+            None => MarketUnderlying::Single(self.coin),
+        }
+    }
+
     pub fn from_slug(
         coin: Coin,
         duration: Duration,
@@ -43,6 +85,7 @@ impl DiscoveryKey {
             duration,
             start_ts_utc,
             slug: slug.into(),
+            quote: None,
         }
     }
 }
@@ -63,6 +106,142 @@ pub enum DiscoveryStatus<M> {
 pub enum UnresolvedReason {
     NotFound,
     TransportError(String),
+    /// Not looked up: too little time was left before the key's deadline (see
+    /// [`discovery_deadline_ts_ms`]).
+    DeadlineSkipped,
+}
+
+/// Gamma market metadata used by discovery, the dashboard, and strategies. Resolved
+/// rows carry this rather than the SDK's market type, so callers build without the
+/// `discovery-sdk` feature and SDK upgrades stay inside the conversion.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MarketMeta {
+    pub slug: Option<String>,
+    /// `0x`-prefixed hex.
+    pub condition_id: Option<String>,
+    /// CLOB token ids (decimal strings), in outcome order: YES/Up first.
+    pub token_ids: Vec<String>,
+    pub accepting_orders: Option<bool>,
+    pub accepting_orders_ts_utc: Option<i64>,
+    pub active: Option<bool>,
+    pub closed: Option<bool>,
+    /// Scheduled close of the market.
+    pub end_ts_utc: Option<i64>,
+    /// Gamma format type, falling back to the market type.
+    pub fee_type: Option<String>,
+    pub fees_enabled: Option<bool>,
+    /// Daily rate of the first CLOB rewards program.
+    pub reward_daily_rate: Option<f64>,
+    /// Total amount of the first CLOB rewards program.
+    pub reward_amount: Option<f64>,
+    pub uma_reward: Option<f64>,
+    /// Traded volume in USDC.
+    pub volume: Option<f64>,
+    /// Traded volume over the last 24 hours in USDC.
+    pub volume_24h: Option<f64>,
+    /// Order book liquidity in USDC.
+    pub liquidity: Option<f64>,
+    /// Whether the market is one outcome of a neg-risk event: its outcomes share
+    /// collateral, and a NO in one converts into YES on every other outcome. Orders go
+    /// through the neg-risk exchange.
+    #[serde(default)]
+    pub neg_risk: Option<bool>,
+    /// `0x`-prefixed id shared by every market of the neg-risk event.
+    #[serde(default)]
+    pub neg_risk_market_id: Option<String>,
+    /// Slug of the Gamma event grouping the market.
+    #[serde(default)]
+    pub event_slug: Option<String>,
+}
+
+impl MarketMeta {
+    /// Whether the market takes orders: `accepting_orders`, else not `closed`, else
+    /// `active`.
+    pub fn bets_open(&self) -> Option<bool> {
+        self.accepting_orders
+            .or(self.closed.map(|closed| !closed))
+            .or(self.active)
+    }
+
+    /// Key of the market's neg-risk group: the neg-risk market id, else the event slug,
+    /// else the market's own slug. `None` for markets outside a neg-risk event.
+    pub fn neg_risk_group(&self) -> Option<&str> {
+        if self.neg_risk != Some(true) {
+            return None;
+        }
+        self.neg_risk_market_id
+            .as_deref()
+            .or(self.event_slug.as_deref())
+            .or(self.slug.as_deref())
+    }
+
+    /// The reward rate shown on the dashboard: the CLOB rewards program first, then
+    /// the UMA reward.
+    pub fn reward_rate(&self) -> Option<f64> {
+        self.reward_daily_rate
+            .or(self.reward_amount)
+            .or(self.uma_reward)
+    }
+}
+
+#[cfg(feature = "discovery-sdk")]
+impl From<&SdkMarket> for MarketMeta {
+    fn from(market: &SdkMarket) -> Self {
+        fn decimal(value: Option<&impl ToString>) -> Option<f64> {
+            value.and_then(|value| value.to_string().parse().ok())
+        }
+        fn non_empty(value: Option<&String>) -> Option<String> {
+            value
+                .map(|value| value.trim())
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        }
+
+        let rewards = market
+            .clob_rewards
+            .as_ref()
+            .and_then(|rewards| rewards.first());
+        let event = market.events.as_ref().and_then(|events| events.first());
+        Self {
+            slug: market.slug.clone(),
+            condition_id: market.condition_id.map(|id| id.to_string()),
+            token_ids: market
+                .clob_token_ids
+                .iter()
+                .flatten()
+                .map(|id| id.to_string())
+                .collect(),
+            accepting_orders: market.accepting_orders,
+            accepting_orders_ts_utc: market
+                .accepting_orders_timestamp
+                .map(|accepting| accepting.timestamp()),
+            active: market.active,
+            closed: market.closed,
+            end_ts_utc: market.end_date.map(|end| end.timestamp()),
+            fee_type: non_empty(market.format_type.as_ref())
+                .or_else(|| non_empty(market.market_type.as_ref())),
+            fees_enabled: market.fees_enabled,
+            reward_daily_rate: decimal(rewards.and_then(|r| r.rewards_daily_rate.as_ref())),
+            reward_amount: decimal(rewards.and_then(|r| r.rewards_amount.as_ref())),
+            uma_reward: decimal(market.uma_reward.as_ref()),
+            volume: decimal(market.volume_num.as_ref().or(market.volume.as_ref())),
+            volume_24h: decimal(
+                market
+                    .volume_24hr
+                    .as_ref()
+                    .or(market.volume_24hr_clob.as_ref()),
+            ),
+            liquidity: decimal(market.liquidity_num.as_ref().or(market.liquidity.as_ref())),
+            neg_risk: market
+                .neg_risk
+                .or(event.and_then(|event| event.neg_risk.or(event.enable_neg_risk))),
+            neg_risk_market_id: market
+                .neg_risk_market_id
+                .or(event.and_then(|event| event.neg_risk_market_id))
+                .map(|id| id.to_string()),
+            event_slug: non_empty(event.and_then(|event| event.slug.as_ref())),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -72,16 +251,32 @@ pub struct DiscoveryConfig {
     pub max_retries: u32,
     pub retry_backoff_ms: u64,
     pub include_tag: bool,
+    /// Cap each lookup at the key's [`discovery_deadline_ts_ms`] and skip lookups that
+    /// cannot finish before it.
+    pub deadline_aware: bool,
+    /// Smallest time left before a deadline still worth an attempt.
+    pub min_call_budget_ms: u64,
 }
 
 impl Default for DiscoveryConfig {
     fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl DiscoveryConfig {
+    /// Reads `PMM_DISCOVERY_TIMEOUT_MS`, `PMM_DISCOVERY_BATCH_SIZE`,
+    /// `PMM_DISCOVERY_MAX_RETRIES`, `PMM_DISCOVERY_RETRY_BACKOFF_MS`,
+    /// `PMM_DISCOVERY_DEADLINES`, and `PMM_DISCOVERY_MIN_CALL_BUDGET_MS`.
+    pub fn from_settings(settings: &Settings) -> Self {
         Self {
-            timeout_ms: 3_000,
-            batch_size: 64,
-            max_retries: 2,
-            retry_backoff_ms: 200,
+            timeout_ms: settings.get("PMM_DISCOVERY_TIMEOUT_MS"),
+            batch_size: settings.get("PMM_DISCOVERY_BATCH_SIZE"),
+            max_retries: settings.get("PMM_DISCOVERY_MAX_RETRIES"),
+            retry_backoff_ms: settings.get("PMM_DISCOVERY_RETRY_BACKOFF_MS"),
             include_tag: false,
+            deadline_aware: settings.get("PMM_DISCOVERY_DEADLINES"),
+            min_call_budget_ms: settings.get("PMM_DISCOVERY_MIN_CALL_BUDGET_MS"),
         }
     }
 }
@@ -91,6 +286,7 @@ pub enum SlugFetchOutcome<M> {
     Found(M),
     Missing,
     TransportError(String),
+    DeadlineSkipped,
 }
 
 #[derive(Debug, Error)]
@@ -99,25 +295,135 @@ pub enum DiscoveryError {
     InvalidBatchSize,
     #[error("discovery transport error: {0}")]
     Transport(String),
+    #[error("invalid gamma endpoint {0}")]
+    InvalidEndpoint(String),
+}
+
+pub const ALL_DURATIONS: [Duration; 5] = Duration::ALL;
+
+/// Coins and durations a strategy instance trades. Discovery, kline ingestion, and
+/// repair derive their work set from this instead of the full product universe.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveryUniverse {
+    pub coins: Vec<Coin>,
+    pub durations: Vec<Duration>,
+    /// Pair markets to discover (see [`build_pair_discovery_keys`]). Empty by default:
+    /// Polymarket lists none yet.
+    pub pairs: Vec<MarketUnderlying>,
 }
 
-pub const ALL_COINS: [Coin; 4] = [Coin::Btc, Coin::Eth, Coin::Sol, Coin::Xrp];
-pub const ALL_DURATIONS: [Duration; 5] = [
-    Duration::M5,
-    Duration::M15,
-    Duration::H1,
-    Duration::H4,
-    Duration::D1,
-];
+impl Default for DiscoveryUniverse {
+    fn default() -> Self {
+        Self {
+            coins: Coin::all(),
+            durations: ALL_DURATIONS.to_vec(),
+            pairs: Vec::new(),
+        }
+    }
+}
+
+impl DiscoveryUniverse {
+    /// Reads `PMM_UNIVERSE_COINS` (e.g. `BTC,ETH`) and `PMM_UNIVERSE_DURATIONS`
+    /// (e.g. `5m,15m`). Missing, empty, or unparseable lists fall back to all.
+    /// `PMM_UNIVERSE_PAIRS` (e.g. `ETH/BTC`) opts into pair markets; missing, empty, or
+    /// unparseable lists mean none.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// [`Self::from_env`] on `settings`.
+    pub fn from_settings(settings: &Settings) -> Self {
+        let defaults = Self::default();
+        Self {
+            coins: settings
+                .parsed("PMM_UNIVERSE_COINS", |raw| {
+                    parse_list(raw, |code| parse_coin(&code.to_ascii_uppercase()))
+                })
+                .unwrap_or(defaults.coins),
+            durations: settings
+                .parsed("PMM_UNIVERSE_DURATIONS", |raw| {
+                    parse_list(raw, |code| Duration::parse(&code.to_ascii_lowercase()))
+                })
+                .unwrap_or(defaults.durations),
+            pairs: settings
+                .parsed("PMM_UNIVERSE_PAIRS", |raw| parse_list(raw, parse_pair))
+                .unwrap_or(defaults.pairs),
+        }
+    }
+
+    /// Previous, active, and next keys of every configured coin and pair market.
+    pub fn previous_active_and_next_keys(
+        &self,
+        now_ts_utc: i64,
+        slug_cfg: SlugConfig,
+    ) -> Result<Vec<ScheduledDiscoveryKey>, SlugError> {
+        let mut keys = build_previous_active_and_next_discovery_keys(
+            now_ts_utc,
+            &self.coins,
+            &self.durations,
+            slug_cfg,
+        )?;
+        keys.extend(build_pair_discovery_keys(
+            now_ts_utc,
+            &self.pairs,
+            &self.durations,
+            slug_cfg,
+        )?);
+        Ok(keys)
+    }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub fn is_full(&self) -> bool {
+        Coin::all().iter().all(|coin| self.coins.contains(coin))
+            && ALL_DURATIONS
+                .iter()
+                .all(|duration| self.durations.contains(duration))
+    }
+
+    /// Length of the longest configured interval, in seconds.
+    pub fn max_duration_seconds(&self) -> i64 {
+        self.durations
+            .iter()
+            .copied()
+            .map(Duration::step_seconds)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+fn parse_pair(code: &str) -> Result<MarketUnderlying, SlugError> {
+    match MarketUnderlying::parse(&code.to_ascii_uppercase())? {
+        pair @ MarketUnderlying::Pair(..) => Ok(pair),
+        MarketUnderlying::Single(_) => Err(SlugError::InvalidPair(code.to_string())),
+    }
+}
+
+fn parse_list<T: PartialEq>(
+    raw: &str,
+    parse: impl Fn(&str) -> Result<T, SlugError>,
+) -> Option<Vec<T>> {
+    let mut out = Vec::new();
+    for part in raw
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+    {
+        let value = parse(part).ok()?;
+        if !out.contains(&value) {
+            out.push(value);
+        }
+    }
+    (!out.is_empty()).then_some(out)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum DiscoveryWindow {
     Previous,
     Active,
     Next,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ScheduledDiscoveryKey {
     pub window: DiscoveryWindow,
     pub key: DiscoveryKey,
@@ -142,7 +448,7 @@ pub fn interval_starts_for_now(
         return interval_starts_for_1d_ny_noon(now_ts_utc);
     }
 
-    let step = duration_step_seconds(duration);
+    let step = duration.step_seconds();
     let offset = interval_offset_seconds(duration, slug_cfg);
     let aligned_base = now_ts_utc.saturating_sub(offset);
     let active_start = aligned_base
@@ -310,14 +616,140 @@ pub fn build_previous_active_and_next_discovery_keys(
     Ok(keys)
 }
 
-fn duration_step_seconds(duration: Duration) -> i64 {
-    match duration {
-        Duration::M5 => 5 * 60,
-        Duration::M15 => 15 * 60,
-        Duration::H1 => 60 * 60,
-        Duration::H4 => 4 * 60 * 60,
-        Duration::D1 => 24 * 60 * 60,
+/// Previous, active, and next keys of every pair market, for the durations pairs list on
+/// ([`PAIR_DURATIONS`]); other durations are skipped.
+pub fn build_pair_discovery_keys(
+    now_ts_utc: i64,
+    pairs: &[MarketUnderlying],
+    durations: &[Duration],
+    slug_cfg: SlugConfig,
+) -> Result<Vec<ScheduledDiscoveryKey>, SlugError> {
+    let mut keys = Vec::with_capacity(pairs.len() * durations.len() * 3);
+
+    for duration in durations
+        .iter()
+        .filter(|duration| PAIR_DURATIONS.contains(duration))
+    {
+        let starts = interval_starts_for_now(*duration, now_ts_utc, slug_cfg);
+        for pair in pairs {
+            for (window, start_ts_utc) in [
+                (DiscoveryWindow::Previous, starts.previous_start_ts_utc),
+                (DiscoveryWindow::Active, starts.active_start_ts_utc),
+                (DiscoveryWindow::Next, starts.next_start_ts_utc),
+            ] {
+                keys.push(ScheduledDiscoveryKey {
+                    window,
+                    key: DiscoveryKey::for_underlying(*pair, *duration, start_ts_utc, slug_cfg)?,
+                });
+            }
+        }
     }
+
+    Ok(keys)
+}
+
+/// The `horizon` intervals after the active one for every coin/duration, soonest first
+/// within each duration. All keys are [`DiscoveryWindow::Next`].
+pub fn build_upcoming_discovery_keys(
+    now_ts_utc: i64,
+    coins: &[Coin],
+    durations: &[Duration],
+    horizon: usize,
+    slug_cfg: SlugConfig,
+) -> Result<Vec<ScheduledDiscoveryKey>, SlugError> {
+    let mut keys = Vec::with_capacity(coins.len() * durations.len() * horizon);
+
+    for duration in durations {
+        let mut start_ts_utc =
+            interval_starts_for_now(*duration, now_ts_utc, slug_cfg).next_start_ts_utc;
+        for _ in 0..horizon {
+            for coin in coins {
+                keys.push(ScheduledDiscoveryKey {
+                    window: DiscoveryWindow::Next,
+                    key: DiscoveryKey::new(*coin, *duration, start_ts_utc, slug_cfg)?,
+                });
+            }
+            start_ts_utc =
+                interval_starts_for_now(*duration, start_ts_utc, slug_cfg).next_start_ts_utc;
+        }
+    }
+
+    Ok(keys)
+}
+
+/// Exclusive end of the interval of `duration` that starts at `start_ts_utc`. Same as
+/// [`Duration::interval_end`].
+pub fn interval_end_ts_utc(duration: Duration, start_ts_utc: i64) -> i64 {
+    duration.interval_end(start_ts_utc)
+}
+
+/// Next schedule boundary of `key` after `now_ts_ms_utc`: the interval start while it is
+/// upcoming, its end while it is active. A lookup answering after that returns data for
+/// a window the next cycle already treats differently. `None` once the interval is over;
+/// a late answer is as good as an early one then.
+pub fn discovery_deadline_ts_ms(key: &DiscoveryKey, now_ts_ms_utc: i64) -> Option<i64> {
+    let start_ts_ms = key.start_ts_utc.saturating_mul(1_000);
+    if now_ts_ms_utc < start_ts_ms {
+        return Some(start_ts_ms);
+    }
+    let end_ts_ms = key
+        .duration
+        .interval_end(key.start_ts_utc)
+        .saturating_mul(1_000);
+    (now_ts_ms_utc < end_ts_ms).then_some(end_ts_ms)
+}
+
+/// Whether `duration` slugs omit the interval timestamp. 1h and 1d slugs only carry
+/// month/day (and 1h repeats across the DST fall-back hour), so the same slug can name
+/// a market from another year or from the other 1am hour.
+pub(crate) fn slug_lacks_timestamp(duration: Duration) -> bool {
+    matches!(duration, Duration::H1 | Duration::D1)
+}
+
+/// Demotes resolved rows whose market belongs to a different window than the key.
+///
+/// Only applies to durations whose slugs lack a timestamp. A market is rejected when
+/// its end date is more than half an interval away from the key's expected end; markets
+/// without an end date are kept. Rejected rows become `NotFound`.
+pub fn reject_window_mismatches<M>(
+    rows: Vec<DiscoveryRow<M>>,
+    market_end_ts_utc: impl Fn(&M) -> Option<i64>,
+) -> Vec<DiscoveryRow<M>> {
+    rows.into_iter()
+        .map(|row| {
+            let DiscoveryStatus::Resolved { market } = &row.status else {
+                return row;
+            };
+            if !slug_lacks_timestamp(row.key.duration) {
+                return row;
+            }
+            let Some(end_ts_utc) = market_end_ts_utc(market) else {
+                return row;
+            };
+            let expected_end_ts_utc = row.key.duration.interval_end(row.key.start_ts_utc);
+            let tolerance = row.key.duration.step_seconds() / 2;
+            if (end_ts_utc - expected_end_ts_utc).abs() <= tolerance {
+                return row;
+            }
+
+            warn!(
+                component = "discovery",
+                event = "discovery.resolve.window_mismatch",
+                slug = %row.key.slug,
+                coin = %row.key.underlying().label(),
+                duration = row.key.duration.as_label(),
+                window_start_ts_utc = row.key.start_ts_utc,
+                expected_end_ts_utc,
+                market_end_ts_utc = end_ts_utc
+            );
+            DiscoveryRow {
+                key: row.key,
+                status: DiscoveryStatus::Unresolved {
+                    reason: UnresolvedReason::NotFound,
+                },
+            }
+        })
+        .collect()
 }
 
 fn interval_offset_seconds(duration: Duration, slug_cfg: SlugConfig) -> i64 {
@@ -418,6 +850,9 @@ fn materialize_rows<M: Clone>(
                 Some(SlugFetchOutcome::TransportError(message)) => DiscoveryStatus::Unresolved {
                     reason: UnresolvedReason::TransportError(message.clone()),
                 },
+                Some(SlugFetchOutcome::DeadlineSkipped) => DiscoveryStatus::Unresolved {
+                    reason: UnresolvedReason::DeadlineSkipped,
+                },
                 Some(SlugFetchOutcome::Missing) | None => DiscoveryStatus::Unresolved {
                     reason: UnresolvedReason::NotFound,
                 },
@@ -441,8 +876,8 @@ fn emit_transport_row_logs<M>(
                 component = "discovery",
                 event = "discovery.degraded.row_transport",
                 slug = %key.slug,
-                coin = %coin_code(key.coin),
-                duration = %duration_code(key.duration),
+                coin = %key.underlying().label(),
+                duration = key.duration.as_label(),
                 window_start_ts_utc = key.start_ts_utc,
                 reason = %reason
             );
@@ -450,47 +885,72 @@ fn emit_transport_row_logs<M>(
     }
 }
 
-fn coin_code(coin: Coin) -> &'static str {
-    match coin {
-        Coin::Btc => "BTC",
-        Coin::Eth => "ETH",
-        Coin::Sol => "SOL",
-        Coin::Xrp => "XRP",
-    }
-}
+#[cfg(feature = "discovery-sdk")]
+type SdkMarket = polymarket_client_sdk::gamma::types::response::Market;
 
-fn duration_code(duration: Duration) -> &'static str {
-    match duration {
-        Duration::M5 => "5m",
-        Duration::M15 => "15m",
-        Duration::H1 => "1h",
-        Duration::H4 => "4h",
-        Duration::D1 => "1d",
-    }
+#[cfg(feature = "discovery-sdk")]
+pub async fn resolve_discovery_batch(
+    keys: &[DiscoveryKey],
+    cfg: &DiscoveryConfig,
+) -> Result<Vec<DiscoveryRow<MarketMeta>>, DiscoveryError> {
+    let endpoints = GammaEndpointPool::new(GammaEndpointConfig::default());
+    resolve_discovery_batch_with_endpoints(keys, cfg, &endpoints).await
 }
 
+/// Resolves `keys` against the endpoints in `endpoints`, failing over between them.
+/// Pass the same pool across cycles so health and latency carry over.
 #[cfg(feature = "discovery-sdk")]
-pub type SdkMarket = polymarket_client_sdk::gamma::types::response::Market;
+pub async fn resolve_discovery_batch_with_endpoints(
+    keys: &[DiscoveryKey],
+    cfg: &DiscoveryConfig,
+    endpoints: &GammaEndpointPool,
+) -> Result<Vec<DiscoveryRow<MarketMeta>>, DiscoveryError> {
+    resolve_discovery_batch_with_sampler(keys, cfg, endpoints, None).await
+}
 
+/// Like [`resolve_discovery_batch_with_endpoints`], also handing each resolved market to
+/// `sampler` for raw payload recording.
 #[cfg(feature = "discovery-sdk")]
-pub async fn resolve_discovery_batch(
+pub async fn resolve_discovery_batch_with_sampler(
     keys: &[DiscoveryKey],
     cfg: &DiscoveryConfig,
-) -> Result<Vec<DiscoveryRow<SdkMarket>>, DiscoveryError> {
+    endpoints: &GammaEndpointPool,
+    sampler: Option<&Arc<GammaPayloadSampler>>,
+) -> Result<Vec<DiscoveryRow<MarketMeta>>, DiscoveryError> {
     use polymarket_client_sdk::gamma::Client as GammaClient;
 
     if cfg.batch_size == 0 {
         return Err(DiscoveryError::InvalidBatchSize);
     }
 
-    let client = GammaClient::default();
+    let clients = endpoints
+        .base_urls()
+        .iter()
+        .map(|url| {
+            // Trailing slash so relative joins keep any path prefix on a proxy URL.
+            GammaClient::new(&format!("{}/", url.trim_end_matches('/')))
+                .map_err(|err| DiscoveryError::InvalidEndpoint(format!("{url}: {err}")))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
     let unique_slugs = ordered_unique_slugs(keys);
-    let mut slug_outcomes: HashMap<String, SlugFetchOutcome<SdkMarket>> =
+    let mut slug_outcomes: HashMap<String, SlugFetchOutcome<MarketMeta>> =
         HashMap::with_capacity(unique_slugs.len());
+    let deadlines = if cfg.deadline_aware {
+        slug_deadlines(
+            keys,
+            crate::faults::now_utc().timestamp_millis(),
+            Instant::now(),
+        )
+    } else {
+        HashMap::new()
+    };
 
     for chunk in unique_slugs.chunks(cfg.batch_size) {
         for slug in chunk {
-            let outcome = fetch_market_by_slug_with_retry(&client, slug, cfg).await;
+            let deadline = deadlines.get(slug).copied();
+            let outcome =
+                fetch_market_by_slug_with_retry(&clients, endpoints, slug, cfg, deadline, sampler)
+                    .await;
             slug_outcomes.insert(slug.clone(), outcome);
         }
     }
@@ -517,70 +977,159 @@ pub async fn resolve_discovery_batch(
         return Err(DiscoveryError::Transport(reason));
     }
 
-    Ok(materialize_rows(keys, &slug_outcomes))
+    Ok(reject_window_mismatches(
+        materialize_rows(keys, &slug_outcomes),
+        |market: &MarketMeta| market.end_ts_utc,
+    ))
 }
 
+/// Earliest deadline per slug, as an instant on the monotonic clock.
+#[cfg(feature = "discovery-sdk")]
+fn slug_deadlines(
+    keys: &[DiscoveryKey],
+    now_ts_ms_utc: i64,
+    now: Instant,
+) -> HashMap<String, Instant> {
+    let mut deadlines: HashMap<String, Instant> = HashMap::new();
+    for key in keys {
+        let Some(deadline_ts_ms) = discovery_deadline_ts_ms(key, now_ts_ms_utc) else {
+            continue;
+        };
+        let deadline =
+            now + std::time::Duration::from_millis((deadline_ts_ms - now_ts_ms_utc) as u64);
+        deadlines
+            .entry(key.slug.clone())
+            .and_modify(|earliest| *earliest = (*earliest).min(deadline))
+            .or_insert(deadline);
+    }
+    deadlines
+}
+
+/// Each attempt goes to the best endpoint not yet tried for this slug, so a retry after
+/// a failure lands on the next endpoint immediately; backoff only applies once every
+/// endpoint has been tried in the current round.
+///
+/// With a `deadline`, each attempt's timeout is capped at the time left before it, and
+/// no attempt starts (nor backoff sleeps) past `cfg.min_call_budget_ms` before it.
 #[cfg(feature = "discovery-sdk")]
 async fn fetch_market_by_slug_with_retry(
-    client: &polymarket_client_sdk::gamma::Client,
+    clients: &[polymarket_client_sdk::gamma::Client],
+    endpoints: &GammaEndpointPool,
     slug: &str,
     cfg: &DiscoveryConfig,
-) -> SlugFetchOutcome<SdkMarket> {
+    deadline: Option<Instant>,
+    sampler: Option<&Arc<GammaPayloadSampler>>,
+) -> SlugFetchOutcome<MarketMeta> {
     use polymarket_client_sdk::gamma::types::request::MarketBySlugRequest;
     use tokio::time::{sleep, timeout, Duration};
 
     let mut attempt: u32 = 0;
+    let mut tried_this_round: Vec<usize> = Vec::new();
+    let mut last_error: Option<String> = None;
+    let budget_left_ms = |deadline: Instant| -> u64 {
+        deadline
+            .saturating_duration_since(Instant::now())
+            .as_millis() as u64
+    };
 
     loop {
-        let request = MarketBySlugRequest::builder()
-            .slug(slug.to_string())
-            .include_tag(cfg.include_tag)
-            .build();
-
-        let call_result = timeout(
-            Duration::from_millis(cfg.timeout_ms),
-            client.market_by_slug(&request),
-        )
-        .await;
-
-        match call_result {
-            Ok(Ok(market)) => return SlugFetchOutcome::Found(market),
-            Ok(Err(err)) if is_not_found_error(&err) => return SlugFetchOutcome::Missing,
-            Ok(Err(err)) => {
-                if attempt >= cfg.max_retries {
-                    let message = err.to_string();
-                    error!(
+        let timeout_ms = match deadline {
+            Some(deadline) => {
+                let left_ms = budget_left_ms(deadline);
+                if left_ms < cfg.min_call_budget_ms {
+                    debug!(
                         component = "discovery",
-                        event = "discovery.resolve.error",
+                        event = "discovery.resolve.deadline_skipped",
                         slug = slug,
                         attempt,
-                        max_retries = cfg.max_retries,
-                        error = %message
+                        budget_left_ms = left_ms
                     );
-                    return SlugFetchOutcome::TransportError(message);
+                    return match last_error {
+                        Some(message) => SlugFetchOutcome::TransportError(message),
+                        None => SlugFetchOutcome::DeadlineSkipped,
+                    };
                 }
+                cfg.timeout_ms.min(left_ms)
             }
-            Err(_) => {
-                if attempt >= cfg.max_retries {
-                    let message = format!(
-                        "timeout after {}ms while resolving slug {}",
-                        cfg.timeout_ms, slug
-                    );
-                    error!(
-                        component = "discovery",
-                        event = "discovery.resolve.error",
-                        slug = slug,
-                        attempt,
-                        max_retries = cfg.max_retries,
-                        error = %message
-                    );
-                    return SlugFetchOutcome::TransportError(message);
+            None => cfg.timeout_ms,
+        };
+        let order = endpoints.order(Instant::now());
+        let idx = order
+            .iter()
+            .copied()
+            .find(|idx| !tried_this_round.contains(idx))
+            .unwrap_or(order[0]);
+        tried_this_round.push(idx);
+        let request = MarketBySlugRequest::builder()
+            .slug(slug.to_string())
+            .include_tag(cfg.include_tag)
+            .build();
+
+        let started = Instant::now();
+        let message = if let Some(fault) = crate::faults::gamma_fault(slug) {
+            fault.message(slug, timeout_ms)
+        } else {
+            let call_result = timeout(
+                Duration::from_millis(timeout_ms),
+                clients[idx].market_by_slug(&request),
+            )
+            .await;
+
+            match call_result {
+                Ok(Ok(market)) => {
+                    endpoints.record_success(idx, started.elapsed());
+                    if let Some(sampler) = sampler {
+                        let base_url = &endpoints.base_urls()[idx];
+                        sampler.sample(base_url, slug, cfg.include_tag, &market);
+                    }
+                    return SlugFetchOutcome::Found(MarketMeta::from(&market));
+                }
+                Ok(Err(err)) if is_not_found_error(&err) => {
+                    endpoints.record_success(idx, started.elapsed());
+                    return SlugFetchOutcome::Missing;
                 }
+                Ok(Err(err)) => err.to_string(),
+                Err(_) => format!("timeout after {timeout_ms}ms while resolving slug {slug}"),
             }
+        };
+        endpoints.record_failure(idx, Instant::now(), &message);
+
+        if attempt >= cfg.max_retries {
+            error!(
+                component = "discovery",
+                event = "discovery.resolve.error",
+                slug = slug,
+                attempt,
+                max_retries = cfg.max_retries,
+                error = %message
+            );
+            return SlugFetchOutcome::TransportError(message);
         }
 
         attempt += 1;
-        sleep(backoff_duration(cfg.retry_backoff_ms, attempt)).await;
+        let next = endpoints
+            .order(Instant::now())
+            .into_iter()
+            .find(|idx| !tried_this_round.contains(idx));
+        match next {
+            Some(next) => debug!(
+                component = "discovery",
+                event = "gamma.endpoint.failover",
+                slug = slug,
+                from = %endpoints.base_urls()[idx],
+                to = %endpoints.base_urls()[next],
+                reason = %message
+            ),
+            None => {
+                tried_this_round.clear();
+                let backoff = backoff_duration(cfg.retry_backoff_ms, attempt);
+                if deadline.is_some_and(|deadline| Instant::now() + backoff >= deadline) {
+                    return SlugFetchOutcome::TransportError(message);
+                }
+                sleep(backoff).await;
+            }
+        }
+        last_error = Some(message);
     }
 }
 
@@ -614,7 +1163,7 @@ mod tests {
     }
 
     fn key(slug: &str) -> DiscoveryKey {
-        DiscoveryKey::from_slug(Coin::Btc, Duration::M5, 1_735_689_600, slug.to_string())
+        DiscoveryKey::from_slug(Coin::BTC, Duration::M5, 1_735_689_600, slug.to_string())
     }
 
     #[test]
@@ -840,24 +1389,50 @@ mod tests {
     fn active_discovery_keys_use_current_active_interval_start() {
         let now = 1_735_689_900; // 2025-01-01 00:05:00 UTC
         let keys =
-            build_active_discovery_keys(now, &ALL_COINS, &[Duration::M5], SlugConfig::default())
+            build_active_discovery_keys(now, &Coin::all(), &[Duration::M5], SlugConfig::default())
                 .unwrap();
-        assert_eq!(keys.len(), ALL_COINS.len());
+        assert_eq!(keys.len(), Coin::all().len());
         assert_eq!(keys[0].start_ts_utc, 1_735_689_900); // 00:05
         assert_eq!(keys[0].slug, "btc-updown-5m-1735689900");
     }
 
+    #[test]
+    fn deadline_is_the_next_boundary_of_the_key_interval() {
+        let now = 1_735_689_901;
+        let scheduled = build_previous_active_and_next_discovery_keys(
+            now,
+            &[Coin::BTC],
+            &[Duration::M5],
+            SlugConfig::default(),
+        )
+        .unwrap();
+        let deadlines: Vec<_> = scheduled
+            .iter()
+            .map(|entry| discovery_deadline_ts_ms(&entry.key, now * 1_000))
+            .collect();
+        // Previous: over, so no deadline. Active: its end. Next: its start.
+        assert_eq!(
+            deadlines,
+            [None, Some(1_735_690_200_000), Some(1_735_690_200_000)]
+        );
+        let next = &scheduled[2].key;
+        assert_eq!(
+            discovery_deadline_ts_ms(next, next.start_ts_utc * 1_000),
+            Some(1_735_690_500_000)
+        );
+    }
+
     #[test]
     fn active_and_next_key_builder_covers_all_pairs() {
         let now = 1_735_689_901;
         let scheduled = build_active_and_next_discovery_keys(
             now,
-            &ALL_COINS,
+            &Coin::all(),
             &ALL_DURATIONS,
             SlugConfig::default(),
         )
         .unwrap();
-        assert_eq!(scheduled.len(), ALL_COINS.len() * ALL_DURATIONS.len() * 2);
+        assert_eq!(scheduled.len(), Coin::all().len() * ALL_DURATIONS.len() * 2);
         assert!(matches!(scheduled[0].window, DiscoveryWindow::Active));
         assert!(matches!(scheduled[1].window, DiscoveryWindow::Next));
     }
@@ -867,14 +1442,171 @@ mod tests {
         let now = 1_735_689_901;
         let scheduled = build_previous_active_and_next_discovery_keys(
             now,
-            &ALL_COINS,
+            &Coin::all(),
             &ALL_DURATIONS,
             SlugConfig::default(),
         )
         .unwrap();
-        assert_eq!(scheduled.len(), ALL_COINS.len() * ALL_DURATIONS.len() * 3);
+        assert_eq!(scheduled.len(), Coin::all().len() * ALL_DURATIONS.len() * 3);
         assert!(matches!(scheduled[0].window, DiscoveryWindow::Previous));
         assert!(matches!(scheduled[1].window, DiscoveryWindow::Active));
         assert!(matches!(scheduled[2].window, DiscoveryWindow::Next));
     }
+
+    #[test]
+    fn pair_keys_cover_updown_durations_only() {
+        let pair = MarketUnderlying::pair(Coin::ETH, Coin::BTC).unwrap();
+        assert_eq!(parse_pair("eth/btc"), Ok(pair));
+        assert!(parse_pair("ETH").is_err());
+
+        let universe = DiscoveryUniverse {
+            coins: vec![Coin::BTC],
+            durations: vec![Duration::M5, Duration::H1],
+            pairs: vec![pair],
+        };
+        let keys = universe
+            .previous_active_and_next_keys(1_735_689_700, SlugConfig::default())
+            .unwrap();
+        // BTC 5m and 1h, plus ETH/BTC 5m; no 1h pair slug exists.
+        assert_eq!(keys.len(), 9);
+        let pair_keys: Vec<_> = keys
+            .iter()
+            .filter(|scheduled| scheduled.key.quote.is_some())
+            .collect();
+        assert_eq!(pair_keys.len(), 3);
+        let active = &pair_keys[1].key;
+        assert_eq!(pair_keys[1].window, DiscoveryWindow::Active);
+        assert_eq!(active.slug, "eth-btc-updown-5m-1735689600");
+        assert_eq!((active.coin, active.underlying()), (Coin::ETH, pair));
+
+        let json = serde_json::to_value(active).unwrap();
+        assert_eq!(json["quote"], "BTC");
+        let single = DiscoveryKey::new(
+            Coin::BTC,
+            Duration::M5,
+            1_735_689_600,
+            SlugConfig::default(),
+        )
+        .unwrap();
+        assert!(serde_json::to_value(&single)
+            .unwrap()
+            .get("quote")
+            .is_none());
+    }
+
+    #[test]
+    fn universe_lists_parse_dedup_and_reject_unknown_codes() {
+        let coins = parse_list("btc, ETH,BTC", |code| {
+            parse_coin(&code.to_ascii_uppercase())
+        });
+        assert_eq!(coins, Some(vec![Coin::BTC, Coin::ETH]));
+        assert_eq!(parse_list("5m,2h", Duration::parse), None);
+        assert_eq!(parse_list(" , ", Duration::parse), None);
+
+        let scoped = DiscoveryUniverse {
+            coins: vec![Coin::BTC],
+            durations: vec![Duration::M5, Duration::M15],
+            pairs: Vec::new(),
+        };
+        assert!(!scoped.is_full());
+        assert_eq!(scoped.max_duration_seconds(), 900);
+        assert!(DiscoveryUniverse::default().is_full());
+        assert_eq!(DiscoveryUniverse::default().max_duration_seconds(), 86_400);
+    }
+
+    #[test]
+    fn timestampless_slug_resolved_to_another_window_is_rejected() {
+        let hour_start = 1_736_971_200; // 2025-01-15 3pm ET
+        let hourly =
+            DiscoveryKey::new(Coin::ETH, Duration::H1, hour_start, SlugConfig::default()).unwrap();
+        let five_min = key("btc-updown-5m-1735689600");
+        let resolved = |key: &DiscoveryKey, end_ts: i64| DiscoveryRow {
+            key: key.clone(),
+            status: DiscoveryStatus::Resolved { market: end_ts },
+        };
+        let rows = vec![
+            resolved(&hourly, hour_start + 3_600),
+            resolved(&hourly, hour_start + 3_600 - 366 * 86_400),
+            resolved(&five_min, 0),
+        ];
+
+        let rows = reject_window_mismatches(rows, |end_ts: &i64| Some(*end_ts));
+
+        assert!(matches!(rows[0].status, DiscoveryStatus::Resolved { .. }));
+        assert_eq!(
+            rows[1].status,
+            DiscoveryStatus::Unresolved {
+                reason: UnresolvedReason::NotFound
+            }
+        );
+        assert!(matches!(rows[2].status, DiscoveryStatus::Resolved { .. }));
+        assert_eq!(Duration::D1.interval_end(1_735_750_800), 1_735_837_200);
+    }
+
+    #[cfg(feature = "discovery-sdk")]
+    #[test]
+    fn market_meta_converts_gamma_markets() {
+        let market: SdkMarket = serde_json::from_value(serde_json::json!({
+            "id": "1",
+            "slug": "btc-updown-5m-1735689600",
+            "conditionId": "0x5f65177b394277fd294cd75650044e32ba009a95022d88a0c1d565897d72f8f1",
+            "clobTokenIds": "[\"123\", \"456\"]",
+            "acceptingOrders": true,
+            "acceptingOrdersTimestamp": "2024-12-31T23:55:00Z",
+            "closed": false,
+            "endDate": "2025-01-01T00:05:00Z",
+            "marketType": " crypto_15_min ",
+            "feesEnabled": true,
+            "clobRewards": [{"rewardsDailyRate": "2.5", "rewardsAmount": "0"}],
+            "umaReward": "5",
+            "volumeNum": "1234.5",
+            "volume24hr": "88.25",
+            "liquidity": "4321"
+        }))
+        .unwrap();
+
+        let meta = MarketMeta::from(&market);
+        assert_eq!(meta.slug.as_deref(), Some("btc-updown-5m-1735689600"));
+        assert_eq!(
+            meta.condition_id.as_deref(),
+            Some("0x5f65177b394277fd294cd75650044e32ba009a95022d88a0c1d565897d72f8f1")
+        );
+        assert_eq!(meta.token_ids, ["123", "456"]);
+        assert_eq!(meta.accepting_orders_ts_utc, Some(1_735_689_300));
+        assert_eq!(meta.end_ts_utc, Some(1_735_689_900));
+        assert_eq!(meta.fee_type.as_deref(), Some("crypto_15_min"));
+        assert_eq!(meta.fees_enabled, Some(true));
+        assert_eq!(meta.reward_rate(), Some(2.5));
+        assert_eq!(meta.volume, Some(1234.5));
+        assert_eq!(meta.volume_24h, Some(88.25));
+        assert_eq!(meta.liquidity, Some(4321.0));
+        assert_eq!(meta.bets_open(), Some(true));
+        assert_eq!((meta.neg_risk, meta.neg_risk_group()), (None, None));
+
+        let grouped: SdkMarket = serde_json::from_value(serde_json::json!({
+            "id": "2",
+            "negRisk": true,
+            "events": [{
+                "id": "9",
+                "slug": "btc-price-buckets",
+                "negRiskMarketID": "0x0000000000000000000000000000000000000000000000000000000000000042"
+            }]
+        }))
+        .unwrap();
+        let grouped = MarketMeta::from(&grouped);
+        assert_eq!(grouped.event_slug.as_deref(), Some("btc-price-buckets"));
+        assert_eq!(
+            grouped.neg_risk_group(),
+            Some("0x0000000000000000000000000000000000000000000000000000000000000042")
+        );
+
+        let closed = MarketMeta {
+            closed: Some(true),
+            uma_reward: Some(5.0),
+            ..MarketMeta::default()
+        };
+        assert_eq!(closed.bets_open(), Some(false));
+        assert_eq!(closed.reward_rate(), Some(5.0));
+        assert_eq!(MarketMeta::default().bets_open(), None);
+    }
 }
@@ -0,0 +1,1851 @@
+//! Registry of the `PMM_*` environment variables.
+//!
+//! [`ENV_VARS`] lists every variable with its type, default, and purpose. Modules read
+//! their settings through [`Settings`], which parses a set value as the field's type
+//! and falls back to the registered default, so each default is written down here
+//! only; configs build their `Default` from [`Settings::defaults`]. Values from the
+//! config file ([`crate::config`]) sit between the environment and the defaults. A bad
+//! value falls back silently, so [`validate_env`] checks set values against the
+//! registry and flags unknown `PMM_*`/`PMFLIPS_*` names. `dashboard_server` logs the
+//! result at startup, preflight's `config` check fails on ill-typed values, and
+//! `pmm env` prints the effective value of each variable.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+use chrono::NaiveDate;
+use serde::Serialize;
+use tracing::warn;
+
+use crate::binance_ws::DEFAULT_BINANCE_WS_URL;
+use crate::canary::{parse_canary_markets, CanaryConfig};
+use crate::clob_stream::DEFAULT_CLOB_WS_URL;
+use crate::gamma_endpoints::DEFAULT_GAMMA_BASE_URL;
+use crate::redaction::REDACTABLE_COLUMNS;
+use crate::rewards::DEFAULT_DATA_API_URL;
+use crate::slug::{parse_coin, CoinSpec, Duration, MarketUnderlying};
+use crate::strategy::{parse_quote_overrides, QuoteParams, StrategyConfig};
+use crate::trading::DEFAULT_CLOB_URL;
+use crate::webhooks::MarketEventKind;
+
+/// Prefixes whose unregistered names are reported as unknown. Nothing reads `PMFLIPS_*`
+/// anymore (the last one was `PMFLIPS_DISCOVERY_OFFSET_4H_MIN`), so leftovers are flagged.
+pub const ENV_PREFIXES: [&str; 2] = ["PMM_", "PMFLIPS_"];
+
+#[derive(Debug, Clone, Copy)]
+pub enum EnvKind {
+    /// Non-negative integer.
+    Count,
+    Number,
+    /// `true|false|1|0`.
+    Bool,
+    OneOf(&'static [&'static str]),
+    SocketAddr,
+    /// `YYYY-MM-DD`.
+    Date,
+    /// `http(s)://` URL, or `off` when the feature can be disabled.
+    Url {
+        allow_off: bool,
+    },
+    /// Comma list of `http(s)://` URLs.
+    UrlList,
+    /// Free-form (paths, names, module-specific syntax).
+    Text,
+    /// Module-specific check, returning what was expected.
+    Custom(fn(&str) -> Result<(), String>),
+}
+
+impl EnvKind {
+    /// `Err` with what was expected when `raw` (trimmed) does not parse.
+    pub fn validate(self, raw: &str) -> Result<(), String> {
+        let raw = raw.trim();
+        let is_url = |value: &str| value.starts_with("http://") || value.starts_with("https://");
+        let ok = match self {
+            Self::Count => raw.parse::<u64>().is_ok(),
+            Self::Number => raw.parse::<f64>().is_ok_and(f64::is_finite),
+            Self::Bool => matches!(
+                raw.to_ascii_lowercase().as_str(),
+                "1" | "0" | "true" | "false"
+            ),
+            Self::OneOf(allowed) => allowed.iter().any(|value| value.eq_ignore_ascii_case(raw)),
+            Self::SocketAddr => raw.parse::<std::net::SocketAddr>().is_ok(),
+            Self::Date => NaiveDate::parse_from_str(raw, "%Y-%m-%d").is_ok(),
+            Self::Url { allow_off } => {
+                is_url(raw) || (allow_off && raw.eq_ignore_ascii_case("off"))
+            }
+            Self::UrlList => raw
+                .split(',')
+                .map(str::trim)
+                .filter(|url| !url.is_empty())
+                .all(is_url),
+            Self::Text => true,
+            Self::Custom(check) => return check(raw),
+        };
+        if ok {
+            return Ok(());
+        }
+        Err(match self {
+            Self::Count => "expected a non-negative integer".to_string(),
+            Self::Number => "expected a number".to_string(),
+            Self::Bool => "expected true/false".to_string(),
+            Self::OneOf(allowed) => format!("expected one of {}", allowed.join("|")),
+            Self::SocketAddr => "expected host:port, e.g. 127.0.0.1:8080".to_string(),
+            Self::Date => "expected YYYY-MM-DD".to_string(),
+            Self::Url { allow_off: true } => "expected an http(s) URL or off".to_string(),
+            Self::Url { allow_off: false } | Self::UrlList => "expected http(s) URLs".to_string(),
+            Self::Text | Self::Custom(_) => unreachable!("validated above"),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct EnvVar {
+    pub name: &'static str,
+    pub kind: EnvKind,
+    /// Effective value when unset; `None` when unset means absent or off.
+    pub default: Option<&'static str>,
+    pub description: &'static str,
+    /// Values are never printed or logged.
+    pub secret: bool,
+}
+
+const fn var(
+    name: &'static str,
+    kind: EnvKind,
+    default: Option<&'static str>,
+    description: &'static str,
+) -> EnvVar {
+    EnvVar {
+        name,
+        kind,
+        default,
+        description,
+        secret: false,
+    }
+}
+
+const fn secret(name: &'static str, kind: EnvKind, description: &'static str) -> EnvVar {
+    EnvVar {
+        name,
+        kind,
+        default: None,
+        description,
+        secret: true,
+    }
+}
+
+fn comma_list(raw: &str, item: impl Fn(&str) -> bool, expected: &str) -> Result<(), String> {
+    let bad: Vec<&str> = raw
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty() && !item(entry))
+        .collect();
+    if bad.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "expected {expected}; not recognized: {}",
+            bad.join(",")
+        ))
+    }
+}
+
+fn coin_list(raw: &str) -> Result<(), String> {
+    comma_list(
+        raw,
+        |code| parse_coin(&code.to_ascii_uppercase()).is_ok(),
+        "registered coin tickers",
+    )
+}
+
+fn duration_list(raw: &str) -> Result<(), String> {
+    comma_list(
+        raw,
+        |code| Duration::parse(&code.to_ascii_lowercase()).is_ok(),
+        "durations (5m,15m,1h,4h,1d)",
+    )
+}
+
+fn pair_list(raw: &str) -> Result<(), String> {
+    comma_list(
+        raw,
+        |code| {
+            MarketUnderlying::parse(&code.to_ascii_uppercase()).is_ok_and(MarketUnderlying::is_pair)
+        },
+        "coin pairs (e.g. ETH/BTC)",
+    )
+}
+
+fn public_columns(raw: &str) -> Result<(), String> {
+    comma_list(
+        raw,
+        |column| {
+            column.eq_ignore_ascii_case("none")
+                || REDACTABLE_COLUMNS.contains(&column.to_ascii_lowercase().as_str())
+        },
+        "none or dashboard column names (e.g. price,probability)",
+    )
+}
+
+fn webhook_events(raw: &str) -> Result<(), String> {
+    comma_list(
+        raw,
+        |name| MarketEventKind::parse(name).is_some(),
+        "webhook event names",
+    )
+}
+
+fn kline_rules(raw: &str) -> Result<(), String> {
+    comma_list(
+        raw,
+        |rule| {
+            matches!(
+                rule.to_ascii_lowercase().as_str(),
+                "volume" | "high_low" | "close_range" | "monotonic" | "none"
+            )
+        },
+        "volume,high_low,close_range,monotonic or none",
+    )
+}
+
+fn coin_specs(raw: &str) -> Result<(), String> {
+    raw.split(';')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .try_for_each(|entry| CoinSpec::parse(entry).map(|_| ()))
+        .map_err(|err| format!("expected TICKER:short:full:SYMBOL entries; {err}"))
+}
+
+fn ws_url_or_off(raw: &str) -> Result<(), String> {
+    if raw.starts_with("ws://") || raw.starts_with("wss://") || raw.eq_ignore_ascii_case("off") {
+        Ok(())
+    } else {
+        Err("expected a ws(s) URL or off".to_string())
+    }
+}
+
+fn count_or_off(raw: &str) -> Result<(), String> {
+    if raw.parse::<u64>().is_ok() || raw.eq_ignore_ascii_case("off") {
+        Ok(())
+    } else {
+        Err("expected a non-negative integer or off".to_string())
+    }
+}
+
+fn strategy_overrides(raw: &str) -> Result<(), String> {
+    parse_quote_overrides(raw, QuoteParams::default()).map(|_| ())
+}
+
+fn canary_markets(raw: &str) -> Result<(), String> {
+    if raw.eq_ignore_ascii_case("off") {
+        return Ok(());
+    }
+    parse_canary_markets(raw).map(|_| ())
+}
+
+fn canary_params(raw: &str) -> Result<(), String> {
+    CanaryConfig::parse("BTC:5m", raw, &StrategyConfig::default()).map(|_| ())
+}
+
+fn postgres_url(raw: &str) -> Result<(), String> {
+    if raw.starts_with("postgres://") || raw.starts_with("postgresql://") {
+        Ok(())
+    } else {
+        Err("expected a postgres:// URL; anything else keeps SQLite".to_string())
+    }
+}
+
+/// Every `PMM_*` variable read anywhere in the crate, its binaries, or its benches.
+pub const ENV_VARS: &[EnvVar] = &[
+    // Process
+    var(
+        "PMM_CONFIG",
+        EnvKind::Text,
+        Some("pmm.toml"),
+        "TOML config file whose values apply to unset variables; off skips it",
+    ),
+    var(
+        "PMM_MODE",
+        EnvKind::OneOf(&["paper", "live"]),
+        Some("paper"),
+        "run mode; live trades real orders and blocks startup on critical preflight failures",
+    ),
+    var(
+        "PMM_PREFLIGHT",
+        EnvKind::OneOf(&["on", "off"]),
+        Some("on"),
+        "off skips the startup preflight checks in dashboard_server",
+    ),
+    var(
+        "PMM_PREFLIGHT_TIMEOUT_MS",
+        EnvKind::Count,
+        Some("5000"),
+        "bound on each preflight network probe",
+    ),
+    var(
+        "PMM_PREFLIGHT_BINANCE_URL",
+        EnvKind::Url { allow_off: true },
+        Some("https://api.binance.com/api/v3/ping"),
+        "Binance ping endpoint probed by preflight",
+    ),
+    secret(
+        "PMM_DATABASE_URL",
+        EnvKind::Custom(postgres_url),
+        "postgres:// URL shared by every process instead of the SQLite files",
+    ),
+    // Logging
+    var(
+        "PMM_LOG_LEVEL",
+        EnvKind::Text,
+        Some("info"),
+        "tracing filter directive",
+    ),
+    var(
+        "PMM_LOG_FORMAT",
+        EnvKind::OneOf(&["pretty", "json"]),
+        Some("pretty"),
+        "log line format",
+    ),
+    var(
+        "PMM_LOG_TARGET",
+        EnvKind::Bool,
+        Some("true"),
+        "include the module target in log lines",
+    ),
+    var(
+        "PMM_LOG_STDOUT",
+        EnvKind::Bool,
+        Some("true"),
+        "also log to stdout when file logging is on",
+    ),
+    var(
+        "PMM_LOG_DIR",
+        EnvKind::Text,
+        None,
+        "directory for rotating log files; unset or off logs to stdout only",
+    ),
+    var(
+        "PMM_LOG_FILE_PREFIX",
+        EnvKind::Text,
+        Some("pmm"),
+        "log file name prefix",
+    ),
+    var(
+        "PMM_LOG_ROTATION",
+        EnvKind::OneOf(&["minutely", "hourly", "daily", "weekly", "size", "never"]),
+        Some("daily"),
+        "log file rotation",
+    ),
+    var(
+        "PMM_LOG_MAX_BYTES",
+        EnvKind::Count,
+        Some("104857600"),
+        "size limit per log file with size rotation",
+    ),
+    var(
+        "PMM_LOG_MAX_FILES",
+        EnvKind::Count,
+        Some("7"),
+        "log files kept, including the active one",
+    ),
+    // Dashboard
+    var(
+        "PMM_DASHBOARD_ADDR",
+        EnvKind::SocketAddr,
+        Some("127.0.0.1:8080"),
+        "dashboard_server listen address",
+    ),
+    var(
+        "PMM_DASHBOARD_USE_DEMO",
+        EnvKind::Bool,
+        Some("false"),
+        "serve demo rows instead of live discovery",
+    ),
+    var(
+        "PMM_DASHBOARD_DEMO_SPEED",
+        EnvKind::Count,
+        None,
+        "simulated seconds per real second for demo rows",
+    ),
+    var(
+        "PMM_DASHBOARD_DISCOVERY_REFRESH_MS",
+        EnvKind::Count,
+        Some("1000"),
+        "delay between live discovery cycles",
+    ),
+    var(
+        "PMM_READY_MAX_STALE_INTERVALS",
+        EnvKind::Count,
+        Some("30"),
+        "refresh intervals the last live discovery refresh may age before /readyz fails",
+    ),
+    var(
+        "PMM_READY_CHECK_KLINES",
+        EnvKind::Bool,
+        Some("false"),
+        "make /readyz also require the kline store (PMM_BINANCE_STORE_PATH) to open",
+    ),
+    var(
+        "PMM_DASHBOARD_LIVE_DEADLINE_MS",
+        EnvKind::Count,
+        Some("15000"),
+        "bound on one live discovery cycle",
+    ),
+    var(
+        "PMM_DASHBOARD_LIVE_ESCALATE_AFTER",
+        EnvKind::Count,
+        Some("3"),
+        "consecutive failed cycles before live-unavailable logs escalate to error",
+    ),
+    var(
+        "PMM_DASHBOARD_LIVE_DEGRADE_AFTER_MINS",
+        EnvKind::Count,
+        Some("5"),
+        "minutes of continuous live failure before the dashboard enters degraded mode",
+    ),
+    var(
+        "PMM_DASHBOARD_LIVE_MAX_BACKOFF_MS",
+        EnvKind::Count,
+        Some("30000"),
+        "cap for the retry delay while live data is unavailable",
+    ),
+    var(
+        "PMM_DASHBOARD_STALE_MAX_FAILURES",
+        EnvKind::Count,
+        Some("3"),
+        "failed lookups before a last-known-good row is downgraded",
+    ),
+    var(
+        "PMM_DASHBOARD_DRY_RUN_QUOTES",
+        EnvKind::Bool,
+        None,
+        "show would-be quotes in the Offer columns; defaults to on unless PMM_MODE=live",
+    ),
+    secret(
+        "PMM_DASHBOARD_TOKEN",
+        EnvKind::Text,
+        "unlocks the full dashboard; requests without it get the public, redacted view",
+    ),
+    var(
+        "PMM_DASHBOARD_PUBLIC_COLUMNS",
+        EnvKind::Custom(public_columns),
+        None,
+        "columns public dashboard requests see; defaults to all but positions, orders, and PnL",
+    ),
+    var(
+        "PMM_DASHBOARD_VIEW_STORE_PATH",
+        EnvKind::Text,
+        Some("data/dashboard_views.sqlite"),
+        "SQLite file for saved dashboard views; off keeps them in memory",
+    ),
+    var(
+        "PMM_DASHBOARD_NUMBER_LOCALE",
+        EnvKind::Text,
+        None,
+        "locale for decimal and grouping separators, e.g. de-DE",
+    ),
+    var(
+        "PMM_DASHBOARD_DECIMAL_SEPARATOR",
+        EnvKind::Text,
+        Some("."),
+        "decimal separator: none, space, or one character",
+    ),
+    var(
+        "PMM_DASHBOARD_THOUSANDS_SEPARATOR",
+        EnvKind::Text,
+        Some("none"),
+        "digit grouping separator: none, space, or one character",
+    ),
+    var(
+        "PMM_DASHBOARD_COLUMN_DECIMALS",
+        EnvKind::Text,
+        None,
+        "fixed decimals per column, e.g. price=2,ref_price=2",
+    ),
+    var(
+        "PMM_ACTIVITY_FEED_CAPACITY",
+        EnvKind::Count,
+        Some("200"),
+        "events kept by the dashboard activity feed",
+    ),
+    var(
+        "PMM_ACTIVITY_PROBABILITY_MOVE",
+        EnvKind::Number,
+        Some("0.05"),
+        "smallest probability change the activity feed reports",
+    ),
+    var(
+        "PMM_CURVE_MAX_POINTS",
+        EnvKind::Count,
+        Some("1000"),
+        "points per series in the interval probability curve",
+    ),
+    // Discovery and Gamma
+    var(
+        "PMM_UNIVERSE_COINS",
+        EnvKind::Custom(coin_list),
+        None,
+        "coins to discover and trade, e.g. BTC,ETH; default every registered coin",
+    ),
+    var(
+        "PMM_UNIVERSE_DURATIONS",
+        EnvKind::Custom(duration_list),
+        None,
+        "durations to discover and trade, e.g. 5m,15m; default all",
+    ),
+    var(
+        "PMM_UNIVERSE_PAIRS",
+        EnvKind::Custom(pair_list),
+        None,
+        "pair markets to discover, e.g. ETH/BTC (5m/15m/4h only); default none",
+    ),
+    var(
+        "PMM_EXTRA_COINS",
+        EnvKind::Custom(coin_specs),
+        None,
+        "extra coins, ;-separated TICKER:short:full:BINANCESYMBOL",
+    ),
+    var(
+        "PMM_DISCOVERY_TIMEOUT_MS",
+        EnvKind::Count,
+        Some("3000"),
+        "timeout of one Gamma lookup",
+    ),
+    var(
+        "PMM_DISCOVERY_BATCH_SIZE",
+        EnvKind::Count,
+        Some("64"),
+        "concurrent Gamma lookups per batch",
+    ),
+    var(
+        "PMM_DISCOVERY_MAX_RETRIES",
+        EnvKind::Count,
+        Some("2"),
+        "retries per failed Gamma lookup",
+    ),
+    var(
+        "PMM_DISCOVERY_RETRY_BACKOFF_MS",
+        EnvKind::Count,
+        Some("200"),
+        "base delay between Gamma lookup retries",
+    ),
+    var(
+        "PMM_DISCOVERY_DEADLINES",
+        EnvKind::Bool,
+        Some("true"),
+        "cap Gamma lookups at the next boundary of their interval and look up imminent ones first",
+    ),
+    var(
+        "PMM_DISCOVERY_MIN_CALL_BUDGET_MS",
+        EnvKind::Count,
+        Some("250"),
+        "time left before a lookup's deadline below which it is skipped",
+    ),
+    var(
+        "PMM_GAMMA_BASE_URLS",
+        EnvKind::UrlList,
+        Some(DEFAULT_GAMMA_BASE_URL),
+        "Gamma base URLs, primary first",
+    ),
+    var(
+        "PMM_GAMMA_UNHEALTHY_AFTER",
+        EnvKind::Count,
+        Some("3"),
+        "consecutive failures that take a Gamma endpoint out",
+    ),
+    var(
+        "PMM_GAMMA_COOLDOWN_MS",
+        EnvKind::Count,
+        Some("30000"),
+        "time an unhealthy Gamma endpoint stays out",
+    ),
+    var(
+        "PMM_GAMMA_PAYLOAD_SAMPLE_EVERY",
+        EnvKind::Count,
+        None,
+        "store every Nth resolved market's raw Gamma JSON for schema-drift checks",
+    ),
+    var(
+        "PMM_GAMMA_PAYLOAD_STORE_PATH",
+        EnvKind::Text,
+        Some("data/gamma_payloads.sqlite"),
+        "SQLite file for sampled Gamma payloads",
+    ),
+    var(
+        "PMM_GAMMA_PAYLOAD_MAX_SAMPLES",
+        EnvKind::Count,
+        Some("500"),
+        "sampled Gamma payloads kept",
+    ),
+    var(
+        "PMM_CLOB_WS_URL",
+        EnvKind::Custom(ws_url_or_off),
+        Some(DEFAULT_CLOB_WS_URL),
+        "CLOB market websocket for live order books; off disables",
+    ),
+    var(
+        "PMM_POSITIONS_POLL_MS",
+        EnvKind::Custom(count_or_off),
+        Some("5000"),
+        "wallet trade-history poll cadence for dashboard positions; off disables",
+    ),
+    var(
+        "PMM_CLOB_PING_INTERVAL_MS",
+        EnvKind::Count,
+        Some("10000"),
+        "keep-alive PING cadence on the CLOB websocket",
+    ),
+    var(
+        "PMM_CLOB_MAX_BACKOFF_MS",
+        EnvKind::Count,
+        Some("30000"),
+        "cap for the CLOB websocket reconnect delay",
+    ),
+    var(
+        "PMM_BINANCE_WS_URL",
+        EnvKind::Custom(ws_url_or_off),
+        Some(DEFAULT_BINANCE_WS_URL),
+        "Binance websocket for live spot prices; off disables",
+    ),
+    var(
+        "PMM_BINANCE_WS_STREAM",
+        EnvKind::OneOf(&["book_ticker", "trade"]),
+        Some("book_ticker"),
+        "Binance stream the live price comes from (bookTicker mid or last trade)",
+    ),
+    var(
+        "PMM_BINANCE_WS_MAX_BACKOFF_MS",
+        EnvKind::Count,
+        Some("30000"),
+        "cap for the Binance websocket reconnect delay",
+    ),
+    var(
+        "PMM_BINANCE_WS_HISTORY_SECS",
+        EnvKind::Count,
+        Some("90000"),
+        "how long per-second live prices are kept for ref_price lookups",
+    ),
+    var(
+        "PMM_BINANCE_WS_MAX_REF_DELAY_MS",
+        EnvKind::Count,
+        Some("5000"),
+        "latest live capture after the interval start accepted as ref_price",
+    ),
+    // Binance klines
+    var(
+        "PMM_BINANCE_DATA_ROOT",
+        EnvKind::Text,
+        Some("data/binance"),
+        "directory for downloaded Binance archives",
+    ),
+    var(
+        "PMM_BINANCE_STORE_PATH",
+        EnvKind::Text,
+        Some("data/binance/klines_1s.sqlite"),
+        "SQLite 1s kline store",
+    ),
+    var(
+        "PMM_KLINE_START_DATE",
+        EnvKind::Date,
+        Some("2025-01-01"),
+        "first day binance_store_sync loads",
+    ),
+    var(
+        "PMM_KLINE_REPAIR_RANGE",
+        EnvKind::OneOf(&["history", "scope"]),
+        Some("history"),
+        "range gap repair covers: full history or the scope lookback",
+    ),
+    var(
+        "PMM_KLINE_SCOPE_FEATURES",
+        EnvKind::Bool,
+        Some("true"),
+        "sync every symbol the cross-asset features need",
+    ),
+    var(
+        "PMM_KLINE_VALIDATION_RULES",
+        EnvKind::Custom(kline_rules),
+        None,
+        "kline ingestion rules to apply; default all",
+    ),
+    var(
+        "PMM_KLINE_VALIDATION_POLICY",
+        EnvKind::OneOf(&["reject", "quarantine"]),
+        Some("quarantine"),
+        "what happens to klines that violate a rule",
+    ),
+    var(
+        "PMM_KLINE_HALT_MIN_SECS",
+        EnvKind::Count,
+        Some("5"),
+        "shortest flat zero-volume run flagged as a micro-halt",
+    ),
+    var(
+        "PMM_KLINE_COMPACT_AFTER_DAYS",
+        EnvKind::Count,
+        Some("30"),
+        "age after which kline_store_compact compacts days",
+    ),
+    var(
+        "PMM_REALIZED_VOL_SCALE",
+        EnvKind::OneOf(&["annualized", "interval", "off"]),
+        Some("annualized"),
+        "scaling of the realized_vol column; off disables kline columns",
+    ),
+    var(
+        "PMM_REALIZED_VOL_MIN_RETURNS",
+        EnvKind::Count,
+        Some("30"),
+        "returns needed before realized_vol is shown",
+    ),
+    var(
+        "PMM_MOVE_SIGMA_LOOKBACK_SECS",
+        EnvKind::Count,
+        Some("3600"),
+        "trailing window of the move_z variance",
+    ),
+    var(
+        "PMM_REF_CAPTURE_ALERT_MS",
+        EnvKind::Count,
+        Some("0"),
+        "reference capture delay that fires ref_price.late_capture",
+    ),
+    var(
+        "PMM_WARMUP_ENABLED",
+        EnvKind::Bool,
+        None,
+        "backfill online features before quoting; defaults to on with PMM_MODE=live",
+    ),
+    var(
+        "PMM_WARMUP_PRESET",
+        EnvKind::OneOf(&["short", "medium", "long"]),
+        None,
+        "feature windows to warm up; default 5,15,60",
+    ),
+    var(
+        "PMM_WARMUP_MAX_LAG_MS",
+        EnvKind::Count,
+        Some("2000"),
+        "largest lag behind now at which warm-up counts as done",
+    ),
+    // Trading
+    var(
+        "PMM_SIZING_KELLY_FRACTION",
+        EnvKind::Number,
+        Some("0.25"),
+        "fraction of full Kelly staked",
+    ),
+    var(
+        "PMM_SIZING_MAX_BANKROLL_FRACTION",
+        EnvKind::Number,
+        Some("0.05"),
+        "cap on one stake as a fraction of bankroll",
+    ),
+    var(
+        "PMM_SIZING_PER_MARKET_BANKROLL",
+        EnvKind::Number,
+        Some("100"),
+        "bankroll per market in USDC",
+    ),
+    var(
+        "PMM_SIZING_GAIN_THRESHOLD",
+        EnvKind::Number,
+        Some("0"),
+        "smallest expected gain worth a trade",
+    ),
+    var(
+        "PMM_SIZING_SIZE_THRESHOLD",
+        EnvKind::Number,
+        Some("1"),
+        "smallest size worth a trade",
+    ),
+    var(
+        "PMM_RISK_MAX_PORTFOLIO_USDC",
+        EnvKind::Number,
+        Some("250"),
+        "cap on correlation-adjusted portfolio exposure",
+    ),
+    var(
+        "PMM_RISK_CORRELATIONS",
+        EnvKind::Text,
+        None,
+        "correlation overrides, e.g. BTC:ETH=0.9,SOL:XRP=0.5",
+    ),
+    var(
+        "PMM_RISK_MAX_GROUP_USDC",
+        EnvKind::Number,
+        Some("100"),
+        "cap on gross exposure across the markets of one neg-risk event",
+    ),
+    var(
+        "PMM_FEE_SCHEDULE_PATH",
+        EnvKind::Text,
+        None,
+        "JSON fee schedule replacing the built-in one",
+    ),
+    var(
+        "PMM_FILL_MODE",
+        EnvKind::OneOf(&["mid", "calibrated"]),
+        Some("calibrated"),
+        "backtest fill model",
+    ),
+    var(
+        "PMM_FILL_TAKER_SLIPPAGE",
+        EnvKind::Number,
+        Some("0"),
+        "taker slippage added by the calibrated fill model",
+    ),
+    var(
+        "PMM_FILL_MAKER_QUEUE_AHEAD",
+        EnvKind::Number,
+        Some("1"),
+        "fraction of displayed size assumed queued ahead of a maker order",
+    ),
+    var(
+        "PMM_FILL_MAKER_HORIZON_SECS",
+        EnvKind::Number,
+        Some("60"),
+        "time a simulated maker order rests",
+    ),
+    var(
+        "PMM_FLATTEN_LEAD_SECONDS",
+        EnvKind::Count,
+        Some("30"),
+        "seconds before the end at which positions are flattened",
+    ),
+    var(
+        "PMM_FLATTEN_HOLD_INTO_RESOLUTION",
+        EnvKind::Bool,
+        Some("false"),
+        "hold positions with enough edge into resolution",
+    ),
+    var(
+        "PMM_FLATTEN_MIN_HOLD_EDGE",
+        EnvKind::Number,
+        Some("0.02"),
+        "edge needed to hold into resolution",
+    ),
+    var(
+        "PMM_PREMARKET_ENABLED",
+        EnvKind::Bool,
+        Some("false"),
+        "quote before the interval starts",
+    ),
+    var(
+        "PMM_PREMARKET_DURATIONS",
+        EnvKind::Custom(duration_list),
+        Some("4h,1d"),
+        "durations quoted pre-market",
+    ),
+    var(
+        "PMM_PREMARKET_WARMUP_SECS",
+        EnvKind::Count,
+        Some("60"),
+        "delay after orders open before pre-market quoting",
+    ),
+    var(
+        "PMM_PREMARKET_CUTOFF_SECS",
+        EnvKind::Count,
+        Some("30"),
+        "seconds before the start at which pre-market quoting stops",
+    ),
+    var(
+        "PMM_PREMARKET_MAX_ORDER_USDC",
+        EnvKind::Number,
+        Some("10"),
+        "largest pre-market order",
+    ),
+    var(
+        "PMM_PREMARKET_MAX_POSITION_USDC",
+        EnvKind::Number,
+        Some("25"),
+        "largest pre-market position",
+    ),
+    var(
+        "PMM_PREMARKET_MIN_EDGE",
+        EnvKind::Number,
+        Some("0.03"),
+        "edge required for pre-market quotes",
+    ),
+    var(
+        "PMM_QUOTE_MAX_ORDER_USDC",
+        EnvKind::Number,
+        Some("50"),
+        "largest in-interval order",
+    ),
+    var(
+        "PMM_QUOTE_MAX_POSITION_USDC",
+        EnvKind::Number,
+        Some("100"),
+        "largest in-interval position",
+    ),
+    var(
+        "PMM_QUOTE_MIN_EDGE",
+        EnvKind::Number,
+        Some("0.01"),
+        "edge required for in-interval quotes",
+    ),
+    var(
+        "PMM_MODEL_DIR",
+        EnvKind::Text,
+        Some("models"),
+        "directory of JSON model artifacts scoring probability; off disables",
+    ),
+    var(
+        "PMM_MODEL_INPUT_STORE_PATH",
+        EnvKind::Text,
+        Some("data/model_inputs.sqlite"),
+        "SQLite file capturing the feature vector of every live prediction; off disables",
+    ),
+    var(
+        "PMM_MODEL_INPUT_RETENTION_SECS",
+        EnvKind::Count,
+        Some("604800"),
+        "age after which captured model inputs are pruned",
+    ),
+    var(
+        "PMM_PROB_SMOOTHING_ALPHA",
+        EnvKind::Number,
+        Some("1"),
+        "EWMA weight of each new model probability, in (0, 1]; 1 disables averaging",
+    ),
+    var(
+        "PMM_PROB_HYSTERESIS",
+        EnvKind::Number,
+        Some("0"),
+        "smallest move of the smoothed probability that is published to quoting",
+    ),
+    var(
+        "PMM_PROB_SMOOTHING_DISPLAY",
+        EnvKind::Bool,
+        Some("false"),
+        "show the smoothed probability in the dashboard probability column",
+    ),
+    var(
+        "PMM_STRATEGY_DRY_RUN",
+        EnvKind::Bool,
+        None,
+        "log quoting intents instead of sending them; defaults to on unless PMM_MODE=live",
+    ),
+    var(
+        "PMM_STRATEGY_HALF_SPREAD",
+        EnvKind::Number,
+        Some("0.02"),
+        "distance of each quoting-engine bid below fair value",
+    ),
+    var(
+        "PMM_STRATEGY_ORDER_USDC",
+        EnvKind::Number,
+        Some("10"),
+        "notional of each quoting-engine bid",
+    ),
+    var(
+        "PMM_STRATEGY_SKEW_PER_SHARE",
+        EnvKind::Number,
+        Some("0.001"),
+        "fair-value shift per net YES share held",
+    ),
+    var(
+        "PMM_STRATEGY_MAX_EXPOSURE_USDC",
+        EnvKind::Number,
+        Some("100"),
+        "largest held plus bid notional per outcome",
+    ),
+    var(
+        "PMM_STRATEGY_OVERRIDES",
+        EnvKind::Custom(strategy_overrides),
+        None,
+        "per-duration quote parameters, e.g. 5m:half_spread=0.03,order_usdc=5;1d:max_exposure_usdc=50",
+    ),
+    var(
+        "PMM_CANARY_MARKETS",
+        EnvKind::Custom(canary_markets),
+        None,
+        "COIN:duration pairs quoted by the canary configuration, e.g. XRP:5m,SOL:5m; off disables",
+    ),
+    var(
+        "PMM_CANARY_PARAMS",
+        EnvKind::Custom(canary_params),
+        None,
+        "quote parameters the canary runs on top of the primary's, e.g. half_spread=0.03",
+    ),
+    var(
+        "PMM_STRATEGY_TOGGLE_STORE_PATH",
+        EnvKind::Text,
+        Some("data/strategy_toggles.sqlite"),
+        "SQLite file for coin/duration toggles; off keeps them in memory",
+    ),
+    var(
+        "PMM_JOURNAL_STORE_PATH",
+        EnvKind::Text,
+        Some("data/trade_journal.sqlite"),
+        "SQLite file for trade journal notes; off keeps them in memory",
+    ),
+    var(
+        "PMM_PAPER_LATENCY_MS",
+        EnvKind::Count,
+        Some("250"),
+        "paper venue delay before orders go live and cancels take effect",
+    ),
+    var(
+        "PMM_PAPER_QUEUE_AHEAD",
+        EnvKind::Number,
+        Some("1"),
+        "share of the displayed level a paper order queues behind, 0..=1",
+    ),
+    var(
+        "PMM_PAPER_STORE_PATH",
+        EnvKind::Text,
+        Some("data/paper_fills.sqlite"),
+        "SQLite file for simulated paper fills; off keeps them in memory",
+    ),
+    var(
+        "PMM_DECISION_STORE_PATH",
+        EnvKind::Text,
+        Some("data/decisions.sqlite"),
+        "SQLite file for strategy decisions read back by pmm replay; off disables",
+    ),
+    var(
+        "PMM_CAPITAL_THROTTLE_START",
+        EnvKind::Number,
+        Some("0.6"),
+        "collateral utilization, 0..=1, above which new quotes are scaled down",
+    ),
+    var(
+        "PMM_CAPITAL_MIN_HEADROOM_USDC",
+        EnvKind::Number,
+        Some("10"),
+        "free wallet USDC kept out of new quotes; none are placed at or below it",
+    ),
+    var(
+        "PMM_SHUTDOWN_CANCEL_TIMEOUT_MS",
+        EnvKind::Count,
+        Some("5000"),
+        "wait for cancel acknowledgements on shutdown",
+    ),
+    var(
+        "PMM_SHUTDOWN_POLL_MS",
+        EnvKind::Count,
+        Some("100"),
+        "poll cadence while waiting for cancel acknowledgements",
+    ),
+    var(
+        "PMM_SHUTDOWN_STATE_PATH",
+        EnvKind::Text,
+        Some("data/shutdown_state.json"),
+        "where the shutdown report is written; off skips it",
+    ),
+    var(
+        "PMM_ORDER_RATE_GLOBAL_PER_MIN",
+        EnvKind::Custom(count_or_off),
+        Some("1200"),
+        "order placements and cancels per rolling minute across all markets; off or 0 lifts it",
+    ),
+    var(
+        "PMM_ORDER_RATE_MARKET_PER_MIN",
+        EnvKind::Custom(count_or_off),
+        Some("120"),
+        "order placements and cancels per rolling minute of one market; off or 0 lifts it",
+    ),
+    var(
+        "PMM_ORDER_RATE_MAX_WAIT_MS",
+        EnvKind::Count,
+        Some("10000"),
+        "longest a throttled order operation queues before it fails as rate limited",
+    ),
+    // Wallet and rewards
+    secret(
+        "PMM_WALLET_PRIVATE_KEY",
+        EnvKind::Text,
+        "trading wallet key, 32-byte hex; required in live mode",
+    ),
+    secret(
+        "PMM_CLOB_API_KEY",
+        EnvKind::Text,
+        "CLOB API key; set with its secret and passphrase or not at all",
+    ),
+    secret("PMM_CLOB_API_SECRET", EnvKind::Text, "CLOB API secret"),
+    secret(
+        "PMM_CLOB_API_PASSPHRASE",
+        EnvKind::Text,
+        "CLOB API passphrase",
+    ),
+    var(
+        "PMM_CLOB_URL",
+        EnvKind::Url { allow_off: false },
+        Some(DEFAULT_CLOB_URL),
+        "CLOB trading API orders are placed on",
+    ),
+    var(
+        "PMM_CLOB_WALLET_KIND",
+        EnvKind::OneOf(&["eoa", "proxy", "gnosis_safe"]),
+        Some("eoa"),
+        "how the trading wallet holds funds; proxy/gnosis_safe sign for PMM_WALLET_ADDRESS",
+    ),
+    var(
+        "PMM_WALLET_ADDRESS",
+        EnvKind::Text,
+        None,
+        "wallet whose rewards reward_reconcile pulls; funder of proxy/Safe trading wallets",
+    ),
+    var(
+        "PMM_DATA_API_URL",
+        EnvKind::Url { allow_off: false },
+        Some(DEFAULT_DATA_API_URL),
+        "Polymarket Data API",
+    ),
+    var(
+        "PMM_REWARD_ACCRUAL_STORE_PATH",
+        EnvKind::Text,
+        Some("data/reward_accruals.sqlite"),
+        "SQLite file for expected reward accruals",
+    ),
+    var(
+        "PMM_REWARD_LOOKBACK_DAYS",
+        EnvKind::Count,
+        Some("7"),
+        "accrual days reward_reconcile compares",
+    ),
+    var(
+        "PMM_REWARD_PAYOUT_LAG_DAYS",
+        EnvKind::Count,
+        Some("1"),
+        "days between accrual and payout",
+    ),
+    var(
+        "PMM_REWARD_ABS_TOLERANCE_USDC",
+        EnvKind::Number,
+        Some("0.5"),
+        "absolute reward discrepancy tolerated",
+    ),
+    var(
+        "PMM_REWARD_REL_TOLERANCE",
+        EnvKind::Number,
+        Some("0.1"),
+        "relative reward discrepancy tolerated",
+    ),
+    // Operations
+    var(
+        "PMM_ALERT_STORE_PATH",
+        EnvKind::Text,
+        Some("data/alerts.sqlite"),
+        "SQLite file for alert state; off disables alerts",
+    ),
+    var(
+        "PMM_ALERT_RENOTIFY_SECS",
+        EnvKind::Count,
+        Some("3600"),
+        "delay before a still-firing alert notifies again",
+    ),
+    var(
+        "PMM_WEBHOOK_URL",
+        EnvKind::Url { allow_off: false },
+        None,
+        "receiver of lifecycle webhooks",
+    ),
+    secret(
+        "PMM_WEBHOOK_SECRET",
+        EnvKind::Text,
+        "HMAC key signing webhook bodies",
+    ),
+    var(
+        "PMM_WEBHOOK_EVENTS",
+        EnvKind::Custom(webhook_events),
+        None,
+        "webhook events to send; default all",
+    ),
+    var(
+        "PMM_WEBHOOK_TIMEOUT_MS",
+        EnvKind::Count,
+        Some("5000"),
+        "timeout of one webhook delivery",
+    ),
+    var(
+        "PMM_WEBHOOK_MAX_RETRIES",
+        EnvKind::Count,
+        Some("3"),
+        "retries per webhook delivery",
+    ),
+    var(
+        "PMM_CLOCK_NTP_SERVER",
+        EnvKind::Text,
+        Some("pool.ntp.org:123"),
+        "SNTP server for clock drift checks; off skips it",
+    ),
+    var(
+        "PMM_CLOCK_BINANCE_TIME_URL",
+        EnvKind::Url { allow_off: true },
+        Some("https://api.binance.com/api/v3/time"),
+        "Binance server time for clock drift checks",
+    ),
+    var(
+        "PMM_CLOCK_CHECK_INTERVAL_MS",
+        EnvKind::Count,
+        Some("300000"),
+        "clock drift check cadence",
+    ),
+    var(
+        "PMM_CLOCK_TIMEOUT_MS",
+        EnvKind::Count,
+        Some("2000"),
+        "timeout of one clock source query",
+    ),
+    var(
+        "PMM_CLOCK_DRIFT_WARN_MS",
+        EnvKind::Count,
+        Some("250"),
+        "offset logged as a warning",
+    ),
+    var(
+        "PMM_CLOCK_DRIFT_ALERT_MS",
+        EnvKind::Count,
+        Some("1000"),
+        "offset logged as an error",
+    ),
+    var(
+        "PMM_GRAFANA_SAMPLE_MS",
+        EnvKind::Custom(count_or_off),
+        Some("5000"),
+        "snapshot recording cadence; off disables sampling and the Grafana routes",
+    ),
+    var(
+        "PMM_GRAFANA_RETENTION_SECS",
+        EnvKind::Count,
+        Some("2592000"),
+        "age after which recorded samples are dropped",
+    ),
+    var(
+        "PMM_GRAFANA_FULL_RES_SECS",
+        EnvKind::Count,
+        Some("86400"),
+        "age up to which samples keep full resolution",
+    ),
+    var(
+        "PMM_GRAFANA_DECIMATED_SECS",
+        EnvKind::Count,
+        Some("604800"),
+        "age up to which samples are decimated rather than bucketed",
+    ),
+    var(
+        "PMM_GRAFANA_DECIMATE_EVERY",
+        EnvKind::Count,
+        Some("10"),
+        "sample intervals per kept sample once decimated",
+    ),
+    var(
+        "PMM_GRAFANA_COARSE_BUCKET_SECS",
+        EnvKind::Count,
+        Some("3600"),
+        "bucket width for the oldest samples",
+    ),
+    var(
+        "PMM_GRAFANA_COMPACT_INTERVAL_SECS",
+        EnvKind::Count,
+        Some("300"),
+        "cadence of sample compaction",
+    ),
+    var(
+        "PMM_SNAPSHOT_STORE_PATH",
+        EnvKind::Text,
+        Some("data/snapshots.sqlite"),
+        "SQLite file for the snapshot history behind /dashboard/history; off disables",
+    ),
+    var(
+        "PMM_SNAPSHOT_STORE_SAMPLE_MS",
+        EnvKind::Count,
+        Some("1000"),
+        "how often changed dashboard rows are appended to the snapshot history",
+    ),
+    var(
+        "PMM_SNAPSHOT_STORE_RETENTION_SECS",
+        EnvKind::Count,
+        Some("2592000"),
+        "age after which snapshot history rows are pruned",
+    ),
+    var(
+        "PMM_QUALITY_STORE_PATH",
+        EnvKind::Text,
+        Some("data/quality.sqlite"),
+        "SQLite file for quality scorecards; off keeps them in memory",
+    ),
+    var(
+        "PMM_QUALITY_INTERVAL_SECS",
+        EnvKind::Count,
+        Some("300"),
+        "quality scorecard update cadence",
+    ),
+    var(
+        "PMM_BENCH_TRANSFORM_DAYS",
+        EnvKind::Count,
+        Some("1"),
+        "synthetic days loaded by the feature_transform benchmark",
+    ),
+];
+
+/// The registry entry for `name`.
+pub fn env_var(name: &str) -> Option<&'static EnvVar> {
+    ENV_VARS.iter().find(|var| var.name == name)
+}
+
+/// A type a setting parses into. Input is trimmed; `None` rejects it.
+pub trait EnvValue: Sized {
+    fn parse_env(raw: &str) -> Option<Self>;
+
+    /// Value of a setting that is unset and has no registry default.
+    fn unset() -> Option<Self> {
+        None
+    }
+}
+
+macro_rules! env_value_from_str {
+    ($($ty:ty),+) => {
+        $(impl EnvValue for $ty {
+            fn parse_env(raw: &str) -> Option<Self> {
+                raw.parse().ok()
+            }
+        })+
+    };
+}
+
+env_value_from_str!(u16, u32, u64, usize, i64, std::net::SocketAddr);
+
+/// Finite numbers only.
+impl EnvValue for f64 {
+    fn parse_env(raw: &str) -> Option<Self> {
+        raw.parse().ok().filter(|value: &f64| value.is_finite())
+    }
+}
+
+/// `true|false|1|0`, any case.
+impl EnvValue for bool {
+    fn parse_env(raw: &str) -> Option<Self> {
+        match raw.to_ascii_lowercase().as_str() {
+            "1" | "true" => Some(true),
+            "0" | "false" => Some(false),
+            _ => None,
+        }
+    }
+}
+
+/// Non-empty.
+impl EnvValue for String {
+    fn parse_env(raw: &str) -> Option<Self> {
+        (!raw.is_empty()).then(|| raw.to_string())
+    }
+}
+
+/// Non-empty.
+impl EnvValue for PathBuf {
+    fn parse_env(raw: &str) -> Option<Self> {
+        (!raw.is_empty()).then(|| PathBuf::from(raw))
+    }
+}
+
+/// Comma list; blank entries are dropped and an unset list is empty.
+impl EnvValue for Vec<String> {
+    fn parse_env(raw: &str) -> Option<Self> {
+        Some(
+            raw.split(',')
+                .map(str::trim)
+                .filter(|entry| !entry.is_empty())
+                .map(str::to_string)
+                .collect(),
+        )
+    }
+
+    fn unset() -> Option<Self> {
+        Some(Vec::new())
+    }
+}
+
+/// `off` (any case) is `None`, as is a setting that is unset without a default.
+impl<T: EnvValue> EnvValue for Option<T> {
+    fn parse_env(raw: &str) -> Option<Self> {
+        if raw.eq_ignore_ascii_case("off") {
+            return Some(None);
+        }
+        T::parse_env(raw).map(Some)
+    }
+
+    fn unset() -> Option<Self> {
+        Some(None)
+    }
+}
+
+/// Where the `PMM_*` settings come from: the process environment, then the values of
+/// the config file (see [`crate::config`]), falling back to each variable's
+/// [`ENV_VARS`] default. A set value that does not parse falls back to the default too,
+/// as [`validate_env`] reports. Binaries build one at startup and pass it, or the
+/// configs built from it, to what they construct.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Settings {
+    /// Read the process environment; off for [`Settings::defaults`].
+    env: bool,
+    /// Config file values by variable name, below the environment.
+    file: BTreeMap<&'static str, String>,
+}
+
+impl Settings {
+    /// The process environment over the registry defaults.
+    pub fn from_env() -> Self {
+        Self {
+            env: true,
+            ..Self::default()
+        }
+    }
+
+    /// The registry defaults alone; configs build their `Default` from these.
+    pub fn defaults() -> Self {
+        Self::default()
+    }
+
+    /// These settings with `values` (variable name, value) under the environment.
+    pub fn with_file_values(
+        mut self,
+        values: impl IntoIterator<Item = (&'static str, String)>,
+    ) -> Self {
+        self.file.extend(values);
+        self
+    }
+
+    /// The value `name` is set to, untrimmed; `None` when unset.
+    pub fn value(&self, name: &str) -> Option<String> {
+        if self.env {
+            if let Ok(value) = std::env::var(name) {
+                return Some(value);
+            }
+        }
+        self.file.get(name).cloned()
+    }
+
+    /// Whether `name` takes its value from the config file.
+    pub fn is_from_file(&self, name: &str) -> bool {
+        self.file.contains_key(name) && !(self.env && std::env::var_os(name).is_some())
+    }
+
+    /// Names of the variables set, registered or not.
+    pub fn names(&self) -> Vec<String> {
+        let mut names = if self.env {
+            process_env_names()
+        } else {
+            Vec::new()
+        };
+        for name in self.file.keys() {
+            if !names.iter().any(|set| set == name) {
+                names.push(name.to_string());
+            }
+        }
+        names
+    }
+
+    /// [`validate_env`] on these settings.
+    pub fn validate(&self) -> EnvValidation {
+        validate_env(&|name| self.value(name), &self.names())
+    }
+
+    /// [`effective_env`] on these settings; config file values report
+    /// [`EnvValueSource::File`].
+    pub fn effective(&self) -> Vec<EffectiveEnvVar> {
+        let mut effective = effective_env(&|name| self.value(name));
+        for var in &mut effective {
+            if var.source == EnvValueSource::Env && self.is_from_file(var.name) {
+                var.source = EnvValueSource::File;
+            }
+        }
+        effective
+    }
+
+    /// `name` as a `T` when set to a value that parses, ignoring the registry default.
+    pub fn explicit<T: EnvValue>(&self, name: &str) -> Option<T> {
+        self.value(name).and_then(|raw| T::parse_env(raw.trim()))
+    }
+
+    /// `name` parsed by `parse`, else its registry default parsed the same way.
+    ///
+    /// # Panics
+    /// When `name` is not registered.
+    pub fn parsed<T>(&self, name: &str, parse: impl Fn(&str) -> Option<T>) -> Option<T> {
+        let var = env_var(name).unwrap_or_else(|| panic!("{name} is not in ENV_VARS"));
+        self.value(name)
+            .and_then(|raw| parse(raw.trim()))
+            .or_else(|| var.default.and_then(&parse))
+    }
+
+    /// `name` as a `T`, else its registry default.
+    ///
+    /// # Panics
+    /// When `name` is not registered, or neither value parses and `T` has no unset
+    /// value; `Option<T>` always has one.
+    pub fn get<T: EnvValue>(&self, name: &str) -> T {
+        self.get_where(name, |_| true)
+    }
+
+    /// [`Settings::get`] that also rejects values failing `accept`.
+    pub fn get_where<T: EnvValue>(&self, name: &str, accept: impl Fn(&T) -> bool) -> T {
+        self.parsed(name, |raw| T::parse_env(raw).filter(|value| accept(value)))
+            .or_else(T::unset)
+            .unwrap_or_else(|| {
+                panic!(
+                    "{name} has no default usable as {}",
+                    std::any::type_name::<T>()
+                )
+            })
+    }
+}
+
+/// [`Settings::get`] on the process environment.
+pub fn get<T: EnvValue>(name: &str) -> T {
+    Settings::from_env().get(name)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InvalidEnvValue {
+    pub name: &'static str,
+    /// `None` for secrets.
+    pub value: Option<String>,
+    pub problem: String,
+}
+
+/// Outcome of checking the environment against [`ENV_VARS`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct EnvValidation {
+    /// Registered variables that are set.
+    pub set: usize,
+    pub invalid: Vec<InvalidEnvValue>,
+    /// Set `PMM_*`/`PMFLIPS_*` names missing from the registry, sorted.
+    pub unknown: Vec<String>,
+}
+
+impl EnvValidation {
+    pub fn is_clean(&self) -> bool {
+        self.invalid.is_empty() && self.unknown.is_empty()
+    }
+
+    /// Logs `config.env.invalid` and `config.env.unknown` once per problem.
+    pub fn log(&self) {
+        for invalid in &self.invalid {
+            warn!(
+                component = "config",
+                event = "config.env.invalid",
+                name = invalid.name,
+                value = invalid.value.as_deref().unwrap_or("<redacted>"),
+                problem = %invalid.problem
+            );
+        }
+        for name in &self.unknown {
+            warn!(
+                component = "config",
+                event = "config.env.unknown",
+                name = %name
+            );
+        }
+    }
+}
+
+/// Checks registered values via `env` and reports unknown names among `names`.
+pub fn validate_env(
+    env: &(dyn Fn(&str) -> Option<String> + Sync),
+    names: &[String],
+) -> EnvValidation {
+    let mut validation = EnvValidation::default();
+    for var in ENV_VARS {
+        let Some(raw) = env(var.name) else {
+            continue;
+        };
+        validation.set += 1;
+        if let Err(problem) = var.kind.validate(&raw) {
+            validation.invalid.push(InvalidEnvValue {
+                name: var.name,
+                value: (!var.secret).then_some(raw),
+                problem,
+            });
+        }
+    }
+    validation.unknown = unknown_env_names(names);
+    validation
+}
+
+/// [`validate_env`] against the process environment.
+pub fn validate_process_env() -> EnvValidation {
+    validate_env(&|name| std::env::var(name).ok(), &process_env_names())
+}
+
+/// Names in `names` with a checked prefix but no registry entry, sorted and deduped.
+pub fn unknown_env_names(names: &[String]) -> Vec<String> {
+    names
+        .iter()
+        .filter(|name| ENV_PREFIXES.iter().any(|prefix| name.starts_with(prefix)))
+        .filter(|name| env_var(name).is_none())
+        .cloned()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Names of every variable in the process environment.
+pub fn process_env_names() -> Vec<String> {
+    std::env::vars_os()
+        .filter_map(|(name, _)| name.into_string().ok())
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EnvValueSource {
+    Env,
+    /// The config file, for a variable the environment leaves unset.
+    File,
+    Default,
+    /// Set but invalid, so the module uses its default.
+    InvalidFallback,
+    Unset,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EffectiveEnvVar {
+    pub name: &'static str,
+    /// `<redacted>` for set secrets.
+    pub value: Option<String>,
+    pub source: EnvValueSource,
+    pub description: &'static str,
+}
+
+/// The value each registered variable takes effect with.
+pub fn effective_env(env: &(dyn Fn(&str) -> Option<String> + Sync)) -> Vec<EffectiveEnvVar> {
+    ENV_VARS
+        .iter()
+        .map(|var| {
+            let default = var.default.map(str::to_string);
+            let (value, source) = match env(var.name) {
+                Some(_) if var.secret => (Some("<redacted>".to_string()), EnvValueSource::Env),
+                Some(raw) if var.kind.validate(&raw).is_ok() => (Some(raw), EnvValueSource::Env),
+                Some(_) => (default, EnvValueSource::InvalidFallback),
+                None if default.is_some() => (default, EnvValueSource::Default),
+                None => (None, EnvValueSource::Unset),
+            };
+            EffectiveEnvVar {
+                name: var.name,
+                value,
+                source,
+                description: var.description,
+            }
+        })
+        .collect()
+}
+
+/// Plain-text table of [`effective_env`] followed by the validation problems.
+pub fn render_env_table(effective: &[EffectiveEnvVar], validation: &EnvValidation) -> String {
+    let name_width = effective
+        .iter()
+        .map(|var| var.name.len())
+        .max()
+        .unwrap_or(0);
+    let mut out = String::new();
+    for var in effective {
+        let source = match var.source {
+            EnvValueSource::Env => "env",
+            EnvValueSource::File => "file",
+            EnvValueSource::Default => "default",
+            EnvValueSource::InvalidFallback => "INVALID",
+            EnvValueSource::Unset => "unset",
+        };
+        out.push_str(&format!(
+            "{:<name_width$}  {:<8} {}  # {}\n",
+            var.name,
+            source,
+            var.value.as_deref().unwrap_or("-"),
+            var.description
+        ));
+    }
+    for invalid in &validation.invalid {
+        out.push_str(&format!(
+            "invalid: {}={}: {}; using the default\n",
+            invalid.name,
+            invalid
+                .value
+                .as_deref()
+                .map_or_else(|| "<redacted>".to_string(), |value| format!("{value:?}")),
+            invalid.problem
+        ));
+    }
+    for name in &validation.unknown {
+        out.push_str(&format!("unknown: {name} is not read by pmm\n"));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env_from(pairs: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> + Sync {
+        let pairs = pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect::<Vec<_>>();
+        move |name| {
+            pairs
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.clone())
+        }
+    }
+
+    #[test]
+    fn settings_fall_back_to_the_registered_default() {
+        let defaults = Settings::defaults();
+        assert_eq!(defaults.get::<u64>("PMM_CLOB_PING_INTERVAL_MS"), 10_000);
+        assert_eq!(
+            defaults.get::<Option<PathBuf>>("PMM_JOURNAL_STORE_PATH"),
+            Some(PathBuf::from("data/trade_journal.sqlite"))
+        );
+        assert_eq!(defaults.get::<Option<PathBuf>>("PMM_LOG_DIR"), None);
+        assert!(defaults.get::<Vec<String>>("PMM_WEBHOOK_EVENTS").is_empty());
+        assert_eq!(defaults.explicit::<u64>("PMM_CLOB_PING_INTERVAL_MS"), None);
+        assert!(defaults.names().is_empty());
+    }
+
+    #[test]
+    fn file_values_override_the_registered_default() {
+        let settings = Settings::defaults().with_file_values([
+            ("PMM_CLOB_PING_INTERVAL_MS", "2500".to_string()),
+            ("PMM_LOG_FORMAT", "yaml".to_string()),
+        ]);
+        assert_eq!(settings.get::<u64>("PMM_CLOB_PING_INTERVAL_MS"), 2_500);
+        assert_eq!(
+            settings.names(),
+            ["PMM_CLOB_PING_INTERVAL_MS", "PMM_LOG_FORMAT"]
+        );
+        let effective = settings.effective();
+        let ping = effective
+            .iter()
+            .find(|var| var.name == "PMM_CLOB_PING_INTERVAL_MS")
+            .unwrap();
+        assert_eq!(ping.source, EnvValueSource::File);
+        assert_eq!(ping.value.as_deref(), Some("2500"));
+        let invalid: Vec<_> = settings
+            .validate()
+            .invalid
+            .iter()
+            .map(|invalid| invalid.name)
+            .collect();
+        assert_eq!(invalid, ["PMM_LOG_FORMAT"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "PMM_NOT_A_SETTING is not in ENV_VARS")]
+    fn settings_refuse_unregistered_names() {
+        Settings::defaults().get::<u64>("PMM_NOT_A_SETTING");
+    }
+
+    #[test]
+    #[should_panic(expected = "PMM_LOG_DIR has no default usable as")]
+    fn required_settings_without_a_default_panic() {
+        Settings::defaults().get::<PathBuf>("PMM_LOG_DIR");
+    }
+
+    #[test]
+    fn env_values_parse_as_their_types() {
+        assert_eq!(bool::parse_env("TRUE"), Some(true));
+        assert_eq!(bool::parse_env("yes"), None);
+        assert_eq!(f64::parse_env("inf"), None);
+        assert_eq!(String::parse_env(""), None);
+        assert_eq!(Option::<u64>::parse_env("Off"), Some(None));
+        assert_eq!(Option::<u64>::parse_env("7"), Some(Some(7)));
+        assert_eq!(Option::<u64>::parse_env("seven"), None);
+        assert_eq!(
+            Vec::<String>::parse_env("a, ,b"),
+            Some(vec!["a".to_string(), "b".to_string()])
+        );
+    }
+
+    #[test]
+    fn every_env_var_read_in_src_is_registered() {
+        let root = std::path::Path::new(env!("CARGO_MANIFEST_DIR"));
+        let mut dirs = vec![root.join("src"), root.join("benches")];
+        let mut missing = BTreeSet::new();
+        while let Some(dir) = dirs.pop() {
+            for entry in std::fs::read_dir(dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    dirs.push(path);
+                    continue;
+                }
+                let source = std::fs::read_to_string(&path).unwrap();
+                // Test modules use made-up names on purpose.
+                let source = source.split("#[cfg(test)]").next().unwrap_or_default();
+                let literals = source.split('"').filter(|chunk| {
+                    ENV_PREFIXES
+                        .iter()
+                        .any(|prefix| chunk.len() > prefix.len() && chunk.starts_with(prefix))
+                        && chunk.bytes().all(|byte| {
+                            byte.is_ascii_uppercase() || byte.is_ascii_digit() || byte == b'_'
+                        })
+                });
+                for literal in literals {
+                    // Prefixes such as `PMM_QUOTE` build their names with `format!`.
+                    let prefix = format!("{literal}_");
+                    if env_var(literal).is_none()
+                        && !ENV_VARS.iter().any(|var| var.name.starts_with(&prefix))
+                    {
+                        missing.insert(literal.to_string());
+                    }
+                }
+            }
+        }
+        assert!(missing.is_empty(), "unregistered env vars: {missing:?}");
+
+        let names: BTreeSet<_> = ENV_VARS.iter().map(|var| var.name).collect();
+        assert_eq!(names.len(), ENV_VARS.len(), "duplicate registry entries");
+        for var in ENV_VARS {
+            if let Some(default) = var.default {
+                assert_eq!(var.kind.validate(default), Ok(()), "{} default", var.name);
+            }
+        }
+    }
+
+    #[test]
+    fn validation_reports_ill_typed_and_unknown_names_without_echoing_secrets() {
+        let env = env_from(&[
+            ("PMM_DASHBOARD_ADDR", "localhost"),
+            ("PMM_UNIVERSE_COINS", "BTC,DOGEE"),
+            ("PMM_LOG_FORMAT", "JSON"),
+            ("PMM_WEBHOOK_SECRET", "s3cret"),
+            ("PMM_CLOB_WS_URL", "https://example.com"),
+        ]);
+        let names = [
+            "PMM_DASHBOARD_ADDR",
+            "PMM_DASHBORD_ADDR",
+            "PMFLIPS_MODE",
+            "HOME",
+        ]
+        .map(str::to_string);
+        let validation = validate_env(&env, &names);
+        assert_eq!(validation.set, 5);
+        let invalid: Vec<_> = validation.invalid.iter().map(|entry| entry.name).collect();
+        assert_eq!(
+            invalid,
+            [
+                "PMM_DASHBOARD_ADDR",
+                "PMM_UNIVERSE_COINS",
+                "PMM_CLOB_WS_URL"
+            ]
+        );
+        assert!(validation.invalid[1].problem.contains("DOGEE"));
+        assert_eq!(validation.unknown, ["PMFLIPS_MODE", "PMM_DASHBORD_ADDR"]);
+
+        let effective = effective_env(&env);
+        let find = |name| effective.iter().find(|var| var.name == name).unwrap();
+        assert_eq!(find("PMM_LOG_FORMAT").source, EnvValueSource::Env);
+        let addr = find("PMM_DASHBOARD_ADDR");
+        assert_eq!(addr.source, EnvValueSource::InvalidFallback);
+        assert_eq!(addr.value.as_deref(), Some("127.0.0.1:8080"));
+        assert_eq!(find("PMM_MODE").source, EnvValueSource::Default);
+        assert_eq!(find("PMM_WEBHOOK_URL").source, EnvValueSource::Unset);
+
+        let table = render_env_table(&effective, &validation);
+        assert!(!table.contains("s3cret"));
+        assert!(table.contains("unknown: PMM_DASHBORD_ADDR"));
+        assert!(table.contains("invalid: PMM_DASHBOARD_ADDR=\"localhost\""));
+    }
+}
@@ -0,0 +1,157 @@
+//! Fault injection for resilience tests (`fault-injection` feature).
+//!
+//! Production code asks this module before Gamma slug lookups, kline store reads and
+//! writes, and when reading the clock of the live dashboard loop. With the feature off
+//! the hooks are inlined no-ops; with it on, [`inject_faults`] installs a
+//! [`FaultPlan`] process-wide until the returned [`FaultGuard`] drops. Guards are
+//! serialized, so tests that inject faults never observe each other's plans.
+
+use chrono::{DateTime, Utc};
+
+/// Failure to inject into the next Gamma lookup attempt of a slug.
+#[cfg_attr(not(feature = "fault-injection"), allow(dead_code))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum GammaFault {
+    Timeout,
+    Transport,
+}
+
+#[cfg_attr(not(feature = "discovery-sdk"), allow(dead_code))]
+impl GammaFault {
+    /// Message recorded for the failed attempt, shaped like the real one.
+    pub(crate) fn message(&self, slug: &str, timeout_ms: u64) -> String {
+        match self {
+            Self::Timeout => {
+                format!("timeout after {timeout_ms}ms while resolving slug {slug} (injected)")
+            }
+            Self::Transport => format!("injected transport error for slug {slug}"),
+        }
+    }
+}
+
+#[cfg(feature = "fault-injection")]
+pub use enabled::{active_faults, inject_faults, FaultGuard, FaultPlan};
+
+#[cfg(feature = "fault-injection")]
+mod enabled {
+    use std::collections::HashSet;
+    use std::sync::{Mutex, MutexGuard};
+
+    use super::GammaFault;
+
+    /// Faults to inject; counted faults are consumed one per affected operation.
+    #[derive(Debug, Clone, Default, PartialEq, Eq)]
+    pub struct FaultPlan {
+        /// Gamma lookup attempts (any slug) that time out.
+        pub gamma_timeouts: u32,
+        /// Slugs whose every lookup attempt fails with a transport error: a partial
+        /// batch failure.
+        pub gamma_failing_slugs: HashSet<String>,
+        /// Kline store operations that fail with `SQLITE_BUSY`.
+        pub sqlite_busy: u32,
+        /// Added to the wall clock seen by the live dashboard loop and snapshot sources.
+        pub clock_offset_ms: i64,
+    }
+
+    static PLAN: Mutex<Option<FaultPlan>> = Mutex::new(None);
+    static SERIAL: Mutex<()> = Mutex::new(());
+
+    /// Active plan; dropping it clears every fault.
+    pub struct FaultGuard {
+        _serial: MutexGuard<'static, ()>,
+    }
+
+    impl FaultGuard {
+        /// Edits the active plan, e.g. to jump the clock mid-test.
+        pub fn update(&self, apply: impl FnOnce(&mut FaultPlan)) {
+            if let Some(plan) = plan().as_mut() {
+                apply(plan);
+            }
+        }
+    }
+
+    impl Drop for FaultGuard {
+        fn drop(&mut self) {
+            *plan() = None;
+        }
+    }
+
+    /// Installs `faults`, blocking while another guard is alive.
+    pub fn inject_faults(faults: FaultPlan) -> FaultGuard {
+        let serial = SERIAL
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        *plan() = Some(faults);
+        FaultGuard { _serial: serial }
+    }
+
+    /// What is left of the active plan (`None` without a guard).
+    pub fn active_faults() -> Option<FaultPlan> {
+        plan().clone()
+    }
+
+    fn plan() -> MutexGuard<'static, Option<FaultPlan>> {
+        PLAN.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn take(counter: &mut u32) -> bool {
+        let hit = *counter > 0;
+        *counter = counter.saturating_sub(1);
+        hit
+    }
+
+    pub(super) fn gamma_fault(slug: &str) -> Option<GammaFault> {
+        let mut plan = plan();
+        let plan = plan.as_mut()?;
+        if plan.gamma_failing_slugs.contains(slug) {
+            Some(GammaFault::Transport)
+        } else {
+            take(&mut plan.gamma_timeouts).then_some(GammaFault::Timeout)
+        }
+    }
+
+    pub(super) fn sqlite_busy() -> bool {
+        plan()
+            .as_mut()
+            .is_some_and(|plan| take(&mut plan.sqlite_busy))
+    }
+
+    pub(super) fn clock_offset_ms() -> i64 {
+        plan().as_ref().map_or(0, |plan| plan.clock_offset_ms)
+    }
+}
+
+/// Injected failure for the next lookup attempt of `slug`, if any.
+#[cfg_attr(not(feature = "discovery-sdk"), allow(dead_code))]
+#[inline]
+pub(crate) fn gamma_fault(slug: &str) -> Option<GammaFault> {
+    #[cfg(feature = "fault-injection")]
+    return enabled::gamma_fault(slug);
+    #[cfg(not(feature = "fault-injection"))]
+    {
+        let _ = slug;
+        None
+    }
+}
+
+/// `Err(SQLITE_BUSY)` when a busy error is injected into this store operation.
+#[inline]
+pub(crate) fn sqlite_busy() -> rusqlite::Result<()> {
+    #[cfg(feature = "fault-injection")]
+    if enabled::sqlite_busy() {
+        return Err(rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_BUSY),
+            Some("database is locked (injected)".to_string()),
+        ));
+    }
+    Ok(())
+}
+
+/// Wall clock, shifted by any injected clock jump.
+#[inline]
+pub(crate) fn now_utc() -> DateTime<Utc> {
+    #[cfg(feature = "fault-injection")]
+    return Utc::now() + chrono::Duration::milliseconds(enabled::clock_offset_ms());
+    #[cfg(not(feature = "fault-injection"))]
+    Utc::now()
+}
@@ -2,15 +2,33 @@
 
 use std::collections::{HashSet, VecDeque};
 use std::f64::consts::PI;
-use std::path::Path;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration as StdDuration, Instant};
 
 use chrono::{Datelike, TimeZone, Timelike, Utc};
+use parquet::basic::{Compression, Repetition, Type as PhysicalType};
+use parquet::data_type::{DoubleType, Int64Type};
+use parquet::errors::ParquetError;
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::format::KeyValue;
+use parquet::schema::types::Type as ParquetType;
 use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use thiserror::Error;
 use tracing::{info, warn};
 
+use crate::binance_klines::{
+    ArchiveKlineSource, BinanceSymbol, HistoricalKlinesConfig, Kline1s, ALL_BINANCE_SYMBOLS,
+};
+use crate::kline_store::{frame_scan_sql, symbol_id, KlineStore, KlineStoreError};
+use crate::kline_validation::KlineValidationConfig;
+use crate::slug::Duration;
+use crate::warmup::KlineTailSource;
+
 const STEP_MS: i64 = 1_000;
 const SYMBOL_COUNT: usize = 4;
 const WEEK_SECONDS: f64 = 7.0 * 24.0 * 60.0 * 60.0;
@@ -61,16 +79,209 @@ pub struct FeatureTransformReport {
     pub input_points: u64,
     pub output_points: u64,
     pub skipped_points: u64,
+    /// Kline rows fetched into the store by `auto_backfill`.
+    #[serde(default)]
+    pub backfilled_points: u64,
     pub gap_ranges: Vec<(i64, i64)>,
     pub first_error: Option<String>,
 }
 
+/// Kline rows per latency sample of [`FeatureTransformTiming::chunk_secs_per_million`].
+pub const TRANSFORM_CHUNK_ROWS: u64 = 1_000_000;
+
+/// Where one transform spent its time.
+///
+/// Read time is SQLite stepping and column decoding; compute time is the rolling
+/// feature math. Each full chunk of [`TRANSFORM_CHUNK_ROWS`] kline rows yields one
+/// latency sample; a final partial chunk of at least a tenth of that is scaled up to a
+/// million rows, smaller ones are too noisy to sample.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FeatureTransformTiming {
+    pub rows_read: u64,
+    pub read_secs: f64,
+    pub compute_secs: f64,
+    pub rows_per_sec: f64,
+    pub chunk_secs_per_million: Vec<f64>,
+}
+
+/// Upper bounds (seconds per million kline rows) of the chunk latency histogram.
+pub const TRANSFORM_CHUNK_LATENCY_BUCKETS: [f64; 9] =
+    [0.25, 0.5, 1.0, 2.0, 4.0, 8.0, 16.0, 32.0, 64.0];
+
+/// Transform metrics accumulated since process start, for `/metrics`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FeatureTransformMetrics {
+    pub runs: u64,
+    pub rows_read: u64,
+    pub read_secs: f64,
+    pub compute_secs: f64,
+    /// Throughput of the most recent run.
+    pub last_rows_per_sec: f64,
+    /// Non-cumulative counts per [`TRANSFORM_CHUNK_LATENCY_BUCKETS`] bound, plus a
+    /// final `+Inf` bucket.
+    pub chunk_latency_buckets: Vec<u64>,
+    pub chunk_latency_sum_secs: f64,
+    pub chunk_latency_count: u64,
+}
+
+static TRANSFORM_METRICS: Mutex<Option<FeatureTransformMetrics>> = Mutex::new(None);
+
+/// Snapshot of the process-wide transform metrics.
+pub fn feature_transform_metrics() -> FeatureTransformMetrics {
+    TRANSFORM_METRICS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone()
+        .unwrap_or_default()
+}
+
+fn record_transform_metrics(timing: &FeatureTransformTiming) {
+    let mut metrics = TRANSFORM_METRICS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let metrics = metrics.get_or_insert_with(|| FeatureTransformMetrics {
+        chunk_latency_buckets: vec![0; TRANSFORM_CHUNK_LATENCY_BUCKETS.len() + 1],
+        ..FeatureTransformMetrics::default()
+    });
+    metrics.runs += 1;
+    metrics.rows_read += timing.rows_read;
+    metrics.read_secs += timing.read_secs;
+    metrics.compute_secs += timing.compute_secs;
+    metrics.last_rows_per_sec = timing.rows_per_sec;
+    for secs in &timing.chunk_secs_per_million {
+        let bucket = TRANSFORM_CHUNK_LATENCY_BUCKETS
+            .iter()
+            .position(|bound| secs <= bound)
+            .unwrap_or(TRANSFORM_CHUNK_LATENCY_BUCKETS.len());
+        metrics.chunk_latency_buckets[bucket] += 1;
+        metrics.chunk_latency_sum_secs += secs;
+        metrics.chunk_latency_count += 1;
+    }
+}
+
+/// Splits transform wall time into read and compute, and samples chunk latency.
+struct TransformClock {
+    started: Instant,
+    compute: StdDuration,
+    chunk_started: Instant,
+    chunk_rows: u64,
+    timing: FeatureTransformTiming,
+}
+
+impl TransformClock {
+    fn start() -> Self {
+        let now = Instant::now();
+        Self {
+            started: now,
+            compute: StdDuration::ZERO,
+            chunk_started: now,
+            chunk_rows: 0,
+            timing: FeatureTransformTiming::default(),
+        }
+    }
+
+    fn row_read(&mut self) {
+        self.timing.rows_read += 1;
+        self.chunk_rows += 1;
+        if self.chunk_rows == TRANSFORM_CHUNK_ROWS {
+            self.close_chunk();
+        }
+    }
+
+    fn compute<T>(&mut self, work: impl FnOnce() -> T) -> T {
+        let started = Instant::now();
+        let out = work();
+        self.compute += started.elapsed();
+        out
+    }
+
+    fn close_chunk(&mut self) {
+        if self.chunk_rows >= TRANSFORM_CHUNK_ROWS / 10 {
+            let secs = self.chunk_started.elapsed().as_secs_f64();
+            self.timing
+                .chunk_secs_per_million
+                .push(secs * TRANSFORM_CHUNK_ROWS as f64 / self.chunk_rows as f64);
+        }
+        self.chunk_started = Instant::now();
+        self.chunk_rows = 0;
+    }
+
+    fn finish(mut self) -> FeatureTransformTiming {
+        self.close_chunk();
+        let total = self.started.elapsed();
+        self.timing.compute_secs = self.compute.as_secs_f64();
+        self.timing.read_secs = total.saturating_sub(self.compute).as_secs_f64();
+        self.timing.rows_per_sec = match total.as_secs_f64() {
+            secs if secs > 0.0 => self.timing.rows_read as f64 / secs,
+            _ => 0.0,
+        };
+        self.timing
+    }
+}
+
+/// Named window sets sized to a market's horizon: second-scale for 5m markets up to
+/// hour-scale for 4h/1d markets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FeatureWindowPreset {
+    Short,
+    Medium,
+    Long,
+}
+
+pub const ALL_FEATURE_WINDOW_PRESETS: [FeatureWindowPreset; 3] = [
+    FeatureWindowPreset::Short,
+    FeatureWindowPreset::Medium,
+    FeatureWindowPreset::Long,
+];
+
+impl FeatureWindowPreset {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Short => "short",
+            Self::Medium => "medium",
+            Self::Long => "long",
+        }
+    }
+
+    pub fn parse(raw: &str) -> Option<Self> {
+        ALL_FEATURE_WINDOW_PRESETS
+            .into_iter()
+            .find(|preset| preset.as_str().eq_ignore_ascii_case(raw.trim()))
+    }
+
+    pub fn windows_seconds(self) -> Vec<u32> {
+        match self {
+            Self::Short => vec![5, 15, 60],
+            Self::Medium => vec![60, 300, 900],
+            Self::Long => vec![900, 3_600, 14_400],
+        }
+    }
+
+    /// Preset for a market duration: 5m -> short, 15m/1h -> medium, 4h/1d -> long.
+    pub fn for_duration(duration: Duration) -> Self {
+        match duration {
+            Duration::M5 => Self::Short,
+            Duration::M15 | Duration::H1 => Self::Medium,
+            Duration::H4 | Duration::D1 => Self::Long,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct FeatureTransformConfig {
     pub windows_seconds: Vec<u32>,
     pub max_duration_seconds: u32,
     pub gap_policy: GapPolicy,
     pub schema_version: u32,
+    /// Named window set; when set, `windows_seconds` must equal the preset's windows
+    /// and the preset name is part of the schema fingerprint.
+    #[serde(default)]
+    pub preset: Option<FeatureWindowPreset>,
+    /// Under [`GapPolicy::ReportAndSkip`], backfill gaps from the Binance loader into
+    /// the store and transform them instead of skipping them. Not part of the schema.
+    #[serde(default)]
+    pub auto_backfill: bool,
 }
 
 impl Default for FeatureTransformConfig {
@@ -80,10 +291,26 @@ impl Default for FeatureTransformConfig {
             max_duration_seconds: 86_400,
             gap_policy: GapPolicy::Strict,
             schema_version: FEATURE_SCHEMA_VERSION,
+            preset: None,
+            auto_backfill: false,
         }
     }
 }
 
+impl FeatureTransformConfig {
+    pub fn with_preset(preset: FeatureWindowPreset) -> Self {
+        Self {
+            windows_seconds: preset.windows_seconds(),
+            preset: Some(preset),
+            ..Self::default()
+        }
+    }
+
+    pub fn for_duration(duration: Duration) -> Self {
+        Self::with_preset(FeatureWindowPreset::for_duration(duration))
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct HorizonConditioning {
     pub log_horizon_norm: f64,
@@ -117,10 +344,24 @@ pub enum FeatureError {
         actual_ts_ms_utc: i64,
         missing_points: u64,
     },
+    #[error("frame at {ts_ms_utc} is not after the last frame at {last_ts_ms_utc}")]
+    OutOfOrderFrame { last_ts_ms_utc: i64, ts_ms_utc: i64 },
     #[error("schema version mismatch: expected {expected}, got {actual}")]
     SchemaVersionMismatch { expected: u32, actual: u32 },
     #[error("schema fingerprint mismatch: expected {expected}, got {actual}")]
     SchemaFingerprintMismatch { expected: String, actual: String },
+    #[error("feature row at {ts_ms_utc} has {actual} values; the schema has {expected} columns")]
+    RowWidthMismatch {
+        ts_ms_utc: i64,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("kline store error: {0}")]
+    KlineStore(#[from] KlineStoreError),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Parquet error: {0}")]
+    Parquet(#[from] parquet::errors::ParquetError),
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -333,6 +574,7 @@ pub fn build_feature_schema(cfg: &FeatureTransformConfig) -> FeatureSchema {
         event = "features.schema.built",
         version = cfg.schema_version,
         windows = ?cfg.windows_seconds,
+        preset = cfg.preset.map(FeatureWindowPreset::as_str),
         column_count = columns.len(),
         fingerprint = fingerprint
     );
@@ -349,6 +591,100 @@ pub fn transform_store_range(
     req: &FeatureTransformRequest,
     cfg: &FeatureTransformConfig,
 ) -> Result<(FeatureSchema, Vec<FeatureRow>, FeatureTransformReport), FeatureError> {
+    let (schema, rows, report, _) = transform_store_range_with_timing(store_path, req, cfg)?;
+    Ok((schema, rows, report))
+}
+
+/// [`transform_store_range`] that also returns where the time went. The timing is
+/// recorded into [`feature_transform_metrics`] either way.
+pub fn transform_store_range_with_timing(
+    store_path: &Path,
+    req: &FeatureTransformRequest,
+    cfg: &FeatureTransformConfig,
+) -> Result<
+    (
+        FeatureSchema,
+        Vec<FeatureRow>,
+        FeatureTransformReport,
+        FeatureTransformTiming,
+    ),
+    FeatureError,
+> {
+    let mut output_rows = Vec::new();
+    let (schema, report, timing) = transform_store_range_into(store_path, req, cfg, |row| {
+        output_rows.push(row);
+        Ok(())
+    })?;
+    Ok((schema, output_rows, report, timing))
+}
+
+/// Streams a transform of `req` into `parquet_path` (see [`FeatureParquetWriter`])
+/// instead of collecting its rows, so memory stays bounded by one row group however
+/// long the range.
+pub fn transform_store_range_to_parquet(
+    store_path: &Path,
+    req: &FeatureTransformRequest,
+    cfg: &FeatureTransformConfig,
+    parquet_path: &Path,
+) -> Result<(FeatureSchema, FeatureTransformReport), FeatureError> {
+    validate_request(req)?;
+    validate_config(cfg)?;
+    let mut writer = FeatureParquetWriter::create(parquet_path, &build_feature_schema(cfg))?;
+    match transform_store_range_into(store_path, req, cfg, |row| writer.write(&row)) {
+        Ok((schema, report, _)) => {
+            writer.finish()?;
+            Ok((schema, report))
+        }
+        Err(err) => {
+            writer.discard();
+            Err(err)
+        }
+    }
+}
+
+/// The transform proper: hands each output row to `sink` as soon as it is computed.
+/// Time spent in `sink` is counted as neither read nor compute time.
+///
+/// With `cfg.auto_backfill` under [`GapPolicy::ReportAndSkip`], gaps are first
+/// backfilled from the Binance Vision archives ([`HistoricalKlinesConfig::default`]);
+/// [`transform_store_range_with_backfill_into`] takes another source.
+pub fn transform_store_range_into(
+    store_path: &Path,
+    req: &FeatureTransformRequest,
+    cfg: &FeatureTransformConfig,
+    sink: impl FnMut(FeatureRow) -> Result<(), FeatureError>,
+) -> Result<
+    (
+        FeatureSchema,
+        FeatureTransformReport,
+        FeatureTransformTiming,
+    ),
+    FeatureError,
+> {
+    let mut archives = ArchiveKlineSource::new(HistoricalKlinesConfig::default());
+    transform_store_range_with_backfill_into(store_path, req, cfg, &mut archives, sink)
+}
+
+/// [`transform_store_range_into`] that backfills gaps from `source`. With
+/// `cfg.auto_backfill` set and [`GapPolicy::ReportAndSkip`], every continuity gap and
+/// incomplete frame of at most [`MAX_BACKFILL_POINTS`] seconds is fetched for the
+/// symbols missing from the store, upserted there, and read back in order before the
+/// transform moves on; only what is still missing goes through the gap policy. A failed
+/// fetch is logged and leaves the gap to the policy. Otherwise `source` is not used.
+pub fn transform_store_range_with_backfill_into(
+    store_path: &Path,
+    req: &FeatureTransformRequest,
+    cfg: &FeatureTransformConfig,
+    source: &mut dyn KlineTailSource,
+    mut sink: impl FnMut(FeatureRow) -> Result<(), FeatureError>,
+) -> Result<
+    (
+        FeatureSchema,
+        FeatureTransformReport,
+        FeatureTransformTiming,
+    ),
+    FeatureError,
+> {
     validate_request(req)?;
     validate_config(cfg)?;
 
@@ -359,48 +695,26 @@ pub fn transform_store_range(
         start_ts_ms_utc = req.start_ts_ms_utc,
         end_ts_ms_utc_exclusive = req.end_ts_ms_utc_exclusive,
         windows = ?cfg.windows_seconds,
-        gap_policy = ?cfg.gap_policy
+        gap_policy = ?cfg.gap_policy,
+        auto_backfill = cfg.auto_backfill
     );
 
     let schema = build_feature_schema(cfg);
+    // Opened (and migrated) before the scan starts so its writes never race the reader.
+    let mut backfill = if cfg.auto_backfill && cfg.gap_policy == GapPolicy::ReportAndSkip {
+        Some(GapBackfill {
+            store: KlineStore::open(store_path, KlineValidationConfig::default())?,
+            source,
+        })
+    } else {
+        None
+    };
+    let mut run = TransformRun::new(req, cfg);
     let conn = Connection::open(store_path)?;
-    let mut stmt = conn.prepare(
-        "
-        SELECT
-            open_time_ms,
-            symbol_id,
-            high,
-            low,
-            close,
-            quote_asset_volume
-        FROM klines_1s
-        WHERE open_time_ms >= ?1
-          AND open_time_ms < ?2
-        ORDER BY open_time_ms ASC, symbol_id ASC
-        ",
-    )?;
+    let mut stmt = conn.prepare(&frame_scan_sql(&conn)?)?;
 
     let mut rows = stmt.query(params![req.start_ts_ms_utc, req.end_ts_ms_utc_exclusive])?;
-    let mut report = FeatureTransformReport {
-        input_points: expected_points(req.start_ts_ms_utc, req.end_ts_ms_utc_exclusive),
-        output_points: 0,
-        skipped_points: 0,
-        gap_ranges: Vec::new(),
-        first_error: None,
-    };
-
-    let max_window = cfg.windows_seconds.iter().copied().max().unwrap_or(1) as usize;
-    let windows_usize: Vec<usize> = cfg
-        .windows_seconds
-        .iter()
-        .copied()
-        .map(|w| w as usize)
-        .collect();
-    let mut state = TransformState::new(max_window.max(1));
-
     let mut current_frame: Option<Frame> = None;
-    let mut last_seen_ts: Option<i64> = None;
-    let mut output_rows = Vec::new();
 
     while let Some(row) = rows.next()? {
         let ts_ms_utc: i64 = row.get(0)?;
@@ -414,22 +728,17 @@ pub fn transform_store_range(
         if ts_ms_utc % STEP_MS != 0 {
             return Err(FeatureError::InvalidTimestamp(ts_ms_utc));
         }
+        run.clock.row_read();
 
         match current_frame.as_mut() {
             Some(frame) if frame.ts_ms_utc == ts_ms_utc => {
                 insert_frame_point(frame, symbol_id, ts_ms_utc, point)?;
             }
             Some(frame) => {
-                process_frame(
-                    frame,
-                    req,
-                    cfg,
-                    &mut state,
-                    &windows_usize,
-                    &mut last_seen_ts,
-                    &mut report,
-                    &mut output_rows,
-                )?;
+                if let Some(backfill) = backfill.as_mut() {
+                    run.backfill_before(backfill, frame, &mut sink)?;
+                }
+                run.emit(frame, &mut sink)?;
 
                 let mut next_frame = Frame::new(ts_ms_utc);
                 insert_frame_point(&mut next_frame, symbol_id, ts_ms_utc, point)?;
@@ -443,19 +752,26 @@ pub fn transform_store_range(
         }
     }
 
-    if let Some(frame) = current_frame.take() {
-        process_frame(
-            &frame,
-            req,
-            cfg,
-            &mut state,
-            &windows_usize,
-            &mut last_seen_ts,
-            &mut report,
-            &mut output_rows,
-        )?;
+    if let Some(mut frame) = current_frame.take() {
+        if let Some(backfill) = backfill.as_mut() {
+            run.backfill_before(backfill, &mut frame, &mut sink)?;
+        }
+        run.emit(&frame, &mut sink)?;
+    }
+    if let Some(backfill) = backfill.as_mut() {
+        let start = run.next_expected_ts();
+        for frame in backfill.fill(start, req.end_ts_ms_utc_exclusive, &mut run.report)? {
+            run.emit(&frame, &mut sink)?;
+        }
     }
 
+    let TransformRun {
+        mut state,
+        last_seen_ts,
+        mut report,
+        clock,
+        ..
+    } = run;
     match last_seen_ts {
         Some(last_ts) => {
             let expected_last = req.end_ts_ms_utc_exclusive - STEP_MS;
@@ -484,7 +800,8 @@ pub fn transform_store_range(
         }
     }
 
-    report.output_points = output_rows.len() as u64;
+    let timing = clock.finish();
+    record_transform_metrics(&timing);
 
     info!(
         component = "features",
@@ -492,10 +809,349 @@ pub fn transform_store_range(
         input_points = report.input_points,
         output_points = report.output_points,
         skipped_points = report.skipped_points,
-        gap_ranges_reported = report.gap_ranges.len()
+        backfilled_points = report.backfilled_points,
+        gap_ranges_reported = report.gap_ranges.len(),
+        rows_read = timing.rows_read,
+        read_ms = (timing.read_secs * 1_000.0) as u64,
+        compute_ms = (timing.compute_secs * 1_000.0) as u64,
+        rows_per_sec = timing.rows_per_sec as u64
     );
 
-    Ok((schema, output_rows, report))
+    Ok((schema, report, timing))
+}
+
+/// Mutable state of one batch transform run.
+struct TransformRun<'a> {
+    req: &'a FeatureTransformRequest,
+    cfg: &'a FeatureTransformConfig,
+    windows: Vec<usize>,
+    state: TransformState,
+    last_seen_ts: Option<i64>,
+    report: FeatureTransformReport,
+    clock: TransformClock,
+}
+
+impl<'a> TransformRun<'a> {
+    fn new(req: &'a FeatureTransformRequest, cfg: &'a FeatureTransformConfig) -> Self {
+        let max_window = cfg.windows_seconds.iter().copied().max().unwrap_or(1) as usize;
+        Self {
+            req,
+            cfg,
+            windows: cfg.windows_seconds.iter().map(|w| *w as usize).collect(),
+            state: TransformState::new(max_window.max(1)),
+            last_seen_ts: None,
+            report: FeatureTransformReport {
+                input_points: expected_points(req.start_ts_ms_utc, req.end_ts_ms_utc_exclusive),
+                output_points: 0,
+                skipped_points: 0,
+                backfilled_points: 0,
+                gap_ranges: Vec::new(),
+                first_error: None,
+            },
+            clock: TransformClock::start(),
+        }
+    }
+
+    /// The second the next frame should open at.
+    fn next_expected_ts(&self) -> i64 {
+        self.last_seen_ts
+            .map_or(self.req.start_ts_ms_utc, |last_ts| last_ts + STEP_MS)
+    }
+
+    fn emit(
+        &mut self,
+        frame: &Frame,
+        sink: &mut impl FnMut(FeatureRow) -> Result<(), FeatureError>,
+    ) -> Result<(), FeatureError> {
+        let Self {
+            req,
+            cfg,
+            windows,
+            state,
+            last_seen_ts,
+            report,
+            clock,
+        } = self;
+        let output = clock
+            .compute(|| process_frame(frame, req, cfg, state, windows, last_seen_ts, report))?;
+        if let Some(output) = output {
+            report.output_points += 1;
+            sink(output)?;
+        }
+        Ok(())
+    }
+
+    /// Backfills the gap before `frame`, and `frame` itself when incomplete, emitting the
+    /// frames read back ahead of it.
+    fn backfill_before(
+        &mut self,
+        backfill: &mut GapBackfill<'_>,
+        frame: &mut Frame,
+        sink: &mut impl FnMut(FeatureRow) -> Result<(), FeatureError>,
+    ) -> Result<(), FeatureError> {
+        let start = self.next_expected_ts();
+        let end = if missing_symbols(frame).is_some() {
+            frame.ts_ms_utc + STEP_MS
+        } else {
+            frame.ts_ms_utc
+        };
+        for filled in backfill.fill(start, end, &mut self.report)? {
+            if filled.ts_ms_utc == frame.ts_ms_utc {
+                *frame = filled;
+            } else {
+                self.emit(&filled, sink)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Largest gap, in seconds, the transform backfills; longer gaps are left to the gap
+/// policy (run `binance_store_sync` for those).
+pub const MAX_BACKFILL_POINTS: u64 = 86_400;
+
+struct GapBackfill<'a> {
+    store: KlineStore,
+    source: &'a mut dyn KlineTailSource,
+}
+
+impl GapBackfill<'_> {
+    /// Fetches `[start, end)` for every symbol the store is short of, and returns the
+    /// range's frames as stored afterwards; empty when nothing was added.
+    fn fill(
+        &mut self,
+        start: i64,
+        end: i64,
+        report: &mut FeatureTransformReport,
+    ) -> Result<Vec<Frame>, FeatureError> {
+        let points = expected_points(start, end);
+        if points == 0 || points > MAX_BACKFILL_POINTS {
+            return Ok(Vec::new());
+        }
+        let mut filled = 0;
+        for symbol in ALL_BINANCE_SYMBOLS {
+            if self.store.count_range(symbol, start, end)? >= points {
+                continue;
+            }
+            match self.source.fetch(symbol, start, end) {
+                Ok(rows) => {
+                    let fetched = rows.len() as u64;
+                    let counts = self.store.upsert_rows(symbol, rows)?;
+                    filled += fetched.saturating_sub(counts.rows_rejected);
+                }
+                Err(message) => warn!(
+                    component = "features",
+                    event = "features.transform.backfill_failed",
+                    symbol = symbol.as_str(),
+                    start_ts_ms_utc = start,
+                    end_ts_ms_utc_exclusive = end,
+                    message
+                ),
+            }
+        }
+        if filled == 0 {
+            return Ok(Vec::new());
+        }
+        report.backfilled_points += filled;
+        info!(
+            component = "features",
+            event = "features.transform.backfilled",
+            start_ts_ms_utc = start,
+            end_ts_ms_utc_exclusive = end,
+            points = filled
+        );
+        read_frames(self.store.connection(), start, end)
+    }
+}
+
+/// Frames stored in `[start, end)`, in time order.
+fn read_frames(conn: &Connection, start: i64, end: i64) -> Result<Vec<Frame>, FeatureError> {
+    let mut stmt = conn.prepare(&frame_scan_sql(conn)?)?;
+    let mut rows = stmt.query(params![start, end])?;
+    let mut frames: Vec<Frame> = Vec::new();
+    while let Some(row) = rows.next()? {
+        let ts_ms_utc: i64 = row.get(0)?;
+        let symbol_id: i64 = row.get(1)?;
+        let point = KlinePoint {
+            high: row.get(2)?,
+            low: row.get(3)?,
+            close: row.get(4)?,
+            quote_asset_volume: row.get(5)?,
+        };
+        if ts_ms_utc % STEP_MS != 0 {
+            return Err(FeatureError::InvalidTimestamp(ts_ms_utc));
+        }
+        if frames
+            .last()
+            .is_none_or(|frame| frame.ts_ms_utc != ts_ms_utc)
+        {
+            frames.push(Frame::new(ts_ms_utc));
+        }
+        let frame = frames.last_mut().expect("frame pushed");
+        insert_frame_point(frame, symbol_id, ts_ms_utc, point)?;
+    }
+    Ok(frames)
+}
+
+/// Footer key of [`FeatureSchema::version`] in Parquet files written here.
+pub const PARQUET_SCHEMA_VERSION_KEY: &str = "pmm.schema_version";
+/// Footer key of [`FeatureSchema::fingerprint`] in Parquet files written here.
+pub const PARQUET_SCHEMA_FINGERPRINT_KEY: &str = "pmm.schema_fingerprint";
+/// Rows buffered per Parquet row group.
+pub const FEATURE_PARQUET_ROW_GROUP_ROWS: usize = 65_536;
+
+/// Footer key/value metadata naming the schema a Parquet file's feature columns follow.
+pub(crate) fn parquet_schema_metadata(schema: &FeatureSchema) -> Vec<KeyValue> {
+    vec![
+        KeyValue::new(
+            PARQUET_SCHEMA_VERSION_KEY.to_string(),
+            schema.version.to_string(),
+        ),
+        KeyValue::new(
+            PARQUET_SCHEMA_FINGERPRINT_KEY.to_string(),
+            schema.fingerprint.clone(),
+        ),
+    ]
+}
+
+/// Streams [`FeatureRow`]s into a Parquet file: an `INT64 ts_ms_utc` column followed
+/// by one `DOUBLE` column per schema column, Snappy-compressed, with the schema's
+/// version and fingerprint in the footer. Rows are buffered column-wise and flushed
+/// every [`FEATURE_PARQUET_ROW_GROUP_ROWS`] rows.
+///
+/// The file is written under a `.partial` suffix and renamed into place by
+/// [`FeatureParquetWriter::finish`], so readers never see a truncated file.
+pub struct FeatureParquetWriter {
+    writer: SerializedFileWriter<File>,
+    partial_path: PathBuf,
+    path: PathBuf,
+    ts_ms_utc: Vec<i64>,
+    columns: Vec<Vec<f64>>,
+    rows_written: u64,
+}
+
+impl FeatureParquetWriter {
+    pub fn create(path: &Path, schema: &FeatureSchema) -> Result<Self, FeatureError> {
+        let mut fields = vec![Arc::new(
+            ParquetType::primitive_type_builder("ts_ms_utc", PhysicalType::INT64)
+                .with_repetition(Repetition::REQUIRED)
+                .build()?,
+        )];
+        for column in &schema.columns {
+            fields.push(Arc::new(
+                ParquetType::primitive_type_builder(&column.name, PhysicalType::DOUBLE)
+                    .with_repetition(Repetition::REQUIRED)
+                    .build()?,
+            ));
+        }
+        let message = ParquetType::group_type_builder("feature_row")
+            .with_fields(fields)
+            .build()?;
+        let props = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .set_key_value_metadata(Some(parquet_schema_metadata(schema)))
+            .build();
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut partial = path.as_os_str().to_owned();
+        partial.push(".partial");
+        let partial_path = PathBuf::from(partial);
+        let writer = SerializedFileWriter::new(
+            File::create(&partial_path)?,
+            Arc::new(message),
+            Arc::new(props),
+        )?;
+        Ok(Self {
+            writer,
+            partial_path,
+            path: path.to_path_buf(),
+            ts_ms_utc: Vec::new(),
+            columns: vec![Vec::new(); schema.columns.len()],
+            rows_written: 0,
+        })
+    }
+
+    pub fn write(&mut self, row: &FeatureRow) -> Result<(), FeatureError> {
+        if row.values.len() != self.columns.len() {
+            return Err(FeatureError::RowWidthMismatch {
+                ts_ms_utc: row.ts_ms_utc,
+                expected: self.columns.len(),
+                actual: row.values.len(),
+            });
+        }
+        self.ts_ms_utc.push(row.ts_ms_utc);
+        for (column, value) in self.columns.iter_mut().zip(&row.values) {
+            column.push(*value);
+        }
+        if self.ts_ms_utc.len() >= FEATURE_PARQUET_ROW_GROUP_ROWS {
+            self.flush_row_group()?;
+        }
+        Ok(())
+    }
+
+    /// Flushes buffered rows, writes the footer, and moves the file into place.
+    /// Returns the number of rows written.
+    pub fn finish(mut self) -> Result<u64, FeatureError> {
+        self.flush_row_group()?;
+        self.writer.close()?;
+        std::fs::rename(&self.partial_path, &self.path)?;
+        Ok(self.rows_written)
+    }
+
+    /// Drops the partial file without moving it into place.
+    fn discard(self) {
+        let partial_path = self.partial_path.clone();
+        drop(self);
+        let _ = std::fs::remove_file(partial_path);
+    }
+
+    fn flush_row_group(&mut self) -> Result<(), FeatureError> {
+        if self.ts_ms_utc.is_empty() {
+            return Ok(());
+        }
+        let mut group = self.writer.next_row_group()?;
+        let mut column = group.next_column()?.ok_or_else(missing_parquet_column)?;
+        column
+            .typed::<Int64Type>()
+            .write_batch(&self.ts_ms_utc, None, None)?;
+        column.close()?;
+        for values in &mut self.columns {
+            let mut column = group.next_column()?.ok_or_else(missing_parquet_column)?;
+            column
+                .typed::<DoubleType>()
+                .write_batch(values, None, None)?;
+            column.close()?;
+            values.clear();
+        }
+        group.close()?;
+        self.rows_written += self.ts_ms_utc.len() as u64;
+        self.ts_ms_utc.clear();
+        Ok(())
+    }
+}
+
+fn missing_parquet_column() -> ParquetError {
+    ParquetError::General("row group has fewer columns than the schema".to_string())
+}
+
+/// Writes `rows` to `path` with a [`FeatureParquetWriter`]; returns the rows written.
+pub fn write_feature_rows_parquet(
+    path: &Path,
+    schema: &FeatureSchema,
+    rows: &[FeatureRow],
+) -> Result<u64, FeatureError> {
+    let mut writer = FeatureParquetWriter::create(path, schema)?;
+    for row in rows {
+        if let Err(err) = writer.write(row) {
+            writer.discard();
+            return Err(err);
+        }
+    }
+    writer.finish()
 }
 
 pub fn transform_store_range_for_training(
@@ -514,6 +1170,171 @@ pub fn transform_store_range_for_runtime_cold_start(
     transform_store_range(store_path, req, cfg)
 }
 
+/// Online counterpart of the batch transform: takes one 1s frame at a time from the
+/// live Binance feed and keeps the rolling state warm after cold start. Rows come from
+/// the same code as [`transform_store_range_into`], so a frame yields exactly the row
+/// the batch transform would emit for it.
+///
+/// Gaps and incomplete frames never fail the updater, whatever `gap_policy` says: they
+/// reset the rolling state, are recorded in [`FeatureUpdater::report`], and rows resume
+/// once the largest window is filled again.
+#[derive(Debug, Clone)]
+pub struct FeatureUpdater {
+    cfg: FeatureTransformConfig,
+    schema: FeatureSchema,
+    windows: Vec<usize>,
+    state: TransformState,
+    last_seen_ts: Option<i64>,
+    report: FeatureTransformReport,
+    latest: Option<FeatureRow>,
+}
+
+impl FeatureUpdater {
+    pub fn new(cfg: &FeatureTransformConfig) -> Result<Self, FeatureError> {
+        validate_config(cfg)?;
+        let windows: Vec<usize> = cfg.windows_seconds.iter().map(|w| *w as usize).collect();
+        let max_window = windows.iter().copied().max().unwrap_or(1).max(1);
+        Ok(Self {
+            cfg: FeatureTransformConfig {
+                gap_policy: GapPolicy::ReportAndSkip,
+                ..cfg.clone()
+            },
+            schema: build_feature_schema(cfg),
+            windows,
+            state: TransformState::new(max_window),
+            last_seen_ts: None,
+            report: FeatureTransformReport {
+                input_points: 0,
+                output_points: 0,
+                skipped_points: 0,
+                backfilled_points: 0,
+                gap_ranges: Vec::new(),
+                first_error: None,
+            },
+            latest: None,
+        })
+    }
+
+    /// Replays the stored frames needed to warm the state, `max(windows) + 1` seconds
+    /// ending at `end_ts_ms_utc_exclusive`, so the first live frame after cold start
+    /// already yields a row. Frames at or before the last pushed one are skipped.
+    /// Returns the number of frames read.
+    pub fn warm_from_store(
+        &mut self,
+        store_path: &Path,
+        end_ts_ms_utc_exclusive: i64,
+    ) -> Result<u64, FeatureError> {
+        if end_ts_ms_utc_exclusive % STEP_MS != 0 {
+            return Err(FeatureError::InvalidTimestamp(end_ts_ms_utc_exclusive));
+        }
+        let mut start = end_ts_ms_utc_exclusive - (self.state.max_window as i64 + 1) * STEP_MS;
+        if let Some(last_ts) = self.last_seen_ts {
+            start = start.max(last_ts + STEP_MS);
+        }
+        if start >= end_ts_ms_utc_exclusive {
+            return Ok(0);
+        }
+
+        let conn = Connection::open(store_path)?;
+        let frames = read_frames(&conn, start, end_ts_ms_utc_exclusive)?;
+        for frame in &frames {
+            self.push(frame)?;
+        }
+        let frames = frames.len() as u64;
+
+        info!(
+            component = "features",
+            event = "features.updater.warmed",
+            start_ts_ms_utc = start,
+            end_ts_ms_utc_exclusive,
+            frames,
+            warm = self.latest.is_some()
+        );
+        Ok(frames)
+    }
+
+    /// Feeds the 1s klines opening at `ts_ms_utc`, one per symbol, and returns the row
+    /// for that second; `None` while warming up or when the frame is incomplete.
+    pub fn push_frame(
+        &mut self,
+        ts_ms_utc: i64,
+        klines: &[(BinanceSymbol, Kline1s)],
+    ) -> Result<Option<FeatureRow>, FeatureError> {
+        if ts_ms_utc % STEP_MS != 0 {
+            return Err(FeatureError::InvalidTimestamp(ts_ms_utc));
+        }
+        let mut frame = Frame::new(ts_ms_utc);
+        for (symbol, kline) in klines {
+            if kline.open_time_ms != ts_ms_utc {
+                return Err(FeatureError::InvalidTimestamp(kline.open_time_ms));
+            }
+            let point = KlinePoint {
+                high: kline.high,
+                low: kline.low,
+                close: kline.close,
+                quote_asset_volume: kline.quote_asset_volume,
+            };
+            insert_frame_point(&mut frame, symbol_id(*symbol), ts_ms_utc, point)?;
+        }
+        self.push(&frame)
+    }
+
+    /// The row of the last pushed frame; cleared while the state is warming.
+    pub fn latest(&self) -> Option<&FeatureRow> {
+        self.latest.as_ref()
+    }
+
+    pub fn schema(&self) -> &FeatureSchema {
+        &self.schema
+    }
+
+    /// Frames pushed (`input_points`), rows emitted, and the gaps that reset the state.
+    pub fn report(&self) -> &FeatureTransformReport {
+        &self.report
+    }
+
+    pub fn last_ts_ms_utc(&self) -> Option<i64> {
+        self.last_seen_ts
+    }
+
+    fn push(&mut self, frame: &Frame) -> Result<Option<FeatureRow>, FeatureError> {
+        if let Some(last_ts_ms_utc) = self.last_seen_ts {
+            if frame.ts_ms_utc <= last_ts_ms_utc {
+                return Err(FeatureError::OutOfOrderFrame {
+                    last_ts_ms_utc,
+                    ts_ms_utc: frame.ts_ms_utc,
+                });
+            }
+            let expected = last_ts_ms_utc + STEP_MS;
+            if frame.ts_ms_utc != expected {
+                handle_gap(
+                    expected,
+                    expected,
+                    frame.ts_ms_utc,
+                    &self.cfg,
+                    &mut self.report,
+                    &mut self.state,
+                )?;
+            }
+        }
+        self.last_seen_ts = Some(frame.ts_ms_utc);
+        self.report.input_points += 1;
+
+        let output = advance_frame(
+            frame,
+            &self.cfg,
+            &mut self.state,
+            &self.windows,
+            &mut self.report,
+        )?;
+        if output.is_some() {
+            self.report.output_points += 1;
+        }
+        self.latest.clone_from(&output);
+        Ok(output)
+    }
+}
+
 pub fn horizon_conditioning(
     horizon_seconds: u32,
     max_duration_seconds: u32,
@@ -593,6 +1414,17 @@ fn validate_config(cfg: &FeatureTransformConfig) -> Result<(), FeatureError> {
         )));
     }
 
+    if let Some(preset) = cfg.preset {
+        if cfg.windows_seconds != preset.windows_seconds() {
+            return Err(FeatureError::InvalidConfig(format!(
+                "windows_seconds {:?} do not match preset '{}' ({:?})",
+                cfg.windows_seconds,
+                preset.as_str(),
+                preset.windows_seconds()
+            )));
+        }
+    }
+
     let mut seen = HashSet::new();
     for window in &cfg.windows_seconds {
         if *window == 0 {
@@ -619,10 +1451,9 @@ fn process_frame(
     windows: &[usize],
     last_seen_ts: &mut Option<i64>,
     report: &mut FeatureTransformReport,
-    output_rows: &mut Vec<FeatureRow>,
-) -> Result<(), FeatureError> {
+) -> Result<Option<FeatureRow>, FeatureError> {
     if frame.ts_ms_utc < req.start_ts_ms_utc || frame.ts_ms_utc >= req.end_ts_ms_utc_exclusive {
-        return Ok(());
+        return Ok(None);
     }
 
     if let Some(prev_seen) = *last_seen_ts {
@@ -643,13 +1474,26 @@ fn process_frame(
 
     *last_seen_ts = Some(frame.ts_ms_utc);
 
+    advance_frame(frame, cfg, state, windows, report)
+}
+
+/// Pushes one in-order frame into the rolling state and computes its row once warm. The
+/// single implementation of the feature math, shared by the batch transform and
+/// [`FeatureUpdater`].
+fn advance_frame(
+    frame: &Frame,
+    cfg: &FeatureTransformConfig,
+    state: &mut TransformState,
+    windows: &[usize],
+    report: &mut FeatureTransformReport,
+) -> Result<Option<FeatureRow>, FeatureError> {
     if let Some(missing_symbols) = missing_symbols(frame) {
         let reason = format!(
             "incomplete frame at {} missing={missing_symbols:?}",
             frame.ts_ms_utc
         );
         handle_incomplete_frame(frame.ts_ms_utc, missing_symbols, cfg, report, state, reason)?;
-        return Ok(());
+        return Ok(None);
     }
 
     for (idx, slot) in frame.points.iter().enumerate() {
@@ -658,7 +1502,7 @@ fn process_frame(
     }
 
     if !is_warm(state, windows) {
-        return Ok(());
+        return Ok(None);
     }
 
     let mut values = Vec::new();
@@ -690,12 +1534,10 @@ fn process_frame(
     values.push(tow_sin);
     values.push(tow_cos);
 
-    output_rows.push(FeatureRow {
+    Ok(Some(FeatureRow {
         ts_ms_utc: frame.ts_ms_utc,
         values,
-    });
-
-    Ok(())
+    }))
 }
 
 fn insert_frame_point(
@@ -880,6 +1722,9 @@ fn schema_fingerprint(cfg: &FeatureTransformConfig, columns: &[FeatureColumn]) -
         "max_duration_seconds:{};",
         cfg.max_duration_seconds
     ));
+    if let Some(preset) = cfg.preset {
+        hasher.update(format!("preset:{};", preset.as_str()));
+    }
     hasher.update("windows:");
     for window in &cfg.windows_seconds {
         hasher.update(format!("{window},"));
@@ -0,0 +1,287 @@
+//! Fee schedule registry with effective-date ranges.
+//!
+//! Polymarket has changed fee parameters over time, so a fee type maps to a list of
+//! [`FeeParams`], each in force over `[effective_from, effective_until)`. Live code asks
+//! for the parameters in force now; backtests and historical PnL recomputation ask for
+//! the ones in force at the simulated trade with [`FeeRegistry::fee_params_at`].
+//!
+//! The built-in schedule only carries the current profile, open-ended in both
+//! directions. Point `PMM_FEE_SCHEDULE_PATH` at a JSON array of [`FeeScheduleEntry`] to
+//! record past changes; it replaces the built-in schedule.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::env_vars::Settings;
+use crate::sizing::taker_fee_per_share;
+
+/// Gamma `feeType` of the 5m/15m crypto up/down markets.
+pub const CRYPTO_15_MIN_FEE_TYPE: &str = "crypto_15_min";
+
+/// Fee curve parameters: a fee of `rate * (p * (1 - p))^exponent` USDC per share.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FeeParams {
+    pub taker_fee_rate: f64,
+    /// Negative rates are maker rebates.
+    pub maker_fee_rate: f64,
+    /// `None` when fees are disabled.
+    pub fee_exponent: Option<f64>,
+}
+
+impl FeeParams {
+    pub const DISABLED: Self = Self {
+        taker_fee_rate: 0.0,
+        maker_fee_rate: 0.0,
+        fee_exponent: None,
+    };
+
+    pub fn taker_fee_per_share(&self, price: f64) -> f64 {
+        taker_fee_per_share(price, self.taker_fee_rate, self.fee_exponent)
+    }
+
+    /// Maker fee per share at `price`; negative for a rebate.
+    pub fn maker_fee_per_share(&self, price: f64) -> f64 {
+        match self.fee_exponent {
+            Some(exponent) if (0.0..=1.0).contains(&price) => {
+                self.maker_fee_rate * (price * (1.0 - price)).powf(exponent)
+            }
+            _ => 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeeScheduleEntry {
+    pub fee_type: String,
+    /// Inclusive UTC start; `None` reaches back indefinitely.
+    #[serde(default)]
+    pub effective_from_ts_utc: Option<i64>,
+    /// Exclusive UTC end; `None` while the parameters are still in force.
+    #[serde(default)]
+    pub effective_until_ts_utc: Option<i64>,
+    #[serde(flatten)]
+    pub params: FeeParams,
+}
+
+impl FeeScheduleEntry {
+    fn covers(&self, ts_utc: i64) -> bool {
+        self.effective_from_ts_utc.is_none_or(|from| ts_utc >= from)
+            && self
+                .effective_until_ts_utc
+                .is_none_or(|until| ts_utc < until)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum FeeScheduleError {
+    #[error("failed to read fee schedule {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("invalid fee schedule {path}: {source}")]
+    Parse {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    #[error("empty effective range for fee type {0}")]
+    EmptyRange(String),
+    #[error("overlapping effective ranges for fee type {0}")]
+    Overlap(String),
+}
+
+/// Fee parameters per fee type over time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeeRegistry {
+    entries: Vec<FeeScheduleEntry>,
+}
+
+impl Default for FeeRegistry {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl FeeRegistry {
+    /// Current profile only: `crypto_15_min` taker `0.25`, maker `-0.05`, exponent `2`.
+    pub fn builtin() -> Self {
+        Self {
+            entries: vec![FeeScheduleEntry {
+                fee_type: CRYPTO_15_MIN_FEE_TYPE.to_string(),
+                effective_from_ts_utc: None,
+                effective_until_ts_utc: None,
+                params: FeeParams {
+                    taker_fee_rate: 0.25,
+                    maker_fee_rate: -0.05,
+                    fee_exponent: Some(2.0),
+                },
+            }],
+        }
+    }
+
+    /// Rejects empty ranges and ranges of one fee type that overlap.
+    pub fn new(mut entries: Vec<FeeScheduleEntry>) -> Result<Self, FeeScheduleError> {
+        entries.sort_by(|a, b| {
+            (a.fee_type.as_str(), a.effective_from_ts_utc)
+                .cmp(&(b.fee_type.as_str(), b.effective_from_ts_utc))
+        });
+        for entry in &entries {
+            if let (Some(from), Some(until)) =
+                (entry.effective_from_ts_utc, entry.effective_until_ts_utc)
+            {
+                if from >= until {
+                    return Err(FeeScheduleError::EmptyRange(entry.fee_type.clone()));
+                }
+            }
+        }
+        for pair in entries.windows(2) {
+            let (prev, next) = (&pair[0], &pair[1]);
+            if prev.fee_type != next.fee_type {
+                continue;
+            }
+            // Sorted by start, so `None` (unbounded) starts come first.
+            let overlaps = match (prev.effective_until_ts_utc, next.effective_from_ts_utc) {
+                (Some(until), Some(from)) => from < until,
+                _ => true,
+            };
+            if overlaps {
+                return Err(FeeScheduleError::Overlap(prev.fee_type.clone()));
+            }
+        }
+        Ok(Self { entries })
+    }
+
+    /// Reads a JSON array of [`FeeScheduleEntry`].
+    pub fn load(path: &Path) -> Result<Self, FeeScheduleError> {
+        let raw = std::fs::read_to_string(path).map_err(|source| FeeScheduleError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let entries = serde_json::from_str(&raw).map_err(|source| FeeScheduleError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::new(entries)
+    }
+
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Result<Self, FeeScheduleError> {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// The schedule at `PMM_FEE_SCHEDULE_PATH`, or the built-in one when unset.
+    pub fn from_settings(settings: &Settings) -> Result<Self, FeeScheduleError> {
+        match settings.get::<Option<PathBuf>>("PMM_FEE_SCHEDULE_PATH") {
+            Some(path) => Self::load(&path),
+            None => Ok(Self::builtin()),
+        }
+    }
+
+    pub fn entries(&self) -> &[FeeScheduleEntry] {
+        &self.entries
+    }
+
+    /// Parameters of `fee_type` in force at `ts_utc`; `None` when the schedule has no
+    /// entry for that time (callers charge no fees).
+    pub fn fee_params_at(&self, fee_type: &str, ts_utc: i64) -> Option<FeeParams> {
+        self.entries
+            .iter()
+            .find(|entry| entry.fee_type == fee_type && entry.covers(ts_utc))
+            .map(|entry| entry.params)
+    }
+}
+
+/// [`FeeRegistry::fee_params_at`] on the built-in schedule.
+pub fn fee_params_at(fee_type: &str, ts_utc: i64) -> Option<FeeParams> {
+    FeeRegistry::builtin().fee_params_at(fee_type, ts_utc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHANGE_TS: i64 = 1_767_225_600;
+
+    fn entry(from: Option<i64>, until: Option<i64>, taker_fee_rate: f64) -> FeeScheduleEntry {
+        FeeScheduleEntry {
+            fee_type: CRYPTO_15_MIN_FEE_TYPE.to_string(),
+            effective_from_ts_utc: from,
+            effective_until_ts_utc: until,
+            params: FeeParams {
+                taker_fee_rate,
+                maker_fee_rate: 0.0,
+                fee_exponent: Some(2.0),
+            },
+        }
+    }
+
+    #[test]
+    fn lookup_picks_the_range_in_force_at_the_trade() {
+        let registry = FeeRegistry::new(vec![
+            entry(Some(CHANGE_TS), None, 0.25),
+            entry(Some(CHANGE_TS - 86_400), Some(CHANGE_TS), 0.1),
+        ])
+        .unwrap();
+        let taker = |ts| {
+            registry
+                .fee_params_at(CRYPTO_15_MIN_FEE_TYPE, ts)
+                .map(|params| params.taker_fee_rate)
+        };
+        assert_eq!(taker(CHANGE_TS - 86_401), None);
+        assert_eq!(taker(CHANGE_TS - 1), Some(0.1));
+        assert_eq!(taker(CHANGE_TS), Some(0.25));
+        assert_eq!(registry.fee_params_at("sports", CHANGE_TS), None);
+
+        let params = fee_params_at(CRYPTO_15_MIN_FEE_TYPE, CHANGE_TS).unwrap();
+        assert!((params.taker_fee_per_share(0.5) - 0.015625).abs() < 1e-12);
+        assert!((params.maker_fee_per_share(0.5) + 0.003125).abs() < 1e-12);
+        assert_eq!(FeeParams::DISABLED.taker_fee_per_share(0.5), 0.0);
+    }
+
+    #[test]
+    fn schedules_with_overlapping_or_empty_ranges_are_rejected() {
+        assert!(matches!(
+            FeeRegistry::new(vec![
+                entry(None, None, 0.25),
+                entry(Some(CHANGE_TS), None, 0.1)
+            ]),
+            Err(FeeScheduleError::Overlap(_))
+        ));
+        assert!(matches!(
+            FeeRegistry::new(vec![
+                entry(Some(0), Some(CHANGE_TS + 1), 0.1),
+                entry(Some(CHANGE_TS), None, 0.25),
+            ]),
+            Err(FeeScheduleError::Overlap(_))
+        ));
+        assert!(matches!(
+            FeeRegistry::new(vec![entry(Some(CHANGE_TS), Some(CHANGE_TS), 0.1)]),
+            Err(FeeScheduleError::EmptyRange(_))
+        ));
+    }
+
+    #[test]
+    fn schedule_file_replaces_the_builtin_profile() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fees.json");
+        std::fs::write(
+            &path,
+            format!(
+                r#"[{{"fee_type":"crypto_15_min","effective_from_ts_utc":{CHANGE_TS},
+                    "taker_fee_rate":0.25,"maker_fee_rate":-0.05,"fee_exponent":2}}]"#
+            ),
+        )
+        .unwrap();
+        let registry = FeeRegistry::load(&path).unwrap();
+        assert_eq!(
+            registry.fee_params_at(CRYPTO_15_MIN_FEE_TYPE, CHANGE_TS - 1),
+            None
+        );
+        assert_eq!(
+            registry.fee_params_at(CRYPTO_15_MIN_FEE_TYPE, CHANGE_TS),
+            fee_params_at(CRYPTO_15_MIN_FEE_TYPE, CHANGE_TS)
+        );
+    }
+}
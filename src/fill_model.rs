@@ -0,0 +1,642 @@
+//! Backtest fill model.
+//!
+//! "Fill at mid" overstates PnL by the half-spread on every trade and assumes every
+//! resting quote fills. The calibrated model instead:
+//! - crosses the spread for taker orders: buys lift the ask, sells hit the bid, capped
+//!   at the displayed top size, plus a configured slippage
+//! - queues maker orders behind the displayed size at their price and fills them from
+//!   taker flow trading through that level, at a rate estimated from recorded sessions
+//!
+//! Prices are YES-token prices; a NO order is the opposite YES side at `1 - price`.
+//! Fees are not part of the fill; [`expected_fee_usdc`] charges the fee
+//! schedule in force at the book's timestamp.
+//!
+//! Maker flow model: taker volume reaching the quote over the horizon is taken as
+//! exponential with mean `V = rate * horizon_secs`. With `Q` shares queued ahead and
+//! order size `s`, the full-fill probability is `exp(-(Q + s) / V)` and the expected
+//! filled size is `V * exp(-Q / V) * (1 - exp(-s / V))`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::env_vars::{EnvValue, Settings};
+use crate::fees::FeeRegistry;
+use crate::queue_position::QueueEstimate;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderSide {
+    Buy,
+    Sell,
+}
+
+/// Top of the YES book at one point in a recorded session.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BookTop {
+    pub ts_ms: i64,
+    pub best_bid: Option<f64>,
+    pub best_ask: Option<f64>,
+    pub bid_size: f64,
+    pub ask_size: f64,
+}
+
+impl BookTop {
+    pub fn mid(&self) -> Option<f64> {
+        Some((self.best_bid? + self.best_ask?) / 2.0)
+    }
+}
+
+/// One recorded trade; `taker_side` is the aggressor (a `Buy` consumed asks).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TradePrint {
+    pub ts_ms: i64,
+    pub price: f64,
+    pub size: f64,
+    pub taker_side: OrderSide,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FillMode {
+    /// Every order fills in full at mid; kept as the optimistic baseline.
+    NaiveMid,
+    Calibrated,
+}
+
+impl EnvValue for FillMode {
+    fn parse_env(raw: &str) -> Option<Self> {
+        match raw {
+            "mid" => Some(Self::NaiveMid),
+            "calibrated" => Some(Self::Calibrated),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FillModelConfig {
+    pub mode: FillMode,
+    /// Price per share paid beyond the touch by taker orders.
+    pub taker_slippage: f64,
+    /// Fraction of the displayed size at the quote price assumed ahead of a new maker
+    /// order (`1.0` = back of the queue).
+    pub maker_queue_ahead_fraction: f64,
+    /// Seconds a maker quote rests before the simulation cancels it.
+    pub maker_horizon_secs: f64,
+}
+
+impl Default for FillModelConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl FillModelConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_FILL_MODE` (`mid|calibrated`), `PMM_FILL_TAKER_SLIPPAGE`,
+    /// `PMM_FILL_MAKER_QUEUE_AHEAD`, and `PMM_FILL_MAKER_HORIZON_SECS`, falling back to
+    /// defaults for missing/invalid values.
+    pub fn from_settings(settings: &Settings) -> Self {
+        let read = |name: &str| settings.get_where(name, |value: &f64| *value >= 0.0);
+        Self {
+            mode: settings.get("PMM_FILL_MODE"),
+            taker_slippage: read("PMM_FILL_TAKER_SLIPPAGE"),
+            maker_queue_ahead_fraction: read("PMM_FILL_MAKER_QUEUE_AHEAD").min(1.0),
+            maker_horizon_secs: read("PMM_FILL_MAKER_HORIZON_SECS"),
+        }
+    }
+}
+
+/// Flow statistics estimated from recorded sessions. Sessions combine with
+/// [`FillCalibration::merge`].
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct FillCalibration {
+    pub observed_secs: f64,
+    pub trade_count: u64,
+    /// Taker buy volume (fills resting sells).
+    pub buy_taker_volume: f64,
+    /// Taker sell volume (fills resting buys).
+    pub sell_taker_volume: f64,
+    /// Book samples with both sides present.
+    pub two_sided_samples: u64,
+    pub mean_spread: Option<f64>,
+    pub mean_top_size: Option<f64>,
+}
+
+impl FillCalibration {
+    pub fn buy_volume_per_sec(&self) -> f64 {
+        per_sec(self.buy_taker_volume, self.observed_secs)
+    }
+
+    pub fn sell_volume_per_sec(&self) -> f64 {
+        per_sec(self.sell_taker_volume, self.observed_secs)
+    }
+
+    pub fn merge(&self, other: &Self) -> Self {
+        let samples = self.two_sided_samples + other.two_sided_samples;
+        let weighted = |a: Option<f64>, b: Option<f64>| {
+            let sum = a.unwrap_or(0.0) * self.two_sided_samples as f64
+                + b.unwrap_or(0.0) * other.two_sided_samples as f64;
+            (samples > 0).then(|| sum / samples as f64)
+        };
+        Self {
+            observed_secs: self.observed_secs + other.observed_secs,
+            trade_count: self.trade_count + other.trade_count,
+            buy_taker_volume: self.buy_taker_volume + other.buy_taker_volume,
+            sell_taker_volume: self.sell_taker_volume + other.sell_taker_volume,
+            two_sided_samples: samples,
+            mean_spread: weighted(self.mean_spread, other.mean_spread),
+            mean_top_size: weighted(self.mean_top_size, other.mean_top_size),
+        }
+    }
+}
+
+fn per_sec(volume: f64, secs: f64) -> f64 {
+    if secs > 0.0 {
+        volume / secs
+    } else {
+        0.0
+    }
+}
+
+/// Estimates flow statistics from one recorded session. The session length is the
+/// span covered by its book samples and trades.
+pub fn calibrate_fill_model(books: &[BookTop], trades: &[TradePrint]) -> FillCalibration {
+    let timestamps = books
+        .iter()
+        .map(|book| book.ts_ms)
+        .chain(trades.iter().map(|trade| trade.ts_ms));
+    let (first, last) = timestamps.fold((i64::MAX, i64::MIN), |(lo, hi), ts| {
+        (lo.min(ts), hi.max(ts))
+    });
+    let observed_secs = if last > first {
+        (last - first) as f64 / 1_000.0
+    } else {
+        0.0
+    };
+
+    let mut calibration = FillCalibration {
+        observed_secs,
+        trade_count: trades.len() as u64,
+        ..FillCalibration::default()
+    };
+    for trade in trades {
+        match trade.taker_side {
+            OrderSide::Buy => calibration.buy_taker_volume += trade.size,
+            OrderSide::Sell => calibration.sell_taker_volume += trade.size,
+        }
+    }
+
+    let (mut spread_sum, mut size_sum) = (0.0, 0.0);
+    for book in books {
+        if let (Some(bid), Some(ask)) = (book.best_bid, book.best_ask) {
+            calibration.two_sided_samples += 1;
+            spread_sum += ask - bid;
+            size_sum += (book.bid_size + book.ask_size) / 2.0;
+        }
+    }
+    if calibration.two_sided_samples > 0 {
+        let n = calibration.two_sided_samples as f64;
+        calibration.mean_spread = Some(spread_sum / n);
+        calibration.mean_top_size = Some(size_sum / n);
+    }
+    calibration
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "style")]
+pub enum OrderStyle {
+    Taker,
+    Maker { price: f64 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SimOrder {
+    pub side: OrderSide,
+    pub size: f64,
+    pub style: OrderStyle,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SimFill {
+    /// Probability the whole order fills.
+    pub fill_probability: f64,
+    /// Expected shares filled.
+    pub expected_size: f64,
+    /// Fill price; `None` when nothing can fill.
+    pub price: Option<f64>,
+}
+
+impl SimFill {
+    fn none() -> Self {
+        Self {
+            fill_probability: 0.0,
+            expected_size: 0.0,
+            price: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FillModel {
+    pub config: FillModelConfig,
+    pub calibration: FillCalibration,
+}
+
+impl FillModel {
+    pub fn new(config: FillModelConfig, calibration: FillCalibration) -> Self {
+        Self {
+            config,
+            calibration,
+        }
+    }
+
+    /// Expected outcome of submitting `order` against `book`.
+    pub fn simulate(&self, order: &SimOrder, book: &BookTop) -> SimFill {
+        if order.size.is_nan() || order.size <= 0.0 {
+            return SimFill::none();
+        }
+        if self.config.mode == FillMode::NaiveMid {
+            return match book.mid() {
+                Some(mid) => SimFill {
+                    fill_probability: 1.0,
+                    expected_size: order.size,
+                    price: Some(mid),
+                },
+                None => SimFill::none(),
+            };
+        }
+
+        match order.style {
+            OrderStyle::Taker => self.taker_fill(order.side, order.size, book),
+            OrderStyle::Maker { price } => {
+                if crosses_spread(order.side, price, book) {
+                    // Marketable limit: takes at the touch, never worse than its limit.
+                    let mut fill = self.taker_fill(order.side, order.size, book);
+                    fill.price = fill.price.map(|fill_price| match order.side {
+                        OrderSide::Buy => fill_price.min(price),
+                        OrderSide::Sell => fill_price.max(price),
+                    });
+                    fill
+                } else {
+                    self.maker_fill(order.side, order.size, price, book)
+                }
+            }
+        }
+    }
+
+    /// Expected outcome of a resting order over the maker horizon, queued behind its
+    /// tracked size ahead rather than the fraction of the touch assumed at placement.
+    pub fn queued_maker_fill(&self, estimate: &QueueEstimate) -> SimFill {
+        let size = estimate.remaining();
+        if size <= 0.0 {
+            return SimFill::none();
+        }
+        self.flow_fill(
+            estimate.order.side,
+            estimate.ahead.max(0.0),
+            size,
+            estimate.order.price,
+        )
+    }
+
+    /// One random realization of [`FillModel::simulate`] for paper trading. A resting
+    /// quote fills from taker volume drawn from the flow model's exponential with
+    /// `uniform` in `[0, 1)`; taker and naive-mid fills are deterministic.
+    /// `fill_probability` is `1` when the whole order filled and `0` otherwise.
+    pub fn sample_fill(&self, order: &SimOrder, book: &BookTop, uniform: f64) -> SimFill {
+        let expected = self.simulate(order, book);
+        let OrderStyle::Maker { price } = order.style else {
+            return expected;
+        };
+        if self.config.mode == FillMode::NaiveMid
+            || expected.price.is_none()
+            || crosses_spread(order.side, price, book)
+        {
+            return expected;
+        }
+        let mean_volume = self.flow_rate(order.side) * self.config.maker_horizon_secs;
+        let volume = -mean_volume * (1.0 - uniform.clamp(0.0, 1.0)).ln();
+        let filled =
+            (volume - self.maker_queue_ahead(order.side, price, book)).clamp(0.0, order.size);
+        SimFill {
+            fill_probability: if filled >= order.size { 1.0 } else { 0.0 },
+            expected_size: filled,
+            price: (filled > 0.0).then_some(price),
+        }
+    }
+
+    fn taker_fill(&self, side: OrderSide, size: f64, book: &BookTop) -> SimFill {
+        let (touch, available) = match side {
+            OrderSide::Buy => (book.best_ask, book.ask_size),
+            OrderSide::Sell => (book.best_bid, book.bid_size),
+        };
+        let Some(touch) = touch else {
+            return SimFill::none();
+        };
+        let filled = size.min(available.max(0.0));
+        if filled <= 0.0 {
+            return SimFill::none();
+        }
+        let price = match side {
+            OrderSide::Buy => (touch + self.config.taker_slippage).min(1.0),
+            OrderSide::Sell => (touch - self.config.taker_slippage).max(0.0),
+        };
+        SimFill {
+            fill_probability: if filled >= size { 1.0 } else { 0.0 },
+            expected_size: filled,
+            price: Some(price),
+        }
+    }
+
+    fn maker_fill(&self, side: OrderSide, size: f64, price: f64, book: &BookTop) -> SimFill {
+        self.flow_fill(side, self.maker_queue_ahead(side, price, book), size, price)
+    }
+
+    fn maker_queue_ahead(&self, side: OrderSide, price: f64, book: &BookTop) -> f64 {
+        let (touch, touch_size) = match side {
+            OrderSide::Buy => (book.best_bid, book.bid_size),
+            OrderSide::Sell => (book.best_ask, book.ask_size),
+        };
+        let improves = match (side, touch) {
+            (_, None) => true,
+            (OrderSide::Buy, Some(touch)) => price > touch,
+            (OrderSide::Sell, Some(touch)) => price < touch,
+        };
+        // Joining the touch queues behind part of it; quoting behind the touch waits for
+        // the whole displayed level (deeper levels are not recorded).
+        if improves {
+            0.0
+        } else if touch == Some(price) {
+            touch_size.max(0.0) * self.config.maker_queue_ahead_fraction
+        } else {
+            touch_size.max(0.0)
+        }
+    }
+
+    /// Taker volume per second that can fill a resting order on `side`: resting buys
+    /// fill from taker sells and vice versa.
+    fn flow_rate(&self, side: OrderSide) -> f64 {
+        match side {
+            OrderSide::Buy => self.calibration.sell_volume_per_sec(),
+            OrderSide::Sell => self.calibration.buy_volume_per_sec(),
+        }
+    }
+
+    fn flow_fill(&self, side: OrderSide, ahead: f64, size: f64, price: f64) -> SimFill {
+        let mean_volume = self.flow_rate(side) * self.config.maker_horizon_secs;
+        if mean_volume <= 0.0 {
+            return SimFill::none();
+        }
+        SimFill {
+            fill_probability: (-(ahead + size) / mean_volume).exp(),
+            expected_size: mean_volume
+                * (-ahead / mean_volume).exp()
+                * (1.0 - (-size / mean_volume).exp()),
+            price: Some(price),
+        }
+    }
+}
+
+/// Expected fee in USDC for `fill` of `order` (as returned by [`FillModel::simulate`]),
+/// charged with the `fee_type` parameters in force at `book.ts_ms`. Taker orders and
+/// marketable maker limits pay the taker curve; resting quotes the maker curve
+/// (negative for a rebate). No schedule entry means no fee.
+pub fn expected_fee_usdc(
+    order: &SimOrder,
+    book: &BookTop,
+    fill: &SimFill,
+    fees: &FeeRegistry,
+    fee_type: &str,
+) -> f64 {
+    let (Some(price), Some(params)) = (
+        fill.price,
+        fees.fee_params_at(fee_type, book.ts_ms.div_euclid(1_000)),
+    ) else {
+        return 0.0;
+    };
+    let takes = match order.style {
+        OrderStyle::Taker => true,
+        OrderStyle::Maker { price: limit } => crosses_spread(order.side, limit, book),
+    };
+    let per_share = if takes {
+        params.taker_fee_per_share(price)
+    } else {
+        params.maker_fee_per_share(price)
+    };
+    per_share * fill.expected_size
+}
+
+fn crosses_spread(side: OrderSide, limit: f64, book: &BookTop) -> bool {
+    match side {
+        OrderSide::Buy => book.best_ask.is_some_and(|ask| limit >= ask),
+        OrderSide::Sell => book.best_bid.is_some_and(|bid| limit <= bid),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::queue_position::{QueuePositionTracker, RestingOrder};
+    use crate::sizing::SizingSide;
+
+    fn book() -> BookTop {
+        BookTop {
+            ts_ms: 0,
+            best_bid: Some(0.48),
+            best_ask: Some(0.52),
+            bid_size: 100.0,
+            ask_size: 40.0,
+        }
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn taker_crosses_spread_where_naive_mid_does_not() {
+        let order = SimOrder {
+            side: OrderSide::Buy,
+            size: 50.0,
+            style: OrderStyle::Taker,
+        };
+        let naive = FillModel::new(
+            FillModelConfig {
+                mode: FillMode::NaiveMid,
+                ..FillModelConfig::default()
+            },
+            FillCalibration::default(),
+        )
+        .simulate(&order, &book());
+        assert_eq!(naive.price, Some(0.5));
+        assert_eq!(naive.expected_size, 50.0);
+
+        let model = FillModel::new(
+            FillModelConfig {
+                taker_slippage: 0.01,
+                ..FillModelConfig::default()
+            },
+            FillCalibration::default(),
+        );
+        let fill = model.simulate(&order, &book());
+        assert_eq!(fill.price, Some(0.53));
+        assert_eq!(fill.expected_size, 40.0);
+        assert_eq!(fill.fill_probability, 0.0);
+
+        let sell = model.simulate(
+            &SimOrder {
+                side: OrderSide::Sell,
+                size: 10.0,
+                style: OrderStyle::Maker { price: 0.45 },
+            },
+            &book(),
+        );
+        assert_eq!(sell.price, Some(0.47));
+        assert_eq!(sell.fill_probability, 1.0);
+    }
+
+    #[test]
+    fn maker_fill_probability_comes_from_calibrated_flow_and_queue() {
+        let books = [
+            book(),
+            BookTop {
+                ts_ms: 100_000,
+                best_bid: Some(0.49),
+                best_ask: Some(0.51),
+                ..book()
+            },
+        ];
+        let trades = [
+            TradePrint {
+                ts_ms: 10_000,
+                price: 0.48,
+                size: 300.0,
+                taker_side: OrderSide::Sell,
+            },
+            TradePrint {
+                ts_ms: 20_000,
+                price: 0.52,
+                size: 100.0,
+                taker_side: OrderSide::Buy,
+            },
+        ];
+        let calibration = calibrate_fill_model(&books, &trades);
+        assert_eq!(calibration.observed_secs, 100.0);
+        assert!(close(calibration.sell_volume_per_sec(), 3.0));
+        assert!(close(calibration.mean_spread.unwrap(), 0.03));
+
+        let doubled = calibration.merge(&calibration);
+        assert!(close(doubled.sell_volume_per_sec(), 3.0));
+        assert_eq!(doubled.two_sided_samples, 4);
+
+        // 60s horizon at 3 shares/s: V = 180.
+        let model = FillModel::new(FillModelConfig::default(), calibration);
+        let join = |price: f64| {
+            model.simulate(
+                &SimOrder {
+                    side: OrderSide::Buy,
+                    size: 20.0,
+                    style: OrderStyle::Maker { price },
+                },
+                &book(),
+            )
+        };
+        let at_touch = join(0.48);
+        assert!(close(at_touch.fill_probability, (-120.0_f64 / 180.0).exp()));
+        assert!(close(
+            at_touch.expected_size,
+            180.0 * (-100.0_f64 / 180.0).exp() * (1.0 - (-20.0_f64 / 180.0).exp())
+        ));
+        let improving = join(0.49);
+        assert!(improving.fill_probability > at_touch.fill_probability);
+        assert!(close(improving.fill_probability, (-20.0_f64 / 180.0).exp()));
+
+        // Once resting, the tracked queue replaces the placement-time assumption.
+        let mut tracker = QueuePositionTracker::new();
+        tracker.place(
+            RestingOrder {
+                order_id: "a".to_string(),
+                slug: "btc-updown-5m-1".to_string(),
+                outcome: SizingSide::Yes,
+                side: OrderSide::Buy,
+                price: 0.48,
+                size: 20.0,
+                placed_ts_ms: 0,
+            },
+            100.0,
+        );
+        tracker.on_trade(
+            "btc-updown-5m-1",
+            SizingSide::Yes,
+            &TradePrint {
+                ts_ms: 1_000,
+                price: 0.48,
+                size: 70.0,
+                taker_side: OrderSide::Sell,
+            },
+        );
+        let queued = model.queued_maker_fill(tracker.get("a").unwrap());
+        assert!(close(queued.fill_probability, (-50.0_f64 / 180.0).exp()));
+        assert!(queued.fill_probability > at_touch.fill_probability);
+
+        let no_flow = FillModel::new(FillModelConfig::default(), FillCalibration::default());
+        assert_eq!(
+            no_flow
+                .simulate(
+                    &SimOrder {
+                        side: OrderSide::Buy,
+                        size: 20.0,
+                        style: OrderStyle::Maker { price: 0.49 },
+                    },
+                    &book(),
+                )
+                .expected_size,
+            0.0
+        );
+    }
+
+    #[test]
+    fn fees_follow_the_schedule_at_the_book_timestamp() {
+        use crate::fees::{FeeParams, FeeScheduleEntry, CRYPTO_15_MIN_FEE_TYPE};
+
+        let fees = FeeRegistry::new(vec![FeeScheduleEntry {
+            fee_type: CRYPTO_15_MIN_FEE_TYPE.to_string(),
+            effective_from_ts_utc: Some(1_000),
+            effective_until_ts_utc: None,
+            params: FeeParams {
+                taker_fee_rate: 0.25,
+                maker_fee_rate: -0.05,
+                fee_exponent: Some(2.0),
+            },
+        }])
+        .unwrap();
+        let fee = |style, ts_ms| {
+            let order = SimOrder {
+                side: OrderSide::Buy,
+                size: 10.0,
+                style,
+            };
+            let book = BookTop { ts_ms, ..book() };
+            let fill = SimFill {
+                fill_probability: 1.0,
+                expected_size: 10.0,
+                price: Some(0.5),
+            };
+            expected_fee_usdc(&order, &book, &fill, &fees, CRYPTO_15_MIN_FEE_TYPE)
+        };
+        assert!(close(fee(OrderStyle::Taker, 1_000_000), 0.15625));
+        assert!(close(
+            fee(OrderStyle::Maker { price: 0.55 }, 1_000_000),
+            0.15625
+        ));
+        assert!(close(
+            fee(OrderStyle::Maker { price: 0.48 }, 1_000_000),
+            -0.03125
+        ));
+        assert_eq!(fee(OrderStyle::Taker, 999_999), 0.0);
+    }
+}
@@ -0,0 +1,282 @@
+//! Gamma base URL pool with passive health checks and latency tracking.
+//!
+//! Every lookup reports its outcome back to the pool. An endpoint that fails
+//! `unhealthy_after_failures` times in a row is skipped for `cooldown_ms`, then gets a
+//! single half-open trial: success restores it, failure starts a new cooldown. Healthy
+//! endpoints are tried fastest first by an exponentially weighted latency average, so a
+//! slow primary loses traffic to a mirror before it starts failing outright.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::env_vars::Settings;
+
+pub const DEFAULT_GAMMA_BASE_URL: &str = "https://gamma-api.polymarket.com";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GammaEndpointConfig {
+    /// Base URLs in preference order; ties in latency go to the earlier one.
+    pub base_urls: Vec<String>,
+    pub unhealthy_after_failures: u32,
+    pub cooldown_ms: u64,
+    /// Weight of the newest sample in the latency average (`0 < alpha <= 1`).
+    pub latency_ewma_alpha: f64,
+}
+
+impl Default for GammaEndpointConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl GammaEndpointConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_GAMMA_BASE_URLS` (comma list, primary first),
+    /// `PMM_GAMMA_UNHEALTHY_AFTER`, and `PMM_GAMMA_COOLDOWN_MS`, falling back to
+    /// defaults for missing/invalid values.
+    pub fn from_settings(settings: &Settings) -> Self {
+        let base_urls = settings.parsed("PMM_GAMMA_BASE_URLS", |raw| {
+            let urls: Vec<String> = raw
+                .split(',')
+                .map(|url| url.trim().trim_end_matches('/').to_string())
+                .filter(|url| !url.is_empty())
+                .collect();
+            (!urls.is_empty()).then_some(urls)
+        });
+        Self {
+            base_urls: base_urls.expect("PMM_GAMMA_BASE_URLS has a default"),
+            unhealthy_after_failures: settings
+                .get_where("PMM_GAMMA_UNHEALTHY_AFTER", |failures| *failures > 0),
+            cooldown_ms: settings.get("PMM_GAMMA_COOLDOWN_MS"),
+            latency_ewma_alpha: 0.3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GammaEndpointStatus {
+    pub base_url: String,
+    pub healthy: bool,
+    pub ewma_latency_ms: Option<f64>,
+    pub last_latency_ms: Option<u64>,
+    pub consecutive_failures: u32,
+    pub successes: u64,
+    pub failures: u64,
+}
+
+#[derive(Debug)]
+struct EndpointState {
+    status: GammaEndpointStatus,
+    /// Set while unhealthy; the endpoint is skipped until then.
+    retry_at: Option<Instant>,
+}
+
+/// Shared endpoint pool; clones share health and latency state.
+#[derive(Debug, Clone)]
+pub struct GammaEndpointPool {
+    cfg: Arc<GammaEndpointConfig>,
+    endpoints: Arc<Mutex<Vec<EndpointState>>>,
+}
+
+impl GammaEndpointPool {
+    pub fn new(cfg: GammaEndpointConfig) -> Self {
+        let base_urls = if cfg.base_urls.is_empty() {
+            GammaEndpointConfig::default().base_urls
+        } else {
+            cfg.base_urls.clone()
+        };
+        let endpoints = base_urls
+            .into_iter()
+            .map(|base_url| EndpointState {
+                status: GammaEndpointStatus {
+                    base_url,
+                    healthy: true,
+                    ewma_latency_ms: None,
+                    last_latency_ms: None,
+                    consecutive_failures: 0,
+                    successes: 0,
+                    failures: 0,
+                },
+                retry_at: None,
+            })
+            .collect();
+        Self {
+            cfg: Arc::new(cfg),
+            endpoints: Arc::new(Mutex::new(endpoints)),
+        }
+    }
+
+    pub fn base_urls(&self) -> Vec<String> {
+        self.lock()
+            .iter()
+            .map(|endpoint| endpoint.status.base_url.clone())
+            .collect()
+    }
+
+    /// Endpoint indexes in the order they should be tried: healthy ones by latency,
+    /// then unhealthy ones whose cooldown has passed. When every endpoint is cooling
+    /// down, all of them are returned (soonest retry first) rather than none.
+    pub fn order(&self, now: Instant) -> Vec<usize> {
+        let endpoints = self.lock();
+        let mut healthy = Vec::new();
+        let mut trial = Vec::new();
+        let mut cooling = Vec::new();
+        for (idx, endpoint) in endpoints.iter().enumerate() {
+            match endpoint.retry_at {
+                None => healthy.push(idx),
+                Some(retry_at) if retry_at <= now => trial.push(idx),
+                Some(retry_at) => cooling.push((retry_at, idx)),
+            }
+        }
+        // Unmeasured endpoints sort first so they get a latency sample.
+        healthy.sort_by(|a, b| {
+            let latency = |idx: &usize| endpoints[*idx].status.ewma_latency_ms.unwrap_or(0.0);
+            latency(a).total_cmp(&latency(b)).then(a.cmp(b))
+        });
+        healthy.extend(trial);
+        if healthy.is_empty() {
+            cooling.sort();
+            healthy.extend(cooling.into_iter().map(|(_, idx)| idx));
+        }
+        healthy
+    }
+
+    /// Records a response (including "not found") from endpoint `idx`.
+    pub fn record_success(&self, idx: usize, latency: Duration) {
+        let alpha = self.cfg.latency_ewma_alpha.clamp(f64::EPSILON, 1.0);
+        let mut endpoints = self.lock();
+        let Some(endpoint) = endpoints.get_mut(idx) else {
+            return;
+        };
+        let latency_ms = latency.as_millis() as u64;
+        let status = &mut endpoint.status;
+        status.ewma_latency_ms = Some(match status.ewma_latency_ms {
+            Some(prev) => prev + alpha * (latency_ms as f64 - prev),
+            None => latency_ms as f64,
+        });
+        status.last_latency_ms = Some(latency_ms);
+        status.successes += 1;
+        status.consecutive_failures = 0;
+        if endpoint.retry_at.take().is_some() {
+            status.healthy = true;
+            info!(
+                component = "discovery",
+                event = "gamma.endpoint.recovered",
+                base_url = %status.base_url,
+                latency_ms
+            );
+        }
+    }
+
+    /// Records a transport failure or timeout from endpoint `idx`.
+    pub fn record_failure(&self, idx: usize, now: Instant, reason: &str) {
+        let cooldown = Duration::from_millis(self.cfg.cooldown_ms);
+        let threshold = self.cfg.unhealthy_after_failures.max(1);
+        let mut endpoints = self.lock();
+        let Some(endpoint) = endpoints.get_mut(idx) else {
+            return;
+        };
+        let status = &mut endpoint.status;
+        status.failures += 1;
+        status.consecutive_failures = status.consecutive_failures.saturating_add(1);
+        let was_healthy = endpoint.retry_at.is_none();
+        if !was_healthy || status.consecutive_failures >= threshold {
+            // A failed half-open trial restarts the cooldown.
+            endpoint.retry_at = Some(now + cooldown);
+            status.healthy = false;
+            if was_healthy {
+                warn!(
+                    component = "discovery",
+                    event = "gamma.endpoint.unhealthy",
+                    base_url = %status.base_url,
+                    consecutive_failures = status.consecutive_failures,
+                    cooldown_ms = self.cfg.cooldown_ms,
+                    reason
+                );
+            }
+        }
+    }
+
+    pub fn statuses(&self) -> Vec<GammaEndpointStatus> {
+        self.lock()
+            .iter()
+            .map(|endpoint| endpoint.status.clone())
+            .collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<EndpointState>> {
+        self.endpoints
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool() -> GammaEndpointPool {
+        GammaEndpointPool::new(GammaEndpointConfig {
+            base_urls: vec!["http://primary".to_string(), "http://mirror".to_string()],
+            unhealthy_after_failures: 2,
+            cooldown_ms: 1_000,
+            latency_ewma_alpha: 0.5,
+        })
+    }
+
+    #[test]
+    fn failing_primary_is_skipped_until_cooldown_then_trialled() {
+        let pool = pool();
+        let t0 = Instant::now();
+        assert_eq!(pool.order(t0), vec![0, 1]);
+
+        pool.record_failure(0, t0, "timeout");
+        assert_eq!(pool.order(t0), vec![0, 1]);
+        pool.record_failure(0, t0, "timeout");
+        assert_eq!(pool.order(t0), vec![1]);
+        assert!(!pool.statuses()[0].healthy);
+
+        let later = t0 + Duration::from_millis(1_000);
+        assert_eq!(pool.order(later), vec![1, 0]);
+        // One failed trial is enough to cool down again.
+        pool.record_failure(0, later, "timeout");
+        assert_eq!(pool.order(later), vec![1]);
+
+        let much_later = later + Duration::from_millis(1_000);
+        pool.record_success(1, Duration::from_millis(200));
+        pool.record_success(0, Duration::from_millis(40));
+        assert_eq!(pool.order(much_later), vec![0, 1]);
+        assert!(pool.statuses()[0].healthy);
+        assert_eq!(pool.statuses()[0].consecutive_failures, 0);
+
+        for idx in [0, 1] {
+            pool.record_failure(idx, much_later, "down");
+            pool.record_failure(idx, much_later, "down");
+        }
+        // Everything cooling down: still hand out endpoints rather than none.
+        assert_eq!(pool.order(much_later).len(), 2);
+    }
+
+    #[test]
+    fn healthy_endpoints_are_ordered_by_latency_average() {
+        let pool = pool();
+        let now = Instant::now();
+        pool.record_success(0, Duration::from_millis(900));
+        assert_eq!(pool.order(now), vec![1, 0]);
+        pool.record_success(1, Duration::from_millis(100));
+        assert_eq!(pool.order(now), vec![1, 0]);
+
+        pool.record_success(1, Duration::from_millis(1_900));
+        let mirror = &pool.statuses()[1];
+        assert_eq!(mirror.ewma_latency_ms, Some(1_000.0));
+        assert_eq!(mirror.last_latency_ms, Some(1_900));
+        assert_eq!(pool.order(now), vec![0, 1]);
+    }
+}
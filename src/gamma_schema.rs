@@ -0,0 +1,498 @@
+//! Raw Gamma payload sampling for schema-drift detection.
+//!
+//! The SDK's `Market` type silently drops fields it does not know. When enabled, every
+//! Nth resolved market is fetched again as raw JSON and stored next to the SDK's parsed
+//! (re-serialized) view of the same market. Keys present in the raw payload but missing
+//! from the parsed view are fields the SDK drops; [`schema_drift_report`] aggregates
+//! them across stored samples and flags fee/reward-looking names, since those feed fee
+//! modeling.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use chrono::Utc;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+use tracing::{debug, warn};
+
+use crate::env_vars::Settings;
+
+/// Name fragments that make an unknown field fee/reward-relevant.
+const FEE_REWARD_MARKERS: [&str; 3] = ["fee", "reward", "rebate"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GammaPayloadConfig {
+    /// Record one of every N resolved markets; `None` disables sampling.
+    pub sample_every: Option<u64>,
+    pub store_path: PathBuf,
+    /// Stored samples kept; older ones are deleted on insert.
+    pub max_samples: usize,
+    /// Timeout for the raw payload request.
+    pub timeout_ms: u64,
+}
+
+impl Default for GammaPayloadConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl GammaPayloadConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_GAMMA_PAYLOAD_SAMPLE_EVERY` (unset, `0`, or `off` disables),
+    /// `PMM_GAMMA_PAYLOAD_STORE_PATH`, and `PMM_GAMMA_PAYLOAD_MAX_SAMPLES`, falling back
+    /// to defaults for missing/invalid values.
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            sample_every: settings
+                .get::<Option<u64>>("PMM_GAMMA_PAYLOAD_SAMPLE_EVERY")
+                .filter(|every| *every > 0),
+            store_path: settings.get("PMM_GAMMA_PAYLOAD_STORE_PATH"),
+            max_samples: settings.get_where("PMM_GAMMA_PAYLOAD_MAX_SAMPLES", |max| *max > 0),
+            timeout_ms: 3_000,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum GammaPayloadError {
+    #[error("sqlite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("json error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("http error: {0}")]
+    Http(String),
+}
+
+/// One stored sample: the raw Gamma payload and the SDK's parsed view of it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GammaPayloadSample {
+    pub slug: String,
+    pub endpoint: String,
+    pub fetched_ts_utc: i64,
+    pub raw: Value,
+    pub parsed: Value,
+}
+
+impl GammaPayloadSample {
+    /// Fields in `raw` that the parsed view dropped.
+    pub fn unknown_fields(&self) -> BTreeSet<String> {
+        unknown_fields(&self.raw, &self.parsed)
+    }
+}
+
+/// SQLite store of sampled payloads (`gamma_payloads`), bounded to `max_samples` rows.
+pub struct GammaPayloadStore {
+    conn: Connection,
+    max_samples: usize,
+}
+
+impl GammaPayloadStore {
+    pub fn open(path: &Path, max_samples: usize) -> Result<Self, GammaPayloadError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "
+            PRAGMA journal_mode=WAL;
+            CREATE TABLE IF NOT EXISTS gamma_payloads (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                slug TEXT NOT NULL,
+                endpoint TEXT NOT NULL,
+                fetched_ts_utc INTEGER NOT NULL,
+                raw_json TEXT NOT NULL,
+                parsed_json TEXT NOT NULL
+            );
+            ",
+        )?;
+        Ok(Self {
+            conn,
+            max_samples: max_samples.max(1),
+        })
+    }
+
+    pub fn insert(&mut self, sample: &GammaPayloadSample) -> Result<(), GammaPayloadError> {
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT INTO gamma_payloads (slug, endpoint, fetched_ts_utc, raw_json, parsed_json)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                sample.slug,
+                sample.endpoint,
+                sample.fetched_ts_utc,
+                serde_json::to_string(&sample.raw)?,
+                serde_json::to_string(&sample.parsed)?,
+            ],
+        )?;
+        tx.execute(
+            "DELETE FROM gamma_payloads WHERE id NOT IN (
+                 SELECT id FROM gamma_payloads ORDER BY id DESC LIMIT ?1
+             )",
+            params![self.max_samples as i64],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Up to `limit` samples, newest first.
+    pub fn samples(&self, limit: usize) -> Result<Vec<GammaPayloadSample>, GammaPayloadError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT slug, endpoint, fetched_ts_utc, raw_json, parsed_json
+             FROM gamma_payloads ORDER BY id DESC LIMIT ?1",
+        )?;
+        let rows = stmt.query_map(params![limit.min(i64::MAX as usize) as i64], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+            ))
+        })?;
+        rows.map(|row| {
+            let (slug, endpoint, fetched_ts_utc, raw, parsed) = row?;
+            Ok(GammaPayloadSample {
+                slug,
+                endpoint,
+                fetched_ts_utc,
+                raw: serde_json::from_str(&raw)?,
+                parsed: serde_json::from_str(&parsed)?,
+            })
+        })
+        .collect()
+    }
+}
+
+/// Dotted paths of object keys present in `raw` but absent from `parsed`. Arrays are
+/// compared element by element and shown as `[]`; values the parsed side holds as
+/// `null` or a scalar are not descended into.
+pub fn unknown_fields(raw: &Value, parsed: &Value) -> BTreeSet<String> {
+    let mut out = BTreeSet::new();
+    collect_unknown_fields(raw, parsed, "", &mut out);
+    out
+}
+
+fn collect_unknown_fields(raw: &Value, parsed: &Value, path: &str, out: &mut BTreeSet<String>) {
+    match (raw, parsed) {
+        (Value::Object(raw), Value::Object(parsed)) => {
+            for (key, raw_value) in raw {
+                let child = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                match parsed.get(key) {
+                    Some(parsed_value) => {
+                        collect_unknown_fields(raw_value, parsed_value, &child, out)
+                    }
+                    None => {
+                        out.insert(child);
+                    }
+                }
+            }
+        }
+        (Value::Array(raw), Value::Array(parsed)) => {
+            let child = format!("{path}[]");
+            for (raw_value, parsed_value) in raw.iter().zip(parsed) {
+                collect_unknown_fields(raw_value, parsed_value, &child, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Whether a field path names something fee/reward-like.
+pub fn is_fee_or_reward_field(path: &str) -> bool {
+    let lower = path.to_ascii_lowercase();
+    FEE_REWARD_MARKERS
+        .iter()
+        .any(|marker| lower.contains(marker))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnknownGammaField {
+    pub path: String,
+    /// Samples containing the field.
+    pub samples: usize,
+    pub fee_or_reward: bool,
+    /// Most recent sample containing it.
+    pub example_slug: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaDriftReport {
+    pub samples: usize,
+    /// Fee/reward-relevant fields first, then by path.
+    pub unknown_fields: Vec<UnknownGammaField>,
+}
+
+impl SchemaDriftReport {
+    pub fn has_fee_or_reward_drift(&self) -> bool {
+        self.unknown_fields.iter().any(|field| field.fee_or_reward)
+    }
+}
+
+/// Aggregates unknown fields over `samples` (newest first, as returned by
+/// [`GammaPayloadStore::samples`]).
+pub fn schema_drift_report(samples: &[GammaPayloadSample]) -> SchemaDriftReport {
+    let mut fields: BTreeMap<String, (usize, &str)> = BTreeMap::new();
+    for sample in samples {
+        for path in sample.unknown_fields() {
+            fields.entry(path).or_insert((0, &sample.slug)).0 += 1;
+        }
+    }
+    let mut unknown_fields = fields
+        .into_iter()
+        .map(|(path, (samples, example_slug))| UnknownGammaField {
+            fee_or_reward: is_fee_or_reward_field(&path),
+            path,
+            samples,
+            example_slug: example_slug.to_string(),
+        })
+        .collect::<Vec<_>>();
+    unknown_fields.sort_by(|a, b| {
+        b.fee_or_reward
+            .cmp(&a.fee_or_reward)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    SchemaDriftReport {
+        samples: samples.len(),
+        unknown_fields,
+    }
+}
+
+/// Samples resolved markets during discovery: fetches the raw payload, stores it with
+/// the parsed view, and logs fields the parser dropped.
+pub struct GammaPayloadSampler {
+    every: u64,
+    seen: AtomicU64,
+    store: Mutex<GammaPayloadStore>,
+    http: reqwest::Client,
+}
+
+impl GammaPayloadSampler {
+    /// `Ok(None)` when sampling is disabled.
+    pub fn open(cfg: &GammaPayloadConfig) -> Result<Option<Self>, GammaPayloadError> {
+        let Some(every) = cfg.sample_every else {
+            return Ok(None);
+        };
+        let store = GammaPayloadStore::open(&cfg.store_path, cfg.max_samples)?;
+        let http = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_millis(cfg.timeout_ms))
+            .build()
+            .map_err(|err| GammaPayloadError::Http(err.to_string()))?;
+        Ok(Some(Self {
+            every: every.max(1),
+            seen: AtomicU64::new(0),
+            store: Mutex::new(store),
+            http,
+        }))
+    }
+
+    /// Counts one resolved market; `true` for every Nth (starting with the first).
+    pub fn should_sample(&self) -> bool {
+        self.seen
+            .fetch_add(1, Ordering::Relaxed)
+            .is_multiple_of(self.every)
+    }
+
+    /// Counts one resolved market and, when it is sampled, records it in the background
+    /// so discovery never waits on the extra request. Needs a Tokio runtime.
+    pub fn sample<M: Serialize>(
+        self: &Arc<Self>,
+        base_url: &str,
+        slug: &str,
+        include_tag: bool,
+        parsed: &M,
+    ) {
+        if !self.should_sample() {
+            return;
+        }
+        let parsed = match serde_json::to_value(parsed) {
+            Ok(parsed) => parsed,
+            Err(err) => {
+                log_sample_error(base_url, slug, &err.into());
+                return;
+            }
+        };
+        let sampler = Arc::clone(self);
+        let base_url = base_url.to_string();
+        let slug = slug.to_string();
+        tokio::spawn(async move {
+            sampler.record(&base_url, &slug, include_tag, parsed).await;
+        });
+    }
+
+    /// Fetches `slug` from `base_url` as raw JSON and stores it next to `parsed`.
+    /// Failures are logged (`gamma_schema.sample_error`) and never affect discovery.
+    pub async fn record(&self, base_url: &str, slug: &str, include_tag: bool, parsed: Value) {
+        if let Err(err) = self.try_record(base_url, slug, include_tag, parsed).await {
+            log_sample_error(base_url, slug, &err);
+        }
+    }
+
+    async fn try_record(
+        &self,
+        base_url: &str,
+        slug: &str,
+        include_tag: bool,
+        parsed: Value,
+    ) -> Result<(), GammaPayloadError> {
+        let mut url = format!("{}/markets/slug/{slug}", base_url.trim_end_matches('/'));
+        if include_tag {
+            url.push_str("?include_tag=true");
+        }
+        let http_err = |err: reqwest::Error| GammaPayloadError::Http(err.to_string());
+        let body = self
+            .http
+            .get(&url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(http_err)?
+            .bytes()
+            .await
+            .map_err(http_err)?;
+        let sample = GammaPayloadSample {
+            slug: slug.to_string(),
+            endpoint: base_url.to_string(),
+            fetched_ts_utc: Utc::now().timestamp(),
+            raw: serde_json::from_slice(&body)?,
+            parsed,
+        };
+        let unknown = sample.unknown_fields();
+        self.store
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(&sample)?;
+
+        if unknown.is_empty() {
+            debug!(
+                component = "gamma_schema",
+                event = "gamma_schema.sampled",
+                slug,
+                endpoint = base_url
+            );
+        } else {
+            let fields = unknown.iter().cloned().collect::<Vec<_>>().join(",");
+            warn!(
+                component = "gamma_schema",
+                event = "gamma_schema.unknown_fields",
+                slug,
+                endpoint = base_url,
+                fee_or_reward = unknown.iter().any(|path| is_fee_or_reward_field(path)),
+                fields = %fields
+            );
+        }
+        Ok(())
+    }
+}
+
+fn log_sample_error(base_url: &str, slug: &str, err: &GammaPayloadError) {
+    warn!(
+        component = "gamma_schema",
+        event = "gamma_schema.sample_error",
+        slug,
+        endpoint = base_url,
+        error = %err
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sample(slug: &str, raw: Value, parsed: Value) -> GammaPayloadSample {
+        GammaPayloadSample {
+            slug: slug.to_string(),
+            endpoint: "http://gamma".to_string(),
+            fetched_ts_utc: 0,
+            raw,
+            parsed,
+        }
+    }
+
+    #[test]
+    fn reports_dropped_fields_with_fee_and_reward_names_first() {
+        let parsed = json!({
+            "id": "1",
+            "fee": null,
+            "outcomes": "[\"Up\",\"Down\"]",
+            "events": [{"id": "9", "series": null}],
+        });
+        let older = sample(
+            "btc-updown-5m-1",
+            json!({
+                "id": "1",
+                "fee": "0",
+                "outcomes": "[\"Up\",\"Down\"]",
+                "events": [{"id": "9", "series": {"id": "3"}, "newTag": 1}],
+            }),
+            parsed.clone(),
+        );
+        let newer = sample(
+            "btc-updown-5m-2",
+            json!({
+                "id": "1",
+                "feeSchedule": {"taker": "0.02"},
+                "events": [{"id": "9", "newTag": 1}],
+            }),
+            parsed,
+        );
+        assert_eq!(
+            older.unknown_fields().into_iter().collect::<Vec<_>>(),
+            ["events[].newTag"]
+        );
+
+        let report = schema_drift_report(&[newer, older]);
+        assert_eq!(report.samples, 2);
+        assert!(report.has_fee_or_reward_drift());
+        assert_eq!(
+            report.unknown_fields,
+            [
+                UnknownGammaField {
+                    path: "feeSchedule".to_string(),
+                    samples: 1,
+                    fee_or_reward: true,
+                    example_slug: "btc-updown-5m-2".to_string(),
+                },
+                UnknownGammaField {
+                    path: "events[].newTag".to_string(),
+                    samples: 2,
+                    fee_or_reward: false,
+                    example_slug: "btc-updown-5m-2".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn store_keeps_only_the_newest_samples() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = GammaPayloadStore::open(&dir.path().join("payloads.sqlite"), 2).unwrap();
+        for n in 0..3 {
+            store
+                .insert(&sample(&format!("slug-{n}"), json!({"n": n}), json!({})))
+                .unwrap();
+        }
+        let slugs = store
+            .samples(10)
+            .unwrap()
+            .into_iter()
+            .map(|sample| sample.slug)
+            .collect::<Vec<_>>();
+        assert_eq!(slugs, ["slug-2", "slug-1"]);
+    }
+}
@@ -0,0 +1,563 @@
+//! Snapshot time series for Grafana's JSON datasource.
+//!
+//! [`SnapshotRecorder`] samples the dashboard snapshot source on an interval and keeps
+//! per-slug series of the numeric columns in memory, bounded by age and downsampled by
+//! a [`SnapshotRetentionPolicy`] compaction job. [`grafana_router`]
+//! serves them with the simple-JSON datasource API (`/grafana`, `/grafana/search`,
+//! `/grafana/query`), so a stock Grafana can chart pmm data without an exporter.
+//!
+//! Targets are `<metric>:<slug>`, `<metric>:*` (every slug), or `net_profit:total`.
+//! Mock (demo) cells are never recorded.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::dashboard::{DashboardRow, DashboardSnapshot, DashboardSnapshotSource};
+use crate::env_vars::Settings;
+
+/// Snapshot columns recorded as series, by dashboard column key. `position_net` is
+/// recorded as the signed position size (NO positions negative); `mid` is the YES
+/// book mid from `best_bid_yes`/`best_ask_yes`.
+pub const GRAFANA_METRICS: [&str; 5] =
+    ["net_profit", "position_net", "probability", "price", "mid"];
+/// Sum of `net_profit` across rows with a live value.
+pub const NET_PROFIT_TOTAL_TARGET: &str = "net_profit:total";
+
+/// Time-bucketed downsampling of recorded series, by sample age: every sample for
+/// `full_secs`, one per `decimate_every` sample intervals up to `decimated_secs`, and
+/// one per `coarse_bucket_secs` beyond. Buckets are aligned to the epoch, so compacting
+/// an already compacted series changes nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnapshotRetentionPolicy {
+    pub full_secs: i64,
+    pub decimated_secs: i64,
+    pub decimate_every: u32,
+    pub coarse_bucket_secs: i64,
+    /// Period of the compaction job started by [`SnapshotRecorder::spawn`].
+    pub compact_interval_secs: u64,
+}
+
+impl Default for SnapshotRetentionPolicy {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl SnapshotRetentionPolicy {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_GRAFANA_FULL_RES_SECS`, `PMM_GRAFANA_DECIMATED_SECS`,
+    /// `PMM_GRAFANA_DECIMATE_EVERY`, `PMM_GRAFANA_COARSE_BUCKET_SECS`, and
+    /// `PMM_GRAFANA_COMPACT_INTERVAL_SECS`, falling back to defaults for missing/invalid
+    /// values. A decimated horizon shorter than the full-resolution one is raised to it.
+    pub fn from_settings(settings: &Settings) -> Self {
+        let full_secs = settings.get_where("PMM_GRAFANA_FULL_RES_SECS", |secs| *secs > 0);
+        Self {
+            full_secs,
+            decimated_secs: settings
+                .get_where("PMM_GRAFANA_DECIMATED_SECS", |secs: &i64| *secs > 0)
+                .max(full_secs),
+            decimate_every: settings.get_where("PMM_GRAFANA_DECIMATE_EVERY", |every| *every > 0),
+            coarse_bucket_secs: settings
+                .get_where("PMM_GRAFANA_COARSE_BUCKET_SECS", |secs| *secs > 0),
+            compact_interval_secs: settings
+                .get_where("PMM_GRAFANA_COMPACT_INTERVAL_SECS", |secs| *secs > 0),
+        }
+    }
+}
+
+/// Point counts of one compaction pass, across all series.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SnapshotCompaction {
+    pub points_before: usize,
+    pub points_after: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotRecorderConfig {
+    /// Sampling period; `None` disables recording and the Grafana routes.
+    pub sample_interval_ms: Option<u64>,
+    /// Points older than this are dropped.
+    pub retention_secs: i64,
+    pub retention: SnapshotRetentionPolicy,
+}
+
+impl Default for SnapshotRecorderConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl SnapshotRecorderConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_GRAFANA_SAMPLE_MS` (`off` disables), `PMM_GRAFANA_RETENTION_SECS`, and
+    /// the [`SnapshotRetentionPolicy`] variables, falling back to defaults for
+    /// missing/invalid values.
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            sample_interval_ms: settings.get_where("PMM_GRAFANA_SAMPLE_MS", |ms| *ms != Some(0)),
+            retention_secs: settings.get_where("PMM_GRAFANA_RETENTION_SECS", |secs| *secs > 0),
+            retention: SnapshotRetentionPolicy::from_settings(settings),
+        }
+    }
+}
+
+type SeriesMap = HashMap<String, VecDeque<(i64, f64)>>;
+
+/// In-memory per-target series; clones share the same data.
+#[derive(Clone)]
+pub struct SnapshotRecorder {
+    retention_ms: i64,
+    series: Arc<RwLock<SeriesMap>>,
+}
+
+impl SnapshotRecorder {
+    pub fn new(retention_secs: i64) -> Self {
+        Self {
+            retention_ms: retention_secs.max(1) * 1_000,
+            series: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Starts sampling `source` and the compaction job; `None` when recording is
+    /// disabled. Needs a Tokio runtime.
+    pub fn spawn(
+        source: Arc<dyn DashboardSnapshotSource>,
+        cfg: &SnapshotRecorderConfig,
+    ) -> Option<Self> {
+        let interval_ms = cfg.sample_interval_ms?;
+        let recorder = Self::new(cfg.retention_secs);
+        let task_recorder = recorder.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(Duration::from_millis(interval_ms));
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            loop {
+                ticker.tick().await;
+                task_recorder.record(&source.snapshot(), Utc::now().timestamp_millis());
+            }
+        });
+        let compact_recorder = recorder.clone();
+        let policy = cfg.retention;
+        tokio::spawn(async move {
+            let mut ticker =
+                tokio::time::interval(Duration::from_secs(policy.compact_interval_secs.max(1)));
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            loop {
+                ticker.tick().await;
+                let started_at = std::time::Instant::now();
+                let compaction =
+                    compact_recorder.compact(&policy, interval_ms, Utc::now().timestamp_millis());
+                info!(
+                    component = "grafana",
+                    event = "snapshot_retention.compacted",
+                    points_before = compaction.points_before,
+                    points_after = compaction.points_after,
+                    duration_ms = started_at.elapsed().as_millis() as u64
+                );
+            }
+        });
+        Some(recorder)
+    }
+
+    /// Appends one sample of every numeric, non-mock cell and drops expired points.
+    pub fn record(&self, snapshot: &DashboardSnapshot, ts_ms: i64) {
+        let mut samples = Vec::new();
+        let mut net_profit_total = None::<f64>;
+        for row in &snapshot.rows {
+            for metric in GRAFANA_METRICS {
+                let Some(value) = metric_value(row, metric) else {
+                    continue;
+                };
+                if metric == "net_profit" {
+                    *net_profit_total.get_or_insert(0.0) += value;
+                }
+                samples.push((format!("{metric}:{}", row.slug), value));
+            }
+        }
+        if let Some(total) = net_profit_total {
+            samples.push((NET_PROFIT_TOTAL_TARGET.to_string(), total));
+        }
+
+        let cutoff_ms = ts_ms - self.retention_ms;
+        let mut series = self.write();
+        for (target, value) in samples {
+            series.entry(target).or_default().push_back((ts_ms, value));
+        }
+        series.retain(|_, points| {
+            while points.front().is_some_and(|(ts, _)| *ts < cutoff_ms) {
+                points.pop_front();
+            }
+            !points.is_empty()
+        });
+    }
+
+    /// Downsamples every series by `policy` as of `now_ms`; `sample_interval_ms` sizes
+    /// the decimated buckets.
+    pub fn compact(
+        &self,
+        policy: &SnapshotRetentionPolicy,
+        sample_interval_ms: u64,
+        now_ms: i64,
+    ) -> SnapshotCompaction {
+        let mut compaction = SnapshotCompaction::default();
+        let mut series = self.write();
+        for points in series.values_mut() {
+            compaction.points_before += points.len();
+            compact_points(points, policy, sample_interval_ms, now_ms);
+            compaction.points_after += points.len();
+        }
+        compaction
+    }
+
+    /// Known targets containing `filter`, sorted.
+    pub fn targets(&self, filter: &str) -> Vec<String> {
+        let mut targets = self
+            .read()
+            .keys()
+            .filter(|target| target.contains(filter))
+            .cloned()
+            .collect::<Vec<_>>();
+        targets.sort();
+        targets
+    }
+
+    /// Points of `target` in `[from_ms, to_ms]`, thinned evenly to at most `max_points`.
+    pub fn points(
+        &self,
+        target: &str,
+        from_ms: i64,
+        to_ms: i64,
+        max_points: usize,
+    ) -> Vec<(i64, f64)> {
+        let series = self.read();
+        let Some(points) = series.get(target) else {
+            return Vec::new();
+        };
+        let in_range = points
+            .iter()
+            .filter(|(ts, _)| (from_ms..=to_ms).contains(ts))
+            .copied()
+            .collect::<Vec<_>>();
+        thin_points(in_range, max_points)
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, SeriesMap> {
+        self.series
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, SeriesMap> {
+        self.series
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Keeps the first point of each retention bucket of a time-ordered series.
+fn compact_points(
+    points: &mut VecDeque<(i64, f64)>,
+    policy: &SnapshotRetentionPolicy,
+    sample_interval_ms: u64,
+    now_ms: i64,
+) {
+    let full_cutoff_ms = now_ms - policy.full_secs * 1_000;
+    let decimated_cutoff_ms = now_ms - policy.decimated_secs * 1_000;
+    let decimated_bucket_ms =
+        (sample_interval_ms.saturating_mul(u64::from(policy.decimate_every)) as i64).max(1);
+    let coarse_bucket_ms = (policy.coarse_bucket_secs * 1_000).max(1);
+    let mut last_bucket = None;
+    points.retain(|(ts, _)| {
+        let bucket = if *ts >= full_cutoff_ms {
+            return true;
+        } else if *ts >= decimated_cutoff_ms {
+            (false, ts.div_euclid(decimated_bucket_ms))
+        } else {
+            (true, ts.div_euclid(coarse_bucket_ms))
+        };
+        if last_bucket == Some(bucket) {
+            return false;
+        }
+        last_bucket = Some(bucket);
+        true
+    });
+}
+
+/// Keeps every n-th point so at most `max_points` remain.
+pub(crate) fn thin_points(points: Vec<(i64, f64)>, max_points: usize) -> Vec<(i64, f64)> {
+    let stride = points.len().div_ceil(max_points.max(1)).max(1);
+    points.into_iter().step_by(stride).collect()
+}
+
+fn metric_value(row: &DashboardRow, metric: &str) -> Option<f64> {
+    let mocked = |column: &str| row.mock_columns.iter().any(|entry| entry == column);
+    if metric == "mid" {
+        if mocked("best_bid_yes") || mocked("best_ask_yes") {
+            return None;
+        }
+        let bid = parse_number(row.best_bid_yes.as_deref()?)?;
+        let ask = parse_number(row.best_ask_yes.as_deref()?)?;
+        return Some((bid + ask) / 2.0);
+    }
+    if mocked(metric) {
+        return None;
+    }
+    match metric {
+        "net_profit" => parse_number(row.net_profit.as_deref()?),
+        "position_net" => signed_position_size(row.position_net.as_deref()?),
+        "probability" => parse_number(row.probability.as_deref()?),
+        "price" => parse_number(row.price.as_deref()?),
+        _ => None,
+    }
+}
+
+fn parse_number(raw: &str) -> Option<f64> {
+    raw.trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|value| value.is_finite())
+}
+
+/// `position_net` is `size@price@side`; charted as size, negative for NO.
+fn signed_position_size(raw: &str) -> Option<f64> {
+    let mut parts = raw.split('@');
+    let size = parse_number(parts.next()?)?;
+    match parts.nth(1).map(str::trim) {
+        Some(side) if side.eq_ignore_ascii_case("no") => Some(-size),
+        _ => Some(size),
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct GrafanaSearchRequest {
+    #[serde(default)]
+    pub target: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GrafanaRange {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GrafanaTarget {
+    pub target: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrafanaQueryRequest {
+    pub range: GrafanaRange,
+    #[serde(default)]
+    pub targets: Vec<GrafanaTarget>,
+    #[serde(default)]
+    pub max_data_points: Option<usize>,
+}
+
+/// One `timeserie` response entry: `datapoints` are `[value, unix_ms]`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GrafanaSeries {
+    pub target: String,
+    pub datapoints: Vec<(f64, i64)>,
+}
+
+/// Router serving the Grafana simple-JSON datasource API under `/grafana`.
+pub fn grafana_router(recorder: SnapshotRecorder) -> Router {
+    Router::new()
+        .route("/grafana", get(|| async { StatusCode::OK }))
+        .route("/grafana/", get(|| async { StatusCode::OK }))
+        .route("/grafana/search", post(post_grafana_search))
+        .route("/grafana/query", post(post_grafana_query))
+        .with_state(recorder)
+}
+
+async fn post_grafana_search(
+    State(recorder): State<SnapshotRecorder>,
+    body: Option<Json<GrafanaSearchRequest>>,
+) -> Json<Vec<String>> {
+    let filter = body.map(|Json(request)| request.target).unwrap_or_default();
+    Json(recorder.targets(&filter))
+}
+
+async fn post_grafana_query(
+    State(recorder): State<SnapshotRecorder>,
+    Json(request): Json<GrafanaQueryRequest>,
+) -> Response {
+    let from_ms = request.range.from.timestamp_millis();
+    let to_ms = request.range.to.timestamp_millis();
+    if from_ms > to_ms {
+        return (StatusCode::BAD_REQUEST, "range.from is after range.to").into_response();
+    }
+    let max_points = request.max_data_points.unwrap_or(1_000);
+    let mut out = Vec::new();
+    for requested in &request.targets {
+        let targets = match requested.target.strip_suffix(":*") {
+            Some(metric) => recorder
+                .targets(&format!("{metric}:"))
+                .into_iter()
+                .filter(|target| target != NET_PROFIT_TOTAL_TARGET)
+                .collect(),
+            None => vec![requested.target.clone()],
+        };
+        for target in targets {
+            let datapoints = recorder
+                .points(&target, from_ms, to_ms, max_points)
+                .into_iter()
+                .map(|(ts, value)| (value, ts))
+                .collect();
+            out.push(GrafanaSeries { target, datapoints });
+        }
+    }
+    Json(out).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    fn row(slug: &str, net_profit: Option<&str>, probability: Option<&str>) -> DashboardRow {
+        let mut row = DashboardRow::unresolved(slug, "BTC", "5m");
+        row.mock_columns.clear();
+        row.net_profit = net_profit.map(str::to_string);
+        row.probability = probability.map(str::to_string);
+        row
+    }
+
+    #[test]
+    fn recorder_skips_mock_and_non_numeric_cells_and_expires_old_points() {
+        let recorder = SnapshotRecorder::new(60);
+        let mut mocked = row("c", Some("9"), None);
+        mocked.mock_columns.push("net_profit".to_string());
+        let snapshot = DashboardSnapshot {
+            rows: vec![
+                row("a", Some("1.5"), Some("0.6")),
+                row("b", Some("-0.5"), Some("-")),
+                mocked,
+            ],
+            seq: 1,
+        };
+        recorder.record(&snapshot, 1_000);
+        assert_eq!(
+            recorder.targets(""),
+            [
+                "net_profit:a",
+                "net_profit:b",
+                "net_profit:total",
+                "probability:a"
+            ]
+        );
+        assert_eq!(
+            recorder.points(NET_PROFIT_TOTAL_TARGET, 0, 2_000, 10),
+            [(1_000, 1.0)]
+        );
+
+        for ts in (2_000..=61_000).step_by(1_000) {
+            recorder.record(&snapshot, ts);
+        }
+        let points = recorder.points("net_profit:a", 0, i64::MAX, 1_000);
+        assert_eq!(points.first(), Some(&(1_000, 1.5)));
+        recorder.record(&snapshot, 62_000);
+        let points = recorder.points("net_profit:a", 0, i64::MAX, 1_000);
+        assert_eq!(points.first(), Some(&(2_000, 1.5)));
+        let thinned = recorder.points("net_profit:a", 0, i64::MAX, 10);
+        assert!(thinned.len() <= 10 && thinned.len() > 5);
+
+        let mut short = row("d", None, None);
+        short.position_net = Some("2.5@0.4@NO".to_string());
+        recorder.record(
+            &DashboardSnapshot {
+                rows: vec![short],
+                seq: 2,
+            },
+            62_000,
+        );
+        assert_eq!(
+            recorder.points("position_net:d", 0, i64::MAX, 10),
+            [(62_000, -2.5)]
+        );
+
+        recorder.record(&DashboardSnapshot::default(), 200_000);
+        assert!(recorder.targets("").is_empty());
+    }
+
+    #[test]
+    fn compaction_downsamples_a_synthetic_week_by_age() {
+        // Eight days of 1 Hz samples, compacted hourly like the background job.
+        const START_MS: i64 = 1_735_689_600_000; // 2025-01-01T00:00:00Z
+        let policy = SnapshotRetentionPolicy::default();
+        let recorder = SnapshotRecorder::new(30 * 86_400);
+        let snapshot = DashboardSnapshot {
+            rows: vec![row("a", None, Some("0.5"))],
+            seq: 1,
+        };
+        let end_ms = START_MS + 8 * 86_400_000;
+        let mut raw_points = 0;
+        for ts in (START_MS..end_ms).step_by(1_000) {
+            recorder.record(&snapshot, ts);
+            raw_points += 1;
+            if (ts - START_MS) % 3_600_000 == 0 {
+                recorder.compact(&policy, 1_000, ts);
+            }
+        }
+        let now_ms = end_ms - 1_000;
+        let compaction = recorder.compact(&policy, 1_000, now_ms);
+        assert!(compaction.points_after < compaction.points_before);
+        assert_eq!(
+            recorder.compact(&policy, 1_000, now_ms),
+            SnapshotCompaction {
+                points_before: compaction.points_after,
+                points_after: compaction.points_after,
+            }
+        );
+
+        let points = recorder.points("probability:a", 0, i64::MAX, usize::MAX);
+        assert_eq!(points.len(), compaction.points_after);
+        assert!(points.len() < raw_points / 4);
+        let tier = |from_ms: i64, to_ms: i64| {
+            points
+                .iter()
+                .map(|(ts, _)| *ts)
+                .filter(|ts| (from_ms..to_ms).contains(ts))
+                .collect::<Vec<_>>()
+        };
+        let spacing = |tier: &[i64]| {
+            tier.windows(2)
+                .map(|pair| pair[1] - pair[0])
+                .collect::<HashSet<_>>()
+        };
+
+        let full = tier(now_ms - 86_400_000, i64::MAX);
+        assert_eq!(full.len(), 86_401);
+        assert_eq!(spacing(&full), HashSet::from([1_000]));
+
+        // Away from the tier boundaries: every 10th sample, then hourly.
+        let decimated = tier(
+            now_ms - 7 * 86_400_000 + 60_000,
+            now_ms - 86_400_000 - 60_000,
+        );
+        assert_eq!(spacing(&decimated), HashSet::from([10_000]));
+        let coarse = tier(START_MS, now_ms - 7 * 86_400_000);
+        assert_eq!(coarse.len(), 24);
+        assert_eq!(spacing(&coarse), HashSet::from([3_600_000]));
+    }
+}
@@ -0,0 +1,214 @@
+//! `/healthz`: process liveness plus the latest clock drift measurement and, for the
+//! live strategy, cold-start warm-up progress.
+//!
+//! Always answers `200` while the process serves requests; `status` turns `degraded`
+//! when the measured clock offset is at or past the alert threshold or warm-up failed.
+//!
+//! Readiness (`/readyz`, served by `dashboard_router` next to the snapshot source it
+//! checks) is separate: it answers `503` until the dashboard serves fresh live rows,
+//! so orchestrators do not route to an instance still showing demo data.
+
+use std::path::PathBuf;
+
+use axum::extract::State;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::{Json, Router};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::clock_drift::{ClockDriftLevel, ClockDriftMonitor, ClockDriftStatus};
+use crate::dashboard::{SnapshotProvenance, SnapshotStatus};
+use crate::env_vars::Settings;
+use crate::persistence::PersistenceBackend;
+use crate::realized_vol::RealizedVolConfig;
+use crate::warmup::{WarmupPhase, WarmupProgress, WarmupTracker};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HealthReport {
+    /// `ok` or `degraded`.
+    pub status: String,
+    pub now_ts_utc: i64,
+    /// `None` until the first drift check completes (or when checks are disabled).
+    pub clock: Option<ClockDriftStatus>,
+    /// Present only when the process runs a warm-up.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warmup: Option<WarmupProgress>,
+}
+
+pub fn health_report(
+    clock: Option<&ClockDriftMonitor>,
+    warmup: Option<&WarmupTracker>,
+    now_ts_utc: i64,
+) -> HealthReport {
+    let clock = clock.and_then(ClockDriftMonitor::status);
+    let warmup = warmup.map(WarmupTracker::progress);
+    let degraded = clock
+        .as_ref()
+        .is_some_and(|status| status.level == ClockDriftLevel::Alert)
+        || warmup
+            .as_ref()
+            .is_some_and(|progress| progress.phase == WarmupPhase::Failed);
+    HealthReport {
+        status: if degraded { "degraded" } else { "ok" }.to_string(),
+        now_ts_utc,
+        clock,
+        warmup,
+    }
+}
+
+#[derive(Clone)]
+struct HealthState {
+    clock: Option<ClockDriftMonitor>,
+    warmup: Option<WarmupTracker>,
+}
+
+/// Router serving `/healthz`; merge it next to `dashboard_router`.
+pub fn health_router(clock: Option<ClockDriftMonitor>) -> Router {
+    health_router_with_warmup(clock, None)
+}
+
+/// [`health_router`] that also reports warm-up progress from `warmup`.
+pub fn health_router_with_warmup(
+    clock: Option<ClockDriftMonitor>,
+    warmup: Option<WarmupTracker>,
+) -> Router {
+    Router::new()
+        .route("/healthz", get(get_healthz))
+        .with_state(HealthState { clock, warmup })
+}
+
+async fn get_healthz(State(state): State<HealthState>) -> impl IntoResponse {
+    Json(health_report(
+        state.clock.as_ref(),
+        state.warmup.as_ref(),
+        Utc::now().timestamp(),
+    ))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadinessConfig {
+    /// Delay between live discovery cycles.
+    pub refresh_interval_ms: u64,
+    /// The last live refresh may be at most this many refresh intervals old.
+    pub max_stale_intervals: u32,
+    /// Kline store that must open and answer; `None` skips the check.
+    pub kline_store: Option<PathBuf>,
+    /// Where `kline_store` lives; with Postgres the path only turns the check on.
+    pub backend: PersistenceBackend,
+}
+
+impl Default for ReadinessConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl ReadinessConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_DASHBOARD_DISCOVERY_REFRESH_MS`, `PMM_READY_MAX_STALE_INTERVALS`, and
+    /// `PMM_READY_CHECK_KLINES` (checks the store of [`RealizedVolConfig::from_env`]),
+    /// falling back to defaults for missing/invalid values.
+    pub fn from_settings(settings: &Settings) -> Self {
+        let klines = RealizedVolConfig::from_settings(settings);
+        let check_klines: bool = settings.get("PMM_READY_CHECK_KLINES");
+        Self {
+            refresh_interval_ms: settings.get("PMM_DASHBOARD_DISCOVERY_REFRESH_MS"),
+            max_stale_intervals: settings
+                .get_where("PMM_READY_MAX_STALE_INTERVALS", |intervals| *intervals > 0),
+            kline_store: check_klines.then_some(klines.store_path).flatten(),
+            backend: klines.backend,
+        }
+    }
+
+    /// Oldest acceptable live refresh, in whole seconds (at least one).
+    pub fn max_refresh_age_secs(&self) -> i64 {
+        let ms = self
+            .refresh_interval_ms
+            .max(1)
+            .saturating_mul(u64::from(self.max_stale_intervals));
+        i64::try_from(ms.div_ceil(1_000)).unwrap_or(i64::MAX).max(1)
+    }
+}
+
+/// One readiness condition.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReadinessCheck {
+    /// `snapshot`, `discovery`, or `klines`.
+    pub name: String,
+    pub ok: bool,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReadinessReport {
+    /// All checks passed; `/readyz` answers `200`, else `503`.
+    pub ready: bool,
+    pub now_ts_utc: i64,
+    pub checks: Vec<ReadinessCheck>,
+}
+
+/// Readiness of a dashboard whose source reports `status`. Static sources (rows
+/// supplied by the caller) run no discovery loop and pass both snapshot checks; live
+/// sources need a live snapshot no older than [`ReadinessConfig::max_refresh_age_secs`].
+/// `klines` is the result of probing the configured store, `None` when unconfigured.
+pub fn readiness_report(
+    status: &SnapshotStatus,
+    klines: Option<Result<(), String>>,
+    cfg: &ReadinessConfig,
+    now_ts_utc: i64,
+) -> ReadinessReport {
+    let check = |name: &str, ok: bool, detail: String| ReadinessCheck {
+        name: name.to_string(),
+        ok,
+        detail,
+    };
+    let mut checks = Vec::new();
+    if status.provenance == SnapshotProvenance::Static {
+        checks.push(check("snapshot", true, "static source".to_string()));
+        checks.push(check("discovery", true, "no discovery loop".to_string()));
+    } else {
+        checks.push(match status.last_live_ts_utc {
+            Some(ts) => check("snapshot", true, format!("first live rows by {ts}")),
+            None => check(
+                "snapshot",
+                false,
+                format!("no live snapshot yet; serving {:?} rows", status.provenance)
+                    .to_lowercase(),
+            ),
+        });
+        let limit = cfg.max_refresh_age_secs();
+        checks.push(match status.last_live_ts_utc {
+            Some(ts) if now_ts_utc - ts <= limit => check(
+                "discovery",
+                true,
+                format!("last live refresh {}s ago", now_ts_utc - ts),
+            ),
+            Some(ts) => check(
+                "discovery",
+                false,
+                format!(
+                    "last live refresh {}s ago, limit {limit}s ({} failed cycles)",
+                    now_ts_utc - ts,
+                    status.consecutive_failures
+                ),
+            ),
+            None => check("discovery", false, "no live refresh yet".to_string()),
+        });
+    }
+    if let Some(result) = klines {
+        checks.push(match result {
+            Ok(()) => check("klines", true, "kline store reachable".to_string()),
+            Err(err) => check("klines", false, err),
+        });
+    }
+    ReadinessReport {
+        ready: checks.iter().all(|check| check.ok),
+        now_ts_utc,
+        checks,
+    }
+}
@@ -0,0 +1,383 @@
+//! Trade journal: free-text review notes attached to one interval's market.
+//!
+//! `POST /markets/{slug}/journal` stores a note with its author and time;
+//! `GET /markets/{slug}/journal` returns the market's notes next to its position
+//! ledger, so post-trade review sees the "why" beside the numbers. Notes are linked
+//! to the interval their slug names (coin, duration, start/end) when the slug parses;
+//! pair and unrecognised slugs keep the note without interval fields. Notes are kept
+//! in SQLite and never edited; a correction is another note.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use axum::extract::{Path as UrlPath, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::env_vars::Settings;
+use crate::positions::{MarketPosition, PositionSource};
+use crate::slug::{parse_coin, parse_slug_at, Coin, Duration};
+
+/// Longest note body accepted, in characters.
+pub const MAX_NOTE_CHARS: usize = 4_000;
+/// Longest author accepted, in characters.
+pub const MAX_AUTHOR_CHARS: usize = 64;
+/// Most notes `GET /journal` returns.
+const MAX_LISTED_NOTES: usize = 500;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JournalConfig {
+    /// SQLite file holding the notes; `None` keeps them in memory only.
+    pub store_path: Option<PathBuf>,
+}
+
+impl Default for JournalConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl JournalConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_JOURNAL_STORE_PATH` (`off` keeps notes in memory), falling back to
+    /// the default path when unset.
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            store_path: settings.get("PMM_JOURNAL_STORE_PATH"),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum JournalError {
+    #[error("sqlite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid note: {0}")]
+    InvalidNote(String),
+}
+
+/// Interval a note's slug names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalInterval {
+    pub coin: Coin,
+    pub duration: Duration,
+    pub start_ts_utc: i64,
+    pub end_ts_utc: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalNote {
+    pub id: i64,
+    pub slug: String,
+    pub interval: Option<JournalInterval>,
+    pub author: String,
+    pub text: String,
+    pub created_ts_utc: i64,
+}
+
+/// Body of `POST /markets/{slug}/journal`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NewJournalNote {
+    pub author: String,
+    pub text: String,
+}
+
+/// Shared journal; clones share the same connection.
+#[derive(Clone)]
+pub struct TradeJournal {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl TradeJournal {
+    /// A journal that is not persisted.
+    pub fn in_memory() -> Self {
+        Self::init(Connection::open_in_memory().expect("in-memory sqlite"))
+            .expect("journal schema on in-memory sqlite")
+    }
+
+    /// Opens (creating if needed) the SQLite store at `path`.
+    pub fn open(path: &Path) -> Result<Self, JournalError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        Self::init(Connection::open(path)?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, JournalError> {
+        conn.execute_batch(
+            "
+            PRAGMA journal_mode=WAL;
+            CREATE TABLE IF NOT EXISTS journal_notes (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                slug TEXT NOT NULL,
+                coin TEXT,
+                duration TEXT,
+                start_ts_utc INTEGER,
+                end_ts_utc INTEGER,
+                author TEXT NOT NULL,
+                text TEXT NOT NULL,
+                created_ts_utc INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS journal_notes_slug ON journal_notes(slug, id);
+            CREATE INDEX IF NOT EXISTS journal_notes_created ON journal_notes(created_ts_utc);
+            ",
+        )?;
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+
+    /// Opens the configured store; falls back to an in-memory journal when it cannot
+    /// be opened (logged as `journal.unpersisted`).
+    pub fn from_config(cfg: &JournalConfig) -> Self {
+        let Some(path) = &cfg.store_path else {
+            return Self::in_memory();
+        };
+        match Self::open(path) {
+            Ok(journal) => journal,
+            Err(err) => {
+                warn!(
+                    component = "journal",
+                    event = "journal.unpersisted",
+                    path = %path.display(),
+                    error = %err
+                );
+                Self::in_memory()
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.conn
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Stores a note on `slug`. Author and text are trimmed and must be non-empty and
+    /// within [`MAX_AUTHOR_CHARS`] / [`MAX_NOTE_CHARS`].
+    pub fn add(
+        &self,
+        slug: &str,
+        note: &NewJournalNote,
+        now_ts_utc: i64,
+    ) -> Result<JournalNote, JournalError> {
+        let slug = slug.trim();
+        let author = note.author.trim();
+        let text = note.text.trim();
+        let invalid = |reason: &str| Err(JournalError::InvalidNote(reason.to_string()));
+        if slug.is_empty() {
+            return invalid("slug is empty");
+        }
+        if author.is_empty() || author.chars().count() > MAX_AUTHOR_CHARS {
+            return invalid(&format!(
+                "author must be 1 to {MAX_AUTHOR_CHARS} characters"
+            ));
+        }
+        if text.is_empty() || text.chars().count() > MAX_NOTE_CHARS {
+            return invalid(&format!("text must be 1 to {MAX_NOTE_CHARS} characters"));
+        }
+        let interval =
+            parse_slug_at(slug, now_ts_utc)
+                .ok()
+                .map(|(coin, duration, start_ts_utc)| JournalInterval {
+                    coin,
+                    duration,
+                    start_ts_utc,
+                    end_ts_utc: duration.interval_end(start_ts_utc),
+                });
+        let conn = self.lock();
+        conn.execute(
+            "INSERT INTO journal_notes
+             (slug, coin, duration, start_ts_utc, end_ts_utc, author, text, created_ts_utc)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                slug,
+                interval.map(|interval| interval.coin.ticker()),
+                interval.map(|interval| interval.duration.as_label()),
+                interval.map(|interval| interval.start_ts_utc),
+                interval.map(|interval| interval.end_ts_utc),
+                author,
+                text,
+                now_ts_utc
+            ],
+        )?;
+        let note = JournalNote {
+            id: conn.last_insert_rowid(),
+            slug: slug.to_string(),
+            interval,
+            author: author.to_string(),
+            text: text.to_string(),
+            created_ts_utc: now_ts_utc,
+        };
+        info!(
+            component = "journal",
+            event = "journal.note.added",
+            id = note.id,
+            slug = %note.slug,
+            author = %note.author
+        );
+        Ok(note)
+    }
+
+    /// Notes on `slug`, oldest first.
+    pub fn notes(&self, slug: &str) -> Result<Vec<JournalNote>, JournalError> {
+        self.query("WHERE slug = ?1 ORDER BY id", params![slug.trim()])
+    }
+
+    /// Notes created at or after `since_ts_utc`, newest first, at most `limit`.
+    pub fn recent(
+        &self,
+        since_ts_utc: i64,
+        limit: usize,
+    ) -> Result<Vec<JournalNote>, JournalError> {
+        self.query(
+            "WHERE created_ts_utc >= ?1 ORDER BY id DESC LIMIT ?2",
+            params![since_ts_utc, limit as i64],
+        )
+    }
+
+    fn query(
+        &self,
+        filter: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<JournalNote>, JournalError> {
+        let conn = self.lock();
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT id, slug, coin, duration, start_ts_utc, end_ts_utc, author, text,
+                    created_ts_utc
+             FROM journal_notes {filter}"
+        ))?;
+        let rows = stmt.query_map(params, |row| {
+            let coin: Option<String> = row.get(2)?;
+            let duration: Option<String> = row.get(3)?;
+            let start: Option<i64> = row.get(4)?;
+            let end: Option<i64> = row.get(5)?;
+            let interval = match (coin, duration, start, end) {
+                (Some(coin), Some(duration), Some(start_ts_utc), Some(end_ts_utc)) => {
+                    match (parse_coin(&coin), Duration::parse(&duration)) {
+                        (Ok(coin), Ok(duration)) => Some(JournalInterval {
+                            coin,
+                            duration,
+                            start_ts_utc,
+                            end_ts_utc,
+                        }),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            };
+            Ok(JournalNote {
+                id: row.get(0)?,
+                slug: row.get(1)?,
+                interval,
+                author: row.get(6)?,
+                text: row.get(7)?,
+                created_ts_utc: row.get(8)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+}
+
+impl std::fmt::Debug for TradeJournal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TradeJournal").finish_non_exhaustive()
+    }
+}
+
+/// `GET /markets/{slug}/journal`: the market's notes and its position ledger.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarketJournal {
+    pub slug: String,
+    pub notes: Vec<JournalNote>,
+    /// `None` without a position source or before the market's first fill.
+    pub position: Option<MarketPosition>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct JournalQuery {
+    pub since_ts_utc: Option<i64>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Clone)]
+struct JournalState {
+    journal: TradeJournal,
+    positions: Option<Arc<dyn PositionSource>>,
+}
+
+/// Router serving `GET`/`POST /markets/{slug}/journal` and `GET /journal` (recent
+/// notes across markets). `positions` fills the ledger shown next to a market's notes.
+pub fn journal_router(journal: TradeJournal, positions: Option<Arc<dyn PositionSource>>) -> Router {
+    Router::new()
+        .route(
+            "/markets/{slug}/journal",
+            get(get_market_journal).post(post_journal_note),
+        )
+        .route("/journal", get(get_recent_notes))
+        .with_state(JournalState { journal, positions })
+}
+
+async fn get_market_journal(
+    State(state): State<JournalState>,
+    UrlPath(slug): UrlPath<String>,
+) -> Response {
+    match state.journal.notes(&slug) {
+        Ok(notes) => Json(MarketJournal {
+            position: state
+                .positions
+                .as_ref()
+                .and_then(|positions| positions.position(&slug)),
+            slug,
+            notes,
+        })
+        .into_response(),
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+    }
+}
+
+async fn post_journal_note(
+    State(state): State<JournalState>,
+    UrlPath(slug): UrlPath<String>,
+    Json(note): Json<NewJournalNote>,
+) -> Response {
+    match state
+        .journal
+        .add(&slug, &note, crate::faults::now_utc().timestamp())
+    {
+        Ok(note) => (StatusCode::CREATED, Json(note)).into_response(),
+        Err(err @ JournalError::InvalidNote(_)) => {
+            (StatusCode::BAD_REQUEST, err.to_string()).into_response()
+        }
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+    }
+}
+
+async fn get_recent_notes(
+    State(state): State<JournalState>,
+    Query(query): Query<JournalQuery>,
+) -> Response {
+    let limit = query
+        .limit
+        .unwrap_or(MAX_LISTED_NOTES)
+        .clamp(1, MAX_LISTED_NOTES);
+    match state
+        .journal
+        .recent(query.since_ts_utc.unwrap_or(i64::MIN), limit)
+    {
+        Ok(notes) => Json(notes).into_response(),
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+    }
+}
@@ -0,0 +1,569 @@
+//! Import of user-maintained 1s kline archives into the [`KlineStore`], so a store can
+//! be bootstrapped without re-downloading from Binance.
+//!
+//! Accepted files:
+//! - CSV in the Binance archive layout (12 columns, no header), as the loader parses it
+//! - CSV or Parquet with named columns: `open_time` (or `open_time_ms`), `open`,
+//!   `high`, `low`, `close`, `volume`, and `quote_asset_volume` (or `quote_volume`) are
+//!   required; `close_time`, `trade_count` (or `number_of_trades`), and the taker-buy
+//!   volumes are optional (`open_time + 999` and `0` when absent, as in compacted rows)
+//!
+//! Timestamps in microseconds or nanoseconds are normalized to milliseconds. Rows must
+//! sit on whole seconds; repeated seconds are dropped when identical and fail the import
+//! otherwise. Missing seconds are reported, not filled. Rows already stored with other
+//! OHLCV values are conflicts: kept or overwritten per [`KlineConflictPolicy`], and
+//! reported either way.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use csv::{ByteRecord, StringRecord};
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use parquet::record::Field;
+use serde::Serialize;
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::binance_klines::{
+    compute_coverage, normalize_to_millis, parse_kline_record, BinanceSymbol, Kline1s,
+    KlineCoverageReport, KlineLoadError, KlineLoadRequest,
+};
+use crate::kline_store::{KlineStore, KlineStoreError};
+use crate::kline_validation::{KlineViolationCounts, OhlcvPoint};
+
+const STEP_MS: i64 = 1_000;
+/// Rows compared against the store and written per batch.
+const MERGE_BATCH_POINTS: usize = 86_400;
+/// Conflicts listed in [`KlineImportReport::conflict_samples`].
+pub const MAX_REPORTED_CONFLICTS: usize = 20;
+
+#[derive(Debug, Error)]
+pub enum KlineImportError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("CSV error: {0}")]
+    Csv(#[from] csv::Error),
+    #[error("Parquet error: {0}")]
+    Parquet(#[from] parquet::errors::ParquetError),
+    #[error("kline parse error: {0}")]
+    Parse(#[from] KlineLoadError),
+    #[error("kline store error: {0}")]
+    Store(#[from] KlineStoreError),
+    #[error("cannot tell the format of {path}; use a .csv or .parquet file")]
+    UnknownFormat { path: PathBuf },
+    #[error("missing required column {column}")]
+    MissingColumn { column: &'static str },
+    #[error("row {row}: invalid {column} value '{value}'")]
+    InvalidValue {
+        row: u64,
+        column: &'static str,
+        value: String,
+    },
+    #[error("row {row}: open time {open_time_ms} is not on a whole second")]
+    Misaligned { row: u64, open_time_ms: i64 },
+    #[error("second {open_time_ms} appears twice with different values")]
+    ConflictingDuplicate { open_time_ms: i64 },
+    #[error("{missing_points} seconds are missing across {gap_ranges} gaps")]
+    Discontinuous {
+        missing_points: u64,
+        gap_ranges: u64,
+    },
+    #[error("{path} holds no klines")]
+    Empty { path: PathBuf },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KlineImportFormat {
+    Csv,
+    Parquet,
+}
+
+impl KlineImportFormat {
+    /// From the file extension (`.csv`, `.parquet`, `.pq`).
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "csv" => Some(Self::Csv),
+            "parquet" | "pq" => Some(Self::Parquet),
+            _ => None,
+        }
+    }
+}
+
+/// What happens to a stored row the file disagrees with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KlineConflictPolicy {
+    /// The stored row stays; the conflict is reported.
+    #[default]
+    Keep,
+    /// The file's row replaces the stored one; the conflict is reported.
+    Overwrite,
+}
+
+impl KlineConflictPolicy {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "keep" => Some(Self::Keep),
+            "overwrite" => Some(Self::Overwrite),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KlineImportRequest {
+    pub path: PathBuf,
+    pub symbol: BinanceSymbol,
+    /// `None` picks the format from the file extension.
+    pub format: Option<KlineImportFormat>,
+    pub on_conflict: KlineConflictPolicy,
+    /// Fail instead of importing when seconds are missing between the first and last row.
+    pub require_continuous: bool,
+    /// Validate and compare against the store without writing.
+    pub dry_run: bool,
+}
+
+impl KlineImportRequest {
+    pub fn new(path: impl Into<PathBuf>, symbol: BinanceSymbol) -> Self {
+        Self {
+            path: path.into(),
+            symbol,
+            format: None,
+            on_conflict: KlineConflictPolicy::default(),
+            require_continuous: false,
+            dry_run: false,
+        }
+    }
+}
+
+/// A second stored with other OHLCV values than the imported file has.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KlineImportConflict {
+    pub open_time_ms: i64,
+    pub stored: OhlcvPoint,
+    pub imported: OhlcvPoint,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KlineImportReport {
+    pub symbol: BinanceSymbol,
+    pub path: PathBuf,
+    pub format: KlineImportFormat,
+    pub rows_read: u64,
+    pub first_open_time_ms: i64,
+    pub last_open_time_ms: i64,
+    /// Continuity of the file between its first and last second.
+    pub coverage: KlineCoverageReport,
+    /// Seconds not stored before (written unless `dry_run`).
+    pub inserted: u64,
+    /// Seconds already stored with the same OHLCV.
+    pub unchanged: u64,
+    /// Seconds stored with other OHLCV values.
+    pub conflicts: u64,
+    /// Conflicts replaced by the file's rows (written unless `dry_run`).
+    pub overwritten: u64,
+    /// The first [`MAX_REPORTED_CONFLICTS`] conflicts.
+    pub conflict_samples: Vec<KlineImportConflict>,
+    /// Store validation of the written rows; rejected rows are not stored.
+    pub validation: KlineViolationCounts,
+    pub dry_run: bool,
+}
+
+/// Reads, checks, and merges `req.path` into `store`.
+pub fn import_klines(
+    store: &mut KlineStore,
+    req: &KlineImportRequest,
+) -> Result<KlineImportReport, KlineImportError> {
+    let format = match req.format {
+        Some(format) => format,
+        None => KlineImportFormat::from_path(&req.path).ok_or_else(|| {
+            KlineImportError::UnknownFormat {
+                path: req.path.clone(),
+            }
+        })?,
+    };
+    info!(
+        component = "kline_import",
+        event = "kline.import.start",
+        symbol = req.symbol.as_str(),
+        path = %req.path.display(),
+        dry_run = req.dry_run
+    );
+    let mut rows = match format {
+        KlineImportFormat::Csv => read_csv(&req.path)?,
+        KlineImportFormat::Parquet => read_parquet(&req.path)?,
+    };
+    let rows_read = rows.len() as u64;
+    let duplicates_removed = sort_and_dedupe(&mut rows)?;
+    let (Some(first), Some(last)) = (rows.first(), rows.last()) else {
+        return Err(KlineImportError::Empty {
+            path: req.path.clone(),
+        });
+    };
+    let (first_open_time_ms, last_open_time_ms) = (first.open_time_ms, last.open_time_ms);
+    let coverage = compute_coverage(
+        &KlineLoadRequest {
+            symbol: req.symbol,
+            start_ts_ms_utc: first_open_time_ms,
+            end_ts_ms_utc_exclusive: last_open_time_ms + STEP_MS,
+        },
+        &rows,
+        duplicates_removed,
+    );
+    if req.require_continuous && coverage.missing_points > 0 {
+        return Err(KlineImportError::Discontinuous {
+            missing_points: coverage.missing_points,
+            gap_ranges: coverage.total_gap_ranges,
+        });
+    }
+
+    let mut report = KlineImportReport {
+        symbol: req.symbol,
+        path: req.path.clone(),
+        format,
+        rows_read,
+        first_open_time_ms,
+        last_open_time_ms,
+        coverage,
+        inserted: 0,
+        unchanged: 0,
+        conflicts: 0,
+        overwritten: 0,
+        conflict_samples: Vec::new(),
+        validation: KlineViolationCounts::default(),
+        dry_run: req.dry_run,
+    };
+    for batch in rows.chunks(MERGE_BATCH_POINTS) {
+        let to_write = merge_batch(store, req, batch, &mut report)?;
+        if !req.dry_run && !to_write.is_empty() {
+            let counts = store.upsert_rows(req.symbol, to_write)?;
+            report.validation.add(&counts);
+        }
+    }
+
+    if report.conflicts > 0 {
+        warn!(
+            component = "kline_import",
+            event = "kline.import.conflicts",
+            symbol = req.symbol.as_str(),
+            conflicts = report.conflicts,
+            overwritten = report.overwritten
+        );
+    }
+    info!(
+        component = "kline_import",
+        event = "kline.import.finish",
+        symbol = req.symbol.as_str(),
+        rows_read = report.rows_read,
+        inserted = report.inserted,
+        unchanged = report.unchanged,
+        conflicts = report.conflicts,
+        missing_points = report.coverage.missing_points,
+        rejected = report.validation.rows_rejected,
+        dry_run = req.dry_run
+    );
+    Ok(report)
+}
+
+/// Compares `batch` (sorted, one row per second) with the stored rows and returns the
+/// rows to write.
+fn merge_batch(
+    store: &KlineStore,
+    req: &KlineImportRequest,
+    batch: &[Kline1s],
+    report: &mut KlineImportReport,
+) -> Result<Vec<Kline1s>, KlineImportError> {
+    let (Some(first), Some(last)) = (batch.first(), batch.last()) else {
+        return Ok(Vec::new());
+    };
+    let stored: HashMap<i64, OhlcvPoint> = store
+        .ohlcv_range(req.symbol, first.open_time_ms, last.open_time_ms + STEP_MS)?
+        .into_iter()
+        .map(|point| (point.open_time_ms, point))
+        .collect();
+
+    let mut to_write = Vec::new();
+    for row in batch {
+        let Some(existing) = stored.get(&row.open_time_ms) else {
+            report.inserted += 1;
+            to_write.push(row.clone());
+            continue;
+        };
+        let imported = ohlcv(row);
+        if *existing == imported {
+            report.unchanged += 1;
+            continue;
+        }
+        report.conflicts += 1;
+        if report.conflict_samples.len() < MAX_REPORTED_CONFLICTS {
+            report.conflict_samples.push(KlineImportConflict {
+                open_time_ms: row.open_time_ms,
+                stored: *existing,
+                imported,
+            });
+        }
+        if req.on_conflict == KlineConflictPolicy::Overwrite {
+            report.overwritten += 1;
+            to_write.push(row.clone());
+        }
+    }
+    Ok(to_write)
+}
+
+fn ohlcv(row: &Kline1s) -> OhlcvPoint {
+    OhlcvPoint {
+        open_time_ms: row.open_time_ms,
+        open: row.open,
+        high: row.high,
+        low: row.low,
+        close: row.close,
+        volume: row.volume,
+    }
+}
+
+/// Sorts by open time and drops repeated seconds, returning how many were dropped.
+fn sort_and_dedupe(rows: &mut Vec<Kline1s>) -> Result<u64, KlineImportError> {
+    rows.sort_by_key(|row| row.open_time_ms);
+    let before = rows.len();
+    let mut conflicting = None;
+    rows.dedup_by(|later, kept| {
+        if later.open_time_ms != kept.open_time_ms {
+            return false;
+        }
+        if later != kept {
+            conflicting.get_or_insert(later.open_time_ms);
+        }
+        true
+    });
+    if let Some(open_time_ms) = conflicting {
+        return Err(KlineImportError::ConflictingDuplicate { open_time_ms });
+    }
+    Ok((before - rows.len()) as u64)
+}
+
+/// Named kline columns and the names they are accepted under.
+const OPEN_TIME: (&str, &[&str]) = ("open_time", &["open_time", "open_time_ms"]);
+const OPEN: (&str, &[&str]) = ("open", &["open"]);
+const HIGH: (&str, &[&str]) = ("high", &["high"]);
+const LOW: (&str, &[&str]) = ("low", &["low"]);
+const CLOSE: (&str, &[&str]) = ("close", &["close"]);
+const VOLUME: (&str, &[&str]) = ("volume", &["volume"]);
+const QUOTE_VOLUME: (&str, &[&str]) = (
+    "quote_asset_volume",
+    &["quote_asset_volume", "quote_volume"],
+);
+const CLOSE_TIME: (&str, &[&str]) = ("close_time", &["close_time", "close_time_ms"]);
+const TRADE_COUNT: (&str, &[&str]) = ("trade_count", &["trade_count", "number_of_trades"]);
+const TAKER_BASE: (&str, &[&str]) = (
+    "taker_buy_base_volume",
+    &["taker_buy_base_volume", "taker_buy_base_asset_volume"],
+);
+const TAKER_QUOTE: (&str, &[&str]) = (
+    "taker_buy_quote_volume",
+    &["taker_buy_quote_volume", "taker_buy_quote_asset_volume"],
+);
+
+/// Positions of the named columns in a file.
+struct ColumnMap {
+    open_time: usize,
+    open: usize,
+    high: usize,
+    low: usize,
+    close: usize,
+    volume: usize,
+    quote_volume: usize,
+    close_time: Option<usize>,
+    trade_count: Option<usize>,
+    taker_base: Option<usize>,
+    taker_quote: Option<usize>,
+}
+
+impl ColumnMap {
+    fn resolve<'a>(names: impl Iterator<Item = &'a str> + Clone) -> Result<Self, KlineImportError> {
+        let find = |(_, aliases): (&'static str, &[&str])| {
+            names.clone().position(|name| {
+                let name = name.trim();
+                aliases.iter().any(|alias| alias.eq_ignore_ascii_case(name))
+            })
+        };
+        let require = |column: (&'static str, &[&str])| {
+            find(column).ok_or(KlineImportError::MissingColumn { column: column.0 })
+        };
+        Ok(Self {
+            open_time: require(OPEN_TIME)?,
+            open: require(OPEN)?,
+            high: require(HIGH)?,
+            low: require(LOW)?,
+            close: require(CLOSE)?,
+            volume: require(VOLUME)?,
+            quote_volume: require(QUOTE_VOLUME)?,
+            close_time: find(CLOSE_TIME),
+            trade_count: find(TRADE_COUNT),
+            taker_base: find(TAKER_BASE),
+            taker_quote: find(TAKER_QUOTE),
+        })
+    }
+
+    /// Builds the kline of row `row` from `value(column) -> (text, parsed)` lookups.
+    fn kline(
+        &self,
+        row: u64,
+        int: impl Fn(usize) -> Option<Result<i64, String>>,
+        float: impl Fn(usize) -> Option<Result<f64, String>>,
+    ) -> Result<Kline1s, KlineImportError> {
+        let invalid = |column: &'static str| {
+            move |value: String| KlineImportError::InvalidValue { row, column, value }
+        };
+        let required_int = |idx: usize, column: &'static str| {
+            int(idx)
+                .unwrap_or_else(|| Err(String::new()))
+                .map_err(invalid(column))
+        };
+        let required_float = |idx: usize, column: &'static str| {
+            float(idx)
+                .unwrap_or_else(|| Err(String::new()))
+                .map_err(invalid(column))
+        };
+        let optional_float = |idx: Option<usize>, column: &'static str| {
+            idx.and_then(&float)
+                .transpose()
+                .map(|value| value.unwrap_or(0.0))
+                .map_err(invalid(column))
+        };
+
+        let open_time_ms = normalize_to_millis(required_int(self.open_time, OPEN_TIME.0)?);
+        if open_time_ms.rem_euclid(STEP_MS) != 0 {
+            return Err(KlineImportError::Misaligned { row, open_time_ms });
+        }
+        let close_time_ms = match self.close_time.and_then(&int).transpose() {
+            Ok(Some(raw)) => normalize_to_millis(raw),
+            Ok(None) => open_time_ms + STEP_MS - 1,
+            Err(value) => return Err(invalid(CLOSE_TIME.0)(value)),
+        };
+        let trade_count = match self.trade_count.and_then(&int).transpose() {
+            Ok(raw) => u64::try_from(raw.unwrap_or(0))
+                .map_err(|_| invalid(TRADE_COUNT.0)(raw.unwrap_or(0).to_string()))?,
+            Err(value) => return Err(invalid(TRADE_COUNT.0)(value)),
+        };
+        Ok(Kline1s {
+            open_time_ms,
+            open: required_float(self.open, OPEN.0)?,
+            high: required_float(self.high, HIGH.0)?,
+            low: required_float(self.low, LOW.0)?,
+            close: required_float(self.close, CLOSE.0)?,
+            volume: required_float(self.volume, VOLUME.0)?,
+            close_time_ms,
+            quote_asset_volume: required_float(self.quote_volume, QUOTE_VOLUME.0)?,
+            trade_count,
+            taker_buy_base_volume: optional_float(self.taker_base, TAKER_BASE.0)?,
+            taker_buy_quote_volume: optional_float(self.taker_quote, TAKER_QUOTE.0)?,
+        })
+    }
+}
+
+/// A CSV whose first field is a number is headerless and read in the Binance archive
+/// layout; otherwise the first line names the columns.
+fn read_csv(path: &Path) -> Result<Vec<Kline1s>, KlineImportError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(File::open(path)?);
+    let mut first = StringRecord::new();
+    if !reader.read_record(&mut first)? {
+        return Ok(Vec::new());
+    }
+    let headerless = first
+        .get(0)
+        .is_some_and(|field| field.trim().parse::<i64>().is_ok());
+
+    let mut rows = Vec::new();
+    if headerless {
+        rows.push(parse_kline_record(first.as_byte_record())?);
+        let mut record = ByteRecord::new();
+        while reader.read_byte_record(&mut record)? {
+            rows.push(parse_kline_record(&record)?);
+        }
+        for (idx, row) in rows.iter().enumerate() {
+            if row.open_time_ms.rem_euclid(STEP_MS) != 0 {
+                return Err(KlineImportError::Misaligned {
+                    row: idx as u64 + 1,
+                    open_time_ms: row.open_time_ms,
+                });
+            }
+        }
+        return Ok(rows);
+    }
+
+    let columns = ColumnMap::resolve(first.iter())?;
+    let mut record = StringRecord::new();
+    let mut row = 0u64;
+    while reader.read_record(&mut record)? {
+        row += 1;
+        let text = |idx: usize| record.get(idx).map(str::trim).filter(|raw| !raw.is_empty());
+        rows.push(columns.kline(
+            row,
+            |idx| text(idx).map(|raw| raw.parse::<i64>().map_err(|_| raw.to_string())),
+            |idx| text(idx).map(|raw| raw.parse::<f64>().map_err(|_| raw.to_string())),
+        )?);
+    }
+    Ok(rows)
+}
+
+/// Reads a Parquet file with named columns; integer, floating-point, timestamp, and
+/// numeric string columns are accepted.
+fn read_parquet(path: &Path) -> Result<Vec<Kline1s>, KlineImportError> {
+    let reader = SerializedFileReader::new(File::open(path)?)?;
+    let schema = reader.metadata().file_metadata().schema_descr_ptr();
+    let names: Vec<String> = schema
+        .columns()
+        .iter()
+        .map(|column| column.name().to_string())
+        .collect();
+    let columns = ColumnMap::resolve(names.iter().map(String::as_str))?;
+
+    let mut rows = Vec::new();
+    for (idx, record) in reader.get_row_iter(None)?.enumerate() {
+        let record = record?;
+        let fields: Vec<&Field> = record.get_column_iter().map(|(_, field)| field).collect();
+        rows.push(columns.kline(
+            idx as u64 + 1,
+            |col| fields.get(col).and_then(|field| field_i64(field)),
+            |col| fields.get(col).and_then(|field| field_f64(field)),
+        )?);
+    }
+    Ok(rows)
+}
+
+fn field_i64(field: &Field) -> Option<Result<i64, String>> {
+    Some(match field {
+        Field::Null => return None,
+        Field::Byte(v) => Ok(i64::from(*v)),
+        Field::Short(v) => Ok(i64::from(*v)),
+        Field::Int(v) => Ok(i64::from(*v)),
+        Field::Long(v) | Field::TimestampMillis(v) => Ok(*v),
+        Field::TimestampMicros(v) => Ok(v.div_euclid(1_000)),
+        Field::UByte(v) => Ok(i64::from(*v)),
+        Field::UShort(v) => Ok(i64::from(*v)),
+        Field::UInt(v) => Ok(i64::from(*v)),
+        Field::ULong(v) => i64::try_from(*v).map_err(|_| v.to_string()),
+        Field::Double(v) if v.fract() == 0.0 => Ok(*v as i64),
+        Field::Str(raw) => raw.trim().parse().map_err(|_| raw.clone()),
+        other => Err(other.to_string()),
+    })
+}
+
+fn field_f64(field: &Field) -> Option<Result<f64, String>> {
+    Some(match field {
+        Field::Null => return None,
+        Field::Float(v) => Ok(f64::from(*v)),
+        Field::Double(v) => Ok(*v),
+        Field::Str(raw) => raw.trim().parse().map_err(|_| raw.clone()),
+        other => match field_i64(other)? {
+            Ok(v) => Ok(v as f64),
+            Err(value) => Err(value),
+        },
+    })
+}
@@ -0,0 +1,1180 @@
+//! Combined SQLite store for Binance 1s klines, shared by the sync binary and the
+//! feature transform (which reads `klines_1s` directly).
+//!
+//! For long retention, [`KlineStore::compact_older_than`] moves old rows into
+//! `klines_1s_slim`, which drops `close_time_ms` (always `open_time_ms + 999`),
+//! `trade_count`, and the taker-buy volumes. Readers see both tables through
+//! [`klines_source_sql`]. The feature transform scans time-major across symbols via
+//! [`frame_scan_sql`], served by `(open_time_ms, symbol_id, ...)` covering indexes on
+//! both tables.
+//!
+//! Micro-halts found by [`KlineStore::flag_micro_halts`] are recorded in `kline_halts`;
+//! the frame scan leaves the seconds they cover out, so the transform's gap policy
+//! handles them like missing data.
+
+use std::path::Path;
+
+use chrono::Utc;
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+use thiserror::Error;
+use tracing::info;
+
+use crate::binance_klines::{BinanceSymbol, Kline1s};
+use crate::kline_validation::{
+    detect_micro_halts, validate_klines, KlineHalt, KlineValidationConfig, KlineValidationPolicy,
+    KlineViolationCounts, MicroHaltConfig, OhlcvPoint, RejectedKline,
+};
+use crate::persistence::KlineBackend;
+
+/// `PRAGMA user_version` stamped by [`KlineStore::open`]; `0` marks a store created
+/// before versioning. Version 2 adds `klines_1s_slim`; version 3 adds the time-major
+/// covering indexes read by the feature transform; version 4 adds `kline_halts`.
+pub const KLINE_STORE_SCHEMA_VERSION: i64 = 4;
+
+#[derive(Debug, Error)]
+pub enum KlineStoreError {
+    #[error("sqlite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[cfg(feature = "postgres")]
+    #[error("postgres error: {0}")]
+    Postgres(#[from] postgres::Error),
+    #[error("unsupported existing klines_1s schema: {0}")]
+    UnsupportedSchema(String),
+    #[error("storage backend unavailable: {0}")]
+    BackendUnavailable(String),
+}
+
+/// Combined 1s kline store (`klines_1s`, one row per `(symbol_id, open_time_ms)`).
+///
+/// Rows are validated on the way in; depending on the validation policy, rejected rows
+/// are dropped or quarantined in `klines_rejected`.
+pub struct KlineStore {
+    conn: Connection,
+    validation: KlineValidationConfig,
+}
+
+impl KlineStore {
+    /// Opens (or creates) the store, migrating a legacy `klines_1s` layout in place.
+    pub fn open(path: &Path, validation: KlineValidationConfig) -> Result<Self, KlineStoreError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "
+            PRAGMA journal_mode=WAL;
+            PRAGMA synchronous=NORMAL;
+            PRAGMA temp_store=MEMORY;
+            ",
+        )?;
+        let version = read_schema_version(&conn)?;
+        if version > KLINE_STORE_SCHEMA_VERSION {
+            return Err(KlineStoreError::UnsupportedSchema(format!(
+                "schema version {version} is newer than supported {KLINE_STORE_SCHEMA_VERSION}"
+            )));
+        }
+        ensure_compact_schema(&conn)?;
+        create_slim_table(&conn)?;
+        create_time_indexes(&conn)?;
+        create_rejected_table(&conn)?;
+        create_halts_table(&conn)?;
+        conn.pragma_update(None, "user_version", KLINE_STORE_SCHEMA_VERSION)?;
+
+        Ok(Self { conn, validation })
+    }
+
+    /// The store's `PRAGMA user_version` (see [`KLINE_STORE_SCHEMA_VERSION`]).
+    pub fn schema_version(&self) -> Result<i64, KlineStoreError> {
+        read_schema_version(&self.conn)
+    }
+
+    /// Opens an existing store for reads only; no schema changes are made, so readers
+    /// can run next to the sync process.
+    pub fn open_read_only(path: &Path) -> Result<Self, KlineStoreError> {
+        let conn = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+        Ok(Self {
+            conn,
+            validation: KlineValidationConfig::default(),
+        })
+    }
+
+    /// The underlying connection, for reads that must see this store's own writes.
+    pub(crate) fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Validates `rows`, upserts the accepted ones, and quarantines the rest when the
+    /// policy asks for it.
+    pub fn upsert_rows(
+        &mut self,
+        symbol: BinanceSymbol,
+        rows: Vec<Kline1s>,
+    ) -> Result<KlineViolationCounts, KlineStoreError> {
+        crate::faults::sqlite_busy()?;
+        let outcome = validate_klines(symbol, rows, &self.validation);
+        if self.validation.policy == KlineValidationPolicy::Quarantine {
+            self.quarantine_rows(symbol, &outcome.rejected)?;
+        }
+        self.write_rows(symbol, &outcome.accepted)?;
+        Ok(outcome.counts)
+    }
+
+    fn write_rows(
+        &mut self,
+        symbol: BinanceSymbol,
+        rows: &[Kline1s],
+    ) -> Result<(), KlineStoreError> {
+        if rows.is_empty() {
+            return Ok(());
+        }
+
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "
+                INSERT INTO klines_1s (
+                    symbol_id,
+                    open_time_ms,
+                    open,
+                    high,
+                    low,
+                    close,
+                    volume,
+                    close_time_ms,
+                    quote_asset_volume,
+                    trade_count,
+                    taker_buy_base_volume,
+                    taker_buy_quote_volume
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+                ON CONFLICT(symbol_id, open_time_ms) DO UPDATE SET
+                    open = excluded.open,
+                    high = excluded.high,
+                    low = excluded.low,
+                    close = excluded.close,
+                    volume = excluded.volume,
+                    close_time_ms = excluded.close_time_ms,
+                    quote_asset_volume = excluded.quote_asset_volume,
+                    trade_count = excluded.trade_count,
+                    taker_buy_base_volume = excluded.taker_buy_base_volume,
+                    taker_buy_quote_volume = excluded.taker_buy_quote_volume
+                ",
+            )?;
+
+            // A rewritten row that was compacted earlier lives in full form again.
+            let mut unslim = tx
+                .prepare("DELETE FROM klines_1s_slim WHERE symbol_id = ?1 AND open_time_ms = ?2")?;
+
+            for row in rows {
+                unslim.execute(params![symbol_id(symbol), row.open_time_ms])?;
+                stmt.execute(params![
+                    symbol_id(symbol),
+                    row.open_time_ms,
+                    row.open,
+                    row.high,
+                    row.low,
+                    row.close,
+                    row.volume,
+                    row.close_time_ms,
+                    row.quote_asset_volume,
+                    row.trade_count,
+                    row.taker_buy_base_volume,
+                    row.taker_buy_quote_volume,
+                ])?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn quarantine_rows(
+        &mut self,
+        symbol: BinanceSymbol,
+        rejected: &[RejectedKline],
+    ) -> Result<(), KlineStoreError> {
+        if rejected.is_empty() {
+            return Ok(());
+        }
+
+        let rejected_at_ms = Utc::now().timestamp_millis();
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "
+                INSERT INTO klines_rejected (
+                    symbol_id,
+                    open_time_ms,
+                    rules,
+                    detail,
+                    open,
+                    high,
+                    low,
+                    close,
+                    volume,
+                    close_time_ms,
+                    quote_asset_volume,
+                    trade_count,
+                    taker_buy_base_volume,
+                    taker_buy_quote_volume,
+                    rejected_at_ms
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
+                ON CONFLICT(symbol_id, open_time_ms) DO UPDATE SET
+                    rules = excluded.rules,
+                    detail = excluded.detail,
+                    open = excluded.open,
+                    high = excluded.high,
+                    low = excluded.low,
+                    close = excluded.close,
+                    volume = excluded.volume,
+                    close_time_ms = excluded.close_time_ms,
+                    quote_asset_volume = excluded.quote_asset_volume,
+                    trade_count = excluded.trade_count,
+                    taker_buy_base_volume = excluded.taker_buy_base_volume,
+                    taker_buy_quote_volume = excluded.taker_buy_quote_volume,
+                    rejected_at_ms = excluded.rejected_at_ms
+                ",
+            )?;
+
+            for rejected in rejected {
+                let row = &rejected.row;
+                let rules: Vec<&str> = rejected.violations.iter().map(|v| v.rule()).collect();
+                let detail: Vec<String> = rejected.violations.iter().map(|v| v.detail()).collect();
+                stmt.execute(params![
+                    symbol_id(symbol),
+                    row.open_time_ms,
+                    rules.join(","),
+                    detail.join("; "),
+                    row.open,
+                    row.high,
+                    row.low,
+                    row.close,
+                    row.volume,
+                    row.close_time_ms,
+                    row.quote_asset_volume,
+                    row.trade_count,
+                    row.taker_buy_base_volume,
+                    row.taker_buy_quote_volume,
+                    rejected_at_ms,
+                ])?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Stored rows for `symbol` in `[start_ts_ms, end_ts_ms_exclusive)`.
+    pub fn count_range(
+        &self,
+        symbol: BinanceSymbol,
+        start_ts_ms: i64,
+        end_ts_ms_exclusive: i64,
+    ) -> Result<u64, KlineStoreError> {
+        crate::faults::sqlite_busy()?;
+        let count: i64 = self.conn.query_row(
+            &format!(
+                "
+                SELECT COUNT(*)
+                FROM {}
+                WHERE symbol_id = ?1
+                  AND open_time_ms >= ?2
+                  AND open_time_ms < ?3
+                ",
+                klines_source_sql(&self.conn)?
+            ),
+            params![symbol_id(symbol), start_ts_ms, end_ts_ms_exclusive],
+            |row| row.get(0),
+        )?;
+        Ok(count as u64)
+    }
+
+    /// `(open_time_ms, close)` for `symbol` in `[start_ts_ms, end_ts_ms_exclusive)`,
+    /// oldest first.
+    pub fn closes_range(
+        &self,
+        symbol: BinanceSymbol,
+        start_ts_ms: i64,
+        end_ts_ms_exclusive: i64,
+    ) -> Result<Vec<(i64, f64)>, KlineStoreError> {
+        crate::faults::sqlite_busy()?;
+        let mut stmt = self.conn.prepare_cached(&format!(
+            "
+            SELECT open_time_ms, close
+            FROM {}
+            WHERE symbol_id = ?1
+              AND open_time_ms >= ?2
+              AND open_time_ms < ?3
+            ORDER BY open_time_ms
+            ",
+            klines_source_sql(&self.conn)?
+        ))?;
+        let rows = stmt.query_map(
+            params![symbol_id(symbol), start_ts_ms, end_ts_ms_exclusive],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// OHLCV for `symbol` in `[start_ts_ms, end_ts_ms_exclusive)`, oldest first.
+    pub fn ohlcv_range(
+        &self,
+        symbol: BinanceSymbol,
+        start_ts_ms: i64,
+        end_ts_ms_exclusive: i64,
+    ) -> Result<Vec<OhlcvPoint>, KlineStoreError> {
+        crate::faults::sqlite_busy()?;
+        let mut stmt = self.conn.prepare_cached(&format!(
+            "
+            SELECT open_time_ms, open, high, low, close, volume
+            FROM {}
+            WHERE symbol_id = ?1
+              AND open_time_ms >= ?2
+              AND open_time_ms < ?3
+            ORDER BY open_time_ms
+            ",
+            klines_source_sql(&self.conn)?
+        ))?;
+        let rows = stmt.query_map(
+            params![symbol_id(symbol), start_ts_ms, end_ts_ms_exclusive],
+            |row| {
+                Ok(OhlcvPoint {
+                    open_time_ms: row.get(0)?,
+                    open: row.get(1)?,
+                    high: row.get(2)?,
+                    low: row.get(3)?,
+                    close: row.get(4)?,
+                    volume: row.get(5)?,
+                })
+            },
+        )?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// `(open_time_ms, open)` of the first stored kline for `symbol` in
+    /// `[start_ts_ms, end_ts_ms_exclusive)`.
+    pub fn first_open_in_range(
+        &self,
+        symbol: BinanceSymbol,
+        start_ts_ms: i64,
+        end_ts_ms_exclusive: i64,
+    ) -> Result<Option<(i64, f64)>, KlineStoreError> {
+        crate::faults::sqlite_busy()?;
+        Ok(self
+            .conn
+            .query_row(
+                &format!(
+                    "
+                    SELECT open_time_ms, open
+                    FROM {}
+                    WHERE symbol_id = ?1
+                      AND open_time_ms >= ?2
+                      AND open_time_ms < ?3
+                    ORDER BY open_time_ms
+                    LIMIT 1
+                    ",
+                    klines_source_sql(&self.conn)?
+                ),
+                params![symbol_id(symbol), start_ts_ms, end_ts_ms_exclusive],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?)
+    }
+
+    /// Moves rows with `open_time_ms < cutoff_ms` from `klines_1s` into
+    /// `klines_1s_slim`, then vacuums so the file actually shrinks. Readers going through
+    /// [`klines_source_sql`] see the same open/high/low/close/volume data afterwards.
+    pub fn compact_older_than(&mut self, cutoff_ms: i64) -> Result<u64, KlineStoreError> {
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "
+            INSERT OR REPLACE INTO klines_1s_slim (
+                symbol_id,
+                open_time_ms,
+                open,
+                high,
+                low,
+                close,
+                volume,
+                quote_asset_volume
+            )
+            SELECT
+                symbol_id,
+                open_time_ms,
+                open,
+                high,
+                low,
+                close,
+                volume,
+                quote_asset_volume
+            FROM klines_1s
+            WHERE open_time_ms < ?1
+            ",
+            params![cutoff_ms],
+        )?;
+        let moved = tx.execute(
+            "DELETE FROM klines_1s WHERE open_time_ms < ?1",
+            params![cutoff_ms],
+        )?;
+        tx.commit()?;
+
+        info!(
+            component = "kline_store",
+            event = "kline_store.compact.finish",
+            cutoff_ms,
+            rows_moved = moved
+        );
+        if moved > 0 {
+            self.conn
+                .execute_batch("PRAGMA wal_checkpoint(TRUNCATE); VACUUM;")?;
+        }
+        Ok(moved as u64)
+    }
+
+    /// Detects micro-halts among the stored rows for `symbol` in
+    /// `[start_ts_ms, end_ts_ms_exclusive)` and records them in `kline_halts`, replacing
+    /// the halts previously recorded as starting in that range. A halt crossing the range
+    /// edges is only seen in part.
+    pub fn flag_micro_halts(
+        &mut self,
+        symbol: BinanceSymbol,
+        start_ts_ms: i64,
+        end_ts_ms_exclusive: i64,
+        cfg: &MicroHaltConfig,
+    ) -> Result<Vec<KlineHalt>, KlineStoreError> {
+        let points = self.ohlcv_range(symbol, start_ts_ms, end_ts_ms_exclusive)?;
+        let halts = detect_micro_halts(&points, cfg);
+
+        let detected_at_ms = Utc::now().timestamp_millis();
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "DELETE FROM kline_halts WHERE symbol_id = ?1 AND start_ms >= ?2 AND start_ms < ?3",
+            params![symbol_id(symbol), start_ts_ms, end_ts_ms_exclusive],
+        )?;
+        {
+            let mut stmt = tx.prepare(
+                "
+                INSERT OR REPLACE INTO kline_halts (
+                    symbol_id,
+                    start_ms,
+                    end_ms_exclusive,
+                    detected_at_ms
+                ) VALUES (?1, ?2, ?3, ?4)
+                ",
+            )?;
+            for halt in &halts {
+                stmt.execute(params![
+                    symbol_id(symbol),
+                    halt.start_ms,
+                    halt.end_ms_exclusive,
+                    detected_at_ms
+                ])?;
+            }
+        }
+        tx.commit()?;
+        log_flagged_halts(symbol, &halts);
+        Ok(halts)
+    }
+
+    /// Recorded halts for `symbol` overlapping `[start_ts_ms, end_ts_ms_exclusive)`,
+    /// oldest first.
+    pub fn halts_range(
+        &self,
+        symbol: BinanceSymbol,
+        start_ts_ms: i64,
+        end_ts_ms_exclusive: i64,
+    ) -> Result<Vec<KlineHalt>, KlineStoreError> {
+        if !table_exists(&self.conn, "kline_halts")? {
+            return Ok(Vec::new());
+        }
+        let mut stmt = self.conn.prepare(
+            "
+            SELECT start_ms, end_ms_exclusive
+            FROM kline_halts
+            WHERE symbol_id = ?1
+              AND start_ms < ?3
+              AND end_ms_exclusive > ?2
+            ORDER BY start_ms
+            ",
+        )?;
+        let rows = stmt.query_map(
+            params![symbol_id(symbol), start_ts_ms, end_ts_ms_exclusive],
+            |row| {
+                Ok(KlineHalt {
+                    start_ms: row.get(0)?,
+                    end_ms_exclusive: row.get(1)?,
+                })
+            },
+        )?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// Quarantined rows for `symbol` in `[start_ts_ms, end_ts_ms_exclusive)`.
+    pub fn count_rejected_range(
+        &self,
+        symbol: BinanceSymbol,
+        start_ts_ms: i64,
+        end_ts_ms_exclusive: i64,
+    ) -> Result<u64, KlineStoreError> {
+        let count: i64 = self.conn.query_row(
+            "
+            SELECT COUNT(*)
+            FROM klines_rejected
+            WHERE symbol_id = ?1
+              AND open_time_ms >= ?2
+              AND open_time_ms < ?3
+            ",
+            params![symbol_id(symbol), start_ts_ms, end_ts_ms_exclusive],
+            |row| row.get(0),
+        )?;
+        Ok(count as u64)
+    }
+}
+
+impl KlineBackend for KlineStore {
+    fn upsert_rows(
+        &mut self,
+        symbol: BinanceSymbol,
+        rows: Vec<Kline1s>,
+    ) -> Result<KlineViolationCounts, KlineStoreError> {
+        KlineStore::upsert_rows(self, symbol, rows)
+    }
+
+    fn count_range(
+        &mut self,
+        symbol: BinanceSymbol,
+        start_ts_ms: i64,
+        end_ts_ms_exclusive: i64,
+    ) -> Result<u64, KlineStoreError> {
+        KlineStore::count_range(self, symbol, start_ts_ms, end_ts_ms_exclusive)
+    }
+
+    fn closes_range(
+        &mut self,
+        symbol: BinanceSymbol,
+        start_ts_ms: i64,
+        end_ts_ms_exclusive: i64,
+    ) -> Result<Vec<(i64, f64)>, KlineStoreError> {
+        KlineStore::closes_range(self, symbol, start_ts_ms, end_ts_ms_exclusive)
+    }
+
+    fn first_open_in_range(
+        &mut self,
+        symbol: BinanceSymbol,
+        start_ts_ms: i64,
+        end_ts_ms_exclusive: i64,
+    ) -> Result<Option<(i64, f64)>, KlineStoreError> {
+        KlineStore::first_open_in_range(self, symbol, start_ts_ms, end_ts_ms_exclusive)
+    }
+
+    fn count_rejected_range(
+        &mut self,
+        symbol: BinanceSymbol,
+        start_ts_ms: i64,
+        end_ts_ms_exclusive: i64,
+    ) -> Result<u64, KlineStoreError> {
+        KlineStore::count_rejected_range(self, symbol, start_ts_ms, end_ts_ms_exclusive)
+    }
+
+    fn flag_micro_halts(
+        &mut self,
+        symbol: BinanceSymbol,
+        start_ts_ms: i64,
+        end_ts_ms_exclusive: i64,
+        cfg: &MicroHaltConfig,
+    ) -> Result<Vec<KlineHalt>, KlineStoreError> {
+        KlineStore::flag_micro_halts(self, symbol, start_ts_ms, end_ts_ms_exclusive, cfg)
+    }
+}
+
+pub(crate) fn log_flagged_halts(symbol: BinanceSymbol, halts: &[KlineHalt]) {
+    if halts.is_empty() {
+        return;
+    }
+    info!(
+        component = "kline_store",
+        event = "kline_store.halts.flagged",
+        symbol = symbol.as_str(),
+        halts = halts.len(),
+        halted_secs = halts.iter().map(KlineHalt::seconds).sum::<i64>(),
+        first_start_ms = halts[0].start_ms
+    );
+}
+
+fn read_schema_version(conn: &Connection) -> Result<i64, KlineStoreError> {
+    Ok(conn.pragma_query_value(None, "user_version", |row| row.get(0))?)
+}
+
+fn ensure_compact_schema(conn: &Connection) -> Result<(), KlineStoreError> {
+    if !table_exists(conn, "klines_1s")? {
+        create_compact_table(conn)?;
+        return Ok(());
+    }
+
+    let has_symbol_id = table_has_column(conn, "klines_1s", "symbol_id")?;
+    let has_symbol = table_has_column(conn, "klines_1s", "symbol")?;
+    let without_rowid = table_is_without_rowid(conn, "klines_1s")?;
+
+    if has_symbol_id && without_rowid {
+        return Ok(());
+    }
+
+    info!(
+        component = "kline_store",
+        event = "kline_store.migrate.start",
+        has_symbol,
+        has_symbol_id,
+        without_rowid
+    );
+    conn.execute_batch("BEGIN IMMEDIATE;")?;
+    let migrate_result = (|| -> Result<(), KlineStoreError> {
+        conn.execute_batch(
+            "
+            CREATE TABLE klines_1s_new (
+                symbol_id INTEGER NOT NULL,
+                open_time_ms INTEGER NOT NULL,
+                open REAL NOT NULL,
+                high REAL NOT NULL,
+                low REAL NOT NULL,
+                close REAL NOT NULL,
+                volume REAL NOT NULL,
+                close_time_ms INTEGER NOT NULL,
+                quote_asset_volume REAL NOT NULL,
+                trade_count INTEGER NOT NULL,
+                taker_buy_base_volume REAL NOT NULL,
+                taker_buy_quote_volume REAL NOT NULL,
+                PRIMARY KEY(symbol_id, open_time_ms)
+            ) WITHOUT ROWID;
+            ",
+        )?;
+
+        if has_symbol {
+            conn.execute_batch(
+                "
+                INSERT INTO klines_1s_new (
+                    symbol_id,
+                    open_time_ms,
+                    open,
+                    high,
+                    low,
+                    close,
+                    volume,
+                    close_time_ms,
+                    quote_asset_volume,
+                    trade_count,
+                    taker_buy_base_volume,
+                    taker_buy_quote_volume
+                )
+                SELECT
+                    CASE symbol
+                        WHEN 'BTCUSDT' THEN 1
+                        WHEN 'ETHUSDT' THEN 2
+                        WHEN 'SOLUSDT' THEN 3
+                        WHEN 'XRPUSDT' THEN 4
+                    END AS symbol_id,
+                    open_time_ms,
+                    open,
+                    high,
+                    low,
+                    close,
+                    volume,
+                    close_time_ms,
+                    quote_asset_volume,
+                    trade_count,
+                    taker_buy_base_volume,
+                    taker_buy_quote_volume
+                FROM klines_1s;
+                ",
+            )?;
+        } else if has_symbol_id {
+            conn.execute_batch(
+                "
+                INSERT INTO klines_1s_new (
+                    symbol_id,
+                    open_time_ms,
+                    open,
+                    high,
+                    low,
+                    close,
+                    volume,
+                    close_time_ms,
+                    quote_asset_volume,
+                    trade_count,
+                    taker_buy_base_volume,
+                    taker_buy_quote_volume
+                )
+                SELECT
+                    symbol_id,
+                    open_time_ms,
+                    open,
+                    high,
+                    low,
+                    close,
+                    volume,
+                    close_time_ms,
+                    quote_asset_volume,
+                    trade_count,
+                    taker_buy_base_volume,
+                    taker_buy_quote_volume
+                FROM klines_1s;
+                ",
+            )?;
+        } else {
+            return Err(KlineStoreError::UnsupportedSchema(
+                "missing symbol/symbol_id".to_string(),
+            ));
+        }
+
+        conn.execute_batch(
+            "
+            DROP TABLE klines_1s;
+            ALTER TABLE klines_1s_new RENAME TO klines_1s;
+            ",
+        )?;
+        Ok(())
+    })();
+
+    match migrate_result {
+        Ok(()) => {
+            conn.execute_batch("COMMIT;")?;
+            info!(
+                component = "kline_store",
+                event = "kline_store.migrate.finish"
+            );
+            conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE); VACUUM;")?;
+            Ok(())
+        }
+        Err(err) => {
+            let _ = conn.execute_batch("ROLLBACK;");
+            Err(err)
+        }
+    }
+}
+
+fn create_compact_table(conn: &Connection) -> Result<(), KlineStoreError> {
+    conn.execute_batch(
+        "
+        CREATE TABLE klines_1s (
+            symbol_id INTEGER NOT NULL,
+            open_time_ms INTEGER NOT NULL,
+            open REAL NOT NULL,
+            high REAL NOT NULL,
+            low REAL NOT NULL,
+            close REAL NOT NULL,
+            volume REAL NOT NULL,
+            close_time_ms INTEGER NOT NULL,
+            quote_asset_volume REAL NOT NULL,
+            trade_count INTEGER NOT NULL,
+            taker_buy_base_volume REAL NOT NULL,
+            taker_buy_quote_volume REAL NOT NULL,
+            PRIMARY KEY(symbol_id, open_time_ms)
+        ) WITHOUT ROWID;
+        ",
+    )?;
+    Ok(())
+}
+
+/// Compacted rows: `klines_1s` without `close_time_ms`, `trade_count`, and the
+/// taker-buy volumes.
+fn create_slim_table(conn: &Connection) -> Result<(), KlineStoreError> {
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS klines_1s_slim (
+            symbol_id INTEGER NOT NULL,
+            open_time_ms INTEGER NOT NULL,
+            open REAL NOT NULL,
+            high REAL NOT NULL,
+            low REAL NOT NULL,
+            close REAL NOT NULL,
+            volume REAL NOT NULL,
+            quote_asset_volume REAL NOT NULL,
+            PRIMARY KEY(symbol_id, open_time_ms)
+        ) WITHOUT ROWID;
+        ",
+    )?;
+    Ok(())
+}
+
+/// Indexes `(open_time_ms, symbol_id)` plus the columns the feature transform reads,
+/// so its time-ordered scan across symbols is an index-only range read instead of a
+/// sort over the `(symbol_id, open_time_ms)` primary key. Building them on a large
+/// existing store takes a while, so that is logged.
+fn create_time_indexes(conn: &Connection) -> Result<(), KlineStoreError> {
+    for (index, table) in [
+        ("klines_1s_by_time", "klines_1s"),
+        ("klines_1s_slim_by_time", "klines_1s_slim"),
+    ] {
+        let exists = conn
+            .query_row(
+                "SELECT 1 FROM sqlite_master WHERE type='index' AND name=?1",
+                params![index],
+                |row| row.get::<_, i64>(0),
+            )
+            .optional()?
+            .is_some();
+        if exists {
+            continue;
+        }
+        info!(
+            component = "kline_store",
+            event = "kline_store.index.start",
+            index
+        );
+        conn.execute_batch(&format!(
+            "CREATE INDEX {index}
+             ON {table}(open_time_ms, symbol_id, high, low, close, quote_asset_volume);"
+        ))?;
+        info!(
+            component = "kline_store",
+            event = "kline_store.index.finish",
+            index
+        );
+    }
+    Ok(())
+}
+
+/// `FROM` source covering both layouts, exposing the columns they share
+/// (`symbol_id`, `open_time_ms`, `open`, `high`, `low`, `close`, `volume`,
+/// `quote_asset_volume`). Plain `klines_1s` for stores that were never compacted
+/// (including pre-v2 stores opened read-only).
+pub(crate) fn klines_source_sql(conn: &Connection) -> rusqlite::Result<&'static str> {
+    let has_slim = conn
+        .query_row(
+            "SELECT 1 FROM sqlite_master WHERE type='table' AND name='klines_1s_slim'",
+            [],
+            |row| row.get::<_, i64>(0),
+        )
+        .optional()?
+        .is_some();
+    if !has_slim {
+        return Ok("klines_1s");
+    }
+    Ok("(
+        SELECT symbol_id, open_time_ms, open, high, low, close, volume, quote_asset_volume
+        FROM klines_1s
+        UNION ALL
+        SELECT symbol_id, open_time_ms, open, high, low, close, volume, quote_asset_volume
+        FROM klines_1s_slim
+    )")
+}
+
+/// Time-major scan of `[?1, ?2)` across all symbols, as read by the feature
+/// transform: `open_time_ms, symbol_id, high, low, close, quote_asset_volume` ordered by
+/// `(open_time_ms, symbol_id)`. Served by the `*_by_time` covering indexes. Seconds
+/// inside a recorded micro-halt are left out.
+pub(crate) fn frame_scan_sql(conn: &Connection) -> rusqlite::Result<String> {
+    let has_halts = conn
+        .query_row(
+            "SELECT 1 FROM sqlite_master WHERE type='table' AND name='kline_halts'",
+            [],
+            |row| row.get::<_, i64>(0),
+        )
+        .optional()?
+        .is_some();
+    let skip_halts = if has_halts {
+        "
+          AND NOT EXISTS (
+              SELECT 1
+              FROM kline_halts AS h
+              WHERE h.symbol_id = k.symbol_id
+                AND h.start_ms <= k.open_time_ms
+                AND k.open_time_ms < h.end_ms_exclusive
+          )"
+    } else {
+        ""
+    };
+    Ok(format!(
+        "
+        SELECT
+            k.open_time_ms,
+            k.symbol_id,
+            k.high,
+            k.low,
+            k.close,
+            k.quote_asset_volume
+        FROM {} AS k
+        WHERE k.open_time_ms >= ?1
+          AND k.open_time_ms < ?2{skip_halts}
+        ORDER BY k.open_time_ms ASC, k.symbol_id ASC
+        ",
+        klines_source_sql(conn)?
+    ))
+}
+
+/// Quarantined rows keep their raw values (nullable, since SQLite stores NaN as NULL)
+/// plus the violated rules, so they can be inspected or replayed after a fix.
+fn create_rejected_table(conn: &Connection) -> Result<(), KlineStoreError> {
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS klines_rejected (
+            symbol_id INTEGER NOT NULL,
+            open_time_ms INTEGER NOT NULL,
+            rules TEXT NOT NULL,
+            detail TEXT NOT NULL,
+            open REAL,
+            high REAL,
+            low REAL,
+            close REAL,
+            volume REAL,
+            close_time_ms INTEGER,
+            quote_asset_volume REAL,
+            trade_count INTEGER,
+            taker_buy_base_volume REAL,
+            taker_buy_quote_volume REAL,
+            rejected_at_ms INTEGER NOT NULL,
+            PRIMARY KEY(symbol_id, open_time_ms)
+        ) WITHOUT ROWID;
+        ",
+    )?;
+    Ok(())
+}
+
+/// Micro-halts per symbol, keyed by their first second (see
+/// [`KlineStore::flag_micro_halts`]).
+fn create_halts_table(conn: &Connection) -> Result<(), KlineStoreError> {
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS kline_halts (
+            symbol_id INTEGER NOT NULL,
+            start_ms INTEGER NOT NULL,
+            end_ms_exclusive INTEGER NOT NULL,
+            detected_at_ms INTEGER NOT NULL,
+            PRIMARY KEY(symbol_id, start_ms)
+        ) WITHOUT ROWID;
+        ",
+    )?;
+    Ok(())
+}
+
+fn table_exists(conn: &Connection, table: &str) -> Result<bool, KlineStoreError> {
+    let exists = conn
+        .query_row(
+            "SELECT 1 FROM sqlite_master WHERE type='table' AND name=?1 LIMIT 1",
+            params![table],
+            |row| row.get::<_, i64>(0),
+        )
+        .optional()?
+        .is_some();
+    Ok(exists)
+}
+
+fn table_has_column(conn: &Connection, table: &str, column: &str) -> Result<bool, KlineStoreError> {
+    let pragma = format!("PRAGMA table_info({table})");
+    let mut stmt = conn.prepare(&pragma)?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let name: String = row.get(1)?;
+        if name == column {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn table_is_without_rowid(conn: &Connection, table: &str) -> Result<bool, KlineStoreError> {
+    let sql: Option<String> = conn
+        .query_row(
+            "SELECT sql FROM sqlite_master WHERE type='table' AND name=?1",
+            params![table],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(sql
+        .as_deref()
+        .map(|ddl| ddl.to_ascii_uppercase().contains("WITHOUT ROWID"))
+        .unwrap_or(false))
+}
+
+pub(crate) fn symbol_id(symbol: BinanceSymbol) -> i64 {
+    match symbol {
+        BinanceSymbol::BtcUsdt => 1,
+        BinanceSymbol::EthUsdt => 2,
+        BinanceSymbol::SolUsdt => 3,
+        BinanceSymbol::XrpUsdt => 4,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kline(open_time_ms: i64, volume: f64) -> Kline1s {
+        Kline1s {
+            open_time_ms,
+            open: 100.0,
+            high: 101.0,
+            low: 99.0,
+            close: 100.5,
+            volume,
+            close_time_ms: open_time_ms + 999,
+            quote_asset_volume: 1_000.0,
+            trade_count: 3,
+            taker_buy_base_volume: 0.5,
+            taker_buy_quote_volume: 50.0,
+        }
+    }
+
+    #[test]
+    fn upsert_counts_accepted_rows_and_quarantines_rejects() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = KlineStore::open(
+            &dir.path().join("nested/klines_1s.sqlite"),
+            KlineValidationConfig::default(),
+        )
+        .unwrap();
+
+        let rows = vec![kline(0, 1.0), kline(1_000, -1.0), kline(2_000, 1.0)];
+        let counts = store.upsert_rows(BinanceSymbol::EthUsdt, rows).unwrap();
+        assert_eq!(counts.rows_rejected, 1);
+        assert_eq!(
+            store.count_range(BinanceSymbol::EthUsdt, 0, 3_000).unwrap(),
+            2
+        );
+        assert_eq!(
+            store.count_range(BinanceSymbol::BtcUsdt, 0, 3_000).unwrap(),
+            0
+        );
+        assert_eq!(
+            store
+                .count_rejected_range(BinanceSymbol::EthUsdt, 0, 3_000)
+                .unwrap(),
+            1
+        );
+
+        // Upserts are idempotent.
+        store
+            .upsert_rows(BinanceSymbol::EthUsdt, vec![kline(0, 2.0)])
+            .unwrap();
+        assert_eq!(
+            store.count_range(BinanceSymbol::EthUsdt, 0, 3_000).unwrap(),
+            2
+        );
+    }
+
+    #[test]
+    fn frame_scan_reads_the_time_indexes_without_sorting() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = KlineStore::open(
+            &dir.path().join("klines_1s.sqlite"),
+            KlineValidationConfig::default(),
+        )
+        .unwrap();
+        let plan = store
+            .conn
+            .prepare(&format!(
+                "EXPLAIN QUERY PLAN {}",
+                frame_scan_sql(&store.conn).unwrap()
+            ))
+            .unwrap()
+            .query_map(params![0, 1_000], |row| row.get::<_, String>(3))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+            .join("\n");
+        assert!(
+            plan.contains("COVERING INDEX klines_1s_by_time")
+                && plan.contains("COVERING INDEX klines_1s_slim_by_time"),
+            "{plan}"
+        );
+        assert!(!plan.contains("TEMP B-TREE"), "{plan}");
+    }
+
+    #[test]
+    fn compaction_keeps_rows_visible_and_rewrites_restore_full_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = KlineStore::open(
+            &dir.path().join("klines_1s.sqlite"),
+            KlineValidationConfig::default(),
+        )
+        .unwrap();
+        let rows = (0..4).map(|n| kline(n * 1_000, 1.0)).collect();
+        store.upsert_rows(BinanceSymbol::BtcUsdt, rows).unwrap();
+
+        assert_eq!(store.compact_older_than(2_000).unwrap(), 2);
+        assert_eq!(store.compact_older_than(2_000).unwrap(), 0);
+        assert_eq!(
+            store.count_range(BinanceSymbol::BtcUsdt, 0, 4_000).unwrap(),
+            4
+        );
+        assert_eq!(
+            store
+                .closes_range(BinanceSymbol::BtcUsdt, 0, 4_000)
+                .unwrap(),
+            (0..4).map(|n| (n * 1_000, 100.5)).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            store
+                .first_open_in_range(BinanceSymbol::BtcUsdt, 0, 4_000)
+                .unwrap(),
+            Some((0, 100.0))
+        );
+
+        // Re-syncing a compacted row moves it back instead of duplicating it.
+        store
+            .upsert_rows(BinanceSymbol::BtcUsdt, vec![kline(0, 2.0)])
+            .unwrap();
+        assert_eq!(
+            store.count_range(BinanceSymbol::BtcUsdt, 0, 4_000).unwrap(),
+            4
+        );
+        let slim: i64 = store
+            .conn
+            .query_row("SELECT COUNT(*) FROM klines_1s_slim", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(slim, 1);
+    }
+
+    #[test]
+    fn legacy_symbol_column_schema_is_migrated() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("klines_1s.sqlite");
+        {
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(
+                "
+                CREATE TABLE klines_1s (
+                    symbol TEXT NOT NULL,
+                    open_time_ms INTEGER NOT NULL,
+                    open REAL NOT NULL,
+                    high REAL NOT NULL,
+                    low REAL NOT NULL,
+                    close REAL NOT NULL,
+                    volume REAL NOT NULL,
+                    close_time_ms INTEGER NOT NULL,
+                    quote_asset_volume REAL NOT NULL,
+                    trade_count INTEGER NOT NULL,
+                    taker_buy_base_volume REAL NOT NULL,
+                    taker_buy_quote_volume REAL NOT NULL,
+                    PRIMARY KEY(symbol, open_time_ms)
+                );
+                INSERT INTO klines_1s VALUES ('SOLUSDT', 0, 1, 1, 1, 1, 1, 999, 1, 1, 1, 1);
+                ",
+            )
+            .unwrap();
+        }
+
+        let store = KlineStore::open(&path, KlineValidationConfig::default()).unwrap();
+        assert_eq!(
+            store.count_range(BinanceSymbol::SolUsdt, 0, 1_000).unwrap(),
+            1
+        );
+        assert!(table_is_without_rowid(&store.conn, "klines_1s").unwrap());
+        assert!(!table_has_column(&store.conn, "klines_1s", "symbol").unwrap());
+        assert_eq!(store.schema_version().unwrap(), KLINE_STORE_SCHEMA_VERSION);
+        drop(store);
+
+        Connection::open(&path)
+            .unwrap()
+            .pragma_update(None, "user_version", KLINE_STORE_SCHEMA_VERSION + 1)
+            .unwrap();
+        assert!(matches!(
+            KlineStore::open(&path, KlineValidationConfig::default()),
+            Err(KlineStoreError::UnsupportedSchema(_))
+        ));
+    }
+}
@@ -0,0 +1,522 @@
+//! Row validation for kline ingestion.
+//!
+//! Rules (each can be turned off):
+//! - `volume`: volume, quote volume, and taker buy volumes are finite and >= 0
+//! - `high_low`: `high >= low`
+//! - `close_range`: `low <= close <= high`
+//! - `monotonic`: open times strictly increase within one ingested batch
+//!
+//! Rows that break a rule are kept out of `klines_1s`. Under
+//! [`KlineValidationPolicy::Quarantine`] the ingesting binary writes them to a
+//! `klines_rejected` table instead of discarding them.
+//!
+//! Micro-halts are valid rows that are not market data: during maintenance Binance
+//! prints runs of zero-volume seconds repeating the same price. [`detect_micro_halts`]
+//! finds them so the store can flag them (`kline_halts`) for readers to skip.
+
+use serde::Serialize;
+use tracing::warn;
+
+use crate::env_vars::{EnvValue, Settings};
+use crate::{BinanceSymbol, Kline1s};
+
+/// Per-row violation logs per batch; the rest only show up in the batch summary.
+const MAX_LOGGED_VIOLATIONS_PER_BATCH: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KlineValidationPolicy {
+    /// Drop violating rows (counted and logged only).
+    Reject,
+    /// Drop violating rows from the main table and keep them in `klines_rejected`.
+    Quarantine,
+}
+
+impl EnvValue for KlineValidationPolicy {
+    fn parse_env(raw: &str) -> Option<Self> {
+        match raw.to_ascii_lowercase().as_str() {
+            "reject" => Some(Self::Reject),
+            "quarantine" => Some(Self::Quarantine),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KlineValidationConfig {
+    pub check_volumes: bool,
+    pub check_high_low: bool,
+    pub check_close_range: bool,
+    pub check_monotonic: bool,
+    pub policy: KlineValidationPolicy,
+}
+
+impl Default for KlineValidationConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl KlineValidationConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_KLINE_VALIDATION_RULES` (comma list of `volume`, `high_low`,
+    /// `close_range`, `monotonic`, or `none`) and `PMM_KLINE_VALIDATION_POLICY`
+    /// (`reject|quarantine`). Missing or unparseable values fall back to defaults.
+    pub fn from_settings(settings: &Settings) -> Self {
+        let policy = settings.get("PMM_KLINE_VALIDATION_POLICY");
+        settings
+            .parsed("PMM_KLINE_VALIDATION_RULES", |raw| {
+                Self::with_rules(raw, policy)
+            })
+            .unwrap_or(Self {
+                check_volumes: true,
+                check_high_low: true,
+                check_close_range: true,
+                check_monotonic: true,
+                policy,
+            })
+    }
+
+    fn with_rules(raw: &str, policy: KlineValidationPolicy) -> Option<Self> {
+        let mut cfg = Self {
+            check_volumes: false,
+            check_high_low: false,
+            check_close_range: false,
+            check_monotonic: false,
+            policy,
+        };
+        for rule in raw
+            .split(',')
+            .map(str::trim)
+            .filter(|rule| !rule.is_empty())
+        {
+            match rule.to_ascii_lowercase().as_str() {
+                "volume" => cfg.check_volumes = true,
+                "high_low" => cfg.check_high_low = true,
+                "close_range" => cfg.check_close_range = true,
+                "monotonic" => cfg.check_monotonic = true,
+                "none" => {}
+                _ => return None,
+            }
+        }
+        Some(cfg)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum KlineViolation {
+    NegativeVolume { field: &'static str, value: f64 },
+    HighBelowLow { high: f64, low: f64 },
+    CloseOutsideRange { close: f64, low: f64, high: f64 },
+    NonIncreasingTimestamp { previous_open_time_ms: i64 },
+}
+
+impl KlineViolation {
+    pub fn rule(&self) -> &'static str {
+        match self {
+            Self::NegativeVolume { .. } => "volume",
+            Self::HighBelowLow { .. } => "high_low",
+            Self::CloseOutsideRange { .. } => "close_range",
+            Self::NonIncreasingTimestamp { .. } => "monotonic",
+        }
+    }
+
+    pub fn detail(&self) -> String {
+        match self {
+            Self::NegativeVolume { field, value } => format!("{field}={value}"),
+            Self::HighBelowLow { high, low } => format!("high={high} < low={low}"),
+            Self::CloseOutsideRange { close, low, high } => {
+                format!("close={close} outside [{low}, {high}]")
+            }
+            Self::NonIncreasingTimestamp {
+                previous_open_time_ms,
+            } => format!("previous open_time_ms={previous_open_time_ms}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RejectedKline {
+    pub row: Kline1s,
+    pub violations: Vec<KlineViolation>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct KlineViolationCounts {
+    pub rows_checked: u64,
+    pub rows_rejected: u64,
+    pub negative_volume: u64,
+    pub high_below_low: u64,
+    pub close_outside_range: u64,
+    pub non_increasing_timestamp: u64,
+}
+
+impl KlineViolationCounts {
+    pub fn add(&mut self, other: &Self) {
+        self.rows_checked += other.rows_checked;
+        self.rows_rejected += other.rows_rejected;
+        self.negative_volume += other.negative_volume;
+        self.high_below_low += other.high_below_low;
+        self.close_outside_range += other.close_outside_range;
+        self.non_increasing_timestamp += other.non_increasing_timestamp;
+    }
+
+    fn record(&mut self, violation: &KlineViolation) {
+        match violation {
+            KlineViolation::NegativeVolume { .. } => self.negative_volume += 1,
+            KlineViolation::HighBelowLow { .. } => self.high_below_low += 1,
+            KlineViolation::CloseOutsideRange { .. } => self.close_outside_range += 1,
+            KlineViolation::NonIncreasingTimestamp { .. } => self.non_increasing_timestamp += 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KlineValidationOutcome {
+    pub accepted: Vec<Kline1s>,
+    pub rejected: Vec<RejectedKline>,
+    pub counts: KlineViolationCounts,
+}
+
+/// Violations of the enabled rules for one row. `previous_open_time_ms` is the open
+/// time of the last accepted row in the same batch.
+fn kline_violations(
+    row: &Kline1s,
+    previous_open_time_ms: Option<i64>,
+    cfg: &KlineValidationConfig,
+) -> Vec<KlineViolation> {
+    let mut violations = Vec::new();
+
+    if cfg.check_volumes {
+        for (field, value) in [
+            ("volume", row.volume),
+            ("quote_asset_volume", row.quote_asset_volume),
+            ("taker_buy_base_volume", row.taker_buy_base_volume),
+            ("taker_buy_quote_volume", row.taker_buy_quote_volume),
+        ] {
+            if !(value.is_finite() && value >= 0.0) {
+                violations.push(KlineViolation::NegativeVolume { field, value });
+            }
+        }
+    }
+    // NaN prices fail both price rules.
+    if cfg.check_high_low && (row.high.is_nan() || row.low.is_nan() || row.high < row.low) {
+        violations.push(KlineViolation::HighBelowLow {
+            high: row.high,
+            low: row.low,
+        });
+    }
+    if cfg.check_close_range
+        && (row.close.is_nan()
+            || row.low.is_nan()
+            || row.high.is_nan()
+            || row.close < row.low
+            || row.close > row.high)
+    {
+        violations.push(KlineViolation::CloseOutsideRange {
+            close: row.close,
+            low: row.low,
+            high: row.high,
+        });
+    }
+    if cfg.check_monotonic {
+        if let Some(previous_open_time_ms) = previous_open_time_ms {
+            if row.open_time_ms <= previous_open_time_ms {
+                violations.push(KlineViolation::NonIncreasingTimestamp {
+                    previous_open_time_ms,
+                });
+            }
+        }
+    }
+
+    violations
+}
+
+/// Splits one ingestion batch for `symbol` into accepted and rejected rows.
+///
+/// The first violations of a batch are logged individually with the symbol and row
+/// timestamp; a summary follows whenever anything was rejected.
+pub fn validate_klines(
+    symbol: BinanceSymbol,
+    rows: Vec<Kline1s>,
+    cfg: &KlineValidationConfig,
+) -> KlineValidationOutcome {
+    let mut outcome = KlineValidationOutcome {
+        accepted: Vec::with_capacity(rows.len()),
+        rejected: Vec::new(),
+        counts: KlineViolationCounts {
+            rows_checked: rows.len() as u64,
+            ..KlineViolationCounts::default()
+        },
+    };
+    let mut previous_open_time_ms = None;
+
+    for row in rows {
+        let violations = kline_violations(&row, previous_open_time_ms, cfg);
+        if violations.is_empty() {
+            previous_open_time_ms = Some(row.open_time_ms);
+            outcome.accepted.push(row);
+            continue;
+        }
+
+        for violation in &violations {
+            outcome.counts.record(violation);
+        }
+        if outcome.rejected.len() < MAX_LOGGED_VIOLATIONS_PER_BATCH {
+            let rules: Vec<&str> = violations.iter().map(KlineViolation::rule).collect();
+            let details: Vec<String> = violations.iter().map(KlineViolation::detail).collect();
+            warn!(
+                component = "kline_validation",
+                event = "kline.validation.violation",
+                symbol = symbol.as_str(),
+                open_time_ms = row.open_time_ms,
+                rules = %rules.join(","),
+                detail = %details.join("; "),
+                policy = ?cfg.policy
+            );
+        }
+        outcome.counts.rows_rejected += 1;
+        outcome.rejected.push(RejectedKline { row, violations });
+    }
+
+    if outcome.counts.rows_rejected > 0 {
+        warn!(
+            component = "kline_validation",
+            event = "kline.validation.summary",
+            symbol = symbol.as_str(),
+            rows_checked = outcome.counts.rows_checked,
+            rows_rejected = outcome.counts.rows_rejected,
+            negative_volume = outcome.counts.negative_volume,
+            high_below_low = outcome.counts.high_below_low,
+            close_outside_range = outcome.counts.close_outside_range,
+            non_increasing_timestamp = outcome.counts.non_increasing_timestamp,
+            policy = ?cfg.policy
+        );
+    }
+
+    outcome
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MicroHaltConfig {
+    /// Consecutive flat zero-volume seconds that make a halt; `0` disables detection.
+    pub min_run_secs: u32,
+}
+
+impl Default for MicroHaltConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl MicroHaltConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_KLINE_HALT_MIN_SECS`, falling back to the default when missing or
+    /// invalid.
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            min_run_secs: settings.get("PMM_KLINE_HALT_MIN_SECS"),
+        }
+    }
+}
+
+/// OHLCV of one stored second, as scanned for micro-halts.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct OhlcvPoint {
+    pub open_time_ms: i64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+}
+
+impl OhlcvPoint {
+    fn is_flat_and_empty(&self) -> bool {
+        self.volume == 0.0
+            && self.open == self.high
+            && self.open == self.low
+            && self.open == self.close
+    }
+}
+
+/// A run of halted seconds `[start_ms, end_ms_exclusive)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct KlineHalt {
+    pub start_ms: i64,
+    pub end_ms_exclusive: i64,
+}
+
+impl KlineHalt {
+    pub fn seconds(&self) -> i64 {
+        (self.end_ms_exclusive - self.start_ms) / 1_000
+    }
+}
+
+/// Runs of at least `cfg.min_run_secs` consecutive seconds that each have zero volume
+/// and `open == high == low == close`, at the same price throughout. `points` must be
+/// one symbol's rows ordered by open time; a missing second ends a run.
+pub fn detect_micro_halts(points: &[OhlcvPoint], cfg: &MicroHaltConfig) -> Vec<KlineHalt> {
+    let min_run = i64::from(cfg.min_run_secs);
+    let mut halts = Vec::new();
+    if min_run == 0 {
+        return halts;
+    }
+    let mut run: Option<(OhlcvPoint, OhlcvPoint)> = None;
+    let mut close_run = |run: Option<(OhlcvPoint, OhlcvPoint)>| {
+        if let Some((first, last)) = run {
+            let halt = KlineHalt {
+                start_ms: first.open_time_ms,
+                end_ms_exclusive: last.open_time_ms + 1_000,
+            };
+            if halt.seconds() >= min_run {
+                halts.push(halt);
+            }
+        }
+    };
+    for point in points {
+        if !point.is_flat_and_empty() {
+            close_run(run.take());
+            continue;
+        }
+        run = match run {
+            Some((first, last))
+                if point.open_time_ms == last.open_time_ms + 1_000 && point.close == last.close =>
+            {
+                Some((first, *point))
+            }
+            previous => {
+                close_run(previous);
+                Some((*point, *point))
+            }
+        };
+    }
+    close_run(run);
+    halts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(open_time_ms: i64) -> Kline1s {
+        Kline1s {
+            open_time_ms,
+            open: 100.0,
+            high: 101.0,
+            low: 99.0,
+            close: 100.5,
+            volume: 2.0,
+            close_time_ms: open_time_ms + 999,
+            quote_asset_volume: 200.0,
+            trade_count: 3,
+            taker_buy_base_volume: 1.0,
+            taker_buy_quote_volume: 100.0,
+        }
+    }
+
+    #[test]
+    fn each_rule_flags_its_violation_and_rejected_rows_do_not_advance_the_clock() {
+        let mut rows = vec![row(1_000), row(2_000), row(3_000), row(4_000), row(500)];
+        rows[1].volume = -1.0;
+        rows[2].high = 98.0;
+        rows[3].close = 102.0;
+        rows.push(row(5_000));
+
+        let outcome = validate_klines(
+            BinanceSymbol::BtcUsdt,
+            rows,
+            &KlineValidationConfig::default(),
+        );
+
+        let accepted: Vec<i64> = outcome.accepted.iter().map(|r| r.open_time_ms).collect();
+        assert_eq!(accepted, vec![1_000, 5_000]);
+        assert_eq!(outcome.counts.rows_checked, 6);
+        assert_eq!(outcome.counts.rows_rejected, 4);
+        assert_eq!(outcome.counts.negative_volume, 1);
+        // high=98 < low=99 also puts close outside [low, high].
+        assert_eq!(outcome.counts.high_below_low, 1);
+        assert_eq!(outcome.counts.close_outside_range, 2);
+        assert_eq!(outcome.counts.non_increasing_timestamp, 1);
+        assert_eq!(outcome.rejected[3].violations[0].rule(), "monotonic");
+    }
+
+    #[test]
+    fn disabled_rules_and_nan_handling() {
+        let mut bad = row(1_000);
+        bad.volume = f64::NAN;
+        bad.close = 150.0;
+
+        let only_close =
+            KlineValidationConfig::with_rules("close_range", KlineValidationPolicy::Reject)
+                .unwrap();
+        let violations = kline_violations(&bad, None, &only_close);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule(), "close_range");
+
+        let all = kline_violations(&bad, None, &KlineValidationConfig::default());
+        assert_eq!(all[0].rule(), "volume");
+
+        let none =
+            KlineValidationConfig::with_rules("none", KlineValidationPolicy::Reject).unwrap();
+        assert!(kline_violations(&bad, Some(5_000), &none).is_empty());
+        assert!(
+            KlineValidationConfig::with_rules("volume,bogus", KlineValidationPolicy::Reject)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn micro_halts_need_a_long_enough_flat_zero_volume_run() {
+        let flat = |second: i64, price: f64| OhlcvPoint {
+            open_time_ms: second * 1_000,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume: 0.0,
+        };
+        let traded = |second: i64| OhlcvPoint {
+            volume: 1.0,
+            ..flat(second, 100.0)
+        };
+        let mut points = vec![traded(0)];
+        points.extend((1..=6).map(|second| flat(second, 100.0)));
+        points.push(traded(7));
+        // Too short, then broken by a price change, then by a missing second.
+        points.extend((8..=10).map(|second| flat(second, 100.0)));
+        points.extend((11..=14).map(|second| flat(second, 101.0)));
+        points.extend((16..=21).map(|second| flat(second, 101.0)));
+
+        let cfg = MicroHaltConfig { min_run_secs: 5 };
+        let halts = detect_micro_halts(&points, &cfg);
+        assert_eq!(
+            halts,
+            vec![
+                KlineHalt {
+                    start_ms: 1_000,
+                    end_ms_exclusive: 7_000,
+                },
+                KlineHalt {
+                    start_ms: 16_000,
+                    end_ms_exclusive: 22_000,
+                },
+            ]
+        );
+        assert_eq!(halts[0].seconds(), 6);
+        assert_eq!(
+            detect_micro_halts(&points, &MicroHaltConfig { min_run_secs: 4 }).len(),
+            3
+        );
+        assert!(detect_micro_halts(&points, &MicroHaltConfig { min_run_secs: 0 }).is_empty());
+    }
+}
@@ -0,0 +1,491 @@
+//! Forward-looking up/down labels and the training dataset export.
+//!
+//! A training sample joins one feature row with the outcome of one market interval.
+//! For every `(coin, duration, horizon)` the export takes each interval of that
+//! duration in the range and samples it `horizon` seconds before it closes: the sample
+//! is the feature row of the second ending at `end - horizon` (for a full-length
+//! horizon, the row [`crate::run_backtest`] decides on). The label compares closes at
+//! the interval boundaries, like the market resolves: `label_up` is `1` when the close
+//! of the interval's last 1s kline is at or above the close of the kline ending at its
+//! start.
+//!
+//! Samples are written in shards of [`TrainingExportConfig::shard_rows`] rows, one
+//! directory per duration since each duration's features use its own window preset
+//! (and so its own [`FeatureSchema`]), next to a `manifest.json`
+//! [`TrainingExportReport`]. Intervals whose boundary klines or sample row are missing
+//! are counted as skipped instead of guessed.
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use parquet::basic::{Compression, ConvertedType, Repetition, Type as PhysicalType};
+use parquet::data_type::{ByteArray, ByteArrayType, DoubleType, Int32Type, Int64Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::types::Type as ParquetType;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::info;
+
+use crate::backtest::interval_starts;
+use crate::binance_klines::BinanceSymbol;
+use crate::features::{
+    horizon_conditioning, parquet_schema_metadata, transform_store_range_for_training,
+    FeatureError, FeatureRow, FeatureSchema, FeatureTransformConfig, FeatureTransformReport,
+    FeatureTransformRequest, GapPolicy,
+};
+use crate::kline_store::{KlineStore, KlineStoreError};
+use crate::report_json::{JsonReport, ReportJsonError};
+use crate::slug::{Coin, Duration};
+
+const STEP_MS: i64 = 1_000;
+
+/// Columns every sample carries ahead of its feature columns.
+pub const LABEL_COLUMNS: [&str; 12] = [
+    "coin",
+    "duration",
+    "horizon_seconds",
+    "start_ts_utc",
+    "end_ts_utc",
+    "ts_ms_utc",
+    "ref_close",
+    "final_close",
+    "ret_since_start",
+    "log_horizon_norm",
+    "sqrt_horizon_norm",
+    "label_up",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DatasetFormat {
+    Csv,
+    Parquet,
+}
+
+impl DatasetFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Parquet => "parquet",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrainingExportConfig {
+    pub output_dir: PathBuf,
+    pub format: DatasetFormat,
+    pub coins: Vec<Coin>,
+    pub durations: Vec<Duration>,
+    /// Seconds before the interval close each sample is taken at. A horizon longer
+    /// than an interval is skipped for it; empty means one sample per interval, at its
+    /// start.
+    pub horizons_seconds: Vec<u32>,
+    /// Gap policy of each duration's transform, which otherwise uses the duration's
+    /// window preset.
+    pub gap_policy: GapPolicy,
+    /// Samples per shard file.
+    pub shard_rows: usize,
+}
+
+impl TrainingExportConfig {
+    pub fn new(output_dir: impl Into<PathBuf>, format: DatasetFormat) -> Self {
+        Self {
+            output_dir: output_dir.into(),
+            format,
+            coins: vec![Coin::BTC, Coin::ETH, Coin::SOL, Coin::XRP],
+            durations: vec![Duration::M5, Duration::M15, Duration::H1],
+            horizons_seconds: Vec::new(),
+            gap_policy: GapPolicy::ReportAndSkip,
+            shard_rows: 100_000,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum DatasetExportError {
+    #[error("invalid training export: {0}")]
+    InvalidConfig(String),
+    #[error("coin {0:?} has no klines in the store")]
+    UnsupportedCoin(Coin),
+    #[error("feature transform failed: {0}")]
+    Features(#[from] FeatureError),
+    #[error("kline store error: {0}")]
+    Klines(#[from] KlineStoreError),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("CSV error: {0}")]
+    Csv(#[from] csv::Error),
+    #[error("Parquet error: {0}")]
+    Parquet(#[from] parquet::errors::ParquetError),
+    #[error("manifest error: {0}")]
+    Manifest(#[from] ReportJsonError),
+}
+
+/// One labelled sample.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrainingSample {
+    pub coin: Coin,
+    pub duration: Duration,
+    pub horizon_seconds: u32,
+    pub start_ts_utc: i64,
+    pub end_ts_utc: i64,
+    /// Open time of the sample's feature row; its kline closes `horizon_seconds`
+    /// before `end_ts_utc`.
+    pub ts_ms_utc: i64,
+    /// Close of the kline ending at the interval start.
+    pub ref_close: f64,
+    /// Close of the interval's last kline.
+    pub final_close: f64,
+    /// `ln(close at the sample / ref_close)`; `0` for a full-length horizon.
+    pub ret_since_start: f64,
+    pub log_horizon_norm: f64,
+    pub sqrt_horizon_norm: f64,
+    pub label_up: bool,
+    pub features: Vec<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatasetShard {
+    /// Relative to [`TrainingExportConfig::output_dir`].
+    pub path: PathBuf,
+    pub rows: u64,
+}
+
+/// Samples of one duration.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DurationDataset {
+    pub duration: Duration,
+    pub schema: FeatureSchema,
+    pub samples: u64,
+    pub up_labels: u64,
+    /// `(coin, interval, horizon)` combinations without boundary klines or a sample
+    /// row.
+    pub skipped_samples: u64,
+    pub shards: Vec<DatasetShard>,
+    pub transform: FeatureTransformReport,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrainingExportReport {
+    pub format: DatasetFormat,
+    pub start_ts_ms_utc: i64,
+    pub end_ts_ms_utc_exclusive: i64,
+    pub horizons_seconds: Vec<u32>,
+    pub durations: Vec<DurationDataset>,
+}
+
+impl JsonReport for TrainingExportReport {
+    const KIND: &'static str = "training_dataset";
+    const SCHEMA_VERSION: u32 = 1;
+}
+
+/// Labels every interval of `cfg.durations` inside `range` and writes the samples to
+/// `cfg.output_dir` as `{duration}/part-NNNNN.{csv,parquet}` plus `manifest.json`.
+pub fn export_training_dataset(
+    store_path: &Path,
+    range: &FeatureTransformRequest,
+    cfg: &TrainingExportConfig,
+) -> Result<TrainingExportReport, DatasetExportError> {
+    if range.end_ts_ms_utc_exclusive <= range.start_ts_ms_utc {
+        return Err(DatasetExportError::InvalidConfig(format!(
+            "end {} is not after start {}",
+            range.end_ts_ms_utc_exclusive, range.start_ts_ms_utc
+        )));
+    }
+    if cfg.shard_rows == 0 {
+        return Err(DatasetExportError::InvalidConfig(
+            "shard_rows must be positive".to_string(),
+        ));
+    }
+    let symbols = cfg
+        .coins
+        .iter()
+        .map(|&coin| BinanceSymbol::for_coin(coin).ok_or(DatasetExportError::UnsupportedCoin(coin)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let klines = KlineStore::open_read_only(store_path)?;
+
+    let mut durations = Vec::new();
+    for &duration in &cfg.durations {
+        let transform_cfg = FeatureTransformConfig {
+            gap_policy: cfg.gap_policy,
+            ..FeatureTransformConfig::for_duration(duration)
+        };
+        // Warm the rolling windows up before the first interval's sample row.
+        let warmup_ms = i64::from(
+            transform_cfg
+                .windows_seconds
+                .iter()
+                .copied()
+                .max()
+                .unwrap_or(1),
+        ) * STEP_MS;
+        let req = FeatureTransformRequest {
+            start_ts_ms_utc: range.start_ts_ms_utc - warmup_ms - STEP_MS,
+            end_ts_ms_utc_exclusive: range.end_ts_ms_utc_exclusive,
+        };
+        let (schema, rows, transform) =
+            transform_store_range_for_training(store_path, &req, &transform_cfg)?;
+
+        let dir = PathBuf::from(duration.as_label());
+        std::fs::create_dir_all(cfg.output_dir.join(&dir))?;
+        let mut dataset = DurationDataset {
+            duration,
+            schema,
+            samples: 0,
+            up_labels: 0,
+            skipped_samples: 0,
+            shards: Vec::new(),
+            transform,
+        };
+        let mut pending = Vec::new();
+        let intervals = interval_starts(
+            duration,
+            range.start_ts_ms_utc.div_euclid(1_000),
+            range.end_ts_ms_utc_exclusive.div_euclid(1_000),
+        );
+        for (start_ts_utc, end_ts_utc) in intervals {
+            let length = u32::try_from(end_ts_utc - start_ts_utc).unwrap_or(u32::MAX);
+            let horizons = if cfg.horizons_seconds.is_empty() {
+                vec![length]
+            } else {
+                cfg.horizons_seconds.clone()
+            };
+            for (&coin, &symbol) in cfg.coins.iter().zip(&symbols) {
+                let start_ms = start_ts_utc * 1_000;
+                let end_ms = end_ts_utc * 1_000;
+                let (Some(ref_close), Some(final_close)) = (
+                    close_at(&klines, symbol, start_ms - STEP_MS)?,
+                    close_at(&klines, symbol, end_ms - STEP_MS)?,
+                ) else {
+                    dataset.skipped_samples += horizons.len() as u64;
+                    continue;
+                };
+                for &horizon_seconds in &horizons {
+                    if horizon_seconds == 0 || horizon_seconds > length {
+                        continue;
+                    }
+                    let sample_ms = end_ms - i64::from(horizon_seconds) * 1_000 - STEP_MS;
+                    let (Some(row), Some(sample_close)) = (
+                        row_at(&rows, sample_ms),
+                        close_at(&klines, symbol, sample_ms)?,
+                    ) else {
+                        dataset.skipped_samples += 1;
+                        continue;
+                    };
+                    let conditioning =
+                        horizon_conditioning(horizon_seconds, transform_cfg.max_duration_seconds);
+                    let label_up = final_close >= ref_close;
+                    dataset.samples += 1;
+                    dataset.up_labels += u64::from(label_up);
+                    pending.push(TrainingSample {
+                        coin,
+                        duration,
+                        horizon_seconds,
+                        start_ts_utc,
+                        end_ts_utc,
+                        ts_ms_utc: row.ts_ms_utc,
+                        ref_close,
+                        final_close,
+                        ret_since_start: (sample_close / ref_close).ln(),
+                        log_horizon_norm: conditioning.log_horizon_norm,
+                        sqrt_horizon_norm: conditioning.sqrt_horizon_norm,
+                        label_up,
+                        features: row.values.clone(),
+                    });
+                    if pending.len() == cfg.shard_rows {
+                        flush_shard(cfg, &dir, &mut dataset, &mut pending)?;
+                    }
+                }
+            }
+        }
+        if !pending.is_empty() {
+            flush_shard(cfg, &dir, &mut dataset, &mut pending)?;
+        }
+        info!(
+            component = "labels",
+            event = "labels.export.duration",
+            duration = duration.as_label(),
+            samples = dataset.samples,
+            up_labels = dataset.up_labels,
+            skipped_samples = dataset.skipped_samples,
+            shards = dataset.shards.len()
+        );
+        durations.push(dataset);
+    }
+
+    let report = TrainingExportReport {
+        format: cfg.format,
+        start_ts_ms_utc: range.start_ts_ms_utc,
+        end_ts_ms_utc_exclusive: range.end_ts_ms_utc_exclusive,
+        horizons_seconds: cfg.horizons_seconds.clone(),
+        durations,
+    };
+    report.write_report_json(&cfg.output_dir.join("manifest.json"))?;
+    Ok(report)
+}
+
+/// Close of the kline opening at `open_time_ms`, if stored.
+fn close_at(
+    klines: &KlineStore,
+    symbol: BinanceSymbol,
+    open_time_ms: i64,
+) -> Result<Option<f64>, KlineStoreError> {
+    Ok(klines
+        .closes_range(symbol, open_time_ms, open_time_ms + STEP_MS)?
+        .first()
+        .map(|&(_, close)| close))
+}
+
+fn row_at(rows: &[FeatureRow], ts_ms_utc: i64) -> Option<&FeatureRow> {
+    rows.binary_search_by_key(&ts_ms_utc, |row| row.ts_ms_utc)
+        .ok()
+        .map(|idx| &rows[idx])
+}
+
+fn flush_shard(
+    cfg: &TrainingExportConfig,
+    dir: &Path,
+    dataset: &mut DurationDataset,
+    pending: &mut Vec<TrainingSample>,
+) -> Result<(), DatasetExportError> {
+    let path = dir.join(format!(
+        "part-{:05}.{}",
+        dataset.shards.len(),
+        cfg.format.extension()
+    ));
+    let full = cfg.output_dir.join(&path);
+    match cfg.format {
+        DatasetFormat::Csv => write_samples_csv(&full, &dataset.schema, pending)?,
+        DatasetFormat::Parquet => write_samples_parquet(&full, &dataset.schema, pending)?,
+    }
+    dataset.shards.push(DatasetShard {
+        path,
+        rows: pending.len() as u64,
+    });
+    pending.clear();
+    Ok(())
+}
+
+fn write_samples_csv(
+    path: &Path,
+    schema: &FeatureSchema,
+    samples: &[TrainingSample],
+) -> Result<(), DatasetExportError> {
+    let mut writer = csv::Writer::from_path(path)?;
+    writer.write_record(
+        LABEL_COLUMNS
+            .iter()
+            .copied()
+            .chain(schema.columns.iter().map(|column| column.name.as_str())),
+    )?;
+    for sample in samples {
+        let mut record = vec![
+            sample.coin.ticker().to_string(),
+            sample.duration.as_label().to_string(),
+            sample.horizon_seconds.to_string(),
+            sample.start_ts_utc.to_string(),
+            sample.end_ts_utc.to_string(),
+            sample.ts_ms_utc.to_string(),
+            sample.ref_close.to_string(),
+            sample.final_close.to_string(),
+            sample.ret_since_start.to_string(),
+            sample.log_horizon_norm.to_string(),
+            sample.sqrt_horizon_norm.to_string(),
+            u8::from(sample.label_up).to_string(),
+        ];
+        record.extend(sample.features.iter().map(f64::to_string));
+        writer.write_record(&record)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// One row group per shard, with the feature schema's version and fingerprint in the
+/// footer like [`crate::FeatureParquetWriter`] files.
+fn write_samples_parquet(
+    path: &Path,
+    schema: &FeatureSchema,
+    samples: &[TrainingSample],
+) -> Result<(), DatasetExportError> {
+    let column = |name: &str, physical: PhysicalType| {
+        let builder = ParquetType::primitive_type_builder(name, physical)
+            .with_repetition(Repetition::REQUIRED);
+        let builder = if physical == PhysicalType::BYTE_ARRAY {
+            builder.with_converted_type(ConvertedType::UTF8)
+        } else {
+            builder
+        };
+        builder.build().map(Arc::new)
+    };
+    let mut fields = vec![
+        column("coin", PhysicalType::BYTE_ARRAY)?,
+        column("duration", PhysicalType::BYTE_ARRAY)?,
+        column("horizon_seconds", PhysicalType::INT32)?,
+        column("start_ts_utc", PhysicalType::INT64)?,
+        column("end_ts_utc", PhysicalType::INT64)?,
+        column("ts_ms_utc", PhysicalType::INT64)?,
+        column("ref_close", PhysicalType::DOUBLE)?,
+        column("final_close", PhysicalType::DOUBLE)?,
+        column("ret_since_start", PhysicalType::DOUBLE)?,
+        column("log_horizon_norm", PhysicalType::DOUBLE)?,
+        column("sqrt_horizon_norm", PhysicalType::DOUBLE)?,
+        column("label_up", PhysicalType::INT32)?,
+    ];
+    for feature in &schema.columns {
+        fields.push(column(&feature.name, PhysicalType::DOUBLE)?);
+    }
+    let message = ParquetType::group_type_builder("training_sample")
+        .with_fields(fields)
+        .build()?;
+    let props = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .set_key_value_metadata(Some(parquet_schema_metadata(schema)))
+        .build();
+    let mut writer =
+        SerializedFileWriter::new(File::create(path)?, Arc::new(message), Arc::new(props))?;
+    let mut group = writer.next_row_group()?;
+
+    let text = |value: fn(&TrainingSample) -> String| -> Vec<ByteArray> {
+        samples
+            .iter()
+            .map(|sample| ByteArray::from(value(sample).into_bytes()))
+            .collect()
+    };
+    let int64 =
+        |value: fn(&TrainingSample) -> i64| -> Vec<i64> { samples.iter().map(value).collect() };
+    let double =
+        |value: fn(&TrainingSample) -> f64| -> Vec<f64> { samples.iter().map(value).collect() };
+    let int32 =
+        |value: fn(&TrainingSample) -> i32| -> Vec<i32> { samples.iter().map(value).collect() };
+    macro_rules! write_column {
+        ($kind:ty, $values:expr) => {{
+            let mut column = group
+                .next_column()?
+                .ok_or_else(|| parquet::errors::ParquetError::General("missing column".into()))?;
+            column.typed::<$kind>().write_batch(&$values, None, None)?;
+            column.close()?;
+        }};
+    }
+    write_column!(ByteArrayType, text(|s| s.coin.ticker().to_string()));
+    write_column!(ByteArrayType, text(|s| s.duration.as_label().to_string()));
+    write_column!(Int32Type, int32(|s| s.horizon_seconds as i32));
+    write_column!(Int64Type, int64(|s| s.start_ts_utc));
+    write_column!(Int64Type, int64(|s| s.end_ts_utc));
+    write_column!(Int64Type, int64(|s| s.ts_ms_utc));
+    write_column!(DoubleType, double(|s| s.ref_close));
+    write_column!(DoubleType, double(|s| s.final_close));
+    write_column!(DoubleType, double(|s| s.ret_since_start));
+    write_column!(DoubleType, double(|s| s.log_horizon_norm));
+    write_column!(DoubleType, double(|s| s.sqrt_horizon_norm));
+    write_column!(Int32Type, int32(|s| i32::from(s.label_up)));
+    for idx in 0..schema.columns.len() {
+        let values: Vec<f64> = samples.iter().map(|s| s.features[idx]).collect();
+        write_column!(DoubleType, values);
+    }
+    group.close()?;
+    writer.close()?;
+    Ok(())
+}
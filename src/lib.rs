@@ -1,50 +1,382 @@
 //! PMM core crate.
 //!
-//! Current implemented scope:
-//! - Step 1: deterministic slug generation
-//! - Step 2: discovery-resolution data model and batch mapping flow
-//! - Step 8: historical Binance 1s kline loading
+//! Areas:
+//! - Slugs and discovery: deterministic slug generation, interval scheduling, and Gamma
+//!   resolution into dashboard rows (live resolution behind `discovery-sdk`, with
+//!   failover across Gamma endpoints), plus live CLOB order books over the market
+//!   websocket
+//! - Dashboard: snapshot sources, display formatting, HTML/JSON router with a redacted
+//!   public view, `/healthz`
+//! - Binance history: 1s kline archives, ingestion validation, and the SQLite
+//!   `KlineStore`, plus live spot prices over the Binance websocket
+//! - Features: schema-versioned transforms, window presets, and drift analysis, with
+//!   transform and kline coverage reports exportable as versioned JSON files
+//! - Trading: probability models per coin/duration with ensembles, capped fractional
+//!   Kelly sizing, portfolio exposure limits across correlated coins, backtest fill
+//!   model, seeded paper fills, strategy routines with a decision log, a two-sided
+//!   quoting engine with a dry-run mode, CLOB order placement (`OrderClient`, behind
+//!   `trading`), and wallet positions and PnL reconciled from CLOB fills
+//! - Operations: logging, signed lifecycle webhooks, clock drift detection, persistent
+//!   alert state with `/alerts`, startup preflight checks, Grafana JSON
+//!   datasource over recorded snapshots, Prometheus `/metrics`, weekly data-quality
+//!   scorecards on `/quality`, snapshot history on `/dashboard/history`, and the
+//!   `PMM_*` env var registry with startup validation
+//! - Persistence: storage traits for klines and alerts; SQLite files by default, one
+//!   shared Postgres database behind the `postgres` feature
+//!
+//! Most callers only need [`prelude`]. Runnable end-to-end flows live under
+//! `examples/` (`slug_roundtrip`, `offline_dashboard`, `kline_fixture_load`,
+//! `feature_transform`) and run as part of `cargo test`; `demo_feature_transform` runs
+//! on the bundled dataset of the `demo-data` feature.
 
+mod alerts;
+mod analysis;
+mod backtest;
 mod binance_klines;
+mod binance_ws;
+mod canary;
+mod capital;
+mod clob_stream;
+mod clock_drift;
+mod config;
+mod curve;
 mod dashboard;
+#[cfg(feature = "demo-data")]
+mod demo_data;
 mod discovery;
+mod env_vars;
+mod faults;
 mod features;
+mod fees;
+mod fill_model;
+mod gamma_endpoints;
+mod gamma_schema;
+mod grafana;
+mod health;
+mod journal;
+mod kline_import;
+mod kline_store;
+mod kline_validation;
+mod labels;
+mod metrics;
+mod model;
+mod model_inputs;
+mod models;
 mod observability;
+mod orders;
+mod paper_fills;
+mod paper_trading;
+mod persistence;
+mod positions;
+#[cfg(feature = "postgres")]
+mod postgres_store;
+mod preflight;
+mod quality;
+mod queue_position;
+mod realized_vol;
+mod redaction;
+mod replay;
+mod report_json;
+mod rewards;
+mod risk;
+mod schedule;
+mod sizing;
 mod slug;
+mod slug_audit;
+mod smoothing;
+mod snapshot_store;
+mod strategy;
+mod toggles;
+mod trading;
+mod views;
+mod warmup;
+mod webhooks;
+
+/// The types most callers need: `use pmm::prelude::*;`.
+pub mod prelude {
+    pub use crate::{
+        build_slug, parse_coin, parse_duration, parse_slug_at, BinanceSymbol, Coin, DashboardRow,
+        DashboardSnapshot, DashboardSnapshotSource, DecisionLog, DiscoveryConfig, DiscoveryKey,
+        DiscoveryStatus, DiscoveryUniverse, Duration, FeatureTransformConfig,
+        FeatureTransformRequest, FeatureWindowPreset, HistoricalKlinesConfig,
+        InMemoryMockSnapshotSource, Kline1s, KlineLoadRequest, KlineStore, KlineValidationConfig,
+        SizingConfig, SlugConfig, SnapshotStatus, ALL_DURATIONS,
+    };
+}
 
+pub use alerts::{
+    alerts_router, AlertConfig, AlertDecision, AlertManager, AlertRecord, AlertSeverity,
+    AlertState, AlertStore, AlertStoreError, AlertsQuery, SuppressReason,
+};
+pub use analysis::{
+    drift_level, feature_distribution, feature_drift, feature_drift_report,
+    population_stability_index, settle_paper_fills, time_of_day_attribution, AttributionSample,
+    DriftConfig, DriftError, DriftLevel, FeatureDistribution, FeatureDrift, FeatureDriftReport,
+    TimeOfDayCell, TimeOfDayMatrix, TimeOfDayReport, WEEKDAY_LABELS,
+};
+pub use backtest::{
+    run_backtest, BacktestConfig, BacktestEntry, BacktestError, BacktestInterval, BacktestReport,
+    BacktestSummary, BacktestTrade, Strategy,
+};
 pub use binance_klines::{
-    load_1s_klines, plan_required_archives, sync_archives, ArchiveKind, ArchiveRef, BinanceSymbol,
+    fetch_rest_klines, load_1s_klines, parse_kline_csv, parse_rest_kline_row,
+    plan_required_archives, sync_archives, write_kline_archive, ArchiveKind, ArchiveKlineSource,
+    ArchiveRef, BinanceSymbol, ChecksumFailure, ChecksumFailureLog, ChecksumFailureRecord,
     HistoricalKlinesConfig, Kline1s, KlineCoverageReport, KlineLoadError, KlineLoadRequest,
-    KlineLoadResult, LocalArchive, LocalArchiveSource,
+    KlineLoadResult, KlineScope, LocalArchive, LocalArchiveSource, ALL_BINANCE_SYMBOLS,
+    BINANCE_REST_KLINES_URL, CHECKSUM_FAILURE_LOG_FILE,
+};
+pub use binance_ws::{
+    BinanceStreamKind, BinanceWsConfig, BinanceWsError, BinanceWsStream, LivePrice, LivePrices,
+    PriceSource, DEFAULT_BINANCE_WS_URL,
+};
+pub use canary::{
+    parse_canary_markets, ArmPnl, CanaryConfig, CanaryPnl, CanaryQuoting, StrategyArm,
+    CANARY_ROUTINE,
 };
+pub use capital::{CapitalConfig, CapitalSource, CapitalUsage, WalletReport};
+pub use clob_stream::{
+    BookLevel, BookSubscription, ClobBookStream, ClobBooks, ClobStreamConfig, ClobStreamError,
+    OrderBook, OrderBookSource, DEFAULT_CLOB_WS_URL,
+};
+pub use clock_drift::{
+    measure_clock_drift, ClockDriftConfig, ClockDriftLevel, ClockDriftMonitor, ClockDriftStatus,
+    ClockSample, ClockSource, ClockSourceError, CLOCK_DRIFT_ALERT_KEY,
+};
+pub use config::{
+    config_path, load_settings, ConfigError, ConfigValue, DashboardSection, DiscoverySection,
+    FeaturesSection, InvalidConfigValue, KlinesSection, LoggingSection, PmmConfig, StrategySection,
+    DEFAULT_CONFIG_PATH,
+};
+pub use curve::{curve_router, market_curve, MarketCurve, MarketCurveConfig};
 pub use dashboard::{
-    apply_filters, build_display_snapshot, compute_in_interval, dashboard_router, demo_snapshot,
-    format_row_for_display, market_link, render_dashboard_html, BetsOpenFilter,
-    DashboardDisplayRow, DashboardDisplaySnapshot, DashboardFilters, DashboardQuery, DashboardRow,
-    DashboardSnapshot, DashboardSnapshotSource, InIntervalFilter, InMemoryMockSnapshotSource,
-    DASHBOARD_HEADERS,
+    activity_between, apply_dry_run_quotes, apply_filters, apply_live_prices,
+    apply_model_probabilities, apply_order_books, apply_positions, apply_probability_smoothing,
+    apply_queue_estimates, apply_snapshot_delta, apply_suggested_sizes, build_display_snapshot,
+    build_display_snapshot_with_format, compute_in_interval, dashboard_router,
+    dashboard_router_with_config, dashboard_router_with_format, dashboard_router_with_redaction,
+    dashboard_router_with_views, demo_snapshot, demo_snapshot_at, diff_display_snapshots,
+    format_row_for_display, format_row_for_display_with_format, late_ref_captures, market_link,
+    occupancy_grid, render_dashboard_html, suggested_size_for_row, ActivityEvent, ActivityFeed,
+    ActivityFeedConfig, ActivityFeedView, ActivityKind, BetsOpenFilter, DashboardConfig,
+    DashboardDisplayRow, DashboardDisplaySnapshot, DashboardFilters, DashboardPush, DashboardQuery,
+    DashboardRow, DashboardSnapshot, DashboardSnapshotDelta, DashboardSnapshotSource,
+    DryRunQuoteConfig, InIntervalFilter, InMemoryMockSnapshotSource, NumberFormatConfig,
+    OccupancyCell, OccupancyGrid, SimulatedClock, SimulatedDemoSnapshotSource, SnapshotProvenance,
+    SnapshotSeqGap, SnapshotStatus, WindowOccupancy, DASHBOARD_COLUMN_KEYS, DASHBOARD_HEADERS,
+    PROBABILITY_HORIZONS_SECONDS,
 };
 #[cfg(feature = "discovery-sdk")]
-pub use dashboard::{LiveDiscoveryConfig, LiveDiscoverySnapshotSource};
+pub use dashboard::{
+    LiveDiscoveryConfig, LiveDiscoverySnapshotSource, LIVE_UNAVAILABLE_ALERT_KEY,
+    REF_CAPTURE_ALERT_KEY,
+};
+#[cfg(feature = "demo-data")]
+pub use demo_data::{
+    demo_klines, demo_replay_config, demo_replay_timeline, demo_snapshot_history,
+    write_demo_kline_store, DemoDataError, DEMO_END_TS_UTC, DEMO_START_TS_UTC,
+};
 pub use discovery::resolve_discovery_batch_with_fetcher;
 pub use discovery::{
-    build_active_and_next_discovery_keys, build_active_discovery_keys,
-    build_previous_active_and_next_discovery_keys, interval_starts_for_now, DiscoveryConfig,
-    DiscoveryError, DiscoveryKey, DiscoveryRow, DiscoveryStatus, DiscoveryWindow, IntervalStarts,
-    ScheduledDiscoveryKey, SlugFetchOutcome, UnresolvedReason, ALL_COINS, ALL_DURATIONS,
+    build_active_and_next_discovery_keys, build_active_discovery_keys, build_pair_discovery_keys,
+    build_previous_active_and_next_discovery_keys, build_upcoming_discovery_keys,
+    discovery_deadline_ts_ms, interval_end_ts_utc, interval_starts_for_now,
+    reject_window_mismatches, DiscoveryConfig, DiscoveryError, DiscoveryKey, DiscoveryRow,
+    DiscoveryStatus, DiscoveryUniverse, DiscoveryWindow, IntervalStarts, MarketMeta,
+    ScheduledDiscoveryKey, SlugFetchOutcome, UnresolvedReason, ALL_DURATIONS,
 };
 #[cfg(feature = "discovery-sdk")]
-pub use discovery::{resolve_discovery_batch, SdkMarket};
+pub use discovery::{
+    resolve_discovery_batch, resolve_discovery_batch_with_endpoints,
+    resolve_discovery_batch_with_sampler,
+};
 
+pub use env_vars::{
+    effective_env, env_var, process_env_names, render_env_table, unknown_env_names, validate_env,
+    validate_process_env, EffectiveEnvVar, EnvKind, EnvValidation, EnvValue, EnvValueSource,
+    EnvVar, InvalidEnvValue, Settings, ENV_PREFIXES, ENV_VARS,
+};
+#[cfg(feature = "fault-injection")]
+pub use faults::{active_faults, inject_faults, FaultGuard, FaultPlan};
 pub use features::{
-    assert_schema_compatible, build_feature_schema, horizon_conditioning, transform_store_range,
-    transform_store_range_for_runtime_cold_start, transform_store_range_for_training,
-    FeatureColumn, FeatureDType, FeatureError, FeatureRow, FeatureSchema, FeatureTransformConfig,
-    FeatureTransformReport, FeatureTransformRequest, GapPolicy, HorizonConditioning,
-    FEATURE_SCHEMA_VERSION,
+    assert_schema_compatible, build_feature_schema, feature_transform_metrics,
+    horizon_conditioning, transform_store_range, transform_store_range_for_runtime_cold_start,
+    transform_store_range_for_training, transform_store_range_into,
+    transform_store_range_to_parquet, transform_store_range_with_backfill_into,
+    transform_store_range_with_timing, write_feature_rows_parquet, FeatureColumn, FeatureDType,
+    FeatureError, FeatureParquetWriter, FeatureRow, FeatureSchema, FeatureTransformConfig,
+    FeatureTransformMetrics, FeatureTransformReport, FeatureTransformRequest,
+    FeatureTransformTiming, FeatureUpdater, FeatureWindowPreset, GapPolicy, HorizonConditioning,
+    ALL_FEATURE_WINDOW_PRESETS, FEATURE_PARQUET_ROW_GROUP_ROWS, FEATURE_SCHEMA_VERSION,
+    MAX_BACKFILL_POINTS, PARQUET_SCHEMA_FINGERPRINT_KEY, PARQUET_SCHEMA_VERSION_KEY,
+    TRANSFORM_CHUNK_LATENCY_BUCKETS, TRANSFORM_CHUNK_ROWS,
+};
+pub use fees::{
+    fee_params_at, FeeParams, FeeRegistry, FeeScheduleEntry, FeeScheduleError,
+    CRYPTO_15_MIN_FEE_TYPE,
+};
+pub use fill_model::{
+    calibrate_fill_model, expected_fee_usdc, BookTop, FillCalibration, FillMode, FillModel,
+    FillModelConfig, OrderSide, OrderStyle, SimFill, SimOrder, TradePrint,
+};
+pub use gamma_endpoints::{
+    GammaEndpointConfig, GammaEndpointPool, GammaEndpointStatus, DEFAULT_GAMMA_BASE_URL,
+};
+pub use gamma_schema::{
+    is_fee_or_reward_field, schema_drift_report, unknown_fields, GammaPayloadConfig,
+    GammaPayloadError, GammaPayloadSample, GammaPayloadSampler, GammaPayloadStore,
+    SchemaDriftReport, UnknownGammaField,
+};
+pub use grafana::{
+    grafana_router, GrafanaQueryRequest, GrafanaSeries, SnapshotCompaction, SnapshotRecorder,
+    SnapshotRecorderConfig, SnapshotRetentionPolicy, GRAFANA_METRICS, NET_PROFIT_TOTAL_TARGET,
+};
+pub use health::{
+    health_report, health_router, health_router_with_warmup, readiness_report, HealthReport,
+    ReadinessCheck, ReadinessConfig, ReadinessReport,
+};
+pub use journal::{
+    journal_router, JournalConfig, JournalError, JournalInterval, JournalNote, JournalQuery,
+    MarketJournal, NewJournalNote, TradeJournal, MAX_AUTHOR_CHARS, MAX_NOTE_CHARS,
+};
+pub use kline_import::{
+    import_klines, KlineConflictPolicy, KlineImportConflict, KlineImportError, KlineImportFormat,
+    KlineImportReport, KlineImportRequest, MAX_REPORTED_CONFLICTS,
+};
+pub use kline_store::{KlineStore, KlineStoreError, KLINE_STORE_SCHEMA_VERSION};
+pub use kline_validation::{
+    detect_micro_halts, validate_klines, KlineHalt, KlineValidationConfig, KlineValidationOutcome,
+    KlineValidationPolicy, KlineViolation, KlineViolationCounts, MicroHaltConfig, OhlcvPoint,
+    RejectedKline,
+};
+pub use labels::{
+    export_training_dataset, DatasetExportError, DatasetFormat, DatasetShard, DurationDataset,
+    TrainingExportConfig, TrainingExportReport, TrainingSample, LABEL_COLUMNS,
+};
+pub use metrics::{metrics_router, render_prometheus, PROMETHEUS_CONTENT_TYPE};
+pub use model::{
+    load_model_registry, ModelArtifact, ModelConfig, ModelError, ModelKind, RegressionTree,
+    TrainedModel, TreeNode, MODEL_ARTIFACT_VERSION,
+};
+pub use model_inputs::{
+    model_input_router, ModelInputCaptures, ModelInputConfig, ModelInputError, ModelInputQuery,
+    ModelInputRecord, ModelInputSource, ModelInputStore,
+};
+pub use models::{
+    Ensemble, EnsembleCombiner, ModelPrediction, ModelRegistry, ModelRegistryError, Predictor,
 };
 pub use observability::{
-    init_logging, log_app_bind, log_app_start, log_source_selected, logging_config_from_env,
-    LogFormat, LoggingConfig, LoggingInitError,
+    init_logging, log_app_bind, log_app_start, log_source_selected, logging_config,
+    logging_config_from_env, with_request_ids, LogFileConfig, LogFormat, LogRotation,
+    LoggingConfig, LoggingInitError, SizeRotatingFile, SizeRotatingWriter, REQUEST_ID_HEADER,
+};
+pub use orders::{
+    cancel_all_on_shutdown, load_shutdown_report, order_rate_metrics, shutdown_signal, OrderError,
+    OrderOp, OrderRateLimitConfig, OrderRateLimiter, OrderRateMetrics, PaperOrderBackend,
+    ShutdownConfig, ShutdownReport, ThrottledTradingClient,
+};
+pub use paper_fills::{PaperFill, PaperFillGenerator, PaperLedger};
+pub use paper_trading::{
+    read_paper_fills, PaperOrderClient, PaperTradingConfig, PaperTradingError, SimulatedFill,
+};
+pub use persistence::{
+    open_alert_backend, open_kline_backend, open_kline_reader, AlertBackend, KlineBackend,
+    PersistenceBackend, SQLITE_ONLY_STORES,
+};
+#[cfg(feature = "trading")]
+pub use positions::PositionFeed;
+pub use positions::{
+    MarketPosition, OutcomePosition, PositionBook, PositionConfig, PositionFill, PositionSource,
+};
+#[cfg(feature = "postgres")]
+pub use postgres_store::{PgAlertStore, PgDecisionLog, PgKlineStore};
+pub use preflight::{
+    run_preflight, run_preflight_with_env, PreflightCheck, PreflightConfig, PreflightReport,
+    PreflightStatus, RunMode,
+};
+pub use quality::{
+    quality_router, score_klines, snapshot_up, week_start_ts_utc, DiscoveryCycleTotals,
+    KlineQuality, QualityConfig, QualityError, QualityQuery, QualityScorecard, QualityScorecards,
+};
+pub use queue_position::{QueueEstimate, QueuePositionTracker, RestingOrder};
+pub use realized_vol::{
+    IntervalKlineStats, RealizedVarianceAccumulator, RealizedVolConfig, RealizedVolScale,
+    RealizedVolTracker, SECONDS_PER_YEAR,
+};
+pub use redaction::{
+    with_dashboard_token, PublicRequest, RedactionConfig, PRIVATE_COLUMNS, PRIVATE_ROUTE_PREFIXES,
+    REDACTABLE_COLUMNS, REDACTED, TOKEN_COOKIE,
+};
+pub use replay::{
+    load_replay_timeline, parse_replay_speed, play_replay, ReplayConfig, ReplayError, ReplayEvent,
+    ReplayEventKind, ReplaySnapshotSource, ReplaySummary, ReplayTimeline, MAX_REPLAY_ROWS,
+};
+pub use report_json::{
+    read_report_json, write_report_json, JsonReport, ReportEnvelope, ReportJsonError,
+};
+pub use rewards::{
+    fetch_reward_payouts, parse_reward_activity, reconcile_rewards, RewardAccrual,
+    RewardAccrualStore, RewardDayTotal, RewardDiscrepancy, RewardPayout, RewardReconcileConfig,
+    RewardReconcileError, RewardReconciliation, DEFAULT_DATA_API_URL,
+};
+pub use risk::{
+    CorrelationMatrix, MarketExposure, PortfolioCheck, PortfolioRisk, PortfolioRiskConfig,
+};
+pub use schedule::{
+    discovery_schedule, discovery_schedule_router, DiscoverySchedule, MAX_SCHEDULE_HORIZON,
+};
+pub use sizing::{
+    kelly_stake, taker_fee_per_share, SizingConfig, SizingDecision, SizingError, SizingInput,
+    SizingLimit, SizingSide,
+};
+pub use slug::{
+    build_slug, build_underlying_slug, parse_coin, parse_duration, parse_slug, parse_slug_at,
+    parse_underlying_slug_at, Coin, CoinRegistry, CoinSpec, Duration, MarketUnderlying, SlugConfig,
+    SlugError, MAX_COINS, PAIR_DURATIONS,
+};
+#[cfg(feature = "discovery-sdk")]
+pub use slug_audit::count_gamma_markets_by_slug;
+pub use slug_audit::{
+    audit_slug_uniqueness, cross_check_gamma_sample, GammaCrossCheck, GammaSlugError,
+    SlugAuditDurationSummary, SlugAuditError, SlugAuditReport, SlugAuditRequest, SlugCollision,
+    SlugCollisionKind, SlugOwner,
+};
+pub use smoothing::{ProbabilitySmoother, ProbabilitySmoothingConfig};
+pub use snapshot_store::{
+    snapshot_history_router, SnapshotHistory, SnapshotHistoryPoint, SnapshotHistoryQuery,
+    SnapshotStore, SnapshotStoreConfig, SnapshotStoreError, MAX_HISTORY_POINTS,
+};
+pub use strategy::{
+    decision_store_path, decision_store_path_from_env, in_flatten_window, parse_quote_overrides,
+    plan_end_of_interval, plan_maker_quotes, plan_quote_window, plan_two_sided_quotes,
+    quote_feature_schema, quote_phase, record_end_of_interval_plan, record_quote_phase_change,
+    spot_feature_row, DecisionAction, DecisionLog, DecisionRecord, EndOfIntervalPlan,
+    FlattenConfig, FlattenOrder, HeldPosition, HoldReason, MakerQuotes, MarketPositionState,
+    OrderIntent, PreMarketQuoteConfig, QuoteLevel, QuoteMarket, QuoteMarketState, QuoteParams,
+    QuotePhase, QuoteRiskLimits, QuoteSources, QuoteWindowInput, QuoteWindowPlan, QuotingEngine,
+    RestingQuote, SqliteDecisionLog, StrategyConfig, TracingDecisionLog, QUOTE_FEATURE_COLUMNS,
+    QUOTE_TICK,
+};
+pub use toggles::{
+    strategy_toggle_router, MarketToggle, MarketToggleConfig, MarketToggleError, MarketToggles,
+    StrategyEnableQuery,
+};
+#[cfg(feature = "trading")]
+pub use trading::OrderClient;
+pub use trading::{
+    CancelOutcome, ClobCredentials, LimitOrder, OpenOrder, PlacedOrder, TimeInForce, TradingClient,
+    TradingConfig, TradingError, WalletKind, DEFAULT_CLOB_URL, GTD_MIN_LEAD_SECS,
+};
+pub use views::{
+    DashboardView, DashboardViewConfig, DashboardViewError, DashboardViewSpec, DashboardViews,
+    MAX_VIEW_NAME_LEN,
+};
+pub use warmup::{
+    run_warmup, warmup_range, BinanceRestTail, KlineTailSource, WarmupConfig, WarmupError,
+    WarmupOutput, WarmupPhase, WarmupProgress, WarmupTracker,
+};
+pub use webhooks::{
+    market_events_between, sign_webhook_payload, MarketEvent, MarketEventKind, WebhookConfig,
+    WebhookEmitter, ALL_MARKET_EVENT_KINDS, WEBHOOK_EVENT_HEADER, WEBHOOK_SIGNATURE_HEADER,
+    WEBHOOK_TIMESTAMP_HEADER,
 };
-pub use slug::{build_slug, parse_coin, parse_duration, Coin, Duration, SlugConfig, SlugError};
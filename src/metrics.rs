@@ -0,0 +1,181 @@
+//! Prometheus text exposition on `/metrics`.
+//!
+//! The feature transform reports rows read, the split between SQLite read time and
+//! feature compute time, and a histogram of the latency per million kline rows, so
+//! layout or rolling-math regressions show up on a dashboard. The order-rate throttle
+//! reports admitted, queued, and rejected order operations. Values are cumulative since
+//! process start.
+
+use std::fmt::Write;
+
+use axum::http::header::CONTENT_TYPE;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+
+use crate::features::{
+    feature_transform_metrics, FeatureTransformMetrics, TRANSFORM_CHUNK_LATENCY_BUCKETS,
+};
+use crate::orders::{order_rate_metrics, OrderRateMetrics};
+
+/// Content type of the Prometheus text format.
+pub const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Renders every metric in the Prometheus text format.
+pub fn render_prometheus() -> String {
+    let mut out = render_feature_transform(&feature_transform_metrics());
+    out.push_str(&render_order_rate(&order_rate_metrics()));
+    out
+}
+
+fn render_order_rate(metrics: &OrderRateMetrics) -> String {
+    let mut out = String::new();
+    let name = "pmm_order_ops_total";
+    let _ = writeln!(
+        out,
+        "# HELP {name} Order operations admitted by the rate limiter."
+    );
+    let _ = writeln!(out, "# TYPE {name} counter");
+    let _ = writeln!(out, "{name}{{op=\"place\"}} {}", metrics.placed);
+    let _ = writeln!(out, "{name}{{op=\"cancel\"}} {}", metrics.cancelled);
+    let mut scalar = |name: &str, kind: &str, help: &str, value: String| {
+        let _ = writeln!(out, "# HELP {name} {help}");
+        let _ = writeln!(out, "# TYPE {name} {kind}");
+        let _ = writeln!(out, "{name} {value}");
+    };
+    scalar(
+        "pmm_order_ops_throttled_total",
+        "counter",
+        "Order operations that queued for a rate-limit slot.",
+        metrics.throttled.to_string(),
+    );
+    scalar(
+        "pmm_order_ops_rate_limited_total",
+        "counter",
+        "Order operations that gave up waiting for a rate-limit slot.",
+        metrics.rejected.to_string(),
+    );
+    scalar(
+        "pmm_order_ops_queued",
+        "gauge",
+        "Order operations waiting for a rate-limit slot.",
+        metrics.queued.to_string(),
+    );
+    scalar(
+        "pmm_order_rate_wait_seconds_total",
+        "counter",
+        "Time admitted order operations spent queued.",
+        metrics.wait_secs.to_string(),
+    );
+    out
+}
+
+fn render_feature_transform(metrics: &FeatureTransformMetrics) -> String {
+    let mut out = String::new();
+    let mut scalar = |name: &str, kind: &str, help: &str, value: String| {
+        let _ = writeln!(out, "# HELP {name} {help}");
+        let _ = writeln!(out, "# TYPE {name} {kind}");
+        let _ = writeln!(out, "{name} {value}");
+    };
+    scalar(
+        "pmm_feature_transform_runs_total",
+        "counter",
+        "Completed feature transforms.",
+        metrics.runs.to_string(),
+    );
+    scalar(
+        "pmm_feature_transform_rows_total",
+        "counter",
+        "Kline rows read by feature transforms.",
+        metrics.rows_read.to_string(),
+    );
+    scalar(
+        "pmm_feature_transform_read_seconds_total",
+        "counter",
+        "Time spent reading klines from the store.",
+        metrics.read_secs.to_string(),
+    );
+    scalar(
+        "pmm_feature_transform_compute_seconds_total",
+        "counter",
+        "Time spent computing rolling features.",
+        metrics.compute_secs.to_string(),
+    );
+    scalar(
+        "pmm_feature_transform_rows_per_second",
+        "gauge",
+        "Kline rows per second of the most recent transform.",
+        metrics.last_rows_per_sec.to_string(),
+    );
+
+    let name = "pmm_feature_transform_chunk_seconds";
+    let _ = writeln!(
+        out,
+        "# HELP {name} Transform latency per million kline rows."
+    );
+    let _ = writeln!(out, "# TYPE {name} histogram");
+    let mut cumulative = 0;
+    for (index, bound) in TRANSFORM_CHUNK_LATENCY_BUCKETS.iter().enumerate() {
+        cumulative += metrics
+            .chunk_latency_buckets
+            .get(index)
+            .copied()
+            .unwrap_or(0);
+        let _ = writeln!(out, "{name}_bucket{{le=\"{bound}\"}} {cumulative}");
+    }
+    let _ = writeln!(
+        out,
+        "{name}_bucket{{le=\"+Inf\"}} {}",
+        metrics.chunk_latency_count
+    );
+    let _ = writeln!(out, "{name}_sum {}", metrics.chunk_latency_sum_secs);
+    let _ = writeln!(out, "{name}_count {}", metrics.chunk_latency_count);
+    out
+}
+
+/// Router serving `GET /metrics`.
+pub fn metrics_router() -> Router {
+    Router::new().route("/metrics", get(get_metrics))
+}
+
+async fn get_metrics() -> impl IntoResponse {
+    (
+        [(CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)],
+        render_prometheus(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram_buckets_are_cumulative() {
+        let mut buckets = vec![0; TRANSFORM_CHUNK_LATENCY_BUCKETS.len() + 1];
+        buckets[0] = 1;
+        buckets[2] = 2;
+        buckets[TRANSFORM_CHUNK_LATENCY_BUCKETS.len()] = 1;
+        let text = render_feature_transform(&FeatureTransformMetrics {
+            runs: 2,
+            rows_read: 4_000_000,
+            read_secs: 3.0,
+            compute_secs: 1.5,
+            last_rows_per_sec: 1e6,
+            chunk_latency_buckets: buckets,
+            chunk_latency_sum_secs: 70.0,
+            chunk_latency_count: 4,
+        });
+        assert!(text.contains("pmm_feature_transform_rows_total 4000000\n"));
+        assert!(text.contains("pmm_feature_transform_read_seconds_total 3\n"));
+        assert!(text.contains("pmm_feature_transform_chunk_seconds_bucket{le=\"0.25\"} 1\n"));
+        assert!(text.contains("pmm_feature_transform_chunk_seconds_bucket{le=\"0.5\"} 1\n"));
+        assert!(text.contains("pmm_feature_transform_chunk_seconds_bucket{le=\"1\"} 3\n"));
+        assert!(text.contains("pmm_feature_transform_chunk_seconds_bucket{le=\"64\"} 3\n"));
+        assert!(text.contains("pmm_feature_transform_chunk_seconds_bucket{le=\"+Inf\"} 4\n"));
+        assert!(text.contains("pmm_feature_transform_chunk_seconds_count 4\n"));
+
+        // Before any transform ran, every series is still exposed at zero.
+        let empty = render_feature_transform(&FeatureTransformMetrics::default());
+        assert!(empty.contains("pmm_feature_transform_chunk_seconds_bucket{le=\"+Inf\"} 0\n"));
+    }
+}
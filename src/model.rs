@@ -0,0 +1,530 @@
+//! Trained model artifacts: loading, schema validation, and scoring.
+//!
+//! A model is trained offline on the rows of one [`FeatureSchema`] and exported as a
+//! JSON [`ModelArtifact`]: a logistic regression or a gradient-boosted tree ensemble
+//! (ONNX and other binary formats are not read; export to this format instead). The
+//! artifact records the schema's version and fingerprint, and loading refuses it
+//! unless they match the schema the caller will score with, so a model never sees
+//! columns in an order it was not trained on.
+//!
+//! Each artifact scores one coin at one or more horizons. [`load_model_registry`]
+//! registers every artifact of [`ModelConfig::dir`] in a [`ModelRegistry`] under its
+//! `(coin, horizon)` pairs; the live dashboard fills its `probability` column from it
+//! and the [`crate::QuotingEngine`] quotes around the same predictions.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::info;
+
+use crate::env_vars::Settings;
+use crate::features::{assert_schema_compatible, FeatureError, FeatureRow, FeatureSchema};
+use crate::models::{ModelRegistry, Predictor};
+use crate::slug::{Coin, Duration};
+
+pub const MODEL_ARTIFACT_VERSION: u32 = 1;
+
+/// Where model artifacts are loaded from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelConfig {
+    /// Directory of `*.json` artifacts; `None` disables model scoring.
+    pub dir: Option<PathBuf>,
+}
+
+impl Default for ModelConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl ModelConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_MODEL_DIR` (`off` disables).
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            dir: settings.get("PMM_MODEL_DIR"),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ModelError {
+    #[error("failed to read {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("invalid model artifact {path}: {source}")]
+    Parse {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    #[error("model {model_id} has artifact version {version}; expected {MODEL_ARTIFACT_VERSION}")]
+    UnsupportedVersion { model_id: String, version: u32 },
+    #[error("model {model_id} was trained on another feature schema: {source}")]
+    Schema {
+        model_id: String,
+        source: FeatureError,
+    },
+    #[error("model {model_id} is malformed: {reason}")]
+    Invalid { model_id: String, reason: String },
+    #[error("models {first} and {second} both score {coin:?} {horizon:?}")]
+    Duplicate {
+        coin: Coin,
+        horizon: Duration,
+        first: String,
+        second: String,
+    },
+}
+
+/// One node of a [`RegressionTree`]. Splits send rows whose feature is below the
+/// threshold, or not finite, to `left`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TreeNode {
+    Split {
+        feature: usize,
+        threshold: f64,
+        left: usize,
+        right: usize,
+    },
+    Leaf {
+        value: f64,
+    },
+}
+
+/// Nodes in array order, rooted at `0`; children always come after their parent.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RegressionTree {
+    pub nodes: Vec<TreeNode>,
+}
+
+impl RegressionTree {
+    fn score(&self, values: &[f64]) -> f64 {
+        let mut index = 0;
+        loop {
+            match &self.nodes[index] {
+                TreeNode::Leaf { value } => return *value,
+                TreeNode::Split {
+                    feature,
+                    threshold,
+                    left,
+                    right,
+                } => {
+                    let value = values[*feature];
+                    index = if !value.is_finite() || value < *threshold {
+                        *left
+                    } else {
+                        *right
+                    };
+                }
+            }
+        }
+    }
+
+    fn validate(&self, columns: usize) -> Result<(), String> {
+        if self.nodes.is_empty() {
+            return Err("tree has no nodes".to_string());
+        }
+        for (index, node) in self.nodes.iter().enumerate() {
+            match node {
+                TreeNode::Leaf { value } if !value.is_finite() => {
+                    return Err(format!("leaf {index} is not finite"));
+                }
+                TreeNode::Leaf { .. } => {}
+                TreeNode::Split {
+                    feature,
+                    threshold,
+                    left,
+                    right,
+                } => {
+                    if *feature >= columns {
+                        return Err(format!("node {index} splits on missing column {feature}"));
+                    }
+                    if !threshold.is_finite() {
+                        return Err(format!("node {index} threshold is not finite"));
+                    }
+                    for child in [left, right] {
+                        if *child <= index || *child >= self.nodes.len() {
+                            return Err(format!("node {index} has invalid child {child}"));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Model family and parameters. Both produce `sigmoid(margin)`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum ModelKind {
+    /// `margin = intercept + coefficients · features`, one coefficient per column.
+    Logistic {
+        intercept: f64,
+        coefficients: Vec<f64>,
+    },
+    /// `margin = base_score + learning_rate × Σ tree(features)`.
+    Gbm {
+        base_score: f64,
+        learning_rate: f64,
+        trees: Vec<RegressionTree>,
+    },
+}
+
+/// A trained model as exported by the training pipeline.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelArtifact {
+    pub format_version: u32,
+    /// Recorded with every prediction, like [`Predictor::model_id`].
+    pub model_id: String,
+    pub coin: Coin,
+    /// Market durations the model was trained for.
+    pub horizons: Vec<Duration>,
+    pub schema_version: u32,
+    pub schema_fingerprint: String,
+    pub model: ModelKind,
+}
+
+/// A [`ModelArtifact`] checked against the schema it scores.
+#[derive(Debug, Clone)]
+pub struct TrainedModel {
+    artifact: ModelArtifact,
+    columns: usize,
+}
+
+impl TrainedModel {
+    /// Validates `artifact` against `schema`: version, fingerprint, and parameters
+    /// sized to its columns.
+    pub fn new(artifact: ModelArtifact, schema: &FeatureSchema) -> Result<Self, ModelError> {
+        let invalid = |reason: String| ModelError::Invalid {
+            model_id: artifact.model_id.clone(),
+            reason,
+        };
+        if artifact.format_version != MODEL_ARTIFACT_VERSION {
+            return Err(ModelError::UnsupportedVersion {
+                model_id: artifact.model_id.clone(),
+                version: artifact.format_version,
+            });
+        }
+        assert_schema_compatible(
+            artifact.schema_version,
+            &artifact.schema_fingerprint,
+            schema,
+        )
+        .map_err(|source| ModelError::Schema {
+            model_id: artifact.model_id.clone(),
+            source,
+        })?;
+        if artifact.horizons.is_empty() {
+            return Err(invalid("no horizons".to_string()));
+        }
+        let columns = schema.columns.len();
+        match &artifact.model {
+            ModelKind::Logistic {
+                intercept,
+                coefficients,
+            } => {
+                if coefficients.len() != columns {
+                    return Err(invalid(format!(
+                        "{} coefficients for {columns} columns",
+                        coefficients.len()
+                    )));
+                }
+                if !intercept.is_finite() || coefficients.iter().any(|coef| !coef.is_finite()) {
+                    return Err(invalid("parameters are not finite".to_string()));
+                }
+            }
+            ModelKind::Gbm {
+                base_score,
+                learning_rate,
+                trees,
+            } => {
+                if !base_score.is_finite() || !learning_rate.is_finite() {
+                    return Err(invalid("parameters are not finite".to_string()));
+                }
+                for (index, tree) in trees.iter().enumerate() {
+                    tree.validate(columns)
+                        .map_err(|reason| invalid(format!("tree {index}: {reason}")))?;
+                }
+            }
+        }
+        Ok(Self { artifact, columns })
+    }
+
+    /// Reads and validates the artifact at `path`.
+    pub fn load(path: &Path, schema: &FeatureSchema) -> Result<Self, ModelError> {
+        let raw = std::fs::read(path).map_err(|source| ModelError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let artifact = serde_json::from_slice(&raw).map_err(|source| ModelError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::new(artifact, schema)
+    }
+
+    pub fn artifact(&self) -> &ModelArtifact {
+        &self.artifact
+    }
+
+    /// Probability that the coin closes the interval up, for an interval of `horizon`;
+    /// `None` for horizons the model was not trained for and rows of another width.
+    pub fn predict_up_probability(&self, features: &FeatureRow, horizon: Duration) -> Option<f64> {
+        if !self.artifact.horizons.contains(&horizon) {
+            return None;
+        }
+        self.score(features)
+    }
+
+    fn score(&self, features: &FeatureRow) -> Option<f64> {
+        if features.values.len() != self.columns {
+            return None;
+        }
+        let margin = match &self.artifact.model {
+            ModelKind::Logistic {
+                intercept,
+                coefficients,
+            } => coefficients
+                .iter()
+                .zip(&features.values)
+                .fold(*intercept, |acc, (coef, value)| acc + coef * value),
+            ModelKind::Gbm {
+                base_score,
+                learning_rate,
+                trees,
+            } => {
+                base_score
+                    + learning_rate
+                        * trees
+                            .iter()
+                            .map(|tree| tree.score(&features.values))
+                            .sum::<f64>()
+            }
+        };
+        let probability = 1.0 / (1.0 + (-margin).exp());
+        probability.is_finite().then_some(probability)
+    }
+}
+
+impl Predictor for TrainedModel {
+    fn model_id(&self) -> &str {
+        &self.artifact.model_id
+    }
+
+    fn predict(&self, row: &FeatureRow) -> Option<f64> {
+        self.score(row)
+    }
+}
+
+/// Loads every `*.json` artifact in `cfg.dir`, validated against `schema`, and
+/// registers each under its `(coin, horizon)` pairs. An unset or missing directory
+/// gives an empty registry; any invalid artifact, or two covering the same pair, fails
+/// the whole load.
+pub fn load_model_registry(
+    cfg: &ModelConfig,
+    schema: &FeatureSchema,
+) -> Result<ModelRegistry, ModelError> {
+    let mut registry = ModelRegistry::new();
+    let Some(dir) = &cfg.dir else {
+        return Ok(registry);
+    };
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(registry),
+        Err(source) => {
+            return Err(ModelError::Io {
+                path: dir.clone(),
+                source,
+            })
+        }
+    };
+    let mut paths = entries
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|source| ModelError::Io {
+            path: dir.clone(),
+            source,
+        })?;
+    paths.retain(|path| path.extension().is_some_and(|ext| ext == "json"));
+    paths.sort();
+
+    let mut owners: HashMap<(Coin, Duration), String> = HashMap::new();
+    for path in paths {
+        let model = Arc::new(TrainedModel::load(&path, schema)?);
+        let artifact = model.artifact();
+        for &horizon in &artifact.horizons {
+            if let Some(first) = owners.insert((artifact.coin, horizon), artifact.model_id.clone())
+            {
+                return Err(ModelError::Duplicate {
+                    coin: artifact.coin,
+                    horizon,
+                    first,
+                    second: artifact.model_id.clone(),
+                });
+            }
+            registry.register(artifact.coin, horizon, model.clone());
+        }
+        info!(
+            component = "model",
+            event = "model.loaded",
+            model_id = %artifact.model_id,
+            coin = artifact.coin.ticker(),
+            horizons = ?artifact.horizons.iter().map(|h| h.as_label()).collect::<Vec<_>>(),
+            path = %path.display()
+        );
+    }
+    Ok(registry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::{FeatureColumn, FeatureDType};
+
+    fn schema() -> FeatureSchema {
+        FeatureSchema {
+            version: 1,
+            fingerprint: "abc".to_string(),
+            columns: ["a", "b"]
+                .into_iter()
+                .map(|name| FeatureColumn {
+                    name: name.to_string(),
+                    dtype: FeatureDType::F64,
+                })
+                .collect(),
+        }
+    }
+
+    fn artifact(model: ModelKind) -> ModelArtifact {
+        ModelArtifact {
+            format_version: MODEL_ARTIFACT_VERSION,
+            model_id: "m".to_string(),
+            coin: Coin::BTC,
+            horizons: vec![Duration::M5],
+            schema_version: 1,
+            schema_fingerprint: "abc".to_string(),
+            model,
+        }
+    }
+
+    fn row(a: f64, b: f64) -> FeatureRow {
+        FeatureRow {
+            ts_ms_utc: 0,
+            values: vec![a, b],
+        }
+    }
+
+    #[test]
+    fn logistic_and_gbm_artifacts_score_through_a_sigmoid() {
+        let logistic = TrainedModel::new(
+            artifact(ModelKind::Logistic {
+                intercept: 0.5,
+                coefficients: vec![2.0, -1.0],
+            }),
+            &schema(),
+        )
+        .unwrap();
+        // margin 0.5 + 2 × 0.25 - 1 × 1 = 0
+        assert_eq!(
+            logistic.predict_up_probability(&row(0.25, 1.0), Duration::M5),
+            Some(0.5)
+        );
+        assert_eq!(
+            logistic.predict_up_probability(&row(0.25, 1.0), Duration::H1),
+            None
+        );
+        assert_eq!(
+            logistic.predict(&FeatureRow {
+                ts_ms_utc: 0,
+                values: vec![1.0],
+            }),
+            None
+        );
+
+        let stump = RegressionTree {
+            nodes: vec![
+                TreeNode::Split {
+                    feature: 1,
+                    threshold: 0.0,
+                    left: 1,
+                    right: 2,
+                },
+                TreeNode::Leaf { value: -1.0 },
+                TreeNode::Leaf { value: 1.0 },
+            ],
+        };
+        let gbm = TrainedModel::new(
+            artifact(ModelKind::Gbm {
+                base_score: 0.0,
+                learning_rate: 0.5,
+                trees: vec![stump.clone(), stump],
+            }),
+            &schema(),
+        )
+        .unwrap();
+        let up = gbm
+            .predict_up_probability(&row(0.0, 3.0), Duration::M5)
+            .unwrap();
+        assert!((up - 1.0 / (1.0 + (-1.0_f64).exp())).abs() < 1e-12);
+        // Missing values take the left branch.
+        let down = gbm.predict(&row(0.0, f64::NAN)).unwrap();
+        assert!((up + down - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn artifacts_must_match_the_schema_and_be_well_formed() {
+        let stale = ModelArtifact {
+            schema_fingerprint: "old".to_string(),
+            ..artifact(ModelKind::Logistic {
+                intercept: 0.0,
+                coefficients: vec![1.0, 1.0],
+            })
+        };
+        assert!(matches!(
+            TrainedModel::new(stale, &schema()),
+            Err(ModelError::Schema {
+                source: FeatureError::SchemaFingerprintMismatch { .. },
+                ..
+            })
+        ));
+        assert!(matches!(
+            TrainedModel::new(
+                artifact(ModelKind::Logistic {
+                    intercept: 0.0,
+                    coefficients: vec![1.0],
+                }),
+                &schema()
+            ),
+            Err(ModelError::Invalid { .. })
+        ));
+        let looping = RegressionTree {
+            nodes: vec![TreeNode::Split {
+                feature: 0,
+                threshold: 0.0,
+                left: 0,
+                right: 0,
+            }],
+        };
+        assert!(matches!(
+            TrainedModel::new(
+                artifact(ModelKind::Gbm {
+                    base_score: 0.0,
+                    learning_rate: 1.0,
+                    trees: vec![looping],
+                }),
+                &schema()
+            ),
+            Err(ModelError::Invalid { .. })
+        ));
+    }
+}
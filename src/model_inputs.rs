@@ -0,0 +1,485 @@
+//! Model input capture for every live prediction.
+//!
+//! Each probability the dashboard shows or the quoting loop trades on is stored with
+//! the exact feature vector it was scored on and the fingerprint of that vector's
+//! schema, keyed by `(slug, ts_utc, source)`. Values are kept as little-endian `f64`
+//! bytes, so [`ModelInputRecord::feature_row`] hands the model the same bits it saw
+//! live and a surprising output can be reproduced offline.
+//!
+//! `GET /models/inputs?slug=...&ts=...` returns the captures in effect at `ts`.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::env_vars::Settings;
+use crate::features::{FeatureRow, FeatureSchema};
+use crate::models::ModelPrediction;
+
+const F64_BYTES: usize = std::mem::size_of::<f64>();
+const PRUNE_INTERVAL_SECS: i64 = 3_600;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelInputConfig {
+    /// SQLite file holding the captures; `None` disables capture and the endpoint.
+    pub store_path: Option<PathBuf>,
+    /// Captures older than this are pruned.
+    pub retention_secs: i64,
+}
+
+impl Default for ModelInputConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl ModelInputConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_MODEL_INPUT_STORE_PATH` (`off` disables) and
+    /// `PMM_MODEL_INPUT_RETENTION_SECS`, falling back to defaults for unset or invalid
+    /// values.
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            store_path: settings.get("PMM_MODEL_INPUT_STORE_PATH"),
+            retention_secs: settings.get_where("PMM_MODEL_INPUT_RETENTION_SECS", |secs| *secs > 0),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ModelInputError {
+    #[error("sqlite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("stored feature vector of {len} bytes is not a whole number of f64s")]
+    InvalidValues { len: usize },
+}
+
+/// Where a prediction was used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelInputSource {
+    /// Shown in the dashboard's `probability` column.
+    Dashboard,
+    /// Scored by the quoting loop.
+    Quoting,
+}
+
+impl ModelInputSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Dashboard => "dashboard",
+            Self::Quoting => "quoting",
+        }
+    }
+
+    fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            "dashboard" => Some(Self::Dashboard),
+            "quoting" => Some(Self::Quoting),
+            _ => None,
+        }
+    }
+}
+
+/// One prediction and the exact inputs it was scored on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelInputRecord {
+    pub slug: String,
+    /// When the prediction was made, UTC seconds.
+    pub ts_utc: i64,
+    pub source: ModelInputSource,
+    pub model_id: String,
+    pub schema_version: u32,
+    pub schema_fingerprint: String,
+    /// [`FeatureRow::ts_ms_utc`] of the scored row.
+    pub feature_ts_ms_utc: i64,
+    pub values: Vec<f64>,
+    pub probability_yes: f64,
+}
+
+impl ModelInputRecord {
+    pub fn new(
+        slug: &str,
+        ts_utc: i64,
+        source: ModelInputSource,
+        schema: &FeatureSchema,
+        row: &FeatureRow,
+        prediction: &ModelPrediction,
+    ) -> Self {
+        Self {
+            slug: slug.to_string(),
+            ts_utc,
+            source,
+            model_id: prediction.model_id.clone(),
+            schema_version: schema.version,
+            schema_fingerprint: schema.fingerprint.clone(),
+            feature_ts_ms_utc: row.ts_ms_utc,
+            values: row.values.clone(),
+            probability_yes: prediction.probability_yes,
+        }
+    }
+
+    /// The row the model scored, bit for bit.
+    pub fn feature_row(&self) -> FeatureRow {
+        FeatureRow {
+            ts_ms_utc: self.feature_ts_ms_utc,
+            values: self.values.clone(),
+        }
+    }
+}
+
+/// Response of `/models/inputs`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelInputCaptures {
+    pub slug: String,
+    pub ts_utc: i64,
+    /// Newest capture of each source at or before `ts_utc`.
+    pub captures: Vec<ModelInputRecord>,
+}
+
+struct StoreState {
+    conn: Connection,
+    retention_secs: i64,
+    last_prune_ts_utc: i64,
+}
+
+/// SQLite model input captures; clones share the same connection.
+#[derive(Clone)]
+pub struct ModelInputStore {
+    state: Arc<Mutex<StoreState>>,
+}
+
+impl ModelInputStore {
+    /// Opens (creating if needed) the store at `path`.
+    pub fn open(path: &Path, retention_secs: i64) -> Result<Self, ModelInputError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "
+            PRAGMA journal_mode=WAL;
+            CREATE TABLE IF NOT EXISTS model_inputs (
+                slug TEXT NOT NULL,
+                ts_utc INTEGER NOT NULL,
+                source TEXT NOT NULL,
+                model_id TEXT NOT NULL,
+                schema_version INTEGER NOT NULL,
+                schema_fingerprint TEXT NOT NULL,
+                feature_ts_ms_utc INTEGER NOT NULL,
+                feature_values BLOB NOT NULL,
+                probability_yes REAL NOT NULL,
+                PRIMARY KEY(slug, ts_utc, source)
+            ) WITHOUT ROWID;
+            CREATE INDEX IF NOT EXISTS model_inputs_ts ON model_inputs(ts_utc);
+            ",
+        )?;
+        Ok(Self {
+            state: Arc::new(Mutex::new(StoreState {
+                conn,
+                retention_secs,
+                last_prune_ts_utc: i64::MIN,
+            })),
+        })
+    }
+
+    /// Opens the configured store; `None` when disabled or the store cannot be opened
+    /// (logged as `model_inputs.disabled`).
+    pub fn from_config(cfg: &ModelInputConfig) -> Option<Self> {
+        let path = cfg.store_path.as_ref()?;
+        match Self::open(path, cfg.retention_secs) {
+            Ok(store) => {
+                info!(
+                    component = "model_inputs",
+                    event = "model_inputs.opened",
+                    path = %path.display()
+                );
+                Some(store)
+            }
+            Err(err) => {
+                warn!(
+                    component = "model_inputs",
+                    event = "model_inputs.disabled",
+                    path = %path.display(),
+                    error = %err
+                );
+                None
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, StoreState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Stores `records` in one transaction; a later capture of the same slug, second,
+    /// and source replaces the earlier one. Prunes expired captures once an hour.
+    pub fn record(&self, records: &[ModelInputRecord]) -> Result<usize, ModelInputError> {
+        if records.is_empty() {
+            return Ok(0);
+        }
+        let mut state = self.lock();
+        let tx = state.conn.transaction()?;
+        {
+            let mut insert = tx.prepare_cached(
+                "INSERT OR REPLACE INTO model_inputs (
+                    slug, ts_utc, source, model_id, schema_version, schema_fingerprint,
+                    feature_ts_ms_utc, feature_values, probability_yes
+                 ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            )?;
+            for record in records {
+                insert.execute(params![
+                    record.slug,
+                    record.ts_utc,
+                    record.source.as_str(),
+                    record.model_id,
+                    record.schema_version,
+                    record.schema_fingerprint,
+                    record.feature_ts_ms_utc,
+                    encode_values(&record.values),
+                    record.probability_yes,
+                ])?;
+            }
+        }
+        tx.commit()?;
+
+        let newest = records.iter().map(|record| record.ts_utc).max();
+        if let Some(now_ts_utc) = newest {
+            if now_ts_utc.saturating_sub(state.last_prune_ts_utc) >= PRUNE_INTERVAL_SECS {
+                state.last_prune_ts_utc = now_ts_utc;
+                let cutoff = now_ts_utc - state.retention_secs;
+                state.conn.execute(
+                    "DELETE FROM model_inputs WHERE ts_utc < ?1",
+                    params![cutoff],
+                )?;
+            }
+        }
+        Ok(records.len())
+    }
+
+    /// The newest capture of each source for `slug` at or before `ts_utc`.
+    pub fn at(&self, slug: &str, ts_utc: i64) -> Result<Vec<ModelInputRecord>, ModelInputError> {
+        let state = self.lock();
+        let mut stmt = state.conn.prepare_cached(
+            "
+            SELECT slug, ts_utc, source, model_id, schema_version, schema_fingerprint,
+                   feature_ts_ms_utc, feature_values, probability_yes
+            FROM model_inputs AS m
+            WHERE slug = ?1
+              AND ts_utc = (
+                  SELECT MAX(ts_utc) FROM model_inputs
+                  WHERE slug = ?1 AND source = m.source AND ts_utc <= ?2
+              )
+            ORDER BY source
+            ",
+        )?;
+        let rows = stmt.query_map(params![slug, ts_utc], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, u32>(4)?,
+                row.get::<_, String>(5)?,
+                row.get::<_, i64>(6)?,
+                row.get::<_, Vec<u8>>(7)?,
+                row.get::<_, f64>(8)?,
+            ))
+        })?;
+        let mut records = Vec::new();
+        for row in rows {
+            let (
+                slug,
+                ts_utc,
+                source,
+                model_id,
+                schema_version,
+                schema_fingerprint,
+                feature_ts_ms_utc,
+                values,
+                probability_yes,
+            ) = row?;
+            // Rows of sources this build does not know are left out.
+            let Some(source) = ModelInputSource::parse(&source) else {
+                continue;
+            };
+            records.push(ModelInputRecord {
+                slug,
+                ts_utc,
+                source,
+                model_id,
+                schema_version,
+                schema_fingerprint,
+                feature_ts_ms_utc,
+                values: decode_values(&values)?,
+                probability_yes,
+            });
+        }
+        Ok(records)
+    }
+
+    /// Stores `records`, logging `model_inputs.error` instead of failing the caller.
+    pub fn record_or_log(&self, records: &[ModelInputRecord]) {
+        if let Err(err) = self.record(records) {
+            warn!(
+                component = "model_inputs",
+                event = "model_inputs.error",
+                records = records.len(),
+                error = %err
+            );
+        }
+    }
+}
+
+impl std::fmt::Debug for ModelInputStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ModelInputStore")
+            .field("retention_secs", &self.lock().retention_secs)
+            .finish()
+    }
+}
+
+fn encode_values(values: &[f64]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .collect()
+}
+
+fn decode_values(bytes: &[u8]) -> Result<Vec<f64>, ModelInputError> {
+    if !bytes.len().is_multiple_of(F64_BYTES) {
+        return Err(ModelInputError::InvalidValues { len: bytes.len() });
+    }
+    Ok(bytes
+        .chunks_exact(F64_BYTES)
+        .map(|chunk| f64::from_le_bytes(chunk.try_into().expect("chunk is 8 bytes")))
+        .collect())
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ModelInputQuery {
+    pub slug: String,
+    /// UTC seconds (default: now).
+    pub ts: Option<i64>,
+}
+
+/// Router serving `GET /models/inputs?slug=...&ts=...`; `404` when nothing was
+/// captured for the slug by then.
+pub fn model_input_router(store: ModelInputStore) -> Router {
+    Router::new()
+        .route("/models/inputs", get(get_model_inputs))
+        .with_state(store)
+}
+
+async fn get_model_inputs(
+    State(store): State<ModelInputStore>,
+    Query(query): Query<ModelInputQuery>,
+) -> Response {
+    let ts_utc = query
+        .ts
+        .unwrap_or_else(|| crate::faults::now_utc().timestamp());
+    let slug = query.slug.clone();
+    match tokio::task::spawn_blocking(move || store.at(&slug, ts_utc)).await {
+        Ok(Ok(captures)) if captures.is_empty() => (
+            StatusCode::NOT_FOUND,
+            format!("no model inputs captured for {} by {ts_utc}", query.slug),
+        )
+            .into_response(),
+        Ok(Ok(captures)) => Json(ModelInputCaptures {
+            slug: query.slug,
+            ts_utc,
+            captures,
+        })
+        .into_response(),
+        Ok(Err(err)) => {
+            warn!(
+                component = "model_inputs",
+                event = "model_inputs.error",
+                error = %err
+            );
+            (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response()
+        }
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(ts_utc: i64, source: ModelInputSource, values: Vec<f64>) -> ModelInputRecord {
+        ModelInputRecord {
+            slug: "btc-updown-5m-1735689600".to_string(),
+            ts_utc,
+            source,
+            model_id: "btc-short".to_string(),
+            schema_version: 1,
+            schema_fingerprint: "abc".to_string(),
+            feature_ts_ms_utc: ts_utc * 1_000 - 250,
+            values,
+            probability_yes: 0.5,
+        }
+    }
+
+    #[test]
+    fn captures_round_trip_bit_for_bit_and_resolve_by_time() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ModelInputStore::open(&dir.path().join("inputs.sqlite"), 86_400).unwrap();
+        let awkward = vec![0.1 + 0.2, -0.0, f64::MIN_POSITIVE, 1.0 / 3.0];
+        store
+            .record(&[
+                record(100, ModelInputSource::Dashboard, vec![1.0, 2.0]),
+                record(100, ModelInputSource::Quoting, awkward.clone()),
+                record(105, ModelInputSource::Dashboard, vec![3.0, 4.0]),
+            ])
+            .unwrap();
+
+        let slug = "btc-updown-5m-1735689600";
+        let at = store.at(slug, 103).unwrap();
+        assert_eq!(at.len(), 2);
+        assert_eq!(at[0].values, vec![1.0, 2.0]);
+        let bits = |values: &[f64]| values.iter().map(|v| v.to_bits()).collect::<Vec<_>>();
+        assert_eq!(bits(&at[1].values), bits(&awkward));
+        assert_eq!(at[1].feature_row().ts_ms_utc, 99_750);
+
+        let at = store.at(slug, 105).unwrap();
+        assert_eq!((at[0].ts_utc, at[1].ts_utc), (105, 100));
+        assert!(store.at(slug, 99).unwrap().is_empty());
+        assert!(store.at("eth", 105).unwrap().is_empty());
+    }
+
+    #[test]
+    fn expired_captures_are_pruned() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ModelInputStore::open(&dir.path().join("inputs.sqlite"), 60).unwrap();
+        let slug = "btc-updown-5m-1735689600";
+        store
+            .record(&[record(100, ModelInputSource::Dashboard, vec![1.0])])
+            .unwrap();
+        store
+            .record(&[record(
+                100 + PRUNE_INTERVAL_SECS,
+                ModelInputSource::Quoting,
+                vec![2.0],
+            )])
+            .unwrap();
+        assert!(store.at(slug, 100).unwrap().is_empty());
+    }
+}
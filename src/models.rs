@@ -0,0 +1,345 @@
+//! Probability models per coin and duration.
+//!
+//! A [`Predictor`] scores one [`FeatureRow`] as the probability of YES. Horizons behave
+//! differently, so the [`ModelRegistry`] maps `(coin, duration)` to its own model and
+//! falls back to a default one. A registered model may be an [`Ensemble`] of members
+//! combined by an [`EnsembleCombiner`].
+//!
+//! Every [`ModelPrediction`] carries the id of the model (or ensemble) that produced it;
+//! strategy routines copy it into [`DecisionRecord::model_id`](crate::DecisionRecord).
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::features::FeatureRow;
+use crate::slug::{Coin, Duration};
+
+pub trait Predictor: Send + Sync {
+    /// Stable id recorded with every probability the model produces.
+    fn model_id(&self) -> &str;
+
+    /// Probability of YES for `row`; `None` when the model cannot score it.
+    fn predict(&self, row: &FeatureRow) -> Option<f64>;
+}
+
+/// How an [`Ensemble`] turns member probabilities into one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum EnsembleCombiner {
+    /// Weighted mean of the member probabilities; weights are normalized.
+    WeightedAverage { weights: Vec<f64> },
+    /// Logistic stacking: `sigmoid(intercept + sum(coefficients[i] * logit(p[i])))`.
+    Stacking {
+        intercept: f64,
+        coefficients: Vec<f64>,
+    },
+}
+
+impl EnsembleCombiner {
+    fn len(&self) -> usize {
+        match self {
+            Self::WeightedAverage { weights } => weights.len(),
+            Self::Stacking { coefficients, .. } => coefficients.len(),
+        }
+    }
+
+    fn combine(&self, probabilities: &[f64]) -> f64 {
+        match self {
+            Self::WeightedAverage { weights } => {
+                let total: f64 = weights.iter().sum();
+                weights
+                    .iter()
+                    .zip(probabilities)
+                    .map(|(weight, p)| weight * p)
+                    .sum::<f64>()
+                    / total
+            }
+            Self::Stacking {
+                intercept,
+                coefficients,
+            } => {
+                let z = coefficients
+                    .iter()
+                    .zip(probabilities)
+                    .fold(*intercept, |acc, (coef, p)| acc + coef * logit(*p));
+                1.0 / (1.0 + (-z).exp())
+            }
+        }
+    }
+}
+
+/// Keeps the logit finite for members that answer exactly 0 or 1.
+const LOGIT_CLAMP: f64 = 1e-6;
+
+fn logit(p: f64) -> f64 {
+    let p = p.clamp(LOGIT_CLAMP, 1.0 - LOGIT_CLAMP);
+    (p / (1.0 - p)).ln()
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ModelRegistryError {
+    #[error("ensemble {id} has no members")]
+    EmptyEnsemble { id: String },
+    #[error("ensemble {id} has {members} members but {params} combiner parameters")]
+    CombinerMismatch {
+        id: String,
+        members: usize,
+        params: usize,
+    },
+    #[error("ensemble {id} needs finite, non-negative weights with a positive sum")]
+    InvalidWeights { id: String },
+}
+
+/// Several predictors scored on the same row and combined into one probability. Scores
+/// nothing when any member cannot score the row.
+pub struct Ensemble {
+    id: String,
+    members: Vec<Arc<dyn Predictor>>,
+    combiner: EnsembleCombiner,
+}
+
+impl Ensemble {
+    pub fn new(
+        id: impl Into<String>,
+        members: Vec<Arc<dyn Predictor>>,
+        combiner: EnsembleCombiner,
+    ) -> Result<Self, ModelRegistryError> {
+        let id = id.into();
+        if members.is_empty() {
+            return Err(ModelRegistryError::EmptyEnsemble { id });
+        }
+        if combiner.len() != members.len() {
+            return Err(ModelRegistryError::CombinerMismatch {
+                id,
+                members: members.len(),
+                params: combiner.len(),
+            });
+        }
+        if let EnsembleCombiner::WeightedAverage { weights } = &combiner {
+            let valid = weights
+                .iter()
+                .all(|weight| weight.is_finite() && *weight >= 0.0)
+                && weights.iter().sum::<f64>() > 0.0;
+            if !valid {
+                return Err(ModelRegistryError::InvalidWeights { id });
+            }
+        }
+        Ok(Self {
+            id,
+            members,
+            combiner,
+        })
+    }
+
+    /// Member ids, in combiner order.
+    pub fn member_ids(&self) -> Vec<&str> {
+        self.members
+            .iter()
+            .map(|member| member.model_id())
+            .collect()
+    }
+}
+
+impl Predictor for Ensemble {
+    fn model_id(&self) -> &str {
+        &self.id
+    }
+
+    fn predict(&self, row: &FeatureRow) -> Option<f64> {
+        let probabilities = self
+            .members
+            .iter()
+            .map(|member| member.predict(row).filter(|p| (0.0..=1.0).contains(p)))
+            .collect::<Option<Vec<_>>>()?;
+        Some(self.combiner.combine(&probabilities))
+    }
+}
+
+/// A probability and the model that produced it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrediction {
+    pub probability_yes: f64,
+    pub model_id: String,
+}
+
+/// Model per `(coin, duration)`, with an optional default for unmapped markets.
+#[derive(Default)]
+pub struct ModelRegistry {
+    default: Option<Arc<dyn Predictor>>,
+    by_market: HashMap<(Coin, Duration), Arc<dyn Predictor>>,
+}
+
+impl ModelRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Model for markets without a model of their own.
+    pub fn set_default(&mut self, model: Arc<dyn Predictor>) {
+        self.default = Some(model);
+    }
+
+    /// Maps `(coin, duration)` to `model`, replacing any previous mapping.
+    pub fn register(&mut self, coin: Coin, duration: Duration, model: Arc<dyn Predictor>) {
+        self.by_market.insert((coin, duration), model);
+    }
+
+    /// The model scoring `(coin, duration)`: its own, else the default.
+    pub fn model_for(&self, coin: Coin, duration: Duration) -> Option<&Arc<dyn Predictor>> {
+        self.by_market
+            .get(&(coin, duration))
+            .or(self.default.as_ref())
+    }
+
+    /// Scores `row` with the model for `(coin, duration)`. `None` when no model is
+    /// mapped or the model returns no probability in `[0, 1]`.
+    pub fn predict(
+        &self,
+        coin: Coin,
+        duration: Duration,
+        row: &FeatureRow,
+    ) -> Option<ModelPrediction> {
+        let model = self.model_for(coin, duration)?;
+        let probability_yes = model.predict(row).filter(|p| (0.0..=1.0).contains(p))?;
+        Some(ModelPrediction {
+            probability_yes,
+            model_id: model.model_id().to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Constant(&'static str, Option<f64>);
+
+    impl Predictor for Constant {
+        fn model_id(&self) -> &str {
+            self.0
+        }
+
+        fn predict(&self, _row: &FeatureRow) -> Option<f64> {
+            self.1
+        }
+    }
+
+    fn row() -> FeatureRow {
+        FeatureRow {
+            ts_ms_utc: 0,
+            values: vec![0.0],
+        }
+    }
+
+    #[test]
+    fn registry_prefers_the_market_model_and_falls_back_to_the_default() {
+        let mut registry = ModelRegistry::new();
+        assert_eq!(registry.predict(Coin::BTC, Duration::M5, &row()), None);
+
+        registry.set_default(Arc::new(Constant("base", Some(0.5))));
+        registry.register(
+            Coin::BTC,
+            Duration::M5,
+            Arc::new(Constant("btc-5m", Some(0.6))),
+        );
+        registry.register(
+            Coin::ETH,
+            Duration::M5,
+            Arc::new(Constant("broken", Some(1.5))),
+        );
+
+        let btc = registry.predict(Coin::BTC, Duration::M5, &row()).unwrap();
+        assert_eq!(btc.model_id, "btc-5m");
+        assert_eq!(btc.probability_yes, 0.6);
+        let fallback = registry.predict(Coin::BTC, Duration::H1, &row()).unwrap();
+        assert_eq!(fallback.model_id, "base");
+        assert_eq!(registry.predict(Coin::ETH, Duration::M5, &row()), None);
+    }
+
+    #[test]
+    fn ensembles_combine_members_and_report_their_own_id() {
+        let members: Vec<Arc<dyn Predictor>> = vec![
+            Arc::new(Constant("a", Some(0.6))),
+            Arc::new(Constant("b", Some(0.9))),
+        ];
+        let average = Ensemble::new(
+            "avg",
+            members.clone(),
+            EnsembleCombiner::WeightedAverage {
+                weights: vec![2.0, 1.0],
+            },
+        )
+        .unwrap();
+        assert_eq!(average.member_ids(), ["a", "b"]);
+        assert!((average.predict(&row()).unwrap() - 0.7).abs() < 1e-12);
+
+        // Unit coefficients on a single member give back its probability.
+        let stacked = Ensemble::new(
+            "stack",
+            members[..1].to_vec(),
+            EnsembleCombiner::Stacking {
+                intercept: 0.0,
+                coefficients: vec![1.0],
+            },
+        )
+        .unwrap();
+        assert!((stacked.predict(&row()).unwrap() - 0.6).abs() < 1e-12);
+
+        let mut registry = ModelRegistry::new();
+        registry.register(Coin::SOL, Duration::H4, Arc::new(average));
+        let prediction = registry.predict(Coin::SOL, Duration::H4, &row()).unwrap();
+        assert_eq!(prediction.model_id, "avg");
+
+        let with_gap = Ensemble::new(
+            "gap",
+            vec![members[0].clone(), Arc::new(Constant("none", None))],
+            EnsembleCombiner::WeightedAverage {
+                weights: vec![1.0, 1.0],
+            },
+        )
+        .unwrap();
+        assert_eq!(with_gap.predict(&row()), None);
+    }
+
+    #[test]
+    fn ensemble_parameters_must_match_members() {
+        let member: Arc<dyn Predictor> = Arc::new(Constant("a", Some(0.5)));
+        assert_eq!(
+            Ensemble::new(
+                "x",
+                vec![member.clone()],
+                EnsembleCombiner::WeightedAverage {
+                    weights: vec![1.0, 1.0]
+                }
+            )
+            .err(),
+            Some(ModelRegistryError::CombinerMismatch {
+                id: "x".to_string(),
+                members: 1,
+                params: 2,
+            })
+        );
+        assert!(matches!(
+            Ensemble::new(
+                "x",
+                vec![member],
+                EnsembleCombiner::WeightedAverage { weights: vec![0.0] }
+            ),
+            Err(ModelRegistryError::InvalidWeights { .. })
+        ));
+        assert!(matches!(
+            Ensemble::new(
+                "x",
+                Vec::new(),
+                EnsembleCombiner::Stacking {
+                    intercept: 0.0,
+                    coefficients: Vec::new()
+                }
+            ),
+            Err(ModelRegistryError::EmptyEnsemble { .. })
+        ));
+    }
+}
@@ -1,11 +1,34 @@
 //! Shared logging configuration and initialization.
-
-use std::env;
+//!
+//! Logs go to stdout by default. Setting `PMM_LOG_DIR` also writes them to rotating
+//! files there, so hosts without a log collector keep a bounded history.
+//!
+//! [`with_request_ids`] tags every HTTP request with an ID (the inbound `X-Request-Id`
+//! when it is usable), carried by the `http.request` span around the handler and
+//! echoed in the response, so a user-reported request can be found in the logs.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
 use std::net::SocketAddr;
-
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use axum::extract::Request;
+use axum::http::HeaderValue;
+use axum::middleware::{self, Next};
+use axum::response::Response;
+use axum::Router;
 use thiserror::Error;
-use tracing::info;
-use tracing_subscriber::EnvFilter;
+use tracing::{debug, info, info_span, Instrument, Subscriber};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{EnvFilter, Layer, Registry};
+
+use crate::env_vars::{EnvValue, Settings};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LogFormat {
@@ -18,14 +41,65 @@ pub struct LoggingConfig {
     pub level: String,
     pub format: LogFormat,
     pub include_target: bool,
+    /// Write to stdout. Only honored as `false` when [`Self::file`] is set.
+    pub stdout: bool,
+    /// Optional rotating file output, in addition to (or instead of) stdout.
+    pub file: Option<LogFileConfig>,
 }
 
 impl Default for LoggingConfig {
     fn default() -> Self {
+        logging_config(&Settings::defaults())
+    }
+}
+
+impl EnvValue for LogFormat {
+    fn parse_env(raw: &str) -> Option<Self> {
+        parse_log_format(raw)
+    }
+}
+
+/// When the active log file is rolled over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogRotation {
+    Minutely,
+    Hourly,
+    Daily,
+    Weekly,
+    /// Roll once the active file would exceed this many bytes.
+    Size(u64),
+    Never,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogFileConfig {
+    pub dir: PathBuf,
+    /// File name stem: `<prefix>.<date>.log` for time rotation, `<prefix>.log` (rolled to
+    /// `<prefix>.log.1`, `.2`, ...) for size rotation.
+    pub prefix: String,
+    pub rotation: LogRotation,
+    /// Log files kept, including the active one; older files are deleted on rotation.
+    pub max_files: usize,
+}
+
+impl LogFileConfig {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self::from_settings(dir, &Settings::defaults())
+    }
+
+    /// Output to `dir`, tuned by `PMM_LOG_FILE_PREFIX`, `PMM_LOG_ROTATION`,
+    /// `PMM_LOG_MAX_BYTES`, and `PMM_LOG_MAX_FILES`.
+    pub fn from_settings(dir: impl Into<PathBuf>, settings: &Settings) -> Self {
+        let max_bytes = settings.get_where("PMM_LOG_MAX_BYTES", |bytes| *bytes > 0);
         Self {
-            level: "info".to_string(),
-            format: LogFormat::Pretty,
-            include_target: true,
+            dir: dir.into(),
+            prefix: settings.get_where("PMM_LOG_FILE_PREFIX", |prefix: &String| {
+                !prefix.contains(['/', '\\'])
+            }),
+            rotation: settings
+                .parsed("PMM_LOG_ROTATION", |raw| parse_log_rotation(raw, max_bytes))
+                .expect("PMM_LOG_ROTATION has a default"),
+            max_files: settings.get_where("PMM_LOG_MAX_FILES", |files| *files > 0),
         }
     }
 }
@@ -34,47 +108,275 @@ impl Default for LoggingConfig {
 pub enum LoggingInitError {
     #[error("logging already initialized: {0}")]
     AlreadyInitialized(#[from] tracing::subscriber::SetGlobalDefaultError),
+    #[error("log file output in {dir}: {message}")]
+    File { dir: PathBuf, message: String },
 }
 
+/// [`logging_config`] on the process environment.
 pub fn logging_config_from_env() -> LoggingConfig {
-    let mut config = LoggingConfig::default();
+    logging_config(&Settings::from_env())
+}
 
-    if let Ok(level) = env::var("PMM_LOG_LEVEL") {
-        let trimmed = level.trim();
-        if !trimmed.is_empty() {
-            config.level = trimmed.to_string();
-        }
+/// Reads `PMM_LOG_LEVEL`, `PMM_LOG_FORMAT` (`pretty|json`), `PMM_LOG_TARGET`, and
+/// `PMM_LOG_STDOUT`. `PMM_LOG_DIR` enables file output (unset, empty, or `off` keeps
+/// stdout only), tuned as in [`LogFileConfig::from_settings`].
+pub fn logging_config(settings: &Settings) -> LoggingConfig {
+    LoggingConfig {
+        level: settings.get("PMM_LOG_LEVEL"),
+        format: settings.get("PMM_LOG_FORMAT"),
+        include_target: settings.get("PMM_LOG_TARGET"),
+        stdout: settings.get("PMM_LOG_STDOUT"),
+        file: settings
+            .get::<Option<PathBuf>>("PMM_LOG_DIR")
+            .map(|dir| LogFileConfig::from_settings(dir, settings)),
+    }
+}
+
+pub fn init_logging(config: &LoggingConfig) -> Result<(), LoggingInitError> {
+    let env_filter =
+        EnvFilter::try_new(config.level.clone()).unwrap_or_else(|_| EnvFilter::new("info"));
+
+    let mut layers = Vec::new();
+    if config.stdout || config.file.is_none() {
+        layers.push(fmt_layer(
+            config,
+            io::stdout,
+            matches!(config.format, LogFormat::Pretty),
+        ));
+    }
+    if let Some(file) = &config.file {
+        let file_err = |message: String| LoggingInitError::File {
+            dir: file.dir.clone(),
+            message,
+        };
+        fs::create_dir_all(&file.dir).map_err(|err| file_err(err.to_string()))?;
+        let layer = match file.rotation {
+            LogRotation::Size(max_bytes) => fmt_layer(
+                config,
+                SizeRotatingFile::open(
+                    file.dir.join(format!("{}.log", file.prefix)),
+                    max_bytes,
+                    file.max_files,
+                )
+                .map_err(|err| file_err(err.to_string()))?,
+                false,
+            ),
+            rotation => fmt_layer(
+                config,
+                RollingFileAppender::builder()
+                    .rotation(time_rotation(rotation))
+                    .filename_prefix(&file.prefix)
+                    .filename_suffix("log")
+                    .max_log_files(file.max_files)
+                    .build(&file.dir)
+                    .map_err(|err| file_err(err.to_string()))?,
+                false,
+            ),
+        };
+        layers.push(layer);
+    }
+
+    tracing::subscriber::set_global_default(Registry::default().with(layers).with(env_filter))?;
+    Ok(())
+}
+
+fn fmt_layer<S, W>(
+    config: &LoggingConfig,
+    writer: W,
+    ansi: bool,
+) -> Box<dyn Layer<S> + Send + Sync + 'static>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let layer = tracing_subscriber::fmt::layer()
+        .with_target(config.include_target)
+        .with_ansi(ansi)
+        .with_writer(writer);
+    match config.format {
+        LogFormat::Json => layer.json().boxed(),
+        LogFormat::Pretty => layer.pretty().boxed(),
+    }
+}
+
+fn time_rotation(rotation: LogRotation) -> Rotation {
+    match rotation {
+        LogRotation::Minutely => Rotation::MINUTELY,
+        LogRotation::Hourly => Rotation::HOURLY,
+        LogRotation::Daily => Rotation::DAILY,
+        LogRotation::Weekly => Rotation::WEEKLY,
+        LogRotation::Size(_) | LogRotation::Never => Rotation::NEVER,
+    }
+}
+
+/// Log file rolled by size: `<path>` is active, older files are `<path>.1` (newest)
+/// through `<path>.<max_files - 1>`.
+pub struct SizeRotatingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    max_files: usize,
+    state: Mutex<SizeRotatingState>,
+}
+
+struct SizeRotatingState {
+    file: File,
+    written: u64,
+}
+
+impl SizeRotatingFile {
+    pub fn open(path: impl Into<PathBuf>, max_bytes: u64, max_files: usize) -> io::Result<Self> {
+        let path = path.into();
+        let file = append(&path)?;
+        let written = file.metadata()?.len();
+        Ok(Self {
+            path,
+            max_bytes: max_bytes.max(1),
+            max_files: max_files.max(1),
+            state: Mutex::new(SizeRotatingState { file, written }),
+        })
     }
 
-    if let Ok(format) = env::var("PMM_LOG_FORMAT") {
-        if let Some(parsed) = parse_log_format(&format) {
-            config.format = parsed;
+    fn rotate(&self, state: &mut SizeRotatingState) -> io::Result<()> {
+        state.file.flush()?;
+        let rotated = |n: usize| {
+            let mut name = self.path.clone().into_os_string();
+            name.push(format!(".{n}"));
+            PathBuf::from(name)
+        };
+        if self.max_files == 1 {
+            state.file = OpenOptions::new()
+                .create(true)
+                .write(true)
+                .truncate(true)
+                .open(&self.path)?;
+        } else {
+            remove_if_exists(&rotated(self.max_files - 1))?;
+            for n in (1..self.max_files - 1).rev() {
+                rename_if_exists(&rotated(n), &rotated(n + 1))?;
+            }
+            fs::rename(&self.path, rotated(1))?;
+            state.file = append(&self.path)?;
         }
+        state.written = 0;
+        Ok(())
     }
 
-    if let Ok(include_target) = env::var("PMM_LOG_TARGET") {
-        if let Some(parsed) = parse_bool(&include_target) {
-            config.include_target = parsed;
+    fn lock(&self) -> MutexGuard<'_, SizeRotatingState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+fn remove_if_exists(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
+fn rename_if_exists(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::rename(from, to) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
+/// Writer for one formatted event; the event lands whole in one file.
+pub struct SizeRotatingWriter<'a>(&'a SizeRotatingFile);
+
+impl Write for SizeRotatingWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut state = self.0.lock();
+        if state.written > 0 && state.written + buf.len() as u64 > self.0.max_bytes {
+            self.0.rotate(&mut state)?;
         }
+        let written = state.file.write(buf)?;
+        state.written += written as u64;
+        Ok(written)
     }
 
-    config
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.lock().file.flush()
+    }
 }
 
-pub fn init_logging(config: &LoggingConfig) -> Result<(), LoggingInitError> {
-    let env_filter =
-        EnvFilter::try_new(config.level.clone()).unwrap_or_else(|_| EnvFilter::new("info"));
-    let builder = tracing_subscriber::fmt()
-        .with_env_filter(env_filter)
-        .with_target(config.include_target)
-        .with_ansi(matches!(config.format, LogFormat::Pretty));
+impl<'a> MakeWriter<'a> for SizeRotatingFile {
+    type Writer = SizeRotatingWriter<'a>;
 
-    match config.format {
-        LogFormat::Json => tracing::subscriber::set_global_default(builder.json().finish())?,
-        LogFormat::Pretty => tracing::subscriber::set_global_default(builder.pretty().finish())?,
+    fn make_writer(&'a self) -> Self::Writer {
+        SizeRotatingWriter(self)
     }
+}
 
-    Ok(())
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longest inbound request ID that is reused as-is.
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Wraps `app` so each request runs inside an `http.request` span carrying its
+/// `request_id` and the response carries the same ID in `X-Request-Id`. Apply it last,
+/// after merging every router.
+pub fn with_request_ids(app: Router) -> Router {
+    app.layer(middleware::from_fn(request_id_middleware))
+}
+
+async fn request_id_middleware(mut request: Request, next: Next) -> Response {
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| is_usable_request_id(value))
+        .map(str::to_string)
+        .unwrap_or_else(next_request_id);
+    let header = HeaderValue::from_str(&request_id).expect("request ids are visible ASCII");
+    request
+        .headers_mut()
+        .insert(REQUEST_ID_HEADER, header.clone());
+
+    let span = info_span!(
+        "http.request",
+        request_id = %request_id,
+        method = %request.method(),
+        path = %request.uri().path()
+    );
+    let started = Instant::now();
+    let mut response = next.run(request).instrument(span.clone()).await;
+    span.in_scope(|| {
+        debug!(
+            component = "dashboard_server",
+            event = "http.request.finish",
+            status = response.status().as_u16(),
+            duration_ms = started.elapsed().as_millis() as u64
+        )
+    });
+    response.headers_mut().insert(REQUEST_ID_HEADER, header);
+    response
+}
+
+fn is_usable_request_id(raw: &str) -> bool {
+    !raw.is_empty()
+        && raw.len() <= MAX_REQUEST_ID_LEN
+        && raw.bytes().all(|byte| byte.is_ascii_graphic())
+}
+
+/// `<process prefix>-<sequence>`: unique within the process, and the random-ish prefix
+/// keeps IDs from different restarts apart.
+fn next_request_id() -> String {
+    static PREFIX: OnceLock<String> = OnceLock::new();
+    static SEQ: AtomicU64 = AtomicU64::new(1);
+    let prefix = PREFIX.get_or_init(|| {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos() as u64)
+            .unwrap_or_default();
+        format!("{:08x}", (nanos ^ u64::from(std::process::id())) as u32)
+    });
+    format!("{prefix}-{:06}", SEQ.fetch_add(1, Ordering::Relaxed))
 }
 
 pub fn log_app_start(config: &LoggingConfig) {
@@ -83,7 +385,11 @@ pub fn log_app_start(config: &LoggingConfig) {
         event = "app.start",
         log_level = %config.level,
         log_format = ?config.format,
-        include_target = config.include_target
+        include_target = config.include_target,
+        log_dir = config
+            .file
+            .as_ref()
+            .map(|file| tracing::field::display(file.dir.display()))
     );
 }
 
@@ -133,10 +439,15 @@ fn parse_log_format(raw: &str) -> Option<LogFormat> {
     }
 }
 
-fn parse_bool(raw: &str) -> Option<bool> {
+/// `minutely|hourly|daily|weekly|never`, or `size` (rolls at `max_bytes`).
+fn parse_log_rotation(raw: &str, max_bytes: u64) -> Option<LogRotation> {
     match raw.trim().to_ascii_lowercase().as_str() {
-        "1" | "true" | "yes" | "on" => Some(true),
-        "0" | "false" | "no" | "off" => Some(false),
+        "minutely" => Some(LogRotation::Minutely),
+        "hourly" => Some(LogRotation::Hourly),
+        "daily" => Some(LogRotation::Daily),
+        "weekly" => Some(LogRotation::Weekly),
+        "size" => Some(LogRotation::Size(max_bytes)),
+        "never" => Some(LogRotation::Never),
         _ => None,
     }
 }
@@ -144,6 +455,7 @@ fn parse_bool(raw: &str) -> Option<bool> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::env;
     use std::sync::{Mutex, OnceLock};
 
     fn env_lock() -> &'static Mutex<()> {
@@ -184,6 +496,8 @@ mod tests {
                 ("PMM_LOG_LEVEL", None),
                 ("PMM_LOG_FORMAT", None),
                 ("PMM_LOG_TARGET", None),
+                ("PMM_LOG_STDOUT", None),
+                ("PMM_LOG_DIR", None),
             ],
             logging_config_from_env,
         );
@@ -222,4 +536,72 @@ mod tests {
         assert_eq!(cfg.format, LogFormat::Pretty);
         assert!(cfg.include_target);
     }
+
+    #[test]
+    fn parses_file_output_and_rotation_from_env() {
+        let cfg = with_env_vars(
+            &[
+                ("PMM_LOG_DIR", Some("logs")),
+                ("PMM_LOG_FILE_PREFIX", Some("dashboard")),
+                ("PMM_LOG_ROTATION", Some("size")),
+                ("PMM_LOG_MAX_BYTES", Some("1048576")),
+                ("PMM_LOG_MAX_FILES", Some("3")),
+                ("PMM_LOG_STDOUT", Some("false")),
+            ],
+            logging_config_from_env,
+        );
+        assert!(!cfg.stdout);
+        assert_eq!(
+            cfg.file,
+            Some(LogFileConfig {
+                dir: PathBuf::from("logs"),
+                prefix: "dashboard".to_string(),
+                rotation: LogRotation::Size(1_048_576),
+                max_files: 3,
+            })
+        );
+
+        let cfg = with_env_vars(
+            &[
+                ("PMM_LOG_DIR", Some("logs")),
+                ("PMM_LOG_FILE_PREFIX", Some("../escape")),
+                ("PMM_LOG_ROTATION", Some("fortnightly")),
+                ("PMM_LOG_MAX_BYTES", None),
+                ("PMM_LOG_MAX_FILES", Some("0")),
+                ("PMM_LOG_STDOUT", None),
+            ],
+            logging_config_from_env,
+        );
+        assert!(cfg.stdout);
+        assert_eq!(cfg.file, Some(LogFileConfig::new("logs")));
+
+        let cfg = with_env_vars(&[("PMM_LOG_DIR", Some("off"))], logging_config_from_env);
+        assert_eq!(cfg.file, None);
+    }
+
+    #[test]
+    fn size_rotation_keeps_whole_events_and_bounded_file_count() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pmm.log");
+        let file = SizeRotatingFile::open(&path, 20, 3).unwrap();
+        for i in 0..6 {
+            file.make_writer()
+                .write_all(format!("event-{i} 123456\n").as_bytes())
+                .unwrap();
+        }
+        file.make_writer().flush().unwrap();
+
+        let read = |name: &str| fs::read_to_string(dir.path().join(name)).unwrap();
+        assert_eq!(read("pmm.log"), "event-5 123456\n");
+        assert_eq!(read("pmm.log.1"), "event-4 123456\n");
+        assert_eq!(read("pmm.log.2"), "event-3 123456\n");
+        assert!(!dir.path().join("pmm.log.3").exists());
+
+        // Reopening appends and counts the existing size.
+        let file = SizeRotatingFile::open(&path, 40, 3).unwrap();
+        file.make_writer().write_all(b"event-6 123456\n").unwrap();
+        assert_eq!(read("pmm.log"), "event-5 123456\nevent-6 123456\n");
+        file.make_writer().write_all(b"event-7 123456\n").unwrap();
+        assert_eq!(read("pmm.log"), "event-7 123456\n");
+    }
 }
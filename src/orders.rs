@@ -0,0 +1,750 @@
+//! Order layer: the shutdown sequence and client-side order throttling.
+//!
+//! Stopping the process must not leave quotes on the book. [`cancel_all_on_shutdown`]
+//! requests a cancel for every open order of a [`TradingClient`] (the CLOB, paper
+//! fills, or the in-memory [`PaperOrderBackend`]), waits for the venue to acknowledge
+//! them (an acknowledged order no longer shows in [`TradingClient::open_orders`]) up
+//! to a timeout, and persists what it saw before the caller exits, so orders still
+//! resting after a timeout can be found and cancelled by hand.
+//!
+//! [`ThrottledTradingClient`] keeps order placements and cancels under per-market and
+//! global ops/minute limits, queueing operations until a slot frees up, so a volatile
+//! period cannot run us into the CLOB's rate limits.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::time::Instant as TokioInstant;
+use tracing::{error, info, warn};
+
+use crate::env_vars::Settings;
+use crate::trading::{
+    CancelOutcome, LimitOrder, OpenOrder, PlacedOrder, TimeInForce, TradingClient, TradingError,
+};
+
+#[derive(Debug, Error)]
+pub enum OrderError {
+    #[error(transparent)]
+    Trading(#[from] TradingError),
+    #[error("i/o error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("json error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// In-memory venue for exercising the shutdown sequence. Cancels are acknowledged
+/// `cancel_ack_delay` after they are requested; orders can be made to ignore cancels to
+/// exercise timeouts. Clones share the venue.
+#[derive(Debug, Clone, Default)]
+pub struct PaperOrderBackend {
+    cancel_ack_delay: Duration,
+    venue: Arc<Mutex<PaperVenue>>,
+}
+
+#[derive(Debug, Default)]
+struct PaperVenue {
+    orders: BTreeMap<String, OpenOrder>,
+    cancel_requested: HashMap<String, Instant>,
+    unresponsive: BTreeSet<String>,
+    placed: u64,
+}
+
+impl PaperOrderBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_cancel_ack_delay(mut self, delay: Duration) -> Self {
+        self.cancel_ack_delay = delay;
+        self
+    }
+
+    /// Rests `order`; replaces an order with the same id.
+    pub fn place(&self, order: OpenOrder) {
+        self.venue().orders.insert(order.order_id.clone(), order);
+    }
+
+    /// Cancels of `order_id` are accepted but never acknowledged.
+    pub fn ignore_cancels(&self, order_id: impl Into<String>) {
+        self.venue().unresponsive.insert(order_id.into());
+    }
+
+    fn venue(&self) -> std::sync::MutexGuard<'_, PaperVenue> {
+        self.venue
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl TradingClient for PaperOrderBackend {
+    fn venue(&self) -> &'static str {
+        "paper"
+    }
+
+    async fn place_limit_order(&self, order: &LimitOrder) -> Result<PlacedOrder, TradingError> {
+        let mut venue = self.venue();
+        venue.placed += 1;
+        let order_id = format!("paper-{}", venue.placed);
+        let open = OpenOrder {
+            order_id: order_id.clone(),
+            token_id: order.token_id.clone(),
+            market: order.token_id.clone(),
+            outcome: String::new(),
+            side: order.side,
+            price: order.price,
+            original_size: order.size,
+            size_matched: 0.0,
+            status: "LIVE".to_string(),
+            created_ts_utc: crate::faults::now_utc().timestamp(),
+            expires_ts_utc: match order.time_in_force {
+                TimeInForce::Gtd { expires_ts_utc } => Some(expires_ts_utc),
+                _ => None,
+            },
+        };
+        venue.orders.insert(order_id.clone(), open);
+        Ok(PlacedOrder {
+            order_id,
+            status: "LIVE".to_string(),
+            making_amount: 0.0,
+            taking_amount: 0.0,
+        })
+    }
+
+    /// Accepts the request; the order stays open until the cancel is acknowledged.
+    async fn cancel(&self, order_id: &str) -> Result<CancelOutcome, TradingError> {
+        let mut venue = self.venue();
+        let mut outcome = CancelOutcome::default();
+        if !venue.orders.contains_key(order_id) {
+            outcome
+                .not_cancelled
+                .insert(order_id.to_string(), "unknown order".to_string());
+            return Ok(outcome);
+        }
+        venue
+            .cancel_requested
+            .entry(order_id.to_string())
+            .or_insert_with(Instant::now);
+        outcome.cancelled.push(order_id.to_string());
+        Ok(outcome)
+    }
+
+    async fn open_orders(&self, token_id: Option<&str>) -> Result<Vec<OpenOrder>, TradingError> {
+        let delay = self.cancel_ack_delay;
+        let mut venue = self.venue();
+        let PaperVenue {
+            orders,
+            cancel_requested,
+            unresponsive,
+            ..
+        } = &mut *venue;
+        cancel_requested.retain(|order_id, requested| {
+            let acked = !unresponsive.contains(order_id) && requested.elapsed() >= delay;
+            if acked {
+                orders.remove(order_id);
+            }
+            !acked
+        });
+        Ok(orders
+            .values()
+            .filter(|order| token_id.is_none_or(|token| order.token_id == token))
+            .cloned()
+            .collect())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShutdownConfig {
+    /// How long to wait for cancel acknowledgements before giving up.
+    pub cancel_timeout_ms: u64,
+    /// Interval between `open_orders` polls while waiting.
+    pub poll_interval_ms: u64,
+    /// JSON file the final [`ShutdownReport`] is written to; `None` skips persisting.
+    pub state_path: Option<PathBuf>,
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl ShutdownConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_SHUTDOWN_CANCEL_TIMEOUT_MS`, `PMM_SHUTDOWN_POLL_MS`, and
+    /// `PMM_SHUTDOWN_STATE_PATH` (`off` disables persisting), falling back to defaults
+    /// for missing/invalid values.
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            cancel_timeout_ms: settings.get_where("PMM_SHUTDOWN_CANCEL_TIMEOUT_MS", |ms| *ms > 0),
+            poll_interval_ms: settings.get_where("PMM_SHUTDOWN_POLL_MS", |ms| *ms > 0),
+            state_path: settings.get("PMM_SHUTDOWN_STATE_PATH"),
+        }
+    }
+}
+
+/// Final order state at shutdown.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShutdownReport {
+    pub finished_ts_utc: i64,
+    /// Orders whose cancel was acknowledged.
+    pub cancelled: Vec<OpenOrder>,
+    /// Orders still resting when the sequence gave up, by order id.
+    pub still_open: Vec<OpenOrder>,
+    pub timed_out: bool,
+}
+
+impl ShutdownReport {
+    pub fn is_clean(&self) -> bool {
+        self.still_open.is_empty()
+    }
+}
+
+/// Cancels every open order of `client`, waits up to `cfg.cancel_timeout_ms` for the
+/// acknowledgements, and persists the resulting [`ShutdownReport`] to
+/// `cfg.state_path`. A cancel request that fails leaves its order in `still_open`
+/// rather than aborting the others. Errors only when the open orders cannot be read at
+/// all or the report cannot be written.
+pub async fn cancel_all_on_shutdown(
+    client: &impl TradingClient,
+    cfg: &ShutdownConfig,
+) -> Result<ShutdownReport, OrderError> {
+    let started = Instant::now();
+    let orders = client.open_orders(None).await?;
+    info!(
+        component = "orders",
+        event = "shutdown.cancel.start",
+        open_orders = orders.len()
+    );
+    for order in &orders {
+        if let Err(err) = client.cancel(&order.order_id).await {
+            warn!(
+                component = "orders",
+                event = "shutdown.cancel.error",
+                order_id = %order.order_id,
+                error = %err
+            );
+        }
+    }
+
+    let timeout = Duration::from_millis(cfg.cancel_timeout_ms);
+    let mut open = client.open_orders(None).await?;
+    while !open.is_empty() && started.elapsed() < timeout {
+        let wait = Duration::from_millis(cfg.poll_interval_ms.max(1))
+            .min(timeout.saturating_sub(started.elapsed()));
+        tokio::time::sleep(wait).await;
+        open = client.open_orders(None).await?;
+    }
+
+    let timed_out = !open.is_empty();
+    if timed_out {
+        error!(
+            component = "orders",
+            event = "shutdown.cancel.timeout",
+            still_open = open.len(),
+            order_ids = ?open.iter().map(|order| order.order_id.as_str()).collect::<Vec<_>>()
+        );
+    }
+    let report = ShutdownReport {
+        finished_ts_utc: crate::faults::now_utc().timestamp(),
+        cancelled: orders
+            .into_iter()
+            .filter(|order| !open.iter().any(|still| still.order_id == order.order_id))
+            .collect(),
+        still_open: open,
+        timed_out,
+    };
+    if let Some(path) = &cfg.state_path {
+        persist_report(path, &report)?;
+    }
+    info!(
+        component = "orders",
+        event = "shutdown.finish",
+        cancelled = report.cancelled.len(),
+        still_open = report.still_open.len(),
+        elapsed_ms = started.elapsed().as_millis() as u64
+    );
+    Ok(report)
+}
+
+/// Reads a report written by [`cancel_all_on_shutdown`].
+pub fn load_shutdown_report(path: &Path) -> Result<ShutdownReport, OrderError> {
+    Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+}
+
+/// Writes to a temporary file first so a crash mid-write keeps the previous report.
+fn persist_report(path: &Path, report: &ShutdownReport) -> Result<(), OrderError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let tmp_path = path.with_extension("json.tmp");
+    std::fs::write(&tmp_path, serde_json::to_vec_pretty(report)?)?;
+    std::fs::rename(&tmp_path, path)?;
+    info!(
+        component = "orders",
+        event = "shutdown.state.persisted",
+        path = %path.display()
+    );
+    Ok(())
+}
+
+/// Resolves on Ctrl-C or, on Unix, SIGTERM.
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(err) = tokio::signal::ctrl_c().await {
+            warn!(component = "orders", event = "shutdown.signal_error", error = %err);
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(err) => {
+                warn!(component = "orders", event = "shutdown.signal_error", error = %err);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    let signal = tokio::select! {
+        _ = ctrl_c => "ctrl_c",
+        _ = terminate => "sigterm",
+    };
+    info!(component = "orders", event = "shutdown.signal", signal);
+}
+
+/// Window the ops/minute limits count over.
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderRateLimitConfig {
+    /// Placements and cancels per rolling minute across all markets; `None` is no
+    /// limit.
+    pub global_per_min: Option<u32>,
+    /// Placements and cancels per rolling minute of one market (CLOB token); `None`
+    /// is no limit.
+    pub market_per_min: Option<u32>,
+    /// Longest an operation queues for a slot before it fails with
+    /// [`TradingError::RateLimited`] instead of going out late.
+    pub max_wait_ms: u64,
+}
+
+impl Default for OrderRateLimitConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl OrderRateLimitConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_ORDER_RATE_GLOBAL_PER_MIN` and `PMM_ORDER_RATE_MARKET_PER_MIN` (`off`
+    /// or `0` lifts the limit) and `PMM_ORDER_RATE_MAX_WAIT_MS`, falling back to
+    /// defaults for missing/invalid values.
+    pub fn from_settings(settings: &Settings) -> Self {
+        let per_min = |name: &str| settings.get::<Option<u32>>(name).filter(|limit| *limit > 0);
+        Self {
+            global_per_min: per_min("PMM_ORDER_RATE_GLOBAL_PER_MIN"),
+            market_per_min: per_min("PMM_ORDER_RATE_MARKET_PER_MIN"),
+            max_wait_ms: settings.get("PMM_ORDER_RATE_MAX_WAIT_MS"),
+        }
+    }
+}
+
+/// An order operation counted against the rate limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderOp {
+    Place,
+    Cancel,
+}
+
+impl OrderOp {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Place => "place",
+            Self::Cancel => "cancel",
+        }
+    }
+}
+
+/// Process-wide throttle counters, exposed on `/metrics`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OrderRateMetrics {
+    pub placed: u64,
+    pub cancelled: u64,
+    /// Operations that had to queue for a slot, admitted or not.
+    pub throttled: u64,
+    /// Operations that gave up after `max_wait_ms`.
+    pub rejected: u64,
+    /// Operations queueing right now.
+    pub queued: u64,
+    pub wait_secs: f64,
+}
+
+static ORDER_RATE_METRICS: Mutex<OrderRateMetrics> = Mutex::new(OrderRateMetrics {
+    placed: 0,
+    cancelled: 0,
+    throttled: 0,
+    rejected: 0,
+    queued: 0,
+    wait_secs: 0.0,
+});
+
+/// Snapshot of the process-wide throttle counters.
+pub fn order_rate_metrics() -> OrderRateMetrics {
+    lock_order_rate_metrics().clone()
+}
+
+fn lock_order_rate_metrics() -> std::sync::MutexGuard<'static, OrderRateMetrics> {
+    ORDER_RATE_METRICS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Counts one operation as queued until dropped, so an abandoned wait does not leave
+/// the gauge up.
+struct QueuedOp;
+
+impl QueuedOp {
+    fn enter() -> Self {
+        let mut metrics = lock_order_rate_metrics();
+        metrics.throttled += 1;
+        metrics.queued += 1;
+        Self
+    }
+}
+
+impl Drop for QueuedOp {
+    fn drop(&mut self) {
+        let mut metrics = lock_order_rate_metrics();
+        metrics.queued = metrics.queued.saturating_sub(1);
+    }
+}
+
+/// Rolling-window ops/minute limits, global and per market. Times come from the tokio
+/// clock, so a paused test runtime drives the windows.
+#[derive(Debug)]
+pub struct OrderRateLimiter {
+    cfg: OrderRateLimitConfig,
+    window: Duration,
+    admitted: Mutex<AdmittedOps>,
+}
+
+/// Admission times within the window.
+#[derive(Debug, Default)]
+struct AdmittedOps {
+    global: VecDeque<TokioInstant>,
+    markets: HashMap<String, VecDeque<TokioInstant>>,
+}
+
+impl OrderRateLimiter {
+    /// A limit of `Some(0)` is no limit, as `0` is in the environment.
+    pub fn new(cfg: OrderRateLimitConfig) -> Self {
+        Self {
+            cfg: OrderRateLimitConfig {
+                global_per_min: cfg.global_per_min.filter(|limit| *limit > 0),
+                market_per_min: cfg.market_per_min.filter(|limit| *limit > 0),
+                ..cfg
+            },
+            window: RATE_WINDOW,
+            admitted: Mutex::new(AdmittedOps::default()),
+        }
+    }
+
+    pub fn config(&self) -> &OrderRateLimitConfig {
+        &self.cfg
+    }
+
+    /// Waits for a slot for one `op` on `market` (`None`: counted globally only) and
+    /// takes it. Fails without taking one when no slot frees up within `max_wait_ms`;
+    /// that is logged as `orders.rate_limited`.
+    pub async fn acquire(&self, market: Option<&str>, op: OrderOp) -> Result<(), TradingError> {
+        let started = TokioInstant::now();
+        let deadline = started + Duration::from_millis(self.cfg.max_wait_ms);
+        let mut queued = None;
+        loop {
+            let Some(ready_at) = self.try_admit(market, TokioInstant::now()) else {
+                let waited = started.elapsed();
+                let mut metrics = lock_order_rate_metrics();
+                match op {
+                    OrderOp::Place => metrics.placed += 1,
+                    OrderOp::Cancel => metrics.cancelled += 1,
+                }
+                metrics.wait_secs += waited.as_secs_f64();
+                return Ok(());
+            };
+            if ready_at > deadline {
+                lock_order_rate_metrics().rejected += 1;
+                warn!(
+                    component = "orders",
+                    event = "orders.rate_limited",
+                    op = op.as_str(),
+                    market = market.unwrap_or("-"),
+                    max_wait_ms = self.cfg.max_wait_ms
+                );
+                return Err(TradingError::RateLimited(format!(
+                    "no {} slot for {} within {}ms",
+                    op.as_str(),
+                    market.unwrap_or("any market"),
+                    self.cfg.max_wait_ms
+                )));
+            }
+            if queued.is_none() {
+                queued = Some(QueuedOp::enter());
+                info!(
+                    component = "orders",
+                    event = "orders.throttled",
+                    op = op.as_str(),
+                    market = market.unwrap_or("-"),
+                    wait_ms = ready_at.saturating_duration_since(started).as_millis() as u64
+                );
+            }
+            tokio::time::sleep_until(ready_at).await;
+        }
+    }
+
+    /// Takes a slot at `now` when both windows have one; otherwise when the fuller of
+    /// them frees one.
+    fn try_admit(&self, market: Option<&str>, now: TokioInstant) -> Option<TokioInstant> {
+        let mut admitted = self
+            .admitted
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let AdmittedOps { global, markets } = &mut *admitted;
+        let window = self.window;
+        let expire = |times: &mut VecDeque<TokioInstant>| {
+            while times
+                .front()
+                .is_some_and(|at| now.duration_since(*at) >= window)
+            {
+                times.pop_front();
+            }
+        };
+        expire(global);
+        markets.retain(|_, times| {
+            expire(times);
+            !times.is_empty()
+        });
+        // When a full window frees its oldest slot.
+        let free_at = |times: &VecDeque<TokioInstant>, limit: Option<u32>| {
+            limit
+                .filter(|limit| times.len() >= *limit as usize)
+                .map(|limit| times[times.len() - limit as usize] + window)
+        };
+        let market_times = market.and_then(|market| markets.get(market));
+        let ready_at = [
+            free_at(global, self.cfg.global_per_min),
+            market_times.and_then(|times| free_at(times, self.cfg.market_per_min)),
+        ]
+        .into_iter()
+        .flatten()
+        .max();
+        if ready_at.is_some() {
+            return ready_at;
+        }
+        global.push_back(now);
+        if let Some(market) = market {
+            markets
+                .entry(market.to_string())
+                .or_default()
+                .push_back(now);
+        }
+        None
+    }
+}
+
+/// A [`TradingClient`] whose placements and cancels go through an
+/// [`OrderRateLimiter`]. Placements count against their token's market; cancels against
+/// the market the order was placed on through this client, or only globally for
+/// orders it did not place. Open-order reads are not limited.
+#[derive(Debug)]
+pub struct ThrottledTradingClient<C> {
+    inner: C,
+    limiter: OrderRateLimiter,
+    /// Token id of each order placed through this client and not known gone.
+    order_markets: Mutex<HashMap<String, String>>,
+}
+
+impl<C: TradingClient> ThrottledTradingClient<C> {
+    pub fn new(inner: C, cfg: OrderRateLimitConfig) -> Self {
+        Self {
+            inner,
+            limiter: OrderRateLimiter::new(cfg),
+            order_markets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    pub fn limiter(&self) -> &OrderRateLimiter {
+        &self.limiter
+    }
+
+    fn order_markets(&self) -> std::sync::MutexGuard<'_, HashMap<String, String>> {
+        self.order_markets
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<C: TradingClient> TradingClient for ThrottledTradingClient<C> {
+    fn venue(&self) -> &'static str {
+        self.inner.venue()
+    }
+
+    async fn place_limit_order(&self, order: &LimitOrder) -> Result<PlacedOrder, TradingError> {
+        self.limiter
+            .acquire(Some(&order.token_id), OrderOp::Place)
+            .await?;
+        let placed = self.inner.place_limit_order(order).await?;
+        self.order_markets()
+            .insert(placed.order_id.clone(), order.token_id.clone());
+        Ok(placed)
+    }
+
+    async fn cancel(&self, order_id: &str) -> Result<CancelOutcome, TradingError> {
+        let market = self.order_markets().get(order_id).cloned();
+        self.limiter
+            .acquire(market.as_deref(), OrderOp::Cancel)
+            .await?;
+        let outcome = self.inner.cancel(order_id).await?;
+        let mut order_markets = self.order_markets();
+        for gone in outcome.cancelled.iter().chain(outcome.not_cancelled.keys()) {
+            order_markets.remove(gone);
+        }
+        Ok(outcome)
+    }
+
+    async fn open_orders(&self, token_id: Option<&str>) -> Result<Vec<OpenOrder>, TradingError> {
+        let open = self.inner.open_orders(token_id).await?;
+        if token_id.is_none() {
+            self.order_markets()
+                .retain(|order_id, _| open.iter().any(|order| &order.order_id == order_id));
+        }
+        Ok(open)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fill_model::OrderSide;
+
+    fn order(order_id: &str) -> OpenOrder {
+        OpenOrder {
+            order_id: order_id.to_string(),
+            token_id: "1".to_string(),
+            market: "btc-updown-5m-1".to_string(),
+            outcome: "Up".to_string(),
+            side: OrderSide::Buy,
+            price: 0.5,
+            original_size: 10.0,
+            size_matched: 0.0,
+            status: "LIVE".to_string(),
+            created_ts_utc: 0,
+            expires_ts_utc: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn paper_backend_acknowledges_cancels_after_the_delay() {
+        let backend = PaperOrderBackend::new().with_cancel_ack_delay(Duration::from_secs(3_600));
+        backend.place(order("a"));
+        backend.place(order("b"));
+        assert_eq!(backend.cancel("a").await.unwrap().cancelled, ["a"]);
+        assert!(backend
+            .cancel("missing")
+            .await
+            .unwrap()
+            .not_cancelled
+            .contains_key("missing"));
+        // Requested but not yet acknowledged.
+        assert_eq!(backend.open_orders(None).await.unwrap().len(), 2);
+
+        let backend = PaperOrderBackend::new();
+        backend.place(order("a"));
+        backend.place(order("b"));
+        backend.ignore_cancels("b");
+        backend.cancel("a").await.unwrap();
+        backend.cancel("b").await.unwrap();
+        let open = backend.open_orders(None).await.unwrap();
+        assert_eq!(open, vec![order("b")]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn rate_limiter_queues_per_market_and_globally_then_gives_up() {
+        let limiter = OrderRateLimiter {
+            window: Duration::from_millis(300),
+            ..OrderRateLimiter::new(OrderRateLimitConfig {
+                global_per_min: Some(3),
+                market_per_min: Some(2),
+                max_wait_ms: 2_000,
+            })
+        };
+        let started = TokioInstant::now();
+        for market in ["a", "a", "b"] {
+            limiter.acquire(Some(market), OrderOp::Place).await.unwrap();
+        }
+        assert_eq!(started.elapsed(), Duration::ZERO);
+        // Market `a` and the global window are both full: the cancel queues until the
+        // first placement leaves the window.
+        limiter.acquire(Some("a"), OrderOp::Cancel).await.unwrap();
+        assert_eq!(started.elapsed(), Duration::from_millis(300));
+
+        let strict = OrderRateLimiter {
+            window: Duration::from_secs(60),
+            ..OrderRateLimiter::new(OrderRateLimitConfig {
+                global_per_min: Some(1),
+                market_per_min: None,
+                max_wait_ms: 50,
+            })
+        };
+        strict.acquire(None, OrderOp::Place).await.unwrap();
+        let rejected = order_rate_metrics().rejected;
+        let err = strict.acquire(Some("a"), OrderOp::Place).await.unwrap_err();
+        assert!(matches!(err, TradingError::RateLimited(_)));
+        assert!(order_rate_metrics().rejected > rejected);
+
+        let unlimited = OrderRateLimiter::new(OrderRateLimitConfig {
+            global_per_min: None,
+            market_per_min: None,
+            max_wait_ms: 0,
+        });
+        for _ in 0..100 {
+            unlimited.acquire(Some("a"), OrderOp::Place).await.unwrap();
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn zero_limits_are_no_limit() {
+        let limiter = OrderRateLimiter::new(OrderRateLimitConfig {
+            global_per_min: Some(0),
+            market_per_min: Some(0),
+            max_wait_ms: 0,
+        });
+        assert_eq!(limiter.config().global_per_min, None);
+        assert_eq!(limiter.config().market_per_min, None);
+        let started = TokioInstant::now();
+        for _ in 0..10 {
+            limiter.acquire(Some("a"), OrderOp::Place).await.unwrap();
+        }
+        assert_eq!(started.elapsed(), Duration::ZERO);
+    }
+}
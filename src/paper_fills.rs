@@ -0,0 +1,178 @@
+//! Seeded fill generator and ledger for paper trading.
+//!
+//! [`FillModel::simulate`] gives expected fills; a paper session needs actual ones.
+//! [`PaperFillGenerator`] draws each resting quote's fill from the model with a seeded
+//! generator, so the same seed and the same orders against the same books produce the
+//! same fills, and paper PnL tests can assert exact ledger values. The generator is
+//! SplitMix64, implemented here so sequences never change with a dependency upgrade.
+//! Every order consumes exactly one draw, taker orders included, so changing one
+//! order's style does not shift the draws of the orders after it.
+
+use serde::{Deserialize, Serialize};
+
+use crate::fees::FeeRegistry;
+use crate::fill_model::{expected_fee_usdc, BookTop, FillModel, OrderSide, SimOrder};
+
+/// One realized paper fill.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PaperFill {
+    /// Position of the order in the generator's sequence, from `0`.
+    pub seq: u64,
+    pub ts_ms: i64,
+    pub side: OrderSide,
+    pub size: f64,
+    pub price: f64,
+    pub fee_usdc: f64,
+}
+
+/// Realizes paper orders against books with a seeded generator.
+#[derive(Debug, Clone)]
+pub struct PaperFillGenerator {
+    model: FillModel,
+    fees: Option<(FeeRegistry, String)>,
+    seed: u64,
+    state: u64,
+    seq: u64,
+}
+
+impl PaperFillGenerator {
+    pub fn new(model: FillModel, seed: u64) -> Self {
+        Self {
+            model,
+            fees: None,
+            seed,
+            state: seed,
+            seq: 0,
+        }
+    }
+
+    /// Charges fills with the `fee_type` schedule of `fees` (see [`expected_fee_usdc`]).
+    pub fn with_fees(mut self, fees: FeeRegistry, fee_type: impl Into<String>) -> Self {
+        self.fees = Some((fees, fee_type.into()));
+        self
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Realizes `order` against `book`; `None` when nothing filled.
+    pub fn fill(&mut self, order: &SimOrder, book: &BookTop) -> Option<PaperFill> {
+        let seq = self.seq;
+        self.seq += 1;
+        let uniform = self.next_uniform();
+        let sampled = self.model.sample_fill(order, book, uniform);
+        let price = sampled.price.filter(|_| sampled.expected_size > 0.0)?;
+        let fee_usdc = self.fees.as_ref().map_or(0.0, |(fees, fee_type)| {
+            expected_fee_usdc(order, book, &sampled, fees, fee_type)
+        });
+        Some(PaperFill {
+            seq,
+            ts_ms: book.ts_ms,
+            side: order.side,
+            size: sampled.expected_size,
+            price,
+            fee_usdc,
+        })
+    }
+
+    /// SplitMix64, scaled to `[0, 1)` with 53 bits.
+    fn next_uniform(&mut self) -> f64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Cash and YES position of a paper account.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PaperLedger {
+    /// Net YES shares; negative is short YES.
+    pub position: f64,
+    /// USDC received minus paid, fees included.
+    pub cash_usdc: f64,
+    pub fees_usdc: f64,
+    pub fills: Vec<PaperFill>,
+}
+
+impl PaperLedger {
+    pub fn apply(&mut self, fill: &PaperFill) {
+        let notional = fill.size * fill.price;
+        match fill.side {
+            OrderSide::Buy => {
+                self.position += fill.size;
+                self.cash_usdc -= notional;
+            }
+            OrderSide::Sell => {
+                self.position -= fill.size;
+                self.cash_usdc += notional;
+            }
+        }
+        self.cash_usdc -= fill.fee_usdc;
+        self.fees_usdc += fill.fee_usdc;
+        self.fills.push(*fill);
+    }
+
+    /// PnL with the position marked at `price` (`1`/`0` once the market resolves).
+    pub fn pnl_usdc(&self, price: f64) -> f64 {
+        self.cash_usdc + self.position * price
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fill_model::{FillCalibration, FillModelConfig, OrderStyle};
+
+    #[test]
+    fn splitmix_sequence_is_pinned() {
+        // Reference values of SplitMix64 seeded with 0; paper tests depend on them.
+        let mut generator = PaperFillGenerator::new(
+            FillModel::new(FillModelConfig::default(), FillCalibration::default()),
+            0,
+        );
+        let draws = (0..3).map(|_| generator.next_uniform()).collect::<Vec<_>>();
+        let expected = [
+            0xE220_A839_7B1D_CDAFu64,
+            0x6E78_9E6A_A1B9_65F4,
+            0x06C4_5D18_8009_454F,
+        ]
+        .map(|z| (z >> 11) as f64 / (1u64 << 53) as f64);
+        assert_eq!(draws, expected);
+    }
+
+    #[test]
+    fn maker_fills_follow_the_flow_model_on_average() {
+        let model = FillModel::new(
+            FillModelConfig::default(),
+            FillCalibration {
+                observed_secs: 100.0,
+                sell_taker_volume: 50.0,
+                ..FillCalibration::default()
+            },
+        );
+        let book = BookTop {
+            ts_ms: 0,
+            best_bid: Some(0.48),
+            best_ask: Some(0.52),
+            bid_size: 20.0,
+            ask_size: 20.0,
+        };
+        let order = SimOrder {
+            side: OrderSide::Buy,
+            size: 10.0,
+            style: OrderStyle::Maker { price: 0.48 },
+        };
+        let expected = model.simulate(&order, &book);
+        let mut generator = PaperFillGenerator::new(model, 7);
+        let n = 20_000;
+        let filled: f64 = (0..n)
+            .filter_map(|_| generator.fill(&order, &book))
+            .map(|fill| fill.size)
+            .sum();
+        assert!((filled / n as f64 - expected.expected_size).abs() < 0.1);
+    }
+}
@@ -0,0 +1,843 @@
+//! Paper execution: the strategy's orders filled against live books, with no capital
+//! at risk.
+//!
+//! [`PaperOrderClient`] implements [`TradingClient`] like the live
+//! [`crate::OrderClient`], but nothing leaves the process. Orders are matched against
+//! the streamed YES book of their market (a NO order at `p` sits on the YES book at
+//! `1 - p`, on the other side):
+//! - an order goes live `latency_ms` after it is placed; if it then crosses the book, a
+//!   post-only order is rejected and any other order takes the opposite top of book (a
+//!   FOK order only when the top covers all of it)
+//! - a resting order joins its price level behind `queue_ahead_fraction` of the size
+//!   displayed there
+//! - a level that shrinks is taken as trades from the front: the size ahead goes
+//!   first, then the order fills by the excess; the opposite side moving through the
+//!   order's price once it rests fills the rest of it
+//! - cancels take effect `latency_ms` after they are requested, so fills can still land
+//!   in between; GTD orders expire at their expiry
+//!
+//! Unlike [`crate::PaperFillGenerator`], which draws fills from the fill model, this
+//! follows the book level by level. Fees are not charged. Every fill is appended to SQLite (`paper_fills`) and folded
+//! into a [`PositionBook`], so the client is also the [`PositionSource`] the quoting
+//! engine reads its inventory from.
+
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use rusqlite::{params, Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::clob_stream::{BookSubscription, OrderBook, OrderBookSource};
+use crate::env_vars::Settings;
+use crate::fill_model::OrderSide;
+use crate::positions::{MarketPosition, PositionBook, PositionFill, PositionSource};
+use crate::sizing::SizingSide;
+use crate::trading::{
+    CancelOutcome, LimitOrder, OpenOrder, PlacedOrder, TimeInForce, TradingClient, TradingError,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaperTradingConfig {
+    /// Delay before a placed order is live and before a cancel takes effect.
+    pub latency_ms: u64,
+    /// Share of the displayed level size a new resting order queues behind: `1` is the
+    /// back of the level, `0` the front.
+    pub queue_ahead_fraction: f64,
+    /// SQLite file simulated fills are appended to; `None` keeps them in memory.
+    pub store_path: Option<PathBuf>,
+}
+
+impl Default for PaperTradingConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl PaperTradingConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_PAPER_LATENCY_MS`, `PMM_PAPER_QUEUE_AHEAD` (`0..=1`), and
+    /// `PMM_PAPER_STORE_PATH` (`off` keeps fills in memory), falling back to defaults
+    /// for missing/invalid values.
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            latency_ms: settings.get("PMM_PAPER_LATENCY_MS"),
+            queue_ahead_fraction: settings.get_where("PMM_PAPER_QUEUE_AHEAD", |fraction| {
+                (0.0..=1.0).contains(fraction)
+            }),
+            store_path: settings.get("PMM_PAPER_STORE_PATH"),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum PaperTradingError {
+    #[error("sqlite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// One simulated fill, as stored in `paper_fills`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimulatedFill {
+    pub fill_id: String,
+    pub order_id: String,
+    pub slug: String,
+    pub outcome: SizingSide,
+    pub token_id: String,
+    pub side: OrderSide,
+    pub price: f64,
+    pub size: f64,
+    /// Filled on arrival against the opposite side rather than from the queue.
+    pub taker: bool,
+    pub ts_ms: i64,
+}
+
+impl SimulatedFill {
+    pub fn to_position_fill(&self) -> PositionFill {
+        PositionFill {
+            fill_id: self.fill_id.clone(),
+            token_id: self.token_id.clone(),
+            side: self.side,
+            price: self.price,
+            size: self.size,
+            fee_usdc: 0.0,
+            ts_utc: self.ts_ms.div_euclid(1_000),
+            failed: false,
+        }
+    }
+}
+
+/// Which side of the YES book an order sits on, and at which YES price.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct YesLevel {
+    bid: bool,
+    price: f64,
+}
+
+impl YesLevel {
+    fn of(outcome: SizingSide, side: OrderSide, price: f64) -> Self {
+        match (outcome, side) {
+            (SizingSide::Yes, OrderSide::Buy) => Self { bid: true, price },
+            (SizingSide::Yes, OrderSide::Sell) => Self { bid: false, price },
+            (SizingSide::No, OrderSide::Buy) => Self {
+                bid: false,
+                price: outcome_price(SizingSide::No, price),
+            },
+            (SizingSide::No, OrderSide::Sell) => Self {
+                bid: true,
+                price: outcome_price(SizingSide::No, price),
+            },
+        }
+    }
+
+    /// Size displayed at this level.
+    fn displayed(self, book: &OrderBook) -> f64 {
+        let levels = if self.bid { &book.bids } else { &book.asks };
+        levels
+            .iter()
+            .find(|level| (level.price - self.price).abs() < 1e-9)
+            .map_or(0.0, |level| level.size)
+    }
+
+    /// Opposite top of book when it trades at or through this level.
+    fn crossing_top(self, book: &OrderBook) -> Option<(f64, f64)> {
+        let top = book.top();
+        if self.bid {
+            let ask = top.best_ask.filter(|ask| *ask <= self.price + 1e-9)?;
+            Some((ask, top.ask_size))
+        } else {
+            let bid = top.best_bid.filter(|bid| *bid >= self.price - 1e-9)?;
+            Some((bid, top.bid_size))
+        }
+    }
+}
+
+/// Price of `outcome` when YES trades at `yes_price`.
+fn outcome_price(outcome: SizingSide, yes_price: f64) -> f64 {
+    match outcome {
+        SizingSide::Yes => yes_price,
+        SizingSide::No => 1.0 - yes_price,
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PaperOrder {
+    order_id: String,
+    slug: String,
+    outcome: SizingSide,
+    level: YesLevel,
+    order: LimitOrder,
+    placed_ms: i64,
+    filled: f64,
+    /// Queue state once live: size ahead and the level size last seen.
+    queue: Option<(f64, f64)>,
+    /// Opposite top crossing the order when last matched; liquidity the order already
+    /// took, or saw without filling, is not filled again.
+    crossed: Option<(f64, f64)>,
+    cancel_at_ms: Option<i64>,
+}
+
+impl PaperOrder {
+    fn remaining(&self) -> f64 {
+        (self.order.size - self.filled).max(0.0)
+    }
+}
+
+struct PaperState {
+    conn: Connection,
+    /// Outcome token -> (scheduled slug of its market, outcome).
+    outcome_by_token: HashMap<String, (String, SizingSide)>,
+    orders: BTreeMap<String, PaperOrder>,
+    next_order: u64,
+    next_fill: u64,
+}
+
+/// Paper [`TradingClient`]; clones share orders, fills, and positions.
+#[derive(Clone)]
+pub struct PaperOrderClient {
+    cfg: PaperTradingConfig,
+    books: Arc<dyn OrderBookSource>,
+    positions: PositionBook,
+    state: Arc<Mutex<PaperState>>,
+}
+
+impl PaperOrderClient {
+    /// Opens (creating if needed) the fill store of `cfg` and matches orders against
+    /// `books`.
+    pub fn open(
+        cfg: PaperTradingConfig,
+        books: Arc<dyn OrderBookSource>,
+    ) -> Result<Self, PaperTradingError> {
+        let conn = match &cfg.store_path {
+            Some(path) => {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                Connection::open(path)?
+            }
+            None => Connection::open_in_memory()?,
+        };
+        conn.execute_batch(
+            "
+            PRAGMA journal_mode=WAL;
+            CREATE TABLE IF NOT EXISTS paper_fills (
+                fill_id TEXT PRIMARY KEY,
+                order_id TEXT NOT NULL,
+                slug TEXT NOT NULL,
+                outcome TEXT NOT NULL,
+                token_id TEXT NOT NULL,
+                side TEXT NOT NULL,
+                price REAL NOT NULL,
+                size REAL NOT NULL,
+                taker INTEGER NOT NULL,
+                ts_ms INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS paper_fills_slug ON paper_fills(slug, ts_ms);
+            ",
+        )?;
+        Ok(Self {
+            cfg,
+            books,
+            positions: PositionBook::new(),
+            state: Arc::new(Mutex::new(PaperState {
+                conn,
+                outcome_by_token: HashMap::new(),
+                orders: BTreeMap::new(),
+                next_order: 0,
+                next_fill: 0,
+            })),
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, PaperState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Maps the outcome tokens of `markets` to their slugs, so orders find their book.
+    pub fn track_markets(&self, markets: &[BookSubscription]) {
+        self.positions.track_markets(markets);
+        let mut state = self.lock();
+        for market in markets {
+            let tokens = std::iter::once((&market.yes_token_id, SizingSide::Yes)).chain(
+                market
+                    .no_token_id
+                    .iter()
+                    .map(|token| (token, SizingSide::No)),
+            );
+            for (token, outcome) in tokens {
+                state
+                    .outcome_by_token
+                    .insert(token.clone(), (market.key.slug.clone(), outcome));
+            }
+        }
+    }
+
+    /// Accepts `order` at `now_ms`; it goes live `latency_ms` later.
+    pub fn place_at(&self, order: &LimitOrder, now_ms: i64) -> Result<PlacedOrder, TradingError> {
+        order.validate(now_ms.div_euclid(1_000))?;
+        let mut state = self.lock();
+        let Some((slug, outcome)) = state.outcome_by_token.get(&order.token_id).cloned() else {
+            return Err(TradingError::InvalidOrder(format!(
+                "token id {} is not a tracked market",
+                order.token_id
+            )));
+        };
+        state.next_order += 1;
+        let order_id = format!("paper-{}", state.next_order);
+        state.orders.insert(
+            order_id.clone(),
+            PaperOrder {
+                order_id: order_id.clone(),
+                slug,
+                outcome,
+                level: YesLevel::of(outcome, order.side, order.price),
+                order: order.clone(),
+                placed_ms: now_ms,
+                filled: 0.0,
+                queue: None,
+                crossed: None,
+                cancel_at_ms: None,
+            },
+        );
+        Ok(PlacedOrder {
+            order_id,
+            status: if self.cfg.latency_ms > 0 {
+                "DELAYED"
+            } else {
+                "LIVE"
+            }
+            .to_string(),
+            making_amount: 0.0,
+            taking_amount: 0.0,
+        })
+    }
+
+    /// Requests a cancel of `order_id` at `now_ms`; it takes effect `latency_ms` later.
+    pub fn cancel_at(&self, order_id: &str, now_ms: i64) -> CancelOutcome {
+        let mut outcome = CancelOutcome::default();
+        match self.lock().orders.get_mut(order_id) {
+            Some(order) => {
+                order
+                    .cancel_at_ms
+                    .get_or_insert(now_ms + self.cfg.latency_ms as i64);
+                outcome.cancelled.push(order_id.to_string());
+            }
+            None => {
+                outcome
+                    .not_cancelled
+                    .insert(order_id.to_string(), "order not found".to_string());
+            }
+        }
+        outcome
+    }
+
+    /// Orders not yet filled, cancelled, or expired.
+    pub fn open_orders_at(&self, token_id: Option<&str>) -> Vec<OpenOrder> {
+        self.lock()
+            .orders
+            .values()
+            .filter(|order| token_id.is_none_or(|token| order.order.token_id == token))
+            .map(|order| OpenOrder {
+                order_id: order.order_id.clone(),
+                token_id: order.order.token_id.clone(),
+                market: order.slug.clone(),
+                outcome: order.outcome.as_str().to_string(),
+                side: order.order.side,
+                price: order.order.price,
+                original_size: order.order.size,
+                size_matched: order.filled,
+                status: if order.queue.is_some() {
+                    "LIVE"
+                } else {
+                    "DELAYED"
+                }
+                .to_string(),
+                created_ts_utc: order.placed_ms.div_euclid(1_000),
+                expires_ts_utc: match order.order.time_in_force {
+                    TimeInForce::Gtd { expires_ts_utc } => Some(expires_ts_utc),
+                    _ => None,
+                },
+            })
+            .collect()
+    }
+
+    /// Advances every order to `now_ms` against the current books and returns the new
+    /// fills, which are stored and applied to positions.
+    pub fn match_books(&self, now_ms: i64) -> Result<Vec<SimulatedFill>, PaperTradingError> {
+        let mut state = self.lock();
+        let latency_ms = self.cfg.latency_ms as i64;
+        let mut fills = Vec::new();
+        let mut done = Vec::new();
+        let mut books: HashMap<String, Option<OrderBook>> = HashMap::new();
+        for order in state.orders.values_mut() {
+            if now_ms < order.placed_ms + latency_ms {
+                continue;
+            }
+            let book = books
+                .entry(order.slug.clone())
+                .or_insert_with(|| self.books.book(&order.slug));
+            let Some(book) = book else {
+                continue;
+            };
+            for (yes_price, size, taker) in self.advance(order, book) {
+                order.filled += size;
+                let price = outcome_price(order.outcome, yes_price);
+                fills.push((order.clone(), price, size, taker));
+            }
+            let expired = matches!(
+                order.order.time_in_force,
+                TimeInForce::Gtd { expires_ts_utc } if now_ms.div_euclid(1_000) >= expires_ts_utc
+            );
+            let cancelled = order.cancel_at_ms.is_some_and(|at| now_ms >= at);
+            if order.queue.is_none() || order.remaining() <= 1e-9 || expired || cancelled {
+                done.push(order.order_id.clone());
+            }
+        }
+        for order_id in done {
+            state.orders.remove(&order_id);
+        }
+
+        let mut simulated = Vec::with_capacity(fills.len());
+        for (order, price, size, taker) in fills {
+            state.next_fill += 1;
+            simulated.push(SimulatedFill {
+                fill_id: format!("{}-{}", order.order_id, state.next_fill),
+                order_id: order.order_id,
+                slug: order.slug,
+                outcome: order.outcome,
+                token_id: order.order.token_id,
+                side: order.order.side,
+                price: (price * 1e6).round() / 1e6,
+                size,
+                taker,
+                ts_ms: now_ms,
+            });
+        }
+        if simulated.is_empty() {
+            return Ok(simulated);
+        }
+        let tx = state.conn.transaction()?;
+        {
+            let mut insert = tx.prepare_cached(
+                "INSERT OR REPLACE INTO paper_fills
+                 (fill_id, order_id, slug, outcome, token_id, side, price, size, taker, ts_ms)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            )?;
+            for fill in &simulated {
+                insert.execute(params![
+                    fill.fill_id,
+                    fill.order_id,
+                    fill.slug,
+                    fill.outcome.as_str(),
+                    fill.token_id,
+                    side_label(fill.side),
+                    fill.price,
+                    fill.size,
+                    fill.taker,
+                    fill.ts_ms
+                ])?;
+            }
+        }
+        tx.commit()?;
+        drop(state);
+        for fill in &simulated {
+            self.positions.record_fill(fill.to_position_fill());
+            info!(
+                component = "paper",
+                event = "paper.fill",
+                order_id = %fill.order_id,
+                slug = %fill.slug,
+                outcome = fill.outcome.as_str(),
+                price = fill.price,
+                size = fill.size,
+                taker = fill.taker
+            );
+        }
+        Ok(simulated)
+    }
+
+    /// Fills of one order step as `(yes_price, size, taker)`. Leaves `order.queue`
+    /// unset when the order never came to rest (rejected or unfilled FOK).
+    fn advance(&self, order: &mut PaperOrder, book: &OrderBook) -> Vec<(f64, f64, bool)> {
+        let level = order.level;
+        let crossed = level.crossing_top(book);
+        let seen_cross = std::mem::replace(&mut order.crossed, crossed);
+        let Some((ahead, last_seen)) = order.queue else {
+            // Arrival: cross, or join the queue.
+            let mut fills = Vec::new();
+            if let Some((top_price, top_size)) = crossed {
+                if order.order.post_only {
+                    warn!(
+                        component = "paper",
+                        event = "paper.order.rejected",
+                        order_id = %order.order_id,
+                        reason = "post-only order would cross"
+                    );
+                    return fills;
+                }
+                let fok = order.order.time_in_force == TimeInForce::Fok;
+                if fok && top_size + 1e-9 < order.remaining() {
+                    return fills;
+                }
+                let size = order.remaining().min(top_size);
+                fills.push((top_price, size, true));
+                if fok || size >= order.remaining() - 1e-9 {
+                    order.queue = Some((0.0, 0.0));
+                    return fills;
+                }
+            }
+            let displayed = level.displayed(book);
+            order.queue = Some((displayed * self.cfg.queue_ahead_fraction, displayed));
+            return fills;
+        };
+
+        let remaining = order.remaining();
+        if crossed.is_some() && crossed != seen_cross {
+            order.queue = Some((0.0, level.displayed(book)));
+            return vec![(level.price, remaining, false)];
+        }
+        let displayed = level.displayed(book);
+        let shrink = (last_seen - displayed).max(0.0);
+        let consumed_ahead = shrink.min(ahead);
+        let size = (shrink - consumed_ahead).min(remaining);
+        order.queue = Some((ahead - consumed_ahead, displayed));
+        if size > 1e-9 {
+            vec![(level.price, size, false)]
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Ledger the simulated fills are folded into.
+    pub fn positions(&self) -> &PositionBook {
+        &self.positions
+    }
+
+    /// Stored fills of `slug`, oldest first.
+    pub fn fills(&self, slug: &str) -> Result<Vec<SimulatedFill>, PaperTradingError> {
+        let state = self.lock();
+        let mut stmt = state.conn.prepare_cached(
+            "SELECT fill_id, order_id, slug, outcome, token_id, side, price, size, taker, ts_ms
+             FROM paper_fills WHERE slug = ?1 ORDER BY ts_ms, fill_id",
+        )?;
+        let rows = stmt.query_map(params![slug], fill_from_row)?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Path of the fill store; `None` when fills are kept in memory.
+    pub fn store_path(&self) -> Option<&Path> {
+        self.cfg.store_path.as_deref()
+    }
+}
+
+/// Fills stored at `path` with `from_ms <= ts_ms <= to_ms`, oldest first, read
+/// without opening a [`PaperOrderClient`]. An absent store has no fills.
+pub fn read_paper_fills(
+    path: &Path,
+    from_ms: i64,
+    to_ms: i64,
+) -> Result<Vec<SimulatedFill>, PaperTradingError> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let mut stmt = conn.prepare(
+        "SELECT fill_id, order_id, slug, outcome, token_id, side, price, size, taker, ts_ms
+         FROM paper_fills WHERE ts_ms BETWEEN ?1 AND ?2 ORDER BY ts_ms, fill_id",
+    )?;
+    let rows = stmt.query_map(params![from_ms, to_ms], fill_from_row)?;
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
+fn fill_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<SimulatedFill> {
+    let outcome: String = row.get(3)?;
+    let side: String = row.get(5)?;
+    Ok(SimulatedFill {
+        fill_id: row.get(0)?,
+        order_id: row.get(1)?,
+        slug: row.get(2)?,
+        outcome: if outcome == "NO" {
+            SizingSide::No
+        } else {
+            SizingSide::Yes
+        },
+        token_id: row.get(4)?,
+        side: if side == "sell" {
+            OrderSide::Sell
+        } else {
+            OrderSide::Buy
+        },
+        price: row.get(6)?,
+        size: row.get(7)?,
+        taker: row.get(8)?,
+        ts_ms: row.get(9)?,
+    })
+}
+
+fn side_label(side: OrderSide) -> &'static str {
+    match side {
+        OrderSide::Buy => "buy",
+        OrderSide::Sell => "sell",
+    }
+}
+
+fn now_ms() -> i64 {
+    crate::faults::now_utc().timestamp_millis()
+}
+
+impl PositionSource for PaperOrderClient {
+    fn position(&self, slug: &str) -> Option<MarketPosition> {
+        self.positions.position(slug)
+    }
+}
+
+impl TradingClient for PaperOrderClient {
+    fn venue(&self) -> &'static str {
+        "paper"
+    }
+
+    fn place_limit_order(
+        &self,
+        order: &LimitOrder,
+    ) -> impl Future<Output = Result<PlacedOrder, TradingError>> + Send {
+        let now_ms = now_ms();
+        let placed = self.match_books(now_ms).map_err(paper_err).and_then(|_| {
+            let placed = self.place_at(order, now_ms)?;
+            self.match_books(now_ms).map_err(paper_err)?;
+            Ok(placed)
+        });
+        std::future::ready(placed)
+    }
+
+    fn cancel(
+        &self,
+        order_id: &str,
+    ) -> impl Future<Output = Result<CancelOutcome, TradingError>> + Send {
+        let now_ms = now_ms();
+        let outcome = self
+            .match_books(now_ms)
+            .map_err(paper_err)
+            .map(|_| self.cancel_at(order_id, now_ms));
+        std::future::ready(outcome)
+    }
+
+    fn open_orders(
+        &self,
+        token_id: Option<&str>,
+    ) -> impl Future<Output = Result<Vec<OpenOrder>, TradingError>> + Send {
+        let orders = self
+            .match_books(now_ms())
+            .map_err(paper_err)
+            .map(|_| self.open_orders_at(token_id));
+        std::future::ready(orders)
+    }
+}
+
+fn paper_err(err: PaperTradingError) -> TradingError {
+    TradingError::Clob(format!("paper venue: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clob_stream::BookLevel;
+    use crate::discovery::DiscoveryKey;
+    use crate::slug::{Coin, Duration, SlugConfig};
+
+    const NOW_MS: i64 = 1_771_449_000_000;
+
+    #[derive(Default)]
+    struct TestBooks(Mutex<Option<OrderBook>>);
+
+    impl TestBooks {
+        fn set(&self, bids: &[(f64, f64)], asks: &[(f64, f64)]) {
+            let levels = |levels: &[(f64, f64)]| {
+                levels
+                    .iter()
+                    .map(|&(price, size)| BookLevel { price, size })
+                    .collect()
+            };
+            *self.0.lock().unwrap() = Some(OrderBook {
+                bids: levels(bids),
+                asks: levels(asks),
+                updated_ms_utc: NOW_MS,
+            });
+        }
+    }
+
+    impl OrderBookSource for TestBooks {
+        fn book(&self, _slug: &str) -> Option<OrderBook> {
+            self.0.lock().unwrap().clone()
+        }
+    }
+
+    fn paper_client(cfg: PaperTradingConfig) -> (PaperOrderClient, Arc<TestBooks>, String) {
+        let key = DiscoveryKey::new(
+            Coin::BTC,
+            Duration::M5,
+            NOW_MS / 1_000,
+            SlugConfig::default(),
+        )
+        .unwrap();
+        let books = Arc::new(TestBooks::default());
+        let client = PaperOrderClient::open(cfg, books.clone()).unwrap();
+        client.track_markets(&[BookSubscription {
+            key: key.clone(),
+            market_slug: None,
+            yes_token_id: "101".to_string(),
+            no_token_id: Some("102".to_string()),
+            neg_risk: false,
+            neg_risk_group: None,
+        }]);
+        (client, books, key.slug)
+    }
+
+    fn in_memory(latency_ms: u64, queue_ahead_fraction: f64) -> PaperTradingConfig {
+        PaperTradingConfig {
+            latency_ms,
+            queue_ahead_fraction,
+            store_path: None,
+        }
+    }
+
+    fn bid(token: &str, price: f64, size: f64) -> LimitOrder {
+        LimitOrder::new(token, OrderSide::Buy, price, size, TimeInForce::Gtc)
+    }
+
+    #[test]
+    fn resting_order_goes_live_after_latency_and_fills_behind_its_queue() {
+        let (client, books, slug) = paper_client(in_memory(200, 0.5));
+        books.set(&[(0.48, 30.0)], &[(0.52, 40.0)]);
+        let placed = client.place_at(&bid("101", 0.48, 10.0), NOW_MS).unwrap();
+        assert_eq!(placed.order_id, "paper-1");
+
+        // In flight: the book is not looked at until the order arrives.
+        assert!(client.match_books(NOW_MS + 100).unwrap().is_empty());
+        assert_eq!(client.open_orders_at(None)[0].status, "DELAYED");
+        assert!(client.match_books(NOW_MS + 200).unwrap().is_empty());
+        assert_eq!(client.open_orders_at(None)[0].status, "LIVE");
+
+        // Queued behind 15 of 30 shares: 20 trading clears the queue and fills 5.
+        books.set(&[(0.48, 10.0)], &[(0.52, 40.0)]);
+        let fills = client.match_books(NOW_MS + 300).unwrap();
+        assert_eq!(fills.len(), 1);
+        assert!((fills[0].size - 5.0).abs() < 1e-9);
+        assert!(!fills[0].taker);
+
+        // The ask dropping through the bid fills the rest at the order's price.
+        books.set(&[(0.46, 10.0)], &[(0.47, 40.0)]);
+        let fills = client.match_books(NOW_MS + 400).unwrap();
+        assert_eq!(fills.len(), 1);
+        assert!((fills[0].size - 5.0).abs() < 1e-9);
+        assert!((fills[0].price - 0.48).abs() < 1e-9);
+        assert!(client.open_orders_at(None).is_empty());
+
+        let position = client.position(&slug).unwrap();
+        assert!((position.net_shares() - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn crossing_orders_take_the_top_unless_post_only() {
+        let (client, books, _) = paper_client(in_memory(0, 1.0));
+        books.set(&[(0.55, 20.0)], &[(0.58, 4.0)]);
+
+        client
+            .place_at(&bid("101", 0.60, 10.0).post_only(), NOW_MS)
+            .unwrap();
+        assert!(client.match_books(NOW_MS).unwrap().is_empty());
+        assert!(client.open_orders_at(None).is_empty());
+
+        // Takes the 4 shares at the ask; the other 6 rest at the limit.
+        client.place_at(&bid("101", 0.60, 10.0), NOW_MS).unwrap();
+        let fills = client.match_books(NOW_MS).unwrap();
+        assert_eq!(fills.len(), 1);
+        assert!(fills[0].taker);
+        assert!((fills[0].price - 0.58).abs() < 1e-9);
+        assert!((fills[0].size - 4.0).abs() < 1e-9);
+        assert!((client.open_orders_at(None)[0].remaining() - 6.0).abs() < 1e-9);
+
+        // A NO bid at 0.46 is a YES offer at 0.54, hit by the 0.55 YES bid.
+        client.place_at(&bid("102", 0.46, 5.0), NOW_MS).unwrap();
+        let fills = client.match_books(NOW_MS).unwrap();
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].outcome, SizingSide::No);
+        assert!((fills[0].price - 0.45).abs() < 1e-9);
+
+        // FOK larger than the top is killed without a fill.
+        let fok = LimitOrder::new("101", OrderSide::Buy, 0.60, 50.0, TimeInForce::Fok);
+        client.place_at(&fok, NOW_MS).unwrap();
+        assert!(client.match_books(NOW_MS).unwrap().is_empty());
+        assert_eq!(client.open_orders_at(None).len(), 1);
+    }
+
+    #[test]
+    fn cancels_land_after_latency_and_fills_persist() {
+        let dir = tempfile::tempdir().unwrap();
+        let cfg = PaperTradingConfig {
+            latency_ms: 100,
+            queue_ahead_fraction: 0.0,
+            store_path: Some(dir.path().join("paper.sqlite")),
+        };
+        let (client, books, slug) = paper_client(cfg.clone());
+        books.set(&[(0.40, 10.0)], &[(0.45, 10.0)]);
+        let placed = client.place_at(&bid("101", 0.40, 8.0), NOW_MS).unwrap();
+        client.match_books(NOW_MS + 100).unwrap();
+
+        let cancel = client.cancel_at(&placed.order_id, NOW_MS + 150);
+        assert_eq!(cancel.cancelled, vec![placed.order_id.clone()]);
+        assert!(client
+            .cancel_at("paper-99", NOW_MS + 150)
+            .not_cancelled
+            .contains_key("paper-99"));
+
+        // Trades before the cancel lands still fill the order.
+        books.set(&[(0.40, 7.0)], &[(0.45, 10.0)]);
+        assert_eq!(client.match_books(NOW_MS + 200).unwrap().len(), 1);
+        assert_eq!(client.open_orders_at(Some("101")).len(), 1);
+        assert!(client.match_books(NOW_MS + 250).unwrap().is_empty());
+        assert!(client.open_orders_at(None).is_empty());
+        books.set(&[(0.40, 1.0)], &[(0.45, 10.0)]);
+        assert!(client.match_books(NOW_MS + 300).unwrap().is_empty());
+
+        let stored = client.fills(&slug).unwrap();
+        drop(client);
+        let (reopened, _, _) = paper_client(cfg);
+        assert_eq!(reopened.fills(&slug).unwrap(), stored);
+        assert!((stored[0].size - 3.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn shutdown_cancels_paper_orders_through_the_throttled_client() {
+        use crate::orders::{
+            cancel_all_on_shutdown, OrderRateLimitConfig, ShutdownConfig, ThrottledTradingClient,
+        };
+
+        let (client, books, _) = paper_client(in_memory(0, 0.5));
+        books.set(&[(0.40, 10.0)], &[(0.45, 10.0)]);
+        let client = ThrottledTradingClient::new(client, OrderRateLimitConfig::default());
+        let placed = client
+            .place_limit_order(&bid("101", 0.40, 5.0))
+            .await
+            .unwrap();
+
+        let cfg = ShutdownConfig {
+            state_path: None,
+            ..ShutdownConfig::default()
+        };
+        let report = cancel_all_on_shutdown(&client, &cfg).await.unwrap();
+        assert!(report.is_clean());
+        assert_eq!(report.cancelled[0].order_id, placed.order_id);
+        assert!(client.open_orders(None).await.unwrap().is_empty());
+    }
+}
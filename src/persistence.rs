@@ -0,0 +1,325 @@
+//! Storage backend selection.
+//!
+//! The kline store and alert state are reached through [`KlineBackend`] and
+//! [`AlertBackend`]. SQLite (one file per store) is the default and keeps everything on
+//! one host. Setting `PMM_DATABASE_URL` to a `postgres://` URL points every process at
+//! one shared Postgres database instead (requires the `postgres` feature), so the
+//! dashboard, strategy, and kline sync can run on separate machines.
+//!
+//! Everything else stays in per-host SQLite files under either backend
+//! ([`SQLITE_ONLY_STORES`]), and the commands that read the kline store directly (the
+//! feature transform behind `pmm drift`, `pmm sync`, `pmm attribution`) refuse to
+//! fall back to a local file while Postgres is selected.
+
+use std::path::Path;
+
+use tracing::warn;
+
+use crate::alerts::{AlertDecision, AlertRecord, AlertSeverity, AlertStore, AlertStoreError};
+use crate::binance_klines::{BinanceSymbol, Kline1s};
+use crate::env_vars::Settings;
+use crate::kline_store::{KlineStore, KlineStoreError};
+use crate::kline_validation::{
+    KlineHalt, KlineValidationConfig, KlineViolationCounts, MicroHaltConfig,
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum PersistenceBackend {
+    /// Per-store SQLite files at the configured paths.
+    #[default]
+    Sqlite,
+    /// One shared Postgres database; store paths are ignored.
+    Postgres { url: String },
+}
+
+impl PersistenceBackend {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_DATABASE_URL`: a `postgres://`/`postgresql://` URL selects Postgres,
+    /// anything else (or unset) keeps SQLite.
+    pub fn from_settings(settings: &Settings) -> Self {
+        settings
+            .parsed("PMM_DATABASE_URL", |raw| {
+                is_postgres_url(raw).then(|| Self::Postgres {
+                    url: raw.to_string(),
+                })
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Sqlite => "sqlite",
+            Self::Postgres { .. } => "postgres",
+        }
+    }
+
+    /// Errors with Postgres selected: `command` reads the SQLite kline store directly,
+    /// so it would miss the shared klines.
+    pub fn require_sqlite_klines(&self, command: &str) -> Result<(), KlineStoreError> {
+        match self {
+            Self::Sqlite => Ok(()),
+            Self::Postgres { .. } => Err(KlineStoreError::BackendUnavailable(format!(
+                "{command} reads a SQLite kline store, but PMM_DATABASE_URL selects Postgres; \
+                 pass --store with a SQLite copy"
+            ))),
+        }
+    }
+
+    /// Logs `persistence.sqlite_only` with Postgres selected, naming the stores that
+    /// still live on this host.
+    pub fn log_sqlite_only_stores(&self) {
+        if let Self::Postgres { .. } = self {
+            warn!(
+                component = "persistence",
+                event = "persistence.sqlite_only",
+                stores = SQLITE_ONLY_STORES.join(",")
+            );
+        }
+    }
+}
+
+/// Stores kept in per-host SQLite files under either backend, by the variable naming
+/// each file. With Postgres they are not shared between hosts.
+pub const SQLITE_ONLY_STORES: [&str; 10] = [
+    "PMM_SNAPSHOT_STORE_PATH",
+    "PMM_PAPER_STORE_PATH",
+    "PMM_DECISION_STORE_PATH",
+    "PMM_MODEL_INPUT_STORE_PATH",
+    "PMM_QUALITY_STORE_PATH",
+    "PMM_JOURNAL_STORE_PATH",
+    "PMM_DASHBOARD_VIEW_STORE_PATH",
+    "PMM_STRATEGY_TOGGLE_STORE_PATH",
+    "PMM_GAMMA_PAYLOAD_STORE_PATH",
+    "PMM_REWARD_ACCRUAL_STORE_PATH",
+];
+
+fn is_postgres_url(raw: &str) -> bool {
+    raw.starts_with("postgres://") || raw.starts_with("postgresql://")
+}
+
+/// Kline storage shared by the sync binary (writer) and kline-derived dashboard columns
+/// (readers).
+pub trait KlineBackend: Send {
+    /// Validates `rows`, upserts the accepted ones, and quarantines the rest when the
+    /// policy asks for it.
+    fn upsert_rows(
+        &mut self,
+        symbol: BinanceSymbol,
+        rows: Vec<Kline1s>,
+    ) -> Result<KlineViolationCounts, KlineStoreError>;
+
+    /// Stored rows for `symbol` in `[start_ts_ms, end_ts_ms_exclusive)`.
+    fn count_range(
+        &mut self,
+        symbol: BinanceSymbol,
+        start_ts_ms: i64,
+        end_ts_ms_exclusive: i64,
+    ) -> Result<u64, KlineStoreError>;
+
+    /// `(open_time_ms, close)` in `[start_ts_ms, end_ts_ms_exclusive)`, oldest first.
+    fn closes_range(
+        &mut self,
+        symbol: BinanceSymbol,
+        start_ts_ms: i64,
+        end_ts_ms_exclusive: i64,
+    ) -> Result<Vec<(i64, f64)>, KlineStoreError>;
+
+    /// `(open_time_ms, open)` of the first stored kline in
+    /// `[start_ts_ms, end_ts_ms_exclusive)`.
+    fn first_open_in_range(
+        &mut self,
+        symbol: BinanceSymbol,
+        start_ts_ms: i64,
+        end_ts_ms_exclusive: i64,
+    ) -> Result<Option<(i64, f64)>, KlineStoreError>;
+
+    /// Quarantined rows for `symbol` in `[start_ts_ms, end_ts_ms_exclusive)`.
+    fn count_rejected_range(
+        &mut self,
+        symbol: BinanceSymbol,
+        start_ts_ms: i64,
+        end_ts_ms_exclusive: i64,
+    ) -> Result<u64, KlineStoreError>;
+
+    /// Detects micro-halts in `[start_ts_ms, end_ts_ms_exclusive)` and records them,
+    /// replacing the halts previously recorded as starting in that range.
+    fn flag_micro_halts(
+        &mut self,
+        symbol: BinanceSymbol,
+        start_ts_ms: i64,
+        end_ts_ms_exclusive: i64,
+        cfg: &MicroHaltConfig,
+    ) -> Result<Vec<KlineHalt>, KlineStoreError>;
+}
+
+/// Alert rows keyed by alert key. Backends only store records; the notify/suppress
+/// rules live in the provided [`AlertBackend::fire`] and [`AlertBackend::acknowledge`].
+pub trait AlertBackend: Send {
+    /// Minimum seconds between notifications for an alert that keeps firing.
+    fn renotify_after_secs(&self) -> i64;
+
+    fn get(&mut self, key: &str) -> Result<Option<AlertRecord>, AlertStoreError>;
+
+    /// All alerts, most recently fired first.
+    fn list(&mut self) -> Result<Vec<AlertRecord>, AlertStoreError>;
+
+    /// Inserts or replaces the row for `record.key`.
+    fn write(&mut self, record: &AlertRecord) -> Result<(), AlertStoreError>;
+
+    /// Marks `key` as cleared. Returns `true` when it was firing.
+    fn clear(&mut self, key: &str, now_ts_utc: i64) -> Result<bool, AlertStoreError>;
+
+    /// Records that `key` is firing and decides whether to notify.
+    fn fire(
+        &mut self,
+        key: &str,
+        severity: AlertSeverity,
+        message: &str,
+        now_ts_utc: i64,
+    ) -> Result<AlertDecision, AlertStoreError> {
+        let prev = self.get(key)?;
+        let (decision, record) = crate::alerts::next_fire(
+            prev,
+            key,
+            severity,
+            message,
+            now_ts_utc,
+            self.renotify_after_secs(),
+        );
+        self.write(&record)?;
+        Ok(decision)
+    }
+
+    /// Marks `key` as acknowledged. Returns `None` when it is unknown or cleared.
+    fn acknowledge(
+        &mut self,
+        key: &str,
+        now_ts_utc: i64,
+    ) -> Result<Option<AlertRecord>, AlertStoreError> {
+        let Some(record) = crate::alerts::acknowledged(self.get(key)?, now_ts_utc) else {
+            return Ok(None);
+        };
+        self.write(&record)?;
+        Ok(Some(record))
+    }
+}
+
+/// Opens the kline store for writing: the SQLite file at `sqlite_path`, or the shared
+/// Postgres tables.
+pub fn open_kline_backend(
+    backend: &PersistenceBackend,
+    sqlite_path: &Path,
+    validation: KlineValidationConfig,
+) -> Result<Box<dyn KlineBackend>, KlineStoreError> {
+    match backend {
+        PersistenceBackend::Sqlite => Ok(Box::new(KlineStore::open(sqlite_path, validation)?)),
+        PersistenceBackend::Postgres { url } => postgres_klines(url, Some(validation)),
+    }
+}
+
+/// Opens the kline store for reads only; SQLite is opened read-only so readers can run
+/// next to the sync process.
+pub fn open_kline_reader(
+    backend: &PersistenceBackend,
+    sqlite_path: &Path,
+) -> Result<Box<dyn KlineBackend>, KlineStoreError> {
+    match backend {
+        PersistenceBackend::Sqlite => Ok(Box::new(KlineStore::open_read_only(sqlite_path)?)),
+        PersistenceBackend::Postgres { url } => postgres_klines(url, None),
+    }
+}
+
+pub fn open_alert_backend(
+    backend: &PersistenceBackend,
+    sqlite_path: &Path,
+    renotify_after_secs: i64,
+) -> Result<Box<dyn AlertBackend>, AlertStoreError> {
+    match backend {
+        PersistenceBackend::Sqlite => Ok(Box::new(AlertStore::open(
+            sqlite_path,
+            renotify_after_secs,
+        )?)),
+        #[cfg(feature = "postgres")]
+        PersistenceBackend::Postgres { url } => Ok(Box::new(
+            crate::postgres_store::PgAlertStore::connect(url, renotify_after_secs)?,
+        )),
+        #[cfg(not(feature = "postgres"))]
+        PersistenceBackend::Postgres { .. } => Err(AlertStoreError::BackendUnavailable(
+            POSTGRES_DISABLED.to_string(),
+        )),
+    }
+}
+
+#[cfg(not(feature = "postgres"))]
+const POSTGRES_DISABLED: &str =
+    "PMM_DATABASE_URL is a Postgres URL but pmm was built without the `postgres` feature";
+
+#[cfg(feature = "postgres")]
+fn postgres_klines(
+    url: &str,
+    validation: Option<KlineValidationConfig>,
+) -> Result<Box<dyn KlineBackend>, KlineStoreError> {
+    Ok(Box::new(crate::postgres_store::PgKlineStore::connect(
+        url, validation,
+    )?))
+}
+
+#[cfg(not(feature = "postgres"))]
+fn postgres_klines(
+    _url: &str,
+    _validation: Option<KlineValidationConfig>,
+) -> Result<Box<dyn KlineBackend>, KlineStoreError> {
+    Err(KlineStoreError::BackendUnavailable(
+        POSTGRES_DISABLED.to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn postgres_urls_select_the_shared_backend() {
+        assert!(is_postgres_url("postgres://pmm@db/pmm"));
+        assert!(is_postgres_url("postgresql://db/pmm"));
+        assert!(!is_postgres_url("data/klines_1s.sqlite"));
+        assert!(!is_postgres_url(""));
+    }
+
+    #[test]
+    fn direct_kline_readers_refuse_postgres() {
+        assert!(PersistenceBackend::Sqlite
+            .require_sqlite_klines("pmm drift")
+            .is_ok());
+        let err = PersistenceBackend::Postgres {
+            url: "postgres://db/pmm".to_string(),
+        }
+        .require_sqlite_klines("pmm drift")
+        .unwrap_err();
+        assert!(matches!(err, KlineStoreError::BackendUnavailable(_)));
+        assert!(err.to_string().contains("pmm drift"));
+    }
+
+    #[test]
+    fn sqlite_only_stores_are_registered() {
+        for name in SQLITE_ONLY_STORES {
+            assert!(crate::env_vars::env_var(name).is_some(), "{name}");
+        }
+    }
+
+    #[cfg(not(feature = "postgres"))]
+    #[test]
+    fn postgres_without_the_feature_is_an_error_not_a_silent_sqlite_fallback() {
+        let backend = PersistenceBackend::Postgres {
+            url: "postgres://db/pmm".to_string(),
+        };
+        let err = open_kline_reader(&backend, Path::new("unused.sqlite"))
+            .err()
+            .expect("error");
+        assert!(matches!(err, KlineStoreError::BackendUnavailable(_)));
+    }
+}
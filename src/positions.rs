@@ -0,0 +1,515 @@
+//! Position tracking: the wallet's fills reconciled into per-market YES/NO positions.
+//!
+//! Fills come from the CLOB trade history of the trading wallet ([`PositionFeed`]
+//! polls it through [`crate::OrderClient::fills`]). Each fill is keyed by trade and
+//! order, so overlapping polls and status updates (matched, mined, confirmed) replace
+//! it instead of counting it twice, and a trade that failed on chain removes it again.
+//! [`PositionBook`] maps both outcome tokens of every discovered market to the slugs
+//! the dashboard shows it by, like the order book subscriptions, and values each
+//! outcome at average cost: buys move the average, sells realize against it. Consumers
+//! read positions through the [`PositionSource`] trait, so the dashboard's `pos_yes`,
+//! `pos_no`, `position_net`, and `net_profit` columns can come from any source.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::clob_stream::BookSubscription;
+use crate::env_vars::Settings;
+use crate::fill_model::OrderSide;
+use crate::sizing::SizingSide;
+use crate::trading::TradingConfig;
+
+/// One of our orders filling, fully or in part.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PositionFill {
+    /// Trade id, plus the order id for maker fills: one trade can fill several of our
+    /// resting orders.
+    pub fill_id: String,
+    /// Outcome token that changed hands.
+    pub token_id: String,
+    pub side: OrderSide,
+    pub price: f64,
+    pub size: f64,
+    pub fee_usdc: f64,
+    pub ts_utc: i64,
+    /// The trade failed on chain and never settled.
+    pub failed: bool,
+}
+
+/// Shares held in one outcome and what they cost.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct OutcomePosition {
+    pub shares: f64,
+    /// Average cost per share of the shares still held; `0` when flat.
+    pub avg_price: f64,
+    /// Sale proceeds minus the average cost of the shares sold, before fees.
+    pub realized_pnl_usdc: f64,
+}
+
+impl OutcomePosition {
+    fn apply(&mut self, side: OrderSide, price: f64, size: f64) {
+        match side {
+            OrderSide::Buy => {
+                let shares = self.shares + size;
+                self.avg_price = (self.shares * self.avg_price + size * price) / shares;
+                self.shares = shares;
+            }
+            OrderSide::Sell => {
+                // The CLOB cannot sell shares we do not hold; anything beyond the
+                // position is ignored rather than turned into a short.
+                let closed = size.min(self.shares);
+                self.realized_pnl_usdc += closed * (price - self.avg_price);
+                self.shares -= closed;
+                if self.shares <= f64::EPSILON {
+                    self.shares = 0.0;
+                    self.avg_price = 0.0;
+                }
+            }
+        }
+    }
+}
+
+/// YES and NO holdings of one market.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct MarketPosition {
+    pub yes: OutcomePosition,
+    pub no: OutcomePosition,
+    pub fees_usdc: f64,
+}
+
+impl MarketPosition {
+    /// Folds `fills` of the market's YES and NO tokens in time order.
+    pub fn from_fills<'a>(
+        fills: impl IntoIterator<Item = (SizingSide, &'a PositionFill)>,
+    ) -> MarketPosition {
+        let mut fills: Vec<_> = fills.into_iter().filter(|(_, fill)| !fill.failed).collect();
+        fills.sort_by(|(_, a), (_, b)| (a.ts_utc, &a.fill_id).cmp(&(b.ts_utc, &b.fill_id)));
+        let mut position = MarketPosition::default();
+        for (outcome, fill) in fills {
+            let held = match outcome {
+                SizingSide::Yes => &mut position.yes,
+                SizingSide::No => &mut position.no,
+            };
+            held.apply(fill.side, fill.price, fill.size);
+            position.fees_usdc += fill.fee_usdc;
+        }
+        position
+    }
+
+    /// YES shares minus NO shares: a YES and a NO share together redeem for 1 USDC
+    /// whatever the outcome, so only the difference is exposed.
+    pub fn net_shares(&self) -> f64 {
+        self.yes.shares - self.no.shares
+    }
+
+    /// Realized PnL of both outcomes, fees paid included.
+    pub fn realized_pnl_usdc(&self) -> f64 {
+        self.yes.realized_pnl_usdc + self.no.realized_pnl_usdc - self.fees_usdc
+    }
+
+    /// Open shares marked at `mark_yes` for YES and `1 - mark_yes` for NO.
+    pub fn unrealized_pnl_usdc(&self, mark_yes: f64) -> f64 {
+        self.yes.shares * (mark_yes - self.yes.avg_price)
+            + self.no.shares * (1.0 - mark_yes - self.no.avg_price)
+    }
+
+    /// Realized plus unrealized PnL; realized only without a mark.
+    pub fn net_profit_usdc(&self, mark_yes: Option<f64>) -> f64 {
+        self.realized_pnl_usdc() + mark_yes.map_or(0.0, |mark| self.unrealized_pnl_usdc(mark))
+    }
+}
+
+/// Positions by market slug.
+pub trait PositionSource: Send + Sync {
+    /// Position in the market shown as `slug`; `None` until one of its tokens filled.
+    fn position(&self, slug: &str) -> Option<MarketPosition>;
+}
+
+#[derive(Debug, Default)]
+struct PositionState {
+    /// Outcome token -> (scheduled slug of its market, outcome).
+    outcome_by_token: HashMap<String, (String, SizingSide)>,
+    /// Every slug a market is shown by -> its scheduled slug.
+    market_by_slug: HashMap<String, String>,
+    fills: HashMap<String, PositionFill>,
+}
+
+/// Shared fill ledger; cloning shares it.
+#[derive(Debug, Clone, Default)]
+pub struct PositionBook {
+    state: Arc<Mutex<PositionState>>,
+}
+
+impl PositionBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, PositionState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Adds the outcome tokens of `markets`. Markets stay known after they leave
+    /// discovery, so ended windows keep showing what they hold.
+    pub fn track_markets(&self, markets: &[BookSubscription]) {
+        let mut state = self.lock();
+        for market in markets {
+            let key = market.key.slug.clone();
+            state
+                .outcome_by_token
+                .insert(market.yes_token_id.clone(), (key.clone(), SizingSide::Yes));
+            if let Some(no_token_id) = &market.no_token_id {
+                state
+                    .outcome_by_token
+                    .insert(no_token_id.clone(), (key.clone(), SizingSide::No));
+            }
+            for slug in market.slugs() {
+                state.market_by_slug.insert(slug.to_string(), key.clone());
+            }
+        }
+    }
+
+    /// Records `fill`, replacing an earlier report of the same fill; a failed fill is
+    /// removed. Returns whether the ledger changed.
+    pub fn record_fill(&self, fill: PositionFill) -> bool {
+        let mut state = self.lock();
+        if fill.failed {
+            return state.fills.remove(&fill.fill_id).is_some();
+        }
+        state.fills.insert(fill.fill_id.clone(), fill.clone()) != Some(fill)
+    }
+
+    /// Number of fills held.
+    pub fn fill_count(&self) -> usize {
+        self.lock().fills.len()
+    }
+
+    /// Position of every market with a fill, once per market however many slugs show
+    /// it.
+    pub fn positions(&self) -> Vec<MarketPosition> {
+        let state = self.lock();
+        let mut by_market: HashMap<&str, Vec<(SizingSide, &PositionFill)>> = HashMap::new();
+        for fill in state.fills.values() {
+            if let Some((market, outcome)) = state.outcome_by_token.get(&fill.token_id) {
+                by_market
+                    .entry(market.as_str())
+                    .or_default()
+                    .push((*outcome, fill));
+            }
+        }
+        by_market
+            .into_values()
+            .map(MarketPosition::from_fills)
+            .collect()
+    }
+}
+
+impl PositionSource for PositionBook {
+    fn position(&self, slug: &str) -> Option<MarketPosition> {
+        let state = self.lock();
+        let market = state.market_by_slug.get(slug)?;
+        let fills: Vec<(SizingSide, &PositionFill)> = state
+            .fills
+            .values()
+            .filter_map(|fill| match state.outcome_by_token.get(&fill.token_id) {
+                Some((key, outcome)) if key == market => Some((*outcome, fill)),
+                _ => None,
+            })
+            .collect();
+        (!fills.is_empty()).then(|| MarketPosition::from_fills(fills))
+    }
+}
+
+/// Trade-history polling for the trading wallet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PositionConfig {
+    /// Poll cadence; `None` disables position tracking.
+    pub poll_ms: Option<u64>,
+    pub trading: TradingConfig,
+}
+
+impl Default for PositionConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl PositionConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_POSITIONS_POLL_MS` (`off` disables) and the wallet settings of
+    /// [`TradingConfig::from_env`].
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            poll_ms: settings.get_where("PMM_POSITIONS_POLL_MS", |ms| *ms != Some(0)),
+            trading: TradingConfig::from_settings(settings),
+        }
+    }
+}
+
+#[cfg(feature = "trading")]
+pub use feed::PositionFeed;
+
+#[cfg(feature = "trading")]
+mod feed {
+    use std::sync::{Arc, Mutex};
+
+    use tracing::{info, warn};
+
+    use super::{BookSubscription, MarketPosition, PositionBook, PositionConfig, PositionSource};
+    use crate::capital::{CapitalSource, CapitalUsage};
+    use crate::trading::OrderClient;
+
+    /// Trades can still fail on chain a while after they matched; every poll looks
+    /// back this far so their final status replaces the matched one.
+    const STATUS_LOOKBACK_SECS: i64 = 3_600;
+
+    /// Handle to the background trade-history poller. Cloning shares the ledger.
+    #[derive(Debug, Clone)]
+    pub struct PositionFeed {
+        book: PositionBook,
+        capital: Arc<Mutex<Option<CapitalUsage>>>,
+    }
+
+    impl PositionFeed {
+        /// Starts polling, or `None` when polling is off or no wallet key is set. The
+        /// first poll reads the whole history, later ones the lookback window.
+        pub fn spawn(cfg: PositionConfig) -> Option<Self> {
+            let poll_ms = cfg.poll_ms?;
+            cfg.trading.private_key.as_ref()?;
+            let feed = Self {
+                book: PositionBook::new(),
+                capital: Arc::default(),
+            };
+            tokio::spawn(run_feed(
+                cfg,
+                poll_ms,
+                feed.book.clone(),
+                feed.capital.clone(),
+            ));
+            Some(feed)
+        }
+
+        /// Maps the outcome tokens of `markets` to their slugs.
+        pub fn track_markets(&self, markets: &[BookSubscription]) {
+            self.book.track_markets(markets);
+        }
+
+        pub fn book(&self) -> &PositionBook {
+            &self.book
+        }
+    }
+
+    impl PositionSource for PositionFeed {
+        fn position(&self, slug: &str) -> Option<MarketPosition> {
+            self.book.position(slug)
+        }
+    }
+
+    impl CapitalSource for PositionFeed {
+        fn capital_usage(&self) -> Option<CapitalUsage> {
+            self.capital
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .clone()
+        }
+    }
+
+    async fn run_feed(
+        cfg: PositionConfig,
+        poll_ms: u64,
+        book: PositionBook,
+        capital: Arc<Mutex<Option<CapitalUsage>>>,
+    ) {
+        let interval = std::time::Duration::from_millis(poll_ms);
+        let client = loop {
+            match OrderClient::connect(&cfg.trading).await {
+                Ok(client) => break client,
+                Err(err) => {
+                    warn!(
+                        component = "positions",
+                        event = "positions.connect.error",
+                        error = %err
+                    );
+                    tokio::time::sleep(interval.max(std::time::Duration::from_secs(30))).await;
+                }
+            }
+        };
+        let mut after = None;
+        loop {
+            let polled_at = crate::faults::now_utc().timestamp();
+            match client.fills(after).await {
+                Ok(fills) => {
+                    let polled = fills.len();
+                    let changed = fills
+                        .into_iter()
+                        .filter(|fill| book.record_fill(fill.clone()))
+                        .count();
+                    if changed > 0 {
+                        info!(
+                            component = "positions",
+                            event = "positions.fills",
+                            polled,
+                            changed,
+                            held = book.fill_count()
+                        );
+                    }
+                    after = Some(polled_at - STATUS_LOOKBACK_SECS);
+                }
+                Err(err) => warn!(
+                    component = "positions",
+                    event = "positions.poll.error",
+                    error = %err
+                ),
+            }
+            // Open orders and balance after the fills, so a bid that just filled is
+            // counted as position cost rather than twice.
+            match client.open_orders(None).await {
+                Ok(orders) => {
+                    let balance = match client.usdc_balance().await {
+                        Ok(balance) => Some(balance),
+                        Err(err) => {
+                            warn!(
+                                component = "positions",
+                                event = "positions.balance.error",
+                                error = %err
+                            );
+                            None
+                        }
+                    };
+                    let usage = CapitalUsage::compute(
+                        balance,
+                        &orders,
+                        book.positions(),
+                        crate::faults::now_utc().timestamp(),
+                    );
+                    *capital
+                        .lock()
+                        .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(usage);
+                }
+                Err(err) => warn!(
+                    component = "positions",
+                    event = "positions.orders.error",
+                    error = %err
+                ),
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery::DiscoveryKey;
+    use crate::slug::{Coin, Duration, SlugConfig};
+
+    fn fill(
+        id: &str,
+        token: &str,
+        side: OrderSide,
+        price: f64,
+        size: f64,
+        ts: i64,
+    ) -> PositionFill {
+        PositionFill {
+            fill_id: id.to_string(),
+            token_id: token.to_string(),
+            side,
+            price,
+            size,
+            fee_usdc: 0.0,
+            ts_utc: ts,
+            failed: false,
+        }
+    }
+
+    #[test]
+    fn average_cost_realizes_on_sells_and_marks_the_rest() {
+        let position = MarketPosition::from_fills([
+            (
+                SizingSide::Yes,
+                &fill("b", "y", OrderSide::Buy, 0.60, 10.0, 2),
+            ),
+            (
+                SizingSide::Yes,
+                &fill("a", "y", OrderSide::Buy, 0.40, 10.0, 1),
+            ),
+            (
+                SizingSide::Yes,
+                &fill("c", "y", OrderSide::Sell, 0.70, 5.0, 3),
+            ),
+            (
+                SizingSide::No,
+                &fill("d", "n", OrderSide::Buy, 0.30, 4.0, 4),
+            ),
+        ]);
+        assert_eq!(position.yes.shares, 15.0);
+        assert!((position.yes.avg_price - 0.5).abs() < 1e-12);
+        assert!((position.yes.realized_pnl_usdc - 1.0).abs() < 1e-12);
+        assert_eq!(position.net_shares(), 11.0);
+        // YES at 0.55: 15 * 0.05 on YES, 4 * (0.45 - 0.30) on NO.
+        assert!((position.unrealized_pnl_usdc(0.55) - 1.35).abs() < 1e-12);
+        assert!((position.net_profit_usdc(Some(0.55)) - 2.35).abs() < 1e-12);
+        assert!((position.net_profit_usdc(None) - 1.0).abs() < 1e-12);
+
+        // Selling out flattens the average; overselling does not go short.
+        let flat = MarketPosition::from_fills([
+            (
+                SizingSide::No,
+                &fill("a", "n", OrderSide::Buy, 0.30, 4.0, 1),
+            ),
+            (
+                SizingSide::No,
+                &fill("b", "n", OrderSide::Sell, 0.20, 6.0, 2),
+            ),
+        ]);
+        assert_eq!((flat.no.shares, flat.no.avg_price), (0.0, 0.0));
+        assert!((flat.realized_pnl_usdc() + 0.4).abs() < 1e-12);
+    }
+
+    #[test]
+    fn book_dedupes_fills_and_maps_tokens_to_slugs() {
+        let key = DiscoveryKey::new(
+            Coin::BTC,
+            Duration::M5,
+            1_771_449_000,
+            SlugConfig::default(),
+        )
+        .unwrap();
+        let book = PositionBook::new();
+        book.track_markets(&[BookSubscription {
+            key: key.clone(),
+            market_slug: Some("btc-up-or-down".to_string()),
+            yes_token_id: "yes".to_string(),
+            no_token_id: Some("no".to_string()),
+            neg_risk: false,
+            neg_risk_group: None,
+        }]);
+        assert_eq!(book.position(&key.slug), None);
+
+        let buy = fill("t1", "yes", OrderSide::Buy, 0.5, 10.0, 1);
+        assert!(book.record_fill(buy.clone()));
+        // The same fill reported again (e.g. mined after matched) changes nothing.
+        assert!(!book.record_fill(buy.clone()));
+        assert!(book.record_fill(fill("t2:o1", "no", OrderSide::Buy, 0.4, 2.0, 2)));
+        assert!(book.record_fill(fill("t3", "other", OrderSide::Buy, 0.4, 2.0, 2)));
+        let position = book.position("btc-up-or-down").unwrap();
+        assert_eq!(Some(position), book.position(&key.slug));
+        assert_eq!((position.yes.shares, position.no.shares), (10.0, 2.0));
+
+        assert!(book.record_fill(PositionFill {
+            failed: true,
+            ..buy
+        }));
+        assert_eq!(book.position(&key.slug).unwrap().yes.shares, 0.0);
+        assert_eq!(book.fill_count(), 2);
+    }
+}
@@ -0,0 +1,551 @@
+//! Postgres implementations of the storage traits (`postgres` feature).
+//!
+//! Tables mirror the SQLite layouts (`klines_1s`, `klines_rejected`, `alerts`) plus
+//! `strategy_decisions`. Writers create missing tables on connect; kline readers make
+//! no schema changes, like the read-only SQLite store.
+//!
+//! The client is blocking. Inside a multi-thread Tokio runtime each call runs under
+//! `block_in_place`; a current-thread runtime cannot host it.
+
+use std::sync::Mutex;
+
+use chrono::Utc;
+use postgres::{Client, NoTls, Row};
+use tokio::runtime::{Handle, RuntimeFlavor};
+use tracing::error;
+
+use crate::alerts::{alert_state, AlertRecord, AlertSeverity, AlertStoreError};
+use crate::binance_klines::{BinanceSymbol, Kline1s};
+use crate::kline_store::{log_flagged_halts, symbol_id, KlineStoreError};
+use crate::kline_validation::{
+    detect_micro_halts, validate_klines, KlineHalt, KlineValidationConfig, KlineValidationPolicy,
+    KlineViolationCounts, MicroHaltConfig, OhlcvPoint,
+};
+use crate::persistence::{AlertBackend, KlineBackend};
+use crate::strategy::{DecisionLog, DecisionRecord};
+
+fn blocking<T>(f: impl FnOnce() -> T) -> T {
+    match Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(f)
+        }
+        _ => f(),
+    }
+}
+
+fn connect(url: &str) -> Result<Client, postgres::Error> {
+    blocking(|| Client::connect(url, NoTls))
+}
+
+const KLINE_SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS klines_1s (
+        symbol_id BIGINT NOT NULL,
+        open_time_ms BIGINT NOT NULL,
+        open DOUBLE PRECISION NOT NULL,
+        high DOUBLE PRECISION NOT NULL,
+        low DOUBLE PRECISION NOT NULL,
+        close DOUBLE PRECISION NOT NULL,
+        volume DOUBLE PRECISION NOT NULL,
+        close_time_ms BIGINT NOT NULL,
+        quote_asset_volume DOUBLE PRECISION NOT NULL,
+        trade_count BIGINT NOT NULL,
+        taker_buy_base_volume DOUBLE PRECISION NOT NULL,
+        taker_buy_quote_volume DOUBLE PRECISION NOT NULL,
+        PRIMARY KEY (symbol_id, open_time_ms)
+    );
+    CREATE TABLE IF NOT EXISTS klines_rejected (
+        symbol_id BIGINT NOT NULL,
+        open_time_ms BIGINT NOT NULL,
+        rules TEXT NOT NULL,
+        detail TEXT NOT NULL,
+        open DOUBLE PRECISION,
+        high DOUBLE PRECISION,
+        low DOUBLE PRECISION,
+        close DOUBLE PRECISION,
+        volume DOUBLE PRECISION,
+        close_time_ms BIGINT,
+        quote_asset_volume DOUBLE PRECISION,
+        trade_count BIGINT,
+        taker_buy_base_volume DOUBLE PRECISION,
+        taker_buy_quote_volume DOUBLE PRECISION,
+        rejected_at_ms BIGINT NOT NULL,
+        PRIMARY KEY (symbol_id, open_time_ms)
+    );
+    CREATE TABLE IF NOT EXISTS kline_halts (
+        symbol_id BIGINT NOT NULL,
+        start_ms BIGINT NOT NULL,
+        end_ms_exclusive BIGINT NOT NULL,
+        detected_at_ms BIGINT NOT NULL,
+        PRIMARY KEY (symbol_id, start_ms)
+    );
+";
+
+/// Shared 1s kline store in Postgres.
+pub struct PgKlineStore {
+    client: Client,
+    validation: KlineValidationConfig,
+}
+
+impl PgKlineStore {
+    /// Connects to `url`. `Some(validation)` opens a writer (creating missing tables);
+    /// `None` opens a reader.
+    pub fn connect(
+        url: &str,
+        validation: Option<KlineValidationConfig>,
+    ) -> Result<Self, KlineStoreError> {
+        let mut client = connect(url)?;
+        if validation.is_some() {
+            blocking(|| client.batch_execute(KLINE_SCHEMA))?;
+        }
+        Ok(Self {
+            client,
+            validation: validation.unwrap_or_default(),
+        })
+    }
+
+    fn count(
+        &mut self,
+        sql: &str,
+        symbol: BinanceSymbol,
+        start: i64,
+        end: i64,
+    ) -> Result<u64, KlineStoreError> {
+        let row = blocking(|| {
+            self.client
+                .query_one(sql, &[&symbol_id(symbol), &start, &end])
+        })?;
+        Ok(row.get::<_, i64>(0).max(0) as u64)
+    }
+}
+
+impl KlineBackend for PgKlineStore {
+    fn upsert_rows(
+        &mut self,
+        symbol: BinanceSymbol,
+        rows: Vec<Kline1s>,
+    ) -> Result<KlineViolationCounts, KlineStoreError> {
+        let outcome = validate_klines(symbol, rows, &self.validation);
+        let quarantine = self.validation.policy == KlineValidationPolicy::Quarantine;
+        let id = symbol_id(symbol);
+        blocking(|| -> Result<(), postgres::Error> {
+            let mut tx = self.client.transaction()?;
+            if quarantine && !outcome.rejected.is_empty() {
+                let rejected_at_ms = Utc::now().timestamp_millis();
+                let stmt = tx.prepare(
+                    "
+                    INSERT INTO klines_rejected (
+                        symbol_id, open_time_ms, rules, detail, open, high, low, close,
+                        volume, close_time_ms, quote_asset_volume, trade_count,
+                        taker_buy_base_volume, taker_buy_quote_volume, rejected_at_ms
+                    ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+                    ON CONFLICT (symbol_id, open_time_ms) DO UPDATE SET
+                        rules = EXCLUDED.rules,
+                        detail = EXCLUDED.detail,
+                        open = EXCLUDED.open,
+                        high = EXCLUDED.high,
+                        low = EXCLUDED.low,
+                        close = EXCLUDED.close,
+                        volume = EXCLUDED.volume,
+                        close_time_ms = EXCLUDED.close_time_ms,
+                        quote_asset_volume = EXCLUDED.quote_asset_volume,
+                        trade_count = EXCLUDED.trade_count,
+                        taker_buy_base_volume = EXCLUDED.taker_buy_base_volume,
+                        taker_buy_quote_volume = EXCLUDED.taker_buy_quote_volume,
+                        rejected_at_ms = EXCLUDED.rejected_at_ms
+                    ",
+                )?;
+                for rejected in &outcome.rejected {
+                    let row = &rejected.row;
+                    let rules: Vec<&str> = rejected.violations.iter().map(|v| v.rule()).collect();
+                    let detail: Vec<String> =
+                        rejected.violations.iter().map(|v| v.detail()).collect();
+                    tx.execute(
+                        &stmt,
+                        &[
+                            &id,
+                            &row.open_time_ms,
+                            &rules.join(","),
+                            &detail.join("; "),
+                            &row.open,
+                            &row.high,
+                            &row.low,
+                            &row.close,
+                            &row.volume,
+                            &row.close_time_ms,
+                            &row.quote_asset_volume,
+                            &(row.trade_count as i64),
+                            &row.taker_buy_base_volume,
+                            &row.taker_buy_quote_volume,
+                            &rejected_at_ms,
+                        ],
+                    )?;
+                }
+            }
+            if !outcome.accepted.is_empty() {
+                let stmt = tx.prepare(
+                    "
+                    INSERT INTO klines_1s (
+                        symbol_id, open_time_ms, open, high, low, close, volume,
+                        close_time_ms, quote_asset_volume, trade_count,
+                        taker_buy_base_volume, taker_buy_quote_volume
+                    ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+                    ON CONFLICT (symbol_id, open_time_ms) DO UPDATE SET
+                        open = EXCLUDED.open,
+                        high = EXCLUDED.high,
+                        low = EXCLUDED.low,
+                        close = EXCLUDED.close,
+                        volume = EXCLUDED.volume,
+                        close_time_ms = EXCLUDED.close_time_ms,
+                        quote_asset_volume = EXCLUDED.quote_asset_volume,
+                        trade_count = EXCLUDED.trade_count,
+                        taker_buy_base_volume = EXCLUDED.taker_buy_base_volume,
+                        taker_buy_quote_volume = EXCLUDED.taker_buy_quote_volume
+                    ",
+                )?;
+                for row in &outcome.accepted {
+                    tx.execute(
+                        &stmt,
+                        &[
+                            &id,
+                            &row.open_time_ms,
+                            &row.open,
+                            &row.high,
+                            &row.low,
+                            &row.close,
+                            &row.volume,
+                            &row.close_time_ms,
+                            &row.quote_asset_volume,
+                            &(row.trade_count as i64),
+                            &row.taker_buy_base_volume,
+                            &row.taker_buy_quote_volume,
+                        ],
+                    )?;
+                }
+            }
+            tx.commit()
+        })?;
+        Ok(outcome.counts)
+    }
+
+    fn count_range(
+        &mut self,
+        symbol: BinanceSymbol,
+        start_ts_ms: i64,
+        end_ts_ms_exclusive: i64,
+    ) -> Result<u64, KlineStoreError> {
+        self.count(
+            "SELECT COUNT(*) FROM klines_1s
+             WHERE symbol_id = $1 AND open_time_ms >= $2 AND open_time_ms < $3",
+            symbol,
+            start_ts_ms,
+            end_ts_ms_exclusive,
+        )
+    }
+
+    fn closes_range(
+        &mut self,
+        symbol: BinanceSymbol,
+        start_ts_ms: i64,
+        end_ts_ms_exclusive: i64,
+    ) -> Result<Vec<(i64, f64)>, KlineStoreError> {
+        let rows = blocking(|| {
+            self.client.query(
+                "SELECT open_time_ms, close FROM klines_1s
+                 WHERE symbol_id = $1 AND open_time_ms >= $2 AND open_time_ms < $3
+                 ORDER BY open_time_ms",
+                &[&symbol_id(symbol), &start_ts_ms, &end_ts_ms_exclusive],
+            )
+        })?;
+        Ok(rows.iter().map(|row| (row.get(0), row.get(1))).collect())
+    }
+
+    fn first_open_in_range(
+        &mut self,
+        symbol: BinanceSymbol,
+        start_ts_ms: i64,
+        end_ts_ms_exclusive: i64,
+    ) -> Result<Option<(i64, f64)>, KlineStoreError> {
+        let row = blocking(|| {
+            self.client.query_opt(
+                "SELECT open_time_ms, open FROM klines_1s
+                 WHERE symbol_id = $1 AND open_time_ms >= $2 AND open_time_ms < $3
+                 ORDER BY open_time_ms
+                 LIMIT 1",
+                &[&symbol_id(symbol), &start_ts_ms, &end_ts_ms_exclusive],
+            )
+        })?;
+        Ok(row.map(|row| (row.get(0), row.get(1))))
+    }
+
+    fn count_rejected_range(
+        &mut self,
+        symbol: BinanceSymbol,
+        start_ts_ms: i64,
+        end_ts_ms_exclusive: i64,
+    ) -> Result<u64, KlineStoreError> {
+        self.count(
+            "SELECT COUNT(*) FROM klines_rejected
+             WHERE symbol_id = $1 AND open_time_ms >= $2 AND open_time_ms < $3",
+            symbol,
+            start_ts_ms,
+            end_ts_ms_exclusive,
+        )
+    }
+
+    fn flag_micro_halts(
+        &mut self,
+        symbol: BinanceSymbol,
+        start_ts_ms: i64,
+        end_ts_ms_exclusive: i64,
+        cfg: &MicroHaltConfig,
+    ) -> Result<Vec<KlineHalt>, KlineStoreError> {
+        let id = symbol_id(symbol);
+        let rows = blocking(|| {
+            self.client.query(
+                "SELECT open_time_ms, open, high, low, close, volume FROM klines_1s
+                 WHERE symbol_id = $1 AND open_time_ms >= $2 AND open_time_ms < $3
+                 ORDER BY open_time_ms",
+                &[&id, &start_ts_ms, &end_ts_ms_exclusive],
+            )
+        })?;
+        let points = rows
+            .iter()
+            .map(|row| OhlcvPoint {
+                open_time_ms: row.get(0),
+                open: row.get(1),
+                high: row.get(2),
+                low: row.get(3),
+                close: row.get(4),
+                volume: row.get(5),
+            })
+            .collect::<Vec<_>>();
+        let halts = detect_micro_halts(&points, cfg);
+
+        let detected_at_ms = Utc::now().timestamp_millis();
+        blocking(|| -> Result<(), postgres::Error> {
+            let mut tx = self.client.transaction()?;
+            tx.execute(
+                "DELETE FROM kline_halts
+                 WHERE symbol_id = $1 AND start_ms >= $2 AND start_ms < $3",
+                &[&id, &start_ts_ms, &end_ts_ms_exclusive],
+            )?;
+            let stmt = tx.prepare(
+                "INSERT INTO kline_halts (symbol_id, start_ms, end_ms_exclusive, detected_at_ms)
+                 VALUES ($1, $2, $3, $4)
+                 ON CONFLICT (symbol_id, start_ms) DO UPDATE SET
+                     end_ms_exclusive = EXCLUDED.end_ms_exclusive,
+                     detected_at_ms = EXCLUDED.detected_at_ms",
+            )?;
+            for halt in &halts {
+                tx.execute(
+                    &stmt,
+                    &[&id, &halt.start_ms, &halt.end_ms_exclusive, &detected_at_ms],
+                )?;
+            }
+            tx.commit()
+        })?;
+        log_flagged_halts(symbol, &halts);
+        Ok(halts)
+    }
+}
+
+const ALERT_COLUMNS: &str = "key, severity, message, first_fired_ts_utc, last_fired_ts_utc, \
+     last_notified_ts_utc, fire_count, acked_ts_utc, cleared_ts_utc";
+
+/// Shared alert state in Postgres; every process sees the same acknowledgements.
+pub struct PgAlertStore {
+    client: Client,
+    renotify_after_secs: i64,
+}
+
+impl PgAlertStore {
+    pub fn connect(url: &str, renotify_after_secs: i64) -> Result<Self, AlertStoreError> {
+        let mut client = connect(url)?;
+        blocking(|| {
+            client.batch_execute(
+                "
+                CREATE TABLE IF NOT EXISTS alerts (
+                    key TEXT PRIMARY KEY,
+                    severity TEXT NOT NULL,
+                    message TEXT NOT NULL,
+                    first_fired_ts_utc BIGINT NOT NULL,
+                    last_fired_ts_utc BIGINT NOT NULL,
+                    last_notified_ts_utc BIGINT NOT NULL,
+                    fire_count BIGINT NOT NULL,
+                    acked_ts_utc BIGINT,
+                    cleared_ts_utc BIGINT
+                );
+                ",
+            )
+        })?;
+        Ok(Self {
+            client,
+            renotify_after_secs,
+        })
+    }
+}
+
+impl AlertBackend for PgAlertStore {
+    fn renotify_after_secs(&self) -> i64 {
+        self.renotify_after_secs
+    }
+
+    fn get(&mut self, key: &str) -> Result<Option<AlertRecord>, AlertStoreError> {
+        let row = blocking(|| {
+            self.client.query_opt(
+                &format!("SELECT {ALERT_COLUMNS} FROM alerts WHERE key = $1"),
+                &[&key],
+            )
+        })?;
+        row.as_ref().map(read_alert_row).transpose()
+    }
+
+    fn list(&mut self) -> Result<Vec<AlertRecord>, AlertStoreError> {
+        let rows = blocking(|| {
+            self.client.query(
+                &format!("SELECT {ALERT_COLUMNS} FROM alerts ORDER BY last_fired_ts_utc DESC, key"),
+                &[],
+            )
+        })?;
+        rows.iter().map(read_alert_row).collect()
+    }
+
+    fn write(&mut self, record: &AlertRecord) -> Result<(), AlertStoreError> {
+        blocking(|| {
+            self.client.execute(
+                &format!(
+                    "INSERT INTO alerts ({ALERT_COLUMNS})
+                     VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                     ON CONFLICT (key) DO UPDATE SET
+                         severity = EXCLUDED.severity,
+                         message = EXCLUDED.message,
+                         first_fired_ts_utc = EXCLUDED.first_fired_ts_utc,
+                         last_fired_ts_utc = EXCLUDED.last_fired_ts_utc,
+                         last_notified_ts_utc = EXCLUDED.last_notified_ts_utc,
+                         fire_count = EXCLUDED.fire_count,
+                         acked_ts_utc = EXCLUDED.acked_ts_utc,
+                         cleared_ts_utc = EXCLUDED.cleared_ts_utc"
+                ),
+                &[
+                    &record.key,
+                    &record.severity.as_str(),
+                    &record.message,
+                    &record.first_fired_ts_utc,
+                    &record.last_fired_ts_utc,
+                    &record.last_notified_ts_utc,
+                    &(record.fire_count as i64),
+                    &record.acked_ts_utc,
+                    &record.cleared_ts_utc,
+                ],
+            )
+        })?;
+        Ok(())
+    }
+
+    fn clear(&mut self, key: &str, now_ts_utc: i64) -> Result<bool, AlertStoreError> {
+        let changed = blocking(|| {
+            self.client.execute(
+                "UPDATE alerts SET cleared_ts_utc = $2 WHERE key = $1 AND cleared_ts_utc IS NULL",
+                &[&key, &now_ts_utc],
+            )
+        })?;
+        Ok(changed > 0)
+    }
+}
+
+fn read_alert_row(row: &Row) -> Result<AlertRecord, AlertStoreError> {
+    let severity: String = row.try_get(1)?;
+    let Some(severity) = AlertSeverity::parse(&severity) else {
+        return Err(AlertStoreError::InvalidRow(format!(
+            "unknown severity {severity:?}"
+        )));
+    };
+    let acked_ts_utc: Option<i64> = row.try_get(7)?;
+    let cleared_ts_utc: Option<i64> = row.try_get(8)?;
+    Ok(AlertRecord {
+        key: row.try_get(0)?,
+        severity,
+        message: row.try_get(2)?,
+        state: alert_state(acked_ts_utc, cleared_ts_utc),
+        first_fired_ts_utc: row.try_get(3)?,
+        last_fired_ts_utc: row.try_get(4)?,
+        last_notified_ts_utc: row.try_get(5)?,
+        fire_count: row.try_get::<_, i64>(6)?.max(0) as u64,
+        acked_ts_utc,
+        cleared_ts_utc,
+    })
+}
+
+/// Decision log appending to `strategy_decisions`, so decisions from every strategy
+/// host land in one table. Write failures are logged (`strategy.decision_log_error`)
+/// and do not stop the strategy.
+pub struct PgDecisionLog {
+    client: Mutex<Client>,
+}
+
+impl PgDecisionLog {
+    pub fn connect(url: &str) -> Result<Self, postgres::Error> {
+        let mut client = connect(url)?;
+        blocking(|| {
+            client.batch_execute(
+                "
+                CREATE TABLE IF NOT EXISTS strategy_decisions (
+                    id BIGSERIAL PRIMARY KEY,
+                    ts_utc BIGINT NOT NULL,
+                    slug TEXT NOT NULL,
+                    routine TEXT NOT NULL,
+                    action TEXT NOT NULL,
+                    side TEXT,
+                    shares DOUBLE PRECISION,
+                    price DOUBLE PRECISION,
+                    edge DOUBLE PRECISION,
+                    reason TEXT
+                );
+                ALTER TABLE strategy_decisions ADD COLUMN IF NOT EXISTS model_id TEXT;
+                CREATE INDEX IF NOT EXISTS strategy_decisions_slug_ts
+                    ON strategy_decisions (slug, ts_utc);
+                ",
+            )
+        })?;
+        Ok(Self {
+            client: Mutex::new(client),
+        })
+    }
+}
+
+impl DecisionLog for PgDecisionLog {
+    fn record(&self, record: &DecisionRecord) {
+        let mut client = self
+            .client
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let result = blocking(|| {
+            client.execute(
+                "INSERT INTO strategy_decisions
+                     (ts_utc, slug, routine, action, side, shares, price, edge, reason, model_id)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+                &[
+                    &record.ts_utc,
+                    &record.slug,
+                    &record.routine,
+                    &record.action.as_str(),
+                    &record.side.map(|side| side.as_str()),
+                    &record.shares,
+                    &record.price,
+                    &record.edge,
+                    &record.reason,
+                    &record.model_id,
+                ],
+            )
+        });
+        if let Err(err) = result {
+            error!(
+                component = "strategy",
+                event = "strategy.decision_log_error",
+                slug = %record.slug,
+                routine = %record.routine,
+                error = %err
+            );
+        }
+    }
+}
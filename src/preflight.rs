@@ -0,0 +1,658 @@
+//! Startup preflight checks.
+//!
+//! Before serving, a process verifies its configuration, stores, upstream reachability
+//! (Gamma, Binance), clock drift, and, in live mode, wallet credentials. Each check
+//! reports pass/warn/fail with a hint on how to fix it. `Fail` is reserved for
+//! critical problems: in live mode any of them refuses the start, in paper mode the
+//! table is printed and startup continues.
+
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tracing::{error, info};
+
+use crate::alerts::AlertConfig;
+use crate::binance_klines::BinanceSymbol;
+use crate::clock_drift::{measure_clock_drift, ClockDriftConfig, ClockDriftLevel};
+use crate::env_vars::{validate_env, EnvValue, Settings};
+use crate::gamma_endpoints::GammaEndpointConfig;
+use crate::kline_store::{KlineStore, KLINE_STORE_SCHEMA_VERSION};
+use crate::persistence::{open_alert_backend, open_kline_reader, PersistenceBackend};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunMode {
+    /// No real orders; preflight failures are reported but do not block startup.
+    #[default]
+    Paper,
+    Live,
+}
+
+impl RunMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Paper => "paper",
+            Self::Live => "live",
+        }
+    }
+
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "paper" => Some(Self::Paper),
+            "live" => Some(Self::Live),
+            _ => None,
+        }
+    }
+}
+
+impl EnvValue for RunMode {
+    fn parse_env(raw: &str) -> Option<Self> {
+        Self::parse(raw)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PreflightStatus {
+    Skip,
+    Pass,
+    Warn,
+    /// A critical failure.
+    Fail,
+}
+
+impl PreflightStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Skip => "SKIP",
+            Self::Pass => "PASS",
+            Self::Warn => "WARN",
+            Self::Fail => "FAIL",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PreflightCheck {
+    pub name: &'static str,
+    pub status: PreflightStatus,
+    pub detail: String,
+    /// What to change when the check did not pass.
+    pub hint: Option<String>,
+}
+
+impl PreflightCheck {
+    fn new(name: &'static str, status: PreflightStatus, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PreflightReport {
+    pub mode: RunMode,
+    pub checks: Vec<PreflightCheck>,
+}
+
+impl PreflightReport {
+    pub fn failures(&self) -> usize {
+        self.count(PreflightStatus::Fail)
+    }
+
+    pub fn warnings(&self) -> usize {
+        self.count(PreflightStatus::Warn)
+    }
+
+    /// `true` when the process must not start: live mode with a critical failure.
+    pub fn blocks_start(&self) -> bool {
+        self.mode == RunMode::Live && self.failures() > 0
+    }
+
+    /// Plain-text pass/fail table with fix hints under failing rows.
+    pub fn render_table(&self) -> String {
+        let mut out = format!("pmm preflight (mode: {})\n", self.mode.as_str());
+        for check in &self.checks {
+            out.push_str(&format!(
+                "  {}  {:<14} {}\n",
+                check.status.as_str(),
+                check.name,
+                check.detail
+            ));
+            if let Some(hint) = &check.hint {
+                out.push_str(&format!("        {:<14} fix: {hint}\n", ""));
+            }
+        }
+        let verdict = if self.blocks_start() {
+            "refusing to start in live mode"
+        } else if self.failures() > 0 {
+            "continuing in paper mode"
+        } else {
+            "ok"
+        };
+        out.push_str(&format!(
+            "RESULT: {} critical, {} warnings; {verdict}\n",
+            self.failures(),
+            self.warnings()
+        ));
+        out
+    }
+
+    fn count(&self, status: PreflightStatus) -> usize {
+        self.checks
+            .iter()
+            .filter(|check| check.status == status)
+            .count()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreflightConfig {
+    pub mode: RunMode,
+    /// Bound on each network probe.
+    pub timeout_ms: u64,
+    pub gamma: GammaEndpointConfig,
+    /// Binance REST ping endpoint; `None` skips the check.
+    pub binance_ping_url: Option<String>,
+    pub clock: ClockDriftConfig,
+    /// SQLite kline store; `None` skips the check.
+    pub kline_store_path: Option<PathBuf>,
+    pub alerts: AlertConfig,
+    pub backend: PersistenceBackend,
+    /// `PMM_*` names in the environment to check against the
+    /// [`ENV_VARS`](crate::ENV_VARS) registry; unknown ones warn.
+    pub env_names: Vec<String>,
+}
+
+impl Default for PreflightConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl PreflightConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_MODE` (`paper|live`), `PMM_PREFLIGHT_TIMEOUT_MS`,
+    /// `PMM_PREFLIGHT_BINANCE_URL` (`off` skips), and the Gamma, clock, store, and alert
+    /// settings the server itself uses.
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            mode: settings.get("PMM_MODE"),
+            timeout_ms: settings.get_where("PMM_PREFLIGHT_TIMEOUT_MS", |ms| *ms > 0),
+            gamma: GammaEndpointConfig::from_settings(settings),
+            binance_ping_url: settings.get("PMM_PREFLIGHT_BINANCE_URL"),
+            clock: ClockDriftConfig::from_settings(settings),
+            kline_store_path: settings.get("PMM_BINANCE_STORE_PATH"),
+            alerts: AlertConfig::from_settings(settings),
+            backend: PersistenceBackend::from_settings(settings),
+            env_names: settings.names(),
+        }
+    }
+}
+
+/// Runs every check against `settings` and logs `preflight.finish`.
+pub async fn run_preflight(cfg: &PreflightConfig, settings: &Settings) -> PreflightReport {
+    run_preflight_with_env(cfg, &|name| settings.value(name)).await
+}
+
+/// [`run_preflight`] with an explicit env lookup (config and wallet checks).
+pub async fn run_preflight_with_env(
+    cfg: &PreflightConfig,
+    env: &(dyn Fn(&str) -> Option<String> + Sync),
+) -> PreflightReport {
+    let timeout = Duration::from_millis(cfg.timeout_ms.max(1));
+    let (gamma, binance, clock) = tokio::join!(
+        check_gamma(&cfg.gamma, timeout),
+        check_binance(cfg.binance_ping_url.as_deref(), timeout),
+        check_clock(&cfg.clock),
+    );
+    let report = PreflightReport {
+        mode: cfg.mode,
+        checks: vec![
+            check_config(env, &cfg.env_names),
+            check_kline_store(&cfg.backend, cfg.kline_store_path.as_ref()),
+            check_alert_store(&cfg.backend, &cfg.alerts),
+            gamma,
+            binance,
+            clock,
+            check_wallet(cfg.mode, env),
+        ],
+    };
+
+    for check in &report.checks {
+        if check.status == PreflightStatus::Fail {
+            error!(
+                component = "preflight",
+                event = "preflight.check_failed",
+                check = check.name,
+                detail = %check.detail
+            );
+        }
+    }
+    info!(
+        component = "preflight",
+        event = "preflight.finish",
+        mode = cfg.mode.as_str(),
+        failures = report.failures(),
+        warnings = report.warnings(),
+        blocks_start = report.blocks_start()
+    );
+    report
+}
+
+fn check_config(
+    env: &(dyn Fn(&str) -> Option<String> + Sync),
+    env_names: &[String],
+) -> PreflightCheck {
+    let validation = validate_env(env, env_names);
+    if !validation.invalid.is_empty() {
+        let problems: Vec<String> = validation
+            .invalid
+            .iter()
+            .map(|invalid| match &invalid.value {
+                Some(value) => format!("{}={value:?}: {}", invalid.name, invalid.problem),
+                None => format!("{}: {}", invalid.name, invalid.problem),
+            })
+            .collect();
+        return PreflightCheck::new("config", PreflightStatus::Fail, problems.join("; "))
+            .hint("fix or unset these variables; invalid values would silently use defaults");
+    }
+    if !validation.unknown.is_empty() {
+        return PreflightCheck::new(
+            "config",
+            PreflightStatus::Warn,
+            format!("unknown variables: {}", validation.unknown.join(", ")),
+        )
+        .hint("nothing reads these; check for typos (`pmm env` lists every variable)");
+    }
+    PreflightCheck::new(
+        "config",
+        PreflightStatus::Pass,
+        format!("{} PMM_* settings valid", validation.set),
+    )
+}
+
+fn check_kline_store(backend: &PersistenceBackend, path: Option<&PathBuf>) -> PreflightCheck {
+    const NAME: &str = "kline_store";
+    let Some(path) = path else {
+        return PreflightCheck::new(NAME, PreflightStatus::Skip, "no store configured");
+    };
+    if let PersistenceBackend::Postgres { .. } = backend {
+        return match open_kline_reader(backend, path)
+            .and_then(|mut store| store.count_range(BinanceSymbol::BtcUsdt, 0, 0))
+        {
+            Ok(_) => {
+                PreflightCheck::new(NAME, PreflightStatus::Pass, "postgres klines_1s readable")
+            }
+            Err(err) => PreflightCheck::new(NAME, PreflightStatus::Fail, err.to_string()).hint(
+                "check PMM_DATABASE_URL and run binance_store_sync once to create the tables",
+            ),
+        };
+    }
+    if !path.exists() {
+        return PreflightCheck::new(
+            NAME,
+            PreflightStatus::Fail,
+            format!("{} does not exist", path.display()),
+        )
+        .hint("run binance_store_sync to create it, or point PMM_BINANCE_STORE_PATH at it");
+    }
+    let store = match KlineStore::open_read_only(path) {
+        Ok(store) => store,
+        Err(err) => {
+            return PreflightCheck::new(
+                NAME,
+                PreflightStatus::Fail,
+                format!("{}: {err}", path.display()),
+            )
+            .hint("check file permissions and that it is a SQLite database")
+        }
+    };
+    let version = match store.schema_version() {
+        Ok(version) => version,
+        Err(err) => return PreflightCheck::new(NAME, PreflightStatus::Fail, err.to_string()),
+    };
+    if let Err(err) = store.count_range(BinanceSymbol::BtcUsdt, 0, 0) {
+        return PreflightCheck::new(NAME, PreflightStatus::Fail, err.to_string())
+            .hint("run binance_store_sync once to migrate the klines_1s layout");
+    }
+    let detail = format!("{} schema v{version}", path.display());
+    if version > KLINE_STORE_SCHEMA_VERSION {
+        PreflightCheck::new(NAME, PreflightStatus::Fail, detail).hint(format!(
+            "written by a newer pmm (supports v{KLINE_STORE_SCHEMA_VERSION}); upgrade this binary"
+        ))
+    } else if version < KLINE_STORE_SCHEMA_VERSION {
+        PreflightCheck::new(NAME, PreflightStatus::Warn, detail)
+            .hint("run binance_store_sync once to stamp the current schema version")
+    } else {
+        PreflightCheck::new(NAME, PreflightStatus::Pass, detail)
+    }
+}
+
+/// Alerting degrades to logs when its store is unusable, so this never fails hard.
+fn check_alert_store(backend: &PersistenceBackend, cfg: &AlertConfig) -> PreflightCheck {
+    const NAME: &str = "alert_store";
+    let Some(path) = &cfg.store_path else {
+        return PreflightCheck::new(NAME, PreflightStatus::Skip, "alerting disabled");
+    };
+    if *backend == PersistenceBackend::Sqlite && !path.exists() {
+        return PreflightCheck::new(
+            NAME,
+            PreflightStatus::Pass,
+            format!("{} will be created on start", path.display()),
+        );
+    }
+    match open_alert_backend(backend, path, cfg.renotify_after_secs)
+        .and_then(|mut store| store.list())
+    {
+        Ok(records) => PreflightCheck::new(
+            NAME,
+            PreflightStatus::Pass,
+            format!("{} ({} alerts)", backend.as_str(), records.len()),
+        ),
+        Err(err) => PreflightCheck::new(NAME, PreflightStatus::Warn, err.to_string())
+            .hint("fix PMM_ALERT_STORE_PATH / PMM_DATABASE_URL, or set PMM_ALERT_STORE_PATH=off"),
+    }
+}
+
+async fn probe(client: &reqwest::Client, url: &str) -> Result<Duration, String> {
+    let started = Instant::now();
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|err| err.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("HTTP {}", response.status()));
+    }
+    Ok(started.elapsed())
+}
+
+fn http_client(timeout: Duration) -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .timeout(timeout)
+        .build()
+        .map_err(|err| err.to_string())
+}
+
+async fn check_gamma(cfg: &GammaEndpointConfig, timeout: Duration) -> PreflightCheck {
+    const NAME: &str = "gamma";
+    let client = match http_client(timeout) {
+        Ok(client) => client,
+        Err(err) => return PreflightCheck::new(NAME, PreflightStatus::Fail, err),
+    };
+    let mut reachable = Vec::new();
+    let mut failed = Vec::new();
+    for base_url in &cfg.base_urls {
+        let url = format!("{}/markets?limit=1", base_url.trim_end_matches('/'));
+        match probe(&client, &url).await {
+            Ok(latency) => reachable.push(format!("{base_url} {}ms", latency.as_millis())),
+            Err(err) => failed.push(format!("{base_url}: {err}")),
+        }
+    }
+    let total = cfg.base_urls.len();
+    if reachable.is_empty() {
+        PreflightCheck::new(NAME, PreflightStatus::Fail, failed.join("; "))
+            .hint("check outbound network access or add a mirror to PMM_GAMMA_BASE_URLS")
+    } else if !failed.is_empty() {
+        PreflightCheck::new(
+            NAME,
+            PreflightStatus::Warn,
+            format!(
+                "{}/{total} endpoints reachable ({}); down: {}",
+                reachable.len(),
+                reachable.join(", "),
+                failed.join("; ")
+            ),
+        )
+        .hint("lookups will fail over; remove or fix the unreachable endpoint")
+    } else {
+        PreflightCheck::new(
+            NAME,
+            PreflightStatus::Pass,
+            format!(
+                "{total}/{total} endpoints reachable ({})",
+                reachable.join(", ")
+            ),
+        )
+    }
+}
+
+async fn check_binance(url: Option<&str>, timeout: Duration) -> PreflightCheck {
+    const NAME: &str = "binance";
+    let Some(url) = url else {
+        return PreflightCheck::new(NAME, PreflightStatus::Skip, "PMM_PREFLIGHT_BINANCE_URL=off");
+    };
+    let result = match http_client(timeout) {
+        Ok(client) => probe(&client, url).await,
+        Err(err) => Err(err),
+    };
+    match result {
+        Ok(latency) => PreflightCheck::new(
+            NAME,
+            PreflightStatus::Pass,
+            format!("{url} {}ms", latency.as_millis()),
+        ),
+        Err(err) => PreflightCheck::new(NAME, PreflightStatus::Fail, format!("{url}: {err}"))
+            .hint("check outbound network access; Binance blocks some regions (HTTP 451)"),
+    }
+}
+
+async fn check_clock(cfg: &ClockDriftConfig) -> PreflightCheck {
+    const NAME: &str = "clock";
+    if cfg.ntp_server.is_none() && cfg.binance_time_url.is_none() {
+        return PreflightCheck::new(NAME, PreflightStatus::Skip, "no clock source configured");
+    }
+    let status = measure_clock_drift(cfg).await;
+    let offset = status
+        .offset_ms
+        .map(|offset| format!("offset {offset}ms"))
+        .unwrap_or_else(|| "no offset".to_string());
+    let sync_hint = "sync the host clock (chrony/ntpd) before trading";
+    match status.level {
+        ClockDriftLevel::Ok => PreflightCheck::new(NAME, PreflightStatus::Pass, offset),
+        ClockDriftLevel::Warn => PreflightCheck::new(
+            NAME,
+            PreflightStatus::Warn,
+            format!("{offset} (warn at {}ms)", cfg.warn_threshold_ms),
+        )
+        .hint(sync_hint),
+        ClockDriftLevel::Alert => PreflightCheck::new(
+            NAME,
+            PreflightStatus::Fail,
+            format!("{offset} (alert at {}ms)", cfg.alert_threshold_ms),
+        )
+        .hint(sync_hint),
+        ClockDriftLevel::Unknown => {
+            let errors = status
+                .errors
+                .iter()
+                .map(|err| format!("{}: {}", err.source.as_str(), err.error))
+                .collect::<Vec<_>>();
+            PreflightCheck::new(NAME, PreflightStatus::Warn, errors.join("; ")).hint(
+                "allow UDP 123 to PMM_CLOCK_NTP_SERVER or HTTPS to PMM_CLOCK_BINANCE_TIME_URL",
+            )
+        }
+    }
+}
+
+const CLOB_API_ENV: [&str; 3] = [
+    "PMM_CLOB_API_KEY",
+    "PMM_CLOB_API_SECRET",
+    "PMM_CLOB_API_PASSPHRASE",
+];
+
+/// Live mode needs `PMM_WALLET_PRIVATE_KEY` (32-byte hex, optional `0x`) and either
+/// all or none of the CLOB API credentials. Values are never echoed.
+fn check_wallet(mode: RunMode, env: &(dyn Fn(&str) -> Option<String> + Sync)) -> PreflightCheck {
+    const NAME: &str = "wallet";
+    if mode == RunMode::Paper {
+        return PreflightCheck::new(NAME, PreflightStatus::Skip, "paper mode");
+    }
+    let non_empty = |name: &str| env(name).filter(|value| !value.trim().is_empty());
+    let Some(key) = non_empty("PMM_WALLET_PRIVATE_KEY") else {
+        return PreflightCheck::new(
+            NAME,
+            PreflightStatus::Fail,
+            "PMM_WALLET_PRIVATE_KEY not set",
+        )
+        .hint("export the trading wallet key, or run with PMM_MODE=paper");
+    };
+    let hex = key.trim().trim_start_matches("0x");
+    if hex.len() != 64 || !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return PreflightCheck::new(
+            NAME,
+            PreflightStatus::Fail,
+            "PMM_WALLET_PRIVATE_KEY is not 32-byte hex",
+        )
+        .hint("expected 64 hex characters, optionally prefixed with 0x");
+    }
+    let missing = CLOB_API_ENV
+        .iter()
+        .filter(|name| non_empty(name).is_none())
+        .copied()
+        .collect::<Vec<_>>();
+    match missing.len() {
+        0 => PreflightCheck::new(
+            NAME,
+            PreflightStatus::Pass,
+            "private key and CLOB API credentials set",
+        ),
+        n if n == CLOB_API_ENV.len() => PreflightCheck::new(
+            NAME,
+            PreflightStatus::Pass,
+            "private key set; no CLOB API credentials",
+        ),
+        _ => PreflightCheck::new(
+            NAME,
+            PreflightStatus::Fail,
+            format!(
+                "partial CLOB API credentials; missing {}",
+                missing.join(", ")
+            ),
+        )
+        .hint("set all of PMM_CLOB_API_KEY/SECRET/PASSPHRASE or none"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env_from(pairs: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> + Sync {
+        let pairs = pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect::<Vec<_>>();
+        move |name| {
+            pairs
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.clone())
+        }
+    }
+
+    #[test]
+    fn config_check_names_each_invalid_variable() {
+        let check = check_config(
+            &env_from(&[
+                ("PMM_DASHBOARD_ADDR", "localhost"),
+                ("PMM_DISCOVERY_TIMEOUT_MS", "5s"),
+                ("PMM_LOG_FORMAT", "JSON"),
+                (
+                    "PMM_GAMMA_BASE_URLS",
+                    "https://a.example, https://b.example",
+                ),
+            ]),
+            &[],
+        );
+        assert_eq!(check.status, PreflightStatus::Fail);
+        assert!(check.detail.contains("PMM_DASHBOARD_ADDR"));
+        assert!(check.detail.contains("PMM_DISCOVERY_TIMEOUT_MS"));
+        assert!(!check.detail.contains("PMM_LOG_FORMAT"));
+        assert!(!check.detail.contains("PMM_GAMMA_BASE_URLS"));
+
+        let check = check_config(&env_from(&[("PMM_MODE", "live")]), &[]);
+        assert_eq!(check.status, PreflightStatus::Pass);
+
+        let names = ["PMM_MODE".to_string(), "PMM_MDOE".to_string()];
+        let check = check_config(&env_from(&[("PMM_MODE", "live")]), &names);
+        assert_eq!(check.status, PreflightStatus::Warn);
+        assert!(check.detail.contains("PMM_MDOE"));
+    }
+
+    #[test]
+    fn wallet_check_only_applies_in_live_mode_and_never_echoes_the_key() {
+        let key = "ab".repeat(32);
+        assert_eq!(
+            check_wallet(RunMode::Paper, &env_from(&[])).status,
+            PreflightStatus::Skip
+        );
+        assert_eq!(
+            check_wallet(RunMode::Live, &env_from(&[])).status,
+            PreflightStatus::Fail
+        );
+        let bad = check_wallet(
+            RunMode::Live,
+            &env_from(&[("PMM_WALLET_PRIVATE_KEY", "0x1234secret")]),
+        );
+        assert_eq!(bad.status, PreflightStatus::Fail);
+        assert!(!bad.detail.contains("secret"));
+
+        let key_env = format!("0x{key}");
+        let partial = check_wallet(
+            RunMode::Live,
+            &env_from(&[
+                ("PMM_WALLET_PRIVATE_KEY", &key_env),
+                ("PMM_CLOB_API_KEY", "k"),
+            ]),
+        );
+        assert_eq!(partial.status, PreflightStatus::Fail);
+        assert!(partial.detail.contains("PMM_CLOB_API_SECRET"));
+        assert_eq!(
+            check_wallet(
+                RunMode::Live,
+                &env_from(&[("PMM_WALLET_PRIVATE_KEY", &key)])
+            )
+            .status,
+            PreflightStatus::Pass
+        );
+    }
+
+    #[test]
+    fn only_live_mode_critical_failures_block_start() {
+        let mut report = PreflightReport {
+            mode: RunMode::Paper,
+            checks: vec![
+                PreflightCheck::new("config", PreflightStatus::Pass, "ok"),
+                PreflightCheck::new("gamma", PreflightStatus::Fail, "down").hint("check network"),
+                PreflightCheck::new("clock", PreflightStatus::Warn, "offset 300ms"),
+            ],
+        };
+        assert!(!report.blocks_start());
+        assert!(report.render_table().contains("continuing in paper mode"));
+
+        report.mode = RunMode::Live;
+        assert!(report.blocks_start());
+        let table = report.render_table();
+        assert!(table.contains("FAIL  gamma"));
+        assert!(table.contains("fix: check network"));
+        assert!(table.contains("RESULT: 1 critical, 1 warnings; refusing to start in live mode"));
+
+        report.checks[1].status = PreflightStatus::Warn;
+        assert!(!report.blocks_start());
+    }
+}
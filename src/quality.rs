@@ -0,0 +1,643 @@
+//! Weekly data-quality scorecard and `/quality`.
+//!
+//! A background job keeps one scorecard per ISO week (Monday 00:00 UTC onwards) so data
+//! health trends are visible before they turn into incidents:
+//! - kline coverage, gaps, and quarantined anomalies per symbol, read from the kline store
+//! - Gamma resolution success rate and average discovery cycle latency, accumulated from
+//!   the live discovery loop
+//! - snapshot uptime: the share of the week the dashboard served its source's rows rather
+//!   than demo or placeholder fallbacks; time the process was not running counts as down
+//!
+//! Scorecards are kept in SQLite and the current week is rewritten on every job tick.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+
+use axum::extract::{Query, State};
+use axum::routing::get;
+use axum::{Json, Router};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{debug, info, warn};
+
+use crate::binance_klines::ALL_BINANCE_SYMBOLS;
+use crate::dashboard::{DashboardSnapshotSource, SnapshotProvenance, SnapshotStatus};
+use crate::env_vars::Settings;
+use crate::kline_store::{KlineStore, KlineStoreError};
+
+const WEEK_SECS: i64 = 7 * 86_400;
+const DEFAULT_QUALITY_WEEKS: usize = 12;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QualityConfig {
+    /// SQLite file holding the scorecards; `None` keeps them in memory only.
+    pub store_path: Option<PathBuf>,
+    /// Kline store scored for coverage; `None` leaves the kline section empty.
+    pub kline_store_path: Option<PathBuf>,
+    /// How often the current week is rescored and snapshot uptime sampled.
+    pub interval_secs: u64,
+}
+
+impl Default for QualityConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl QualityConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_QUALITY_STORE_PATH` (`off` keeps scorecards in memory),
+    /// `PMM_BINANCE_STORE_PATH`, and `PMM_QUALITY_INTERVAL_SECS`, falling back to defaults
+    /// for unset or invalid values.
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            store_path: settings.get("PMM_QUALITY_STORE_PATH"),
+            kline_store_path: Some(settings.get("PMM_BINANCE_STORE_PATH")),
+            interval_secs: settings.get_where("PMM_QUALITY_INTERVAL_SECS", |secs| *secs > 0),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum QualityError {
+    #[error("sqlite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid stored scorecard: {0}")]
+    InvalidRow(#[from] serde_json::Error),
+}
+
+/// Discovery cycle counters; the live loop adds to a process-wide total that the
+/// scorecard job drains on each tick.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiscoveryCycleTotals {
+    pub cycles: u64,
+    /// Cycles that failed as a whole (batch error or deadline exceeded).
+    pub failed_cycles: u64,
+    pub scheduled_lookups: u64,
+    pub resolved_lookups: u64,
+    /// Lookups that failed on Gamma's side; a market not listed yet is not a failure.
+    pub failed_lookups: u64,
+    pub latency_ms_sum: u64,
+}
+
+impl DiscoveryCycleTotals {
+    pub fn add(&mut self, other: &Self) {
+        self.cycles += other.cycles;
+        self.failed_cycles += other.failed_cycles;
+        self.scheduled_lookups += other.scheduled_lookups;
+        self.resolved_lookups += other.resolved_lookups;
+        self.failed_lookups += other.failed_lookups;
+        self.latency_ms_sum += other.latency_ms_sum;
+    }
+}
+
+static DISCOVERY_TOTALS: Mutex<DiscoveryCycleTotals> = Mutex::new(DiscoveryCycleTotals {
+    cycles: 0,
+    failed_cycles: 0,
+    scheduled_lookups: 0,
+    resolved_lookups: 0,
+    failed_lookups: 0,
+    latency_ms_sum: 0,
+});
+
+/// Adds one discovery cycle to the process-wide totals.
+#[cfg_attr(not(feature = "discovery-sdk"), allow(dead_code))]
+pub(crate) fn record_discovery_cycle(
+    scheduled: usize,
+    resolved: usize,
+    failed: usize,
+    cycle_failed: bool,
+    duration_ms: u64,
+) {
+    let mut totals = DISCOVERY_TOTALS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    totals.add(&DiscoveryCycleTotals {
+        cycles: 1,
+        failed_cycles: u64::from(cycle_failed),
+        scheduled_lookups: scheduled as u64,
+        resolved_lookups: resolved as u64,
+        failed_lookups: failed as u64,
+        latency_ms_sum: duration_ms,
+    });
+}
+
+fn take_discovery_totals() -> DiscoveryCycleTotals {
+    std::mem::take(
+        &mut *DISCOVERY_TOTALS
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()),
+    )
+}
+
+/// Kline health of one symbol over the scored part of a week.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KlineQuality {
+    pub symbol: String,
+    pub expected_rows: u64,
+    pub stored_rows: u64,
+    pub coverage_pct: f64,
+    /// Runs of missing seconds, including at the start and end of the range.
+    pub gap_count: u64,
+    pub total_gap_secs: u64,
+    pub longest_gap_secs: u64,
+    /// Rows quarantined by ingestion validation.
+    pub anomaly_count: u64,
+}
+
+/// Scores the 1s klines of `symbol` in `[start_ts_ms, end_ts_ms_exclusive)`.
+pub fn score_klines(
+    store: &KlineStore,
+    symbol: crate::binance_klines::BinanceSymbol,
+    start_ts_ms: i64,
+    end_ts_ms_exclusive: i64,
+) -> Result<KlineQuality, KlineStoreError> {
+    let expected_rows = ((end_ts_ms_exclusive - start_ts_ms).max(0) / 1_000) as u64;
+    let rows = store.closes_range(symbol, start_ts_ms, end_ts_ms_exclusive)?;
+    let mut gaps = Vec::new();
+    let mut next_ts_ms = start_ts_ms;
+    for (open_time_ms, _) in &rows {
+        if *open_time_ms > next_ts_ms {
+            gaps.push(((open_time_ms - next_ts_ms) / 1_000) as u64);
+        }
+        next_ts_ms = next_ts_ms.max(open_time_ms + 1_000);
+    }
+    if end_ts_ms_exclusive > next_ts_ms {
+        gaps.push(((end_ts_ms_exclusive - next_ts_ms) / 1_000) as u64);
+    }
+    Ok(KlineQuality {
+        symbol: symbol.as_str().to_string(),
+        expected_rows,
+        stored_rows: rows.len() as u64,
+        coverage_pct: percent(rows.len() as u64, expected_rows).unwrap_or(0.0),
+        gap_count: gaps.len() as u64,
+        total_gap_secs: gaps.iter().sum(),
+        longest_gap_secs: gaps.iter().copied().max().unwrap_or(0),
+        anomaly_count: store.count_rejected_range(symbol, start_ts_ms, end_ts_ms_exclusive)?,
+    })
+}
+
+/// Data health of one ISO week.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QualityScorecard {
+    pub week_start_ts_utc: i64,
+    pub week_end_ts_utc: i64,
+    /// Last time the scorecard was advanced; uptime is sampled up to here.
+    pub updated_ts_utc: i64,
+    pub klines: Vec<KlineQuality>,
+    /// Stored over expected rows across symbols; `None` without a kline store.
+    pub kline_coverage_pct: Option<f64>,
+    pub kline_gap_count: u64,
+    pub kline_longest_gap_secs: u64,
+    pub kline_anomaly_count: u64,
+    pub discovery: DiscoveryCycleTotals,
+    /// Resolved over resolved-or-failed lookups.
+    pub gamma_resolution_success_pct: Option<f64>,
+    pub avg_discovery_latency_ms: Option<f64>,
+    pub snapshot_observed_secs: i64,
+    pub snapshot_up_secs: i64,
+    pub snapshot_uptime_pct: Option<f64>,
+}
+
+impl QualityScorecard {
+    /// Empty scorecard for the week containing `now_ts_utc`, observed from then on.
+    pub fn new(now_ts_utc: i64) -> Self {
+        let week_start_ts_utc = week_start_ts_utc(now_ts_utc);
+        Self {
+            week_start_ts_utc,
+            week_end_ts_utc: week_start_ts_utc + WEEK_SECS,
+            updated_ts_utc: now_ts_utc,
+            klines: Vec::new(),
+            kline_coverage_pct: None,
+            kline_gap_count: 0,
+            kline_longest_gap_secs: 0,
+            kline_anomaly_count: 0,
+            discovery: DiscoveryCycleTotals::default(),
+            gamma_resolution_success_pct: None,
+            avg_discovery_latency_ms: None,
+            snapshot_observed_secs: 0,
+            snapshot_up_secs: 0,
+            snapshot_uptime_pct: None,
+        }
+    }
+
+    /// Counts the time since the last update as up or down. At most `max_up_secs` of
+    /// it counts as up: a longer silence means the job (or process) was not running.
+    pub fn observe_snapshot(&mut self, up: bool, now_ts_utc: i64, max_up_secs: i64) {
+        let now_ts_utc = now_ts_utc.min(self.week_end_ts_utc);
+        let elapsed = (now_ts_utc - self.updated_ts_utc).max(0);
+        self.snapshot_observed_secs += elapsed;
+        if up {
+            self.snapshot_up_secs += elapsed.min(max_up_secs);
+        }
+        self.updated_ts_utc = self.updated_ts_utc.max(now_ts_utc);
+        self.snapshot_uptime_pct = percent(
+            self.snapshot_up_secs as u64,
+            self.snapshot_observed_secs as u64,
+        );
+    }
+
+    pub fn add_discovery(&mut self, totals: &DiscoveryCycleTotals) {
+        self.discovery.add(totals);
+        let discovery = &self.discovery;
+        self.gamma_resolution_success_pct = percent(
+            discovery.resolved_lookups,
+            discovery.resolved_lookups + discovery.failed_lookups,
+        );
+        self.avg_discovery_latency_ms = (discovery.cycles > 0)
+            .then(|| discovery.latency_ms_sum as f64 / discovery.cycles as f64);
+    }
+
+    pub fn set_klines(&mut self, klines: Vec<KlineQuality>) {
+        let expected = klines.iter().map(|kline| kline.expected_rows).sum();
+        let stored = klines.iter().map(|kline| kline.stored_rows).sum();
+        self.kline_coverage_pct = percent(stored, expected);
+        self.kline_gap_count = klines.iter().map(|kline| kline.gap_count).sum();
+        self.kline_longest_gap_secs = klines
+            .iter()
+            .map(|kline| kline.longest_gap_secs)
+            .max()
+            .unwrap_or(0);
+        self.kline_anomaly_count = klines.iter().map(|kline| kline.anomaly_count).sum();
+        self.klines = klines;
+    }
+}
+
+fn percent(part: u64, whole: u64) -> Option<f64> {
+    (whole > 0).then(|| part as f64 * 100.0 / whole as f64)
+}
+
+/// Monday 00:00 UTC of the ISO week containing `ts_utc`.
+pub fn week_start_ts_utc(ts_utc: i64) -> i64 {
+    let day = ts_utc.div_euclid(86_400);
+    // 1970-01-01 was a Thursday, three days after a Monday.
+    (day - (day + 3).rem_euclid(7)) * 86_400
+}
+
+/// Whether a snapshot source is serving its own rows rather than a fallback.
+pub fn snapshot_up(status: &SnapshotStatus) -> bool {
+    matches!(
+        status.provenance,
+        SnapshotProvenance::Live | SnapshotProvenance::Static
+    ) && !status.live_unavailable()
+}
+
+/// Persisted scorecards; clones share the same data.
+#[derive(Clone, Default)]
+pub struct QualityScorecards {
+    cards: Arc<RwLock<BTreeMap<i64, QualityScorecard>>>,
+    conn: Option<Arc<Mutex<Connection>>>,
+}
+
+impl QualityScorecards {
+    /// Scorecards that are not persisted.
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Opens (creating if needed) the SQLite store at `path` and loads its scorecards.
+    pub fn open(path: &Path) -> Result<Self, QualityError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "
+            PRAGMA journal_mode=WAL;
+            CREATE TABLE IF NOT EXISTS quality_scorecards (
+                week_start_ts_utc INTEGER PRIMARY KEY,
+                scorecard_json TEXT NOT NULL
+            );
+            ",
+        )?;
+        let mut cards = BTreeMap::new();
+        {
+            let mut stmt = conn.prepare("SELECT scorecard_json FROM quality_scorecards")?;
+            let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+            for row in rows {
+                let card: QualityScorecard = serde_json::from_str(&row?)?;
+                cards.insert(card.week_start_ts_utc, card);
+            }
+        }
+        Ok(Self {
+            cards: Arc::new(RwLock::new(cards)),
+            conn: Some(Arc::new(Mutex::new(conn))),
+        })
+    }
+
+    /// Opens the configured store; falls back to in-memory scorecards when it cannot be
+    /// opened (logged as `quality.scorecards.unpersisted`).
+    pub fn from_config(cfg: &QualityConfig) -> Self {
+        let Some(path) = &cfg.store_path else {
+            return Self::in_memory();
+        };
+        match Self::open(path) {
+            Ok(cards) => cards,
+            Err(err) => {
+                warn!(
+                    component = "quality",
+                    event = "quality.scorecards.unpersisted",
+                    path = %path.display(),
+                    error = %err
+                );
+                Self::in_memory()
+            }
+        }
+    }
+
+    /// Stores `card`, replacing the scorecard of the same week.
+    pub fn upsert(&self, card: QualityScorecard) -> Result<(), QualityError> {
+        if let Some(conn) = &self.conn {
+            conn.lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .execute(
+                    "INSERT OR REPLACE INTO quality_scorecards
+                     (week_start_ts_utc, scorecard_json) VALUES (?1, ?2)",
+                    params![card.week_start_ts_utc, serde_json::to_string(&card)?],
+                )?;
+        }
+        self.cards
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(card.week_start_ts_utc, card);
+        Ok(())
+    }
+
+    /// The latest `weeks` scorecards, newest first.
+    pub fn latest(&self, weeks: usize) -> Vec<QualityScorecard> {
+        self.cards
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .values()
+            .rev()
+            .take(weeks)
+            .cloned()
+            .collect()
+    }
+
+    /// Advances the scorecard of the current week to `now_ts_utc`, finalizing the
+    /// previous week first when the job crossed a week boundary. Logs
+    /// `quality.scorecard.finalized` for a closed week.
+    pub fn tick(
+        &self,
+        up: bool,
+        discovery: &DiscoveryCycleTotals,
+        kline_store_path: Option<&Path>,
+        max_up_secs: i64,
+        now_ts_utc: i64,
+    ) -> Result<QualityScorecard, QualityError> {
+        let week = week_start_ts_utc(now_ts_utc);
+        let latest = self.latest(1).into_iter().next();
+        let mut card = match latest {
+            Some(mut previous) if previous.week_start_ts_utc < week => {
+                previous.observe_snapshot(up, previous.week_end_ts_utc, max_up_secs);
+                score_kline_store(&mut previous, kline_store_path);
+                self.upsert(previous.clone())?;
+                info!(
+                    component = "quality",
+                    event = "quality.scorecard.finalized",
+                    week_start_ts_utc = previous.week_start_ts_utc,
+                    kline_coverage_pct = previous.kline_coverage_pct,
+                    gamma_resolution_success_pct = previous.gamma_resolution_success_pct,
+                    snapshot_uptime_pct = previous.snapshot_uptime_pct
+                );
+                // The gap between the last tick and the boundary went to the old week.
+                let mut card = QualityScorecard::new(week);
+                card.updated_ts_utc = week.max(previous.updated_ts_utc);
+                card
+            }
+            Some(current) if current.week_start_ts_utc == week => current,
+            _ => QualityScorecard::new(now_ts_utc),
+        };
+        card.observe_snapshot(up, now_ts_utc, max_up_secs);
+        card.add_discovery(discovery);
+        score_kline_store(&mut card, kline_store_path);
+        self.upsert(card.clone())?;
+        Ok(card)
+    }
+
+    /// Rescores the current week every `interval_secs`, sampling `source` for uptime
+    /// and draining the discovery totals.
+    pub fn spawn_job(&self, source: Arc<dyn DashboardSnapshotSource>, cfg: &QualityConfig) {
+        let cards = self.clone();
+        let cfg = cfg.clone();
+        tokio::spawn(async move {
+            let mut ticker =
+                tokio::time::interval(std::time::Duration::from_secs(cfg.interval_secs));
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            loop {
+                ticker.tick().await;
+                let up = snapshot_up(&source.status());
+                let discovery = take_discovery_totals();
+                let cards = cards.clone();
+                let kline_store_path = cfg.kline_store_path.clone();
+                let max_up_secs = 2 * cfg.interval_secs as i64;
+                let scored = tokio::task::spawn_blocking(move || {
+                    cards.tick(
+                        up,
+                        &discovery,
+                        kline_store_path.as_deref(),
+                        max_up_secs,
+                        crate::faults::now_utc().timestamp(),
+                    )
+                })
+                .await;
+                match scored {
+                    Ok(Ok(card)) => debug!(
+                        component = "quality",
+                        event = "quality.scorecard.updated",
+                        week_start_ts_utc = card.week_start_ts_utc,
+                        kline_coverage_pct = card.kline_coverage_pct,
+                        gamma_resolution_success_pct = card.gamma_resolution_success_pct,
+                        snapshot_uptime_pct = card.snapshot_uptime_pct
+                    ),
+                    Ok(Err(err)) => warn!(
+                        component = "quality",
+                        event = "quality.scorecard.error",
+                        error = %err
+                    ),
+                    Err(err) => warn!(
+                        component = "quality",
+                        event = "quality.scorecard.error",
+                        error = %err
+                    ),
+                }
+            }
+        });
+    }
+}
+
+impl std::fmt::Debug for QualityScorecards {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QualityScorecards")
+            .field(
+                "weeks",
+                &self
+                    .cards
+                    .read()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .len(),
+            )
+            .field("persisted", &self.conn.is_some())
+            .finish()
+    }
+}
+
+/// Scores the week of `card` up to its last update. A missing or unreadable store
+/// leaves the kline section as it was (logged as `quality.klines.unavailable`).
+fn score_kline_store(card: &mut QualityScorecard, kline_store_path: Option<&Path>) {
+    let Some(path) = kline_store_path else {
+        return;
+    };
+    let start_ms = card.week_start_ts_utc * 1_000;
+    let end_ms = card.updated_ts_utc.min(card.week_end_ts_utc) * 1_000;
+    let scored = KlineStore::open_read_only(path).and_then(|store| {
+        ALL_BINANCE_SYMBOLS
+            .iter()
+            .map(|symbol| score_klines(&store, *symbol, start_ms, end_ms))
+            .collect::<Result<Vec<_>, _>>()
+    });
+    match scored {
+        Ok(klines) => card.set_klines(klines),
+        Err(err) => warn!(
+            component = "quality",
+            event = "quality.klines.unavailable",
+            path = %path.display(),
+            error = %err
+        ),
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct QualityQuery {
+    /// Number of weeks to return, newest first (default 12).
+    pub weeks: Option<usize>,
+}
+
+/// Router serving `GET /quality?weeks=N`: the latest weekly scorecards, newest first.
+pub fn quality_router(cards: QualityScorecards) -> Router {
+    Router::new()
+        .route("/quality", get(get_quality))
+        .with_state(cards)
+}
+
+async fn get_quality(
+    State(cards): State<QualityScorecards>,
+    Query(query): Query<QualityQuery>,
+) -> Json<Vec<QualityScorecard>> {
+    Json(cards.latest(query.weeks.unwrap_or(DEFAULT_QUALITY_WEEKS)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binance_klines::{BinanceSymbol, Kline1s};
+    use crate::kline_validation::KlineValidationConfig;
+
+    // Monday 2026-03-02 00:00:00 UTC.
+    const MONDAY: i64 = 1_772_409_600;
+
+    fn kline(open_time_ms: i64) -> Kline1s {
+        Kline1s {
+            open_time_ms,
+            open: 100.0,
+            high: 100.5,
+            low: 99.5,
+            close: 100.0,
+            volume: 1.0,
+            close_time_ms: open_time_ms + 999,
+            quote_asset_volume: 100.0,
+            trade_count: 1,
+            taker_buy_base_volume: 0.5,
+            taker_buy_quote_volume: 50.0,
+        }
+    }
+
+    #[test]
+    fn weeks_start_on_monday_utc() {
+        assert_eq!(week_start_ts_utc(MONDAY), MONDAY);
+        assert_eq!(week_start_ts_utc(MONDAY + WEEK_SECS - 1), MONDAY);
+        assert_eq!(week_start_ts_utc(MONDAY - 1), MONDAY - WEEK_SECS);
+        assert_eq!(week_start_ts_utc(0), -3 * 86_400);
+    }
+
+    #[test]
+    fn kline_gaps_count_missing_runs_including_the_edges() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("klines_1s.sqlite");
+        let mut store = KlineStore::open(&path, KlineValidationConfig::default()).unwrap();
+        let start_ms = MONDAY * 1_000;
+        // Seconds 2..=9 and 15..=17 of a 20 second range.
+        let rows = (2..10)
+            .chain(15..18)
+            .map(|sec| kline(start_ms + sec * 1_000))
+            .collect();
+        store.upsert_rows(BinanceSymbol::BtcUsdt, rows).unwrap();
+
+        let quality =
+            score_klines(&store, BinanceSymbol::BtcUsdt, start_ms, start_ms + 20_000).unwrap();
+        assert_eq!((quality.expected_rows, quality.stored_rows), (20, 11));
+        assert_eq!(quality.coverage_pct, 55.0);
+        assert_eq!(quality.gap_count, 3);
+        assert_eq!(quality.total_gap_secs, 9);
+        assert_eq!(quality.longest_gap_secs, 5);
+        assert_eq!(quality.anomaly_count, 0);
+    }
+
+    #[test]
+    fn ticks_accumulate_the_week_and_finalize_it_at_the_boundary() {
+        let cards = QualityScorecards::in_memory();
+        let discovery = DiscoveryCycleTotals {
+            cycles: 4,
+            failed_cycles: 0,
+            scheduled_lookups: 40,
+            resolved_lookups: 30,
+            failed_lookups: 2,
+            latency_ms_sum: 2_000,
+        };
+        let end = MONDAY + WEEK_SECS;
+        cards.tick(true, &discovery, None, 600, end - 900).unwrap();
+        cards.tick(true, &discovery, None, 600, end - 600).unwrap();
+        // Down for the next sample.
+        cards
+            .tick(
+                false,
+                &DiscoveryCycleTotals::default(),
+                None,
+                600,
+                end - 300,
+            )
+            .unwrap();
+
+        // The first tick of the next week closes the old one up to the boundary.
+        let current = cards
+            .tick(true, &DiscoveryCycleTotals::default(), None, 600, end + 120)
+            .unwrap();
+        assert_eq!(current.week_start_ts_utc, end);
+        assert_eq!(current.snapshot_observed_secs, 120);
+        assert_eq!(current.snapshot_uptime_pct, Some(100.0));
+
+        let weeks = cards.latest(12);
+        assert_eq!(weeks.len(), 2);
+        let closed = &weeks[1];
+        assert_eq!(closed.week_start_ts_utc, MONDAY);
+        assert_eq!(closed.updated_ts_utc, end);
+        assert_eq!(closed.snapshot_observed_secs, 900);
+        assert_eq!(closed.snapshot_up_secs, 300 + 300);
+        assert_eq!(closed.discovery.cycles, 8);
+        assert_eq!(
+            closed.gamma_resolution_success_pct,
+            Some(60.0 * 100.0 / 64.0)
+        );
+        assert_eq!(closed.avg_discovery_latency_ms, Some(500.0));
+        assert_eq!(closed.kline_coverage_pct, None);
+    }
+}
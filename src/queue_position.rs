@@ -0,0 +1,238 @@
+//! Maker queue position estimates for resting orders.
+//!
+//! Books are price-time priority: a new order joins the back of its price level, so
+//! everything displayed there when it is placed is ahead of it. From then on the
+//! estimate follows the market data of the order's outcome token:
+//! - trades at the level consume the queue from the front, the size ahead first and
+//!   then the order itself; a trade through the level means the level was cleared
+//! - a level shrinking by more than the trades explain is cancels, taken from ahead of
+//!   and behind the order in proportion to their sizes
+//! - a level growing is new orders joining behind
+//!
+//! Fill probability depends mostly on the size ahead; [`FillModel::queued_maker_fill`]
+//! prices an estimate with the calibrated taker flow.
+//!
+//! [`FillModel::queued_maker_fill`]: crate::fill_model::FillModel::queued_maker_fill
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::fill_model::{OrderSide, TradePrint};
+use crate::sizing::SizingSide;
+
+/// One of our resting limit orders. Prices and sizes are in the outcome token.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RestingOrder {
+    pub order_id: String,
+    pub slug: String,
+    pub outcome: SizingSide,
+    pub side: OrderSide,
+    pub price: f64,
+    pub size: f64,
+    pub placed_ts_ms: i64,
+}
+
+/// Estimated queue state of one resting order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueueEstimate {
+    pub order: RestingOrder,
+    /// Shares estimated ahead of the order at its price.
+    pub ahead: f64,
+    /// Shares estimated behind it (joined later).
+    pub behind: f64,
+    /// Shares of the order estimated filled.
+    pub filled: f64,
+    pub updated_ts_ms: i64,
+}
+
+impl QueueEstimate {
+    pub fn remaining(&self) -> f64 {
+        (self.order.size - self.filled).max(0.0)
+    }
+
+    /// Position in the level as a fraction of the other displayed size: `0` at the
+    /// front, `1` at the back. `None` when the order is alone at its price.
+    pub fn queue_fraction(&self) -> Option<f64> {
+        let others = self.ahead + self.behind;
+        (others > 0.0).then(|| self.ahead / others)
+    }
+}
+
+/// Queue estimates of our open orders, fed with the book and trade updates of their
+/// outcome tokens.
+#[derive(Debug, Clone, Default)]
+pub struct QueuePositionTracker {
+    orders: HashMap<String, QueueEstimate>,
+}
+
+impl QueuePositionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts tracking `order` behind `displayed_size`, the size shown at its price
+    /// just before it was placed. Replaces an order with the same id.
+    pub fn place(&mut self, order: RestingOrder, displayed_size: f64) {
+        let estimate = QueueEstimate {
+            ahead: displayed_size.max(0.0),
+            behind: 0.0,
+            filled: 0.0,
+            updated_ts_ms: order.placed_ts_ms,
+            order,
+        };
+        self.orders
+            .insert(estimate.order.order_id.clone(), estimate);
+    }
+
+    /// Stops tracking an order (cancelled or done).
+    pub fn remove(&mut self, order_id: &str) -> Option<QueueEstimate> {
+        self.orders.remove(order_id)
+    }
+
+    pub fn get(&self, order_id: &str) -> Option<&QueueEstimate> {
+        self.orders.get(order_id)
+    }
+
+    /// Every tracked order, by slug then order id.
+    pub fn estimates(&self) -> Vec<QueueEstimate> {
+        let mut out = self.orders.values().cloned().collect::<Vec<_>>();
+        out.sort_by(|a, b| {
+            (&a.order.slug, &a.order.order_id).cmp(&(&b.order.slug, &b.order.order_id))
+        });
+        out
+    }
+
+    /// Applies a trade in the `outcome` token of `slug`. Resting buys fill from taker
+    /// sells at or below their price, resting sells from taker buys at or above it.
+    pub fn on_trade(&mut self, slug: &str, outcome: SizingSide, trade: &TradePrint) {
+        for estimate in self.orders_at(slug, outcome) {
+            let order = &estimate.order;
+            let (reaches, through) = match order.side {
+                OrderSide::Buy => (
+                    trade.taker_side == OrderSide::Sell && trade.price <= order.price,
+                    trade.price < order.price,
+                ),
+                OrderSide::Sell => (
+                    trade.taker_side == OrderSide::Buy && trade.price >= order.price,
+                    trade.price > order.price,
+                ),
+            };
+            if !reaches || estimate.remaining() <= 0.0 {
+                continue;
+            }
+            if through {
+                // Price-time priority: nothing trades below our bid while it rests.
+                estimate.ahead = 0.0;
+                estimate.filled = order.size;
+            } else {
+                let from_ahead = estimate.ahead.min(trade.size.max(0.0));
+                estimate.ahead -= from_ahead;
+                estimate.filled += (trade.size - from_ahead).clamp(0.0, estimate.remaining());
+            }
+            estimate.updated_ts_ms = trade.ts_ms;
+        }
+    }
+
+    /// Applies the displayed size of one price level in the `outcome` token of `slug`.
+    /// `displayed_size` includes the unfilled part of our own orders at that price.
+    pub fn on_level(
+        &mut self,
+        slug: &str,
+        outcome: SizingSide,
+        side: OrderSide,
+        price: f64,
+        displayed_size: f64,
+        ts_ms: i64,
+    ) {
+        for estimate in self.orders_at(slug, outcome) {
+            if estimate.order.side != side || estimate.order.price != price {
+                continue;
+            }
+            let others = (displayed_size - estimate.remaining()).max(0.0);
+            let known = estimate.ahead + estimate.behind;
+            if others < known {
+                let keep = others / known;
+                estimate.ahead *= keep;
+                estimate.behind *= keep;
+            } else {
+                estimate.behind += others - known;
+            }
+            estimate.updated_ts_ms = ts_ms;
+        }
+    }
+
+    fn orders_at<'a>(
+        &'a mut self,
+        slug: &'a str,
+        outcome: SizingSide,
+    ) -> impl Iterator<Item = &'a mut QueueEstimate> + 'a {
+        self.orders.values_mut().filter(move |estimate| {
+            estimate.order.slug == slug && estimate.order.outcome == outcome
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bid(order_id: &str, price: f64, size: f64) -> RestingOrder {
+        RestingOrder {
+            order_id: order_id.to_string(),
+            slug: "btc-updown-5m-1".to_string(),
+            outcome: SizingSide::Yes,
+            side: OrderSide::Buy,
+            price,
+            size,
+            placed_ts_ms: 1_000,
+        }
+    }
+
+    fn sell_print(ts_ms: i64, price: f64, size: f64) -> TradePrint {
+        TradePrint {
+            ts_ms,
+            price,
+            size,
+            taker_side: OrderSide::Sell,
+        }
+    }
+
+    #[test]
+    fn queue_advances_on_trades_and_cancels_then_fills() {
+        let slug = "btc-updown-5m-1";
+        let mut tracker = QueuePositionTracker::new();
+        tracker.place(bid("a", 0.52, 10.0), 100.0);
+        // Our 10 shares joined the level; 20 more arrive behind us.
+        tracker.on_level(slug, SizingSide::Yes, OrderSide::Buy, 0.52, 130.0, 1_100);
+        let estimate = tracker.get("a").unwrap();
+        assert_eq!((estimate.ahead, estimate.behind), (100.0, 20.0));
+        assert_eq!(estimate.queue_fraction(), Some(100.0 / 120.0));
+
+        // 30 traded from the front, then the level shows 70 (30 traded + 30 cancelled).
+        tracker.on_trade(slug, SizingSide::Yes, &sell_print(1_200, 0.52, 30.0));
+        assert_eq!(tracker.get("a").unwrap().ahead, 70.0);
+        tracker.on_level(slug, SizingSide::Yes, OrderSide::Buy, 0.52, 70.0, 1_300);
+        let estimate = tracker.get("a").unwrap();
+        assert!((estimate.ahead - 70.0 * 60.0 / 90.0).abs() < 1e-9);
+        assert!((estimate.behind - 20.0 * 60.0 / 90.0).abs() < 1e-9);
+
+        // Other outcomes, other prices, and taker buys do not move the queue.
+        tracker.on_trade(slug, SizingSide::No, &sell_print(1_400, 0.52, 500.0));
+        tracker.on_trade(slug, SizingSide::Yes, &sell_print(1_400, 0.53, 500.0));
+        tracker.on_level(slug, SizingSide::Yes, OrderSide::Buy, 0.51, 0.0, 1_400);
+        assert!((tracker.get("a").unwrap().ahead - 46.666_666_666_666_664).abs() < 1e-9);
+
+        // A trade larger than the size ahead reaches us.
+        tracker.on_trade(slug, SizingSide::Yes, &sell_print(1_500, 0.52, 50.0));
+        let estimate = tracker.get("a").unwrap();
+        assert_eq!(estimate.ahead, 0.0);
+        assert!((estimate.filled - (50.0 - 46.666_666_666_666_664)).abs() < 1e-9);
+
+        // A print below our bid means the level was cleared.
+        tracker.on_trade(slug, SizingSide::Yes, &sell_print(1_600, 0.50, 1.0));
+        assert_eq!(tracker.get("a").unwrap().remaining(), 0.0);
+        assert!(tracker.remove("a").is_some());
+        assert!(tracker.estimates().is_empty());
+    }
+}
@@ -0,0 +1,620 @@
+//! Realized volatility of the underlying since an interval started, from stored 1s
+//! klines.
+//!
+//! Variance is the sum of squared log close-to-close returns divided by the elapsed
+//! time they span, so missing seconds widen the step they fall in instead of biasing the
+//! rate. The rate is then scaled to a year (crypto trades around the clock) or to the
+//! full interval length.
+//!
+//! The same reads give the move since the interval reference (the open of its first
+//! stored kline), both in percent and as a z-score against trailing volatility.
+//!
+//! A reference taken from a kline after the interval start is off by whatever the
+//! price did in between, so the tracker records how late it was captured and keeps
+//! looking for earlier klines until the store is backfilled up to the start.
+
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+
+use crate::binance_klines::BinanceSymbol;
+use crate::env_vars::{EnvValue, Settings};
+use crate::kline_store::KlineStoreError;
+use crate::persistence::{open_kline_reader, KlineBackend, PersistenceBackend};
+use crate::slug::Coin;
+use tracing::{info, warn};
+
+pub const SECONDS_PER_YEAR: f64 = 365.0 * 24.0 * 60.0 * 60.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RealizedVolScale {
+    /// `sqrt(variance per second * seconds per year)`.
+    Annualized,
+    /// `sqrt(variance per second * interval length)`: the move one interval of this
+    /// volatility would produce.
+    Interval,
+}
+
+impl EnvValue for RealizedVolScale {
+    fn parse_env(raw: &str) -> Option<Self> {
+        match raw {
+            "annualized" => Some(Self::Annualized),
+            "interval" => Some(Self::Interval),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RealizedVolConfig {
+    /// SQLite kline store to read; `None` disables the column (with any backend).
+    pub store_path: Option<PathBuf>,
+    pub scale: RealizedVolScale,
+    /// Returns required before a value is reported.
+    pub min_returns: u64,
+    /// Trailing window whose volatility normalizes the move since the reference.
+    pub sigma_lookback_secs: i64,
+    /// Reference capture delay beyond which the row raises the `ref_price.late_capture`
+    /// alert.
+    pub ref_capture_alert_ms: i64,
+    pub backend: PersistenceBackend,
+}
+
+impl Default for RealizedVolConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl RealizedVolConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_REALIZED_VOL_SCALE` (`annualized|interval|off`),
+    /// `PMM_REALIZED_VOL_MIN_RETURNS`, `PMM_MOVE_SIGMA_LOOKBACK_SECS`,
+    /// `PMM_REF_CAPTURE_ALERT_MS`, `PMM_DATABASE_URL`, and the store path from `PMM_BINANCE_STORE_PATH`
+    /// (default `data/binance/klines_1s.sqlite`), falling back to defaults for
+    /// missing/invalid values.
+    pub fn from_settings(settings: &Settings) -> Self {
+        // `off` disables the column; the scale is then never used.
+        let scale: Option<RealizedVolScale> = settings.get("PMM_REALIZED_VOL_SCALE");
+        Self {
+            store_path: scale.map(|_| settings.get("PMM_BINANCE_STORE_PATH")),
+            scale: scale.unwrap_or(RealizedVolScale::Annualized),
+            min_returns: settings.get("PMM_REALIZED_VOL_MIN_RETURNS"),
+            sigma_lookback_secs: settings
+                .get_where("PMM_MOVE_SIGMA_LOOKBACK_SECS", |secs| *secs > 0),
+            ref_capture_alert_ms: settings.get_where("PMM_REF_CAPTURE_ALERT_MS", |ms| *ms >= 0),
+            backend: PersistenceBackend::from_settings(settings),
+        }
+    }
+}
+
+/// Running sum of squared log returns over a series of closes.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct RealizedVarianceAccumulator {
+    first_ts_ms: Option<i64>,
+    last_ts_ms: Option<i64>,
+    last_close: Option<f64>,
+    sum_sq_log_returns: f64,
+    returns: u64,
+}
+
+impl RealizedVarianceAccumulator {
+    /// Adds one close; out-of-order timestamps and non-positive closes are ignored.
+    pub fn push(&mut self, ts_ms: i64, close: f64) {
+        if !(close.is_finite() && close > 0.0) || self.last_ts_ms.is_some_and(|last| ts_ms <= last)
+        {
+            return;
+        }
+        if let Some(prev) = self.last_close {
+            let log_return = (close / prev).ln();
+            self.sum_sq_log_returns += log_return * log_return;
+            self.returns += 1;
+        }
+        self.first_ts_ms.get_or_insert(ts_ms);
+        self.last_ts_ms = Some(ts_ms);
+        self.last_close = Some(close);
+    }
+
+    pub fn returns(&self) -> u64 {
+        self.returns
+    }
+
+    pub fn last_ts_ms(&self) -> Option<i64> {
+        self.last_ts_ms
+    }
+
+    pub fn last_close(&self) -> Option<f64> {
+        self.last_close
+    }
+
+    /// Realized variance per second over the span covered so far.
+    pub fn variance_per_sec(&self) -> Option<f64> {
+        let span_ms = self.last_ts_ms? - self.first_ts_ms?;
+        (span_ms > 0).then(|| self.sum_sq_log_returns / (span_ms as f64 / 1_000.0))
+    }
+
+    pub fn realized_vol(&self, scale: RealizedVolScale, interval_secs: i64) -> Option<f64> {
+        let per_sec = self.variance_per_sec()?;
+        let horizon_secs = match scale {
+            RealizedVolScale::Annualized => SECONDS_PER_YEAR,
+            RealizedVolScale::Interval => interval_secs as f64,
+        };
+        Some((per_sec * horizon_secs).sqrt())
+    }
+}
+
+/// Squared log returns over a rolling lookback, for normalizing the move since the
+/// interval reference.
+#[derive(Debug, Clone, Default)]
+struct TrailingVariance {
+    /// `(previous_ts_ms, ts_ms, squared log return)`.
+    returns: VecDeque<(i64, i64, f64)>,
+    last_ts_ms: Option<i64>,
+    last_close: Option<f64>,
+    sum_sq: f64,
+}
+
+impl TrailingVariance {
+    fn push(&mut self, ts_ms: i64, close: f64) {
+        if !(close.is_finite() && close > 0.0) || self.last_ts_ms.is_some_and(|last| ts_ms <= last)
+        {
+            return;
+        }
+        if let (Some(prev_ts), Some(prev)) = (self.last_ts_ms, self.last_close) {
+            let sq = (close / prev).ln().powi(2);
+            self.returns.push_back((prev_ts, ts_ms, sq));
+            self.sum_sq += sq;
+        }
+        self.last_ts_ms = Some(ts_ms);
+        self.last_close = Some(close);
+    }
+
+    fn evict_before(&mut self, cutoff_ms: i64) {
+        while self
+            .returns
+            .front()
+            .is_some_and(|(prev_ts, _, _)| *prev_ts < cutoff_ms)
+        {
+            if let Some((_, _, sq)) = self.returns.pop_front() {
+                self.sum_sq -= sq;
+            }
+        }
+        if self.returns.is_empty() {
+            // Resets accumulated rounding error.
+            self.sum_sq = 0.0;
+        }
+    }
+
+    fn variance_per_sec(&self) -> Option<f64> {
+        let (first_prev_ts, _, _) = self.returns.front()?;
+        let (_, last_ts, _) = self.returns.back()?;
+        let span_ms = last_ts - first_prev_ts;
+        (span_ms > 0).then(|| self.sum_sq.max(0.0) / (span_ms as f64 / 1_000.0))
+    }
+}
+
+/// Kline-derived numbers for one row's interval.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct IntervalKlineStats {
+    /// Open of the first stored kline at or after the interval start.
+    pub ref_price: Option<f64>,
+    /// Open time of that kline minus the interval start: `0` when the reference was
+    /// captured on time.
+    pub ref_capture_delay_ms: Option<i64>,
+    /// Latest stored close up to `min(now, end)`.
+    pub spot: Option<f64>,
+    /// `(spot / ref - 1) * 100`.
+    pub price_vs_ref_pct: Option<f64>,
+    /// `ln(spot / ref)` over the trailing volatility (up to now) scaled to the elapsed
+    /// time: how many standard deviations the move since the reference is.
+    pub move_z: Option<f64>,
+    /// Scaled per [`RealizedVolConfig::scale`]; `None` below `min_returns`.
+    pub realized_vol: Option<f64>,
+}
+
+/// Incremental kline statistics per `(symbol, interval start)` plus a trailing
+/// variance per symbol: each call reads only the klines stored since the previous one.
+pub struct RealizedVolTracker {
+    store: Box<dyn KlineBackend>,
+    cfg: RealizedVolConfig,
+    windows: HashMap<(BinanceSymbol, i64), TrackedWindow>,
+    trailing: HashMap<BinanceSymbol, TrailingVariance>,
+    pass: u64,
+}
+
+struct TrackedWindow {
+    accumulator: RealizedVarianceAccumulator,
+    /// `(open_time_ms, open)` of the reference kline.
+    reference: Option<(i64, f64)>,
+    seen_in_pass: u64,
+}
+
+impl RealizedVolTracker {
+    pub fn new(store: Box<dyn KlineBackend>, cfg: RealizedVolConfig) -> Self {
+        Self {
+            store,
+            cfg,
+            windows: HashMap::new(),
+            trailing: HashMap::new(),
+            pass: 0,
+        }
+    }
+
+    /// Opens the configured store read-only; `Ok(None)` when the column is disabled.
+    pub fn open(cfg: RealizedVolConfig) -> Result<Option<Self>, KlineStoreError> {
+        let Some(path) = cfg.store_path.clone() else {
+            return Ok(None);
+        };
+        let store = open_kline_reader(&cfg.backend, &path)?;
+        Ok(Some(Self::new(store, cfg)))
+    }
+
+    /// Realized volatility of `coin` from `start_ts_utc` up to `min(now, end)`.
+    /// `None` before the interval starts or until `min_returns` returns are stored.
+    pub fn realized_vol(
+        &mut self,
+        coin: Coin,
+        start_ts_utc: i64,
+        end_ts_utc: i64,
+        now_ts_utc: i64,
+    ) -> Result<Option<f64>, KlineStoreError> {
+        Ok(self
+            .interval_stats(coin, start_ts_utc, end_ts_utc, now_ts_utc)?
+            .realized_vol)
+    }
+
+    /// All kline statistics for one interval; everything is `None` before it starts and
+    /// for coins without stored klines.
+    pub fn interval_stats(
+        &mut self,
+        coin: Coin,
+        start_ts_utc: i64,
+        end_ts_utc: i64,
+        now_ts_utc: i64,
+    ) -> Result<IntervalKlineStats, KlineStoreError> {
+        let Some(symbol) = BinanceSymbol::for_coin(coin).filter(|_| now_ts_utc > start_ts_utc)
+        else {
+            return Ok(IntervalKlineStats::default());
+        };
+        let start_ms = start_ts_utc * 1_000;
+        let to_ms = now_ts_utc.min(end_ts_utc) * 1_000;
+        let trailing_per_sec = self.trailing_variance_per_sec(symbol, now_ts_utc * 1_000)?;
+
+        let window = self
+            .windows
+            .entry((symbol, start_ts_utc))
+            .or_insert_with(|| TrackedWindow {
+                accumulator: RealizedVarianceAccumulator::default(),
+                reference: None,
+                seen_in_pass: 0,
+            });
+        window.seen_in_pass = self.pass;
+
+        // Until the reference is on time, look for klines stored before it since.
+        let search_to_ms = window.reference.map_or(to_ms, |(ts_ms, _)| ts_ms);
+        if search_to_ms > start_ms {
+            if let Some((ts_ms, open)) =
+                self.store
+                    .first_open_in_range(symbol, start_ms, search_to_ms)?
+            {
+                let delay_ms = ts_ms - start_ms;
+                match window.reference {
+                    Some((late_ts_ms, _)) => {
+                        info!(
+                            component = "realized_vol",
+                            event = "ref_price.backfilled",
+                            symbol = symbol.as_str(),
+                            start_ts_utc,
+                            late_delay_ms = late_ts_ms - start_ms,
+                            delay_ms
+                        );
+                        // The earlier closes were skipped too; re-read them in order.
+                        window.accumulator = RealizedVarianceAccumulator::default();
+                    }
+                    None if delay_ms > 0 => warn!(
+                        component = "realized_vol",
+                        event = "ref_price.late_capture",
+                        symbol = symbol.as_str(),
+                        start_ts_utc,
+                        delay_ms
+                    ),
+                    None => {}
+                }
+                window.reference = Some((ts_ms, open));
+            }
+        }
+        let from_ms = window
+            .accumulator
+            .last_ts_ms()
+            .map_or(start_ms, |last| last + 1);
+        if from_ms < to_ms {
+            for (ts_ms, close) in self.store.closes_range(symbol, from_ms, to_ms)? {
+                window.accumulator.push(ts_ms, close);
+            }
+        }
+
+        let accumulator = &window.accumulator;
+        let ref_price = window
+            .reference
+            .map(|(_, open)| open)
+            .filter(|price| *price > 0.0);
+        let spot = accumulator.last_close();
+        let log_move = ref_price
+            .zip(spot)
+            .map(|(reference, spot)| (spot / reference).ln());
+        // A 1s kline's close is at the end of its second.
+        let elapsed_secs = accumulator
+            .last_ts_ms()
+            .map(|last| (last + 1_000 - start_ms) as f64 / 1_000.0);
+        let move_z = log_move.zip(trailing_per_sec.zip(elapsed_secs)).and_then(
+            |(log_move, (per_sec, elapsed))| {
+                let sigma = (per_sec * elapsed).sqrt();
+                (sigma > 0.0).then(|| log_move / sigma)
+            },
+        );
+        let realized_vol = if accumulator.returns() < self.cfg.min_returns.max(1) {
+            None
+        } else {
+            accumulator.realized_vol(self.cfg.scale, end_ts_utc - start_ts_utc)
+        };
+
+        Ok(IntervalKlineStats {
+            ref_price,
+            ref_capture_delay_ms: window.reference.map(|(ts_ms, _)| ts_ms - start_ms),
+            spot,
+            price_vs_ref_pct: log_move.map(|log_move| log_move.exp_m1() * 100.0),
+            move_z,
+            realized_vol,
+        })
+    }
+
+    /// Drops windows not queried since the previous call, so intervals that rolled off
+    /// the dashboard stop holding state.
+    pub fn end_pass(&mut self) {
+        let pass = self.pass;
+        self.windows.retain(|_, window| window.seen_in_pass == pass);
+        self.pass += 1;
+    }
+
+    fn trailing_variance_per_sec(
+        &mut self,
+        symbol: BinanceSymbol,
+        now_ms: i64,
+    ) -> Result<Option<f64>, KlineStoreError> {
+        let lookback_ms = self.cfg.sigma_lookback_secs.max(1) * 1_000;
+        let trailing = self.trailing.entry(symbol).or_default();
+        let cutoff_ms = now_ms - lookback_ms;
+        let from_ms = trailing
+            .last_ts_ms
+            .map_or(cutoff_ms, |last| (last + 1).max(cutoff_ms));
+        if from_ms < now_ms {
+            for (ts_ms, close) in self.store.closes_range(symbol, from_ms, now_ms)? {
+                trailing.push(ts_ms, close);
+            }
+        }
+        trailing.evict_before(cutoff_ms);
+        if (trailing.returns.len() as u64) < self.cfg.min_returns.max(1) {
+            return Ok(None);
+        }
+        Ok(trailing.variance_per_sec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binance_klines::Kline1s;
+    use crate::kline_store::KlineStore;
+    use crate::kline_validation::KlineValidationConfig;
+
+    fn kline(open_time_ms: i64, close: f64) -> Kline1s {
+        Kline1s {
+            open_time_ms,
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 1.0,
+            close_time_ms: open_time_ms + 999,
+            quote_asset_volume: close,
+            trade_count: 1,
+            taker_buy_base_volume: 0.5,
+            taker_buy_quote_volume: close / 2.0,
+        }
+    }
+
+    #[test]
+    fn accumulator_scales_variance_rate_and_skips_bad_points() {
+        let mut acc = RealizedVarianceAccumulator::default();
+        assert_eq!(acc.realized_vol(RealizedVolScale::Annualized, 300), None);
+        // Alternating +/-1% log moves, one per second, with a one-second gap.
+        let mut price = 100.0;
+        acc.push(0, price);
+        for (i, ts) in [1_000, 2_000, 4_000].into_iter().enumerate() {
+            price *= if i % 2 == 0 {
+                0.01_f64.exp()
+            } else {
+                (-0.01_f64).exp()
+            };
+            acc.push(ts, price);
+        }
+        acc.push(3_000, 1.0);
+        acc.push(5_000, 0.0);
+        assert_eq!(acc.returns(), 3);
+
+        let per_sec = acc.variance_per_sec().unwrap();
+        assert!((per_sec - 3.0 * 0.0001 / 4.0).abs() < 1e-12);
+        let interval = acc.realized_vol(RealizedVolScale::Interval, 300).unwrap();
+        assert!((interval - (per_sec * 300.0).sqrt()).abs() < 1e-12);
+        let annual = acc.realized_vol(RealizedVolScale::Annualized, 300).unwrap();
+        assert!((annual - (per_sec * SECONDS_PER_YEAR).sqrt()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn tracker_reads_incrementally_and_prunes_old_windows() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("klines_1s.sqlite");
+        let start = 1_735_689_600;
+        let mut writer = KlineStore::open(&path, KlineValidationConfig::default()).unwrap();
+        let rows = (0..=60)
+            .map(|i| kline((start + i) * 1_000, if i % 2 == 0 { 100.0 } else { 101.0 }))
+            .collect();
+        writer.upsert_rows(BinanceSymbol::BtcUsdt, rows).unwrap();
+
+        let cfg = RealizedVolConfig {
+            store_path: Some(path.clone()),
+            scale: RealizedVolScale::Interval,
+            min_returns: 10,
+            sigma_lookback_secs: 3_600,
+            ref_capture_alert_ms: 0,
+            backend: PersistenceBackend::Sqlite,
+        };
+        let mut tracker = RealizedVolTracker::open(cfg).unwrap().unwrap();
+        let end = start + 300;
+        assert_eq!(
+            tracker.realized_vol(Coin::BTC, start, end, start).unwrap(),
+            None
+        );
+        assert_eq!(
+            tracker
+                .realized_vol(Coin::BTC, start, end, start + 5)
+                .unwrap(),
+            None
+        );
+        let early = tracker
+            .realized_vol(Coin::BTC, start, end, start + 31)
+            .unwrap()
+            .unwrap();
+        let later = tracker
+            .realized_vol(Coin::BTC, start, end, start + 61)
+            .unwrap()
+            .unwrap();
+        let step = (101.0_f64 / 100.0).ln();
+        assert!((later - (step * step * 300.0).sqrt()).abs() < 1e-9);
+        assert!((early - later).abs() < 1e-9);
+        assert_eq!(
+            tracker
+                .realized_vol(Coin::ETH, start, end, start + 61)
+                .unwrap(),
+            None
+        );
+
+        tracker.end_pass();
+        assert_eq!(tracker.windows.len(), 2);
+        tracker
+            .realized_vol(Coin::BTC, start, end, start + 61)
+            .unwrap();
+        tracker.end_pass();
+        assert_eq!(tracker.windows.len(), 1);
+    }
+
+    #[test]
+    fn interval_stats_normalizes_move_by_trailing_volatility() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("klines_1s.sqlite");
+        let start = 1_735_689_600;
+        let mut writer = KlineStore::open(&path, KlineValidationConfig::default()).unwrap();
+        // An hour of +/-1% chop before the interval, then a steady 2% climb inside it.
+        let step = 0.01_f64;
+        let mut rows = (-3_600..0)
+            .map(|i| {
+                kline(
+                    (start + i) * 1_000,
+                    if i % 2 == 0 {
+                        100.0
+                    } else {
+                        100.0 * step.exp()
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+        rows.extend(
+            (0..=100).map(|i| kline((start + i) * 1_000, 100.0 * (0.0002 * i as f64).exp())),
+        );
+        writer.upsert_rows(BinanceSymbol::BtcUsdt, rows).unwrap();
+
+        let cfg = RealizedVolConfig {
+            store_path: Some(path),
+            scale: RealizedVolScale::Interval,
+            min_returns: 10,
+            sigma_lookback_secs: 3_600,
+            ref_capture_alert_ms: 0,
+            backend: PersistenceBackend::Sqlite,
+        };
+        let mut tracker = RealizedVolTracker::open(cfg).unwrap().unwrap();
+        let end = start + 300;
+        assert_eq!(
+            tracker
+                .interval_stats(Coin::BTC, start, end, start)
+                .unwrap(),
+            IntervalKlineStats::default()
+        );
+
+        let stats = tracker
+            .interval_stats(Coin::BTC, start, end, start + 101)
+            .unwrap();
+        assert_eq!(stats.ref_price, Some(100.0));
+        let log_move = 0.0002_f64 * 100.0;
+        assert!((stats.spot.unwrap() - 100.0 * log_move.exp()).abs() < 1e-9);
+        assert!((stats.price_vs_ref_pct.unwrap() - log_move.exp_m1() * 100.0).abs() < 1e-9);
+        // Trailing variance is dominated by the chop: ~step^2 per second over the hour.
+        let z = stats.move_z.unwrap();
+        let expected = log_move / (step * step * 101.0).sqrt();
+        assert!(
+            (z - expected).abs() / expected < 0.05,
+            "z={z} expected={expected}"
+        );
+        assert!(stats.realized_vol.is_some());
+    }
+
+    #[test]
+    fn late_reference_is_backfilled_from_the_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("klines_1s.sqlite");
+        let start = 1_735_689_600;
+        let mut writer = KlineStore::open(&path, KlineValidationConfig::default()).unwrap();
+        // The live capture missed the first three seconds of the interval.
+        writer
+            .upsert_rows(
+                BinanceSymbol::BtcUsdt,
+                (3..=20)
+                    .map(|i| kline((start + i) * 1_000, 102.0))
+                    .collect(),
+            )
+            .unwrap();
+
+        let cfg = RealizedVolConfig {
+            store_path: Some(path.clone()),
+            min_returns: 1,
+            ..RealizedVolConfig::default()
+        };
+        let mut tracker = RealizedVolTracker::open(cfg).unwrap().unwrap();
+        let end = start + 300;
+        let stats = tracker
+            .interval_stats(Coin::BTC, start, end, start + 21)
+            .unwrap();
+        assert_eq!(stats.ref_price, Some(102.0));
+        assert_eq!(stats.ref_capture_delay_ms, Some(3_000));
+
+        // A later pass finds the gap filled and re-bases on the on-time kline.
+        writer
+            .upsert_rows(
+                BinanceSymbol::BtcUsdt,
+                (0..3).map(|i| kline((start + i) * 1_000, 100.0)).collect(),
+            )
+            .unwrap();
+        let stats = tracker
+            .interval_stats(Coin::BTC, start, end, start + 21)
+            .unwrap();
+        assert_eq!(stats.ref_price, Some(100.0));
+        assert_eq!(stats.ref_capture_delay_ms, Some(0));
+        assert!((stats.price_vs_ref_pct.unwrap() - 2.0).abs() < 1e-9);
+        // The backfilled closes count toward realized vol: one 100 -> 102 step.
+        let step = (102.0_f64 / 100.0).ln();
+        let expected = (step * step / 20.0 * SECONDS_PER_YEAR).sqrt();
+        assert!((stats.realized_vol.unwrap() - expected).abs() < 1e-9);
+    }
+}
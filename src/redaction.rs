@@ -0,0 +1,345 @@
+//! Public, read-only dashboard views.
+//!
+//! With `PMM_DASHBOARD_TOKEN` set, dashboard requests that do not carry the token get
+//! a redacted view: only the columns in `PMM_DASHBOARD_PUBLIC_COLUMNS` keep their
+//! values. Withheld cells render as [`REDACTED`] in HTML and are left out of JSON
+//! entirely, and the endpoints that only describe our own book (`/wallet`,
+//! `/dashboard/occupancy`) answer `401`. The token is accepted as
+//! `Authorization: Bearer <token>` or as the `pmm_token` cookie, so a browser that
+//! has the cookie sees the full dashboard.
+//!
+//! The routers merged next to the dashboard are covered by [`with_dashboard_token`],
+//! one layer over the whole app: without the token, writes and the position/PnL
+//! routes answer `401`, and `/dashboard/history` redacts its rows.
+
+use std::sync::Arc;
+
+use axum::extract::{Request, State};
+use axum::http::header::{AUTHORIZATION, COOKIE};
+use axum::http::{HeaderMap, Method, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::Router;
+
+use crate::dashboard::{DashboardDisplayRow, DashboardRow};
+use crate::env_vars::{EnvValue, Settings};
+
+/// What a withheld cell shows in HTML.
+pub const REDACTED: &str = "–";
+
+/// Cookie carrying the dashboard token.
+pub const TOKEN_COOKIE: &str = "pmm_token";
+
+/// Display columns a profile can withhold, by their JSON name. The market link, coin,
+/// duration, and interval timing columns are always shown.
+pub const REDACTABLE_COLUMNS: [&str; 24] = [
+    "ref_price",
+    "price",
+    "probability",
+    "probability_raw",
+    "realized_vol",
+    "price_vs_ref_pct",
+    "move_z",
+    "best_bid_yes",
+    "best_ask_yes",
+    "position_net",
+    "pos_yes",
+    "pos_no",
+    "offer_yes",
+    "offer_no",
+    "queue_ahead_yes",
+    "queue_ahead_no",
+    "suggested_size",
+    "net_profit",
+    "taker_fee_pct",
+    "maker_fee_pct",
+    "fee_exponent",
+    "reward_pct",
+    "volume_24h",
+    "liquidity",
+];
+
+/// Route prefixes that serve positions or PnL (the Grafana `net_profit`/`position_net`
+/// series, market position ledgers and trade notes): `401` without the token.
+pub const PRIVATE_ROUTE_PREFIXES: [&str; 3] = ["/grafana", "/markets/", "/journal"];
+
+/// Columns that reveal positions, orders, or PnL; everything else is public by default.
+pub const PRIVATE_COLUMNS: [&str; 9] = [
+    "position_net",
+    "pos_yes",
+    "pos_no",
+    "offer_yes",
+    "offer_no",
+    "queue_ahead_yes",
+    "queue_ahead_no",
+    "suggested_size",
+    "net_profit",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RedactionConfig {
+    /// Token unlocking the full dashboard; `None` serves everyone unredacted.
+    pub token: Option<String>,
+    /// Redactable columns shown to requests without the token.
+    pub public_columns: Vec<String>,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            token: None,
+            public_columns: default_public_columns(),
+        }
+    }
+}
+
+fn default_public_columns() -> Vec<String> {
+    REDACTABLE_COLUMNS
+        .iter()
+        .filter(|column| !PRIVATE_COLUMNS.contains(column))
+        .map(|column| column.to_string())
+        .collect()
+}
+
+impl RedactionConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_DASHBOARD_TOKEN` and `PMM_DASHBOARD_PUBLIC_COLUMNS` (comma list of
+    /// [`REDACTABLE_COLUMNS`], or `none`). Unknown column names are ignored.
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            token: settings.parsed("PMM_DASHBOARD_TOKEN", String::parse_env),
+            public_columns: settings
+                .parsed("PMM_DASHBOARD_PUBLIC_COLUMNS", |raw| {
+                    (!raw.is_empty()).then(|| parse_public_columns(raw))
+                })
+                .unwrap_or_else(default_public_columns),
+        }
+    }
+
+    /// Whether a request with `headers` gets the redacted view.
+    pub fn redacts(&self, headers: &HeaderMap) -> bool {
+        match &self.token {
+            Some(token) => !request_token(headers).is_some_and(|given| token_eq(given, token)),
+            None => false,
+        }
+    }
+
+    pub fn column_public(&self, column: &str) -> bool {
+        self.public_columns.iter().any(|public| public == column)
+    }
+
+    /// Clears every withheld cell of a raw snapshot row, as [`Self::redact_row`] does
+    /// for display rows. Used where raw rows are served, like `/dashboard/history`.
+    pub fn redact_dashboard_row(&self, row: &mut DashboardRow) {
+        for column in REDACTABLE_COLUMNS {
+            let shown_with = match column {
+                "queue_ahead_yes" => "offer_yes",
+                "queue_ahead_no" => "offer_no",
+                "probability_raw" => "probability",
+                _ => column,
+            };
+            if !self.column_public(shown_with) {
+                if let Some(cell) = dashboard_column_mut(row, column) {
+                    *cell = None;
+                }
+            }
+        }
+        if !self.column_public("ref_price") {
+            row.ref_capture_delay_ms = None;
+        }
+        if !self.column_public("probability") {
+            row.probability_smoothed = None;
+            row.probability_horizons.clear();
+        }
+        let withheld = |column: &String| {
+            REDACTABLE_COLUMNS.contains(&column.as_str()) && !self.column_public(column)
+        };
+        row.mock_columns.retain(|column| !withheld(column));
+        row.simulated_columns.retain(|column| !withheld(column));
+    }
+
+    /// Replaces every withheld cell of `row` with [`REDACTED`] and drops withheld
+    /// columns from its mock/simulated markers. Queue estimates and the raw
+    /// probability are tags on their offer and probability cells: they are cleared,
+    /// not marked, along with them.
+    pub fn redact_row(&self, row: &mut DashboardDisplayRow) {
+        for column in REDACTABLE_COLUMNS {
+            let (shown_with, withheld) = match column {
+                "queue_ahead_yes" => ("offer_yes", ""),
+                "queue_ahead_no" => ("offer_no", ""),
+                "probability_raw" => ("probability", ""),
+                _ => (column, REDACTED),
+            };
+            if !self.column_public(shown_with) {
+                if let Some(cell) = column_mut(row, column) {
+                    *cell = withheld.to_string();
+                }
+            }
+        }
+        if !self.column_public("ref_price") {
+            row.ref_capture_delay_ms = None;
+        }
+        if !self.column_public("probability") {
+            row.probability_horizons.clear();
+        }
+        let withheld = |column: &String| {
+            REDACTABLE_COLUMNS.contains(&column.as_str()) && !self.column_public(column)
+        };
+        row.mock_columns.retain(|column| !withheld(column));
+        row.simulated_columns.retain(|column| !withheld(column));
+    }
+}
+
+/// Marks a request without the token; [`with_dashboard_token`] adds it so handlers
+/// outside the dashboard router can redact what they serve.
+#[derive(Debug, Clone)]
+pub struct PublicRequest(pub Arc<RedactionConfig>);
+
+/// Applies the dashboard token to every route of `app`. With a token configured,
+/// requests without it get `401` for anything but `GET`/`HEAD` and for
+/// [`PRIVATE_ROUTE_PREFIXES`]; the rest carry a [`PublicRequest`] extension. Without a
+/// token configured, `app` is served as is.
+pub fn with_dashboard_token(app: Router, redaction: RedactionConfig) -> Router {
+    if redaction.token.is_none() {
+        return app;
+    }
+    app.layer(middleware::from_fn_with_state(
+        Arc::new(redaction),
+        token_middleware,
+    ))
+}
+
+async fn token_middleware(
+    State(redaction): State<Arc<RedactionConfig>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    if !redaction.redacts(request.headers()) {
+        return next.run(request).await;
+    }
+    let read_only = matches!(*request.method(), Method::GET | Method::HEAD);
+    if !read_only || private_path(request.uri().path()) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    request.extensions_mut().insert(PublicRequest(redaction));
+    next.run(request).await
+}
+
+/// Whether `path` only serves requests with the token; see [`PRIVATE_ROUTE_PREFIXES`].
+/// Market curves are public.
+fn private_path(path: &str) -> bool {
+    PRIVATE_ROUTE_PREFIXES
+        .iter()
+        .any(|prefix| path.starts_with(prefix))
+        && !path.ends_with("/curve")
+}
+
+fn parse_public_columns(raw: &str) -> Vec<String> {
+    if raw.trim().eq_ignore_ascii_case("none") {
+        return Vec::new();
+    }
+    raw.split(',')
+        .map(|column| column.trim().to_ascii_lowercase())
+        .filter(|column| REDACTABLE_COLUMNS.contains(&column.as_str()))
+        .collect()
+}
+
+fn request_token(headers: &HeaderMap) -> Option<&str> {
+    let bearer = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().strip_prefix("Bearer "))
+        .map(str::trim);
+    bearer.or_else(|| {
+        headers
+            .get_all(COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|cookies| cookies.split(';'))
+            .filter_map(|pair| pair.trim().split_once('='))
+            .find(|(name, _)| *name == TOKEN_COOKIE)
+            .map(|(_, value)| value.trim())
+    })
+}
+
+/// Compares without returning early on the first differing byte.
+fn token_eq(given: &str, token: &str) -> bool {
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+fn column_mut<'a>(row: &'a mut DashboardDisplayRow, column: &str) -> Option<&'a mut String> {
+    Some(match column {
+        "ref_price" => &mut row.ref_price,
+        "price" => &mut row.price,
+        "probability" => &mut row.probability,
+        "probability_raw" => &mut row.probability_raw,
+        "realized_vol" => &mut row.realized_vol,
+        "price_vs_ref_pct" => &mut row.price_vs_ref_pct,
+        "move_z" => &mut row.move_z,
+        "best_bid_yes" => &mut row.best_bid_yes,
+        "best_ask_yes" => &mut row.best_ask_yes,
+        "position_net" => &mut row.position_net,
+        "pos_yes" => &mut row.pos_yes,
+        "pos_no" => &mut row.pos_no,
+        "offer_yes" => &mut row.offer_yes,
+        "offer_no" => &mut row.offer_no,
+        "queue_ahead_yes" => &mut row.queue_ahead_yes,
+        "queue_ahead_no" => &mut row.queue_ahead_no,
+        "suggested_size" => &mut row.suggested_size,
+        "net_profit" => &mut row.net_profit,
+        "taker_fee_pct" => &mut row.taker_fee_pct,
+        "maker_fee_pct" => &mut row.maker_fee_pct,
+        "fee_exponent" => &mut row.fee_exponent,
+        "reward_pct" => &mut row.reward_pct,
+        "volume_24h" => &mut row.volume_24h,
+        "liquidity" => &mut row.liquidity,
+        _ => return None,
+    })
+}
+
+fn dashboard_column_mut<'a>(
+    row: &'a mut DashboardRow,
+    column: &str,
+) -> Option<&'a mut Option<String>> {
+    Some(match column {
+        "ref_price" => &mut row.ref_price,
+        "price" => &mut row.price,
+        "probability" => &mut row.probability,
+        "probability_raw" => &mut row.probability_raw,
+        "realized_vol" => &mut row.realized_vol,
+        "price_vs_ref_pct" => &mut row.price_vs_ref_pct,
+        "move_z" => &mut row.move_z,
+        "best_bid_yes" => &mut row.best_bid_yes,
+        "best_ask_yes" => &mut row.best_ask_yes,
+        "position_net" => &mut row.position_net,
+        "pos_yes" => &mut row.pos_yes,
+        "pos_no" => &mut row.pos_no,
+        "offer_yes" => &mut row.offer_yes,
+        "offer_no" => &mut row.offer_no,
+        "queue_ahead_yes" => &mut row.queue_ahead_yes,
+        "queue_ahead_no" => &mut row.queue_ahead_no,
+        "suggested_size" => &mut row.suggested_size,
+        "net_profit" => &mut row.net_profit,
+        "taker_fee_pct" => &mut row.taker_fee_pct,
+        "maker_fee_pct" => &mut row.maker_fee_pct,
+        "fee_exponent" => &mut row.fee_exponent,
+        "reward_pct" => &mut row.reward_pct,
+        "volume_24h" => &mut row.volume_24h,
+        "liquidity" => &mut row.liquidity,
+        _ => return None,
+    })
+}
+
+/// Serde predicate for display cells: withheld cells are omitted from JSON.
+pub(crate) fn is_redacted(cell: &str) -> bool {
+    cell == REDACTED
+}
@@ -0,0 +1,344 @@
+//! `pmm replay`: recorded dashboard rows, strategy decisions, and paper fills played
+//! back in accelerated time.
+//!
+//! [`load_replay_timeline`] merges the snapshot history ([`SnapshotStore`]), the decision
+//! store ([`SqliteDecisionLog`]), and the paper fill store into one time-ordered list.
+//! [`play_replay`] then walks it on a [`SimulatedClock`]: rows are published through a
+//! [`ReplaySnapshotSource`] (so `dashboard_router` shows the market as it was), and
+//! decisions and fills are logged as `replay.decision` / `replay.fill` when the replay
+//! clock reaches them.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::watch;
+use tracing::{debug, info};
+
+use crate::dashboard::{
+    DashboardRow, DashboardSnapshot, DashboardSnapshotSource, InMemoryMockSnapshotSource,
+    SimulatedClock, SnapshotStatus,
+};
+use crate::env_vars::Settings;
+use crate::paper_trading::{
+    read_paper_fills, PaperTradingConfig, PaperTradingError, SimulatedFill,
+};
+use crate::snapshot_store::{SnapshotStore, SnapshotStoreConfig, SnapshotStoreError};
+use crate::strategy::{decision_store_path, DecisionRecord, SqliteDecisionLog};
+
+/// Most snapshot rows one replay loads; later ones are dropped and reported as
+/// truncated.
+pub const MAX_REPLAY_ROWS: usize = 1_000_000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayConfig {
+    pub from_ts_utc: i64,
+    pub to_ts_utc: i64,
+    /// Replay seconds per real second.
+    pub speed: u32,
+    /// How often (real time) the replay clock advances and due events are applied.
+    pub tick_ms: u64,
+    /// Stores to read; `None` (or a missing file) contributes no events.
+    pub snapshot_store: Option<PathBuf>,
+    pub decision_store: Option<PathBuf>,
+    pub paper_store: Option<PathBuf>,
+}
+
+impl ReplayConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env(from_ts_utc: i64, to_ts_utc: i64, speed: u32) -> Self {
+        Self::from_settings(&Settings::from_env(), from_ts_utc, to_ts_utc, speed)
+    }
+
+    /// Replays `from_ts_utc..=to_ts_utc` from the stores configured by
+    /// `PMM_SNAPSHOT_STORE_PATH`, `PMM_DECISION_STORE_PATH`, and `PMM_PAPER_STORE_PATH`.
+    pub fn from_settings(
+        settings: &Settings,
+        from_ts_utc: i64,
+        to_ts_utc: i64,
+        speed: u32,
+    ) -> Self {
+        Self {
+            from_ts_utc,
+            to_ts_utc,
+            speed: speed.max(1),
+            tick_ms: 250,
+            snapshot_store: SnapshotStoreConfig::from_settings(settings).store_path,
+            decision_store: decision_store_path(settings),
+            paper_store: PaperTradingConfig::from_settings(settings).store_path,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ReplayError {
+    #[error("empty replay range {from_ts_utc}..{to_ts_utc}")]
+    EmptyRange { from_ts_utc: i64, to_ts_utc: i64 },
+    #[error("snapshot store: {0}")]
+    SnapshotStore(#[from] SnapshotStoreError),
+    #[error("decision store: {0}")]
+    DecisionStore(#[from] rusqlite::Error),
+    #[error("paper fill store: {0}")]
+    PaperStore(#[from] PaperTradingError),
+}
+
+/// Parses a replay speed such as `10x`, `10`, or `1x`.
+pub fn parse_replay_speed(raw: &str) -> Option<u32> {
+    let raw = raw.trim();
+    raw.strip_suffix(['x', 'X'])
+        .unwrap_or(raw)
+        .parse::<u32>()
+        .ok()
+        .filter(|speed| *speed > 0)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplayEventKind {
+    Row(Box<DashboardRow>),
+    Decision(DecisionRecord),
+    Fill(SimulatedFill),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayEvent {
+    /// Rows in effect before the range start are stamped with the start.
+    pub ts_ms: i64,
+    pub kind: ReplayEventKind,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReplayTimeline {
+    /// Oldest first; at equal times rows come before decisions, decisions before fills.
+    pub events: Vec<ReplayEvent>,
+    /// Set when more than [`MAX_REPLAY_ROWS`] snapshot rows matched.
+    pub rows_truncated: bool,
+}
+
+/// What a finished replay applied.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplaySummary {
+    pub from_ts_utc: i64,
+    pub to_ts_utc: i64,
+    pub speed: u32,
+    pub rows: usize,
+    pub decisions: usize,
+    pub fills: usize,
+    pub rows_truncated: bool,
+}
+
+/// Reads every recorded event of `cfg`'s range from its stores.
+pub fn load_replay_timeline(cfg: &ReplayConfig) -> Result<ReplayTimeline, ReplayError> {
+    if cfg.to_ts_utc <= cfg.from_ts_utc {
+        return Err(ReplayError::EmptyRange {
+            from_ts_utc: cfg.from_ts_utc,
+            to_ts_utc: cfg.to_ts_utc,
+        });
+    }
+    let from_ms = cfg.from_ts_utc * 1_000;
+    let to_ms = cfg.to_ts_utc * 1_000;
+    let mut timeline = ReplayTimeline::default();
+
+    if let Some(path) = existing(&cfg.snapshot_store) {
+        let (points, truncated) =
+            SnapshotStore::open(path)?.range(from_ms, to_ms, MAX_REPLAY_ROWS)?;
+        timeline.rows_truncated = truncated;
+        timeline
+            .events
+            .extend(points.into_iter().map(|point| ReplayEvent {
+                ts_ms: point.ts_ms.max(from_ms),
+                kind: ReplayEventKind::Row(Box::new(point.row)),
+            }));
+    }
+    if let Some(path) = existing(&cfg.decision_store) {
+        let decisions = SqliteDecisionLog::open(path)?.range(cfg.from_ts_utc, cfg.to_ts_utc)?;
+        timeline
+            .events
+            .extend(decisions.into_iter().map(|decision| ReplayEvent {
+                ts_ms: decision.ts_utc * 1_000,
+                kind: ReplayEventKind::Decision(decision),
+            }));
+    }
+    if let Some(path) = &cfg.paper_store {
+        let fills = read_paper_fills(path, from_ms, to_ms)?;
+        timeline
+            .events
+            .extend(fills.into_iter().map(|fill| ReplayEvent {
+                ts_ms: fill.ts_ms,
+                kind: ReplayEventKind::Fill(fill),
+            }));
+    }
+    // Stable, so each store's own order and the rows/decisions/fills order hold.
+    timeline.events.sort_by_key(|event| event.ts_ms);
+    Ok(timeline)
+}
+
+fn existing(path: &Option<PathBuf>) -> Option<&Path> {
+    path.as_deref().filter(|path| path.exists())
+}
+
+/// Dashboard source showing the replayed rows at the replay clock.
+#[derive(Clone)]
+pub struct ReplaySnapshotSource {
+    inner: InMemoryMockSnapshotSource,
+    clock_ts_utc: Arc<AtomicI64>,
+    /// Latest replayed state per slug, ordered like the live dashboard.
+    rows: Arc<Mutex<BTreeMap<(i64, String), DashboardRow>>>,
+}
+
+impl ReplaySnapshotSource {
+    pub fn new(start_ts_utc: i64) -> Self {
+        Self {
+            inner: InMemoryMockSnapshotSource::new(DashboardSnapshot::default()),
+            clock_ts_utc: Arc::new(AtomicI64::new(start_ts_utc)),
+            rows: Arc::new(Mutex::new(BTreeMap::new())),
+        }
+    }
+
+    /// Moves the replay clock, applies `rows`, and republishes when the visible rows
+    /// changed. Rows whose interval ended by `now_ts_utc` are dropped.
+    fn advance(&self, now_ts_utc: i64, rows: Vec<DashboardRow>) {
+        self.clock_ts_utc.store(now_ts_utc, Ordering::Relaxed);
+        let mut state = self
+            .rows
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        for row in rows {
+            state.retain(|(_, slug), _| *slug != row.slug);
+            state.insert((row.start_ts_utc, row.slug.clone()), row);
+        }
+        state.retain(|_, row| row.end_ts_utc > now_ts_utc);
+        let visible: Vec<DashboardRow> = state.values().cloned().collect();
+        if visible != self.inner.snapshot().rows {
+            self.inner.replace_snapshot(DashboardSnapshot {
+                rows: visible,
+                seq: 0,
+            });
+        }
+    }
+}
+
+impl DashboardSnapshotSource for ReplaySnapshotSource {
+    fn snapshot(&self) -> Arc<DashboardSnapshot> {
+        self.inner.snapshot()
+    }
+
+    fn status(&self) -> SnapshotStatus {
+        SnapshotStatus {
+            updated_at_ts_utc: Some(self.now_ts_utc()),
+            ..SnapshotStatus::default()
+        }
+    }
+
+    fn now_ts_utc(&self) -> i64 {
+        self.clock_ts_utc.load(Ordering::Relaxed)
+    }
+
+    fn published(&self) -> Option<watch::Receiver<u64>> {
+        self.inner.published()
+    }
+}
+
+/// Plays `timeline` into `source` at `cfg.speed`, returning once the replay clock
+/// reaches `cfg.to_ts_utc`.
+pub async fn play_replay(
+    timeline: ReplayTimeline,
+    source: &ReplaySnapshotSource,
+    cfg: &ReplayConfig,
+) -> ReplaySummary {
+    let mut summary = ReplaySummary {
+        from_ts_utc: cfg.from_ts_utc,
+        to_ts_utc: cfg.to_ts_utc,
+        speed: cfg.speed,
+        rows_truncated: timeline.rows_truncated,
+        ..ReplaySummary::default()
+    };
+    info!(
+        component = "replay",
+        event = "replay.start",
+        from_ts_utc = cfg.from_ts_utc,
+        to_ts_utc = cfg.to_ts_utc,
+        speed = cfg.speed,
+        events = timeline.events.len(),
+        rows_truncated = timeline.rows_truncated
+    );
+    let clock = SimulatedClock::new(cfg.from_ts_utc, cfg.speed);
+    let mut events = timeline.events.into_iter().peekable();
+    loop {
+        let now_ts_utc = clock.now_ts_utc().min(cfg.to_ts_utc);
+        let mut rows = Vec::new();
+        while let Some(event) = events.next_if(|event| event.ts_ms <= now_ts_utc * 1_000) {
+            match event.kind {
+                ReplayEventKind::Row(row) => {
+                    debug!(
+                        component = "replay",
+                        event = "replay.row",
+                        ts_ms = event.ts_ms,
+                        slug = %row.slug
+                    );
+                    summary.rows += 1;
+                    rows.push(*row);
+                }
+                ReplayEventKind::Decision(decision) => {
+                    info!(
+                        component = "replay",
+                        event = "replay.decision",
+                        ts_utc = decision.ts_utc,
+                        slug = %decision.slug,
+                        routine = %decision.routine,
+                        action = decision.action.as_str(),
+                        side = decision.side.map(|side| side.as_str()).unwrap_or("-"),
+                        shares = ?decision.shares,
+                        price = ?decision.price,
+                        reason = decision.reason.as_deref().unwrap_or("-")
+                    );
+                    summary.decisions += 1;
+                }
+                ReplayEventKind::Fill(fill) => {
+                    info!(
+                        component = "replay",
+                        event = "replay.fill",
+                        ts_ms = fill.ts_ms,
+                        slug = %fill.slug,
+                        fill_id = %fill.fill_id,
+                        outcome = fill.outcome.as_str(),
+                        side = ?fill.side,
+                        price = fill.price,
+                        size = fill.size,
+                        taker = fill.taker
+                    );
+                    summary.fills += 1;
+                }
+            }
+        }
+        source.advance(now_ts_utc, rows);
+        if now_ts_utc >= cfg.to_ts_utc {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(cfg.tick_ms.max(1))).await;
+    }
+    info!(
+        component = "replay",
+        event = "replay.finish",
+        rows = summary.rows,
+        decisions = summary.decisions,
+        fills = summary.fills
+    );
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_speed_with_or_without_suffix() {
+        assert_eq!(parse_replay_speed("10x"), Some(10));
+        assert_eq!(parse_replay_speed(" 60X "), Some(60));
+        assert_eq!(parse_replay_speed("3"), Some(3));
+        assert_eq!(parse_replay_speed("0x"), None);
+        assert_eq!(parse_replay_speed("fast"), None);
+    }
+}
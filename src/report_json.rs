@@ -0,0 +1,174 @@
+//! Versioned JSON artifacts for batch reports.
+//!
+//! Batch jobs write their reports with [`write_report_json`] so the next pipeline
+//! step can gate on them. Each file wraps the report in a [`ReportEnvelope`] naming
+//! its `kind` and `schema_version`; [`read_report_json`] refuses a file of another
+//! kind or of a newer version than this binary knows. Adding a field keeps the
+//! version; renaming, removing, or changing the meaning of one bumps it.
+
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::backtest::BacktestReport;
+use crate::binance_klines::KlineCoverageReport;
+use crate::faults::now_utc;
+use crate::features::FeatureTransformReport;
+
+/// A report with a stable JSON schema.
+pub trait JsonReport: Serialize + DeserializeOwned {
+    /// `kind` of the envelope, e.g. `feature_transform`.
+    const KIND: &'static str;
+    const SCHEMA_VERSION: u32;
+
+    /// Writes the report to `path`; see [`write_report_json`].
+    fn write_report_json(&self, path: &Path) -> Result<(), ReportJsonError> {
+        write_report_json(path, self)
+    }
+}
+
+impl JsonReport for FeatureTransformReport {
+    const KIND: &'static str = "feature_transform";
+    const SCHEMA_VERSION: u32 = 1;
+}
+
+impl JsonReport for KlineCoverageReport {
+    const KIND: &'static str = "kline_coverage";
+    const SCHEMA_VERSION: u32 = 1;
+}
+
+impl JsonReport for BacktestReport {
+    const KIND: &'static str = "backtest";
+    const SCHEMA_VERSION: u32 = 1;
+}
+
+/// On-disk layout of a report file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReportEnvelope<T> {
+    pub kind: String,
+    pub schema_version: u32,
+    pub generated_ts_utc: i64,
+    pub report: T,
+}
+
+#[derive(Debug, Error)]
+pub enum ReportJsonError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("expected a {expected} report, found {found}")]
+    KindMismatch {
+        expected: &'static str,
+        found: String,
+    },
+    #[error("{kind} report schema v{found} is newer than supported v{supported}")]
+    UnsupportedVersion {
+        kind: &'static str,
+        found: u32,
+        supported: u32,
+    },
+}
+
+/// Writes `report` to `path` as pretty-printed JSON in its envelope, creating parent
+/// directories. The file is replaced atomically, so readers never see a partial
+/// report.
+pub fn write_report_json<R: JsonReport>(path: &Path, report: &R) -> Result<(), ReportJsonError> {
+    let envelope = ReportEnvelope {
+        kind: R::KIND.to_string(),
+        schema_version: R::SCHEMA_VERSION,
+        generated_ts_utc: now_utc().timestamp(),
+        report,
+    };
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        std::fs::create_dir_all(parent)?;
+    }
+    let tmp_path = path.with_extension("json.tmp");
+    std::fs::write(&tmp_path, serde_json::to_vec_pretty(&envelope)?)?;
+    std::fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// Reads a report written by [`write_report_json`], checking its kind and version.
+pub fn read_report_json<R: JsonReport>(path: &Path) -> Result<ReportEnvelope<R>, ReportJsonError> {
+    let envelope: ReportEnvelope<serde_json::Value> =
+        serde_json::from_slice(&std::fs::read(path)?)?;
+    if envelope.kind != R::KIND {
+        return Err(ReportJsonError::KindMismatch {
+            expected: R::KIND,
+            found: envelope.kind,
+        });
+    }
+    if envelope.schema_version > R::SCHEMA_VERSION {
+        return Err(ReportJsonError::UnsupportedVersion {
+            kind: R::KIND,
+            found: envelope.schema_version,
+            supported: R::SCHEMA_VERSION,
+        });
+    }
+    Ok(ReportEnvelope {
+        kind: envelope.kind,
+        schema_version: envelope.schema_version,
+        generated_ts_utc: envelope.generated_ts_utc,
+        report: serde_json::from_value(envelope.report)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coverage() -> KlineCoverageReport {
+        KlineCoverageReport {
+            expected_points: 10,
+            actual_points: 8,
+            missing_points: 2,
+            duplicate_points_removed: 1,
+            total_gap_ranges: 1,
+            gap_ranges: vec![(3_000, 4_000)],
+        }
+    }
+
+    #[test]
+    fn reports_round_trip_in_a_versioned_envelope() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("reports/coverage.json");
+        coverage().write_report_json(&path).unwrap();
+
+        let raw: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(raw["kind"], "kline_coverage");
+        assert_eq!(raw["schema_version"], 1);
+        assert_eq!(raw["report"]["missing_points"], 2);
+        assert_eq!(raw["report"]["gap_ranges"][0][1], 4_000);
+
+        let envelope = read_report_json::<KlineCoverageReport>(&path).unwrap();
+        assert_eq!(envelope.report, coverage());
+        assert!(matches!(
+            read_report_json::<FeatureTransformReport>(&path),
+            Err(ReportJsonError::KindMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn newer_schema_versions_are_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("coverage.json");
+        let newer = ReportEnvelope {
+            kind: "kline_coverage".to_string(),
+            schema_version: 2,
+            generated_ts_utc: 0,
+            report: coverage(),
+        };
+        std::fs::write(&path, serde_json::to_vec(&newer).unwrap()).unwrap();
+        assert!(matches!(
+            read_report_json::<KlineCoverageReport>(&path),
+            Err(ReportJsonError::UnsupportedVersion { found: 2, .. })
+        ));
+    }
+}
@@ -0,0 +1,430 @@
+//! Reward earnings reconciliation.
+//!
+//! The reward estimator records what it expects each interval market to earn in a
+//! `reward_accruals` table ([`RewardAccrualStore`]). Polymarket pays liquidity rewards
+//! once per UTC day, shortly after midnight, for the previous day's accruals, so the
+//! reconciliation compares per UTC day (and per market where the payout names one):
+//! estimates are summed over intervals ending on the accrual day, payouts are fetched
+//! from the Data API's `REWARD` activity for the wallet and shifted back by
+//! `payout_lag_days`. Differences beyond the configured tolerance are flagged.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::env_vars::Settings;
+
+pub const DEFAULT_DATA_API_URL: &str = "https://data-api.polymarket.com";
+
+/// Activity rows requested per Data API page.
+const ACTIVITY_PAGE_SIZE: usize = 500;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RewardReconcileConfig {
+    /// Wallet (proxy/funder address) the rewards are paid to.
+    pub wallet: Option<String>,
+    pub data_api_url: String,
+    pub accrual_store_path: PathBuf,
+    /// Days reconciled, ending with yesterday (UTC).
+    pub lookback_days: u32,
+    /// Days between accrual and payout (`1`: day D is paid on D+1).
+    pub payout_lag_days: u32,
+    /// A day/market is flagged when `|actual - estimated|` exceeds both this ...
+    pub abs_tolerance_usdc: f64,
+    /// ... and this fraction of the actual payout.
+    pub rel_tolerance: f64,
+    pub timeout_ms: u64,
+}
+
+impl Default for RewardReconcileConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl RewardReconcileConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_WALLET_ADDRESS`, `PMM_DATA_API_URL`, `PMM_REWARD_ACCRUAL_STORE_PATH`,
+    /// `PMM_REWARD_LOOKBACK_DAYS`, `PMM_REWARD_PAYOUT_LAG_DAYS`,
+    /// `PMM_REWARD_ABS_TOLERANCE_USDC`, and `PMM_REWARD_REL_TOLERANCE`, falling back to
+    /// defaults for missing/invalid values.
+    pub fn from_settings(settings: &Settings) -> Self {
+        let non_negative = |value: &f64| *value >= 0.0;
+        Self {
+            wallet: settings.get("PMM_WALLET_ADDRESS"),
+            data_api_url: settings.get("PMM_DATA_API_URL"),
+            accrual_store_path: settings.get("PMM_REWARD_ACCRUAL_STORE_PATH"),
+            lookback_days: settings.get_where("PMM_REWARD_LOOKBACK_DAYS", |days| *days > 0),
+            payout_lag_days: settings.get("PMM_REWARD_PAYOUT_LAG_DAYS"),
+            abs_tolerance_usdc: settings.get_where("PMM_REWARD_ABS_TOLERANCE_USDC", non_negative),
+            rel_tolerance: settings.get_where("PMM_REWARD_REL_TOLERANCE", non_negative),
+            timeout_ms: 10_000,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum RewardReconcileError {
+    #[error("sqlite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("http error: {0}")]
+    Http(String),
+    #[error("unexpected Data API response: {0}")]
+    Decode(String),
+    #[error("PMM_WALLET_ADDRESS not set")]
+    MissingWallet,
+}
+
+/// Estimated reward for one interval market.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RewardAccrual {
+    pub slug: String,
+    pub start_ts_utc: i64,
+    pub end_ts_utc: i64,
+    pub estimated_usdc: f64,
+}
+
+/// One reward payout to the wallet.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RewardPayout {
+    pub paid_ts_utc: i64,
+    /// Market the payout is for, when the activity row names one.
+    pub slug: Option<String>,
+    pub amount_usdc: f64,
+}
+
+/// SQLite store of estimated accruals (`reward_accruals`), keyed by slug.
+pub struct RewardAccrualStore {
+    conn: Connection,
+}
+
+impl RewardAccrualStore {
+    pub fn open(path: &Path) -> Result<Self, RewardReconcileError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "
+            PRAGMA journal_mode=WAL;
+            CREATE TABLE IF NOT EXISTS reward_accruals (
+                slug TEXT PRIMARY KEY,
+                start_ts_utc INTEGER NOT NULL,
+                end_ts_utc INTEGER NOT NULL,
+                estimated_usdc REAL NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS reward_accruals_end ON reward_accruals(end_ts_utc);
+            ",
+        )?;
+        Ok(Self { conn })
+    }
+
+    /// Inserts or replaces the estimate for `accrual.slug`.
+    pub fn upsert(&self, accrual: &RewardAccrual) -> Result<(), RewardReconcileError> {
+        self.conn.execute(
+            "INSERT INTO reward_accruals (slug, start_ts_utc, end_ts_utc, estimated_usdc)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(slug) DO UPDATE SET
+                 start_ts_utc = excluded.start_ts_utc,
+                 end_ts_utc = excluded.end_ts_utc,
+                 estimated_usdc = excluded.estimated_usdc",
+            params![
+                accrual.slug,
+                accrual.start_ts_utc,
+                accrual.end_ts_utc,
+                accrual.estimated_usdc
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Accruals whose interval ends in `[start_ts_utc, end_ts_utc)`.
+    pub fn accruals_ending_between(
+        &self,
+        start_ts_utc: i64,
+        end_ts_utc: i64,
+    ) -> Result<Vec<RewardAccrual>, RewardReconcileError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT slug, start_ts_utc, end_ts_utc, estimated_usdc FROM reward_accruals
+             WHERE end_ts_utc >= ?1 AND end_ts_utc < ?2 ORDER BY end_ts_utc, slug",
+        )?;
+        let rows = stmt.query_map(params![start_ts_utc, end_ts_utc], |row| {
+            Ok(RewardAccrual {
+                slug: row.get(0)?,
+                start_ts_utc: row.get(1)?,
+                end_ts_utc: row.get(2)?,
+                estimated_usdc: row.get(3)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ActivityRow {
+    timestamp: i64,
+    #[serde(default)]
+    slug: Option<String>,
+    #[serde(default)]
+    usdc_size: Option<f64>,
+}
+
+/// Parses one Data API `/activity?type=REWARD` page.
+pub fn parse_reward_activity(body: &[u8]) -> Result<Vec<RewardPayout>, RewardReconcileError> {
+    let rows: Vec<ActivityRow> = serde_json::from_slice(body)
+        .map_err(|err| RewardReconcileError::Decode(err.to_string()))?;
+    Ok(rows
+        .into_iter()
+        .map(|row| RewardPayout {
+            paid_ts_utc: row.timestamp,
+            slug: row.slug.filter(|slug| !slug.trim().is_empty()),
+            amount_usdc: row.usdc_size.unwrap_or(0.0),
+        })
+        .collect())
+}
+
+/// Fetches reward payouts for `cfg.wallet` paid in `[start_ts_utc, end_ts_utc)`.
+pub async fn fetch_reward_payouts(
+    cfg: &RewardReconcileConfig,
+    start_ts_utc: i64,
+    end_ts_utc: i64,
+) -> Result<Vec<RewardPayout>, RewardReconcileError> {
+    let wallet = cfg
+        .wallet
+        .as_deref()
+        .ok_or(RewardReconcileError::MissingWallet)?;
+    let http_err = |err: reqwest::Error| RewardReconcileError::Http(err.to_string());
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_millis(cfg.timeout_ms))
+        .build()
+        .map_err(http_err)?;
+    let url = format!("{}/activity", cfg.data_api_url.trim_end_matches('/'));
+
+    let mut payouts = Vec::new();
+    for page in 0.. {
+        let body = client
+            .get(&url)
+            .query(&[
+                ("user", wallet.to_string()),
+                ("type", "REWARD".to_string()),
+                ("start", start_ts_utc.to_string()),
+                ("end", (end_ts_utc - 1).to_string()),
+                ("limit", ACTIVITY_PAGE_SIZE.to_string()),
+                ("offset", (page * ACTIVITY_PAGE_SIZE).to_string()),
+            ])
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(http_err)?
+            .bytes()
+            .await
+            .map_err(http_err)?;
+        let rows = parse_reward_activity(&body)?;
+        let done = rows.len() < ACTIVITY_PAGE_SIZE;
+        payouts.extend(
+            rows.into_iter()
+                .filter(|row| (start_ts_utc..end_ts_utc).contains(&row.paid_ts_utc)),
+        );
+        if done {
+            break;
+        }
+    }
+    Ok(payouts)
+}
+
+/// Estimated vs. actual rewards for one accrual day and market (`slug = None`:
+/// payouts that name no market).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RewardDiscrepancy {
+    pub accrual_day: NaiveDate,
+    pub slug: Option<String>,
+    pub estimated_usdc: f64,
+    pub actual_usdc: f64,
+    /// `actual - estimated`.
+    pub diff_usdc: f64,
+    pub flagged: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RewardDayTotal {
+    pub accrual_day: NaiveDate,
+    pub estimated_usdc: f64,
+    pub actual_usdc: f64,
+    pub diff_usdc: f64,
+    pub flagged: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RewardReconciliation {
+    /// Per day, oldest first.
+    pub days: Vec<RewardDayTotal>,
+    /// Per day and market, oldest day first, then by slug.
+    pub markets: Vec<RewardDiscrepancy>,
+}
+
+impl RewardReconciliation {
+    pub fn flagged_count(&self) -> usize {
+        self.days.iter().filter(|day| day.flagged).count()
+            + self.markets.iter().filter(|market| market.flagged).count()
+    }
+}
+
+fn utc_day(ts_utc: i64) -> Option<NaiveDate> {
+    DateTime::<Utc>::from_timestamp(ts_utc, 0).map(|dt| dt.date_naive())
+}
+
+fn exceeds_tolerance(estimated: f64, actual: f64, cfg: &RewardReconcileConfig) -> bool {
+    let diff = (actual - estimated).abs();
+    diff > cfg.abs_tolerance_usdc && diff > cfg.rel_tolerance * actual.abs()
+}
+
+/// Groups accruals by the UTC day their interval ends and payouts by the day they
+/// were paid minus `payout_lag_days`, then compares per day and per market.
+pub fn reconcile_rewards(
+    accruals: &[RewardAccrual],
+    payouts: &[RewardPayout],
+    cfg: &RewardReconcileConfig,
+) -> RewardReconciliation {
+    let mut markets: BTreeMap<(NaiveDate, Option<String>), (f64, f64)> = BTreeMap::new();
+    for accrual in accruals {
+        if let Some(day) = utc_day(accrual.end_ts_utc) {
+            markets
+                .entry((day, Some(accrual.slug.clone())))
+                .or_default()
+                .0 += accrual.estimated_usdc;
+        }
+    }
+    for payout in payouts {
+        let Some(day) = utc_day(payout.paid_ts_utc)
+            .and_then(|day| day.checked_sub_days(Days::new(u64::from(cfg.payout_lag_days))))
+        else {
+            continue;
+        };
+        markets.entry((day, payout.slug.clone())).or_default().1 += payout.amount_usdc;
+    }
+
+    let mut days: BTreeMap<NaiveDate, (f64, f64)> = BTreeMap::new();
+    let markets = markets
+        .into_iter()
+        .map(|((accrual_day, slug), (estimated_usdc, actual_usdc))| {
+            let day = days.entry(accrual_day).or_default();
+            day.0 += estimated_usdc;
+            day.1 += actual_usdc;
+            RewardDiscrepancy {
+                accrual_day,
+                // Unattributed payouts are only meaningful in the day total.
+                flagged: slug.is_some() && exceeds_tolerance(estimated_usdc, actual_usdc, cfg),
+                slug,
+                estimated_usdc,
+                actual_usdc,
+                diff_usdc: actual_usdc - estimated_usdc,
+            }
+        })
+        .collect();
+    let days = days
+        .into_iter()
+        .map(
+            |(accrual_day, (estimated_usdc, actual_usdc))| RewardDayTotal {
+                accrual_day,
+                estimated_usdc,
+                actual_usdc,
+                diff_usdc: actual_usdc - estimated_usdc,
+                flagged: exceeds_tolerance(estimated_usdc, actual_usdc, cfg),
+            },
+        )
+        .collect();
+    RewardReconciliation { days, markets }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 86_400;
+    // 2025-01-01 00:00:00 UTC
+    const JAN_1: i64 = 1_735_689_600;
+
+    fn accrual(slug: &str, end_ts_utc: i64, estimated_usdc: f64) -> RewardAccrual {
+        RewardAccrual {
+            slug: slug.to_string(),
+            start_ts_utc: end_ts_utc - 300,
+            end_ts_utc,
+            estimated_usdc,
+        }
+    }
+
+    #[test]
+    fn parses_reward_activity_rows() {
+        let body = br#"[
+            {"proxyWallet":"0xabc","timestamp":1735776000,"type":"REWARD","usdcSize":1.25,"slug":"btc-updown-5m-1735689900"},
+            {"proxyWallet":"0xabc","timestamp":1735776001,"type":"REWARD","usdcSize":0.5,"slug":""}
+        ]"#;
+        let payouts = parse_reward_activity(body).unwrap();
+        assert_eq!(payouts.len(), 2);
+        assert_eq!(payouts[0].slug.as_deref(), Some("btc-updown-5m-1735689900"));
+        assert_eq!(payouts[0].amount_usdc, 1.25);
+        assert_eq!(payouts[1].slug, None);
+    }
+
+    #[test]
+    fn reconciles_payouts_against_previous_day_accruals() {
+        let cfg = RewardReconcileConfig::default();
+        let accruals = [
+            accrual("btc-a", JAN_1 + 600, 2.0),
+            accrual("btc-a", JAN_1 + 900, 1.0),
+            accrual("eth-b", JAN_1 + 1_200, 4.0),
+        ];
+        let paid = JAN_1 + DAY + 60;
+        let payouts = [
+            RewardPayout {
+                paid_ts_utc: paid,
+                slug: Some("btc-a".to_string()),
+                amount_usdc: 2.9,
+            },
+            RewardPayout {
+                paid_ts_utc: paid,
+                slug: Some("eth-b".to_string()),
+                amount_usdc: 1.0,
+            },
+        ];
+
+        let report = reconcile_rewards(&accruals, &payouts, &cfg);
+        let jan_1 = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        assert_eq!(report.days.len(), 1);
+        assert_eq!(report.days[0].accrual_day, jan_1);
+        assert_eq!(report.days[0].estimated_usdc, 7.0);
+        assert!((report.days[0].diff_usdc + 3.1).abs() < 1e-9);
+        assert!(report.days[0].flagged);
+
+        let flagged = report
+            .markets
+            .iter()
+            .map(|market| (market.slug.as_deref().unwrap(), market.flagged))
+            .collect::<Vec<_>>();
+        assert_eq!(flagged, [("btc-a", false), ("eth-b", true)]);
+        assert_eq!(report.flagged_count(), 2);
+    }
+
+    #[test]
+    fn accrual_store_upserts_and_filters_by_interval_end() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = RewardAccrualStore::open(&dir.path().join("accruals.sqlite")).unwrap();
+        store.upsert(&accrual("btc-a", JAN_1 + 300, 1.0)).unwrap();
+        store.upsert(&accrual("btc-a", JAN_1 + 300, 1.5)).unwrap();
+        store.upsert(&accrual("btc-b", JAN_1 + DAY, 2.0)).unwrap();
+
+        let day = store.accruals_ending_between(JAN_1, JAN_1 + DAY).unwrap();
+        assert_eq!(day, [accrual("btc-a", JAN_1 + 300, 1.5)]);
+    }
+}
@@ -0,0 +1,439 @@
+//! Portfolio-level risk: aggregate directional exposure across correlated coins.
+//!
+//! Per-market limits ([`crate::QuoteRiskLimits`]) do not see that BTC and ETH up/down
+//! outcomes move together. Each market's position is reduced to a signed "up" exposure
+//! in USDC (YES positive, NO negative); exposures of one coin add up across durations,
+//! and the portfolio exposure is `sqrt(e' C e)` over the per-coin vector `e` with
+//! correlation matrix `C`. A quote is blocked when it would push that above
+//! `max_exposure_usdc`; quotes that reduce the exposure are always allowed.
+//!
+//! Markets of one neg-risk event share collateral: a NO in one converts into YES on
+//! every other outcome, so netting them per coin would understate what is at stake.
+//! Their positions also count, gross, against `max_group_usdc` per group.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::env_vars::Settings;
+use crate::sizing::SizingSide;
+use crate::slug::{parse_coin, Coin, Duration, MAX_COINS};
+
+/// Symmetric coin-by-coin correlation of up/down outcomes, indexed by
+/// [`Coin::index`]. Coins without an entry are uncorrelated with the rest.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CorrelationMatrix([[f64; MAX_COINS]; MAX_COINS]);
+
+impl Default for CorrelationMatrix {
+    /// Rough 1s-return correlations of the majors; override with
+    /// `PMM_RISK_CORRELATIONS`.
+    fn default() -> Self {
+        let mut matrix = Self::identity();
+        for (a, b, rho) in [
+            (Coin::BTC, Coin::ETH, 0.85),
+            (Coin::BTC, Coin::SOL, 0.75),
+            (Coin::BTC, Coin::XRP, 0.65),
+            (Coin::ETH, Coin::SOL, 0.75),
+            (Coin::ETH, Coin::XRP, 0.65),
+            (Coin::SOL, Coin::XRP, 0.6),
+        ] {
+            matrix.set(a, b, rho);
+        }
+        matrix
+    }
+}
+
+impl CorrelationMatrix {
+    /// Uncorrelated coins.
+    pub fn identity() -> Self {
+        let mut matrix = [[0.0; MAX_COINS]; MAX_COINS];
+        for (index, row) in matrix.iter_mut().enumerate() {
+            row[index] = 1.0;
+        }
+        Self(matrix)
+    }
+
+    pub fn get(&self, a: Coin, b: Coin) -> f64 {
+        self.0[a.index()][b.index()]
+    }
+
+    /// Sets both `(a, b)` and `(b, a)`; the diagonal stays `1`.
+    pub fn set(&mut self, a: Coin, b: Coin, rho: f64) {
+        if a == b {
+            return;
+        }
+        let (a, b) = (a.index(), b.index());
+        self.0[a][b] = rho;
+        self.0[b][a] = rho;
+    }
+
+    /// Parses `BTC:ETH=0.85,BTC:SOL=0.7` on top of the defaults; `None` when any entry
+    /// is malformed or outside `[-1, 1]`.
+    pub fn parse(raw: &str) -> Option<Self> {
+        let mut matrix = Self::default();
+        for entry in raw
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+        {
+            let (pair, rho) = entry.split_once('=')?;
+            let (a, b) = pair.split_once(':')?;
+            let a = parse_coin(&a.trim().to_ascii_uppercase()).ok()?;
+            let b = parse_coin(&b.trim().to_ascii_uppercase()).ok()?;
+            let rho = rho.trim().parse::<f64>().ok()?;
+            if !(-1.0..=1.0).contains(&rho) {
+                return None;
+            }
+            matrix.set(a, b, rho);
+        }
+        Some(matrix)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PortfolioRiskConfig {
+    /// Cap on the correlated aggregate directional exposure in USDC.
+    pub max_exposure_usdc: f64,
+    pub correlations: CorrelationMatrix,
+    /// Cap on the gross USDC across the markets of one neg-risk group.
+    pub max_group_usdc: f64,
+}
+
+impl Default for PortfolioRiskConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl PortfolioRiskConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_RISK_MAX_PORTFOLIO_USDC`, `PMM_RISK_CORRELATIONS`, and
+    /// `PMM_RISK_MAX_GROUP_USDC`, falling back to defaults for missing/invalid values.
+    pub fn from_settings(settings: &Settings) -> Self {
+        let usdc = |name: &str| settings.get_where(name, |value: &f64| *value >= 0.0);
+        Self {
+            max_exposure_usdc: usdc("PMM_RISK_MAX_PORTFOLIO_USDC"),
+            correlations: settings
+                .parsed("PMM_RISK_CORRELATIONS", CorrelationMatrix::parse)
+                .unwrap_or_default(),
+            max_group_usdc: usdc("PMM_RISK_MAX_GROUP_USDC"),
+        }
+    }
+}
+
+/// Directional exposure of one market position or proposed quote.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarketExposure {
+    pub slug: String,
+    pub coin: Coin,
+    pub duration: Duration,
+    /// USDC at risk on "up"; negative when the position is on "down".
+    pub up_usdc: f64,
+    /// Neg-risk group of the market (see [`crate::MarketMeta::neg_risk_group`]).
+    pub neg_risk_group: Option<String>,
+}
+
+impl MarketExposure {
+    /// Exposure of `usdc` spent on `side` (YES is "up").
+    pub fn new(
+        slug: impl Into<String>,
+        coin: Coin,
+        duration: Duration,
+        side: SizingSide,
+        usdc: f64,
+    ) -> Self {
+        Self {
+            slug: slug.into(),
+            coin,
+            duration,
+            up_usdc: match side {
+                SizingSide::Yes => usdc,
+                SizingSide::No => -usdc,
+            },
+            neg_risk_group: None,
+        }
+    }
+
+    pub fn in_neg_risk_group(mut self, group: impl Into<String>) -> Self {
+        self.neg_risk_group = Some(group.into());
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PortfolioCheck {
+    pub exposure_usdc: f64,
+    /// Exposure if the quote filled in full.
+    pub proposed_exposure_usdc: f64,
+    /// Gross USDC of the quote's neg-risk group, before and after; `0` outside a
+    /// group.
+    #[serde(default)]
+    pub group_exposure_usdc: f64,
+    #[serde(default)]
+    pub proposed_group_exposure_usdc: f64,
+    pub allowed: bool,
+}
+
+/// Current portfolio exposure and the admission rule for new quotes.
+#[derive(Debug, Clone)]
+pub struct PortfolioRisk {
+    cfg: PortfolioRiskConfig,
+    per_coin: [f64; MAX_COINS],
+    /// Net "up" USDC per market, per neg-risk group.
+    per_group: HashMap<String, HashMap<String, f64>>,
+}
+
+impl PortfolioRisk {
+    pub fn new(cfg: PortfolioRiskConfig, positions: &[MarketExposure]) -> Self {
+        let mut risk = Self {
+            cfg,
+            per_coin: [0.0; MAX_COINS],
+            per_group: HashMap::new(),
+        };
+        for position in positions {
+            risk.add(position);
+        }
+        risk
+    }
+
+    /// Net "up" USDC of `coin` across all its durations.
+    pub fn coin_exposure_usdc(&self, coin: Coin) -> f64 {
+        self.per_coin[coin.index()]
+    }
+
+    /// `sqrt(e' C e)` over the per-coin exposures.
+    pub fn exposure_usdc(&self) -> f64 {
+        exposure(&self.per_coin, &self.cfg.correlations)
+    }
+
+    /// Gross USDC across the markets of neg-risk `group`: each market's net position,
+    /// unsigned, since a position on any outcome is collateral of the whole event.
+    pub fn group_exposure_usdc(&self, group: &str) -> f64 {
+        self.per_group
+            .get(group)
+            .map_or(0.0, |markets| markets.values().map(|usdc| usdc.abs()).sum())
+    }
+
+    /// Whether `quote` may be placed on top of the current positions: within both the
+    /// portfolio and its group cap, or reducing whichever it would exceed.
+    pub fn check(&self, quote: &MarketExposure) -> PortfolioCheck {
+        let exposure_usdc = self.exposure_usdc();
+        let mut proposed = self.per_coin;
+        proposed[quote.coin.index()] += quote.up_usdc;
+        let proposed_exposure_usdc = exposure(&proposed, &self.cfg.correlations);
+        let (group_exposure_usdc, proposed_group_exposure_usdc) = match &quote.neg_risk_group {
+            Some(group) => {
+                let current = self.group_exposure_usdc(group);
+                let market = self
+                    .per_group
+                    .get(group)
+                    .and_then(|markets| markets.get(&quote.slug))
+                    .copied()
+                    .unwrap_or(0.0);
+                (
+                    current,
+                    current - market.abs() + (market + quote.up_usdc).abs(),
+                )
+            }
+            None => (0.0, 0.0),
+        };
+        PortfolioCheck {
+            exposure_usdc,
+            proposed_exposure_usdc,
+            group_exposure_usdc,
+            proposed_group_exposure_usdc,
+            allowed: (proposed_exposure_usdc <= self.cfg.max_exposure_usdc
+                || proposed_exposure_usdc <= exposure_usdc)
+                && (proposed_group_exposure_usdc <= self.cfg.max_group_usdc
+                    || proposed_group_exposure_usdc <= group_exposure_usdc),
+        }
+    }
+
+    pub fn add(&mut self, exposure: &MarketExposure) {
+        self.per_coin[exposure.coin.index()] += exposure.up_usdc;
+        if let Some(group) = &exposure.neg_risk_group {
+            *self
+                .per_group
+                .entry(group.clone())
+                .or_default()
+                .entry(exposure.slug.clone())
+                .or_default() += exposure.up_usdc;
+        }
+    }
+
+    /// Admits `quotes` in order, counting each admitted quote as filled for the ones
+    /// after it. Returns the admitted and the blocked quotes; blocks are logged as
+    /// `risk.portfolio.blocked`.
+    pub fn admit_quotes(
+        &mut self,
+        quotes: Vec<MarketExposure>,
+    ) -> (Vec<MarketExposure>, Vec<MarketExposure>) {
+        let mut admitted = Vec::new();
+        let mut blocked = Vec::new();
+        for quote in quotes {
+            let check = self.check(&quote);
+            if check.allowed {
+                self.add(&quote);
+                admitted.push(quote);
+            } else {
+                warn!(
+                    component = "risk",
+                    event = "risk.portfolio.blocked",
+                    slug = %quote.slug,
+                    coin = quote.coin.ticker(),
+                    duration = quote.duration.as_label(),
+                    up_usdc = quote.up_usdc,
+                    exposure_usdc = check.exposure_usdc,
+                    proposed_exposure_usdc = check.proposed_exposure_usdc,
+                    max_exposure_usdc = self.cfg.max_exposure_usdc,
+                    neg_risk_group = quote.neg_risk_group.as_deref().unwrap_or(""),
+                    proposed_group_exposure_usdc = check.proposed_group_exposure_usdc
+                );
+                blocked.push(quote);
+            }
+        }
+        (admitted, blocked)
+    }
+}
+
+fn exposure(per_coin: &[f64; MAX_COINS], correlations: &CorrelationMatrix) -> f64 {
+    let coins = Coin::all();
+    let mut variance = 0.0;
+    for &a in &coins {
+        for &b in &coins {
+            variance += per_coin[a.index()] * correlations.get(a, b) * per_coin[b.index()];
+        }
+    }
+    // Negative correlations can make a non-PSD matrix dip below zero.
+    variance.max(0.0).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exposure(coin: Coin, side: SizingSide, usdc: f64) -> MarketExposure {
+        MarketExposure::new("m", coin, Duration::M5, side, usdc)
+    }
+
+    #[test]
+    fn correlated_coins_add_up_and_hedges_net_out() {
+        let cfg = PortfolioRiskConfig {
+            max_exposure_usdc: 150.0,
+            ..PortfolioRiskConfig::default()
+        };
+        let mut risk = PortfolioRisk::new(
+            cfg,
+            &[
+                exposure(Coin::BTC, SizingSide::Yes, 60.0),
+                MarketExposure::new("m15", Coin::BTC, Duration::M15, SizingSide::Yes, 20.0),
+            ],
+        );
+        assert_eq!(risk.coin_exposure_usdc(Coin::BTC), 80.0);
+
+        // sqrt(80^2 + 2 * 0.85 * 80 * 80 + 80^2) ~= 155.4: over the cap although each
+        // market is small.
+        let eth_up = risk.check(&exposure(Coin::ETH, SizingSide::Yes, 80.0));
+        assert!(!eth_up.allowed);
+        assert!((eth_up.proposed_exposure_usdc - 80.0 * 3.7_f64.sqrt()).abs() < 1e-9);
+
+        // Uncorrelated, the same quote fits: sqrt(2) * 80 ~= 113.
+        let independent = PortfolioRisk::new(
+            PortfolioRiskConfig {
+                correlations: CorrelationMatrix::identity(),
+                ..cfg
+            },
+            &[exposure(Coin::BTC, SizingSide::Yes, 80.0)],
+        );
+        assert!(
+            independent
+                .check(&exposure(Coin::ETH, SizingSide::Yes, 80.0))
+                .allowed
+        );
+
+        let (admitted, blocked) = risk.admit_quotes(vec![
+            exposure(Coin::ETH, SizingSide::No, 80.0),
+            exposure(Coin::SOL, SizingSide::Yes, 200.0),
+        ]);
+        assert_eq!(admitted.len(), 1);
+        assert_eq!(blocked[0].coin, Coin::SOL);
+        assert!(risk.exposure_usdc() < 80.0);
+    }
+
+    #[test]
+    fn quotes_that_reduce_exposure_pass_even_over_the_cap() {
+        let risk = PortfolioRisk::new(
+            PortfolioRiskConfig {
+                max_exposure_usdc: 10.0,
+                ..PortfolioRiskConfig::default()
+            },
+            &[exposure(Coin::BTC, SizingSide::Yes, 100.0)],
+        );
+        assert!(
+            risk.check(&exposure(Coin::BTC, SizingSide::No, 30.0))
+                .allowed
+        );
+        assert!(
+            !risk
+                .check(&exposure(Coin::BTC, SizingSide::Yes, 1.0))
+                .allowed
+        );
+    }
+
+    #[test]
+    fn neg_risk_groups_cap_gross_exposure_across_their_markets() {
+        let cfg = PortfolioRiskConfig {
+            max_group_usdc: 50.0,
+            ..PortfolioRiskConfig::default()
+        };
+        let grouped = |slug: &str, side, usdc| {
+            MarketExposure::new(slug, Coin::BTC, Duration::D1, side, usdc).in_neg_risk_group("0x42")
+        };
+        let mut risk = PortfolioRisk::new(
+            cfg,
+            &[
+                grouped("btc-above-90k", SizingSide::Yes, 30.0),
+                grouped("btc-above-100k", SizingSide::No, 15.0),
+            ],
+        );
+        // The YES and the NO net out per coin but not within the group.
+        assert_eq!(risk.coin_exposure_usdc(Coin::BTC), 15.0);
+        assert_eq!(risk.group_exposure_usdc("0x42"), 45.0);
+
+        let check = risk.check(&grouped("btc-above-110k", SizingSide::Yes, 10.0));
+        assert!(!check.allowed);
+        assert_eq!(check.proposed_group_exposure_usdc, 55.0);
+        // Reducing a grouped position, or trading outside the group, still passes.
+        assert!(
+            risk.check(&grouped("btc-above-90k", SizingSide::No, 10.0))
+                .allowed
+        );
+        assert!(
+            risk.check(&exposure(Coin::BTC, SizingSide::Yes, 10.0))
+                .allowed
+        );
+        let (admitted, blocked) = risk.admit_quotes(vec![
+            grouped("btc-above-110k", SizingSide::Yes, 5.0),
+            grouped("btc-above-120k", SizingSide::Yes, 5.0),
+        ]);
+        assert_eq!((admitted.len(), blocked.len()), (1, 1));
+        assert_eq!(risk.group_exposure_usdc("0x42"), 50.0);
+    }
+
+    #[test]
+    fn correlation_overrides_parse_symmetrically() {
+        let matrix = CorrelationMatrix::parse("btc:eth=0.5, SOL:XRP=-0.2").unwrap();
+        assert_eq!(matrix.get(Coin::ETH, Coin::BTC), 0.5);
+        assert_eq!(matrix.get(Coin::XRP, Coin::SOL), -0.2);
+        assert_eq!(matrix.get(Coin::BTC, Coin::SOL), 0.75);
+        assert_eq!(matrix.get(Coin::BTC, Coin::BTC), 1.0);
+        assert!(CorrelationMatrix::parse("BTC:ETH=1.5").is_none());
+        assert!(CorrelationMatrix::parse("BTC-ETH=0.5").is_none());
+        assert!(CorrelationMatrix::parse("BTC:DOGE=0.5").is_none());
+    }
+}
@@ -0,0 +1,126 @@
+//! `/discovery/schedule`: the slugs discovery will need next, for external schedulers.
+//!
+//! Slugs follow a deterministic calendar, so warm-up jobs (e.g. pre-fetching order
+//! books) can be driven off the same [`ScheduledDiscoveryKey`]s discovery resolves,
+//! before the markets become active.
+
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+
+use crate::discovery::{build_upcoming_discovery_keys, DiscoveryUniverse, ScheduledDiscoveryKey};
+use crate::slug::{SlugConfig, SlugError};
+
+/// Largest `horizon` accepted by `/discovery/schedule`.
+pub const MAX_SCHEDULE_HORIZON: usize = 96;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiscoverySchedule {
+    pub now_ts_utc: i64,
+    /// Upcoming intervals per coin/duration.
+    pub horizon: usize,
+    pub keys: Vec<ScheduledDiscoveryKey>,
+}
+
+/// The next `horizon` intervals after the active one for every market in `universe`.
+pub fn discovery_schedule(
+    now_ts_utc: i64,
+    universe: &DiscoveryUniverse,
+    horizon: usize,
+    slug_cfg: SlugConfig,
+) -> Result<DiscoverySchedule, SlugError> {
+    Ok(DiscoverySchedule {
+        now_ts_utc,
+        horizon,
+        keys: build_upcoming_discovery_keys(
+            now_ts_utc,
+            &universe.coins,
+            &universe.durations,
+            horizon,
+            slug_cfg,
+        )?,
+    })
+}
+
+#[derive(Clone)]
+struct ScheduleState {
+    universe: DiscoveryUniverse,
+    slug_cfg: SlugConfig,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ScheduleQuery {
+    horizon: Option<usize>,
+}
+
+/// Router serving `GET /discovery/schedule?horizon=N` (default `1`).
+pub fn discovery_schedule_router(universe: DiscoveryUniverse, slug_cfg: SlugConfig) -> Router {
+    Router::new()
+        .route("/discovery/schedule", get(get_discovery_schedule))
+        .with_state(ScheduleState { universe, slug_cfg })
+}
+
+async fn get_discovery_schedule(
+    State(state): State<ScheduleState>,
+    Query(query): Query<ScheduleQuery>,
+) -> Response {
+    let horizon = query.horizon.unwrap_or(1);
+    if !(1..=MAX_SCHEDULE_HORIZON).contains(&horizon) {
+        return (
+            StatusCode::BAD_REQUEST,
+            format!("horizon must be within 1..={MAX_SCHEDULE_HORIZON}"),
+        )
+            .into_response();
+    }
+    let now_ts_utc = crate::faults::now_utc().timestamp();
+    match discovery_schedule(now_ts_utc, &state.universe, horizon, state.slug_cfg) {
+        Ok(schedule) => Json(schedule).into_response(),
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery::DiscoveryWindow;
+    use crate::slug::{Coin, Duration};
+
+    // 2025-01-01T00:02:00Z
+    const NOW: i64 = 1_735_689_720;
+
+    #[test]
+    fn schedule_lists_the_next_intervals_in_order() {
+        let universe = DiscoveryUniverse {
+            coins: vec![Coin::BTC, Coin::ETH],
+            durations: vec![Duration::M5, Duration::H4],
+            pairs: Vec::new(),
+        };
+        let schedule = discovery_schedule(NOW, &universe, 3, SlugConfig::default()).unwrap();
+        assert_eq!(schedule.keys.len(), 2 * 2 * 3);
+        assert!(schedule
+            .keys
+            .iter()
+            .all(|scheduled| scheduled.window == DiscoveryWindow::Next));
+
+        let btc_5m = schedule
+            .keys
+            .iter()
+            .filter(|scheduled| {
+                scheduled.key.coin == Coin::BTC && scheduled.key.duration == Duration::M5
+            })
+            .map(|scheduled| scheduled.key.start_ts_utc)
+            .collect::<Vec<_>>();
+        assert_eq!(btc_5m, [NOW - 120 + 300, NOW - 120 + 600, NOW - 120 + 900]);
+
+        let json = serde_json::to_value(&schedule.keys[0]).unwrap();
+        assert_eq!(json["window"], "next");
+        assert_eq!(json["key"]["coin"], "BTC");
+        assert_eq!(json["key"]["duration"], "5m");
+        assert_eq!(json["key"]["slug"], "btc-updown-5m-1735689900");
+        let back: ScheduledDiscoveryKey = serde_json::from_value(json).unwrap();
+        assert_eq!(back, schedule.keys[0]);
+    }
+}
@@ -0,0 +1,355 @@
+//! Trade sizing: capped fractional Kelly for binary YES/NO contracts.
+//!
+//! A share pays 1 USDC if its outcome wins. Buying at price `p` with a taker fee of
+//! `fee` USDC per share costs `c = p + fee`, so the full-Kelly bankroll fraction is
+//! `(q - c) / (1 - c)` for win probability `q`. The NO side is priced virtually at
+//! `1 - p` from the YES book. The bankroll is an input: callers pass the wallet
+//! balance, or `per_market_bankroll` when no wallet is wired in.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::env_vars::Settings;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SizingConfig {
+    /// Multiplier applied to the full-Kelly fraction (e.g. `0.25` = quarter Kelly).
+    pub kelly_fraction: f64,
+    /// Hard cap on the stake as a fraction of the bankroll.
+    pub max_bankroll_fraction: f64,
+    /// Hard cap on the stake in USDC for a single market.
+    pub per_market_bankroll: f64,
+    /// Minimum expected log-growth per trade for a stake to be suggested.
+    pub gain_threshold: f64,
+    /// Minimum stake in USDC; smaller suggestions are dropped.
+    pub size_threshold: f64,
+}
+
+impl Default for SizingConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl SizingConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_SIZING_KELLY_FRACTION`, `PMM_SIZING_MAX_BANKROLL_FRACTION`,
+    /// `PMM_SIZING_PER_MARKET_BANKROLL`, `PMM_SIZING_GAIN_THRESHOLD`, and
+    /// `PMM_SIZING_SIZE_THRESHOLD`, falling back to defaults for missing/invalid values.
+    pub fn from_settings(settings: &Settings) -> Self {
+        let read = |name: &str| settings.get_where(name, |value: &f64| *value >= 0.0);
+        Self {
+            kelly_fraction: read("PMM_SIZING_KELLY_FRACTION"),
+            max_bankroll_fraction: read("PMM_SIZING_MAX_BANKROLL_FRACTION"),
+            per_market_bankroll: read("PMM_SIZING_PER_MARKET_BANKROLL"),
+            gain_threshold: read("PMM_SIZING_GAIN_THRESHOLD"),
+            size_threshold: read("PMM_SIZING_SIZE_THRESHOLD"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SizingInput {
+    /// Model probability that YES resolves true.
+    pub probability_yes: f64,
+    /// Current YES price (the NO price is taken as `1 - price_yes`).
+    pub price_yes: f64,
+    /// Taker fee in USDC per share at the traded price.
+    pub fee_per_share: f64,
+    /// Available bankroll in USDC.
+    pub bankroll: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SizingSide {
+    Yes,
+    No,
+}
+
+impl SizingSide {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Yes => "YES",
+            Self::No => "NO",
+        }
+    }
+}
+
+/// Which constraint determined the final stake.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SizingLimit {
+    NoEdge,
+    BelowGainThreshold,
+    BelowSizeThreshold,
+    BankrollFraction,
+    PerMarketBankroll,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SizingDecision {
+    pub side: Option<SizingSide>,
+    /// Fee-adjusted cost per share on the chosen side.
+    pub cost_per_share: f64,
+    /// Unscaled Kelly fraction of the bankroll.
+    pub full_kelly_fraction: f64,
+    /// Final stake as a fraction of the bankroll after scaling and caps.
+    pub stake_fraction: f64,
+    pub stake_usdc: f64,
+    pub shares: f64,
+    /// Expected log-growth of the bankroll at the final stake.
+    pub expected_log_growth: f64,
+    pub limit: Option<SizingLimit>,
+}
+
+impl SizingDecision {
+    fn no_trade(limit: SizingLimit) -> Self {
+        Self {
+            side: None,
+            cost_per_share: 0.0,
+            full_kelly_fraction: 0.0,
+            stake_fraction: 0.0,
+            stake_usdc: 0.0,
+            shares: 0.0,
+            expected_log_growth: 0.0,
+            limit: Some(limit),
+        }
+    }
+
+    pub fn is_trade(&self) -> bool {
+        self.side.is_some() && self.stake_usdc > 0.0
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum SizingError {
+    #[error("probability must be within [0, 1], got {0}")]
+    InvalidProbability(f64),
+    #[error("price must be within (0, 1), got {0}")]
+    InvalidPrice(f64),
+    #[error("fee per share must be finite and non-negative, got {0}")]
+    InvalidFee(f64),
+    #[error("bankroll must be finite and non-negative, got {0}")]
+    InvalidBankroll(f64),
+}
+
+/// Taker fee in USDC per share for the Polymarket fee curve
+/// `fee_rate * (p * (1 - p))^exponent`. A missing exponent means fees are disabled.
+pub fn taker_fee_per_share(price: f64, fee_rate: f64, fee_exponent: Option<f64>) -> f64 {
+    match fee_exponent {
+        Some(exponent) if fee_rate > 0.0 && (0.0..=1.0).contains(&price) => {
+            fee_rate * (price * (1.0 - price)).powf(exponent)
+        }
+        _ => 0.0,
+    }
+}
+
+/// Sizes a taker entry with capped fractional Kelly on the side with positive edge.
+///
+/// ```
+/// use pmm::{kelly_stake, SizingConfig, SizingInput, SizingSide};
+///
+/// let decision = kelly_stake(
+///     &SizingInput {
+///         probability_yes: 0.7,
+///         price_yes: 0.5,
+///         fee_per_share: 0.0,
+///         bankroll: 1_000.0,
+///     },
+///     &SizingConfig::default(),
+/// )?;
+/// assert_eq!(decision.side, Some(SizingSide::Yes));
+/// assert!(decision.stake_usdc <= 50.0);
+/// # Ok::<(), pmm::SizingError>(())
+/// ```
+pub fn kelly_stake(input: &SizingInput, cfg: &SizingConfig) -> Result<SizingDecision, SizingError> {
+    validate(input)?;
+
+    let q_yes = input.probability_yes;
+    let cost_yes = input.price_yes + input.fee_per_share;
+    let cost_no = (1.0 - input.price_yes) + input.fee_per_share;
+    let edge_yes = q_yes - cost_yes;
+    let edge_no = (1.0 - q_yes) - cost_no;
+
+    let (side, q, cost) = if edge_yes > 0.0 && edge_yes >= edge_no {
+        (SizingSide::Yes, q_yes, cost_yes)
+    } else if edge_no > 0.0 {
+        (SizingSide::No, 1.0 - q_yes, cost_no)
+    } else {
+        return Ok(SizingDecision::no_trade(SizingLimit::NoEdge));
+    };
+    if cost >= 1.0 {
+        return Ok(SizingDecision::no_trade(SizingLimit::NoEdge));
+    }
+
+    let full_kelly_fraction = (q - cost) / (1.0 - cost);
+    let mut stake_fraction = full_kelly_fraction * cfg.kelly_fraction;
+    let mut limit = None;
+    if stake_fraction > cfg.max_bankroll_fraction {
+        stake_fraction = cfg.max_bankroll_fraction;
+        limit = Some(SizingLimit::BankrollFraction);
+    }
+    let mut stake_usdc = stake_fraction * input.bankroll;
+    if stake_usdc > cfg.per_market_bankroll {
+        stake_usdc = cfg.per_market_bankroll;
+        stake_fraction = if input.bankroll > 0.0 {
+            stake_usdc / input.bankroll
+        } else {
+            0.0
+        };
+        limit = Some(SizingLimit::PerMarketBankroll);
+    }
+
+    let expected_log_growth = expected_log_growth(q, cost, stake_fraction);
+    if expected_log_growth < cfg.gain_threshold {
+        return Ok(SizingDecision {
+            side: Some(side),
+            cost_per_share: cost,
+            full_kelly_fraction,
+            expected_log_growth,
+            ..SizingDecision::no_trade(SizingLimit::BelowGainThreshold)
+        });
+    }
+    if stake_usdc < cfg.size_threshold {
+        return Ok(SizingDecision {
+            side: Some(side),
+            cost_per_share: cost,
+            full_kelly_fraction,
+            expected_log_growth,
+            ..SizingDecision::no_trade(SizingLimit::BelowSizeThreshold)
+        });
+    }
+
+    Ok(SizingDecision {
+        side: Some(side),
+        cost_per_share: cost,
+        full_kelly_fraction,
+        stake_fraction,
+        stake_usdc,
+        shares: stake_usdc / cost,
+        expected_log_growth,
+        limit,
+    })
+}
+
+/// `q * ln(1 + f * b) + (1 - q) * ln(1 - f)` with net odds `b = (1 - c) / c`.
+fn expected_log_growth(q: f64, cost: f64, fraction: f64) -> f64 {
+    if fraction <= 0.0 {
+        return 0.0;
+    }
+    let odds = (1.0 - cost) / cost;
+    q * (fraction * odds).ln_1p() + (1.0 - q) * (-fraction).ln_1p()
+}
+
+fn validate(input: &SizingInput) -> Result<(), SizingError> {
+    if !(0.0..=1.0).contains(&input.probability_yes) {
+        return Err(SizingError::InvalidProbability(input.probability_yes));
+    }
+    if !(input.price_yes > 0.0 && input.price_yes < 1.0) {
+        return Err(SizingError::InvalidPrice(input.price_yes));
+    }
+    if !(input.fee_per_share.is_finite() && input.fee_per_share >= 0.0) {
+        return Err(SizingError::InvalidFee(input.fee_per_share));
+    }
+    if !(input.bankroll.is_finite() && input.bankroll >= 0.0) {
+        return Err(SizingError::InvalidBankroll(input.bankroll));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(probability_yes: f64, price_yes: f64) -> SizingInput {
+        SizingInput {
+            probability_yes,
+            price_yes,
+            fee_per_share: 0.0,
+            bankroll: 1_000.0,
+        }
+    }
+
+    fn uncapped() -> SizingConfig {
+        SizingConfig {
+            kelly_fraction: 1.0,
+            max_bankroll_fraction: 1.0,
+            per_market_bankroll: f64::MAX,
+            gain_threshold: 0.0,
+            size_threshold: 0.0,
+        }
+    }
+
+    #[test]
+    fn full_kelly_matches_closed_form_for_both_sides() {
+        let yes = kelly_stake(&input(0.6, 0.5), &uncapped()).unwrap();
+        assert_eq!(yes.side, Some(SizingSide::Yes));
+        assert!((yes.full_kelly_fraction - 0.2).abs() < 1e-12);
+        assert!((yes.stake_usdc - 200.0).abs() < 1e-9);
+        assert!((yes.shares - 400.0).abs() < 1e-9);
+        assert!(yes.expected_log_growth > 0.0);
+
+        let no = kelly_stake(&input(0.3, 0.5), &uncapped()).unwrap();
+        assert_eq!(no.side, Some(SizingSide::No));
+        assert!((no.full_kelly_fraction - 0.4).abs() < 1e-12);
+    }
+
+    #[test]
+    fn fees_remove_thin_edges() {
+        let mut thin = input(0.51, 0.5);
+        assert!(kelly_stake(&thin, &uncapped()).unwrap().is_trade());
+
+        thin.fee_per_share = taker_fee_per_share(0.5, 0.25, Some(2.0));
+        assert!((thin.fee_per_share - 0.015625).abs() < 1e-12);
+        let decision = kelly_stake(&thin, &uncapped()).unwrap();
+        assert!(!decision.is_trade());
+        assert_eq!(decision.limit, Some(SizingLimit::NoEdge));
+        assert_eq!(taker_fee_per_share(0.5, 0.25, None), 0.0);
+    }
+
+    #[test]
+    fn caps_and_thresholds_apply_in_order() {
+        let cfg = SizingConfig {
+            kelly_fraction: 0.5,
+            max_bankroll_fraction: 0.05,
+            per_market_bankroll: 30.0,
+            gain_threshold: 0.0,
+            size_threshold: 1.0,
+        };
+        let decision = kelly_stake(&input(0.7, 0.5), &cfg).unwrap();
+        assert_eq!(decision.limit, Some(SizingLimit::PerMarketBankroll));
+        assert!((decision.stake_usdc - 30.0).abs() < 1e-9);
+
+        let small = SizingInput {
+            bankroll: 10.0,
+            ..input(0.7, 0.5)
+        };
+        let decision = kelly_stake(&small, &cfg).unwrap();
+        assert_eq!(decision.limit, Some(SizingLimit::BelowSizeThreshold));
+        assert_eq!(decision.stake_usdc, 0.0);
+
+        let picky = SizingConfig {
+            gain_threshold: 1.0,
+            ..cfg
+        };
+        let decision = kelly_stake(&input(0.7, 0.5), &picky).unwrap();
+        assert_eq!(decision.limit, Some(SizingLimit::BelowGainThreshold));
+    }
+
+    #[test]
+    fn invalid_inputs_are_rejected() {
+        assert_eq!(
+            kelly_stake(&input(1.2, 0.5), &uncapped()),
+            Err(SizingError::InvalidProbability(1.2))
+        );
+        assert_eq!(
+            kelly_stake(&input(0.5, 1.0), &uncapped()),
+            Err(SizingError::InvalidPrice(1.0))
+        );
+    }
+}
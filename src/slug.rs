@@ -7,41 +7,409 @@
 //! - 1h formatting uses America/New_York wall-clock time (DST-aware)
 //! - 1d formatting uses the market resolution date in America/New_York
 //! - 4h alignment uses America/New_York wall-clock 4h boundaries
+//!
+//! Coin names come from the [`CoinRegistry`], so a new up/down coin only needs a
+//! registry entry. Pair (relative-performance) markets such as `eth-btc-updown-5m-{ts}`
+//! are not listed yet; [`MarketUnderlying::Pair`] and [`build_underlying_slug`] assume
+//! they follow the 5m/15m/4h scheme with both coin short names. [`parse_slug_at`] inverts [`build_slug`]. 1h and 1d slugs carry no year, so they
+//! resolve to the occurrence nearest a reference time.
+
+use std::sync::{OnceLock, RwLock};
 
 use chrono::{DateTime, Datelike, Days, TimeZone, Timelike, Utc};
 use chrono_tz::America::New_York;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use tracing::warn;
+
+use crate::env_vars;
+
+/// Most coins the [`CoinRegistry`] holds, built-ins included.
+pub const MAX_COINS: usize = 16;
+
+/// A coin in the process-wide [`CoinRegistry`]: BTC, ETH, SOL, XRP, plus any registered
+/// at runtime. Serialized as its ticker (`"BTC"`).
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Coin(u8);
+
+impl Coin {
+    pub const BTC: Coin = Coin(0);
+    pub const ETH: Coin = Coin(1);
+    pub const SOL: Coin = Coin(2);
+    pub const XRP: Coin = Coin(3);
+
+    /// Every registered coin, in registration order.
+    pub fn all() -> Vec<Coin> {
+        CoinRegistry::global().coins()
+    }
+
+    /// Position in the registry, below [`MAX_COINS`].
+    pub fn index(self) -> usize {
+        usize::from(self.0)
+    }
+
+    pub fn spec(self) -> &'static CoinSpec {
+        CoinRegistry::global().spec(self)
+    }
+
+    /// Dashboard and config code (`BTC`).
+    pub fn ticker(self) -> &'static str {
+        &self.spec().ticker
+    }
+}
+
+impl std::fmt::Debug for Coin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.ticker())
+    }
+}
+
+impl Serialize for Coin {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.ticker())
+    }
+}
+
+impl<'de> Deserialize<'de> for Coin {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let ticker = String::deserialize(deserializer)?;
+        parse_coin(&ticker).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Names of one coin across Polymarket slugs, the dashboard, and Binance.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoinSpec {
+    /// Uppercase dashboard and config code (`BTC`).
+    pub ticker: String,
+    /// Name in 5m/15m/4h slugs (`btc`).
+    pub short_name: String,
+    /// Name in 1h/1d slugs (`bitcoin`).
+    pub full_name: String,
+    /// Binance spot pair the coin is priced from (`BTCUSDT`).
+    pub binance_symbol: String,
+}
+
+impl CoinSpec {
+    pub fn new(ticker: &str, short_name: &str, full_name: &str, binance_symbol: &str) -> Self {
+        Self {
+            ticker: ticker.to_string(),
+            short_name: short_name.to_string(),
+            full_name: full_name.to_string(),
+            binance_symbol: binance_symbol.to_string(),
+        }
+    }
+
+    /// Parses `TICKER:short:full:BINANCESYMBOL` (`DOGE:doge:dogecoin:DOGEUSDT`).
+    pub fn parse(raw: &str) -> Result<Self, SlugError> {
+        let parts: Vec<&str> = raw.split(':').map(str::trim).collect();
+        let [ticker, short_name, full_name, binance_symbol] = parts.as_slice() else {
+            return Err(SlugError::InvalidCoinSpec(raw.to_string()));
+        };
+        let spec = Self::new(ticker, short_name, full_name, binance_symbol);
+        spec.validate()?;
+        Ok(spec)
+    }
+
+    fn validate(&self) -> Result<(), SlugError> {
+        let slug_name = |name: &str| {
+            !name.is_empty()
+                && name
+                    .bytes()
+                    .all(|byte| byte.is_ascii_lowercase() || byte.is_ascii_digit())
+        };
+        let upper = |name: &str| {
+            !name.is_empty()
+                && name
+                    .bytes()
+                    .all(|byte| byte.is_ascii_uppercase() || byte.is_ascii_digit())
+        };
+        if upper(&self.ticker)
+            && slug_name(&self.short_name)
+            && slug_name(&self.full_name)
+            && upper(&self.binance_symbol)
+        {
+            Ok(())
+        } else {
+            Err(SlugError::InvalidCoinSpec(format!(
+                "{}:{}:{}:{}",
+                self.ticker, self.short_name, self.full_name, self.binance_symbol
+            )))
+        }
+    }
+}
+
+/// Coins known to this process. Starts with BTC, ETH, SOL, and XRP plus the entries of
+/// `PMM_EXTRA_COINS` (`;`-separated [`CoinSpec::parse`] entries; invalid ones are
+/// logged and skipped); [`CoinRegistry::register`] adds more at runtime. Coins are never
+/// removed, so a [`Coin`] stays valid for the life of the process.
+pub struct CoinRegistry {
+    specs: RwLock<Vec<&'static CoinSpec>>,
+}
+
+static COIN_REGISTRY: OnceLock<CoinRegistry> = OnceLock::new();
+
+impl CoinRegistry {
+    pub fn global() -> &'static CoinRegistry {
+        COIN_REGISTRY.get_or_init(|| {
+            let registry = CoinRegistry {
+                specs: RwLock::new(Vec::with_capacity(MAX_COINS)),
+            };
+            for spec in [
+                CoinSpec::new("BTC", "btc", "bitcoin", "BTCUSDT"),
+                CoinSpec::new("ETH", "eth", "ethereum", "ETHUSDT"),
+                CoinSpec::new("SOL", "sol", "solana", "SOLUSDT"),
+                CoinSpec::new("XRP", "xrp", "xrp", "XRPUSDT"),
+            ] {
+                registry
+                    .register(spec)
+                    .expect("built-in coins are valid and distinct");
+            }
+            if let Some(raw) = env_vars::get::<Option<String>>("PMM_EXTRA_COINS") {
+                for entry in raw
+                    .split(';')
+                    .map(str::trim)
+                    .filter(|entry| !entry.is_empty())
+                {
+                    if let Err(err) =
+                        CoinSpec::parse(entry).and_then(|spec| registry.register(spec))
+                    {
+                        warn!(
+                            component = "slug",
+                            event = "slug.coin_registry.invalid_entry",
+                            entry,
+                            error = %err
+                        );
+                    }
+                }
+            }
+            registry
+        })
+    }
+
+    /// Adds `spec` and returns its coin. Registering an identical spec again returns
+    /// the existing coin; a spec reusing another coin's ticker or slug names is
+    /// rejected.
+    pub fn register(&self, spec: CoinSpec) -> Result<Coin, SlugError> {
+        spec.validate()?;
+        let mut specs = self.write();
+        if let Some(index) = specs.iter().position(|existing| **existing == spec) {
+            return Ok(Coin(index as u8));
+        }
+        let clash = specs.iter().any(|existing| {
+            existing.ticker == spec.ticker
+                || existing.short_name == spec.short_name
+                || existing.full_name == spec.full_name
+        });
+        if clash {
+            return Err(SlugError::InvalidCoinSpec(format!(
+                "{} clashes with a registered coin",
+                spec.ticker
+            )));
+        }
+        if specs.len() >= MAX_COINS {
+            return Err(SlugError::CoinRegistryFull(spec.ticker));
+        }
+        // Specs live for the process so `Coin` accessors can hand out `&'static str`.
+        specs.push(Box::leak(Box::new(spec)));
+        Ok(Coin((specs.len() - 1) as u8))
+    }
+
+    pub fn coins(&self) -> Vec<Coin> {
+        (0..self.read().len())
+            .map(|index| Coin(index as u8))
+            .collect()
+    }
+
+    pub fn spec(&self, coin: Coin) -> &'static CoinSpec {
+        self.read()[coin.index()]
+    }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum Coin {
-    Btc,
-    Eth,
-    Sol,
-    Xrp,
+    /// The coin whose spec satisfies `matches`.
+    pub fn find(&self, matches: impl Fn(&CoinSpec) -> bool) -> Option<Coin> {
+        self.read()
+            .iter()
+            .position(|spec| matches(spec))
+            .map(|index| Coin(index as u8))
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, Vec<&'static CoinSpec>> {
+        self.specs
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, Vec<&'static CoinSpec>> {
+        self.specs
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Serialized as its [`Duration::as_label`] label (`"5m"`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Duration {
+    #[serde(rename = "5m")]
     M5,
+    #[serde(rename = "15m")]
     M15,
+    #[serde(rename = "1h")]
     H1,
+    #[serde(rename = "4h")]
     H4,
+    #[serde(rename = "1d")]
     D1,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct SlugConfig {
-    pub discovery_offset_4h_min: i32,
+impl Duration {
+    /// Every duration, shortest first.
+    pub const ALL: [Duration; 5] = [
+        Duration::M5,
+        Duration::M15,
+        Duration::H1,
+        Duration::H4,
+        Duration::D1,
+    ];
+
+    /// Short label used in slugs, config, and the dashboard (`5m`, `15m`, `1h`, `4h`,
+    /// `1d`).
+    pub const fn as_label(self) -> &'static str {
+        match self {
+            Duration::M5 => "5m",
+            Duration::M15 => "15m",
+            Duration::H1 => "1h",
+            Duration::H4 => "4h",
+            Duration::D1 => "1d",
+        }
+    }
+
+    /// Parses an exact [`Duration::as_label`] label.
+    pub fn parse(input: &str) -> Result<Self, SlugError> {
+        Self::ALL
+            .into_iter()
+            .find(|duration| duration.as_label() == input)
+            .ok_or_else(|| SlugError::InvalidDuration(input.to_string()))
+    }
+
+    /// Nominal interval length. 4h and 1d intervals follow New York wall-clock time, so
+    /// across DST changes the real interval can be an hour shorter or longer; use
+    /// [`Duration::interval_end`] for exact bounds.
+    pub const fn step_seconds(self) -> i64 {
+        match self {
+            Duration::M5 => 5 * 60,
+            Duration::M15 => 15 * 60,
+            Duration::H1 => 60 * 60,
+            Duration::H4 => 4 * 60 * 60,
+            Duration::D1 => 24 * 60 * 60,
+        }
+    }
+
+    /// Exclusive end of the interval starting at `start_ts_utc`, from the discovery
+    /// interval calendar.
+    pub fn interval_end(self, start_ts_utc: i64) -> i64 {
+        crate::discovery::interval_starts_for_now(self, start_ts_utc, SlugConfig::default())
+            .next_start_ts_utc
+    }
 }
 
-impl Default for SlugConfig {
-    fn default() -> Self {
-        Self {
-            discovery_offset_4h_min: 0,
+/// Durations pair markets are expected on: the `-updown-` family only.
+pub const PAIR_DURATIONS: [Duration; 3] = [Duration::M5, Duration::M15, Duration::H4];
+
+/// What an up/down market tracks: one coin against USD, or the relative performance of
+/// `base` against `quote` (`Pair(ETH, BTC)` resolves on ETH/BTC). Serialized as its
+/// [`MarketUnderlying::label`] (`"BTC"`, `"ETH/BTC"`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum MarketUnderlying {
+    Single(Coin),
+    Pair(Coin, Coin),
+}
+
+impl MarketUnderlying {
+    /// A pair of two distinct coins.
+    pub fn pair(base: Coin, quote: Coin) -> Result<Self, SlugError> {
+        if base == quote {
+            return Err(SlugError::InvalidPair(format!(
+                "{}/{}",
+                base.ticker(),
+                quote.ticker()
+            )));
+        }
+        Ok(Self::Pair(base, quote))
+    }
+
+    /// The single coin, or the first coin of a pair.
+    pub fn base(self) -> Coin {
+        match self {
+            Self::Single(coin) | Self::Pair(coin, _) => coin,
+        }
+    }
+
+    pub fn quote(self) -> Option<Coin> {
+        match self {
+            Self::Single(_) => None,
+            Self::Pair(_, quote) => Some(quote),
+        }
+    }
+
+    pub fn is_pair(self) -> bool {
+        matches!(self, Self::Pair(..))
+    }
+
+    /// Coins whose prices decide the market, base first.
+    pub fn coins(self) -> Vec<Coin> {
+        match self {
+            Self::Single(coin) => vec![coin],
+            Self::Pair(base, quote) => vec![base, quote],
+        }
+    }
+
+    /// Dashboard and config code: the ticker (`BTC`) or `BASE/QUOTE` (`ETH/BTC`).
+    pub fn label(self) -> String {
+        match self {
+            Self::Single(coin) => coin.ticker().to_string(),
+            Self::Pair(base, quote) => format!("{}/{}", base.ticker(), quote.ticker()),
+        }
+    }
+
+    /// Parses an exact [`MarketUnderlying::label`].
+    pub fn parse(input: &str) -> Result<Self, SlugError> {
+        match input.split_once('/') {
+            None => parse_coin(input).map(Self::Single),
+            Some((base, quote)) => {
+                let base =
+                    parse_coin(base).map_err(|_| SlugError::InvalidPair(input.to_string()))?;
+                let quote =
+                    parse_coin(quote).map_err(|_| SlugError::InvalidPair(input.to_string()))?;
+                Self::pair(base, quote)
+            }
         }
     }
 }
 
+impl From<Coin> for MarketUnderlying {
+    fn from(coin: Coin) -> Self {
+        Self::Single(coin)
+    }
+}
+
+impl Serialize for MarketUnderlying {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.label())
+    }
+}
+
+impl<'de> Deserialize<'de> for MarketUnderlying {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let label = String::deserialize(deserializer)?;
+        Self::parse(&label).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Slug formatting options. Currently empty: slugs are fully determined by coin,
+/// duration, and interval start.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SlugConfig {}
+
 #[derive(Debug, Error, PartialEq, Eq)]
 pub enum SlugError {
     #[error("unsupported coin: {0}")]
@@ -50,29 +418,41 @@ pub enum SlugError {
     InvalidDuration(String),
     #[error("invalid unix timestamp: {0}")]
     InvalidTimestamp(i64),
+    #[error("unrecognized slug: {0}")]
+    InvalidSlug(String),
+    #[error("invalid coin spec: {0}")]
+    InvalidCoinSpec(String),
+    #[error("coin registry is full; cannot add {0}")]
+    CoinRegistryFull(String),
+    #[error("invalid coin pair: {0}")]
+    InvalidPair(String),
+    #[error("no pair market slug for duration {0}")]
+    UnsupportedPairDuration(String),
 }
 
+/// Looks up a registered coin by its exact ticker (`BTC`).
 pub fn parse_coin(input: &str) -> Result<Coin, SlugError> {
-    match input {
-        "BTC" => Ok(Coin::Btc),
-        "ETH" => Ok(Coin::Eth),
-        "SOL" => Ok(Coin::Sol),
-        "XRP" => Ok(Coin::Xrp),
-        other => Err(SlugError::UnsupportedCoin(other.to_string())),
-    }
+    CoinRegistry::global()
+        .find(|spec| spec.ticker == input)
+        .ok_or_else(|| SlugError::UnsupportedCoin(input.to_string()))
 }
 
+/// Same as [`Duration::parse`].
 pub fn parse_duration(input: &str) -> Result<Duration, SlugError> {
-    match input {
-        "5m" => Ok(Duration::M5),
-        "15m" => Ok(Duration::M15),
-        "1h" => Ok(Duration::H1),
-        "4h" => Ok(Duration::H4),
-        "1d" => Ok(Duration::D1),
-        other => Err(SlugError::InvalidDuration(other.to_string())),
-    }
+    Duration::parse(input)
 }
 
+/// Builds the Polymarket slug for the interval of `duration` starting at `end_ts_utc`.
+///
+/// ```
+/// use pmm::{build_slug, parse_coin, parse_duration, SlugConfig};
+///
+/// let coin = parse_coin("BTC")?;
+/// let duration = parse_duration("5m")?;
+/// let slug = build_slug(coin, duration, 1_771_449_000, SlugConfig::default())?;
+/// assert_eq!(slug, "btc-updown-5m-1771449000");
+/// # Ok::<(), pmm::SlugError>(())
+/// ```
 pub fn build_slug(
     coin: Coin,
     duration: Duration,
@@ -83,11 +463,20 @@ pub fn build_slug(
     let _ = utc_from_ts(end_ts_utc)?;
 
     match duration {
-        Duration::M5 => Ok(format!("{}-updown-5m-{}", coin_short(coin), end_ts_utc)),
-        Duration::M15 => Ok(format!("{}-updown-15m-{}", coin_short(coin), end_ts_utc)),
+        Duration::M5 | Duration::M15 => Ok(format!(
+            "{}-updown-{}-{}",
+            coin_short(coin),
+            duration.as_label(),
+            end_ts_utc
+        )),
         Duration::H4 => {
             let aligned = align_4h_start_ts_et(end_ts_utc)?;
-            Ok(format!("{}-updown-4h-{}", coin_short(coin), aligned))
+            Ok(format!(
+                "{}-updown-{}-{}",
+                coin_short(coin),
+                duration.as_label(),
+                aligned
+            ))
         }
         Duration::H1 => {
             let ny = utc_from_ts(end_ts_utc)?.with_timezone(&New_York);
@@ -124,6 +513,198 @@ pub fn build_slug(
     }
 }
 
+/// Builds the slug of a single-coin or pair market. Single coins use [`build_slug`];
+/// pairs use `{base_short}-{quote_short}-updown-{duration}-{ts}` and exist only for
+/// [`PAIR_DURATIONS`].
+///
+/// ```
+/// use pmm::{build_underlying_slug, Coin, Duration, MarketUnderlying, SlugConfig};
+///
+/// let pair = MarketUnderlying::pair(Coin::ETH, Coin::BTC)?;
+/// let slug = build_underlying_slug(pair, Duration::M5, 1_771_449_000, SlugConfig::default())?;
+/// assert_eq!(slug, "eth-btc-updown-5m-1771449000");
+/// # Ok::<(), pmm::SlugError>(())
+/// ```
+pub fn build_underlying_slug(
+    underlying: MarketUnderlying,
+    duration: Duration,
+    start_ts_utc: i64,
+    cfg: SlugConfig,
+) -> Result<String, SlugError> {
+    let (base, quote) = match underlying {
+        MarketUnderlying::Single(coin) => return build_slug(coin, duration, start_ts_utc, cfg),
+        MarketUnderlying::Pair(base, quote) => (base, quote),
+    };
+    if !PAIR_DURATIONS.contains(&duration) {
+        return Err(SlugError::UnsupportedPairDuration(
+            duration.as_label().to_string(),
+        ));
+    }
+    MarketUnderlying::pair(base, quote)?;
+    // The single-coin updown slug already carries the duration and aligned timestamp;
+    // only the coin prefix differs.
+    let single = build_slug(base, duration, start_ts_utc, cfg)?;
+    Ok(format!(
+        "{}-{}{}",
+        coin_short(base),
+        coin_short(quote),
+        &single[coin_short(base).len()..]
+    ))
+}
+
+/// Same as [`parse_slug_at`], also accepting pair slugs built by
+/// [`build_underlying_slug`].
+pub fn parse_underlying_slug_at(
+    slug: &str,
+    reference_ts_utc: i64,
+) -> Result<(MarketUnderlying, Duration, i64), SlugError> {
+    let invalid = || SlugError::InvalidSlug(slug.to_string());
+
+    let pair = slug.split_once("-updown-").and_then(|(prefix, rest)| {
+        let (base, quote) = prefix.split_once('-')?;
+        Some((base, quote, rest))
+    });
+    let Some((base, quote, rest)) = pair else {
+        let (coin, duration, start_ts_utc) = parse_slug_at(slug, reference_ts_utc)?;
+        return Ok((MarketUnderlying::Single(coin), duration, start_ts_utc));
+    };
+    let base = coin_from_slug(base, coin_short).ok_or_else(invalid)?;
+    let quote = coin_from_slug(quote, coin_short).ok_or_else(invalid)?;
+    let underlying = MarketUnderlying::pair(base, quote).map_err(|_| invalid())?;
+    let single = format!("{}-updown-{rest}", coin_short(base));
+    let (_, duration, start_ts_utc) =
+        parse_slug_at(&single, reference_ts_utc).map_err(|_| invalid())?;
+    if build_underlying_slug(underlying, duration, start_ts_utc, SlugConfig::default())
+        .ok()
+        .as_deref()
+        != Some(slug)
+    {
+        return Err(invalid());
+    }
+    Ok((underlying, duration, start_ts_utc))
+}
+
+/// Recovers coin, duration, and interval start from a slug built by [`build_slug`],
+/// resolving the year of 1h/1d slugs against the current time. See [`parse_slug_at`].
+pub fn parse_slug(slug: &str) -> Result<(Coin, Duration, i64), SlugError> {
+    parse_slug_at(slug, Utc::now().timestamp())
+}
+
+/// Recovers coin, duration, and interval start from a slug built by [`build_slug`].
+///
+/// 1h and 1d slugs name a New York date without a year; the interval start nearest
+/// `reference_ts_utc` wins (pass the time the slug was logged). A 1h slug naming the
+/// repeated hour of a DST fall-back resolves to its first occurrence; one naming the
+/// skipped spring-forward hour is an error.
+///
+/// ```
+/// use pmm::{parse_slug_at, Coin, Duration};
+///
+/// let parsed = parse_slug_at("bitcoin-up-or-down-january-15-3pm-et", 1_736_900_000)?;
+/// assert_eq!(parsed, (Coin::BTC, Duration::H1, 1_736_971_200));
+/// # Ok::<(), pmm::SlugError>(())
+/// ```
+pub fn parse_slug_at(
+    slug: &str,
+    reference_ts_utc: i64,
+) -> Result<(Coin, Duration, i64), SlugError> {
+    let invalid = || SlugError::InvalidSlug(slug.to_string());
+
+    if let Some((coin, rest)) = slug.split_once("-updown-") {
+        let coin = coin_from_slug(coin, coin_short).ok_or_else(invalid)?;
+        let (label, ts) = rest.split_once('-').ok_or_else(invalid)?;
+        let duration = match Duration::parse(label) {
+            Ok(duration @ (Duration::M5 | Duration::M15 | Duration::H4)) => duration,
+            _ => return Err(invalid()),
+        };
+        let start_ts_utc = parse_digits(ts).ok_or_else(invalid)?;
+        // Reject anything `build_slug` would not have produced for this timestamp.
+        if build_slug(coin, duration, start_ts_utc, SlugConfig::default())
+            .ok()
+            .as_deref()
+            != Some(slug)
+        {
+            return Err(invalid());
+        }
+        return Ok((coin, duration, start_ts_utc));
+    }
+
+    let (coin, rest) = slug.split_once("-up-or-down-").ok_or_else(invalid)?;
+    let coin = coin_from_slug(coin, coin_full).ok_or_else(invalid)?;
+    let parts: Vec<&str> = rest.split('-').collect();
+    let (duration, month, day, hour) = match parts.as_slice() {
+        ["on", month, day] => (Duration::D1, *month, *day, None),
+        [month, day, hour, "et"] => (Duration::H1, *month, *day, Some(*hour)),
+        _ => return Err(invalid()),
+    };
+    let month = month_from_name(month).ok_or_else(invalid)?;
+    let day = parse_digits(day)
+        .and_then(|day| u32::try_from(day).ok())
+        .ok_or_else(invalid)?;
+    let hour24 = match hour {
+        Some(hour) => Some(parse_hour12(hour).ok_or_else(invalid)?),
+        None => None,
+    };
+
+    let reference_year = utc_from_ts(reference_ts_utc)?
+        .with_timezone(&New_York)
+        .year();
+    // Pick the year on the naive wall-clock start, then resolve it in New York so a
+    // skipped hour is an error rather than a match in another year.
+    let naive_start_for_year = |year: i32| -> Option<chrono::NaiveDateTime> {
+        let date = chrono::NaiveDate::from_ymd_opt(year, month, day)?;
+        match hour24 {
+            Some(hour) => date.and_hms_opt(hour, 0, 0),
+            // The slug names the resolution date; the interval starts at noon the day
+            // before.
+            None => date.checked_sub_days(Days::new(1))?.and_hms_opt(12, 0, 0),
+        }
+    };
+    let naive_start = (reference_year - 1..=reference_year + 1)
+        .filter_map(naive_start_for_year)
+        .min_by_key(|start| start.and_utc().timestamp().abs_diff(reference_ts_utc))
+        .ok_or_else(invalid)?;
+    let start_ts_utc = New_York
+        .from_local_datetime(&naive_start)
+        .earliest()
+        .ok_or_else(invalid)?
+        .with_timezone(&Utc)
+        .timestamp();
+    Ok((coin, duration, start_ts_utc))
+}
+
+fn coin_from_slug(name: &str, naming: fn(Coin) -> &'static str) -> Option<Coin> {
+    Coin::all().into_iter().find(|coin| naming(*coin) == name)
+}
+
+fn parse_digits(raw: &str) -> Option<i64> {
+    if raw.is_empty() || !raw.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    raw.parse().ok()
+}
+
+fn month_from_name(name: &str) -> Option<u32> {
+    (1..=12).find(|month| {
+        chrono::NaiveDate::from_ymd_opt(2000, *month, 1)
+            .is_some_and(|date| date.format("%B").to_string().to_lowercase() == name)
+    })
+}
+
+/// `3pm` -> `15`, `12am` -> `0`.
+fn parse_hour12(raw: &str) -> Option<u32> {
+    let (digits, pm) = if let Some(digits) = raw.strip_suffix("am") {
+        (digits, false)
+    } else {
+        (raw.strip_suffix("pm")?, true)
+    };
+    let hour = u32::try_from(parse_digits(digits)?).ok()?;
+    if !(1..=12).contains(&hour) || digits.starts_with('0') {
+        return None;
+    }
+    Some(hour % 12 + if pm { 12 } else { 0 })
+}
+
 fn ny_noon_for_date(date: chrono::NaiveDate) -> Option<chrono::DateTime<chrono_tz::Tz>> {
     New_York
         .with_ymd_and_hms(date.year(), date.month(), date.day(), 12, 0, 0)
@@ -137,21 +718,11 @@ fn utc_from_ts(ts: i64) -> Result<DateTime<Utc>, SlugError> {
 }
 
 fn coin_short(coin: Coin) -> &'static str {
-    match coin {
-        Coin::Btc => "btc",
-        Coin::Eth => "eth",
-        Coin::Sol => "sol",
-        Coin::Xrp => "xrp",
-    }
+    &coin.spec().short_name
 }
 
 fn coin_full(coin: Coin) -> &'static str {
-    match coin {
-        Coin::Btc => "bitcoin",
-        Coin::Eth => "ethereum",
-        Coin::Sol => "solana",
-        Coin::Xrp => "xrp",
-    }
+    &coin.spec().full_name
 }
 
 fn align_4h_start_ts_et(start_ts_utc: i64) -> Result<i64, SlugError> {
@@ -170,20 +741,14 @@ mod tests {
     use super::*;
     use regex::Regex;
 
-    fn cfg(offset_min: i32) -> SlugConfig {
-        SlugConfig {
-            discovery_offset_4h_min: offset_min,
-        }
-    }
-
     #[test]
     fn formats_5m_for_all_coins() {
         let ts = 1_771_449_000;
         let cases = [
-            (Coin::Btc, "btc-updown-5m-1771449000"),
-            (Coin::Eth, "eth-updown-5m-1771449000"),
-            (Coin::Sol, "sol-updown-5m-1771449000"),
-            (Coin::Xrp, "xrp-updown-5m-1771449000"),
+            (Coin::BTC, "btc-updown-5m-1771449000"),
+            (Coin::ETH, "eth-updown-5m-1771449000"),
+            (Coin::SOL, "sol-updown-5m-1771449000"),
+            (Coin::XRP, "xrp-updown-5m-1771449000"),
         ];
 
         for (coin, expected) in cases {
@@ -196,10 +761,10 @@ mod tests {
     fn formats_15m_for_all_coins() {
         let ts = 1_771_448_400;
         let cases = [
-            (Coin::Btc, "btc-updown-15m-1771448400"),
-            (Coin::Eth, "eth-updown-15m-1771448400"),
-            (Coin::Sol, "sol-updown-15m-1771448400"),
-            (Coin::Xrp, "xrp-updown-15m-1771448400"),
+            (Coin::BTC, "btc-updown-15m-1771448400"),
+            (Coin::ETH, "eth-updown-15m-1771448400"),
+            (Coin::SOL, "sol-updown-15m-1771448400"),
+            (Coin::XRP, "xrp-updown-15m-1771448400"),
         ];
 
         for (coin, expected) in cases {
@@ -213,10 +778,10 @@ mod tests {
         // 2025-01-01 03:10:00 UTC = 2024-12-31 22:10 ET -> aligned to 20:00 ET.
         let ts = 1_735_702_200;
         let cases = [
-            (Coin::Btc, "btc-updown-4h-1735693200"),
-            (Coin::Eth, "eth-updown-4h-1735693200"),
-            (Coin::Sol, "sol-updown-4h-1735693200"),
-            (Coin::Xrp, "xrp-updown-4h-1735693200"),
+            (Coin::BTC, "btc-updown-4h-1735693200"),
+            (Coin::ETH, "eth-updown-4h-1735693200"),
+            (Coin::SOL, "sol-updown-4h-1735693200"),
+            (Coin::XRP, "xrp-updown-4h-1735693200"),
         ];
 
         for (coin, expected) in cases {
@@ -230,10 +795,10 @@ mod tests {
         // 2025-01-15 20:00:00 UTC -> 2025-01-15 3pm ET
         let ts = 1_736_971_200;
         let cases = [
-            (Coin::Btc, "bitcoin-up-or-down-january-15-3pm-et"),
-            (Coin::Eth, "ethereum-up-or-down-january-15-3pm-et"),
-            (Coin::Sol, "solana-up-or-down-january-15-3pm-et"),
-            (Coin::Xrp, "xrp-up-or-down-january-15-3pm-et"),
+            (Coin::BTC, "bitcoin-up-or-down-january-15-3pm-et"),
+            (Coin::ETH, "ethereum-up-or-down-january-15-3pm-et"),
+            (Coin::SOL, "solana-up-or-down-january-15-3pm-et"),
+            (Coin::XRP, "xrp-up-or-down-january-15-3pm-et"),
         ];
 
         for (coin, expected) in cases {
@@ -246,7 +811,7 @@ mod tests {
     fn formats_1h_for_est_and_edt_periods() {
         // EST: Jan 10, 2025 13:00 UTC => 8am ET
         let est = build_slug(
-            Coin::Btc,
+            Coin::BTC,
             Duration::H1,
             1_736_514_000,
             SlugConfig::default(),
@@ -256,7 +821,7 @@ mod tests {
 
         // EDT: Jul 10, 2025 13:00 UTC => 9am ET
         let edt = build_slug(
-            Coin::Btc,
+            Coin::BTC,
             Duration::H1,
             1_752_152_400,
             SlugConfig::default(),
@@ -271,10 +836,10 @@ mod tests {
         // Resolution is 2026-02-26 12:00 ET -> slug date is february-26.
         let ts = 1_772_040_400;
         let cases = [
-            (Coin::Btc, "bitcoin-up-or-down-on-february-26"),
-            (Coin::Eth, "ethereum-up-or-down-on-february-26"),
-            (Coin::Sol, "solana-up-or-down-on-february-26"),
-            (Coin::Xrp, "xrp-up-or-down-on-february-26"),
+            (Coin::BTC, "bitcoin-up-or-down-on-february-26"),
+            (Coin::ETH, "ethereum-up-or-down-on-february-26"),
+            (Coin::SOL, "solana-up-or-down-on-february-26"),
+            (Coin::XRP, "xrp-up-or-down-on-february-26"),
         ];
 
         for (coin, expected) in cases {
@@ -295,12 +860,96 @@ mod tests {
         );
     }
 
+    #[test]
+    fn duration_labels_round_trip_and_intervals_follow_the_calendar() {
+        for duration in Duration::ALL {
+            assert_eq!(Duration::parse(duration.as_label()), Ok(duration));
+        }
+        assert!(Duration::parse("5M").is_err());
+
+        // 2025-01-15 17:00 UTC = 12:00 ET (1d start, 4h block 12:00-16:00 ET).
+        let start = 1_736_960_400;
+        for duration in Duration::ALL {
+            assert_eq!(
+                duration.interval_end(start) - start,
+                duration.step_seconds(),
+                "{duration:?}"
+            );
+        }
+        // 2025-03-08 17:00 UTC = 12:00 EST; the next noon is EDT, 23 hours later.
+        let dst_start = 1_741_453_200;
+        assert_eq!(
+            Duration::D1.interval_end(dst_start) - dst_start,
+            Duration::D1.step_seconds() - 3_600
+        );
+    }
+
+    #[test]
+    fn parse_slug_inverts_build_slug_for_every_duration() {
+        let cases = [
+            (Coin::BTC, Duration::M5, 1_771_449_000),
+            (Coin::ETH, Duration::M15, 1_771_448_400),
+            (Coin::SOL, Duration::H4, 1_735_693_200),
+            // EST and EDT hours, midnight and noon.
+            (Coin::XRP, Duration::H1, 1_736_514_000),
+            (Coin::BTC, Duration::H1, 1_752_152_400),
+            (Coin::ETH, Duration::H1, 1_736_917_200),
+            (Coin::SOL, Duration::H1, 1_736_960_400),
+            (Coin::BTC, Duration::D1, 1_772_038_800),
+            // Daily market resolving on New Year's Day.
+            (Coin::ETH, Duration::D1, 1_735_664_400),
+        ];
+        for (coin, duration, start) in cases {
+            let slug = build_slug(coin, duration, start, SlugConfig::default()).unwrap();
+            for reference in [start - 90 * 86_400, start, start + 90 * 86_400] {
+                assert_eq!(
+                    parse_slug_at(&slug, reference),
+                    Ok((coin, duration, start)),
+                    "{slug} @ {reference}"
+                );
+            }
+        }
+
+        // No year in the slug: the occurrence nearest the reference wins.
+        let slug = "bitcoin-up-or-down-january-15-3pm-et";
+        assert_eq!(
+            parse_slug_at(slug, 1_736_971_200 + 300 * 86_400).unwrap().2,
+            1_768_507_200
+        );
+    }
+
+    #[test]
+    fn parse_slug_rejects_malformed_slugs() {
+        for slug in [
+            "doge-updown-5m-1771449000",
+            "btc-updown-1h-1771449000",
+            "btc-updown-5m-",
+            "btc-updown-5m-+177",
+            // Not on an ET 4h boundary.
+            "btc-updown-4h-1735702200",
+            "bitcoin-up-or-down-smarch-15-3pm-et",
+            "bitcoin-up-or-down-january-15-13pm-et",
+            "bitcoin-up-or-down-january-15-03pm-et",
+            "bitcoin-up-or-down-february-30-3pm-et",
+            "bitcoin-up-or-down-on-january",
+            "bitcoin-up-or-down-january-15-3pm",
+            // Skipped by the 2025 spring-forward.
+            "bitcoin-up-or-down-march-9-2am-et",
+        ] {
+            assert_eq!(
+                parse_slug_at(slug, 1_741_500_000),
+                Err(SlugError::InvalidSlug(slug.to_string())),
+                "{slug}"
+            );
+        }
+    }
+
     #[test]
     fn boundary_time_exactly_on_interval_start_is_stable() {
         // Exactly on 4h ET boundary (2025-01-01 01:00 UTC = 20:00 ET).
         let exact_boundary = 1_735_693_200;
         let slug = build_slug(
-            Coin::Eth,
+            Coin::ETH,
             Duration::H4,
             exact_boundary,
             SlugConfig::default(),
@@ -318,30 +967,83 @@ mod tests {
         let re_1d = Regex::new(r"^[a-z]+-up-or-down-on-[a-z]+-\d{1,2}$").unwrap();
 
         for ts in (1_735_689_600..1_735_776_000).step_by(61) {
-            let a = build_slug(Coin::Btc, Duration::M5, ts, cfg(15)).unwrap();
-            let b = build_slug(Coin::Btc, Duration::M5, ts, cfg(15)).unwrap();
+            let a = build_slug(Coin::BTC, Duration::M5, ts, SlugConfig::default()).unwrap();
+            let b = build_slug(Coin::BTC, Duration::M5, ts, SlugConfig::default()).unwrap();
             assert_eq!(a, b);
             assert!(re_5m.is_match(&a));
 
-            let a = build_slug(Coin::Eth, Duration::M15, ts, cfg(15)).unwrap();
-            let b = build_slug(Coin::Eth, Duration::M15, ts, cfg(15)).unwrap();
+            let a = build_slug(Coin::ETH, Duration::M15, ts, SlugConfig::default()).unwrap();
+            let b = build_slug(Coin::ETH, Duration::M15, ts, SlugConfig::default()).unwrap();
             assert_eq!(a, b);
             assert!(re_15m.is_match(&a));
 
-            let a = build_slug(Coin::Sol, Duration::H4, ts, cfg(15)).unwrap();
-            let b = build_slug(Coin::Sol, Duration::H4, ts, cfg(15)).unwrap();
+            let a = build_slug(Coin::SOL, Duration::H4, ts, SlugConfig::default()).unwrap();
+            let b = build_slug(Coin::SOL, Duration::H4, ts, SlugConfig::default()).unwrap();
             assert_eq!(a, b);
             assert!(re_4h.is_match(&a));
 
-            let a = build_slug(Coin::Xrp, Duration::H1, ts, cfg(15)).unwrap();
-            let b = build_slug(Coin::Xrp, Duration::H1, ts, cfg(15)).unwrap();
+            let a = build_slug(Coin::XRP, Duration::H1, ts, SlugConfig::default()).unwrap();
+            let b = build_slug(Coin::XRP, Duration::H1, ts, SlugConfig::default()).unwrap();
             assert_eq!(a, b);
             assert!(re_1h.is_match(&a));
 
-            let a = build_slug(Coin::Btc, Duration::D1, ts, cfg(15)).unwrap();
-            let b = build_slug(Coin::Btc, Duration::D1, ts, cfg(15)).unwrap();
+            let a = build_slug(Coin::BTC, Duration::D1, ts, SlugConfig::default()).unwrap();
+            let b = build_slug(Coin::BTC, Duration::D1, ts, SlugConfig::default()).unwrap();
             assert_eq!(a, b);
             assert!(re_1d.is_match(&a));
         }
     }
+
+    #[test]
+    fn pair_slugs_round_trip_for_updown_durations() {
+        let pair = MarketUnderlying::pair(Coin::ETH, Coin::BTC).unwrap();
+        // 2025-01-01 03:10:00 UTC; 4h aligns to 20:00 ET the previous day.
+        let ts = 1_735_702_200;
+        let cases = [
+            (Duration::M5, ts, "eth-btc-updown-5m-1735702200"),
+            (Duration::M15, ts, "eth-btc-updown-15m-1735702200"),
+            (Duration::H4, 1_735_693_200, "eth-btc-updown-4h-1735693200"),
+        ];
+        for (duration, start, expected) in cases {
+            let slug = build_underlying_slug(pair, duration, start, SlugConfig::default()).unwrap();
+            assert_eq!(slug, expected);
+            assert_eq!(
+                parse_underlying_slug_at(&slug, start).unwrap(),
+                (pair, duration, start)
+            );
+        }
+
+        assert_eq!(
+            build_underlying_slug(pair, Duration::H1, ts, SlugConfig::default()),
+            Err(SlugError::UnsupportedPairDuration("1h".to_string()))
+        );
+        assert!(MarketUnderlying::pair(Coin::BTC, Coin::BTC).is_err());
+        assert!(parse_underlying_slug_at("btc-btc-updown-5m-1735702200", ts).is_err());
+        assert!(parse_underlying_slug_at("eth-doge-updown-5m-1735702200", ts).is_err());
+        // Pair slugs are not single-coin slugs.
+        assert!(parse_slug_at("eth-btc-updown-5m-1735702200", ts).is_err());
+        assert_eq!(
+            parse_underlying_slug_at("btc-updown-5m-1735702200", ts).unwrap(),
+            (MarketUnderlying::Single(Coin::BTC), Duration::M5, ts)
+        );
+    }
+
+    #[test]
+    fn underlying_labels_round_trip() {
+        let pair = MarketUnderlying::Pair(Coin::SOL, Coin::ETH);
+        assert_eq!(pair.label(), "SOL/ETH");
+        assert_eq!(pair.coins(), vec![Coin::SOL, Coin::ETH]);
+        assert_eq!(MarketUnderlying::parse("SOL/ETH").unwrap(), pair);
+        assert_eq!(
+            MarketUnderlying::parse("XRP").unwrap(),
+            MarketUnderlying::Single(Coin::XRP)
+        );
+        assert!(MarketUnderlying::parse("ETH/ETH").is_err());
+        assert!(MarketUnderlying::parse("ETH/").is_err());
+        assert_eq!(serde_json::to_string(&pair).unwrap(), "\"SOL/ETH\"");
+        assert_eq!(
+            serde_json::from_str::<MarketUnderlying>("\"SOL/ETH\"").unwrap(),
+            pair
+        );
+    }
 }
@@ -0,0 +1,456 @@
+//! Slug uniqueness audit.
+//!
+//! Enumerates every slug the discovery schedule would build over a date range and
+//! reports slugs shared by more than one interval. 5m/15m/4h slugs embed the interval
+//! timestamp, so any collision there is a bug. 1h and 1d slugs only carry month/day:
+//! - the same 1h/1d slug recurs every year (`CrossYear`)
+//! - the 1am ET hour repeats on the DST fall-back day (`DstRepeat`)
+//!
+//! Those two kinds are expected and handled at resolution time by
+//! [`crate::reject_window_mismatches`], which drops markets whose end date belongs to a
+//! different window. A sample of slugs can also be cross-checked against Gamma to
+//! confirm each one names exactly one market.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::discovery::slug_lacks_timestamp;
+use crate::{
+    build_slug, interval_starts_for_now, Coin, DiscoveryUniverse, Duration, SlugConfig, SlugError,
+};
+
+/// Gaps at least this long between owners of one slug are treated as year rollovers.
+const CROSS_YEAR_MIN_GAP_SECONDS: i64 = 300 * 86_400;
+/// Two 1h intervals sharing a slug within this span are the DST fall-back repeat.
+const DST_REPEAT_MAX_GAP_SECONDS: i64 = 2 * 3_600;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlugAuditRequest {
+    pub start_ts_utc: i64,
+    pub end_ts_utc_exclusive: i64,
+    pub universe: DiscoveryUniverse,
+    pub slug_cfg: SlugConfig,
+    /// Number of slugs picked (evenly spaced) for the Gamma cross-check. Colliding
+    /// slugs are always added on top.
+    pub sample_size: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SlugCollisionKind {
+    CrossYear,
+    DstRepeat,
+    Unexpected,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SlugOwner {
+    pub coin: &'static str,
+    pub duration: &'static str,
+    pub start_ts_utc: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SlugCollision {
+    pub slug: String,
+    pub owners: Vec<SlugOwner>,
+    pub kind: SlugCollisionKind,
+    /// True when discovery disambiguates the owners by market end date.
+    pub handled: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SlugAuditDurationSummary {
+    pub duration: &'static str,
+    pub slugs: usize,
+    pub unique_slugs: usize,
+    pub collisions: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GammaSlugError {
+    pub slug: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct GammaCrossCheck {
+    pub checked: usize,
+    pub exactly_once: usize,
+    pub missing: Vec<String>,
+    pub duplicated: Vec<String>,
+    pub errors: Vec<GammaSlugError>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SlugAuditReport {
+    pub range_start_ts_utc: i64,
+    pub range_end_ts_utc_exclusive: i64,
+    pub total_slugs: usize,
+    pub unique_slugs: usize,
+    pub durations: Vec<SlugAuditDurationSummary>,
+    pub collisions: Vec<SlugCollision>,
+    pub unhandled_collisions: usize,
+    pub gamma_sample: Vec<String>,
+    pub gamma: Option<GammaCrossCheck>,
+}
+
+impl SlugAuditReport {
+    /// No unhandled collisions and, if Gamma was checked, no slug matched several markets.
+    pub fn passed(&self) -> bool {
+        self.unhandled_collisions == 0
+            && self
+                .gamma
+                .as_ref()
+                .map(|gamma| gamma.duplicated.is_empty())
+                .unwrap_or(true)
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SlugAuditError {
+    #[error("empty audit range: start {start_ts_utc} >= end {end_ts_utc}")]
+    EmptyRange { start_ts_utc: i64, end_ts_utc: i64 },
+    #[error(transparent)]
+    Slug(#[from] SlugError),
+}
+
+pub fn audit_slug_uniqueness(req: &SlugAuditRequest) -> Result<SlugAuditReport, SlugAuditError> {
+    if req.end_ts_utc_exclusive <= req.start_ts_utc {
+        return Err(SlugAuditError::EmptyRange {
+            start_ts_utc: req.start_ts_utc,
+            end_ts_utc: req.end_ts_utc_exclusive,
+        });
+    }
+    info!(
+        component = "slug_audit",
+        event = "slug_audit.start",
+        range_start_ts_utc = req.start_ts_utc,
+        range_end_ts_utc = req.end_ts_utc_exclusive,
+        coins = req.universe.coins.len(),
+        durations = req.universe.durations.len()
+    );
+
+    let mut owners: Vec<(Coin, Duration, i64)> = Vec::new();
+    let mut first_owner: HashMap<String, usize> = HashMap::new();
+    let mut shared: HashMap<String, Vec<usize>> = HashMap::new();
+    let mut durations = Vec::with_capacity(req.universe.durations.len());
+
+    for &duration in &req.universe.durations {
+        let before = owners.len();
+        let mut duplicates = 0usize;
+        let mut start = first_start_at_or_after(duration, req.start_ts_utc, req.slug_cfg);
+        while start < req.end_ts_utc_exclusive {
+            for &coin in &req.universe.coins {
+                let slug = build_slug(coin, duration, start, req.slug_cfg)?;
+                let index = owners.len();
+                owners.push((coin, duration, start));
+                match first_owner.get(&slug) {
+                    Some(&first) => {
+                        duplicates += 1;
+                        shared
+                            .entry(slug)
+                            .or_insert_with(|| vec![first])
+                            .push(index);
+                    }
+                    None => {
+                        first_owner.insert(slug, index);
+                    }
+                }
+            }
+            start = interval_starts_for_now(duration, start, req.slug_cfg).next_start_ts_utc;
+        }
+
+        let slugs = owners.len() - before;
+        durations.push(SlugAuditDurationSummary {
+            duration: duration.as_label(),
+            slugs,
+            unique_slugs: slugs - duplicates,
+            collisions: 0,
+        });
+    }
+
+    let mut collisions: Vec<SlugCollision> = shared
+        .into_iter()
+        .map(|(slug, indices)| {
+            let group: Vec<(Coin, Duration, i64)> = indices.iter().map(|&i| owners[i]).collect();
+            let kind = classify_collision(&group);
+            SlugCollision {
+                slug,
+                owners: group
+                    .iter()
+                    .map(|&(coin, duration, start_ts_utc)| SlugOwner {
+                        coin: coin.ticker(),
+                        duration: duration.as_label(),
+                        start_ts_utc,
+                    })
+                    .collect(),
+                kind,
+                handled: kind != SlugCollisionKind::Unexpected,
+            }
+        })
+        .collect();
+    collisions.sort_by(|a, b| {
+        (a.owners[0].start_ts_utc, &a.slug).cmp(&(b.owners[0].start_ts_utc, &b.slug))
+    });
+
+    for collision in &collisions {
+        if let Some(summary) = durations
+            .iter_mut()
+            .find(|summary| summary.duration == collision.owners[0].duration)
+        {
+            summary.collisions += 1;
+        }
+        if !collision.handled {
+            warn!(
+                component = "slug_audit",
+                event = "slug_audit.collision",
+                slug = %collision.slug,
+                owners = collision.owners.len(),
+                kind = ?collision.kind
+            );
+        }
+    }
+
+    let mut gamma_sample = evenly_spaced(&owners, req.sample_size)
+        .into_iter()
+        .map(|(coin, duration, start)| build_slug(coin, duration, start, req.slug_cfg))
+        .collect::<Result<Vec<_>, _>>()?;
+    for collision in &collisions {
+        if !gamma_sample.contains(&collision.slug) {
+            gamma_sample.push(collision.slug.clone());
+        }
+    }
+
+    let report = SlugAuditReport {
+        range_start_ts_utc: req.start_ts_utc,
+        range_end_ts_utc_exclusive: req.end_ts_utc_exclusive,
+        total_slugs: owners.len(),
+        unique_slugs: first_owner.len(),
+        durations,
+        unhandled_collisions: collisions.iter().filter(|c| !c.handled).count(),
+        collisions,
+        gamma_sample,
+        gamma: None,
+    };
+    info!(
+        component = "slug_audit",
+        event = "slug_audit.finish",
+        total_slugs = report.total_slugs,
+        unique_slugs = report.unique_slugs,
+        collisions = report.collisions.len(),
+        unhandled_collisions = report.unhandled_collisions
+    );
+    Ok(report)
+}
+
+/// Looks up every slug in `report.gamma_sample` with `count_markets` (number of Gamma
+/// markets carrying that slug) and stores the result on the report.
+pub fn cross_check_gamma_sample<F>(report: &mut SlugAuditReport, mut count_markets: F)
+where
+    F: FnMut(&str) -> Result<usize, String>,
+{
+    let mut check = GammaCrossCheck::default();
+    for slug in &report.gamma_sample {
+        check.checked += 1;
+        match count_markets(slug) {
+            Ok(1) => check.exactly_once += 1,
+            Ok(0) => check.missing.push(slug.clone()),
+            Ok(_) => check.duplicated.push(slug.clone()),
+            Err(error) => check.errors.push(GammaSlugError {
+                slug: slug.clone(),
+                error,
+            }),
+        }
+    }
+    if !check.duplicated.is_empty() {
+        warn!(
+            component = "slug_audit",
+            event = "slug_audit.gamma_duplicate",
+            duplicated = check.duplicated.len()
+        );
+    }
+    report.gamma = Some(check);
+}
+
+/// Counts Gamma markets per slug via the `/markets?slug=` listing, which (unlike the
+/// by-slug endpoint) returns every match instead of the first.
+#[cfg(feature = "discovery-sdk")]
+pub async fn count_gamma_markets_by_slug(
+    slugs: &[String],
+    timeout_ms: u64,
+) -> HashMap<String, Result<usize, String>> {
+    use polymarket_client_sdk::gamma::types::request::MarketsRequest;
+    use polymarket_client_sdk::gamma::Client as GammaClient;
+
+    let client = GammaClient::default();
+    let mut counts = HashMap::with_capacity(slugs.len());
+    for slug in slugs {
+        let request = MarketsRequest::builder().slug(vec![slug.clone()]).build();
+        let outcome = match tokio::time::timeout(
+            std::time::Duration::from_millis(timeout_ms),
+            client.markets(&request),
+        )
+        .await
+        {
+            Ok(Ok(markets)) => Ok(markets.len()),
+            Ok(Err(err)) => Err(err.to_string()),
+            Err(_) => Err(format!("timeout after {timeout_ms}ms")),
+        };
+        counts.insert(slug.clone(), outcome);
+    }
+    counts
+}
+
+fn first_start_at_or_after(duration: Duration, ts_utc: i64, slug_cfg: SlugConfig) -> i64 {
+    let starts = interval_starts_for_now(duration, ts_utc, slug_cfg);
+    if starts.active_start_ts_utc >= ts_utc {
+        starts.active_start_ts_utc
+    } else {
+        starts.next_start_ts_utc
+    }
+}
+
+fn classify_collision(group: &[(Coin, Duration, i64)]) -> SlugCollisionKind {
+    let (coin, duration, _) = group[0];
+    let same_market_family = group.iter().all(|&(c, d, _)| c == coin && d == duration);
+    if !same_market_family || !slug_lacks_timestamp(duration) {
+        return SlugCollisionKind::Unexpected;
+    }
+
+    let mut starts: Vec<i64> = group.iter().map(|&(_, _, start)| start).collect();
+    starts.sort_unstable();
+    let mut kind = SlugCollisionKind::DstRepeat;
+    for pair in starts.windows(2) {
+        let gap = pair[1] - pair[0];
+        if gap >= CROSS_YEAR_MIN_GAP_SECONDS {
+            kind = SlugCollisionKind::CrossYear;
+        } else if !(duration == Duration::H1 && gap <= DST_REPEAT_MAX_GAP_SECONDS) {
+            return SlugCollisionKind::Unexpected;
+        }
+    }
+    kind
+}
+
+fn evenly_spaced<T: Copy>(items: &[T], count: usize) -> Vec<T> {
+    if count == 0 || items.is_empty() {
+        return Vec::new();
+    }
+    let count = count.min(items.len());
+    (0..count).map(|i| items[i * items.len() / count]).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JAN_1_2024: i64 = 1_704_067_200;
+    const JUL_1_2024: i64 = 1_719_792_000;
+    const JUL_1_2025: i64 = 1_751_328_000;
+    const JAN_1_2026: i64 = 1_767_225_600;
+
+    fn request(durations: Vec<Duration>) -> SlugAuditRequest {
+        SlugAuditRequest {
+            start_ts_utc: JAN_1_2024,
+            end_ts_utc_exclusive: JAN_1_2026,
+            universe: DiscoveryUniverse {
+                coins: Coin::all(),
+                durations,
+                pairs: Vec::new(),
+            },
+            slug_cfg: SlugConfig::default(),
+            sample_size: 8,
+        }
+    }
+
+    #[test]
+    fn timestamped_slugs_never_collide() {
+        let report = audit_slug_uniqueness(&request(vec![Duration::M15, Duration::H4])).unwrap();
+
+        assert!(report.collisions.is_empty());
+        assert_eq!(report.total_slugs, report.unique_slugs);
+        assert_eq!(report.durations[0].slugs, 731 * 96 * 4);
+        assert!(report.passed());
+    }
+
+    #[test]
+    fn hourly_slugs_repeat_on_dst_fall_back_and_are_handled() {
+        let report = audit_slug_uniqueness(&SlugAuditRequest {
+            start_ts_utc: JUL_1_2024,
+            end_ts_utc_exclusive: JUL_1_2025,
+            ..request(vec![Duration::H1])
+        })
+        .unwrap();
+
+        // 2024-11-03 repeats the 1am ET hour; nothing else in the year shares a slug.
+        assert_eq!(report.collisions.len(), 4);
+        let btc = &report.collisions[0];
+        assert_eq!(btc.slug, "bitcoin-up-or-down-november-3-1am-et");
+        assert_eq!(btc.kind, SlugCollisionKind::DstRepeat);
+        assert_eq!(
+            btc.owners[1].start_ts_utc - btc.owners[0].start_ts_utc,
+            3_600
+        );
+        assert_eq!(report.total_slugs, report.unique_slugs + 4);
+        assert!(report.passed());
+    }
+
+    #[test]
+    fn daily_slugs_collide_across_years_and_are_handled() {
+        let report = audit_slug_uniqueness(&request(vec![Duration::D1])).unwrap();
+
+        let june_1 = report
+            .collisions
+            .iter()
+            .find(|c| c.slug == "solana-up-or-down-on-june-1")
+            .unwrap();
+        assert_eq!(june_1.kind, SlugCollisionKind::CrossYear);
+        assert_eq!(june_1.owners.len(), 2);
+        assert_eq!(report.unhandled_collisions, 0);
+        for collision in &report.collisions {
+            assert!(report.gamma_sample.contains(&collision.slug));
+        }
+    }
+
+    #[test]
+    fn gamma_cross_check_flags_slugs_matching_several_markets() {
+        let mut report = audit_slug_uniqueness(&SlugAuditRequest {
+            end_ts_utc_exclusive: JAN_1_2024 + 86_400,
+            sample_size: 3,
+            ..request(vec![Duration::M5])
+        })
+        .unwrap();
+        let duplicated = report.gamma_sample[1].clone();
+        let missing = report.gamma_sample[2].clone();
+
+        cross_check_gamma_sample(&mut report, |slug| {
+            if slug == duplicated {
+                Ok(2)
+            } else if slug == missing {
+                Ok(0)
+            } else {
+                Ok(1)
+            }
+        });
+
+        let gamma = report.gamma.as_ref().unwrap();
+        assert_eq!(gamma.checked, 3);
+        assert_eq!(gamma.exactly_once, 1);
+        assert_eq!(gamma.missing, vec![missing]);
+        assert_eq!(gamma.duplicated, vec![duplicated]);
+        assert!(!report.passed());
+    }
+
+    #[test]
+    fn empty_range_is_rejected() {
+        let err = audit_slug_uniqueness(&SlugAuditRequest {
+            end_ts_utc_exclusive: JAN_1_2024,
+            ..request(vec![Duration::M5])
+        })
+        .unwrap_err();
+        assert!(matches!(err, SlugAuditError::EmptyRange { .. }));
+    }
+}
@@ -0,0 +1,148 @@
+//! Probability smoothing with hysteresis.
+//!
+//! Raw model probabilities are rescored every second and can flicker by a tick or two,
+//! which requotes both bids each time. [`ProbabilitySmoother`] keeps an EWMA of each
+//! market's raw probability and publishes a new value only once the average has moved
+//! at least the hysteresis band away from the last published one. The quoting loop
+//! always acts on the published value; the dashboard shows it only when
+//! `PMM_PROB_SMOOTHING_DISPLAY` is set, and keeps both values on the row either way.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::env_vars::Settings;
+
+const PRUNE_INTERVAL_SECS: i64 = 3_600;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProbabilitySmoothingConfig {
+    /// EWMA weight of each new raw probability, in `(0, 1]`; `1` disables averaging.
+    pub alpha: f64,
+    /// Smallest move of the average that changes the published probability; `0`
+    /// publishes every change.
+    pub hysteresis: f64,
+    /// Whether the dashboard `probability` column shows the smoothed value.
+    pub display: bool,
+}
+
+impl Default for ProbabilitySmoothingConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl ProbabilitySmoothingConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_PROB_SMOOTHING_ALPHA`, `PMM_PROB_HYSTERESIS`, and
+    /// `PMM_PROB_SMOOTHING_DISPLAY`, falling back to defaults for unset or invalid
+    /// values.
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            alpha: settings.get_where("PMM_PROB_SMOOTHING_ALPHA", |alpha| {
+                *alpha > 0.0 && *alpha <= 1.0
+            }),
+            hysteresis: settings.get_where("PMM_PROB_HYSTERESIS", |band| (0.0..1.0).contains(band)),
+            display: settings.get("PMM_PROB_SMOOTHING_DISPLAY"),
+        }
+    }
+
+    /// Whether smoothing can ever publish something other than the raw probability.
+    pub fn enabled(&self) -> bool {
+        self.alpha < 1.0 || self.hysteresis > 0.0
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SmoothedProbability {
+    ewma: f64,
+    published: f64,
+    updated_ts_utc: i64,
+}
+
+#[derive(Default)]
+struct SmootherState {
+    by_slug: HashMap<String, SmoothedProbability>,
+    last_prune_ts_utc: i64,
+}
+
+/// Per-market EWMA and hysteresis state; shared by reference between callers.
+#[derive(Default)]
+pub struct ProbabilitySmoother {
+    config: ProbabilitySmoothingConfig,
+    state: Mutex<SmootherState>,
+}
+
+impl ProbabilitySmoother {
+    pub fn new(config: ProbabilitySmoothingConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::default(),
+        }
+    }
+
+    pub fn config(&self) -> &ProbabilitySmoothingConfig {
+        &self.config
+    }
+
+    /// Folds `raw` into `slug`'s average and returns the published probability. The
+    /// first value of a market is published as is; markets not updated for an hour
+    /// are forgotten.
+    pub fn update(&self, slug: &str, raw: f64, now_ts_utc: i64) -> f64 {
+        if !self.config.enabled() || !raw.is_finite() {
+            return raw;
+        }
+        let mut state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+        if now_ts_utc - state.last_prune_ts_utc >= PRUNE_INTERVAL_SECS {
+            state
+                .by_slug
+                .retain(|_, entry| now_ts_utc - entry.updated_ts_utc < PRUNE_INTERVAL_SECS);
+            state.last_prune_ts_utc = now_ts_utc;
+        }
+        let entry = state
+            .by_slug
+            .entry(slug.to_string())
+            .or_insert(SmoothedProbability {
+                ewma: raw,
+                published: raw,
+                updated_ts_utc: now_ts_utc,
+            });
+        entry.ewma += self.config.alpha * (raw - entry.ewma);
+        entry.updated_ts_utc = now_ts_utc;
+        // The epsilon keeps a move of exactly one band from being lost to rounding.
+        if (entry.ewma - entry.published).abs() >= self.config.hysteresis - 1e-12 {
+            entry.published = entry.ewma;
+        }
+        entry.published
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ewma_and_hysteresis_hold_the_published_probability() {
+        let smoother = ProbabilitySmoother::new(ProbabilitySmoothingConfig {
+            alpha: 0.5,
+            hysteresis: 0.02,
+            display: false,
+        });
+        assert_eq!(smoother.update("m", 0.50, 0), 0.50);
+        // Flicker inside the band is absorbed.
+        assert_eq!(smoother.update("m", 0.52, 1), 0.50);
+        assert_eq!(smoother.update("m", 0.49, 2), 0.50);
+        // A sustained move is published once the average leaves the band.
+        assert_eq!(smoother.update("m", 0.53, 3), 0.50);
+        let published = smoother.update("m", 0.60, 4);
+        assert!((published - 0.5575).abs() < 1e-9, "{published}");
+        // Markets are independent, and disabled smoothing passes values through.
+        assert_eq!(smoother.update("other", 0.3, 4), 0.3);
+        let off = ProbabilitySmoother::default();
+        assert_eq!(off.update("m", 0.5, 0), 0.5);
+        assert_eq!(off.update("m", 0.51, 1), 0.51);
+    }
+}
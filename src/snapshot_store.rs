@@ -0,0 +1,547 @@
+//! Persistent dashboard snapshot history and `/dashboard/history`.
+//!
+//! [`SnapshotStore::spawn`] samples the snapshot source and appends each row to SQLite
+//! whenever it differs from the last one stored for its slug, so the table holds
+//! row-level deltas: every change of probability, bids, offers, and positions with the
+//! time it was first seen. Only live (or static) snapshots are recorded; demo and
+//! placeholder rows never reach the history.
+//!
+//! `GET /dashboard/history?slug=...&from=...&to=...` replays one slug: the row in effect
+//! at `from` followed by every change up to `to`, for post-mortem analysis of fills.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Extension, Json, Router};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{debug, info, warn};
+
+use crate::dashboard::{DashboardRow, DashboardSnapshot, DashboardSnapshotSource};
+use crate::env_vars::Settings;
+use crate::quality::snapshot_up;
+use crate::redaction::PublicRequest;
+
+/// Most history points one request returns.
+pub const MAX_HISTORY_POINTS: usize = 10_000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotStoreConfig {
+    /// SQLite file holding the history; `None` disables recording and the endpoint.
+    pub store_path: Option<PathBuf>,
+    /// How often the source is sampled for changed rows.
+    pub sample_interval_ms: u64,
+    /// Rows older than this are pruned.
+    pub retention_secs: i64,
+}
+
+impl Default for SnapshotStoreConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl SnapshotStoreConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_SNAPSHOT_STORE_PATH` (`off` disables), `PMM_SNAPSHOT_STORE_SAMPLE_MS`,
+    /// and `PMM_SNAPSHOT_STORE_RETENTION_SECS`, falling back to defaults for unset or
+    /// invalid values.
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            store_path: settings.get("PMM_SNAPSHOT_STORE_PATH"),
+            sample_interval_ms: settings.get_where("PMM_SNAPSHOT_STORE_SAMPLE_MS", |ms| *ms > 0),
+            retention_secs: settings
+                .get_where("PMM_SNAPSHOT_STORE_RETENTION_SECS", |secs| *secs > 0),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SnapshotStoreError {
+    #[error("sqlite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid stored row: {0}")]
+    InvalidRow(#[from] serde_json::Error),
+}
+
+/// One stored state of a row: what the dashboard showed from `ts_ms` until the next
+/// point.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotHistoryPoint {
+    pub ts_ms: i64,
+    /// Publish sequence number of the snapshot the row came from.
+    pub seq: u64,
+    pub row: DashboardRow,
+}
+
+/// Response of `/dashboard/history`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotHistory {
+    pub slug: String,
+    pub from_ts_utc: i64,
+    pub to_ts_utc: i64,
+    /// Oldest first; the first point may predate `from_ts_utc` when it was still in
+    /// effect then.
+    pub points: Vec<SnapshotHistoryPoint>,
+    /// Set when more than [`MAX_HISTORY_POINTS`] points matched; later ones are left
+    /// out.
+    pub truncated: bool,
+}
+
+struct StoreState {
+    conn: Connection,
+    /// Last row JSON stored per slug, so unchanged rows are not appended again.
+    last_rows: HashMap<String, String>,
+}
+
+/// SQLite snapshot history; clones share the same connection.
+#[derive(Clone)]
+pub struct SnapshotStore {
+    state: Arc<Mutex<StoreState>>,
+}
+
+impl SnapshotStore {
+    /// Opens (creating if needed) the history at `path`.
+    pub fn open(path: &Path) -> Result<Self, SnapshotStoreError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "
+            PRAGMA journal_mode=WAL;
+            CREATE TABLE IF NOT EXISTS snapshot_rows (
+                slug TEXT NOT NULL,
+                ts_ms INTEGER NOT NULL,
+                seq INTEGER NOT NULL,
+                row_json TEXT NOT NULL,
+                PRIMARY KEY(slug, ts_ms)
+            ) WITHOUT ROWID;
+            CREATE INDEX IF NOT EXISTS snapshot_rows_ts ON snapshot_rows(ts_ms);
+            ",
+        )?;
+        // Seed the dedup state from the newest stored row of each slug, so a restart
+        // does not append every row again.
+        let mut last_rows = HashMap::new();
+        {
+            let mut stmt = conn.prepare(
+                "
+                SELECT slug, row_json FROM snapshot_rows AS r
+                WHERE ts_ms = (SELECT MAX(ts_ms) FROM snapshot_rows WHERE slug = r.slug)
+                ",
+            )?;
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+            for row in rows {
+                let (slug, row_json): (String, String) = row?;
+                last_rows.insert(slug, row_json);
+            }
+        }
+        Ok(Self {
+            state: Arc::new(Mutex::new(StoreState { conn, last_rows })),
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, StoreState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Appends the rows of `snapshot` that changed since they were last stored,
+    /// stamped `ts_ms`. Returns how many were written.
+    pub fn append(
+        &self,
+        snapshot: &DashboardSnapshot,
+        ts_ms: i64,
+    ) -> Result<usize, SnapshotStoreError> {
+        let mut state = self.lock();
+        let mut changed = Vec::new();
+        for row in &snapshot.rows {
+            let row_json = serde_json::to_string(row)?;
+            if state.last_rows.get(&row.slug) != Some(&row_json) {
+                changed.push((row.slug.clone(), row_json));
+            }
+        }
+        if changed.is_empty() {
+            return Ok(0);
+        }
+        let tx = state.conn.transaction()?;
+        {
+            let mut insert = tx.prepare_cached(
+                "INSERT OR REPLACE INTO snapshot_rows (slug, ts_ms, seq, row_json)
+                 VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            for (slug, row_json) in &changed {
+                insert.execute(params![slug, ts_ms, snapshot.seq as i64, row_json])?;
+            }
+        }
+        tx.commit()?;
+        let written = changed.len();
+        state.last_rows.extend(changed);
+        Ok(written)
+    }
+
+    /// Stored states of `slug` between `from_ts_ms` and `to_ts_ms` (inclusive), oldest
+    /// first, led by the state in effect at `from_ts_ms`. At most `limit` points; the
+    /// flag reports whether more matched.
+    pub fn history(
+        &self,
+        slug: &str,
+        from_ts_ms: i64,
+        to_ts_ms: i64,
+        limit: usize,
+    ) -> Result<(Vec<SnapshotHistoryPoint>, bool), SnapshotStoreError> {
+        let state = self.lock();
+        let mut stmt = state.conn.prepare_cached(
+            "
+            SELECT ts_ms, seq, row_json FROM snapshot_rows
+            WHERE slug = ?1
+              AND ts_ms <= ?3
+              AND ts_ms >= COALESCE(
+                  (SELECT MAX(ts_ms) FROM snapshot_rows WHERE slug = ?1 AND ts_ms <= ?2),
+                  ?2
+              )
+            ORDER BY ts_ms ASC
+            LIMIT ?4
+            ",
+        )?;
+        let rows = stmt.query_map(
+            params![slug, from_ts_ms, to_ts_ms, limit as i64 + 1],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            },
+        )?;
+        let mut points = Vec::new();
+        for row in rows {
+            let (ts_ms, seq, row_json) = row?;
+            points.push(SnapshotHistoryPoint {
+                ts_ms,
+                seq: seq as u64,
+                row: serde_json::from_str(&row_json)?,
+            });
+        }
+        let truncated = points.len() > limit;
+        points.truncate(limit);
+        Ok((points, truncated))
+    }
+
+    /// Stored states of every slug between `from_ts_ms` and `to_ts_ms` (inclusive),
+    /// oldest first, led by each slug's state in effect at `from_ts_ms`. At most
+    /// `limit` points; the flag reports whether more matched.
+    pub fn range(
+        &self,
+        from_ts_ms: i64,
+        to_ts_ms: i64,
+        limit: usize,
+    ) -> Result<(Vec<SnapshotHistoryPoint>, bool), SnapshotStoreError> {
+        let state = self.lock();
+        let mut stmt = state.conn.prepare_cached(
+            "
+            SELECT ts_ms, seq, row_json FROM snapshot_rows AS r
+            WHERE ts_ms <= ?2
+              AND ts_ms >= COALESCE(
+                  (SELECT MAX(ts_ms) FROM snapshot_rows
+                   WHERE slug = r.slug AND ts_ms <= ?1),
+                  ?1
+              )
+            ORDER BY ts_ms ASC, seq ASC
+            LIMIT ?3
+            ",
+        )?;
+        let rows = stmt.query_map(params![from_ts_ms, to_ts_ms, limit as i64 + 1], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?;
+        let mut points = Vec::new();
+        for row in rows {
+            let (ts_ms, seq, row_json) = row?;
+            points.push(SnapshotHistoryPoint {
+                ts_ms,
+                seq: seq as u64,
+                row: serde_json::from_str(&row_json)?,
+            });
+        }
+        let truncated = points.len() > limit;
+        points.truncate(limit);
+        Ok((points, truncated))
+    }
+
+    /// Deletes rows older than `cutoff_ms`, keeping the newest row of each slug so
+    /// unchanged rows are not appended again. Returns how many were deleted.
+    pub fn prune_older_than(&self, cutoff_ms: i64) -> Result<u64, SnapshotStoreError> {
+        let state = self.lock();
+        let deleted = state.conn.execute(
+            "
+            DELETE FROM snapshot_rows AS r
+            WHERE ts_ms < ?1
+              AND ts_ms < (SELECT MAX(ts_ms) FROM snapshot_rows WHERE slug = r.slug)
+            ",
+            params![cutoff_ms],
+        )?;
+        Ok(deleted as u64)
+    }
+
+    /// Opens the configured store and starts sampling `source` every
+    /// `sample_interval_ms`, pruning once an hour. `None` when disabled or the store
+    /// cannot be opened (logged as `snapshot_store.disabled`). Needs a Tokio runtime.
+    pub fn spawn(
+        source: Arc<dyn DashboardSnapshotSource>,
+        cfg: &SnapshotStoreConfig,
+    ) -> Option<Self> {
+        let path = cfg.store_path.as_ref()?;
+        let store = match Self::open(path) {
+            Ok(store) => store,
+            Err(err) => {
+                warn!(
+                    component = "snapshot_store",
+                    event = "snapshot_store.disabled",
+                    path = %path.display(),
+                    error = %err
+                );
+                return None;
+            }
+        };
+        info!(
+            component = "snapshot_store",
+            event = "snapshot_store.opened",
+            path = %path.display(),
+            sample_interval_ms = cfg.sample_interval_ms
+        );
+        let task_store = store.clone();
+        let cfg = cfg.clone();
+        tokio::spawn(async move {
+            let mut ticker =
+                tokio::time::interval(std::time::Duration::from_millis(cfg.sample_interval_ms));
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            let mut last_prune_ms = i64::MIN;
+            loop {
+                ticker.tick().await;
+                if !snapshot_up(&source.status()) {
+                    continue;
+                }
+                let snapshot = source.snapshot();
+                let store = task_store.clone();
+                let retention_ms = cfg.retention_secs * 1_000;
+                let prune = {
+                    let now_ms = crate::faults::now_utc().timestamp_millis();
+                    let due = now_ms.saturating_sub(last_prune_ms) >= 3_600_000;
+                    if due {
+                        last_prune_ms = now_ms;
+                    }
+                    due
+                };
+                let appended = tokio::task::spawn_blocking(move || {
+                    let now_ms = crate::faults::now_utc().timestamp_millis();
+                    let written = store.append(&snapshot, now_ms)?;
+                    let pruned = if prune {
+                        store.prune_older_than(now_ms - retention_ms)?
+                    } else {
+                        0
+                    };
+                    Ok::<_, SnapshotStoreError>((written, pruned))
+                })
+                .await;
+                match appended {
+                    Ok(Ok((written, pruned))) => debug!(
+                        component = "snapshot_store",
+                        event = "snapshot_store.appended",
+                        rows = written,
+                        pruned = pruned
+                    ),
+                    Ok(Err(err)) => warn!(
+                        component = "snapshot_store",
+                        event = "snapshot_store.error",
+                        error = %err
+                    ),
+                    Err(err) => warn!(
+                        component = "snapshot_store",
+                        event = "snapshot_store.error",
+                        error = %err
+                    ),
+                }
+            }
+        });
+        Some(store)
+    }
+}
+
+impl std::fmt::Debug for SnapshotStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SnapshotStore")
+            .field("slugs", &self.lock().last_rows.len())
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SnapshotHistoryQuery {
+    pub slug: String,
+    /// Range start, UTC seconds (default: 24 hours before `to`).
+    pub from: Option<i64>,
+    /// Range end, UTC seconds (default: now).
+    pub to: Option<i64>,
+}
+
+/// Router serving `GET /dashboard/history?slug=...&from=...&to=...`.
+pub fn snapshot_history_router(store: SnapshotStore) -> Router {
+    Router::new()
+        .route("/dashboard/history", get(get_snapshot_history))
+        .with_state(store)
+}
+
+/// Rows are redacted for requests marked [`PublicRequest`] by `with_dashboard_token`.
+async fn get_snapshot_history(
+    State(store): State<SnapshotStore>,
+    public: Option<Extension<PublicRequest>>,
+    Query(query): Query<SnapshotHistoryQuery>,
+) -> Response {
+    let to_ts_utc = query
+        .to
+        .unwrap_or_else(|| crate::faults::now_utc().timestamp());
+    let from_ts_utc = query.from.unwrap_or(to_ts_utc - 86_400);
+    if from_ts_utc > to_ts_utc {
+        return (
+            StatusCode::BAD_REQUEST,
+            format!("from ({from_ts_utc}) is after to ({to_ts_utc})"),
+        )
+            .into_response();
+    }
+    let slug = query.slug.clone();
+    let history = tokio::task::spawn_blocking(move || {
+        store.history(
+            &slug,
+            from_ts_utc.saturating_mul(1_000),
+            to_ts_utc.saturating_mul(1_000).saturating_add(999),
+            MAX_HISTORY_POINTS,
+        )
+    })
+    .await;
+    match history {
+        Ok(Ok((mut points, truncated))) => {
+            if let Some(Extension(PublicRequest(redaction))) = public {
+                for point in &mut points {
+                    redaction.redact_dashboard_row(&mut point.row);
+                }
+            }
+            Json(SnapshotHistory {
+                slug: query.slug,
+                from_ts_utc,
+                to_ts_utc,
+                points,
+                truncated,
+            })
+            .into_response()
+        }
+        Ok(Err(err)) => {
+            warn!(
+                component = "snapshot_store",
+                event = "snapshot_store.error",
+                error = %err
+            );
+            (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response()
+        }
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(slug: &str, probability: &str) -> DashboardRow {
+        let mut row = DashboardRow::unresolved_with_times(slug, "BTC", "5m", 100, 400);
+        row.probability = Some(probability.to_string());
+        row
+    }
+
+    fn snapshot(rows: Vec<DashboardRow>, seq: u64) -> DashboardSnapshot {
+        DashboardSnapshot { rows, seq }
+    }
+
+    #[test]
+    fn only_changed_rows_are_appended_and_replayed_from_the_state_in_effect() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("snapshots.sqlite");
+        let store = SnapshotStore::open(&path).unwrap();
+
+        let written = store
+            .append(
+                &snapshot(vec![row("a", "0.50"), row("b", "0.40")], 1),
+                1_000,
+            )
+            .unwrap();
+        assert_eq!(written, 2);
+        let unchanged = snapshot(vec![row("a", "0.50"), row("b", "0.40")], 2);
+        assert_eq!(store.append(&unchanged, 2_000).unwrap(), 0);
+        assert_eq!(
+            store
+                .append(
+                    &snapshot(vec![row("a", "0.55"), row("b", "0.40")], 3),
+                    3_000
+                )
+                .unwrap(),
+            1
+        );
+        store
+            .append(&snapshot(vec![row("a", "0.60")], 4), 5_000)
+            .unwrap();
+
+        // From t=2000 the replay starts with the state stored at t=1000.
+        let (points, truncated) = store.history("a", 2_000, 10_000, 10).unwrap();
+        assert!(!truncated);
+        let seen: Vec<(i64, u64, Option<&str>)> = points
+            .iter()
+            .map(|point| (point.ts_ms, point.seq, point.row.probability.as_deref()))
+            .collect();
+        assert_eq!(
+            seen,
+            [
+                (1_000, 1, Some("0.50")),
+                (3_000, 3, Some("0.55")),
+                (5_000, 4, Some("0.60"))
+            ]
+        );
+        let (points, truncated) = store.history("a", 0, 10_000, 2).unwrap();
+        assert!(truncated);
+        assert_eq!(points.len(), 2);
+        assert!(store.history("c", 0, 10_000, 10).unwrap().0.is_empty());
+
+        // A reopened store remembers the last rows and does not append them again.
+        drop(store);
+        let store = SnapshotStore::open(&path).unwrap();
+        assert_eq!(
+            store
+                .append(
+                    &snapshot(vec![row("a", "0.60"), row("b", "0.40")], 5),
+                    6_000
+                )
+                .unwrap(),
+            0
+        );
+
+        // Pruning keeps the newest row of each slug.
+        assert_eq!(store.prune_older_than(4_000).unwrap(), 2);
+        let (points, _) = store.history("b", 0, 10_000, 10).unwrap();
+        assert_eq!(points.len(), 1);
+        assert_eq!(points[0].ts_ms, 1_000);
+    }
+}
@@ -0,0 +1,2095 @@
+//! Strategy routines and the decision log they report to.
+//!
+//! Routines are pure planners: they take a market's position/quote state and return
+//! the actions to take. Executing those actions (cancels, taker orders) belongs to the
+//! order layer; every planned action is recorded through a [`DecisionLog`]. The
+//! [`QuotingEngine`] is the exception: it keeps the bids it has rested and hands its
+//! intents to a [`TradingClient`] itself, always behind a [`ThrottledTradingClient`]
+//! so quoting stays under the order-rate limits, or only logs them in dry-run mode.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
+
+use crate::binance_ws::PriceSource;
+use crate::capital::{CapitalConfig, CapitalUsage};
+use crate::clob_stream::{BookSubscription, OrderBookSource};
+use crate::env_vars::Settings;
+use crate::features::{
+    FeatureColumn, FeatureDType, FeatureRow, FeatureSchema, FEATURE_SCHEMA_VERSION,
+};
+use crate::fill_model::{BookTop, OrderSide};
+use crate::model_inputs::{ModelInputRecord, ModelInputSource, ModelInputStore};
+use crate::models::{ModelPrediction, ModelRegistry};
+use crate::orders::ThrottledTradingClient;
+use crate::positions::{MarketPosition, PositionSource};
+use crate::preflight::RunMode;
+use crate::sizing::{kelly_stake, taker_fee_per_share, SizingConfig, SizingInput, SizingSide};
+use crate::slug::{Coin, Duration};
+use crate::smoothing::ProbabilitySmoother;
+use crate::toggles::MarketToggles;
+use crate::trading::{LimitOrder, TimeInForce, TradingClient, GTD_MIN_LEAD_SECS};
+
+/// End-of-interval flattening policy.
+///
+/// By default any open position is flattened with taker orders inside the window;
+/// holding into resolution requires `hold_into_resolution = true` *and* an expected
+/// resolution edge of at least `min_hold_edge` per share.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FlattenConfig {
+    /// Seconds before interval end at which quotes are cancelled and positions reviewed.
+    pub lead_seconds: i64,
+    pub hold_into_resolution: bool,
+    /// Minimum `expected payout - exit proceeds` per share required to hold.
+    pub min_hold_edge: f64,
+}
+
+impl Default for FlattenConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl FlattenConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_FLATTEN_LEAD_SECONDS`, `PMM_FLATTEN_HOLD_INTO_RESOLUTION`, and
+    /// `PMM_FLATTEN_MIN_HOLD_EDGE`, falling back to defaults for missing/invalid values.
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            lead_seconds: settings.get_where("PMM_FLATTEN_LEAD_SECONDS", |secs| *secs >= 0),
+            hold_into_resolution: settings.get("PMM_FLATTEN_HOLD_INTO_RESOLUTION"),
+            min_hold_edge: settings.get("PMM_FLATTEN_MIN_HOLD_EDGE"),
+        }
+    }
+}
+
+/// Position and book state of one market as seen by the flattening routine.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarketPositionState {
+    pub slug: String,
+    pub end_ts_utc: i64,
+    pub yes_shares: f64,
+    pub no_shares: f64,
+    pub open_quote_count: usize,
+    pub probability_yes: Option<f64>,
+    /// Model that produced `probability_yes` (see `ModelRegistry`).
+    pub model_id: Option<String>,
+    pub best_bid_yes: Option<f64>,
+    pub best_ask_yes: Option<f64>,
+    pub taker_fee_rate: f64,
+    pub fee_exponent: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FlattenOrder {
+    pub side: SizingSide,
+    pub shares: f64,
+    /// Worst acceptable sell price for the outcome token.
+    pub limit_price: f64,
+    pub hold_edge: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HoldReason {
+    /// Explicitly configured and the resolution edge clears `min_hold_edge`.
+    ResolutionEdge,
+    /// Flattening was required but there is no bid to sell into.
+    NoExitLiquidity,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HeldPosition {
+    pub side: SizingSide,
+    pub shares: f64,
+    pub hold_edge: Option<f64>,
+    pub reason: HoldReason,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EndOfIntervalPlan {
+    pub slug: String,
+    /// Model behind the hold edges, copied from the position state.
+    pub model_id: Option<String>,
+    pub cancel_quotes: bool,
+    pub flatten: Vec<FlattenOrder>,
+    pub held: Vec<HeldPosition>,
+}
+
+impl EndOfIntervalPlan {
+    pub fn is_empty(&self) -> bool {
+        !self.cancel_quotes && self.flatten.is_empty() && self.held.is_empty()
+    }
+}
+
+pub fn in_flatten_window(now_ts_utc: i64, end_ts_utc: i64, cfg: &FlattenConfig) -> bool {
+    end_ts_utc.saturating_sub(cfg.lead_seconds) <= now_ts_utc && now_ts_utc < end_ts_utc
+}
+
+/// Plans the end-of-interval routine for one market. Returns `None` outside the window.
+pub fn plan_end_of_interval(
+    state: &MarketPositionState,
+    now_ts_utc: i64,
+    cfg: &FlattenConfig,
+) -> Option<EndOfIntervalPlan> {
+    if !in_flatten_window(now_ts_utc, state.end_ts_utc, cfg) {
+        return None;
+    }
+
+    let mut plan = EndOfIntervalPlan {
+        slug: state.slug.clone(),
+        model_id: state.model_id.clone(),
+        cancel_quotes: state.open_quote_count > 0,
+        flatten: Vec::new(),
+        held: Vec::new(),
+    };
+
+    let legs = [
+        (
+            SizingSide::Yes,
+            state.yes_shares,
+            state.probability_yes,
+            state.best_bid_yes,
+        ),
+        (
+            SizingSide::No,
+            state.no_shares,
+            state.probability_yes.map(|q| 1.0 - q),
+            state.best_ask_yes.map(|ask| 1.0 - ask),
+        ),
+    ];
+    for (side, shares, win_probability, exit_price) in legs {
+        if shares <= 0.0 {
+            continue;
+        }
+
+        let hold_edge = match (win_probability, exit_price) {
+            (Some(q), Some(bid)) => {
+                let fee = taker_fee_per_share(bid, state.taker_fee_rate, state.fee_exponent);
+                Some(q - (bid - fee))
+            }
+            _ => None,
+        };
+        let hold =
+            cfg.hold_into_resolution && hold_edge.is_some_and(|edge| edge >= cfg.min_hold_edge);
+
+        match (hold, exit_price) {
+            (true, _) => plan.held.push(HeldPosition {
+                side,
+                shares,
+                hold_edge,
+                reason: HoldReason::ResolutionEdge,
+            }),
+            (false, Some(limit_price)) if limit_price > 0.0 => plan.flatten.push(FlattenOrder {
+                side,
+                shares,
+                limit_price,
+                hold_edge,
+            }),
+            (false, _) => plan.held.push(HeldPosition {
+                side,
+                shares,
+                hold_edge,
+                reason: HoldReason::NoExitLiquidity,
+            }),
+        }
+    }
+
+    Some(plan)
+}
+
+/// Risk limits for quoting in one phase of a market's life.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct QuoteRiskLimits {
+    pub max_order_usdc: f64,
+    pub max_position_usdc: f64,
+    /// Minimum model edge per share before a quote is placed.
+    pub min_edge: f64,
+}
+
+impl QuoteRiskLimits {
+    /// Reads `{prefix}_MAX_ORDER_USDC`, `{prefix}_MAX_POSITION_USDC`, and
+    /// `{prefix}_MIN_EDGE`.
+    fn from_settings(settings: &Settings, prefix: &str) -> Self {
+        let usdc = |suffix: &str| {
+            settings.get_where(&format!("{prefix}_{suffix}"), |value: &f64| *value >= 0.0)
+        };
+        Self {
+            max_order_usdc: usdc("MAX_ORDER_USDC"),
+            max_position_usdc: usdc("MAX_POSITION_USDC"),
+            min_edge: usdc("MIN_EDGE"),
+        }
+    }
+}
+
+/// When quoting is allowed and under which limits.
+///
+/// 4h/1d markets accept orders well before their interval starts. Pre-market quoting
+/// is opt-in, keyed off the accepting-orders timestamp captured by discovery (never
+/// the interval start), and runs under its own, tighter limits: there is no reference
+/// price yet and the book is thin.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreMarketQuoteConfig {
+    pub enabled: bool,
+    pub durations: Vec<Duration>,
+    /// Seconds after orders open before quoting, so the book can form.
+    pub warmup_secs: i64,
+    /// Seconds before interval start at which pre-market quotes are pulled; the
+    /// in-interval limits take over at start.
+    pub cutoff_before_start_secs: i64,
+    pub pre_market: QuoteRiskLimits,
+    pub in_interval: QuoteRiskLimits,
+}
+
+impl Default for PreMarketQuoteConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl PreMarketQuoteConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_PREMARKET_ENABLED`, `PMM_PREMARKET_DURATIONS` (comma list, e.g.
+    /// `4h,1d`), `PMM_PREMARKET_WARMUP_SECS`, `PMM_PREMARKET_CUTOFF_SECS`,
+    /// `PMM_PREMARKET_MAX_ORDER_USDC`, `PMM_PREMARKET_MAX_POSITION_USDC`,
+    /// `PMM_PREMARKET_MIN_EDGE`, and the in-interval `PMM_QUOTE_MAX_ORDER_USDC`,
+    /// `PMM_QUOTE_MAX_POSITION_USDC`, `PMM_QUOTE_MIN_EDGE`, falling back to defaults
+    /// for missing/invalid values.
+    pub fn from_settings(settings: &Settings) -> Self {
+        let secs = |name: &str| settings.get_where(name, |secs: &i64| *secs >= 0);
+        Self {
+            enabled: settings.get("PMM_PREMARKET_ENABLED"),
+            durations: settings
+                .parsed("PMM_PREMARKET_DURATIONS", |raw| {
+                    raw.split(',')
+                        .map(|label| Duration::parse(label.trim()).ok())
+                        .collect::<Option<Vec<_>>>()
+                })
+                .unwrap_or_default(),
+            warmup_secs: secs("PMM_PREMARKET_WARMUP_SECS"),
+            cutoff_before_start_secs: secs("PMM_PREMARKET_CUTOFF_SECS"),
+            pre_market: QuoteRiskLimits::from_settings(settings, "PMM_PREMARKET"),
+            in_interval: QuoteRiskLimits::from_settings(settings, "PMM_QUOTE"),
+        }
+    }
+}
+
+/// Timing of one market as seen by the quoting routine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuoteWindowInput {
+    pub slug: String,
+    pub duration: Duration,
+    pub start_ts_utc: i64,
+    pub end_ts_utc: i64,
+    /// From discovery; `None` when Gamma did not report it.
+    pub accepting_orders_ts_utc: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotePhase {
+    /// Before start, and orders are not (known to be) accepted yet.
+    AwaitingOrders,
+    /// Orders accepted, inside the warm-up delay.
+    Warmup,
+    /// Orders accepted but pre-market quoting is disabled for this market.
+    AwaitingStart,
+    PreMarket,
+    /// Inside `cutoff_before_start_secs` of the interval start.
+    PreStartCutoff,
+    InInterval,
+    Ended,
+    /// The coin/duration pair is switched off (see `MarketToggles`).
+    Disabled,
+}
+
+impl QuotePhase {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::AwaitingOrders => "awaiting_orders",
+            Self::Warmup => "warmup",
+            Self::AwaitingStart => "awaiting_start",
+            Self::PreMarket => "pre_market",
+            Self::PreStartCutoff => "pre_start_cutoff",
+            Self::InInterval => "in_interval",
+            Self::Ended => "ended",
+            Self::Disabled => "disabled",
+        }
+    }
+
+    pub fn allows_quotes(self) -> bool {
+        matches!(self, Self::PreMarket | Self::InInterval)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuoteWindowPlan {
+    pub slug: String,
+    pub phase: QuotePhase,
+    /// Limits to quote under; `None` when quoting is not allowed.
+    pub limits: Option<QuoteRiskLimits>,
+}
+
+pub fn quote_phase(
+    input: &QuoteWindowInput,
+    now_ts_utc: i64,
+    cfg: &PreMarketQuoteConfig,
+) -> QuotePhase {
+    if now_ts_utc >= input.end_ts_utc {
+        return QuotePhase::Ended;
+    }
+    if now_ts_utc >= input.start_ts_utc {
+        return QuotePhase::InInterval;
+    }
+    let Some(accepting) = input.accepting_orders_ts_utc else {
+        return QuotePhase::AwaitingOrders;
+    };
+    if now_ts_utc < accepting {
+        return QuotePhase::AwaitingOrders;
+    }
+    if now_ts_utc < accepting.saturating_add(cfg.warmup_secs) {
+        return QuotePhase::Warmup;
+    }
+    if !cfg.enabled || !cfg.durations.contains(&input.duration) {
+        return QuotePhase::AwaitingStart;
+    }
+    if now_ts_utc
+        >= input
+            .start_ts_utc
+            .saturating_sub(cfg.cutoff_before_start_secs)
+    {
+        return QuotePhase::PreStartCutoff;
+    }
+    QuotePhase::PreMarket
+}
+
+pub fn plan_quote_window(
+    input: &QuoteWindowInput,
+    now_ts_utc: i64,
+    cfg: &PreMarketQuoteConfig,
+) -> QuoteWindowPlan {
+    let phase = quote_phase(input, now_ts_utc, cfg);
+    QuoteWindowPlan {
+        slug: input.slug.clone(),
+        phase,
+        limits: match phase {
+            QuotePhase::PreMarket => Some(cfg.pre_market),
+            QuotePhase::InInterval => Some(cfg.in_interval),
+            _ => None,
+        },
+    }
+}
+
+/// Records a quoting phase change: `enable_quotes` (with the phase's `min_edge`) when
+/// entering a quoting phase, `cancel_quotes` when leaving one. Returns `None` when the phase
+/// is unchanged or neither side quotes.
+pub fn record_quote_phase_change(
+    previous: Option<QuotePhase>,
+    plan: &QuoteWindowPlan,
+    now_ts_utc: i64,
+    log: &dyn DecisionLog,
+) -> Option<DecisionRecord> {
+    if previous == Some(plan.phase) {
+        return None;
+    }
+    let was_quoting = previous.is_some_and(QuotePhase::allows_quotes);
+    let action = if plan.phase.allows_quotes() {
+        DecisionAction::EnableQuotes
+    } else if was_quoting {
+        DecisionAction::CancelQuotes
+    } else {
+        return None;
+    };
+    let record = DecisionRecord {
+        ts_utc: now_ts_utc,
+        slug: plan.slug.clone(),
+        routine: "quote_window".to_string(),
+        action,
+        side: None,
+        shares: None,
+        price: None,
+        edge: plan.limits.map(|limits| limits.min_edge),
+        reason: Some(plan.phase.as_str().to_string()),
+        model_id: None,
+    };
+    log.record(&record);
+    Some(record)
+}
+
+/// Price grid of Polymarket order books.
+pub const QUOTE_TICK: f64 = 0.01;
+
+/// One resting bid: `size` shares at `price`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct QuoteLevel {
+    pub price: f64,
+    pub size: f64,
+}
+
+/// The bids the quoting routine would rest on each outcome. A side is `None` when it
+/// has no edge or would cross the book.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct MakerQuotes {
+    pub yes: Option<QuoteLevel>,
+    pub no: Option<QuoteLevel>,
+}
+
+/// Plans maker bids on YES and NO around the model probability: each side bids its fair
+/// value less `limits.min_edge`, rounded down to the tick and kept one tick inside the
+/// opposite side of the YES book (a NO bid at `p` crosses a YES bid at `1 - p`). Sizes
+/// spend `limits.max_order_usdc`, rounded down to cents of a share.
+pub fn plan_maker_quotes(
+    probability_yes: f64,
+    best_bid_yes: Option<f64>,
+    best_ask_yes: Option<f64>,
+    limits: &QuoteRiskLimits,
+) -> MakerQuotes {
+    if !(0.0..=1.0).contains(&probability_yes) {
+        return MakerQuotes::default();
+    }
+    let level = |fair: f64, cross_at: Option<f64>| {
+        let mut price = fair - limits.min_edge;
+        if let Some(cross_at) = cross_at {
+            price = price.min(cross_at - QUOTE_TICK);
+        }
+        // Nudge before flooring so 0.57 - 0.02 does not land on 0.54.
+        let price = ((price / QUOTE_TICK) + 1e-9).floor() * QUOTE_TICK;
+        if price < QUOTE_TICK {
+            return None;
+        }
+        let size = (limits.max_order_usdc / price * 100.0).floor() / 100.0;
+        (size > 0.0).then_some(QuoteLevel {
+            price: (price * 100.0).round() / 100.0,
+            size,
+        })
+    };
+    MakerQuotes {
+        yes: level(probability_yes, best_ask_yes),
+        no: level(1.0 - probability_yes, best_bid_yes.map(|bid| 1.0 - bid)),
+    }
+}
+
+/// Parameters of the two-sided quoting loop for one market duration.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct QuoteParams {
+    /// Distance of each bid below its fair value; the phase's `min_edge` is the floor.
+    pub half_spread: f64,
+    /// Notional of each bid, capped by the phase's `max_order_usdc`.
+    pub order_usdc: f64,
+    /// Fair-value shift per net YES share held: a long YES inventory lowers the YES bid
+    /// and raises the NO bid, and vice versa.
+    pub skew_per_share: f64,
+    /// Largest notional of one outcome, held plus bid; capped by the phase's
+    /// `max_position_usdc`.
+    pub max_exposure_usdc: f64,
+}
+
+impl Default for QuoteParams {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl QuoteParams {
+    /// Reads `PMM_STRATEGY_HALF_SPREAD`, `PMM_STRATEGY_ORDER_USDC`,
+    /// `PMM_STRATEGY_SKEW_PER_SHARE`, and `PMM_STRATEGY_MAX_EXPOSURE_USDC`.
+    pub fn from_settings(settings: &Settings) -> Self {
+        let float = |name: &str| settings.get_where(name, |value: &f64| *value >= 0.0);
+        Self {
+            half_spread: float("PMM_STRATEGY_HALF_SPREAD"),
+            order_usdc: float("PMM_STRATEGY_ORDER_USDC"),
+            skew_per_share: float("PMM_STRATEGY_SKEW_PER_SHARE"),
+            max_exposure_usdc: float("PMM_STRATEGY_MAX_EXPOSURE_USDC"),
+        }
+    }
+
+    /// Applies comma-separated `key=value` pairs (`half_spread`, `order_usdc`,
+    /// `skew_per_share`, `max_exposure_usdc`) on top of `self`.
+    pub(crate) fn with_overrides(mut self, raw: &str) -> Result<Self, String> {
+        for pair in raw
+            .split(',')
+            .map(str::trim)
+            .filter(|pair| !pair.is_empty())
+        {
+            let (key, value) = pair
+                .split_once('=')
+                .ok_or_else(|| format!("expected key=value, got {pair:?}"))?;
+            let value = value
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|value| value.is_finite() && *value >= 0.0)
+                .ok_or_else(|| format!("{pair:?} is not a non-negative number"))?;
+            match key.trim() {
+                "half_spread" => self.half_spread = value,
+                "order_usdc" => self.order_usdc = value,
+                "skew_per_share" => self.skew_per_share = value,
+                "max_exposure_usdc" => self.max_exposure_usdc = value,
+                other => return Err(format!("unknown quote parameter {other:?}")),
+            }
+        }
+        Ok(self)
+    }
+}
+
+/// Parses per-duration quote overrides: `;`-separated `duration:key=value,...` entries
+/// (e.g. `5m:half_spread=0.03,order_usdc=5;1d:max_exposure_usdc=50`), each applied on
+/// top of `base`.
+pub fn parse_quote_overrides(
+    raw: &str,
+    base: QuoteParams,
+) -> Result<HashMap<Duration, QuoteParams>, String> {
+    let mut overrides = HashMap::new();
+    for entry in raw
+        .split(';')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+    {
+        let (label, pairs) = entry
+            .split_once(':')
+            .ok_or_else(|| format!("expected duration:key=value, got {entry:?}"))?;
+        let duration = Duration::parse(label.trim()).map_err(|err| err.to_string())?;
+        let params: &mut QuoteParams = overrides.entry(duration).or_insert(base);
+        *params = params.with_overrides(pairs)?;
+    }
+    Ok(overrides)
+}
+
+/// Configuration of the [`QuotingEngine`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrategyConfig {
+    /// Logs and tracks intents without sending them to the CLOB.
+    pub dry_run: bool,
+    pub default: QuoteParams,
+    pub overrides: HashMap<Duration, QuoteParams>,
+}
+
+impl Default for StrategyConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl StrategyConfig {
+    /// Parameters for markets of `duration`.
+    pub fn params(&self, duration: Duration) -> QuoteParams {
+        self.overrides
+            .get(&duration)
+            .copied()
+            .unwrap_or(self.default)
+    }
+
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_STRATEGY_DRY_RUN` (defaults to on unless `PMM_MODE=live`), the
+    /// [`QuoteParams`] settings, and `PMM_STRATEGY_OVERRIDES` (see
+    /// [`parse_quote_overrides`]), falling back to defaults for missing/invalid values.
+    pub fn from_settings(settings: &Settings) -> Self {
+        let live = settings.get::<RunMode>("PMM_MODE") == RunMode::Live;
+        let default = QuoteParams::from_settings(settings);
+        Self {
+            dry_run: settings
+                .get::<Option<bool>>("PMM_STRATEGY_DRY_RUN")
+                .unwrap_or(!live),
+            default,
+            overrides: settings
+                .parsed("PMM_STRATEGY_OVERRIDES", |raw| {
+                    parse_quote_overrides(raw, default).ok()
+                })
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Plans two-sided maker bids around an inventory-skewed fair value: the model
+/// probability less `skew_per_share` per net YES share. Bids sit `half_spread` (at
+/// least the phase's `min_edge`) below fair value, as in [`plan_maker_quotes`], and a
+/// side's size is cut so that its held plus bid notional stays within
+/// `max_exposure_usdc`.
+pub fn plan_two_sided_quotes(
+    probability_yes: f64,
+    best_bid_yes: Option<f64>,
+    best_ask_yes: Option<f64>,
+    net_yes_shares: f64,
+    params: &QuoteParams,
+    limits: &QuoteRiskLimits,
+) -> MakerQuotes {
+    if !(0.0..=1.0).contains(&probability_yes) {
+        return MakerQuotes::default();
+    }
+    let limits = QuoteRiskLimits {
+        max_order_usdc: params.order_usdc.min(limits.max_order_usdc),
+        max_position_usdc: params.max_exposure_usdc.min(limits.max_position_usdc),
+        min_edge: params.half_spread.max(limits.min_edge),
+    };
+    let fair_yes = (probability_yes - params.skew_per_share * net_yes_shares).clamp(0.0, 1.0);
+    let quotes = plan_maker_quotes(fair_yes, best_bid_yes, best_ask_yes, &limits);
+    let cap = |level: Option<QuoteLevel>, held: f64| {
+        let level = level?;
+        let room = limits.max_position_usdc / level.price - held.max(0.0);
+        let size = level.size.min((room * 100.0).floor() / 100.0);
+        (size > 0.0).then_some(QuoteLevel { size, ..level })
+    };
+    MakerQuotes {
+        yes: cap(quotes.yes, net_yes_shares),
+        no: cap(quotes.no, -net_yes_shares),
+    }
+}
+
+/// `level` cut to the Kelly stake for buying `side` at its price. Bids rest as maker
+/// orders, so no taker fee is charged.
+fn kelly_capped(
+    level: Option<QuoteLevel>,
+    side: SizingSide,
+    probability_yes: f64,
+    sizing: &SizingConfig,
+) -> Option<QuoteLevel> {
+    let level = level?;
+    let price_yes = match side {
+        SizingSide::Yes => level.price,
+        SizingSide::No => 1.0 - level.price,
+    };
+    let input = SizingInput {
+        probability_yes,
+        price_yes,
+        fee_per_share: 0.0,
+        bankroll: sizing.per_market_bankroll,
+    };
+    let decision = kelly_stake(&input, sizing).ok()?;
+    if decision.side != Some(side) || !decision.is_trade() {
+        return None;
+    }
+    let size = level
+        .size
+        .min((decision.stake_usdc / level.price * 100.0).floor() / 100.0);
+    (size > 0.0).then_some(QuoteLevel { size, ..level })
+}
+
+/// One strategy decision, as persisted/emitted by a [`DecisionLog`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecisionRecord {
+    pub ts_utc: i64,
+    pub slug: String,
+    pub routine: String,
+    pub action: DecisionAction,
+    pub side: Option<SizingSide>,
+    pub shares: Option<f64>,
+    pub price: Option<f64>,
+    pub edge: Option<f64>,
+    pub reason: Option<String>,
+    /// Model whose probability the decision used; `None` when it used none.
+    #[serde(default)]
+    pub model_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DecisionAction {
+    CancelQuotes,
+    Flatten,
+    Hold,
+    EnableQuotes,
+    PlaceQuote,
+}
+
+impl DecisionAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::CancelQuotes => "cancel_quotes",
+            Self::Flatten => "flatten",
+            Self::Hold => "hold",
+            Self::EnableQuotes => "enable_quotes",
+            Self::PlaceQuote => "place_quote",
+        }
+    }
+}
+
+pub trait DecisionLog: Send + Sync {
+    fn record(&self, record: &DecisionRecord);
+}
+
+/// Decision log backed by structured `tracing` events (`event = "strategy.decision"`).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TracingDecisionLog;
+
+impl DecisionLog for TracingDecisionLog {
+    fn record(&self, record: &DecisionRecord) {
+        info!(
+            component = "strategy",
+            event = "strategy.decision",
+            routine = %record.routine,
+            slug = %record.slug,
+            action = record.action.as_str(),
+            side = record.side.map(SizingSide::as_str),
+            shares = record.shares,
+            price = record.price,
+            edge = record.edge,
+            reason = record.reason.as_deref(),
+            model_id = record.model_id.as_deref(),
+            ts_utc = record.ts_utc
+        );
+    }
+}
+
+/// Decision log appending to a local SQLite `strategy_decisions` table, the
+/// single-host counterpart of `PgDecisionLog`; `pmm replay` reads it back. Write
+/// failures are logged (`strategy.decision_log_error`) and do not stop the strategy.
+pub struct SqliteDecisionLog {
+    conn: std::sync::Mutex<rusqlite::Connection>,
+}
+
+impl SqliteDecisionLog {
+    /// Opens (creating if needed) the decision store at `path`.
+    pub fn open(path: &std::path::Path) -> Result<Self, rusqlite::Error> {
+        if let Some(parent) = path.parent() {
+            // A missing parent surfaces as the open error below.
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let conn = rusqlite::Connection::open(path)?;
+        conn.execute_batch(
+            "
+            PRAGMA journal_mode=WAL;
+            CREATE TABLE IF NOT EXISTS strategy_decisions (
+                id INTEGER PRIMARY KEY,
+                ts_utc INTEGER NOT NULL,
+                slug TEXT NOT NULL,
+                record_json TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS strategy_decisions_ts ON strategy_decisions(ts_utc);
+            ",
+        )?;
+        Ok(Self {
+            conn: std::sync::Mutex::new(conn),
+        })
+    }
+
+    /// Decisions with `from_ts_utc <= ts_utc <= to_ts_utc`, in recording order.
+    pub fn range(
+        &self,
+        from_ts_utc: i64,
+        to_ts_utc: i64,
+    ) -> Result<Vec<DecisionRecord>, rusqlite::Error> {
+        let conn = self
+            .conn
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut stmt = conn.prepare_cached(
+            "SELECT record_json FROM strategy_decisions
+             WHERE ts_utc BETWEEN ?1 AND ?2 ORDER BY ts_utc, id",
+        )?;
+        let rows = stmt.query_map(rusqlite::params![from_ts_utc, to_ts_utc], |row| {
+            let json: String = row.get(0)?;
+            serde_json::from_str(&json).map_err(|err| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    0,
+                    rusqlite::types::Type::Text,
+                    err.into(),
+                )
+            })
+        })?;
+        rows.collect()
+    }
+}
+
+impl DecisionLog for SqliteDecisionLog {
+    fn record(&self, record: &DecisionRecord) {
+        let conn = self
+            .conn
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let result = serde_json::to_string(record)
+            .map_err(|err| err.to_string())
+            .and_then(|json| {
+                conn.execute(
+                    "INSERT INTO strategy_decisions (ts_utc, slug, record_json)
+                     VALUES (?1, ?2, ?3)",
+                    rusqlite::params![record.ts_utc, record.slug, json],
+                )
+                .map_err(|err| err.to_string())
+            });
+        if let Err(err) = result {
+            warn!(
+                component = "strategy",
+                event = "strategy.decision_log_error",
+                slug = %record.slug,
+                routine = %record.routine,
+                error = %err
+            );
+        }
+    }
+}
+
+/// Where [`SqliteDecisionLog`] keeps decisions: `PMM_DECISION_STORE_PATH` (default
+/// `data/decisions.sqlite`; `off` disables).
+pub fn decision_store_path_from_env() -> Option<std::path::PathBuf> {
+    decision_store_path(&Settings::from_env())
+}
+
+/// [`decision_store_path_from_env`] on `settings`.
+pub fn decision_store_path(settings: &Settings) -> Option<std::path::PathBuf> {
+    settings.get("PMM_DECISION_STORE_PATH")
+}
+
+/// Converts a plan into decision records and writes them to `log`, in execution order:
+/// quote cancels first, then flattening orders, then explicit holds.
+pub fn record_end_of_interval_plan(
+    plan: &EndOfIntervalPlan,
+    now_ts_utc: i64,
+    log: &dyn DecisionLog,
+) -> Vec<DecisionRecord> {
+    let base = DecisionRecord {
+        ts_utc: now_ts_utc,
+        slug: plan.slug.clone(),
+        routine: "end_of_interval".to_string(),
+        action: DecisionAction::CancelQuotes,
+        side: None,
+        shares: None,
+        price: None,
+        edge: None,
+        reason: None,
+        model_id: plan.model_id.clone(),
+    };
+
+    let mut records = Vec::new();
+    if plan.cancel_quotes {
+        records.push(DecisionRecord {
+            reason: Some("interval_end_window".to_string()),
+            ..base.clone()
+        });
+    }
+    for order in &plan.flatten {
+        records.push(DecisionRecord {
+            action: DecisionAction::Flatten,
+            side: Some(order.side),
+            shares: Some(order.shares),
+            price: Some(order.limit_price),
+            edge: order.hold_edge,
+            reason: Some(
+                match order.hold_edge {
+                    Some(_) => "hold_not_selected",
+                    None => "edge_unknown",
+                }
+                .to_string(),
+            ),
+            ..base.clone()
+        });
+    }
+    for held in &plan.held {
+        records.push(DecisionRecord {
+            action: DecisionAction::Hold,
+            side: Some(held.side),
+            shares: Some(held.shares),
+            edge: held.hold_edge,
+            reason: Some(
+                match held.reason {
+                    HoldReason::ResolutionEdge => "resolution_edge",
+                    HoldReason::NoExitLiquidity => "no_exit_liquidity",
+                }
+                .to_string(),
+            ),
+            ..base.clone()
+        });
+    }
+
+    for record in &records {
+        log.record(record);
+    }
+    records
+}
+
+/// Columns of the feature row the quoting loop scores: the spot log return since the
+/// interval's reference price and the fraction of the interval still to run. Models
+/// registered for live quoting must expect these values, in this order.
+pub const QUOTE_FEATURE_COLUMNS: [&str; 2] = ["spot_log_return", "remaining_fraction"];
+
+/// Schema of [`QUOTE_FEATURE_COLUMNS`]. Model artifacts for live scoring are validated
+/// against its fingerprint (see [`crate::load_model_registry`]).
+pub fn quote_feature_schema() -> FeatureSchema {
+    let mut hasher = Sha256::new();
+    hasher.update(format!("quote;version:{FEATURE_SCHEMA_VERSION};columns:"));
+    for column in QUOTE_FEATURE_COLUMNS {
+        hasher.update(column.as_bytes());
+        hasher.update(":f64;");
+    }
+    FeatureSchema {
+        version: FEATURE_SCHEMA_VERSION,
+        fingerprint: hex::encode(hasher.finalize()),
+        columns: QUOTE_FEATURE_COLUMNS
+            .into_iter()
+            .map(|name| FeatureColumn {
+                name: name.to_string(),
+                dtype: FeatureDType::F64,
+            })
+            .collect(),
+    }
+}
+
+/// A discovered market the quoting loop runs on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuoteMarket {
+    pub slug: String,
+    pub coin: Coin,
+    pub duration: Duration,
+    pub start_ts_utc: i64,
+    pub end_ts_utc: i64,
+    /// From discovery; `None` when Gamma did not report it.
+    pub accepting_orders_ts_utc: Option<i64>,
+    pub yes_token_id: String,
+    pub no_token_id: String,
+    /// Orders go through the neg-risk exchange (see [`crate::MarketMeta::neg_risk`]).
+    pub neg_risk: bool,
+    /// Markets sharing collateral with this one count against one group cap (see
+    /// [`crate::PortfolioRiskConfig::max_group_usdc`]).
+    pub neg_risk_group: Option<String>,
+}
+
+impl QuoteMarket {
+    /// Market of a book subscription; `None` for pair markets, which have no single
+    /// spot price to score, and for markets without a NO token.
+    pub fn from_subscription(
+        subscription: &BookSubscription,
+        accepting_orders_ts_utc: Option<i64>,
+    ) -> Option<Self> {
+        let key = &subscription.key;
+        if key.quote.is_some() {
+            return None;
+        }
+        Some(Self {
+            slug: key.slug.clone(),
+            coin: key.coin,
+            duration: key.duration,
+            start_ts_utc: key.start_ts_utc,
+            end_ts_utc: key.duration.interval_end(key.start_ts_utc),
+            accepting_orders_ts_utc,
+            yes_token_id: subscription.yes_token_id.clone(),
+            no_token_id: subscription.no_token_id.clone()?,
+            neg_risk: subscription.neg_risk,
+            neg_risk_group: subscription.neg_risk_group.clone(),
+        })
+    }
+
+    fn window_input(&self) -> QuoteWindowInput {
+        QuoteWindowInput {
+            slug: self.slug.clone(),
+            duration: self.duration,
+            start_ts_utc: self.start_ts_utc,
+            end_ts_utc: self.end_ts_utc,
+            accepting_orders_ts_utc: self.accepting_orders_ts_utc,
+        }
+    }
+
+    fn token_id(&self, side: SizingSide) -> &str {
+        match side {
+            SizingSide::Yes => &self.yes_token_id,
+            SizingSide::No => &self.no_token_id,
+        }
+    }
+}
+
+/// What the quoting loop knows about one market in a cycle.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QuoteMarketState {
+    pub prediction: Option<ModelPrediction>,
+    pub book: Option<BookTop>,
+    pub position: Option<MarketPosition>,
+}
+
+/// The live inputs of the quoting loop.
+#[derive(Clone, Copy)]
+pub struct QuoteSources<'a> {
+    pub prices: &'a dyn PriceSource,
+    pub models: &'a ModelRegistry,
+    pub books: &'a dyn OrderBookSource,
+    pub positions: &'a dyn PositionSource,
+    /// Captures the inputs of every prediction scored; `None` records nothing.
+    pub model_inputs: Option<&'a ModelInputStore>,
+    /// Smooths the model probability before it is quoted on; `None` quotes the raw one.
+    pub smoother: Option<&'a ProbabilitySmoother>,
+}
+
+impl QuoteSources<'_> {
+    /// Gathers `market`'s state: the model scored on [`spot_feature_row`] (smoothed
+    /// when a smoother is set; the raw output is what gets captured), the YES top of
+    /// book, and the wallet position.
+    pub fn state(&self, market: &QuoteMarket, now_ts_utc: i64) -> QuoteMarketState {
+        let features = spot_feature_row(self.prices, market, now_ts_utc);
+        let mut prediction = features
+            .as_ref()
+            .and_then(|row| self.models.predict(market.coin, market.duration, row));
+        if let (Some(store), Some(row), Some(prediction)) =
+            (self.model_inputs, &features, &prediction)
+        {
+            store.record_or_log(&[ModelInputRecord::new(
+                &market.slug,
+                now_ts_utc,
+                ModelInputSource::Quoting,
+                &quote_feature_schema(),
+                row,
+                prediction,
+            )]);
+        }
+        if let (Some(smoother), Some(prediction)) = (self.smoother, &mut prediction) {
+            prediction.probability_yes =
+                smoother.update(&market.slug, prediction.probability_yes, now_ts_utc);
+        }
+        QuoteMarketState {
+            prediction,
+            book: self.books.top_of_book(&market.slug),
+            position: self.positions.position(&market.slug),
+        }
+    }
+}
+
+/// Feature row of [`QUOTE_FEATURE_COLUMNS`]; `None` until both the latest spot price
+/// and the interval's reference price are known.
+pub fn spot_feature_row(
+    prices: &dyn PriceSource,
+    market: &QuoteMarket,
+    now_ts_utc: i64,
+) -> Option<FeatureRow> {
+    interval_feature_row(
+        prices,
+        market.coin,
+        market.start_ts_utc,
+        market.end_ts_utc,
+        now_ts_utc,
+    )
+}
+
+/// [`spot_feature_row`] of `coin`'s interval `[start_ts_utc, end_ts_utc)`.
+pub(crate) fn interval_feature_row(
+    prices: &dyn PriceSource,
+    coin: Coin,
+    start_ts_utc: i64,
+    end_ts_utc: i64,
+    now_ts_utc: i64,
+) -> Option<FeatureRow> {
+    let latest = prices.latest(coin)?;
+    let reference = prices.reference(coin, start_ts_utc)?;
+    if latest.price <= 0.0 || reference.price <= 0.0 {
+        return None;
+    }
+    let length = (end_ts_utc - start_ts_utc).max(1) as f64;
+    let remaining = (end_ts_utc - now_ts_utc).clamp(0, end_ts_utc - start_ts_utc);
+    Some(FeatureRow {
+        ts_ms_utc: latest.ts_ms_utc,
+        values: vec![
+            (latest.price / reference.price).ln(),
+            remaining as f64 / length,
+        ],
+    })
+}
+
+/// An order action planned by the [`QuotingEngine`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum OrderIntent {
+    /// Rest a post-only GTD bid, expiring at the interval end.
+    Place {
+        slug: String,
+        side: SizingSide,
+        order: LimitOrder,
+    },
+    Cancel {
+        slug: String,
+        side: SizingSide,
+        order_id: String,
+    },
+}
+
+impl OrderIntent {
+    pub fn slug(&self) -> &str {
+        match self {
+            Self::Place { slug, .. } | Self::Cancel { slug, .. } => slug,
+        }
+    }
+
+    pub fn side(&self) -> SizingSide {
+        match self {
+            Self::Place { side, .. } | Self::Cancel { side, .. } => *side,
+        }
+    }
+}
+
+/// A bid the engine believes is resting.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RestingQuote {
+    pub order_id: String,
+    pub level: QuoteLevel,
+}
+
+/// Two-sided quoting loop over discovered markets.
+///
+/// Each cycle diffs the target bids of every market against the bids the engine
+/// tracks as resting and emits [`OrderIntent`]s: a cancel for each bid that is no
+/// longer wanted or has moved, then a place for each new level. Markets stop quoting
+/// outside a quoting phase (see [`MarketToggles::plan_quote_window`]), inside the
+/// flattening window, or without a model probability; tracked bids of markets missing
+/// from a cycle are cancelled. Intents are recorded through a [`DecisionLog`] as
+/// `place_quote`/`cancel_quotes` decisions.
+///
+/// With a wallet [`CapitalUsage`] set, bid sizes are scaled by
+/// [`CapitalUsage::quote_scale`] (at a scale of `0` the engine bids nothing, which
+/// releases the collateral of its resting bids), and a cycle's new bids lock no more
+/// than [`CapitalUsage::quote_budget_usdc`] plus what its cancels release; the rest
+/// wait for a later cycle.
+///
+/// With a [`SizingConfig`] set, each bid is also capped at the [`kelly_stake`] for
+/// buying its outcome at the bid price; a bid the model sees no edge in is dropped.
+#[derive(Debug)]
+pub struct QuotingEngine {
+    cfg: StrategyConfig,
+    window: PreMarketQuoteConfig,
+    flatten: FlattenConfig,
+    toggles: MarketToggles,
+    capital: CapitalConfig,
+    capital_usage: Option<CapitalUsage>,
+    sizing: Option<SizingConfig>,
+    /// `routine` of the decisions the engine records.
+    routine: &'static str,
+    resting: HashMap<(String, SizingSide), RestingQuote>,
+    dry_run_orders: u64,
+}
+
+impl QuotingEngine {
+    pub fn new(
+        cfg: StrategyConfig,
+        window: PreMarketQuoteConfig,
+        flatten: FlattenConfig,
+        toggles: MarketToggles,
+    ) -> Self {
+        Self {
+            cfg,
+            window,
+            flatten,
+            toggles,
+            capital: CapitalConfig::default(),
+            capital_usage: None,
+            sizing: None,
+            routine: "two_sided_quotes",
+            resting: HashMap::new(),
+            dry_run_orders: 0,
+        }
+    }
+
+    /// Records decisions under `routine` instead of `two_sided_quotes`.
+    pub fn with_routine(mut self, routine: &'static str) -> Self {
+        self.routine = routine;
+        self
+    }
+
+    /// Throttles quoting by `capital` instead of the defaults.
+    pub fn with_capital(mut self, capital: CapitalConfig) -> Self {
+        self.capital = capital;
+        self
+    }
+
+    /// Caps bid sizes by the Kelly stake under `sizing`, against its
+    /// `per_market_bankroll`.
+    pub fn with_sizing(mut self, sizing: SizingConfig) -> Self {
+        self.sizing = Some(sizing);
+        self
+    }
+
+    /// Latest wallet usage the throttle works from; `None` quotes unthrottled.
+    pub fn set_capital_usage(&mut self, usage: Option<CapitalUsage>) {
+        self.capital_usage = usage;
+    }
+
+    pub fn config(&self) -> &StrategyConfig {
+        &self.cfg
+    }
+
+    /// The bid tracked as resting on `side` of `slug`.
+    pub fn resting(&self, slug: &str, side: SizingSide) -> Option<&RestingQuote> {
+        self.resting.get(&(slug.to_string(), side))
+    }
+
+    /// The bids `market` should rest now.
+    pub fn target_quotes(
+        &self,
+        market: &QuoteMarket,
+        state: &QuoteMarketState,
+        now_ts_utc: i64,
+    ) -> MakerQuotes {
+        let plan = self.toggles.plan_quote_window(
+            market.coin,
+            &market.window_input(),
+            now_ts_utc,
+            &self.window,
+        );
+        let (Some(limits), Some(prediction)) = (plan.limits, &state.prediction) else {
+            return MakerQuotes::default();
+        };
+        if in_flatten_window(now_ts_utc, market.end_ts_utc, &self.flatten)
+            || market.end_ts_utc - now_ts_utc < GTD_MIN_LEAD_SECS
+        {
+            return MakerQuotes::default();
+        }
+        let book = state.book.as_ref();
+        let mut params = self.cfg.params(market.duration);
+        if let Some(usage) = &self.capital_usage {
+            params.order_usdc *= usage.quote_scale(&self.capital);
+        }
+        let quotes = plan_two_sided_quotes(
+            prediction.probability_yes,
+            book.and_then(|top| top.best_bid),
+            book.and_then(|top| top.best_ask),
+            state
+                .position
+                .as_ref()
+                .map_or(0.0, MarketPosition::net_shares),
+            &params,
+            &limits,
+        );
+        match &self.sizing {
+            Some(sizing) => MakerQuotes {
+                yes: kelly_capped(
+                    quotes.yes,
+                    SizingSide::Yes,
+                    prediction.probability_yes,
+                    sizing,
+                ),
+                no: kelly_capped(
+                    quotes.no,
+                    SizingSide::No,
+                    prediction.probability_yes,
+                    sizing,
+                ),
+            },
+            None => quotes,
+        }
+    }
+
+    /// Intents that move the tracked bids to this cycle's targets: all cancels first,
+    /// then all places.
+    pub fn plan_cycle(
+        &self,
+        markets: &[(QuoteMarket, QuoteMarketState)],
+        now_ts_utc: i64,
+    ) -> Vec<OrderIntent> {
+        let mut cancels = Vec::new();
+        let mut places = Vec::new();
+        let mut seen = HashSet::new();
+        let mut budget_usdc = self
+            .capital_usage
+            .as_ref()
+            .and_then(|usage| usage.quote_budget_usdc(&self.capital));
+        for (market, state) in markets {
+            let targets = self.target_quotes(market, state, now_ts_utc);
+            for (side, target) in [(SizingSide::Yes, targets.yes), (SizingSide::No, targets.no)] {
+                seen.insert((market.slug.clone(), side));
+                let resting = self.resting(&market.slug, side);
+                if resting.map(|quote| quote.level) == target {
+                    continue;
+                }
+                if let Some(resting) = resting {
+                    if let Some(budget) = &mut budget_usdc {
+                        *budget += resting.level.price * resting.level.size;
+                    }
+                    cancels.push(OrderIntent::Cancel {
+                        slug: market.slug.clone(),
+                        side,
+                        order_id: resting.order_id.clone(),
+                    });
+                }
+                if let Some(level) = target {
+                    let order = LimitOrder::new(
+                        market.token_id(side),
+                        OrderSide::Buy,
+                        level.price,
+                        level.size,
+                        TimeInForce::Gtd {
+                            expires_ts_utc: market.end_ts_utc,
+                        },
+                    )
+                    .post_only()
+                    .with_neg_risk(market.neg_risk);
+                    places.push(OrderIntent::Place {
+                        slug: market.slug.clone(),
+                        side,
+                        order,
+                    });
+                }
+            }
+        }
+        let mut stale: Vec<_> = self
+            .resting
+            .iter()
+            .filter(|(key, _)| !seen.contains(*key))
+            .map(|((slug, side), quote)| {
+                if let Some(budget) = &mut budget_usdc {
+                    *budget += quote.level.price * quote.level.size;
+                }
+                OrderIntent::Cancel {
+                    slug: slug.clone(),
+                    side: *side,
+                    order_id: quote.order_id.clone(),
+                }
+            })
+            .collect();
+        stale.sort_by(|a, b| a.slug().cmp(b.slug()));
+        stale.extend(cancels);
+        if let Some(mut budget) = budget_usdc {
+            places.retain(|intent| {
+                let OrderIntent::Place { order, .. } = intent else {
+                    return true;
+                };
+                let notional = order.price * order.size;
+                let fits = notional <= budget + 1e-9;
+                if fits {
+                    budget -= notional;
+                }
+                fits
+            });
+        }
+        stale.extend(places);
+        stale
+    }
+
+    /// Updates the tracked bids after an intent was executed; `order_id` is the id a
+    /// placed order rests under.
+    pub fn apply(&mut self, intent: &OrderIntent, order_id: Option<String>) {
+        match intent {
+            OrderIntent::Place { slug, side, order } => {
+                if let Some(order_id) = order_id {
+                    let level = QuoteLevel {
+                        price: order.price,
+                        size: order.size,
+                    };
+                    self.resting
+                        .insert((slug.clone(), *side), RestingQuote { order_id, level });
+                }
+            }
+            OrderIntent::Cancel { slug, side, .. } => {
+                self.resting.remove(&(slug.clone(), *side));
+            }
+        }
+    }
+
+    /// Plans a cycle and executes it on paper: intents are recorded and tracked under
+    /// synthetic `dry-run-<n>` order ids.
+    pub fn run_dry_cycle(
+        &mut self,
+        markets: &[(QuoteMarket, QuoteMarketState)],
+        now_ts_utc: i64,
+        log: &dyn DecisionLog,
+    ) -> Vec<OrderIntent> {
+        let intents = self.plan_cycle(markets, now_ts_utc);
+        for intent in &intents {
+            record_order_intent(intent, markets, now_ts_utc, self.routine, "dry_run", log);
+            let order_id = matches!(intent, OrderIntent::Place { .. }).then(|| {
+                self.dry_run_orders += 1;
+                format!("dry-run-{}", self.dry_run_orders)
+            });
+            self.apply(intent, order_id);
+        }
+        intents
+    }
+
+    /// Plans a cycle and executes it through `client` (the CLOB, or paper fills), or
+    /// runs [`Self::run_dry_cycle`] when the config is dry-run. Placements and cancels
+    /// queue for the client's per-market and global rate limits. A failed intent
+    /// (including one that gave up waiting for a slot) is logged and skipped: a failed
+    /// cancel keeps its bid tracked, so it is retried next cycle, and the replacing
+    /// place on the same side is held back so two bids never rest there. Returns the
+    /// intents that went through.
+    pub async fn run_cycle(
+        &mut self,
+        markets: &[(QuoteMarket, QuoteMarketState)],
+        now_ts_utc: i64,
+        client: &ThrottledTradingClient<impl TradingClient>,
+        log: &dyn DecisionLog,
+    ) -> Vec<OrderIntent> {
+        if self.cfg.dry_run {
+            return self.run_dry_cycle(markets, now_ts_utc, log);
+        }
+        let mut executed = Vec::new();
+        let mut failed_cancels = HashSet::new();
+        for intent in self.plan_cycle(markets, now_ts_utc) {
+            let key = (intent.slug().to_string(), intent.side());
+            if matches!(intent, OrderIntent::Place { .. }) && failed_cancels.contains(&key) {
+                warn!(
+                    component = "strategy",
+                    event = "strategy.place_held",
+                    slug = intent.slug(),
+                    side = intent.side().as_str(),
+                    reason = "cancel_failed"
+                );
+                continue;
+            }
+            let result = match &intent {
+                OrderIntent::Place { order, .. } => client
+                    .place_limit_order(order)
+                    .await
+                    .map(|placed| Some(placed.order_id)),
+                OrderIntent::Cancel { order_id, .. } => client.cancel(order_id).await.map(|_| None),
+            };
+            match result {
+                Ok(order_id) => {
+                    record_order_intent(
+                        &intent,
+                        markets,
+                        now_ts_utc,
+                        self.routine,
+                        client.venue(),
+                        log,
+                    );
+                    self.apply(&intent, order_id);
+                    executed.push(intent);
+                }
+                Err(err) => {
+                    warn!(
+                        component = "strategy",
+                        event = "strategy.intent_failed",
+                        slug = intent.slug(),
+                        side = intent.side().as_str(),
+                        error = %err
+                    );
+                    if matches!(intent, OrderIntent::Cancel { .. }) {
+                        failed_cancels.insert(key);
+                    }
+                }
+            }
+        }
+        executed
+    }
+}
+
+fn record_order_intent(
+    intent: &OrderIntent,
+    markets: &[(QuoteMarket, QuoteMarketState)],
+    now_ts_utc: i64,
+    routine: &str,
+    reason: &str,
+    log: &dyn DecisionLog,
+) {
+    let prediction = markets
+        .iter()
+        .find(|(market, _)| market.slug == intent.slug())
+        .and_then(|(_, state)| state.prediction.as_ref());
+    let mut record = DecisionRecord {
+        ts_utc: now_ts_utc,
+        slug: intent.slug().to_string(),
+        routine: routine.to_string(),
+        action: DecisionAction::CancelQuotes,
+        side: Some(intent.side()),
+        shares: None,
+        price: None,
+        edge: None,
+        reason: Some(reason.to_string()),
+        model_id: prediction.map(|prediction| prediction.model_id.clone()),
+    };
+    if let OrderIntent::Place { side, order, .. } = intent {
+        let fair = prediction.map(|prediction| match side {
+            SizingSide::Yes => prediction.probability_yes,
+            SizingSide::No => 1.0 - prediction.probability_yes,
+        });
+        record.action = DecisionAction::PlaceQuote;
+        record.shares = Some(order.size);
+        record.price = Some(order.price);
+        record.edge = fair.map(|fair| fair - order.price);
+    }
+    log.record(&record);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::orders::OrderRateLimitConfig;
+    use crate::trading::{CancelOutcome, OpenOrder, PlacedOrder, TradingError};
+
+    #[derive(Default)]
+    struct MemoryLog(Mutex<Vec<DecisionRecord>>);
+
+    impl DecisionLog for MemoryLog {
+        fn record(&self, record: &DecisionRecord) {
+            self.0.lock().unwrap().push(record.clone());
+        }
+    }
+
+    fn state(yes_shares: f64, no_shares: f64) -> MarketPositionState {
+        MarketPositionState {
+            slug: "btc-updown-15m-1".to_string(),
+            end_ts_utc: 1_000,
+            yes_shares,
+            no_shares,
+            open_quote_count: 2,
+            probability_yes: Some(0.9),
+            model_id: Some("btc-15m".to_string()),
+            best_bid_yes: Some(0.8),
+            best_ask_yes: Some(0.82),
+            taker_fee_rate: 0.0,
+            fee_exponent: None,
+        }
+    }
+
+    #[test]
+    fn window_is_lead_inclusive_and_end_exclusive() {
+        let cfg = FlattenConfig::default();
+        assert!(!in_flatten_window(969, 1_000, &cfg));
+        assert!(in_flatten_window(970, 1_000, &cfg));
+        assert!(!in_flatten_window(1_000, 1_000, &cfg));
+        assert_eq!(plan_end_of_interval(&state(5.0, 0.0), 900, &cfg), None);
+    }
+
+    #[test]
+    fn default_policy_flattens_even_with_positive_edge() {
+        let plan = plan_end_of_interval(&state(5.0, 3.0), 980, &FlattenConfig::default()).unwrap();
+        assert!(plan.cancel_quotes);
+        assert!(plan.held.is_empty());
+        assert_eq!(plan.flatten.len(), 2);
+        assert_eq!(plan.flatten[0].side, SizingSide::Yes);
+        assert_eq!(plan.flatten[0].limit_price, 0.8);
+        assert_eq!(plan.flatten[1].side, SizingSide::No);
+        assert!((plan.flatten[1].limit_price - 0.18).abs() < 1e-12);
+    }
+
+    #[test]
+    fn explicit_hold_requires_edge_above_threshold() {
+        let cfg = FlattenConfig {
+            hold_into_resolution: true,
+            min_hold_edge: 0.05,
+            ..FlattenConfig::default()
+        };
+        let plan = plan_end_of_interval(&state(5.0, 3.0), 980, &cfg).unwrap();
+        assert_eq!(plan.held.len(), 1);
+        assert_eq!(plan.held[0].side, SizingSide::Yes);
+        assert_eq!(plan.held[0].reason, HoldReason::ResolutionEdge);
+        assert_eq!(plan.flatten.len(), 1);
+        assert_eq!(plan.flatten[0].side, SizingSide::No);
+
+        let mut unknown = state(5.0, 0.0);
+        unknown.probability_yes = None;
+        let plan = plan_end_of_interval(&unknown, 980, &cfg).unwrap();
+        assert_eq!(plan.flatten.len(), 1);
+        assert_eq!(plan.flatten[0].hold_edge, None);
+    }
+
+    #[test]
+    fn plan_is_recorded_in_execution_order() {
+        let mut no_bid = state(5.0, 0.0);
+        no_bid.best_bid_yes = None;
+        let plan = plan_end_of_interval(&no_bid, 990, &FlattenConfig::default()).unwrap();
+        let log = MemoryLog::default();
+        let records = record_end_of_interval_plan(&plan, 990, &log);
+
+        let actions: Vec<_> = records.iter().map(|record| record.action).collect();
+        assert_eq!(
+            actions,
+            [DecisionAction::CancelQuotes, DecisionAction::Hold]
+        );
+        assert_eq!(records[1].reason.as_deref(), Some("no_exit_liquidity"));
+        assert!(records
+            .iter()
+            .all(|record| record.model_id.as_deref() == Some("btc-15m")));
+        assert_eq!(*log.0.lock().unwrap(), records);
+    }
+
+    fn daily(accepting: Option<i64>) -> QuoteWindowInput {
+        QuoteWindowInput {
+            slug: "bitcoin-up-or-down-on-june-1".to_string(),
+            duration: Duration::D1,
+            start_ts_utc: 100_000,
+            end_ts_utc: 186_400,
+            accepting_orders_ts_utc: accepting,
+        }
+    }
+
+    #[test]
+    fn pre_market_quoting_follows_accepting_orders_timestamp() {
+        let cfg = PreMarketQuoteConfig {
+            enabled: true,
+            ..PreMarketQuoteConfig::default()
+        };
+        let input = daily(Some(10_000));
+        let phase = |now| quote_phase(&input, now, &cfg);
+        assert_eq!(phase(9_999), QuotePhase::AwaitingOrders);
+        assert_eq!(phase(10_000), QuotePhase::Warmup);
+        assert_eq!(phase(10_060), QuotePhase::PreMarket);
+        assert_eq!(phase(99_970), QuotePhase::PreStartCutoff);
+        assert_eq!(phase(100_000), QuotePhase::InInterval);
+        assert_eq!(phase(186_400), QuotePhase::Ended);
+
+        assert_eq!(
+            quote_phase(&daily(None), 50_000, &cfg),
+            QuotePhase::AwaitingOrders
+        );
+        let mut five_minute = daily(Some(10_000));
+        five_minute.duration = Duration::M5;
+        assert_eq!(
+            quote_phase(&five_minute, 50_000, &cfg),
+            QuotePhase::AwaitingStart
+        );
+        assert_eq!(
+            quote_phase(&input, 50_000, &PreMarketQuoteConfig::default()),
+            QuotePhase::AwaitingStart
+        );
+
+        let pre = plan_quote_window(&input, 50_000, &cfg);
+        assert_eq!(pre.limits, Some(cfg.pre_market));
+        let live = plan_quote_window(&input, 100_000, &cfg);
+        assert_eq!(live.limits, Some(cfg.in_interval));
+        assert_eq!(plan_quote_window(&input, 99_990, &cfg).limits, None);
+    }
+
+    #[test]
+    fn quote_phase_changes_are_recorded() {
+        let cfg = PreMarketQuoteConfig {
+            enabled: true,
+            ..PreMarketQuoteConfig::default()
+        };
+        let input = daily(Some(10_000));
+        let log = MemoryLog::default();
+
+        let mut previous = None;
+        let mut actions = Vec::new();
+        for now in [10_000, 10_030, 50_000, 60_000, 99_990, 100_000, 186_400] {
+            let plan = plan_quote_window(&input, now, &cfg);
+            if let Some(record) = record_quote_phase_change(previous, &plan, now, &log) {
+                actions.push((record.action, record.reason.unwrap()));
+            }
+            previous = Some(plan.phase);
+        }
+        assert_eq!(
+            actions,
+            [
+                (DecisionAction::EnableQuotes, "pre_market".to_string()),
+                (DecisionAction::CancelQuotes, "pre_start_cutoff".to_string()),
+                (DecisionAction::EnableQuotes, "in_interval".to_string()),
+                (DecisionAction::CancelQuotes, "ended".to_string()),
+            ]
+        );
+        assert_eq!(log.0.lock().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn maker_quotes_bid_below_fair_and_stay_inside_the_book() {
+        let limits = QuoteRiskLimits {
+            max_order_usdc: 10.0,
+            max_position_usdc: 100.0,
+            min_edge: 0.02,
+        };
+        let quotes = plan_maker_quotes(0.57, Some(0.50), Some(0.60), &limits);
+        assert_eq!(
+            quotes.yes,
+            Some(QuoteLevel {
+                price: 0.55,
+                size: 18.18
+            })
+        );
+        assert_eq!(
+            quotes.no,
+            Some(QuoteLevel {
+                price: 0.41,
+                size: 24.39
+            })
+        );
+
+        // A tight book caps both sides one tick inside the opposite quote.
+        let tight = plan_maker_quotes(0.57, Some(0.56), Some(0.53), &limits);
+        assert_eq!(tight.yes.map(|level| level.price), Some(0.52));
+        assert_eq!(tight.no.map(|level| level.price), Some(0.41));
+
+        // No edge left on NO near certainty; nonsense probabilities quote nothing.
+        assert_eq!(plan_maker_quotes(0.995, None, None, &limits).no, None);
+        assert_eq!(
+            plan_maker_quotes(1.5, None, None, &limits),
+            MakerQuotes::default()
+        );
+    }
+
+    #[test]
+    fn strategy_overrides_apply_per_duration() {
+        let base = QuoteParams::default();
+        let overrides = parse_quote_overrides(
+            "5m:half_spread=0.03,order_usdc=5; 1d:max_exposure_usdc=50",
+            base,
+        )
+        .unwrap();
+        let cfg = StrategyConfig {
+            overrides,
+            ..StrategyConfig::default()
+        };
+        let m5 = cfg.params(Duration::M5);
+        assert_eq!((m5.half_spread, m5.order_usdc), (0.03, 5.0));
+        assert_eq!(m5.max_exposure_usdc, base.max_exposure_usdc);
+        assert_eq!(cfg.params(Duration::D1).max_exposure_usdc, 50.0);
+        assert_eq!(cfg.params(Duration::H1), base);
+
+        assert!(parse_quote_overrides("5m:spread=0.03", base).is_err());
+        assert!(parse_quote_overrides("2m:order_usdc=5", base).is_err());
+        assert!(parse_quote_overrides("5m:order_usdc=-1", base).is_err());
+    }
+
+    #[test]
+    fn two_sided_quotes_skew_with_inventory_and_cap_exposure() {
+        let limits = PreMarketQuoteConfig::default().in_interval;
+        let params = QuoteParams::default();
+        let flat = plan_two_sided_quotes(0.6, Some(0.5), Some(0.7), 0.0, &params, &limits);
+        assert_eq!(
+            flat.yes,
+            Some(QuoteLevel {
+                price: 0.58,
+                size: 17.24
+            })
+        );
+        assert_eq!(flat.no.map(|level| level.price), Some(0.38));
+
+        // Long 20 YES shifts fair value down two cents: cheaper YES, richer NO.
+        let long = plan_two_sided_quotes(0.6, Some(0.5), Some(0.7), 20.0, &params, &limits);
+        assert_eq!(long.yes.map(|level| level.price), Some(0.56));
+        assert_eq!(long.no.map(|level| level.price), Some(0.4));
+
+        // Already over the exposure cap on YES: only NO is bid.
+        let capped = QuoteParams {
+            max_exposure_usdc: 10.0,
+            ..params
+        };
+        let quotes = plan_two_sided_quotes(0.6, Some(0.5), Some(0.7), 20.0, &capped, &limits);
+        assert_eq!(quotes.yes, None);
+        assert_eq!(
+            quotes.no,
+            Some(QuoteLevel {
+                price: 0.4,
+                size: 25.0
+            })
+        );
+    }
+
+    #[test]
+    fn dry_run_engine_diffs_targets_against_resting_bids() {
+        let market = QuoteMarket {
+            slug: "btc-updown-15m-900".to_string(),
+            coin: Coin::BTC,
+            duration: Duration::M15,
+            start_ts_utc: 900,
+            end_ts_utc: 1_800,
+            accepting_orders_ts_utc: None,
+            yes_token_id: "1".to_string(),
+            no_token_id: "2".to_string(),
+            neg_risk: false,
+            neg_risk_group: None,
+        };
+        let state = |probability_yes: f64| QuoteMarketState {
+            prediction: Some(ModelPrediction {
+                probability_yes,
+                model_id: "btc-15m".to_string(),
+            }),
+            book: Some(BookTop {
+                ts_ms: 1_000_000,
+                best_bid: Some(0.5),
+                best_ask: Some(0.7),
+                bid_size: 10.0,
+                ask_size: 10.0,
+            }),
+            position: None,
+        };
+        let mut engine = QuotingEngine::new(
+            StrategyConfig::default(),
+            PreMarketQuoteConfig::default(),
+            FlattenConfig::default(),
+            MarketToggles::in_memory(),
+        );
+        let log = MemoryLog::default();
+
+        let placed = engine.run_dry_cycle(&[(market.clone(), state(0.6))], 1_000, &log);
+        assert_eq!(placed.len(), 2);
+        let OrderIntent::Place { order, .. } = &placed[0] else {
+            panic!("expected a place, got {:?}", placed[0]);
+        };
+        assert_eq!(order.token_id, "1");
+        assert!(order.post_only);
+        assert_eq!(
+            order.time_in_force,
+            TimeInForce::Gtd {
+                expires_ts_utc: 1_800
+            }
+        );
+        assert_eq!(
+            engine
+                .resting(&market.slug, SizingSide::No)
+                .unwrap()
+                .order_id,
+            "dry-run-2"
+        );
+
+        // Unchanged targets leave the bids alone; a moved probability requotes both.
+        assert!(engine
+            .run_dry_cycle(&[(market.clone(), state(0.6))], 1_010, &log)
+            .is_empty());
+        let moved = engine.run_dry_cycle(&[(market.clone(), state(0.65))], 1_020, &log);
+        assert_eq!(moved.len(), 4);
+        assert!(matches!(moved[0], OrderIntent::Cancel { .. }));
+        assert!(matches!(moved[3], OrderIntent::Place { .. }));
+
+        // The flattening window and vanished markets pull every bid.
+        assert_eq!(
+            engine
+                .run_dry_cycle(&[(market.clone(), state(0.65))], 1_775, &log)
+                .len(),
+            2
+        );
+        engine.run_dry_cycle(&[(market.clone(), state(0.65))], 1_030, &log);
+        let gone = engine.run_dry_cycle(&[], 1_040, &log);
+        assert_eq!(gone.len(), 2);
+        assert!(engine.resting(&market.slug, SizingSide::Yes).is_none());
+
+        let records = log.0.lock().unwrap();
+        assert_eq!(records[0].action, DecisionAction::PlaceQuote);
+        assert_eq!(records[0].reason.as_deref(), Some("dry_run"));
+        assert!((records[0].edge.unwrap() - 0.02).abs() < 1e-9);
+        assert_eq!(records[0].model_id.as_deref(), Some("btc-15m"));
+        assert_eq!(records.last().unwrap().action, DecisionAction::CancelQuotes);
+    }
+
+    #[test]
+    fn sizing_caps_bids_at_the_kelly_stake() {
+        let market = QuoteMarket {
+            slug: "btc-updown-15m-900".to_string(),
+            coin: Coin::BTC,
+            duration: Duration::M15,
+            start_ts_utc: 900,
+            end_ts_utc: 1_800,
+            accepting_orders_ts_utc: None,
+            yes_token_id: "1".to_string(),
+            no_token_id: "2".to_string(),
+            neg_risk: false,
+            neg_risk_group: None,
+        };
+        let state = QuoteMarketState {
+            prediction: Some(ModelPrediction {
+                probability_yes: 0.6,
+                model_id: "btc-15m".to_string(),
+            }),
+            book: Some(BookTop {
+                ts_ms: 1_000_000,
+                best_bid: Some(0.5),
+                best_ask: Some(0.7),
+                bid_size: 10.0,
+                ask_size: 10.0,
+            }),
+            position: None,
+        };
+        let engine = || {
+            QuotingEngine::new(
+                StrategyConfig::default(),
+                PreMarketQuoteConfig::default(),
+                FlattenConfig::default(),
+                MarketToggles::in_memory(),
+            )
+        };
+        let unsized_quotes = engine().target_quotes(&market, &state, 1_000);
+        let yes = unsized_quotes.yes.unwrap();
+        assert!(yes.price * yes.size > 9.9);
+        assert!(unsized_quotes.no.is_some());
+
+        let sizing = SizingConfig::default();
+        let stake = kelly_stake(
+            &SizingInput {
+                probability_yes: 0.6,
+                price_yes: yes.price,
+                fee_per_share: 0.0,
+                bankroll: sizing.per_market_bankroll,
+            },
+            &sizing,
+        )
+        .unwrap();
+        let quotes = engine()
+            .with_sizing(sizing)
+            .target_quotes(&market, &state, 1_000);
+        let capped = quotes.yes.unwrap();
+        assert_eq!(capped.price, yes.price);
+        assert!(capped.size < yes.size);
+        assert!(capped.price * capped.size <= stake.stake_usdc);
+        // The NO bid's stake falls under `size_threshold`.
+        assert_eq!(quotes.no, None);
+    }
+
+    /// Places rest under `live-<n>`; every cancel fails.
+    #[derive(Default)]
+    struct CancelFailingClient {
+        placed: Mutex<Vec<LimitOrder>>,
+    }
+
+    impl TradingClient for CancelFailingClient {
+        fn venue(&self) -> &'static str {
+            "stub"
+        }
+
+        async fn place_limit_order(&self, order: &LimitOrder) -> Result<PlacedOrder, TradingError> {
+            let mut placed = self.placed.lock().unwrap();
+            placed.push(order.clone());
+            Ok(PlacedOrder {
+                order_id: format!("live-{}", placed.len()),
+                status: "LIVE".to_string(),
+                making_amount: 0.0,
+                taking_amount: 0.0,
+            })
+        }
+
+        async fn cancel(&self, _order_id: &str) -> Result<CancelOutcome, TradingError> {
+            Err(TradingError::Clob("timeout".to_string()))
+        }
+
+        async fn open_orders(
+            &self,
+            _token_id: Option<&str>,
+        ) -> Result<Vec<OpenOrder>, TradingError> {
+            Ok(Vec::new())
+        }
+    }
+
+    #[tokio::test]
+    async fn failed_cancel_keeps_the_old_bid_and_holds_its_replacement() {
+        let market = QuoteMarket {
+            slug: "btc-updown-15m-900".to_string(),
+            coin: Coin::BTC,
+            duration: Duration::M15,
+            start_ts_utc: 900,
+            end_ts_utc: 1_800,
+            accepting_orders_ts_utc: None,
+            yes_token_id: "1".to_string(),
+            no_token_id: "2".to_string(),
+            neg_risk: false,
+            neg_risk_group: None,
+        };
+        let state = |probability_yes: f64| QuoteMarketState {
+            prediction: Some(ModelPrediction {
+                probability_yes,
+                model_id: "btc-15m".to_string(),
+            }),
+            book: Some(BookTop {
+                ts_ms: 1_000_000,
+                best_bid: Some(0.5),
+                best_ask: Some(0.7),
+                bid_size: 10.0,
+                ask_size: 10.0,
+            }),
+            position: None,
+        };
+        let mut engine = QuotingEngine::new(
+            StrategyConfig {
+                dry_run: false,
+                ..StrategyConfig::default()
+            },
+            PreMarketQuoteConfig::default(),
+            FlattenConfig::default(),
+            MarketToggles::in_memory(),
+        );
+        let client = ThrottledTradingClient::new(
+            CancelFailingClient::default(),
+            OrderRateLimitConfig::default(),
+        );
+        let log = MemoryLog::default();
+
+        let placed = engine
+            .run_cycle(&[(market.clone(), state(0.6))], 1_000, &client, &log)
+            .await;
+        assert_eq!(placed.len(), 2);
+
+        // The probability moved, so both bids are requoted, but neither cancel lands.
+        let requoted = engine
+            .run_cycle(&[(market.clone(), state(0.65))], 1_010, &client, &log)
+            .await;
+        assert!(requoted.is_empty());
+        assert_eq!(client.inner().placed.lock().unwrap().len(), 2);
+        for (side, order_id) in [(SizingSide::Yes, "live-1"), (SizingSide::No, "live-2")] {
+            assert_eq!(
+                engine.resting(&market.slug, side).unwrap().order_id,
+                order_id
+            );
+        }
+    }
+
+    #[test]
+    fn capital_usage_shrinks_and_budgets_new_bids() {
+        use crate::capital::CapitalUsage;
+        use crate::positions::OutcomePosition;
+
+        let market = QuoteMarket {
+            slug: "btc-updown-15m-900".to_string(),
+            coin: Coin::BTC,
+            duration: Duration::M15,
+            start_ts_utc: 900,
+            end_ts_utc: 1_800,
+            accepting_orders_ts_utc: None,
+            yes_token_id: "1".to_string(),
+            no_token_id: "2".to_string(),
+            neg_risk: false,
+            neg_risk_group: None,
+        };
+        let state = QuoteMarketState {
+            prediction: Some(ModelPrediction {
+                probability_yes: 0.6,
+                model_id: "btc-15m".to_string(),
+            }),
+            book: Some(BookTop {
+                ts_ms: 1_000_000,
+                best_bid: Some(0.5),
+                best_ask: Some(0.7),
+                bid_size: 10.0,
+                ask_size: 10.0,
+            }),
+            position: None,
+        };
+        let markets = [(market, state)];
+        let mut engine = QuotingEngine::new(
+            StrategyConfig::default(),
+            PreMarketQuoteConfig::default(),
+            FlattenConfig::default(),
+            MarketToggles::in_memory(),
+        );
+        let notionals = |intents: &[OrderIntent]| -> Vec<f64> {
+            intents
+                .iter()
+                .filter_map(|intent| match intent {
+                    OrderIntent::Place { order, .. } => Some(order.price * order.size),
+                    OrderIntent::Cancel { .. } => None,
+                })
+                .collect()
+        };
+        let held = |cost: f64| MarketPosition {
+            yes: OutcomePosition {
+                shares: cost * 2.0,
+                avg_price: 0.5,
+                realized_pnl_usdc: 0.0,
+            },
+            ..MarketPosition::default()
+        };
+
+        // Plenty of headroom: two full 10 USDC bids.
+        engine.set_capital_usage(Some(CapitalUsage::compute(Some(100.0), [], [], 0)));
+        let full = notionals(&engine.plan_cycle(&markets, 1_000));
+        assert_eq!(full.len(), 2);
+        assert!(full.iter().all(|notional| *notional > 9.9));
+
+        // 15 USDC above the floor fits one of them.
+        engine.set_capital_usage(Some(CapitalUsage::compute(Some(25.0), [], [], 0)));
+        assert_eq!(notionals(&engine.plan_cycle(&markets, 1_000)).len(), 1);
+
+        // 80% used: both bids at half size.
+        engine.set_capital_usage(Some(CapitalUsage::compute(Some(20.0), [], [held(80.0)], 0)));
+        let halved = notionals(&engine.plan_cycle(&markets, 1_000));
+        assert_eq!(halved.len(), 2);
+        assert!(halved.iter().all(|notional| *notional < 5.01));
+
+        // At the floor nothing new is bid.
+        engine.set_capital_usage(Some(CapitalUsage::compute(Some(10.0), [], [], 0)));
+        assert!(engine.plan_cycle(&markets, 1_000).is_empty());
+    }
+}
@@ -0,0 +1,365 @@
+//! Coin/duration enablement toggles and `/strategy/enable`.
+//!
+//! Operators can switch a coin/duration pair off at runtime when its markets misbehave.
+//! A disabled pair stops quoting on the next plan ([`QuotePhase::Disabled`]) and its
+//! markets are resolved last in each discovery cycle, so a slow or failing pair cannot
+//! eat the cycle deadline of the rest. Toggles are kept in SQLite so they survive
+//! restarts; pairs without a stored toggle are enabled.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::discovery::ALL_DURATIONS;
+use crate::env_vars::Settings;
+use crate::slug::{parse_coin, Coin, Duration};
+use crate::strategy::{
+    plan_quote_window, PreMarketQuoteConfig, QuotePhase, QuoteWindowInput, QuoteWindowPlan,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarketToggleConfig {
+    /// SQLite file holding the toggles; `None` keeps them in memory only.
+    pub store_path: Option<PathBuf>,
+}
+
+impl Default for MarketToggleConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl MarketToggleConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_STRATEGY_TOGGLE_STORE_PATH` (`off` keeps toggles in memory), falling
+    /// back to the default path when unset.
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            store_path: settings.get("PMM_STRATEGY_TOGGLE_STORE_PATH"),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum MarketToggleError {
+    #[error("sqlite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid stored toggle row: {0}")]
+    InvalidRow(String),
+}
+
+/// Enablement of one coin/duration pair.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MarketToggle {
+    pub coin: Coin,
+    pub duration: Duration,
+    pub enabled: bool,
+    /// When the toggle was last set; `None` for pairs never toggled.
+    pub updated_ts_utc: Option<i64>,
+}
+
+/// Shared toggle state; clones share the same data. Reads never touch the store.
+#[derive(Clone, Default)]
+pub struct MarketToggles {
+    toggles: Arc<RwLock<HashMap<(Coin, Duration), MarketToggle>>>,
+    conn: Option<Arc<Mutex<Connection>>>,
+}
+
+impl MarketToggles {
+    /// Toggles that are not persisted.
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Opens (creating if needed) the SQLite store at `path` and loads its toggles.
+    pub fn open(path: &Path) -> Result<Self, MarketToggleError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "
+            PRAGMA journal_mode=WAL;
+            CREATE TABLE IF NOT EXISTS strategy_toggles (
+                coin TEXT NOT NULL,
+                duration TEXT NOT NULL,
+                enabled INTEGER NOT NULL,
+                updated_ts_utc INTEGER NOT NULL,
+                PRIMARY KEY (coin, duration)
+            );
+            ",
+        )?;
+        let mut toggles = HashMap::new();
+        {
+            let mut stmt = conn
+                .prepare("SELECT coin, duration, enabled, updated_ts_utc FROM strategy_toggles")?;
+            let rows = stmt.query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, bool>(2)?,
+                    row.get::<_, i64>(3)?,
+                ))
+            })?;
+            for row in rows {
+                let (coin, duration, enabled, updated_ts_utc) = row?;
+                let coin = parse_coin(&coin)
+                    .map_err(|err| MarketToggleError::InvalidRow(err.to_string()))?;
+                let duration = Duration::parse(&duration)
+                    .map_err(|err| MarketToggleError::InvalidRow(err.to_string()))?;
+                toggles.insert(
+                    (coin, duration),
+                    MarketToggle {
+                        coin,
+                        duration,
+                        enabled,
+                        updated_ts_utc: Some(updated_ts_utc),
+                    },
+                );
+            }
+        }
+        Ok(Self {
+            toggles: Arc::new(RwLock::new(toggles)),
+            conn: Some(Arc::new(Mutex::new(conn))),
+        })
+    }
+
+    /// Opens the configured store; falls back to in-memory toggles when it cannot be
+    /// opened (logged as `strategy.toggles.unpersisted`).
+    pub fn from_config(cfg: &MarketToggleConfig) -> Self {
+        let Some(path) = &cfg.store_path else {
+            return Self::in_memory();
+        };
+        match Self::open(path) {
+            Ok(toggles) => toggles,
+            Err(err) => {
+                warn!(
+                    component = "strategy",
+                    event = "strategy.toggles.unpersisted",
+                    path = %path.display(),
+                    error = %err
+                );
+                Self::in_memory()
+            }
+        }
+    }
+
+    pub fn is_enabled(&self, coin: Coin, duration: Duration) -> bool {
+        self.read()
+            .get(&(coin, duration))
+            .is_none_or(|toggle| toggle.enabled)
+    }
+
+    /// Sets the toggle of a pair, persisting it before it takes effect. Logs
+    /// `strategy.toggle.changed` when the enablement changes.
+    pub fn set(
+        &self,
+        coin: Coin,
+        duration: Duration,
+        enabled: bool,
+        now_ts_utc: i64,
+    ) -> Result<MarketToggle, MarketToggleError> {
+        if let Some(conn) = &self.conn {
+            conn.lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .execute(
+                    "INSERT OR REPLACE INTO strategy_toggles
+                     (coin, duration, enabled, updated_ts_utc) VALUES (?1, ?2, ?3, ?4)",
+                    params![coin.ticker(), duration.as_label(), enabled, now_ts_utc],
+                )?;
+        }
+        let toggle = MarketToggle {
+            coin,
+            duration,
+            enabled,
+            updated_ts_utc: Some(now_ts_utc),
+        };
+        let previous = self.write().insert((coin, duration), toggle);
+        if previous.is_none_or(|previous| previous.enabled != enabled) {
+            info!(
+                component = "strategy",
+                event = "strategy.toggle.changed",
+                coin = coin.ticker(),
+                duration = duration.as_label(),
+                enabled
+            );
+        }
+        Ok(toggle)
+    }
+
+    /// Every coin/duration pair, in universe order.
+    pub fn list(&self) -> Vec<MarketToggle> {
+        let toggles = self.read();
+        Coin::all()
+            .into_iter()
+            .flat_map(|coin| ALL_DURATIONS.iter().map(move |duration| (coin, *duration)))
+            .map(|(coin, duration)| {
+                toggles
+                    .get(&(coin, duration))
+                    .copied()
+                    .unwrap_or(MarketToggle {
+                        coin,
+                        duration,
+                        enabled: true,
+                        updated_ts_utc: None,
+                    })
+            })
+            .collect()
+    }
+
+    /// [`plan_quote_window`], except that a disabled pair plans [`QuotePhase::Disabled`]
+    /// without limits.
+    pub fn plan_quote_window(
+        &self,
+        coin: Coin,
+        input: &QuoteWindowInput,
+        now_ts_utc: i64,
+        cfg: &PreMarketQuoteConfig,
+    ) -> QuoteWindowPlan {
+        if self.is_enabled(coin, input.duration) {
+            return plan_quote_window(input, now_ts_utc, cfg);
+        }
+        QuoteWindowPlan {
+            slug: input.slug.clone(),
+            phase: QuotePhase::Disabled,
+            limits: None,
+        }
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, HashMap<(Coin, Duration), MarketToggle>> {
+        self.toggles
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, HashMap<(Coin, Duration), MarketToggle>> {
+        self.toggles
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl std::fmt::Debug for MarketToggles {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MarketToggles")
+            .field("toggles", &*self.read())
+            .field("persisted", &self.conn.is_some())
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct StrategyEnableQuery {
+    pub coin: Option<String>,
+    pub duration: Option<String>,
+    pub enabled: Option<bool>,
+}
+
+/// Router serving `GET /strategy/enable` (every pair) and
+/// `POST /strategy/enable?coin=BTC&duration=5m&enabled=false`.
+pub fn strategy_toggle_router(toggles: MarketToggles) -> Router {
+    Router::new()
+        .route("/strategy/enable", get(get_strategy_toggles))
+        .route("/strategy/enable", post(post_strategy_toggle))
+        .with_state(toggles)
+}
+
+async fn get_strategy_toggles(State(toggles): State<MarketToggles>) -> Json<Vec<MarketToggle>> {
+    Json(toggles.list())
+}
+
+async fn post_strategy_toggle(
+    State(toggles): State<MarketToggles>,
+    Query(query): Query<StrategyEnableQuery>,
+) -> Response {
+    let (Some(coin), Some(duration), Some(enabled)) = (
+        query.coin.as_deref(),
+        query.duration.as_deref(),
+        query.enabled,
+    ) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            "coin, duration, and enabled are required",
+        )
+            .into_response();
+    };
+    let parsed = parse_coin(&coin.trim().to_ascii_uppercase())
+        .and_then(|coin| Ok((coin, Duration::parse(duration.trim())?)));
+    let (coin, duration) = match parsed {
+        Ok(pair) => pair,
+        Err(err) => return (StatusCode::BAD_REQUEST, err.to_string()).into_response(),
+    };
+    match toggles.set(
+        coin,
+        duration,
+        enabled,
+        crate::faults::now_utc().timestamp(),
+    ) {
+        Ok(toggle) => Json(toggle).into_response(),
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toggles_survive_reopen_and_disable_quoting() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("strategy_toggles.sqlite");
+
+        let toggles = MarketToggles::open(&path).unwrap();
+        assert!(toggles.is_enabled(Coin::BTC, Duration::M5));
+        toggles.set(Coin::BTC, Duration::M5, false, 1_000).unwrap();
+        toggles.set(Coin::ETH, Duration::H1, false, 1_010).unwrap();
+        toggles.set(Coin::ETH, Duration::H1, true, 1_020).unwrap();
+        drop(toggles);
+
+        let toggles = MarketToggles::open(&path).unwrap();
+        assert!(!toggles.is_enabled(Coin::BTC, Duration::M5));
+        assert!(toggles.is_enabled(Coin::BTC, Duration::M15));
+        assert!(toggles.is_enabled(Coin::ETH, Duration::H1));
+        let list = toggles.list();
+        assert_eq!(list.len(), 20);
+        assert_eq!(
+            list[0],
+            MarketToggle {
+                coin: Coin::BTC,
+                duration: Duration::M5,
+                enabled: false,
+                updated_ts_utc: Some(1_000),
+            }
+        );
+
+        let input = QuoteWindowInput {
+            slug: "btc-updown-5m-100".to_string(),
+            duration: Duration::M5,
+            start_ts_utc: 100,
+            end_ts_utc: 400,
+            accepting_orders_ts_utc: None,
+        };
+        let cfg = PreMarketQuoteConfig::default();
+        let plan = toggles.plan_quote_window(Coin::BTC, &input, 200, &cfg);
+        assert_eq!(plan.phase, QuotePhase::Disabled);
+        assert_eq!(plan.limits, None);
+        let plan = toggles.plan_quote_window(Coin::SOL, &input, 200, &cfg);
+        assert_eq!(plan.phase, QuotePhase::InInterval);
+    }
+}
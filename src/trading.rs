@@ -0,0 +1,768 @@
+//! CLOB order placement.
+//!
+//! [`OrderClient`] signs limit orders for one outcome token of a market with the
+//! trading wallet (EIP-712, through the Polymarket SDK), submits them, cancels them,
+//! and lists the wallet's open orders and fills. Requests authenticate with the CLOB API key,
+//! secret, and passphrase from `PMM_CLOB_API_*`; without them the client derives the
+//! wallet's API key when it connects.
+//!
+//! Orders are checked by [`LimitOrder::validate`] before anything is signed, so a
+//! malformed quote never reaches the venue. Tick size and fee rate are looked up per
+//! token by the SDK; a price finer than the market's tick is rejected there.
+//!
+//! Strategy code places orders through the [`TradingClient`] trait, which the live
+//! client and [`crate::PaperOrderClient`] both implement.
+//!
+//! The client needs the `trading` feature; the order and config types and the trait do
+//! not.
+
+use std::collections::BTreeMap;
+use std::future::Future;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::env_vars::{EnvValue, Settings};
+use crate::fill_model::OrderSide;
+
+pub const DEFAULT_CLOB_URL: &str = "https://clob.polymarket.com";
+
+/// GTD orders must expire at least this long after they are submitted; the CLOB
+/// rejects shorter expirations.
+pub const GTD_MIN_LEAD_SECS: i64 = 60;
+
+/// CLOB API credentials (L2 auth). Debug output redacts the secret and passphrase.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ClobCredentials {
+    pub api_key: String,
+    pub secret: String,
+    pub passphrase: String,
+}
+
+impl std::fmt::Debug for ClobCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClobCredentials")
+            .field("api_key", &self.api_key)
+            .field("secret", &"<redacted>")
+            .field("passphrase", &"<redacted>")
+            .finish()
+    }
+}
+
+/// How the trading wallet holds funds: a plain key (`eoa`), or a Polymarket proxy or
+/// Gnosis Safe wallet funded at `TradingConfig::funder`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WalletKind {
+    #[default]
+    Eoa,
+    Proxy,
+    GnosisSafe,
+}
+
+impl WalletKind {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "eoa" => Some(Self::Eoa),
+            "proxy" => Some(Self::Proxy),
+            "gnosis_safe" => Some(Self::GnosisSafe),
+            _ => None,
+        }
+    }
+}
+
+impl EnvValue for WalletKind {
+    fn parse_env(raw: &str) -> Option<Self> {
+        Self::parse(raw)
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct TradingConfig {
+    pub clob_url: String,
+    /// Trading wallet key, 32-byte hex; required to connect.
+    pub private_key: Option<String>,
+    /// Derived from the wallet key at connect time when unset.
+    pub credentials: Option<ClobCredentials>,
+    pub wallet_kind: WalletKind,
+    /// Proxy/Safe address holding the funds; derived from the key when unset. Ignored
+    /// for [`WalletKind::Eoa`].
+    pub funder: Option<String>,
+}
+
+impl std::fmt::Debug for TradingConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TradingConfig")
+            .field("clob_url", &self.clob_url)
+            .field(
+                "private_key",
+                &self.private_key.as_ref().map(|_| "<redacted>"),
+            )
+            .field("credentials", &self.credentials)
+            .field("wallet_kind", &self.wallet_kind)
+            .field("funder", &self.funder)
+            .finish()
+    }
+}
+
+impl Default for TradingConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl TradingConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_CLOB_URL`, `PMM_WALLET_PRIVATE_KEY`, `PMM_CLOB_API_KEY`,
+    /// `PMM_CLOB_API_SECRET`, `PMM_CLOB_API_PASSPHRASE`, `PMM_CLOB_WALLET_KIND`
+    /// (`eoa`, `proxy`, `gnosis_safe`), and `PMM_WALLET_ADDRESS` (the funder of proxy
+    /// and Safe wallets). Credentials count only when all three are set; `pmm preflight`
+    /// flags partial sets.
+    pub fn from_settings(settings: &Settings) -> Self {
+        let secret = |name: &str| settings.parsed(name, String::parse_env);
+        let credentials = match (
+            secret("PMM_CLOB_API_KEY"),
+            secret("PMM_CLOB_API_SECRET"),
+            secret("PMM_CLOB_API_PASSPHRASE"),
+        ) {
+            (Some(api_key), Some(secret), Some(passphrase)) => Some(ClobCredentials {
+                api_key,
+                secret,
+                passphrase,
+            }),
+            _ => None,
+        };
+        Self {
+            clob_url: settings.get("PMM_CLOB_URL"),
+            private_key: secret("PMM_WALLET_PRIVATE_KEY"),
+            credentials,
+            wallet_kind: settings.get("PMM_CLOB_WALLET_KIND"),
+            funder: secret("PMM_WALLET_ADDRESS"),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum TradingError {
+    #[error("PMM_WALLET_PRIVATE_KEY not set")]
+    MissingPrivateKey,
+    #[error("invalid trading config: {0}")]
+    InvalidConfig(String),
+    #[error("invalid order: {0}")]
+    InvalidOrder(String),
+    #[error("order rejected: {0}")]
+    Rejected(String),
+    #[error("CLOB error: {0}")]
+    Clob(String),
+    #[error("order rate limited: {0}")]
+    RateLimited(String),
+}
+
+/// How long an order may rest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TimeInForce {
+    /// Good 'til cancelled.
+    Gtc,
+    /// Good 'til date: rests until `expires_ts_utc` unless filled or cancelled.
+    Gtd { expires_ts_utc: i64 },
+    /// Fill or kill: fills in full immediately or not at all.
+    Fok,
+}
+
+/// A limit order for one outcome token. Price is per share, in USDC; size is shares.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LimitOrder {
+    /// CLOB token id of the outcome, as in `MarketMeta::clob_token_ids`.
+    pub token_id: String,
+    pub side: OrderSide,
+    pub price: f64,
+    pub size: f64,
+    pub time_in_force: TimeInForce,
+    /// Rejected instead of taking liquidity; GTC/GTD only.
+    pub post_only: bool,
+    /// Whether the token belongs to a neg-risk market, when discovery knows. Orders of
+    /// neg-risk markets are signed for the neg-risk exchange; `None` leaves the lookup
+    /// to the CLOB.
+    #[serde(default)]
+    pub neg_risk: Option<bool>,
+}
+
+impl LimitOrder {
+    pub fn new(
+        token_id: impl Into<String>,
+        side: OrderSide,
+        price: f64,
+        size: f64,
+        time_in_force: TimeInForce,
+    ) -> Self {
+        Self {
+            token_id: token_id.into(),
+            side,
+            price,
+            size,
+            time_in_force,
+            post_only: false,
+            neg_risk: None,
+        }
+    }
+
+    pub fn post_only(mut self) -> Self {
+        self.post_only = true;
+        self
+    }
+
+    pub fn with_neg_risk(mut self, neg_risk: bool) -> Self {
+        self.neg_risk = Some(neg_risk);
+        self
+    }
+
+    /// Checks what can be checked without the market: a numeric token id, a price
+    /// strictly between 0 and 1, a positive size, a GTD expiry at least
+    /// [`GTD_MIN_LEAD_SECS`] after `now_ts_utc`, and no post-only FOK.
+    pub fn validate(&self, now_ts_utc: i64) -> Result<(), TradingError> {
+        let invalid = |reason: String| Err(TradingError::InvalidOrder(reason));
+        if self.token_id.is_empty() || !self.token_id.bytes().all(|byte| byte.is_ascii_digit()) {
+            return invalid(format!("token id {:?} is not numeric", self.token_id));
+        }
+        if !(self.price.is_finite() && self.price > 0.0 && self.price < 1.0) {
+            return invalid(format!("price {} outside (0, 1)", self.price));
+        }
+        if !(self.size.is_finite() && self.size > 0.0) {
+            return invalid(format!("size {} is not positive", self.size));
+        }
+        let earliest_expiry = now_ts_utc + GTD_MIN_LEAD_SECS;
+        match self.time_in_force {
+            TimeInForce::Gtd { expires_ts_utc } if expires_ts_utc < earliest_expiry => invalid(
+                format!("GTD expiry {expires_ts_utc} is before {earliest_expiry}"),
+            ),
+            TimeInForce::Fok if self.post_only => invalid("FOK orders cannot be post-only".into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The CLOB's answer to a submitted order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlacedOrder {
+    pub order_id: String,
+    /// `LIVE` (resting), `MATCHED`, `DELAYED`, or `UNMATCHED`.
+    pub status: String,
+    /// What we gave and received on immediate fills: USDC and shares for a buy,
+    /// shares and USDC for a sell.
+    pub making_amount: f64,
+    pub taking_amount: f64,
+}
+
+/// One of the wallet's orders still on the book.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpenOrder {
+    pub order_id: String,
+    pub token_id: String,
+    /// Market condition id.
+    pub market: String,
+    /// Outcome name (`Up`, `Down`).
+    pub outcome: String,
+    pub side: OrderSide,
+    pub price: f64,
+    pub original_size: f64,
+    pub size_matched: f64,
+    pub status: String,
+    pub created_ts_utc: i64,
+    /// Set for GTD orders.
+    pub expires_ts_utc: Option<i64>,
+}
+
+impl OpenOrder {
+    pub fn remaining(&self) -> f64 {
+        (self.original_size - self.size_matched).max(0.0)
+    }
+}
+
+/// Result of a cancel request: the ids the CLOB cancelled and, per id, why others
+/// were not (already filled, unknown, ...).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CancelOutcome {
+    pub cancelled: Vec<String>,
+    pub not_cancelled: BTreeMap<String, String>,
+}
+
+/// Order entry as the strategy sees it: the CLOB for [`OrderClient`], simulated fills
+/// for [`crate::PaperOrderClient`].
+pub trait TradingClient: Send + Sync {
+    /// Short venue name recorded with the decisions executed through it.
+    fn venue(&self) -> &'static str;
+
+    fn place_limit_order(
+        &self,
+        order: &LimitOrder,
+    ) -> impl Future<Output = Result<PlacedOrder, TradingError>> + Send;
+
+    /// Requests a cancel of `order_id`.
+    fn cancel(
+        &self,
+        order_id: &str,
+    ) -> impl Future<Output = Result<CancelOutcome, TradingError>> + Send;
+
+    /// Open orders; only those for `token_id` when given.
+    fn open_orders(
+        &self,
+        token_id: Option<&str>,
+    ) -> impl Future<Output = Result<Vec<OpenOrder>, TradingError>> + Send;
+}
+
+#[cfg(feature = "trading")]
+pub use client::OrderClient;
+
+#[cfg(feature = "trading")]
+mod client {
+    use std::str::FromStr;
+
+    use alloy_signer_local::PrivateKeySigner;
+    use polymarket_client_sdk::auth::state::Authenticated;
+    use polymarket_client_sdk::auth::{ApiKey, Credentials, Normal, Signer, Uuid};
+    use polymarket_client_sdk::clob::types::request::{
+        BalanceAllowanceRequest, OrdersRequest, TradesRequest,
+    };
+    use polymarket_client_sdk::clob::types::response::{
+        CancelOrdersResponse, OpenOrderResponse, TradeResponse,
+    };
+    use polymarket_client_sdk::clob::types::{
+        OrderType, Side, SignatureType, TradeStatusType, TraderSide,
+    };
+    use polymarket_client_sdk::clob::{Client, Config};
+    use polymarket_client_sdk::types::{Address, Decimal, U256};
+    use polymarket_client_sdk::POLYGON;
+    use tracing::{info, warn};
+
+    use super::{
+        CancelOutcome, LimitOrder, OpenOrder, PlacedOrder, TimeInForce, TradingClient,
+        TradingConfig, TradingError, WalletKind,
+    };
+    use crate::fill_model::OrderSide;
+    use crate::positions::PositionFill;
+
+    /// Marks the last page of a CLOB listing.
+    const TERMINAL_CURSOR: &str = "LTE=";
+
+    /// The CLOB reports USDC balances in the token's 6-decimal base units.
+    const USDC_BASE_UNITS: f64 = 1_000_000.0;
+
+    /// Authenticated CLOB trading client for one wallet.
+    pub struct OrderClient {
+        client: Client<Authenticated<Normal>>,
+        signer: PrivateKeySigner,
+    }
+
+    impl OrderClient {
+        /// Authenticates against `cfg.clob_url`, deriving API credentials from the
+        /// wallet key when `cfg.credentials` is unset.
+        pub async fn connect(cfg: &TradingConfig) -> Result<Self, TradingError> {
+            let key = cfg
+                .private_key
+                .as_deref()
+                .ok_or(TradingError::MissingPrivateKey)?;
+            let signer = PrivateKeySigner::from_str(key.trim())
+                .map_err(|_| {
+                    TradingError::InvalidConfig("PMM_WALLET_PRIVATE_KEY is not a valid key".into())
+                })?
+                .with_chain_id(Some(POLYGON));
+
+            let mut builder = Client::new(&cfg.clob_url, Config::default())
+                .map_err(clob_err)?
+                .authentication_builder(&signer);
+            if let Some(credentials) = &cfg.credentials {
+                let api_key = Uuid::parse_str(&credentials.api_key).map_err(|_| {
+                    TradingError::InvalidConfig("PMM_CLOB_API_KEY is not a UUID".into())
+                })?;
+                builder = builder.credentials(Credentials::new(
+                    api_key,
+                    credentials.secret.clone(),
+                    credentials.passphrase.clone(),
+                ));
+            }
+            match cfg.wallet_kind {
+                WalletKind::Eoa => {}
+                kind => {
+                    builder = builder.signature_type(match kind {
+                        WalletKind::Proxy => SignatureType::Proxy,
+                        _ => SignatureType::GnosisSafe,
+                    });
+                    if let Some(funder) = &cfg.funder {
+                        builder = builder.funder(Address::from_str(funder).map_err(|_| {
+                            TradingError::InvalidConfig(
+                                "PMM_WALLET_ADDRESS is not an address".into(),
+                            )
+                        })?);
+                    }
+                }
+            }
+            let client = builder.authenticate().await.map_err(clob_err)?;
+            info!(
+                component = "trading",
+                event = "trading.connect",
+                address = %client.address(),
+                derived_credentials = cfg.credentials.is_none()
+            );
+            Ok(Self { client, signer })
+        }
+
+        /// Address that signs orders.
+        pub fn address(&self) -> String {
+            self.client.address().to_string()
+        }
+
+        /// Validates, signs, and submits `order`. An order the CLOB does not accept is
+        /// [`TradingError::Rejected`].
+        pub async fn place_limit_order(
+            &self,
+            order: &LimitOrder,
+        ) -> Result<PlacedOrder, TradingError> {
+            order.validate(crate::faults::now_utc().timestamp())?;
+            let token_id = U256::from_str(&order.token_id)
+                .map_err(|_| TradingError::InvalidOrder(format!("token id {}", order.token_id)))?;
+            // The exchange contract the order is signed for depends on the flag; a known
+            // one saves the CLOB lookup.
+            if let Some(neg_risk) = order.neg_risk {
+                self.client.set_neg_risk(token_id, neg_risk);
+            }
+            let mut builder = self
+                .client
+                .limit_order()
+                .token_id(token_id)
+                .side(match order.side {
+                    OrderSide::Buy => Side::Buy,
+                    OrderSide::Sell => Side::Sell,
+                })
+                .price(to_decimal(order.price)?)
+                .size(to_decimal(order.size)?)
+                .post_only(order.post_only);
+            builder = match order.time_in_force {
+                TimeInForce::Gtc => builder.order_type(OrderType::GTC),
+                TimeInForce::Fok => builder.order_type(OrderType::FOK),
+                TimeInForce::Gtd { expires_ts_utc } => {
+                    builder.order_type(OrderType::GTD).expiration(
+                        chrono::DateTime::from_timestamp(expires_ts_utc, 0).ok_or_else(|| {
+                            TradingError::InvalidOrder(format!("expiry {expires_ts_utc}"))
+                        })?,
+                    )
+                }
+            };
+            let signable = builder.build().await.map_err(order_err)?;
+            let signed = self
+                .client
+                .sign(&self.signer, signable)
+                .await
+                .map_err(clob_err)?;
+            let response = self.client.post_order(signed).await.map_err(clob_err)?;
+            if !response.success {
+                let reason = response
+                    .error_msg
+                    .filter(|msg| !msg.is_empty())
+                    .unwrap_or_else(|| response.status.to_string());
+                warn!(
+                    component = "trading",
+                    event = "trading.order.rejected",
+                    token_id = %order.token_id,
+                    reason = %reason
+                );
+                return Err(TradingError::Rejected(reason));
+            }
+            info!(
+                component = "trading",
+                event = "trading.order.placed",
+                order_id = %response.order_id,
+                token_id = %order.token_id,
+                status = %response.status
+            );
+            Ok(PlacedOrder {
+                order_id: response.order_id,
+                status: response.status.to_string(),
+                making_amount: to_f64(response.making_amount),
+                taking_amount: to_f64(response.taking_amount),
+            })
+        }
+
+        /// Requests a cancel of `order_id`.
+        pub async fn cancel(&self, order_id: &str) -> Result<CancelOutcome, TradingError> {
+            let response = self.client.cancel_order(order_id).await.map_err(clob_err)?;
+            Ok(cancel_outcome(response))
+        }
+
+        /// Requests a cancel of every open order of the wallet.
+        pub async fn cancel_all(&self) -> Result<CancelOutcome, TradingError> {
+            let response = self.client.cancel_all_orders().await.map_err(clob_err)?;
+            Ok(cancel_outcome(response))
+        }
+
+        /// The wallet's open orders, across all pages; only those for `token_id` when
+        /// given.
+        pub async fn open_orders(
+            &self,
+            token_id: Option<&str>,
+        ) -> Result<Vec<OpenOrder>, TradingError> {
+            let asset_id = token_id
+                .map(|id| {
+                    U256::from_str(id)
+                        .map_err(|_| TradingError::InvalidOrder(format!("token id {id}")))
+                })
+                .transpose()?;
+            let request = OrdersRequest::builder().maybe_asset_id(asset_id).build();
+            let mut orders = Vec::new();
+            let mut cursor = None;
+            loop {
+                let page = self
+                    .client
+                    .orders(&request, cursor)
+                    .await
+                    .map_err(clob_err)?;
+                orders.extend(page.data.into_iter().map(open_order));
+                if page.next_cursor.is_empty() || page.next_cursor == TERMINAL_CURSOR {
+                    return Ok(orders);
+                }
+                cursor = Some(page.next_cursor);
+            }
+        }
+
+        /// The wallet's USDC collateral balance.
+        pub async fn usdc_balance(&self) -> Result<f64, TradingError> {
+            let response = self
+                .client
+                .balance_allowance(BalanceAllowanceRequest::default())
+                .await
+                .map_err(clob_err)?;
+            Ok(to_f64(response.balance) / USDC_BASE_UNITS)
+        }
+
+        /// Our fills in trades matched at or after `after_ts_utc` (all trades when
+        /// `None`), across all pages. As taker the trade itself is our fill; as maker,
+        /// each of our orders it matched is one.
+        pub async fn fills(
+            &self,
+            after_ts_utc: Option<i64>,
+        ) -> Result<Vec<PositionFill>, TradingError> {
+            let request = TradesRequest::builder().maybe_after(after_ts_utc).build();
+            let owner = self.client.credentials().key();
+            let mut fills = Vec::new();
+            let mut cursor = None;
+            loop {
+                let page = self
+                    .client
+                    .trades(&request, cursor)
+                    .await
+                    .map_err(clob_err)?;
+                for trade in page.data {
+                    fills.extend(trade_fills(trade, owner));
+                }
+                if page.next_cursor.is_empty() || page.next_cursor == TERMINAL_CURSOR {
+                    return Ok(fills);
+                }
+                cursor = Some(page.next_cursor);
+            }
+        }
+    }
+
+    impl TradingClient for OrderClient {
+        fn venue(&self) -> &'static str {
+            "clob"
+        }
+
+        async fn place_limit_order(&self, order: &LimitOrder) -> Result<PlacedOrder, TradingError> {
+            OrderClient::place_limit_order(self, order).await
+        }
+
+        async fn cancel(&self, order_id: &str) -> Result<CancelOutcome, TradingError> {
+            OrderClient::cancel(self, order_id).await
+        }
+
+        async fn open_orders(
+            &self,
+            token_id: Option<&str>,
+        ) -> Result<Vec<OpenOrder>, TradingError> {
+            OrderClient::open_orders(self, token_id).await
+        }
+    }
+
+    fn trade_fills(trade: TradeResponse, owner: ApiKey) -> Vec<PositionFill> {
+        let failed = trade.status == TradeStatusType::Failed;
+        let ts_utc = trade.match_time.timestamp();
+        let fill = |fill_id: String, token_id: U256, side: Side, price, size, fee_rate_bps| {
+            let (price, size) = (to_f64(price), to_f64(size));
+            PositionFill {
+                fill_id,
+                token_id: token_id.to_string(),
+                side: order_side(side),
+                price,
+                size,
+                fee_usdc: fee_usdc(to_f64(fee_rate_bps), price, size),
+                ts_utc,
+                failed,
+            }
+        };
+        if trade.trader_side == TraderSide::Taker {
+            return vec![fill(
+                trade.id,
+                trade.asset_id,
+                trade.side,
+                trade.price,
+                trade.size,
+                trade.fee_rate_bps,
+            )];
+        }
+        trade
+            .maker_orders
+            .into_iter()
+            .filter(|maker| maker.owner == owner)
+            .map(|maker| {
+                fill(
+                    format!("{}:{}", trade.id, maker.order_id),
+                    maker.asset_id,
+                    maker.side,
+                    maker.price,
+                    maker.matched_amount,
+                    maker.fee_rate_bps,
+                )
+            })
+            .collect()
+    }
+
+    /// The CLOB's fee: the base rate on the cheaper side of the price, per share.
+    fn fee_usdc(fee_rate_bps: f64, price: f64, size: f64) -> f64 {
+        fee_rate_bps / 10_000.0 * price.min(1.0 - price) * size
+    }
+
+    fn order_side(side: Side) -> OrderSide {
+        match side {
+            Side::Sell => OrderSide::Sell,
+            _ => OrderSide::Buy,
+        }
+    }
+
+    fn open_order(order: OpenOrderResponse) -> OpenOrder {
+        let expires_ts_utc = order.expiration.timestamp();
+        OpenOrder {
+            order_id: order.id,
+            token_id: order.asset_id.to_string(),
+            market: order.market.to_string(),
+            outcome: order.outcome,
+            side: order_side(order.side),
+            price: to_f64(order.price),
+            original_size: to_f64(order.original_size),
+            size_matched: to_f64(order.size_matched),
+            status: order.status.to_string(),
+            created_ts_utc: order.created_at.timestamp(),
+            expires_ts_utc: (expires_ts_utc > 0).then_some(expires_ts_utc),
+        }
+    }
+
+    fn cancel_outcome(response: CancelOrdersResponse) -> CancelOutcome {
+        CancelOutcome {
+            cancelled: response.canceled,
+            not_cancelled: response.not_canceled.into_iter().collect(),
+        }
+    }
+
+    /// Shortest decimal that round-trips `value`, so `0.55` is sent as `0.55`.
+    fn to_decimal(value: f64) -> Result<Decimal, TradingError> {
+        Decimal::from_str(&value.to_string())
+            .map_err(|_| TradingError::InvalidOrder(format!("{value} is not a decimal")))
+    }
+
+    fn to_f64(value: Decimal) -> f64 {
+        value.to_string().parse().unwrap_or(0.0)
+    }
+
+    fn clob_err(err: polymarket_client_sdk::error::Error) -> TradingError {
+        TradingError::Clob(err.to_string())
+    }
+
+    /// The SDK validates tick size and lot size while building; those failures are the
+    /// order's fault, anything else (lookups) the venue's.
+    fn order_err(err: polymarket_client_sdk::error::Error) -> TradingError {
+        use polymarket_client_sdk::error::Kind;
+        match err.kind() {
+            Kind::Validation => TradingError::InvalidOrder(err.to_string()),
+            _ => clob_err(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_735_689_600;
+
+    fn order(time_in_force: TimeInForce) -> LimitOrder {
+        LimitOrder::new("1234", OrderSide::Buy, 0.55, 10.0, time_in_force)
+    }
+
+    #[test]
+    fn validation_rejects_bad_prices_sizes_and_expiries() {
+        assert!(order(TimeInForce::Gtc).validate(NOW).is_ok());
+        assert!(order(TimeInForce::Gtc).post_only().validate(NOW).is_ok());
+        assert!(order(TimeInForce::Fok).validate(NOW).is_ok());
+        assert!(order(TimeInForce::Fok).post_only().validate(NOW).is_err());
+
+        let gtd = |lead| {
+            order(TimeInForce::Gtd {
+                expires_ts_utc: NOW + lead,
+            })
+            .validate(NOW)
+        };
+        assert!(gtd(GTD_MIN_LEAD_SECS).is_ok());
+        assert!(gtd(GTD_MIN_LEAD_SECS - 1).is_err());
+
+        for price in [0.0, 1.0, -0.1, f64::NAN] {
+            let bad = LimitOrder {
+                price,
+                ..order(TimeInForce::Gtc)
+            };
+            assert!(bad.validate(NOW).is_err(), "price {price}");
+        }
+        for size in [0.0, -1.0, f64::INFINITY] {
+            let bad = LimitOrder {
+                size,
+                ..order(TimeInForce::Gtc)
+            };
+            assert!(bad.validate(NOW).is_err(), "size {size}");
+        }
+        for token_id in ["", "0xabc", "12 3"] {
+            let bad = LimitOrder {
+                token_id: token_id.to_string(),
+                ..order(TimeInForce::Gtc)
+            };
+            assert!(matches!(
+                bad.validate(NOW),
+                Err(TradingError::InvalidOrder(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn debug_output_redacts_secrets() {
+        let cfg = TradingConfig {
+            private_key: Some("deadbeef".to_string()),
+            credentials: Some(ClobCredentials {
+                api_key: "key-1".to_string(),
+                secret: "s3cret".to_string(),
+                passphrase: "pass-phrase".to_string(),
+            }),
+            ..TradingConfig::default()
+        };
+        let debug = format!("{cfg:?}");
+        assert!(debug.contains("key-1"));
+        for secret in ["deadbeef", "s3cret", "pass-phrase"] {
+            assert!(!debug.contains(secret), "{debug}");
+        }
+        assert_eq!(
+            WalletKind::parse("Gnosis_Safe"),
+            Some(WalletKind::GnosisSafe)
+        );
+        assert_eq!(WalletKind::parse("safe"), None);
+    }
+}
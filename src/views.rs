@@ -0,0 +1,429 @@
+//! Saved dashboard views.
+//!
+//! A view is a named preset of dashboard columns and filters, e.g. a `scalping` view
+//! showing only 5m markets with their prices and offers. `?view=<name>` on any dashboard
+//! route applies it; query keys the request sets itself win over the view's, and an
+//! unknown view is ignored. Views are kept in SQLite so they survive restarts, and are
+//! managed over `/dashboard/views`.
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::dashboard::{DASHBOARD_COLUMN_KEYS, DASHBOARD_FILTER_KEYS};
+use crate::env_vars::Settings;
+
+/// Longest accepted view name.
+pub const MAX_VIEW_NAME_LEN: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DashboardViewConfig {
+    /// SQLite file holding the views; `None` keeps them in memory only.
+    pub store_path: Option<PathBuf>,
+}
+
+impl Default for DashboardViewConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl DashboardViewConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_DASHBOARD_VIEW_STORE_PATH` (`off` keeps views in memory), falling back
+    /// to the default path when unset.
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            store_path: settings.get("PMM_DASHBOARD_VIEW_STORE_PATH"),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum DashboardViewError {
+    #[error("sqlite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid stored view row: {0}")]
+    InvalidRow(String),
+    #[error("invalid view name {0:?}: use 1-{MAX_VIEW_NAME_LEN} of a-z, 0-9, _ and -")]
+    InvalidName(String),
+    #[error("unknown dashboard column: {0}")]
+    UnknownColumn(String),
+    #[error("unknown dashboard filter: {0}")]
+    UnknownFilter(String),
+}
+
+impl DashboardViewError {
+    /// Whether the error is the caller's (a bad name or spec) rather than the store's.
+    pub fn is_invalid_input(&self) -> bool {
+        matches!(
+            self,
+            Self::InvalidName(_) | Self::UnknownColumn(_) | Self::UnknownFilter(_)
+        )
+    }
+}
+
+/// What a view presets: the columns to show (empty: all) and dashboard query values
+/// by key, e.g. `{"duration": ["5m"], "sort_by": ["net_profit"]}`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DashboardViewSpec {
+    #[serde(default)]
+    pub columns: Vec<String>,
+    #[serde(default)]
+    pub filters: BTreeMap<String, Vec<String>>,
+}
+
+impl DashboardViewSpec {
+    /// The spec with column and filter keys in their canonical lowercase form; unknown
+    /// keys are an error.
+    fn normalized(self) -> Result<Self, DashboardViewError> {
+        let columns = self
+            .columns
+            .iter()
+            .map(|raw| {
+                DASHBOARD_COLUMN_KEYS
+                    .into_iter()
+                    .find(|key| key.eq_ignore_ascii_case(raw.trim()))
+                    .map(str::to_string)
+                    .ok_or_else(|| DashboardViewError::UnknownColumn(raw.clone()))
+            })
+            .collect::<Result<_, _>>()?;
+        let mut filters = BTreeMap::new();
+        for (raw, values) in self.filters {
+            let key = DASHBOARD_FILTER_KEYS
+                .into_iter()
+                .find(|key| key.eq_ignore_ascii_case(raw.trim()))
+                .ok_or_else(|| DashboardViewError::UnknownFilter(raw.clone()))?;
+            filters
+                .entry(key.to_string())
+                .or_insert_with(Vec::new)
+                .extend(values);
+        }
+        Ok(Self { columns, filters })
+    }
+}
+
+/// A stored view.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DashboardView {
+    pub name: String,
+    #[serde(flatten)]
+    pub spec: DashboardViewSpec,
+    pub updated_ts_utc: i64,
+}
+
+/// Shared view state; clones share the same data. Reads never touch the store.
+#[derive(Clone, Default)]
+pub struct DashboardViews {
+    views: Arc<RwLock<BTreeMap<String, DashboardView>>>,
+    conn: Option<Arc<Mutex<Connection>>>,
+}
+
+impl DashboardViews {
+    /// Views that are not persisted.
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Opens (creating if needed) the SQLite store at `path` and loads its views.
+    pub fn open(path: &Path) -> Result<Self, DashboardViewError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "
+            PRAGMA journal_mode=WAL;
+            CREATE TABLE IF NOT EXISTS dashboard_views (
+                name TEXT PRIMARY KEY,
+                spec_json TEXT NOT NULL,
+                updated_ts_utc INTEGER NOT NULL
+            );
+            ",
+        )?;
+        let mut views = BTreeMap::new();
+        {
+            let mut stmt =
+                conn.prepare("SELECT name, spec_json, updated_ts_utc FROM dashboard_views")?;
+            let rows = stmt.query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, i64>(2)?,
+                ))
+            })?;
+            for row in rows {
+                let (name, spec_json, updated_ts_utc) = row?;
+                let spec = serde_json::from_str(&spec_json)
+                    .map_err(|err| DashboardViewError::InvalidRow(err.to_string()))?;
+                views.insert(
+                    name.clone(),
+                    DashboardView {
+                        name,
+                        spec,
+                        updated_ts_utc,
+                    },
+                );
+            }
+        }
+        Ok(Self {
+            views: Arc::new(RwLock::new(views)),
+            conn: Some(Arc::new(Mutex::new(conn))),
+        })
+    }
+
+    /// Opens the configured store; falls back to in-memory views when it cannot be
+    /// opened (logged as `dashboard.views.unpersisted`).
+    pub fn from_config(cfg: &DashboardViewConfig) -> Self {
+        let Some(path) = &cfg.store_path else {
+            return Self::in_memory();
+        };
+        match Self::open(path) {
+            Ok(views) => views,
+            Err(err) => {
+                warn!(
+                    component = "dashboard",
+                    event = "dashboard.views.unpersisted",
+                    path = %path.display(),
+                    error = %err
+                );
+                Self::in_memory()
+            }
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<DashboardView> {
+        self.read().get(name).cloned()
+    }
+
+    /// Every view, by name.
+    pub fn list(&self) -> Vec<DashboardView> {
+        self.read().values().cloned().collect()
+    }
+
+    /// Every view name, sorted.
+    pub fn names(&self) -> Vec<String> {
+        self.read().keys().cloned().collect()
+    }
+
+    /// Creates or replaces the view `name`, persisting it before it takes effect. Logs
+    /// `dashboard.view.saved`.
+    pub fn upsert(
+        &self,
+        name: &str,
+        spec: DashboardViewSpec,
+        now_ts_utc: i64,
+    ) -> Result<DashboardView, DashboardViewError> {
+        validate_view_name(name)?;
+        let spec = spec.normalized()?;
+        if let Some(conn) = &self.conn {
+            let spec_json = serde_json::to_string(&spec)
+                .map_err(|err| DashboardViewError::InvalidRow(err.to_string()))?;
+            conn.lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .execute(
+                    "INSERT OR REPLACE INTO dashboard_views (name, spec_json, updated_ts_utc)
+                 VALUES (?1, ?2, ?3)",
+                    params![name, spec_json, now_ts_utc],
+                )?;
+        }
+        let view = DashboardView {
+            name: name.to_string(),
+            spec,
+            updated_ts_utc: now_ts_utc,
+        };
+        self.write().insert(name.to_string(), view.clone());
+        info!(
+            component = "dashboard",
+            event = "dashboard.view.saved",
+            view = name,
+            columns = view.spec.columns.len(),
+            filters = view.spec.filters.len()
+        );
+        Ok(view)
+    }
+
+    /// Removes the view `name`; `false` when there was none. Logs
+    /// `dashboard.view.deleted`.
+    pub fn delete(&self, name: &str) -> Result<bool, DashboardViewError> {
+        if let Some(conn) = &self.conn {
+            conn.lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .execute("DELETE FROM dashboard_views WHERE name = ?1", params![name])?;
+        }
+        let removed = self.write().remove(name).is_some();
+        if removed {
+            info!(
+                component = "dashboard",
+                event = "dashboard.view.deleted",
+                view = name
+            );
+        }
+        Ok(removed)
+    }
+
+    /// Name of the view `query_pairs` select with `view=`, when it exists.
+    pub fn selected(&self, query_pairs: &[(String, String)]) -> Option<String> {
+        query_pairs
+            .iter()
+            .rev()
+            .find(|(key, _)| key.trim().eq_ignore_ascii_case("view"))
+            .map(|(_, name)| name.trim())
+            .filter(|name| self.read().contains_key(*name))
+            .map(str::to_string)
+    }
+
+    /// `query_pairs` with the selected view's columns and filters added for every key
+    /// the request does not set itself. Without a (known) view the pairs are unchanged.
+    pub fn expand(&self, query_pairs: &[(String, String)]) -> Vec<(String, String)> {
+        let mut pairs = query_pairs.to_vec();
+        let Some(view) = self.selected(query_pairs).and_then(|name| self.get(&name)) else {
+            return pairs;
+        };
+        let requested: HashSet<String> = query_pairs
+            .iter()
+            .map(|(key, _)| key.trim().to_ascii_lowercase())
+            .collect();
+        if !view.spec.columns.is_empty() && !requested.contains("columns") {
+            pairs.push(("columns".to_string(), view.spec.columns.join(",")));
+        }
+        for (key, values) in &view.spec.filters {
+            if !requested.contains(key) {
+                pairs.extend(values.iter().map(|value| (key.clone(), value.clone())));
+            }
+        }
+        pairs
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, BTreeMap<String, DashboardView>> {
+        self.views
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, BTreeMap<String, DashboardView>> {
+        self.views
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl std::fmt::Debug for DashboardViews {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DashboardViews")
+            .field("views", &*self.read())
+            .field("persisted", &self.conn.is_some())
+            .finish()
+    }
+}
+
+fn validate_view_name(name: &str) -> Result<(), DashboardViewError> {
+    let valid = (1..=MAX_VIEW_NAME_LEN).contains(&name.len())
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_' || b == b'-');
+    if valid {
+        Ok(())
+    } else {
+        Err(DashboardViewError::InvalidName(name.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(raw: &[(&str, &str)]) -> Vec<(String, String)> {
+        raw.iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn views_survive_reopen_and_expand_under_request_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dashboard_views.sqlite");
+
+        let views = DashboardViews::open(&path).unwrap();
+        let spec = DashboardViewSpec {
+            columns: vec!["Coin".to_string(), "price".to_string()],
+            filters: BTreeMap::from([
+                ("duration".to_string(), vec!["5m".to_string()]),
+                (
+                    "coin".to_string(),
+                    vec!["BTC".to_string(), "ETH".to_string()],
+                ),
+            ]),
+        };
+        views.upsert("scalping", spec, 1_000).unwrap();
+        views
+            .upsert("gone", DashboardViewSpec::default(), 1_010)
+            .unwrap();
+        assert!(views.delete("gone").unwrap());
+        assert!(!views.delete("gone").unwrap());
+        drop(views);
+
+        let views = DashboardViews::open(&path).unwrap();
+        assert_eq!(views.names(), vec!["scalping".to_string()]);
+        let view = views.get("scalping").unwrap();
+        assert_eq!(view.spec.columns, vec!["coin", "price"]);
+        assert_eq!(view.updated_ts_utc, 1_000);
+
+        let expanded = views.expand(&pairs(&[("view", "scalping"), ("coin", "SOL")]));
+        assert_eq!(
+            expanded,
+            pairs(&[
+                ("view", "scalping"),
+                ("coin", "SOL"),
+                ("columns", "coin,price"),
+                ("duration", "5m"),
+            ])
+        );
+        let unknown = pairs(&[("view", "swing"), ("coin", "SOL")]);
+        assert_eq!(views.expand(&unknown), unknown);
+    }
+
+    #[test]
+    fn upsert_rejects_bad_names_and_unknown_keys() {
+        let views = DashboardViews::in_memory();
+        let err = views
+            .upsert("Scalping!", DashboardViewSpec::default(), 0)
+            .unwrap_err();
+        assert!(matches!(err, DashboardViewError::InvalidName(_)));
+        assert!(err.is_invalid_input());
+        let err = views
+            .upsert(
+                "ok",
+                DashboardViewSpec {
+                    columns: vec!["pnl".to_string()],
+                    ..DashboardViewSpec::default()
+                },
+                0,
+            )
+            .unwrap_err();
+        assert!(matches!(err, DashboardViewError::UnknownColumn(column) if column == "pnl"));
+        let err = views
+            .upsert(
+                "ok",
+                DashboardViewSpec {
+                    filters: BTreeMap::from([("view".to_string(), vec!["ok".to_string()])]),
+                    ..DashboardViewSpec::default()
+                },
+                0,
+            )
+            .unwrap_err();
+        assert!(matches!(err, DashboardViewError::UnknownFilter(key) if key == "view"));
+        assert!(views.list().is_empty());
+    }
+}
@@ -0,0 +1,468 @@
+//! Cold-start warm-up for the live strategy.
+//!
+//! The online feature state needs `max_window + 1` seconds of 1s klines for every symbol
+//! before its first row. [`run_warmup`] plans that range ending at "now", fills the tail
+//! the store does not have yet from a [`KlineTailSource`] (Binance REST in production),
+//! loads the range through the cold-start transform with the strict gap policy, and
+//! repeats while the clock moved on by more than `max_lag_ms` during the load. Quoting
+//! stays disabled until that succeeds; [`WarmupTracker`] publishes progress for
+//! `/healthz`.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration as StdDuration;
+
+use arc_swap::ArcSwap;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::binance_klines::{
+    fetch_rest_klines, BinanceSymbol, Kline1s, ALL_BINANCE_SYMBOLS, BINANCE_REST_KLINES_URL,
+};
+use crate::env_vars::Settings;
+use crate::features::{
+    transform_store_range_for_runtime_cold_start, FeatureError, FeatureRow, FeatureSchema,
+    FeatureTransformConfig, FeatureTransformRequest, FeatureWindowPreset, GapPolicy,
+};
+use crate::kline_store::{KlineStore, KlineStoreError};
+use crate::kline_validation::KlineValidationConfig;
+use crate::preflight::RunMode;
+
+const STEP_MS: i64 = 1_000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WarmupConfig {
+    /// Off by default in paper mode; the live strategy must not quote before warm-up.
+    pub enabled: bool,
+    pub store_path: PathBuf,
+    /// Transform the online state is seeded with; its gap policy is forced to strict.
+    pub transform: FeatureTransformConfig,
+    pub rest_url: String,
+    pub rest_timeout_ms: u64,
+    /// Warm-up is done once the loaded range ends at most this far behind "now".
+    pub max_lag_ms: i64,
+    /// Plan/fill/load rounds before giving up on catching up with "now".
+    pub max_passes: u32,
+}
+
+impl Default for WarmupConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl WarmupConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_WARMUP_ENABLED` (defaults to on when `PMM_MODE=live`),
+    /// `PMM_BINANCE_STORE_PATH`, `PMM_WARMUP_PRESET` (`short|medium|long`), and
+    /// `PMM_WARMUP_MAX_LAG_MS`, falling back to defaults for missing/invalid values.
+    pub fn from_settings(settings: &Settings) -> Self {
+        let live = settings.get::<RunMode>("PMM_MODE") == RunMode::Live;
+        Self {
+            enabled: settings
+                .get::<Option<bool>>("PMM_WARMUP_ENABLED")
+                .unwrap_or(live),
+            store_path: settings.get("PMM_BINANCE_STORE_PATH"),
+            transform: settings
+                .parsed("PMM_WARMUP_PRESET", FeatureWindowPreset::parse)
+                .map(FeatureTransformConfig::with_preset)
+                .unwrap_or_default(),
+            rest_url: BINANCE_REST_KLINES_URL.to_string(),
+            rest_timeout_ms: 15_000,
+            max_lag_ms: settings.get_where("PMM_WARMUP_MAX_LAG_MS", |lag| *lag >= 0),
+            max_passes: 3,
+        }
+    }
+}
+
+/// Range the online state needs at `now_ms`: `max_window + 1` closed seconds ending
+/// at the current second.
+pub fn warmup_range(now_ms: i64, transform: &FeatureTransformConfig) -> FeatureTransformRequest {
+    let end = now_ms.div_euclid(STEP_MS) * STEP_MS;
+    let max_window = transform.windows_seconds.iter().copied().max().unwrap_or(1);
+    FeatureTransformRequest {
+        start_ts_ms_utc: end - (i64::from(max_window) + 1) * STEP_MS,
+        end_ts_ms_utc_exclusive: end,
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum WarmupError {
+    #[error("kline store error: {0}")]
+    Store(#[from] KlineStoreError),
+    #[error("tail fill failed for {symbol}: {message}")]
+    Tail { symbol: String, message: String },
+    #[error("history not continuous: {0}")]
+    Continuity(#[from] FeatureError),
+    #[error("still {lag_ms}ms behind now after {passes} passes")]
+    Lagging { lag_ms: i64, passes: u32 },
+}
+
+/// Source for the klines between the store's last row and "now".
+pub trait KlineTailSource {
+    fn fetch(
+        &mut self,
+        symbol: BinanceSymbol,
+        start_ts_ms: i64,
+        end_ts_ms_exclusive: i64,
+    ) -> Result<Vec<Kline1s>, String>;
+}
+
+/// Binance REST `/api/v3/klines`.
+pub struct BinanceRestTail {
+    client: reqwest::blocking::Client,
+    url: String,
+}
+
+impl BinanceRestTail {
+    pub fn new(cfg: &WarmupConfig) -> Result<Self, String> {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(StdDuration::from_millis(cfg.rest_timeout_ms))
+            .build()
+            .map_err(|err| err.to_string())?;
+        Ok(Self {
+            client,
+            url: cfg.rest_url.clone(),
+        })
+    }
+}
+
+impl KlineTailSource for BinanceRestTail {
+    fn fetch(
+        &mut self,
+        symbol: BinanceSymbol,
+        start_ts_ms: i64,
+        end_ts_ms_exclusive: i64,
+    ) -> Result<Vec<Kline1s>, String> {
+        fetch_rest_klines(
+            &self.client,
+            &self.url,
+            symbol,
+            start_ts_ms,
+            end_ts_ms_exclusive,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WarmupPhase {
+    #[default]
+    Pending,
+    FillingTail,
+    Loading,
+    Ready,
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct WarmupProgress {
+    pub phase: WarmupPhase,
+    pub start_ts_ms_utc: Option<i64>,
+    pub end_ts_ms_utc_exclusive: Option<i64>,
+    /// Klines the range needs across all symbols.
+    pub required_points: u64,
+    /// Of those, klines already in the store.
+    pub stored_points: u64,
+    /// Klines fetched from the tail source so far.
+    pub tail_filled_points: u64,
+    pub passes: u32,
+    pub quoting_enabled: bool,
+    pub error: Option<String>,
+    pub updated_at_ts_utc: i64,
+}
+
+/// Shared warm-up progress; clones see the same state.
+#[derive(Clone, Default)]
+pub struct WarmupTracker {
+    inner: Arc<ArcSwap<WarmupProgress>>,
+}
+
+impl WarmupTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn progress(&self) -> WarmupProgress {
+        self.inner.load().as_ref().clone()
+    }
+
+    /// Quoting gate: `true` only after a successful warm-up.
+    pub fn quoting_enabled(&self) -> bool {
+        self.inner.load().quoting_enabled
+    }
+
+    /// Marks the warm-up failed and keeps quoting disabled.
+    pub fn fail(&self, error: impl std::fmt::Display) {
+        let error = error.to_string();
+        warn!(component = "warmup", event = "warmup.failed", error = %error);
+        self.update(|progress| {
+            progress.phase = WarmupPhase::Failed;
+            progress.quoting_enabled = false;
+            progress.error = Some(error);
+        });
+    }
+
+    fn update(&self, apply: impl FnOnce(&mut WarmupProgress)) {
+        let mut next = self.progress();
+        apply(&mut next);
+        next.updated_at_ts_utc = Utc::now().timestamp();
+        self.inner.store(Arc::new(next));
+    }
+}
+
+/// Loaded warm-up history: the seed rows for the online feature state.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WarmupOutput {
+    pub schema: FeatureSchema,
+    pub rows: Vec<FeatureRow>,
+    pub range: FeatureTransformRequest,
+}
+
+/// Runs the warm-up and enables quoting on `tracker` when it succeeds. `now_ms` is the
+/// clock the range is planned against.
+pub fn run_warmup(
+    cfg: &WarmupConfig,
+    tail: &mut dyn KlineTailSource,
+    tracker: &WarmupTracker,
+    now_ms: &dyn Fn() -> i64,
+) -> Result<WarmupOutput, WarmupError> {
+    let result = warmup_passes(cfg, tail, tracker, now_ms);
+    match &result {
+        Ok(output) => {
+            tracker.update(|progress| {
+                progress.phase = WarmupPhase::Ready;
+                progress.quoting_enabled = true;
+                progress.error = None;
+            });
+            info!(
+                component = "warmup",
+                event = "warmup.ready",
+                start_ts_ms_utc = output.range.start_ts_ms_utc,
+                end_ts_ms_utc_exclusive = output.range.end_ts_ms_utc_exclusive,
+                rows = output.rows.len()
+            );
+        }
+        Err(err) => tracker.fail(err),
+    }
+    result
+}
+
+fn warmup_passes(
+    cfg: &WarmupConfig,
+    tail: &mut dyn KlineTailSource,
+    tracker: &WarmupTracker,
+    now_ms: &dyn Fn() -> i64,
+) -> Result<WarmupOutput, WarmupError> {
+    let transform = FeatureTransformConfig {
+        gap_policy: GapPolicy::Strict,
+        ..cfg.transform.clone()
+    };
+    let mut store = KlineStore::open(&cfg.store_path, KlineValidationConfig::default())?;
+    let mut lag_ms = 0;
+    let passes = cfg.max_passes.max(1);
+    for pass in 1..=passes {
+        let range = warmup_range(now_ms(), &transform);
+        let per_symbol = ((range.end_ts_ms_utc_exclusive - range.start_ts_ms_utc) / STEP_MS) as u64;
+        let mut stored_points = 0;
+        for symbol in ALL_BINANCE_SYMBOLS {
+            stored_points +=
+                store.count_range(symbol, range.start_ts_ms_utc, range.end_ts_ms_utc_exclusive)?;
+        }
+        tracker.update(|progress| {
+            progress.phase = WarmupPhase::FillingTail;
+            progress.start_ts_ms_utc = Some(range.start_ts_ms_utc);
+            progress.end_ts_ms_utc_exclusive = Some(range.end_ts_ms_utc_exclusive);
+            progress.required_points = per_symbol * ALL_BINANCE_SYMBOLS.len() as u64;
+            progress.stored_points = stored_points;
+            progress.passes = pass;
+        });
+
+        for symbol in ALL_BINANCE_SYMBOLS {
+            let filled = fill_tail(&mut store, tail, symbol, &range)?;
+            tracker.update(|progress| {
+                progress.tail_filled_points += filled;
+                progress.stored_points += filled;
+            });
+        }
+
+        tracker.update(|progress| progress.phase = WarmupPhase::Loading);
+        let (schema, rows, _) =
+            transform_store_range_for_runtime_cold_start(&cfg.store_path, &range, &transform)?;
+
+        lag_ms = now_ms() - range.end_ts_ms_utc_exclusive;
+        info!(
+            component = "warmup",
+            event = "warmup.pass",
+            pass,
+            start_ts_ms_utc = range.start_ts_ms_utc,
+            end_ts_ms_utc_exclusive = range.end_ts_ms_utc_exclusive,
+            lag_ms
+        );
+        if lag_ms <= cfg.max_lag_ms {
+            return Ok(WarmupOutput {
+                schema,
+                rows,
+                range,
+            });
+        }
+    }
+    Err(WarmupError::Lagging { lag_ms, passes })
+}
+
+/// Fetches everything after the symbol's last stored kline in `range` and upserts it.
+fn fill_tail(
+    store: &mut KlineStore,
+    tail: &mut dyn KlineTailSource,
+    symbol: BinanceSymbol,
+    range: &FeatureTransformRequest,
+) -> Result<u64, WarmupError> {
+    let last_stored = store
+        .closes_range(symbol, range.start_ts_ms_utc, range.end_ts_ms_utc_exclusive)?
+        .last()
+        .map(|(open_time_ms, _)| *open_time_ms);
+    let tail_start = last_stored.map_or(range.start_ts_ms_utc, |last| last + STEP_MS);
+    if tail_start >= range.end_ts_ms_utc_exclusive {
+        return Ok(0);
+    }
+    let rows = tail
+        .fetch(symbol, tail_start, range.end_ts_ms_utc_exclusive)
+        .map_err(|message| WarmupError::Tail {
+            symbol: symbol.as_str().to_string(),
+            message,
+        })?;
+    let fetched = rows.len() as u64;
+    let counts = store.upsert_rows(symbol, rows)?;
+    Ok(fetched.saturating_sub(counts.rows_rejected))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2025-01-01T00:00:00Z
+    const START_MS: i64 = 1_735_689_600_000;
+
+    fn kline(open_time_ms: i64) -> Kline1s {
+        let close = 100.0 + (open_time_ms - START_MS) as f64 / 1_000.0;
+        Kline1s {
+            open_time_ms,
+            open: close,
+            high: close + 0.5,
+            low: close - 0.5,
+            close,
+            volume: 1.0,
+            close_time_ms: open_time_ms + 999,
+            quote_asset_volume: 100.0,
+            trade_count: 1,
+            taker_buy_base_volume: 0.5,
+            taker_buy_quote_volume: 50.0,
+        }
+    }
+
+    /// Serves every requested second and records the requests.
+    #[derive(Default)]
+    struct FakeTail {
+        requests: Vec<(BinanceSymbol, i64, i64)>,
+    }
+
+    impl KlineTailSource for FakeTail {
+        fn fetch(
+            &mut self,
+            symbol: BinanceSymbol,
+            start_ts_ms: i64,
+            end_ts_ms_exclusive: i64,
+        ) -> Result<Vec<Kline1s>, String> {
+            self.requests
+                .push((symbol, start_ts_ms, end_ts_ms_exclusive));
+            Ok((start_ts_ms..end_ts_ms_exclusive)
+                .step_by(STEP_MS as usize)
+                .map(kline)
+                .collect())
+        }
+    }
+
+    fn cfg(store_path: PathBuf) -> WarmupConfig {
+        WarmupConfig {
+            store_path,
+            transform: FeatureTransformConfig {
+                windows_seconds: vec![2, 5],
+                ..FeatureTransformConfig::default()
+            },
+            ..WarmupConfig::default()
+        }
+    }
+
+    #[test]
+    fn range_covers_the_largest_window_plus_one_second() {
+        let range = warmup_range(START_MS + 10_400, &FeatureTransformConfig::default());
+        assert_eq!(range.end_ts_ms_utc_exclusive, START_MS + 10_000);
+        assert_eq!(range.start_ts_ms_utc, START_MS + 10_000 - 61_000);
+    }
+
+    #[test]
+    fn fills_the_tail_then_enables_quoting() {
+        let dir = tempfile::tempdir().unwrap();
+        let cfg = cfg(dir.path().join("klines_1s.sqlite"));
+        {
+            let mut store =
+                KlineStore::open(&cfg.store_path, KlineValidationConfig::default()).unwrap();
+            for symbol in ALL_BINANCE_SYMBOLS {
+                let rows = (0..3).map(|n| kline(START_MS + n * STEP_MS)).collect();
+                store.upsert_rows(symbol, rows).unwrap();
+            }
+        }
+
+        let tracker = WarmupTracker::new();
+        assert!(!tracker.quoting_enabled());
+        let mut tail = FakeTail::default();
+        let now = START_MS + 6_200;
+        let output = run_warmup(&cfg, &mut tail, &tracker, &|| now).unwrap();
+
+        assert_eq!(output.range.start_ts_ms_utc, START_MS);
+        assert_eq!(output.rows.len(), 1);
+        assert_eq!(output.rows[0].ts_ms_utc, START_MS + 5_000);
+        assert_eq!(tail.requests.len(), 4);
+        assert!(tail
+            .requests
+            .iter()
+            .all(|(_, start, end)| (*start, *end) == (START_MS + 3_000, START_MS + 6_000)));
+
+        let progress = tracker.progress();
+        assert_eq!(progress.phase, WarmupPhase::Ready);
+        assert!(progress.quoting_enabled);
+        assert_eq!(progress.required_points, 24);
+        assert_eq!(progress.tail_filled_points, 12);
+        assert_eq!(progress.stored_points, 24);
+    }
+
+    #[test]
+    fn gives_up_when_the_clock_outruns_the_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let cfg = WarmupConfig {
+            max_passes: 2,
+            ..cfg(dir.path().join("klines_1s.sqlite"))
+        };
+        let clock = std::cell::Cell::new(START_MS + 6_000);
+        let now = || {
+            // Every read jumps ten seconds ahead.
+            let now = clock.get();
+            clock.set(now + 10_000);
+            now
+        };
+        let tracker = WarmupTracker::new();
+        let err = run_warmup(&cfg, &mut FakeTail::default(), &tracker, &now).unwrap_err();
+
+        assert!(matches!(err, WarmupError::Lagging { passes: 2, .. }));
+        let progress = tracker.progress();
+        assert_eq!(progress.phase, WarmupPhase::Failed);
+        assert!(!progress.quoting_enabled);
+        assert_eq!(progress.passes, 2);
+    }
+}
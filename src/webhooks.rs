@@ -0,0 +1,457 @@
+//! Market lifecycle webhooks.
+//!
+//! Events are derived by diffing consecutive dashboard snapshots:
+//! - `interval.rollover`: the in-interval market for a coin/duration changed
+//! - `market.resolved`: a market whose interval has ended stopped accepting orders
+//! - `discovery.resolved` / `discovery.unresolved`: a slug gained or lost Gamma metadata
+//! - `position.opened` / `position.closed`: a row's net position became non-zero / zero
+//!
+//! Each event is POSTed as JSON to `PMM_WEBHOOK_URL`. With `PMM_WEBHOOK_SECRET` set the
+//! request carries `X-PMM-Signature: sha256=<hex>`, an HMAC-SHA256 over
+//! `"{X-PMM-Timestamp}.{body}"`, so receivers can verify origin and reject replays.
+//! Delivery runs on a background task with bounded retries and never blocks the
+//! snapshot refresh loop; events are dropped (and logged) when the queue is full.
+
+use std::collections::HashMap;
+
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+use crate::dashboard::{DashboardRow, DashboardSnapshot};
+use crate::env_vars::{EnvValue, Settings};
+
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "x-pmm-signature";
+pub const WEBHOOK_TIMESTAMP_HEADER: &str = "x-pmm-timestamp";
+pub const WEBHOOK_EVENT_HEADER: &str = "x-pmm-event";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MarketEventKind {
+    #[serde(rename = "interval.rollover")]
+    IntervalRollover,
+    #[serde(rename = "market.resolved")]
+    MarketResolved,
+    #[serde(rename = "discovery.resolved")]
+    DiscoveryResolved,
+    #[serde(rename = "discovery.unresolved")]
+    DiscoveryUnresolved,
+    #[serde(rename = "position.opened")]
+    PositionOpened,
+    #[serde(rename = "position.closed")]
+    PositionClosed,
+}
+
+pub const ALL_MARKET_EVENT_KINDS: [MarketEventKind; 6] = [
+    MarketEventKind::IntervalRollover,
+    MarketEventKind::MarketResolved,
+    MarketEventKind::DiscoveryResolved,
+    MarketEventKind::DiscoveryUnresolved,
+    MarketEventKind::PositionOpened,
+    MarketEventKind::PositionClosed,
+];
+
+impl MarketEventKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::IntervalRollover => "interval.rollover",
+            Self::MarketResolved => "market.resolved",
+            Self::DiscoveryResolved => "discovery.resolved",
+            Self::DiscoveryUnresolved => "discovery.unresolved",
+            Self::PositionOpened => "position.opened",
+            Self::PositionClosed => "position.closed",
+        }
+    }
+
+    pub fn parse(raw: &str) -> Option<Self> {
+        ALL_MARKET_EVENT_KINDS
+            .into_iter()
+            .find(|kind| kind.as_str() == raw)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MarketEvent {
+    pub event: MarketEventKind,
+    pub ts_utc: i64,
+    pub slug: String,
+    pub coin: String,
+    pub duration: String,
+    pub start_ts_utc: i64,
+    pub end_ts_utc: i64,
+    /// Rollovers: the market that was in interval before.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_slug: Option<String>,
+    /// Position events: the dashboard `position_net` value after (opened) or before
+    /// (closed) the change.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position_net: Option<String>,
+}
+
+impl MarketEvent {
+    fn for_row(event: MarketEventKind, row: &DashboardRow, ts_utc: i64) -> Self {
+        Self {
+            event,
+            ts_utc,
+            slug: row.slug.clone(),
+            coin: row.coin.clone(),
+            duration: row.duration.clone(),
+            start_ts_utc: row.start_ts_utc,
+            end_ts_utc: row.end_ts_utc,
+            previous_slug: None,
+            position_net: None,
+        }
+    }
+}
+
+/// Lifecycle events implied by moving from `prev` to `next`, in row order of `next`.
+pub fn market_events_between(
+    prev: &DashboardSnapshot,
+    next: &DashboardSnapshot,
+    now_ts_utc: i64,
+) -> Vec<MarketEvent> {
+    let prev_by_slug: HashMap<&str, &DashboardRow> = prev
+        .rows
+        .iter()
+        .map(|row| (row.slug.as_str(), row))
+        .collect();
+    let prev_active: HashMap<(&str, &str), &DashboardRow> = prev
+        .rows
+        .iter()
+        .filter(|row| row_in_interval(row))
+        .map(|row| ((row.coin.as_str(), row.duration.as_str()), row))
+        .collect();
+
+    let mut events = Vec::new();
+    for row in &next.rows {
+        let before = prev_by_slug.get(row.slug.as_str()).copied();
+
+        if row_in_interval(row) {
+            if let Some(previous) = prev_active.get(&(row.coin.as_str(), row.duration.as_str())) {
+                if previous.slug != row.slug {
+                    let mut event =
+                        MarketEvent::for_row(MarketEventKind::IntervalRollover, row, now_ts_utc);
+                    event.previous_slug = Some(previous.slug.clone());
+                    events.push(event);
+                }
+            }
+        }
+
+        let was_resolved = before.map(row_is_resolved).unwrap_or(false);
+        if row_is_resolved(row) != was_resolved {
+            let kind = if was_resolved {
+                MarketEventKind::DiscoveryUnresolved
+            } else {
+                MarketEventKind::DiscoveryResolved
+            };
+            events.push(MarketEvent::for_row(kind, row, now_ts_utc));
+        }
+
+        let was_closed = before
+            .map(|before| before.bets_open.as_deref() == Some("closed"))
+            .unwrap_or(false);
+        if before.is_some()
+            && !was_closed
+            && row.bets_open.as_deref() == Some("closed")
+            && now_ts_utc >= row.end_ts_utc
+        {
+            events.push(MarketEvent::for_row(
+                MarketEventKind::MarketResolved,
+                row,
+                now_ts_utc,
+            ));
+        }
+
+        let was_open = before.map(position_is_open).unwrap_or(false);
+        match (was_open, position_is_open(row)) {
+            (false, true) => {
+                let mut event =
+                    MarketEvent::for_row(MarketEventKind::PositionOpened, row, now_ts_utc);
+                event.position_net = row.position_net.clone();
+                events.push(event);
+            }
+            (true, false) => {
+                let mut event =
+                    MarketEvent::for_row(MarketEventKind::PositionClosed, row, now_ts_utc);
+                event.position_net = before.and_then(|before| before.position_net.clone());
+                events.push(event);
+            }
+            _ => {}
+        }
+    }
+
+    events
+}
+
+fn row_in_interval(row: &DashboardRow) -> bool {
+    row.in_interval.as_deref() == Some("yes")
+}
+
+/// Resolved rows always carry the market's fee parameters; unresolved and
+/// placeholder rows never do.
+fn row_is_resolved(row: &DashboardRow) -> bool {
+    row.taker_fee_pct.is_some()
+}
+
+/// `position_net` is `size@price@side`; a non-zero size means an open position.
+fn position_is_open(row: &DashboardRow) -> bool {
+    row.position_net
+        .as_deref()
+        .and_then(|raw| raw.split('@').next())
+        .and_then(|size| size.trim().parse::<f64>().ok())
+        .map(|size| size != 0.0)
+        .unwrap_or(false)
+}
+
+/// Hex HMAC-SHA256 of `"{timestamp}.{body}"` under `secret`.
+pub fn sign_webhook_payload(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebhookConfig {
+    /// Delivery is disabled when unset.
+    pub url: Option<String>,
+    pub secret: Option<String>,
+    /// Kinds to deliver; empty means all.
+    pub events: Vec<MarketEventKind>,
+    pub timeout_ms: u64,
+    pub max_retries: u32,
+    pub retry_backoff_ms: u64,
+    pub queue_capacity: usize,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self::from_settings(&Settings::defaults())
+    }
+}
+
+impl WebhookConfig {
+    /// [`Self::from_settings`] on the process environment.
+    pub fn from_env() -> Self {
+        Self::from_settings(&Settings::from_env())
+    }
+
+    /// Reads `PMM_WEBHOOK_URL`, `PMM_WEBHOOK_SECRET`, `PMM_WEBHOOK_EVENTS` (comma list of
+    /// event names), `PMM_WEBHOOK_TIMEOUT_MS`, and `PMM_WEBHOOK_MAX_RETRIES`. Empty or
+    /// invalid values fall back to defaults; unknown event names are ignored.
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            url: settings.parsed("PMM_WEBHOOK_URL", String::parse_env),
+            secret: settings.parsed("PMM_WEBHOOK_SECRET", String::parse_env),
+            events: settings
+                .get::<Vec<String>>("PMM_WEBHOOK_EVENTS")
+                .iter()
+                .filter_map(|name| MarketEventKind::parse(name))
+                .collect(),
+            timeout_ms: settings.get("PMM_WEBHOOK_TIMEOUT_MS"),
+            max_retries: settings.get("PMM_WEBHOOK_MAX_RETRIES"),
+            retry_backoff_ms: 500,
+            queue_capacity: 1_024,
+        }
+    }
+
+    pub fn wants(&self, kind: MarketEventKind) -> bool {
+        self.events.is_empty() || self.events.contains(&kind)
+    }
+}
+
+/// Handle to the background delivery task. Cloning shares the queue.
+#[derive(Debug, Clone)]
+pub struct WebhookEmitter {
+    tx: mpsc::Sender<MarketEvent>,
+    events: Vec<MarketEventKind>,
+}
+
+impl WebhookEmitter {
+    /// Starts the delivery task on the current Tokio runtime. Returns `None` when no
+    /// URL is configured.
+    pub fn spawn(config: WebhookConfig) -> Option<Self> {
+        let url = config.url.clone()?;
+        let client = match reqwest::Client::builder()
+            .timeout(std::time::Duration::from_millis(config.timeout_ms))
+            .build()
+        {
+            Ok(client) => client,
+            Err(err) => {
+                warn!(
+                    component = "webhooks",
+                    event = "webhook.disabled",
+                    error = %err
+                );
+                return None;
+            }
+        };
+        let (tx, mut rx) = mpsc::channel::<MarketEvent>(config.queue_capacity.max(1));
+        let events = config.events.clone();
+
+        tokio::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                deliver(&client, &url, &config, &event).await;
+            }
+        });
+
+        Some(Self { tx, events })
+    }
+
+    /// Queues `event` for delivery if its kind is enabled.
+    pub fn emit(&self, event: MarketEvent) {
+        if !self.events.is_empty() && !self.events.contains(&event.event) {
+            return;
+        }
+        if let Err(err) = self.tx.try_send(event) {
+            let event = match &err {
+                mpsc::error::TrySendError::Full(event)
+                | mpsc::error::TrySendError::Closed(event) => event,
+            };
+            warn!(
+                component = "webhooks",
+                event = "webhook.dropped",
+                kind = event.event.as_str(),
+                slug = %event.slug,
+                reason = if matches!(err, mpsc::error::TrySendError::Full(_)) {
+                    "queue_full"
+                } else {
+                    "closed"
+                }
+            );
+        }
+    }
+}
+
+async fn deliver(client: &reqwest::Client, url: &str, config: &WebhookConfig, event: &MarketEvent) {
+    let body = match serde_json::to_vec(event) {
+        Ok(body) => body,
+        Err(err) => {
+            warn!(component = "webhooks", event = "webhook.failed", error = %err);
+            return;
+        }
+    };
+
+    let mut attempt = 0u32;
+    loop {
+        let timestamp = Utc::now().timestamp();
+        let mut request = client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(WEBHOOK_EVENT_HEADER, event.event.as_str())
+            .header(WEBHOOK_TIMESTAMP_HEADER, timestamp.to_string());
+        if let Some(secret) = &config.secret {
+            request = request.header(
+                WEBHOOK_SIGNATURE_HEADER,
+                format!("sha256={}", sign_webhook_payload(secret, timestamp, &body)),
+            );
+        }
+
+        let failure = match request.body(body.clone()).send().await {
+            Ok(response) if response.status().is_success() => {
+                debug!(
+                    component = "webhooks",
+                    event = "webhook.delivered",
+                    kind = event.event.as_str(),
+                    slug = %event.slug,
+                    attempt
+                );
+                return;
+            }
+            Ok(response) => format!("status {}", response.status()),
+            Err(err) => err.to_string(),
+        };
+
+        if attempt >= config.max_retries {
+            warn!(
+                component = "webhooks",
+                event = "webhook.failed",
+                kind = event.event.as_str(),
+                slug = %event.slug,
+                attempts = attempt + 1,
+                error = %failure
+            );
+            return;
+        }
+        attempt += 1;
+        let backoff = config
+            .retry_backoff_ms
+            .saturating_mul(1u64 << (attempt - 1).min(10));
+        tokio::time::sleep(std::time::Duration::from_millis(backoff)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(slug: &str, start: i64, in_interval: bool) -> DashboardRow {
+        let mut row = DashboardRow::unresolved_with_times(slug, "BTC", "5m", start, start + 300);
+        row.in_interval = Some(if in_interval { "yes" } else { "no" }.to_string());
+        row
+    }
+
+    fn resolved(mut row: DashboardRow, bets_open: &str) -> DashboardRow {
+        row.taker_fee_pct = Some("0.25".to_string());
+        row.bets_open = Some(bets_open.to_string());
+        row
+    }
+
+    fn kinds(events: &[MarketEvent]) -> Vec<(MarketEventKind, &str)> {
+        events
+            .iter()
+            .map(|event| (event.event, event.slug.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn snapshot_diff_emits_lifecycle_events() {
+        let prev = DashboardSnapshot {
+            rows: vec![resolved(row("a", 0, true), "open"), row("b", 300, false)],
+            seq: 1,
+        };
+        let mut a_after = resolved(row("a", 0, false), "closed");
+        a_after.position_net = Some("0@0@YES".to_string());
+        let mut b_after = resolved(row("b", 300, true), "open");
+        b_after.position_net = Some("5@0.5@YES".to_string());
+        let next = DashboardSnapshot {
+            rows: vec![a_after, b_after, row("c", 600, false)],
+            seq: 2,
+        };
+
+        let events = market_events_between(&prev, &next, 310);
+
+        assert_eq!(
+            kinds(&events),
+            vec![
+                (MarketEventKind::MarketResolved, "a"),
+                (MarketEventKind::IntervalRollover, "b"),
+                (MarketEventKind::DiscoveryResolved, "b"),
+                (MarketEventKind::PositionOpened, "b"),
+            ]
+        );
+        assert_eq!(events[1].previous_slug.as_deref(), Some("a"));
+        assert_eq!(events[3].position_net.as_deref(), Some("5@0.5@YES"));
+
+        let back = market_events_between(&next, &prev, 320);
+        assert!(kinds(&back).contains(&(MarketEventKind::DiscoveryUnresolved, "b")));
+        assert!(kinds(&back).contains(&(MarketEventKind::PositionClosed, "b")));
+    }
+
+    #[test]
+    fn signature_is_hmac_sha256_over_timestamp_and_body() {
+        let body = br#"{"event":"interval.rollover"}"#;
+        assert_eq!(
+            sign_webhook_payload("whsec_test", 1_700_000_000, body),
+            "a1cbbe1c3e38fc0469ce5f2ca9b3942e65b46c3f8cd9cc3b3fab4f4cc002def9"
+        );
+        assert_ne!(
+            sign_webhook_payload("whsec_test", 1_700_000_001, body),
+            sign_webhook_payload("whsec_test", 1_700_000_000, body)
+        );
+    }
+}
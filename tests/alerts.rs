@@ -0,0 +1,101 @@
+use axum::body::{to_bytes, Body};
+use axum::http::{Request, StatusCode};
+use axum::Router;
+use pmm::{
+    alerts_router, AlertConfig, AlertDecision, AlertManager, AlertRecord, AlertSeverity,
+    AlertState, SuppressReason,
+};
+use tower::ServiceExt;
+
+async fn call(app: &Router, method: &str, uri: &str) -> (StatusCode, Vec<u8>) {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .expect("response");
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, body.to_vec())
+}
+
+async fn list(app: &Router, uri: &str) -> Vec<AlertRecord> {
+    let (status, body) = call(app, "GET", uri).await;
+    assert_eq!(status, StatusCode::OK);
+    serde_json::from_slice(&body).expect("json")
+}
+
+#[tokio::test]
+async fn alerts_endpoint_lists_acks_and_survives_restart() {
+    let dir = tempfile::tempdir().unwrap();
+    let cfg = AlertConfig {
+        store_path: Some(dir.path().join("alerts.sqlite")),
+        renotify_after_secs: 3_600,
+        ..AlertConfig::default()
+    };
+
+    let alerts = AlertManager::from_config(&cfg).expect("store");
+    assert_eq!(
+        alerts.fire("clock.drift", AlertSeverity::Warn, "offset 400ms"),
+        AlertDecision::Notify
+    );
+    alerts.fire(
+        "discovery.live_unavailable",
+        AlertSeverity::Critical,
+        "down",
+    );
+    alerts.fire("old.alert", AlertSeverity::Warn, "gone");
+    alerts.clear("old.alert");
+
+    let app = alerts_router(alerts);
+    let open = list(&app, "/alerts").await;
+    let mut keys = open
+        .iter()
+        .map(|record| record.key.as_str())
+        .collect::<Vec<_>>();
+    keys.sort();
+    assert_eq!(keys, ["clock.drift", "discovery.live_unavailable"]);
+    assert_eq!(
+        list(&app, "/alerts?state=cleared").await[0].key,
+        "old.alert"
+    );
+    assert_eq!(list(&app, "/alerts?state=all").await.len(), 3);
+    assert_eq!(
+        call(&app, "GET", "/alerts?state=bogus").await.0,
+        StatusCode::BAD_REQUEST
+    );
+
+    let (status, body) = call(&app, "POST", "/alerts/clock.drift/ack").await;
+    assert_eq!(status, StatusCode::OK);
+    let acked: AlertRecord = serde_json::from_slice(&body).unwrap();
+    assert_eq!(acked.state, AlertState::Acknowledged);
+    assert_eq!(
+        call(&app, "POST", "/alerts/old.alert/ack").await.0,
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        list(&app, "/alerts?state=acknowledged").await[0].key,
+        "clock.drift"
+    );
+    drop(app);
+
+    // A restarted process sees the same state and does not re-notify.
+    let alerts = AlertManager::from_config(&cfg).expect("reopen");
+    assert_eq!(
+        alerts.fire("clock.drift", AlertSeverity::Warn, "offset 410ms"),
+        AlertDecision::Suppressed(SuppressReason::Acknowledged)
+    );
+    assert_eq!(
+        alerts.fire(
+            "discovery.live_unavailable",
+            AlertSeverity::Critical,
+            "down"
+        ),
+        AlertDecision::Suppressed(SuppressReason::RecentlyNotified)
+    );
+}
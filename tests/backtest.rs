@@ -0,0 +1,129 @@
+use pmm::{
+    read_report_json, run_backtest, BacktestConfig, BacktestEntry, BacktestInterval,
+    BacktestReport, BinanceSymbol, Coin, Duration, FeatureRow, FeatureSchema, JsonReport, Kline1s,
+    KlineStore, KlineValidationConfig, SizingSide, Strategy, ALL_BINANCE_SYMBOLS,
+};
+
+// 2025-01-01T00:00:00Z
+const START: i64 = 1_735_689_600;
+
+fn kline(open_time_ms: i64, close: f64) -> Kline1s {
+    Kline1s {
+        open_time_ms,
+        open: close,
+        high: close,
+        low: close,
+        close,
+        volume: 1.0,
+        close_time_ms: open_time_ms + 999,
+        quote_asset_volume: close,
+        trade_count: 1,
+        taker_buy_base_volume: 0.5,
+        taker_buy_quote_volume: close / 2.0,
+    }
+}
+
+/// Three 5m intervals from `START`; BTC goes up, down, up, the others stay flat.
+fn seed_store(path: &std::path::Path, first_ts: i64) {
+    let mut store = KlineStore::open(path, KlineValidationConfig::default()).unwrap();
+    for symbol in ALL_BINANCE_SYMBOLS {
+        let rows = (first_ts..START + 900)
+            .map(|ts| {
+                let offset = (ts - START).rem_euclid(300) as f64 * 0.001;
+                let close = match (symbol, (ts - START).div_euclid(300)) {
+                    (BinanceSymbol::BtcUsdt, 0 | 2) => 100.0 + offset,
+                    (BinanceSymbol::BtcUsdt, 1) => 100.0 - offset,
+                    _ => 100.0,
+                };
+                kline(ts * 1_000, close)
+            })
+            .collect();
+        store.upsert_rows(symbol, rows).unwrap();
+    }
+}
+
+/// Buys UP on BTC at a fixed price.
+#[derive(Default)]
+struct AlwaysUp {
+    columns: usize,
+    decisions: Vec<(Coin, i64, i64)>,
+}
+
+impl Strategy for AlwaysUp {
+    fn name(&self) -> &str {
+        "always_up"
+    }
+
+    fn prepare(&mut self, schema: &FeatureSchema) {
+        self.columns = schema.columns.len();
+    }
+
+    fn decide(
+        &mut self,
+        interval: &BacktestInterval,
+        features: &FeatureRow,
+    ) -> Option<BacktestEntry> {
+        assert_eq!(features.values.len(), self.columns);
+        self.decisions
+            .push((interval.coin, interval.start_ts_utc, features.ts_ms_utc));
+        (interval.coin == Coin::BTC).then_some(BacktestEntry {
+            side: SizingSide::Yes,
+            price: 0.5,
+            stake_usdc: 10.0,
+        })
+    }
+}
+
+#[test]
+fn replay_settles_intervals_and_reports_pnl_hit_rate_and_drawdown() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("klines_1s.sqlite");
+    seed_store(&path, START - 120);
+
+    let cfg = BacktestConfig::new(&path, Duration::M5, START, START + 900);
+    let mut strategy = AlwaysUp::default();
+    let report = run_backtest(&cfg, &mut strategy).unwrap();
+
+    assert_eq!(report.strategy, "always_up");
+    assert_eq!(report.intervals, 12);
+    assert_eq!(report.skipped_intervals, 0);
+    assert_eq!(strategy.decisions.len(), 12);
+    // Decisions only see the row of the second before the interval.
+    assert!(strategy
+        .decisions
+        .iter()
+        .all(|(_, start, row_ts)| *row_ts == start * 1_000 - 1_000));
+
+    let won: Vec<bool> = report.trades.iter().map(|trade| trade.won).collect();
+    assert_eq!(won, vec![true, false, true]);
+    assert!((report.trades[0].pnl_usdc - 10.0).abs() < 1e-9);
+    assert!((report.trades[1].pnl_usdc + 10.0).abs() < 1e-9);
+    assert_eq!(report.summary.trades, 3);
+    assert_eq!(report.summary.hit_rate, Some(2.0 / 3.0));
+    assert!((report.summary.pnl_usdc - 10.0).abs() < 1e-9);
+    assert!((report.summary.max_drawdown_usdc - 10.0).abs() < 1e-9);
+    assert_eq!(report.by_coin[&Coin::ETH].trades, 0);
+    assert_eq!(report.by_coin[&Coin::ETH].hit_rate, None);
+
+    let out = dir.path().join("backtest.json");
+    report.write_report_json(&out).unwrap();
+    assert_eq!(
+        read_report_json::<BacktestReport>(&out).unwrap().report,
+        report
+    );
+}
+
+#[test]
+fn intervals_without_warm_features_are_skipped() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("klines_1s.sqlite");
+    seed_store(&path, START);
+
+    let cfg = BacktestConfig::new(&path, Duration::M5, START, START + 900);
+    let report = run_backtest(&cfg, &mut AlwaysUp::default()).unwrap();
+
+    assert_eq!(report.intervals, 12);
+    assert_eq!(report.skipped_intervals, 4);
+    assert_eq!(report.summary.trades, 2);
+    assert!(!report.transform.gap_ranges.is_empty());
+}
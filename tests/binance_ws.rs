@@ -0,0 +1,87 @@
+use std::time::Duration as StdDuration;
+
+use futures_util::{SinkExt, StreamExt};
+use pmm::{
+    apply_live_prices, BinanceStreamKind, BinanceWsConfig, BinanceWsStream, Coin, DashboardRow,
+    PriceSource,
+};
+use tokio::net::TcpListener;
+use tokio_tungstenite::tungstenite::Message;
+
+async fn wait_for<T>(mut probe: impl FnMut() -> Option<T>) -> T {
+    for _ in 0..200 {
+        if let Some(value) = probe() {
+            return value;
+        }
+        tokio::time::sleep(StdDuration::from_millis(10)).await;
+    }
+    panic!("condition not reached");
+}
+
+#[tokio::test]
+async fn stream_fills_price_and_ref_price_from_trades() {
+    let start = chrono::Utc::now().timestamp() - 1;
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        let (tcp, _) = listener.accept().await.unwrap();
+        let mut socket = tokio_tungstenite::accept_async(tcp).await.unwrap();
+        for (price, offset_ms) in [("100.5", 400), ("101.25", 2_000)] {
+            let frame = serde_json::json!({
+                "stream": "btcusdt@trade",
+                "data": {
+                    "e": "trade", "s": "BTCUSDT", "p": price, "q": "0.1",
+                    "T": start * 1_000 + offset_ms
+                }
+            });
+            socket
+                .send(Message::Text(frame.to_string().into()))
+                .await
+                .unwrap();
+        }
+        while let Some(Ok(_)) = socket.next().await {}
+    });
+
+    let stream = BinanceWsStream::spawn(
+        BinanceWsConfig {
+            ws_url: Some(url),
+            stream: BinanceStreamKind::Trade,
+            ..BinanceWsConfig::default()
+        },
+        &[Coin::BTC, Coin::ETH],
+    )
+    .unwrap();
+    let latest = wait_for(|| stream.latest(Coin::BTC).filter(|p| p.price > 101.0)).await;
+    assert_eq!(latest.ts_ms_utc, start * 1_000 + 2_000);
+
+    let mut row =
+        DashboardRow::unresolved_with_times("btc-updown-5m-x", "BTC", "5m", start, start + 300);
+    let mut eth =
+        DashboardRow::unresolved_with_times("eth-updown-5m-x", "ETH", "5m", start, start + 300);
+    assert!(row.is_mock_column("price") && row.is_mock_column("ref_price"));
+    apply_live_prices(&stream, [&mut row, &mut eth]);
+    assert_eq!(row.price.as_deref(), Some("101.25"));
+    assert_eq!(row.ref_price.as_deref(), Some("100.5"));
+    assert_eq!(row.ref_capture_delay_ms, Some(0));
+    assert!(!row.is_mock_column("price") && !row.is_mock_column("ref_price"));
+    assert_eq!((eth.price.as_ref(), eth.ref_price.as_ref()), (None, None));
+    assert!(eth.is_mock_column("price"));
+
+    // A reference already filled (e.g. from the kline store) is kept.
+    let mut stored =
+        DashboardRow::unresolved_with_times("btc-updown-5m-y", "BTC", "5m", start, start + 300);
+    stored.ref_price = Some("99".to_string());
+    apply_live_prices(&stream, [&mut stored]);
+    assert_eq!(stored.ref_price.as_deref(), Some("99"));
+    assert_eq!(stored.price.as_deref(), Some("101.25"));
+}
+
+#[test]
+fn disabled_stream_does_not_spawn() {
+    let cfg = BinanceWsConfig {
+        ws_url: None,
+        ..BinanceWsConfig::default()
+    };
+    assert!(BinanceWsStream::spawn(cfg.clone(), &[Coin::BTC]).is_none());
+    assert!(BinanceWsStream::spawn(BinanceWsConfig::default(), &[]).is_none());
+}
@@ -0,0 +1,97 @@
+use std::time::Duration as StdDuration;
+
+use futures_util::{SinkExt, StreamExt};
+use pmm::{
+    apply_order_books, BookSubscription, ClobBookStream, ClobStreamConfig, Coin, DashboardRow,
+    DiscoveryKey, DiscoveryRow, DiscoveryStatus, Duration, MarketMeta, OrderBookSource, SlugConfig,
+};
+use tokio::net::TcpListener;
+use tokio_tungstenite::tungstenite::Message;
+
+const START: i64 = 1_771_449_000;
+
+fn resolved_row(yes_token: &str) -> DiscoveryRow<MarketMeta> {
+    DiscoveryRow {
+        key: DiscoveryKey::new(Coin::BTC, Duration::M5, START, SlugConfig::default()).unwrap(),
+        status: DiscoveryStatus::Resolved {
+            market: MarketMeta {
+                token_ids: vec![yes_token.to_string(), "no".to_string()],
+                ..MarketMeta::default()
+            },
+        },
+    }
+}
+
+async fn wait_for<T>(mut probe: impl FnMut() -> Option<T>) -> T {
+    for _ in 0..200 {
+        if let Some(value) = probe() {
+            return value;
+        }
+        tokio::time::sleep(StdDuration::from_millis(10)).await;
+    }
+    panic!("condition not reached");
+}
+
+#[tokio::test]
+async fn stream_subscribes_to_yes_tokens_and_fills_best_prices() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let server = tokio::spawn(async move {
+        let (tcp, _) = listener.accept().await.unwrap();
+        let mut socket = tokio_tungstenite::accept_async(tcp).await.unwrap();
+        let subscribe = match socket.next().await.unwrap().unwrap() {
+            Message::Text(text) => serde_json::from_str::<serde_json::Value>(&text).unwrap(),
+            other => panic!("unexpected frame {other:?}"),
+        };
+        socket
+            .send(Message::Text(
+                r#"[{"event_type":"book","asset_id":"yes-1","market":"0x1",
+                    "bids":[{"price":"0.47","size":"12"}],
+                    "asks":[{"price":"0.52","size":"8"}]}]"#
+                    .into(),
+            ))
+            .await
+            .unwrap();
+        // Hold the connection open until the client goes away.
+        while let Some(Ok(_)) = socket.next().await {}
+        subscribe
+    });
+
+    let stream = ClobBookStream::spawn(ClobStreamConfig {
+        ws_url: Some(url),
+        ..ClobStreamConfig::default()
+    })
+    .unwrap();
+    let subscription = BookSubscription::from_discovery_row(&resolved_row("yes-1")).unwrap();
+    stream.subscribe(&[subscription]);
+
+    let slug = "btc-updown-5m-1771449000";
+    let top = wait_for(|| stream.top_of_book(slug)).await;
+    assert_eq!((top.best_bid, top.best_ask), (Some(0.47), Some(0.52)));
+
+    let mut row = DashboardRow::unresolved_with_times(slug, "BTC", "5m", START, START + 300);
+    assert!(row.is_mock_column("best_bid_yes"));
+    apply_order_books(&stream, [&mut row]);
+    assert_eq!(row.best_bid_yes.as_deref(), Some("0.47"));
+    assert_eq!(row.best_ask_yes.as_deref(), Some("0.52"));
+    assert!(!row.is_mock_column("best_bid_yes") && !row.is_mock_column("best_ask_yes"));
+
+    // A new token set reconnects; the old books are gone once the server hangs up.
+    stream.subscribe(&[]);
+    let subscribe = tokio::time::timeout(StdDuration::from_secs(5), server)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(subscribe["type"], "market");
+    assert_eq!(subscribe["assets_ids"], serde_json::json!(["yes-1"]));
+    assert_eq!(stream.top_of_book(slug), None);
+}
+
+#[test]
+fn disabled_stream_does_not_spawn() {
+    let cfg = ClobStreamConfig {
+        ws_url: None,
+        ..ClobStreamConfig::default()
+    };
+    assert!(ClobBookStream::spawn(cfg).is_none());
+}
@@ -0,0 +1,160 @@
+use axum::body::{to_bytes, Body};
+use axum::http::{Request, StatusCode};
+use axum::routing::get;
+use axum::{Json, Router};
+use chrono::Utc;
+use pmm::{
+    health_router, health_router_with_warmup, measure_clock_drift, ClockDriftConfig,
+    ClockDriftLevel, ClockDriftMonitor, ClockSource, HealthReport, WarmupPhase, WarmupTracker,
+};
+use tower::ServiceExt;
+
+const SKEW_MS: i64 = 5_000;
+const NTP_UNIX_EPOCH_DELTA_SECS: i64 = 2_208_988_800;
+
+fn ntp_timestamp(ms: i64) -> [u8; 8] {
+    let secs = (ms.div_euclid(1_000) + NTP_UNIX_EPOCH_DELTA_SECS) as u32;
+    let frac = (((ms.rem_euclid(1_000) as u64) << 32) / 1_000) as u32;
+    let mut out = [0u8; 8];
+    out[..4].copy_from_slice(&secs.to_be_bytes());
+    out[4..].copy_from_slice(&frac.to_be_bytes());
+    out
+}
+
+/// SNTP server whose clock runs `SKEW_MS` ahead of ours.
+async fn skewed_ntp_server() -> String {
+    let socket = tokio::net::UdpSocket::bind("127.0.0.1:0")
+        .await
+        .expect("bind udp");
+    let addr = socket.local_addr().expect("addr");
+    tokio::spawn(async move {
+        let mut buf = [0u8; 48];
+        loop {
+            let Ok((len, peer)) = socket.recv_from(&mut buf).await else {
+                return;
+            };
+            if len < 48 {
+                continue;
+            }
+            let now = Utc::now().timestamp_millis() + SKEW_MS;
+            let mut reply = [0u8; 48];
+            reply[0] = 0x1C;
+            reply[1] = 2;
+            reply[24..32].copy_from_slice(&buf[40..48]);
+            reply[32..40].copy_from_slice(&ntp_timestamp(now));
+            reply[40..48].copy_from_slice(&ntp_timestamp(now));
+            let _ = socket.send_to(&reply, peer).await;
+        }
+    });
+    addr.to_string()
+}
+
+async fn skewed_binance_time() -> String {
+    let app = Router::new().route(
+        "/api/v3/time",
+        get(|| async {
+            Json(serde_json::json!({ "serverTime": Utc::now().timestamp_millis() + SKEW_MS }))
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind");
+    let addr = listener.local_addr().expect("addr");
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.expect("serve");
+    });
+    format!("http://{addr}/api/v3/time")
+}
+
+#[tokio::test]
+async fn skewed_sources_raise_alert_and_degrade_healthz() {
+    let cfg = ClockDriftConfig {
+        ntp_server: Some(skewed_ntp_server().await),
+        binance_time_url: Some(skewed_binance_time().await),
+        ..ClockDriftConfig::default()
+    };
+
+    let status = measure_clock_drift(&cfg).await;
+    assert!(status.errors.is_empty(), "{:?}", status.errors);
+    assert_eq!(
+        status
+            .samples
+            .iter()
+            .map(|sample| sample.source)
+            .collect::<Vec<_>>(),
+        vec![ClockSource::Ntp, ClockSource::Binance]
+    );
+    for sample in &status.samples {
+        assert!(
+            (sample.offset_ms - SKEW_MS).abs() < 200,
+            "offset {} for {:?}",
+            sample.offset_ms,
+            sample.source
+        );
+    }
+    assert_eq!(status.level, ClockDriftLevel::Alert);
+
+    let monitor = ClockDriftMonitor::default();
+    let response = health_router(Some(monitor.clone()))
+        .oneshot(Request::get("/healthz").body(Body::empty()).unwrap())
+        .await
+        .expect("healthz");
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let report: HealthReport = serde_json::from_slice(&body).expect("json");
+    assert_eq!(report.status, "ok");
+    assert!(report.clock.is_none());
+
+    monitor.record(status.clone());
+    let response = health_router(Some(monitor))
+        .oneshot(Request::get("/healthz").body(Body::empty()).unwrap())
+        .await
+        .expect("healthz");
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let report: HealthReport = serde_json::from_slice(&body).expect("json");
+    assert_eq!(report.status, "degraded");
+    assert_eq!(report.clock, Some(status));
+}
+
+#[tokio::test]
+async fn unreachable_sources_report_unknown() {
+    let cfg = ClockDriftConfig {
+        ntp_server: None,
+        binance_time_url: Some("http://127.0.0.1:1/api/v3/time".to_string()),
+        timeout_ms: 500,
+        ..ClockDriftConfig::default()
+    };
+    let status = measure_clock_drift(&cfg).await;
+    assert_eq!(status.level, ClockDriftLevel::Unknown);
+    assert_eq!(status.offset_ms, None);
+    assert_eq!(status.errors.len(), 1);
+    assert_eq!(status.errors[0].source, ClockSource::Binance);
+}
+
+#[tokio::test]
+async fn healthz_reports_warmup_progress() {
+    let tracker = WarmupTracker::new();
+    let response = health_router_with_warmup(None, Some(tracker.clone()))
+        .oneshot(Request::get("/healthz").body(Body::empty()).unwrap())
+        .await
+        .expect("healthz");
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let report: HealthReport = serde_json::from_slice(&body).expect("json");
+    assert_eq!(report.status, "ok");
+    let warmup = report.warmup.expect("warmup progress");
+    assert_eq!(warmup.phase, WarmupPhase::Pending);
+    assert!(!warmup.quoting_enabled);
+
+    tracker.fail("store unreadable");
+    let response = health_router_with_warmup(None, Some(tracker))
+        .oneshot(Request::get("/healthz").body(Body::empty()).unwrap())
+        .await
+        .expect("healthz");
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let report: HealthReport = serde_json::from_slice(&body).expect("json");
+    assert_eq!(report.status, "degraded");
+    assert_eq!(
+        report.warmup.and_then(|warmup| warmup.error).as_deref(),
+        Some("store unreadable")
+    );
+}
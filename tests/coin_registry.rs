@@ -0,0 +1,62 @@
+use pmm::{
+    build_slug, demo_snapshot_at, parse_coin, parse_slug_at, Coin, CoinRegistry, CoinSpec,
+    DiscoveryUniverse, Duration, SlugConfig, SlugError,
+};
+
+#[test]
+fn registered_coin_builds_and_parses_slugs() {
+    let registry = CoinRegistry::global();
+    let doge = registry
+        .register(CoinSpec::parse("DOGE:doge:dogecoin:DOGEUSDT").unwrap())
+        .unwrap();
+    assert_eq!(
+        registry.register(CoinSpec::new("DOGE", "doge", "dogecoin", "DOGEUSDT")),
+        Ok(doge)
+    );
+    assert_eq!(parse_coin("DOGE"), Ok(doge));
+    assert_eq!(doge.ticker(), "DOGE");
+    assert!(Coin::all().contains(&doge));
+    assert!(DiscoveryUniverse::default().coins.contains(&doge));
+    assert!(demo_snapshot_at(1_771_449_000)
+        .rows
+        .iter()
+        .any(|row| row.coin == "DOGE"));
+
+    let cfg = SlugConfig::default();
+    let start = 1_771_449_000;
+    let slug = build_slug(doge, Duration::M5, start, cfg).unwrap();
+    assert_eq!(slug, "doge-updown-5m-1771449000");
+    assert_eq!(parse_slug_at(&slug, start), Ok((doge, Duration::M5, start)));
+
+    let hourly = build_slug(doge, Duration::H1, 1_736_971_200, cfg).unwrap();
+    assert_eq!(hourly, "dogecoin-up-or-down-january-15-3pm-et");
+    assert_eq!(
+        parse_slug_at(&hourly, 1_736_971_200),
+        Ok((doge, Duration::H1, 1_736_971_200))
+    );
+
+    assert_eq!(serde_json::to_string(&doge).unwrap(), "\"DOGE\"");
+    assert_eq!(serde_json::from_str::<Coin>("\"DOGE\"").unwrap(), doge);
+}
+
+#[test]
+fn clashing_or_malformed_specs_are_rejected() {
+    let registry = CoinRegistry::global();
+    assert!(matches!(
+        registry.register(CoinSpec::new("BTC", "btc2", "bitcoin2", "BTCUSDT")),
+        Err(SlugError::InvalidCoinSpec(_))
+    ));
+    assert!(matches!(
+        registry.register(CoinSpec::new("WBTC", "wbtc", "bitcoin", "WBTCUSDT")),
+        Err(SlugError::InvalidCoinSpec(_))
+    ));
+    assert!(matches!(
+        CoinSpec::parse("ADA:ada:cardano"),
+        Err(SlugError::InvalidCoinSpec(_))
+    ));
+    assert!(matches!(
+        CoinSpec::parse("ADA:Ada:cardano:ADAUSDT"),
+        Err(SlugError::InvalidCoinSpec(_))
+    ));
+    assert!(parse_coin("ADA").is_err());
+}
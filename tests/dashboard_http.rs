@@ -4,8 +4,15 @@ use axum::{
     body::{to_bytes, Body},
     http::{Request, StatusCode},
 };
+use futures_util::StreamExt;
 use pmm::{
-    dashboard_router, demo_snapshot, DashboardRow, DashboardSnapshot, InMemoryMockSnapshotSource,
+    alerts_router, dashboard_router, dashboard_router_with_redaction, dashboard_router_with_views,
+    demo_snapshot, grafana_router, journal_router, snapshot_history_router, strategy_toggle_router,
+    with_dashboard_token, ActivityFeedView, ActivityKind, AlertManager, AlertStore, CapitalUsage,
+    DashboardPush, DashboardRow, DashboardSnapshot, DashboardSnapshotSource, DashboardViews,
+    InMemoryMockSnapshotSource, MarketToggles, NumberFormatConfig, OccupancyGrid, ReadinessReport,
+    RedactionConfig, SnapshotHistory, SnapshotProvenance, SnapshotRecorder, SnapshotStatus,
+    SnapshotStore, TradeJournal, WalletReport, WindowOccupancy, DASHBOARD_COLUMN_KEYS,
 };
 use tower::util::ServiceExt;
 
@@ -20,8 +27,15 @@ fn row(coin: &str, duration: &str, start: i64, end: i64, bets_open: Option<&str>
         in_interval: None,
         end_hhmm: None,
         ref_price: Some("0.4987654".to_string()),
+        ref_capture_delay_ms: None,
         price: Some("0.5123456".to_string()),
         probability: Some("0.5".to_string()),
+        probability_raw: None,
+        probability_smoothed: None,
+        probability_horizons: Default::default(),
+        realized_vol: None,
+        price_vs_ref_pct: None,
+        move_z: None,
         best_bid_yes: Some("0.49".to_string()),
         best_ask_yes: Some("0.51".to_string()),
         position_net: Some("1.23456@0.5@YES".to_string()),
@@ -29,19 +43,31 @@ fn row(coin: &str, duration: &str, start: i64, end: i64, bets_open: Option<&str>
         pos_no: None,
         offer_yes: Some("1.9@0.51".to_string()),
         offer_no: Some("1.8@0.49".to_string()),
+        queue_ahead_yes: None,
+        queue_ahead_no: None,
+        suggested_size: None,
         net_profit: Some("0.001234".to_string()),
         taker_fee_pct: Some("0.25".to_string()),
         maker_fee_pct: Some("-0.05".to_string()),
         fee_exponent: Some("2".to_string()),
         reward_pct: Some("0.004567".to_string()),
+        volume_24h: None,
+        liquidity: None,
         mock_columns: vec!["price".to_string()],
+        simulated_columns: Vec::new(),
+        stale_as_of_ts_utc: None,
+        accepting_orders_ts_utc: None,
+        neg_risk_group: None,
     }
 }
 
 #[tokio::test]
 async fn dashboard_page_returns_table_filters_and_polling_script() {
+    let mut grouped = row("ETH", "1d", 100, 200, Some("open"));
+    grouped.neg_risk_group = Some("0x42".to_string());
     let source = Arc::new(InMemoryMockSnapshotSource::new(DashboardSnapshot {
-        rows: vec![row("BTC", "5m", 100, 200, Some("open"))],
+        rows: vec![row("BTC", "5m", 100, 200, Some("open")), grouped],
+        seq: 0,
     }));
 
     let app = dashboard_router(source);
@@ -70,6 +96,64 @@ async fn dashboard_page_returns_table_filters_and_polling_script() {
     assert!(text.contains("setInterval(refresh, 250)"));
     assert!(text.contains("market-btn"));
     assert!(!text.contains("btn-apply"));
+    // Only the neg-risk row carries the badge (the client script renders it too).
+    assert_eq!(
+        text.matches("<span class=\"neg-risk\" title=\"Neg-risk event 0x42")
+            .count(),
+        1
+    );
+    assert!(!text.contains("Neg-risk event btc-"));
+}
+
+#[tokio::test]
+async fn duration_route_presets_the_filter_cadence_and_columns() {
+    let source = Arc::new(InMemoryMockSnapshotSource::new(DashboardSnapshot {
+        rows: vec![
+            row("BTC", "5m", 100, 200, Some("open")),
+            row("BTC", "1h", 100, 3_700, Some("open")),
+            row("ETH", "1h", 100, 3_700, Some("open")),
+        ],
+        seq: 0,
+    }));
+    let app = dashboard_router(source);
+    let get = |uri: &str| {
+        app.clone()
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+    };
+
+    // The route wins over a conflicting duration in the query string.
+    let response = get("/dashboard/1h?coin=BTC&duration=5m").await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let text = String::from_utf8(body.to_vec()).unwrap();
+    assert!(text.contains("<b id=\"row-count\">1</b>"));
+    assert!(text.contains("btc-1h-100"));
+    assert!(!text.contains("btc-5m-100"));
+    assert!(text.contains("action=\"/dashboard/1h\""));
+    assert!(text.contains("const durationPreset = '1h';"));
+    assert!(text.contains("setInterval(refresh, 1000)"));
+    // 1h markets are fee-free: the fee and reward columns (22-25) are hidden.
+    assert!(text.contains("#dashboard-table td:nth-child(25){display:none}"));
+    assert!(text.contains("#dashboard-table td:nth-child(22),"));
+    assert!(!text.contains("name=\"duration\""));
+
+    // Fee-charging durations keep every column and the fastest cadence.
+    let body = to_bytes(get("/dashboard/5m").await.unwrap().into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let text = String::from_utf8(body.to_vec()).unwrap();
+    assert!(text.contains("setInterval(refresh, 250)"));
+    assert!(!text.contains("td:nth-child("));
+
+    // The fixed sub-routes still take precedence, and unknown durations are a 404.
+    assert_eq!(
+        get("/dashboard/snapshot").await.unwrap().status(),
+        StatusCode::OK
+    );
+    assert_eq!(
+        get("/dashboard/2h").await.unwrap().status(),
+        StatusCode::NOT_FOUND
+    );
 }
 
 #[tokio::test]
@@ -81,10 +165,12 @@ async fn snapshot_endpoint_applies_query_filters() {
             row("BTC", "5m", 100, 300, Some("open")),
             row("BTC", "1h", 100, 300, Some("closed")),
         ],
+        seq: 0,
     }));
 
     let app = dashboard_router(source);
     let response = app
+        .clone()
         .oneshot(
             Request::builder()
                 .uri("/dashboard/snapshot?coin=BTC&duration=1h&bets_open=open")
@@ -103,6 +189,30 @@ async fn snapshot_endpoint_applies_query_filters() {
     assert_eq!(rows[0]["coin"], "BTC");
     assert_eq!(rows[0]["duration"], "1h");
     assert_eq!(rows[0]["bets_open"], "open");
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/dashboard/snapshot?bets_open=open&sort_by=coin&order=desc&limit=2&offset=1")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let page: Vec<(&str, &str)> = json["rows"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|row| {
+            (
+                row["coin"].as_str().unwrap(),
+                row["duration"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(page, [("BTC", "1h"), ("BTC", "5m")]);
 }
 
 #[tokio::test]
@@ -113,6 +223,7 @@ async fn snapshot_endpoint_supports_repeated_coin_query_params() {
             row("ETH", "1h", 100, 300, Some("open")),
             row("SOL", "1h", 100, 300, Some("open")),
         ],
+        seq: 0,
     }));
 
     let app = dashboard_router(source);
@@ -145,6 +256,7 @@ async fn snapshot_endpoint_applies_in_interval_boundary_logic() {
             row("BTC", "5m", now - 60, now + 240, Some("open")),
             row("ETH", "5m", now - 300, now, Some("open")),
         ],
+        seq: 0,
     }));
 
     let app = dashboard_router(source);
@@ -175,6 +287,7 @@ async fn snapshot_endpoint_formats_values_and_preserves_unresolved_rows() {
             row("BTC", "5m", 100, 200, Some("open")),
             DashboardRow::unresolved_with_times("xrp-5m-1", "XRP", "5m", 100, 200),
         ],
+        seq: 0,
     }));
 
     let app = dashboard_router(source);
@@ -230,3 +343,1009 @@ async fn demo_snapshot_route_exposes_60_rows() {
     let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
     assert_eq!(json["rows"].as_array().unwrap().len(), 60);
 }
+
+struct UnavailableSource;
+
+impl DashboardSnapshotSource for UnavailableSource {
+    fn snapshot(&self) -> Arc<DashboardSnapshot> {
+        Arc::new(DashboardSnapshot {
+            rows: vec![row("BTC", "5m", 100, 200, None)],
+            seq: 0,
+        })
+    }
+
+    fn status(&self) -> SnapshotStatus {
+        let mut status = SnapshotStatus::awaiting_live(1_700_000_000);
+        status.record_live_failure(1_700_000_015, "discovery cycle exceeded 15000ms deadline");
+        status.provenance = SnapshotProvenance::Placeholder;
+        status
+    }
+}
+
+#[tokio::test]
+async fn unavailable_live_source_surfaces_banner_and_status() {
+    let app = dashboard_router(Arc::new(UnavailableSource));
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/dashboard")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let text = String::from_utf8(body.to_vec()).unwrap();
+    assert!(text.contains("id=\"live-banner\""));
+    assert!(text.contains("data-since-ts=\"1700000000\">"));
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/dashboard/snapshot")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let status = &json["source_status"];
+    assert_eq!(status["provenance"], "placeholder");
+    assert_eq!(status["consecutive_failures"], 1);
+    assert_eq!(status["live_unavailable_since_ts_utc"], 1_700_000_000);
+}
+
+async fn next_sse_frame(stream: &mut axum::body::BodyDataStream) -> String {
+    let chunk = tokio::time::timeout(std::time::Duration::from_secs(5), stream.next())
+        .await
+        .expect("stream event within timeout")
+        .expect("stream still open")
+        .unwrap();
+    String::from_utf8(chunk.to_vec()).unwrap()
+}
+
+fn sse_data(frame: &str) -> serde_json::Value {
+    let data = frame
+        .lines()
+        .find_map(|line| line.strip_prefix("data: "))
+        .expect("data line");
+    serde_json::from_str(data).unwrap()
+}
+
+#[tokio::test]
+async fn stream_opens_with_full_snapshot_then_sends_seq_linked_deltas() {
+    let source = Arc::new(InMemoryMockSnapshotSource::new(DashboardSnapshot {
+        rows: vec![row("BTC", "5m", 100, 200, Some("open"))],
+        seq: 0,
+    }));
+    let app = dashboard_router(source.clone());
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/dashboard/stream?coin=BTC")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()["content-type"],
+        "text/event-stream"
+            .parse::<axum::http::HeaderValue>()
+            .unwrap()
+    );
+    let mut stream = response.into_body().into_data_stream();
+
+    let frame = next_sse_frame(&mut stream).await;
+    assert!(frame.contains("event: snapshot"));
+    assert!(frame.contains("id: 0"));
+    let snapshot = sse_data(&frame);
+    assert_eq!(snapshot["seq"], 0);
+    assert_eq!(snapshot["rows"].as_array().unwrap().len(), 1);
+
+    let seq = source.replace_snapshot(DashboardSnapshot {
+        rows: vec![
+            row("BTC", "5m", 100, 200, Some("open")),
+            row("BTC", "15m", 100, 1_000, Some("open")),
+            row("ETH", "5m", 100, 200, Some("open")),
+        ],
+        seq: 0,
+    });
+    assert_eq!(seq, 1);
+
+    let frame = next_sse_frame(&mut stream).await;
+    assert!(frame.contains("event: delta"));
+    assert!(frame.contains("id: 1"));
+    let delta = sse_data(&frame);
+    assert_eq!(delta["prev_seq"], 0);
+    assert_eq!(delta["seq"], 1);
+    assert_eq!(delta["upserts"].as_array().unwrap().len(), 1);
+    assert_eq!(delta["upserts"][0]["slug"], "btc-15m-100");
+    assert_eq!(delta["order"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn stream_min_interval_slows_updates_for_hidden_tabs() {
+    let source = Arc::new(InMemoryMockSnapshotSource::new(DashboardSnapshot {
+        rows: vec![row("BTC", "5m", 100, 200, Some("open"))],
+        seq: 0,
+    }));
+    let app = dashboard_router(source.clone());
+
+    let html = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/dashboard")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body = to_bytes(html.into_body(), usize::MAX).await.unwrap();
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    assert!(html.contains("id=\"paused-badge\""));
+    assert!(html.contains("visibilitychange"));
+    assert!(html.contains("slowed.set('min_interval', '10000')"));
+    assert!(html.contains("/dashboard/ws"));
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/dashboard/stream?coin=BTC&min_interval=1500")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let mut stream = response.into_body().into_data_stream();
+    assert!(next_sse_frame(&mut stream)
+        .await
+        .contains("event: snapshot"));
+
+    source.replace_snapshot(DashboardSnapshot {
+        rows: vec![row("BTC", "15m", 100, 1_000, Some("open"))],
+        seq: 0,
+    });
+    // The 5m cadence would send the delta within 250ms; the guard holds it back.
+    assert!(
+        tokio::time::timeout(std::time::Duration::from_millis(800), stream.next())
+            .await
+            .is_err()
+    );
+    let frame = next_sse_frame(&mut stream).await;
+    assert!(frame.contains("event: delta"));
+    assert_eq!(sse_data(&frame)["prev_seq"], 0);
+}
+
+async fn next_push(
+    socket: &mut tokio_tungstenite::WebSocketStream<
+        tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
+    >,
+    within: std::time::Duration,
+) -> Option<DashboardPush> {
+    let frame = tokio::time::timeout(within, socket.next()).await.ok()?;
+    let text = frame
+        .expect("socket still open")
+        .unwrap()
+        .into_text()
+        .unwrap();
+    Some(serde_json::from_str(&text).unwrap())
+}
+
+#[tokio::test]
+async fn websocket_pushes_a_snapshot_then_deltas_only_on_change() {
+    let source = Arc::new(InMemoryMockSnapshotSource::new(DashboardSnapshot {
+        rows: vec![row("BTC", "5m", 100, 200, Some("open"))],
+        seq: 0,
+    }));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = dashboard_router(source.clone());
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    let (mut socket, _) =
+        tokio_tungstenite::connect_async(format!("ws://{addr}/dashboard/ws?coin=BTC"))
+            .await
+            .unwrap();
+    let second = std::time::Duration::from_secs(1);
+    match next_push(&mut socket, second).await {
+        Some(DashboardPush::Snapshot(snapshot)) => {
+            assert_eq!(snapshot.seq, 0);
+            assert_eq!(snapshot.rows.len(), 1);
+        }
+        other => panic!("expected a snapshot, got {other:?}"),
+    }
+    // Nothing changed, so nothing is sent.
+    assert_eq!(next_push(&mut socket, 2 * second).await, None);
+
+    source.replace_snapshot(DashboardSnapshot {
+        rows: vec![
+            row("BTC", "5m", 100, 200, Some("open")),
+            row("BTC", "15m", 100, 1_000, Some("open")),
+            row("ETH", "5m", 100, 200, Some("open")),
+        ],
+        seq: 0,
+    });
+    match next_push(&mut socket, second).await {
+        Some(DashboardPush::Delta(delta)) => {
+            assert_eq!((delta.prev_seq, delta.seq), (0, 1));
+            assert_eq!(delta.upserts.len(), 1);
+            assert_eq!(delta.upserts[0].slug, "btc-15m-100");
+            assert_eq!(delta.order.len(), 2);
+        }
+        other => panic!("expected a delta, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn occupancy_endpoint_flags_exposure_left_in_ended_windows() {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    let start = now - now.rem_euclid(300);
+    // BTC 5m: position left in the previous window, orders in the active one.
+    let previous = row("BTC", "5m", start - 300, start, Some("closed"));
+    let mut active = row("BTC", "5m", start, start + 300, Some("open"));
+    active.pos_yes = None;
+    active.position_net = Some("0".to_string());
+    // ETH 15m next window: only mock positions and dry-run quotes, which hold nothing.
+    let mut next = row("ETH", "15m", start + 900, start + 1_800, Some("open"));
+    next.mock_columns = vec![
+        "position_net".to_string(),
+        "pos_yes".to_string(),
+        "offer_no".to_string(),
+    ];
+    next.simulated_columns = vec!["offer_yes".to_string()];
+    let source = Arc::new(InMemoryMockSnapshotSource::new(DashboardSnapshot {
+        rows: vec![previous, active, next],
+        seq: 0,
+    }));
+
+    let app = dashboard_router(source);
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/dashboard/occupancy?coin=ETH")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let grid: OccupancyGrid = serde_json::from_slice(&body).unwrap();
+
+    // Always the full 4x5 grid, whatever the dashboard filters.
+    assert_eq!(grid.cells.len(), 20);
+    assert_eq!(grid.attention_count, 1);
+    let btc_5m = &grid.cells[0];
+    assert_eq!(
+        (btc_5m.coin.as_str(), btc_5m.duration.as_str()),
+        ("BTC", "5m")
+    );
+    assert_eq!(btc_5m.previous, WindowOccupancy::PositionAndOrders);
+    assert_eq!(btc_5m.active, WindowOccupancy::Orders);
+    assert_eq!(btc_5m.next, WindowOccupancy::Empty);
+    assert!(btc_5m.needs_attention);
+    assert!(grid
+        .cells
+        .iter()
+        .skip(1)
+        .all(|cell| !cell.needs_attention && cell.active == WindowOccupancy::Empty));
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/dashboard")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let html = String::from_utf8(
+        to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap()
+            .to_vec(),
+    )
+    .unwrap();
+    assert!(html.contains("id=\"occupancy-body\""));
+    assert_eq!(html.matches("<td class=\"occ-attention\"").count(), 1);
+    assert!(html.contains("/dashboard/occupancy"));
+}
+
+#[tokio::test]
+async fn activity_endpoint_lists_changes_between_published_snapshots() {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    let start = now - now.rem_euclid(3_600);
+    let mut unresolved = row("SOL", "1h", start, start + 3_600, None);
+    unresolved.pos_yes = None;
+    let source = Arc::new(InMemoryMockSnapshotSource::new(DashboardSnapshot {
+        rows: vec![unresolved.clone()],
+        seq: 0,
+    }));
+    let app = dashboard_router(source.clone());
+
+    let fetch = |uri: &'static str| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            to_bytes(response.into_body(), usize::MAX).await.unwrap()
+        }
+    };
+
+    // The first observation is the baseline.
+    let feed: ActivityFeedView =
+        serde_json::from_slice(&fetch("/dashboard/activity").await).unwrap();
+    assert!(feed.events.is_empty());
+
+    let mut resolved = unresolved;
+    resolved.bets_open = Some("open".to_string());
+    resolved.probability = Some("0.7".to_string());
+    resolved.pos_yes = Some("10@0.6".to_string());
+    source.replace_snapshot(DashboardSnapshot {
+        rows: vec![resolved],
+        seq: 0,
+    });
+    // Let the feed's follower observe the publication.
+    tokio::task::yield_now().await;
+
+    let feed: ActivityFeedView =
+        serde_json::from_slice(&fetch("/dashboard/activity?coin=BTC").await).unwrap();
+    let kinds: Vec<_> = feed.events.iter().map(|event| event.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            ActivityKind::RowResolved,
+            ActivityKind::ProbabilityMove,
+            ActivityKind::Fill
+        ]
+    );
+    assert_eq!(feed.events[2].detail, "YES 0 -> 10");
+
+    let html = String::from_utf8(fetch("/dashboard").await.to_vec()).unwrap();
+    assert!(html.contains("id=\"activity-list\""));
+    for kind in ["row_resolved", "probability_move", "fill"] {
+        assert!(html.contains(&format!("<li class=\"act act-{kind}\">")));
+    }
+    assert!(html.contains("/dashboard/activity"));
+}
+
+#[tokio::test]
+async fn activity_feed_follows_publications_without_requests() {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    let start = now - now.rem_euclid(3_600);
+    let base = row("SOL", "1h", start, start + 3_600, Some("open"));
+    let source = Arc::new(InMemoryMockSnapshotSource::new(DashboardSnapshot {
+        rows: vec![base.clone()],
+        seq: 0,
+    }));
+    let app = dashboard_router(source.clone());
+
+    // Up and back down: a feed that only compared the first and last snapshot would
+    // see no move at all.
+    for probability in ["0.6", "0.5"] {
+        let mut next = base.clone();
+        next.probability = Some(probability.to_string());
+        source.replace_snapshot(DashboardSnapshot {
+            rows: vec![next],
+            seq: 0,
+        });
+        tokio::task::yield_now().await;
+    }
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/dashboard/activity")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let feed: ActivityFeedView =
+        serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+    let moves: Vec<_> = feed
+        .events
+        .iter()
+        .map(|event| (event.kind, event.detail.as_str()))
+        .collect();
+    assert_eq!(
+        moves,
+        [
+            (ActivityKind::ProbabilityMove, "60.0% -> 50.0%"),
+            (ActivityKind::ProbabilityMove, "50.0% -> 60.0%"),
+        ]
+    );
+}
+
+struct WalletSource(Option<CapitalUsage>);
+
+impl DashboardSnapshotSource for WalletSource {
+    fn snapshot(&self) -> Arc<DashboardSnapshot> {
+        Arc::new(demo_snapshot())
+    }
+
+    fn capital_usage(&self) -> Option<CapitalUsage> {
+        self.0.clone()
+    }
+}
+
+async fn wallet_report(source: WalletSource) -> WalletReport {
+    let response = dashboard_router(Arc::new(source))
+        .oneshot(
+            Request::builder()
+                .uri("/wallet")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[tokio::test]
+async fn wallet_endpoint_reports_headroom_and_quote_throttle() {
+    let idle = wallet_report(WalletSource(None)).await;
+    assert_eq!(idle.capital, None);
+    assert_eq!(idle.quote_scale, 1.0);
+
+    // 95 of 100 USDC locked by bids: 5 USDC of headroom is under the 10 USDC floor.
+    let usage = CapitalUsage {
+        balance_usdc: Some(100.0),
+        order_collateral_usdc: 95.0,
+        open_orders: 12,
+        headroom_usdc: Some(5.0),
+        utilization: Some(0.95),
+        ..CapitalUsage::default()
+    };
+    let tight = wallet_report(WalletSource(Some(usage.clone()))).await;
+    assert_eq!(tight.capital, Some(usage));
+    assert_eq!(tight.quote_scale, 0.0);
+
+    let response = dashboard_router(Arc::new(WalletSource(None)))
+        .oneshot(
+            Request::builder()
+                .uri("/dashboard")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let html = String::from_utf8(
+        to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap()
+            .to_vec(),
+    )
+    .unwrap();
+    assert!(html.contains("id=\"wallet-headroom\""));
+    assert!(html.contains("fetch('/wallet'"));
+}
+
+struct StatusSource(SnapshotStatus);
+
+impl DashboardSnapshotSource for StatusSource {
+    fn snapshot(&self) -> Arc<DashboardSnapshot> {
+        Arc::new(demo_snapshot())
+    }
+
+    fn status(&self) -> SnapshotStatus {
+        self.0.clone()
+    }
+}
+
+async fn readyz(source: Arc<dyn DashboardSnapshotSource>) -> (StatusCode, ReadinessReport) {
+    let response = dashboard_router(source)
+        .oneshot(
+            Request::builder()
+                .uri("/readyz")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn readyz_waits_for_fresh_live_rows() {
+    let now = chrono::Utc::now().timestamp();
+    let (code, report) = readyz(Arc::new(StatusSource(SnapshotStatus::awaiting_live(now)))).await;
+    assert_eq!(code, StatusCode::SERVICE_UNAVAILABLE);
+    assert!(!report.ready);
+    let failing: Vec<_> = report
+        .checks
+        .iter()
+        .filter(|check| !check.ok)
+        .map(|check| check.name.as_str())
+        .collect();
+    assert_eq!(failing, ["snapshot", "discovery"]);
+
+    let mut live = SnapshotStatus::awaiting_live(now - 3_600);
+    live.record_live_success(now - 2);
+    let (code, report) = readyz(Arc::new(StatusSource(live.clone()))).await;
+    assert_eq!(code, StatusCode::OK);
+    assert!(report.ready);
+
+    // A live source whose refreshes stopped an hour ago is no longer ready.
+    live.record_live_success(now - 3_600);
+    live.record_live_failure(now - 3_590, "gamma unavailable");
+    let (code, report) = readyz(Arc::new(StatusSource(live))).await;
+    assert_eq!(code, StatusCode::SERVICE_UNAVAILABLE);
+    assert!(report.checks[0].ok && !report.checks[1].ok);
+
+    // Caller-supplied rows run no discovery loop.
+    let (code, _) = readyz(Arc::new(InMemoryMockSnapshotSource::demo())).await;
+    assert_eq!(code, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn public_view_redacts_private_columns_without_the_token() {
+    let now = chrono::Utc::now().timestamp();
+    let source = Arc::new(InMemoryMockSnapshotSource::new(DashboardSnapshot {
+        rows: vec![row("BTC", "5m", now - 60, now + 240, Some("open"))],
+        seq: 0,
+    }));
+    let app = |public_columns: Option<&[&str]>| {
+        let mut redaction = RedactionConfig {
+            token: Some("s3cret".to_string()),
+            ..RedactionConfig::default()
+        };
+        if let Some(columns) = public_columns {
+            redaction.public_columns = columns.iter().map(|c| c.to_string()).collect();
+        }
+        dashboard_router_with_redaction(source.clone(), NumberFormatConfig::default(), redaction)
+    };
+    let get = |app: axum::Router, uri: &str, auth: Option<(&str, &str)>| {
+        let mut request = Request::builder().uri(uri);
+        if let Some((name, value)) = auth {
+            request = request.header(name, value);
+        }
+        let request = request.body(Body::empty()).unwrap();
+        async move {
+            let response = app.oneshot(request).await.unwrap();
+            let status = response.status();
+            let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            (status, String::from_utf8(body.to_vec()).unwrap())
+        }
+    };
+
+    // Public JSON leaves positions, orders, and PnL out entirely.
+    let (_, body) = get(app(None), "/dashboard/snapshot", None).await;
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    let public_row = json["rows"][0].as_object().unwrap();
+    for column in [
+        "position_net",
+        "pos_yes",
+        "offer_yes",
+        "offer_no",
+        "net_profit",
+    ] {
+        assert!(!public_row.contains_key(column), "{column} leaked");
+    }
+    assert_eq!(public_row["probability"], "50%");
+    assert!(!body.contains("1.2@0.5"));
+
+    // The token, as a bearer header or cookie, unlocks the full view.
+    let bearer = Some(("authorization", "Bearer s3cret"));
+    let (_, body) = get(app(None), "/dashboard/snapshot", bearer).await;
+    let full: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert!(full["rows"][0]["pos_yes"].is_string());
+    let wrong = Some(("authorization", "Bearer nope"));
+    let (_, body) = get(app(None), "/dashboard/snapshot", wrong).await;
+    assert!(!body.contains("pos_yes"));
+
+    let (_, html) = get(app(None), "/dashboard", None).await;
+    assert!(html.contains("<td class=\"\">–</td>"));
+    assert!(!html.contains("id=\"occupancy-body\""));
+    assert!(!html.contains("id=\"wallet-headroom\""));
+    let cookie = Some(("cookie", "theme=dark; pmm_token=s3cret"));
+    let (_, html) = get(app(None), "/dashboard", cookie).await;
+    assert!(html.contains("id=\"occupancy-body\""));
+    assert!(!html.contains("<td class=\"\">–</td>"));
+
+    for uri in ["/wallet", "/dashboard/occupancy"] {
+        assert_eq!(get(app(None), uri, None).await.0, StatusCode::UNAUTHORIZED);
+        assert_eq!(get(app(None), uri, bearer).await.0, StatusCode::OK);
+    }
+
+    // A narrower whitelist also withholds market columns.
+    let (_, body) = get(app(Some(&["price"])), "/dashboard/snapshot", None).await;
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert!(json["rows"][0]["price"].is_string());
+    assert!(json["rows"][0].get("probability").is_none());
+}
+
+#[tokio::test]
+async fn dashboard_router_ignores_the_token_in_the_environment() {
+    std::env::set_var("PMM_DASHBOARD_TOKEN", "from-env");
+    let now = chrono::Utc::now().timestamp();
+    let source = Arc::new(InMemoryMockSnapshotSource::new(DashboardSnapshot {
+        rows: vec![row("BTC", "5m", now - 60, now + 240, Some("open"))],
+        seq: 0,
+    }));
+    let response = dashboard_router(source)
+        .oneshot(
+            Request::builder()
+                .uri("/dashboard/snapshot")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert!(json["rows"][0]["pos_yes"].is_string());
+}
+
+#[tokio::test]
+async fn token_covers_the_routers_merged_beside_the_dashboard() {
+    let dir = tempfile::tempdir().unwrap();
+    let now = chrono::Utc::now().timestamp();
+    let snapshot = DashboardSnapshot {
+        rows: vec![row("BTC", "5m", now - 60, now + 240, Some("open"))],
+        seq: 1,
+    };
+    let slug = snapshot.rows[0].slug.clone();
+    let source = Arc::new(InMemoryMockSnapshotSource::new(snapshot.clone()));
+    let history = SnapshotStore::open(&dir.path().join("history.sqlite")).unwrap();
+    history.append(&snapshot, now * 1_000).unwrap();
+    let recorder = SnapshotRecorder::new(86_400);
+    recorder.record(&snapshot, now * 1_000);
+    let alerts =
+        AlertManager::new(AlertStore::open(&dir.path().join("alerts.sqlite"), 60).unwrap());
+    let redaction = RedactionConfig {
+        token: Some("s3cret".to_string()),
+        ..RedactionConfig::default()
+    };
+    let app = with_dashboard_token(
+        dashboard_router_with_redaction(source, NumberFormatConfig::default(), redaction.clone())
+            .merge(strategy_toggle_router(MarketToggles::in_memory()))
+            .merge(journal_router(TradeJournal::in_memory(), None))
+            .merge(alerts_router(alerts))
+            .merge(snapshot_history_router(history))
+            .merge(grafana_router(recorder)),
+        redaction,
+    );
+    let send = |method: &str, uri: &str, body: &str, token: bool| {
+        let mut request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json");
+        if token {
+            request = request.header("authorization", "Bearer s3cret");
+        }
+        let request = request.body(Body::from(body.to_string())).unwrap();
+        let app = app.clone();
+        async move {
+            let response = app.oneshot(request).await.unwrap();
+            let status = response.status();
+            let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            (status, String::from_utf8(body.to_vec()).unwrap())
+        }
+    };
+
+    // Writes and the PnL/position routes need the token.
+    let grafana_query = format!(
+        r#"{{"range":{{"from":"{}","to":"{}"}},"targets":[{{"target":"net_profit:*"}},{{"target":"position_net:*"}}]}}"#,
+        chrono::DateTime::from_timestamp(now - 60, 0)
+            .unwrap()
+            .to_rfc3339(),
+        chrono::DateTime::from_timestamp(now + 60, 0)
+            .unwrap()
+            .to_rfc3339(),
+    );
+    let journal_uri = format!("/markets/{slug}/journal");
+    let note = r#"{"author":"ops","text":"widened"}"#;
+    for (method, uri, body) in [
+        ("POST", "/grafana/query", grafana_query.as_str()),
+        (
+            "POST",
+            "/strategy/enable?coin=BTC&duration=5m&enabled=false",
+            "",
+        ),
+        ("POST", "/alerts/clock_drift/ack", ""),
+        ("POST", journal_uri.as_str(), note),
+        ("GET", journal_uri.as_str(), ""),
+        ("GET", "/journal", ""),
+    ] {
+        let (status, _) = send(method, uri, body, false).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED, "{method} {uri}");
+    }
+    let (status, body) = send("POST", "/grafana/query", &grafana_query, true).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("net_profit:"));
+    let (status, _) = send("POST", &journal_uri, note, true).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    // Public history rows keep market data but not positions or PnL.
+    let history_uri = format!("/dashboard/history?slug={slug}");
+    let (status, body) = send("GET", &history_uri, "", false).await;
+    assert_eq!(status, StatusCode::OK);
+    let public: SnapshotHistory = serde_json::from_str(&body).unwrap();
+    let public_row = &public.points[0].row;
+    assert_eq!(public_row.position_net, None);
+    assert_eq!(public_row.pos_yes, None);
+    assert_eq!(public_row.offer_yes, None);
+    assert_eq!(public_row.net_profit, None);
+    assert_eq!(public_row.probability.as_deref(), Some("0.5"));
+    let (_, body) = send("GET", &history_uri, "", true).await;
+    let full: SnapshotHistory = serde_json::from_str(&body).unwrap();
+    assert_eq!(full.points[0].row.net_profit.as_deref(), Some("0.001234"));
+}
+
+#[tokio::test]
+async fn saved_views_are_managed_over_http_and_applied_with_view_param() {
+    let now = chrono::Utc::now().timestamp();
+    let source = Arc::new(InMemoryMockSnapshotSource::new(DashboardSnapshot {
+        rows: vec![
+            row("BTC", "5m", now - 60, now + 240, Some("open")),
+            row("ETH", "15m", now - 60, now + 840, Some("open")),
+        ],
+        seq: 0,
+    }));
+    let views = DashboardViews::in_memory();
+    let app = || {
+        let redaction = RedactionConfig {
+            token: Some("s3cret".to_string()),
+            ..RedactionConfig::default()
+        };
+        dashboard_router_with_views(
+            source.clone(),
+            NumberFormatConfig::default(),
+            redaction,
+            views.clone(),
+        )
+    };
+    let send = |method: &str, uri: &str, body: Option<&str>, authorized: bool| {
+        let mut request = Request::builder().method(method).uri(uri);
+        if authorized {
+            request = request.header("authorization", "Bearer s3cret");
+        }
+        if body.is_some() {
+            request = request.header("content-type", "application/json");
+        }
+        let request = request
+            .body(body.map_or_else(Body::empty, |body| Body::from(body.to_string())))
+            .unwrap();
+        let app = app();
+        async move {
+            let response = app.oneshot(request).await.unwrap();
+            let status = response.status();
+            let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            (status, String::from_utf8(body.to_vec()).unwrap())
+        }
+    };
+    let slugs = |body: &str| -> Vec<String> {
+        let json: serde_json::Value = serde_json::from_str(body).unwrap();
+        json["rows"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|row| row["slug"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    let scalping = r#"{"columns":["coin","Price"],"filters":{"duration":["5m"]}}"#;
+    let uri = "/dashboard/views/scalping";
+    assert_eq!(
+        send("PUT", uri, Some(scalping), false).await.0,
+        StatusCode::UNAUTHORIZED
+    );
+    let (status, body) = send("PUT", uri, Some(scalping), true).await;
+    assert_eq!(status, StatusCode::OK);
+    let saved: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(saved["columns"], serde_json::json!(["coin", "price"]));
+    let bad = r#"{"columns":["pnl"]}"#;
+    let (status, body) = send("PUT", "/dashboard/views/bad", Some(bad), true).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("pnl"));
+
+    let (_, body) = send("GET", "/dashboard/views", None, false).await;
+    let listed: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(listed.as_array().unwrap().len(), 1);
+    assert_eq!(listed[0]["name"], "scalping");
+
+    // The view's filters apply unless the request sets the same key itself.
+    let btc = format!("btc-5m-{}", now - 60);
+    let eth = format!("eth-15m-{}", now - 60);
+    let (_, body) = send("GET", "/dashboard/snapshot?view=scalping", None, false).await;
+    assert_eq!(slugs(&body), vec![btc.clone()]);
+    let (_, body) = send(
+        "GET",
+        "/dashboard/snapshot?view=scalping&duration=15m",
+        None,
+        false,
+    )
+    .await;
+    assert_eq!(slugs(&body), vec![eth.clone()]);
+
+    // The page hides the columns outside the view and keeps it across filter changes.
+    let (_, html) = send("GET", "/dashboard?view=scalping", None, true).await;
+    assert!(html.contains("<input type=\"hidden\" name=\"view\" value=\"scalping\">"));
+    assert!(html.contains("view-link current\" href=\"/dashboard?view=scalping\""));
+    assert!(html.contains("#dashboard-table th:nth-child(9)"));
+    assert!(!html.contains("#dashboard-table th:nth-child(8),"));
+
+    assert_eq!(
+        send("DELETE", uri, None, false).await.0,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        send("DELETE", uri, None, true).await.0,
+        StatusCode::NO_CONTENT
+    );
+    assert_eq!(
+        send("DELETE", uri, None, true).await.0,
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(send("GET", uri, None, false).await.0, StatusCode::NOT_FOUND);
+    let (_, body) = send("GET", "/dashboard/snapshot?view=scalping", None, false).await;
+    assert_eq!(slugs(&body), vec![btc, eth]);
+}
+
+#[tokio::test]
+async fn export_streams_filtered_rows_as_csv_or_json() {
+    let now = chrono::Utc::now().timestamp();
+    let source = Arc::new(InMemoryMockSnapshotSource::new(DashboardSnapshot {
+        rows: vec![
+            row("BTC", "5m", now - 60, now + 240, Some("open")),
+            row("ETH", "15m", now - 60, now + 840, Some("open")),
+        ],
+        seq: 0,
+    }));
+    let app = || {
+        let redaction = RedactionConfig {
+            token: Some("s3cret".to_string()),
+            ..RedactionConfig::default()
+        };
+        dashboard_router_with_redaction(source.clone(), NumberFormatConfig::default(), redaction)
+    };
+    let get = |uri: &str, authorized: bool| {
+        let mut request = Request::builder().uri(uri);
+        if authorized {
+            request = request.header("authorization", "Bearer s3cret");
+        }
+        let request = request.body(Body::empty()).unwrap();
+        let app = app();
+        async move {
+            let response = app.oneshot(request).await.unwrap();
+            let status = response.status();
+            let content_type = response
+                .headers()
+                .get("content-type")
+                .map(|value| value.to_str().unwrap().to_string());
+            let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            (
+                status,
+                content_type,
+                String::from_utf8(body.to_vec()).unwrap(),
+            )
+        }
+    };
+
+    let (status, content_type, csv) = get("/dashboard/export?coin=BTC", true).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.as_deref(), Some("text/csv; charset=utf-8"));
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 2);
+    let mut header: Vec<&str> = DASHBOARD_COLUMN_KEYS.to_vec();
+    header.push("mock_columns");
+    assert_eq!(lines[0], header.join(","));
+    let cells: Vec<&str> = lines[1].split(',').collect();
+    assert_eq!(cells.len(), header.len());
+    assert_eq!(
+        cells[0],
+        format!("https://polymarket.com/event/btc-5m-{}", now - 60)
+    );
+    assert_eq!(cells[1], "BTC");
+    assert_eq!(cells[5], (now + 240).to_string());
+    assert_eq!(cells[header.len() - 1], "price");
+
+    let (status, content_type, json) = get("/dashboard/export?format=json", true).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.as_deref(), Some("application/json"));
+    let records: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let records = records.as_array().unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[1]["coin"], "ETH");
+    assert_eq!(records[1]["pos_yes"], "1.2@0.5");
+    assert_eq!(records[1]["mock_columns"], serde_json::json!(["price"]));
+
+    // Public exports withhold the same cells as the public snapshot.
+    let (_, _, json) = get("/dashboard/export?format=json&coin=ETH", false).await;
+    let records: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert!(records[0]["pos_yes"].is_null());
+    assert_eq!(records[0]["coin"], "ETH");
+
+    let (_, _, empty) = get("/dashboard/export?format=json&coin=SOL", true).await;
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&empty).unwrap(),
+        serde_json::json!([])
+    );
+    assert_eq!(
+        get("/dashboard/export?format=xlsx", true).await.0,
+        StatusCode::BAD_REQUEST
+    );
+}
+
+#[tokio::test]
+async fn horizons_query_adds_probability_columns_by_time_to_expiry() {
+    let now = chrono::Utc::now().timestamp();
+    let mut btc = row("BTC", "5m", now - 60, now + 240, Some("open"));
+    btc.probability_horizons = [(60, "0.7"), (300, "0.55")]
+        .into_iter()
+        .map(|(horizon, probability)| (horizon, probability.to_string()))
+        .collect();
+    let source = Arc::new(InMemoryMockSnapshotSource::new(DashboardSnapshot {
+        rows: vec![btc],
+        seq: 0,
+    }));
+    let get = |uri: &str, public_columns: Vec<String>| {
+        let redaction = RedactionConfig {
+            token: Some("s3cret".to_string()),
+            public_columns,
+        };
+        let app = dashboard_router_with_redaction(
+            source.clone(),
+            NumberFormatConfig::default(),
+            redaction,
+        );
+        let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        async move {
+            let response = app.oneshot(request).await.unwrap();
+            let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            String::from_utf8(body.to_vec()).unwrap()
+        }
+    };
+    let everything = || vec!["probability".to_string()];
+
+    let json: serde_json::Value =
+        serde_json::from_str(&get("/dashboard/snapshot", everything()).await).unwrap();
+    assert!(json["rows"][0].get("probability_horizons").is_none());
+
+    let json: serde_json::Value =
+        serde_json::from_str(&get("/dashboard/snapshot?horizons=60,900s,42", everything()).await)
+            .unwrap();
+    assert_eq!(
+        json["rows"][0]["probability_horizons"],
+        serde_json::json!({"60": "70%", "900": "-"})
+    );
+
+    // An empty value adds every horizon, farthest from expiry first.
+    let html = get("/dashboard?horizons=", everything()).await;
+    let headers = html.find("Prob 15m Left").zip(html.find("Prob 1m Left"));
+    assert!(headers.is_some_and(|(far, near)| far < near));
+    assert!(html.contains("name=\"horizons\" value=\"900,300,60\""));
+    assert!(html.contains("const HORIZONS = [900,300,60];"));
+    assert!(html.contains("<td class=\"\">55%</td>"));
+
+    // Withheld with the probability column.
+    let json: serde_json::Value =
+        serde_json::from_str(&get("/dashboard/snapshot?horizons=", Vec::new()).await).unwrap();
+    assert!(json["rows"][0].get("probability_horizons").is_none());
+}
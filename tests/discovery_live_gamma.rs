@@ -2,34 +2,23 @@
 
 use chrono::Utc;
 use pmm::{
-    build_previous_active_and_next_discovery_keys, resolve_discovery_batch, DiscoveryConfig,
-    DiscoveryKey, DiscoveryStatus, SlugConfig, ALL_COINS, ALL_DURATIONS,
+    build_previous_active_and_next_discovery_keys, resolve_discovery_batch, Coin, DiscoveryConfig,
+    DiscoveryKey, DiscoveryStatus, SlugConfig, ALL_DURATIONS,
 };
 use tokio::time::{sleep, Duration as TokioDuration};
 
-fn slug_config_from_env() -> SlugConfig {
-    let offset = std::env::var("PMFLIPS_DISCOVERY_OFFSET_4H_MIN")
-        .ok()
-        .and_then(|raw| raw.parse::<i32>().ok())
-        .unwrap_or(60);
-
-    SlugConfig {
-        discovery_offset_4h_min: offset,
-    }
-}
-
 fn live_discovery_config() -> DiscoveryConfig {
     DiscoveryConfig {
         timeout_ms: 8_000,
         batch_size: 64,
         max_retries: 2,
         retry_backoff_ms: 300,
-        include_tag: false,
+        ..DiscoveryConfig::default()
     }
 }
 
 fn scheduled_keys(now_ts: i64, slug_cfg: SlugConfig) -> Vec<DiscoveryKey> {
-    build_previous_active_and_next_discovery_keys(now_ts, &ALL_COINS, &ALL_DURATIONS, slug_cfg)
+    build_previous_active_and_next_discovery_keys(now_ts, &Coin::all(), &ALL_DURATIONS, slug_cfg)
         .expect("scheduled discovery key build should succeed")
         .into_iter()
         .map(|scheduled| scheduled.key)
@@ -39,7 +28,7 @@ fn scheduled_keys(now_ts: i64, slug_cfg: SlugConfig) -> Vec<DiscoveryKey> {
 #[tokio::test]
 async fn live_gamma_confirms_all_scheduled_slugs_exist() {
     let now_ts = Utc::now().timestamp();
-    let slug_cfg = slug_config_from_env();
+    let slug_cfg = SlugConfig::default();
     let discovery_cfg = live_discovery_config();
     let keys = scheduled_keys(now_ts, slug_cfg);
 
@@ -0,0 +1,87 @@
+//! The registry's documented defaults against the modules' own: setting every
+//! variable to its `ENV_VARS` default must leave each config equal to `Default`.
+//! One test per binary, since it sets process environment variables.
+
+use pmm::*;
+
+/// Names of the configs whose `from_env` differs from `default`.
+macro_rules! from_env_mismatches {
+    ($($config:ty),+ $(,)?) => {{
+        let mut mismatches = Vec::new();
+        $(
+            if <$config>::from_env() != <$config>::default() {
+                mismatches.push(stringify!($config));
+            }
+        )+
+        mismatches
+    }};
+}
+
+#[test]
+fn registry_defaults_match_the_config_defaults() {
+    for name in process_env_names() {
+        std::env::remove_var(name);
+    }
+    for var in ENV_VARS.iter().filter(|var| !var.secret) {
+        if let Some(default) = var.default {
+            std::env::set_var(var.name, default);
+        }
+    }
+
+    let mut mismatches = from_env_mismatches!(
+        RewardReconcileConfig,
+        ProbabilitySmoothingConfig,
+        WarmupConfig,
+        CapitalConfig,
+        ReadinessConfig,
+        JournalConfig,
+        GammaPayloadConfig,
+        ModelInputConfig,
+        MarketCurveConfig,
+        ModelConfig,
+        PositionConfig,
+        GammaEndpointConfig,
+        TradingConfig,
+        FlattenConfig,
+        PreMarketQuoteConfig,
+        StrategyConfig,
+        KlineValidationConfig,
+        MicroHaltConfig,
+        PersistenceBackend,
+        AlertConfig,
+        WebhookConfig,
+        NumberFormatConfig,
+        DryRunQuoteConfig,
+        ActivityFeedConfig,
+        DiscoveryUniverse,
+        ClobStreamConfig,
+        PortfolioRiskConfig,
+        QualityConfig,
+        SnapshotRetentionPolicy,
+        SnapshotRecorderConfig,
+        DashboardViewConfig,
+        RedactionConfig,
+        BinanceWsConfig,
+        SizingConfig,
+        FillModelConfig,
+        MarketToggleConfig,
+        RealizedVolConfig,
+        ShutdownConfig,
+        OrderRateLimitConfig,
+        PaperTradingConfig,
+        ClockDriftConfig,
+        SnapshotStoreConfig,
+    );
+    // Preflight also lists the variables set, which are all of them here.
+    let preflight = PreflightConfig {
+        env_names: Vec::new(),
+        ..PreflightConfig::from_env()
+    };
+    if preflight != PreflightConfig::default() {
+        mismatches.push("PreflightConfig");
+    }
+    assert!(
+        mismatches.is_empty(),
+        "configs disagreeing with the registry defaults: {mismatches:?}"
+    );
+}
@@ -0,0 +1,175 @@
+#![cfg(feature = "fault-injection")]
+
+use std::sync::Arc;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{Request, StatusCode};
+use chrono::Utc;
+use pmm::{
+    dashboard_router, inject_faults, BinanceSymbol, Coin, DashboardRow, DashboardSnapshot,
+    FaultPlan, InMemoryMockSnapshotSource, Kline1s, KlineStore, KlineStoreError,
+    KlineValidationConfig, RealizedVolConfig, RealizedVolTracker,
+};
+use tower::ServiceExt;
+
+// 2025-01-01T00:00:00Z
+const START: i64 = 1_735_689_600;
+
+fn kline(open_time_ms: i64, close: f64) -> Kline1s {
+    Kline1s {
+        open_time_ms,
+        open: close,
+        high: close,
+        low: close,
+        close,
+        volume: 1.0,
+        close_time_ms: open_time_ms + 999,
+        quote_asset_volume: close,
+        trade_count: 1,
+        taker_buy_base_volume: 0.5,
+        taker_buy_quote_volume: close / 2.0,
+    }
+}
+
+#[test]
+fn sqlite_busy_fails_one_read_without_corrupting_incremental_state() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("klines_1s.sqlite");
+    let mut store = KlineStore::open(&path, KlineValidationConfig::default()).unwrap();
+    let rows = (0..120)
+        .map(|n| kline((START + n) * 1_000, 100.0 + (n % 7) as f64))
+        .collect();
+    store.upsert_rows(BinanceSymbol::BtcUsdt, rows).unwrap();
+    let cfg = RealizedVolConfig {
+        min_returns: 1,
+        ..RealizedVolConfig::default()
+    };
+    let stats = |tracker: &mut RealizedVolTracker| {
+        tracker.interval_stats(Coin::BTC, START, START + 300, START + 120)
+    };
+
+    let mut baseline = RealizedVolTracker::new(
+        Box::new(KlineStore::open_read_only(&path).unwrap()),
+        cfg.clone(),
+    );
+    let expected = stats(&mut baseline).unwrap();
+
+    let mut tracker =
+        RealizedVolTracker::new(Box::new(KlineStore::open_read_only(&path).unwrap()), cfg);
+    let faults = inject_faults(FaultPlan {
+        sqlite_busy: 1,
+        ..FaultPlan::default()
+    });
+    match stats(&mut tracker) {
+        Err(KlineStoreError::Sqlite(err)) => {
+            assert_eq!(
+                err.sqlite_error_code(),
+                Some(rusqlite::ErrorCode::DatabaseBusy)
+            );
+        }
+        other => panic!("expected SQLITE_BUSY, got {other:?}"),
+    }
+    assert_eq!(stats(&mut tracker).unwrap(), expected);
+    drop(faults);
+}
+
+#[tokio::test]
+async fn clock_jump_moves_rows_out_of_their_interval() {
+    let now = Utc::now().timestamp();
+    let mut row = DashboardRow::unresolved_with_times("btc-5m", "BTC", "5m", now - 10, now + 290);
+    row.mock_columns.clear();
+    let source = Arc::new(InMemoryMockSnapshotSource::new(DashboardSnapshot {
+        rows: vec![row],
+        seq: 0,
+    }));
+    let in_interval = || async {
+        let response = dashboard_router(source.clone())
+            .oneshot(
+                Request::get("/dashboard/snapshot")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        json["rows"][0]["in_interval"].as_str().unwrap().to_string()
+    };
+
+    let faults = inject_faults(FaultPlan::default());
+    assert_eq!(in_interval().await, "yes");
+    faults.update(|plan| plan.clock_offset_ms = 600_000);
+    assert_eq!(in_interval().await, "no");
+    faults.update(|plan| plan.clock_offset_ms = -600_000);
+    assert_eq!(in_interval().await, "no");
+    drop(faults);
+    assert_eq!(in_interval().await, "yes");
+}
+
+#[cfg(feature = "discovery-sdk")]
+#[tokio::test]
+async fn gamma_timeouts_retry_and_failing_slugs_stay_row_scoped() {
+    use std::collections::HashSet;
+
+    use axum::routing::get;
+    use axum::Router;
+    use pmm::{
+        resolve_discovery_batch_with_endpoints, DiscoveryConfig, DiscoveryKey, DiscoveryStatus,
+        Duration, GammaEndpointConfig, GammaEndpointPool, UnresolvedReason,
+    };
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = Router::new().route(
+        "/markets/slug/{slug}",
+        get(|| async { StatusCode::NOT_FOUND }),
+    );
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let endpoints = GammaEndpointPool::new(GammaEndpointConfig {
+        base_urls: vec![format!("http://{addr}")],
+        ..GammaEndpointConfig::default()
+    });
+    let cfg = DiscoveryConfig {
+        max_retries: 1,
+        retry_backoff_ms: 1,
+        ..DiscoveryConfig::default()
+    };
+    let keys = ["first", "broken", "last"]
+        .map(|slug| DiscoveryKey::from_slug(Coin::BTC, Duration::M5, START, slug.to_string()))
+        .to_vec();
+
+    let faults = inject_faults(FaultPlan {
+        gamma_timeouts: 1,
+        gamma_failing_slugs: HashSet::from(["broken".to_string()]),
+        ..FaultPlan::default()
+    });
+    let rows = resolve_discovery_batch_with_endpoints(&keys, &cfg, &endpoints)
+        .await
+        .expect("partial failure keeps the batch");
+
+    // The injected timeout hits `first`, whose retry succeeds; `broken` exhausts its
+    // retries without failing the other rows.
+    let statuses = rows
+        .iter()
+        .map(|row| match &row.status {
+            DiscoveryStatus::Unresolved {
+                reason: UnresolvedReason::NotFound,
+            } => "not_found",
+            DiscoveryStatus::Unresolved {
+                reason: UnresolvedReason::TransportError(message),
+            } => {
+                assert!(message.contains("injected"), "{message}");
+                "transport"
+            }
+            other => panic!("unexpected status {other:?}"),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(statuses, ["not_found", "transport", "not_found"]);
+    assert_eq!(
+        pmm::active_faults().map(|plan| plan.gamma_timeouts),
+        Some(0)
+    );
+    drop(faults);
+}
@@ -1,7 +1,14 @@
 use pmm::{
-    assert_schema_compatible, build_feature_schema, horizon_conditioning, transform_store_range,
-    transform_store_range_for_runtime_cold_start, transform_store_range_for_training, FeatureError,
-    FeatureTransformConfig, FeatureTransformRequest, GapPolicy, FEATURE_SCHEMA_VERSION,
+    assert_schema_compatible, build_feature_schema, feature_drift_report,
+    feature_transform_metrics, horizon_conditioning, render_prometheus, transform_store_range,
+    transform_store_range_for_runtime_cold_start, transform_store_range_for_training,
+    transform_store_range_to_parquet, transform_store_range_with_backfill_into,
+    transform_store_range_with_timing, write_feature_rows_parquet, BinanceSymbol, DriftConfig,
+    DriftLevel, Duration, FeatureError, FeatureRow, FeatureTransformConfig,
+    FeatureTransformRequest, FeatureUpdater, FeatureWindowPreset, GapPolicy, Kline1s, KlineHalt,
+    KlineStore, KlineTailSource, KlineValidationConfig, MicroHaltConfig,
+    ALL_FEATURE_WINDOW_PRESETS, FEATURE_SCHEMA_VERSION, PARQUET_SCHEMA_FINGERPRINT_KEY,
+    PARQUET_SCHEMA_VERSION_KEY,
 };
 use rusqlite::{params, Connection};
 use tempfile::NamedTempFile;
@@ -16,6 +23,8 @@ fn schema_order_and_fingerprint_are_deterministic() {
         max_duration_seconds: 86_400,
         gap_policy: GapPolicy::Strict,
         schema_version: FEATURE_SCHEMA_VERSION,
+        preset: None,
+        auto_backfill: false,
     };
 
     let schema_a = build_feature_schema(&cfg);
@@ -33,6 +42,57 @@ fn schema_order_and_fingerprint_are_deterministic() {
     assert_eq!(schema_a, schema_b);
 }
 
+#[test]
+fn window_presets_follow_duration_and_yield_distinct_schemas() {
+    assert_eq!(
+        FeatureWindowPreset::for_duration(Duration::M5),
+        FeatureWindowPreset::Short
+    );
+    assert_eq!(
+        FeatureWindowPreset::for_duration(Duration::H1),
+        FeatureWindowPreset::Medium
+    );
+    assert_eq!(
+        FeatureTransformConfig::for_duration(Duration::D1).windows_seconds,
+        vec![900, 3_600, 14_400]
+    );
+
+    let schemas: Vec<_> = ALL_FEATURE_WINDOW_PRESETS
+        .into_iter()
+        .map(|preset| build_feature_schema(&FeatureTransformConfig::with_preset(preset)))
+        .collect();
+    assert_eq!(schemas[1].columns[1].name, "btc_ret_60s");
+    assert_eq!(schemas[2].columns[3].name, "btc_ret_14400s");
+    for (i, a) in schemas.iter().enumerate() {
+        for b in &schemas[i + 1..] {
+            assert_ne!(a.fingerprint, b.fingerprint);
+        }
+    }
+
+    // The short preset reuses the default windows but still fingerprints differently.
+    let short = FeatureTransformConfig::with_preset(FeatureWindowPreset::Short);
+    assert_eq!(
+        short.windows_seconds,
+        FeatureTransformConfig::default().windows_seconds
+    );
+    assert_ne!(
+        schemas[0].fingerprint,
+        build_feature_schema(&FeatureTransformConfig::default()).fingerprint
+    );
+
+    let tmp = seed_store(START_TS_MS, 10, None, &[]);
+    let req = FeatureTransformRequest {
+        start_ts_ms_utc: START_TS_MS,
+        end_ts_ms_utc_exclusive: START_TS_MS + 10 * STEP_MS,
+    };
+    let mismatched = FeatureTransformConfig {
+        windows_seconds: vec![5, 15],
+        ..short
+    };
+    let err = transform_store_range(tmp.path(), &req, &mismatched).expect_err("preset mismatch");
+    assert!(matches!(err, FeatureError::InvalidConfig(_)));
+}
+
 #[test]
 fn horizon_conditioning_matches_expected_formula() {
     let hc = horizon_conditioning(3_600, 86_400);
@@ -54,6 +114,8 @@ fn transform_is_deterministic_and_emits_expected_math() {
         max_duration_seconds: 86_400,
         gap_policy: GapPolicy::Strict,
         schema_version: FEATURE_SCHEMA_VERSION,
+        preset: None,
+        auto_backfill: false,
     };
 
     let out_a = transform_store_range(tmp.path(), &req, &cfg).expect("first transform succeeds");
@@ -145,6 +207,8 @@ fn strict_policy_fails_on_incomplete_frame() {
         max_duration_seconds: 86_400,
         gap_policy: GapPolicy::Strict,
         schema_version: FEATURE_SCHEMA_VERSION,
+        preset: None,
+        auto_backfill: false,
     };
 
     let err = transform_store_range(tmp.path(), &req, &cfg).expect_err("must fail");
@@ -172,6 +236,8 @@ fn report_and_skip_keeps_running_and_reports_gap() {
         max_duration_seconds: 86_400,
         gap_policy: GapPolicy::ReportAndSkip,
         schema_version: FEATURE_SCHEMA_VERSION,
+        preset: None,
+        auto_backfill: false,
     };
 
     let (_schema, rows, report) =
@@ -186,6 +252,44 @@ fn report_and_skip_keeps_running_and_reports_gap() {
     assert!(report.first_error.is_some());
 }
 
+#[test]
+fn transform_timing_splits_read_and_compute_and_feeds_metrics() {
+    let tmp = seed_store(START_TS_MS, 6, None, &[]);
+    let req = FeatureTransformRequest {
+        start_ts_ms_utc: START_TS_MS,
+        end_ts_ms_utc_exclusive: START_TS_MS + 6 * STEP_MS,
+    };
+    let cfg = FeatureTransformConfig {
+        windows_seconds: vec![2],
+        max_duration_seconds: 86_400,
+        gap_policy: GapPolicy::Strict,
+        schema_version: FEATURE_SCHEMA_VERSION,
+        preset: None,
+        auto_backfill: false,
+    };
+
+    let (_schema, rows, report, timing) =
+        transform_store_range_with_timing(tmp.path(), &req, &cfg).expect("transform succeeds");
+    assert_eq!(
+        (rows, report),
+        transform_store_range(tmp.path(), &req, &cfg)
+            .map(|(_, rows, report)| (rows, report))
+            .unwrap()
+    );
+    assert_eq!(timing.rows_read, 24);
+    assert!(timing.read_secs >= 0.0 && timing.compute_secs >= 0.0);
+    assert!(timing.rows_per_sec > 0.0);
+    // Far below a tenth of a chunk: no latency sample.
+    assert!(timing.chunk_secs_per_million.is_empty());
+
+    // Other tests in this binary transform too, so only lower bounds hold.
+    let metrics = feature_transform_metrics();
+    assert!(metrics.runs >= 1 && metrics.rows_read >= 24);
+    let text = render_prometheus();
+    assert!(text.contains("# TYPE pmm_feature_transform_chunk_seconds histogram\n"));
+    assert!(text.contains("pmm_feature_transform_rows_total "));
+}
+
 #[test]
 fn strict_policy_fails_on_missing_timestamp_continuity_gap() {
     let tmp = seed_store(START_TS_MS, 7, None, &[4]);
@@ -198,6 +302,8 @@ fn strict_policy_fails_on_missing_timestamp_continuity_gap() {
         max_duration_seconds: 86_400,
         gap_policy: GapPolicy::Strict,
         schema_version: FEATURE_SCHEMA_VERSION,
+        preset: None,
+        auto_backfill: false,
     };
 
     let err = transform_store_range(tmp.path(), &req, &cfg).expect_err("must fail");
@@ -222,6 +328,8 @@ fn schema_compatibility_check_matches_version_and_fingerprint() {
         max_duration_seconds: 86_400,
         gap_policy: GapPolicy::Strict,
         schema_version: FEATURE_SCHEMA_VERSION,
+        preset: None,
+        auto_backfill: false,
     };
     let schema = build_feature_schema(&cfg);
 
@@ -252,6 +360,8 @@ fn training_and_runtime_wrappers_share_same_transform_output() {
         max_duration_seconds: 86_400,
         gap_policy: GapPolicy::Strict,
         schema_version: FEATURE_SCHEMA_VERSION,
+        preset: None,
+        auto_backfill: false,
     };
 
     let training = transform_store_range_for_training(tmp.path(), &req, &cfg).expect("training");
@@ -260,6 +370,445 @@ fn training_and_runtime_wrappers_share_same_transform_output() {
     assert_eq!(training, runtime);
 }
 
+#[test]
+fn parquet_output_streams_the_same_rows_with_schema_footer() {
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use parquet::record::RowAccessor;
+
+    let tmp = seed_store(START_TS_MS, 12, None, &[]);
+    let req = FeatureTransformRequest {
+        start_ts_ms_utc: START_TS_MS,
+        end_ts_ms_utc_exclusive: START_TS_MS + 12 * STEP_MS,
+    };
+    let cfg = FeatureTransformConfig {
+        windows_seconds: vec![2],
+        max_duration_seconds: 86_400,
+        gap_policy: GapPolicy::Strict,
+        schema_version: FEATURE_SCHEMA_VERSION,
+        preset: None,
+        auto_backfill: false,
+    };
+    let (schema, rows, report) = transform_store_range(tmp.path(), &req, &cfg).expect("rows");
+
+    let dir = tempfile::tempdir().expect("out dir");
+    let streamed = dir.path().join("streamed.parquet");
+    let (streamed_schema, streamed_report) =
+        transform_store_range_to_parquet(tmp.path(), &req, &cfg, &streamed).expect("stream");
+    assert_eq!((streamed_schema, streamed_report), (schema.clone(), report));
+    assert!(!dir.path().join("streamed.parquet.partial").exists());
+    let written = dir.path().join("written.parquet");
+    assert_eq!(
+        write_feature_rows_parquet(&written, &schema, &rows).expect("write"),
+        rows.len() as u64
+    );
+
+    for path in [&streamed, &written] {
+        let reader = SerializedFileReader::new(std::fs::File::open(path).unwrap()).unwrap();
+        let footer = reader
+            .metadata()
+            .file_metadata()
+            .key_value_metadata()
+            .unwrap()
+            .iter()
+            .map(|kv| (kv.key.as_str(), kv.value.clone().unwrap_or_default()))
+            .collect::<Vec<_>>();
+        assert!(footer.contains(&(PARQUET_SCHEMA_VERSION_KEY, schema.version.to_string())));
+        assert!(footer.contains(&(PARQUET_SCHEMA_FINGERPRINT_KEY, schema.fingerprint.clone())));
+        let read = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| {
+                let row = row.unwrap();
+                FeatureRow {
+                    ts_ms_utc: row.get_long(0).unwrap(),
+                    values: (1..=schema.columns.len())
+                        .map(|idx| row.get_double(idx).unwrap())
+                        .collect(),
+                }
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(read, rows);
+    }
+
+    let narrow = FeatureRow {
+        ts_ms_utc: START_TS_MS,
+        values: vec![0.0],
+    };
+    assert!(matches!(
+        write_feature_rows_parquet(&dir.path().join("bad.parquet"), &schema, &[narrow]),
+        Err(FeatureError::RowWidthMismatch { actual: 1, .. })
+    ));
+}
+
+#[test]
+fn transform_reads_compacted_and_full_rows_alike() {
+    let tmp = seed_store(START_TS_MS, 10, None, &[]);
+    let req = FeatureTransformRequest {
+        start_ts_ms_utc: START_TS_MS,
+        end_ts_ms_utc_exclusive: START_TS_MS + 10 * STEP_MS,
+    };
+    let cfg = FeatureTransformConfig {
+        windows_seconds: vec![2],
+        max_duration_seconds: 86_400,
+        gap_policy: GapPolicy::Strict,
+        schema_version: FEATURE_SCHEMA_VERSION,
+        preset: None,
+        auto_backfill: false,
+    };
+    let before = transform_store_range(tmp.path(), &req, &cfg).expect("before compaction");
+
+    let mut store =
+        KlineStore::open(tmp.path(), KlineValidationConfig::default()).expect("open store");
+    assert_eq!(
+        store
+            .compact_older_than(START_TS_MS + 5 * STEP_MS)
+            .expect("compact"),
+        20
+    );
+    drop(store);
+
+    let after = transform_store_range(tmp.path(), &req, &cfg).expect("after compaction");
+    assert_eq!(before, after);
+}
+
+#[test]
+fn flagged_micro_halts_go_through_the_gap_policy() {
+    let tmp = seed_store(START_TS_MS, 8, None, &[]);
+    // ETH prints flat zero-volume seconds 2..=5 at the close of second 1.
+    let conn = Connection::open(tmp.path()).expect("open sqlite");
+    let flat = base_close(2) + 1.0;
+    conn.execute(
+        "UPDATE klines_1s SET open = ?1, high = ?1, low = ?1, close = ?1, volume = 0
+         WHERE symbol_id = 2 AND open_time_ms >= ?2 AND open_time_ms < ?3",
+        params![flat, START_TS_MS + 2 * STEP_MS, START_TS_MS + 6 * STEP_MS],
+    )
+    .expect("flatten");
+    drop(conn);
+
+    let mut store =
+        KlineStore::open(tmp.path(), KlineValidationConfig::default()).expect("open store");
+    let halts = store
+        .flag_micro_halts(
+            pmm::BinanceSymbol::EthUsdt,
+            START_TS_MS,
+            START_TS_MS + 8 * STEP_MS,
+            &MicroHaltConfig { min_run_secs: 3 },
+        )
+        .expect("flag halts");
+    assert_eq!(
+        halts,
+        vec![KlineHalt {
+            start_ms: START_TS_MS + 2 * STEP_MS,
+            end_ms_exclusive: START_TS_MS + 6 * STEP_MS,
+        }]
+    );
+    drop(store);
+
+    let req = FeatureTransformRequest {
+        start_ts_ms_utc: START_TS_MS,
+        end_ts_ms_utc_exclusive: START_TS_MS + 8 * STEP_MS,
+    };
+    let mut cfg = FeatureTransformConfig {
+        windows_seconds: vec![2],
+        max_duration_seconds: 86_400,
+        gap_policy: GapPolicy::Strict,
+        schema_version: FEATURE_SCHEMA_VERSION,
+        preset: None,
+        auto_backfill: false,
+    };
+    match transform_store_range(tmp.path(), &req, &cfg).expect_err("halt is a gap") {
+        FeatureError::IncompleteFrame {
+            ts_ms_utc,
+            missing_symbols,
+        } => {
+            assert_eq!(ts_ms_utc, START_TS_MS + 2 * STEP_MS);
+            assert_eq!(missing_symbols, vec!["ETH"]);
+        }
+        other => panic!("unexpected error: {other}"),
+    }
+
+    cfg.gap_policy = GapPolicy::ReportAndSkip;
+    let (_schema, _rows, report) =
+        transform_store_range(tmp.path(), &req, &cfg).expect("transform succeeds");
+    assert_eq!(report.gap_ranges[0].0, START_TS_MS + 2 * STEP_MS);
+    assert!(report.skipped_points >= 4);
+}
+
+#[test]
+fn drift_report_flags_trending_quote_volume_between_ranges() {
+    let tmp = seed_store(START_TS_MS, 40, None, &[]);
+    let baseline = FeatureTransformRequest {
+        start_ts_ms_utc: START_TS_MS,
+        end_ts_ms_utc_exclusive: START_TS_MS + 20 * STEP_MS,
+    };
+    let current = FeatureTransformRequest {
+        start_ts_ms_utc: START_TS_MS + 20 * STEP_MS,
+        end_ts_ms_utc_exclusive: START_TS_MS + 40 * STEP_MS,
+    };
+    let cfg = FeatureTransformConfig {
+        windows_seconds: vec![2],
+        max_duration_seconds: 86_400,
+        gap_policy: GapPolicy::Strict,
+        schema_version: FEATURE_SCHEMA_VERSION,
+        preset: None,
+        auto_backfill: false,
+    };
+    let drift_cfg = DriftConfig {
+        max_samples: 10,
+        chunk_seconds: 10,
+        ..DriftConfig::default()
+    };
+
+    let report =
+        feature_drift_report(tmp.path(), &baseline, &current, &cfg, &drift_cfg).expect("report");
+    let schema = build_feature_schema(&cfg);
+    assert_eq!(report.schema_fingerprint, schema.fingerprint);
+    assert_eq!(report.features.len(), schema.columns.len());
+    assert!(report.baseline_rows > 0 && report.baseline_rows <= 10);
+    assert!(report.current_rows > 0 && report.current_rows <= 10);
+
+    let quote_vol = report
+        .features
+        .iter()
+        .find(|drift| drift.column == "btc_quote_vol_2s")
+        .expect("quote volume column");
+    assert_eq!(quote_vol.level, DriftLevel::Significant);
+    assert!(quote_vol.current.mean > quote_vol.baseline.mean);
+    assert!(report
+        .drifted_columns
+        .contains(&"btc_quote_vol_2s".to_string()));
+}
+
+#[test]
+fn online_updater_matches_batch_rows_and_rewarms_after_gaps() {
+    let cfg = FeatureTransformConfig {
+        windows_seconds: vec![2, 3],
+        ..FeatureTransformConfig::default()
+    };
+    let tmp = seed_store(START_TS_MS, 12, None, &[]);
+    let req = FeatureTransformRequest {
+        start_ts_ms_utc: START_TS_MS,
+        end_ts_ms_utc_exclusive: START_TS_MS + 12 * STEP_MS,
+    };
+    let (schema, batch_rows, _) = transform_store_range(tmp.path(), &req, &cfg).unwrap();
+
+    let mut updater = FeatureUpdater::new(&cfg).unwrap();
+    assert_eq!(updater.schema(), &schema);
+    let online_rows: Vec<FeatureRow> = (0..12)
+        .filter_map(|t_idx| {
+            let ts_ms = START_TS_MS + t_idx as i64 * STEP_MS;
+            updater.push_frame(ts_ms, &live_frame(t_idx, 4)).unwrap()
+        })
+        .collect();
+    assert_eq!(online_rows, batch_rows);
+    assert_eq!(updater.latest(), batch_rows.last());
+
+    // Replaying an old second is refused rather than corrupting the state.
+    let err = updater
+        .push_frame(START_TS_MS, &live_frame(0, 4))
+        .expect_err("out of order");
+    assert!(matches!(err, FeatureError::OutOfOrderFrame { .. }));
+
+    // An incomplete frame and a skipped second both reset the state; rows resume once
+    // the largest window refills.
+    let ts = |t_idx: usize| START_TS_MS + t_idx as i64 * STEP_MS;
+    assert_eq!(
+        updater.push_frame(ts(12), &live_frame(12, 3)).unwrap(),
+        None
+    );
+    assert_eq!(updater.latest(), None);
+    for t_idx in [13, 15, 16, 17] {
+        assert_eq!(
+            updater
+                .push_frame(ts(t_idx), &live_frame(t_idx, 4))
+                .unwrap(),
+            None
+        );
+    }
+    let row = updater
+        .push_frame(ts(18), &live_frame(18, 4))
+        .unwrap()
+        .expect("warm again");
+    assert_eq!(row.ts_ms_utc, ts(18));
+    let report = updater.report();
+    assert_eq!(report.gap_ranges, vec![(ts(12), ts(13)), (ts(14), ts(15))]);
+    assert_eq!(report.input_points, 18);
+    assert_eq!(report.output_points, 10);
+}
+
+#[test]
+fn online_updater_warms_from_the_store_after_cold_start() {
+    let cfg = FeatureTransformConfig {
+        windows_seconds: vec![2, 3],
+        ..FeatureTransformConfig::default()
+    };
+    let tmp = seed_store(START_TS_MS, 10, None, &[]);
+    let mut updater = FeatureUpdater::new(&cfg).unwrap();
+    let frames = updater
+        .warm_from_store(tmp.path(), START_TS_MS + 10 * STEP_MS)
+        .unwrap();
+    assert_eq!(frames, 4);
+    assert_eq!(updater.last_ts_ms_utc(), Some(START_TS_MS + 9 * STEP_MS));
+
+    let live_ts = START_TS_MS + 10 * STEP_MS;
+    let row = updater
+        .push_frame(live_ts, &live_frame(10, 4))
+        .unwrap()
+        .expect("first live frame is warm");
+    let tmp = seed_store(START_TS_MS, 11, None, &[]);
+    let req = FeatureTransformRequest {
+        start_ts_ms_utc: START_TS_MS,
+        end_ts_ms_utc_exclusive: live_ts + STEP_MS,
+    };
+    let (_, batch_rows, _) = transform_store_range(tmp.path(), &req, &cfg).unwrap();
+    assert_eq!(Some(&row), batch_rows.last());
+}
+
+#[test]
+fn auto_backfill_fills_gaps_from_the_loader_and_transforms_them() {
+    let cfg = FeatureTransformConfig {
+        windows_seconds: vec![2, 3],
+        gap_policy: GapPolicy::ReportAndSkip,
+        auto_backfill: true,
+        ..FeatureTransformConfig::default()
+    };
+    let req = FeatureTransformRequest {
+        start_ts_ms_utc: START_TS_MS,
+        end_ts_ms_utc_exclusive: START_TS_MS + 15 * STEP_MS,
+    };
+    let complete = seed_store(START_TS_MS, 15, None, &[]);
+    let (_, expected_rows, _) = transform_store_range(complete.path(), &req, &cfg).unwrap();
+
+    // Leading, inner, and trailing gaps plus one incomplete frame.
+    let gappy = seed_store(START_TS_MS, 15, Some((9, 2)), &[0, 5, 6, 14]);
+    let mut loader = FixtureLoader::default();
+    let mut rows = Vec::new();
+    let (_, report, _) =
+        transform_store_range_with_backfill_into(gappy.path(), &req, &cfg, &mut loader, |row| {
+            rows.push(row);
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(rows, expected_rows);
+    assert_eq!(report.backfilled_points, 17);
+    assert_eq!(report.skipped_points, 0);
+    assert!(report.gap_ranges.is_empty());
+    assert!(loader.requests.contains(&(
+        BinanceSymbol::EthUsdt,
+        START_TS_MS + 9 * STEP_MS,
+        START_TS_MS + 10 * STEP_MS
+    )));
+
+    // The backfill went into the store, so a plain rerun needs no loader.
+    let (_, rerun, report) = transform_store_range(gappy.path(), &req, &cfg).unwrap();
+    assert_eq!(rerun, expected_rows);
+    assert_eq!(report.backfilled_points, 0);
+}
+
+#[test]
+fn failed_or_disabled_backfill_leaves_gaps_to_the_policy() {
+    let req = FeatureTransformRequest {
+        start_ts_ms_utc: START_TS_MS,
+        end_ts_ms_utc_exclusive: START_TS_MS + 10 * STEP_MS,
+    };
+    let gap = (START_TS_MS + 5 * STEP_MS, START_TS_MS + 6 * STEP_MS);
+    let skip_only = FeatureTransformConfig {
+        windows_seconds: vec![2],
+        gap_policy: GapPolicy::ReportAndSkip,
+        ..FeatureTransformConfig::default()
+    };
+    let tmp = seed_store(START_TS_MS, 10, None, &[5]);
+
+    let mut failing = FixtureLoader {
+        fail: true,
+        ..FixtureLoader::default()
+    };
+    let cfg = FeatureTransformConfig {
+        auto_backfill: true,
+        ..skip_only.clone()
+    };
+    let (_, report, _) =
+        transform_store_range_with_backfill_into(tmp.path(), &req, &cfg, &mut failing, |_| Ok(()))
+            .unwrap();
+    assert_eq!(failing.requests.len(), 4);
+    assert_eq!(report.gap_ranges, vec![gap]);
+    assert_eq!(report.backfilled_points, 0);
+
+    let mut loader = FixtureLoader::default();
+    let (_, report, _) =
+        transform_store_range_with_backfill_into(tmp.path(), &req, &skip_only, &mut loader, |_| {
+            Ok(())
+        })
+        .unwrap();
+    assert!(loader.requests.is_empty());
+    assert_eq!(report.gap_ranges, vec![gap]);
+}
+
+/// Serves the klines `seed_store` would have written, and records the requests.
+#[derive(Default)]
+struct FixtureLoader {
+    requests: Vec<(BinanceSymbol, i64, i64)>,
+    fail: bool,
+}
+
+impl KlineTailSource for FixtureLoader {
+    fn fetch(
+        &mut self,
+        symbol: BinanceSymbol,
+        start_ts_ms: i64,
+        end_ts_ms_exclusive: i64,
+    ) -> Result<Vec<Kline1s>, String> {
+        self.requests
+            .push((symbol, start_ts_ms, end_ts_ms_exclusive));
+        if self.fail {
+            return Err("archive unavailable".to_string());
+        }
+        Ok((start_ts_ms..end_ts_ms_exclusive)
+            .step_by(STEP_MS as usize)
+            .filter_map(|ts_ms| {
+                let t_idx = ((ts_ms - START_TS_MS) / STEP_MS) as usize;
+                live_frame(t_idx, 4)
+                    .into_iter()
+                    .find(|(frame_symbol, _)| *frame_symbol == symbol)
+                    .map(|(_, kline)| kline)
+            })
+            .collect())
+    }
+}
+
+/// The klines `seed_store` writes at `t_idx`, for the first `symbols` symbols, as they
+/// would arrive from the live feed.
+fn live_frame(t_idx: usize, symbols: usize) -> Vec<(BinanceSymbol, Kline1s)> {
+    [
+        BinanceSymbol::BtcUsdt,
+        BinanceSymbol::EthUsdt,
+        BinanceSymbol::SolUsdt,
+        BinanceSymbol::XrpUsdt,
+    ]
+    .into_iter()
+    .zip(1_i64..)
+    .take(symbols)
+    .map(|(symbol, symbol_id)| {
+        let open_time_ms = START_TS_MS + t_idx as i64 * STEP_MS;
+        let close = base_close(symbol_id) + t_idx as f64;
+        let kline = Kline1s {
+            open_time_ms,
+            open: close,
+            high: close + 0.5,
+            low: close - 0.5,
+            close,
+            volume: 1.0,
+            close_time_ms: open_time_ms + STEP_MS - 1,
+            quote_asset_volume: 1_000.0 + symbol_id as f64 * 10.0 + t_idx as f64,
+            trade_count: 1,
+            taker_buy_base_volume: 0.5,
+            taker_buy_quote_volume: close / 2.0,
+        };
+        (symbol, kline)
+    })
+    .collect()
+}
+
 fn seed_store(
     start_ts_ms: i64,
     points: usize,
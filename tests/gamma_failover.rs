@@ -0,0 +1,145 @@
+#![cfg(feature = "discovery-sdk")]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::Router;
+use pmm::{
+    resolve_discovery_batch_with_endpoints, Coin, DiscoveryConfig, DiscoveryError, DiscoveryKey,
+    DiscoveryStatus, Duration, GammaEndpointConfig, GammaEndpointPool, UnresolvedReason,
+};
+
+async fn serve(app: Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind");
+    let addr = listener.local_addr().expect("addr");
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.expect("serve");
+    });
+    format!("http://{addr}")
+}
+
+#[tokio::test]
+async fn failing_primary_fails_over_to_mirror_without_backoff() {
+    // Primary hangs past the lookup timeout; the mirror answers "no such market".
+    let primary = serve(Router::new().route(
+        "/markets/slug/{slug}",
+        get(|| async {
+            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+            StatusCode::OK
+        }),
+    ))
+    .await;
+    let mirror = serve(Router::new().route(
+        "/proxy/markets/slug/{slug}",
+        get(|| async { StatusCode::NOT_FOUND }),
+    ))
+    .await;
+
+    let endpoints = GammaEndpointPool::new(GammaEndpointConfig {
+        base_urls: vec![primary.clone(), format!("{mirror}/proxy")],
+        unhealthy_after_failures: 1,
+        cooldown_ms: 60_000,
+        ..GammaEndpointConfig::default()
+    });
+    let cfg = DiscoveryConfig {
+        timeout_ms: 300,
+        max_retries: 1,
+        retry_backoff_ms: 10_000,
+        ..DiscoveryConfig::default()
+    };
+    let keys = [100, 200, 300]
+        .map(|n| {
+            DiscoveryKey::from_slug(
+                Coin::BTC,
+                Duration::M5,
+                1_735_689_600,
+                format!("missing-{n}"),
+            )
+        })
+        .to_vec();
+
+    let started = std::time::Instant::now();
+    let rows = resolve_discovery_batch_with_endpoints(&keys, &cfg, &endpoints)
+        .await
+        .expect("rows");
+    // One primary timeout, then every lookup goes straight to the mirror; the 10s
+    // backoff never applies.
+    assert!(started.elapsed() < std::time::Duration::from_secs(3));
+    for row in &rows {
+        assert!(matches!(
+            row.status,
+            DiscoveryStatus::Unresolved {
+                reason: UnresolvedReason::NotFound
+            }
+        ));
+    }
+
+    let statuses = endpoints.statuses();
+    assert_eq!(statuses[0].base_url, primary);
+    assert!(!statuses[0].healthy);
+    assert_eq!(statuses[0].failures, 1);
+    assert!(statuses[1].healthy);
+    assert_eq!(statuses[1].successes, 3);
+    assert!(statuses[1].ewma_latency_ms.is_some());
+}
+
+#[tokio::test]
+async fn lookups_are_capped_at_the_key_deadline_and_doomed_ones_skipped() {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+    let slow = serve(Router::new().route(
+        "/markets/slug/{slug}",
+        get(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            async {
+                tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+                StatusCode::OK
+            }
+        }),
+    ))
+    .await;
+    let endpoints = GammaEndpointPool::new(GammaEndpointConfig {
+        base_urls: vec![slow],
+        ..GammaEndpointConfig::default()
+    });
+    let now = chrono::Utc::now().timestamp();
+    // Starts in 2-3s.
+    let soon = DiscoveryKey::from_slug(Coin::BTC, Duration::M5, now + 3, "soon");
+    let cfg = DiscoveryConfig {
+        timeout_ms: 10_000,
+        max_retries: 3,
+        ..DiscoveryConfig::default()
+    };
+
+    // Less than the minimum budget left: no call at all, and the row says why.
+    let rows = resolve_discovery_batch_with_endpoints(
+        std::slice::from_ref(&soon),
+        &DiscoveryConfig {
+            min_call_budget_ms: 5_000,
+            ..cfg
+        },
+        &endpoints,
+    )
+    .await
+    .expect("rows");
+    assert_eq!(
+        rows[0].status,
+        DiscoveryStatus::Unresolved {
+            reason: UnresolvedReason::DeadlineSkipped
+        }
+    );
+    assert_eq!(hits.load(Ordering::SeqCst), 0);
+
+    // Otherwise the 10s lookup timeout is cut to the time left before the start.
+    let started = std::time::Instant::now();
+    let err = resolve_discovery_batch_with_endpoints(&[soon], &cfg, &endpoints)
+        .await
+        .expect_err("the only lookup timed out");
+    assert!(matches!(err, DiscoveryError::Transport(_)));
+    assert!(started.elapsed() < std::time::Duration::from_secs(4));
+    assert_eq!(hits.load(Ordering::SeqCst), 1, "no retry past the deadline");
+}
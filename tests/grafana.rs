@@ -0,0 +1,113 @@
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+};
+use pmm::{grafana_router, DashboardRow, DashboardSnapshot, GrafanaSeries, SnapshotRecorder};
+use tower::util::ServiceExt;
+
+fn live_row(slug: &str, net_profit: &str, position_net: &str) -> DashboardRow {
+    let mut row = DashboardRow::unresolved(slug, "BTC", "5m");
+    row.mock_columns.clear();
+    row.net_profit = Some(net_profit.to_string());
+    row.position_net = Some(position_net.to_string());
+    row
+}
+
+async fn post_json(recorder: &SnapshotRecorder, path: &str, body: &str) -> (StatusCode, String) {
+    let response = grafana_router(recorder.clone())
+        .oneshot(
+            Request::post(path)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, String::from_utf8(bytes.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn grafana_datasource_serves_recorded_series() {
+    let recorder = SnapshotRecorder::new(3_600);
+    // 2026-01-01T00:00:00Z plus one sample per second.
+    let base_ms = 1_767_225_600_000_i64;
+    for i in 0..10 {
+        let snapshot = DashboardSnapshot {
+            rows: vec![
+                live_row("btc-updown-5m-a", &format!("{i}"), "3@0.5@YES"),
+                live_row("eth-updown-5m-b", "0.5", "1@0.4@NO"),
+            ],
+            seq: i as u64,
+        };
+        recorder.record(&snapshot, base_ms + i * 1_000);
+    }
+
+    let status = grafana_router(recorder.clone())
+        .oneshot(Request::get("/grafana").body(Body::empty()).unwrap())
+        .await
+        .unwrap()
+        .status();
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) =
+        post_json(&recorder, "/grafana/search", r#"{"target":"net_profit"}"#).await;
+    assert_eq!(status, StatusCode::OK);
+    let targets: Vec<String> = serde_json::from_str(&body).unwrap();
+    assert_eq!(
+        targets,
+        [
+            "net_profit:btc-updown-5m-a",
+            "net_profit:eth-updown-5m-b",
+            "net_profit:total"
+        ]
+    );
+
+    let query = r#"{
+        "range": {"from": "2026-01-01T00:00:02Z", "to": "2026-01-01T00:00:05Z"},
+        "targets": [
+            {"target": "net_profit:total", "refId": "A", "type": "timeserie"},
+            {"target": "position_net:*", "refId": "B", "type": "timeserie"}
+        ],
+        "maxDataPoints": 100
+    }"#;
+    let (status, body) = post_json(&recorder, "/grafana/query", query).await;
+    assert_eq!(status, StatusCode::OK);
+    let series: Vec<GrafanaSeries> = serde_json::from_str(&body).unwrap();
+    assert_eq!(series.len(), 3);
+    assert_eq!(series[0].target, "net_profit:total");
+    assert_eq!(
+        series[0].datapoints,
+        [
+            (2.5, base_ms + 2_000),
+            (3.5, base_ms + 3_000),
+            (4.5, base_ms + 4_000),
+            (5.5, base_ms + 5_000)
+        ]
+    );
+    assert_eq!(series[1].target, "position_net:btc-updown-5m-a");
+    assert_eq!(series[2].target, "position_net:eth-updown-5m-b");
+    assert_eq!(series[2].datapoints[0].0, -1.0);
+
+    // maxDataPoints thins the series instead of truncating the range.
+    let query = r#"{
+        "range": {"from": "2026-01-01T00:00:00Z", "to": "2026-01-01T00:01:00Z"},
+        "targets": [{"target": "net_profit:btc-updown-5m-a"}],
+        "maxDataPoints": 5
+    }"#;
+    let (_, body) = post_json(&recorder, "/grafana/query", query).await;
+    let series: Vec<GrafanaSeries> = serde_json::from_str(&body).unwrap();
+    assert_eq!(
+        series[0]
+            .datapoints
+            .iter()
+            .map(|(value, _)| *value)
+            .collect::<Vec<_>>(),
+        [0.0, 2.0, 4.0, 6.0, 8.0]
+    );
+
+    let inverted = r#"{"range": {"from": "2026-01-02T00:00:00Z", "to": "2026-01-01T00:00:00Z"}}"#;
+    let (status, _) = post_json(&recorder, "/grafana/query", inverted).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,92 @@
+use std::sync::Arc;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header, Request, StatusCode};
+use axum::Router;
+use pmm::{
+    journal_router, Coin, Duration, JournalConfig, JournalNote, MarketJournal, MarketPosition,
+    OutcomePosition, PositionSource, TradeJournal,
+};
+use tower::ServiceExt;
+
+const SLUG: &str = "btc-updown-5m-1771449000";
+
+async fn call(app: &Router, method: &str, uri: &str, body: Option<&str>) -> (StatusCode, Vec<u8>) {
+    let mut request = Request::builder().method(method).uri(uri);
+    if body.is_some() {
+        request = request.header(header::CONTENT_TYPE, "application/json");
+    }
+    let response = app
+        .clone()
+        .oneshot(
+            request
+                .body(body.map_or_else(Body::empty, |body| Body::from(body.to_string())))
+                .unwrap(),
+        )
+        .await
+        .expect("response");
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, body.to_vec())
+}
+
+struct Holding;
+
+impl PositionSource for Holding {
+    fn position(&self, slug: &str) -> Option<MarketPosition> {
+        (slug == SLUG).then(|| MarketPosition {
+            yes: OutcomePosition {
+                shares: 10.0,
+                avg_price: 0.45,
+                realized_pnl_usdc: 0.0,
+            },
+            ..MarketPosition::default()
+        })
+    }
+}
+
+#[tokio::test]
+async fn notes_attach_to_intervals_and_come_back_with_the_position() {
+    let dir = tempfile::tempdir().unwrap();
+    let cfg = JournalConfig {
+        store_path: Some(dir.path().join("trade_journal.sqlite")),
+    };
+    let app = journal_router(TradeJournal::from_config(&cfg), Some(Arc::new(Holding)));
+
+    let uri = format!("/markets/{SLUG}/journal");
+    let (status, body) = call(
+        &app,
+        "POST",
+        &uri,
+        Some(r#"{"author":" ana ","text":"Faded the spike: book thin above 0.6"}"#),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let note: JournalNote = serde_json::from_slice(&body).unwrap();
+    assert_eq!(note.author, "ana");
+    let interval = note.interval.expect("5m slug names its interval");
+    assert_eq!(interval.coin, Coin::BTC);
+    assert_eq!(interval.duration, Duration::M5);
+    assert_eq!(interval.start_ts_utc, 1_771_449_000);
+    assert_eq!(interval.end_ts_utc, 1_771_449_300);
+
+    let (status, _) = call(&app, "POST", &uri, Some(r#"{"author":"ana","text":"  "}"#)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Notes survive a restart and are served next to the market's ledger.
+    let app = journal_router(TradeJournal::from_config(&cfg), Some(Arc::new(Holding)));
+    let (status, body) = call(&app, "GET", &uri, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let journal: MarketJournal = serde_json::from_slice(&body).unwrap();
+    assert_eq!(journal.notes, vec![note.clone()]);
+    assert_eq!(journal.position.unwrap().yes.shares, 10.0);
+
+    let (status, body) = call(&app, "GET", "/markets/other-market/journal", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let journal: MarketJournal = serde_json::from_slice(&body).unwrap();
+    assert!(journal.notes.is_empty() && journal.position.is_none());
+
+    let (_, body) = call(&app, "GET", "/journal?since_ts_utc=0&limit=10", None).await;
+    let recent: Vec<JournalNote> = serde_json::from_slice(&body).unwrap();
+    assert_eq!(recent, vec![note]);
+}
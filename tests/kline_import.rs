@@ -0,0 +1,249 @@
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use parquet::data_type::{DoubleType, Int64Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::parser::parse_message_type;
+use pmm::{
+    import_klines, BinanceSymbol, KlineConflictPolicy, KlineImportError, KlineImportFormat,
+    KlineImportRequest, KlineStore, KlineValidationConfig,
+};
+use tempfile::tempdir;
+
+const START_MS: i64 = 1_704_067_200_000; // 2024-01-01T00:00:00Z
+
+fn open_store(dir: &Path) -> KlineStore {
+    KlineStore::open(
+        &dir.join("klines_1s.sqlite"),
+        KlineValidationConfig::default(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn binance_layout_csv_bootstraps_an_empty_store() {
+    let dir = tempdir().unwrap();
+    let mut store = open_store(dir.path());
+    let fixture = Path::new("tests/fixtures/binance/BTCUSDT_1s_sample.csv");
+
+    let report = import_klines(
+        &mut store,
+        &KlineImportRequest::new(fixture, BinanceSymbol::BtcUsdt),
+    )
+    .unwrap();
+    assert_eq!(report.format, KlineImportFormat::Csv);
+    assert_eq!(report.first_open_time_ms, START_MS);
+    assert_eq!(report.inserted, report.rows_read);
+    assert_eq!(report.coverage.missing_points, 0);
+    assert_eq!(
+        store
+            .count_range(BinanceSymbol::BtcUsdt, START_MS, START_MS + 3_600_000)
+            .unwrap(),
+        report.rows_read
+    );
+
+    // The same file again changes nothing.
+    let again = import_klines(
+        &mut store,
+        &KlineImportRequest::new(fixture, BinanceSymbol::BtcUsdt),
+    )
+    .unwrap();
+    assert_eq!((again.inserted, again.unchanged), (0, report.rows_read));
+}
+
+#[test]
+fn named_csv_reports_gaps_duplicates_and_conflicts() {
+    let dir = tempdir().unwrap();
+    let mut store = open_store(dir.path());
+    let path = dir.path().join("btc.csv");
+    let row = |second: i64, close: f64| {
+        format!(
+            "{},100,101,99,{close},2,200\n",
+            (START_MS + second * 1_000) * 1_000 // microseconds
+        )
+    };
+    fs::write(
+        &path,
+        format!(
+            "open_time,open,high,low,close,volume,quote_volume\n{}{}{}{}{}",
+            row(0, 100.0),
+            row(1, 100.5),
+            row(1, 100.5),
+            row(4, 100.0),
+            row(5, 100.0),
+        ),
+    )
+    .unwrap();
+    let import = |store: &mut KlineStore, on_conflict, dry_run| {
+        import_klines(
+            store,
+            &KlineImportRequest {
+                on_conflict,
+                dry_run,
+                ..KlineImportRequest::new(&path, BinanceSymbol::EthUsdt)
+            },
+        )
+    };
+
+    let report = import(&mut store, KlineConflictPolicy::Keep, false).unwrap();
+    assert_eq!(report.rows_read, 5);
+    assert_eq!(report.coverage.duplicate_points_removed, 1);
+    assert_eq!(report.coverage.missing_points, 2);
+    assert_eq!(
+        report.coverage.gap_ranges,
+        vec![(START_MS + 2_000, START_MS + 3_000)]
+    );
+    assert_eq!(report.inserted, 4);
+
+    // Another archive disagrees about second 4.
+    fs::write(
+        &path,
+        format!(
+            "open_time,open,high,low,close,volume,quote_volume\n{}{}",
+            row(4, 100.8),
+            row(6, 100.0)
+        ),
+    )
+    .unwrap();
+    let dry = import(&mut store, KlineConflictPolicy::Overwrite, true).unwrap();
+    assert_eq!((dry.inserted, dry.conflicts, dry.overwritten), (1, 1, 1));
+    let closes = |store: &KlineStore| {
+        store
+            .closes_range(BinanceSymbol::EthUsdt, START_MS, START_MS + 10_000)
+            .unwrap()
+    };
+    assert_eq!(closes(&store).len(), 4);
+
+    let kept = import(&mut store, KlineConflictPolicy::Keep, false).unwrap();
+    assert_eq!((kept.inserted, kept.conflicts, kept.overwritten), (1, 1, 0));
+    let sample = &kept.conflict_samples[0];
+    assert_eq!(sample.open_time_ms, START_MS + 4_000);
+    assert_eq!((sample.stored.close, sample.imported.close), (100.0, 100.8));
+    assert_eq!(closes(&store)[2], (START_MS + 4_000, 100.0));
+
+    let overwritten = import(&mut store, KlineConflictPolicy::Overwrite, false).unwrap();
+    assert_eq!((overwritten.conflicts, overwritten.overwritten), (1, 1));
+    assert_eq!(closes(&store)[2], (START_MS + 4_000, 100.8));
+}
+
+#[test]
+fn invalid_files_fail_before_anything_is_written() {
+    let dir = tempdir().unwrap();
+    let mut store = open_store(dir.path());
+    let path = dir.path().join("bad.csv");
+    let header = "open_time,open,high,low,close,volume,quote_volume\n";
+    let cases = [
+        (
+            "open_time,open,high,low,close,volume\n1704067200000,1,1,1,1,1\n",
+            "missing required column quote_asset_volume",
+        ),
+        ("1704067200500,1,1,1,1,1,1\n", "not on a whole second"),
+        ("1704067200000,1,1,1,x,1,1\n", "invalid close value 'x'"),
+        (
+            "1704067200000,1,1,1,1,1,1\n1704067200000,1,1,1,2,1,1\n",
+            "appears twice with different values",
+        ),
+    ];
+    for (body, expected) in cases {
+        let body = if body.starts_with("open_time") {
+            body.to_string()
+        } else {
+            format!("{header}{body}")
+        };
+        fs::write(&path, body).unwrap();
+        let err = import_klines(
+            &mut store,
+            &KlineImportRequest::new(&path, BinanceSymbol::BtcUsdt),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains(expected), "{err}");
+    }
+
+    fs::write(
+        &path,
+        format!("{header}1704067200000,1,1,1,1,1,1\n1704067203000,1,1,1,1,1,1\n"),
+    )
+    .unwrap();
+    let err = import_klines(
+        &mut store,
+        &KlineImportRequest {
+            require_continuous: true,
+            ..KlineImportRequest::new(&path, BinanceSymbol::BtcUsdt)
+        },
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err,
+        KlineImportError::Discontinuous {
+            missing_points: 2,
+            gap_ranges: 1
+        }
+    ));
+    assert_eq!(
+        store
+            .count_range(BinanceSymbol::BtcUsdt, START_MS, START_MS + 10_000)
+            .unwrap(),
+        0
+    );
+}
+
+#[test]
+fn parquet_archives_import_with_optional_columns_defaulted() {
+    let dir = tempdir().unwrap();
+    let mut store = open_store(dir.path());
+    let path = dir.path().join("sol.parquet");
+    let schema = parse_message_type(
+        "message klines {
+            REQUIRED INT64 open_time;
+            REQUIRED DOUBLE open;
+            REQUIRED DOUBLE high;
+            REQUIRED DOUBLE low;
+            REQUIRED DOUBLE close;
+            REQUIRED DOUBLE volume;
+            REQUIRED DOUBLE quote_asset_volume;
+        }",
+    )
+    .unwrap();
+    let mut writer = SerializedFileWriter::new(
+        fs::File::create(&path).unwrap(),
+        Arc::new(schema),
+        Arc::new(WriterProperties::builder().build()),
+    )
+    .unwrap();
+    let mut group = writer.next_row_group().unwrap();
+    let mut column = group.next_column().unwrap().unwrap();
+    column
+        .typed::<Int64Type>()
+        .write_batch(&[START_MS, START_MS + 1_000], None, None)
+        .unwrap();
+    column.close().unwrap();
+    for values in [[10.0, 10.1], [10.2, 10.3], [9.9, 10.0], [10.1, 10.2]]
+        .into_iter()
+        .chain([[3.0, 4.0], [30.3, 40.8]])
+    {
+        let mut column = group.next_column().unwrap().unwrap();
+        column
+            .typed::<DoubleType>()
+            .write_batch(&values, None, None)
+            .unwrap();
+        column.close().unwrap();
+    }
+    group.close().unwrap();
+    writer.close().unwrap();
+
+    let report = import_klines(
+        &mut store,
+        &KlineImportRequest::new(&path, BinanceSymbol::SolUsdt),
+    )
+    .unwrap();
+    assert_eq!(report.format, KlineImportFormat::Parquet);
+    assert_eq!(report.inserted, 2);
+    assert_eq!(
+        store
+            .closes_range(BinanceSymbol::SolUsdt, START_MS, START_MS + 2_000)
+            .unwrap(),
+        vec![(START_MS, 10.1), (START_MS + 1_000, 10.2)]
+    );
+}
@@ -0,0 +1,146 @@
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use parquet::record::RowAccessor;
+use pmm::{
+    export_training_dataset, read_report_json, BinanceSymbol, DatasetFormat,
+    FeatureTransformRequest, Kline1s, KlineStore, KlineValidationConfig, TrainingExportConfig,
+    TrainingExportReport, ALL_BINANCE_SYMBOLS, LABEL_COLUMNS,
+};
+use pmm::{Coin, Duration};
+
+// 2025-01-01T00:00:00Z
+const START: i64 = 1_735_689_600;
+
+fn kline(open_time_ms: i64, close: f64) -> Kline1s {
+    Kline1s {
+        open_time_ms,
+        open: close,
+        high: close,
+        low: close,
+        close,
+        volume: 1.0,
+        close_time_ms: open_time_ms + 999,
+        quote_asset_volume: close,
+        trade_count: 1,
+        taker_buy_base_volume: 0.5,
+        taker_buy_quote_volume: close / 2.0,
+    }
+}
+
+/// Three 5m intervals from `START`; BTC goes up, down, up, the others stay flat.
+fn seed_store(path: &std::path::Path) {
+    let mut store = KlineStore::open(path, KlineValidationConfig::default()).unwrap();
+    for symbol in ALL_BINANCE_SYMBOLS {
+        let rows = (START - 120..START + 900)
+            .map(|ts| {
+                let offset = (ts - START).rem_euclid(300) as f64 * 0.001;
+                let close = match (symbol, (ts - START).div_euclid(300)) {
+                    (BinanceSymbol::BtcUsdt, 0 | 2) => 100.0 + offset,
+                    (BinanceSymbol::BtcUsdt, 1) => 100.0 - offset,
+                    _ => 100.0,
+                };
+                kline(ts * 1_000, close)
+            })
+            .collect();
+        store.upsert_rows(symbol, rows).unwrap();
+    }
+}
+
+#[test]
+fn export_labels_each_horizon_and_writes_csv_and_parquet_shards() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = dir.path().join("klines_1s.sqlite");
+    seed_store(&store);
+    let range = FeatureTransformRequest {
+        start_ts_ms_utc: START * 1_000,
+        end_ts_ms_utc_exclusive: (START + 900) * 1_000,
+    };
+
+    let mut cfg = TrainingExportConfig::new(dir.path().join("csv"), DatasetFormat::Csv);
+    cfg.durations = vec![Duration::M5];
+    cfg.horizons_seconds = vec![300, 60, 3_600];
+    cfg.shard_rows = 10;
+    let report = export_training_dataset(&store, &range, &cfg).unwrap();
+    let m5 = &report.durations[0];
+    // 3 intervals × 4 coins × 2 horizons that fit; BTC's middle interval closes down.
+    assert_eq!(m5.samples, 24);
+    assert_eq!(m5.up_labels, 22);
+    assert_eq!(m5.skipped_samples, 0);
+    assert_eq!(
+        m5.shards.iter().map(|shard| shard.rows).collect::<Vec<_>>(),
+        vec![10, 10, 4]
+    );
+    assert_eq!(
+        read_report_json::<TrainingExportReport>(&cfg.output_dir.join("manifest.json"))
+            .unwrap()
+            .report,
+        report
+    );
+
+    let mut csv = csv::Reader::from_path(cfg.output_dir.join(&m5.shards[0].path)).unwrap();
+    let header = csv.headers().unwrap().clone();
+    assert_eq!(header.len(), LABEL_COLUMNS.len() + m5.schema.columns.len());
+    assert_eq!(&header[11], "label_up");
+    assert_eq!(&header[12], m5.schema.columns[0].name);
+    let records = csv.records().map(Result::unwrap).collect::<Vec<_>>();
+    let btc = records
+        .iter()
+        .filter(|record| &record[0] == "BTC" && record[3] == START.to_string())
+        .map(|record| {
+            (
+                record[2].parse::<u32>().unwrap(),
+                record[5].parse::<i64>().unwrap(),
+                record[11].to_string(),
+            )
+        })
+        .collect::<Vec<_>>();
+    // Full-length samples sit on the second before the start; 60s ones a minute
+    // before the close.
+    assert_eq!(
+        btc,
+        vec![
+            (300, (START - 1) * 1_000, "1".to_string()),
+            (60, (START + 239) * 1_000, "1".to_string()),
+        ]
+    );
+
+    cfg.output_dir = dir.path().join("parquet");
+    cfg.format = DatasetFormat::Parquet;
+    cfg.shard_rows = 100;
+    let report = export_training_dataset(&store, &range, &cfg).unwrap();
+    let m5 = &report.durations[0];
+    assert_eq!(m5.shards.len(), 1);
+    let reader = SerializedFileReader::new(
+        std::fs::File::open(cfg.output_dir.join(&m5.shards[0].path)).unwrap(),
+    )
+    .unwrap();
+    let footer = reader
+        .metadata()
+        .file_metadata()
+        .key_value_metadata()
+        .unwrap()
+        .iter()
+        .map(|kv| (kv.key.clone(), kv.value.clone().unwrap_or_default()))
+        .collect::<Vec<_>>();
+    assert!(footer.contains(&(
+        "pmm.schema_fingerprint".to_string(),
+        m5.schema.fingerprint.clone()
+    )));
+    let rows = reader
+        .get_row_iter(None)
+        .unwrap()
+        .map(Result::unwrap)
+        .collect::<Vec<_>>();
+    assert_eq!(rows.len(), 24);
+    let down = rows
+        .iter()
+        .filter(|row| row.get_int(11).unwrap() == 0)
+        .collect::<Vec<_>>();
+    assert_eq!(down.len(), 2);
+    for row in down {
+        assert_eq!(row.get_string(0).unwrap(), Coin::BTC.ticker());
+        assert_eq!(row.get_long(3).unwrap(), START + 300);
+        // 100.299 → 99.701
+        assert!((row.get_double(6).unwrap() - 100.299).abs() < 1e-9);
+        assert!((row.get_double(7).unwrap() - 99.701).abs() < 1e-9);
+    }
+}
@@ -10,9 +10,9 @@ use axum::{
 };
 use pmm::{
     dashboard_router, demo_snapshot, log_app_bind, log_app_start, log_source_selected,
-    resolve_discovery_batch_with_fetcher, Coin, DiscoveryConfig, DiscoveryError, DiscoveryKey,
-    DiscoveryStatus, Duration as MarketDuration, InMemoryMockSnapshotSource, LoggingConfig,
-    SlugFetchOutcome,
+    resolve_discovery_batch_with_fetcher, with_request_ids, Coin, DiscoveryConfig, DiscoveryError,
+    DiscoveryKey, DiscoveryStatus, Duration as MarketDuration, InMemoryMockSnapshotSource,
+    LoggingConfig, SlugFetchOutcome, REQUEST_ID_HEADER,
 };
 use tower::util::ServiceExt;
 use tracing::dispatcher::with_default;
@@ -79,7 +79,7 @@ fn capture_logs(max_level: Level, f: impl FnOnce()) -> String {
 
 fn sample_key(slug: &str) -> DiscoveryKey {
     DiscoveryKey::from_slug(
-        Coin::Btc,
+        Coin::BTC,
         MarketDuration::M5,
         1_735_689_900,
         slug.to_string(),
@@ -170,3 +170,51 @@ fn snapshot_route_emits_http_snapshot_event() {
 
     assert!(logs.contains("\"event\":\"http.snapshot.request\""));
 }
+
+#[test]
+fn request_ids_are_logged_and_echoed_in_responses() {
+    let logs = capture_logs(Level::INFO, || {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("single-thread runtime should build");
+
+        rt.block_on(async {
+            let source = Arc::new(InMemoryMockSnapshotSource::new(demo_snapshot()));
+            let app = with_request_ids(dashboard_router(source));
+
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .uri("/dashboard/snapshot")
+                        .header(REQUEST_ID_HEADER, "user-report-42")
+                        .body(Body::empty())
+                        .expect("request should build"),
+                )
+                .await
+                .expect("snapshot request should succeed");
+            assert_eq!(response.headers()[REQUEST_ID_HEADER], "user-report-42");
+
+            let response = app
+                .oneshot(
+                    Request::builder()
+                        .uri("/dashboard/snapshot")
+                        .header(REQUEST_ID_HEADER, "bad id")
+                        .body(Body::empty())
+                        .expect("request should build"),
+                )
+                .await
+                .expect("snapshot request should succeed");
+            let generated = response.headers()[REQUEST_ID_HEADER].to_str().unwrap();
+            assert_ne!(generated, "bad id");
+            assert!(!generated.is_empty());
+        });
+    });
+
+    let tagged = logs
+        .lines()
+        .find(|line| line.contains("\"event\":\"http.snapshot.request\""))
+        .expect("snapshot event should be logged");
+    assert!(tagged.contains("\"request_id\":\"user-report-42\""));
+}
@@ -0,0 +1,192 @@
+use pmm::{
+    apply_model_probabilities, apply_probability_smoothing, format_row_for_display,
+    load_model_registry, quote_feature_schema, Coin, DashboardRow, Duration, LivePrice, LivePrices,
+    ModelConfig, ModelError, ModelInputRecord, ModelInputSource, ModelInputStore,
+    ProbabilitySmoother, ProbabilitySmoothingConfig,
+};
+
+fn write_artifact(dir: &std::path::Path, name: &str, artifact: serde_json::Value) {
+    std::fs::write(dir.join(name), artifact.to_string()).unwrap();
+}
+
+fn logistic(model_id: &str, horizons: &[&str], fingerprint: &str) -> serde_json::Value {
+    serde_json::json!({
+        "format_version": 1,
+        "model_id": model_id,
+        "coin": "BTC",
+        "horizons": horizons,
+        "schema_version": 1,
+        "schema_fingerprint": fingerprint,
+        "model": { "kind": "logistic", "intercept": 0.0, "coefficients": [100.0, 0.0] }
+    })
+}
+
+#[test]
+fn artifacts_load_by_schema_and_fill_the_probability_column() {
+    let schema = quote_feature_schema();
+    let dir = tempfile::tempdir().unwrap();
+    write_artifact(
+        dir.path(),
+        "btc-short.json",
+        logistic("btc-short", &["5m", "15m"], &schema.fingerprint),
+    );
+    std::fs::write(dir.path().join("README.txt"), "not an artifact").unwrap();
+    let cfg = ModelConfig {
+        dir: Some(dir.path().to_path_buf()),
+    };
+    let models = load_model_registry(&cfg, &schema).unwrap();
+    assert_eq!(
+        models
+            .model_for(Coin::BTC, Duration::M15)
+            .map(|model| model.model_id()),
+        Some("btc-short")
+    );
+    assert!(models.model_for(Coin::BTC, Duration::H1).is_none());
+
+    let start = 1_735_689_600;
+    let prices = LivePrices::default();
+    for (ts_ms_utc, price) in [
+        (start * 1_000 + 200, 100.0),
+        (start * 1_000 + 60_000, 101.0),
+    ] {
+        prices.record("BTCUSDT", LivePrice { price, ts_ms_utc });
+    }
+    let mut btc = DashboardRow::unresolved_with_times("btc-5m", "BTC", "5m", start, start + 300);
+    let mut eth = DashboardRow::unresolved_with_times("eth-5m", "ETH", "5m", start, start + 300);
+    btc.mock_columns.push("probability".to_string());
+    let captures = apply_model_probabilities(&models, &prices, [&mut btc, &mut eth], start + 60);
+    // sigmoid(100 × ln(1.01))
+    let probability: f64 = btc.probability.as_deref().unwrap().parse().unwrap();
+    assert!((probability - 1.0 / (1.0 + (-100.0 * 1.01_f64.ln()).exp())).abs() < 1e-12);
+    assert!(!btc.is_mock_column("probability"));
+    assert_eq!(eth.probability, None);
+
+    // The captured inputs reproduce the shown probability exactly.
+    let store = ModelInputStore::open(&dir.path().join("inputs.sqlite"), 86_400).unwrap();
+    assert_eq!(store.record(&captures).unwrap(), 1);
+    let replayed = store.at("btc-5m", start + 90).unwrap();
+    assert_eq!(replayed, captures);
+    let capture = &replayed[0];
+    assert_eq!(capture.source, ModelInputSource::Dashboard);
+    assert_eq!(capture.schema_fingerprint, schema.fingerprint);
+    let rescored = models
+        .predict(Coin::BTC, Duration::M5, &capture.feature_row())
+        .unwrap();
+    assert_eq!(rescored.probability_yes.to_bits(), probability.to_bits());
+    assert_eq!(rescored.model_id, capture.model_id);
+
+    // A second artifact for a covered horizon, or one trained on another schema, fails
+    // the load.
+    write_artifact(
+        dir.path(),
+        "btc-dup.json",
+        logistic("btc-dup", &["5m"], &schema.fingerprint),
+    );
+    assert!(matches!(
+        load_model_registry(&cfg, &schema),
+        Err(ModelError::Duplicate { .. })
+    ));
+    std::fs::remove_file(dir.path().join("btc-dup.json")).unwrap();
+    write_artifact(dir.path(), "stale.json", logistic("stale", &["1h"], "old"));
+    assert!(matches!(
+        load_model_registry(&cfg, &schema),
+        Err(ModelError::Schema { .. })
+    ));
+
+    // No directory, no models.
+    let missing = ModelConfig {
+        dir: Some(dir.path().join("missing")),
+    };
+    assert!(load_model_registry(&missing, &schema)
+        .unwrap()
+        .model_for(Coin::BTC, Duration::M5)
+        .is_none());
+}
+
+#[test]
+fn horizon_probabilities_rescore_the_move_at_each_time_to_expiry() {
+    let schema = quote_feature_schema();
+    let dir = tempfile::tempdir().unwrap();
+    let mut artifact = logistic("btc-horizons", &["5m"], &schema.fingerprint);
+    artifact["model"]["coefficients"] = serde_json::json!([100.0, -1.0]);
+    write_artifact(dir.path(), "btc-horizons.json", artifact);
+    let cfg = ModelConfig {
+        dir: Some(dir.path().to_path_buf()),
+    };
+    let models = load_model_registry(&cfg, &schema).unwrap();
+
+    let start = 1_735_689_600;
+    let prices = LivePrices::default();
+    for (ts_ms_utc, price) in [
+        (start * 1_000 + 200, 100.0),
+        (start * 1_000 + 60_000, 101.0),
+    ] {
+        prices.record("BTCUSDT", LivePrice { price, ts_ms_utc });
+    }
+    let mut btc = DashboardRow::unresolved_with_times("btc-5m", "BTC", "5m", start, start + 300);
+    apply_model_probabilities(&models, &prices, [&mut btc], start + 60);
+
+    // sigmoid(100 × ln(1.01) − remaining / length); 900s does not fit in a 5m interval.
+    let expected = |remaining: f64| 1.0 / (1.0 + (-(100.0 * 1.01_f64.ln() - remaining)).exp());
+    let horizons: Vec<(u32, f64)> = btc
+        .probability_horizons
+        .iter()
+        .map(|(horizon, probability)| (*horizon, probability.parse().unwrap()))
+        .collect();
+    assert_eq!(horizons.len(), 2);
+    assert_eq!(horizons[0].0, 60);
+    assert!((horizons[0].1 - expected(0.2)).abs() < 1e-12);
+    assert_eq!(horizons[1].0, 300);
+    assert!((horizons[1].1 - expected(1.0)).abs() < 1e-12);
+    let probability: f64 = btc.probability.as_deref().unwrap().parse().unwrap();
+    assert!((probability - expected(0.8)).abs() < 1e-12);
+}
+
+#[test]
+fn smoothing_keeps_raw_and_published_probabilities_on_the_row() {
+    let capture = |slug: &str, ts_utc: i64, probability_yes: f64| ModelInputRecord {
+        slug: slug.to_string(),
+        ts_utc,
+        source: ModelInputSource::Dashboard,
+        model_id: "btc-short".to_string(),
+        schema_version: 1,
+        schema_fingerprint: "fp".to_string(),
+        feature_ts_ms_utc: ts_utc * 1_000,
+        values: vec![0.0, 0.0],
+        probability_yes,
+    };
+    let config = ProbabilitySmoothingConfig {
+        alpha: 0.5,
+        hysteresis: 0.03,
+        display: true,
+    };
+    let smoother = ProbabilitySmoother::new(config);
+    let mut btc = DashboardRow::unresolved_with_times("btc-5m", "BTC", "5m", 0, 300);
+    let mut eth = DashboardRow::unresolved_with_times("eth-5m", "ETH", "5m", 0, 300);
+    for (ts_utc, raw) in [(1, 0.50), (2, 0.54)] {
+        btc.probability = Some(raw.to_string());
+        apply_probability_smoothing(
+            &smoother,
+            &[capture("btc-5m", ts_utc, raw)],
+            [&mut btc, &mut eth],
+        );
+    }
+    // 0.54 moves the average to 0.52, inside the band: the published value holds.
+    assert_eq!(btc.probability_raw.as_deref(), Some("0.54"));
+    assert_eq!(btc.probability_smoothed.as_deref(), Some("0.5"));
+    assert_eq!(btc.probability.as_deref(), Some("0.5"));
+    assert_eq!(eth.probability_smoothed, None);
+    let display = format_row_for_display(&btc, 60);
+    assert_eq!(display.probability_raw, "54%");
+
+    // Without `display` the column keeps showing the model output.
+    let smoother = ProbabilitySmoother::new(ProbabilitySmoothingConfig {
+        display: false,
+        ..config
+    });
+    btc.probability = Some("0.6".to_string());
+    apply_probability_smoothing(&smoother, &[capture("btc-5m", 3, 0.6)], [&mut btc]);
+    assert_eq!(btc.probability.as_deref(), Some("0.6"));
+    assert_eq!(btc.probability_smoothed.as_deref(), Some("0.6"));
+    assert!(format_row_for_display(&btc, 60).probability_raw.is_empty());
+}
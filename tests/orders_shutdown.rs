@@ -0,0 +1,115 @@
+use std::time::Duration;
+
+use pmm::{
+    cancel_all_on_shutdown, load_shutdown_report, shutdown_signal, OpenOrder, OrderSide,
+    PaperOrderBackend, ShutdownConfig, SizingSide, TradingClient,
+};
+
+fn order(order_id: &str, outcome: SizingSide, price: f64) -> OpenOrder {
+    OpenOrder {
+        order_id: order_id.to_string(),
+        token_id: format!("token-{}", outcome.as_str()),
+        market: "btc-updown-15m-1767225600".to_string(),
+        outcome: outcome.as_str().to_string(),
+        side: OrderSide::Buy,
+        price,
+        original_size: 10.0,
+        size_matched: 0.0,
+        status: "LIVE".to_string(),
+        created_ts_utc: 1_767_225_600,
+        expires_ts_utc: Some(1_767_226_500),
+    }
+}
+
+fn config(dir: &tempfile::TempDir, cancel_timeout_ms: u64) -> ShutdownConfig {
+    ShutdownConfig {
+        cancel_timeout_ms,
+        poll_interval_ms: 10,
+        state_path: Some(dir.path().join("state").join("shutdown_state.json")),
+    }
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn sigterm_cancels_resting_paper_orders_and_persists_state() {
+    let dir = tempfile::tempdir().unwrap();
+    let cfg = config(&dir, 2_000);
+    let backend = PaperOrderBackend::new().with_cancel_ack_delay(Duration::from_millis(50));
+    backend.place(order("yes-1", SizingSide::Yes, 0.48));
+    backend.place(order("no-1", SizingSide::No, 0.47));
+
+    let task = tokio::spawn({
+        let cfg = cfg.clone();
+        async move {
+            shutdown_signal().await;
+            let report = cancel_all_on_shutdown(&backend, &cfg).await.unwrap();
+            (report, backend)
+        }
+    });
+    // Let the task register its signal handler before the signal arrives.
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let status = std::process::Command::new("kill")
+        .args(["-TERM", &std::process::id().to_string()])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let (report, backend) = tokio::time::timeout(Duration::from_secs(5), task)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(report.is_clean() && !report.timed_out);
+    assert_eq!(
+        report
+            .cancelled
+            .iter()
+            .map(|order| order.order_id.as_str())
+            .collect::<Vec<_>>(),
+        ["no-1", "yes-1"]
+    );
+    assert!(backend.open_orders(None).await.unwrap().is_empty());
+    assert_eq!(
+        load_shutdown_report(cfg.state_path.as_ref().unwrap()).unwrap(),
+        report
+    );
+}
+
+#[tokio::test]
+async fn unacknowledged_cancels_time_out_and_are_persisted_as_open() {
+    let dir = tempfile::tempdir().unwrap();
+    let cfg = config(&dir, 150);
+    let backend = PaperOrderBackend::new();
+    backend.place(order("yes-1", SizingSide::Yes, 0.48));
+    backend.place(order("no-1", SizingSide::No, 0.47));
+    backend.ignore_cancels("no-1");
+
+    let started = std::time::Instant::now();
+    let report = cancel_all_on_shutdown(&backend, &cfg).await.unwrap();
+    assert!(started.elapsed() >= Duration::from_millis(150));
+    assert!(report.timed_out && !report.is_clean());
+    assert_eq!(
+        report.cancelled,
+        vec![order("yes-1", SizingSide::Yes, 0.48)]
+    );
+    assert_eq!(report.still_open, vec![order("no-1", SizingSide::No, 0.47)]);
+    assert_eq!(
+        load_shutdown_report(cfg.state_path.as_ref().unwrap()).unwrap(),
+        report
+    );
+}
+
+#[tokio::test]
+async fn shutdown_without_open_orders_returns_immediately() {
+    let dir = tempfile::tempdir().unwrap();
+    let cfg = ShutdownConfig {
+        state_path: None,
+        ..config(&dir, 60_000)
+    };
+    let started = std::time::Instant::now();
+    let report = cancel_all_on_shutdown(&PaperOrderBackend::new(), &cfg)
+        .await
+        .unwrap();
+    assert!(started.elapsed() < Duration::from_secs(1));
+    assert!(report.is_clean() && report.cancelled.is_empty());
+    assert!(!dir.path().join("state").exists());
+}
@@ -0,0 +1,103 @@
+use pmm::{
+    BookTop, FeeRegistry, FillCalibration, FillModel, FillModelConfig, OrderSide, OrderStyle,
+    PaperFill, PaperFillGenerator, PaperLedger, SimOrder,
+};
+
+fn model() -> FillModel {
+    FillModel::new(
+        FillModelConfig::default(),
+        FillCalibration {
+            observed_secs: 600.0,
+            buy_taker_volume: 300.0,
+            sell_taker_volume: 300.0,
+            ..FillCalibration::default()
+        },
+    )
+}
+
+fn book(ts_ms: i64) -> BookTop {
+    BookTop {
+        ts_ms,
+        best_bid: Some(0.48),
+        best_ask: Some(0.52),
+        bid_size: 20.0,
+        ask_size: 15.0,
+    }
+}
+
+/// Quotes both sides every second and crosses the spread every tenth second.
+fn run_session(generator: &mut PaperFillGenerator) -> PaperLedger {
+    let mut ledger = PaperLedger::default();
+    for sec in 0..100 {
+        let book = book(1_767_225_600_000 + sec * 1_000);
+        let mut orders = vec![
+            SimOrder {
+                side: OrderSide::Buy,
+                size: 10.0,
+                style: OrderStyle::Maker { price: 0.48 },
+            },
+            SimOrder {
+                side: OrderSide::Sell,
+                size: 10.0,
+                style: OrderStyle::Maker { price: 0.52 },
+            },
+        ];
+        if sec % 10 == 0 {
+            orders.push(SimOrder {
+                side: OrderSide::Sell,
+                size: 5.0,
+                style: OrderStyle::Taker,
+            });
+        }
+        for order in &orders {
+            if let Some(fill) = generator.fill(order, &book) {
+                ledger.apply(&fill);
+            }
+        }
+    }
+    ledger
+}
+
+#[test]
+fn same_seed_and_books_give_the_same_ledger() {
+    let fees = FeeRegistry::builtin();
+    let mut first = PaperFillGenerator::new(model(), 42).with_fees(fees.clone(), "crypto_15_min");
+    let mut second = PaperFillGenerator::new(model(), 42).with_fees(fees, "crypto_15_min");
+    let a = run_session(&mut first);
+    let b = run_session(&mut second);
+    assert_eq!(a, b);
+    assert_eq!(a.pnl_usdc(1.0), b.pnl_usdc(1.0));
+
+    // Ten deterministic taker sells at the bid, plus some of the 200 maker quotes.
+    let takers = a
+        .fills
+        .iter()
+        .filter(|fill| fill.side == OrderSide::Sell && fill.price == 0.48)
+        .count();
+    assert_eq!(takers, 10);
+    assert!(a.fills.len() > 10 && a.fills.len() < 210);
+    assert!(a.fees_usdc != 0.0);
+
+    let mut other = PaperFillGenerator::new(model(), 43);
+    assert_ne!(run_session(&mut other).fills, a.fills);
+}
+
+#[test]
+fn ledger_tracks_position_cash_and_fees_exactly() {
+    let mut ledger = PaperLedger::default();
+    let fill = |seq, side, size, price, fee_usdc| PaperFill {
+        seq,
+        ts_ms: 0,
+        side,
+        size,
+        price,
+        fee_usdc,
+    };
+    ledger.apply(&fill(0, OrderSide::Buy, 10.0, 0.25, 0.0));
+    ledger.apply(&fill(1, OrderSide::Sell, 4.0, 0.5, 0.125));
+    assert_eq!(ledger.position, 6.0);
+    assert_eq!(ledger.cash_usdc, -2.5 + 2.0 - 0.125);
+    assert_eq!(ledger.fees_usdc, 0.125);
+    assert_eq!(ledger.pnl_usdc(1.0), 5.375);
+    assert_eq!(ledger.pnl_usdc(0.0), -0.625);
+}
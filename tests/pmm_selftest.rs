@@ -0,0 +1,107 @@
+use std::process::Command;
+
+#[test]
+fn selftest_passes_offline_with_bundled_fixtures() {
+    let output = Command::new(env!("CARGO_BIN_EXE_pmm"))
+        .arg("selftest")
+        .output()
+        .expect("run pmm selftest");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success(), "selftest failed:\n{stdout}");
+    for check in ["slug", "discovery", "klines", "features", "dashboard"] {
+        assert!(
+            stdout
+                .lines()
+                .any(|line| line.contains("PASS") && line.contains(check)),
+            "missing PASS line for {check}:\n{stdout}"
+        );
+    }
+}
+
+#[test]
+fn unknown_command_exits_with_usage() {
+    let output = Command::new(env!("CARGO_BIN_EXE_pmm"))
+        .arg("bogus")
+        .output()
+        .expect("run pmm");
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("usage: pmm"));
+}
+
+#[test]
+fn drift_rejects_malformed_ranges() {
+    let output = Command::new(env!("CARGO_BIN_EXE_pmm"))
+        .args(["drift", "--baseline", "2025-02-01..2025-01-01"])
+        .output()
+        .expect("run pmm drift");
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("empty range"));
+}
+
+#[test]
+fn slug_audit_reports_handled_collisions_as_json() {
+    let output = Command::new(env!("CARGO_BIN_EXE_pmm"))
+        .args([
+            "slug-audit",
+            "--range",
+            "2024-07-01..2025-07-01",
+            "--sample",
+            "4",
+        ])
+        .env("PMM_UNIVERSE_COINS", "BTC")
+        .env("PMM_UNIVERSE_DURATIONS", "1h,1d")
+        .output()
+        .expect("run pmm slug-audit");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success(), "slug-audit failed:\n{stdout}");
+    let report: serde_json::Value = serde_json::from_str(&stdout).expect("json report");
+    assert_eq!(report["unhandled_collisions"], 0);
+    assert_eq!(report["collisions"][0]["kind"], "dst_repeat");
+    assert_eq!(
+        report["collisions"][0]["slug"],
+        "bitcoin-up-or-down-november-3-1am-et"
+    );
+}
+
+#[test]
+fn config_file_fills_unset_variables_and_rejects_bad_keys() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("pmm.toml");
+    std::fs::write(
+        &path,
+        "[logging]\nformat = \"json\"\n\n[dashboard]\naddr = \"0.0.0.0:9000\"\n",
+    )
+    .unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_pmm"))
+        .arg("env")
+        .env("PMM_CONFIG", &path)
+        .env("PMM_DASHBOARD_ADDR", "127.0.0.1:7000")
+        .output()
+        .expect("run pmm env");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let value = |name: &str| {
+        stdout
+            .lines()
+            .find(|line| line.starts_with(&format!("{name} ")))
+            .and_then(|line| line.split_whitespace().nth(2))
+            .map(str::to_string)
+    };
+    assert_eq!(value("PMM_LOG_FORMAT").as_deref(), Some("json"), "{stdout}");
+    assert_eq!(
+        value("PMM_DASHBOARD_ADDR").as_deref(),
+        Some("127.0.0.1:7000")
+    );
+
+    std::fs::write(&path, "[logging]\nformat = \"xml\"\n").unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_pmm"))
+        .arg("env")
+        .env("PMM_CONFIG", &path)
+        .output()
+        .expect("run pmm env");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("logging.format"));
+}
@@ -0,0 +1,113 @@
+use axum::routing::get;
+use axum::Router;
+use pmm::{
+    run_preflight_with_env, AlertConfig, ClockDriftConfig, GammaEndpointConfig, KlineStore,
+    KlineValidationConfig, PreflightConfig, PreflightStatus, RunMode,
+};
+
+async fn serve(app: Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind");
+    let addr = listener.local_addr().expect("addr");
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.expect("serve");
+    });
+    format!("http://{addr}")
+}
+
+async fn closed_port_url() -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind");
+    format!("http://{}", listener.local_addr().expect("addr"))
+}
+
+fn status_of(report: &pmm::PreflightReport, name: &str) -> PreflightStatus {
+    report
+        .checks
+        .iter()
+        .find(|check| check.name == name)
+        .unwrap_or_else(|| panic!("missing check {name}"))
+        .status
+}
+
+#[tokio::test]
+async fn preflight_reports_each_check_and_blocks_only_live_mode() {
+    let upstream = serve(
+        Router::new()
+            .route("/markets", get(|| async { "[]" }))
+            .route("/api/v3/ping", get(|| async { "{}" })),
+    )
+    .await;
+    let down = closed_port_url().await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let store_path = dir.path().join("klines_1s.sqlite");
+    KlineStore::open(&store_path, KlineValidationConfig::default()).unwrap();
+
+    let mut cfg = PreflightConfig {
+        mode: RunMode::Paper,
+        timeout_ms: 2_000,
+        gamma: GammaEndpointConfig {
+            base_urls: vec![upstream.clone(), down],
+            ..GammaEndpointConfig::default()
+        },
+        binance_ping_url: Some(format!("{upstream}/api/v3/ping")),
+        clock: ClockDriftConfig {
+            ntp_server: None,
+            binance_time_url: None,
+            ..ClockDriftConfig::default()
+        },
+        kline_store_path: Some(store_path),
+        alerts: AlertConfig {
+            store_path: Some(dir.path().join("alerts.sqlite")),
+            ..AlertConfig::default()
+        },
+        ..PreflightConfig::default()
+    };
+    let no_env = |_: &str| None;
+
+    let report = run_preflight_with_env(&cfg, &no_env).await;
+    assert_eq!(status_of(&report, "config"), PreflightStatus::Pass);
+    assert_eq!(status_of(&report, "kline_store"), PreflightStatus::Pass);
+    assert_eq!(status_of(&report, "alert_store"), PreflightStatus::Pass);
+    // One of two Gamma endpoints is down: lookups fail over, so only a warning.
+    assert_eq!(status_of(&report, "gamma"), PreflightStatus::Warn);
+    assert_eq!(status_of(&report, "binance"), PreflightStatus::Pass);
+    assert_eq!(status_of(&report, "clock"), PreflightStatus::Skip);
+    assert_eq!(status_of(&report, "wallet"), PreflightStatus::Skip);
+    assert_eq!(report.failures(), 0);
+    assert!(!report.blocks_start());
+
+    // Live mode without wallet credentials refuses to start.
+    cfg.mode = RunMode::Live;
+    let report = run_preflight_with_env(&cfg, &no_env).await;
+    assert_eq!(status_of(&report, "wallet"), PreflightStatus::Fail);
+    assert!(report.blocks_start());
+    assert!(report
+        .render_table()
+        .contains("refusing to start in live mode"));
+
+    let key = "1f".repeat(32);
+    let with_key = move |name: &str| (name == "PMM_WALLET_PRIVATE_KEY").then(|| key.clone());
+    let report = run_preflight_with_env(&cfg, &with_key).await;
+    assert_eq!(status_of(&report, "wallet"), PreflightStatus::Pass);
+    assert!(!report.blocks_start());
+
+    // A missing store is a critical failure with a fix hint.
+    cfg.kline_store_path = Some(dir.path().join("missing.sqlite"));
+    let report = run_preflight_with_env(&cfg, &with_key).await;
+    let store = report
+        .checks
+        .iter()
+        .find(|check| check.name == "kline_store")
+        .unwrap();
+    assert_eq!(store.status, PreflightStatus::Fail);
+    assert!(store
+        .hint
+        .as_deref()
+        .unwrap()
+        .contains("binance_store_sync"));
+    assert!(report.blocks_start());
+}
@@ -0,0 +1,65 @@
+use axum::body::{to_bytes, Body};
+use axum::http::{Request, StatusCode};
+use pmm::{quality_router, DiscoveryCycleTotals, QualityScorecard, QualityScorecards};
+use tower::ServiceExt;
+
+// Monday 2026-03-02 00:00:00 UTC.
+const MONDAY: i64 = 1_772_409_600;
+const WEEK_SECS: i64 = 7 * 86_400;
+
+async fn get_quality(cards: QualityScorecards, uri: &str) -> Vec<QualityScorecard> {
+    let response = quality_router(cards)
+        .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        .await
+        .expect("response");
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[tokio::test]
+async fn quality_endpoint_serves_persisted_weekly_scorecards() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("quality.sqlite");
+    let discovery = DiscoveryCycleTotals {
+        cycles: 2,
+        scheduled_lookups: 20,
+        resolved_lookups: 18,
+        failed_lookups: 2,
+        latency_ms_sum: 900,
+        ..DiscoveryCycleTotals::default()
+    };
+
+    let cards = QualityScorecards::open(&path).unwrap();
+    for week in 0..3 {
+        let start = MONDAY + week * WEEK_SECS;
+        cards.tick(true, &discovery, None, 600, start + 60).unwrap();
+        cards
+            .tick(true, &discovery, None, 600, start + 360)
+            .unwrap();
+    }
+    drop(cards);
+
+    // Reopened: the scorecards survive a restart.
+    let cards = QualityScorecards::open(&path).unwrap();
+    let weeks = get_quality(cards.clone(), "/quality").await;
+    assert_eq!(
+        weeks
+            .iter()
+            .map(|card| card.week_start_ts_utc)
+            .collect::<Vec<_>>(),
+        [MONDAY + 2 * WEEK_SECS, MONDAY + WEEK_SECS, MONDAY]
+    );
+    let closed = &weeks[1];
+    assert_eq!(closed.gamma_resolution_success_pct, Some(90.0));
+    assert_eq!(closed.avg_discovery_latency_ms, Some(450.0));
+    // Observed from the week boundary; the untracked rest of the week counts as down
+    // beyond one job cadence.
+    assert_eq!(closed.snapshot_observed_secs, WEEK_SECS);
+    assert_eq!(closed.snapshot_up_secs, 60 + 300 + 600);
+    assert!(closed.snapshot_uptime_pct.unwrap() < 1.0);
+
+    let latest = get_quality(cards, "/quality?weeks=1").await;
+    assert_eq!(latest.len(), 1);
+    assert_eq!(latest[0].week_start_ts_utc, MONDAY + 2 * WEEK_SECS);
+}
@@ -0,0 +1,141 @@
+use axum::body::{to_bytes, Body};
+use axum::http::Request;
+use pmm::{
+    dashboard_router, load_replay_timeline, play_replay, DashboardRow, DashboardSnapshot,
+    DashboardSnapshotSource, DecisionAction, DecisionLog, DecisionRecord, ReplayConfig,
+    ReplayEventKind, ReplaySnapshotSource, SizingSide, SnapshotStore, SqliteDecisionLog,
+};
+use rusqlite::{params, Connection};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+const START: i64 = 1_771_449_000;
+
+fn row(slug: &str, start: i64, probability: &str) -> DashboardRow {
+    let mut row = DashboardRow::unresolved_with_times(slug, "BTC", "5m", start, start + 300);
+    row.probability = Some(probability.to_string());
+    row
+}
+
+fn decision(ts_utc: i64, action: DecisionAction) -> DecisionRecord {
+    DecisionRecord {
+        ts_utc,
+        slug: "btc-updown-5m-1771449000".to_string(),
+        routine: "maker_quotes".to_string(),
+        action,
+        side: Some(SizingSide::Yes),
+        shares: Some(10.0),
+        price: Some(0.48),
+        edge: Some(0.02),
+        reason: None,
+        model_id: None,
+    }
+}
+
+fn seed_fill(path: &std::path::Path, fill_id: &str, ts_ms: i64) {
+    let conn = Connection::open(path).unwrap();
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS paper_fills (
+            fill_id TEXT PRIMARY KEY, order_id TEXT NOT NULL, slug TEXT NOT NULL,
+            outcome TEXT NOT NULL, token_id TEXT NOT NULL, side TEXT NOT NULL,
+            price REAL NOT NULL, size REAL NOT NULL, taker INTEGER NOT NULL,
+            ts_ms INTEGER NOT NULL
+        )",
+    )
+    .unwrap();
+    conn.execute(
+        "INSERT INTO paper_fills VALUES (?1, 'o1', 'btc-updown-5m-1771449000', 'YES', 't1',
+         'buy', 0.48, 10.0, 0, ?2)",
+        params![fill_id, ts_ms],
+    )
+    .unwrap();
+}
+
+#[tokio::test]
+async fn replay_plays_rows_decisions_and_fills_in_order_and_drops_ended_rows() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut cfg = ReplayConfig::from_env(START, START + 400, 1_000_000);
+    cfg.tick_ms = 1;
+    cfg.snapshot_store = Some(dir.path().join("snapshots.sqlite"));
+    cfg.decision_store = Some(dir.path().join("decisions.sqlite"));
+    cfg.paper_store = Some(dir.path().join("paper_fills.sqlite"));
+
+    let store = SnapshotStore::open(cfg.snapshot_store.as_ref().unwrap()).unwrap();
+    let first = "btc-updown-5m-1771449000";
+    let second = "btc-updown-5m-1771449300";
+    for (offset_secs, rows) in [
+        (-30, vec![row(first, START, "0.50")]),
+        (120, vec![row(first, START, "0.61")]),
+        (300, vec![row(second, START + 300, "0.45")]),
+        (500, vec![row(second, START + 300, "0.90")]),
+    ] {
+        store
+            .append(
+                &DashboardSnapshot { rows, seq: 1 },
+                (START + offset_secs) * 1_000,
+            )
+            .unwrap();
+    }
+    let log = SqliteDecisionLog::open(cfg.decision_store.as_ref().unwrap()).unwrap();
+    log.record(&decision(START + 10, DecisionAction::PlaceQuote));
+    log.record(&decision(START + 120, DecisionAction::CancelQuotes));
+    log.record(&decision(START + 900, DecisionAction::PlaceQuote));
+    seed_fill(
+        cfg.paper_store.as_ref().unwrap(),
+        "f1",
+        (START + 60) * 1_000,
+    );
+    seed_fill(
+        cfg.paper_store.as_ref().unwrap(),
+        "f2",
+        (START + 120) * 1_000,
+    );
+
+    let timeline = load_replay_timeline(&cfg).unwrap();
+    let kinds = timeline
+        .events
+        .iter()
+        .map(|event| {
+            let kind = match &event.kind {
+                ReplayEventKind::Row(_) => "row",
+                ReplayEventKind::Decision(_) => "decision",
+                ReplayEventKind::Fill(_) => "fill",
+            };
+            (event.ts_ms / 1_000 - START, kind)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        vec![
+            (0, "row"),
+            (10, "decision"),
+            (60, "fill"),
+            (120, "row"),
+            (120, "decision"),
+            (120, "fill"),
+            (300, "row"),
+        ]
+    );
+    assert!(!timeline.rows_truncated);
+
+    let source = ReplaySnapshotSource::new(cfg.from_ts_utc);
+    let summary = play_replay(timeline, &source, &cfg).await;
+    assert_eq!((summary.rows, summary.decisions, summary.fills), (3, 2, 2));
+    assert_eq!(source.now_ts_utc(), START + 400);
+    // The first interval ended at +300; only the second row, in its last recorded
+    // state within the range, is left.
+    let rows = source.snapshot().rows.clone();
+    assert_eq!(rows, vec![row(second, START + 300, "0.45")]);
+
+    let response = dashboard_router(Arc::new(source))
+        .oneshot(
+            Request::builder()
+                .uri("/dashboard/snapshot")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert!(String::from_utf8_lossy(&body).contains(second));
+}
@@ -0,0 +1,91 @@
+use axum::body::{to_bytes, Body};
+use axum::http::{Request, StatusCode};
+use pmm::{
+    snapshot_history_router, DashboardRow, DashboardSnapshot, SnapshotHistory, SnapshotStore,
+};
+use tower::ServiceExt;
+
+const START: i64 = 1_771_449_000;
+
+fn row(probability: &str, best_bid: &str) -> DashboardRow {
+    let mut row = DashboardRow::unresolved_with_times(
+        "btc-updown-5m-1771449000",
+        "BTC",
+        "5m",
+        START,
+        START + 300,
+    );
+    row.probability = Some(probability.to_string());
+    row.best_bid_yes = Some(best_bid.to_string());
+    row
+}
+
+async fn get(store: &SnapshotStore, uri: &str) -> (StatusCode, Vec<u8>) {
+    let response = snapshot_history_router(store.clone())
+        .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        .await
+        .expect("response");
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, body.to_vec())
+}
+
+#[tokio::test]
+async fn history_endpoint_replays_row_changes_over_an_interval() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = SnapshotStore::open(&dir.path().join("snapshots.sqlite")).unwrap();
+    for (offset_secs, seq, probability, bid) in [
+        (-60, 1, "0.50", "0.48"),
+        (10, 2, "0.50", "0.48"),
+        (30, 3, "0.56", "0.52"),
+        (200, 4, "0.61", "0.58"),
+        (400, 5, "0.99", "0.97"),
+    ] {
+        let snapshot = DashboardSnapshot {
+            rows: vec![row(probability, bid)],
+            seq,
+        };
+        store
+            .append(&snapshot, (START + offset_secs) * 1_000)
+            .unwrap();
+    }
+
+    let uri = format!(
+        "/dashboard/history?slug=btc-updown-5m-1771449000&from={START}&to={}",
+        START + 300
+    );
+    let (status, body) = get(&store, &uri).await;
+    assert_eq!(status, StatusCode::OK);
+    let history: SnapshotHistory = serde_json::from_slice(&body).unwrap();
+    assert_eq!(history.from_ts_utc, START);
+    assert!(!history.truncated);
+    let seen: Vec<(i64, Option<&str>, Option<&str>)> = history
+        .points
+        .iter()
+        .map(|point| {
+            (
+                point.ts_ms / 1_000 - START,
+                point.row.probability.as_deref(),
+                point.row.best_bid_yes.as_deref(),
+            )
+        })
+        .collect();
+    // The unchanged sample at +10s was not stored; the pre-start row leads the replay.
+    assert_eq!(
+        seen,
+        [
+            (-60, Some("0.50"), Some("0.48")),
+            (30, Some("0.56"), Some("0.52")),
+            (200, Some("0.61"), Some("0.58")),
+        ]
+    );
+
+    let (status, _) = get(
+        &store,
+        &format!("/dashboard/history?slug=x&from={}&to={START}", START + 1),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = get(&store, "/dashboard/history").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,84 @@
+use axum::body::{to_bytes, Body};
+use axum::http::{Request, StatusCode};
+use axum::Router;
+use pmm::{
+    strategy_toggle_router, Coin, Duration, MarketToggle, MarketToggleConfig, MarketToggles,
+};
+use tower::ServiceExt;
+
+async fn call(app: &Router, method: &str, uri: &str) -> (StatusCode, Vec<u8>) {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .expect("response");
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, body.to_vec())
+}
+
+#[tokio::test]
+async fn strategy_enable_endpoint_toggles_pairs_and_survives_restart() {
+    let dir = tempfile::tempdir().unwrap();
+    let cfg = MarketToggleConfig {
+        store_path: Some(dir.path().join("strategy_toggles.sqlite")),
+    };
+
+    let toggles = MarketToggles::from_config(&cfg);
+    let app = strategy_toggle_router(toggles.clone());
+    let (status, body) = call(
+        &app,
+        "POST",
+        "/strategy/enable?coin=BTC&duration=5m&enabled=false",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let toggle: MarketToggle = serde_json::from_slice(&body).unwrap();
+    assert_eq!((toggle.coin, toggle.duration), (Coin::BTC, Duration::M5));
+    assert!(!toggle.enabled);
+    // Takes effect on the shared handle without a restart.
+    assert!(!toggles.is_enabled(Coin::BTC, Duration::M5));
+
+    for bad in [
+        "/strategy/enable?coin=DOGE&duration=5m&enabled=false",
+        "/strategy/enable?coin=BTC&duration=2m&enabled=false",
+        "/strategy/enable?coin=BTC&duration=5m",
+        "/strategy/enable?coin=BTC&duration=5m&enabled=maybe",
+    ] {
+        assert_eq!(
+            call(&app, "POST", bad).await.0,
+            StatusCode::BAD_REQUEST,
+            "{bad}"
+        );
+    }
+    drop(app);
+    drop(toggles);
+
+    let app = strategy_toggle_router(MarketToggles::from_config(&cfg));
+    let (status, body) = call(&app, "GET", "/strategy/enable").await;
+    assert_eq!(status, StatusCode::OK);
+    let list: Vec<MarketToggle> = serde_json::from_slice(&body).unwrap();
+    assert_eq!(list.len(), 20);
+    let disabled = list
+        .iter()
+        .filter(|toggle| !toggle.enabled)
+        .map(|toggle| (toggle.coin, toggle.duration))
+        .collect::<Vec<_>>();
+    assert_eq!(disabled, [(Coin::BTC, Duration::M5)]);
+
+    call(
+        &app,
+        "POST",
+        "/strategy/enable?coin=btc&duration=5m&enabled=true",
+    )
+    .await;
+    let (_, body) = call(&app, "GET", "/strategy/enable").await;
+    let list: Vec<MarketToggle> = serde_json::from_slice(&body).unwrap();
+    assert!(list.iter().all(|toggle| toggle.enabled));
+}
@@ -0,0 +1,360 @@
+#![cfg(feature = "trading")]
+
+use std::sync::{Arc, Mutex};
+
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::{delete, get, post};
+use axum::{Json, Router};
+use pmm::{
+    BookSubscription, ClobCredentials, Coin, DiscoveryKey, Duration, LimitOrder, OrderClient,
+    OrderSide, PositionBook, PositionSource, SlugConfig, TimeInForce, TradingConfig, TradingError,
+};
+use serde_json::{json, Value};
+
+const TOKEN_ID: &str =
+    "71321045679252212594626385532706912750332728571942532289631379312455583992563";
+const MARKET: &str = "0x5f65177b394277fd294cd75650044e32ba009a95022d88a0c1d565897d72f8f1";
+const NO_TOKEN_ID: &str =
+    "52114319501245915516055106046884209969926127482827954674443846427813813222426";
+const API_KEY: &str = "00000000-0000-4000-8000-000000000001";
+const OTHER_KEY: &str = "00000000-0000-4000-8000-000000000002";
+const TX_HASH: &str = "0x0000000000000000000000000000000000000000000000000000000000000001";
+// Well-known development key; never funded.
+const PRIVATE_KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+#[derive(Default)]
+struct Recorded {
+    posted: Vec<Value>,
+    trade_queries: Vec<Option<String>>,
+    api_keys: Vec<String>,
+    cancelled: Vec<Value>,
+}
+
+type Shared = Arc<Mutex<Recorded>>;
+
+fn record_key(state: &Shared, headers: &HeaderMap) {
+    let key = headers
+        .get("POLY_API_KEY")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    state.lock().unwrap().api_keys.push(key);
+}
+
+async fn serve(app: Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    format!("http://{addr}")
+}
+
+async fn post_order(
+    State(state): State<Shared>,
+    headers: HeaderMap,
+    Json(body): Json<Value>,
+) -> Json<Value> {
+    record_key(&state, &headers);
+    let maker_amount = body["order"]["makerAmount"].as_str().map(str::to_string);
+    state.lock().unwrap().posted.push(body);
+    // 1 share at 0.99 stands in for an order the wallet cannot fund.
+    if maker_amount.as_deref() == Some("990000") {
+        return Json(json!({
+            "success": false, "errorMsg": "not enough balance", "orderID": "",
+            "status": "unmatched", "makingAmount": "", "takingAmount": ""
+        }));
+    }
+    Json(json!({
+        "success": true, "errorMsg": "", "orderID": "0xorder1",
+        "status": "live", "makingAmount": "", "takingAmount": ""
+    }))
+}
+
+async fn cancel_order(
+    State(state): State<Shared>,
+    headers: HeaderMap,
+    Json(body): Json<Value>,
+) -> Json<Value> {
+    record_key(&state, &headers);
+    state.lock().unwrap().cancelled.push(body);
+    Json(json!({ "canceled": ["0xorder1"], "not_canceled": {} }))
+}
+
+fn trade(id: &str, status: &str, trader_side: &str, maker_orders: Value) -> Value {
+    json!({
+        "id": id,
+        "taker_order_id": format!("0xtaker-{id}"),
+        "market": MARKET,
+        "asset_id": TOKEN_ID,
+        "side": "BUY",
+        "size": "10",
+        "fee_rate_bps": "100",
+        "price": "0.4",
+        "status": status,
+        "match_time": "1735689600",
+        "last_update": "1735689660",
+        "outcome": "Up",
+        "bucket_index": 0,
+        "owner": API_KEY,
+        "maker_address": "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266",
+        "maker_orders": maker_orders,
+        "transaction_hash": TX_HASH,
+        "trader_side": trader_side
+    })
+}
+
+fn maker_order(order_id: &str, owner: &str, asset_id: &str, side: &str, amount: &str) -> Value {
+    json!({
+        "order_id": order_id,
+        "owner": owner,
+        "maker_address": "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266",
+        "matched_amount": amount,
+        "price": "0.6",
+        "fee_rate_bps": "0",
+        "asset_id": asset_id,
+        "outcome": "Down",
+        "side": side
+    })
+}
+
+async fn list_trades(
+    State(state): State<Shared>,
+    axum::extract::Query(query): axum::extract::Query<Vec<(String, String)>>,
+) -> Json<Value> {
+    let after = query
+        .into_iter()
+        .find(|(key, _)| key == "after")
+        .map(|(_, value)| value);
+    state.lock().unwrap().trade_queries.push(after);
+    Json(json!({
+        "data": [
+            // We took 10 YES at 0.4, paying 1% of min(0.4, 0.6) per share.
+            trade("t1", "CONFIRMED", "TAKER", json!([])),
+            // Our resting NO bid was filled for 6; the other maker order is not ours.
+            trade("t2", "MATCHED", "MAKER", json!([
+                maker_order("0xmine", API_KEY, NO_TOKEN_ID, "BUY", "6"),
+                maker_order("0xtheirs", OTHER_KEY, NO_TOKEN_ID, "BUY", "4")
+            ])),
+            trade("t3", "FAILED", "TAKER", json!([]))
+        ],
+        "next_cursor": "LTE=",
+        "limit": 100,
+        "count": 3
+    }))
+}
+
+fn mock_clob(state: Shared) -> Router {
+    Router::new()
+        .route(
+            "/tick-size",
+            get(|| async { Json(json!({ "minimum_tick_size": "0.01" })) }),
+        )
+        .route(
+            "/neg-risk",
+            get(|| async { Json(json!({ "neg_risk": false })) }),
+        )
+        .route(
+            "/fee-rate",
+            get(|| async { Json(json!({ "base_fee": 0 })) }),
+        )
+        .route("/order", post(post_order).delete(cancel_order))
+        .route("/data/trades", get(list_trades))
+        .route(
+            "/cancel-all",
+            delete(|| async {
+                Json(json!({ "canceled": [], "not_canceled": { "0xgone": "already filled" } }))
+            }),
+        )
+        .route(
+            "/data/orders",
+            get(|headers: HeaderMap| async move {
+                if headers.get("POLY_API_KEY").is_none() {
+                    return Err(StatusCode::UNAUTHORIZED);
+                }
+                Ok(Json(json!({
+                    "data": [{
+                        "id": "0xorder1",
+                        "status": "LIVE",
+                        "owner": API_KEY,
+                        "maker_address": "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266",
+                        "market": MARKET,
+                        "asset_id": TOKEN_ID,
+                        "side": "BUY",
+                        "original_size": "10",
+                        "size_matched": "2.5",
+                        "price": "0.55",
+                        "associate_trades": [],
+                        "outcome": "Up",
+                        "created_at": 1735689600,
+                        "expiration": "1735693200",
+                        "order_type": "GTD"
+                    }],
+                    "next_cursor": "LTE=",
+                    "limit": 100,
+                    "count": 1
+                })))
+            }),
+        )
+        .with_state(state)
+}
+
+async fn connect(url: String) -> OrderClient {
+    OrderClient::connect(&TradingConfig {
+        clob_url: url,
+        private_key: Some(PRIVATE_KEY.to_string()),
+        credentials: Some(ClobCredentials {
+            api_key: API_KEY.to_string(),
+            secret: "c2VjcmV0LXNlY3JldC1zZWNyZXQ=".to_string(),
+            passphrase: "passphrase".to_string(),
+        }),
+        ..TradingConfig::default()
+    })
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn places_cancels_and_lists_orders_against_the_clob() {
+    let state = Shared::default();
+    let client = connect(serve(mock_clob(state.clone())).await).await;
+    assert_eq!(
+        client.address().to_lowercase(),
+        "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266"
+    );
+
+    let expires = chrono::Utc::now().timestamp() + 3_600;
+    let order = LimitOrder::new(
+        TOKEN_ID,
+        OrderSide::Buy,
+        0.55,
+        10.0,
+        TimeInForce::Gtd {
+            expires_ts_utc: expires,
+        },
+    )
+    .post_only();
+    let placed = client.place_limit_order(&order).await.unwrap();
+    assert_eq!(
+        (placed.order_id.as_str(), placed.status.as_str()),
+        ("0xorder1", "LIVE")
+    );
+
+    {
+        let recorded = state.lock().unwrap();
+        let body = &recorded.posted[0];
+        assert_eq!(body["orderType"], "GTD");
+        assert_eq!(body["owner"], API_KEY);
+        assert_eq!(body["postOnly"], true);
+        assert_eq!(body["order"]["side"], "BUY");
+        assert_eq!(body["order"]["tokenId"], TOKEN_ID);
+        // 10 shares at 0.55: we make 5.5 USDC and take 10 shares (6 decimals).
+        assert_eq!(body["order"]["makerAmount"], "5500000");
+        assert_eq!(body["order"]["takerAmount"], "10000000");
+        assert_eq!(body["order"]["expiration"], expires.to_string());
+        assert!(body["order"]["signature"]
+            .as_str()
+            .unwrap()
+            .starts_with("0x"));
+        assert_eq!(recorded.api_keys, vec![API_KEY.to_string()]);
+    }
+
+    // A rejection comes back as an error carrying the CLOB's reason.
+    let too_big = LimitOrder::new(TOKEN_ID, OrderSide::Buy, 0.99, 1.0, TimeInForce::Fok);
+    match client.place_limit_order(&too_big).await {
+        Err(TradingError::Rejected(reason)) => assert_eq!(reason, "not enough balance"),
+        other => panic!("expected a rejection, got {other:?}"),
+    }
+    // Invalid orders are refused before anything is sent.
+    let posted = state.lock().unwrap().posted.len();
+    let sub_tick = LimitOrder::new(TOKEN_ID, OrderSide::Sell, 0.555, 1.0, TimeInForce::Gtc);
+    assert!(matches!(
+        client.place_limit_order(&sub_tick).await,
+        Err(TradingError::InvalidOrder(_))
+    ));
+    assert_eq!(state.lock().unwrap().posted.len(), posted);
+
+    let cancelled = client.cancel("0xorder1").await.unwrap();
+    assert_eq!(cancelled.cancelled, vec!["0xorder1".to_string()]);
+    assert_eq!(state.lock().unwrap().cancelled[0]["orderId"], "0xorder1");
+    let all = client.cancel_all().await.unwrap();
+    assert_eq!(all.not_cancelled["0xgone"], "already filled");
+
+    let open = client.open_orders(Some(TOKEN_ID)).await.unwrap();
+    assert_eq!(open.len(), 1);
+    assert_eq!(open[0].token_id, TOKEN_ID);
+    assert_eq!(open[0].side, OrderSide::Buy);
+    assert_eq!((open[0].price, open[0].remaining()), (0.55, 7.5));
+    assert_eq!(open[0].expires_ts_utc, Some(1_735_693_200));
+    assert_eq!(
+        (open[0].outcome.as_str(), open[0].market.as_str()),
+        ("Up", MARKET)
+    );
+}
+
+#[tokio::test]
+async fn connect_requires_a_valid_private_key() {
+    assert!(matches!(
+        OrderClient::connect(&TradingConfig::default()).await,
+        Err(TradingError::MissingPrivateKey)
+    ));
+    let bad = TradingConfig {
+        private_key: Some("0x1234".to_string()),
+        ..TradingConfig::default()
+    };
+    assert!(matches!(
+        OrderClient::connect(&bad).await,
+        Err(TradingError::InvalidConfig(_))
+    ));
+}
+
+#[tokio::test]
+async fn fills_from_trade_history_reconcile_into_positions() {
+    let state = Shared::default();
+    let client = connect(serve(mock_clob(state.clone())).await).await;
+
+    let fills = client.fills(Some(1_735_686_000)).await.unwrap();
+    assert_eq!(
+        state.lock().unwrap().trade_queries,
+        vec![Some("1735686000".to_string())]
+    );
+    let summary: Vec<_> = fills
+        .iter()
+        .map(|fill| (fill.fill_id.as_str(), fill.side, fill.size, fill.failed))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("t1", OrderSide::Buy, 10.0, false),
+            ("t2:0xmine", OrderSide::Buy, 6.0, false),
+            ("t3", OrderSide::Buy, 10.0, true),
+        ]
+    );
+    assert!((fills[0].fee_usdc - 0.04).abs() < 1e-12);
+    assert_eq!(fills[1].token_id, NO_TOKEN_ID);
+
+    let key = DiscoveryKey::new(
+        Coin::BTC,
+        Duration::M15,
+        1_735_689_600,
+        SlugConfig::default(),
+    )
+    .unwrap();
+    let book = PositionBook::new();
+    book.track_markets(&[BookSubscription {
+        key: key.clone(),
+        market_slug: None,
+        yes_token_id: TOKEN_ID.to_string(),
+        no_token_id: Some(NO_TOKEN_ID.to_string()),
+        neg_risk: false,
+        neg_risk_group: None,
+    }]);
+    for fill in fills {
+        book.record_fill(fill);
+    }
+    let position = book.position(&key.slug).unwrap();
+    assert_eq!((position.yes.shares, position.no.shares), (10.0, 6.0));
+    assert_eq!((position.yes.avg_price, position.no.avg_price), (0.4, 0.6));
+    assert_eq!(position.net_shares(), 4.0);
+}
@@ -0,0 +1,124 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::body::Bytes;
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use axum::Router;
+use pmm::{
+    sign_webhook_payload, MarketEvent, MarketEventKind, WebhookConfig, WebhookEmitter,
+    WEBHOOK_EVENT_HEADER, WEBHOOK_SIGNATURE_HEADER, WEBHOOK_TIMESTAMP_HEADER,
+};
+use tokio::sync::{mpsc, Mutex};
+
+type Captured = (HeaderMap, Bytes);
+
+async fn capture_server(fail_first: usize) -> (String, mpsc::UnboundedReceiver<Captured>) {
+    let (tx, rx) = mpsc::unbounded_channel::<Captured>();
+    let remaining_failures = Arc::new(Mutex::new(fail_first));
+    let app = Router::new().route(
+        "/hook",
+        post(move |headers: HeaderMap, body: Bytes| {
+            let tx = tx.clone();
+            let remaining_failures = remaining_failures.clone();
+            async move {
+                let mut remaining = remaining_failures.lock().await;
+                if *remaining > 0 {
+                    *remaining -= 1;
+                    return StatusCode::SERVICE_UNAVAILABLE;
+                }
+                tx.send((headers, body)).expect("capture");
+                StatusCode::NO_CONTENT
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind");
+    let addr = listener.local_addr().expect("addr");
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.expect("serve");
+    });
+    (format!("http://{addr}/hook"), rx)
+}
+
+fn rollover_event() -> MarketEvent {
+    MarketEvent {
+        event: MarketEventKind::IntervalRollover,
+        ts_utc: 1_772_000_310,
+        slug: "btc-updown-5m-1772000300".to_string(),
+        coin: "BTC".to_string(),
+        duration: "5m".to_string(),
+        start_ts_utc: 1_772_000_300,
+        end_ts_utc: 1_772_000_600,
+        previous_slug: Some("btc-updown-5m-1772000000".to_string()),
+        position_net: None,
+    }
+}
+
+#[tokio::test]
+async fn emitter_posts_signed_event_and_retries_failures() {
+    let (url, mut rx) = capture_server(1).await;
+    let emitter = WebhookEmitter::spawn(WebhookConfig {
+        url: Some(url),
+        secret: Some("whsec_test".to_string()),
+        retry_backoff_ms: 10,
+        ..WebhookConfig::default()
+    })
+    .expect("emitter");
+
+    emitter.emit(rollover_event());
+    let (headers, body) = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+        .await
+        .expect("delivered in time")
+        .expect("captured");
+
+    let delivered: MarketEvent = serde_json::from_slice(&body).expect("json body");
+    assert_eq!(delivered, rollover_event());
+    let json: serde_json::Value = serde_json::from_slice(&body).expect("json");
+    assert_eq!(json["event"], "interval.rollover");
+    assert!(json.get("position_net").is_none());
+
+    assert_eq!(headers[WEBHOOK_EVENT_HEADER], "interval.rollover");
+    let timestamp: i64 = headers[WEBHOOK_TIMESTAMP_HEADER]
+        .to_str()
+        .expect("ascii")
+        .parse()
+        .expect("timestamp");
+    assert_eq!(
+        headers[WEBHOOK_SIGNATURE_HEADER].to_str().expect("ascii"),
+        format!(
+            "sha256={}",
+            sign_webhook_payload("whsec_test", timestamp, &body)
+        )
+    );
+}
+
+#[tokio::test]
+async fn emitter_skips_disabled_kinds_and_missing_url() {
+    assert!(WebhookEmitter::spawn(WebhookConfig::default()).is_none());
+
+    let (url, mut rx) = capture_server(0).await;
+    let emitter = WebhookEmitter::spawn(WebhookConfig {
+        url: Some(url),
+        events: vec![MarketEventKind::MarketResolved],
+        ..WebhookConfig::default()
+    })
+    .expect("emitter");
+
+    emitter.emit(rollover_event());
+    let mut resolved = rollover_event();
+    resolved.event = MarketEventKind::MarketResolved;
+    resolved.previous_slug = None;
+    emitter.emit(resolved);
+
+    let (headers, body) = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+        .await
+        .expect("delivered in time")
+        .expect("captured");
+    assert_eq!(headers[WEBHOOK_EVENT_HEADER], "market.resolved");
+    assert!(headers.get(WEBHOOK_SIGNATURE_HEADER).is_none());
+    let delivered: MarketEvent = serde_json::from_slice(&body).expect("json body");
+    assert_eq!(delivered.event, MarketEventKind::MarketResolved);
+    assert!(rx.try_recv().is_err());
+}